@@ -0,0 +1,11 @@
+// Generates the narayana.brain.v1 messages and gRPC server from proto/brain.proto
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Fall back to the bundled protoc so building doesn't need one installed
+    if std::env::var_os("PROTOC").is_none() {
+        std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+    }
+    println!("cargo:rerun-if-changed=proto/brain.proto");
+    tonic_build::configure().compile(&["proto/brain.proto"], &["proto"])?;
+    Ok(())
+}
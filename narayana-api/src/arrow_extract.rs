@@ -0,0 +1,50 @@
+// Table extracts as Arrow record batches
+// Reads a table one page at a time so GET /api/v1/tables/{id}/arrow can send
+// it as an Arrow IPC stream without holding the whole table.
+
+use futures::stream::{self, Stream};
+use narayana_core::arrow::record_batch_with_schema;
+use narayana_core::{column::Column, types::TableId, Result};
+use narayana_storage::ColumnStore;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// Rows per record batch when the caller sets none
+pub const DEFAULT_BATCH_ROWS: usize = 65_536;
+/// Upper bound on rows per record batch
+pub const MAX_BATCH_ROWS: usize = 1_048_576;
+
+/// Record batches in table order
+pub type BatchStream = Pin<Box<dyn Stream<Item = Result<arrow_array::RecordBatch>> + Send>>;
+
+/// Pages of a table as record batches, `batch_rows` rows per storage read, so
+/// an extract never holds more than one page
+pub fn table_batches(
+    storage: Arc<dyn ColumnStore>,
+    table_id: TableId,
+    column_ids: Vec<u32>,
+    schema: arrow_schema::SchemaRef,
+    batch_rows: usize,
+) -> BatchStream {
+    let batch_rows = batch_rows.clamp(1, MAX_BATCH_ROWS);
+    let pages = stream::unfold(Some(0usize), move |row_start| {
+        let storage = storage.clone();
+        let column_ids = column_ids.clone();
+        let schema = schema.clone();
+        async move {
+            let row_start = row_start?;
+            let columns = match storage.read_columns(table_id, column_ids.clone(), row_start, batch_rows).await {
+                Ok(columns) => columns,
+                Err(e) => return Some((Err(e), None)),
+            };
+            // Empty columns are left out of reads, so a short result is the end
+            let rows = columns.first().map(Column::len).unwrap_or(0);
+            if rows == 0 || columns.len() != column_ids.len() {
+                return None;
+            }
+            let next = (rows == batch_rows).then_some(row_start + rows);
+            Some((record_batch_with_schema(&schema, columns), next))
+        }
+    });
+    Box::pin(pages)
+}
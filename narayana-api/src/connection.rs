@@ -190,6 +190,16 @@ impl DirectConnection {
     pub fn new(storage: Arc<dyn ColumnStore>) -> Self {
         Self { storage }
     }
+
+    /// Connection whose reads apply `principal`'s row policies and column
+    /// masks; build one per caller when serving GraphQL or gRPC over it
+    pub fn for_principal(
+        storage: Arc<dyn ColumnStore>,
+        security: &Arc<narayana_storage::row_security::RowSecurity>,
+        principal: narayana_storage::row_security::Principal,
+    ) -> Self {
+        Self { storage: security.secure(storage, principal) }
+    }
 }
 
 #[async_trait]
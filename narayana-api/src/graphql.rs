@@ -802,6 +802,9 @@ impl MutationRoot {
                 DataType::Map(_, _) => {
                     return Err(async_graphql::Error::new("Map data type not supported in GraphQL inserts"));
                 }
+                DataType::Point | DataType::Geometry => {
+                    return Err(async_graphql::Error::new("Geospatial data types not supported in GraphQL inserts"));
+                }
             };
             columns.push(column);
         }
@@ -1,7 +1,9 @@
 // gRPC API definitions will be generated from .proto files
 // For now, we'll define the service traits
 
-use narayana_core::{schema::Schema, types::TableId, column::Column};
+use narayana_core::{schema::Schema, types::{TableId, TransactionId}, column::Column};
+use narayana_storage::transactions::{CommitResult, CommitTarget, StoreCommit, TransactionInfo, TransactionRegistry};
+use narayana_storage::ColumnStore;
 
 pub trait NarayanaService {
     async fn create_table(&self, request: CreateTableRequest) -> Result<CreateTableResponse, String>;
@@ -32,10 +34,253 @@ pub struct QueryRequest {
     pub columns: Option<Vec<String>>,
     pub filter: Option<serde_json::Value>,
     pub limit: Option<usize>,
+    /// Return the result as an Arrow IPC stream instead of columns
+    pub arrow: bool,
 }
 
 pub struct QueryResponse {
     pub columns: Vec<Column>,
     pub row_count: usize,
+    /// Arrow IPC stream of the result (one record batch) when the request
+    /// set `arrow`; `columns` is empty then
+    pub arrow_ipc: Option<Vec<u8>>,
 }
 
+impl QueryResponse {
+    /// Response for `request`: the columns as they are, or as an Arrow IPC
+    /// stream with `names` as its field names
+    pub fn for_request(request: &QueryRequest, names: &[String], columns: Vec<Column>) -> Result<Self, String> {
+        let row_count = columns.first().map(|c| c.len()).unwrap_or(0);
+        if !request.arrow {
+            return Ok(Self { columns, row_count, arrow_ipc: None });
+        }
+        let arrow_ipc = narayana_core::arrow::columns_to_ipc(names, columns).map_err(|e| e.to_string())?;
+        Ok(Self { columns: Vec::new(), row_count, arrow_ipc: Some(arrow_ipc) })
+    }
+}
+
+
+use narayana_storage::consensus::{
+    AppendEntriesRequest, AppendEntriesResponse, RaftConsensus, RaftStatus,
+    RequestVoteRequest, RequestVoteResponse,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Cluster service: membership changes and Raft RPCs between nodes
+pub trait ClusterService {
+    async fn join(&self, request: JoinClusterRequest) -> Result<JoinClusterResponse, String>;
+    async fn leave(&self, request: LeaveClusterRequest) -> Result<LeaveClusterResponse, String>;
+    async fn status(&self) -> Result<RaftStatus, String>;
+    async fn request_vote(&self, request: RequestVoteRequest) -> Result<RequestVoteResponse, String>;
+    async fn append_entries(&self, request: AppendEntriesRequest) -> Result<AppendEntriesResponse, String>;
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JoinClusterRequest {
+    pub node_id: String,
+    /// Base URL of the joining node's API, replicated so every node can reach it
+    pub address: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JoinClusterResponse {
+    pub accepted: bool,
+    /// Set when this node is not the leader and the caller should retry there
+    pub leader_id: Option<String>,
+    /// Base URL of the leader's API, when this node knows it
+    #[serde(default)]
+    pub leader_address: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaveClusterRequest {
+    pub node_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaveClusterResponse {
+    pub accepted: bool,
+    pub leader_id: Option<String>,
+}
+
+/// ClusterService backed by a local Raft node
+pub struct RaftClusterService {
+    raft: Arc<RaftConsensus>,
+}
+
+impl RaftClusterService {
+    pub fn new(raft: Arc<RaftConsensus>) -> Self {
+        Self { raft }
+    }
+}
+
+impl ClusterService for RaftClusterService {
+    async fn join(&self, request: JoinClusterRequest) -> Result<JoinClusterResponse, String> {
+        if request.node_id.is_empty() {
+            return Err("node_id must not be empty".to_string());
+        }
+        if !request.address.starts_with("http://") && !request.address.starts_with("https://") {
+            return Err(format!("address must be an http(s) URL: {}", request.address));
+        }
+        if !self.raft.is_leader() {
+            let leader_id = self.raft.leader_id();
+            let leader_address = leader_id.as_deref().and_then(|leader| self.raft.peer_address(leader));
+            return Ok(JoinClusterResponse { accepted: false, leader_id, leader_address });
+        }
+        self.raft.join(request.node_id, request.address).await.map_err(|e| e.to_string())?;
+        Ok(JoinClusterResponse { accepted: true, leader_id: self.raft.leader_id(), leader_address: None })
+    }
+
+    async fn leave(&self, request: LeaveClusterRequest) -> Result<LeaveClusterResponse, String> {
+        if !self.raft.is_leader() {
+            return Ok(LeaveClusterResponse { accepted: false, leader_id: self.raft.leader_id() });
+        }
+        self.raft.remove_node(&request.node_id).await.map_err(|e| e.to_string())?;
+        Ok(LeaveClusterResponse { accepted: true, leader_id: self.raft.leader_id() })
+    }
+
+    async fn status(&self) -> Result<RaftStatus, String> {
+        Ok(self.raft.status())
+    }
+
+    async fn request_vote(&self, request: RequestVoteRequest) -> Result<RequestVoteResponse, String> {
+        self.raft.handle_request_vote(request).await.map_err(|e| e.to_string())
+    }
+
+    async fn append_entries(&self, request: AppendEntriesRequest) -> Result<AppendEntriesResponse, String> {
+        self.raft.handle_append_entries(request).await.map_err(|e| e.to_string())
+    }
+}
+
+/// Explicit transactions: begin, run statements bound to the transaction,
+/// then commit or roll back
+pub trait TransactionService {
+    async fn begin(&self, request: BeginTransactionRequest) -> Result<TransactionInfo, String>;
+    async fn execute(&self, request: ExecuteStatementRequest) -> Result<ExecuteStatementResponse, String>;
+    async fn commit(&self, request: EndTransactionRequest) -> Result<CommitResult, String>;
+    async fn rollback(&self, request: EndTransactionRequest) -> Result<TransactionInfo, String>;
+}
+
+pub struct BeginTransactionRequest {
+    /// Idle time after which the server rolls the transaction back (default: server setting)
+    pub timeout_ms: Option<u64>,
+}
+
+pub struct ExecuteStatementRequest {
+    pub transaction_id: u64,
+    pub statement: TransactionStatement,
+}
+
+pub enum TransactionStatement {
+    /// Stage rows; they are written on commit
+    Insert { table_id: u64, columns: Vec<Column> },
+    /// Read committed rows followed by the transaction's staged ones
+    Query { table_id: u64, columns: Option<Vec<String>>, limit: Option<usize> },
+}
+
+pub struct ExecuteStatementResponse {
+    /// Rows an insert staged
+    pub rows_staged: usize,
+    /// Rows a query read
+    pub columns: Vec<Column>,
+    pub row_count: usize,
+}
+
+pub struct EndTransactionRequest {
+    pub transaction_id: u64,
+}
+
+/// Rows a query statement returns when it sets no limit
+pub const DEFAULT_TRANSACTION_QUERY_LIMIT: usize = 1000;
+/// Most rows a query statement returns
+pub const MAX_TRANSACTION_QUERY_LIMIT: usize = 10_000;
+
+/// TransactionService for one caller over a transaction registry and a column
+/// store; transactions are bound to `caller`. Statements read through
+/// `storage`, and commits write straight into it unless a commit target is
+/// set. No server serves this service yet; the server's
+/// `http::transaction_service` builds one per caller that reads and commits
+/// the way the REST transaction endpoints do.
+pub struct RegistryTransactionService {
+    registry: Arc<TransactionRegistry>,
+    storage: Arc<dyn ColumnStore>,
+    caller: String,
+    commit_target: Option<Arc<dyn CommitTarget>>,
+}
+
+impl RegistryTransactionService {
+    pub fn new(registry: Arc<TransactionRegistry>, storage: Arc<dyn ColumnStore>, caller: String) -> Self {
+        Self { registry, storage, caller, commit_target: None }
+    }
+
+    /// Commit through `target` (indexes, change feed, versioning, ...)
+    /// instead of straight into the store; it also vets staged rows
+    pub fn with_commit_target(mut self, target: Arc<dyn CommitTarget>) -> Self {
+        self.commit_target = Some(target);
+        self
+    }
+}
+
+impl TransactionService for RegistryTransactionService {
+    async fn begin(&self, request: BeginTransactionRequest) -> Result<TransactionInfo, String> {
+        let timeout = request.timeout_ms.map(std::time::Duration::from_millis);
+        self.registry.begin(&self.caller, timeout).map_err(|e| e.to_string())
+    }
+
+    async fn execute(&self, request: ExecuteStatementRequest) -> Result<ExecuteStatementResponse, String> {
+        let id = TransactionId(request.transaction_id);
+        match request.statement {
+            TransactionStatement::Insert { table_id, columns } => {
+                let table_id = TableId(table_id);
+                let schema = self.storage.get_schema(table_id).await.map_err(|e| e.to_string())?;
+                if !schema.generated_field_indexes().is_empty() {
+                    return Err("Tables with generated embedding columns can't be written in a transaction".to_string());
+                }
+                if columns.len() != schema.fields.len() {
+                    return Err(format!("Expected {} columns, got {}", schema.fields.len(), columns.len()));
+                }
+                // Refuse rows the commit would, before they are staged
+                if let Some(target) = &self.commit_target {
+                    target.validate(table_id, &schema, &columns).await.map_err(|e| e.to_string())?;
+                }
+                let rows_staged = self.registry.stage_insert(id, &self.caller, table_id, columns)
+                    .map_err(|e| e.to_string())?;
+                Ok(ExecuteStatementResponse { rows_staged, columns: Vec::new(), row_count: 0 })
+            }
+            TransactionStatement::Query { table_id, columns, limit } => {
+                let table_id = TableId(table_id);
+                let schema = self.storage.get_schema(table_id).await.map_err(|e| e.to_string())?;
+                let column_ids: Vec<u32> = match columns {
+                    None => (0..schema.fields.len() as u32).collect(),
+                    Some(names) => names
+                        .iter()
+                        .map(|name| schema.field_index(name).map(|idx| idx as u32).ok_or_else(|| format!("Column '{}' not found", name)))
+                        .collect::<Result<_, _>>()?,
+                };
+                let limit = limit.unwrap_or(DEFAULT_TRANSACTION_QUERY_LIMIT).min(MAX_TRANSACTION_QUERY_LIMIT);
+                let columns = self.registry
+                    .read(self.storage.as_ref(), id, &self.caller, table_id, &column_ids, limit)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                let row_count = columns.first().map(|c| c.len()).unwrap_or(0);
+                Ok(ExecuteStatementResponse { rows_staged: 0, columns, row_count })
+            }
+        }
+    }
+
+    async fn commit(&self, request: EndTransactionRequest) -> Result<CommitResult, String> {
+        let id = TransactionId(request.transaction_id);
+        let committed = match &self.commit_target {
+            Some(target) => self.registry.commit(id, &self.caller, target.as_ref()).await,
+            None => self.registry.commit(id, &self.caller, &StoreCommit(self.storage.as_ref())).await,
+        };
+        committed.map_err(|e| e.to_string())
+    }
+
+    async fn rollback(&self, request: EndTransactionRequest) -> Result<TransactionInfo, String> {
+        self.registry
+            .rollback(TransactionId(request.transaction_id), &self.caller)
+            .map_err(|e| e.to_string())
+    }
+}
@@ -146,3 +146,553 @@ pub struct GrpcResponse {
 
 use std::collections::HashMap;
 
+
+// ============================================================================
+// Cognitive brain service (proto/brain.proto)
+// ============================================================================
+
+use futures::stream::{self, StreamExt};
+use narayana_storage::cognitive::{CognitiveBrain, CognitiveEvent, Memory, MemoryType, Thought, ThoughtState};
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::broadcast;
+
+/// Memories streamed by RetrieveMemories when the request sets no limit
+pub const DEFAULT_RETRIEVE_LIMIT: usize = 100;
+/// Upper bound on memories streamed by one RetrieveMemories call
+pub const MAX_RETRIEVE_LIMIT: usize = 10_000;
+
+/// Server-streaming response
+pub type BrainStream<T> = Pin<Box<dyn Stream<Item = Result<T>> + Send>>;
+
+/// Brain operations exposed over gRPC; streaming RPCs return a `BrainStream`
+pub trait BrainService {
+    async fn create_thought(&self, request: CreateThoughtRequest) -> Result<CreateThoughtResponse>;
+    async fn cancel_thought(&self, request: CancelThoughtRequest) -> Result<CancelThoughtResponse>;
+    async fn store_memory(&self, request: StoreMemoryRequest) -> Result<StoreMemoryResponse>;
+    async fn store_experience(&self, request: StoreExperienceRequest) -> Result<StoreExperienceResponse>;
+    async fn get_memory(&self, request: GetMemoryRequest) -> Result<Memory>;
+    async fn retrieve_memories(&self, request: RetrieveMemoriesRequest) -> Result<BrainStream<Memory>>;
+    async fn stream_thoughts(&self, request: StreamThoughtsRequest) -> Result<BrainStream<Thought>>;
+    async fn watch_events(&self) -> Result<BrainStream<BrainEvent>>;
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateThoughtRequest {
+    pub content: JsonValue,
+    /// 0.0 - 1.0
+    pub priority: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateThoughtResponse {
+    pub thought_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CancelThoughtRequest {
+    pub thought_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CancelThoughtResponse {
+    pub cancelled: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoreMemoryRequest {
+    pub memory_type: MemoryType,
+    pub content: JsonValue,
+    /// Needed for semantic retrieval
+    pub embedding: Option<Vec<f32>>,
+    pub tags: Vec<String>,
+    /// Thought the write is attributed to
+    pub thought_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoreMemoryResponse {
+    pub memory_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoreExperienceRequest {
+    pub event_type: String,
+    pub observation: JsonValue,
+    pub action: Option<JsonValue>,
+    pub outcome: Option<JsonValue>,
+    pub reward: Option<f64>,
+    pub embedding: Option<Vec<f32>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoreExperienceResponse {
+    pub experience_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetMemoryRequest {
+    pub memory_id: String,
+}
+
+/// The `query` oneof of RetrieveMemoriesRequest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum MemoryQuery {
+    /// Most similar embeddings first
+    Semantic { embedding: Vec<f32>, memory_type: Option<MemoryType> },
+    Tag(String),
+    /// Unix seconds, inclusive
+    TimeRange { start: u64, end: u64 },
+    /// Memories linked to this memory id
+    AssociatedWith(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetrieveMemoriesRequest {
+    pub query: MemoryQuery,
+    /// Maximum memories to stream (default `DEFAULT_RETRIEVE_LIMIT`)
+    pub limit: Option<usize>,
+    /// Thought the reads are attributed to (semantic queries only)
+    pub thought_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamThoughtsRequest {
+    /// Only thoughts in this state (None = all)
+    pub state: Option<ThoughtState>,
+    /// Keep the stream open and send thoughts as they are created
+    pub follow: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrainEvent {
+    /// Event variant, e.g. "ThoughtCreated"
+    pub kind: String,
+    /// The variant's fields, e.g. {"thought_id": "..."}
+    pub payload: JsonValue,
+    pub timestamp: u64,
+}
+
+impl BrainEvent {
+    fn from_event(event: &CognitiveEvent) -> Self {
+        // Externally tagged: {"ThoughtCreated": {"thought_id": "..."}}
+        let (kind, payload) = match serde_json::to_value(event) {
+            Ok(JsonValue::Object(map)) => map.into_iter().next().unwrap_or((String::new(), JsonValue::Null)),
+            _ => (String::new(), JsonValue::Null),
+        };
+        Self {
+            kind,
+            payload,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        }
+    }
+}
+
+fn validate_embedding(embedding: &[f32]) -> Result<()> {
+    if embedding.is_empty() {
+        return Err(Error::Query("Embedding must not be empty".to_string()));
+    }
+    if embedding.iter().any(|v| !v.is_finite()) {
+        return Err(Error::Query("Embedding contains NaN or infinite values".to_string()));
+    }
+    Ok(())
+}
+
+/// Receive events until the brain is dropped; lagging receivers skip what they missed
+fn event_stream(receiver: broadcast::Receiver<CognitiveEvent>) -> impl Stream<Item = CognitiveEvent> + Send {
+    stream::unfold(receiver, |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(event) => return Some((event, receiver)),
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    })
+}
+
+/// BrainService backed by the server's CognitiveBrain
+pub struct CognitiveBrainService {
+    brain: Arc<CognitiveBrain>,
+}
+
+impl CognitiveBrainService {
+    pub fn new(brain: Arc<CognitiveBrain>) -> Self {
+        Self { brain }
+    }
+}
+
+impl BrainService for CognitiveBrainService {
+    async fn create_thought(&self, request: CreateThoughtRequest) -> Result<CreateThoughtResponse> {
+        if !request.priority.is_finite() || !(0.0..=1.0).contains(&request.priority) {
+            return Err(Error::Query("Priority must be a number between 0.0 and 1.0".to_string()));
+        }
+        let thought_id = self.brain.create_thought(request.content, request.priority)?;
+        Ok(CreateThoughtResponse { thought_id })
+    }
+
+    async fn cancel_thought(&self, request: CancelThoughtRequest) -> Result<CancelThoughtResponse> {
+        self.brain.cancel_thought(&request.thought_id)?;
+        Ok(CancelThoughtResponse { cancelled: true })
+    }
+
+    async fn store_memory(&self, request: StoreMemoryRequest) -> Result<StoreMemoryResponse> {
+        if let Some(embedding) = &request.embedding {
+            validate_embedding(embedding)?;
+        }
+        let memory_id = self.brain.store_memory(
+            request.memory_type,
+            request.content,
+            request.embedding,
+            request.tags,
+            request.thought_id.as_deref(),
+        )?;
+        Ok(StoreMemoryResponse { memory_id })
+    }
+
+    async fn store_experience(&self, request: StoreExperienceRequest) -> Result<StoreExperienceResponse> {
+        if request.event_type.trim().is_empty() {
+            return Err(Error::Query("event_type must not be empty".to_string()));
+        }
+        if let Some(embedding) = &request.embedding {
+            validate_embedding(embedding)?;
+        }
+        if request.reward.is_some_and(|r| !r.is_finite()) {
+            return Err(Error::Query("Reward must be a finite number".to_string()));
+        }
+        let experience_id = self.brain.store_experience(
+            request.event_type,
+            request.observation,
+            request.action,
+            request.outcome,
+            request.reward,
+            request.embedding,
+        )?;
+        Ok(StoreExperienceResponse { experience_id })
+    }
+
+    async fn get_memory(&self, request: GetMemoryRequest) -> Result<Memory> {
+        self.brain.access_memory(&request.memory_id)
+    }
+
+    async fn retrieve_memories(&self, request: RetrieveMemoriesRequest) -> Result<BrainStream<Memory>> {
+        let limit = request.limit.unwrap_or(DEFAULT_RETRIEVE_LIMIT).min(MAX_RETRIEVE_LIMIT);
+        let mut memories = match request.query {
+            MemoryQuery::Semantic { embedding, memory_type } => {
+                validate_embedding(&embedding)?;
+                self.brain
+                    .retrieve_memories_semantic(&embedding, limit, memory_type, request.thought_id.as_deref())?
+            }
+            MemoryQuery::Tag(tag) => self.brain.retrieve_memories_by_tag(&tag)?,
+            MemoryQuery::TimeRange { start, end } => {
+                if start > end {
+                    return Err(Error::Query("Time range start is after its end".to_string()));
+                }
+                self.brain.retrieve_memories_temporal(start, end)?
+            }
+            MemoryQuery::AssociatedWith(memory_id) => self.brain.retrieve_memories_by_association(&memory_id)?,
+        };
+        memories.truncate(limit);
+        Ok(Box::pin(stream::iter(memories.into_iter().map(Ok))))
+    }
+
+    async fn stream_thoughts(&self, request: StreamThoughtsRequest) -> Result<BrainStream<Thought>> {
+        // Subscribe before taking the snapshot so no thought falls between the two
+        let receiver = request.follow.then(|| self.brain.subscribe());
+
+        let mut snapshot = self.brain.get_thoughts_by_state(request.state.clone());
+        snapshot.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+        let existing = stream::iter(snapshot.clone().into_iter().map(Ok));
+
+        let Some(receiver) = receiver else {
+            return Ok(Box::pin(existing));
+        };
+        let seen: HashSet<String> = snapshot.into_iter().map(|t| t.id).collect();
+        let brain = self.brain.clone();
+        let state = request.state;
+        let created = event_stream(receiver).filter_map(move |event| {
+            let thought = match event {
+                CognitiveEvent::ThoughtCreated { thought_id } if !seen.contains(&thought_id) => {
+                    brain.thoughts.read().get(&thought_id).cloned()
+                }
+                _ => None,
+            };
+            let thought = thought.filter(|t| state.as_ref().map_or(true, |s| t.state == *s));
+            futures::future::ready(thought.map(Ok))
+        });
+        Ok(Box::pin(existing.chain(created)))
+    }
+
+    async fn watch_events(&self) -> Result<BrainStream<BrainEvent>> {
+        let events = event_stream(self.brain.subscribe()).map(|event| Ok(BrainEvent::from_event(&event)));
+        Ok(Box::pin(events))
+    }
+}
+
+// ============================================================================
+// narayana.brain.v1 over tonic
+// ============================================================================
+
+/// Messages and service generated from proto/brain.proto
+pub mod brain_proto {
+    tonic::include_proto!("narayana.brain.v1");
+}
+
+use brain_proto::brain_service_server::BrainServiceServer;
+use brain_proto::retrieve_memories_request::Query as ProtoQuery;
+use tonic::{Request, Response, Status};
+
+/// Serves a `CognitiveBrainService` as `narayana.brain.v1.BrainService`
+pub struct BrainGrpcService {
+    service: CognitiveBrainService,
+}
+
+impl BrainGrpcService {
+    pub fn new(brain: Arc<CognitiveBrain>) -> Self {
+        Self { service: CognitiveBrainService::new(brain) }
+    }
+
+    /// Wrap in the generated tonic server
+    pub fn into_server(self) -> BrainServiceServer<Self> {
+        BrainServiceServer::new(self)
+    }
+}
+
+fn to_status(error: Error) -> Status {
+    match error {
+        Error::Query(message) => Status::invalid_argument(message),
+        Error::Storage(message) if message.contains("not found") => Status::not_found(message),
+        other => Status::internal(other.to_string()),
+    }
+}
+
+fn parse_json(field: &str, json: &str) -> std::result::Result<JsonValue, Status> {
+    serde_json::from_str(json).map_err(|e| Status::invalid_argument(format!("{} is not valid JSON: {}", field, e)))
+}
+
+fn parse_optional_json(field: &str, json: Option<String>) -> std::result::Result<Option<JsonValue>, Status> {
+    json.map(|json| parse_json(field, &json)).transpose()
+}
+
+fn non_empty(embedding: Vec<f32>) -> Option<Vec<f32>> {
+    (!embedding.is_empty()).then_some(embedding)
+}
+
+fn memory_type_from_proto(value: i32) -> std::result::Result<MemoryType, Status> {
+    use brain_proto::MemoryType as P;
+    match P::try_from(value) {
+        Ok(P::Episodic) => Ok(MemoryType::Episodic),
+        Ok(P::Semantic) => Ok(MemoryType::Semantic),
+        Ok(P::Procedural) => Ok(MemoryType::Procedural),
+        Ok(P::Working) => Ok(MemoryType::Working),
+        Ok(P::LongTerm) => Ok(MemoryType::LongTerm),
+        Ok(P::Associative) => Ok(MemoryType::Associative),
+        Ok(P::Emotional) => Ok(MemoryType::Emotional),
+        Ok(P::Spatial) => Ok(MemoryType::Spatial),
+        Ok(P::Temporal) => Ok(MemoryType::Temporal),
+        Ok(P::Unspecified) | Err(_) => Err(Status::invalid_argument("memory_type must be set")),
+    }
+}
+
+fn memory_type_to_proto(memory_type: &MemoryType) -> brain_proto::MemoryType {
+    use brain_proto::MemoryType as P;
+    match memory_type {
+        MemoryType::Episodic => P::Episodic,
+        MemoryType::Semantic => P::Semantic,
+        MemoryType::Procedural => P::Procedural,
+        MemoryType::Working => P::Working,
+        MemoryType::LongTerm => P::LongTerm,
+        MemoryType::Associative => P::Associative,
+        MemoryType::Emotional => P::Emotional,
+        MemoryType::Spatial => P::Spatial,
+        MemoryType::Temporal => P::Temporal,
+    }
+}
+
+fn thought_state_from_proto(value: i32) -> std::result::Result<ThoughtState, Status> {
+    use brain_proto::ThoughtState as P;
+    match P::try_from(value) {
+        Ok(P::Active) => Ok(ThoughtState::Active),
+        Ok(P::Paused) => Ok(ThoughtState::Paused),
+        Ok(P::Completed) => Ok(ThoughtState::Completed),
+        Ok(P::Merged) => Ok(ThoughtState::Merged),
+        Ok(P::Discarded) => Ok(ThoughtState::Discarded),
+        Ok(P::Unspecified) | Err(_) => Err(Status::invalid_argument("Unknown thought state")),
+    }
+}
+
+fn thought_state_to_proto(state: &ThoughtState) -> brain_proto::ThoughtState {
+    use brain_proto::ThoughtState as P;
+    match state {
+        ThoughtState::Active => P::Active,
+        ThoughtState::Paused => P::Paused,
+        ThoughtState::Completed => P::Completed,
+        ThoughtState::Merged => P::Merged,
+        ThoughtState::Discarded => P::Discarded,
+    }
+}
+
+fn memory_to_proto(memory: Memory) -> brain_proto::Memory {
+    brain_proto::Memory {
+        memory_type: memory_type_to_proto(&memory.memory_type) as i32,
+        content_json: memory.content.to_string(),
+        embedding: memory.embedding.unwrap_or_default(),
+        id: memory.id,
+        strength: memory.strength,
+        access_count: memory.access_count,
+        last_accessed: memory.last_accessed,
+        created_at: memory.created_at,
+        associations: memory.associations,
+        tags: memory.tags,
+    }
+}
+
+fn thought_to_proto(thought: Thought) -> brain_proto::Thought {
+    brain_proto::Thought {
+        state: thought_state_to_proto(&thought.state) as i32,
+        content_json: thought.content.to_string(),
+        id: thought.id,
+        thread_id: thought.thread_id,
+        created_at: thought.created_at,
+        updated_at: thought.updated_at,
+        priority: thought.priority,
+        associations: thought.associations,
+        spawned_thoughts: thought.spawned_thoughts,
+    }
+}
+
+/// Convert a `BrainStream` into a tonic response stream
+fn proto_stream<T, P>(stream: BrainStream<T>, convert: fn(T) -> P) -> GrpcBrainStream<P>
+where
+    T: 'static,
+    P: Send + 'static,
+{
+    Box::pin(stream.map(move |item| item.map(convert).map_err(to_status)))
+}
+
+/// Server-streaming response of the generated service
+pub type GrpcBrainStream<T> = Pin<Box<dyn Stream<Item = std::result::Result<T, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl brain_proto::brain_service_server::BrainService for BrainGrpcService {
+    type RetrieveMemoriesStream = GrpcBrainStream<brain_proto::Memory>;
+    type StreamThoughtsStream = GrpcBrainStream<brain_proto::Thought>;
+    type WatchEventsStream = GrpcBrainStream<brain_proto::CognitiveEvent>;
+
+    async fn create_thought(
+        &self,
+        request: Request<brain_proto::CreateThoughtRequest>,
+    ) -> std::result::Result<Response<brain_proto::CreateThoughtResponse>, Status> {
+        let request = request.into_inner();
+        let request = CreateThoughtRequest {
+            content: parse_json("content_json", &request.content_json)?,
+            priority: request.priority,
+        };
+        let response = self.service.create_thought(request).await.map_err(to_status)?;
+        Ok(Response::new(brain_proto::CreateThoughtResponse { thought_id: response.thought_id }))
+    }
+
+    async fn cancel_thought(
+        &self,
+        request: Request<brain_proto::CancelThoughtRequest>,
+    ) -> std::result::Result<Response<brain_proto::CancelThoughtResponse>, Status> {
+        let request = CancelThoughtRequest { thought_id: request.into_inner().thought_id };
+        let response = self.service.cancel_thought(request).await.map_err(to_status)?;
+        Ok(Response::new(brain_proto::CancelThoughtResponse { cancelled: response.cancelled }))
+    }
+
+    async fn store_memory(
+        &self,
+        request: Request<brain_proto::StoreMemoryRequest>,
+    ) -> std::result::Result<Response<brain_proto::StoreMemoryResponse>, Status> {
+        let request = request.into_inner();
+        let request = StoreMemoryRequest {
+            memory_type: memory_type_from_proto(request.memory_type)?,
+            content: parse_json("content_json", &request.content_json)?,
+            embedding: non_empty(request.embedding),
+            tags: request.tags,
+            thought_id: request.thought_id,
+        };
+        let response = self.service.store_memory(request).await.map_err(to_status)?;
+        Ok(Response::new(brain_proto::StoreMemoryResponse { memory_id: response.memory_id }))
+    }
+
+    async fn store_experience(
+        &self,
+        request: Request<brain_proto::StoreExperienceRequest>,
+    ) -> std::result::Result<Response<brain_proto::StoreExperienceResponse>, Status> {
+        let request = request.into_inner();
+        let request = StoreExperienceRequest {
+            event_type: request.event_type,
+            observation: parse_json("observation_json", &request.observation_json)?,
+            action: parse_optional_json("action_json", request.action_json)?,
+            outcome: parse_optional_json("outcome_json", request.outcome_json)?,
+            reward: request.reward,
+            embedding: non_empty(request.embedding),
+        };
+        let response = self.service.store_experience(request).await.map_err(to_status)?;
+        Ok(Response::new(brain_proto::StoreExperienceResponse { experience_id: response.experience_id }))
+    }
+
+    async fn get_memory(
+        &self,
+        request: Request<brain_proto::GetMemoryRequest>,
+    ) -> std::result::Result<Response<brain_proto::Memory>, Status> {
+        let request = GetMemoryRequest { memory_id: request.into_inner().memory_id };
+        let memory = self.service.get_memory(request).await.map_err(to_status)?;
+        Ok(Response::new(memory_to_proto(memory)))
+    }
+
+    async fn retrieve_memories(
+        &self,
+        request: Request<brain_proto::RetrieveMemoriesRequest>,
+    ) -> std::result::Result<Response<Self::RetrieveMemoriesStream>, Status> {
+        let request = request.into_inner();
+        let query = match request.query {
+            Some(ProtoQuery::Semantic(semantic)) => MemoryQuery::Semantic {
+                embedding: semantic.embedding,
+                memory_type: semantic.memory_type.map(memory_type_from_proto).transpose()?,
+            },
+            Some(ProtoQuery::Tag(tag)) => MemoryQuery::Tag(tag),
+            Some(ProtoQuery::TimeRange(range)) => MemoryQuery::TimeRange { start: range.start, end: range.end },
+            Some(ProtoQuery::AssociatedWith(memory_id)) => MemoryQuery::AssociatedWith(memory_id),
+            None => return Err(Status::invalid_argument("query must be set")),
+        };
+        let request = RetrieveMemoriesRequest {
+            query,
+            limit: (request.limit > 0).then_some(request.limit as usize),
+            thought_id: request.thought_id,
+        };
+        let memories = self.service.retrieve_memories(request).await.map_err(to_status)?;
+        Ok(Response::new(proto_stream(memories, memory_to_proto)))
+    }
+
+    async fn stream_thoughts(
+        &self,
+        request: Request<brain_proto::StreamThoughtsRequest>,
+    ) -> std::result::Result<Response<Self::StreamThoughtsStream>, Status> {
+        let request = request.into_inner();
+        let request = StreamThoughtsRequest {
+            state: request.state.map(thought_state_from_proto).transpose()?,
+            follow: request.follow,
+        };
+        let thoughts = self.service.stream_thoughts(request).await.map_err(to_status)?;
+        Ok(Response::new(proto_stream(thoughts, thought_to_proto)))
+    }
+
+    async fn watch_events(
+        &self,
+        _request: Request<brain_proto::WatchEventsRequest>,
+    ) -> std::result::Result<Response<Self::WatchEventsStream>, Status> {
+        let events = self.service.watch_events().await.map_err(to_status)?;
+        Ok(Response::new(proto_stream(events, |event| brain_proto::CognitiveEvent {
+            kind: event.kind,
+            payload_json: event.payload.to_string(),
+            timestamp: event.timestamp,
+        })))
+    }
+}
@@ -8,6 +8,7 @@ pub mod webhooks;
 pub mod ultimate;
 pub mod rest_advanced;
 pub mod grpc_advanced;
+pub mod arrow_extract;
 pub mod query_dsl;
 pub mod connection;
 pub mod graphql;
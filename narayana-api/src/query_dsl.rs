@@ -211,6 +211,42 @@ impl WhereBuilder {
         }
     }
 
+    /// Full-text MATCH (terms, "phrases" and prefix* clauses)
+    pub fn matches(self, query: &str) -> WhereConditionBuilder {
+        WhereConditionBuilder {
+            table: self.table,
+            conditions: vec![Condition {
+                column: self.column,
+                operator: "match".to_string(),
+                value: JsonValue::String(query.to_string()),
+            }],
+        }
+    }
+
+    /// Geometry within `radius_meters` of (lon, lat)
+    pub fn within_radius(self, lon: f64, lat: f64, radius_meters: f64) -> WhereConditionBuilder {
+        WhereConditionBuilder {
+            table: self.table,
+            conditions: vec![Condition {
+                column: self.column,
+                operator: "within_radius".to_string(),
+                value: serde_json::json!({ "lon": lon, "lat": lat, "radius_meters": radius_meters }),
+            }],
+        }
+    }
+
+    /// Geometry intersecting the bounding box
+    pub fn within_box(self, min_lon: f64, min_lat: f64, max_lon: f64, max_lat: f64) -> WhereConditionBuilder {
+        WhereConditionBuilder {
+            table: self.table,
+            conditions: vec![Condition {
+                column: self.column,
+                operator: "within_box".to_string(),
+                value: serde_json::json!({ "min_lon": min_lon, "min_lat": min_lat, "max_lon": max_lon, "max_lat": max_lat }),
+            }],
+        }
+    }
+
     pub fn between(self, min: JsonValue, max: JsonValue) -> WhereConditionBuilder {
         WhereConditionBuilder {
             table: self.table,
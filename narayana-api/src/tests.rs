@@ -962,3 +962,395 @@ async fn test_bulk_operations_with_salt() {
     assert!(result.is_ok());
 }
 
+#[tokio::test]
+async fn test_connection_for_principal_applies_row_policies() {
+    use narayana_storage::row_security::{Principal, RowPolicy, RowSecurity};
+    use narayana_storage::ColumnStore;
+
+    let store = Arc::new(InMemoryColumnStore::new());
+    let schema = Schema::new(vec![
+        Field { name: "id".to_string(), data_type: DataType::Int64, nullable: false, default_value: None },
+        Field { name: "owner".to_string(), data_type: DataType::String, nullable: false, default_value: None },
+    ]);
+    store.create_table(TableId(9), schema).await.unwrap();
+    let owners = vec!["ana".to_string(), "ben".to_string(), "ana".to_string()];
+    store.write_columns(TableId(9), vec![Column::Int64(vec![1, 2, 3]), Column::String(owners)]).await.unwrap();
+    let security = Arc::new(RowSecurity::new());
+    let policy = RowPolicy { name: "own_rows".to_string(), expression: "owner == $sub".to_string(), roles: Vec::new() };
+    security.set_policy(TableId(9), policy).unwrap();
+
+    let ana = DirectConnection::for_principal(store.clone(), &security, Principal::new("ana", Vec::new()));
+    let columns = ana.read_columns(TableId(9), vec![0], 0, 10).await.unwrap();
+    assert!(matches!(&columns[0], Column::Int64(ids) if ids == &vec![1, 3]));
+    let unfiltered = DirectConnection::new(store).read_columns(TableId(9), vec![0], 0, 10).await.unwrap();
+    assert_eq!(unfiltered[0].len(), 3);
+}
+
+
+// ============================================================================
+// BRAIN gRPC SERVICE TESTS
+// ============================================================================
+
+mod brain_service {
+    use crate::grpc_advanced::*;
+    use futures::StreamExt;
+    use narayana_storage::cognitive::{CognitiveBrain, MemoryType, ThoughtState};
+    use serde_json::json;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn service() -> CognitiveBrainService {
+        CognitiveBrainService::new(Arc::new(CognitiveBrain::new()))
+    }
+
+    fn memory(tags: &[&str], embedding: Option<Vec<f32>>) -> StoreMemoryRequest {
+        StoreMemoryRequest {
+            memory_type: MemoryType::Semantic,
+            content: json!({"fact": tags.join(",")}),
+            embedding,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            thought_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_and_cancel_thought() {
+        let service = service();
+        let bad = service.create_thought(CreateThoughtRequest { content: json!("x"), priority: 1.5 }).await;
+        assert!(bad.is_err());
+
+        let created = service
+            .create_thought(CreateThoughtRequest { content: json!({"goal": "dock"}), priority: 0.5 })
+            .await
+            .unwrap();
+        let cancelled = service
+            .cancel_thought(CancelThoughtRequest { thought_id: created.thought_id.clone() })
+            .await
+            .unwrap();
+        assert!(cancelled.cancelled);
+        assert!(service.cancel_thought(CancelThoughtRequest { thought_id: "missing".into() }).await.is_err());
+
+        let discarded: Vec<_> = service
+            .stream_thoughts(StreamThoughtsRequest { state: Some(ThoughtState::Discarded), follow: false })
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        assert_eq!(discarded.len(), 1);
+        assert_eq!(discarded[0].as_ref().unwrap().id, created.thought_id);
+    }
+
+    #[tokio::test]
+    async fn test_store_and_retrieve_memories() {
+        let service = service();
+        let dock = service.store_memory(memory(&["dock"], Some(vec![1.0, 0.0]))).await.unwrap();
+        service.store_memory(memory(&["dock", "charger"], Some(vec![0.0, 1.0]))).await.unwrap();
+        service.store_memory(memory(&["kitchen"], None)).await.unwrap();
+        assert!(service.store_memory(memory(&["bad"], Some(vec![f32::NAN]))).await.is_err());
+
+        let fetched = service.get_memory(GetMemoryRequest { memory_id: dock.memory_id.clone() }).await.unwrap();
+        assert_eq!(fetched.tags, vec!["dock".to_string()]);
+
+        let by_tag: Vec<_> = service
+            .retrieve_memories(RetrieveMemoriesRequest { query: MemoryQuery::Tag("dock".into()), limit: None, thought_id: None })
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        assert_eq!(by_tag.len(), 2);
+
+        let limited: Vec<_> = service
+            .retrieve_memories(RetrieveMemoriesRequest { query: MemoryQuery::Tag("dock".into()), limit: Some(1), thought_id: None })
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        assert_eq!(limited.len(), 1);
+
+        let semantic: Vec<_> = service
+            .retrieve_memories(RetrieveMemoriesRequest {
+                query: MemoryQuery::Semantic { embedding: vec![0.9, 0.1], memory_type: None },
+                limit: Some(5),
+                thought_id: None,
+            })
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        assert_eq!(semantic.len(), 2);
+        assert_eq!(semantic[0].as_ref().unwrap().id, dock.memory_id);
+
+        let reversed = service
+            .retrieve_memories(RetrieveMemoriesRequest { query: MemoryQuery::TimeRange { start: 10, end: 1 }, limit: None, thought_id: None })
+            .await;
+        assert!(reversed.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_follow_thoughts_and_events() {
+        let service = service();
+        let first = service.create_thought(CreateThoughtRequest { content: json!(1), priority: 0.1 }).await.unwrap();
+
+        let mut thoughts = service
+            .stream_thoughts(StreamThoughtsRequest { state: None, follow: true })
+            .await
+            .unwrap();
+        let mut events = service.watch_events().await.unwrap();
+        assert_eq!(thoughts.next().await.unwrap().unwrap().id, first.thought_id);
+
+        let second = service.create_thought(CreateThoughtRequest { content: json!(2), priority: 0.2 }).await.unwrap();
+        let next = tokio::time::timeout(Duration::from_secs(5), thoughts.next()).await.unwrap();
+        assert_eq!(next.unwrap().unwrap().id, second.thought_id);
+
+        let event = tokio::time::timeout(Duration::from_secs(5), events.next()).await.unwrap().unwrap().unwrap();
+        assert_eq!(event.kind, "ThoughtCreated");
+        assert_eq!(event.payload["thought_id"], json!(second.thought_id));
+    }
+
+    #[tokio::test]
+    async fn test_served_over_grpc() {
+        use crate::grpc_advanced::brain_proto::{self, brain_service_client::BrainServiceClient};
+        use brain_proto::retrieve_memories_request::Query;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = BrainGrpcService::new(Arc::new(CognitiveBrain::new())).into_server();
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(server)
+                .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener)),
+        );
+        let mut client = BrainServiceClient::connect(format!("http://{}", addr)).await.unwrap();
+
+        let stored = client
+            .store_memory(brain_proto::StoreMemoryRequest {
+                memory_type: brain_proto::MemoryType::Semantic as i32,
+                content_json: r#"{"fact":"dock"}"#.to_string(),
+                embedding: vec![1.0, 0.0],
+                tags: vec!["dock".to_string()],
+                thought_id: None,
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        let memory = client
+            .get_memory(brain_proto::GetMemoryRequest { memory_id: stored.memory_id.clone() })
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(memory.memory_type, brain_proto::MemoryType::Semantic as i32);
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&memory.content_json).unwrap(), json!({"fact": "dock"}));
+
+        let by_tag: Vec<_> = client
+            .retrieve_memories(brain_proto::RetrieveMemoriesRequest {
+                query: Some(Query::Tag("dock".to_string())),
+                limit: 0,
+                thought_id: None,
+            })
+            .await
+            .unwrap()
+            .into_inner()
+            .collect()
+            .await;
+        assert_eq!(by_tag.len(), 1);
+        assert_eq!(by_tag[0].as_ref().unwrap().id, stored.memory_id);
+
+        let missing = client.get_memory(brain_proto::GetMemoryRequest { memory_id: "missing".into() }).await;
+        assert_eq!(missing.unwrap_err().code(), tonic::Code::NotFound);
+        let invalid = client
+            .create_thought(brain_proto::CreateThoughtRequest { content_json: "{".into(), priority: 0.5 })
+            .await;
+        assert_eq!(invalid.unwrap_err().code(), tonic::Code::InvalidArgument);
+    }
+}
+
+// ============================================================================
+// ARROW EXTRACT TESTS
+// ============================================================================
+
+mod arrow_extract {
+    use crate::arrow_extract::*;
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::Int64Type;
+    use futures::StreamExt;
+    use narayana_core::{
+        column::Column,
+        schema::{DataType, Field, Schema},
+        types::TableId,
+    };
+    use narayana_storage::{ColumnStore, InMemoryColumnStore};
+    use std::sync::Arc;
+
+    async fn store(rows: i64) -> Arc<InMemoryColumnStore> {
+        let store = Arc::new(InMemoryColumnStore::new());
+        let field = |name: &str, data_type| Field { name: name.to_string(), data_type, nullable: false, default_value: None };
+        let schema = Schema::new(vec![field("id", DataType::Int64), field("doc", DataType::Json)]);
+        store.create_table(TableId(7), schema).await.unwrap();
+        if rows > 0 {
+            store
+                .write_columns(
+                    TableId(7),
+                    vec![
+                        Column::Int64((0..rows).collect()),
+                        Column::String((0..rows).map(|i| format!("{{\"n\":{}}}", i)).collect()),
+                    ],
+                )
+                .await
+                .unwrap();
+        }
+        store
+    }
+
+    fn id_schema() -> arrow_schema::SchemaRef {
+        let fields = [Field { name: "id".to_string(), data_type: DataType::Int64, nullable: false, default_value: None }];
+        Arc::new(narayana_core::arrow::arrow_schema(&fields).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_table_batches_page_through_the_table() {
+        let batches: Vec<_> = table_batches(store(10).await, TableId(7), vec![0], id_schema(), 4)
+            .map(|batch| batch.unwrap())
+            .collect()
+            .await;
+        let sizes: Vec<usize> = batches.iter().map(|b| b.num_rows()).collect();
+        assert_eq!(sizes, vec![4, 4, 2]);
+        assert_eq!(batches[0].num_columns(), 1);
+        assert_eq!(batches[2].column(0).as_primitive::<Int64Type>().values(), &[8, 9]);
+    }
+
+    #[tokio::test]
+    async fn test_table_batches_on_empty_table() {
+        let batches: Vec<_> = table_batches(store(0).await, TableId(7), vec![0], id_schema(), DEFAULT_BATCH_ROWS)
+            .collect()
+            .await;
+        assert!(batches.is_empty());
+    }
+}
+
+// ============================================================================
+// TRANSACTION gRPC SERVICE TESTS
+// ============================================================================
+
+mod transaction_service {
+    use crate::grpc::*;
+    use narayana_core::{
+        column::Column,
+        config::TransactionsConfig,
+        schema::{DataType, Field, Schema},
+        types::TableId,
+    };
+    use narayana_storage::transactions::TransactionRegistry;
+    use narayana_storage::{ColumnStore, InMemoryColumnStore};
+    use std::sync::Arc;
+
+    async fn setup() -> (Arc<TransactionRegistry>, Arc<InMemoryColumnStore>) {
+        let store = Arc::new(InMemoryColumnStore::new());
+        let field = |name: &str, data_type| Field { name: name.to_string(), data_type, nullable: false, default_value: None };
+        let schema = Schema::new(vec![field("id", DataType::Int64), field("pose", DataType::String)]);
+        store.create_table(TableId(3), schema).await.unwrap();
+        (Arc::new(TransactionRegistry::new(TransactionsConfig::default())), store)
+    }
+
+    fn insert(transaction_id: u64, ids: Vec<i64>) -> ExecuteStatementRequest {
+        let poses = ids.iter().map(|id| format!("p{}", id)).collect();
+        ExecuteStatementRequest {
+            transaction_id,
+            statement: TransactionStatement::Insert { table_id: 3, columns: vec![Column::Int64(ids), Column::String(poses)] },
+        }
+    }
+
+    fn query(transaction_id: u64) -> ExecuteStatementRequest {
+        ExecuteStatementRequest {
+            transaction_id,
+            statement: TransactionStatement::Query { table_id: 3, columns: Some(vec!["id".to_string()]), limit: None },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_commit_makes_staged_rows_visible() {
+        let (registry, store) = setup().await;
+        let service = RegistryTransactionService::new(registry.clone(), store.clone(), "robot-1".to_string());
+        let other = RegistryTransactionService::new(registry.clone(), store.clone(), "robot-2".to_string());
+
+        let txn = service.begin(BeginTransactionRequest { timeout_ms: None }).await.unwrap();
+        let staged = service.execute(insert(txn.transaction_id, vec![1, 2])).await.unwrap();
+        assert_eq!(staged.rows_staged, 2);
+        assert_eq!(service.execute(query(txn.transaction_id)).await.unwrap().row_count, 2);
+        assert!(other.execute(query(txn.transaction_id)).await.is_err());
+        assert!(store.read_columns(TableId(3), vec![0], 0, 10).await.unwrap().is_empty());
+
+        let committed = service.commit(EndTransactionRequest { transaction_id: txn.transaction_id }).await.unwrap();
+        assert_eq!(committed.rows, 2);
+        let columns = store.read_columns(TableId(3), vec![0], 0, 10).await.unwrap();
+        assert!(matches!(&columns[0], Column::Int64(ids) if ids == &vec![1, 2]));
+        assert!(service.commit(EndTransactionRequest { transaction_id: txn.transaction_id }).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rollback_discards_staged_rows() {
+        let (registry, store) = setup().await;
+        let service = RegistryTransactionService::new(registry.clone(), store.clone(), "robot-1".to_string());
+
+        let txn = service.begin(BeginTransactionRequest { timeout_ms: Some(5_000) }).await.unwrap();
+        assert_eq!(txn.timeout_ms, 5_000);
+        service.execute(insert(txn.transaction_id, vec![7])).await.unwrap();
+        let bad = ExecuteStatementRequest {
+            transaction_id: txn.transaction_id,
+            statement: TransactionStatement::Insert { table_id: 3, columns: vec![Column::Int64(vec![8])] },
+        };
+        assert!(service.execute(bad).await.is_err());
+
+        let rolled_back = service.rollback(EndTransactionRequest { transaction_id: txn.transaction_id }).await.unwrap();
+        assert_eq!(rolled_back.staged_rows, 1);
+        assert!(store.read_columns(TableId(3), vec![0], 0, 10).await.unwrap().is_empty());
+        assert_eq!(registry.metrics().rolled_back, 1);
+        assert_eq!(registry.metrics().active, 0);
+    }
+    /// Commits into a store, refusing rows with a negative id and recording
+    /// the tables it publishes
+    struct Publishing {
+        store: Arc<InMemoryColumnStore>,
+        published: parking_lot::Mutex<Vec<TableId>>,
+    }
+
+    #[async_trait::async_trait]
+    impl narayana_storage::transactions::CommitTarget for Publishing {
+        fn store(&self) -> &dyn ColumnStore {
+            self.store.as_ref()
+        }
+
+        async fn validate(&self, _table_id: TableId, _schema: &Schema, columns: &[Column]) -> narayana_core::Result<()> {
+            match &columns[0] {
+                Column::Int64(ids) if ids.iter().any(|id| *id < 0) => Err(narayana_core::Error::Transaction("negative id".to_string())),
+                _ => Ok(()),
+            }
+        }
+
+        async fn write(&self, guard: &narayana_storage::column_store::TableWriteGuard, columns: Vec<Column>) -> narayana_core::Result<Vec<Column>> {
+            self.store.write_locked(guard, columns.clone()).await?;
+            Ok(columns)
+        }
+
+        fn publish(&self, table_id: TableId, _schema: &Schema, _columns: &[Column]) {
+            self.published.lock().push(table_id);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_commit_goes_through_the_commit_target() {
+        let (registry, store) = setup().await;
+        let target = Arc::new(Publishing { store: store.clone(), published: parking_lot::Mutex::new(Vec::new()) });
+        let service = RegistryTransactionService::new(registry.clone(), store.clone(), "robot-1".to_string())
+            .with_commit_target(target.clone());
+
+        let txn = service.begin(BeginTransactionRequest { timeout_ms: None }).await.unwrap();
+        let refused = service.execute(insert(txn.transaction_id, vec![-1])).await.unwrap_err();
+        assert!(refused.contains("negative id"), "{}", refused);
+        service.execute(insert(txn.transaction_id, vec![4])).await.unwrap();
+        let committed = service.commit(EndTransactionRequest { transaction_id: txn.transaction_id }).await.unwrap();
+        assert_eq!(committed.rows, 1);
+        assert_eq!(*target.published.lock(), vec![TableId(3)]);
+        let columns = store.read_columns(TableId(3), vec![0], 0, 10).await.unwrap();
+        assert!(matches!(&columns[0], Column::Int64(ids) if ids == &vec![4]));
+    }
+}
@@ -28,6 +28,11 @@ pub enum WsMessage {
         #[serde(skip_serializing_if = "Option::is_none")]
         id: Option<String>,
     },
+    /// Events up to `seq` were received; they are dropped from the replay buffer
+    #[serde(rename = "ack")]
+    Ack {
+        seq: u64,
+    },
     
     // Server -> Client messages
     #[serde(rename = "event")]
@@ -36,6 +41,23 @@ pub enum WsMessage {
         event: JsonValue,
         #[serde(skip_serializing_if = "Option::is_none")]
         timestamp: Option<u64>,
+        /// Per-session sequence number, used to resume after a reconnect
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        seq: Option<u64>,
+    },
+    /// Sent on connect; reconnect with `resume_token` and the last seen `seq`
+    #[serde(rename = "session")]
+    Session {
+        resume_token: String,
+        last_seq: u64,
+    },
+    /// Sent when a session is resumed, before the replayed events
+    #[serde(rename = "resumed")]
+    Resumed {
+        last_seq: u64,
+        replayed: usize,
+        /// Events that fell out of the replay buffer and are lost
+        missed: u64,
     },
     #[serde(rename = "query_result")]
     QueryResult {
@@ -136,6 +158,7 @@ impl WsMessage {
             channel: channel.into(),
             event,
             timestamp: None,
+            seq: None,
         }
     }
 
@@ -149,6 +172,28 @@ impl WsMessage {
             channel: channel.into(),
             event,
             timestamp: Some(timestamp),
+            seq: None,
+        }
+    }
+}
+
+/// Binary frame type tags: client messages below 0x40, server messages from 0x40
+impl narayana_core::wire::WireTagged for WsMessage {
+    fn wire_tag(&self) -> u8 {
+        match self {
+            WsMessage::Subscribe { .. } => 0x01,
+            WsMessage::Unsubscribe { .. } => 0x02,
+            WsMessage::Query { .. } => 0x03,
+            WsMessage::Ping { .. } => 0x04,
+            WsMessage::Ack { .. } => 0x05,
+            WsMessage::Event { .. } => 0x40,
+            WsMessage::Session { .. } => 0x41,
+            WsMessage::Resumed { .. } => 0x42,
+            WsMessage::QueryResult { .. } => 0x43,
+            WsMessage::Error { .. } => 0x44,
+            WsMessage::Pong { .. } => 0x45,
+            WsMessage::Subscribed { .. } => 0x46,
+            WsMessage::Unsubscribed { .. } => 0x47,
         }
     }
 }
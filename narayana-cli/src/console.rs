@@ -3,11 +3,115 @@
 
 use anyhow::{Result, anyhow};
 use reqwest::Client;
+use rustyline::completion::{Completer, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::history::DefaultHistory;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
 use serde_json::{json, Value};
-use std::io::{self, Write, BufRead, BufReader};
+use std::io::{self, Write};
 use std::time::{Duration, SystemTime};
 use std::collections::HashMap;
 
+/// Console commands completed locally, before any query text
+const CONSOLE_COMMANDS: &[&str] = &[
+    "help", "exit", "quit", "clear", "databases", "use", "tables", "describe",
+    "query", "history", "var", "vars", "save",
+];
+
+/// Completion requests give up quickly so typing never stalls
+const COMPLETION_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Tab completion: console commands locally, query text through the
+/// server's autocomplete endpoint
+struct ConsoleHelper {
+    client: Client,
+    server_url: String,
+}
+
+impl ConsoleHelper {
+    /// Ask the server to complete `query` at byte offset `cursor`
+    fn server_completions(&self, query: &str, cursor: usize) -> Option<(usize, Vec<Pair>)> {
+        let url = format!("{}/api/v1/autocomplete", self.server_url);
+        let request = self.client
+            .post(&url)
+            .timeout(COMPLETION_TIMEOUT)
+            .json(&json!({ "query": query, "cursor": cursor }));
+        // Completion runs inside the blocking readline call on the runtime
+        let data: Value = tokio::task::block_in_place(|| {
+            tokio::runtime::Handle::current().block_on(async {
+                let response = request.send().await.ok()?;
+                if !response.status().is_success() {
+                    return None;
+                }
+                response.json().await.ok()
+            })
+        })?;
+
+        let start = data.get("replace_start").and_then(|v| v.as_u64())? as usize;
+        let pairs = data.get("suggestions")
+            .and_then(|v| v.as_array())?
+            .iter()
+            .filter_map(|suggestion| {
+                let text = suggestion.get("text").and_then(|v| v.as_str())?;
+                let display = match suggestion.pointer("/metadata/data_type").and_then(|v| v.as_str()) {
+                    Some(data_type) => format!("{} ({})", text, data_type),
+                    None => text.to_string(),
+                };
+                Some(Pair { display, replacement: text.to_string() })
+            })
+            .collect();
+        Some((start, pairs))
+    }
+}
+
+impl Helper for ConsoleHelper {}
+impl Highlighter for ConsoleHelper {}
+impl Validator for ConsoleHelper {}
+
+impl Hinter for ConsoleHelper {
+    type Hint = String;
+}
+
+impl Completer for ConsoleHelper {
+    type Candidate = Pair;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let before = &line[..pos];
+        let first_word_end = before.find(char::is_whitespace);
+
+        // The first word is a console command or the start of a query
+        if first_word_end.is_none() {
+            let typed = before.to_lowercase();
+            let mut pairs: Vec<Pair> = CONSOLE_COMMANDS.iter()
+                .filter(|command| command.starts_with(&typed))
+                .map(|command| Pair { display: command.to_string(), replacement: command.to_string() })
+                .collect();
+            if let Some((_, server)) = self.server_completions(line, pos) {
+                pairs.extend(server.into_iter().filter(|p| !CONSOLE_COMMANDS.contains(&p.replacement.to_lowercase().as_str())));
+            }
+            return Ok((0, pairs));
+        }
+
+        // `query <sql>` completes the SQL after the command; other lines are
+        // completed as queries themselves
+        let offset = match first_word_end {
+            Some(end) if before[..end].eq_ignore_ascii_case("query") => {
+                end + (line[end..].len() - line[end..].trim_start().len())
+            }
+            _ => 0,
+        };
+        if pos < offset {
+            return Ok((pos, Vec::new()));
+        }
+        Ok(self.server_completions(&line[offset..], pos - offset)
+            .map(|(start, pairs)| (start + offset, pairs))
+            .unwrap_or((pos, Vec::new())))
+    }
+}
+
 pub struct InteractiveConsole {
     client: Client,
     server_url: String,
@@ -39,25 +143,29 @@ impl InteractiveConsole {
         self.print_banner();
         self.print_help();
 
-        let stdin = io::stdin();
-        let mut stdin = BufReader::new(stdin.lock());
+        let mut editor: Editor<ConsoleHelper, DefaultHistory> = Editor::new()?;
+        editor.set_helper(Some(ConsoleHelper {
+            client: self.client.clone(),
+            server_url: self.server_url.clone(),
+        }));
 
         loop {
             // Update prompt with current database
             self.update_prompt();
-            print!("{}> ", self.prompt);
-            io::stdout().flush()?;
-
-            let mut line = String::new();
-            stdin.read_line(&mut line)?;
-
-            let line = line.trim().to_string();
+            let line = match editor.readline(&format!("{}> ", self.prompt)) {
+                Ok(line) => line.trim().to_string(),
+                // Ctrl-C discards the line being typed
+                Err(ReadlineError::Interrupted) => continue,
+                Err(ReadlineError::Eof) => break,
+                Err(e) => return Err(e.into()),
+            };
 
             if line.is_empty() {
                 continue;
             }
 
             // Add to history
+            editor.add_history_entry(line.as_str())?;
             if !self.history.contains(&line) {
                 self.history.push(line.clone());
             }
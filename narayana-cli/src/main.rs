@@ -136,6 +136,22 @@ enum Commands {
     /// Manage webhooks
     #[command(subcommand)]
     Webhook(WebhookCommands),
+
+    /// Manage skill packages
+    #[command(subcommand)]
+    Skill(SkillCommands),
+    
+    /// Manage maintenance jobs
+    #[command(subcommand)]
+    Job(JobCommands),
+    
+    /// Manage ingest connectors
+    #[command(subcommand)]
+    Connector(ConnectorCommands),
+    
+    /// Manage conscience persistent loops (CPLs)
+    #[command(subcommand)]
+    Cpl(CplCommands),
     
     /// Backup and restore
     #[command(subcommand)]
@@ -277,6 +293,16 @@ enum ConfigCommands {
     Save {
         file: String,
     },
+    
+    /// Check a configuration file (TOML, YAML or JSON) and NARAYANA__* overrides
+    Validate {
+        /// Configuration file; defaults to NARAYANA_CONFIG, or built-in defaults
+        file: Option<String>,
+        
+        /// Print the effective configuration (secrets masked)
+        #[arg(long)]
+        print: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -293,6 +319,14 @@ enum WebhookCommands {
         /// Scope (global, database, table)
         #[arg(long, short, default_value = "global")]
         scope: String,
+
+        /// HMAC signing secret (generated by the server if omitted)
+        #[arg(long)]
+        secret: Option<String>,
+
+        /// Retries after a failed delivery
+        #[arg(long)]
+        retries: Option<u32>,
     },
     
     /// List webhooks
@@ -302,6 +336,173 @@ enum WebhookCommands {
     Delete {
         webhook_id: String,
     },
+
+    /// Show delivery history of a webhook
+    Deliveries {
+        webhook_id: String,
+
+        /// Maximum deliveries to show (newest first)
+        #[arg(long, short, default_value = "20")]
+        limit: usize,
+    },
+
+    /// Re-send a previous delivery
+    Redeliver {
+        webhook_id: String,
+        delivery_id: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum SkillCommands {
+    /// Install a skill package
+    Install {
+        /// Package directory or manifest file
+        path: String,
+    },
+
+    /// Upgrade an installed skill to a newer package version
+    Upgrade {
+        /// Package directory or manifest file
+        path: String,
+    },
+
+    /// Uninstall a skill
+    Uninstall {
+        name: String,
+
+        /// Also drop the tables the skill created
+        #[arg(long)]
+        purge: bool,
+    },
+
+    /// List installed skills
+    List,
+
+    /// Show install, upgrade and uninstall history of a skill
+    History {
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum JobCommands {
+    /// List jobs with their schedules and last run
+    List,
+
+    /// Show a job and its recent runs
+    Status {
+        name: String,
+
+        /// Maximum runs to show (newest first)
+        #[arg(long, short, default_value = "20")]
+        limit: usize,
+    },
+
+    /// Run a job now
+    Trigger {
+        name: String,
+    },
+
+    /// Stop running a job on its schedule
+    Pause {
+        name: String,
+    },
+
+    /// Put a paused job back on its schedule
+    Resume {
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConnectorCommands {
+    /// List connectors with their cursors and totals
+    List,
+
+    /// Create or replace a connector from a JSON definition
+    Create {
+        /// JSON file with name, table, source, schedule, dedupe_key, filters and transforms
+        file: String,
+    },
+
+    /// Show a connector, its polling job and last run
+    Status {
+        name: String,
+    },
+
+    /// Remove a connector (ingested rows stay)
+    Delete {
+        name: String,
+    },
+
+    /// Poll a connector now
+    Poll {
+        name: String,
+    },
+
+    /// Stop polling a connector on its schedule
+    Pause {
+        name: String,
+    },
+
+    /// Put a paused connector back on its schedule
+    Resume {
+        name: String,
+    },
+
+    /// Forget the cursor and dedupe keys so the source is read from the start
+    Reset {
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum CplCommands {
+    /// List CPLs with their state and health
+    List,
+
+    /// Create a CPL
+    Create {
+        /// Unique name
+        #[arg(long, short)]
+        name: Option<String>,
+
+        /// JSON file with a create request: config (incl. max_iterations, max_memories,
+        /// max_iteration_ms) and genome
+        #[arg(long, short)]
+        file: Option<String>,
+    },
+
+    /// Show a CPL's config, health and world adapters
+    Status {
+        id: String,
+    },
+
+    /// Start a CPL's loop and world adapters
+    Start {
+        id: String,
+    },
+
+    /// Pause a running CPL's loop
+    Pause {
+        id: String,
+    },
+
+    /// Resume a paused CPL's loop
+    Resume {
+        id: String,
+    },
+
+    /// Stop a CPL's loop and world adapters
+    Stop {
+        id: String,
+    },
+
+    /// Stop and remove a CPL
+    Destroy {
+        id: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -379,6 +580,18 @@ async fn main() -> anyhow::Result<()> {
         Commands::Webhook(cmd) => {
             handle_webhook_command(&cli.server, cmd).await?;
         }
+        Commands::Skill(cmd) => {
+            handle_skill_command(&cli.server, cmd).await?;
+        }
+        Commands::Job(cmd) => {
+            handle_job_command(&cli.server, cmd).await?;
+        }
+        Commands::Connector(cmd) => {
+            handle_connector_command(&cli.server, cmd).await?;
+        }
+        Commands::Cpl(cmd) => {
+            handle_cpl_command(&cli.server, cmd).await?;
+        }
         Commands::Backup(cmd) => {
             handle_backup_command(cmd).await?;
         }
@@ -941,6 +1154,45 @@ async fn handle_config_command(server: &str, cmd: ConfigCommands) -> anyhow::Res
             println!("💾 Saving configuration to: {}", file);
             // Implementation would save current config
         }
+        ConfigCommands::Validate { file, print } => {
+            validate_config(file, print)?;
+        }
+    }
+    
+    Ok(())
+}
+
+/// Validate a configuration file the way the server loads it at startup
+fn validate_config(file: Option<String>, print: bool) -> anyhow::Result<()> {
+    use narayana_core::config::NarayanaConfig;
+
+    let file = file.or_else(|| std::env::var("NARAYANA_CONFIG").ok());
+    let report = match NarayanaConfig::load_report(file.as_deref()) {
+        Ok(report) => report,
+        Err(e) => {
+            println!("❌ Invalid configuration: {}", e);
+            return Err(anyhow::anyhow!("configuration is invalid"));
+        }
+    };
+    
+    match &report.source {
+        Some(source) => println!("✅ Configuration is valid: {}", source),
+        None => println!("✅ Configuration is valid (built-in defaults)"),
+    }
+    for name in &report.env_overrides {
+        println!("   overridden by {}", name);
+    }
+    for key in &report.unknown_keys {
+        println!("⚠️  Unknown setting ignored: {}", key);
+    }
+    let config = &report.config;
+    println!("   HTTP:       {}:{}{}", config.network.bind_address, config.network.bind_port,
+        if config.network.enable_tls { " (TLS)" } else { "" });
+    println!("   Data Dir:   {}", config.storage.data_dir);
+    println!("   Subsystems: {}", config.features.enabled().join(", "));
+    
+    if print {
+        println!("{}", serde_json::to_string_pretty(&config.redacted())?);
     }
     
     Ok(())
@@ -973,13 +1225,15 @@ async fn handle_webhook_command(server: &str, cmd: WebhookCommands) -> anyhow::R
     let client = reqwest::Client::new();
     
     match cmd {
-        WebhookCommands::Create { url, events, scope } => {
+        WebhookCommands::Create { url, events, scope, secret, retries } => {
             let response = client
                 .post(&format!("{}/api/v1/webhooks", server))
                 .json(&json!({
                     "url": url,
                     "events": events.map(|e| e.split(',').map(|s| s.to_string()).collect::<Vec<_>>()),
-                    "scope": scope
+                    "scope": scope,
+                    "secret": secret,
+                    "retry_count": retries
                 }))
                 .send()
                 .await?;
@@ -1015,11 +1269,403 @@ async fn handle_webhook_command(server: &str, cmd: WebhookCommands) -> anyhow::R
                 println!("❌ Failed to delete webhook: {}", response.status());
             }
         }
+        WebhookCommands::Deliveries { webhook_id, limit } => {
+            let response = client
+                .get(&format!("{}/api/v1/webhooks/{}/deliveries?limit={}", server, webhook_id, limit))
+                .send()
+                .await?;
+            
+            if response.status().is_success() {
+                let deliveries: serde_json::Value = response.json().await?;
+                println!("📬 Deliveries:");
+                println!("{}", serde_json::to_string_pretty(&deliveries)?);
+            } else {
+                println!("❌ Failed to get deliveries: {}", response.status());
+            }
+        }
+        WebhookCommands::Redeliver { webhook_id, delivery_id } => {
+            let response = client
+                .post(&format!("{}/api/v1/webhooks/{}/deliveries/{}/redeliver", server, webhook_id, delivery_id))
+                .send()
+                .await?;
+            
+            if response.status().is_success() {
+                let delivery: serde_json::Value = response.json().await?;
+                println!("✅ Redelivery finished");
+                println!("{}", serde_json::to_string_pretty(&delivery)?);
+            } else {
+                println!("❌ Failed to redeliver: {}", response.status());
+            }
+        }
     }
     
     Ok(())
 }
 
+/// Handle skill commands
+async fn handle_skill_command(server: &str, cmd: SkillCommands) -> anyhow::Result<()> {
+    use narayana_core::skill::SkillManifest;
+
+    let client = reqwest::Client::new();
+
+    match cmd {
+        SkillCommands::Install { path } => {
+            let bundle = SkillManifest::load(std::path::Path::new(&path))?;
+            let response = client
+                .post(&format!("{}/api/v1/skills", server))
+                .json(&bundle)
+                .send()
+                .await?;
+
+            if response.status().is_success() {
+                let skill: serde_json::Value = response.json().await?;
+                println!("✅ Skill '{}' {} installed", bundle.name, bundle.version);
+                println!("{}", serde_json::to_string_pretty(&skill)?);
+            } else {
+                let status = response.status();
+                println!("❌ Failed to install skill: {} {}", status, response.text().await.unwrap_or_default());
+            }
+        }
+        SkillCommands::Upgrade { path } => {
+            let bundle = SkillManifest::load(std::path::Path::new(&path))?;
+            let response = client
+                .put(&format!("{}/api/v1/skills/{}", server, bundle.name))
+                .json(&bundle)
+                .send()
+                .await?;
+
+            if response.status().is_success() {
+                let skill: serde_json::Value = response.json().await?;
+                println!("✅ Skill '{}' upgraded to {}", bundle.name, bundle.version);
+                println!("{}", serde_json::to_string_pretty(&skill)?);
+            } else {
+                let status = response.status();
+                println!("❌ Failed to upgrade skill: {} {}", status, response.text().await.unwrap_or_default());
+            }
+        }
+        SkillCommands::Uninstall { name, purge } => {
+            let response = client
+                .delete(&format!("{}/api/v1/skills/{}?purge={}", server, name, purge))
+                .send()
+                .await?;
+
+            if response.status().is_success() {
+                println!("✅ Skill '{}' uninstalled", name);
+            } else {
+                let status = response.status();
+                println!("❌ Failed to uninstall skill: {} {}", status, response.text().await.unwrap_or_default());
+            }
+        }
+        SkillCommands::List => {
+            let response = client.get(&format!("{}/api/v1/skills", server)).send().await?;
+
+            if response.status().is_success() {
+                let skills: serde_json::Value = response.json().await?;
+                println!("🧩 Skills:");
+                println!("{}", serde_json::to_string_pretty(&skills)?);
+            } else {
+                println!("❌ Failed to list skills: {}", response.status());
+            }
+        }
+        SkillCommands::History { name } => {
+            let response = client
+                .get(&format!("{}/api/v1/skills/{}/history", server, name))
+                .send()
+                .await?;
+
+            if response.status().is_success() {
+                let history: serde_json::Value = response.json().await?;
+                println!("📜 Skill history:");
+                println!("{}", serde_json::to_string_pretty(&history)?);
+            } else {
+                println!("❌ Failed to get skill history: {}", response.status());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle maintenance job commands
+async fn handle_job_command(server: &str, cmd: JobCommands) -> anyhow::Result<()> {
+    let client = reqwest::Client::new();
+
+    match cmd {
+        JobCommands::List => {
+            let response = client.get(&format!("{}/api/v1/admin/jobs", server)).send().await?;
+
+            if response.status().is_success() {
+                let jobs: serde_json::Value = response.json().await?;
+                println!("🗓️  Jobs:");
+                println!("{}", serde_json::to_string_pretty(&jobs)?);
+            } else {
+                println!("❌ Failed to list jobs: {}", response.status());
+            }
+        }
+        JobCommands::Status { name, limit } => {
+            let response = client
+                .get(&format!("{}/api/v1/admin/jobs/{}?limit={}", server, name, limit))
+                .send()
+                .await?;
+
+            if response.status().is_success() {
+                let job: serde_json::Value = response.json().await?;
+                println!("{}", serde_json::to_string_pretty(&job)?);
+            } else {
+                println!("❌ Failed to get job '{}': {}", name, response.status());
+            }
+        }
+        JobCommands::Trigger { name } => {
+            let response = client
+                .post(&format!("{}/api/v1/admin/jobs/{}/trigger", server, name))
+                .send()
+                .await?;
+
+            if response.status().is_success() {
+                let run: serde_json::Value = response.json().await?;
+                println!("✅ Job '{}' started (run {})", name, run["run_id"].as_str().unwrap_or("?"));
+            } else {
+                let status = response.status();
+                println!("❌ Failed to trigger job: {} {}", status, response.text().await.unwrap_or_default());
+            }
+        }
+        JobCommands::Pause { name } => {
+            let response = client
+                .post(&format!("{}/api/v1/admin/jobs/{}/pause", server, name))
+                .send()
+                .await?;
+
+            if response.status().is_success() {
+                println!("⏸️  Job '{}' paused", name);
+            } else {
+                println!("❌ Failed to pause job: {}", response.status());
+            }
+        }
+        JobCommands::Resume { name } => {
+            let response = client
+                .post(&format!("{}/api/v1/admin/jobs/{}/resume", server, name))
+                .send()
+                .await?;
+
+            if response.status().is_success() {
+                let job: serde_json::Value = response.json().await?;
+                println!("▶️  Job '{}' resumed, next run at {}", name, job["next_run_at"]);
+            } else {
+                println!("❌ Failed to resume job: {}", response.status());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle ingest connector commands
+async fn handle_connector_command(server: &str, cmd: ConnectorCommands) -> anyhow::Result<()> {
+    let client = reqwest::Client::new();
+
+    match cmd {
+        ConnectorCommands::List => {
+            let response = client.get(&format!("{}/api/v1/connectors", server)).send().await?;
+
+            if response.status().is_success() {
+                let connectors: serde_json::Value = response.json().await?;
+                println!("📥 Connectors:");
+                println!("{}", serde_json::to_string_pretty(&connectors)?);
+            } else {
+                println!("❌ Failed to list connectors: {}", response.status());
+            }
+        }
+        ConnectorCommands::Create { file } => {
+            let content = std::fs::read_to_string(&file)?;
+            let spec: serde_json::Value = serde_json::from_str(&content)?;
+            let response = client
+                .post(&format!("{}/api/v1/connectors", server))
+                .json(&spec)
+                .send()
+                .await?;
+
+            if response.status().is_success() {
+                let connector: serde_json::Value = response.json().await?;
+                println!(
+                    "✅ Connector '{}' created ({} into {})",
+                    connector["name"].as_str().unwrap_or("?"),
+                    connector["source"]["type"].as_str().unwrap_or("?"),
+                    connector["table"].as_str().unwrap_or("?"),
+                );
+            } else {
+                let status = response.status();
+                println!("❌ Failed to create connector: {} {}", status, response.text().await.unwrap_or_default());
+            }
+        }
+        ConnectorCommands::Status { name } => {
+            let response = client.get(&format!("{}/api/v1/connectors/{}", server, name)).send().await?;
+
+            if response.status().is_success() {
+                let connector: serde_json::Value = response.json().await?;
+                println!("{}", serde_json::to_string_pretty(&connector)?);
+            } else {
+                println!("❌ Failed to get connector '{}': {}", name, response.status());
+            }
+        }
+        ConnectorCommands::Delete { name } => {
+            let response = client.delete(&format!("{}/api/v1/connectors/{}", server, name)).send().await?;
+
+            if response.status().is_success() {
+                println!("✅ Connector '{}' deleted", name);
+            } else {
+                println!("❌ Failed to delete connector: {}", response.status());
+            }
+        }
+        ConnectorCommands::Poll { name } => {
+            let response = client
+                .post(&format!("{}/api/v1/connectors/{}/poll", server, name))
+                .send()
+                .await?;
+
+            if response.status().is_success() {
+                let run: serde_json::Value = response.json().await?;
+                println!("✅ Connector '{}' polling (run {})", name, run["run_id"].as_str().unwrap_or("?"));
+            } else {
+                let status = response.status();
+                println!("❌ Failed to poll connector: {} {}", status, response.text().await.unwrap_or_default());
+            }
+        }
+        ConnectorCommands::Pause { name } => {
+            let response = client
+                .post(&format!("{}/api/v1/connectors/{}/pause", server, name))
+                .send()
+                .await?;
+
+            if response.status().is_success() {
+                println!("⏸️  Connector '{}' paused", name);
+            } else {
+                println!("❌ Failed to pause connector: {}", response.status());
+            }
+        }
+        ConnectorCommands::Resume { name } => {
+            let response = client
+                .post(&format!("{}/api/v1/connectors/{}/resume", server, name))
+                .send()
+                .await?;
+
+            if response.status().is_success() {
+                let connector: serde_json::Value = response.json().await?;
+                println!("▶️  Connector '{}' resumed, next poll at {}", name, connector["job"]["next_run_at"]);
+            } else {
+                println!("❌ Failed to resume connector: {}", response.status());
+            }
+        }
+        ConnectorCommands::Reset { name } => {
+            let response = client
+                .post(&format!("{}/api/v1/connectors/{}/reset", server, name))
+                .send()
+                .await?;
+
+            if response.status().is_success() {
+                println!("🔄 Connector '{}' reset; the next poll reads its source from the start", name);
+            } else {
+                println!("❌ Failed to reset connector: {}", response.status());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle CPL commands
+async fn handle_cpl_command(server: &str, cmd: CplCommands) -> anyhow::Result<()> {
+    let client = reqwest::Client::new();
+
+    match cmd {
+        CplCommands::List => {
+            let response = client.get(&format!("{}/api/v1/cpls", server)).send().await?;
+
+            if response.status().is_success() {
+                let body: serde_json::Value = response.json().await?;
+                let cpls = body["cpls"].as_array().cloned().unwrap_or_default();
+                println!("🔄 CPLs ({}):", cpls.len());
+                for cpl in cpls {
+                    println!(
+                        "  {} {:<20} {:<10} {} iterations{}",
+                        if cpl["healthy"].as_bool().unwrap_or(false) { "✅" } else { "⚠️ " },
+                        cpl["name"].as_str().unwrap_or(cpl["cpl_id"].as_str().unwrap_or("?")),
+                        cpl["health"]["state"].as_str().unwrap_or("?"),
+                        cpl["health"]["iterations"].as_u64().unwrap_or(0),
+                        cpl["health"]["exhausted"].as_str().map(|reason| format!(" ({})", reason)).unwrap_or_default(),
+                    );
+                    println!("     id: {}", cpl["cpl_id"].as_str().unwrap_or("?"));
+                }
+            } else {
+                println!("❌ Failed to list CPLs: {}", response.status());
+            }
+        }
+        CplCommands::Create { name, file } => {
+            let mut request = match file {
+                Some(file) => serde_json::from_str(&std::fs::read_to_string(&file)?)?,
+                None => serde_json::json!({}),
+            };
+            if let Some(name) = name {
+                request["name"] = serde_json::Value::String(name);
+            }
+            let response = client
+                .post(&format!("{}/api/v1/cpls", server))
+                .json(&request)
+                .send()
+                .await?;
+
+            if response.status().is_success() {
+                let created: serde_json::Value = response.json().await?;
+                println!("✅ CPL {} created", created["cpl_id"].as_str().unwrap_or("?"));
+            } else {
+                let status = response.status();
+                println!("❌ Failed to create CPL: {} {}", status, response.text().await.unwrap_or_default());
+            }
+        }
+        CplCommands::Status { id } => {
+            let response = client.get(&format!("{}/api/v1/cpls/{}", server, id)).send().await?;
+
+            if response.status().is_success() {
+                let cpl: serde_json::Value = response.json().await?;
+                println!("{}", serde_json::to_string_pretty(&cpl)?);
+            } else {
+                println!("❌ Failed to get CPL '{}': {}", id, response.status());
+            }
+        }
+        CplCommands::Start { id } => cpl_action(&client, server, &id, "start", &format!("▶️  CPL '{}' started", id)).await?,
+        CplCommands::Pause { id } => cpl_action(&client, server, &id, "pause", &format!("⏸️  CPL '{}' paused", id)).await?,
+        CplCommands::Resume { id } => cpl_action(&client, server, &id, "resume", &format!("▶️  CPL '{}' resumed", id)).await?,
+        CplCommands::Stop { id } => cpl_action(&client, server, &id, "stop", &format!("⏹️  CPL '{}' stopped", id)).await?,
+        CplCommands::Destroy { id } => {
+            let response = client.delete(&format!("{}/api/v1/cpls/{}", server, id)).send().await?;
+
+            if response.status().is_success() {
+                println!("🗑️  CPL '{}' destroyed", id);
+            } else {
+                let status = response.status();
+                println!("❌ Failed to destroy CPL: {} {}", status, response.text().await.unwrap_or_default());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// POST a lifecycle action (start, pause, ...) to a CPL
+async fn cpl_action(client: &reqwest::Client, server: &str, id: &str, action: &str, done: &str) -> anyhow::Result<()> {
+    let response = client
+        .post(&format!("{}/api/v1/cpls/{}/{}", server, id, action))
+        .send()
+        .await?;
+
+    if response.status().is_success() {
+        println!("{}", done);
+    } else {
+        let status = response.status();
+        println!("❌ Failed to {} CPL: {} {}", action, status, response.text().await.unwrap_or_default());
+    }
+    Ok(())
+}
+
 /// Handle backup commands
 async fn handle_backup_command(cmd: BackupCommands) -> anyhow::Result<()> {
     match cmd {
@@ -1078,5 +1724,28 @@ fn show_help() {
     println!("Query:");
     println!("  narayana query \"SELECT * FROM users\"");
     println!();
+    println!("Skills:");
+    println!("  narayana skill install ./pick-and-place   # Install a skill package");
+    println!("  narayana skill upgrade ./pick-and-place   # Upgrade to a newer version");
+    println!("  narayana skill uninstall pick-and-place --purge");
+    println!("  narayana skill list              # List installed skills");
+    println!();
+    println!("Maintenance Jobs:");
+    println!("  narayana job list                # Schedules, pause state and last runs");
+    println!("  narayana job trigger backup      # Run a job now");
+    println!("  narayana job pause compaction    # Stop running a job on its schedule");
+    println!("  narayana job status backup       # Recent runs");
+    println!();
+    println!("Ingest Connectors:");
+    println!("  narayana connector create weather.json   # HTTP, MQTT, RSS or S3 source into a table");
+    println!("  narayana connector list          # Cursors, totals and last polls");
+    println!("  narayana connector poll weather  # Poll now");
+    println!("  narayana connector reset weather # Read the source from the start");
+    println!();
+    println!("Conscience Persistent Loops:");
+    println!("  narayana cpl create --name rover --file rover.json   # Config, budget and genome");
+    println!("  narayana cpl list                # State, iterations and health");
+    println!("  narayana cpl pause <id>          # Pause / resume / stop / destroy");
+    println!();
     println!("For more information, see: https://github.com/carlosbarbosa/narayana");
 }
@@ -19,6 +19,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         enable_load_balancing: true,
         max_action_queue_size: 1000,
         enable_emergency_stop: true,
+        emergency_stop_timeout_ms: 200,
         action_timeout_ms: 5000,
     };
     
@@ -0,0 +1,843 @@
+//! Declarative behavior trees
+//!
+//! Trees are defined in JSON or YAML: composites (sequence, selector),
+//! decorators (inverter, succeeder, repeat, retry, timeout) and leaves that
+//! invoke CNS capabilities or check world events. Each running tree is ticked
+//! on its own interval; node memory is persisted so a restart resumes where
+//! the tree left off, and snapshots expose per-node status for introspection.
+
+use crate::capability::Capability;
+use crate::error::CnsError;
+use crate::estop::EmergencyStop;
+use crate::registry::ComponentRegistry;
+use crate::transport::TransportDispatcher;
+use async_trait::async_trait;
+use futures::FutureExt;
+#[cfg(feature = "wld-integration")]
+use narayana_wld::event_transformer::WorldEvent;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value as JsonValue};
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+#[cfg(feature = "wld-integration")]
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+/// Deepest allowed tree
+const MAX_DEPTH: usize = 32;
+/// Most nodes in one tree
+const MAX_NODES: usize = 1024;
+/// World events kept for conditions
+const MAX_EVENTS: usize = 256;
+
+/// Behavior tree node definition
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NodeDef {
+    /// Run children in order until one fails
+    Sequence { children: Vec<NodeDef> },
+    /// Run children in order until one succeeds
+    Selector { children: Vec<NodeDef> },
+    /// Swap success and failure
+    Inverter { child: Box<NodeDef> },
+    /// Succeed whenever the child completes
+    Succeeder { child: Box<NodeDef> },
+    /// Run the child `times` times (forever if unset), failing on its first failure
+    Repeat {
+        child: Box<NodeDef>,
+        #[serde(default)]
+        times: Option<u32>,
+    },
+    /// Re-run a failing child up to `attempts` times
+    Retry { child: Box<NodeDef>, attempts: u32 },
+    /// Fail if the child runs longer than `timeout_ms`
+    Timeout { child: Box<NodeDef>, timeout_ms: u64 },
+    /// Invoke a CNS capability
+    Action {
+        capability: String,
+        #[serde(default)]
+        params: JsonValue,
+        #[serde(default)]
+        timeout_ms: Option<u64>,
+    },
+    /// Succeed if a matching world event arrived within `within_ms` (ever, if unset)
+    Condition {
+        event: String,
+        #[serde(default)]
+        within_ms: Option<u64>,
+        /// Fields the event payload must contain, with these values
+        #[serde(default)]
+        matches: Option<JsonValue>,
+    },
+    /// Wait for a matching world event to arrive
+    WaitEvent {
+        event: String,
+        #[serde(default)]
+        matches: Option<JsonValue>,
+        #[serde(default)]
+        timeout_ms: Option<u64>,
+    },
+    /// Succeed after `duration_ms`
+    Wait { duration_ms: u64 },
+}
+
+impl NodeDef {
+    fn children(&self) -> Vec<&NodeDef> {
+        match self {
+            NodeDef::Sequence { children } | NodeDef::Selector { children } => children.iter().collect(),
+            NodeDef::Inverter { child }
+            | NodeDef::Succeeder { child }
+            | NodeDef::Repeat { child, .. }
+            | NodeDef::Retry { child, .. }
+            | NodeDef::Timeout { child, .. } => vec![child.as_ref()],
+            _ => Vec::new(),
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            NodeDef::Sequence { .. } => "sequence",
+            NodeDef::Selector { .. } => "selector",
+            NodeDef::Inverter { .. } => "inverter",
+            NodeDef::Succeeder { .. } => "succeeder",
+            NodeDef::Repeat { .. } => "repeat",
+            NodeDef::Retry { .. } => "retry",
+            NodeDef::Timeout { .. } => "timeout",
+            NodeDef::Action { .. } => "action",
+            NodeDef::Condition { .. } => "condition",
+            NodeDef::WaitEvent { .. } => "wait_event",
+            NodeDef::Wait { .. } => "wait",
+        }
+    }
+
+    /// What the node refers to, for introspection
+    fn label(&self) -> Option<String> {
+        match self {
+            NodeDef::Action { capability, .. } => Some(capability.clone()),
+            NodeDef::Condition { event, .. } | NodeDef::WaitEvent { event, .. } => Some(event.clone()),
+            _ => None,
+        }
+    }
+
+    fn validate(&self, depth: usize, count: &mut usize) -> Result<(), String> {
+        *count += 1;
+        if depth > MAX_DEPTH {
+            return Err(format!("Behavior tree is deeper than {} levels", MAX_DEPTH));
+        }
+        if *count > MAX_NODES {
+            return Err(format!("Behavior tree has more than {} nodes", MAX_NODES));
+        }
+        match self {
+            NodeDef::Sequence { children } | NodeDef::Selector { children } if children.is_empty() => {
+                return Err(format!("{} node needs at least one child", self.kind()));
+            }
+            NodeDef::Retry { attempts: 0, .. } => return Err("retry node needs at least one attempt".to_string()),
+            NodeDef::Repeat { times: Some(0), .. } => return Err("repeat node needs at least one run".to_string()),
+            NodeDef::Timeout { timeout_ms: 0, .. } => return Err("timeout node needs a timeout".to_string()),
+            NodeDef::Action { capability, .. } if capability.is_empty() => {
+                return Err("action node needs a capability".to_string());
+            }
+            NodeDef::Condition { event, .. } | NodeDef::WaitEvent { event, .. } if event.is_empty() => {
+                return Err(format!("{} node needs an event", self.kind()));
+            }
+            _ => {}
+        }
+        self.children().into_iter().try_for_each(|child| child.validate(depth + 1, count))
+    }
+}
+
+/// A named behavior tree
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BehaviorTreeDef {
+    pub name: String,
+    /// Milliseconds between ticks
+    #[serde(default = "default_tick_interval_ms")]
+    pub tick_interval_ms: u64,
+    pub root: NodeDef,
+}
+
+fn default_tick_interval_ms() -> u64 {
+    100
+}
+
+impl BehaviorTreeDef {
+    pub fn from_json(text: &str) -> Result<Self, CnsError> {
+        let def: Self = serde_json::from_str(text)
+            .map_err(|e| CnsError::Validation(format!("Invalid behavior tree JSON: {}", e)))?;
+        def.validate().map_err(CnsError::Validation)?;
+        Ok(def)
+    }
+
+    pub fn from_yaml(text: &str) -> Result<Self, CnsError> {
+        let def: Self = serde_yaml::from_str(text)
+            .map_err(|e| CnsError::Validation(format!("Invalid behavior tree YAML: {}", e)))?;
+        def.validate().map_err(CnsError::Validation)?;
+        Ok(def)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.is_empty() || self.name.len() > 128 {
+            return Err("Behavior tree name must be 1-128 characters".to_string());
+        }
+        // Names become file names
+        if !self.name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-')) {
+            return Err(format!("Invalid behavior tree name: {}", self.name));
+        }
+        if self.tick_interval_ms == 0 {
+            return Err("Tick interval must be greater than 0".to_string());
+        }
+        self.root.validate(1, &mut 0)
+    }
+}
+
+/// Result of ticking a node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NodeStatus {
+    Success,
+    Failure,
+    Running,
+}
+
+/// Lifecycle of a tree
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TreeStatus {
+    Idle,
+    Running,
+    Succeeded,
+    Failed,
+    Stopped,
+}
+
+/// Node memory, persisted between ticks and restarts
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NodeState {
+    pub status: Option<NodeStatus>,
+    /// Current child of a composite
+    pub child: usize,
+    /// Runs or attempts of a decorator
+    pub count: u32,
+    /// Wall-clock start of a waiting or timed node
+    pub started_at_ms: Option<u64>,
+}
+
+/// Invokes capabilities for action nodes
+#[async_trait]
+pub trait CapabilityExecutor: Send + Sync {
+    async fn execute(&self, capability: &str, params: &JsonValue) -> Result<JsonValue, CnsError>;
+}
+
+/// Runs capabilities on the first available component that has them,
+/// over its transport
+pub struct CnsCapabilityExecutor {
+    registry: Arc<ComponentRegistry>,
+    estop: Arc<EmergencyStop>,
+    dispatcher: Arc<TransportDispatcher>,
+}
+
+impl CnsCapabilityExecutor {
+    pub fn new(registry: Arc<ComponentRegistry>, estop: Arc<EmergencyStop>, dispatcher: Arc<TransportDispatcher>) -> Self {
+        Self { registry, estop, dispatcher }
+    }
+}
+
+#[async_trait]
+impl CapabilityExecutor for CnsCapabilityExecutor {
+    async fn execute(&self, capability: &str, params: &JsonValue) -> Result<JsonValue, CnsError> {
+        let component = self.registry.find_by_capability(&Capability::Simple(capability.to_string()))
+            .into_iter()
+            .next()
+            .ok_or_else(|| CnsError::Routing(format!("No component found for capability '{}'", capability)))?;
+        self.estop.check_action(component.id.as_str())?;
+        let mut command = json!({ "command": capability });
+        if let (Some(command), Some(params)) = (command.as_object_mut(), params.as_object()) {
+            for (key, value) in params {
+                command.entry(key.clone()).or_insert_with(|| value.clone());
+            }
+        }
+        self.dispatcher.send_json(&component, &command).await?;
+        Ok(json!({ "component_id": component.id.as_str() }))
+    }
+}
+
+/// A world event as seen by conditions
+#[derive(Debug, Clone)]
+struct RecordedEvent {
+    name: String,
+    payload: JsonValue,
+    at_ms: u64,
+}
+
+/// Recent world events, shared by all trees
+#[derive(Default)]
+struct EventBuffer {
+    events: RwLock<VecDeque<RecordedEvent>>,
+}
+
+impl EventBuffer {
+    fn push(&self, name: &str, payload: JsonValue) {
+        let mut events = self.events.write();
+        events.push_back(RecordedEvent { name: name.to_string(), payload, at_ms: now_ms() });
+        if events.len() > MAX_EVENTS {
+            events.pop_front();
+        }
+    }
+
+    /// Whether a matching event arrived at or after `since_ms`
+    fn seen(&self, name: &str, matches: Option<&JsonValue>, since_ms: u64) -> bool {
+        self.events.read().iter().rev()
+            .take_while(|event| event.at_ms >= since_ms)
+            .any(|event| event.name == name && matches.is_none_or(|expected| contains(&event.payload, expected)))
+    }
+}
+
+/// Whether `value` has every field of `expected` with the same value
+fn contains(value: &JsonValue, expected: &JsonValue) -> bool {
+    match (value, expected) {
+        (JsonValue::Object(value), JsonValue::Object(expected)) => {
+            expected.iter().all(|(key, e)| value.get(key).is_some_and(|v| contains(v, e)))
+        }
+        _ => value == expected,
+    }
+}
+
+/// Per-node status, for introspection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeSnapshot {
+    pub id: usize,
+    pub kind: String,
+    pub label: Option<String>,
+    pub status: Option<NodeStatus>,
+    pub ticks: u64,
+    pub children: Vec<NodeSnapshot>,
+}
+
+/// A tree's definition and live state, for introspection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TreeSnapshot {
+    pub name: String,
+    pub status: TreeStatus,
+    pub ticks: u64,
+    pub last_tick_ms: Option<u64>,
+    pub root: NodeSnapshot,
+}
+
+/// What is written to disk for each tree
+#[derive(Serialize, Deserialize)]
+struct PersistedTree {
+    definition: BehaviorTreeDef,
+    /// Definitions installed without state load as idle
+    #[serde(default = "idle")]
+    status: TreeStatus,
+    #[serde(default)]
+    nodes: Vec<NodeState>,
+}
+
+fn idle() -> TreeStatus {
+    TreeStatus::Idle
+}
+
+/// A tree being executed
+struct TreeRuntime {
+    def: BehaviorTreeDef,
+    /// Nodes in pre-order, with the indices of their children
+    nodes: Vec<(NodeDef, Vec<usize>)>,
+    states: Vec<NodeState>,
+    node_ticks: Vec<u64>,
+    status: TreeStatus,
+    ticks: u64,
+    last_tick_ms: Option<u64>,
+    /// In-flight capability invocations by node
+    actions: HashMap<usize, JoinHandle<Result<JsonValue, CnsError>>>,
+    executor: Arc<dyn CapabilityExecutor>,
+    events: Arc<EventBuffer>,
+    /// State as last written to disk
+    persisted: Option<(TreeStatus, Vec<NodeState>)>,
+}
+
+impl TreeRuntime {
+    fn new(def: BehaviorTreeDef, executor: Arc<dyn CapabilityExecutor>, events: Arc<EventBuffer>) -> Self {
+        let mut nodes = Vec::new();
+        flatten(&def.root, &mut nodes);
+        let count = nodes.len();
+        Self {
+            def,
+            nodes,
+            states: vec![NodeState::default(); count],
+            node_ticks: vec![0; count],
+            status: TreeStatus::Idle,
+            ticks: 0,
+            last_tick_ms: None,
+            actions: HashMap::new(),
+            executor,
+            events,
+            persisted: None,
+        }
+    }
+
+    fn tick(&mut self) -> NodeStatus {
+        let now = now_ms();
+        self.ticks += 1;
+        self.last_tick_ms = Some(now);
+        let status = self.tick_node(0, now);
+        self.status = match status {
+            NodeStatus::Running => TreeStatus::Running,
+            NodeStatus::Success => TreeStatus::Succeeded,
+            NodeStatus::Failure => TreeStatus::Failed,
+        };
+        if status != NodeStatus::Running {
+            self.reset(0);
+        }
+        status
+    }
+
+    fn tick_node(&mut self, id: usize, now: u64) -> NodeStatus {
+        self.node_ticks[id] += 1;
+        let children = self.nodes[id].1.clone();
+        let status = match self.nodes[id].0.clone() {
+            NodeDef::Sequence { .. } => self.tick_composite(id, &children, now, NodeStatus::Success),
+            NodeDef::Selector { .. } => self.tick_composite(id, &children, now, NodeStatus::Failure),
+            NodeDef::Inverter { .. } => match self.tick_node(children[0], now) {
+                NodeStatus::Success => NodeStatus::Failure,
+                NodeStatus::Failure => NodeStatus::Success,
+                NodeStatus::Running => NodeStatus::Running,
+            },
+            NodeDef::Succeeder { .. } => match self.tick_node(children[0], now) {
+                NodeStatus::Running => NodeStatus::Running,
+                _ => NodeStatus::Success,
+            },
+            NodeDef::Repeat { times, .. } => match self.tick_node(children[0], now) {
+                NodeStatus::Running => NodeStatus::Running,
+                NodeStatus::Failure => NodeStatus::Failure,
+                NodeStatus::Success => {
+                    self.states[id].count += 1;
+                    self.reset(children[0]);
+                    // One run per tick, so an instant child cannot spin
+                    if times.is_some_and(|times| self.states[id].count >= times) {
+                        NodeStatus::Success
+                    } else {
+                        NodeStatus::Running
+                    }
+                }
+            },
+            NodeDef::Retry { attempts, .. } => match self.tick_node(children[0], now) {
+                NodeStatus::Failure => {
+                    self.states[id].count += 1;
+                    self.reset(children[0]);
+                    if self.states[id].count >= attempts {
+                        NodeStatus::Failure
+                    } else {
+                        NodeStatus::Running
+                    }
+                }
+                status => status,
+            },
+            NodeDef::Timeout { timeout_ms, .. } => {
+                let started = *self.states[id].started_at_ms.get_or_insert(now);
+                if now.saturating_sub(started) >= timeout_ms {
+                    NodeStatus::Failure
+                } else {
+                    self.tick_node(children[0], now)
+                }
+            }
+            NodeDef::Action { capability, params, timeout_ms } => self.tick_action(id, &capability, &params, timeout_ms, now),
+            NodeDef::Condition { event, within_ms, matches } => {
+                let since = within_ms.map(|within| now.saturating_sub(within)).unwrap_or(0);
+                if self.events.seen(&event, matches.as_ref(), since) {
+                    NodeStatus::Success
+                } else {
+                    NodeStatus::Failure
+                }
+            }
+            NodeDef::WaitEvent { event, matches, timeout_ms } => {
+                let started = *self.states[id].started_at_ms.get_or_insert(now);
+                if self.events.seen(&event, matches.as_ref(), started) {
+                    NodeStatus::Success
+                } else if timeout_ms.is_some_and(|timeout| now.saturating_sub(started) >= timeout) {
+                    NodeStatus::Failure
+                } else {
+                    NodeStatus::Running
+                }
+            }
+            NodeDef::Wait { duration_ms } => {
+                let started = *self.states[id].started_at_ms.get_or_insert(now);
+                if now.saturating_sub(started) >= duration_ms {
+                    NodeStatus::Success
+                } else {
+                    NodeStatus::Running
+                }
+            }
+        };
+
+        if status == NodeStatus::Running {
+            self.states[id].status = Some(status);
+        } else {
+            // Completed nodes start fresh next time; keep the outcome for introspection
+            self.reset(id);
+            self.states[id].status = Some(status);
+        }
+        status
+    }
+
+    /// Sequence (stop on failure) or selector (stop on success), resuming
+    /// from the child that was running
+    fn tick_composite(&mut self, id: usize, children: &[usize], now: u64, continue_on: NodeStatus) -> NodeStatus {
+        while self.states[id].child < children.len() {
+            let status = self.tick_node(children[self.states[id].child], now);
+            if status != continue_on {
+                return status;
+            }
+            self.states[id].child += 1;
+        }
+        continue_on
+    }
+
+    fn tick_action(&mut self, id: usize, capability: &str, params: &JsonValue, timeout_ms: Option<u64>, now: u64) -> NodeStatus {
+        let started = *self.states[id].started_at_ms.get_or_insert(now);
+        if timeout_ms.is_some_and(|timeout| now.saturating_sub(started) >= timeout) {
+            warn!("Behavior '{}': capability '{}' timed out", self.def.name, capability);
+            return NodeStatus::Failure;
+        }
+        // Also re-issues actions that were in flight when state was persisted
+        let handle = self.actions.entry(id).or_insert_with(|| {
+            let executor = self.executor.clone();
+            let capability = capability.to_string();
+            let params = params.clone();
+            tokio::spawn(async move { executor.execute(&capability, &params).await })
+        });
+        if !handle.is_finished() {
+            return NodeStatus::Running;
+        }
+        let result = self.actions.remove(&id).and_then(|handle| handle.now_or_never());
+        match result {
+            Some(Ok(Ok(_))) => NodeStatus::Success,
+            Some(Ok(Err(e))) => {
+                warn!("Behavior '{}': capability '{}' failed: {}", self.def.name, capability, e);
+                NodeStatus::Failure
+            }
+            _ => NodeStatus::Failure,
+        }
+    }
+
+    /// Clear the memory of a subtree and abort its actions
+    fn reset(&mut self, id: usize) {
+        let status = self.states[id].status;
+        self.states[id] = NodeState { status, ..NodeState::default() };
+        if let Some(handle) = self.actions.remove(&id) {
+            handle.abort();
+        }
+        for child in self.nodes[id].1.clone() {
+            self.reset(child);
+        }
+    }
+
+    fn halt(&mut self) {
+        for (_, handle) in self.actions.drain() {
+            handle.abort();
+        }
+        self.reset(0);
+    }
+
+    fn snapshot(&self) -> TreeSnapshot {
+        TreeSnapshot {
+            name: self.def.name.clone(),
+            status: self.status,
+            ticks: self.ticks,
+            last_tick_ms: self.last_tick_ms,
+            root: self.node_snapshot(0),
+        }
+    }
+
+    fn node_snapshot(&self, id: usize) -> NodeSnapshot {
+        let (def, children) = &self.nodes[id];
+        NodeSnapshot {
+            id,
+            kind: def.kind().to_string(),
+            label: def.label(),
+            status: self.states[id].status,
+            ticks: self.node_ticks[id],
+            children: children.iter().map(|&child| self.node_snapshot(child)).collect(),
+        }
+    }
+
+    /// Write the tree if its state changed since the last write
+    fn persist(&mut self, root: Option<&Path>) {
+        let Some(root) = root else { return };
+        if self.persisted.as_ref().is_some_and(|(status, states)| *status == self.status && *states == self.states) {
+            return;
+        }
+        let record = PersistedTree {
+            definition: self.def.clone(),
+            status: self.status,
+            nodes: self.states.clone(),
+        };
+        let path = root.join(format!("{}.json", self.def.name));
+        let written = serde_json::to_vec_pretty(&record)
+            .map_err(std::io::Error::other)
+            .and_then(|bytes| write_atomic(&path, &bytes));
+        match written {
+            Ok(()) => self.persisted = Some((self.status, self.states.clone())),
+            Err(e) => warn!("Failed to persist behavior tree {:?}: {}", path, e),
+        }
+    }
+}
+
+/// Flatten a tree into pre-order, returning the node's index
+fn flatten(def: &NodeDef, nodes: &mut Vec<(NodeDef, Vec<usize>)>) -> usize {
+    let id = nodes.len();
+    nodes.push((def.clone(), Vec::new()));
+    let children = def.children().into_iter().map(|child| flatten(child, nodes)).collect();
+    nodes[id].1 = children;
+    id
+}
+
+/// Loads, runs and inspects behavior trees
+pub struct BehaviorEngine {
+    executor: Arc<dyn CapabilityExecutor>,
+    /// Directory trees are persisted in, one `<name>.json` each
+    root: Option<PathBuf>,
+    trees: RwLock<HashMap<String, Arc<Mutex<TreeRuntime>>>>,
+    tickers: Mutex<HashMap<String, JoinHandle<()>>>,
+    events: Arc<EventBuffer>,
+}
+
+impl BehaviorEngine {
+    pub fn new(executor: Arc<dyn CapabilityExecutor>, root: Option<PathBuf>) -> Result<Self, CnsError> {
+        if let Some(root) = &root {
+            std::fs::create_dir_all(root)
+                .map_err(|e| CnsError::Config(format!("Failed to create behavior directory: {}", e)))?;
+        }
+        Ok(Self {
+            executor,
+            root,
+            trees: RwLock::new(HashMap::new()),
+            tickers: Mutex::new(HashMap::new()),
+            events: Arc::new(EventBuffer::default()),
+        })
+    }
+
+    /// Load trees persisted by a previous run, resuming the ones that were running
+    pub fn load_persisted(&self) -> Result<usize, CnsError> {
+        let Some(root) = &self.root else { return Ok(0) };
+        let entries = std::fs::read_dir(root)
+            .map_err(|e| CnsError::Config(format!("Failed to read behavior directory: {}", e)))?;
+        let mut loaded = 0;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let parsed = std::fs::read(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|bytes| serde_json::from_slice::<PersistedTree>(&bytes).map_err(|e| e.to_string()))
+                .and_then(|record| record.definition.validate().map(|_| record));
+            let record = match parsed {
+                Ok(record) => record,
+                Err(e) => {
+                    warn!("Skipping corrupt behavior tree {:?}: {}", path, e);
+                    continue;
+                }
+            };
+            let name = record.definition.name.clone();
+            let mut runtime = TreeRuntime::new(record.definition, self.executor.clone(), self.events.clone());
+            if record.nodes.len() == runtime.states.len() {
+                runtime.states = record.nodes;
+                runtime.status = record.status;
+            }
+            runtime.persisted = Some((runtime.status, runtime.states.clone()));
+            let resume = runtime.status == TreeStatus::Running;
+            self.trees.write().insert(name.clone(), Arc::new(Mutex::new(runtime)));
+            if resume {
+                self.start(&name)?;
+            }
+            loaded += 1;
+        }
+        info!("Loaded {} behavior trees", loaded);
+        Ok(loaded)
+    }
+
+    /// Add a tree, replacing (and stopping) any tree with the same name
+    pub fn load(&self, def: BehaviorTreeDef) -> Result<(), CnsError> {
+        def.validate().map_err(CnsError::Validation)?;
+        let name = def.name.clone();
+        self.stop(&name).ok();
+        let mut runtime = TreeRuntime::new(def, self.executor.clone(), self.events.clone());
+        runtime.persist(self.root.as_deref());
+        self.trees.write().insert(name.clone(), Arc::new(Mutex::new(runtime)));
+        info!("Loaded behavior tree '{}'", name);
+        Ok(())
+    }
+
+    pub fn load_json(&self, text: &str) -> Result<(), CnsError> {
+        self.load(BehaviorTreeDef::from_json(text)?)
+    }
+
+    pub fn load_yaml(&self, text: &str) -> Result<(), CnsError> {
+        self.load(BehaviorTreeDef::from_yaml(text)?)
+    }
+
+    /// Stop and forget a tree
+    pub fn remove(&self, name: &str) -> Result<bool, CnsError> {
+        self.stop(name).ok();
+        let removed = self.trees.write().remove(name).is_some();
+        if let Some(root) = &self.root {
+            remove_definition(root, name)?;
+        }
+        Ok(removed)
+    }
+
+    /// Tick a tree on its interval until it completes or is stopped
+    pub fn start(&self, name: &str) -> Result<(), CnsError> {
+        let runtime = self.tree(name)?;
+        let mut tickers = self.tickers.lock();
+        if tickers.get(name).is_some_and(|ticker| !ticker.is_finished()) {
+            return Ok(());
+        }
+        let interval = Duration::from_millis(runtime.lock().def.tick_interval_ms);
+        let root = self.root.clone();
+        let tree_name = name.to_string();
+        let ticker = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(interval);
+            loop {
+                interval.tick().await;
+                let status = {
+                    let mut runtime = runtime.lock();
+                    let status = runtime.tick();
+                    runtime.persist(root.as_deref());
+                    status
+                };
+                if status != NodeStatus::Running {
+                    info!("Behavior tree '{}' finished: {:?}", tree_name, status);
+                    break;
+                }
+            }
+        });
+        tickers.insert(name.to_string(), ticker);
+        info!("Started behavior tree '{}'", name);
+        Ok(())
+    }
+
+    /// Stop ticking a tree, aborting its running actions
+    pub fn stop(&self, name: &str) -> Result<(), CnsError> {
+        let runtime = self.tree(name)?;
+        if let Some(ticker) = self.tickers.lock().remove(name) {
+            ticker.abort();
+        }
+        let mut runtime = runtime.lock();
+        if runtime.status == TreeStatus::Running {
+            runtime.halt();
+            runtime.status = TreeStatus::Stopped;
+            runtime.persist(self.root.as_deref());
+        }
+        Ok(())
+    }
+
+    /// Tick a tree once, outside its schedule
+    pub fn tick(&self, name: &str) -> Result<NodeStatus, CnsError> {
+        let runtime = self.tree(name)?;
+        let mut runtime = runtime.lock();
+        let status = runtime.tick();
+        runtime.persist(self.root.as_deref());
+        Ok(status)
+    }
+
+    /// Make a world event visible to conditions
+    pub fn notify_event(&self, name: &str, payload: JsonValue) {
+        self.events.push(name, payload);
+    }
+
+    /// Feed world events to conditions: sensor data by source, system
+    /// events by type, commands by name, and user input as `user_input`
+    #[cfg(feature = "wld-integration")]
+    pub fn follow_world_events(self: &Arc<Self>, mut receiver: broadcast::Receiver<WorldEvent>) -> JoinHandle<()> {
+        let engine = self.clone();
+        tokio::spawn(async move {
+            loop {
+                let event = match receiver.recv().await {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("Behavior engine skipped {} world events", skipped);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                let (name, payload) = match event {
+                    WorldEvent::SensorData { source, data, .. } => (source, data),
+                    WorldEvent::SystemEvent { event_type, payload } => (event_type, payload),
+                    WorldEvent::Command { command, args } => (command, args),
+                    WorldEvent::UserInput { user_id, input, .. } => {
+                        ("user_input".to_string(), json!({ "user_id": user_id, "input": input }))
+                    }
+                };
+                engine.notify_event(&name, payload);
+            }
+        })
+    }
+
+    pub fn definition(&self, name: &str) -> Option<BehaviorTreeDef> {
+        self.trees.read().get(name).map(|runtime| runtime.lock().def.clone())
+    }
+
+    /// Live status of every node of a tree
+    pub fn snapshot(&self, name: &str) -> Option<TreeSnapshot> {
+        self.trees.read().get(name).map(|runtime| runtime.lock().snapshot())
+    }
+
+    /// Names and statuses of all trees
+    pub fn list(&self) -> Vec<(String, TreeStatus)> {
+        let mut trees: Vec<(String, TreeStatus)> = self.trees.read().iter()
+            .map(|(name, runtime)| (name.clone(), runtime.lock().status))
+            .collect();
+        trees.sort_by(|a, b| a.0.cmp(&b.0));
+        trees
+    }
+
+    fn tree(&self, name: &str) -> Result<Arc<Mutex<TreeRuntime>>, CnsError> {
+        self.trees.read().get(name).cloned()
+            .ok_or_else(|| CnsError::Validation(format!("Behavior tree '{}' not found", name)))
+    }
+}
+
+impl Drop for BehaviorEngine {
+    fn drop(&mut self) {
+        for (_, ticker) in self.tickers.lock().drain() {
+            ticker.abort();
+        }
+    }
+}
+
+/// Install a definition into a behavior directory, replacing any persisted
+/// state; engines over `root` load it idle on their next `load_persisted`
+pub fn install_definition(root: &Path, def: &BehaviorTreeDef) -> Result<(), CnsError> {
+    def.validate().map_err(CnsError::Validation)?;
+    std::fs::create_dir_all(root)
+        .map_err(|e| CnsError::Config(format!("Failed to create behavior directory: {}", e)))?;
+    let bytes = serde_json::to_vec_pretty(&json!({ "definition": def }))
+        .map_err(|e| CnsError::Validation(e.to_string()))?;
+    write_atomic(&root.join(format!("{}.json", def.name)), &bytes)
+        .map_err(|e| CnsError::Config(format!("Failed to write behavior tree '{}': {}", def.name, e)))
+}
+
+/// Remove a tree from a behavior directory
+pub fn remove_definition(root: &Path, name: &str) -> Result<bool, CnsError> {
+    match std::fs::remove_file(root.join(format!("{}.json", name))) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(CnsError::Config(format!("Failed to remove behavior tree '{}': {}", name, e))),
+    }
+}
+
+fn write_atomic(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let temp_path = path.with_extension("tmp");
+    std::fs::write(&temp_path, bytes)?;
+    std::fs::rename(&temp_path, path).inspect_err(|_| {
+        let _ = std::fs::remove_file(&temp_path);
+    })
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64
+}
@@ -6,11 +6,19 @@ use crate::router::ActionRouter;
 use crate::safety::{SafetyValidator, SafetyLevel};
 use crate::config::CnsConfig;
 use crate::error::CnsError;
+use crate::estop::{EmergencyStop, EStopReport, SafeState};
+#[cfg(feature = "wld-integration")]
+use crate::estop::WorldStopChannel;
+use crate::update::{UpdateChannel, UpdateConfig, UpdateOrchestrator};
+use crate::behavior::{BehaviorEngine, CnsCapabilityExecutor};
+use crate::transport::TransportDispatcher;
 #[cfg(feature = "wld-integration")]
 use narayana_wld::event_transformer::{WorldAction, WorldEvent};
 #[cfg(feature = "wld-integration")]
 use narayana_wld::world_broker::WorldBrokerHandle;
+use narayana_storage::blob_store::BlobStore;
 use narayana_storage::conscience_persistent_loop::CPLEvent;
+use std::path::PathBuf;
 use std::sync::Arc;
 use parking_lot::RwLock;
 use tokio::sync::broadcast;
@@ -23,10 +31,13 @@ pub struct CentralNervousSystem {
     registry: Arc<ComponentRegistry>,
     router: Arc<ActionRouter>,
     safety_validator: Arc<RwLock<SafetyValidator>>,
+    estop: Arc<EmergencyStop>,
     #[cfg(feature = "wld-integration")]
     action_sender: broadcast::Sender<WorldAction>,
     is_running: Arc<RwLock<bool>>,
     health_check_handle: Arc<RwLock<Option<tokio::task::JoinHandle<()>>>>,
+    updates: Arc<RwLock<Option<Arc<UpdateOrchestrator>>>>,
+    behaviors: Arc<RwLock<Option<Arc<BehaviorEngine>>>>,
 }
 
 impl CentralNervousSystem {
@@ -45,20 +56,25 @@ impl CentralNervousSystem {
             config.default_safety_level,
         )));
         
+        let estop = Arc::new(EmergencyStop::new(registry.clone(), config.emergency_stop_timeout_ms));
+        
         #[cfg(feature = "wld-integration")]
         let (action_sender, _) = broadcast::channel(config.max_action_queue_size);
-        #[cfg(not(feature = "wld-integration"))]
-        let _action_sender: broadcast::Sender<()> = broadcast::channel(0).0; // Placeholder
+        #[cfg(feature = "wld-integration")]
+        estop.add_channel(Arc::new(WorldStopChannel::new(action_sender.clone())));
         
         Ok(Self {
             config: Arc::new(config),
             registry,
             router,
             safety_validator,
+            estop,
             #[cfg(feature = "wld-integration")]
             action_sender,
             is_running: Arc::new(RwLock::new(false)),
             health_check_handle: Arc::new(RwLock::new(None)),
+            updates: Arc::new(RwLock::new(None)),
+            behaviors: Arc::new(RwLock::new(None)),
         })
     }
     
@@ -105,6 +121,7 @@ impl CentralNervousSystem {
         let mut action_receiver = broker_handle.subscribe_actions();
         let router = self.router.clone();
         let safety_validator = self.safety_validator.clone();
+        let estop = self.estop.clone();
         let action_sender = self.action_sender.clone();
         let registry = self.registry.clone();
         let is_running = self.is_running.clone();
@@ -120,6 +137,7 @@ impl CentralNervousSystem {
                                     &action,
                                     &router,
                                     &safety_validator,
+                                    &estop,
                                     &registry,
                                     &action_sender,
                                 ).await {
@@ -170,6 +188,7 @@ impl CentralNervousSystem {
         action: &WorldAction,
         router: &Arc<ActionRouter>,
         safety_validator: &Arc<RwLock<SafetyValidator>>,
+        estop: &Arc<EmergencyStop>,
         registry: &Arc<ComponentRegistry>,
         action_sender: &broadcast::Sender<WorldAction>,
     ) -> Result<(), CnsError> {
@@ -184,6 +203,21 @@ impl CentralNervousSystem {
             }
         };
         
+        // An e-stop request is honoured whatever it targets
+        if ActionRouter::extract_capability_from_command(command)
+            .is_some_and(|c| c.name() == "emergency_stop")
+        {
+            let reason = command.get("reason")
+                .and_then(|v| v.as_str())
+                .unwrap_or("Requested by world action");
+            safety_validator.write().trigger_emergency_stop();
+            estop.engage("world_action", reason).await;
+            return Ok(());
+        }
+        
+        // Nothing moves while the e-stop is engaged
+        estop.check_action(target)?;
+        
         // Find target component
         let component = if !target.is_empty() {
             registry.get_by_name(target)
@@ -223,8 +257,8 @@ impl CentralNervousSystem {
         if !validation.is_safe {
             error!("Action failed safety validation: {:?}", validation.reasons);
             if validation.emergency_stop {
-                let mut validator = safety_validator.write();
-                validator.trigger_emergency_stop();
+                safety_validator.write().trigger_emergency_stop();
+                estop.engage("safety_validator", &validation.reasons.join(", ")).await;
             }
             return Err(CnsError::Safety(format!(
                 "Action unsafe: {}",
@@ -273,6 +307,32 @@ impl CentralNervousSystem {
         self.registry.find_by_capability(capability)
     }
     
+    /// Stop every motor-capable component and reject actions until cleared
+    pub async fn emergency_stop(&self, actor: &str, reason: &str) -> Result<EStopReport, CnsError> {
+        if !self.config.enable_emergency_stop {
+            return Err(CnsError::Config("Emergency stop is disabled".to_string()));
+        }
+        self.safety_validator.write().trigger_emergency_stop();
+        Ok(self.estop.engage(actor, reason).await)
+    }
+    
+    /// Leave the safe state after an emergency stop
+    pub fn clear_emergency_stop(&self, actor: &str, reason: &str) -> Result<(), CnsError> {
+        self.estop.clear(actor, reason)?;
+        self.safety_validator.write().clear_emergency_stop();
+        Ok(())
+    }
+    
+    /// Get safety state
+    pub fn safe_state(&self) -> SafeState {
+        self.estop.state()
+    }
+    
+    /// Get emergency stop controller (channels, audit log)
+    pub fn estop(&self) -> &Arc<EmergencyStop> {
+        &self.estop
+    }
+    
     /// Get safety validator
     pub fn safety_validator(&self) -> &Arc<RwLock<SafetyValidator>> {
         &self.safety_validator
@@ -288,6 +348,44 @@ impl CentralNervousSystem {
         &self.router
     }
     
+    /// Enable firmware updates, storing images in `blobs` and delivering
+    /// them through `channel`
+    pub fn enable_updates(
+        &self,
+        config: UpdateConfig,
+        blobs: Arc<BlobStore>,
+        channel: Arc<dyn UpdateChannel>,
+    ) -> Result<Arc<UpdateOrchestrator>, CnsError> {
+        let orchestrator = Arc::new(UpdateOrchestrator::new(config, self.registry.clone(), blobs, channel)?);
+        *self.updates.write() = Some(orchestrator.clone());
+        Ok(orchestrator)
+    }
+    
+    /// Get update orchestrator, if updates are enabled
+    pub fn updates(&self) -> Option<Arc<UpdateOrchestrator>> {
+        self.updates.read().clone()
+    }
+    
+    /// Enable behavior trees, running their actions on components over
+    /// `dispatcher` and persisting trees under `root`. Trees that were
+    /// running when last persisted are resumed.
+    pub fn enable_behaviors(
+        &self,
+        dispatcher: Arc<TransportDispatcher>,
+        root: Option<PathBuf>,
+    ) -> Result<Arc<BehaviorEngine>, CnsError> {
+        let executor = Arc::new(CnsCapabilityExecutor::new(self.registry.clone(), self.estop.clone(), dispatcher));
+        let engine = Arc::new(BehaviorEngine::new(executor, root)?);
+        engine.load_persisted()?;
+        *self.behaviors.write() = Some(engine.clone());
+        Ok(engine)
+    }
+    
+    /// Get behavior engine, if behaviors are enabled
+    pub fn behaviors(&self) -> Option<Arc<BehaviorEngine>> {
+        self.behaviors.read().clone()
+    }
+    
     /// Subscribe to actions
     #[cfg(feature = "wld-integration")]
     pub fn subscribe_actions(&self) -> broadcast::Receiver<WorldAction> {
@@ -18,6 +18,9 @@ pub struct CnsConfig {
     pub max_action_queue_size: usize,
     /// Enable emergency stop
     pub enable_emergency_stop: bool,
+    /// Longest wait for one emergency stop send, in milliseconds
+    #[serde(default = "default_emergency_stop_timeout_ms")]
+    pub emergency_stop_timeout_ms: u64,
     /// Action timeout in milliseconds
     pub action_timeout_ms: u64,
 }
@@ -31,11 +34,16 @@ impl Default for CnsConfig {
             enable_load_balancing: true,
             max_action_queue_size: 1000,
             enable_emergency_stop: true,
+            emergency_stop_timeout_ms: default_emergency_stop_timeout_ms(),
             action_timeout_ms: 5000,
         }
     }
 }
 
+fn default_emergency_stop_timeout_ms() -> u64 {
+    200
+}
+
 impl CnsConfig {
     /// Validate configuration
     pub fn validate(&self) -> Result<(), String> {
@@ -47,6 +55,10 @@ impl CnsConfig {
             return Err("Max action queue size must be greater than 0".to_string());
         }
         
+        if self.emergency_stop_timeout_ms == 0 {
+            return Err("Emergency stop timeout must be greater than 0".to_string());
+        }
+        
         if self.action_timeout_ms == 0 {
             return Err("Action timeout must be greater than 0".to_string());
         }
@@ -0,0 +1,284 @@
+//! Emergency stop and safe-state machine
+//!
+//! Engaging the e-stop sends a stop command to every motor-capable component
+//! over every stop channel (their own transports, and the world broker when
+//! integrated), then holds the CNS in a safe state that rejects new actions
+//! until an operator clears it. Each step is recorded in the e-stop audit log.
+
+use crate::component::{ComponentId, ComponentInfo, ComponentType};
+use crate::error::CnsError;
+use crate::registry::ComponentRegistry;
+use crate::transport::TransportDispatcher;
+use async_trait::async_trait;
+#[cfg(feature = "wld-integration")]
+use narayana_wld::event_transformer::WorldAction;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value as JsonValue};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+#[cfg(feature = "wld-integration")]
+use tokio::sync::broadcast;
+use tracing::{error, info, warn};
+
+/// Audit entries kept in memory
+const MAX_AUDIT_ENTRIES: usize = 10_000;
+
+/// CNS safety state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SafeState {
+    /// Actions are routed normally
+    Operational,
+    /// Stop commands are being broadcast
+    Stopping,
+    /// All actions are rejected until cleared
+    Stopped,
+}
+
+/// Step of an e-stop sequence
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum EStopEvent {
+    Engaged { actor: String, reason: String },
+    StopSent { component_id: ComponentId, channel: String },
+    StopFailed { component_id: ComponentId, channel: String, error: String },
+    SafeStateEntered { stopped: usize, failed: usize },
+    ActionRejected { target: String },
+    Cleared { actor: String, reason: String },
+}
+
+/// One entry of the e-stop audit log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EStopAuditEntry {
+    pub timestamp_ms: u64,
+    /// E-stop sequence the entry belongs to
+    pub stop_id: u64,
+    pub event: EStopEvent,
+}
+
+/// Outcome of engaging the e-stop
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EStopReport {
+    pub stop_id: u64,
+    /// Components that received the stop on at least one channel
+    pub stopped: Vec<ComponentId>,
+    /// Components no channel could reach, with the last error
+    pub failed: Vec<(ComponentId, String)>,
+}
+
+/// A path stop commands can take to components
+#[async_trait]
+pub trait StopChannel: Send + Sync {
+    /// Name recorded in the audit log
+    fn name(&self) -> String;
+
+    async fn send_stop(&self, component: &ComponentInfo, command: &JsonValue) -> Result<(), CnsError>;
+}
+
+#[async_trait]
+impl StopChannel for TransportDispatcher {
+    fn name(&self) -> String {
+        "transport".to_string()
+    }
+
+    async fn send_stop(&self, component: &ComponentInfo, command: &JsonValue) -> Result<(), CnsError> {
+        self.send_json(component, command).await
+    }
+}
+
+/// Sends stop commands as actuator actions through the world broker
+#[cfg(feature = "wld-integration")]
+pub struct WorldStopChannel {
+    action_sender: broadcast::Sender<WorldAction>,
+}
+
+#[cfg(feature = "wld-integration")]
+impl WorldStopChannel {
+    pub fn new(action_sender: broadcast::Sender<WorldAction>) -> Self {
+        Self { action_sender }
+    }
+}
+
+#[cfg(feature = "wld-integration")]
+#[async_trait]
+impl StopChannel for WorldStopChannel {
+    fn name(&self) -> String {
+        "world_broker".to_string()
+    }
+
+    async fn send_stop(&self, component: &ComponentInfo, command: &JsonValue) -> Result<(), CnsError> {
+        self.action_sender.send(WorldAction::ActuatorCommand {
+            target: component.id.as_str().to_string(),
+            command: command.clone(),
+        })
+        .map(|_| ())
+        .map_err(|_| CnsError::Transport("No world broker subscribers".to_string()))
+    }
+}
+
+/// Emergency stop controller
+pub struct EmergencyStop {
+    registry: Arc<ComponentRegistry>,
+    channels: RwLock<Vec<Arc<dyn StopChannel>>>,
+    state: RwLock<SafeState>,
+    /// Longest wait for one stop send
+    send_timeout: Duration,
+    stop_id: AtomicU64,
+    audit: RwLock<VecDeque<EStopAuditEntry>>,
+}
+
+impl EmergencyStop {
+    /// Create e-stop controller
+    pub fn new(registry: Arc<ComponentRegistry>, send_timeout_ms: u64) -> Self {
+        Self {
+            registry,
+            channels: RwLock::new(Vec::new()),
+            state: RwLock::new(SafeState::Operational),
+            send_timeout: Duration::from_millis(send_timeout_ms),
+            stop_id: AtomicU64::new(0),
+            audit: RwLock::new(VecDeque::new()),
+        }
+    }
+
+    /// Add a channel stop commands are sent over
+    pub fn add_channel(&self, channel: Arc<dyn StopChannel>) {
+        self.channels.write().push(channel);
+    }
+
+    pub fn state(&self) -> SafeState {
+        *self.state.read()
+    }
+
+    /// Whether actions must be rejected
+    pub fn is_engaged(&self) -> bool {
+        self.state() != SafeState::Operational
+    }
+
+    /// Stop every motor-capable component and enter the safe state.
+    /// Engaging again while stopped re-sends the stop.
+    pub async fn engage(&self, actor: &str, reason: &str) -> EStopReport {
+        *self.state.write() = SafeState::Stopping;
+        let stop_id = self.stop_id.fetch_add(1, Ordering::SeqCst) + 1;
+        self.record(stop_id, EStopEvent::Engaged {
+            actor: actor.to_string(),
+            reason: reason.to_string(),
+        });
+
+        let command = json!({
+            "command": "emergency_stop",
+            "stop_id": stop_id,
+            "reason": reason,
+        });
+        let channels = self.channels.read().clone();
+        let components: Vec<ComponentInfo> = self.registry.get_all()
+            .into_iter()
+            .filter(is_motor_capable)
+            .collect();
+
+        let mut report = EStopReport { stop_id, stopped: Vec::new(), failed: Vec::new() };
+        for component in components {
+            let mut last_error = "No stop channels configured".to_string();
+            let mut delivered = false;
+            // Every channel, even after one succeeds: the cost of a duplicate
+            // stop is nothing next to a missed one
+            for channel in &channels {
+                let result = tokio::time::timeout(self.send_timeout, channel.send_stop(&component, &command))
+                    .await
+                    .unwrap_or_else(|_| Err(CnsError::Transport("Stop send timed out".to_string())));
+                match result {
+                    Ok(()) => {
+                        delivered = true;
+                        self.record(stop_id, EStopEvent::StopSent {
+                            component_id: component.id.clone(),
+                            channel: channel.name(),
+                        });
+                    }
+                    Err(e) => {
+                        last_error = e.to_string();
+                        self.record(stop_id, EStopEvent::StopFailed {
+                            component_id: component.id.clone(),
+                            channel: channel.name(),
+                            error: last_error.clone(),
+                        });
+                    }
+                }
+            }
+            if delivered {
+                report.stopped.push(component.id);
+            } else {
+                report.failed.push((component.id, last_error));
+            }
+        }
+
+        *self.state.write() = SafeState::Stopped;
+        self.record(stop_id, EStopEvent::SafeStateEntered {
+            stopped: report.stopped.len(),
+            failed: report.failed.len(),
+        });
+        report
+    }
+
+    /// Reject an action while the e-stop is engaged
+    pub fn check_action(&self, target: &str) -> Result<(), CnsError> {
+        if !self.is_engaged() {
+            return Ok(());
+        }
+        self.record(self.stop_id.load(Ordering::SeqCst), EStopEvent::ActionRejected {
+            target: target.to_string(),
+        });
+        Err(CnsError::Safety("Emergency stop is engaged".to_string()))
+    }
+
+    /// Leave the safe state. Only an identified operator can clear, and not
+    /// while stop commands are still going out.
+    pub fn clear(&self, actor: &str, reason: &str) -> Result<(), CnsError> {
+        if actor.trim().is_empty() {
+            return Err(CnsError::Validation("Clearing the emergency stop requires an actor".to_string()));
+        }
+        {
+            let mut state = self.state.write();
+            match *state {
+                SafeState::Operational => {
+                    return Err(CnsError::Safety("Emergency stop is not engaged".to_string()));
+                }
+                SafeState::Stopping => {
+                    return Err(CnsError::Safety("Emergency stop is still being broadcast".to_string()));
+                }
+                SafeState::Stopped => *state = SafeState::Operational,
+            }
+        }
+        self.record(self.stop_id.load(Ordering::SeqCst), EStopEvent::Cleared {
+            actor: actor.to_string(),
+            reason: reason.to_string(),
+        });
+        Ok(())
+    }
+
+    /// E-stop audit log, oldest first
+    pub fn audit_log(&self) -> Vec<EStopAuditEntry> {
+        self.audit.read().iter().cloned().collect()
+    }
+
+    fn record(&self, stop_id: u64, event: EStopEvent) {
+        match &event {
+            EStopEvent::StopFailed { .. } => error!("ESTOP: stop_id={}, event={:?}", stop_id, event),
+            EStopEvent::ActionRejected { .. } => warn!("ESTOP: stop_id={}, event={:?}", stop_id, event),
+            _ => info!("ESTOP: stop_id={}, event={:?}", stop_id, event),
+        }
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        let mut audit = self.audit.write();
+        audit.push_back(EStopAuditEntry { timestamp_ms, stop_id, event });
+        if audit.len() > MAX_AUDIT_ENTRIES {
+            audit.pop_front();
+        }
+    }
+}
+
+/// Components that can move and so must be stopped
+fn is_motor_capable(component: &ComponentInfo) -> bool {
+    matches!(component.component_type, ComponentType::Actuator | ComponentType::Hybrid)
+}
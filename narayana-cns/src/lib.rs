@@ -4,7 +4,10 @@
 //! - Dynamic component registration and discovery
 //! - Capability-based action routing
 //! - Safety interlock and validation
+//! - Emergency stop broadcast and safe-state machine
 //! - Pluggable transport layer abstraction
+//! - Staged firmware updates with health-gated promotion and rollback
+//! - Declarative behavior trees over capabilities and world events
 //! - Integration with WorldBroker and CPL
 
 pub mod error;
@@ -16,6 +19,9 @@ pub mod router;
 pub mod transport;
 pub mod cns;
 pub mod config;
+pub mod estop;
+pub mod update;
+pub mod behavior;
 
 pub use error::CnsError;
 pub use component::{ComponentInfo, ComponentId, ComponentType, ComponentState};
@@ -23,7 +29,10 @@ pub use capability::{Capability, StructuredCapability, CapabilityMatcher};
 pub use registry::ComponentRegistry;
 pub use safety::{SafetyValidator, SafetyLimits, SafetyLevel, SafetyRule};
 pub use router::ActionRouter;
-pub use transport::{Transport, TransportConfig, TransportRegistry};
+pub use transport::{Transport, TransportConfig, TransportDispatcher, TransportRegistry};
 pub use cns::CentralNervousSystem;
 pub use config::CnsConfig;
+pub use estop::{EmergencyStop, EStopReport, SafeState, StopChannel};
+pub use update::{UpdateConfig, UpdateOrchestrator, UpdateChannel, FirmwareArtifact, Rollout, RolloutStatus};
+pub use behavior::{BehaviorEngine, BehaviorTreeDef, CapabilityExecutor, NodeDef, NodeStatus, TreeStatus};
 
@@ -291,6 +291,35 @@ impl ComponentRegistry {
         Ok(())
     }
     
+    /// Record the firmware version a component reports (e.g. after an update);
+    /// counts as a heartbeat
+    pub fn report_version(&self, component_id: &ComponentId, version: String) -> Result<(), CnsError> {
+        {
+            let mut components = self.components.write();
+            if let Some(component) = components.get_mut(component_id) {
+                if component.version != version {
+                    info!("Component '{}' reports version {}", component_id.as_str(), version);
+                }
+                component.version = version;
+                component.update_heartbeat();
+            } else {
+                return Err(CnsError::Registry(format!("Component '{}' not found", component_id.as_str())));
+            }
+        }
+        
+        // Broadcast event
+        let _ = self.event_sender.send(RegistryEvent::ComponentHeartbeat {
+            component_id: component_id.clone(),
+        });
+        
+        Ok(())
+    }
+    
+    /// Heartbeat timeout in seconds
+    pub fn heartbeat_timeout_secs(&self) -> u64 {
+        self.heartbeat_timeout_secs
+    }
+    
     /// Check component health and update state if needed
     pub fn check_health(&self, component_id: &ComponentId) -> bool {
         let is_healthy = {
@@ -1,5 +1,6 @@
 //! Transport layer abstraction
 
+use crate::component::ComponentInfo;
 use crate::error::CnsError;
 use async_trait::async_trait;
 use bytes::Bytes;
@@ -97,6 +98,43 @@ impl Default for TransportRegistry {
     }
 }
 
+/// Sends payloads to components over their configured transport,
+/// connecting on first use. Shared by everything that talks to components
+/// directly (firmware updates, emergency stop).
+pub struct TransportDispatcher {
+    transports: tokio::sync::Mutex<TransportRegistry>,
+}
+
+impl TransportDispatcher {
+    /// Create dispatcher over a set of transports
+    pub fn new(transports: TransportRegistry) -> Self {
+        Self {
+            transports: tokio::sync::Mutex::new(transports),
+        }
+    }
+    
+    /// Send a payload to a component
+    pub async fn send_to(&self, component: &ComponentInfo, data: &Bytes) -> Result<(), CnsError> {
+        let mut transports = self.transports.lock().await;
+        let transport = transports.get_mut(component.transport.transport_type.clone())
+            .ok_or_else(|| CnsError::Transport(format!(
+                "No {:?} transport for component '{}'",
+                component.transport.transport_type, component.id.as_str()
+            )))?;
+        if !transport.is_connected() {
+            transport.connect(&component.transport).await?;
+        }
+        transport.send(data).await
+    }
+    
+    /// Send a JSON message to a component
+    pub async fn send_json<T: Serialize + Sync>(&self, component: &ComponentInfo, message: &T) -> Result<(), CnsError> {
+        let payload = serde_json::to_vec(message)
+            .map_err(|e| CnsError::Transport(format!("Failed to encode message: {}", e)))?;
+        self.send_to(component, &Bytes::from(payload)).await
+    }
+}
+
 // Placeholder transport implementations
 // Full implementations would go in separate modules
 
@@ -0,0 +1,592 @@
+//! Firmware/OTA update orchestration
+//!
+//! Firmware images are kept in the blob store and pushed to components over
+//! their CNS transport. A rollout updates the matching components in stages;
+//! each stage must report the new version and stay healthy for a window before
+//! the next one starts, and too many failures roll every updated component back.
+
+use crate::component::{ComponentId, ComponentInfo, ComponentState, ComponentType};
+use crate::error::CnsError;
+use crate::registry::ComponentRegistry;
+use crate::transport::TransportDispatcher;
+use async_trait::async_trait;
+use narayana_storage::blob_store::{BlobRef, BlobStore};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tracing::{error, info, warn};
+
+/// Update orchestration configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UpdateConfig {
+    /// Cumulative fraction of components updated by the end of each stage
+    pub stages: Vec<f64>,
+    /// How long a stage's components must stay healthy on the new version
+    pub health_window_ms: u64,
+    /// How long a component may take to come back on the new version
+    pub install_timeout_ms: u64,
+    /// Failed components tolerated before the rollout is rolled back
+    pub max_failures: usize,
+    /// Firmware bytes per transport message
+    pub chunk_size: usize,
+    /// How often `run_rollout` re-evaluates a rollout
+    pub poll_interval_ms: u64,
+}
+
+impl Default for UpdateConfig {
+    fn default() -> Self {
+        Self {
+            stages: vec![0.1, 0.5, 1.0],
+            health_window_ms: 60_000,
+            install_timeout_ms: 300_000,
+            max_failures: 0,
+            chunk_size: 16 * 1024,
+            poll_interval_ms: 1000,
+        }
+    }
+}
+
+impl UpdateConfig {
+    /// Validate configuration
+    pub fn validate(&self) -> Result<(), String> {
+        if self.stages.is_empty() {
+            return Err("Rollout needs at least one stage".to_string());
+        }
+        if self.stages.windows(2).any(|w| w[1] <= w[0]) || self.stages.iter().any(|s| !(*s > 0.0 && *s <= 1.0)) {
+            return Err("Rollout stages must increase within (0, 1]".to_string());
+        }
+        if self.stages.last() != Some(&1.0) {
+            return Err("The last rollout stage must be 1.0".to_string());
+        }
+        if self.install_timeout_ms == 0 {
+            return Err("Install timeout must be greater than 0".to_string());
+        }
+        if self.chunk_size == 0 || self.chunk_size > 1024 * 1024 {
+            return Err("Update chunk size must be between 1 byte and 1MB".to_string());
+        }
+        if self.poll_interval_ms == 0 {
+            return Err("Poll interval must be greater than 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// A firmware image for one kind of component
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FirmwareArtifact {
+    pub component_type: ComponentType,
+    /// Hardware model, matched against the component's `hardware` metadata
+    pub hardware: Option<String>,
+    pub version: String,
+    pub blob: BlobRef,
+    pub size: u64,
+    pub uploaded_at: u64,
+}
+
+impl FirmwareArtifact {
+    fn targets(&self, component: &ComponentInfo) -> bool {
+        component.component_type == self.component_type
+            && component.metadata.get("hardware").and_then(|v| v.as_str()) == self.hardware.as_deref()
+    }
+}
+
+/// Messages sent to a component to install or revert firmware.
+/// An install is `Begin`, the image in `Chunk`s, then `Commit`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum UpdateMessage {
+    Begin {
+        rollout_id: String,
+        version: String,
+        /// SHA-256 of the whole image
+        sha256: String,
+        size: u64,
+    },
+    Chunk {
+        rollout_id: String,
+        offset: u64,
+        /// Hex-encoded bytes
+        data: String,
+    },
+    Commit {
+        rollout_id: String,
+        version: String,
+    },
+    /// Revert to the previously installed image (e.g. the other A/B slot)
+    Rollback {
+        rollout_id: String,
+        version: String,
+    },
+}
+
+/// Delivers update messages to components
+#[async_trait]
+pub trait UpdateChannel: Send + Sync {
+    async fn deliver(&self, component: &ComponentInfo, message: &UpdateMessage) -> Result<(), CnsError>;
+}
+
+#[async_trait]
+impl UpdateChannel for TransportDispatcher {
+    async fn deliver(&self, component: &ComponentInfo, message: &UpdateMessage) -> Result<(), CnsError> {
+        self.send_json(component, message).await
+    }
+}
+
+/// Overall rollout status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RolloutStatus {
+    InProgress,
+    Completed,
+    RolledBack,
+}
+
+/// Update progress of one component
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TargetState {
+    /// In a later stage
+    Waiting,
+    /// Image delivered; waiting for the new version to report healthy
+    Verifying,
+    /// Healthy on the new version for the whole window
+    Updated,
+    Failed(String),
+    RolledBack,
+}
+
+/// One component in a rollout
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateTarget {
+    pub component_id: ComponentId,
+    pub previous_version: String,
+    pub stage: usize,
+    pub state: TargetState,
+}
+
+/// A staged update of all components matching an artifact
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rollout {
+    pub id: String,
+    pub artifact: FirmwareArtifact,
+    pub targets: Vec<UpdateTarget>,
+    pub stage_count: usize,
+    pub current_stage: usize,
+    pub status: RolloutStatus,
+    pub started_at: u64,
+    pub finished_at: Option<u64>,
+}
+
+impl Rollout {
+    pub fn failures(&self) -> usize {
+        self.targets.iter().filter(|t| matches!(t.state, TargetState::Failed(_))).count()
+    }
+}
+
+/// Rollout with the timing the state machine needs
+struct RolloutRun {
+    rollout: Rollout,
+    /// When the current stage's images were delivered
+    stage_started: Option<Instant>,
+    /// When each verifying component was first seen healthy on the new version
+    healthy_since: HashMap<ComponentId, Instant>,
+}
+
+/// Stores firmware artifacts and drives rollouts
+pub struct UpdateOrchestrator {
+    config: UpdateConfig,
+    registry: Arc<ComponentRegistry>,
+    blobs: Arc<BlobStore>,
+    channel: Arc<dyn UpdateChannel>,
+    artifacts: RwLock<Vec<FirmwareArtifact>>,
+    rollouts: RwLock<HashMap<String, RolloutRun>>,
+    /// Serializes `advance`, which awaits deliveries between state updates
+    advance_lock: tokio::sync::Mutex<()>,
+}
+
+impl UpdateOrchestrator {
+    pub fn new(
+        config: UpdateConfig,
+        registry: Arc<ComponentRegistry>,
+        blobs: Arc<BlobStore>,
+        channel: Arc<dyn UpdateChannel>,
+    ) -> Result<Self, CnsError> {
+        config.validate().map_err(CnsError::Config)?;
+        Ok(Self {
+            config,
+            registry,
+            blobs,
+            channel,
+            artifacts: RwLock::new(Vec::new()),
+            rollouts: RwLock::new(HashMap::new()),
+            advance_lock: tokio::sync::Mutex::new(()),
+        })
+    }
+
+    pub fn config(&self) -> &UpdateConfig {
+        &self.config
+    }
+
+    /// Store a firmware image in the blob store
+    pub fn upload_artifact(
+        &self,
+        component_type: ComponentType,
+        hardware: Option<String>,
+        version: &str,
+        image: &[u8],
+    ) -> Result<FirmwareArtifact, CnsError> {
+        if version.is_empty() || version.len() > 64 {
+            return Err(CnsError::Validation("Firmware version must be 1-64 characters".to_string()));
+        }
+        if image.is_empty() {
+            return Err(CnsError::Validation("Firmware image is empty".to_string()));
+        }
+        let (info, _) = self.blobs.put(image, Some("application/octet-stream".to_string()))?;
+        let artifact = FirmwareArtifact {
+            component_type,
+            hardware,
+            version: version.to_string(),
+            blob: info.reference(),
+            size: info.size,
+            uploaded_at: now_secs(),
+        };
+        self.register_artifact(artifact.clone())?;
+        Ok(artifact)
+    }
+
+    /// Track an artifact whose image is already in the blob store
+    /// (e.g. one uploaded before a restart)
+    pub fn register_artifact(&self, artifact: FirmwareArtifact) -> Result<(), CnsError> {
+        let info = self.blobs.info(artifact.blob.id())
+            .ok_or_else(|| CnsError::Validation(format!("Firmware blob {} not found", artifact.blob)))?;
+        if info.size != artifact.size {
+            return Err(CnsError::Validation(format!("Firmware blob {} size does not match", artifact.blob)));
+        }
+        let mut artifacts = self.artifacts.write();
+        artifacts.retain(|a| {
+            !(a.component_type == artifact.component_type && a.hardware == artifact.hardware && a.version == artifact.version)
+        });
+        info!(
+            "Firmware {} for {:?}/{} stored as {}",
+            artifact.version, artifact.component_type, artifact.hardware.as_deref().unwrap_or("*"), artifact.blob
+        );
+        artifacts.push(artifact);
+        Ok(())
+    }
+
+    pub fn artifact(&self, component_type: ComponentType, hardware: Option<&str>, version: &str) -> Option<FirmwareArtifact> {
+        self.artifacts.read().iter()
+            .find(|a| a.component_type == component_type && a.hardware.as_deref() == hardware && a.version == version)
+            .cloned()
+    }
+
+    pub fn artifacts(&self) -> Vec<FirmwareArtifact> {
+        self.artifacts.read().clone()
+    }
+
+    /// Plan a rollout of a stored artifact to every matching component not
+    /// already on its version. Nothing is sent until `advance` is called.
+    pub fn start_rollout(&self, component_type: ComponentType, hardware: Option<&str>, version: &str) -> Result<Rollout, CnsError> {
+        let artifact = self.artifact(component_type, hardware, version)
+            .ok_or_else(|| CnsError::Validation(format!(
+                "No firmware {} for {:?}/{}", version, component_type, hardware.unwrap_or("*")
+            )))?;
+
+        let mut rollouts = self.rollouts.write();
+        let busy = rollouts.values().any(|run| {
+            run.rollout.status == RolloutStatus::InProgress
+                && run.rollout.artifact.component_type == component_type
+                && run.rollout.artifact.hardware.as_deref() == hardware
+        });
+        if busy {
+            return Err(CnsError::Validation(format!(
+                "A rollout for {:?}/{} is already in progress", component_type, hardware.unwrap_or("*")
+            )));
+        }
+
+        let mut components: Vec<ComponentInfo> = self.registry.find_by_type(component_type)
+            .into_iter()
+            .filter(|c| artifact.targets(c) && c.version != artifact.version)
+            .collect();
+        components.sort_by(|a, b| a.id.as_str().cmp(b.id.as_str()));
+
+        // Stage boundaries, dropping stages that would be empty
+        let total = components.len();
+        let mut boundaries: Vec<usize> = self.config.stages.iter()
+            .map(|fraction| ((fraction * total as f64).ceil() as usize).clamp(1, total.max(1)))
+            .collect();
+        boundaries.dedup();
+
+        let targets = components.into_iter().enumerate()
+            .map(|(i, component)| UpdateTarget {
+                component_id: component.id,
+                previous_version: component.version,
+                stage: boundaries.iter().position(|&end| i < end).unwrap_or(0),
+                state: TargetState::Waiting,
+            })
+            .collect::<Vec<_>>();
+        let stage_count = if targets.is_empty() { 0 } else { boundaries.len() };
+
+        let rollout = Rollout {
+            id: uuid::Uuid::new_v4().to_string(),
+            artifact,
+            targets,
+            stage_count,
+            current_stage: 0,
+            status: if stage_count == 0 { RolloutStatus::Completed } else { RolloutStatus::InProgress },
+            started_at: now_secs(),
+            finished_at: if stage_count == 0 { Some(now_secs()) } else { None },
+        };
+        info!(
+            "Rollout {} of firmware {} to {} components in {} stages",
+            rollout.id, rollout.artifact.version, rollout.targets.len(), rollout.stage_count
+        );
+        rollouts.insert(rollout.id.clone(), RolloutRun {
+            rollout: rollout.clone(),
+            stage_started: None,
+            healthy_since: HashMap::new(),
+        });
+        Ok(rollout)
+    }
+
+    pub fn rollout(&self, rollout_id: &str) -> Option<Rollout> {
+        self.rollouts.read().get(rollout_id).map(|run| run.rollout.clone())
+    }
+
+    pub fn rollouts(&self) -> Vec<Rollout> {
+        self.rollouts.read().values().map(|run| run.rollout.clone()).collect()
+    }
+
+    /// Move a rollout forward: deliver the current stage, check its health,
+    /// promote to the next stage, or roll back
+    pub async fn advance(&self, rollout_id: &str) -> Result<Rollout, CnsError> {
+        let _guard = self.advance_lock.lock().await;
+        let (rollout, stage_started) = {
+            let rollouts = self.rollouts.read();
+            let run = rollouts.get(rollout_id)
+                .ok_or_else(|| CnsError::Validation(format!("Rollout '{}' not found", rollout_id)))?;
+            (run.rollout.clone(), run.stage_started)
+        };
+        if rollout.status != RolloutStatus::InProgress {
+            return Ok(rollout);
+        }
+
+        if stage_started.is_none() {
+            self.deliver_stage(&rollout).await;
+        } else {
+            self.verify_stage(rollout_id);
+        }
+
+        let rollout = self.rollout(rollout_id)
+            .ok_or_else(|| CnsError::Validation(format!("Rollout '{}' not found", rollout_id)))?;
+        if rollout.failures() > self.config.max_failures {
+            error!(
+                "Rollout {} has {} failed components, rolling back",
+                rollout.id, rollout.failures()
+            );
+            self.roll_back(&rollout).await;
+        } else {
+            self.promote_if_done(rollout_id);
+        }
+        self.rollout(rollout_id)
+            .ok_or_else(|| CnsError::Validation(format!("Rollout '{}' not found", rollout_id)))
+    }
+
+    /// Advance a rollout until it completes or is rolled back
+    pub async fn run_rollout(&self, rollout_id: &str) -> Result<Rollout, CnsError> {
+        loop {
+            let rollout = self.advance(rollout_id).await?;
+            if rollout.status != RolloutStatus::InProgress {
+                return Ok(rollout);
+            }
+            tokio::time::sleep(Duration::from_millis(self.config.poll_interval_ms)).await;
+        }
+    }
+
+    async fn deliver_stage(&self, rollout: &Rollout) {
+        let stage_targets: Vec<ComponentId> = rollout.targets.iter()
+            .filter(|t| t.stage == rollout.current_stage && t.state == TargetState::Waiting)
+            .map(|t| t.component_id.clone())
+            .collect();
+        let mut results = Vec::with_capacity(stage_targets.len());
+        for component_id in stage_targets {
+            let result = match self.registry.get(&component_id) {
+                Some(component) => {
+                    // Keep actions away from components while they flash
+                    let _ = self.registry.update_state(&component_id, ComponentState::Maintenance);
+                    self.install(&rollout.id, &component, &rollout.artifact).await
+                }
+                None => Err(CnsError::Component("Component unregistered".to_string())),
+            };
+            results.push((component_id, result));
+        }
+
+        let mut rollouts = self.rollouts.write();
+        let Some(run) = rollouts.get_mut(&rollout.id) else { return };
+        run.stage_started = Some(Instant::now());
+        for (component_id, result) in results {
+            let Some(target) = run.rollout.targets.iter_mut().find(|t| t.component_id == component_id) else { continue };
+            target.state = match result {
+                Ok(()) => TargetState::Verifying,
+                Err(e) => {
+                    warn!("Failed to deliver firmware to '{}': {}", component_id.as_str(), e);
+                    TargetState::Failed(e.to_string())
+                }
+            };
+        }
+    }
+
+    fn verify_stage(&self, rollout_id: &str) {
+        let now = Instant::now();
+        let health_window = Duration::from_millis(self.config.health_window_ms);
+        let install_timeout = Duration::from_millis(self.config.install_timeout_ms);
+        let heartbeat_timeout = self.registry.heartbeat_timeout_secs();
+
+        let mut rollouts = self.rollouts.write();
+        let Some(run) = rollouts.get_mut(rollout_id) else { return };
+        let stage_started = run.stage_started.unwrap_or(now);
+        let version = run.rollout.artifact.version.clone();
+        let current_stage = run.rollout.current_stage;
+
+        for target in run.rollout.targets.iter_mut()
+            .filter(|t| t.stage == current_stage && t.state == TargetState::Verifying)
+        {
+            let component = self.registry.get(&target.component_id);
+            let healthy = component.as_ref().is_some_and(|c| {
+                // Heartbeats resuming count even if the health check marked it unavailable mid-flash
+                c.version == version
+                    && c.is_healthy(heartbeat_timeout)
+                    && !matches!(c.state, ComponentState::Error(_))
+            });
+            if healthy {
+                let since = *run.healthy_since.entry(target.component_id.clone()).or_insert_with(|| {
+                    // Back on the new version: let actions through again
+                    let _ = self.registry.update_state(&target.component_id, ComponentState::Available);
+                    now
+                });
+                if now.duration_since(since) >= health_window {
+                    info!("Component '{}' updated to {}", target.component_id.as_str(), version);
+                    target.state = TargetState::Updated;
+                }
+                continue;
+            }
+
+            let was_healthy = run.healthy_since.remove(&target.component_id).is_some();
+            let failure = match component {
+                None => Some("Component unregistered".to_string()),
+                Some(c) => match c.state {
+                    ComponentState::Error(e) => Some(e),
+                    _ if was_healthy => Some("Became unhealthy after update".to_string()),
+                    _ if now.duration_since(stage_started) >= install_timeout => {
+                        Some(format!("Did not report version {} in time", version))
+                    }
+                    _ => None,
+                },
+            };
+            if let Some(reason) = failure {
+                warn!("Update of '{}' failed: {}", target.component_id.as_str(), reason);
+                target.state = TargetState::Failed(reason);
+            }
+        }
+    }
+
+    fn promote_if_done(&self, rollout_id: &str) {
+        let mut rollouts = self.rollouts.write();
+        let Some(run) = rollouts.get_mut(rollout_id) else { return };
+        let current_stage = run.rollout.current_stage;
+        let done = run.stage_started.is_some()
+            && run.rollout.targets.iter()
+                .filter(|t| t.stage == current_stage)
+                .all(|t| matches!(t.state, TargetState::Updated | TargetState::Failed(_)));
+        if !done {
+            return;
+        }
+        run.stage_started = None;
+        run.healthy_since.clear();
+        run.rollout.current_stage += 1;
+        if run.rollout.current_stage >= run.rollout.stage_count {
+            run.rollout.status = RolloutStatus::Completed;
+            run.rollout.finished_at = Some(now_secs());
+            info!("Rollout {} completed", rollout_id);
+        } else {
+            info!("Rollout {} promoted to stage {}", rollout_id, run.rollout.current_stage + 1);
+        }
+    }
+
+    async fn roll_back(&self, rollout: &Rollout) {
+        let mut results = Vec::new();
+        for target in rollout.targets.iter().filter(|t| t.state != TargetState::Waiting) {
+            let Some(component) = self.registry.get(&target.component_id) else { continue };
+            let _ = self.registry.update_state(&target.component_id, ComponentState::Maintenance);
+            // Re-install the previous image if we have it; otherwise ask the
+            // component to revert to the one it kept
+            let previous = self.artifact(
+                rollout.artifact.component_type,
+                rollout.artifact.hardware.as_deref(),
+                &target.previous_version,
+            );
+            let result = match previous {
+                Some(previous) => self.install(&rollout.id, &component, &previous).await,
+                None => self.channel.deliver(&component, &UpdateMessage::Rollback {
+                    rollout_id: rollout.id.clone(),
+                    version: target.previous_version.clone(),
+                }).await,
+            };
+            match &result {
+                Ok(()) => {
+                    let _ = self.registry.update_state(&target.component_id, ComponentState::Available);
+                }
+                Err(e) => {
+                    error!("Failed to roll back '{}': {}", target.component_id.as_str(), e);
+                    let _ = self.registry.update_state(
+                        &target.component_id,
+                        ComponentState::Error(format!("Rollback failed: {}", e)),
+                    );
+                }
+            }
+            results.push((target.component_id.clone(), result));
+        }
+
+        let mut rollouts = self.rollouts.write();
+        let Some(run) = rollouts.get_mut(&rollout.id) else { return };
+        for (component_id, result) in results {
+            if let (Ok(()), Some(target)) = (result, run.rollout.targets.iter_mut().find(|t| t.component_id == component_id)) {
+                target.state = TargetState::RolledBack;
+            }
+        }
+        run.rollout.status = RolloutStatus::RolledBack;
+        run.rollout.finished_at = Some(now_secs());
+    }
+
+    /// Stream an image to a component
+    async fn install(&self, rollout_id: &str, component: &ComponentInfo, artifact: &FirmwareArtifact) -> Result<(), CnsError> {
+        self.channel.deliver(component, &UpdateMessage::Begin {
+            rollout_id: rollout_id.to_string(),
+            version: artifact.version.clone(),
+            sha256: artifact.blob.id().to_string(),
+            size: artifact.size,
+        }).await?;
+        let mut offset = 0;
+        while offset < artifact.size {
+            let chunk = self.blobs.read_range(artifact.blob.id(), offset, self.config.chunk_size as u64)?;
+            if chunk.is_empty() {
+                break;
+            }
+            self.channel.deliver(component, &UpdateMessage::Chunk {
+                rollout_id: rollout_id.to_string(),
+                offset,
+                data: hex::encode(&chunk),
+            }).await?;
+            offset += chunk.len() as u64;
+        }
+        self.channel.deliver(component, &UpdateMessage::Commit {
+            rollout_id: rollout_id.to_string(),
+            version: artifact.version.clone(),
+        }).await
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
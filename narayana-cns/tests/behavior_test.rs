@@ -0,0 +1,162 @@
+//! Tests for behavior trees
+
+use async_trait::async_trait;
+use narayana_cns::behavior::NodeSnapshot;
+use narayana_cns::{BehaviorEngine, BehaviorTreeDef, CapabilityExecutor, CnsError, NodeStatus, TreeStatus};
+use parking_lot::Mutex;
+use serde_json::{json, Value as JsonValue};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Records capability calls; capabilities in `failing` return an error
+struct RecordingExecutor {
+    failing: Vec<&'static str>,
+    calls: Mutex<Vec<(String, JsonValue)>>,
+}
+
+#[async_trait]
+impl CapabilityExecutor for RecordingExecutor {
+    async fn execute(&self, capability: &str, params: &JsonValue) -> Result<JsonValue, CnsError> {
+        self.calls.lock().push((capability.to_string(), params.clone()));
+        if self.failing.contains(&capability) {
+            return Err(CnsError::Routing(format!("{} unavailable", capability)));
+        }
+        Ok(JsonValue::Null)
+    }
+}
+
+fn executor(failing: Vec<&'static str>) -> Arc<RecordingExecutor> {
+    Arc::new(RecordingExecutor { failing, calls: Mutex::new(Vec::new()) })
+}
+
+/// Tick until the tree completes
+async fn run(engine: &BehaviorEngine, name: &str) -> NodeStatus {
+    for _ in 0..100 {
+        let status = engine.tick(name).unwrap();
+        if status != NodeStatus::Running {
+            return status;
+        }
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    panic!("behavior tree '{}' did not complete", name);
+}
+
+const FETCH: &str = r#"
+name: fetch
+tick_interval_ms: 10
+root:
+  type: sequence
+  children:
+    - type: condition
+      event: door
+      matches: { state: open }
+    - type: selector
+      children:
+        - type: action
+          capability: grasp_precise
+        - type: retry
+          attempts: 2
+          child:
+            type: action
+            capability: grasp
+            params: { force: 5 }
+    - type: action
+      capability: deliver
+"#;
+
+#[test]
+fn test_tree_definition_validation() {
+    let def = BehaviorTreeDef::from_yaml(FETCH).unwrap();
+    assert_eq!(def.tick_interval_ms, 10);
+    let json = serde_json::to_string(&def).unwrap();
+    assert_eq!(BehaviorTreeDef::from_json(&json).unwrap(), def);
+
+    assert!(BehaviorTreeDef::from_json(r#"{"name": "empty", "root": {"type": "sequence", "children": []}}"#).is_err());
+    assert!(BehaviorTreeDef::from_json(r#"{"name": "../escape", "root": {"type": "wait", "duration_ms": 1}}"#).is_err());
+    assert!(BehaviorTreeDef::from_json(r#"{"name": "retry", "root": {"type": "retry", "attempts": 0, "child": {"type": "wait", "duration_ms": 1}}}"#).is_err());
+    assert!(BehaviorTreeDef::from_json(r#"{"name": "unknown", "root": {"type": "parallel", "children": []}}"#).is_err());
+}
+
+#[tokio::test]
+async fn test_tree_runs_capabilities_and_conditions() {
+    let executor = executor(vec!["grasp_precise"]);
+    let engine = BehaviorEngine::new(executor.clone(), None).unwrap();
+    engine.load_yaml(FETCH).unwrap();
+    assert_eq!(engine.list(), vec![("fetch".to_string(), TreeStatus::Idle)]);
+
+    // The door was never seen open
+    assert_eq!(run(&engine, "fetch").await, NodeStatus::Failure);
+    assert!(executor.calls.lock().is_empty());
+
+    engine.notify_event("door", json!({ "state": "closed" }));
+    assert_eq!(run(&engine, "fetch").await, NodeStatus::Failure);
+    engine.notify_event("door", json!({ "state": "open", "id": 3 }));
+    assert_eq!(run(&engine, "fetch").await, NodeStatus::Success);
+
+    // The precise grasp failed, so the selector fell back to the plain one
+    let calls: Vec<String> = executor.calls.lock().iter().map(|(capability, _)| capability.clone()).collect();
+    assert_eq!(calls, vec!["grasp_precise", "grasp", "deliver"]);
+    assert_eq!(executor.calls.lock()[1].1, json!({ "force": 5 }));
+
+    let snapshot = engine.snapshot("fetch").unwrap();
+    assert_eq!(snapshot.status, TreeStatus::Succeeded);
+    let selector: &NodeSnapshot = &snapshot.root.children[1];
+    assert_eq!(selector.kind, "selector");
+    assert_eq!(selector.children[0].label.as_deref(), Some("grasp_precise"));
+    assert_eq!(selector.children[0].status, Some(NodeStatus::Failure));
+    assert_eq!(selector.status, Some(NodeStatus::Success));
+}
+
+#[tokio::test]
+async fn test_scheduled_tree_persists_and_resumes() {
+    let dir = tempfile::tempdir().unwrap();
+    let tree = r#"{
+        "name": "patrol",
+        "tick_interval_ms": 5,
+        "root": {"type": "sequence", "children": [
+            {"type": "action", "capability": "move", "params": {"to": "a"}},
+            {"type": "wait_event", "event": "arrived"},
+            {"type": "action", "capability": "move", "params": {"to": "b"}}
+        ]}
+    }"#;
+
+    let first = executor(vec![]);
+    {
+        let engine = BehaviorEngine::new(first.clone(), Some(dir.path().to_path_buf())).unwrap();
+        engine.load_json(tree).unwrap();
+        engine.start("patrol").unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(engine.snapshot("patrol").unwrap().status, TreeStatus::Running);
+    }
+    assert_eq!(first.calls.lock().len(), 1);
+
+    // A new engine picks the tree up waiting for the event, past the first move
+    let second = executor(vec![]);
+    let engine = BehaviorEngine::new(second.clone(), Some(dir.path().to_path_buf())).unwrap();
+    assert_eq!(engine.load_persisted().unwrap(), 1);
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    assert!(second.calls.lock().is_empty());
+
+    engine.notify_event("arrived", JsonValue::Null);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(engine.snapshot("patrol").unwrap().status, TreeStatus::Succeeded);
+    assert_eq!(second.calls.lock()[0].1, json!({ "to": "b" }));
+
+    assert!(engine.remove("patrol").unwrap());
+    assert!(!dir.path().join("patrol.json").exists());
+}
+
+#[tokio::test]
+async fn test_installed_definitions_load_idle() {
+    let dir = tempfile::tempdir().unwrap();
+    let def = BehaviorTreeDef::from_yaml(FETCH).unwrap();
+    narayana_cns::behavior::install_definition(dir.path(), &def).unwrap();
+
+    let engine = BehaviorEngine::new(executor(vec![]), Some(dir.path().to_path_buf())).unwrap();
+    assert_eq!(engine.load_persisted().unwrap(), 1);
+    assert_eq!(engine.list(), vec![("fetch".to_string(), TreeStatus::Idle)]);
+    assert_eq!(engine.definition("fetch").unwrap(), def);
+
+    assert!(narayana_cns::behavior::remove_definition(dir.path(), "fetch").unwrap());
+    assert!(!narayana_cns::behavior::remove_definition(dir.path(), "fetch").unwrap());
+}
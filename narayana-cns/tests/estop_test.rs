@@ -0,0 +1,113 @@
+//! Tests for emergency stop
+
+use async_trait::async_trait;
+use narayana_cns::estop::EStopEvent;
+use narayana_cns::transport::TransportType;
+use narayana_cns::{
+    Capability, CentralNervousSystem, CnsConfig, CnsError, ComponentId, ComponentInfo, ComponentRegistry,
+    ComponentType, EmergencyStop, SafeState, StopChannel, TransportConfig,
+};
+use parking_lot::Mutex;
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Records stops; components in `unreachable` never answer
+struct RecordingChannel {
+    unreachable: Vec<&'static str>,
+    stopped: Mutex<Vec<String>>,
+}
+
+#[async_trait]
+impl StopChannel for RecordingChannel {
+    fn name(&self) -> String {
+        "test".to_string()
+    }
+
+    async fn send_stop(&self, component: &ComponentInfo, command: &JsonValue) -> Result<(), CnsError> {
+        assert_eq!(command["command"], "emergency_stop");
+        if self.unreachable.contains(&component.id.as_str()) {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        }
+        self.stopped.lock().push(component.id.as_str().to_string());
+        Ok(())
+    }
+}
+
+fn component(id: &str, component_type: ComponentType) -> ComponentInfo {
+    ComponentInfo::new(
+        ComponentId::from(id),
+        id.to_string(),
+        component_type,
+        vec![Capability::Simple("move".to_string())],
+        TransportConfig {
+            transport_type: TransportType::Can,
+            config: HashMap::new(),
+        },
+    )
+}
+
+fn register_robot(registry: &ComponentRegistry) {
+    registry.register(component("arm_1", ComponentType::Actuator)).unwrap();
+    registry.register(component("arm_2", ComponentType::Actuator)).unwrap();
+    registry.register(component("gripper", ComponentType::Hybrid)).unwrap();
+    registry.register(component("camera", ComponentType::Sensor)).unwrap();
+}
+
+#[tokio::test]
+async fn test_estop_stops_motors_and_holds_safe_state() {
+    let registry = Arc::new(ComponentRegistry::new(5));
+    register_robot(&registry);
+    let channel = Arc::new(RecordingChannel { unreachable: vec!["arm_2"], stopped: Mutex::new(Vec::new()) });
+    let estop = EmergencyStop::new(registry, 20);
+    estop.add_channel(channel.clone());
+    assert!(estop.check_action("arm_1").is_ok());
+    assert!(estop.clear("operator", "nothing to clear").is_err());
+
+    let report = estop.engage("operator", "person in workcell").await;
+    assert_eq!(estop.state(), SafeState::Stopped);
+    let mut stopped: Vec<&str> = report.stopped.iter().map(|id| id.as_str()).collect();
+    stopped.sort();
+    assert_eq!(stopped, vec!["arm_1", "gripper"]);
+    // The unreachable arm timed out; sensors are never sent a stop
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(report.failed[0].0.as_str(), "arm_2");
+    assert!(!channel.stopped.lock().iter().any(|id| id == "camera"));
+
+    assert!(estop.check_action("arm_1").is_err());
+    assert!(estop.clear(" ", "no operator").is_err());
+    estop.clear("operator", "workcell clear").unwrap();
+    assert_eq!(estop.state(), SafeState::Operational);
+    assert!(estop.check_action("arm_1").is_ok());
+
+    // The whole sequence is in the audit log, in order
+    let log = estop.audit_log();
+    assert!(log.iter().all(|entry| entry.stop_id == report.stop_id));
+    assert!(matches!(&log[0].event, EStopEvent::Engaged { reason, .. } if reason == "person in workcell"));
+    assert!(log.iter().any(|entry| matches!(&entry.event, EStopEvent::StopFailed { component_id, .. } if component_id.as_str() == "arm_2")));
+    let tail: Vec<&EStopEvent> = log.iter().rev().take(3).map(|entry| &entry.event).collect();
+    assert!(matches!(tail[0], EStopEvent::Cleared { actor, .. } if actor == "operator"));
+    assert!(matches!(tail[1], EStopEvent::ActionRejected { target } if target == "arm_1"));
+    assert_eq!(*tail[2], EStopEvent::SafeStateEntered { stopped: 2, failed: 1 });
+}
+
+#[tokio::test]
+async fn test_cns_emergency_stop() {
+    let cns = CentralNervousSystem::new(CnsConfig::default()).unwrap();
+    register_robot(cns.registry());
+    let channel = Arc::new(RecordingChannel { unreachable: vec![], stopped: Mutex::new(Vec::new()) });
+    cns.estop().add_channel(channel.clone());
+
+    let report = cns.emergency_stop("operator", "test").await.unwrap();
+    assert_eq!(report.stopped.len(), 3);
+    assert_eq!(cns.safe_state(), SafeState::Stopped);
+    assert!(cns.safety_validator().read().is_emergency_stop_active());
+
+    cns.clear_emergency_stop("operator", "test done").unwrap();
+    assert_eq!(cns.safe_state(), SafeState::Operational);
+    assert!(!cns.safety_validator().read().is_emergency_stop_active());
+
+    let disabled = CentralNervousSystem::new(CnsConfig { enable_emergency_stop: false, ..CnsConfig::default() }).unwrap();
+    assert!(disabled.emergency_stop("operator", "test").await.is_err());
+}
@@ -0,0 +1,154 @@
+//! Tests for firmware update orchestration
+
+use async_trait::async_trait;
+use narayana_cns::update::{TargetState, UpdateMessage};
+use narayana_cns::{
+    Capability, CnsError, ComponentId, ComponentInfo, ComponentRegistry, ComponentState, ComponentType,
+    RolloutStatus, TransportConfig, UpdateChannel, UpdateConfig, UpdateOrchestrator,
+};
+use narayana_cns::transport::TransportType;
+use narayana_core::config::BlobStoreConfig;
+use narayana_storage::blob_store::BlobStore;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Simulated firmware: flashes on commit and reports the new version,
+/// except for components in `broken` that fault on the new image
+struct SimulatedFirmware {
+    registry: Arc<ComponentRegistry>,
+    broken: Vec<ComponentId>,
+    messages: Mutex<Vec<(ComponentId, UpdateMessage)>>,
+}
+
+#[async_trait]
+impl UpdateChannel for SimulatedFirmware {
+    async fn deliver(&self, component: &ComponentInfo, message: &UpdateMessage) -> Result<(), CnsError> {
+        self.messages.lock().push((component.id.clone(), message.clone()));
+        if let UpdateMessage::Commit { version, .. } = message {
+            if self.broken.contains(&component.id) && version == "2.0" {
+                self.registry.update_state(&component.id, ComponentState::Error("Boot loop".to_string()))?;
+            } else {
+                self.registry.report_version(&component.id, version.clone())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl SimulatedFirmware {
+    fn messages_for(&self, id: &str) -> Vec<UpdateMessage> {
+        self.messages.lock().iter()
+            .filter(|(component_id, _)| component_id.as_str() == id)
+            .map(|(_, message)| message.clone())
+            .collect()
+    }
+}
+
+fn register_motors(registry: &ComponentRegistry, ids: &[&str]) {
+    for id in ids {
+        let mut component = ComponentInfo::new(
+            ComponentId::from(*id),
+            format!("motor_{}", id),
+            ComponentType::Actuator,
+            vec![Capability::Simple("move".to_string())],
+            TransportConfig {
+                transport_type: TransportType::Serial,
+                config: HashMap::new(),
+            },
+        );
+        component.metadata.insert("hardware".to_string(), serde_json::json!("servo-x1"));
+        registry.register(component).unwrap();
+    }
+}
+
+fn setup(ids: &[&str], broken: &[&str]) -> (Arc<ComponentRegistry>, Arc<SimulatedFirmware>, UpdateOrchestrator, tempfile::TempDir) {
+    let dir = tempfile::tempdir().unwrap();
+    let blobs = Arc::new(BlobStore::open(dir.path(), &BlobStoreConfig::default()).unwrap());
+    let registry = Arc::new(ComponentRegistry::new(5));
+    register_motors(&registry, ids);
+    let firmware = Arc::new(SimulatedFirmware {
+        registry: registry.clone(),
+        broken: broken.iter().map(|id| ComponentId::from(*id)).collect(),
+        messages: Mutex::new(Vec::new()),
+    });
+    let config = UpdateConfig {
+        stages: vec![0.5, 1.0],
+        health_window_ms: 20,
+        install_timeout_ms: 1000,
+        chunk_size: 4,
+        poll_interval_ms: 5,
+        ..UpdateConfig::default()
+    };
+    let orchestrator = UpdateOrchestrator::new(config, registry.clone(), blobs, firmware.clone()).unwrap();
+    (registry, firmware, orchestrator, dir)
+}
+
+#[test]
+fn test_update_config_validation() {
+    assert!(UpdateConfig::default().validate().is_ok());
+    assert!(UpdateConfig { stages: vec![0.5, 0.2, 1.0], ..UpdateConfig::default() }.validate().is_err());
+    assert!(UpdateConfig { stages: vec![0.5], ..UpdateConfig::default() }.validate().is_err());
+    assert!(UpdateConfig { chunk_size: 0, ..UpdateConfig::default() }.validate().is_err());
+}
+
+#[tokio::test]
+async fn test_staged_rollout_completes() {
+    let (registry, firmware, orchestrator, _dir) = setup(&["a", "b", "c", "d"], &[]);
+    let image = b"firmware image v2".to_vec();
+    let artifact = orchestrator.upload_artifact(ComponentType::Actuator, Some("servo-x1".to_string()), "2.0", &image).unwrap();
+    assert!(orchestrator.start_rollout(ComponentType::Actuator, None, "2.0").is_err());
+
+    let rollout = orchestrator.start_rollout(ComponentType::Actuator, Some("servo-x1"), "2.0").unwrap();
+    assert_eq!(rollout.stage_count, 2);
+    assert!(orchestrator.start_rollout(ComponentType::Actuator, Some("servo-x1"), "2.0").is_err());
+
+    // The first advance only flashes the first half
+    let rollout = orchestrator.advance(&rollout.id).await.unwrap();
+    assert_eq!(rollout.current_stage, 0);
+    assert!(!firmware.messages_for("a").is_empty());
+    assert!(firmware.messages_for("c").is_empty());
+
+    let rollout = orchestrator.run_rollout(&rollout.id).await.unwrap();
+    assert_eq!(rollout.status, RolloutStatus::Completed);
+    assert!(rollout.targets.iter().all(|t| t.state == TargetState::Updated));
+    for id in ["a", "b", "c", "d"] {
+        let component = registry.get(&ComponentId::from(id)).unwrap();
+        assert_eq!(component.version, "2.0");
+        assert!(component.is_available());
+    }
+
+    // The image arrives intact, chunk by chunk
+    let messages = firmware.messages_for("d");
+    assert!(matches!(&messages[0], UpdateMessage::Begin { sha256, .. } if sha256 == artifact.blob.id()));
+    let received: Vec<u8> = messages.iter()
+        .filter_map(|m| match m {
+            UpdateMessage::Chunk { data, .. } => Some(hex::decode(data).unwrap()),
+            _ => None,
+        })
+        .flatten()
+        .collect();
+    assert_eq!(received, image);
+}
+
+#[tokio::test]
+async fn test_failed_stage_rolls_back() {
+    let (registry, firmware, orchestrator, _dir) = setup(&["a", "b"], &["a"]);
+    orchestrator.upload_artifact(ComponentType::Actuator, Some("servo-x1".to_string()), "1.0.0", b"firmware v1").unwrap();
+    orchestrator.upload_artifact(ComponentType::Actuator, Some("servo-x1".to_string()), "2.0", b"firmware v2").unwrap();
+
+    let rollout = orchestrator.start_rollout(ComponentType::Actuator, Some("servo-x1"), "2.0").unwrap();
+    let rollout = orchestrator.run_rollout(&rollout.id).await.unwrap();
+    assert_eq!(rollout.status, RolloutStatus::RolledBack);
+
+    let target = |id: &str| rollout.targets.iter().find(|t| t.component_id.as_str() == id).unwrap().state.clone();
+    assert_eq!(target("a"), TargetState::RolledBack);
+    // The second stage never started
+    assert_eq!(target("b"), TargetState::Waiting);
+    assert!(firmware.messages_for("b").is_empty());
+
+    // The previous image was re-installed
+    let last = firmware.messages_for("a").pop().unwrap();
+    assert!(matches!(last, UpdateMessage::Commit { version, .. } if version == "1.0.0"));
+    assert_eq!(registry.get(&ComponentId::from("a")).unwrap().version, "1.0.0");
+}
@@ -0,0 +1,324 @@
+// Arrow result format
+// Query results as Arrow record batches, written as an Arrow IPC stream
+// (application/vnd.apache.arrow.stream) so Python/R clients can load them with
+// pyarrow or arrow without parsing JSON. Fixed-width columns hand their Vec to
+// Arrow without copying; string and binary columns are copied into Arrow's
+// offset layout. Timestamps are milliseconds since the epoch, dates days.
+
+use crate::column::Column;
+use crate::schema::{DataType, Field};
+use crate::error::{Error, Result};
+use arrow_array::types::{
+    Date32Type, Float32Type, Float64Type, Int16Type, Int32Type, Int64Type, Int8Type,
+    TimestampMillisecondType, UInt16Type, UInt32Type, UInt64Type, UInt8Type,
+};
+use arrow_array::{ArrayRef, BinaryArray, BooleanArray, PrimitiveArray, RecordBatch, StringArray};
+use arrow_schema::{DataType as ArrowType, Field as ArrowField, Schema as ArrowSchema, SchemaRef, TimeUnit};
+use std::sync::Arc;
+
+pub const ARROW_STREAM_MEDIA_TYPE: &str = "application/vnd.apache.arrow.stream";
+
+/// Arrow type a column converts to
+pub fn arrow_type(column: &Column) -> ArrowType {
+    match column {
+        Column::Int8(_) => ArrowType::Int8,
+        Column::Int16(_) => ArrowType::Int16,
+        Column::Int32(_) => ArrowType::Int32,
+        Column::Int64(_) => ArrowType::Int64,
+        Column::UInt8(_) => ArrowType::UInt8,
+        Column::UInt16(_) => ArrowType::UInt16,
+        Column::UInt32(_) => ArrowType::UInt32,
+        Column::UInt64(_) => ArrowType::UInt64,
+        Column::Float32(_) => ArrowType::Float32,
+        Column::Float64(_) => ArrowType::Float64,
+        Column::Boolean(_) => ArrowType::Boolean,
+        Column::String(_) => ArrowType::Utf8,
+        Column::Binary(_) => ArrowType::Binary,
+        Column::Timestamp(_) => ArrowType::Timestamp(TimeUnit::Millisecond, None),
+        Column::Date(_) => ArrowType::Date32,
+    }
+}
+
+/// Arrow type a field of `data_type` is stored as: Json is UTF-8 text, Point
+/// and Geometry their encoded bytes. Arrays and maps have no Column form.
+pub fn field_arrow_type(data_type: &DataType) -> Result<ArrowType> {
+    Ok(match data_type {
+        DataType::Int8 => ArrowType::Int8,
+        DataType::Int16 => ArrowType::Int16,
+        DataType::Int32 => ArrowType::Int32,
+        DataType::Int64 => ArrowType::Int64,
+        DataType::UInt8 => ArrowType::UInt8,
+        DataType::UInt16 => ArrowType::UInt16,
+        DataType::UInt32 => ArrowType::UInt32,
+        DataType::UInt64 => ArrowType::UInt64,
+        DataType::Float32 => ArrowType::Float32,
+        DataType::Float64 => ArrowType::Float64,
+        DataType::Boolean => ArrowType::Boolean,
+        DataType::String | DataType::Json => ArrowType::Utf8,
+        DataType::Binary | DataType::Point | DataType::Geometry => ArrowType::Binary,
+        DataType::Timestamp => ArrowType::Timestamp(TimeUnit::Millisecond, None),
+        DataType::Date => ArrowType::Date32,
+        DataType::Nullable(inner) => field_arrow_type(inner)?,
+        DataType::Array(_) | DataType::Map(_, _) => {
+            return Err(Error::SchemaMismatch(format!("{:?} columns have no Arrow form", data_type)))
+        }
+    })
+}
+
+/// Arrow schema for reading `fields` as columns. Fields are not nullable:
+/// columns carry no null bitmap.
+pub fn arrow_schema(fields: &[Field]) -> Result<ArrowSchema> {
+    let fields = fields
+        .iter()
+        .map(|field| Ok(ArrowField::new(field.name.as_str(), field_arrow_type(&field.data_type)?, false)))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(ArrowSchema::new(fields))
+}
+
+/// Record batch of columns under an existing schema, e.g. one from `arrow_schema`
+pub fn record_batch_with_schema(schema: &SchemaRef, columns: Vec<Column>) -> Result<RecordBatch> {
+    if columns.is_empty() && schema.fields().is_empty() {
+        return Ok(RecordBatch::new_empty(schema.clone()));
+    }
+    let arrays = columns.into_iter().map(to_array).collect();
+    RecordBatch::try_new(schema.clone(), arrays).map_err(arrow_error)
+}
+
+/// Convert a column into an Arrow array, reusing its buffer where the layouts match
+pub fn to_array(column: Column) -> ArrayRef {
+    match column {
+        Column::Int8(v) => Arc::new(PrimitiveArray::<Int8Type>::from(v)),
+        Column::Int16(v) => Arc::new(PrimitiveArray::<Int16Type>::from(v)),
+        Column::Int32(v) => Arc::new(PrimitiveArray::<Int32Type>::from(v)),
+        Column::Int64(v) => Arc::new(PrimitiveArray::<Int64Type>::from(v)),
+        Column::UInt8(v) => Arc::new(PrimitiveArray::<UInt8Type>::from(v)),
+        Column::UInt16(v) => Arc::new(PrimitiveArray::<UInt16Type>::from(v)),
+        Column::UInt32(v) => Arc::new(PrimitiveArray::<UInt32Type>::from(v)),
+        Column::UInt64(v) => Arc::new(PrimitiveArray::<UInt64Type>::from(v)),
+        Column::Float32(v) => Arc::new(PrimitiveArray::<Float32Type>::from(v)),
+        Column::Float64(v) => Arc::new(PrimitiveArray::<Float64Type>::from(v)),
+        Column::Boolean(v) => Arc::new(BooleanArray::from(v)),
+        Column::String(v) => Arc::new(StringArray::from(v)),
+        Column::Binary(v) => Arc::new(BinaryArray::from_iter_values(v)),
+        Column::Timestamp(v) => Arc::new(PrimitiveArray::<TimestampMillisecondType>::from(v)),
+        Column::Date(v) => Arc::new(PrimitiveArray::<Date32Type>::from(v)),
+    }
+}
+
+/// Build a record batch from columns and their field names (same length, same order)
+pub fn record_batch(names: &[String], columns: Vec<Column>) -> Result<RecordBatch> {
+    if names.len() != columns.len() {
+        return Err(Error::SchemaMismatch(format!(
+            "{} field names for {} columns",
+            names.len(),
+            columns.len()
+        )));
+    }
+    let fields: Vec<ArrowField> = names
+        .iter()
+        .zip(&columns)
+        .map(|(name, column)| ArrowField::new(name.as_str(), arrow_type(column), false))
+        .collect();
+    let schema = Arc::new(ArrowSchema::new(fields));
+    if columns.is_empty() {
+        return Ok(RecordBatch::new_empty(schema));
+    }
+    let arrays = columns.into_iter().map(to_array).collect();
+    RecordBatch::try_new(schema, arrays).map_err(|e| Error::Serialization(format!("Arrow: {}", e)))
+}
+
+/// Split columns into record batches of at most `max_rows` rows. The batches
+/// are slices of one batch, so they share its buffers instead of copying.
+pub fn record_batches(names: &[String], columns: Vec<Column>, max_rows: usize) -> Result<Vec<RecordBatch>> {
+    let batch = record_batch(names, columns)?;
+    let rows = batch.num_rows();
+    if rows == 0 {
+        return Ok(vec![batch]);
+    }
+    let max_rows = max_rows.max(1);
+    Ok((0..rows)
+        .step_by(max_rows)
+        .map(|offset| batch.slice(offset, max_rows.min(rows - offset)))
+        .collect())
+}
+
+/// Write record batches as one Arrow IPC stream; all batches must share the first one's schema
+pub fn write_ipc_stream(batches: &[RecordBatch]) -> Result<Vec<u8>> {
+    let Some(first) = batches.first() else {
+        return Err(Error::Serialization("Arrow: no record batches to write".to_string()));
+    };
+    let error = |e: arrow_schema::ArrowError| Error::Serialization(format!("Arrow: {}", e));
+    let mut writer = arrow_ipc::writer::StreamWriter::try_new(Vec::new(), &first.schema()).map_err(error)?;
+    for batch in batches {
+        writer.write(batch).map_err(error)?;
+    }
+    writer.into_inner().map_err(error)
+}
+
+/// Columns with their field names as an Arrow IPC stream of one record batch
+pub fn columns_to_ipc(names: &[String], columns: Vec<Column>) -> Result<Vec<u8>> {
+    write_ipc_stream(&[record_batch(names, columns)?])
+}
+
+fn arrow_error(e: arrow_schema::ArrowError) -> Error {
+    Error::Serialization(format!("Arrow: {}", e))
+}
+
+/// Writes an Arrow IPC stream piece by piece, so a response can send each
+/// batch as it is read instead of buffering the whole stream
+pub struct IpcStreamChunks {
+    writer: arrow_ipc::writer::StreamWriter<Vec<u8>>,
+}
+
+impl IpcStreamChunks {
+    /// Start a stream; the schema message is part of the first chunk
+    pub fn new(schema: &ArrowSchema) -> Result<Self> {
+        let writer = arrow_ipc::writer::StreamWriter::try_new(Vec::new(), schema).map_err(arrow_error)?;
+        Ok(Self { writer })
+    }
+
+    /// Encode a batch, returning every byte written since the previous chunk
+    pub fn push(&mut self, batch: &RecordBatch) -> Result<Vec<u8>> {
+        self.writer.write(batch).map_err(arrow_error)?;
+        Ok(std::mem::take(self.writer.get_mut()))
+    }
+
+    /// End the stream, returning the remaining bytes and the end-of-stream marker
+    pub fn finish(mut self) -> Result<Vec<u8>> {
+        self.writer.finish().map_err(arrow_error)?;
+        Ok(std::mem::take(self.writer.get_mut()))
+    }
+}
+
+/// Whether an Accept header asks for Arrow/// Whether an Accept header asks for Arrow: the stream media type is listed
+/// with a non-zero quality
+pub fn accepts_arrow(accept: &str) -> bool {
+    accept.split(',').any(|entry| {
+        let mut parts = entry.split(';').map(str::trim);
+        let media_type = parts.next().unwrap_or_default();
+        let zero_quality = parts.any(|param| {
+            param
+                .strip_prefix("q=")
+                .and_then(|q| q.parse::<f32>().ok())
+                .is_some_and(|q| q <= 0.0)
+        });
+        media_type.eq_ignore_ascii_case(ARROW_STREAM_MEDIA_TYPE) && !zero_quality
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::cast::AsArray;
+    use arrow_array::Array;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_ipc_stream_round_trip() {
+        let columns = vec![
+            Column::Int64(vec![1, 2, 3]),
+            Column::String(vec!["a".to_string(), "".to_string(), "ccc".to_string()]),
+            Column::Boolean(vec![true, false, true]),
+            Column::Timestamp(vec![1_700_000_000_000, 0, -1]),
+            Column::Binary(vec![vec![0xff], Vec::new(), vec![1, 2]]),
+        ];
+        let bytes = columns_to_ipc(&names(&["id", "name", "ok", "at", "blob"]), columns).unwrap();
+
+        let reader = arrow_ipc::reader::StreamReader::try_new(std::io::Cursor::new(bytes), None).unwrap();
+        let batches: Vec<RecordBatch> = reader.map(|b| b.unwrap()).collect();
+        assert_eq!(batches.len(), 1);
+        let batch = &batches[0];
+        assert_eq!(batch.num_rows(), 3);
+        let schema = batch.schema();
+        assert_eq!(schema.field(0).name(), "id");
+        assert_eq!(schema.field(3).data_type(), &ArrowType::Timestamp(TimeUnit::Millisecond, None));
+        assert_eq!(batch.column(0).as_primitive::<Int64Type>().values(), &[1, 2, 3]);
+        assert_eq!(batch.column(1).as_string::<i32>().value(2), "ccc");
+        assert!(!batch.column(2).as_boolean().value(1));
+        assert_eq!(batch.column(3).as_primitive::<TimestampMillisecondType>().value(2), -1);
+        assert_eq!(batch.column(4).as_binary::<i32>().value(0), &[0xff]);
+        assert_eq!(batch.column(0).null_count(), 0);
+    }
+
+    #[test]
+    fn test_fixed_width_columns_are_not_copied() {
+        let values = vec![1.5f64, 2.5, 3.5];
+        let pointer = values.as_ptr();
+        let array = to_array(Column::Float64(values));
+        assert_eq!(array.as_primitive::<Float64Type>().values().as_ptr(), pointer);
+    }
+
+    #[test]
+    fn test_mismatched_columns_are_rejected() {
+        let columns = vec![Column::Int32(vec![1, 2]), Column::Int32(vec![1])];
+        assert!(record_batch(&names(&["a", "b"]), columns).is_err());
+        assert!(record_batch(&names(&["a"]), vec![Column::Int32(vec![1]), Column::Int32(vec![2])]).is_err());
+
+        let empty = record_batch(&[], Vec::new()).unwrap();
+        assert_eq!(empty.num_columns(), 0);
+        assert_eq!(empty.num_rows(), 0);
+    }
+
+    #[test]
+    fn test_record_batches_slice_without_copying() {
+        let values: Vec<i64> = (0..10).collect();
+        let pointer = values.as_ptr();
+        let batches = record_batches(&names(&["id"]), vec![Column::Int64(values)], 4).unwrap();
+        let sizes: Vec<usize> = batches.iter().map(|b| b.num_rows()).collect();
+        assert_eq!(sizes, vec![4, 4, 2]);
+        assert_eq!(batches[0].column(0).as_primitive::<Int64Type>().values().as_ptr(), pointer);
+        assert_eq!(batches[2].column(0).as_primitive::<Int64Type>().values(), &[8, 9]);
+
+        let empty = record_batches(&names(&["id"]), vec![Column::Int64(Vec::new())], 4).unwrap();
+        assert_eq!(empty.len(), 1);
+        assert_eq!(empty[0].num_rows(), 0);
+    }
+
+    #[test]
+    fn test_chunked_stream_reads_as_one_stream() {
+        let columns = vec![Column::Int32((0..5).collect()), Column::String((0..5).map(|i| i.to_string()).collect())];
+        let batches = record_batches(&names(&["n", "s"]), columns, 2).unwrap();
+        let mut chunks = IpcStreamChunks::new(&batches[0].schema()).unwrap();
+        let mut bytes = Vec::new();
+        for batch in &batches {
+            let chunk = chunks.push(batch).unwrap();
+            assert!(!chunk.is_empty());
+            bytes.extend(chunk);
+        }
+        bytes.extend(chunks.finish().unwrap());
+
+        let reader = arrow_ipc::reader::StreamReader::try_new(std::io::Cursor::new(bytes), None).unwrap();
+        let read: Vec<RecordBatch> = reader.map(|b| b.unwrap()).collect();
+        assert_eq!(read.len(), 3);
+        assert_eq!(read[2].column(1).as_string::<i32>().value(0), "4");
+    }
+
+    #[test]
+    fn test_schema_batches_use_field_types() {
+        let fields = vec![
+            Field { name: "doc".to_string(), data_type: DataType::Json, nullable: false, default_value: None },
+            Field { name: "at".to_string(), data_type: DataType::Nullable(Box::new(DataType::Timestamp)), nullable: true, default_value: None },
+        ];
+        let schema = Arc::new(arrow_schema(&fields).unwrap());
+        assert_eq!(schema.field(0).data_type(), &ArrowType::Utf8);
+        assert_eq!(schema.field(1).data_type(), &ArrowType::Timestamp(TimeUnit::Millisecond, None));
+
+        let batch = record_batch_with_schema(&schema, vec![Column::String(vec!["{}".to_string()]), Column::Timestamp(vec![5])]).unwrap();
+        assert_eq!(batch.num_rows(), 1);
+        assert!(record_batch_with_schema(&schema, vec![Column::Int32(vec![1]), Column::Timestamp(vec![5])]).is_err());
+        assert!(arrow_schema(&[Field { name: "a".to_string(), data_type: DataType::Array(Box::new(DataType::Int8)), nullable: false, default_value: None }]).is_err());
+    }
+
+    #[test]
+    fn test_accept_negotiation() {
+        assert!(accepts_arrow(ARROW_STREAM_MEDIA_TYPE));
+        assert!(accepts_arrow("application/json;q=0.5, Application/Vnd.Apache.Arrow.Stream"));
+        assert!(!accepts_arrow("application/json"));
+        assert!(!accepts_arrow("*/*"));
+        assert!(!accepts_arrow("application/vnd.apache.arrow.stream;q=0"));
+        assert!(!accepts_arrow("application/vnd.apache.arrow.file"));
+    }
+}
@@ -1,9 +1,15 @@
 // Comprehensive configuration system for NarayanaDB
+// Loaded from TOML, YAML or JSON; every section and field is optional and
+// `NARAYANA__SECTION__FIELD` environment variables override single values.
 
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashMap;
 use std::time::Duration;
 
+/// Prefix of structured environment overrides (`NARAYANA__NETWORK__BIND_PORT=9000`)
+pub const ENV_OVERRIDE_PREFIX: &str = "NARAYANA__";
+
 /// Eviction policy for cache and data
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum EvictionPolicy {
@@ -24,6 +30,7 @@ pub enum ReplicationMode {
     Sync,          // Synchronous replication
     SemiSync,      // Semi-synchronous replication
     Quorum,        // Quorum-based replication
+    MultiPrimary,  // Every region takes writes; see `ReplicationConfig::peers`
 }
 
 /// Consistency level
@@ -38,14 +45,48 @@ pub enum ConsistencyLevel {
 
 /// Connection pool configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ConnectionPoolConfig {
     pub max_connections: usize,
     pub min_connections: usize,
+    #[serde(deserialize_with = "duration::deserialize")]
     pub idle_timeout: Duration,
+    #[serde(deserialize_with = "duration::deserialize")]
     pub max_lifetime: Duration,
+    #[serde(deserialize_with = "duration::deserialize")]
     pub acquire_timeout: Duration,
     pub test_on_acquire: bool,
     pub test_on_idle: bool,
+    /// Per-route admission control for the HTTP API; a request is governed
+    /// by the most specific matching entry, unmatched routes are unlimited
+    pub route_limits: Vec<RouteLimit>,
+}
+
+/// Concurrency limit and bounded wait queue for a group of HTTP routes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RouteLimit {
+    /// Path prefix; `*` matches any single segment (e.g. "/api/v1/tables/*/query")
+    pub path: String,
+    /// Requests served at once
+    pub max_concurrent: usize,
+    /// Requests allowed to wait for a slot; beyond that they get 429
+    pub max_queued: usize,
+    /// How long a queued request waits for a slot before it gets 429
+    #[serde(deserialize_with = "duration::deserialize")]
+    pub queue_timeout: Duration,
+}
+
+impl RouteLimit {
+    pub fn new(path: &str, max_concurrent: usize, max_queued: usize, queue_timeout: Duration) -> Self {
+        Self { path: path.to_string(), max_concurrent, max_queued, queue_timeout }
+    }
+}
+
+impl Default for RouteLimit {
+    fn default() -> Self {
+        Self::new("/", 16, 64, Duration::from_secs(5))
+    }
 }
 
 impl Default for ConnectionPoolConfig {
@@ -58,17 +99,29 @@ impl Default for ConnectionPoolConfig {
             acquire_timeout: Duration::from_secs(30),
             test_on_acquire: false,
             test_on_idle: true,
+            // Heavy analytics, ML and bulk work is capped so it cannot starve
+            // latency-critical robot endpoints (brains, inserts, vectors)
+            route_limits: vec![
+                RouteLimit::new("/api/v1/analytics", 4, 16, Duration::from_secs(5)),
+                RouteLimit::new("/api/v1/ml", 2, 8, Duration::from_secs(5)),
+                RouteLimit::new("/api/v1/tables/*/query", 32, 128, Duration::from_secs(10)),
+                RouteLimit::new("/api/v1/tables/*/bulk", 4, 8, Duration::from_secs(30)),
+            ],
         }
     }
 }
 
 /// Cache configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct CacheConfig {
     pub max_size: usize,
     pub eviction_policy: EvictionPolicy,
+    #[serde(deserialize_with = "duration::deserialize_opt")]
     pub ttl: Option<Duration>,
+    #[serde(deserialize_with = "duration::deserialize_opt")]
     pub max_ttl: Option<Duration>,
+    #[serde(deserialize_with = "duration::deserialize")]
     pub cleanup_interval: Duration,
     pub enable_metrics: bool,
 }
@@ -88,16 +141,39 @@ impl Default for CacheConfig {
 
 /// Replication configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ReplicationConfig {
     pub mode: ReplicationMode,
     pub replica_count: usize,
     pub consistency_level: ConsistencyLevel,
     pub replication_factor: usize,
+    #[serde(deserialize_with = "duration::deserialize")]
     pub sync_timeout: Duration,
     pub enable_auto_failover: bool,
     pub quorum_size: usize,
     pub read_from_replicas: bool,
     pub write_to_all: bool,
+    /// Other regions' deployments that replicated tables exchange changes
+    /// with in `MultiPrimary` mode; this deployment's region is
+    /// `instance.region`
+    pub peers: Vec<ReplicationPeer>,
+    /// How often pending changes are shipped to peers
+    #[serde(deserialize_with = "duration::deserialize")]
+    pub ship_interval: Duration,
+    /// Changes kept for peers that are behind; past this the oldest are
+    /// dropped and counted, and those peers miss them
+    pub max_pending_changes: usize,
+}
+
+/// A deployment in another region
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplicationPeer {
+    pub region: String,
+    /// Base URL of its HTTP API, e.g. `https://eu.example.com:8080`
+    pub url: String,
+    /// Admin token for its API
+    #[serde(default)]
+    pub token: Option<String>,
 }
 
 impl Default for ReplicationConfig {
@@ -112,12 +188,66 @@ impl Default for ReplicationConfig {
             quorum_size: 2,
             read_from_replicas: true,
             write_to_all: false,
+            peers: Vec::new(),
+            ship_interval: Duration::from_secs(1),
+            max_pending_changes: 100_000,
+        }
+    }
+}
+
+/// Raft cluster replicating the table catalog, shard map and replica roles
+/// between nodes; this node's id is `node-{instance.node_id}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClusterConfig {
+    /// Base URL other nodes reach this node's HTTP API on, e.g.
+    /// `http://10.0.0.5:8080`; sent as the address when joining
+    pub advertise_url: Option<String>,
+    /// The other nodes a new cluster starts with; each founding node lists
+    /// the rest. Empty starts a single-node cluster.
+    pub peers: Vec<ClusterPeer>,
+    /// Base URL of a cluster node to join on startup. The node starts with
+    /// no voters and asks to be added, following the leader's address when
+    /// another node answers, until the leader accepts.
+    pub join: Option<String>,
+    /// Join requests sent before the node gives up joining
+    pub join_attempts: u32,
+    /// Wait between join requests that failed or found no leader
+    #[serde(deserialize_with = "duration::deserialize")]
+    pub join_retry_interval: Duration,
+    /// Admin token sent with Raft and join requests to other nodes
+    pub token: Option<String>,
+    /// Timeout of a Raft request to another node; keep it below the 150ms
+    /// election timeout so an unreachable node doesn't delay heartbeats
+    #[serde(deserialize_with = "duration::deserialize")]
+    pub request_timeout: Duration,
+}
+
+/// A founding node of the cluster
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClusterPeer {
+    pub node_id: String,
+    /// Base URL of its HTTP API
+    pub url: String,
+}
+
+impl Default for ClusterConfig {
+    fn default() -> Self {
+        Self {
+            advertise_url: None,
+            peers: Vec::new(),
+            join: None,
+            join_attempts: 30,
+            join_retry_interval: Duration::from_secs(2),
+            token: None,
+            request_timeout: Duration::from_millis(100),
         }
     }
 }
 
 /// Instance configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct InstanceConfig {
     pub instance_id: String,
     pub node_id: usize,
@@ -150,16 +280,266 @@ impl Default for InstanceConfig {
 
 /// Storage configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct StorageConfig {
     pub data_dir: String,
+    /// Write-ahead log of the persistent column store
     pub wal_dir: String,
+    /// How often the WAL starts a new segment and deletes the flushed ones
+    #[serde(deserialize_with = "duration::deserialize")]
     pub checkpoint_interval: Duration,
+    #[serde(deserialize_with = "duration::deserialize")]
     pub compaction_interval: Duration,
     pub max_file_size: u64,
     pub enable_compression: bool,
     pub compression_type: String,
     pub enable_encryption: bool,
     pub encryption_algorithm: String,
+    #[serde(default)]
+    pub recovery: RecoveryConfig,
+    /// Which column store holds each database
+    pub backends: StorageBackendsConfig,
+    /// Binary artifacts (audio, frames, assets, models) under `data_dir/blobs`
+    pub blobs: BlobStoreConfig,
+    /// Scheduled maintenance jobs
+    pub jobs: JobsConfig,
+    /// Background runtime invariant checks
+    pub invariants: InvariantsConfig,
+    /// Size limits per table, database and in total, and the low-disk read-only guard
+    pub quotas: QuotasConfig,
+}
+
+/// Size and row limits of one table, one database or the whole store
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuotaLimits {
+    /// Stored (compressed) bytes
+    pub max_bytes: Option<u64>,
+    pub max_rows: Option<u64>,
+}
+
+impl QuotaLimits {
+    pub fn is_unlimited(&self) -> bool {
+        self.max_bytes.is_none() && self.max_rows.is_none()
+    }
+}
+
+/// Storage quotas
+///
+/// Writes that would take a table, its database or the whole store past its
+/// limits are refused. A warning event goes out once usage reaches
+/// `warn_ratio` of a limit. When free space on the `data_dir` disk drops
+/// below `read_only_below_free_percent`, every write is refused until it is
+/// back above `resume_above_free_percent`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuotasConfig {
+    pub enabled: bool,
+    /// Limits of the whole store
+    pub total: QuotaLimits,
+    /// Limits of tables without an entry in `tables`
+    pub default_table: QuotaLimits,
+    /// Limits of databases without an entry in `databases`
+    pub default_database: QuotaLimits,
+    /// Limits by database name
+    pub databases: HashMap<String, QuotaLimits>,
+    /// Limits by "database.table"
+    pub tables: HashMap<String, QuotaLimits>,
+    /// Share of a limit (0-1) at which a warning is published
+    pub warn_ratio: f64,
+    /// Free disk space (percent) below which storage turns read-only; 0 disables
+    pub read_only_below_free_percent: f64,
+    /// Free disk space (percent) at which writes are accepted again
+    pub resume_above_free_percent: f64,
+    /// How often usage is recounted and free disk space checked
+    #[serde(deserialize_with = "duration::deserialize")]
+    pub check_interval: Duration,
+}
+
+impl Default for QuotasConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            total: QuotaLimits::default(),
+            default_table: QuotaLimits::default(),
+            default_database: QuotaLimits::default(),
+            databases: HashMap::new(),
+            tables: HashMap::new(),
+            warn_ratio: 0.8,
+            read_only_below_free_percent: 5.0,
+            resume_above_free_percent: 10.0,
+            check_interval: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Runtime invariant checks (row counts, event ids, schema consistency)
+///
+/// Every `interval` each table and event stream is checked with probability
+/// `sample_rate`; violations are kept in `data_dir/invariant_violations.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InvariantsConfig {
+    pub enabled: bool,
+    #[serde(deserialize_with = "duration::deserialize")]
+    pub interval: Duration,
+    /// Chance (0-1) that a table or stream is checked on a pass
+    pub sample_rate: f64,
+    /// Invariants to check: row_counts, monotonic_event_ids, schema_consistency
+    pub checks: Vec<String>,
+}
+
+/// Invariant names understood by `storage.invariants.checks`
+pub const INVARIANT_CHECKS: [&str; 3] = ["row_counts", "monotonic_event_ids", "schema_consistency"];
+
+impl Default for InvariantsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval: Duration::from_secs(600),
+            sample_rate: 0.1,
+            checks: INVARIANT_CHECKS.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+}
+
+/// Maintenance jobs (compaction, consolidation, backups, analyze)
+///
+/// Schedules are cron expressions in UTC ("minute hour day month weekday");
+/// an empty schedule leaves the job to manual triggers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JobsConfig {
+    /// Run jobs on their schedules; when off, jobs start paused (triggering by hand works either way)
+    pub enabled: bool,
+    /// Jobs running at once
+    pub max_concurrent: usize,
+    pub compaction_schedule: String,
+    pub consolidation_schedule: String,
+    pub backup_schedule: String,
+    pub analyze_schedule: String,
+    /// Attempts per run before a failure is final
+    pub max_attempts: u32,
+    /// Backups kept under `data_dir/backups`
+    pub backup_retention: usize,
+    /// Ingest connector polls running at once (within `max_concurrent`)
+    pub ingest_concurrency: usize,
+}
+
+impl Default for JobsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_concurrent: 2,
+            compaction_schedule: "0 * * * *".to_string(),
+            consolidation_schedule: "*/15 * * * *".to_string(),
+            backup_schedule: "0 3 * * *".to_string(),
+            analyze_schedule: "*/30 * * * *".to_string(),
+            max_attempts: 3,
+            backup_retention: 7,
+            ingest_concurrency: 2,
+        }
+    }
+}
+
+/// Content-addressed blob store
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BlobStoreConfig {
+    /// Blobs are split into chunks of this many bytes; identical chunks are stored once
+    pub chunk_size: usize,
+    /// Largest accepted upload
+    pub max_blob_size: u64,
+    /// Chunk compression: none, lz4, zstd or snappy (already-compressed media gains little)
+    pub compression: String,
+    /// Encrypt chunks with AES-256-GCM
+    pub encrypt: bool,
+    /// 32-byte key file; created on first use. Defaults to `data_dir/blobs/blob.key`
+    pub key_file: Option<String>,
+}
+
+impl Default for BlobStoreConfig {
+    fn default() -> Self {
+        Self {
+            chunk_size: 1024 * 1024,
+            max_blob_size: 1024 * 1024 * 1024,
+            compression: "none".to_string(),
+            encrypt: false,
+            key_file: None,
+        }
+    }
+}
+
+/// Column store implementations a database can live in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackendKind {
+    /// Block files under `data_dir/columnar`
+    #[default]
+    FileSystem,
+    /// RocksDB under `data_dir/rocksdb`
+    RocksDb,
+    /// Memory only, optionally snapshotted to `data_dir/memory.snapshot`
+    Memory,
+}
+
+/// Backend selection per database
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageBackendsConfig {
+    /// Backend for databases without an entry in `databases`
+    pub default: StorageBackendKind,
+    /// Database name -> backend
+    pub databases: HashMap<String, StorageBackendKind>,
+    /// How often in-memory databases are snapshotted to disk (0 disables
+    /// snapshots; memory tables are then lost on restart)
+    pub memory_snapshot_interval_secs: u64,
+}
+
+impl Default for StorageBackendsConfig {
+    fn default() -> Self {
+        Self {
+            default: StorageBackendKind::FileSystem,
+            databases: HashMap::new(),
+            memory_snapshot_interval_secs: 60,
+        }
+    }
+}
+
+impl StorageBackendsConfig {
+    pub fn backend_for(&self, database: &str) -> StorageBackendKind {
+        self.databases.get(database).copied().unwrap_or(self.default)
+    }
+
+    /// Every backend some database may use
+    pub fn kinds(&self) -> Vec<StorageBackendKind> {
+        let mut kinds = vec![self.default];
+        for kind in self.databases.values() {
+            if !kinds.contains(kind) {
+                kinds.push(*kind);
+            }
+        }
+        kinds
+    }
+}
+
+/// Startup recovery behaviour
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RecoveryConfig {
+    /// Start serving once tables begin loading; tables not loaded yet answer
+    /// 503 and readiness reports degraded. When false, readiness stays down
+    /// until every table is loaded.
+    pub serve_while_loading: bool,
+    /// Copies of `data_dir/columnar` (replica mirrors, restored backups)
+    /// that corrupted blocks are rebuilt from, tried in order
+    pub rebuild_sources: Vec<String>,
+}
+
+impl Default for RecoveryConfig {
+    fn default() -> Self {
+        Self { serve_while_loading: true, rebuild_sources: Vec::new() }
+    }
 }
 
 impl Default for StorageConfig {
@@ -174,14 +554,23 @@ impl Default for StorageConfig {
             compression_type: "lz4".to_string(),
             enable_encryption: false,
             encryption_algorithm: "aes256-gcm".to_string(),
+            recovery: RecoveryConfig::default(),
+            backends: StorageBackendsConfig::default(),
+            blobs: BlobStoreConfig::default(),
+            jobs: JobsConfig::default(),
+            invariants: InvariantsConfig::default(),
+            quotas: QuotasConfig::default(),
         }
     }
 }
 
 /// Query configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct QueryConfig {
+    #[serde(deserialize_with = "duration::deserialize")]
     pub max_query_timeout: Duration,
+    #[serde(deserialize_with = "duration::deserialize")]
     pub default_timeout: Duration,
     pub enable_query_cache: bool,
     pub query_cache_size: usize,
@@ -190,6 +579,8 @@ pub struct QueryConfig {
     pub max_parallelism: usize,
     pub enable_query_planning: bool,
     pub enable_query_optimization: bool,
+    /// Explicit transactions opened by clients
+    pub transactions: TransactionsConfig,
 }
 
 impl Default for QueryConfig {
@@ -204,12 +595,49 @@ impl Default for QueryConfig {
             max_parallelism: num_cpus::get(),
             enable_query_planning: true,
             enable_query_optimization: true,
+            transactions: TransactionsConfig::default(),
+        }
+    }
+}
+
+/// Explicit client transactions
+///
+/// A transaction that runs no statement for its timeout (`default_timeout`
+/// unless the client asks for another, at most `max_timeout`) is rolled back
+/// by the reaper, which runs every `reap_interval`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TransactionsConfig {
+    /// Open transactions across all clients
+    pub max_active: usize,
+    /// Open transactions per client
+    pub max_per_owner: usize,
+    #[serde(deserialize_with = "duration::deserialize")]
+    pub default_timeout: Duration,
+    #[serde(deserialize_with = "duration::deserialize")]
+    pub max_timeout: Duration,
+    /// Rows one transaction may stage before it commits
+    pub max_staged_rows: usize,
+    #[serde(deserialize_with = "duration::deserialize")]
+    pub reap_interval: Duration,
+}
+
+impl Default for TransactionsConfig {
+    fn default() -> Self {
+        Self {
+            max_active: 1000,
+            max_per_owner: 16,
+            default_timeout: Duration::from_secs(30),
+            max_timeout: Duration::from_secs(600),
+            max_staged_rows: 1_000_000,
+            reap_interval: Duration::from_secs(1),
         }
     }
 }
 
 /// Network configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct NetworkConfig {
     pub bind_address: String,
     pub bind_port: u16,
@@ -220,9 +648,174 @@ pub struct NetworkConfig {
     pub cors_origins: Vec<String>,
     pub max_request_size: usize,
     pub enable_compression: bool,
+    #[serde(deserialize_with = "duration::deserialize")]
     pub keep_alive_timeout: Duration,
+    #[serde(deserialize_with = "duration::deserialize")]
     pub read_timeout: Duration,
+    #[serde(deserialize_with = "duration::deserialize")]
     pub write_timeout: Duration,
+    /// CORS methods/headers/credentials (origins are `cors_origins`)
+    #[serde(default)]
+    pub cors: CorsPolicyConfig,
+    #[serde(default)]
+    pub security_headers: SecurityHeadersConfig,
+    /// Per-route overrides; the longest matching path prefix wins
+    #[serde(default)]
+    pub route_headers: Vec<RouteHeadersOverride>,
+    /// Idempotency-Key handling on mutating endpoints
+    #[serde(default)]
+    pub idempotency: IdempotencyConfig,
+    /// Hosting of the built web UI from disk
+    #[serde(default)]
+    pub static_files: StaticFilesConfig,
+    /// gRPC endpoint for the brain service (narayana-api/proto/brain.proto)
+    #[serde(default)]
+    pub grpc: GrpcConfig,
+}
+
+/// gRPC listener next to the HTTP API; calls need the same bearer tokens
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GrpcConfig {
+    pub enabled: bool,
+    /// Listens on `network.bind_address` at this port
+    pub port: u16,
+}
+
+impl Default for GrpcConfig {
+    fn default() -> Self {
+        Self { enabled: true, port: 50051 }
+    }
+}
+
+/// Static hosting for the web UI (a single-page app build such as `narayana-ui/dist`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StaticFilesConfig {
+    /// Directory holding the built UI; when unset, `/` redirects to `dev_server_url`
+    pub directory: Option<String>,
+    pub index: String,
+    /// Serve `index` for unknown extension-less paths so client-side routes resolve
+    pub spa_fallback: bool,
+    /// Compress text assets once and keep them in memory. `.br`/`.gz` files
+    /// next to an asset are served when present regardless of this setting.
+    pub precompress: bool,
+    pub precompress_min_bytes: usize,
+    /// Memory bound for compressed variants
+    pub max_cache_bytes: usize,
+    /// Cache-Control max-age for assets without a content hash in their name
+    pub max_age_secs: u64,
+    pub dev_server_url: String,
+}
+
+impl Default for StaticFilesConfig {
+    fn default() -> Self {
+        Self {
+            directory: None,
+            index: "index.html".to_string(),
+            spa_fallback: true,
+            precompress: true,
+            precompress_min_bytes: 1024,
+            max_cache_bytes: 32 * 1024 * 1024,
+            max_age_secs: 3600,
+            dev_server_url: "http://localhost:3000".to_string(),
+        }
+    }
+}
+
+/// Replay of responses for retried requests carrying an `Idempotency-Key` header
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IdempotencyConfig {
+    pub enabled: bool,
+    /// How long a stored response is replayed for
+    pub window_secs: u64,
+    pub max_entries: usize,
+    /// Responses with larger bodies are not stored (the key is released instead)
+    pub max_response_bytes: usize,
+    pub max_key_length: usize,
+}
+
+impl Default for IdempotencyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window_secs: 24 * 60 * 60,
+            max_entries: 10_000,
+            max_response_bytes: 1024 * 1024,
+            max_key_length: 255,
+        }
+    }
+}
+
+/// CORS settings applied to HTTP API responses
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CorsPolicyConfig {
+    pub allowed_methods: Vec<String>,
+    pub allowed_headers: Vec<String>,
+    pub exposed_headers: Vec<String>,
+    /// Never sent together with a `*` origin
+    pub allow_credentials: bool,
+    pub max_age_secs: u64,
+}
+
+impl Default for CorsPolicyConfig {
+    fn default() -> Self {
+        Self {
+            allowed_methods: ["GET", "POST", "PUT", "PATCH", "DELETE", "OPTIONS"]
+                .iter().map(|m| m.to_string()).collect(),
+            allowed_headers: ["Content-Type", "Authorization", "X-API-Key", "Idempotency-Key"]
+                .iter().map(|h| h.to_string()).collect(),
+            exposed_headers: vec!["Idempotent-Replayed".to_string()],
+            allow_credentials: false,
+            max_age_secs: 600,
+        }
+    }
+}
+
+/// Standard browser security headers. `None` leaves a header unset.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SecurityHeadersConfig {
+    pub content_security_policy: Option<String>,
+    /// Strict-Transport-Security max-age; only enable behind TLS
+    pub hsts_max_age_secs: Option<u64>,
+    pub frame_options: Option<String>,
+    pub referrer_policy: Option<String>,
+    pub permissions_policy: Option<String>,
+    pub content_type_nosniff: bool,
+}
+
+impl Default for SecurityHeadersConfig {
+    fn default() -> Self {
+        Self {
+            content_security_policy: Some(
+                "default-src 'self'; script-src 'self'; style-src 'self' 'unsafe-inline'; \
+                 img-src 'self' data: blob:; connect-src 'self' ws: wss:; frame-ancestors 'none'"
+                    .to_string(),
+            ),
+            hsts_max_age_secs: None,
+            frame_options: Some("DENY".to_string()),
+            referrer_policy: Some("no-referrer".to_string()),
+            permissions_policy: Some("camera=(), microphone=(), geolocation=()".to_string()),
+            content_type_nosniff: true,
+        }
+    }
+}
+
+/// Header settings for requests under `path_prefix`; unset fields inherit the global ones
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RouteHeadersOverride {
+    pub path_prefix: String,
+    #[serde(default)]
+    pub enable_cors: Option<bool>,
+    #[serde(default)]
+    pub cors_origins: Option<Vec<String>>,
+    #[serde(default)]
+    pub cors: Option<CorsPolicyConfig>,
+    #[serde(default)]
+    pub security_headers: Option<SecurityHeadersConfig>,
 }
 
 impl Default for NetworkConfig {
@@ -240,12 +833,19 @@ impl Default for NetworkConfig {
             keep_alive_timeout: Duration::from_secs(60),
             read_timeout: Duration::from_secs(30),
             write_timeout: Duration::from_secs(30),
+            cors: CorsPolicyConfig::default(),
+            security_headers: SecurityHeadersConfig::default(),
+            route_headers: Vec::new(),
+            idempotency: IdempotencyConfig::default(),
+            static_files: StaticFilesConfig::default(),
+            grpc: GrpcConfig::default(),
         }
     }
 }
 
 /// Performance configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct PerformanceConfig {
     pub enable_simd: bool,
     pub enable_parallel_processing: bool,
@@ -256,6 +856,81 @@ pub struct PerformanceConfig {
     pub enable_batch_processing: bool,
     pub batch_size: usize,
     pub enable_zero_copy: bool,
+    /// Resources sized ahead of forecast load
+    pub predictive_scaling: PredictiveScalingConfig,
+    /// GPU device placement, memory pools and health
+    pub gpu: GpuConfig,
+}
+
+/// GPU devices: every device of the compiled-in backends takes tasks, with a
+/// memory pool each
+///
+/// Devices failing `unhealthy_after_failures` tasks in a row take no tasks for
+/// `health_cooldown`; the CPU backend only takes tasks when no GPU can.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GpuConfig {
+    /// Off: everything runs on the CPU backend
+    pub enabled: bool,
+    /// Default placement: round_robin, least_loaded, most_free_memory or pinned:<device>
+    pub placement: String,
+    /// Share of a device's memory its pool may hold
+    pub pool_memory_fraction: f64,
+    /// Pool size in bytes for devices that don't report their memory
+    pub pool_size: u64,
+    pub unhealthy_after_failures: u32,
+    #[serde(deserialize_with = "duration::deserialize")]
+    pub health_cooldown: Duration,
+}
+
+impl Default for GpuConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            placement: "least_loaded".to_string(),
+            pool_memory_fraction: 0.5,
+            pool_size: 256 * 1024 * 1024,
+            unhealthy_after_failures: 3,
+            health_cooldown: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Predictive scaling: time-of-day load forecasts sizing the query thread
+/// pool, the cache budget and compaction
+///
+/// Query and ingest rates are sampled every `interval` into `slot_minutes`
+/// slots of the day; resources are sized for the busier of now and
+/// `lead_minutes` ahead, within the min/max bounds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PredictiveScalingConfig {
+    pub enabled: bool,
+    #[serde(deserialize_with = "duration::deserialize")]
+    pub interval: Duration,
+    /// Length of a time-of-day slot; must divide a day
+    pub slot_minutes: u32,
+    pub lead_minutes: u32,
+    pub min_threads: usize,
+    pub max_threads: usize,
+    /// Cache budget bounds in bytes
+    pub min_cache_size: u64,
+    pub max_cache_size: u64,
+}
+
+impl Default for PredictiveScalingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval: Duration::from_secs(60),
+            slot_minutes: 15,
+            lead_minutes: 30,
+            min_threads: num_cpus::get(),
+            max_threads: num_cpus::get() * 4,
+            min_cache_size: 64 * 1024 * 1024,
+            max_cache_size: 1024 * 1024 * 1024,
+        }
+    }
 }
 
 impl Default for PerformanceConfig {
@@ -270,12 +945,15 @@ impl Default for PerformanceConfig {
             enable_batch_processing: true,
             batch_size: 1000,
             enable_zero_copy: true,
+            predictive_scaling: PredictiveScalingConfig::default(),
+            gpu: GpuConfig::default(),
         }
     }
 }
 
 /// Threading configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ThreadingConfig {
     /// Enable multithreading
     pub enabled: bool,
@@ -336,10 +1014,43 @@ pub struct ThreadingConfig {
     
     /// Sync pool configuration
     pub sync_pool: ThreadPoolConfigSection,
+    
+    /// Priority-class task scheduler configuration
+    pub scheduler: TaskSchedulerConfigSection,
+}
+
+/// Priority-class task scheduler configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TaskSchedulerConfigSection {
+    /// Worker threads, reserved ones included (0 = one per CPU)
+    pub workers: usize,
+    
+    /// Workers that only run realtime tasks, so background work can't hold them
+    pub realtime_reserved_workers: usize,
+    
+    /// Most background tasks running at once (None = half the workers)
+    pub background_max_workers: Option<usize>,
+    
+    /// A class with queued tasks that hasn't been served for this long runs
+    /// ahead of higher classes (realtime-reserved workers excepted)
+    pub starvation_threshold_ms: u64,
+}
+
+impl Default for TaskSchedulerConfigSection {
+    fn default() -> Self {
+        Self {
+            workers: 0,
+            realtime_reserved_workers: 1,
+            background_max_workers: None,
+            starvation_threshold_ms: 100,
+        }
+    }
 }
 
 /// Thread pool configuration section
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ThreadPoolConfigSection {
     /// Minimum number of threads
     pub min_threads: usize,
@@ -467,24 +1178,81 @@ impl Default for ThreadingConfig {
                 thread_name_prefix: "narayana-sync".to_string(),
                 ..Default::default()
             },
+            scheduler: TaskSchedulerConfigSection::default(),
         }
     }
 }
 
 /// Security configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SecurityConfig {
     pub enable_authentication: bool,
     pub enable_authorization: bool,
     pub enable_encryption: bool,
     pub enable_audit_logging: bool,
+    #[serde(deserialize_with = "duration::deserialize")]
     pub session_timeout: Duration,
     pub max_login_attempts: usize,
+    #[serde(deserialize_with = "duration::deserialize")]
     pub lockout_duration: Duration,
     pub password_policy: PasswordPolicy,
+    #[serde(default)]
+    pub ip_access: IpAccessConfig,
+    /// Token signing secret (at least 32 characters); a random one is
+    /// generated per start when unset, which logs everyone out on restart
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jwt_secret: Option<String>,
+    /// Authenticated API requests per client per minute
+    pub api_requests_per_minute: usize,
+}
+
+/// Per-IP request limits and network access rules for the HTTP API
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IpAccessConfig {
+    /// Requests per IP per sliding window (0 disables per-IP limiting)
+    pub requests_per_window: u32,
+    pub window_secs: u64,
+    /// IPs/CIDRs never rate limited (e.g. local tooling)
+    pub exempt: Vec<String>,
+    /// Proxies whose X-Forwarded-For header is trusted for the client address
+    pub trusted_proxies: Vec<String>,
+    /// Path prefixes treated as admin endpoints
+    pub admin_paths: Vec<String>,
+    /// When non-empty, only these IPs/CIDRs may reach admin endpoints
+    pub admin_allow: Vec<String>,
+    /// IPs/CIDRs refused on admin endpoints (checked before the allowlist)
+    pub admin_deny: Vec<String>,
+    /// Auth failures within `greylist_window_secs` that greylist an IP
+    pub greylist_after_failures: u32,
+    pub greylist_window_secs: u64,
+    pub greylist_duration_secs: u64,
+    /// Greylisted IPs get `requests_per_window / greylist_divisor` and no auth attempts
+    pub greylist_divisor: u32,
+}
+
+impl Default for IpAccessConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_window: 1200,
+            window_secs: 60,
+            exempt: vec!["127.0.0.0/8".to_string(), "::1".to_string()],
+            trusted_proxies: vec!["127.0.0.0/8".to_string(), "::1".to_string()],
+            admin_paths: ["/api/v1/auth/setup", "/api/v1/admin", "/api/v1/schema", "/api/v1/system", "/api/v1/sync", "/api/v1/security", "/metrics"]
+                .iter().map(|p| p.to_string()).collect(),
+            admin_allow: Vec::new(),
+            admin_deny: Vec::new(),
+            greylist_after_failures: 10,
+            greylist_window_secs: 300,
+            greylist_duration_secs: 900,
+            greylist_divisor: 10,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct PasswordPolicy {
     pub min_length: usize,
     pub require_uppercase: bool,
@@ -518,21 +1286,56 @@ impl Default for SecurityConfig {
             max_login_attempts: 5,
             lockout_duration: Duration::from_secs(900),
             password_policy: PasswordPolicy::default(),
+            ip_access: IpAccessConfig::default(),
+            jwt_secret: None,
+            api_requests_per_minute: 1000,
         }
     }
 }
 
 /// Monitoring configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct MonitoringConfig {
     pub enable_metrics: bool,
     pub metrics_port: u16,
     pub enable_tracing: bool,
     pub tracing_endpoint: Option<String>,
     pub enable_profiling: bool,
+    #[serde(deserialize_with = "duration::deserialize")]
     pub profiling_interval: Duration,
     pub enable_health_checks: bool,
+    #[serde(deserialize_with = "duration::deserialize")]
     pub health_check_interval: Duration,
+    /// Readiness check settings for /health/ready
+    #[serde(default)]
+    pub health: HealthChecksConfig,
+}
+
+/// Per-subsystem readiness checks. A failing critical check makes the
+/// instance not ready; a failing non-critical one only reports it degraded.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HealthChecksConfig {
+    /// Checks whose failure fails readiness (storage, persistence, wal_replay, llm, websocket)
+    pub critical: Vec<String>,
+    /// Checks that are not run at all
+    pub disabled: Vec<String>,
+    /// Upper bound for a single check
+    pub check_timeout_ms: u64,
+    /// How long results of slow external checks (LLM reachability) are reused
+    pub cache_ttl_secs: u64,
+}
+
+impl Default for HealthChecksConfig {
+    fn default() -> Self {
+        Self {
+            critical: ["storage", "persistence", "wal_replay"].iter().map(|c| c.to_string()).collect(),
+            disabled: Vec::new(),
+            check_timeout_ms: 2000,
+            cache_ttl_secs: 30,
+        }
+    }
 }
 
 impl Default for MonitoringConfig {
@@ -546,17 +1349,62 @@ impl Default for MonitoringConfig {
             profiling_interval: Duration::from_secs(60),
             enable_health_checks: true,
             health_check_interval: Duration::from_secs(30),
+            health: HealthChecksConfig::default(),
         }
     }
 }
 
+/// Optional subsystems hosted alongside the database
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeaturesConfig {
+    /// Avatar bridge (narayana-me); also needs the server's `avatar` build feature
+    pub me: bool,
+    /// WebSocket port of the avatar bridge
+    pub me_port: u16,
+    /// World broker (narayana-wld)
+    pub wld: bool,
+    /// Sound capture (narayana-sc)
+    pub sc: bool,
+    /// Speech synthesis (narayana-spk)
+    pub spk: bool,
+    /// Vision (narayana-eye); runs inside the world broker
+    pub eye: bool,
+}
+
+impl Default for FeaturesConfig {
+    fn default() -> Self {
+        Self {
+            me: true,
+            me_port: 8081,
+            wld: false,
+            sc: false,
+            spk: false,
+            eye: false,
+        }
+    }
+}
+
+impl FeaturesConfig {
+    /// Names of the enabled subsystems
+    pub fn enabled(&self) -> Vec<&'static str> {
+        [("me", self.me), ("wld", self.wld), ("sc", self.sc), ("spk", self.spk), ("eye", self.eye)]
+            .into_iter()
+            .filter(|(_, on)| *on)
+            .map(|(name, _)| name)
+            .collect()
+    }
+}
+
 /// Complete NarayanaDB configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct NarayanaConfig {
     pub instance: InstanceConfig,
     pub storage: StorageConfig,
     pub cache: CacheConfig,
     pub replication: ReplicationConfig,
+    pub cluster: ClusterConfig,
     pub connection_pool: ConnectionPoolConfig,
     pub query: QueryConfig,
     pub network: NetworkConfig,
@@ -564,6 +1412,11 @@ pub struct NarayanaConfig {
     pub threading: ThreadingConfig,
     pub security: SecurityConfig,
     pub monitoring: MonitoringConfig,
+    /// Outbound request policy shared by webhooks, workers, RDE and LLM clients
+    #[serde(default)]
+    pub egress: crate::egress::EgressConfig,
+    /// Subsystem toggles (wld/me/sc/spk/eye)
+    pub features: FeaturesConfig,
     pub custom: HashMap<String, serde_json::Value>,
 }
 
@@ -574,6 +1427,7 @@ impl Default for NarayanaConfig {
             storage: StorageConfig::default(),
             cache: CacheConfig::default(),
             replication: ReplicationConfig::default(),
+            cluster: ClusterConfig::default(),
             connection_pool: ConnectionPoolConfig::default(),
             query: QueryConfig::default(),
             network: NetworkConfig::default(),
@@ -581,20 +1435,71 @@ impl Default for NarayanaConfig {
             threading: ThreadingConfig::default(),
             security: SecurityConfig::default(),
             monitoring: MonitoringConfig::default(),
+            egress: crate::egress::EgressConfig::default(),
+            features: FeaturesConfig::default(),
             custom: HashMap::new(),
         }
     }
 }
 
+/// File formats a configuration can be written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl ConfigFormat {
+    /// Format implied by a file extension
+    pub fn from_path(path: &str) -> Option<Self> {
+        let extension = std::path::Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "toml" => Some(ConfigFormat::Toml),
+            "yaml" | "yml" => Some(ConfigFormat::Yaml),
+            "json" => Some(ConfigFormat::Json),
+            _ => None,
+        }
+    }
+
+    /// Parse a document into a JSON tree (keeps unknown keys for reporting)
+    fn parse(self, content: &str) -> Result<Value, ConfigError> {
+        match self {
+            ConfigFormat::Toml => toml::from_str(content)
+                .map_err(|e| ConfigError::ParseError(format!("TOML: {}", e))),
+            ConfigFormat::Yaml => serde_yaml::from_str(content)
+                .map_err(|e| ConfigError::ParseError(format!("YAML: {}", e))),
+            ConfigFormat::Json => serde_json::from_str(content)
+                .map_err(|e| ConfigError::ParseError(format!("JSON: {}", e))),
+        }
+    }
+}
+
+/// Result of loading a configuration, as shown by `narayana config validate`
+#[derive(Debug, Clone)]
+pub struct ConfigReport {
+    pub config: NarayanaConfig,
+    /// File the configuration was read from (defaults only when `None`)
+    pub source: Option<String>,
+    /// Environment variables that changed a value
+    pub env_overrides: Vec<String>,
+    /// Keys in the file that match no setting (usually typos), as dotted paths
+    pub unknown_keys: Vec<String>,
+}
+
 impl NarayanaConfig {
     /// Load configuration from file
     /// SECURITY: Path validation to prevent reading arbitrary files
     pub fn from_file(path: &str) -> Result<Self, ConfigError> {
+        Self::read_file(path).map(|(config, _)| config)
+    }
+
+    fn read_file(path: &str) -> Result<(Self, Vec<String>), ConfigError> {
         use std::fs;
         use std::path::Path;
         
         // SECURITY: Prevent path traversal
-        let path_obj = Path::new(path);
+        let _path_obj = Path::new(path);
         
         // Check for path traversal sequences
         if path.contains("..") || path.contains("//") || path.contains("\\\\") {
@@ -615,64 +1520,176 @@ impl NarayanaConfig {
             }
         }
         
-        // On Unix, block absolute paths (unless explicitly allowed)
-        #[cfg(unix)]
-        {
-            if path.starts_with('/') && !path.starts_with("/etc/") && !path.starts_with("/var/") && !path.starts_with("./") {
-                // Allow absolute paths only from specific safe directories
-                // This is a compromise - ideally config files should be in a restricted directory
-                // For now, just log a warning but allow it (could be tightened)
-            }
-        }
-        
         let content = fs::read_to_string(path)
-            .map_err(|e| ConfigError::IoError(e.to_string()))?;
-        Self::from_str(&content)
+            .map_err(|e| ConfigError::IoError(format!("{}: {}", path, e)))?;
+        match ConfigFormat::from_path(path) {
+            Some(format) => Self::from_tree(format.parse(&content)?),
+            None => Self::from_tree(Self::detect(&content)?),
+        }
     }
 
-    /// Load configuration from string
+    /// Load configuration from string (JSON, TOML or YAML, detected in that order)
     pub fn from_str(content: &str) -> Result<Self, ConfigError> {
-        // Try JSON first
-        if let Ok(config) = serde_json::from_str::<NarayanaConfig>(content) {
-            return Ok(config);
-        }
-        
-        // Try TOML
-        if let Ok(config) = toml::from_str::<NarayanaConfig>(content) {
-            return Ok(config);
-        }
-        
-        // Try YAML
-        if let Ok(config) = serde_yaml::from_str::<NarayanaConfig>(content) {
-            return Ok(config);
+        Self::from_tree(Self::detect(content)?).map(|(config, _)| config)
+    }
+
+    /// Load configuration from a string in a known format
+    pub fn from_str_as(content: &str, format: ConfigFormat) -> Result<Self, ConfigError> {
+        Self::from_tree(format.parse(content)?).map(|(config, _)| config)
+    }
+
+    fn detect(content: &str) -> Result<Value, ConfigError> {
+        let mut errors = Vec::new();
+        for format in [ConfigFormat::Json, ConfigFormat::Toml, ConfigFormat::Yaml] {
+            match format.parse(content) {
+                // A bare YAML scalar is not a configuration
+                Ok(tree) if tree.is_object() => return Ok(tree),
+                Ok(_) => errors.push(format!("{:?}: not a table of settings", format)),
+                Err(e) => errors.push(e.to_string()),
+            }
         }
-        
-        Err(ConfigError::ParseError("Unknown format".to_string()))
+        Err(ConfigError::ParseError(format!("Unknown format ({})", errors.join("; "))))
+    }
+
+    fn from_tree(tree: Value) -> Result<(Self, Vec<String>), ConfigError> {
+        let config: Self = serde_json::from_value(tree.clone())
+            .map_err(|e| ConfigError::ParseError(e.to_string()))?;
+        let known = serde_json::to_value(&config)
+            .map_err(|e| ConfigError::ParseError(e.to_string()))?;
+        let mut unknown = Vec::new();
+        collect_unknown_keys(&tree, &known, "", &mut unknown);
+        Ok((config, unknown))
+    }
+
+    /// Load the startup configuration: the file (defaults when `None`), then
+    /// environment overrides, then validation
+    pub fn load(path: Option<&str>) -> Result<Self, ConfigError> {
+        Self::load_report(path).map(|report| report.config)
+    }
+
+    /// Like `load`, also reporting which environment variables applied and
+    /// which file keys were not recognised
+    pub fn load_report(path: Option<&str>) -> Result<ConfigReport, ConfigError> {
+        let (mut config, unknown_keys) = match path {
+            Some(path) => Self::read_file(path)?,
+            None => (Self::default(), Vec::new()),
+        };
+        let env_overrides = config.apply_env(std::env::vars())?;
+        config.validate()?;
+        Ok(ConfigReport {
+            config,
+            source: path.map(str::to_string),
+            env_overrides,
+            unknown_keys,
+        })
     }
 
     /// Load configuration from environment variables
     pub fn from_env() -> Self {
         let mut config = Self::default();
-        
-        // Load from environment
-        if let Ok(port) = std::env::var("NARAYANA_PORT") {
-            if let Ok(p) = port.parse::<u16>() {
-                config.network.bind_port = p;
+        // Invalid overrides are reported by `load`; here they leave the defaults
+        let _ = config.apply_env(std::env::vars());
+        config
+    }
+
+    /// Apply environment variables and return the names of those that changed
+    /// something. Shorthands (`NARAYANA_PORT`/`NARAYANA_HTTP_PORT`,
+    /// `NARAYANA_HOST`, `NARAYANA_DATA_DIR`, `NARAYANA_LOG_LEVEL`,
+    /// `NARAYANA_JWT_SECRET`, `NARAYANA_EGRESS_*`) apply first, then
+    /// `NARAYANA__SECTION__FIELD` overrides. Override values are parsed as
+    /// JSON when possible (numbers, booleans, arrays); lists also accept
+    /// comma-separated items and durations accept "30s"-style strings.
+    pub fn apply_env<I>(&mut self, vars: I) -> Result<Vec<String>, ConfigError>
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        let vars: HashMap<String, String> = vars.into_iter().collect();
+        let mut applied = Vec::new();
+
+        for name in ["NARAYANA_PORT", "NARAYANA_HTTP_PORT"] {
+            if let Some(port) = vars.get(name) {
+                self.network.bind_port = port.trim().parse().map_err(|_| {
+                    ConfigError::ParseError(format!("{}: '{}' is not a port", name, port))
+                })?;
+                applied.push(name.to_string());
             }
         }
-        
-        if let Ok(host) = std::env::var("NARAYANA_HOST") {
-            config.network.bind_address = host;
+        if let Some(host) = vars.get("NARAYANA_HOST") {
+            self.network.bind_address = host.clone();
+            applied.push("NARAYANA_HOST".to_string());
         }
-        
-        if let Ok(data_dir) = std::env::var("NARAYANA_DATA_DIR") {
-            config.storage.data_dir = data_dir;
+        if let Some(data_dir) = vars.get("NARAYANA_DATA_DIR") {
+            self.storage.data_dir = data_dir.clone();
+            applied.push("NARAYANA_DATA_DIR".to_string());
         }
-        
-        if let Ok(log_level) = std::env::var("NARAYANA_LOG_LEVEL") {
-            config.instance.log_level = log_level;
+        if let Some(log_level) = vars.get("NARAYANA_LOG_LEVEL") {
+            self.instance.log_level = log_level.clone();
+            applied.push("NARAYANA_LOG_LEVEL".to_string());
+        }
+        if let Some(secret) = vars.get("NARAYANA_JWT_SECRET") {
+            self.security.jwt_secret = Some(secret.clone());
+            applied.push("NARAYANA_JWT_SECRET".to_string());
+        }
+        self.egress.apply_env();
+
+        let mut overrides: Vec<(&String, &String)> = vars
+            .iter()
+            .filter(|(name, _)| name.starts_with(ENV_OVERRIDE_PREFIX))
+            .collect();
+        if overrides.is_empty() {
+            return Ok(applied);
+        }
+        overrides.sort();
+
+        let mut tree = serde_json::to_value(&*self)
+            .map_err(|e| ConfigError::ParseError(e.to_string()))?;
+        for (name, raw) in overrides {
+            let path: Vec<String> = name[ENV_OVERRIDE_PREFIX.len()..]
+                .split("__")
+                .map(|segment| segment.to_ascii_lowercase())
+                .collect();
+            if path.iter().any(|segment| segment.is_empty()) {
+                return Err(ConfigError::ParseError(format!("{}: empty path segment", name)));
+            }
+            let typed = env_value(raw, lookup(&tree, &path));
+            set_path(&mut tree, &path, typed).map_err(|e| ConfigError::ParseError(format!("{}: {}", name, e)))?;
+            // Strings that happen to parse as JSON ("123", "true") are retried verbatim
+            if serde_json::from_value::<Self>(tree.clone()).is_err() {
+                set_path(&mut tree, &path, Value::String(raw.clone()))
+                    .map_err(|e| ConfigError::ParseError(format!("{}: {}", name, e)))?;
+            }
+            let parsed: Self = serde_json::from_value(tree.clone())
+                .map_err(|e| ConfigError::ParseError(format!("{}: {}", name, e)))?;
+            // Serde drops unknown fields, so a typo only shows as a missing path
+            let known = serde_json::to_value(&parsed)
+                .map_err(|e| ConfigError::ParseError(e.to_string()))?;
+            if lookup(&known, &path).is_none() {
+                return Err(ConfigError::ParseError(format!(
+                    "{}: '{}' is not a configuration setting",
+                    name,
+                    path.join(".")
+                )));
+            }
+            applied.push(name.clone());
+        }
+        *self = serde_json::from_value(tree).map_err(|e| ConfigError::ParseError(e.to_string()))?;
+        Ok(applied)
+    }
+
+    /// Copy with secrets masked, for printing or serving over the API
+    pub fn redacted(&self) -> Self {
+        let mut config = self.clone();
+        if config.security.jwt_secret.is_some() {
+            config.security.jwt_secret = Some("********".to_string());
+        }
+        for peer in &mut config.replication.peers {
+            if peer.token.is_some() {
+                peer.token = Some("********".to_string());
+            }
+        }
+        if config.cluster.token.is_some() {
+            config.cluster.token = Some("********".to_string());
         }
-        
         config
     }
 
@@ -683,6 +1700,7 @@ impl NarayanaConfig {
         self.storage = other.storage;
         self.cache = other.cache;
         self.replication = other.replication;
+        self.cluster = other.cluster;
         self.connection_pool = other.connection_pool;
         self.query = other.query;
         self.network = other.network;
@@ -690,6 +1708,8 @@ impl NarayanaConfig {
         self.threading = other.threading;
         self.security = other.security;
         self.monitoring = other.monitoring;
+        self.egress = other.egress;
+        self.features = other.features;
         
         // Merge custom settings
         for (k, v) in other.custom {
@@ -705,6 +1725,18 @@ impl NarayanaConfig {
                 "min_connections cannot be greater than max_connections".to_string()
             ));
         }
+        for limit in &self.connection_pool.route_limits {
+            if !limit.path.starts_with('/') {
+                return Err(ConfigError::ValidationError(
+                    format!("route_limits path must start with '/': {}", limit.path)
+                ));
+            }
+            if limit.max_concurrent == 0 {
+                return Err(ConfigError::ValidationError(
+                    format!("route_limits max_concurrent must be > 0 for {}", limit.path)
+                ));
+            }
+        }
         
         // Validate replication
         if self.replication.replica_count == 0 && self.replication.mode != ReplicationMode::None {
@@ -712,7 +1744,46 @@ impl NarayanaConfig {
                 "replica_count must be > 0 when replication is enabled".to_string()
             ));
         }
-        
+        let mut regions = vec![&self.instance.region];
+        for peer in &self.replication.peers {
+            if regions.contains(&&peer.region) {
+                return Err(ConfigError::ValidationError(
+                    format!("replication peer region '{}' is used twice or is this instance's region", peer.region)
+                ));
+            }
+            if !peer.url.starts_with("http://") && !peer.url.starts_with("https://") {
+                return Err(ConfigError::ValidationError(
+                    format!("replication peer url must be http(s): {}", peer.url)
+                ));
+            }
+            regions.push(&peer.region);
+        }
+        if self.replication.mode == ReplicationMode::MultiPrimary && self.replication.max_pending_changes == 0 {
+            return Err(ConfigError::ValidationError(
+                "replication.max_pending_changes must be > 0".to_string()
+            ));
+        }
+
+        // Validate cluster
+        let cluster_urls = self.cluster.peers.iter().map(|peer| ("cluster.peers url", &peer.url))
+            .chain(self.cluster.advertise_url.iter().map(|url| ("cluster.advertise_url", url)))
+            .chain(self.cluster.join.iter().map(|url| ("cluster.join", url)));
+        for (key, url) in cluster_urls {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                return Err(ConfigError::ValidationError(format!("{} must be http(s): {}", key, url)));
+            }
+        }
+        if self.cluster.join.is_some() && (self.cluster.advertise_url.is_none() || !self.cluster.peers.is_empty()) {
+            return Err(ConfigError::ValidationError(
+                "cluster.join needs cluster.advertise_url and can't be combined with cluster.peers".to_string()
+            ));
+        }
+        if self.cluster.join_attempts == 0 {
+            return Err(ConfigError::ValidationError(
+                "cluster.join_attempts must be > 0".to_string()
+            ));
+        }
+
         // Validate cache
         if self.cache.max_size == 0 {
             return Err(ConfigError::ValidationError(
@@ -727,6 +1798,217 @@ impl NarayanaConfig {
             ));
         }
         
+        if self.network.enable_tls {
+            for (key, path) in [
+                ("network.tls_cert_path", &self.network.tls_cert_path),
+                ("network.tls_key_path", &self.network.tls_key_path),
+            ] {
+                match path {
+                    None => {
+                        return Err(ConfigError::ValidationError(format!(
+                            "{} is required when network.enable_tls is set",
+                            key
+                        )))
+                    }
+                    Some(path) if !std::path::Path::new(path).is_file() => {
+                        return Err(ConfigError::ValidationError(format!(
+                            "{} does not exist: {}",
+                            key, path
+                        )))
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+        
+        if self.network.grpc.enabled {
+            if self.network.grpc.port == 0 {
+                return Err(ConfigError::ValidationError(
+                    "network.grpc.port must be > 0".to_string()
+                ));
+            }
+            if self.network.grpc.port == self.network.bind_port {
+                return Err(ConfigError::ValidationError(
+                    "network.grpc.port must differ from network.bind_port".to_string()
+                ));
+            }
+        }
+        
+        if self.features.me && self.features.me_port == self.network.bind_port {
+            return Err(ConfigError::ValidationError(
+                "features.me_port must differ from network.bind_port".to_string()
+            ));
+        }
+        
+        // Validate storage and instance
+        if self.storage.data_dir.trim().is_empty() {
+            return Err(ConfigError::ValidationError(
+                "storage.data_dir cannot be empty".to_string()
+            ));
+        }
+
+        let blobs = &self.storage.blobs;
+        if blobs.chunk_size < 4096 || blobs.chunk_size > 64 * 1024 * 1024 {
+            return Err(ConfigError::ValidationError(
+                "storage.blobs.chunk_size must be between 4KB and 64MB".to_string()
+            ));
+        }
+        if !["none", "lz4", "zstd", "snappy"].contains(&blobs.compression.to_lowercase().as_str()) {
+            return Err(ConfigError::ValidationError(format!(
+                "storage.blobs.compression must be none, lz4, zstd or snappy, not {:?}",
+                blobs.compression
+            )));
+        }
+        
+        if self.storage.jobs.max_concurrent == 0 || self.storage.jobs.max_attempts == 0 {
+            return Err(ConfigError::ValidationError(
+                "storage.jobs.max_concurrent and storage.jobs.max_attempts must be at least 1".to_string()
+            ));
+        }
+
+        if self.storage.checkpoint_interval.is_zero() {
+            return Err(ConfigError::ValidationError(
+                "storage.checkpoint_interval must be greater than zero".to_string()
+            ));
+        }
+
+        let invariants = &self.storage.invariants;
+        if !(0.0..=1.0).contains(&invariants.sample_rate) {
+            return Err(ConfigError::ValidationError(
+                "storage.invariants.sample_rate must be between 0 and 1".to_string()
+            ));
+        }
+        if invariants.interval.is_zero() {
+            return Err(ConfigError::ValidationError(
+                "storage.invariants.interval must be greater than zero".to_string()
+            ));
+        }
+        if let Some(check) = invariants.checks.iter().find(|c| !INVARIANT_CHECKS.contains(&c.as_str())) {
+            return Err(ConfigError::ValidationError(format!(
+                "storage.invariants.checks: unknown invariant '{}' (expected one of {})",
+                check,
+                INVARIANT_CHECKS.join(", ")
+            )));
+        }
+
+        let quotas = &self.storage.quotas;
+        if !(0.0..=1.0).contains(&quotas.warn_ratio) {
+            return Err(ConfigError::ValidationError(
+                "storage.quotas.warn_ratio must be between 0 and 1".to_string()
+            ));
+        }
+        if !(0.0..=100.0).contains(&quotas.read_only_below_free_percent)
+            || !(0.0..=100.0).contains(&quotas.resume_above_free_percent)
+            || quotas.resume_above_free_percent < quotas.read_only_below_free_percent
+        {
+            return Err(ConfigError::ValidationError(
+                "storage.quotas: free percents must be between 0 and 100, resume_above_free_percent at least read_only_below_free_percent".to_string()
+            ));
+        }
+        if quotas.check_interval.is_zero() {
+            return Err(ConfigError::ValidationError(
+                "storage.quotas.check_interval must be greater than zero".to_string()
+            ));
+        }
+        if let Some(name) = quotas.tables.keys().find(|name| !name.contains('.')) {
+            return Err(ConfigError::ValidationError(format!(
+                "storage.quotas.tables: '{}' must be \"database.table\"",
+                name
+            )));
+        }
+
+        let transactions = &self.query.transactions;
+        if transactions.max_active == 0 || transactions.max_per_owner == 0 || transactions.max_staged_rows == 0 {
+            return Err(ConfigError::ValidationError(
+                "query.transactions.max_active, max_per_owner and max_staged_rows must be at least 1".to_string()
+            ));
+        }
+        if transactions.default_timeout.is_zero()
+            || transactions.default_timeout > transactions.max_timeout
+            || transactions.reap_interval.is_zero()
+        {
+            return Err(ConfigError::ValidationError(
+                "query.transactions: timeouts and reap_interval must be greater than zero, default_timeout at most max_timeout".to_string()
+            ));
+        }
+
+        let scaling = &self.performance.predictive_scaling;
+        if scaling.slot_minutes == 0 || 1440 % scaling.slot_minutes != 0 {
+            return Err(ConfigError::ValidationError(
+                "performance.predictive_scaling.slot_minutes must divide a day (1440 minutes)".to_string()
+            ));
+        }
+        if scaling.interval.is_zero() || scaling.lead_minutes > 1440 {
+            return Err(ConfigError::ValidationError(
+                "performance.predictive_scaling.interval must be greater than zero and lead_minutes at most a day".to_string()
+            ));
+        }
+        if scaling.min_threads == 0 || scaling.min_threads > scaling.max_threads || scaling.min_cache_size > scaling.max_cache_size {
+            return Err(ConfigError::ValidationError(
+                "performance.predictive_scaling: min_threads must be at least 1 and min values must not exceed max values".to_string()
+            ));
+        }
+
+        let gpu = &self.performance.gpu;
+        let pinned = gpu.placement.strip_prefix("pinned:").is_some_and(|device| device.parse::<usize>().is_ok());
+        if !pinned && !["round_robin", "least_loaded", "most_free_memory"].contains(&gpu.placement.as_str()) {
+            return Err(ConfigError::ValidationError(format!(
+                "performance.gpu.placement must be round_robin, least_loaded, most_free_memory or pinned:<device> (got '{}')",
+                gpu.placement
+            )));
+        }
+        if !(gpu.pool_memory_fraction > 0.0 && gpu.pool_memory_fraction <= 1.0) || gpu.unhealthy_after_failures == 0 {
+            return Err(ConfigError::ValidationError(
+                "performance.gpu: pool_memory_fraction must be in (0, 1] and unhealthy_after_failures at least 1".to_string()
+            ));
+        }
+        
+        if !["error", "warn", "info", "debug", "trace"].contains(&self.instance.log_level.to_ascii_lowercase().as_str()) {
+            return Err(ConfigError::ValidationError(format!(
+                "instance.log_level must be one of error, warn, info, debug, trace (got '{}')",
+                self.instance.log_level
+            )));
+        }
+        
+        // Validate security
+        if matches!(&self.security.jwt_secret, Some(secret) if secret.len() < 32) {
+            return Err(ConfigError::ValidationError(
+                "security.jwt_secret must be at least 32 characters".to_string()
+            ));
+        }
+        
+        if self.network.idempotency.enabled && self.network.idempotency.window_secs == 0 {
+            return Err(ConfigError::ValidationError(
+                "network.idempotency.window_secs must be > 0 when enabled".to_string()
+            ));
+        }
+        
+        // A credentialed wildcard origin would let any site act as the user
+        let wildcard_with_credentials = |origins: &[String], cors: &CorsPolicyConfig| {
+            cors.allow_credentials && origins.iter().any(|o| o == "*")
+        };
+        if wildcard_with_credentials(&self.network.cors_origins, &self.network.cors) {
+            return Err(ConfigError::ValidationError(
+                "network.cors.allow_credentials cannot be combined with a '*' origin".to_string()
+            ));
+        }
+        for route in &self.network.route_headers {
+            if !route.path_prefix.starts_with('/') {
+                return Err(ConfigError::ValidationError(format!(
+                    "network.route_headers path_prefix must start with '/': {}",
+                    route.path_prefix
+                )));
+            }
+            let origins = route.cors_origins.as_ref().unwrap_or(&self.network.cors_origins);
+            let cors = route.cors.as_ref().unwrap_or(&self.network.cors);
+            if wildcard_with_credentials(origins, cors) {
+                return Err(ConfigError::ValidationError(format!(
+                    "CORS credentials cannot be combined with a '*' origin on {}",
+                    route.path_prefix
+                )));
+            }
+        }
+        
         Ok(())
     }
 
@@ -744,6 +2026,109 @@ impl NarayanaConfig {
     }
 }
 
+/// Dotted paths of keys in `tree` that have no counterpart in `known`
+fn collect_unknown_keys(tree: &Value, known: &Value, prefix: &str, unknown: &mut Vec<String>) {
+    let (Value::Object(tree), Value::Object(known)) = (tree, known) else {
+        return;
+    };
+    for (key, value) in tree {
+        let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+        match known.get(key) {
+            Some(known) => collect_unknown_keys(value, known, &path, unknown),
+            None => unknown.push(path),
+        }
+    }
+}
+
+fn lookup<'a>(tree: &'a Value, path: &[String]) -> Option<&'a Value> {
+    path.iter().try_fold(tree, |node, key| node.as_object()?.get(key))
+}
+
+fn set_path(tree: &mut Value, path: &[String], value: Value) -> Result<(), String> {
+    let (last, parents) = path.split_last().ok_or("empty path")?;
+    let mut node = tree;
+    for key in parents {
+        let object = node.as_object_mut().ok_or_else(|| format!("'{}' is not a section", key))?;
+        node = object.entry(key.clone()).or_insert_with(|| Value::Object(Default::default()));
+    }
+    node.as_object_mut()
+        .ok_or_else(|| format!("'{}' is not a section", last))?
+        .insert(last.clone(), value);
+    Ok(())
+}
+
+/// Typed value of an override, shaped after the setting it replaces
+fn env_value(raw: &str, current: Option<&Value>) -> Value {
+    if matches!(current, Some(Value::Array(_))) && !raw.trim_start().starts_with('[') {
+        return Value::Array(
+            raw.split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(|item| Value::String(item.to_string()))
+                .collect(),
+        );
+    }
+    serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string()))
+}
+
+/// Durations in configuration files: whole or fractional seconds, a string with
+/// a unit ("250ms", "30s", "5m", "2h", "1d") or the `{ secs, nanos }` table
+/// they serialize to
+mod duration {
+    use serde::{de, Deserialize, Deserializer};
+    use std::time::Duration;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+        Secs(u64),
+        Fractional(f64),
+        Text(String),
+        Table {
+            secs: u64,
+            #[serde(default)]
+            nanos: u32,
+        },
+    }
+
+    impl Repr {
+        fn into_duration<E: de::Error>(self) -> Result<Duration, E> {
+            match self {
+                Repr::Secs(secs) => Ok(Duration::from_secs(secs)),
+                Repr::Fractional(secs) => Duration::try_from_secs_f64(secs).map_err(E::custom),
+                Repr::Text(text) => parse(&text).map_err(E::custom),
+                Repr::Table { secs, nanos } => Ok(Duration::new(secs, nanos)),
+            }
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Duration, String> {
+        let text = text.trim();
+        let split = text.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(text.len());
+        let (number, unit) = text.split_at(split);
+        let value: f64 = number.parse().map_err(|_| format!("invalid duration '{}'", text))?;
+        let scale = match unit.trim() {
+            "ms" => 0.001,
+            "" | "s" => 1.0,
+            "m" => 60.0,
+            "h" => 3600.0,
+            "d" => 86400.0,
+            other => return Err(format!("unknown duration unit '{}' in '{}' (use ms, s, m, h or d)", other, text)),
+        };
+        Duration::try_from_secs_f64(value * scale).map_err(|e| format!("invalid duration '{}': {}", text, e))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        Repr::deserialize(deserializer)?.into_duration()
+    }
+
+    pub fn deserialize_opt<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Duration>, D::Error> {
+        Option::<Repr>::deserialize(deserializer)?
+            .map(Repr::into_duration)
+            .transpose()
+    }
+}
+
 #[derive(Debug)]
 pub enum ConfigError {
     IoError(String),
@@ -763,3 +2148,258 @@ impl std::fmt::Display for ConfigError {
 
 impl std::error::Error for ConfigError {}
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_partial_toml_and_yaml() {
+        let toml = r#"
+            [network]
+            bind_port = 9000
+            keep_alive_timeout = "90s"
+
+            [cache]
+            max_size = 500
+            max_ttl = "2h"
+
+            [features]
+            wld = true
+            eye = true
+        "#;
+        let yaml = "
+network:
+  bind_port: 9000
+  keep_alive_timeout: 90
+cache:
+  max_size: 500
+  max_ttl: 7200
+features:
+  wld: true
+  eye: true
+";
+        for config in [
+            NarayanaConfig::from_str_as(toml, ConfigFormat::Toml).unwrap(),
+            NarayanaConfig::from_str_as(yaml, ConfigFormat::Yaml).unwrap(),
+            NarayanaConfig::from_str(toml).unwrap(),
+        ] {
+            assert_eq!(config.network.bind_port, 9000);
+            assert_eq!(config.network.keep_alive_timeout, Duration::from_secs(90));
+            assert_eq!(config.cache.max_size, 500);
+            assert_eq!(config.cache.max_ttl, Some(Duration::from_secs(7200)));
+            assert_eq!(config.features.enabled(), vec!["me", "wld", "eye"]);
+            // Untouched settings keep their defaults
+            assert_eq!(config.storage.data_dir, "./data");
+            assert_eq!(config.network.read_timeout, Duration::from_secs(30));
+            config.validate().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_example_file_is_valid() {
+        let tree = ConfigFormat::Toml.parse(include_str!("../../config.example.toml")).unwrap();
+        let (config, unknown) = NarayanaConfig::from_tree(tree).unwrap();
+        assert!(unknown.is_empty(), "{:?}", unknown);
+        config.validate().unwrap();
+        assert_eq!(config.security.lockout_duration, Duration::from_secs(900));
+    }
+
+    #[test]
+    fn test_storage_backends_per_database() {
+        let config = NarayanaConfig::from_str_as(
+            "[storage.backends]\ndefault = \"rocksdb\"\n[storage.backends.databases]\nscratch = \"memory\"\n",
+            ConfigFormat::Toml,
+        )
+        .unwrap();
+        let backends = &config.storage.backends;
+        assert_eq!(backends.backend_for("scratch"), StorageBackendKind::Memory);
+        assert_eq!(backends.backend_for("analytics"), StorageBackendKind::RocksDb);
+        assert_eq!(backends.kinds(), vec![StorageBackendKind::RocksDb, StorageBackendKind::Memory]);
+        assert_eq!(backends.memory_snapshot_interval_secs, 60);
+        assert!(NarayanaConfig::from_str_as("[storage.backends]\ndefault = \"tape\"\n", ConfigFormat::Toml).is_err());
+    }
+
+    #[test]
+    fn test_defaults_round_trip() {
+        let config = NarayanaConfig::default();
+        let toml = toml::to_string(&config).unwrap();
+        let back = NarayanaConfig::from_str_as(&toml, ConfigFormat::Toml).unwrap();
+        assert_eq!(back.cache.cleanup_interval, config.cache.cleanup_interval);
+        assert_eq!(back.features, config.features);
+
+        let json = serde_json::to_string(&config).unwrap();
+        let (_, unknown) = NarayanaConfig::from_tree(serde_json::from_str(&json).unwrap()).unwrap();
+        assert!(unknown.is_empty(), "{:?}", unknown);
+    }
+
+    #[test]
+    fn test_route_limits() {
+        let config = NarayanaConfig::from_str_as(
+            "[[connection_pool.route_limits]]\npath = \"/api/v1/ml\"\nmax_concurrent = 1\nqueue_timeout = \"250ms\"\n",
+            ConfigFormat::Toml,
+        )
+        .unwrap();
+        // Listed entries replace the defaults; omitted fields fall back per entry
+        assert_eq!(
+            config.connection_pool.route_limits,
+            vec![RouteLimit::new("/api/v1/ml", 1, 64, Duration::from_millis(250))]
+        );
+        assert_eq!(config.connection_pool.max_connections, 100);
+        config.validate().unwrap();
+        assert!(!NarayanaConfig::default().connection_pool.route_limits.is_empty());
+
+        let mut config = NarayanaConfig::default();
+        config.connection_pool.route_limits = vec![RouteLimit::new("/api/v1/ml", 0, 0, Duration::ZERO)];
+        assert!(config.validate().is_err());
+        config.connection_pool.route_limits = vec![RouteLimit::new("api/v1/ml", 1, 0, Duration::ZERO)];
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_unknown_keys_are_reported() {
+        let tree = ConfigFormat::Toml
+            .parse("[network]\nbind_prot = 1\n[featurez]\nwld = true\n[custom]\nanything = 1\n")
+            .unwrap();
+        let (_, unknown) = NarayanaConfig::from_tree(tree).unwrap();
+        assert_eq!(unknown, vec!["featurez".to_string(), "network.bind_prot".to_string()]);
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        let mut config = NarayanaConfig::default();
+        let applied = config
+            .apply_env(vars(&[
+                ("NARAYANA_HTTP_PORT", "7000"),
+                ("NARAYANA__NETWORK__BIND_PORT", "9000"),
+                ("NARAYANA__NETWORK__CORS_ORIGINS", "https://a.example, https://b.example"),
+                ("NARAYANA__NETWORK__TLS_CERT_PATH", "/etc/narayana/cert.pem"),
+                ("NARAYANA__CACHE__MAX_TTL", "5m"),
+                ("NARAYANA__INSTANCE__CLUSTER_NAME", "123"),
+                ("NARAYANA__FEATURES__WLD", "true"),
+                ("NARAYANA__CUSTOM__TEAM", "robots"),
+                ("UNRELATED", "x"),
+            ]))
+            .unwrap();
+        assert_eq!(applied.len(), 8);
+        // Structured overrides win over shorthands
+        assert_eq!(config.network.bind_port, 9000);
+        assert_eq!(config.network.cors_origins, vec!["https://a.example", "https://b.example"]);
+        assert_eq!(config.network.tls_cert_path.as_deref(), Some("/etc/narayana/cert.pem"));
+        assert_eq!(config.cache.max_ttl, Some(Duration::from_secs(300)));
+        assert_eq!(config.instance.cluster_name, "123");
+        assert!(config.features.wld);
+        assert_eq!(config.get_custom::<String>("team").as_deref(), Some("robots"));
+    }
+
+    #[test]
+    fn test_env_override_errors() {
+        let mut config = NarayanaConfig::default();
+        let err = config.apply_env(vars(&[("NARAYANA__NETWORK__BIND_PROT", "9000")])).unwrap_err();
+        assert!(err.to_string().contains("network.bind_prot"), "{}", err);
+        let err = config.apply_env(vars(&[("NARAYANA__NETWORK__BIND_PORT", "high")])).unwrap_err();
+        assert!(err.to_string().contains("NARAYANA__NETWORK__BIND_PORT"), "{}", err);
+        let err = config.apply_env(vars(&[("NARAYANA__CACHE__TTL", "5 weeks")])).unwrap_err();
+        assert!(err.to_string().contains("duration"), "{}", err);
+        assert_eq!(config.network.bind_port, 8080);
+    }
+
+    #[test]
+    fn test_validation() {
+        let mut config = NarayanaConfig::default();
+        config.network.enable_tls = true;
+        assert!(config.validate().unwrap_err().to_string().contains("tls_cert_path"));
+
+        let mut config = NarayanaConfig::default();
+        config.security.jwt_secret = Some("short".to_string());
+        assert!(config.validate().is_err());
+        config.security.jwt_secret = Some("x".repeat(32));
+        config.validate().unwrap();
+        assert_eq!(config.redacted().security.jwt_secret.as_deref(), Some("********"));
+
+        let mut config = NarayanaConfig::default();
+        config.instance.log_level = "loud".to_string();
+        assert!(config.validate().is_err());
+
+        let mut config = NarayanaConfig::default();
+        config.features.me_port = config.network.bind_port;
+        assert!(config.validate().is_err());
+
+        let mut config = NarayanaConfig::default();
+        config.network.grpc.port = config.network.bind_port;
+        assert!(config.validate().is_err());
+        config.network.grpc.enabled = false;
+        config.validate().unwrap();
+
+        let mut config = NarayanaConfig::default();
+        config.storage.invariants.sample_rate = 1.5;
+        assert!(config.validate().is_err());
+        config.storage.invariants.sample_rate = 1.0;
+        config.storage.invariants.checks.push("row_order".to_string());
+        assert!(config.validate().unwrap_err().to_string().contains("row_order"));
+
+        let mut config = NarayanaConfig::default();
+        config.storage.quotas.resume_above_free_percent = 2.0;
+        assert!(config.validate().unwrap_err().to_string().contains("resume_above_free_percent"));
+        config.storage.quotas.resume_above_free_percent = 10.0;
+        config.storage.quotas.tables.insert("telemetry".to_string(), QuotaLimits { max_bytes: Some(1), max_rows: None });
+        assert!(config.validate().unwrap_err().to_string().contains("telemetry"));
+
+        let mut config = NarayanaConfig::default();
+        config.performance.predictive_scaling.slot_minutes = 7;
+        assert!(config.validate().is_err());
+        config.performance.predictive_scaling.slot_minutes = 30;
+        config.performance.predictive_scaling.max_threads = 0;
+        assert!(config.validate().is_err());
+
+        let mut config = NarayanaConfig::default();
+        config.performance.gpu.placement = "pinned:1".to_string();
+        assert!(config.validate().is_ok());
+        config.performance.gpu.placement = "fastest".to_string();
+        assert!(config.validate().unwrap_err().to_string().contains("performance.gpu.placement"));
+        config.performance.gpu.placement = "round_robin".to_string();
+        config.performance.gpu.pool_memory_fraction = 1.5;
+        assert!(config.validate().is_err());
+
+        let mut config = NarayanaConfig::default();
+        config.cluster.join = Some("http://10.0.0.4:8080".to_string());
+        assert!(config.validate().unwrap_err().to_string().contains("cluster.advertise_url"));
+        config.cluster.advertise_url = Some("10.0.0.5:8080".to_string());
+        assert!(config.validate().unwrap_err().to_string().contains("must be http(s)"));
+        config.cluster.advertise_url = Some("http://10.0.0.5:8080".to_string());
+        config.cluster.token = Some("secret".to_string());
+        config.validate().unwrap();
+        assert_eq!(config.redacted().cluster.token.as_deref(), Some("********"));
+        config.cluster.join_attempts = 0;
+        assert!(config.validate().unwrap_err().to_string().contains("cluster.join_attempts"));
+
+        let mut config = NarayanaConfig::default();
+        config.replication.mode = ReplicationMode::MultiPrimary;
+        let peer = ReplicationPeer {
+            region: "eu-west-1".to_string(),
+            url: "https://eu.example.com".to_string(),
+            token: Some("secret".to_string()),
+        };
+        config.replication.peers = vec![peer.clone(), peer];
+        assert!(config.validate().unwrap_err().to_string().contains("eu-west-1"));
+        config.replication.peers.pop();
+        config.validate().unwrap();
+        assert_eq!(config.redacted().replication.peers[0].token.as_deref(), Some("********"));
+        config.replication.peers[0].region = config.instance.region.clone();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_duration_strings() {
+        assert_eq!(duration::parse("250ms").unwrap(), Duration::from_millis(250));
+        assert_eq!(duration::parse("1.5h").unwrap(), Duration::from_secs(5400));
+        assert_eq!(duration::parse(" 2d ").unwrap(), Duration::from_secs(172800));
+        assert_eq!(duration::parse("45").unwrap(), Duration::from_secs(45));
+        assert!(duration::parse("soon").is_err());
+        assert!(duration::parse("3 fortnights").is_err());
+    }
+}
@@ -0,0 +1,743 @@
+// Outbound request (egress) policy
+// One SSRF gate for every subsystem that makes HTTP calls: scheme and host
+// allow/deny lists, private address blocking, and resolved-address pinning
+// so a hostname cannot be rebound to an internal address after the check.
+
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs};
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// Maximum accepted URL length
+pub const DEFAULT_MAX_URL_LENGTH: usize = 2048;
+
+/// Subsystems that make outbound requests; each may override the base policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EgressSubsystem {
+    General,
+    Webhooks,
+    Workers,
+    Rde,
+    Llm,
+    Persistence,
+    Models,
+    Connectors,
+}
+
+impl EgressSubsystem {
+    pub const ALL: [EgressSubsystem; 8] = [
+        EgressSubsystem::General,
+        EgressSubsystem::Webhooks,
+        EgressSubsystem::Workers,
+        EgressSubsystem::Rde,
+        EgressSubsystem::Llm,
+        EgressSubsystem::Persistence,
+        EgressSubsystem::Models,
+        EgressSubsystem::Connectors,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EgressSubsystem::General => "general",
+            EgressSubsystem::Webhooks => "webhooks",
+            EgressSubsystem::Workers => "workers",
+            EgressSubsystem::Rde => "rde",
+            EgressSubsystem::Llm => "llm",
+            EgressSubsystem::Persistence => "persistence",
+            EgressSubsystem::Models => "models",
+            EgressSubsystem::Connectors => "connectors",
+        }
+    }
+}
+
+/// Per-subsystem additions to the base policy. Allow/deny patterns are
+/// appended to the base lists; the optional fields replace the base values.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EgressRules {
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+    pub block_private: Option<bool>,
+    pub allowed_schemes: Option<Vec<String>>,
+}
+
+/// Egress policy configuration.
+///
+/// Host patterns: `example.com`, `*.example.com` (subdomains only), `*`,
+/// IP literals, CIDR ranges (`10.0.0.0/8`), each optionally with a scheme
+/// prefix (`https://`) and a port suffix (`:443`, `:*`). Deny always wins.
+/// When `allow` is non-empty only matching hosts are reachable. An exact
+/// host, IP or CIDR allow entry also exempts that target from private
+/// address blocking (e.g. `localhost:8080` for a sidecar); wildcards never do.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EgressConfig {
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+    pub block_private: bool,
+    pub allowed_schemes: Vec<String>,
+    pub max_url_length: usize,
+    pub overrides: HashMap<EgressSubsystem, EgressRules>,
+}
+
+impl Default for EgressConfig {
+    fn default() -> Self {
+        let https_only = EgressRules {
+            allowed_schemes: Some(vec!["https".to_string()]),
+            ..Default::default()
+        };
+        let mut overrides = HashMap::new();
+        overrides.insert(EgressSubsystem::Llm, https_only.clone());
+        overrides.insert(EgressSubsystem::Persistence, https_only);
+        // RDE also publishes to MQTT and AMQP brokers
+        overrides.insert(EgressSubsystem::Rde, EgressRules {
+            allowed_schemes: Some(["http", "https", "mqtt", "mqtts", "amqp", "amqps"].map(String::from).to_vec()),
+            ..Default::default()
+        });
+        // Ingest connectors subscribe to MQTT topics too
+        overrides.insert(EgressSubsystem::Connectors, EgressRules {
+            allowed_schemes: Some(["http", "https", "mqtt", "mqtts"].map(String::from).to_vec()),
+            ..Default::default()
+        });
+        Self {
+            allow: Vec::new(),
+            deny: Vec::new(),
+            block_private: true,
+            allowed_schemes: vec!["http".to_string(), "https".to_string()],
+            max_url_length: DEFAULT_MAX_URL_LENGTH,
+            overrides,
+        }
+    }
+}
+
+impl EgressConfig {
+    /// Apply `NARAYANA_EGRESS_*` environment variables. Lists are comma
+    /// separated; `NARAYANA_EGRESS_<SUBSYSTEM>_ALLOW` / `_DENY` extend one subsystem.
+    pub fn apply_env(&mut self) {
+        fn list(name: &str) -> Option<Vec<String>> {
+            std::env::var(name).ok().map(|v| {
+                v.split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect()
+            })
+        }
+
+        if let Some(allow) = list("NARAYANA_EGRESS_ALLOW") {
+            self.allow.extend(allow);
+        }
+        if let Some(deny) = list("NARAYANA_EGRESS_DENY") {
+            self.deny.extend(deny);
+        }
+        if let Ok(value) = std::env::var("NARAYANA_EGRESS_BLOCK_PRIVATE") {
+            self.block_private = !matches!(value.to_lowercase().as_str(), "0" | "false" | "no" | "off");
+        }
+        for subsystem in EgressSubsystem::ALL {
+            let prefix = format!("NARAYANA_EGRESS_{}", subsystem.as_str().to_uppercase());
+            let allow = list(&format!("{}_ALLOW", prefix));
+            let deny = list(&format!("{}_DENY", prefix));
+            if allow.is_none() && deny.is_none() {
+                continue;
+            }
+            let rules = self.overrides.entry(subsystem).or_default();
+            rules.allow.extend(allow.unwrap_or_default());
+            rules.deny.extend(deny.unwrap_or_default());
+        }
+    }
+}
+
+/// Parsed destination of an outbound request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EgressTarget {
+    pub scheme: String,
+    /// Lowercased host without brackets or trailing dot
+    pub host: String,
+    pub port: u16,
+    /// Set when the host is an IP literal (including shorthand IPv4 forms)
+    pub ip: Option<IpAddr>,
+}
+
+/// A checked target together with the addresses it may connect to.
+/// Clients must connect only to `addrs` (see `pinned_client`).
+#[derive(Debug, Clone)]
+pub struct ResolvedTarget {
+    pub target: EgressTarget,
+    pub addrs: Vec<SocketAddr>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum HostMatcher {
+    Any,
+    Exact(String),
+    /// Leading-dot suffix, e.g. ".example.com"
+    Suffix(String),
+    Ip(IpAddr),
+    Cidr(IpAddr, u8),
+}
+
+#[derive(Debug, Clone)]
+struct HostPattern {
+    scheme: Option<String>,
+    host: HostMatcher,
+    port: Option<u16>,
+}
+
+impl HostPattern {
+    fn parse(pattern: &str) -> Result<Self> {
+        let raw = pattern.trim().to_lowercase();
+        if raw.is_empty() {
+            return Err(Error::Configuration("Empty egress pattern".to_string()));
+        }
+        let (scheme, rest) = match raw.split_once("://") {
+            Some((scheme, rest)) => (Some(scheme.to_string()), rest.to_string()),
+            None => (None, raw.clone()),
+        };
+
+        // CIDR ranges contain a '/', so try them before stripping a path
+        if let Some((addr, prefix)) = rest.split_once('/') {
+            if let (Ok(ip), Ok(bits)) = (addr.trim_matches(|c| c == '[' || c == ']').parse::<IpAddr>(), prefix.parse::<u8>()) {
+                let max = if ip.is_ipv4() { 32 } else { 128 };
+                if bits > max {
+                    return Err(Error::Configuration(format!("Invalid CIDR prefix in egress pattern: {}", pattern)));
+                }
+                return Ok(Self { scheme, host: HostMatcher::Cidr(ip, bits), port: None });
+            }
+        }
+
+        let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+        let (host, port) = split_host_port(authority)
+            .ok_or_else(|| Error::Configuration(format!("Invalid egress pattern: {}", pattern)))?;
+        let port = match port {
+            None | Some("*") => None,
+            Some(p) => Some(p.parse::<u16>().map_err(|_| {
+                Error::Configuration(format!("Invalid port in egress pattern: {}", pattern))
+            })?),
+        };
+        let host = host.trim_end_matches('.');
+        let matcher = if host == "*" {
+            HostMatcher::Any
+        } else if let Some(suffix) = host.strip_prefix("*.") {
+            if suffix.is_empty() || suffix.contains('*') {
+                return Err(Error::Configuration(format!("Invalid wildcard in egress pattern: {}", pattern)));
+            }
+            HostMatcher::Suffix(format!(".{}", suffix))
+        } else if host.is_empty() || host.contains('*') {
+            return Err(Error::Configuration(format!("Invalid egress pattern: {}", pattern)));
+        } else if let Some(ip) = parse_ip_host(host) {
+            HostMatcher::Ip(ip)
+        } else {
+            HostMatcher::Exact(host.to_string())
+        };
+        Ok(Self { scheme, host: matcher, port })
+    }
+
+    /// Whether this pattern names a specific destination (may bypass private blocking)
+    fn is_specific(&self) -> bool {
+        !matches!(self.host, HostMatcher::Any | HostMatcher::Suffix(_))
+    }
+
+    fn matches_endpoint(&self, target: &EgressTarget) -> bool {
+        if self.scheme.as_deref().is_some_and(|s| s != target.scheme) {
+            return false;
+        }
+        self.port.is_none_or(|p| p == target.port)
+    }
+
+    fn matches_host(&self, host: &str) -> bool {
+        match &self.host {
+            HostMatcher::Any => true,
+            HostMatcher::Exact(h) => h == host,
+            HostMatcher::Suffix(s) => host.ends_with(s.as_str()),
+            HostMatcher::Ip(_) | HostMatcher::Cidr(..) => false,
+        }
+    }
+
+    fn matches_ip(&self, ip: &IpAddr) -> bool {
+        let ip = canonical_ip(ip);
+        match &self.host {
+            HostMatcher::Any => true,
+            HostMatcher::Ip(p) => canonical_ip(p) == ip,
+            HostMatcher::Cidr(net, bits) => cidr_contains(&canonical_ip(net), *bits, &ip),
+            HostMatcher::Exact(_) | HostMatcher::Suffix(_) => false,
+        }
+    }
+
+    /// Match against the URL itself (host name or IP literal)
+    fn matches_target(&self, target: &EgressTarget) -> bool {
+        self.matches_endpoint(target)
+            && match &target.ip {
+                Some(ip) => self.matches_ip(ip),
+                None => self.matches_host(&target.host),
+            }
+    }
+}
+
+/// Effective rules for one subsystem
+#[derive(Debug, Clone)]
+struct CompiledRules {
+    allow: Vec<HostPattern>,
+    deny: Vec<HostPattern>,
+    block_private: bool,
+    allowed_schemes: Vec<String>,
+}
+
+/// Compiled egress policy
+#[derive(Debug, Clone)]
+pub struct EgressPolicy {
+    config: EgressConfig,
+    base: CompiledRules,
+    overrides: HashMap<EgressSubsystem, CompiledRules>,
+}
+
+impl EgressPolicy {
+    pub fn new(config: EgressConfig) -> Result<Self> {
+        let compile = |patterns: &[String]| -> Result<Vec<HostPattern>> {
+            patterns.iter().map(|p| HostPattern::parse(p)).collect()
+        };
+        let normalize = |schemes: &[String]| -> Vec<String> {
+            schemes.iter().map(|s| s.trim().to_lowercase()).collect()
+        };
+
+        let base = CompiledRules {
+            allow: compile(&config.allow)?,
+            deny: compile(&config.deny)?,
+            block_private: config.block_private,
+            allowed_schemes: normalize(&config.allowed_schemes),
+        };
+        let mut overrides = HashMap::new();
+        for (subsystem, rules) in &config.overrides {
+            let mut compiled = base.clone();
+            compiled.allow.extend(compile(&rules.allow)?);
+            compiled.deny.extend(compile(&rules.deny)?);
+            if let Some(block) = rules.block_private {
+                compiled.block_private = block;
+            }
+            if let Some(schemes) = &rules.allowed_schemes {
+                compiled.allowed_schemes = normalize(schemes);
+            }
+            overrides.insert(*subsystem, compiled);
+        }
+        Ok(Self { config, base, overrides })
+    }
+
+    pub fn config(&self) -> &EgressConfig {
+        &self.config
+    }
+
+    fn rules(&self, subsystem: EgressSubsystem) -> &CompiledRules {
+        self.overrides.get(&subsystem).unwrap_or(&self.base)
+    }
+
+    /// Parse a URL and check it without resolving DNS. Hostnames are only
+    /// fully vetted by `check_resolved` / `resolve`.
+    pub fn check_url(&self, subsystem: EgressSubsystem, url: &str) -> Result<EgressTarget> {
+        let target = parse_url(url, self.config.max_url_length)?;
+        self.check_target(subsystem, &target, &[])?;
+        Ok(target)
+    }
+
+    /// Check a target against the addresses its host resolved to. Every
+    /// address must pass; the caller must then connect only to those.
+    pub fn check_resolved(&self, subsystem: EgressSubsystem, target: &EgressTarget, addrs: &[IpAddr]) -> Result<()> {
+        if target.ip.is_none() && addrs.is_empty() {
+            return Err(deny(subsystem, target, "host did not resolve"));
+        }
+        self.check_target(subsystem, target, addrs)
+    }
+
+    /// Check a URL, resolve its host (blocking) and validate every address
+    pub fn resolve(&self, subsystem: EgressSubsystem, url: &str) -> Result<ResolvedTarget> {
+        let target = self.check_url(subsystem, url)?;
+        let addrs: Vec<SocketAddr> = match target.ip {
+            Some(ip) => vec![SocketAddr::new(ip, target.port)],
+            None => (target.host.as_str(), target.port)
+                .to_socket_addrs()
+                .map_err(|e| Error::Storage(format!("Failed to resolve {}: {}", target.host, e)))?
+                .collect(),
+        };
+        let ips: Vec<IpAddr> = addrs.iter().map(|a| a.ip()).collect();
+        self.check_resolved(subsystem, &target, &ips)?;
+        Ok(ResolvedTarget { target, addrs })
+    }
+
+    fn check_target(&self, subsystem: EgressSubsystem, target: &EgressTarget, resolved: &[IpAddr]) -> Result<()> {
+        let rules = self.rules(subsystem);
+
+        if !rules.allowed_schemes.iter().any(|s| s == &target.scheme) {
+            return Err(deny(subsystem, target, &format!("scheme '{}' is not allowed", target.scheme)));
+        }
+
+        let denied = rules.deny.iter().any(|p| {
+            p.matches_target(target)
+                || (p.matches_endpoint(target) && resolved.iter().any(|ip| p.matches_ip(ip)))
+        });
+        if denied {
+            return Err(deny(subsystem, target, "destination is denylisted"));
+        }
+
+        // A hostname allowed through a CIDR entry must resolve entirely inside it
+        let allowed_by = |p: &HostPattern| {
+            p.matches_target(target)
+                || (target.ip.is_none()
+                    && p.matches_endpoint(target)
+                    && matches!(p.host, HostMatcher::Cidr(..) | HostMatcher::Ip(_))
+                    && !resolved.is_empty()
+                    && resolved.iter().all(|ip| p.matches_ip(ip)))
+        };
+        let allowlisted = rules.allow.iter().any(&allowed_by);
+        if !rules.allow.is_empty() && !allowlisted {
+            return Err(deny(subsystem, target, "destination is not in the allowlist"));
+        }
+
+        let exempt = rules.allow.iter().any(|p| p.is_specific() && allowed_by(p));
+        if rules.block_private && !exempt {
+            if is_local_hostname(&target.host) {
+                return Err(deny(subsystem, target, "localhost is not allowed"));
+            }
+            if let Some(ip) = target.ip {
+                if is_blocked_ip(&ip) {
+                    return Err(deny(subsystem, target, "private or reserved address"));
+                }
+            }
+            if let Some(ip) = resolved.iter().find(|ip| is_blocked_ip(ip)) {
+                return Err(deny(subsystem, target, &format!("host resolves to private or reserved address {}", ip)));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn deny(subsystem: EgressSubsystem, target: &EgressTarget, reason: &str) -> Error {
+    Error::Storage(format!(
+        "Egress to {}:{} blocked for {}: {}",
+        target.host, target.port, subsystem.as_str(), reason
+    ))
+}
+
+static GLOBAL_POLICY: OnceLock<RwLock<Arc<EgressPolicy>>> = OnceLock::new();
+
+fn global_cell() -> &'static RwLock<Arc<EgressPolicy>> {
+    GLOBAL_POLICY.get_or_init(|| {
+        let policy = EgressPolicy::new(EgressConfig::default()).expect("default egress policy is valid");
+        RwLock::new(Arc::new(policy))
+    })
+}
+
+/// Process-wide policy used by all outbound clients
+pub fn global() -> Arc<EgressPolicy> {
+    global_cell().read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Replace the process-wide policy (typically once at startup)
+pub fn install(policy: EgressPolicy) {
+    *global_cell().write().unwrap_or_else(|e| e.into_inner()) = Arc::new(policy);
+}
+
+/// Check a URL against the process-wide policy without resolving DNS
+pub fn check_url(subsystem: EgressSubsystem, url: &str) -> Result<EgressTarget> {
+    global().check_url(subsystem, url)
+}
+
+/// Resolve and check a URL against the process-wide policy, then build a
+/// client pinned to the vetted addresses. Redirects are disabled because a
+/// redirect target would bypass the check.
+#[cfg(feature = "http-client")]
+pub async fn pinned_client(
+    subsystem: EgressSubsystem,
+    url: &str,
+    builder: reqwest::ClientBuilder,
+) -> Result<reqwest::Client> {
+    let policy = global();
+    let target = policy.check_url(subsystem, url)?;
+    let builder = builder.redirect(reqwest::redirect::Policy::none());
+    let builder = match target.ip {
+        Some(_) => builder,
+        None => {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((target.host.as_str(), target.port))
+                .await
+                .map_err(|e| Error::Storage(format!("Failed to resolve {}: {}", target.host, e)))?
+                .collect();
+            let ips: Vec<IpAddr> = addrs.iter().map(|a| a.ip()).collect();
+            policy.check_resolved(subsystem, &target, &ips)?;
+            builder.resolve_to_addrs(&target.host, &addrs)
+        }
+    };
+    builder
+        .build()
+        .map_err(|e| Error::Storage(format!("Failed to build HTTP client: {}", e)))
+}
+
+/// Parse the parts of a URL the policy needs. Mirrors WHATWG parsing for
+/// the tricks used to smuggle internal hosts (userinfo, backslashes,
+/// percent-encoding, shorthand IPv4).
+pub fn parse_url(url: &str, max_len: usize) -> Result<EgressTarget> {
+    let invalid = |reason: &str| Error::Storage(format!("Invalid URL ({}): {}", reason, url));
+    if url.len() > max_len {
+        return Err(Error::Storage(format!("URL too long (max {} chars)", max_len)));
+    }
+    if url.chars().any(|c| c.is_control() || c.is_whitespace()) {
+        return Err(invalid("contains whitespace or control characters"));
+    }
+    let (scheme, rest) = url.split_once("://").ok_or_else(|| invalid("missing scheme"))?;
+    let scheme = scheme.to_lowercase();
+    if scheme.is_empty() || !scheme.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.')) {
+        return Err(invalid("bad scheme"));
+    }
+
+    let authority = rest.split(['/', '\\', '?', '#']).next().unwrap_or_default();
+    let host_port = authority.rsplit('@').next().unwrap_or_default();
+    let (host, port) = split_host_port(host_port).ok_or_else(|| invalid("bad authority"))?;
+    let host = percent_decode(host).ok_or_else(|| invalid("bad host encoding"))?;
+    let host = host.to_lowercase().trim_end_matches('.').to_string();
+    if host.is_empty() {
+        return Err(invalid("empty host"));
+    }
+    if host.chars().any(|c| matches!(c, '%' | '@' | '\\' | '/' | ' ')) {
+        return Err(invalid("bad host"));
+    }
+
+    let port = match port {
+        Some(p) if !p.is_empty() => p.parse::<u16>().map_err(|_| invalid("bad port"))?,
+        _ => match scheme.as_str() {
+            "http" | "ws" => 80,
+            "https" | "wss" => 443,
+            "mqtt" => 1883,
+            "mqtts" => 8883,
+            "amqp" => 5672,
+            "amqps" => 5671,
+            _ => return Err(invalid("port required for this scheme")),
+        },
+    };
+    let ip = parse_ip_host(&host);
+    Ok(EgressTarget { scheme, host, port, ip })
+}
+
+/// Split `host[:port]` / `[v6][:port]`
+fn split_host_port(authority: &str) -> Option<(&str, Option<&str>)> {
+    if let Some(rest) = authority.strip_prefix('[') {
+        let (host, after) = rest.split_once(']')?;
+        return match after {
+            "" => Some((host, None)),
+            _ => Some((host, Some(after.strip_prefix(':')?))),
+        };
+    }
+    match authority.split_once(':') {
+        Some((host, port)) if !port.contains(':') => Some((host, Some(port))),
+        Some(_) => None,
+        None => Some((authority, None)),
+    }
+}
+
+fn percent_decode(input: &str) -> Option<String> {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = input.get(i + 1..i + 3)?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+/// Parse an IP host, accepting the inet_aton shorthand IPv4 forms browsers
+/// and URL parsers normalise (`127.1`, `0x7f.0.0.1`, `2130706433`, `0177.0.0.1`)
+pub fn parse_ip_host(host: &str) -> Option<IpAddr> {
+    if let Ok(v6) = host.parse::<Ipv6Addr>() {
+        return Some(IpAddr::V6(v6));
+    }
+    let parts: Vec<&str> = host.split('.').collect();
+    if parts.is_empty() || parts.len() > 4 {
+        return None;
+    }
+    let mut numbers = Vec::with_capacity(parts.len());
+    for part in &parts {
+        let lower = part.to_lowercase();
+        let value = if let Some(hex) = lower.strip_prefix("0x") {
+            if hex.is_empty() {
+                0
+            } else {
+                u64::from_str_radix(hex, 16).ok()?
+            }
+        } else if lower.len() > 1 && lower.starts_with('0') {
+            u64::from_str_radix(&lower[1..], 8).ok()?
+        } else {
+            lower.parse::<u64>().ok()?
+        };
+        numbers.push(value);
+    }
+    let (last, leading) = numbers.split_last()?;
+    if leading.iter().any(|n| *n > 255) {
+        return None;
+    }
+    let remaining_bits = 8 * (4 - leading.len() as u32);
+    if *last >= 1u64 << remaining_bits {
+        return None;
+    }
+    let mut value: u32 = 0;
+    for (i, n) in leading.iter().enumerate() {
+        value |= (*n as u32) << (24 - 8 * i as u32);
+    }
+    value |= *last as u32;
+    Some(IpAddr::V4(Ipv4Addr::from(value)))
+}
+
+/// Map IPv4-mapped / NAT64 IPv6 addresses to the IPv4 they carry
+fn canonical_ip(ip: &IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => {
+            let s = v6.segments();
+            if let Some(v4) = v6.to_ipv4_mapped() {
+                IpAddr::V4(v4)
+            } else if s[0] == 0x64 && s[1] == 0xff9b && s[2..6] == [0, 0, 0, 0] {
+                IpAddr::V4(Ipv4Addr::new((s[6] >> 8) as u8, s[6] as u8, (s[7] >> 8) as u8, s[7] as u8))
+            } else {
+                *ip
+            }
+        }
+        IpAddr::V4(_) => *ip,
+    }
+}
+
+fn cidr_contains(net: &IpAddr, bits: u8, ip: &IpAddr) -> bool {
+    match (net, ip) {
+        (IpAddr::V4(n), IpAddr::V4(a)) => {
+            let mask = if bits == 0 { 0 } else { u32::MAX << (32 - bits as u32) };
+            u32::from(*n) & mask == u32::from(*a) & mask
+        }
+        (IpAddr::V6(n), IpAddr::V6(a)) => {
+            let mask = if bits == 0 { 0 } else { u128::MAX << (128 - bits as u32) };
+            u128::from(*n) & mask == u128::from(*a) & mask
+        }
+        _ => false,
+    }
+}
+
+/// Names that always refer to the local machine
+pub fn is_local_hostname(host: &str) -> bool {
+    let host = host.trim_end_matches('.').to_lowercase();
+    host == "localhost" || host.ends_with(".localhost") || host == "localhost.localdomain"
+}
+
+/// Loopback, private, link-local, shared, multicast and reserved ranges
+pub fn is_blocked_ip(ip: &IpAddr) -> bool {
+    match canonical_ip(ip) {
+        IpAddr::V4(v4) => {
+            let o = v4.octets();
+            v4.is_unspecified()
+                || v4.is_loopback()
+                || v4.is_private()
+                || v4.is_link_local()
+                || v4.is_broadcast()
+                || v4.is_multicast()
+                || o[0] == 0                                   // 0.0.0.0/8
+                || (o[0] == 100 && (o[1] & 0xc0) == 64)        // 100.64.0.0/10 (CGNAT)
+                || (o[0] == 192 && o[1] == 0 && o[2] == 0)     // 192.0.0.0/24
+                || (o[0] == 198 && (o[1] & 0xfe) == 18)        // 198.18.0.0/15
+                || o[0] >= 240                                 // 240.0.0.0/4
+        }
+        IpAddr::V6(v6) => {
+            let s = v6.segments();
+            v6.is_unspecified()
+                || v6.is_loopback()
+                || v6.is_multicast()
+                || (s[0] & 0xfe00) == 0xfc00                   // fc00::/7 unique local
+                || (s[0] & 0xffc0) == 0xfe80                   // fe80::/10 link-local
+                || (s[0] & 0xffc0) == 0xfec0                   // fec0::/10 site-local
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(config: EgressConfig) -> EgressPolicy {
+        EgressPolicy::new(config).unwrap()
+    }
+
+    #[test]
+    fn test_blocks_private_and_obfuscated_hosts() {
+        let p = policy(EgressConfig::default());
+        let g = EgressSubsystem::General;
+        assert!(p.check_url(g, "https://example.com/hook").is_ok());
+        for url in [
+            "http://localhost/",
+            "http://127.0.0.1:8080/",
+            "http://127.1/",
+            "http://2130706433/",
+            "http://0x7f.0.0.1/",
+            "http://0177.0.0.1/",
+            "http://%31%32%37.0.0.1/",
+            "http://[::1]/",
+            "http://[::ffff:10.0.0.1]/",
+            "http://169.254.169.254/latest/meta-data",
+            "http://evil.com@192.168.1.1/",
+            "http://api.localhost/",
+            "ftp://example.com:21/",
+        ] {
+            assert!(p.check_url(g, url).is_err(), "{} should be blocked", url);
+        }
+        // Backslash ends the authority, so the real host is example.com
+        assert_eq!(p.check_url(g, "http://example.com\\@127.0.0.1/").unwrap().host, "example.com");
+    }
+
+    #[test]
+    fn test_resolved_addresses_are_checked() {
+        let p = policy(EgressConfig::default());
+        let target = p.check_url(EgressSubsystem::Webhooks, "https://rebind.example/").unwrap();
+        let public: IpAddr = "93.184.216.34".parse().unwrap();
+        let private: IpAddr = "10.1.2.3".parse().unwrap();
+        assert!(p.check_resolved(EgressSubsystem::Webhooks, &target, &[public]).is_ok());
+        assert!(p.check_resolved(EgressSubsystem::Webhooks, &target, &[public, private]).is_err());
+        assert!(p.check_resolved(EgressSubsystem::Webhooks, &target, &[]).is_err());
+    }
+
+    #[test]
+    fn test_allow_deny_and_subsystem_overrides() {
+        let mut config = EgressConfig {
+            deny: vec!["*.internal.example.com".to_string(), "203.0.113.0/24".to_string()],
+            ..Default::default()
+        };
+        config.overrides.insert(EgressSubsystem::Workers, EgressRules {
+            allow: vec!["http://localhost:*".to_string(), "api.partner.com:443".to_string()],
+            ..Default::default()
+        });
+        let p = policy(config);
+
+        // Deny applies everywhere, including to resolved addresses
+        assert!(p.check_url(EgressSubsystem::General, "https://db.internal.example.com/").is_err());
+        let target = p.check_url(EgressSubsystem::General, "https://cdn.example.net/").unwrap();
+        let denied: IpAddr = "203.0.113.7".parse().unwrap();
+        assert!(p.check_resolved(EgressSubsystem::General, &target, &[denied]).is_err());
+
+        // Workers only reach their allowlist; the explicit localhost entry is exempt from private blocking
+        assert!(p.check_url(EgressSubsystem::Workers, "http://localhost:3000/x").is_ok());
+        assert!(p.check_url(EgressSubsystem::Workers, "https://api.partner.com/v1").is_ok());
+        assert!(p.check_url(EgressSubsystem::Workers, "http://api.partner.com/v1").is_err());
+        assert!(p.check_url(EgressSubsystem::Workers, "https://example.com/").is_err());
+        assert!(p.check_url(EgressSubsystem::General, "http://localhost:3000/x").is_err());
+
+        // Defaults: LLM and persistence traffic must use https
+        assert!(p.check_url(EgressSubsystem::Llm, "http://api.openai.com/v1").is_err());
+        assert!(p.check_url(EgressSubsystem::Llm, "https://api.openai.com/v1").is_ok());
+        // Only RDE and ingest connectors may reach MQTT brokers, only RDE AMQP ones; ports default per scheme
+        assert_eq!(p.check_url(EgressSubsystem::Rde, "mqtts://broker.example.com").unwrap().port, 8883);
+        assert_eq!(p.check_url(EgressSubsystem::Rde, "mqtt://broker.example.com").unwrap().port, 1883);
+        assert!(p.check_url(EgressSubsystem::Connectors, "mqtts://broker.example.com").is_ok());
+        assert!(p.check_url(EgressSubsystem::Webhooks, "mqtt://broker.example.com").is_err());
+        assert_eq!(p.check_url(EgressSubsystem::Rde, "amqps://rabbit.example.com").unwrap().port, 5671);
+        assert_eq!(p.check_url(EgressSubsystem::Rde, "amqp://rabbit.example.com").unwrap().port, 5672);
+        assert!(p.check_url(EgressSubsystem::Webhooks, "amqp://rabbit.example.com").is_err());
+        assert!(p.check_url(EgressSubsystem::Connectors, "amqp://rabbit.example.com").is_err());
+
+        assert!(EgressPolicy::new(EgressConfig { allow: vec!["api.*.com".to_string()], ..Default::default() }).is_err());
+    }
+}
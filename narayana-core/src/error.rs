@@ -37,6 +37,12 @@ pub enum Error {
 
     #[error("Configuration error: {0}")]
     Configuration(String),
+
+    #[error("Quota exceeded: {0}")]
+    QuotaExceeded(String),
+
+    #[error("Storage is read-only: {0}")]
+    ReadOnly(String),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
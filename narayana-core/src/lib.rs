@@ -8,9 +8,14 @@ pub mod config;
 pub mod json_support;
 pub mod banner;
 pub mod transforms;
+pub mod egress;
+pub mod wire;
+pub mod skill;
+#[cfg(feature = "arrow")]
+pub mod arrow;
 
 pub use error::{Error, Result};
-pub use schema::{Schema, Field, DataType};
+pub use schema::{Schema, Field, DataType, EmbedAnnotation, EmbedMode};
 pub use row::Row;
 pub use column::Column;
 pub use transaction::{Transaction, TransactionManager, TransactionStatus, Version};
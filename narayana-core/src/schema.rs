@@ -22,6 +22,10 @@ pub enum DataType {
     Nullable(Box<DataType>),
     Array(Box<DataType>),
     Map(Box<DataType>, Box<DataType>),
+    /// WGS84 point, stored as an encoded geometry in a Binary column
+    Point,
+    /// Point, line string or polygon, stored as an encoded geometry in a Binary column
+    Geometry,
 }
 
 impl DataType {
@@ -31,7 +35,7 @@ impl DataType {
             DataType::Int16 | DataType::UInt16 => Some(2),
             DataType::Int32 | DataType::UInt32 | DataType::Float32 => Some(4),
             DataType::Int64 | DataType::UInt64 | DataType::Float64 | DataType::Timestamp | DataType::Date => Some(8),
-            DataType::String | DataType::Binary | DataType::Json | DataType::Nullable(_) | DataType::Array(_) | DataType::Map(_, _) => None,
+            DataType::String | DataType::Binary | DataType::Json | DataType::Nullable(_) | DataType::Array(_) | DataType::Map(_, _) | DataType::Point | DataType::Geometry => None,
         }
     }
 
@@ -48,10 +52,39 @@ pub struct Field {
     pub default_value: Option<serde_json::Value>,
 }
 
+/// How embeddings for an annotated column are computed on insert
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EmbedMode {
+    /// Compute during the insert; the vector column is filled before the write
+    #[default]
+    Sync,
+    /// Queue for background computation; the vector column and index are
+    /// filled in once the worker has embedded the rows
+    Async,
+}
+
+/// Marks a text column as "embed": inserts compute an embedding of `source_column`
+/// into the paired `vector_column` (Binary, little-endian f32) and its vector index
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmbedAnnotation {
+    pub source_column: String,
+    pub vector_column: String,
+    pub dimensions: usize,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub mode: EmbedMode,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct Schema {
     pub fields: Vec<Field>,
     pub field_map: HashMap<String, usize>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub embeddings: Vec<EmbedAnnotation>,
+    /// Column holding each row's expiry time (see `with_ttl_column`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl_column: Option<String>,
 }
 
 impl<'de> Deserialize<'de> for Schema {
@@ -64,6 +97,10 @@ impl<'de> Deserialize<'de> for Schema {
             fields: Vec<Field>,
             #[serde(default)]
             field_map: Option<HashMap<String, usize>>,
+            #[serde(default)]
+            embeddings: Vec<EmbedAnnotation>,
+            #[serde(default)]
+            ttl_column: Option<String>,
         }
         
         let helper = SchemaHelper::deserialize(deserializer)?;
@@ -82,6 +119,8 @@ impl<'de> Deserialize<'de> for Schema {
         Ok(Schema {
             fields: helper.fields,
             field_map,
+            embeddings: helper.embeddings,
+            ttl_column: helper.ttl_column,
         })
     }
 }
@@ -94,7 +133,89 @@ impl Schema {
             .map(|(idx, field)| (field.name.clone(), idx))
             .collect();
 
-        Self { fields, field_map }
+        Self { fields, field_map, embeddings: Vec::new(), ttl_column: None }
+    }
+
+    /// Annotate a text column for automatic embedding on insert
+    pub fn with_embedding(mut self, annotation: EmbedAnnotation) -> Self {
+        self.embeddings.push(annotation);
+        self
+    }
+
+    /// Validate embed annotations: source must be a String column, the paired
+    /// vector column must be Binary, and a column can only be generated once
+    pub fn validate_embeddings(&self) -> crate::Result<()> {
+        let mut generated = std::collections::HashSet::new();
+        for annotation in &self.embeddings {
+            match self.field(&annotation.source_column) {
+                Some(f) if matches!(f.data_type, DataType::String)
+                    || f.data_type == DataType::Nullable(Box::new(DataType::String)) => {}
+                Some(f) => {
+                    return Err(crate::Error::SchemaMismatch(format!(
+                        "Embed source column '{}' must be String, got {:?}",
+                        annotation.source_column, f.data_type
+                    )))
+                }
+                None => return Err(crate::Error::ColumnNotFound(annotation.source_column.clone())),
+            }
+            match self.field(&annotation.vector_column) {
+                Some(f) if f.data_type == DataType::Binary => {}
+                Some(f) => {
+                    return Err(crate::Error::SchemaMismatch(format!(
+                        "Embed vector column '{}' must be Binary, got {:?}",
+                        annotation.vector_column, f.data_type
+                    )))
+                }
+                None => return Err(crate::Error::ColumnNotFound(annotation.vector_column.clone())),
+            }
+            if annotation.dimensions == 0 {
+                return Err(crate::Error::SchemaMismatch(
+                    "Embed annotation dimensions must be greater than zero".to_string(),
+                ));
+            }
+            if !generated.insert(annotation.vector_column.as_str()) {
+                return Err(crate::Error::SchemaMismatch(format!(
+                    "Vector column '{}' is generated by more than one annotation",
+                    annotation.vector_column
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Give rows an expiry time: `column` holds milliseconds since the epoch
+    /// after which the row is hidden from reads and removed by compaction
+    /// (0 or less never expires)
+    pub fn with_ttl_column(mut self, column: impl Into<String>) -> Self {
+        self.ttl_column = Some(column.into());
+        self
+    }
+
+    /// The TTL column must exist and be Timestamp, Int64 or UInt64
+    pub fn validate_ttl(&self) -> crate::Result<()> {
+        let Some(column) = &self.ttl_column else { return Ok(()) };
+        match self.field(column) {
+            Some(f) if matches!(f.data_type, DataType::Timestamp | DataType::Int64 | DataType::UInt64) => Ok(()),
+            Some(f) => Err(crate::Error::SchemaMismatch(format!(
+                "TTL column '{}' must be Timestamp, Int64 or UInt64, got {:?}",
+                column, f.data_type
+            ))),
+            None => Err(crate::Error::ColumnNotFound(column.clone())),
+        }
+    }
+
+    pub fn ttl_field_index(&self) -> Option<usize> {
+        self.ttl_column.as_deref().and_then(|column| self.field_index(column))
+    }
+
+    /// Indexes of columns generated by embed annotations (not supplied on insert)
+    pub fn generated_field_indexes(&self) -> Vec<usize> {
+        let mut indexes: Vec<usize> = self.embeddings.iter()
+            .filter_map(|a| self.field_index(&a.vector_column))
+            .collect();
+        indexes.sort_unstable();
+        indexes.dedup();
+        indexes
     }
 
     pub fn field_index(&self, name: &str) -> Option<usize> {
@@ -168,4 +289,64 @@ mod tests {
         assert_eq!(schema.field("id").unwrap().name, "id");
         assert!(schema.field("nonexistent").is_none());
     }
+
+    #[test]
+    fn test_schema_embed_annotation_validation() {
+        let schema = Schema::new(vec![
+            Field {
+                name: "text".to_string(),
+                data_type: DataType::String,
+                nullable: false,
+                default_value: None,
+            },
+            Field {
+                name: "text_vec".to_string(),
+                data_type: DataType::Binary,
+                nullable: false,
+                default_value: None,
+            },
+        ]);
+        let annotation = EmbedAnnotation {
+            source_column: "text".to_string(),
+            vector_column: "text_vec".to_string(),
+            dimensions: 384,
+            model: None,
+            mode: EmbedMode::Sync,
+        };
+
+        let valid = schema.clone().with_embedding(annotation.clone());
+        assert!(valid.validate_embeddings().is_ok());
+        assert_eq!(valid.generated_field_indexes(), vec![1]);
+
+        let invalid = schema.with_embedding(EmbedAnnotation {
+            source_column: "text_vec".to_string(),
+            ..annotation
+        });
+        assert!(invalid.validate_embeddings().is_err());
+    }
+
+    #[test]
+    fn test_schema_ttl_column() {
+        let field = |name: &str, data_type: DataType| Field {
+            name: name.to_string(),
+            data_type,
+            nullable: false,
+            default_value: None,
+        };
+        let schema = Schema::new(vec![field("id", DataType::Int64), field("expires_at", DataType::Timestamp)]);
+
+        let valid = schema.clone().with_ttl_column("expires_at");
+        assert!(valid.validate_ttl().is_ok());
+        assert_eq!(valid.ttl_field_index(), Some(1));
+        assert!(schema.clone().with_ttl_column("missing").validate_ttl().is_err());
+        let text = Schema::new(vec![field("expires_at", DataType::String)]).with_ttl_column("expires_at");
+        assert!(text.validate_ttl().is_err());
+
+        // Kept through JSON; schemas written before it have none
+        let json = serde_json::to_string(&valid).unwrap();
+        let parsed: Schema = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.ttl_column.as_deref(), Some("expires_at"));
+        let parsed: Schema = serde_json::from_str(&serde_json::to_string(&schema).unwrap()).unwrap();
+        assert_eq!(parsed.ttl_column, None);
+    }
 }
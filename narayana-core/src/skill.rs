@@ -0,0 +1,327 @@
+// Skill packages
+// A skill bundles table schemas, worker scripts, RDE subscriptions and
+// behavior trees under one name and version. Packages are authored as a
+// directory with a `skill.yaml` (or `skill.json`) manifest whose workers and
+// behaviors may point at files; loading resolves those into a self-contained
+// bundle that can be sent to a server and installed as a unit.
+
+use crate::error::{Error, Result};
+use crate::schema::Schema;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fmt;
+use std::path::{Component, Path};
+
+/// Manifest file names, in lookup order
+pub const MANIFEST_FILES: [&str; 3] = ["skill.yaml", "skill.yml", "skill.json"];
+
+/// Largest file a manifest may reference
+const MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
+
+/// Skill version, `MAJOR.MINOR.PATCH`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SkillVersion {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl SkillVersion {
+    pub fn parse(version: &str) -> Result<Self> {
+        let parts: Vec<&str> = version.trim().split('.').collect();
+        let numbers: Option<Vec<u64>> = parts.iter().map(|p| p.parse().ok()).collect();
+        match numbers.as_deref() {
+            Some([major, minor, patch]) => Ok(Self { major: *major, minor: *minor, patch: *patch }),
+            _ => Err(Error::Configuration(format!("Invalid skill version '{}' (expected MAJOR.MINOR.PATCH)", version))),
+        }
+    }
+}
+
+impl Ord for SkillVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch).cmp(&(other.major, other.minor, other.patch))
+    }
+}
+
+impl PartialOrd for SkillVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for SkillVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Table the skill creates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillTable {
+    pub name: String,
+    pub schema: Schema,
+}
+
+/// Worker the skill deploys
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillWorker {
+    pub name: String,
+    pub route: String,
+    /// Inline script; filled from `code_file` when the package is loaded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// Script path, relative to the package directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code_file: Option<String>,
+    #[serde(default)]
+    pub allowed_urls: Vec<String>,
+}
+
+/// RDE subscription the skill registers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillSubscription {
+    /// Event name, `actor:event` or a bare event name
+    pub event: String,
+    /// RDE transport (webhook, websocket, grpc, sse, worker, table)
+    pub transport: String,
+    /// Worker of this skill to invoke (worker transport)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub worker: Option<String>,
+    /// Transport-specific configuration
+    #[serde(default)]
+    pub config: serde_json::Value,
+}
+
+/// Behavior tree the skill installs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillBehavior {
+    /// Inline tree definition; filled from `file` when the package is loaded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub definition: Option<serde_json::Value>,
+    /// JSON or YAML tree definition, relative to the package directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+}
+
+/// Skill manifest; a bundle is a manifest with every file inlined
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillManifest {
+    pub name: String,
+    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub tables: Vec<SkillTable>,
+    #[serde(default)]
+    pub workers: Vec<SkillWorker>,
+    #[serde(default)]
+    pub subscriptions: Vec<SkillSubscription>,
+    #[serde(default)]
+    pub behaviors: Vec<SkillBehavior>,
+}
+
+impl SkillManifest {
+    /// Load a package directory (or a single manifest file) into a bundle
+    pub fn load(path: &Path) -> Result<Self> {
+        let (dir, manifest_path) = if path.is_dir() {
+            let manifest = MANIFEST_FILES.iter()
+                .map(|name| path.join(name))
+                .find(|candidate| candidate.is_file())
+                .ok_or_else(|| Error::Configuration(format!("No skill manifest in {}", path.display())))?;
+            (path.to_path_buf(), manifest)
+        } else {
+            (path.parent().map(Path::to_path_buf).unwrap_or_default(), path.to_path_buf())
+        };
+
+        let text = read_file(&manifest_path)?;
+        let mut manifest: Self = parse_document(&manifest_path, &text)?;
+        for worker in &mut manifest.workers {
+            if let Some(file) = worker.code_file.take() {
+                if worker.code.is_some() {
+                    return Err(Error::Configuration(format!("Worker '{}' has both code and code_file", worker.name)));
+                }
+                worker.code = Some(read_file(&resolve(&dir, &file)?)?);
+            }
+        }
+        for behavior in &mut manifest.behaviors {
+            if let Some(file) = behavior.file.take() {
+                if behavior.definition.is_some() {
+                    return Err(Error::Configuration(format!("Behavior '{}' has both definition and file", file)));
+                }
+                let path = resolve(&dir, &file)?;
+                behavior.definition = Some(parse_document(&path, &read_file(&path)?)?);
+            }
+        }
+        manifest.validate()?;
+        Ok(manifest)
+    }
+
+    pub fn parsed_version(&self) -> Result<SkillVersion> {
+        SkillVersion::parse(&self.version)
+    }
+
+    /// Check the bundle is self-contained and consistent
+    pub fn validate(&self) -> Result<()> {
+        if self.name.is_empty() || self.name.len() > 128
+            || !self.name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(Error::Configuration(format!("Invalid skill name '{}'", self.name)));
+        }
+        self.parsed_version()?;
+
+        let mut tables = HashSet::new();
+        for table in &self.tables {
+            if !tables.insert(table.name.as_str()) {
+                return Err(Error::Configuration(format!("Duplicate table '{}'", table.name)));
+            }
+            if table.schema.fields.is_empty() {
+                return Err(Error::Configuration(format!("Table '{}' has no fields", table.name)));
+            }
+        }
+
+        let mut workers = HashSet::new();
+        for worker in &self.workers {
+            if !workers.insert(worker.name.as_str()) {
+                return Err(Error::Configuration(format!("Duplicate worker '{}'", worker.name)));
+            }
+            if worker.code.as_deref().is_none_or(str::is_empty) {
+                return Err(Error::Configuration(format!("Worker '{}' has no code", worker.name)));
+            }
+        }
+
+        for subscription in &self.subscriptions {
+            if subscription.event.is_empty() {
+                return Err(Error::Configuration("Subscription event cannot be empty".to_string()));
+            }
+            if let Some(worker) = &subscription.worker {
+                if !workers.contains(worker.as_str()) {
+                    return Err(Error::Configuration(format!(
+                        "Subscription to '{}' names unknown worker '{}'",
+                        subscription.event, worker
+                    )));
+                }
+            }
+        }
+
+        let mut behaviors = HashSet::new();
+        for behavior in &self.behaviors {
+            let name = behavior.definition.as_ref()
+                .and_then(|d| d.get("name"))
+                .and_then(|n| n.as_str())
+                .ok_or_else(|| Error::Configuration("Behavior definitions need a name".to_string()))?;
+            if !behaviors.insert(name) {
+                return Err(Error::Configuration(format!("Duplicate behavior '{}'", name)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Names of the behavior trees in the bundle
+    pub fn behavior_names(&self) -> Vec<String> {
+        self.behaviors.iter()
+            .filter_map(|b| b.definition.as_ref()?.get("name")?.as_str().map(str::to_string))
+            .collect()
+    }
+}
+
+/// Resolve a package-relative path, refusing to leave the package
+fn resolve(dir: &Path, file: &str) -> Result<std::path::PathBuf> {
+    let relative = Path::new(file);
+    if relative.is_absolute() || relative.components().any(|c| !matches!(c, Component::Normal(_) | Component::CurDir)) {
+        return Err(Error::Configuration(format!("Skill file '{}' must be inside the package", file)));
+    }
+    Ok(dir.join(relative))
+}
+
+fn read_file(path: &Path) -> Result<String> {
+    let size = std::fs::metadata(path)?.len();
+    if size > MAX_FILE_SIZE {
+        return Err(Error::Configuration(format!("{} is too large ({} bytes)", path.display(), size)));
+    }
+    Ok(std::fs::read_to_string(path)?)
+}
+
+/// Parse JSON or YAML by file extension
+fn parse_document<T: for<'de> Deserialize<'de>>(path: &Path, text: &str) -> Result<T> {
+    let yaml = matches!(path.extension().and_then(|e| e.to_str()), Some("yaml" | "yml"));
+    if yaml {
+        serde_yaml::from_str(text).map_err(|e| Error::Deserialization(format!("{}: {}", path.display(), e)))
+    } else {
+        serde_json::from_str(text).map_err(|e| Error::Deserialization(format!("{}: {}", path.display(), e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"
+name: pick-and-place
+version: 1.2.0
+tables:
+  - name: grasps
+    schema:
+      fields:
+        - { name: object, data_type: String, nullable: false }
+        - { name: score, data_type: Float64, nullable: false }
+workers:
+  - name: scorer
+    route: /skills/pick/score
+    code_file: workers/score.js
+subscriptions:
+  - event: "vision:object_detected"
+    transport: worker
+    worker: scorer
+behaviors:
+  - file: behaviors/pick.yaml
+"#;
+
+    fn package(manifest: &str) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("workers")).unwrap();
+        std::fs::create_dir_all(dir.path().join("behaviors")).unwrap();
+        std::fs::write(dir.path().join("skill.yaml"), manifest).unwrap();
+        std::fs::write(dir.path().join("workers/score.js"), "export default { fetch() {} }").unwrap();
+        std::fs::write(
+            dir.path().join("behaviors/pick.yaml"),
+            "name: pick\nroot:\n  type: action\n  capability: grasp\n",
+        ).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_load_inlines_files() {
+        let dir = package(MANIFEST);
+        let bundle = SkillManifest::load(dir.path()).unwrap();
+        assert_eq!(bundle.parsed_version().unwrap(), SkillVersion { major: 1, minor: 2, patch: 0 });
+        assert!(bundle.workers[0].code.as_deref().unwrap().contains("fetch"));
+        assert!(bundle.workers[0].code_file.is_none());
+        assert_eq!(bundle.behavior_names(), vec!["pick"]);
+        assert_eq!(bundle.tables[0].schema.fields.len(), 2);
+
+        // The bundle round-trips through JSON on its way to the server
+        let json = serde_json::to_string(&bundle).unwrap();
+        let sent: SkillManifest = serde_json::from_str(&json).unwrap();
+        assert!(sent.validate().is_ok());
+    }
+
+    #[test]
+    fn test_load_rejects_invalid_packages() {
+        let escape = package(&MANIFEST.replace("workers/score.js", "../score.js"));
+        assert!(SkillManifest::load(escape.path()).is_err());
+        let unknown_worker = package(&MANIFEST.replace("worker: scorer", "worker: planner"));
+        assert!(SkillManifest::load(unknown_worker.path()).is_err());
+        let bad_version = package(&MANIFEST.replace("1.2.0", "latest"));
+        assert!(SkillManifest::load(bad_version.path()).is_err());
+    }
+
+    #[test]
+    fn test_version_ordering() {
+        let v = |s: &str| SkillVersion::parse(s).unwrap();
+        assert!(v("1.10.0") > v("1.9.3"));
+        assert!(v("2.0.0") > v("1.99.99"));
+        assert_eq!(v("1.2.3").to_string(), "1.2.3");
+        assert!(SkillVersion::parse("1.2").is_err());
+    }
+}
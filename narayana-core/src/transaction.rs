@@ -90,6 +90,30 @@ impl TransactionManager {
     pub fn get_transaction(&self, id: TransactionId) -> Option<&Transaction> {
         self.active_transactions.get(&id)
     }
+
+    /// Note that an active transaction read a table
+    pub fn record_read(&mut self, id: TransactionId, table_id: u64) -> crate::Result<()> {
+        let txn = self.active_transactions.get_mut(&id)
+            .ok_or_else(|| crate::Error::Transaction(format!("Transaction {} not found", id.0)))?;
+        if !txn.read_set.contains(&table_id) {
+            txn.read_set.push(table_id);
+        }
+        Ok(())
+    }
+
+    /// Note that an active transaction wrote a table
+    pub fn record_write(&mut self, id: TransactionId, table_id: u64) -> crate::Result<()> {
+        let txn = self.active_transactions.get_mut(&id)
+            .ok_or_else(|| crate::Error::Transaction(format!("Transaction {} not found", id.0)))?;
+        if !txn.write_set.contains(&table_id) {
+            txn.write_set.push(table_id);
+        }
+        Ok(())
+    }
+
+    pub fn active_count(&self) -> usize {
+        self.active_transactions.len()
+    }
 }
 
 #[cfg(test)]
@@ -148,6 +172,23 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_transaction_manager_read_write_sets() {
+        let mut manager = TransactionManager::new();
+        let id = manager.begin_transaction();
+        manager.record_read(id, 1).unwrap();
+        manager.record_write(id, 2).unwrap();
+        manager.record_write(id, 2).unwrap();
+        let txn = manager.get_transaction(id).unwrap();
+        assert_eq!(txn.read_set, vec![1]);
+        assert_eq!(txn.write_set, vec![2]);
+        assert_eq!(manager.active_count(), 1);
+
+        manager.commit_transaction(id).unwrap();
+        assert!(manager.record_write(id, 3).is_err());
+        assert_eq!(manager.active_count(), 0);
+    }
+
     #[test]
     fn test_transaction_manager_abort_nonexistent() {
         let mut manager = TransactionManager::new();
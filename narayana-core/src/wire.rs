@@ -0,0 +1,233 @@
+// WebSocket wire formats
+// JSON text frames by default; MessagePack or CBOR binary frames when the client
+// negotiates one through Sec-WebSocket-Protocol. Binary frames carry a one-byte
+// message type tag ahead of the payload so receivers can route or reject a frame
+// without decoding it.
+
+use crate::error::{Error, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+pub const JSON_SUBPROTOCOL: &str = "narayana.json";
+pub const MSGPACK_SUBPROTOCOL: &str = "narayana.msgpack";
+pub const CBOR_SUBPROTOCOL: &str = "narayana.cbor";
+
+/// Messages that can travel in a tagged binary frame
+pub trait WireTagged {
+    /// Type tag written ahead of the binary payload
+    fn wire_tag(&self) -> u8;
+}
+
+/// Encoding used on a WebSocket connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WireFormat {
+    #[default]
+    Json,
+    MessagePack,
+    Cbor,
+}
+
+/// One encoded WebSocket frame
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WireFrame {
+    Text(String),
+    /// `[type tag][payload]`
+    Binary(Vec<u8>),
+}
+
+impl WireFrame {
+    pub fn len(&self) -> usize {
+        match self {
+            WireFrame::Text(text) => text.len(),
+            WireFrame::Binary(bytes) => bytes.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Type tag of a binary frame, without decoding the payload
+    pub fn tag(&self) -> Option<u8> {
+        match self {
+            WireFrame::Text(_) => None,
+            WireFrame::Binary(bytes) => bytes.first().copied(),
+        }
+    }
+}
+
+impl WireFormat {
+    /// Sub-protocols a server accepts, most compact first
+    pub const SUBPROTOCOLS: [&'static str; 3] = [MSGPACK_SUBPROTOCOL, CBOR_SUBPROTOCOL, JSON_SUBPROTOCOL];
+
+    pub fn subprotocol(self) -> &'static str {
+        match self {
+            WireFormat::Json => JSON_SUBPROTOCOL,
+            WireFormat::MessagePack => MSGPACK_SUBPROTOCOL,
+            WireFormat::Cbor => CBOR_SUBPROTOCOL,
+        }
+    }
+
+    pub fn from_subprotocol(name: &str) -> Option<Self> {
+        let name = name.trim();
+        [WireFormat::MessagePack, WireFormat::Cbor, WireFormat::Json]
+            .into_iter()
+            .find(|format| format.subprotocol().eq_ignore_ascii_case(name))
+    }
+
+    /// Format for the sub-protocol selected during the upgrade; JSON when none was
+    pub fn negotiated(selected: Option<&str>) -> Self {
+        selected.and_then(Self::from_subprotocol).unwrap_or_default()
+    }
+
+    pub fn is_binary(self) -> bool {
+        !matches!(self, WireFormat::Json)
+    }
+
+    /// Encode a message, tagging binary frames with its `wire_tag`
+    pub fn encode<T: Serialize + WireTagged>(self, message: &T) -> Result<WireFrame> {
+        self.encode_tagged(message.wire_tag(), message)
+    }
+
+    /// Encode a value with an explicit type tag (ignored for JSON)
+    pub fn encode_tagged<T: Serialize + ?Sized>(self, tag: u8, value: &T) -> Result<WireFrame> {
+        let mut buf = vec![tag];
+        match self {
+            WireFormat::Json => {
+                return serde_json::to_string(value)
+                    .map(WireFrame::Text)
+                    .map_err(|e| Error::Serialization(e.to_string()));
+            }
+            // Named encoding keeps struct fields as map keys, which
+            // internally tagged enums need to decode again
+            WireFormat::MessagePack => rmp_serde::encode::write_named(&mut buf, value)
+                .map_err(|e| Error::Serialization(format!("MessagePack: {}", e)))?,
+            WireFormat::Cbor => ciborium::ser::into_writer(value, &mut buf)
+                .map_err(|e| Error::Serialization(format!("CBOR: {}", e)))?,
+        }
+        Ok(WireFrame::Binary(buf))
+    }
+
+    /// Decode a frame and check its type tag against the decoded message.
+    /// JSON text frames are accepted on every format.
+    pub fn decode<T: DeserializeOwned + WireTagged>(self, frame: &WireFrame) -> Result<T> {
+        let (tag, message) = self.decode_tagged::<T>(frame)?;
+        match tag {
+            Some(tag) if tag != message.wire_tag() => Err(Error::Deserialization(format!(
+                "Frame type tag {:#04x} does not match message type {:#04x}",
+                tag,
+                message.wire_tag()
+            ))),
+            _ => Ok(message),
+        }
+    }
+
+    /// Decode a frame, returning the binary type tag (if any) alongside the value
+    pub fn decode_tagged<T: DeserializeOwned>(self, frame: &WireFrame) -> Result<(Option<u8>, T)> {
+        let bytes = match frame {
+            WireFrame::Text(text) => {
+                return serde_json::from_str(text)
+                    .map(|value| (None, value))
+                    .map_err(|e| Error::Deserialization(e.to_string()));
+            }
+            WireFrame::Binary(bytes) => bytes,
+        };
+        let (&tag, payload) = bytes
+            .split_first()
+            .ok_or_else(|| Error::Deserialization("Empty binary frame".to_string()))?;
+        let value = match self {
+            WireFormat::Json => {
+                return Err(Error::Deserialization(
+                    "Binary frame on a JSON connection".to_string(),
+                ))
+            }
+            WireFormat::MessagePack => rmp_serde::from_slice(payload)
+                .map_err(|e| Error::Deserialization(format!("MessagePack: {}", e)))?,
+            WireFormat::Cbor => ciborium::de::from_reader(payload)
+                .map_err(|e| Error::Deserialization(format!("CBOR: {}", e)))?,
+        };
+        Ok((Some(tag), value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(tag = "type")]
+    enum Msg {
+        #[serde(rename = "ping")]
+        Ping { id: Option<String> },
+        #[serde(rename = "event")]
+        Event { channel: String, event: serde_json::Value },
+    }
+
+    impl WireTagged for Msg {
+        fn wire_tag(&self) -> u8 {
+            match self {
+                Msg::Ping { .. } => 0x01,
+                Msg::Event { .. } => 0x02,
+            }
+        }
+    }
+
+    fn event() -> Msg {
+        Msg::Event {
+            channel: "db:1".to_string(),
+            event: json!({"rows": [1, 2, 3], "name": "x", "nested": {"ok": true}}),
+        }
+    }
+
+    #[test]
+    fn test_binary_round_trip() {
+        for format in [WireFormat::MessagePack, WireFormat::Cbor] {
+            let frame = format.encode(&event()).unwrap();
+            assert!(matches!(frame, WireFrame::Binary(_)));
+            assert_eq!(frame.tag(), Some(0x02));
+            assert_eq!(format.decode::<Msg>(&frame).unwrap(), event());
+        }
+    }
+
+    #[test]
+    fn test_json_is_text_and_accepted_everywhere() {
+        let frame = WireFormat::Json.encode(&event()).unwrap();
+        assert_eq!(frame.tag(), None);
+        let WireFrame::Text(ref text) = frame else { panic!("expected text frame") };
+        assert!(text.contains("\"type\":\"event\""));
+        for format in [WireFormat::Json, WireFormat::MessagePack, WireFormat::Cbor] {
+            assert_eq!(format.decode::<Msg>(&frame).unwrap(), event());
+        }
+    }
+
+    #[test]
+    fn test_binary_is_smaller_than_json() {
+        let json = WireFormat::Json.encode(&event()).unwrap();
+        let msgpack = WireFormat::MessagePack.encode(&event()).unwrap();
+        assert!(msgpack.len() < json.len());
+    }
+
+    #[test]
+    fn test_rejects_bad_frames() {
+        let mut frame = WireFormat::MessagePack.encode(&event()).unwrap();
+        if let WireFrame::Binary(ref mut bytes) = frame {
+            bytes[0] = 0x01;
+        }
+        assert!(WireFormat::MessagePack.decode::<Msg>(&frame).is_err());
+        assert!(WireFormat::Json.decode::<Msg>(&frame).is_err());
+        assert!(WireFormat::Cbor.decode::<Msg>(&WireFrame::Binary(Vec::new())).is_err());
+    }
+
+    #[test]
+    fn test_negotiation() {
+        assert_eq!(WireFormat::negotiated(Some("narayana.msgpack")), WireFormat::MessagePack);
+        assert_eq!(WireFormat::negotiated(Some(" Narayana.CBOR ")), WireFormat::Cbor);
+        assert_eq!(WireFormat::negotiated(Some("graphql-ws")), WireFormat::Json);
+        assert_eq!(WireFormat::negotiated(None), WireFormat::Json);
+        for name in WireFormat::SUBPROTOCOLS {
+            assert_eq!(WireFormat::from_subprotocol(name).unwrap().subprotocol(), name);
+        }
+    }
+}
@@ -0,0 +1,60 @@
+//! Configuration for conversation sessions
+
+use serde::{Deserialize, Serialize};
+
+/// Conversation configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DialogConfig {
+    /// System prompt sent ahead of the history on every turn
+    pub system_prompt: Option<String>,
+    /// Turns of history kept per session (user and assistant turns count separately)
+    pub max_history_turns: usize,
+    /// Let the user interrupt the assistant by speaking
+    pub barge_in: bool,
+    /// Stay in the speaking state after the last audio is sent, until the
+    /// client reports playback finished
+    pub wait_for_playback: bool,
+    /// Longest text sent to speech synthesis at once; longer sentences are
+    /// split at a word boundary
+    pub max_chunk_chars: usize,
+    /// Longest accepted user utterance
+    pub max_utterance_chars: usize,
+    /// Sessions an orchestrator serves at once
+    pub max_sessions: usize,
+}
+
+impl Default for DialogConfig {
+    fn default() -> Self {
+        Self {
+            system_prompt: None,
+            max_history_turns: 20,
+            barge_in: true,
+            wait_for_playback: true,
+            max_chunk_chars: 200,
+            max_utterance_chars: 10_000,
+            max_sessions: 1_000,
+        }
+    }
+}
+
+impl DialogConfig {
+    /// Validate configuration values
+    pub fn validate(&self) -> Result<(), String> {
+        if self.system_prompt.as_ref().is_some_and(|p| p.len() > 100_000) {
+            return Err("system_prompt must be at most 100000 bytes".to_string());
+        }
+        if self.max_history_turns == 0 || self.max_history_turns > 100 {
+            return Err("max_history_turns must be between 1 and 100".to_string());
+        }
+        if self.max_chunk_chars < 20 || self.max_chunk_chars > 10_000 {
+            return Err("max_chunk_chars must be between 20 and 10000".to_string());
+        }
+        if self.max_utterance_chars == 0 || self.max_utterance_chars > 100_000 {
+            return Err("max_utterance_chars must be between 1 and 100000".to_string());
+        }
+        if self.max_sessions == 0 {
+            return Err("max_sessions must be greater than 0".to_string());
+        }
+        Ok(())
+    }
+}
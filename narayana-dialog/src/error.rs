@@ -0,0 +1,44 @@
+//! Error types for narayana-dialog
+
+use narayana_core::Error as CoreError;
+use thiserror::Error;
+
+/// Conversation errors
+#[derive(Error, Debug)]
+pub enum DialogError {
+    #[error("Configuration error: {0}")]
+    Config(String),
+
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+
+    #[error("Session busy: {0}")]
+    Busy(String),
+
+    #[error("Session closed")]
+    Closed,
+
+    #[error("Session limit reached ({0})")]
+    SessionLimit(usize),
+
+    #[error("Recognition error: {0}")]
+    Recognition(String),
+
+    #[error("Response error: {0}")]
+    Response(String),
+
+    #[error("Synthesis error: {0}")]
+    Synthesis(String),
+
+    #[error("Presentation error: {0}")]
+    Presentation(String),
+
+    #[error("Core error: {0}")]
+    Core(#[from] CoreError),
+}
+
+impl From<DialogError> for CoreError {
+    fn from(err: DialogError) -> Self {
+        CoreError::Storage(format!("Dialog error: {}", err))
+    }
+}
@@ -0,0 +1,163 @@
+//! Stage implementations backed by the other narayana crates
+//!
+//! Each is behind its own feature: `llm`, `audio-input`, `tts` and `avatar`.
+
+#[cfg(feature = "llm")]
+pub use self::llm::LlmResponder;
+#[cfg(feature = "audio-input")]
+pub use self::audio_input::AudioRecognizer;
+#[cfg(feature = "tts")]
+pub use self::tts::SpeechVoice;
+#[cfg(feature = "avatar")]
+pub use self::avatar::AvatarPresenter;
+
+#[cfg(feature = "llm")]
+mod llm {
+    use crate::error::DialogError;
+    use crate::session::{DialogTurn, Speaker};
+    use crate::stages::Responder;
+    use async_trait::async_trait;
+    use narayana_llm::{LLMManager, Message, MessageRole, Provider};
+    use std::sync::Arc;
+    use tokio::sync::mpsc;
+
+    /// Replies with an LLM chat completion
+    ///
+    /// The completion arrives whole; the session still voices it sentence by
+    /// sentence, so the first audio follows as soon as the first sentence is
+    /// synthesized.
+    pub struct LlmResponder {
+        manager: Arc<LLMManager>,
+        provider: Option<Provider>,
+    }
+
+    impl LlmResponder {
+        pub fn new(manager: Arc<LLMManager>, provider: Option<Provider>) -> Self {
+            Self { manager, provider }
+        }
+    }
+
+    #[async_trait]
+    impl Responder for LlmResponder {
+        async fn respond(
+            &self,
+            system_prompt: Option<&str>,
+            history: &[DialogTurn],
+            chunks: mpsc::Sender<String>,
+        ) -> Result<(), DialogError> {
+            let mut messages = Vec::with_capacity(history.len() + 1);
+            if let Some(prompt) = system_prompt {
+                messages.push(Message { role: MessageRole::System, content: prompt.to_string() });
+            }
+            messages.extend(history.iter().map(|turn| Message {
+                role: match turn.speaker {
+                    Speaker::User => MessageRole::User,
+                    Speaker::Assistant => MessageRole::Assistant,
+                },
+                content: turn.text.clone(),
+            }));
+            // `chat` holds lock guards across awaits, so its future isn't `Send`;
+            // drive it on a blocking thread instead of the session task
+            let manager = Arc::clone(&self.manager);
+            let provider = self.provider;
+            let runtime = tokio::runtime::Handle::current();
+            let reply = tokio::task::spawn_blocking(move || runtime.block_on(manager.chat(messages, provider)))
+                .await
+                .map_err(|e| DialogError::Response(format!("LLM task failed: {}", e)))?
+                .map_err(|e| DialogError::Response(e.to_string()))?;
+            // A closed channel means the turn was interrupted
+            let _ = chunks.send(reply).await;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "audio-input")]
+mod audio_input {
+    use crate::error::DialogError;
+    use crate::stages::Recognizer;
+    use async_trait::async_trait;
+    use bytes::Bytes;
+    use narayana_sc::LlmAudioProcessor;
+    use std::sync::Arc;
+
+    /// Transcribes utterances with the sound-capture voice-to-text processor
+    pub struct AudioRecognizer {
+        processor: Arc<LlmAudioProcessor>,
+    }
+
+    impl AudioRecognizer {
+        pub fn new(processor: Arc<LlmAudioProcessor>) -> Self {
+            Self { processor }
+        }
+    }
+
+    #[async_trait]
+    impl Recognizer for AudioRecognizer {
+        async fn recognize(&self, audio: Bytes) -> Result<Option<String>, DialogError> {
+            self.processor.process_audio_to_text(&audio).await
+                .map_err(|e| DialogError::Recognition(e.to_string()))
+        }
+    }
+}
+
+#[cfg(feature = "tts")]
+mod tts {
+    use crate::error::DialogError;
+    use crate::stages::Voice;
+    use async_trait::async_trait;
+    use bytes::Bytes;
+    use narayana_spk::SpeechSynthesizer;
+    use std::sync::Arc;
+
+    /// Voices replies with the speech synthesizer
+    pub struct SpeechVoice {
+        synthesizer: Arc<SpeechSynthesizer>,
+    }
+
+    impl SpeechVoice {
+        pub fn new(synthesizer: Arc<SpeechSynthesizer>) -> Self {
+            Self { synthesizer }
+        }
+    }
+
+    #[async_trait]
+    impl Voice for SpeechVoice {
+        async fn synthesize(&self, text: &str) -> Result<Bytes, DialogError> {
+            self.synthesizer.speak(text).await
+                .map_err(|e| DialogError::Synthesis(e.to_string()))
+        }
+    }
+}
+
+#[cfg(feature = "avatar")]
+mod avatar {
+    use crate::error::DialogError;
+    use crate::stages::Presenter;
+    use async_trait::async_trait;
+    use bytes::Bytes;
+    use narayana_me::AvatarBroker;
+    use std::sync::Arc;
+
+    /// Lip-syncs an avatar to the reply audio
+    pub struct AvatarPresenter {
+        broker: Arc<AvatarBroker>,
+    }
+
+    impl AvatarPresenter {
+        pub fn new(broker: Arc<AvatarBroker>) -> Self {
+            Self { broker }
+        }
+    }
+
+    #[async_trait]
+    impl Presenter for AvatarPresenter {
+        async fn present(&self, _text: &str, audio: Option<&Bytes>) -> Result<(), DialogError> {
+            let Some(audio) = audio else {
+                return Ok(());
+            };
+            self.broker.send_audio(audio.to_vec()).await
+                .map_err(|e| DialogError::Presentation(e.to_string()))
+        }
+    }
+}
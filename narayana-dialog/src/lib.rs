@@ -0,0 +1,25 @@
+//! narayana-dialog: Conversation orchestration for talking avatars
+//!
+//! Coordinates the stages of a spoken conversation:
+//! - Speech recognition (narayana-sc) turns user audio into text
+//! - Response generation (narayana-llm) streams the reply text
+//! - Speech synthesis (narayana-spk) voices the reply sentence by sentence
+//! - Presentation (narayana-me) lip-syncs the avatar to the audio
+//!
+//! Each conversation is a `DialogSession` that manages turn-taking, barge-in
+//! and history; `ConversationOrchestrator` serves one session per WebSocket.
+//! Stages are traits, so any of them can be swapped or mocked; the concrete
+//! integrations are behind feature flags and off by default.
+
+pub mod error;
+pub mod config;
+pub mod stages;
+pub mod session;
+pub mod orchestrator;
+pub mod integrations;
+
+pub use error::DialogError;
+pub use config::DialogConfig;
+pub use stages::{DialogStages, Recognizer, Responder, Voice, Presenter};
+pub use session::{DialogSession, DialogEvent, DialogTurn, Speaker, TurnState};
+pub use orchestrator::ConversationOrchestrator;
@@ -0,0 +1,190 @@
+//! Conversation orchestrator: one dialog session per WebSocket
+//!
+//! Clients connect to `/dialog/ws` and exchange JSON text frames:
+//! - client → server: `{"type": "speech_start"}` (barge-in),
+//!   `{"type": "utterance", "text": "..."}`, `{"type": "interrupt"}` and
+//!   `{"type": "playback_finished"}`; a binary frame is an audio utterance
+//! - server → client: `DialogEvent`s as JSON, with reply audio as binary
+//!   frames following their `assistant_text` event
+
+use crate::config::DialogConfig;
+use crate::error::DialogError;
+use crate::session::{DialogEvent, DialogSession};
+use crate::stages::DialogStages;
+use axum::{
+    extract::ws::{Message, WebSocket, WebSocketUpgrade},
+    extract::State,
+    response::Response,
+    routing::get,
+    Router,
+};
+use bytes::Bytes;
+use futures_util::{SinkExt, StreamExt};
+use parking_lot::RwLock;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+
+/// Largest client frame accepted (audio utterances included)
+const MAX_CLIENT_FRAME: usize = 10 * 1024 * 1024;
+
+/// Messages sent by clients
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClientMessage {
+    SpeechStart,
+    Utterance { text: String },
+    Interrupt,
+    PlaybackFinished,
+}
+
+/// Creates and tracks conversation sessions
+pub struct ConversationOrchestrator {
+    config: DialogConfig,
+    stages: DialogStages,
+    sessions: RwLock<HashMap<String, Arc<DialogSession>>>,
+}
+
+impl ConversationOrchestrator {
+    pub fn new(config: DialogConfig, stages: DialogStages) -> Result<Self, DialogError> {
+        config.validate().map_err(DialogError::Config)?;
+        Ok(Self {
+            config,
+            stages,
+            sessions: RwLock::new(HashMap::new()),
+        })
+    }
+
+    /// Start a new conversation
+    pub fn create_session(&self) -> Result<Arc<DialogSession>, DialogError> {
+        let mut sessions = self.sessions.write();
+        if sessions.len() >= self.config.max_sessions {
+            return Err(DialogError::SessionLimit(self.config.max_sessions));
+        }
+        let session = DialogSession::new(self.config.clone(), self.stages.clone())?;
+        sessions.insert(session.id().to_string(), Arc::clone(&session));
+        info!("Dialog session {} started", session.id());
+        Ok(session)
+    }
+
+    pub fn session(&self, session_id: &str) -> Option<Arc<DialogSession>> {
+        self.sessions.read().get(session_id).cloned()
+    }
+
+    /// End a conversation; returns false if it didn't exist
+    pub fn close_session(&self, session_id: &str) -> bool {
+        let Some(session) = self.sessions.write().remove(session_id) else {
+            return false;
+        };
+        session.close();
+        info!("Dialog session {} closed", session_id);
+        true
+    }
+
+    pub fn session_count(&self) -> usize {
+        self.sessions.read().len()
+    }
+
+    /// Router serving `/dialog/ws`
+    pub fn router(self: Arc<Self>) -> Router {
+        Router::new()
+            .route("/dialog/ws", get(websocket_handler))
+            .with_state(self)
+    }
+}
+
+async fn websocket_handler(
+    ws: WebSocketUpgrade,
+    State(orchestrator): State<Arc<ConversationOrchestrator>>,
+) -> Response {
+    ws.max_message_size(MAX_CLIENT_FRAME)
+        .on_upgrade(move |socket| handle_socket(socket, orchestrator))
+}
+
+async fn handle_socket(socket: WebSocket, orchestrator: Arc<ConversationOrchestrator>) {
+    let (mut sender, mut receiver) = socket.split();
+    let session = match orchestrator.create_session() {
+        Ok(session) => session,
+        Err(e) => {
+            warn!("Rejecting dialog connection: {}", e);
+            let event = DialogEvent::Error { turn: None, message: e.to_string() };
+            if let Ok(json) = serde_json::to_string(&event) {
+                let _ = sender.send(Message::Text(json)).await;
+            }
+            let _ = sender.close().await;
+            return;
+        }
+    };
+    let session_id = session.id().to_string();
+
+    let mut events = session.subscribe();
+    let started = DialogEvent::SessionStarted { session_id: session_id.clone() };
+    let mut send_task = tokio::spawn(async move {
+        let mut next = Some(started);
+        loop {
+            let event = match next.take() {
+                Some(event) => event,
+                None => match events.recv().await {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("Dialog client lagged, skipped {} events", skipped);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+            };
+            let message = match &event {
+                DialogEvent::Audio { data, .. } => Message::Binary(data.to_vec()),
+                event => match serde_json::to_string(event) {
+                    Ok(json) => Message::Text(json),
+                    Err(e) => {
+                        warn!("Failed to serialize dialog event: {}", e);
+                        continue;
+                    }
+                },
+            };
+            if sender.send(message).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let recv_session = Arc::clone(&session);
+    let mut recv_task = tokio::spawn(async move {
+        while let Some(Ok(message)) = receiver.next().await {
+            let result = match message {
+                Message::Text(text) => match serde_json::from_str::<ClientMessage>(&text) {
+                    Ok(ClientMessage::SpeechStart) => {
+                        recv_session.speech_started();
+                        Ok(())
+                    }
+                    Ok(ClientMessage::Utterance { text }) => recv_session.submit_text(&text).map(|_| ()),
+                    Ok(ClientMessage::Interrupt) => {
+                        recv_session.interrupt();
+                        Ok(())
+                    }
+                    Ok(ClientMessage::PlaybackFinished) => {
+                        recv_session.playback_finished();
+                        Ok(())
+                    }
+                    Err(e) => Err(DialogError::InvalidInput(format!("Invalid message: {}", e))),
+                },
+                Message::Binary(audio) => recv_session.submit_audio(Bytes::from(audio)).await.map(|_| ()),
+                Message::Close(_) => break,
+                Message::Ping(_) | Message::Pong(_) => Ok(()),
+            };
+            if let Err(e) = result {
+                debug!("Dialog session {}: rejected client message: {}", recv_session.id(), e);
+                recv_session.report_error(&e);
+            }
+        }
+    });
+
+    tokio::select! {
+        _ = &mut send_task => recv_task.abort(),
+        _ = &mut recv_task => send_task.abort(),
+    }
+    orchestrator.close_session(&session_id);
+}
@@ -0,0 +1,428 @@
+//! Conversation sessions: turn-taking, barge-in and streaming handoff
+//!
+//! A user turn starts a pipeline of three concurrent stages: the responder
+//! streams reply text, the text is cut into sentences and voiced one at a
+//! time, and each voiced sentence is published and presented while the next
+//! one is synthesized. Barge-in cancels the whole pipeline; the history then
+//! keeps only the part of the reply that was actually spoken.
+
+use crate::config::DialogConfig;
+use crate::error::DialogError;
+use crate::stages::DialogStages;
+use bytes::Bytes;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+/// Largest audio utterance accepted for recognition
+const MAX_UTTERANCE_AUDIO: usize = 10 * 1024 * 1024;
+
+/// Whose turn it is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TurnState {
+    /// Waiting for the user
+    Listening,
+    /// Generating a reply, nothing spoken yet
+    Thinking,
+    /// Reply audio is being produced or played back
+    Speaking,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Speaker {
+    User,
+    Assistant,
+}
+
+/// One turn of the conversation history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DialogTurn {
+    pub speaker: Speaker,
+    pub text: String,
+    /// The assistant was cut off; `text` is what was spoken before that
+    #[serde(default)]
+    pub interrupted: bool,
+}
+
+/// Published to session subscribers as the conversation progresses
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DialogEvent {
+    SessionStarted { session_id: String },
+    State { state: TurnState },
+    UserText { turn: u64, text: String },
+    /// A reply chunk, published as it is spoken
+    AssistantText { turn: u64, text: String },
+    /// Speech for the preceding reply chunk (a binary frame on WebSocket)
+    Audio {
+        turn: u64,
+        #[serde(skip)]
+        data: Bytes,
+    },
+    /// The reply was cut off; clients should stop playback
+    Interrupted { turn: u64 },
+    /// All of the reply has been produced
+    TurnComplete { turn: u64 },
+    Error { turn: Option<u64>, message: String },
+}
+
+struct Inner {
+    state: TurnState,
+    /// Number of the latest user turn
+    turn: u64,
+    /// Turn whose reply is in progress; cleared on interrupt
+    active: Option<u64>,
+    history: VecDeque<DialogTurn>,
+    /// Reply text of the active turn spoken so far
+    spoken: String,
+    task: Option<JoinHandle<()>>,
+    closed: bool,
+}
+
+/// One conversation
+pub struct DialogSession {
+    id: String,
+    config: DialogConfig,
+    stages: DialogStages,
+    inner: Mutex<Inner>,
+    events: broadcast::Sender<DialogEvent>,
+}
+
+impl DialogSession {
+    pub fn new(config: DialogConfig, stages: DialogStages) -> Result<Arc<Self>, DialogError> {
+        config.validate().map_err(DialogError::Config)?;
+        let (events, _) = broadcast::channel(1024);
+        Ok(Arc::new(Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            config,
+            stages,
+            inner: Mutex::new(Inner {
+                state: TurnState::Listening,
+                turn: 0,
+                active: None,
+                history: VecDeque::new(),
+                spoken: String::new(),
+                task: None,
+                closed: false,
+            }),
+            events,
+        }))
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn state(&self) -> TurnState {
+        self.inner.lock().state
+    }
+
+    /// Conversation history, oldest turn first
+    pub fn history(&self) -> Vec<DialogTurn> {
+        self.inner.lock().history.iter().cloned().collect()
+    }
+
+    /// Subscribe to session events
+    pub fn subscribe(&self) -> broadcast::Receiver<DialogEvent> {
+        self.events.subscribe()
+    }
+
+    /// The user started speaking; interrupts the assistant when barge-in is on
+    ///
+    /// Returns whether a reply was interrupted.
+    pub fn speech_started(&self) -> bool {
+        if !self.config.barge_in {
+            return false;
+        }
+        self.interrupt()
+    }
+
+    /// Cut off the reply in progress, if any
+    pub fn interrupt(&self) -> bool {
+        let mut inner = self.inner.lock();
+        self.interrupt_locked(&mut inner)
+    }
+
+    /// The client finished playing the reply audio
+    pub fn playback_finished(&self) {
+        let mut inner = self.inner.lock();
+        // Ignore reports that arrive while the reply is still being produced
+        if inner.state != TurnState::Speaking || inner.task.is_some() {
+            return;
+        }
+        self.end_turn(&mut inner, false);
+    }
+
+    /// Take a user utterance and start replying
+    ///
+    /// Returns the turn number. Interrupts a reply in progress when barge-in
+    /// is on; otherwise fails while the assistant has the turn.
+    pub fn submit_text(self: &Arc<Self>, text: &str) -> Result<u64, DialogError> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(DialogError::InvalidInput("Utterance is empty".to_string()));
+        }
+        if text.chars().count() > self.config.max_utterance_chars {
+            return Err(DialogError::InvalidInput(format!(
+                "Utterance too long (max {} characters)",
+                self.config.max_utterance_chars
+            )));
+        }
+
+        let mut inner = self.inner.lock();
+        if inner.closed {
+            return Err(DialogError::Closed);
+        }
+        if inner.state != TurnState::Listening {
+            if !self.config.barge_in {
+                return Err(DialogError::Busy("The assistant has the turn".to_string()));
+            }
+            self.interrupt_locked(&mut inner);
+        }
+
+        inner.turn += 1;
+        let turn = inner.turn;
+        self.push_history(&mut inner, DialogTurn {
+            speaker: Speaker::User,
+            text: text.to_string(),
+            interrupted: false,
+        });
+        inner.active = Some(turn);
+        inner.state = TurnState::Thinking;
+        let history: Vec<DialogTurn> = inner.history.iter().cloned().collect();
+        self.emit(DialogEvent::UserText { turn, text: text.to_string() });
+        self.emit(DialogEvent::State { state: TurnState::Thinking });
+        inner.task = Some(tokio::spawn(Arc::clone(self).run_turn(turn, history)));
+        Ok(turn)
+    }
+
+    /// Recognize an audio utterance and reply to it
+    ///
+    /// Returns the turn number, or `None` when no speech was recognized.
+    pub async fn submit_audio(self: &Arc<Self>, audio: Bytes) -> Result<Option<u64>, DialogError> {
+        let recognizer = self.stages.recognizer.clone()
+            .ok_or_else(|| DialogError::Config("No recognizer configured".to_string()))?;
+        if audio.len() > MAX_UTTERANCE_AUDIO {
+            return Err(DialogError::InvalidInput(format!(
+                "Utterance audio too large (max {} bytes)",
+                MAX_UTTERANCE_AUDIO
+            )));
+        }
+        match recognizer.recognize(audio).await? {
+            Some(text) if !text.trim().is_empty() => self.submit_text(&text).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Tell subscribers about an error outside a turn, e.g. a rejected message
+    pub fn report_error(&self, error: &DialogError) {
+        self.emit(DialogEvent::Error { turn: None, message: error.to_string() });
+    }
+
+    /// End the session; the reply in progress is interrupted
+    pub fn close(&self) {
+        let mut inner = self.inner.lock();
+        inner.closed = true;
+        self.interrupt_locked(&mut inner);
+    }
+
+    async fn run_turn(self: Arc<Self>, turn: u64, history: Vec<DialogTurn>) {
+        let this = &*self;
+        let (text_tx, mut text_rx) = mpsc::channel::<String>(64);
+        // Small buffer: synthesize at most a couple of sentences ahead of playback
+        let (speech_tx, mut speech_rx) = mpsc::channel::<(String, Option<Bytes>)>(2);
+
+        let respond = this.stages.responder.respond(this.config.system_prompt.as_deref(), &history, text_tx);
+
+        let speak = async move {
+            let mut chunker = SentenceChunker::new(this.config.max_chunk_chars);
+            while let Some(text) = text_rx.recv().await {
+                for sentence in chunker.push(&text) {
+                    if !this.voice_chunk(&speech_tx, sentence).await? {
+                        return Ok(());
+                    }
+                }
+            }
+            if let Some(rest) = chunker.finish() {
+                this.voice_chunk(&speech_tx, rest).await?;
+            }
+            Ok::<(), DialogError>(())
+        };
+
+        let present = async move {
+            while let Some((text, audio)) = speech_rx.recv().await {
+                if !this.record_spoken(turn, &text, audio.as_ref()) {
+                    break;
+                }
+                if let Some(presenter) = &this.stages.presenter {
+                    presenter.present(&text, audio.as_ref()).await?;
+                }
+            }
+            Ok::<(), DialogError>(())
+        };
+
+        let (responded, spoken, presented) = tokio::join!(respond, speak, present);
+        self.finish_turn(turn, responded.and(spoken).and(presented));
+    }
+
+    /// Voice a chunk and hand it to the presenter; false once the presenter is gone
+    async fn voice_chunk(
+        &self,
+        speech_tx: &mpsc::Sender<(String, Option<Bytes>)>,
+        text: String,
+    ) -> Result<bool, DialogError> {
+        let audio = match &self.stages.voice {
+            Some(voice) => Some(voice.synthesize(&text).await?),
+            None => None,
+        };
+        Ok(speech_tx.send((text, audio)).await.is_ok())
+    }
+
+    /// Publish a spoken chunk; false if the turn was interrupted
+    fn record_spoken(&self, turn: u64, text: &str, audio: Option<&Bytes>) -> bool {
+        let mut inner = self.inner.lock();
+        if inner.active != Some(turn) {
+            return false;
+        }
+        if inner.state == TurnState::Thinking {
+            inner.state = TurnState::Speaking;
+            self.emit(DialogEvent::State { state: TurnState::Speaking });
+        }
+        if !inner.spoken.is_empty() {
+            inner.spoken.push(' ');
+        }
+        inner.spoken.push_str(text);
+        self.emit(DialogEvent::AssistantText { turn, text: text.to_string() });
+        if let Some(data) = audio {
+            self.emit(DialogEvent::Audio { turn, data: data.clone() });
+        }
+        true
+    }
+
+    fn finish_turn(&self, turn: u64, result: Result<(), DialogError>) {
+        let mut inner = self.inner.lock();
+        if inner.active != Some(turn) {
+            return;
+        }
+        // This is the task itself; dropping the handle detaches it
+        inner.task = None;
+        if let Err(e) = &result {
+            warn!("Dialog session {}: turn {} failed: {}", self.id, turn, e);
+            self.emit(DialogEvent::Error { turn: Some(turn), message: e.to_string() });
+        }
+        let awaiting_playback = result.is_ok()
+            && self.config.wait_for_playback
+            && self.stages.voice.is_some()
+            && !inner.spoken.is_empty();
+        self.emit(DialogEvent::TurnComplete { turn });
+        if !awaiting_playback {
+            self.end_turn(&mut inner, result.is_err());
+        }
+    }
+
+    /// Commit the spoken reply to history and hand the turn back to the user
+    fn end_turn(&self, inner: &mut Inner, interrupted: bool) {
+        let spoken = std::mem::take(&mut inner.spoken);
+        if !spoken.is_empty() {
+            self.push_history(inner, DialogTurn {
+                speaker: Speaker::Assistant,
+                text: spoken,
+                interrupted,
+            });
+        }
+        inner.active = None;
+        if inner.state != TurnState::Listening {
+            inner.state = TurnState::Listening;
+            self.emit(DialogEvent::State { state: TurnState::Listening });
+        }
+    }
+
+    fn interrupt_locked(&self, inner: &mut Inner) -> bool {
+        if inner.state == TurnState::Listening {
+            return false;
+        }
+        if let Some(task) = inner.task.take() {
+            task.abort();
+        }
+        let turn = inner.turn;
+        debug!("Dialog session {}: turn {} interrupted", self.id, turn);
+        self.emit(DialogEvent::Interrupted { turn });
+        self.end_turn(inner, true);
+        true
+    }
+
+    fn push_history(&self, inner: &mut Inner, turn: DialogTurn) {
+        inner.history.push_back(turn);
+        while inner.history.len() > self.config.max_history_turns {
+            inner.history.pop_front();
+        }
+    }
+
+    fn emit(&self, event: DialogEvent) {
+        // No subscribers is fine
+        let _ = self.events.send(event);
+    }
+}
+
+/// Cuts streamed text into sentences for speech synthesis
+struct SentenceChunker {
+    buffer: String,
+    max_chars: usize,
+}
+
+impl SentenceChunker {
+    fn new(max_chars: usize) -> Self {
+        Self { buffer: String::new(), max_chars }
+    }
+
+    /// Add text and take the complete sentences
+    fn push(&mut self, text: &str) -> Vec<String> {
+        self.buffer.push_str(text);
+        let mut sentences = Vec::new();
+        while let Some(end) = self.next_boundary() {
+            let sentence: String = self.buffer.drain(..end).collect();
+            let sentence = sentence.trim();
+            if !sentence.is_empty() {
+                sentences.push(sentence.to_string());
+            }
+        }
+        sentences
+    }
+
+    /// Whatever is left at the end of the reply
+    fn finish(&mut self) -> Option<String> {
+        let rest = std::mem::take(&mut self.buffer);
+        let rest = rest.trim();
+        (!rest.is_empty()).then(|| rest.to_string())
+    }
+
+    /// Byte offset just past the first complete sentence in the buffer
+    fn next_boundary(&self) -> Option<usize> {
+        let mut last_space = None;
+        let mut chars = self.buffer.char_indices().enumerate().peekable();
+        while let Some((count, (i, c))) = chars.next() {
+            if count >= self.max_chars {
+                // Overlong sentence: break at the last word boundary
+                return Some(last_space.unwrap_or(i));
+            }
+            let end = i + c.len_utf8();
+            if c == '\n' {
+                return Some(end);
+            }
+            // Punctuation ends a sentence only once followed by whitespace ("3.14", "...")
+            if matches!(c, '.' | '!' | '?') && chars.peek().is_some_and(|(_, (_, next))| next.is_whitespace()) {
+                return Some(end);
+            }
+            if c.is_whitespace() {
+                last_space = Some(end);
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,79 @@
+//! Pipeline stages of a conversation
+
+use crate::error::DialogError;
+use crate::session::DialogTurn;
+use async_trait::async_trait;
+use bytes::Bytes;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+/// Turns user audio into text
+#[async_trait]
+pub trait Recognizer: Send + Sync {
+    /// Transcribe an utterance; `None` when nothing was recognized
+    async fn recognize(&self, audio: Bytes) -> Result<Option<String>, DialogError>;
+}
+
+/// Produces the assistant's reply
+#[async_trait]
+pub trait Responder: Send + Sync {
+    /// Stream the reply to `history` (oldest turn first, ending with the
+    /// user's turn) into `chunks`, in pieces of any size
+    ///
+    /// A closed `chunks` channel means the turn was interrupted; stop and
+    /// return `Ok`.
+    async fn respond(
+        &self,
+        system_prompt: Option<&str>,
+        history: &[DialogTurn],
+        chunks: mpsc::Sender<String>,
+    ) -> Result<(), DialogError>;
+}
+
+/// Turns reply text into speech audio
+#[async_trait]
+pub trait Voice: Send + Sync {
+    async fn synthesize(&self, text: &str) -> Result<Bytes, DialogError>;
+}
+
+/// Presents spoken output, e.g. lip-syncing an avatar
+#[async_trait]
+pub trait Presenter: Send + Sync {
+    /// Called for each reply chunk in order; `audio` is `None` without a voice
+    async fn present(&self, text: &str, audio: Option<&Bytes>) -> Result<(), DialogError>;
+}
+
+/// The stages a session runs; only the responder is required
+#[derive(Clone)]
+pub struct DialogStages {
+    pub recognizer: Option<Arc<dyn Recognizer>>,
+    pub responder: Arc<dyn Responder>,
+    pub voice: Option<Arc<dyn Voice>>,
+    pub presenter: Option<Arc<dyn Presenter>>,
+}
+
+impl DialogStages {
+    pub fn new(responder: Arc<dyn Responder>) -> Self {
+        Self {
+            recognizer: None,
+            responder,
+            voice: None,
+            presenter: None,
+        }
+    }
+
+    pub fn with_recognizer(mut self, recognizer: Arc<dyn Recognizer>) -> Self {
+        self.recognizer = Some(recognizer);
+        self
+    }
+
+    pub fn with_voice(mut self, voice: Arc<dyn Voice>) -> Self {
+        self.voice = Some(voice);
+        self
+    }
+
+    pub fn with_presenter(mut self, presenter: Arc<dyn Presenter>) -> Self {
+        self.presenter = Some(presenter);
+        self
+    }
+}
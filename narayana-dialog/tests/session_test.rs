@@ -0,0 +1,180 @@
+//! Conversation session tests for narayana-dialog
+//! Turn-taking, sentence streaming, barge-in and history, with mock stages
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use narayana_dialog::*;
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc};
+
+/// Streams a fixed reply in small pieces, pausing between them
+struct ScriptedResponder {
+    reply: String,
+    delay: Duration,
+    seen: Mutex<Vec<usize>>,
+}
+
+#[async_trait]
+impl Responder for ScriptedResponder {
+    async fn respond(
+        &self,
+        _system_prompt: Option<&str>,
+        history: &[DialogTurn],
+        chunks: mpsc::Sender<String>,
+    ) -> Result<(), DialogError> {
+        self.seen.lock().push(history.len());
+        for piece in self.reply.split_inclusive(' ') {
+            tokio::time::sleep(self.delay).await;
+            if chunks.send(piece.to_string()).await.is_err() {
+                return Ok(());
+            }
+        }
+        Ok(())
+    }
+}
+
+struct EchoVoice;
+
+#[async_trait]
+impl Voice for EchoVoice {
+    async fn synthesize(&self, text: &str) -> Result<Bytes, DialogError> {
+        Ok(Bytes::from(text.as_bytes().to_vec()))
+    }
+}
+
+#[derive(Default)]
+struct RecordingPresenter {
+    presented: Mutex<Vec<String>>,
+}
+
+#[async_trait]
+impl Presenter for RecordingPresenter {
+    async fn present(&self, text: &str, audio: Option<&Bytes>) -> Result<(), DialogError> {
+        assert_eq!(audio.map(|a| a.as_ref()), Some(text.as_bytes()));
+        self.presented.lock().push(text.to_string());
+        Ok(())
+    }
+}
+
+fn responder(reply: &str, delay_ms: u64) -> Arc<ScriptedResponder> {
+    Arc::new(ScriptedResponder {
+        reply: reply.to_string(),
+        delay: Duration::from_millis(delay_ms),
+        seen: Mutex::new(Vec::new()),
+    })
+}
+
+async fn next_matching(events: &mut broadcast::Receiver<DialogEvent>, f: impl Fn(&DialogEvent) -> bool) -> DialogEvent {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            let event = events.recv().await.unwrap();
+            if f(&event) {
+                return event;
+            }
+        }
+    })
+    .await
+    .expect("event not received")
+}
+
+#[tokio::test]
+async fn test_turn_streams_sentences_and_records_history() {
+    let responder = responder("Hello there. How can I help?", 1);
+    let presenter = Arc::new(RecordingPresenter::default());
+    let stages = DialogStages::new(responder.clone())
+        .with_voice(Arc::new(EchoVoice))
+        .with_presenter(presenter.clone());
+    let config = DialogConfig { wait_for_playback: false, ..Default::default() };
+    let session = DialogSession::new(config, stages).unwrap();
+    let mut events = session.subscribe();
+
+    let turn = session.submit_text("  hi  ").unwrap();
+    assert_eq!(session.state(), TurnState::Thinking);
+    next_matching(&mut events, |e| matches!(e, DialogEvent::TurnComplete { .. })).await;
+
+    assert_eq!(*presenter.presented.lock(), vec!["Hello there.", "How can I help?"]);
+    assert_eq!(session.state(), TurnState::Listening);
+    let history = session.history();
+    assert_eq!(history.len(), 2);
+    assert_eq!((history[0].speaker, history[0].text.as_str()), (Speaker::User, "hi"));
+    assert_eq!(history[1].text, "Hello there. How can I help?");
+    assert!(!history[1].interrupted);
+
+    // The next turn sees the whole history
+    let next = session.submit_text("thanks").unwrap();
+    assert_eq!(next, turn + 1);
+    next_matching(&mut events, |e| matches!(e, DialogEvent::TurnComplete { turn } if *turn == next)).await;
+    assert_eq!(*responder.seen.lock(), vec![1, 3]);
+}
+
+#[tokio::test]
+async fn test_barge_in_keeps_only_spoken_text() {
+    let stages = DialogStages::new(responder("One. Two. Three. Four. Five.", 30))
+        .with_voice(Arc::new(EchoVoice));
+    let session = DialogSession::new(DialogConfig::default(), stages).unwrap();
+    let mut events = session.subscribe();
+
+    let turn = session.submit_text("count").unwrap();
+    next_matching(&mut events, |e| matches!(e, DialogEvent::AssistantText { text, .. } if text == "Two.")).await;
+    assert_eq!(session.state(), TurnState::Speaking);
+
+    assert!(session.speech_started());
+    assert_eq!(session.state(), TurnState::Listening);
+    let interrupted = next_matching(&mut events, |e| matches!(e, DialogEvent::Interrupted { .. })).await;
+    assert!(matches!(interrupted, DialogEvent::Interrupted { turn: t } if t == turn));
+
+    // Nothing more is spoken for the cancelled turn
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    let history = session.history();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[1].text, "One. Two.");
+    assert!(history[1].interrupted);
+    assert!(!session.speech_started());
+}
+
+#[tokio::test]
+async fn test_playback_and_turn_taking_without_barge_in() {
+    let stages = DialogStages::new(responder("Short answer.", 1)).with_voice(Arc::new(EchoVoice));
+    let config = DialogConfig { barge_in: false, ..Default::default() };
+    let session = DialogSession::new(config, stages).unwrap();
+    let mut events = session.subscribe();
+
+    session.submit_text("question").unwrap();
+    // Waits for the client to finish playing the reply
+    next_matching(&mut events, |e| matches!(e, DialogEvent::TurnComplete { .. })).await;
+    assert_eq!(session.state(), TurnState::Speaking);
+    assert!(!session.speech_started());
+    assert!(matches!(session.submit_text("another"), Err(DialogError::Busy(_))));
+    assert_eq!(session.history().len(), 1);
+
+    session.playback_finished();
+    assert_eq!(session.state(), TurnState::Listening);
+    assert_eq!(session.history()[1].text, "Short answer.");
+
+    assert!(matches!(session.submit_text("   "), Err(DialogError::InvalidInput(_))));
+    assert!(matches!(session.submit_audio(Bytes::from_static(b"pcm")).await, Err(DialogError::Config(_))));
+    session.close();
+    assert!(matches!(session.submit_text("hello"), Err(DialogError::Closed)));
+}
+
+#[tokio::test]
+async fn test_orchestrator_sessions() {
+    let config = DialogConfig { max_sessions: 2, ..Default::default() };
+    let orchestrator = ConversationOrchestrator::new(config, DialogStages::new(responder("Hi.", 1))).unwrap();
+
+    let first = orchestrator.create_session().unwrap();
+    let second = orchestrator.create_session().unwrap();
+    assert_ne!(first.id(), second.id());
+    assert!(matches!(orchestrator.create_session(), Err(DialogError::SessionLimit(2))));
+
+    assert!(orchestrator.session(first.id()).is_some());
+    assert!(orchestrator.close_session(first.id()));
+    assert!(!orchestrator.close_session(first.id()));
+    assert!(matches!(first.submit_text("hello"), Err(DialogError::Closed)));
+    assert_eq!(orchestrator.session_count(), 1);
+
+    let invalid = DialogConfig { max_history_turns: 0, ..Default::default() };
+    assert!(ConversationOrchestrator::new(invalid, DialogStages::new(responder("Hi.", 1))).is_err());
+}
@@ -0,0 +1,74 @@
+//! A robot keeping its sensor log and place memories in-process
+//!
+//! Run with: cargo run -p narayana-embedded --example robot
+
+use narayana_embedded::{Database, DataType, Field, Filter, Schema};
+use serde_json::json;
+use std::collections::HashMap;
+
+fn field(name: &str, data_type: DataType) -> Field {
+    Field {
+        name: name.to_string(),
+        data_type,
+        nullable: false,
+        default_value: None,
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = std::env::temp_dir().join("narayana_robot_example");
+    let db = Database::open(&dir).await?;
+
+    // Sensor readings, one row per sample
+    let readings = match db.table("readings").await {
+        Ok(table) => table,
+        Err(_) => {
+            db.create_table(
+                "readings",
+                Schema::new(vec![
+                    field("ts", DataType::Int64),
+                    field("sensor", DataType::String),
+                    field("value", DataType::Float64),
+                ]),
+            )
+            .await?
+        }
+    };
+    readings
+        .insert(&[
+            json!({"ts": 1_700_000_000, "sensor": "lidar_front", "value": 1.82}),
+            json!({"ts": 1_700_000_001, "sensor": "battery", "value": 0.64}),
+            json!({"ts": 1_700_000_002, "sensor": "lidar_front", "value": 0.41}),
+        ])
+        .await?;
+
+    // Close obstacles seen by the front lidar
+    let close = readings
+        .query()
+        .where_eq("sensor", "lidar_front")
+        .filter(Filter::Lt { column: "value".to_string(), value: json!(0.5) })
+        .select(&["ts", "value"])
+        .rows()
+        .await?;
+    println!("close obstacles: {}", serde_json::to_string(&close)?);
+    println!("{} readings stored in {:?}", readings.count().await?, db.path());
+
+    // Places the robot remembers, searchable by scene embedding
+    if !db.vector_indexes().contains_key("places") {
+        db.create_vector_index("places", 3)?;
+    }
+    for (id, name, embedding) in [
+        (1, "kitchen", vec![0.9, 0.1, 0.0]),
+        (2, "charging dock", vec![0.0, 0.2, 0.9]),
+        (3, "hallway", vec![0.5, 0.5, 0.1]),
+    ] {
+        let metadata = HashMap::from([("name".to_string(), json!(name))]);
+        db.add_vector("places", id, embedding, metadata)?;
+    }
+    for hit in db.search_vectors("places", &[0.1, 0.2, 0.8], 2)? {
+        println!("looks like {} (score {:.2})", hit.metadata["name"], hit.score);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,303 @@
+// Embedded database handle
+// Tables are named in a small catalog next to the column store so that
+// reopening the same directory finds them again; the column store keeps the
+// schemas and data. Writes are serialised so appended blocks stay in order.
+
+use narayana_core::column::Column;
+use narayana_core::schema::Schema;
+use narayana_core::types::{CompressionType, TableId};
+use narayana_core::{Error, Result};
+use narayana_storage::column_store::{ColumnStore, InMemoryColumnStore};
+use narayana_storage::persistent_column_store::PersistentColumnStore;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::query::Query;
+use crate::rows;
+
+const CATALOG_FILE: &str = "catalog.json";
+const MAX_NAME_LENGTH: usize = 255;
+
+/// In-process NarayanaDB; cheap to clone and share between tasks
+#[derive(Clone)]
+pub struct Database {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    root: Option<PathBuf>,
+    store: Arc<dyn ColumnStore>,
+    catalog: RwLock<Catalog>,
+    write_lock: tokio::sync::Mutex<()>,
+    #[cfg(feature = "vector")]
+    vectors: crate::vectors::VectorIndexes,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Catalog {
+    tables: BTreeMap<String, TableId>,
+    next_table_id: u64,
+}
+
+/// Names shared by tables and vector indexes: they become file names
+pub(crate) fn validate_name(kind: &str, name: &str) -> Result<()> {
+    if name.is_empty() || name.len() > MAX_NAME_LENGTH {
+        return Err(Error::Storage(format!(
+            "{} name must be 1 to {} characters",
+            kind, MAX_NAME_LENGTH
+        )));
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        return Err(Error::Storage(format!(
+            "{} name '{}' may only contain letters, digits, '_' and '-'",
+            kind, name
+        )));
+    }
+    Ok(())
+}
+
+impl Database {
+    /// Open (or create) a database stored under `path`
+    pub async fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let root = path.as_ref().to_path_buf();
+        std::fs::create_dir_all(&root)
+            .map_err(|e| Error::Storage(format!("Failed to create database directory: {}", e)))?;
+
+        let store = PersistentColumnStore::new(root.join("tables"), CompressionType::LZ4)?;
+        store.load_all_tables().await?;
+
+        let catalog_path = root.join(CATALOG_FILE);
+        let mut catalog = if catalog_path.exists() {
+            let bytes = std::fs::read(&catalog_path)?;
+            serde_json::from_slice::<Catalog>(&bytes)
+                .map_err(|e| Error::Deserialization(format!("Invalid catalog {:?}: {}", catalog_path, e)))?
+        } else {
+            Catalog::default()
+        };
+        // A table created right before a crash may be in the catalog only
+        let mut missing = Vec::new();
+        for (name, table_id) in &catalog.tables {
+            if store.get_schema(*table_id).await.is_err() {
+                warn!("Table '{}' is in the catalog but has no data; dropping it", name);
+                missing.push(name.clone());
+            }
+        }
+        for name in missing {
+            catalog.tables.remove(&name);
+        }
+
+        #[cfg(feature = "vector")]
+        let vectors = crate::vectors::VectorIndexes::open(Some(root.join("vectors")))?;
+
+        info!("Opened embedded database at {:?} ({} tables)", root, catalog.tables.len());
+        Ok(Self {
+            inner: Arc::new(Inner {
+                root: Some(root),
+                store: Arc::new(store),
+                catalog: RwLock::new(catalog),
+                write_lock: tokio::sync::Mutex::new(()),
+                #[cfg(feature = "vector")]
+                vectors,
+            }),
+        })
+    }
+
+    /// A database that lives only as long as the process
+    pub fn in_memory() -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                root: None,
+                store: Arc::new(InMemoryColumnStore::new()),
+                catalog: RwLock::new(Catalog::default()),
+                write_lock: tokio::sync::Mutex::new(()),
+                #[cfg(feature = "vector")]
+                vectors: crate::vectors::VectorIndexes::open(None)
+                    .expect("in-memory vector indexes never touch the filesystem"),
+            }),
+        }
+    }
+
+    /// Directory the database is stored in, `None` when in memory
+    pub fn path(&self) -> Option<&Path> {
+        self.inner.root.as_deref()
+    }
+
+    /// Create a table; fails if the name is taken
+    pub async fn create_table(&self, name: &str, schema: Schema) -> Result<Table> {
+        validate_name("Table", name)?;
+        if schema.fields.is_empty() {
+            return Err(Error::SchemaMismatch("A table needs at least one field".to_string()));
+        }
+
+        let _guard = self.inner.write_lock.lock().await;
+        let table_id = {
+            let mut catalog = self.inner.catalog.write();
+            if catalog.tables.contains_key(name) {
+                return Err(Error::Storage(format!("Table '{}' already exists", name)));
+            }
+            catalog.next_table_id += 1;
+            TableId(catalog.next_table_id)
+        };
+        // Persist the reserved id first so a crash can't hand it out twice
+        self.save_catalog()?;
+        self.inner.store.create_table(table_id, schema.clone()).await?;
+        self.inner.catalog.write().tables.insert(name.to_string(), table_id);
+        self.save_catalog()?;
+
+        info!("Created embedded table '{}' ({})", name, table_id.0);
+        Ok(Table {
+            db: self.clone(),
+            name: name.to_string(),
+            id: table_id,
+            schema,
+        })
+    }
+
+    /// Handle to an existing table
+    pub async fn table(&self, name: &str) -> Result<Table> {
+        let table_id = self
+            .inner
+            .catalog
+            .read()
+            .tables
+            .get(name)
+            .copied()
+            .ok_or_else(|| Error::Storage(format!("Table '{}' not found", name)))?;
+        let schema = self.inner.store.get_schema(table_id).await?;
+        Ok(Table {
+            db: self.clone(),
+            name: name.to_string(),
+            id: table_id,
+            schema,
+        })
+    }
+
+    /// Table names, sorted
+    pub fn table_names(&self) -> Vec<String> {
+        self.inner.catalog.read().tables.keys().cloned().collect()
+    }
+
+    /// Drop a table and its data
+    pub async fn drop_table(&self, name: &str) -> Result<()> {
+        let _guard = self.inner.write_lock.lock().await;
+        let table_id = self
+            .inner
+            .catalog
+            .write()
+            .tables
+            .remove(name)
+            .ok_or_else(|| Error::Storage(format!("Table '{}' not found", name)))?;
+        self.save_catalog()?;
+        self.inner.store.delete_table(table_id).await?;
+        info!("Dropped embedded table '{}'", name);
+        Ok(())
+    }
+
+    fn save_catalog(&self) -> Result<()> {
+        let Some(root) = &self.inner.root else { return Ok(()) };
+        let bytes = serde_json::to_vec_pretty(&*self.inner.catalog.read())
+            .map_err(|e| Error::Serialization(format!("Failed to serialize catalog: {}", e)))?;
+        let path = root.join(CATALOG_FILE);
+        let temp = path.with_extension("json.tmp");
+        std::fs::write(&temp, bytes)?;
+        std::fs::rename(&temp, &path)?;
+        Ok(())
+    }
+
+    pub(crate) fn store(&self) -> &Arc<dyn ColumnStore> {
+        &self.inner.store
+    }
+
+    #[cfg(feature = "vector")]
+    pub(crate) fn vectors(&self) -> &crate::vectors::VectorIndexes {
+        &self.inner.vectors
+    }
+}
+
+/// Handle to one table
+#[derive(Clone)]
+pub struct Table {
+    db: Database,
+    name: String,
+    id: TableId,
+    schema: Schema,
+}
+
+impl Table {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn id(&self) -> TableId {
+        self.id
+    }
+
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Append JSON object rows; returns the number of rows written
+    pub async fn insert(&self, rows: &[Value]) -> Result<usize> {
+        if rows.is_empty() {
+            return Ok(0);
+        }
+        let columns = rows::rows_to_columns(&self.schema, rows)?;
+        self.insert_columns(columns).await?;
+        Ok(rows.len())
+    }
+
+    /// Append one column per schema field, all of the same length
+    pub async fn insert_columns(&self, columns: Vec<Column>) -> Result<()> {
+        if columns.len() != self.schema.fields.len() {
+            return Err(Error::SchemaMismatch(format!(
+                "Table '{}' has {} fields, got {} columns",
+                self.name,
+                self.schema.fields.len(),
+                columns.len()
+            )));
+        }
+        let rows = columns[0].len();
+        for (field, column) in self.schema.fields.iter().zip(&columns) {
+            if !rows::column_matches(field, column) {
+                return Err(Error::InvalidDataType {
+                    expected: format!("{:?} for field '{}'", field.data_type, field.name),
+                    actual: format!("{:?}", column.data_type()),
+                });
+            }
+            if column.len() != rows {
+                return Err(Error::SchemaMismatch(format!(
+                    "Column '{}' has {} rows, expected {}",
+                    field.name,
+                    column.len(),
+                    rows
+                )));
+            }
+        }
+        if rows == 0 {
+            return Ok(());
+        }
+
+        let _guard = self.db.inner.write_lock.lock().await;
+        self.db.store().write_columns(self.id, columns).await
+    }
+
+    /// Number of rows in the table
+    pub async fn count(&self) -> Result<usize> {
+        let columns = self.db.store().read_columns(self.id, vec![0], 0, usize::MAX).await?;
+        Ok(columns.first().map(Column::len).unwrap_or(0))
+    }
+
+    /// Start a query over this table
+    pub fn query(&self) -> Query {
+        Query::new(self.clone())
+    }
+
+    pub(crate) fn database(&self) -> &Database {
+        &self.db
+    }
+}
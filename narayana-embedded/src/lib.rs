@@ -0,0 +1,26 @@
+//! narayana-embedded: NarayanaDB inside your process
+//!
+//! Provides:
+//! - `Database::open(path)` over the filesystem column store, or `Database::in_memory()`
+//! - Named tables with JSON row inserts and column inserts
+//! - Queries with projection, filters, offset and limit
+//! - Persistent vector indexes with cosine similarity search (`vector` feature)
+//!
+//! No HTTP, gRPC or GraphQL server is linked; everything runs on the caller's
+//! tokio runtime.
+
+pub mod database;
+pub mod query;
+pub mod rows;
+#[cfg(feature = "vector")]
+pub mod vectors;
+
+pub use database::{Database, Table};
+pub use query::{Query, Row};
+#[cfg(feature = "vector")]
+pub use vectors::VectorMatch;
+
+pub use narayana_core::column::Column;
+pub use narayana_core::schema::{DataType, Field, Schema};
+pub use narayana_core::{Error, Result};
+pub use narayana_query::plan::Filter;
@@ -0,0 +1,133 @@
+// Table queries
+// Projection, filters, offset and limit over one table. Unfiltered queries
+// read only the requested row range; filtered queries scan the table and run
+// the query engine's filter operator over it.
+
+use narayana_core::column::Column;
+use narayana_core::schema::Field;
+use narayana_core::{Error, Result};
+use narayana_query::operators::FilterOperator;
+use narayana_query::plan::Filter;
+use serde_json::{Map, Value};
+
+use crate::database::Table;
+use crate::rows;
+
+/// A result row keyed by field name
+pub type Row = Map<String, Value>;
+
+/// Query builder returned by `Table::query`
+#[derive(Clone)]
+pub struct Query {
+    table: Table,
+    columns: Vec<String>,
+    filter: Option<Filter>,
+    offset: usize,
+    limit: Option<usize>,
+}
+
+impl Query {
+    pub(crate) fn new(table: Table) -> Self {
+        Self {
+            table,
+            columns: Vec::new(),
+            filter: None,
+            offset: 0,
+            limit: None,
+        }
+    }
+
+    /// Return only these fields, in this order (all fields by default)
+    pub fn select(mut self, columns: &[&str]) -> Self {
+        self.columns = columns.iter().map(|c| c.to_string()).collect();
+        self
+    }
+
+    /// Keep rows matching `filter`; repeated calls are combined with AND
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filter = Some(match self.filter.take() {
+            None => filter,
+            Some(existing) => Filter::And {
+                left: Box::new(existing),
+                right: Box::new(filter),
+            },
+        });
+        self
+    }
+
+    /// Shorthand for an equality filter
+    pub fn where_eq(self, column: &str, value: impl Into<Value>) -> Self {
+        self.filter(Filter::Eq {
+            column: column.to_string(),
+            value: value.into(),
+        })
+    }
+
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Run the query and return the selected columns
+    pub async fn columns(&self) -> Result<Vec<Column>> {
+        Ok(self.run().await?.1)
+    }
+
+    /// Run the query and return rows as JSON objects
+    pub async fn rows(&self) -> Result<Vec<Row>> {
+        let (fields, columns) = self.run().await?;
+        Ok(rows::columns_to_rows(&fields, &columns))
+    }
+
+    fn selected(&self) -> Result<Vec<(u32, &Field)>> {
+        let schema = self.table.schema();
+        if self.columns.is_empty() {
+            return Ok(schema.fields.iter().enumerate().map(|(idx, f)| (idx as u32, f)).collect());
+        }
+        self.columns
+            .iter()
+            .map(|name| {
+                schema
+                    .field_index(name)
+                    .map(|idx| (idx as u32, &schema.fields[idx]))
+                    .ok_or_else(|| Error::ColumnNotFound(name.clone()))
+            })
+            .collect()
+    }
+
+    async fn run(&self) -> Result<(Vec<&Field>, Vec<Column>)> {
+        let selected = self.selected()?;
+        let fields = selected.iter().map(|(_, f)| *f).collect();
+        let store = self.table.database().store();
+        let limit = self.limit.unwrap_or(usize::MAX);
+
+        let Some(filter) = &self.filter else {
+            let ids = selected.iter().map(|(id, _)| *id).collect();
+            let columns = store.read_columns(self.table.id(), ids, self.offset, limit).await?;
+            return Ok((fields, columns));
+        };
+
+        // The filter operator addresses columns by schema position
+        let schema = self.table.schema();
+        let all_ids = (0..schema.fields.len() as u32).collect();
+        let columns = store.read_columns(self.table.id(), all_ids, 0, usize::MAX).await?;
+        if columns.len() < schema.fields.len() {
+            // Nothing written yet
+            return Ok((fields, Vec::new()));
+        }
+        let matched = FilterOperator::new(filter.clone(), schema.clone()).apply(&columns)?;
+        let row_count = matched.first().map(Column::len).unwrap_or(0);
+        let offset = self.offset.min(row_count);
+        let count = limit.min(row_count - offset);
+        let columns = selected
+            .iter()
+            .map(|(id, _)| matched[*id as usize].slice(offset, count))
+            .collect::<Result<Vec<_>>>()?;
+        Ok((fields, columns))
+    }
+}
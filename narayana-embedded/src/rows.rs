@@ -0,0 +1,155 @@
+// Row <-> column conversion
+// JSON objects are split into one column per schema field on insert and
+// zipped back into objects on read. Columns have no null bitmap, so a null or
+// missing value in a nullable field is stored as the type's zero value.
+
+use narayana_core::column::Column;
+use narayana_core::schema::{DataType, Field, Schema};
+use narayana_core::{Error, Result};
+use serde_json::{Map, Value};
+
+use crate::query::Row;
+
+/// Split JSON object rows into columns in schema order
+pub fn rows_to_columns(schema: &Schema, rows: &[Value]) -> Result<Vec<Column>> {
+    let mut objects = Vec::with_capacity(rows.len());
+    for (idx, row) in rows.iter().enumerate() {
+        let object = row
+            .as_object()
+            .ok_or_else(|| Error::SchemaMismatch(format!("Row {} is not a JSON object", idx)))?;
+        if let Some(unknown) = object.keys().find(|key| schema.field_index(key).is_none()) {
+            return Err(Error::ColumnNotFound(unknown.clone()));
+        }
+        objects.push(object);
+    }
+
+    schema
+        .fields
+        .iter()
+        .map(|field| {
+            let values: Vec<&Value> = objects
+                .iter()
+                .map(|object| match object.get(&field.name) {
+                    Some(value) => value,
+                    None => field.default_value.as_ref().unwrap_or(&Value::Null),
+                })
+                .collect();
+            column_from_values(field, &values)
+        })
+        .collect()
+}
+
+fn base_type(data_type: &DataType) -> &DataType {
+    match data_type {
+        DataType::Nullable(inner) => base_type(inner),
+        other => other,
+    }
+}
+
+fn collect<T: Default>(field: &Field, values: &[&Value], convert: impl Fn(&Value) -> Option<T>) -> Result<Vec<T>> {
+    values
+        .iter()
+        .map(|value| {
+            if value.is_null() {
+                return if field.nullable || matches!(field.data_type, DataType::Nullable(_)) {
+                    Ok(T::default())
+                } else {
+                    Err(Error::SchemaMismatch(format!("Field '{}' is not nullable", field.name)))
+                };
+            }
+            convert(value).ok_or_else(|| Error::InvalidDataType {
+                expected: format!("{:?} for field '{}'", field.data_type, field.name),
+                actual: value.to_string(),
+            })
+        })
+        .collect()
+}
+
+fn int<T: TryFrom<i64>>(value: &Value) -> Option<T> {
+    value.as_i64().and_then(|n| T::try_from(n).ok())
+}
+
+fn uint<T: TryFrom<u64>>(value: &Value) -> Option<T> {
+    value.as_u64().and_then(|n| T::try_from(n).ok())
+}
+
+fn bytes(value: &Value) -> Option<Vec<u8>> {
+    match value {
+        Value::String(s) => Some(s.as_bytes().to_vec()),
+        Value::Array(items) => items.iter().map(uint::<u8>).collect(),
+        _ => None,
+    }
+}
+
+fn column_from_values(field: &Field, values: &[&Value]) -> Result<Column> {
+    Ok(match base_type(&field.data_type) {
+        DataType::Int8 => Column::Int8(collect(field, values, int)?),
+        DataType::Int16 => Column::Int16(collect(field, values, int)?),
+        DataType::Int32 => Column::Int32(collect(field, values, int)?),
+        DataType::Int64 => Column::Int64(collect(field, values, int)?),
+        DataType::UInt8 => Column::UInt8(collect(field, values, uint)?),
+        DataType::UInt16 => Column::UInt16(collect(field, values, uint)?),
+        DataType::UInt32 => Column::UInt32(collect(field, values, uint)?),
+        DataType::UInt64 => Column::UInt64(collect(field, values, uint)?),
+        DataType::Float32 => Column::Float32(collect(field, values, |v| v.as_f64().map(|f| f as f32))?),
+        DataType::Float64 => Column::Float64(collect(field, values, Value::as_f64)?),
+        DataType::Boolean => Column::Boolean(collect(field, values, Value::as_bool)?),
+        DataType::String => Column::String(collect(field, values, |v| v.as_str().map(str::to_string))?),
+        DataType::Json => Column::String(collect(field, values, |v| Some(v.to_string()))?),
+        DataType::Binary => Column::Binary(collect(field, values, bytes)?),
+        DataType::Timestamp => Column::Timestamp(collect(field, values, Value::as_i64)?),
+        DataType::Date => Column::Date(collect(field, values, int)?),
+        other => {
+            return Err(Error::SchemaMismatch(format!(
+                "Field '{}' of type {:?} cannot be inserted as JSON rows; use insert_columns",
+                field.name, other
+            )))
+        }
+    })
+}
+
+/// Whether a column can be stored in a field
+pub(crate) fn column_matches(field: &Field, column: &Column) -> bool {
+    match (base_type(&field.data_type), column) {
+        (DataType::Json, Column::String(_)) => true,
+        (DataType::Point | DataType::Geometry, Column::Binary(_)) => true,
+        (expected, column) => *expected == column.data_type(),
+    }
+}
+
+fn value_at(field: &Field, column: &Column, idx: usize) -> Value {
+    match column {
+        Column::Int8(v) => Value::from(v[idx]),
+        Column::Int16(v) => Value::from(v[idx]),
+        Column::Int32(v) => Value::from(v[idx]),
+        Column::Int64(v) => Value::from(v[idx]),
+        Column::UInt8(v) => Value::from(v[idx]),
+        Column::UInt16(v) => Value::from(v[idx]),
+        Column::UInt32(v) => Value::from(v[idx]),
+        Column::UInt64(v) => Value::from(v[idx]),
+        Column::Float32(v) => Value::from(v[idx]),
+        Column::Float64(v) => Value::from(v[idx]),
+        Column::Boolean(v) => Value::from(v[idx]),
+        Column::String(v) if *base_type(&field.data_type) == DataType::Json => {
+            serde_json::from_str(&v[idx]).unwrap_or_else(|_| Value::String(v[idx].clone()))
+        }
+        Column::String(v) => Value::from(v[idx].clone()),
+        Column::Binary(v) => Value::from(v[idx].clone()),
+        Column::Timestamp(v) => Value::from(v[idx]),
+        Column::Date(v) => Value::from(v[idx]),
+    }
+}
+
+/// Zip columns back into JSON objects keyed by field name
+pub fn columns_to_rows(fields: &[&Field], columns: &[Column]) -> Vec<Row> {
+    let row_count = columns.iter().map(Column::len).min().unwrap_or(0);
+    (0..row_count)
+        .map(|idx| {
+            fields
+                .iter()
+                .zip(columns)
+                .map(|(field, column)| (field.name.clone(), value_at(field, column, idx)))
+                .collect::<Map<String, Value>>()
+        })
+        .collect()
+}
@@ -0,0 +1,191 @@
+// Persistent vector indexes
+// Each index is an append-only JSON-lines log: a header with the dimension,
+// then one embedding per line. Opening the database replays the logs into
+// flat (exact cosine) indexes; a later line for the same id replaces it.
+
+use narayana_core::{Error, Result};
+use narayana_storage::vector_search::{Embedding, IndexType, VectorStore};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+use crate::database::{validate_name, Database};
+
+const LOG_EXTENSION: &str = "jsonl";
+
+/// One search hit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VectorMatch {
+    pub id: u64,
+    /// Cosine similarity, 1.0 is identical
+    pub score: f32,
+    pub metadata: HashMap<String, Value>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LogHeader {
+    dimension: usize,
+}
+
+pub(crate) struct VectorIndexes {
+    dir: Option<PathBuf>,
+    store: VectorStore,
+    dimensions: RwLock<BTreeMap<String, usize>>,
+    // Serialises appends so log lines never interleave
+    append_lock: Mutex<()>,
+}
+
+impl VectorIndexes {
+    pub(crate) fn open(dir: Option<PathBuf>) -> Result<Self> {
+        let indexes = Self {
+            dir,
+            store: VectorStore::new(),
+            dimensions: RwLock::new(BTreeMap::new()),
+            append_lock: Mutex::new(()),
+        };
+        let Some(dir) = &indexes.dir else { return Ok(indexes) };
+        std::fs::create_dir_all(dir)
+            .map_err(|e| Error::Storage(format!("Failed to create vector directory: {}", e)))?;
+
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some(LOG_EXTENSION) {
+                continue;
+            }
+            let Some(name) = path.file_stem().and_then(|s| s.to_str()).map(str::to_string) else { continue };
+            indexes.replay(&name, &path)?;
+        }
+        Ok(indexes)
+    }
+
+    fn replay(&self, name: &str, path: &Path) -> Result<()> {
+        let mut lines = BufReader::new(std::fs::File::open(path)?).lines();
+        let header: LogHeader = match lines.next() {
+            Some(line) => serde_json::from_str(&line?)
+                .map_err(|e| Error::Deserialization(format!("Invalid vector index header in {:?}: {}", path, e)))?,
+            None => return Ok(()),
+        };
+        self.store.create_index(name.to_string(), header.dimension, IndexType::Flat);
+        self.dimensions.write().insert(name.to_string(), header.dimension);
+
+        let mut loaded = 0usize;
+        for (line_no, line) in lines.enumerate() {
+            let line = line?;
+            match serde_json::from_str::<Embedding>(&line) {
+                Ok(embedding) => {
+                    self.store.add_embedding(name, embedding)?;
+                    loaded += 1;
+                }
+                // e.g. a torn final line after a crash
+                Err(e) => warn!("Skipping line {} of vector index '{}': {}", line_no + 2, name, e),
+            }
+        }
+        info!("Loaded vector index '{}' ({} vectors, dimension {})", name, loaded, header.dimension);
+        Ok(())
+    }
+
+    fn log_path(&self, name: &str) -> Option<PathBuf> {
+        self.dir.as_ref().map(|dir| dir.join(format!("{}.{}", name, LOG_EXTENSION)))
+    }
+
+    fn append(&self, name: &str, line: &str) -> Result<()> {
+        let Some(path) = self.log_path(name) else { return Ok(()) };
+        let _guard = self.append_lock.lock();
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        file.write_all(line.as_bytes())?;
+        file.write_all(b"\n")?;
+        file.sync_data()?;
+        Ok(())
+    }
+
+    fn create(&self, name: &str, dimension: usize) -> Result<()> {
+        validate_name("Vector index", name)?;
+        if dimension == 0 {
+            return Err(Error::Index("Vector dimension must be greater than zero".to_string()));
+        }
+        let mut dimensions = self.dimensions.write();
+        if dimensions.contains_key(name) {
+            return Err(Error::Index(format!("Vector index '{}' already exists", name)));
+        }
+        let header = serde_json::to_string(&LogHeader { dimension })
+            .map_err(|e| Error::Serialization(e.to_string()))?;
+        self.append(name, &header)?;
+        self.store.create_index(name.to_string(), dimension, IndexType::Flat);
+        dimensions.insert(name.to_string(), dimension);
+        Ok(())
+    }
+
+    fn add(&self, name: &str, embedding: Embedding) -> Result<()> {
+        let dimension = self
+            .dimensions
+            .read()
+            .get(name)
+            .copied()
+            .ok_or_else(|| Error::Index(format!("Vector index '{}' not found", name)))?;
+        if embedding.vector.len() != dimension {
+            return Err(Error::Index(format!(
+                "Vector has dimension {}, index '{}' expects {}",
+                embedding.vector.len(),
+                name,
+                dimension
+            )));
+        }
+        check_finite(&embedding.vector)?;
+        let line = serde_json::to_string(&embedding).map_err(|e| Error::Serialization(e.to_string()))?;
+        self.append(name, &line)?;
+        self.store.add_embedding(name, embedding)
+    }
+}
+
+fn check_finite(vector: &[f32]) -> Result<()> {
+    if vector.iter().all(|v| v.is_finite()) {
+        Ok(())
+    } else {
+        Err(Error::Index("Vectors may not contain NaN or infinite values".to_string()))
+    }
+}
+
+impl Database {
+    /// Create a vector index of fixed dimension
+    pub fn create_vector_index(&self, name: &str, dimension: usize) -> Result<()> {
+        self.vectors().create(name, dimension)
+    }
+
+    /// Vector index names with their dimensions
+    pub fn vector_indexes(&self) -> BTreeMap<String, usize> {
+        self.vectors().dimensions.read().clone()
+    }
+
+    /// Add (or replace) the vector stored under `id`
+    pub fn add_vector(&self, index: &str, id: u64, vector: Vec<f32>, metadata: HashMap<String, Value>) -> Result<()> {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        self.vectors().add(index, Embedding { id, vector, metadata, timestamp })
+    }
+
+    /// The `k` vectors most similar to `query`, best first
+    pub fn search_vectors(&self, index: &str, query: &[f32], k: usize) -> Result<Vec<VectorMatch>> {
+        let indexes = self.vectors();
+        if !indexes.dimensions.read().contains_key(index) {
+            return Err(Error::Index(format!("Vector index '{}' not found", index)));
+        }
+        check_finite(query)?;
+        Ok(indexes
+            .store
+            .search(index, query, k)?
+            .into_iter()
+            .map(|result| VectorMatch {
+                id: result.id,
+                score: result.similarity,
+                metadata: result.embedding.metadata,
+            })
+            .collect())
+    }
+}
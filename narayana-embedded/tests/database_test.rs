@@ -0,0 +1,156 @@
+//! Tests for the embedded database API
+
+use narayana_embedded::{Column, DataType, Database, Field, Filter, Schema};
+use serde_json::json;
+use std::collections::HashMap;
+
+fn field(name: &str, data_type: DataType, nullable: bool) -> Field {
+    Field {
+        name: name.to_string(),
+        data_type,
+        nullable,
+        default_value: None,
+    }
+}
+
+fn readings_schema() -> Schema {
+    Schema::new(vec![
+        field("id", DataType::Int64, false),
+        field("sensor", DataType::String, false),
+        field("value", DataType::Float64, true),
+    ])
+}
+
+#[tokio::test]
+async fn test_reopen_keeps_tables_rows_and_vectors() {
+    let dir = tempfile::tempdir().unwrap();
+    {
+        let db = Database::open(dir.path()).await.unwrap();
+        let table = db.create_table("readings", readings_schema()).await.unwrap();
+        table.insert(&[json!({"id": 1, "sensor": "lidar", "value": 0.5})]).await.unwrap();
+        table.insert(&[json!({"id": 2, "sensor": "imu", "value": 9.8})]).await.unwrap();
+
+        db.create_vector_index("places", 2).unwrap();
+        db.add_vector("places", 7, vec![1.0, 0.0], HashMap::from([("name".to_string(), json!("dock"))])).unwrap();
+    }
+
+    let db = Database::open(dir.path()).await.unwrap();
+    assert_eq!(db.table_names(), vec!["readings".to_string()]);
+    let table = db.table("readings").await.unwrap();
+    assert_eq!(table.count().await.unwrap(), 2);
+    let rows = table.query().rows().await.unwrap();
+    assert_eq!(rows[0]["sensor"], json!("lidar"));
+    assert_eq!(rows[1]["id"], json!(2));
+
+    // Appends after reopening continue after the stored rows
+    table.insert(&[json!({"id": 3, "sensor": "gps"})]).await.unwrap();
+    let ids = table.query().select(&["id"]).offset(1).columns().await.unwrap();
+    assert!(matches!(&ids[0], Column::Int64(v) if v == &vec![2, 3]));
+
+    assert_eq!(db.vector_indexes().get("places"), Some(&2));
+    let hits = db.search_vectors("places", &[0.9, 0.1], 1).unwrap();
+    assert_eq!(hits[0].id, 7);
+    assert_eq!(hits[0].metadata["name"], json!("dock"));
+}
+
+#[tokio::test]
+async fn test_query_filters_projection_and_paging() {
+    let db = Database::in_memory();
+    let table = db.create_table("readings", readings_schema()).await.unwrap();
+    let rows: Vec<_> = (0..10)
+        .map(|i| json!({"id": i, "sensor": if i % 2 == 0 { "lidar" } else { "imu" }, "value": i as f64 / 10.0}))
+        .collect();
+    assert_eq!(table.insert(&rows).await.unwrap(), 10);
+
+    let lidar = table.query().where_eq("sensor", "lidar").select(&["id"]).rows().await.unwrap();
+    let ids: Vec<_> = lidar.iter().map(|row| row["id"].clone()).collect();
+    assert_eq!(ids, vec![json!(0), json!(2), json!(4), json!(6), json!(8)]);
+    assert_eq!(lidar[0].len(), 1);
+
+    let page = table
+        .query()
+        .where_eq("sensor", "lidar")
+        .filter(Filter::Gt { column: "value".to_string(), value: json!(0.1) })
+        .offset(1)
+        .limit(2)
+        .rows()
+        .await
+        .unwrap();
+    assert_eq!(page.iter().map(|row| row["id"].clone()).collect::<Vec<_>>(), vec![json!(4), json!(6)]);
+
+    let tail = table.query().offset(8).limit(5).rows().await.unwrap();
+    assert_eq!(tail.len(), 2);
+    assert!(table.query().offset(50).rows().await.unwrap().is_empty());
+    assert!(table.query().select(&["missing"]).rows().await.is_err());
+}
+
+#[tokio::test]
+async fn test_inserts_are_validated_against_the_schema() {
+    let db = Database::in_memory();
+    let table = db.create_table("readings", readings_schema()).await.unwrap();
+
+    // Unknown fields, missing non-nullable values and wrong types
+    assert!(table.insert(&[json!({"id": 1, "sensor": "a", "colour": "red"})]).await.is_err());
+    assert!(table.insert(&[json!({"sensor": "a"})]).await.is_err());
+    assert!(table.insert(&[json!({"id": "one", "sensor": "a"})]).await.is_err());
+    assert!(table.insert(&[json!([1, "a", 0.5])]).await.is_err());
+    assert!(table.insert_columns(vec![Column::Int64(vec![1])]).await.is_err());
+    assert!(table
+        .insert_columns(vec![Column::Int64(vec![1]), Column::String(vec!["a".to_string()]), Column::Int64(vec![1])])
+        .await
+        .is_err());
+    assert_eq!(table.count().await.unwrap(), 0);
+
+    // A missing nullable value is stored as zero
+    table.insert(&[json!({"id": 1, "sensor": "a"})]).await.unwrap();
+    assert_eq!(table.query().rows().await.unwrap()[0]["value"], json!(0.0));
+
+    assert!(db.create_table("readings", readings_schema()).await.is_err());
+    assert!(db.create_table("../escape", readings_schema()).await.is_err());
+    assert!(db.create_table("empty", Schema::new(vec![])).await.is_err());
+}
+
+#[tokio::test]
+async fn test_drop_table() {
+    let dir = tempfile::tempdir().unwrap();
+    let db = Database::open(dir.path()).await.unwrap();
+    let table = db.create_table("scratch", readings_schema()).await.unwrap();
+    table.insert(&[json!({"id": 1, "sensor": "a"})]).await.unwrap();
+
+    db.drop_table("scratch").await.unwrap();
+    assert!(db.table("scratch").await.is_err());
+    assert!(db.drop_table("scratch").await.is_err());
+
+    let db = Database::open(dir.path()).await.unwrap();
+    assert!(db.table_names().is_empty());
+    // The name can be reused and starts empty
+    let table = db.create_table("scratch", readings_schema()).await.unwrap();
+    assert_eq!(table.count().await.unwrap(), 0);
+}
+
+#[test]
+fn test_vector_search_and_validation() {
+    let db = Database::in_memory();
+    db.create_vector_index("faces", 3).unwrap();
+    assert!(db.create_vector_index("faces", 3).is_err());
+    assert!(db.create_vector_index("flat", 0).is_err());
+
+    db.add_vector("faces", 1, vec![1.0, 0.0, 0.0], HashMap::new()).unwrap();
+    db.add_vector("faces", 2, vec![0.0, 1.0, 0.0], HashMap::new()).unwrap();
+    db.add_vector("faces", 3, vec![0.7, 0.7, 0.0], HashMap::new()).unwrap();
+
+    let hits = db.search_vectors("faces", &[1.0, 0.1, 0.0], 2).unwrap();
+    assert_eq!(hits.iter().map(|h| h.id).collect::<Vec<_>>(), vec![1, 3]);
+    assert!(hits[0].score > hits[1].score);
+
+    // Re-adding an id replaces its vector
+    db.add_vector("faces", 1, vec![0.0, 0.0, 1.0], HashMap::new()).unwrap();
+    assert_eq!(db.search_vectors("faces", &[0.0, 0.0, 1.0], 1).unwrap()[0].id, 1);
+    assert_eq!(db.search_vectors("faces", &[1.0, 0.0, 0.0], 5).unwrap().len(), 3);
+
+    assert!(db.add_vector("faces", 4, vec![1.0, 0.0], HashMap::new()).is_err());
+    assert!(db.add_vector("faces", 4, vec![f32::NAN, 0.0, 0.0], HashMap::new()).is_err());
+    assert!(db.add_vector("missing", 1, vec![1.0, 0.0, 0.0], HashMap::new()).is_err());
+    assert!(db.search_vectors("faces", &[1.0], 1).is_err());
+    assert!(db.search_vectors("missing", &[1.0, 0.0, 0.0], 1).is_err());
+}
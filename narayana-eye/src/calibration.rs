@@ -0,0 +1,410 @@
+//! Camera calibration and undistortion
+//!
+//! Intrinsics come from a checkerboard held in front of the camera in a
+//! number of poses; extrinsics from one view of a checkerboard lying at the
+//! world origin. Calibrations are kept in narayana-storage, and the
+//! `Undistorter` stage removes lens distortion before frames reach the models,
+//! so pixel positions map to straight rays for 3D position estimates.
+
+use crate::error::VisionError;
+use narayana_storage::camera_calibration::{CameraCalibration, CameraExtrinsics};
+use opencv::calib3d;
+use opencv::core::{self, Mat, Point2f, Point3f, Rect, Scalar, Size, TermCriteria, Vector, CV_32FC1, CV_64F};
+use opencv::imgproc;
+use opencv::prelude::*;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+
+/// Checkerboard used for calibration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CheckerboardConfig {
+    /// Inner corners per row and per column
+    pub inner_corners: (u32, u32),
+    /// Edge length of one square, in meters
+    pub square_size_m: f64,
+    /// Views needed before calibrating
+    pub min_samples: usize,
+    /// Frames tried before giving up
+    pub max_attempts: usize,
+    /// Pause between captured frames, so the board can be moved
+    pub capture_interval_ms: u64,
+}
+
+impl Default for CheckerboardConfig {
+    fn default() -> Self {
+        Self {
+            inner_corners: (9, 6),
+            square_size_m: 0.025,
+            min_samples: 12,
+            max_attempts: 200,
+            capture_interval_ms: 500,
+        }
+    }
+}
+
+impl CheckerboardConfig {
+    /// Validate configuration
+    pub fn validate(&self) -> Result<(), String> {
+        let (cols, rows) = self.inner_corners;
+        if cols < 2 || rows < 2 || cols > 64 || rows > 64 {
+            return Err("Checkerboard inner corners must be between 2 and 64 per side".to_string());
+        }
+        if !(self.square_size_m > 0.0 && self.square_size_m.is_finite()) {
+            return Err("Checkerboard square size must be positive".to_string());
+        }
+        if self.min_samples < 3 {
+            return Err("Calibration needs at least 3 samples".to_string());
+        }
+        if self.max_attempts < self.min_samples {
+            return Err("Calibration max_attempts must be at least min_samples".to_string());
+        }
+        Ok(())
+    }
+
+    fn pattern_size(&self) -> Size {
+        Size::new(self.inner_corners.0 as i32, self.inner_corners.1 as i32)
+    }
+
+    /// Corner positions on the board plane (z = 0), in meters
+    fn object_points(&self) -> Vector<Point3f> {
+        let (cols, rows) = self.inner_corners;
+        let square = self.square_size_m as f32;
+        let mut points = Vector::new();
+        for row in 0..rows {
+            for col in 0..cols {
+                points.push(Point3f::new(col as f32 * square, row as f32 * square, 0.0));
+            }
+        }
+        points
+    }
+}
+
+/// Collects checkerboard views and computes camera intrinsics
+pub struct CheckerboardCalibrator {
+    board: CheckerboardConfig,
+    image_size: Option<Size>,
+    image_points: Vector<Vector<Point2f>>,
+}
+
+impl CheckerboardCalibrator {
+    pub fn new(board: CheckerboardConfig) -> Result<Self, VisionError> {
+        board.validate().map_err(VisionError::Config)?;
+        Ok(Self {
+            board,
+            image_size: None,
+            image_points: Vector::new(),
+        })
+    }
+
+    pub fn board(&self) -> &CheckerboardConfig {
+        &self.board
+    }
+
+    /// Add a view; returns false if the board was not found in `frame`
+    pub fn add_frame(&mut self, frame: &Mat) -> Result<bool, VisionError> {
+        let size = frame.size().map_err(|e| VisionError::OpenCv(format!("Failed to read frame size: {}", e)))?;
+        if let Some(expected) = self.image_size {
+            if expected != size {
+                return Err(VisionError::Config(format!(
+                    "Calibration frames must all be {}x{}, got {}x{}",
+                    expected.width, expected.height, size.width, size.height
+                )));
+            }
+        }
+        let Some(corners) = find_checkerboard(frame, &self.board)? else {
+            return Ok(false);
+        };
+        self.image_size = Some(size);
+        self.image_points.push(corners);
+        debug!("Calibration sample {} captured", self.image_points.len());
+        Ok(true)
+    }
+
+    pub fn sample_count(&self) -> usize {
+        self.image_points.len()
+    }
+
+    pub fn has_enough_samples(&self) -> bool {
+        self.sample_count() >= self.board.min_samples
+    }
+
+    /// Compute intrinsics and distortion from the collected views
+    pub fn calibrate(&self, camera_id: &str) -> Result<CameraCalibration, VisionError> {
+        if !self.has_enough_samples() {
+            return Err(VisionError::Processing(format!(
+                "Calibration needs {} checkerboard views, have {}",
+                self.board.min_samples,
+                self.sample_count()
+            )));
+        }
+        let image_size = self.image_size.unwrap_or_default();
+
+        let mut object_points: Vector<Vector<Point3f>> = Vector::new();
+        for _ in 0..self.image_points.len() {
+            object_points.push(self.board.object_points());
+        }
+        let mut camera_matrix = Mat::default();
+        let mut dist_coeffs = Mat::default();
+        let mut rvecs: Vector<Mat> = Vector::new();
+        let mut tvecs: Vector<Mat> = Vector::new();
+        let criteria = TermCriteria::new(
+            core::TermCriteria_Type::COUNT as i32 + core::TermCriteria_Type::EPS as i32,
+            30,
+            f64::EPSILON,
+        ).map_err(|e| VisionError::OpenCv(format!("Failed to create termination criteria: {}", e)))?;
+        let rms = calib3d::calibrate_camera(
+            &object_points,
+            &self.image_points,
+            image_size,
+            &mut camera_matrix,
+            &mut dist_coeffs,
+            &mut rvecs,
+            &mut tvecs,
+            0,
+            criteria,
+        ).map_err(|e| VisionError::OpenCv(format!("Camera calibration failed: {}", e)))?;
+
+        let calibration = CameraCalibration {
+            camera_id: camera_id.to_string(),
+            image_size: (image_size.width as u32, image_size.height as u32),
+            camera_matrix: mat_to_matrix(&camera_matrix)?,
+            distortion: mat_to_coeffs(&dist_coeffs)?,
+            extrinsics: None,
+            reprojection_error: rms,
+            calibrated_at: 0,
+        };
+        calibration.validate()?;
+        info!(
+            "Calibrated {} from {} views, reprojection error {:.3}px",
+            camera_id,
+            self.sample_count(),
+            rms
+        );
+        Ok(calibration)
+    }
+}
+
+/// World-to-camera transform from one view of a checkerboard at the world origin
+///
+/// Returns `None` if the board is not visible in `frame`.
+pub fn estimate_extrinsics(
+    calibration: &CameraCalibration,
+    frame: &Mat,
+    board: &CheckerboardConfig,
+) -> Result<Option<CameraExtrinsics>, VisionError> {
+    board.validate().map_err(VisionError::Config)?;
+    let Some(corners) = find_checkerboard(frame, board)? else {
+        return Ok(None);
+    };
+    let size = frame.size().map_err(|e| VisionError::OpenCv(format!("Failed to read frame size: {}", e)))?;
+    let camera_matrix = matrix_to_mat(&scaled_camera_matrix(calibration, size))?;
+    let dist_coeffs = coeffs_to_mat(&calibration.distortion)?;
+
+    let mut rvec = Mat::default();
+    let mut tvec = Mat::default();
+    let found = calib3d::solve_pnp(
+        &board.object_points(),
+        &corners,
+        &camera_matrix,
+        &dist_coeffs,
+        &mut rvec,
+        &mut tvec,
+        false,
+        calib3d::SOLVEPNP_ITERATIVE,
+    ).map_err(|e| VisionError::OpenCv(format!("Pose estimation failed: {}", e)))?;
+    if !found {
+        return Ok(None);
+    }
+    Ok(Some(CameraExtrinsics {
+        rotation: mat_to_vec3(&rvec)?,
+        translation: mat_to_vec3(&tvec)?,
+    }))
+}
+
+/// Rectification maps for one frame size
+struct UndistortMaps {
+    size: Size,
+    map1: Mat,
+    map2: Mat,
+    camera_matrix: [f64; 9],
+}
+
+/// Pipeline stage removing lens distortion from frames
+pub struct Undistorter {
+    calibration: CameraCalibration,
+    maps: Mutex<Option<UndistortMaps>>,
+}
+
+impl Undistorter {
+    pub fn new(calibration: CameraCalibration) -> Result<Self, VisionError> {
+        calibration.validate()?;
+        Ok(Self {
+            calibration,
+            maps: Mutex::new(None),
+        })
+    }
+
+    pub fn calibration(&self) -> &CameraCalibration {
+        &self.calibration
+    }
+
+    /// Undistorted copy of `frame`
+    ///
+    /// Frames at another size than the calibration (e.g. downscaled by the
+    /// governor) use intrinsics scaled to match.
+    pub fn undistort(&self, frame: &Mat) -> Result<Mat, VisionError> {
+        let size = frame.size().map_err(|e| VisionError::OpenCv(format!("Failed to read frame size: {}", e)))?;
+        let mut maps = self.maps.lock();
+        if maps.as_ref().is_none_or(|maps| maps.size != size) {
+            *maps = Some(self.build_maps(size)?);
+        }
+        let maps = maps.as_ref().expect("maps were just built");
+
+        let mut undistorted = Mat::default();
+        imgproc::remap(
+            frame,
+            &mut undistorted,
+            &maps.map1,
+            &maps.map2,
+            imgproc::INTER_LINEAR,
+            core::BORDER_CONSTANT,
+            Scalar::default(),
+        ).map_err(|e| VisionError::OpenCv(format!("Failed to undistort frame: {}", e)))?;
+        Ok(undistorted)
+    }
+
+    /// Row-major camera matrix of the undistorted frames, once one was processed
+    pub fn rectified_intrinsics(&self) -> Option<[f64; 9]> {
+        self.maps.lock().as_ref().map(|maps| maps.camera_matrix)
+    }
+
+    fn build_maps(&self, size: Size) -> Result<UndistortMaps, VisionError> {
+        let camera_matrix = matrix_to_mat(&scaled_camera_matrix(&self.calibration, size))?;
+        let dist_coeffs = coeffs_to_mat(&self.calibration.distortion)?;
+        // Alpha 0 crops to pixels that are valid after undistortion
+        let mut roi = Rect::default();
+        let new_matrix = calib3d::get_optimal_new_camera_matrix(
+            &camera_matrix,
+            &dist_coeffs,
+            size,
+            0.0,
+            size,
+            &mut roi,
+            false,
+        ).map_err(|e| VisionError::OpenCv(format!("Failed to compute rectified camera matrix: {}", e)))?;
+
+        let mut map1 = Mat::default();
+        let mut map2 = Mat::default();
+        calib3d::init_undistort_rectify_map(
+            &camera_matrix,
+            &dist_coeffs,
+            &Mat::default(),
+            &new_matrix,
+            size,
+            CV_32FC1,
+            &mut map1,
+            &mut map2,
+        ).map_err(|e| VisionError::OpenCv(format!("Failed to build undistortion maps: {}", e)))?;
+
+        debug!("Built undistortion maps for {}x{}", size.width, size.height);
+        Ok(UndistortMaps {
+            size,
+            map1,
+            map2,
+            camera_matrix: mat_to_matrix(&new_matrix)?,
+        })
+    }
+}
+
+/// Sub-pixel checkerboard corners in `frame`, if the whole board is visible
+fn find_checkerboard(frame: &Mat, board: &CheckerboardConfig) -> Result<Option<Vector<Point2f>>, VisionError> {
+    let mut gray = Mat::default();
+    let converted = if frame.channels() == 1 {
+        frame.copy_to(&mut gray)
+    } else {
+        imgproc::cvt_color(frame, &mut gray, imgproc::COLOR_BGR2GRAY, 0)
+    };
+    converted.map_err(|e| VisionError::OpenCv(format!("Failed to convert to grayscale: {}", e)))?;
+
+    let mut corners: Vector<Point2f> = Vector::new();
+    let found = calib3d::find_chessboard_corners(
+        &gray,
+        board.pattern_size(),
+        &mut corners,
+        calib3d::CALIB_CB_ADAPTIVE_THRESH + calib3d::CALIB_CB_NORMALIZE_IMAGE + calib3d::CALIB_CB_FAST_CHECK,
+    ).map_err(|e| VisionError::OpenCv(format!("Checkerboard detection failed: {}", e)))?;
+    if !found {
+        return Ok(None);
+    }
+
+    let criteria = TermCriteria::new(
+        core::TermCriteria_Type::COUNT as i32 + core::TermCriteria_Type::EPS as i32,
+        30,
+        0.001,
+    ).map_err(|e| VisionError::OpenCv(format!("Failed to create termination criteria: {}", e)))?;
+    imgproc::corner_sub_pix(&gray, &mut corners, Size::new(11, 11), Size::new(-1, -1), criteria)
+        .map_err(|e| VisionError::OpenCv(format!("Corner refinement failed: {}", e)))?;
+    Ok(Some(corners))
+}
+
+/// Camera matrix for frames of `size`, scaled from the calibrated size
+fn scaled_camera_matrix(calibration: &CameraCalibration, size: Size) -> [f64; 9] {
+    let sx = size.width as f64 / calibration.image_size.0 as f64;
+    let sy = size.height as f64 / calibration.image_size.1 as f64;
+    let mut matrix = calibration.camera_matrix;
+    matrix[0] *= sx; // fx
+    matrix[1] *= sx; // skew
+    matrix[2] *= sx; // cx
+    matrix[4] *= sy; // fy
+    matrix[5] *= sy; // cy
+    matrix
+}
+
+fn matrix_to_mat(matrix: &[f64; 9]) -> Result<Mat, VisionError> {
+    let mut mat = Mat::new_rows_cols_with_default(3, 3, CV_64F, Scalar::all(0.0))
+        .map_err(|e| VisionError::OpenCv(format!("Failed to allocate matrix: {}", e)))?;
+    for (i, value) in matrix.iter().enumerate() {
+        *mat.at_2d_mut::<f64>(i as i32 / 3, i as i32 % 3)
+            .map_err(|e| VisionError::OpenCv(format!("Failed to write matrix: {}", e)))? = *value;
+    }
+    Ok(mat)
+}
+
+fn mat_to_matrix(mat: &Mat) -> Result<[f64; 9], VisionError> {
+    let mut matrix = [0.0; 9];
+    for (i, value) in matrix.iter_mut().enumerate() {
+        *value = *mat.at_2d::<f64>(i as i32 / 3, i as i32 % 3)
+            .map_err(|e| VisionError::OpenCv(format!("Failed to read matrix: {}", e)))?;
+    }
+    Ok(matrix)
+}
+
+fn coeffs_to_mat(coeffs: &[f64]) -> Result<Mat, VisionError> {
+    let mut mat = Mat::new_rows_cols_with_default(1, coeffs.len() as i32, CV_64F, Scalar::all(0.0))
+        .map_err(|e| VisionError::OpenCv(format!("Failed to allocate matrix: {}", e)))?;
+    for (i, value) in coeffs.iter().enumerate() {
+        *mat.at_mut::<f64>(i as i32)
+            .map_err(|e| VisionError::OpenCv(format!("Failed to write matrix: {}", e)))? = *value;
+    }
+    Ok(mat)
+}
+
+fn mat_to_coeffs(mat: &Mat) -> Result<Vec<f64>, VisionError> {
+    (0..mat.total() as i32)
+        .map(|i| {
+            mat.at::<f64>(i)
+                .copied()
+                .map_err(|e| VisionError::OpenCv(format!("Failed to read matrix: {}", e)))
+        })
+        .collect()
+}
+
+fn mat_to_vec3(mat: &Mat) -> Result<[f64; 3], VisionError> {
+    let values = mat_to_coeffs(mat)?;
+    values
+        .get(..3)
+        .and_then(|v| v.try_into().ok())
+        .ok_or_else(|| VisionError::Processing("Expected a 3-vector".to_string()))
+}
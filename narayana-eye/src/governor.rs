@@ -0,0 +1,333 @@
+//! Adaptive processing governor
+//!
+//! Vision models can take more CPU than the robot has to spare. The governor
+//! watches per-frame pipeline latency and system load and walks a ladder of
+//! quality levels: lower frame rate, then lower resolution, and finally
+//! on-demand processing only. It steps back up once there is headroom again.
+
+use crate::config::{ProcessingMode, VisionConfig};
+use crate::error::VisionError;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Governor configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GovernorConfig {
+    pub enabled: bool,
+    /// Share of the frame interval the pipeline may take (0.0-1.0]
+    pub target_utilization: f64,
+    /// System load (per core, 1.0 = all cores busy) above which quality is lowered
+    pub max_cpu_load: f64,
+    /// Lowest frame rate before falling back to on-demand processing
+    pub min_frame_rate: u32,
+    /// How often latency and load are evaluated
+    pub evaluation_interval_ms: u64,
+    /// Healthy evaluations in a row before quality is raised again
+    pub step_up_after: u32,
+    /// Whether the last resort is processing only requested frames
+    pub allow_on_demand: bool,
+}
+
+impl Default for GovernorConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            target_utilization: 0.8,
+            max_cpu_load: 0.85,
+            min_frame_rate: 2,
+            evaluation_interval_ms: 2000,
+            step_up_after: 3,
+            allow_on_demand: true,
+        }
+    }
+}
+
+impl GovernorConfig {
+    /// Validate configuration
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.target_utilization > 0.0 && self.target_utilization <= 1.0) {
+            return Err("Governor target_utilization must be in (0.0, 1.0]".to_string());
+        }
+        if !(self.max_cpu_load > 0.0 && self.max_cpu_load.is_finite()) {
+            return Err("Governor max_cpu_load must be greater than 0".to_string());
+        }
+        if self.min_frame_rate == 0 {
+            return Err("Governor min_frame_rate must be greater than 0".to_string());
+        }
+        if self.evaluation_interval_ms < 100 {
+            return Err("Governor evaluation_interval_ms must be at least 100".to_string());
+        }
+        if self.step_up_after == 0 {
+            return Err("Governor step_up_after must be greater than 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// One rung of the quality ladder
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QualityLevel {
+    /// 0 is full quality; higher is cheaper
+    pub level: usize,
+    pub frame_rate: u32,
+    pub resolution: (u32, u32),
+    pub mode: ProcessingMode,
+}
+
+/// Outcome of an evaluation, as reported to the world broker
+#[derive(Debug, Clone, Serialize)]
+pub struct QualityReport {
+    #[serde(flatten)]
+    pub quality: QualityLevel,
+    /// Levels on the ladder
+    pub levels: usize,
+    /// 90th percentile pipeline latency over the last window
+    pub p90_latency_ms: Option<f64>,
+    pub cpu_load: Option<f64>,
+}
+
+/// Source of system load figures
+pub trait LoadMonitor: Send + Sync {
+    /// Current load per core (1.0 = all cores busy), if known
+    fn load(&self) -> Option<f64>;
+}
+
+/// One-minute load average over the number of cores
+///
+/// Reads `/proc/loadavg`, so it only reports on Linux; elsewhere the governor
+/// goes by pipeline latency alone.
+pub struct SystemLoadMonitor;
+
+impl LoadMonitor for SystemLoadMonitor {
+    fn load(&self) -> Option<f64> {
+        let loadavg = std::fs::read_to_string("/proc/loadavg").ok()?;
+        let one_minute: f64 = loadavg.split_whitespace().next()?.parse().ok()?;
+        let cores = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+        Some(one_minute / cores as f64)
+    }
+}
+
+struct GovernorState {
+    level: usize,
+    latencies_ms: Vec<f64>,
+    healthy_windows: u32,
+    last_frame: Option<Instant>,
+    frame_requested: bool,
+}
+
+/// Decides which frames to process and at what quality
+pub struct ProcessingGovernor {
+    config: GovernorConfig,
+    ladder: Vec<QualityLevel>,
+    monitor: Box<dyn LoadMonitor>,
+    state: Mutex<GovernorState>,
+}
+
+/// Latencies kept per evaluation window
+const MAX_SAMPLES: usize = 1000;
+
+impl ProcessingGovernor {
+    pub fn new(vision: &VisionConfig, config: GovernorConfig) -> Result<Self, VisionError> {
+        config.validate().map_err(VisionError::Config)?;
+        Ok(Self {
+            ladder: quality_ladder(vision, &config),
+            config,
+            monitor: Box::new(SystemLoadMonitor),
+            state: Mutex::new(GovernorState {
+                level: 0,
+                latencies_ms: Vec::new(),
+                healthy_windows: 0,
+                last_frame: None,
+                frame_requested: false,
+            }),
+        })
+    }
+
+    /// Use a different source of system load
+    pub fn with_load_monitor(mut self, monitor: Box<dyn LoadMonitor>) -> Self {
+        self.monitor = monitor;
+        self
+    }
+
+    pub fn config(&self) -> &GovernorConfig {
+        &self.config
+    }
+
+    /// The quality levels, from full quality down
+    pub fn ladder(&self) -> &[QualityLevel] {
+        &self.ladder
+    }
+
+    /// Active quality level
+    pub fn current(&self) -> QualityLevel {
+        self.ladder[self.state.lock().level].clone()
+    }
+
+    /// Whether the frame that just arrived should go through the pipeline
+    ///
+    /// Thins the camera stream out to the active frame rate; at the
+    /// on-demand level only requested frames pass.
+    pub fn should_process(&self) -> bool {
+        if !self.config.enabled {
+            return true;
+        }
+        let mut state = self.state.lock();
+        let quality = &self.ladder[state.level];
+        let now = Instant::now();
+        let due = match quality.mode {
+            ProcessingMode::OnDemand => false,
+            ProcessingMode::RealTime => {
+                // Frames arrive with some jitter; allow a little slack so
+                // half rate does not turn into a third
+                let interval = Duration::from_secs_f64(0.9 / quality.frame_rate as f64);
+                state.last_frame.is_none_or(|last| now.duration_since(last) >= interval)
+            }
+        };
+        if due || std::mem::take(&mut state.frame_requested) {
+            state.last_frame = Some(now);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Let the next frame through regardless of the active frame rate
+    pub fn request_frame(&self) {
+        self.state.lock().frame_requested = true;
+    }
+
+    /// Record how long the pipeline took for one frame
+    pub fn record_frame(&self, latency: Duration) {
+        let mut state = self.state.lock();
+        if state.latencies_ms.len() < MAX_SAMPLES {
+            state.latencies_ms.push(latency.as_secs_f64() * 1000.0);
+        }
+    }
+
+    /// Close the current window and move along the ladder if needed
+    ///
+    /// Called every `evaluation_interval_ms`. Steps down one level when the
+    /// pipeline overruns its share of the frame interval or the system is
+    /// overloaded; steps up after `step_up_after` windows in a row with room
+    /// for the next level up. Returns the report when the level changed.
+    pub fn evaluate(&self) -> Option<QualityReport> {
+        if !self.config.enabled {
+            return None;
+        }
+        let load = self.monitor.load();
+        let mut state = self.state.lock();
+        let p90 = percentile(&mut state.latencies_ms, 0.9);
+        state.latencies_ms.clear();
+
+        let level = state.level;
+        let budget = self.budget_ms(level);
+        let overloaded = p90.is_some_and(|p90| p90 > budget) || load.is_some_and(|load| load > self.config.max_cpu_load);
+        if overloaded {
+            state.healthy_windows = 0;
+            if level + 1 < self.ladder.len() {
+                state.level += 1;
+            }
+        } else if level > 0 {
+            // The level above processes more pixels more often; estimate its
+            // latency from this one and require some margin
+            let upper = &self.ladder[level - 1];
+            let current = &self.ladder[level];
+            let pixel_ratio = pixels(upper.resolution) / pixels(current.resolution);
+            let fits = p90.is_none_or(|p90| p90 * pixel_ratio < 0.8 * self.budget_ms(level - 1));
+            let idle = load.is_none_or(|load| load < 0.8 * self.config.max_cpu_load);
+            if fits && idle {
+                state.healthy_windows += 1;
+                if state.healthy_windows >= self.config.step_up_after {
+                    state.healthy_windows = 0;
+                    state.level -= 1;
+                }
+            } else {
+                state.healthy_windows = 0;
+            }
+        }
+
+        (state.level != level).then(|| QualityReport {
+            quality: self.ladder[state.level].clone(),
+            levels: self.ladder.len(),
+            p90_latency_ms: p90,
+            cpu_load: load,
+        })
+    }
+
+    /// Report on the active level without evaluating
+    pub fn report(&self) -> QualityReport {
+        QualityReport {
+            quality: self.current(),
+            levels: self.ladder.len(),
+            p90_latency_ms: None,
+            cpu_load: self.monitor.load(),
+        }
+    }
+
+    /// Pipeline time allowed per frame at `level`
+    fn budget_ms(&self, level: usize) -> f64 {
+        1000.0 / self.ladder[level].frame_rate as f64 * self.config.target_utilization
+    }
+}
+
+/// Quality levels for `vision`, from full quality down
+///
+/// Halves the frame rate first, then trades resolution, then drops to
+/// `min_frame_rate` and finally to on-demand processing.
+pub fn quality_ladder(vision: &VisionConfig, config: &GovernorConfig) -> Vec<QualityLevel> {
+    let full_rate = vision.frame_rate.max(1);
+    let min_rate = config.min_frame_rate.clamp(1, full_rate);
+    let steps = [
+        (full_rate, 1.0),
+        (full_rate / 2, 1.0),
+        (full_rate / 2, 0.75),
+        (full_rate / 4, 0.5),
+        (min_rate, 0.5),
+    ];
+
+    let mut ladder: Vec<QualityLevel> = Vec::new();
+    for (frame_rate, scale) in steps {
+        let quality = QualityLevel {
+            level: ladder.len(),
+            frame_rate: frame_rate.max(min_rate),
+            resolution: scale_resolution(vision.resolution, scale),
+            mode: ProcessingMode::RealTime,
+        };
+        let duplicate = ladder
+            .last()
+            .is_some_and(|last| last.frame_rate == quality.frame_rate && last.resolution == quality.resolution);
+        if !duplicate {
+            ladder.push(quality);
+        }
+    }
+    if config.allow_on_demand {
+        let cheapest = ladder[ladder.len() - 1].clone();
+        ladder.push(QualityLevel {
+            level: ladder.len(),
+            mode: ProcessingMode::OnDemand,
+            ..cheapest
+        });
+    }
+    ladder
+}
+
+fn scale_resolution((width, height): (u32, u32), scale: f64) -> (u32, u32) {
+    // Even dimensions keep chroma subsampling and model strides happy
+    let scale_dim = |dim: u32| (((dim as f64 * scale) as u32) & !1).clamp(2.min(dim), dim);
+    (scale_dim(width), scale_dim(height))
+}
+
+fn pixels((width, height): (u32, u32)) -> f64 {
+    (width as f64 * height as f64).max(1.0)
+}
+
+fn percentile(samples: &mut [f64], quantile: f64) -> Option<f64> {
+    if samples.is_empty() {
+        return None;
+    }
+    samples.sort_by(|a, b| a.total_cmp(b));
+    let index = ((samples.len() - 1) as f64 * quantile).round() as usize;
+    Some(samples[index])
+}
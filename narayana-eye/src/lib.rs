@@ -8,14 +8,19 @@
 
 pub mod vision_adapter;
 pub mod camera;
+pub mod calibration;
 pub mod config;
 pub mod models;
 pub mod processing;
 pub mod scene;
 pub mod error;
+pub mod governor;
+pub mod privacy;
 mod utils;
 
 pub use vision_adapter::VisionAdapter;
 pub use config::{VisionConfig, ProcessingMode};
 pub use error::VisionError;
+pub use governor::{GovernorConfig, ProcessingGovernor, QualityLevel, QualityReport};
+pub use privacy::{PrivacyManager, PrivacyZone, RedactionMode};
 
@@ -2,6 +2,7 @@
 
 use crate::error::VisionError;
 use crate::config::VisionConfig;
+use narayana_storage::model_registry::ModelArtifactRegistry;
 use std::path::{Path, PathBuf};
 use std::fs;
 use std::sync::Arc;
@@ -25,6 +26,7 @@ const CLIP_VIT_B_32_CHECKSUM: &str = ""; // Note: CLIP models are typically .pt
 pub struct ModelManager {
     config: Arc<VisionConfig>,
     models_loaded: Arc<RwLock<std::collections::HashMap<String, bool>>>,
+    artifacts: Option<Arc<ModelArtifactRegistry>>,
 }
 
 impl ModelManager {
@@ -33,9 +35,46 @@ impl ModelManager {
         Self {
             config,
             models_loaded: Arc::new(RwLock::new(std::collections::HashMap::new())),
+            artifacts: None,
         }
     }
 
+    /// Prefer models promoted in the artifact registry over the default downloads.
+    /// Registry names are the file stem, e.g. `yolov8n` for `yolov8n.onnx`.
+    pub fn with_artifact_registry(mut self, artifacts: Arc<ModelArtifactRegistry>) -> Self {
+        self.artifacts = Some(artifacts);
+        self
+    }
+
+    /// Write the serving (Production, else Staging) registry version of a model
+    /// into the model directory. Returns None when the registry has no such model.
+    fn materialize_from_registry(&self, model_name: &str) -> Result<Option<PathBuf>, VisionError> {
+        let Some(artifacts) = &self.artifacts else { return Ok(None) };
+        let path = Path::new(model_name);
+        let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or(model_name);
+        let Some(version) = artifacts.resolve_serving(stem) else { return Ok(None) };
+
+        let file_name = match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) => format!("{}-{}.{}", stem, version.version, ext),
+            None => format!("{}-{}", stem, version.version),
+        };
+        let model_path = self.config.model_path.join(file_name);
+        if model_path.exists() {
+            return Ok(Some(model_path));
+        }
+
+        // load_artifact verifies the registry checksum
+        let bytes = artifacts.load_artifact(stem, &version.version.to_string())?;
+        let temp_path = model_path.with_extension("tmp");
+        fs::write(&temp_path, &bytes)?;
+        fs::rename(&temp_path, &model_path).map_err(|e| {
+            let _ = fs::remove_file(&temp_path);
+            VisionError::Io(e)
+        })?;
+        info!("Model {} version {} ({:?}) loaded from registry", stem, version.version, version.stage);
+        Ok(Some(model_path))
+    }
+
     /// Ensure model directory exists
     pub fn ensure_model_dir(&self) -> Result<PathBuf, VisionError> {
         let model_path = &self.config.model_path;
@@ -81,12 +120,20 @@ impl ModelManager {
             return Err(VisionError::Model("Path traversal detected".to_string()));
         }
         
+        if let Some(registry_path) = self.materialize_from_registry(model_name)? {
+            return Ok(registry_path);
+        }
+
         // Check if model already exists
         if model_path.exists() {
             info!("Model {} already exists at {:?}", model_name, model_path);
             return Ok(model_path);
         }
 
+        // SECURITY: Model sources go through the shared egress policy
+        narayana_core::egress::check_url(narayana_core::egress::EgressSubsystem::Models, url)
+            .map_err(|e| VisionError::Model(format!("Model URL not allowed: {}", e)))?;
+
         info!("Downloading model {} from {}", model_name, url);
         
         // Download model with size limit and timeout
@@ -270,5 +317,23 @@ mod tests {
         manager.mark_loaded("test_model");
         assert!(manager.is_loaded("test_model"));
     }
-}
 
+    #[tokio::test]
+    async fn test_model_manager_prefers_registry_production_version() {
+        use narayana_storage::model_registry::{ModelStage, ModelType};
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = VisionConfig::default();
+        config.model_path = temp_dir.path().to_path_buf();
+
+        let artifacts = Arc::new(ModelArtifactRegistry::new(None).unwrap());
+        artifacts.register_version("yolov8n", "2.0.0", ModelType::Perception, "onnx", vec![7; 2048], Default::default()).unwrap();
+        artifacts.transition_stage("yolov8n", "2.0.0", ModelStage::Staging).unwrap();
+        artifacts.transition_stage("yolov8n", "2.0.0", ModelStage::Production).unwrap();
+
+        let manager = ModelManager::new(Arc::new(config)).with_artifact_registry(artifacts);
+        let path = manager.get_yolo_model().await.unwrap();
+        assert!(path.ends_with("yolov8n-2.0.0.onnx"));
+        assert_eq!(fs::read(path).unwrap(), vec![7; 2048]);
+    }
+}
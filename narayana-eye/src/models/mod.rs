@@ -4,9 +4,11 @@ pub mod manager;
 pub mod yolo;
 pub mod sam;
 pub mod clip;
+pub mod scheduler;
 
 pub use manager::ModelManager;
 pub use yolo::{YoloModel, DetectedObject};
 pub use sam::SamModel;
 pub use clip::ClipModel;
+pub use scheduler::{InferenceScheduler, SchedulerConfig, ModelProfile, PipelineStats, TensorData};
 
@@ -0,0 +1,502 @@
+//! Shared inference scheduler
+//!
+//! With several cameras and pipelines running, every pipeline calling its own
+//! model session wastes the accelerator on batch-of-one runs and lets models
+//! outgrow GPU memory. The scheduler owns one queue per model: requests from
+//! all pipelines are batched together (most urgent first), each model's
+//! weights and each batch's activations are accounted against a memory
+//! budget, and every request carries the latency SLO of its pipeline so stale
+//! frames are dropped instead of delaying fresh ones.
+
+use crate::error::VisionError;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, oneshot, OwnedSemaphorePermit, Semaphore};
+use tracing::{debug, info, warn};
+
+/// A single float tensor, without a batch dimension
+#[derive(Debug, Clone, PartialEq)]
+pub struct TensorData {
+    pub shape: Vec<usize>,
+    pub data: Vec<f32>,
+}
+
+impl TensorData {
+    pub fn new(shape: Vec<usize>, data: Vec<f32>) -> Result<Self, VisionError> {
+        let elements = shape
+            .iter()
+            .try_fold(1usize, |acc, &dim| acc.checked_mul(dim))
+            .ok_or_else(|| VisionError::Processing("Tensor shape would overflow".to_string()))?;
+        if elements != data.len() {
+            return Err(VisionError::Processing(format!(
+                "Tensor shape {:?} does not match {} elements",
+                shape,
+                data.len()
+            )));
+        }
+        Ok(Self { shape, data })
+    }
+
+    /// Stack same-shaped tensors along a new leading batch dimension
+    pub fn stack(items: &[TensorData]) -> Result<TensorData, VisionError> {
+        let first = items
+            .first()
+            .ok_or_else(|| VisionError::Processing("Cannot stack an empty batch".to_string()))?;
+        if items.iter().any(|item| item.shape != first.shape) {
+            return Err(VisionError::Processing("Cannot stack tensors of different shapes".to_string()));
+        }
+        let mut shape = Vec::with_capacity(first.shape.len() + 1);
+        shape.push(items.len());
+        shape.extend_from_slice(&first.shape);
+        let data = items.iter().flat_map(|item| item.data.iter().copied()).collect();
+        TensorData::new(shape, data)
+    }
+
+    /// Split a batched tensor along its leading dimension
+    pub fn unstack(self) -> Result<Vec<TensorData>, VisionError> {
+        let (&batch, item_shape) = self
+            .shape
+            .split_first()
+            .ok_or_else(|| VisionError::Processing("Cannot unstack a scalar tensor".to_string()))?;
+        if batch == 0 {
+            return Ok(Vec::new());
+        }
+        let item_len = self.data.len() / batch;
+        Ok((0..batch)
+            .map(|i| TensorData {
+                shape: item_shape.to_vec(),
+                data: self.data[i * item_len..(i + 1) * item_len].to_vec(),
+            })
+            .collect())
+    }
+}
+
+/// A model that can run several inputs in one call
+///
+/// Called from a blocking thread; outputs are returned in input order.
+pub trait BatchModel: Send + Sync {
+    fn run_batch(&self, inputs: &[TensorData]) -> Result<Vec<TensorData>, VisionError>;
+}
+
+/// Resource profile of a model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelProfile {
+    /// Memory held by the loaded weights, in MB
+    pub weights_mb: u32,
+    /// Activation memory per batched input, in MB
+    pub per_input_mb: u32,
+    /// Largest batch the model accepts
+    pub max_batch: usize,
+}
+
+/// Scheduler configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SchedulerConfig {
+    /// Upper bound on any batch
+    pub max_batch_size: usize,
+    /// Longest a request waits for others to batch with
+    pub max_batch_wait_ms: u64,
+    /// Accelerator memory shared by all models, in MB
+    pub memory_budget_mb: u32,
+    /// SLO for pipelines that were not registered
+    pub default_slo_ms: u64,
+    /// Requests queued per model before new ones are rejected
+    pub queue_capacity: usize,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            max_batch_size: 8,
+            max_batch_wait_ms: 5,
+            memory_budget_mb: 4096,
+            default_slo_ms: 200,
+            queue_capacity: 64,
+        }
+    }
+}
+
+impl SchedulerConfig {
+    /// Validate configuration
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_batch_size == 0 || self.max_batch_size > 256 {
+            return Err("Scheduler max_batch_size must be between 1 and 256".to_string());
+        }
+        if self.max_batch_wait_ms > 1000 {
+            return Err("Scheduler max_batch_wait_ms must be at most 1000".to_string());
+        }
+        if self.memory_budget_mb == 0 {
+            return Err("Scheduler memory_budget_mb must be greater than 0".to_string());
+        }
+        if self.default_slo_ms == 0 {
+            return Err("Scheduler default_slo_ms must be greater than 0".to_string());
+        }
+        if self.queue_capacity == 0 {
+            return Err("Scheduler queue_capacity must be greater than 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Per-pipeline request counters
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PipelineStats {
+    pub requests: u64,
+    pub completed: u64,
+    pub failed: u64,
+    /// Dropped because the queue was full
+    pub rejected: u64,
+    /// Expired before running, or completed after the deadline
+    pub deadline_misses: u64,
+    /// Mean time from request to result for completed requests
+    pub mean_latency_ms: f64,
+    pub max_latency_ms: f64,
+    /// Mean size of the batches this pipeline's requests ran in
+    pub mean_batch_size: f64,
+}
+
+impl PipelineStats {
+    fn record_completion(&mut self, latency: Duration, batch_size: usize, late: bool) {
+        let latency_ms = latency.as_secs_f64() * 1000.0;
+        self.completed += 1;
+        let n = self.completed as f64;
+        self.mean_latency_ms += (latency_ms - self.mean_latency_ms) / n;
+        self.mean_batch_size += (batch_size as f64 - self.mean_batch_size) / n;
+        self.max_latency_ms = self.max_latency_ms.max(latency_ms);
+        if late {
+            self.deadline_misses += 1;
+        }
+    }
+}
+
+type StatsMap = Arc<Mutex<HashMap<String, PipelineStats>>>;
+
+struct Request {
+    ticket: Ticket,
+    input: TensorData,
+}
+
+/// Who is waiting for a result, and until when
+struct Ticket {
+    pipeline: String,
+    enqueued: Instant,
+    deadline: Instant,
+    reply: oneshot::Sender<Result<TensorData, VisionError>>,
+}
+
+/// Requests to run together and the activation memory reserved for them
+struct Batch {
+    requests: Vec<Request>,
+    memory: Option<OwnedSemaphorePermit>,
+}
+
+struct ModelEntry {
+    profile: ModelProfile,
+    sender: mpsc::Sender<Request>,
+}
+
+/// Batches inference requests across pipelines, per model
+pub struct InferenceScheduler {
+    config: SchedulerConfig,
+    models: RwLock<HashMap<String, ModelEntry>>,
+    slos: RwLock<HashMap<String, Duration>>,
+    stats: StatsMap,
+    /// Free memory, one permit per MB
+    memory: Arc<Semaphore>,
+}
+
+impl InferenceScheduler {
+    pub fn new(config: SchedulerConfig) -> Result<Self, VisionError> {
+        config.validate().map_err(VisionError::Config)?;
+        let memory = Arc::new(Semaphore::new(config.memory_budget_mb as usize));
+        Ok(Self {
+            config,
+            models: RwLock::new(HashMap::new()),
+            slos: RwLock::new(HashMap::new()),
+            stats: Arc::new(Mutex::new(HashMap::new())),
+            memory,
+        })
+    }
+
+    pub fn config(&self) -> &SchedulerConfig {
+        &self.config
+    }
+
+    /// Add a model and start its batching worker (must run inside a Tokio runtime)
+    ///
+    /// The model's weights are reserved against the memory budget for as long
+    /// as it stays registered. Returns false if a model of that name is already
+    /// registered; pipelines then share the existing one.
+    pub fn register_model(&self, name: &str, model: Arc<dyn BatchModel>, profile: ModelProfile) -> Result<bool, VisionError> {
+        if profile.max_batch == 0 {
+            return Err(VisionError::Config(format!("Model {} max_batch must be greater than 0", name)));
+        }
+        let mut models = self.models.write();
+        if models.contains_key(name) {
+            return Ok(false);
+        }
+        // A single input must fit next to the weights, or the model could never run
+        let needed = profile.weights_mb.saturating_add(profile.per_input_mb);
+        if needed as usize > self.memory.available_permits() {
+            return Err(VisionError::Model(format!(
+                "Not enough accelerator memory for {}: needs {} MB, {} MB free",
+                name,
+                needed,
+                self.memory.available_permits()
+            )));
+        }
+        if profile.weights_mb > 0 {
+            self.memory
+                .try_acquire_many(profile.weights_mb)
+                .map_err(|_| VisionError::Model(format!("Not enough accelerator memory for {}", name)))?
+                .forget();
+        }
+
+        let (sender, receiver) = mpsc::channel(self.config.queue_capacity);
+        let worker = ModelWorker {
+            name: name.to_string(),
+            model,
+            max_batch: profile.max_batch.min(self.config.max_batch_size),
+            per_input_mb: profile.per_input_mb,
+            max_wait: Duration::from_millis(self.config.max_batch_wait_ms),
+            memory: self.memory.clone(),
+            stats: self.stats.clone(),
+            batch_latency: None,
+        };
+        tokio::spawn(worker.run(receiver));
+        info!("Registered model {} with the inference scheduler ({} MB)", name, profile.weights_mb);
+        models.insert(name.to_string(), ModelEntry { profile, sender });
+        Ok(true)
+    }
+
+    /// Remove a model, freeing its memory; queued requests still complete
+    pub fn unregister_model(&self, name: &str) -> bool {
+        match self.models.write().remove(name) {
+            Some(entry) => {
+                self.memory.add_permits(entry.profile.weights_mb as usize);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn has_model(&self, name: &str) -> bool {
+        self.models.read().contains_key(name)
+    }
+
+    /// Set the latency SLO of a pipeline
+    pub fn register_pipeline(&self, pipeline: &str, slo: Duration) {
+        self.slos.write().insert(pipeline.to_string(), slo);
+    }
+
+    /// Accelerator memory not reserved by models or running batches, in MB
+    pub fn free_memory_mb(&self) -> usize {
+        self.memory.available_permits()
+    }
+
+    /// Counters for a pipeline
+    pub fn stats(&self, pipeline: &str) -> PipelineStats {
+        self.stats.lock().get(pipeline).cloned().unwrap_or_default()
+    }
+
+    /// Run `input` through `model` as part of the next batch
+    ///
+    /// Fails without running if the pipeline's SLO runs out while the
+    /// request is queued.
+    pub async fn infer(&self, pipeline: &str, model: &str, input: TensorData) -> Result<TensorData, VisionError> {
+        let slo = self
+            .slos
+            .read()
+            .get(pipeline)
+            .copied()
+            .unwrap_or(Duration::from_millis(self.config.default_slo_ms));
+        let sender = self
+            .models
+            .read()
+            .get(model)
+            .map(|entry| entry.sender.clone())
+            .ok_or_else(|| VisionError::Model(format!("Model {} is not registered with the scheduler", model)))?;
+
+        self.stats.lock().entry(pipeline.to_string()).or_default().requests += 1;
+        let (reply, result) = oneshot::channel();
+        let now = Instant::now();
+        let request = Request {
+            ticket: Ticket {
+                pipeline: pipeline.to_string(),
+                enqueued: now,
+                deadline: now + slo,
+                reply,
+            },
+            input,
+        };
+        if sender.try_send(request).is_err() {
+            self.stats.lock().entry(pipeline.to_string()).or_default().rejected += 1;
+            return Err(VisionError::Processing(format!("Inference queue for {} is full", model)));
+        }
+        result
+            .await
+            .map_err(|_| VisionError::Processing(format!("Inference worker for {} stopped", model)))?
+    }
+}
+
+struct ModelWorker {
+    name: String,
+    model: Arc<dyn BatchModel>,
+    max_batch: usize,
+    per_input_mb: u32,
+    max_wait: Duration,
+    memory: Arc<Semaphore>,
+    stats: StatsMap,
+    /// Smoothed time a batch takes to run
+    batch_latency: Option<Duration>,
+}
+
+impl ModelWorker {
+    async fn run(mut self, mut receiver: mpsc::Receiver<Request>) {
+        let mut pending: Vec<Request> = Vec::new();
+        loop {
+            if pending.is_empty() {
+                match receiver.recv().await {
+                    Some(request) => pending.push(request),
+                    None => break,
+                }
+            }
+
+            // Wait for more requests, but not past the point where the most
+            // urgent one could no longer finish in time
+            let earliest = pending.iter().map(|r| r.ticket.deadline).min().unwrap_or_else(Instant::now);
+            let oldest = pending.iter().map(|r| r.ticket.enqueued).min().unwrap_or_else(Instant::now);
+            let run_by = earliest.checked_sub(self.batch_latency.unwrap_or_default()).unwrap_or(earliest);
+            let close_at = (oldest + self.max_wait).min(run_by);
+            while pending.len() < self.max_batch {
+                match tokio::time::timeout_at(close_at.into(), receiver.recv()).await {
+                    Ok(Some(request)) => pending.push(request),
+                    _ => break,
+                }
+            }
+
+            self.expire(&mut pending);
+            if pending.is_empty() {
+                continue;
+            }
+            let batch = self.take_batch(&mut pending).await;
+            self.run_batch(batch).await;
+        }
+        // Channel closed: finish what is left
+        while !pending.is_empty() {
+            self.expire(&mut pending);
+            if pending.is_empty() {
+                break;
+            }
+            let batch = self.take_batch(&mut pending).await;
+            self.run_batch(batch).await;
+        }
+        debug!("Inference worker for {} stopped", self.name);
+    }
+
+    /// Fail requests whose deadline has already passed
+    fn expire(&self, pending: &mut Vec<Request>) {
+        let now = Instant::now();
+        let (expired, live): (Vec<_>, Vec<_>) = pending.drain(..).partition(|r| r.ticket.deadline <= now);
+        *pending = live;
+        if expired.is_empty() {
+            return;
+        }
+        let mut stats = self.stats.lock();
+        for Request { ticket, .. } in expired {
+            let entry = stats.entry(ticket.pipeline.clone()).or_default();
+            entry.deadline_misses += 1;
+            entry.failed += 1;
+            let _ = ticket.reply.send(Err(VisionError::Processing(format!(
+                "Inference deadline exceeded for {}",
+                ticket.pipeline
+            ))));
+        }
+    }
+
+    /// The most urgent requests sharing one input shape, as many as fit in memory
+    async fn take_batch(&self, pending: &mut Vec<Request>) -> Batch {
+        pending.sort_by_key(|r| r.ticket.deadline);
+        let shape = pending[0].input.shape.clone();
+        let mut limit = self.max_batch;
+        if self.per_input_mb > 0 {
+            let affordable = self.memory.available_permits() / self.per_input_mb as usize;
+            limit = limit.min(affordable.max(1));
+        }
+
+        let mut batch = Vec::new();
+        let mut rest = Vec::new();
+        for request in pending.drain(..) {
+            if batch.len() < limit && request.input.shape == shape {
+                batch.push(request);
+            } else {
+                rest.push(request);
+            }
+        }
+        *pending = rest;
+
+        let memory = if self.per_input_mb > 0 {
+            let needed = self.per_input_mb.saturating_mul(batch.len() as u32);
+            self.memory.clone().acquire_many_owned(needed).await.ok()
+        } else {
+            None
+        };
+        Batch { requests: batch, memory }
+    }
+
+    async fn run_batch(&mut self, batch: Batch) {
+        let Batch { requests, memory } = batch;
+        let size = requests.len();
+        let (tickets, inputs): (Vec<_>, Vec<_>) = requests.into_iter().map(|r| (r.ticket, r.input)).unzip();
+
+        let model = self.model.clone();
+        let started = Instant::now();
+        let result = tokio::task::spawn_blocking(move || model.run_batch(&inputs))
+            .await
+            .unwrap_or_else(|e| Err(VisionError::Processing(format!("Inference task failed: {}", e))));
+        drop(memory);
+        let elapsed = started.elapsed();
+        self.batch_latency = Some(match self.batch_latency {
+            Some(previous) => previous.mul_f64(0.8) + elapsed.mul_f64(0.2),
+            None => elapsed,
+        });
+        debug!("{} ran a batch of {} in {:?}", self.name, size, elapsed);
+
+        let outputs = match result {
+            Ok(outputs) if outputs.len() == size => outputs,
+            Ok(outputs) => {
+                let message = format!("{} returned {} outputs for {} inputs", self.name, outputs.len(), size);
+                warn!("{}", message);
+                self.fail_all(tickets, &message);
+                return;
+            }
+            Err(e) => {
+                warn!("{} batch inference failed: {}", self.name, e);
+                self.fail_all(tickets, &e.to_string());
+                return;
+            }
+        };
+
+        let now = Instant::now();
+        let mut stats = self.stats.lock();
+        for (ticket, output) in tickets.into_iter().zip(outputs) {
+            stats
+                .entry(ticket.pipeline)
+                .or_default()
+                .record_completion(now - ticket.enqueued, size, now > ticket.deadline);
+            let _ = ticket.reply.send(Ok(output));
+        }
+    }
+
+    fn fail_all(&self, tickets: Vec<Ticket>, message: &str) {
+        let mut stats = self.stats.lock();
+        for ticket in tickets {
+            stats.entry(ticket.pipeline).or_default().failed += 1;
+            let _ = ticket.reply.send(Err(VisionError::Model(message.to_string())));
+        }
+    }
+}
@@ -1,11 +1,13 @@
 //! YOLO object detection model
 
 use crate::error::VisionError;
+use crate::models::scheduler::{BatchModel, ModelProfile, TensorData};
 use crate::utils::mat_to_chw_tensor;
 use ort::{Session, Value, Environment};
 use opencv::prelude::Mat;
 use opencv::imgproc;
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tracing::{info, warn, debug};
 
@@ -36,9 +38,23 @@ pub struct DetectedObject {
 pub struct YoloModel {
     session: Arc<Session>,
     input_size: (u32, u32),
+    /// Cleared once the model turns out to have a fixed batch size of one
+    batching: AtomicBool,
 }
 
 impl YoloModel {
+    /// Name the model is registered under with the inference scheduler
+    pub const NAME: &'static str = "yolo";
+
+    /// Resource profile for the inference scheduler
+    pub fn profile() -> ModelProfile {
+        ModelProfile {
+            weights_mb: 128,
+            per_input_mb: 64,
+            max_batch: 16,
+        }
+    }
+
     /// Create a new YOLO model
     pub fn new(model_path: &Path) -> Result<Self, VisionError> {
         let environment = Environment::builder()
@@ -56,6 +72,7 @@ impl YoloModel {
         Ok(Self {
             session: Arc::new(session),
             input_size: (640, 640), // YOLO standard input size
+            batching: AtomicBool::new(true),
         })
     }
 
@@ -76,10 +93,27 @@ impl YoloModel {
         Ok(detections)
     }
 
-    /// Preprocess frame for YOLO input
-    fn preprocess(&self, frame: &Mat) -> Result<Value, VisionError> {
-        let (width, height) = (frame.cols(), frame.rows());
-        
+    /// Preprocess a frame into a single [3, H, W] input for batched inference
+    pub fn prepare(&self, frame: &Mat) -> Result<TensorData, VisionError> {
+        let data = self.preprocess_chw(frame)?;
+        TensorData::new(vec![3, self.input_size.1 as usize, self.input_size.0 as usize], data)
+    }
+
+    /// Detections from one output of a batched run on `frame`
+    pub fn detections_from(&self, output: TensorData, frame: &Mat) -> Result<Vec<DetectedObject>, VisionError> {
+        // Postprocessing expects the batch dimension back
+        let mut shape = Vec::with_capacity(output.shape.len() + 1);
+        shape.push(1);
+        shape.extend_from_slice(&output.shape);
+        let value = Value::from_array(
+            ort::ndarray::Array::from_shape_vec(ort::ndarray::IxDyn(&shape), output.data)
+                .map_err(|e| VisionError::Ort(format!("Failed to create output array: {}", e)))?
+        ).map_err(|e| VisionError::Ort(format!("Failed to create output value: {}", e)))?;
+        self.postprocess(&[value], frame)
+    }
+
+    /// Resize, convert and normalize a frame to a [3, H, W] tensor
+    fn preprocess_chw(&self, frame: &Mat) -> Result<Vec<f32>, VisionError> {
         // Resize frame to model input size
         let mut resized = Mat::default();
         imgproc::resize(
@@ -101,9 +135,14 @@ impl YoloModel {
         rgb.convert_to(&mut float_mat, opencv::core::CV_32F, 1.0 / 255.0, 0.0)
             .map_err(|e| VisionError::OpenCv(format!("Failed to convert to float: {}", e)))?;
 
-        // Extract pixel data and reshape to [1, 3, H, W]
+        // Extract pixel data as [3, H, W]
+        mat_to_chw_tensor(&float_mat, self.input_size.0, self.input_size.1)
+    }
+
+    /// Preprocess frame for YOLO input
+    fn preprocess(&self, frame: &Mat) -> Result<Value, VisionError> {
         let input_shape = vec![1, 3, self.input_size.1 as i64, self.input_size.0 as i64];
-        let mut input_data = mat_to_chw_tensor(&float_mat, self.input_size.0, self.input_size.1)?;
+        let input_data = self.preprocess_chw(frame)?;
         
         // Add batch dimension: [3, H, W] -> [1, 3, H, W]
         // Prevent integer overflow
@@ -347,3 +386,44 @@ impl YoloModel {
         }
     }
 }
+
+impl BatchModel for YoloModel {
+    fn run_batch(&self, inputs: &[TensorData]) -> Result<Vec<TensorData>, VisionError> {
+        if inputs.len() > 1 && self.batching.load(Ordering::Relaxed) {
+            match self.run_stacked(inputs) {
+                Ok(outputs) => return Ok(outputs),
+                Err(e) => {
+                    // Exports without a dynamic batch axis only take one input
+                    warn!("YOLO batch inference failed, running inputs one at a time: {}", e);
+                    self.batching.store(false, Ordering::Relaxed);
+                }
+            }
+        }
+        let mut outputs = Vec::with_capacity(inputs.len());
+        for input in inputs {
+            outputs.extend(self.run_stacked(std::slice::from_ref(input))?);
+        }
+        Ok(outputs)
+    }
+}
+
+impl YoloModel {
+    /// Run `inputs` as one [N, 3, H, W] tensor
+    fn run_stacked(&self, inputs: &[TensorData]) -> Result<Vec<TensorData>, VisionError> {
+        let batch = TensorData::stack(inputs)?;
+        let input = Value::from_array(
+            ort::ndarray::Array::from_shape_vec(ort::ndarray::IxDyn(&batch.shape), batch.data)
+                .map_err(|e| VisionError::Ort(format!("Failed to create input array: {}", e)))?
+        ).map_err(|e| VisionError::Ort(format!("Failed to create input value: {}", e)))?;
+
+        let outputs = self.session.run(vec![input])
+            .map_err(|e| VisionError::Ort(format!("YOLO batch inference failed: {}", e)))?;
+        let output = outputs.first()
+            .ok_or_else(|| VisionError::Ort("YOLO returned no outputs".to_string()))?;
+        let output_array = output.try_extract_tensor::<f32>()
+            .map_err(|e| VisionError::Ort(format!("Failed to extract output tensor: {}", e)))?;
+
+        let output = TensorData::new(output_array.shape().to_vec(), output_array.iter().copied().collect())?;
+        output.unstack()
+    }
+}
@@ -0,0 +1,258 @@
+//! Privacy zones
+//!
+//! Each camera can have polygons (in normalized image coordinates) where
+//! nothing may be seen: the pixels are blurred or blacked out before the frame
+//! reaches any model or leaves the processing module, and detections centered
+//! in a zone are dropped. Every change to the zones is written to an audit log.
+
+use crate::error::VisionError;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+/// Maximum vertices per zone polygon
+const MAX_VERTICES: usize = 64;
+/// Maximum zones per camera
+const MAX_ZONES: usize = 32;
+/// Audit entries kept in memory
+const MAX_AUDIT_ENTRIES: usize = 10_000;
+
+/// How a zone's pixels are hidden
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RedactionMode {
+    /// Coarse pixelation; the scene layout stays recognizable
+    Blur,
+    /// Solid black
+    Blackout,
+}
+
+/// A region of the image that must not be seen
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PrivacyZone {
+    pub id: String,
+    /// Vertices in normalized image coordinates (0.0-1.0)
+    pub polygon: Vec<(f32, f32)>,
+    pub mode: RedactionMode,
+}
+
+impl PrivacyZone {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.id.is_empty() || self.id.len() > 64 {
+            return Err("Privacy zone id must be 1-64 characters".to_string());
+        }
+        if !self.id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-')) {
+            return Err(format!("Invalid privacy zone id: {}", self.id));
+        }
+        if self.polygon.len() < 3 || self.polygon.len() > MAX_VERTICES {
+            return Err(format!("Privacy zone polygon must have 3-{} vertices", MAX_VERTICES));
+        }
+        let in_range = |v: f32| v.is_finite() && (0.0..=1.0).contains(&v);
+        if !self.polygon.iter().all(|&(x, y)| in_range(x) && in_range(y)) {
+            return Err("Privacy zone vertices must be normalized to 0.0-1.0".to_string());
+        }
+        Ok(())
+    }
+
+    /// Whether a normalized point lies inside the polygon
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        // Even-odd ray casting
+        let mut inside = false;
+        let mut j = self.polygon.len() - 1;
+        for i in 0..self.polygon.len() {
+            let (xi, yi) = self.polygon[i];
+            let (xj, yj) = self.polygon[j];
+            if (yi > y) != (yj > y) && x < (xj - xi) * (y - yi) / (yj - yi) + xi {
+                inside = !inside;
+            }
+            j = i;
+        }
+        inside
+    }
+}
+
+/// Kind of change recorded in the audit log
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PrivacyChange {
+    ZoneAdded,
+    ZoneUpdated,
+    ZoneRemoved,
+    ZonesCleared,
+}
+
+/// One audited change to a camera's privacy zones
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrivacyAuditEntry {
+    pub timestamp: u64,
+    pub camera_id: String,
+    /// Who made the change
+    pub actor: String,
+    pub change: PrivacyChange,
+    pub zone_id: Option<String>,
+    /// The zone as configured after the change
+    pub zone: Option<PrivacyZone>,
+    /// Zones on the camera after the change
+    pub zone_count: usize,
+}
+
+/// Privacy zones of all cameras, with an audit log of changes
+pub struct PrivacyManager {
+    zones: RwLock<HashMap<String, Vec<PrivacyZone>>>,
+    audit: Mutex<VecDeque<PrivacyAuditEntry>>,
+    /// Append-only JSON lines copy of the audit log
+    audit_file: Option<PathBuf>,
+}
+
+impl PrivacyManager {
+    pub fn new() -> Self {
+        Self {
+            zones: RwLock::new(HashMap::new()),
+            audit: Mutex::new(VecDeque::new()),
+            audit_file: None,
+        }
+    }
+
+    /// Also append audit entries to `path`, one JSON object per line
+    pub fn with_audit_file(mut self, path: PathBuf) -> Result<Self, VisionError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        // Fail now rather than on the first change
+        std::fs::OpenOptions::new().create(true).append(true).open(&path)?;
+        self.audit_file = Some(path);
+        Ok(self)
+    }
+
+    /// Add a zone, or replace the camera's zone with the same id
+    pub fn set_zone(&self, camera_id: &str, zone: PrivacyZone, actor: &str) -> Result<(), VisionError> {
+        zone.validate().map_err(VisionError::Config)?;
+        let (change, zone_count) = {
+            let mut zones = self.zones.write();
+            let camera_zones = zones.entry(camera_id.to_string()).or_default();
+            let change = match camera_zones.iter_mut().find(|z| z.id == zone.id) {
+                Some(existing) => {
+                    *existing = zone.clone();
+                    PrivacyChange::ZoneUpdated
+                }
+                None => {
+                    if camera_zones.len() >= MAX_ZONES {
+                        return Err(VisionError::Config(format!(
+                            "Camera {} already has {} privacy zones",
+                            camera_id, MAX_ZONES
+                        )));
+                    }
+                    camera_zones.push(zone.clone());
+                    PrivacyChange::ZoneAdded
+                }
+            };
+            (change, camera_zones.len())
+        };
+        self.record(camera_id, actor, change, Some(zone.id.clone()), Some(zone), zone_count);
+        Ok(())
+    }
+
+    /// Remove a zone; returns false if the camera had no zone with that id
+    pub fn remove_zone(&self, camera_id: &str, zone_id: &str, actor: &str) -> bool {
+        let zone_count = {
+            let mut zones = self.zones.write();
+            let Some(camera_zones) = zones.get_mut(camera_id) else {
+                return false;
+            };
+            let before = camera_zones.len();
+            camera_zones.retain(|z| z.id != zone_id);
+            if camera_zones.len() == before {
+                return false;
+            }
+            camera_zones.len()
+        };
+        self.record(camera_id, actor, PrivacyChange::ZoneRemoved, Some(zone_id.to_string()), None, zone_count);
+        true
+    }
+
+    /// Remove all of a camera's zones
+    pub fn clear(&self, camera_id: &str, actor: &str) {
+        if self.zones.write().remove(camera_id).is_some() {
+            self.record(camera_id, actor, PrivacyChange::ZonesCleared, None, None, 0);
+        }
+    }
+
+    /// Zones currently configured for a camera
+    pub fn zones(&self, camera_id: &str) -> Vec<PrivacyZone> {
+        self.zones.read().get(camera_id).cloned().unwrap_or_default()
+    }
+
+    pub fn has_zones(&self, camera_id: &str) -> bool {
+        self.zones.read().get(camera_id).is_some_and(|zones| !zones.is_empty())
+    }
+
+    /// Whether a normalized point of a camera's image is inside any zone
+    pub fn is_private(&self, camera_id: &str, x: f32, y: f32) -> bool {
+        self.zones
+            .read()
+            .get(camera_id)
+            .is_some_and(|zones| zones.iter().any(|zone| zone.contains(x, y)))
+    }
+
+    /// Audited changes, oldest first, optionally for one camera
+    pub fn audit_log(&self, camera_id: Option<&str>) -> Vec<PrivacyAuditEntry> {
+        self.audit
+            .lock()
+            .iter()
+            .filter(|entry| camera_id.is_none_or(|id| entry.camera_id == id))
+            .cloned()
+            .collect()
+    }
+
+    fn record(
+        &self,
+        camera_id: &str,
+        actor: &str,
+        change: PrivacyChange,
+        zone_id: Option<String>,
+        zone: Option<PrivacyZone>,
+        zone_count: usize,
+    ) {
+        let entry = PrivacyAuditEntry {
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+            camera_id: camera_id.to_string(),
+            actor: actor.chars().take(128).collect(),
+            change,
+            zone_id,
+            zone,
+            zone_count,
+        };
+        info!(
+            "PRIVACY_CONFIG: camera={}, actor={}, change={:?}, zone={:?}, zones={}",
+            entry.camera_id, entry.actor, entry.change, entry.zone_id, entry.zone_count
+        );
+
+        if let Some(path) = &self.audit_file {
+            let written = serde_json::to_string(&entry)
+                .map_err(std::io::Error::other)
+                .and_then(|line| {
+                    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+                    writeln!(file, "{}", line)
+                });
+            if let Err(e) = written {
+                warn!("Failed to write privacy audit log {:?}: {}", path, e);
+            }
+        }
+
+        let mut audit = self.audit.lock();
+        audit.push_back(entry);
+        if audit.len() > MAX_AUDIT_ENTRIES {
+            audit.pop_front();
+        }
+    }
+}
+
+impl Default for PrivacyManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
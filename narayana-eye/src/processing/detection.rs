@@ -1,7 +1,7 @@
 //! Object detection pipeline
 
 use crate::error::VisionError;
-use crate::models::{YoloModel, DetectedObject};
+use crate::models::{YoloModel, DetectedObject, InferenceScheduler};
 use opencv::prelude::Mat;
 use std::sync::Arc;
 use tracing::debug;
@@ -9,12 +9,22 @@ use tracing::debug;
 /// Object detection pipeline
 pub struct DetectionPipeline {
     yolo: Arc<YoloModel>,
+    /// Shared scheduler and this pipeline's name with it
+    scheduler: Option<(Arc<InferenceScheduler>, String)>,
 }
 
 impl DetectionPipeline {
     /// Create a new detection pipeline
     pub fn new(yolo: Arc<YoloModel>) -> Self {
-        Self { yolo }
+        Self { yolo, scheduler: None }
+    }
+
+    /// Run inference through a shared scheduler, batched with other pipelines
+    ///
+    /// The YOLO model must be registered with the scheduler as `YoloModel::NAME`.
+    pub fn with_scheduler(mut self, scheduler: Arc<InferenceScheduler>, pipeline: &str) -> Self {
+        self.scheduler = Some((scheduler, pipeline.to_string()));
+        self
     }
 
     /// Process frame and detect objects
@@ -24,6 +34,17 @@ impl DetectionPipeline {
         debug!("Detected {} objects", detections.len());
         Ok(detections)
     }
-}
-
 
+    /// Detect objects, through the scheduler if there is one
+    pub async fn detect_scheduled(&self, frame: &Mat) -> Result<Vec<DetectedObject>, VisionError> {
+        let Some((scheduler, pipeline)) = &self.scheduler else {
+            return self.detect(frame);
+        };
+        debug!("Running scheduled object detection on frame");
+        let input = self.yolo.prepare(frame)?;
+        let output = scheduler.infer(pipeline, YoloModel::NAME, input).await?;
+        let detections = self.yolo.detections_from(output, frame)?;
+        debug!("Detected {} objects", detections.len());
+        Ok(detections)
+    }
+}
@@ -1,10 +1,12 @@
 //! Vision processing pipelines
 
 pub mod detection;
+pub mod redaction;
 pub mod segmentation;
 pub mod tracker;
 
 pub use detection::DetectionPipeline;
+pub use redaction::redact_frame;
 pub use segmentation::SegmentationPipeline;
 pub use tracker::ObjectTracker;
 
@@ -0,0 +1,68 @@
+//! Privacy zone redaction
+
+use crate::error::VisionError;
+use crate::privacy::{PrivacyZone, RedactionMode};
+use opencv::{
+    core::{self, Mat, Point, Scalar, Size, Vector},
+    imgproc,
+    prelude::*,
+};
+
+/// Pixelation blocks across the frame width
+const BLUR_BLOCKS: i32 = 24;
+
+/// Hide the pixels of `zones` in a frame.
+/// Returns `None` when there is nothing to hide, so callers can keep the frame.
+pub fn redact_frame(frame: &Mat, zones: &[PrivacyZone]) -> Result<Option<Mat>, VisionError> {
+    if zones.is_empty() {
+        return Ok(None);
+    }
+    let (width, height) = (frame.cols(), frame.rows());
+    if width <= 0 || height <= 0 {
+        return Err(VisionError::Processing("Cannot redact an empty frame".to_string()));
+    }
+
+    let blur_mask = zone_mask(zones, RedactionMode::Blur, width, height)?;
+    let blackout_mask = zone_mask(zones, RedactionMode::Blackout, width, height)?;
+    let mut redacted = frame.try_clone()?;
+
+    if let Some(mask) = blur_mask {
+        // Pixelate rather than Gaussian blur: cheap at any zone size and not invertible
+        let mut small = Mat::default();
+        imgproc::resize(
+            frame,
+            &mut small,
+            Size::new(BLUR_BLOCKS, (BLUR_BLOCKS * height / width).max(1)),
+            0.0,
+            0.0,
+            imgproc::INTER_AREA,
+        )?;
+        let mut pixelated = Mat::default();
+        imgproc::resize(&small, &mut pixelated, Size::new(width, height), 0.0, 0.0, imgproc::INTER_NEAREST)?;
+        pixelated.copy_to_masked(&mut redacted, &mask)?;
+    }
+    if let Some(mask) = blackout_mask {
+        redacted.set_to(&Scalar::all(0.0), &mask)?;
+    }
+    Ok(Some(redacted))
+}
+
+/// Mask covering the zones with the given mode, if there are any
+fn zone_mask(zones: &[PrivacyZone], mode: RedactionMode, width: i32, height: i32) -> Result<Option<Mat>, VisionError> {
+    let polygons: Vector<Vector<Point>> = zones
+        .iter()
+        .filter(|zone| zone.mode == mode)
+        .map(|zone| {
+            zone.polygon
+                .iter()
+                .map(|&(x, y)| Point::new((x * width as f32).round() as i32, (y * height as f32).round() as i32))
+                .collect()
+        })
+        .collect();
+    if polygons.is_empty() {
+        return Ok(None);
+    }
+    let mut mask = Mat::new_rows_cols_with_default(height, width, core::CV_8UC1, Scalar::all(0.0))?;
+    imgproc::fill_poly(&mut mask, &polygons, Scalar::all(255.0), imgproc::LINE_8, 0, Point::new(0, 0))?;
+    Ok(Some(mask))
+}
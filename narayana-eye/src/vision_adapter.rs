@@ -1,21 +1,27 @@
 //! Vision adapter for narayana-wld integration
 
+use crate::calibration::{estimate_extrinsics, CheckerboardCalibrator, CheckerboardConfig, Undistorter};
 use crate::camera::CameraManager;
 use crate::config::{VisionConfig, ProcessingMode};
 use crate::error::VisionError;
-use crate::models::{ModelManager, YoloModel, SamModel, ClipModel};
-use crate::processing::{DetectionPipeline, SegmentationPipeline, ObjectTracker};
+use crate::governor::{GovernorConfig, ProcessingGovernor, QualityReport};
+use crate::privacy::PrivacyManager;
+use crate::models::{ModelManager, YoloModel, SamModel, ClipModel, InferenceScheduler};
+use crate::processing::{redact_frame, DetectionPipeline, SegmentationPipeline, ObjectTracker};
 use crate::scene::{SceneAnalyzer, LLMProvider};
 use narayana_llm::{LLMManager};
+use narayana_storage::camera_calibration::{CameraCalibration, CameraCalibrationStore, CameraExtrinsics};
 use narayana_llm::config::{Message, MessageRole};
 use narayana_wld::protocol_adapters::ProtocolAdapter;
 use narayana_wld::world_broker::WorldBrokerHandle;
 use narayana_wld::event_transformer::{WorldEvent, WorldAction};
 use narayana_core::Error;
 use async_trait::async_trait;
-use opencv::prelude::Mat;
+use opencv::prelude::{Mat, MatTraitConst};
+use opencv::imgproc;
 use serde_json::json;
 use std::sync::Arc;
+use std::time::Instant;
 use parking_lot::RwLock;
 use tokio::sync::broadcast;
 use tokio::sync::mpsc;
@@ -34,9 +40,15 @@ pub struct VisionAdapter {
     is_running: Arc<RwLock<bool>>,
     frame_receiver: Arc<RwLock<Option<mpsc::Receiver<Mat>>>>,
     llm_manager: Option<Arc<LLMManager>>,
+    inference_scheduler: Option<Arc<InferenceScheduler>>,
+    calibration_store: Option<Arc<CameraCalibrationStore>>,
+    undistorter: Arc<RwLock<Option<Arc<Undistorter>>>>,
+    privacy: Arc<PrivacyManager>,
     process_request_sender: Arc<RwLock<Option<mpsc::Sender<()>>>>,
     processing_handle: Arc<RwLock<Option<tokio::task::JoinHandle<()>>>>,
     on_demand_handle: Arc<RwLock<Option<tokio::task::JoinHandle<()>>>>,
+    governor: Arc<ProcessingGovernor>,
+    governor_handle: Arc<RwLock<Option<tokio::task::JoinHandle<()>>>>,
 }
 
 impl VisionAdapter {
@@ -49,6 +61,8 @@ impl VisionAdapter {
         let camera = Arc::new(CameraManager::new(config.clone()));
         let model_manager = Arc::new(ModelManager::new(config.clone()));
         let tracker = Arc::new(ObjectTracker::new(30, 0.3)); // max_age=30, iou_threshold=0.3
+        let governor = ProcessingGovernor::new(&config, GovernorConfig::default())
+            .map_err(|e| Error::Storage(format!("Invalid governor config: {}", e)))?;
 
         Ok(Self {
             config: config.clone(),
@@ -62,12 +76,31 @@ impl VisionAdapter {
             is_running: Arc::new(RwLock::new(false)),
             frame_receiver: Arc::new(RwLock::new(None)),
             llm_manager: None,
+            inference_scheduler: None,
+            calibration_store: None,
+            undistorter: Arc::new(RwLock::new(None)),
+            privacy: Arc::new(PrivacyManager::new()),
             process_request_sender: Arc::new(RwLock::new(None)),
             processing_handle: Arc::new(RwLock::new(None)),
             on_demand_handle: Arc::new(RwLock::new(None)),
+            governor: Arc::new(governor),
+            governor_handle: Arc::new(RwLock::new(None)),
         })
     }
 
+    /// Replace the processing governor configuration
+    pub fn with_governor_config(mut self, governor_config: GovernorConfig) -> Result<Self, Error> {
+        let governor = ProcessingGovernor::new(&self.config, governor_config)
+            .map_err(|e| Error::Storage(format!("Invalid governor config: {}", e)))?;
+        self.governor = Arc::new(governor);
+        Ok(self)
+    }
+
+    /// Governor adapting processing quality to pipeline latency and system load
+    pub fn governor(&self) -> &Arc<ProcessingGovernor> {
+        &self.governor
+    }
+
     /// Process a single frame on demand
     pub async fn process_frame_on_demand(&self) -> Result<(), VisionError> {
         let frame = self.camera.capture_frame()?;
@@ -79,6 +112,8 @@ impl VisionAdapter {
             &self.tracker,
             &self.scene_analyzer,
             &self.event_sender,
+            &self.undistorter,
+            &self.privacy,
         ).await
     }
 
@@ -87,6 +122,94 @@ impl VisionAdapter {
         self.llm_manager = llm_manager;
     }
 
+    /// Share an inference scheduler with other adapters, so detection
+    /// requests from all cameras are batched together
+    pub fn set_inference_scheduler(&mut self, scheduler: Option<Arc<InferenceScheduler>>) {
+        self.inference_scheduler = scheduler;
+    }
+
+    /// Keep camera calibrations in `store`; a stored calibration for this
+    /// camera is applied when the adapter starts
+    pub fn set_calibration_store(&mut self, store: Option<Arc<CameraCalibrationStore>>) {
+        self.calibration_store = store;
+    }
+
+    /// Share privacy zones (and their audit log) with other adapters
+    pub fn set_privacy_manager(&mut self, privacy: Arc<PrivacyManager>) {
+        self.privacy = privacy;
+    }
+
+    /// Privacy zones; this camera's are keyed by `camera_{id}`
+    pub fn privacy(&self) -> &Arc<PrivacyManager> {
+        &self.privacy
+    }
+
+    /// Name calibrations of this camera are stored under
+    fn calibration_key(&self) -> String {
+        format!("camera_{}", self.config.camera_id)
+    }
+
+    /// Undistort frames with `calibration` from now on (`None` to stop)
+    pub fn apply_calibration(&self, calibration: Option<CameraCalibration>) -> Result<(), VisionError> {
+        let undistorter = calibration.map(Undistorter::new).transpose()?;
+        *self.undistorter.write() = undistorter.map(Arc::new);
+        Ok(())
+    }
+
+    /// Calibrate intrinsics from views of a checkerboard moved in front of the camera
+    ///
+    /// Captures a frame every `capture_interval_ms` until `min_samples` views
+    /// of the board were found. The result is stored (when there is a
+    /// calibration store) and applied to the pipeline.
+    pub async fn calibrate_intrinsics(&self, board: CheckerboardConfig) -> Result<CameraCalibration, VisionError> {
+        let mut calibrator = CheckerboardCalibrator::new(board.clone())?;
+        let interval = std::time::Duration::from_millis(board.capture_interval_ms);
+        for _ in 0..board.max_attempts {
+            let frame = self.camera.capture_frame()?;
+            if calibrator.add_frame(&frame)? {
+                info!("Checkerboard view {}/{} captured", calibrator.sample_count(), board.min_samples);
+                if calibrator.has_enough_samples() {
+                    break;
+                }
+                tokio::time::sleep(interval).await;
+            }
+        }
+        let mut calibration = calibrator.calibrate(&self.calibration_key())?;
+        // Recalibrating intrinsics leaves the mounting pose unchanged
+        if let Some(previous) = self.calibration_store.as_ref().and_then(|store| store.get(&calibration.camera_id)) {
+            calibration.extrinsics = previous.extrinsics;
+        }
+        if let Some(store) = &self.calibration_store {
+            calibration = store.save(calibration)?;
+        }
+        self.apply_calibration(Some(calibration.clone()))?;
+        Ok(calibration)
+    }
+
+    /// Locate the camera from a checkerboard lying at the world origin
+    ///
+    /// Needs intrinsics from `calibrate_intrinsics` (or the store) first.
+    pub async fn calibrate_extrinsics(&self, board: CheckerboardConfig) -> Result<CameraExtrinsics, VisionError> {
+        let calibration = self.undistorter.read().as_ref()
+            .map(|undistorter| undistorter.calibration().clone())
+            .ok_or_else(|| VisionError::Config("Camera has no intrinsic calibration".to_string()))?;
+        let interval = std::time::Duration::from_millis(board.capture_interval_ms);
+        for _ in 0..board.max_attempts {
+            let frame = self.camera.capture_frame()?;
+            if let Some(extrinsics) = estimate_extrinsics(&calibration, &frame, &board)? {
+                let mut calibration = calibration;
+                calibration.extrinsics = Some(extrinsics.clone());
+                if let Some(store) = &self.calibration_store {
+                    calibration = store.save(calibration)?;
+                }
+                self.apply_calibration(Some(calibration))?;
+                return Ok(extrinsics);
+            }
+            tokio::time::sleep(interval).await;
+        }
+        Err(VisionError::Processing("Checkerboard not found for extrinsic calibration".to_string()))
+    }
+
     /// Clone adapter for on-demand processing
     fn clone_for_on_demand(&self) -> VisionAdapterOnDemand {
         VisionAdapterOnDemand {
@@ -97,6 +220,8 @@ impl VisionAdapter {
             tracker: self.tracker.clone(),
             scene_analyzer: self.scene_analyzer.clone(),
             event_sender: self.event_sender.clone(),
+            undistorter: self.undistorter.clone(),
+            privacy: self.privacy.clone(),
         }
     }
 
@@ -113,7 +238,10 @@ impl VisionAdapter {
         let tracker = self.tracker.clone();
         let scene_analyzer = self.scene_analyzer.clone();
         let event_sender = self.event_sender.clone();
+        let undistorter = self.undistorter.clone();
+        let privacy = self.privacy.clone();
         let is_running = self.is_running.clone();
+        let governor = self.governor.clone();
 
         let handle = tokio::spawn(async move {
             let mut frame_receiver = frame_receiver;
@@ -129,6 +257,18 @@ impl VisionAdapter {
                     frame_receiver.recv()
                 ).await {
                     Ok(Some(frame)) => {
+                        // Skip frames the governor has no budget for
+                        if !governor.should_process() {
+                            continue;
+                        }
+                        let frame = match downscale(frame, &config, governor.current().resolution) {
+                            Ok(frame) => frame,
+                            Err(e) => {
+                                error!("Frame processing error: {}", e);
+                                continue;
+                            }
+                        };
+                        let started = Instant::now();
                         if let Err(e) = process_frame_internal(
                             &frame,
                             &config,
@@ -137,9 +277,12 @@ impl VisionAdapter {
                             &tracker,
                             &scene_analyzer,
                             &event_sender,
+                            &undistorter,
+                            &privacy,
                         ).await {
                             error!("Frame processing error: {}", e);
                         }
+                        governor.record_frame(started.elapsed());
                     }
                     Ok(None) => {
                         warn!("Frame receiver closed, stopping processing loop");
@@ -162,6 +305,36 @@ impl VisionAdapter {
         Ok(())
     }
 
+    /// Evaluate the governor periodically and report quality changes to the broker
+    fn start_governor(&self, broker: WorldBrokerHandle) {
+        let governor = self.governor.clone();
+        let camera_id = self.config.camera_id;
+        let interval = std::time::Duration::from_millis(governor.config().evaluation_interval_ms);
+
+        let handle = tokio::spawn(async move {
+            report_quality(&broker, camera_id, governor.report()).await;
+            let mut ticker = tokio::time::interval(interval);
+            // The first tick completes immediately
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                if let Some(report) = governor.evaluate() {
+                    info!(
+                        "Vision quality level {} of {}: {} fps at {}x{} ({:?})",
+                        report.quality.level,
+                        report.levels - 1,
+                        report.quality.frame_rate,
+                        report.quality.resolution.0,
+                        report.quality.resolution.1,
+                        report.quality.mode,
+                    );
+                    report_quality(&broker, camera_id, report).await;
+                }
+            }
+        });
+        *self.governor_handle.write() = Some(handle);
+    }
+
     /// Initialize models
     async fn initialize_models(&self) -> Result<(), VisionError> {
         info!("Initializing vision models...");
@@ -175,8 +348,21 @@ impl VisionAdapter {
                 Ok(yolo_path) => {
                     match YoloModel::new(&yolo_path) {
                         Ok(yolo) => {
-                            let detection = Arc::new(DetectionPipeline::new(Arc::new(yolo)));
-                            *self.detection_pipeline.write() = Some(detection);
+                            let yolo = Arc::new(yolo);
+                            let mut detection = DetectionPipeline::new(yolo.clone());
+                            if let Some(scheduler) = &self.inference_scheduler {
+                                // Another camera may have registered the model already; share it
+                                if let Err(e) = scheduler.register_model(YoloModel::NAME, yolo, YoloModel::profile()) {
+                                    self.rollback_models(&loaded_models);
+                                    return Err(e);
+                                }
+                                let pipeline = format!("camera_{}/detection", self.config.camera_id);
+                                // Results older than a frame interval are superseded by the next frame
+                                let slo = std::time::Duration::from_millis(1000 / self.config.frame_rate.max(1) as u64);
+                                scheduler.register_pipeline(&pipeline, slo);
+                                detection = detection.with_scheduler(scheduler.clone(), &pipeline);
+                            }
+                            *self.detection_pipeline.write() = Some(Arc::new(detection));
                             loaded_models.push("yolo");
                             info!("YOLO detection model loaded");
                         }
@@ -304,6 +490,8 @@ struct VisionAdapterOnDemand {
     tracker: Arc<ObjectTracker>,
     scene_analyzer: Arc<RwLock<Option<Arc<SceneAnalyzer>>>>,
     event_sender: Arc<RwLock<Option<broadcast::Sender<WorldEvent>>>>,
+    undistorter: Arc<RwLock<Option<Arc<Undistorter>>>>,
+    privacy: Arc<PrivacyManager>,
 }
 
 impl VisionAdapterOnDemand {
@@ -317,6 +505,8 @@ impl VisionAdapterOnDemand {
             &self.tracker,
             &self.scene_analyzer,
             &self.event_sender,
+            &self.undistorter,
+            &self.privacy,
         ).await
     }
 }
@@ -335,6 +525,8 @@ impl VisionAdapter {
         let tracker = self.tracker.clone();
         let scene_analyzer = self.scene_analyzer.clone();
         let event_sender = self.event_sender.clone();
+        let undistorter = self.undistorter.clone();
+        let privacy = self.privacy.clone();
         let is_running = self.is_running.clone();
 
         tokio::spawn(async move {
@@ -354,6 +546,8 @@ impl VisionAdapter {
                             &tracker,
                             &scene_analyzer,
                             &event_sender,
+                            &undistorter,
+                            &privacy,
                         ).await {
                             error!("Frame processing error: {}", e);
                         }
@@ -370,6 +564,42 @@ impl VisionAdapter {
     }
 }
 
+/// Shrink `frame` to the governor's resolution when it is below the configured one
+fn downscale(frame: Mat, config: &VisionConfig, resolution: (u32, u32)) -> Result<Mat, VisionError> {
+    if resolution == config.resolution {
+        return Ok(frame);
+    }
+    let mut resized = Mat::default();
+    imgproc::resize(
+        &frame,
+        &mut resized,
+        opencv::core::Size::new(resolution.0 as i32, resolution.1 as i32),
+        0.0,
+        0.0,
+        imgproc::INTER_AREA,
+    ).map_err(|e| VisionError::OpenCv(format!("Failed to resize frame: {}", e)))?;
+    Ok(resized)
+}
+
+/// Send the active quality level to the world broker
+async fn report_quality(broker: &WorldBrokerHandle, camera_id: u32, report: QualityReport) {
+    let mut payload = match serde_json::to_value(&report) {
+        Ok(payload) => payload,
+        Err(e) => {
+            warn!("Failed to serialize vision quality report: {}", e);
+            return;
+        }
+    };
+    payload["camera_id"] = json!(camera_id);
+    let event = WorldEvent::SystemEvent {
+        event_type: "vision_quality".to_string(),
+        payload,
+    };
+    if let Err(e) = broker.process_world_event(event).await {
+        warn!("Failed to report vision quality: {}", e);
+    }
+}
+
 /// Internal frame processing function
 async fn process_frame_internal(
     frame: &Mat,
@@ -379,6 +609,8 @@ async fn process_frame_internal(
     tracker: &Arc<ObjectTracker>,
     scene_analyzer: &Arc<RwLock<Option<Arc<SceneAnalyzer>>>>,
     event_sender: &Arc<RwLock<Option<broadcast::Sender<WorldEvent>>>>,
+    undistorter: &Arc<RwLock<Option<Arc<Undistorter>>>>,
+    privacy: &Arc<PrivacyManager>,
 ) -> Result<(), VisionError> {
     // Use timestamp_nanos_opt to handle potential overflow gracefully
     let timestamp = chrono::Utc::now()
@@ -390,12 +622,45 @@ async fn process_frame_internal(
         "camera_id": config.camera_id,
     });
 
+    // Remove lens distortion first, so detections are in rectified pixels
+    let undistorter = undistorter.read().clone();
+    let undistorted;
+    let frame = match &undistorter {
+        Some(undistorter) => {
+            undistorted = undistorter.undistort(frame)?;
+            if let Some(m) = undistorter.rectified_intrinsics() {
+                vision_data["intrinsics"] = json!({
+                    "fx": m[0],
+                    "fy": m[4],
+                    "cx": m[2],
+                    "cy": m[5],
+                });
+            }
+            &undistorted
+        }
+        None => frame,
+    };
+
+    // Redact privacy zones before any model sees the frame
+    let zones = privacy.zones(&format!("camera_{}", config.camera_id));
+    let redacted = redact_frame(frame, &zones)?;
+    let frame = redacted.as_ref().unwrap_or(frame);
+    let (frame_width, frame_height) = (frame.cols().max(1) as f32, frame.rows().max(1) as f32);
+
     // Object detection
     let mut detections = Vec::new();
     if config.enable_detection {
-        if let Some(detection) = detection_pipeline.read().as_ref() {
-            match detection.detect(frame) {
-                Ok(dets) => {
+        // Clone out of the lock; scheduled detection awaits
+        let detection = detection_pipeline.read().clone();
+        if let Some(detection) = detection {
+            match detection.detect_scheduled(frame).await {
+                Ok(mut dets) => {
+                    // Objects centered in a privacy zone were not seen
+                    dets.retain(|d| {
+                        let center_x = (d.bbox.0 + d.bbox.2 / 2.0) / frame_width;
+                        let center_y = (d.bbox.1 + d.bbox.3 / 2.0) / frame_height;
+                        !zones.iter().any(|zone| zone.contains(center_x, center_y))
+                    });
                     // Limit detections to prevent JSON serialization DoS
                     const MAX_DETECTIONS: usize = 100;
                     detections = if dets.len() > MAX_DETECTIONS {
@@ -557,6 +822,14 @@ impl ProtocolAdapter for VisionAdapter {
             return Err(Error::Storage(format!("Camera initialization failed: {}", e)));
         }
 
+        // Apply the stored calibration; without one frames pass through as captured
+        if let Some(calibration) = self.calibration_store.as_ref().and_then(|store| store.get(&self.calibration_key())) {
+            match self.apply_calibration(Some(calibration)) {
+                Ok(()) => info!("Applied stored calibration for {}", self.calibration_key()),
+                Err(e) => warn!("Ignoring stored calibration for {}: {}", self.calibration_key(), e),
+            }
+        }
+
         // Initialize models (with rollback on failure)
         if let Err(e) = self.initialize_models().await {
             *self.is_running.write() = false;
@@ -596,6 +869,9 @@ impl ProtocolAdapter for VisionAdapter {
                         return Err(Error::Storage(format!("Failed to start processing loop: {}", e)));
                     }
                 }
+                if self.governor.config().enabled {
+                    self.start_governor(broker);
+                }
             }
             ProcessingMode::OnDemand => {
                 // On-demand processing: set up command channel
@@ -662,6 +938,10 @@ impl ProtocolAdapter for VisionAdapter {
             ).await;
         }
 
+        if let Some(handle) = self.governor_handle.write().take() {
+            handle.abort();
+        }
+
         if let Some(handle) = self.on_demand_handle.write().take() {
             handle.abort();
             // Wait a bit for task to finish
@@ -700,12 +980,24 @@ impl ProtocolAdapter for VisionAdapter {
                     // Check for on-demand processing request
                     if let Some(cmd_str) = command.get("command").and_then(|v| v.as_str()) {
                         if cmd_str == "process_frame" {
+                            // In real-time mode the governor may be skipping frames
+                            self.governor.request_frame();
                             // Trigger on-demand frame processing
                             if let Some(sender) = self.process_request_sender.read().as_ref() {
                                 if sender.send(()).await.is_err() {
                                     warn!("Failed to send on-demand processing request");
                                 }
                             }
+                        } else if cmd_str == "set_privacy_zone" {
+                            let zone = command.get("zone").cloned()
+                                .ok_or_else(|| Error::Storage("set_privacy_zone needs a zone".to_string()))
+                                .and_then(|zone| serde_json::from_value(zone)
+                                    .map_err(|e| Error::Storage(format!("Invalid privacy zone: {}", e))))?;
+                            self.privacy.set_zone(&target, zone, "world_action")?;
+                        } else if cmd_str == "remove_privacy_zone" {
+                            if let Some(zone_id) = command.get("zone_id").and_then(|v| v.as_str()) {
+                                self.privacy.remove_zone(&target, zone_id, "world_action");
+                            }
                         }
                     }
                 }
@@ -713,6 +1005,7 @@ impl ProtocolAdapter for VisionAdapter {
             WorldAction::Command { command, args } => {
                 // Handle direct commands for vision system
                 if command == "process_frame" {
+                    self.governor.request_frame();
                     if let Some(sender) = self.process_request_sender.read().as_ref() {
                         if sender.send(()).await.is_err() {
                             warn!("Failed to send on-demand processing request");
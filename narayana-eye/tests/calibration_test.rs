@@ -0,0 +1,61 @@
+//! Tests for camera calibration
+
+use narayana_eye::calibration::{CheckerboardCalibrator, CheckerboardConfig, Undistorter};
+use narayana_storage::camera_calibration::CameraCalibration;
+use opencv::core::{Mat, Scalar, CV_8UC3};
+use opencv::prelude::*;
+
+fn calibration() -> CameraCalibration {
+    CameraCalibration {
+        camera_id: "camera_0".to_string(),
+        image_size: (640, 480),
+        camera_matrix: [500.0, 0.0, 320.0, 0.0, 500.0, 240.0, 0.0, 0.0, 1.0],
+        distortion: vec![-0.2, 0.05, 0.0, 0.0, 0.0],
+        extrinsics: None,
+        reprojection_error: 0.3,
+        calibrated_at: 0,
+    }
+}
+
+#[test]
+fn test_checkerboard_config_validation() {
+    assert!(CheckerboardConfig::default().validate().is_ok());
+
+    let config = CheckerboardConfig { inner_corners: (1, 6), ..CheckerboardConfig::default() };
+    assert!(config.validate().is_err());
+
+    let config = CheckerboardConfig { square_size_m: 0.0, ..CheckerboardConfig::default() };
+    assert!(config.validate().is_err());
+
+    let config = CheckerboardConfig { min_samples: 12, max_attempts: 11, ..CheckerboardConfig::default() };
+    assert!(config.validate().is_err());
+    assert!(CheckerboardCalibrator::new(config).is_err());
+}
+
+#[test]
+fn test_calibrator_needs_board_views() {
+    let mut calibrator = CheckerboardCalibrator::new(CheckerboardConfig::default()).unwrap();
+    // A blank frame has no checkerboard in it
+    let blank = Mat::new_rows_cols_with_default(480, 640, CV_8UC3, Scalar::all(255.0)).unwrap();
+    assert!(!calibrator.add_frame(&blank).unwrap());
+    assert_eq!(calibrator.sample_count(), 0);
+    assert!(calibrator.calibrate("camera_0").is_err());
+}
+
+#[test]
+fn test_undistorter_scales_to_frame_size() {
+    let mut invalid = calibration();
+    invalid.distortion = vec![0.0; 2];
+    assert!(Undistorter::new(invalid).is_err());
+
+    let undistorter = Undistorter::new(calibration()).unwrap();
+    assert!(undistorter.rectified_intrinsics().is_none());
+
+    // Half-size frames (e.g. downscaled by the governor) get half the focal length
+    let frame = Mat::new_rows_cols_with_default(240, 320, CV_8UC3, Scalar::all(128.0)).unwrap();
+    let undistorted = undistorter.undistort(&frame).unwrap();
+    assert_eq!(undistorted.rows(), 240);
+    assert_eq!(undistorted.cols(), 320);
+    let intrinsics = undistorter.rectified_intrinsics().unwrap();
+    assert!(intrinsics[0] > 150.0 && intrinsics[0] < 350.0);
+}
@@ -0,0 +1,145 @@
+//! Tests for the adaptive processing governor
+
+use narayana_eye::config::{ProcessingMode, VisionConfig};
+use narayana_eye::governor::{GovernorConfig, LoadMonitor, ProcessingGovernor};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Load figure the test controls (stored as per mille)
+struct FixedLoad(Arc<AtomicU64>);
+
+impl LoadMonitor for FixedLoad {
+    fn load(&self) -> Option<f64> {
+        Some(self.0.load(Ordering::Relaxed) as f64 / 1000.0)
+    }
+}
+
+fn governor(step_up_after: u32) -> (ProcessingGovernor, Arc<AtomicU64>) {
+    let vision = VisionConfig {
+        frame_rate: 30,
+        resolution: (640, 480),
+        ..VisionConfig::default()
+    };
+    let config = GovernorConfig {
+        step_up_after,
+        ..GovernorConfig::default()
+    };
+    let load = Arc::new(AtomicU64::new(100));
+    let governor = ProcessingGovernor::new(&vision, config)
+        .unwrap()
+        .with_load_monitor(Box::new(FixedLoad(load.clone())));
+    (governor, load)
+}
+
+fn record(governor: &ProcessingGovernor, latency_ms: u64, frames: usize) {
+    for _ in 0..frames {
+        governor.record_frame(Duration::from_millis(latency_ms));
+    }
+}
+
+#[test]
+fn test_quality_ladder() {
+    let (governor, _) = governor(3);
+    let ladder = governor.ladder();
+
+    assert_eq!(ladder[0].frame_rate, 30);
+    assert_eq!(ladder[0].resolution, (640, 480));
+    assert_eq!(ladder[0].mode, ProcessingMode::RealTime);
+    // Each level is cheaper than the one before
+    for pair in ladder.windows(2) {
+        let cost = |q: &narayana_eye::QualityLevel| q.frame_rate as u64 * q.resolution.0 as u64 * q.resolution.1 as u64;
+        assert!(cost(&pair[1]) <= cost(&pair[0]));
+        assert_eq!(pair[1].level, pair[0].level + 1);
+    }
+    let last = ladder.last().unwrap();
+    assert_eq!(last.mode, ProcessingMode::OnDemand);
+    assert_eq!(last.frame_rate, 2);
+}
+
+#[test]
+fn test_steps_down_on_latency_and_load() {
+    let (governor, load) = governor(3);
+
+    // 30 fps leaves ~26ms per frame; 20ms is fine
+    record(&governor, 20, 30);
+    assert!(governor.evaluate().is_none());
+    assert_eq!(governor.current().level, 0);
+
+    // 50ms per frame overruns the budget
+    record(&governor, 50, 30);
+    let report = governor.evaluate().expect("level should drop");
+    assert_eq!(report.quality.level, 1);
+    assert_eq!(report.quality.frame_rate, 15);
+    assert!(report.p90_latency_ms.unwrap() >= 50.0);
+
+    // Fast frames but a busy system still step down
+    load.store(950, Ordering::Relaxed);
+    record(&governor, 5, 30);
+    assert_eq!(governor.evaluate().unwrap().quality.level, 2);
+
+    // Never past the last level
+    for _ in 0..10 {
+        governor.evaluate();
+    }
+    assert_eq!(governor.current().mode, ProcessingMode::OnDemand);
+}
+
+#[test]
+fn test_steps_up_after_healthy_windows() {
+    let (governor, load) = governor(2);
+    load.store(950, Ordering::Relaxed);
+    governor.evaluate();
+    governor.evaluate();
+    assert_eq!(governor.current().level, 2);
+
+    load.store(100, Ordering::Relaxed);
+    record(&governor, 5, 10);
+    assert!(governor.evaluate().is_none());
+    record(&governor, 5, 10);
+    assert_eq!(governor.evaluate().unwrap().quality.level, 1);
+
+    // An overloaded window resets the count
+    record(&governor, 5, 10);
+    assert!(governor.evaluate().is_none());
+    record(&governor, 500, 10);
+    assert_eq!(governor.evaluate().unwrap().quality.level, 2);
+}
+
+#[test]
+fn test_frame_throttling() {
+    let (governor, load) = governor(3);
+    // At full rate every spaced-out frame passes
+    assert!(governor.should_process());
+
+    load.store(950, Ordering::Relaxed);
+    for _ in 0..10 {
+        governor.evaluate();
+    }
+    assert_eq!(governor.current().mode, ProcessingMode::OnDemand);
+    assert!(!governor.should_process());
+
+    // A requested frame passes once
+    governor.request_frame();
+    assert!(governor.should_process());
+    assert!(!governor.should_process());
+}
+
+#[test]
+fn test_disabled_governor() {
+    let config = GovernorConfig {
+        enabled: false,
+        ..GovernorConfig::default()
+    };
+    let governor = ProcessingGovernor::new(&VisionConfig::default(), config).unwrap();
+    record(&governor, 10_000, 10);
+    assert!(governor.evaluate().is_none());
+    assert!(governor.should_process());
+    assert!(governor.should_process());
+
+    let invalid = GovernorConfig {
+        target_utilization: 1.5,
+        ..GovernorConfig::default()
+    };
+    assert!(ProcessingGovernor::new(&VisionConfig::default(), invalid).is_err());
+}
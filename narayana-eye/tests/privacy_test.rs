@@ -0,0 +1,64 @@
+//! Tests for privacy zones
+
+use narayana_eye::privacy::{PrivacyChange, PrivacyManager, PrivacyZone, RedactionMode};
+
+fn zone(id: &str, polygon: Vec<(f32, f32)>) -> PrivacyZone {
+    PrivacyZone {
+        id: id.to_string(),
+        polygon,
+        mode: RedactionMode::Blur,
+    }
+}
+
+#[test]
+fn test_zone_validation() {
+    assert!(zone("window", vec![(0.0, 0.0), (0.5, 0.0), (0.5, 0.5)]).validate().is_ok());
+    assert!(zone("window", vec![(0.0, 0.0), (0.5, 0.0)]).validate().is_err());
+    assert!(zone("window", vec![(0.0, 0.0), (1.5, 0.0), (0.5, 0.5)]).validate().is_err());
+    assert!(zone("window", vec![(0.0, 0.0), (f32::NAN, 0.0), (0.5, 0.5)]).validate().is_err());
+    assert!(zone("../window", vec![(0.0, 0.0), (0.5, 0.0), (0.5, 0.5)]).validate().is_err());
+}
+
+#[test]
+fn test_point_in_polygon() {
+    // L-shaped zone: the top-right quadrant is outside it
+    let l_shape = zone("desk", vec![(0.0, 0.0), (0.5, 0.0), (0.5, 0.5), (1.0, 0.5), (1.0, 1.0), (0.0, 1.0)]);
+    assert!(l_shape.contains(0.25, 0.25));
+    assert!(l_shape.contains(0.75, 0.75));
+    assert!(!l_shape.contains(0.75, 0.25));
+
+    let manager = PrivacyManager::new();
+    manager.set_zone("camera_0", l_shape, "test").unwrap();
+    assert!(manager.is_private("camera_0", 0.25, 0.75));
+    assert!(!manager.is_private("camera_0", 0.75, 0.25));
+    assert!(!manager.is_private("camera_1", 0.25, 0.75));
+}
+
+#[test]
+fn test_changes_are_audited() {
+    let path = std::env::temp_dir().join(format!("narayana_privacy_{}.jsonl", uuid::Uuid::new_v4()));
+    let manager = PrivacyManager::new().with_audit_file(path.clone()).unwrap();
+    let square = vec![(0.1, 0.1), (0.4, 0.1), (0.4, 0.4), (0.1, 0.4)];
+
+    manager.set_zone("camera_0", zone("door", square.clone()), "alice").unwrap();
+    let mut blackout = zone("door", square);
+    blackout.mode = RedactionMode::Blackout;
+    manager.set_zone("camera_0", blackout, "bob").unwrap();
+    assert_eq!(manager.zones("camera_0")[0].mode, RedactionMode::Blackout);
+    // Rejected changes leave the zones and the log untouched
+    assert!(manager.set_zone("camera_0", zone("bad", vec![]), "mallory").is_err());
+    assert!(!manager.remove_zone("camera_0", "missing", "bob"));
+    assert!(manager.remove_zone("camera_0", "door", "bob"));
+    assert!(!manager.has_zones("camera_0"));
+
+    let log = manager.audit_log(Some("camera_0"));
+    let changes: Vec<PrivacyChange> = log.iter().map(|entry| entry.change).collect();
+    assert_eq!(changes, vec![PrivacyChange::ZoneAdded, PrivacyChange::ZoneUpdated, PrivacyChange::ZoneRemoved]);
+    assert_eq!(log[0].actor, "alice");
+    assert_eq!(log[2].zone_count, 0);
+    assert!(manager.audit_log(Some("camera_1")).is_empty());
+
+    let persisted = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(persisted.lines().count(), 3);
+    let _ = std::fs::remove_file(path);
+}
@@ -0,0 +1,146 @@
+//! Tests for the shared inference scheduler
+
+use narayana_eye::error::VisionError;
+use narayana_eye::models::scheduler::BatchModel;
+use narayana_eye::models::{InferenceScheduler, ModelProfile, SchedulerConfig, TensorData};
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Doubles every value and records the batch sizes it ran
+struct DoublingModel {
+    batches: Mutex<Vec<usize>>,
+    delay: Duration,
+}
+
+impl DoublingModel {
+    fn new(delay: Duration) -> Arc<Self> {
+        Arc::new(Self {
+            batches: Mutex::new(Vec::new()),
+            delay,
+        })
+    }
+}
+
+impl BatchModel for DoublingModel {
+    fn run_batch(&self, inputs: &[TensorData]) -> Result<Vec<TensorData>, VisionError> {
+        self.batches.lock().push(inputs.len());
+        std::thread::sleep(self.delay);
+        Ok(inputs
+            .iter()
+            .map(|input| TensorData {
+                shape: input.shape.clone(),
+                data: input.data.iter().map(|v| v * 2.0).collect(),
+            })
+            .collect())
+    }
+}
+
+fn profile(weights_mb: u32, per_input_mb: u32) -> ModelProfile {
+    ModelProfile {
+        weights_mb,
+        per_input_mb,
+        max_batch: 8,
+    }
+}
+
+fn input(value: f32) -> TensorData {
+    TensorData::new(vec![2], vec![value, value + 1.0]).unwrap()
+}
+
+#[test]
+fn test_tensor_stack_roundtrip() {
+    let stacked = TensorData::stack(&[input(1.0), input(3.0)]).unwrap();
+    assert_eq!(stacked.shape, vec![2, 2]);
+    assert_eq!(stacked.data, vec![1.0, 2.0, 3.0, 4.0]);
+    assert_eq!(stacked.unstack().unwrap(), vec![input(1.0), input(3.0)]);
+
+    assert!(TensorData::new(vec![3], vec![1.0]).is_err());
+    let other = TensorData::new(vec![1], vec![1.0]).unwrap();
+    assert!(TensorData::stack(&[input(1.0), other]).is_err());
+}
+
+#[tokio::test]
+async fn test_batches_across_pipelines() {
+    let config = SchedulerConfig {
+        max_batch_wait_ms: 50,
+        ..SchedulerConfig::default()
+    };
+    let scheduler = Arc::new(InferenceScheduler::new(config).unwrap());
+    let model = DoublingModel::new(Duration::ZERO);
+    assert!(scheduler.register_model("double", model.clone(), profile(100, 10)).unwrap());
+    // A second registration shares the first model
+    assert!(!scheduler.register_model("double", DoublingModel::new(Duration::ZERO), profile(100, 10)).unwrap());
+
+    let mut tasks = Vec::new();
+    for i in 0..4 {
+        let scheduler = scheduler.clone();
+        tasks.push(tokio::spawn(async move {
+            let pipeline = format!("camera_{}/detection", i);
+            scheduler.infer(&pipeline, "double", input(i as f32)).await
+        }));
+    }
+    for (i, task) in tasks.into_iter().enumerate() {
+        let output = task.await.unwrap().unwrap();
+        assert_eq!(output.data, vec![i as f32 * 2.0, (i as f32 + 1.0) * 2.0]);
+    }
+
+    assert_eq!(*model.batches.lock(), vec![4]);
+    let stats = scheduler.stats("camera_0/detection");
+    assert_eq!(stats.requests, 1);
+    assert_eq!(stats.completed, 1);
+    assert_eq!(stats.mean_batch_size, 4.0);
+
+    let missing = scheduler.infer("camera_0/detection", "missing", input(0.0)).await;
+    assert!(missing.is_err());
+}
+
+#[tokio::test]
+async fn test_memory_budget() {
+    let config = SchedulerConfig {
+        memory_budget_mb: 1000,
+        ..SchedulerConfig::default()
+    };
+    let scheduler = InferenceScheduler::new(config).unwrap();
+    scheduler.register_model("a", DoublingModel::new(Duration::ZERO), profile(600, 50)).unwrap();
+    assert_eq!(scheduler.free_memory_mb(), 400);
+
+    // Weights plus one input no longer fit
+    assert!(scheduler.register_model("b", DoublingModel::new(Duration::ZERO), profile(400, 50)).is_err());
+    assert!(!scheduler.has_model("b"));
+
+    assert!(scheduler.unregister_model("a"));
+    assert_eq!(scheduler.free_memory_mb(), 1000);
+    scheduler.register_model("b", DoublingModel::new(Duration::ZERO), profile(400, 50)).unwrap();
+}
+
+#[tokio::test]
+async fn test_expired_requests_are_dropped() {
+    let config = SchedulerConfig {
+        max_batch_size: 1,
+        max_batch_wait_ms: 0,
+        ..SchedulerConfig::default()
+    };
+    let scheduler = Arc::new(InferenceScheduler::new(config).unwrap());
+    scheduler
+        .register_model("slow", DoublingModel::new(Duration::from_millis(100)), profile(10, 0))
+        .unwrap();
+    scheduler.register_pipeline("relaxed", Duration::from_secs(5));
+    scheduler.register_pipeline("urgent", Duration::from_millis(20));
+
+    let busy = {
+        let scheduler = scheduler.clone();
+        tokio::spawn(async move { scheduler.infer("relaxed", "slow", input(1.0)).await })
+    };
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    // Queued behind a 100ms batch, a 20ms SLO cannot be met
+    let result = scheduler.infer("urgent", "slow", input(2.0)).await;
+    assert!(matches!(result, Err(VisionError::Processing(_))));
+    assert!(busy.await.unwrap().is_ok());
+
+    let stats = scheduler.stats("urgent");
+    assert_eq!(stats.deadline_misses, 1);
+    assert_eq!(stats.completed, 0);
+    assert_eq!(scheduler.stats("relaxed").completed, 1);
+}
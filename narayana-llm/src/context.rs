@@ -0,0 +1,412 @@
+// Hierarchical context retrieval: assemble prompt context from working memory,
+// episodic memories and vector search under a token budget, with provenance
+
+use crate::config::*;
+use crate::error::{LLMError, Result};
+use crate::manager::LLMManager;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Overflowing chunks summarized together at each level of the hierarchy
+const SUMMARY_GROUP_SIZE: usize = 8;
+/// Below this many tokens a summary carries no useful information
+const MIN_SUMMARY_TOKENS: usize = 24;
+/// Candidates requested from a provider per source
+const CANDIDATES_PER_SOURCE: usize = 50;
+
+/// Where a piece of context came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContextSource {
+    WorkingMemory,
+    Episodic,
+    Vector,
+}
+
+impl ContextSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContextSource::WorkingMemory => "working_memory",
+            ContextSource::Episodic => "episodic",
+            ContextSource::Vector => "vector",
+        }
+    }
+
+    const ALL: [ContextSource; 3] = [ContextSource::WorkingMemory, ContextSource::Episodic, ContextSource::Vector];
+}
+
+/// A chunk a provider offers for a query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextCandidate {
+    pub id: String,
+    pub source: ContextSource,
+    pub text: String,
+    /// Relevance to the query, 0.0-1.0
+    pub relevance: f64,
+    pub timestamp: u64,
+}
+
+/// Where an included chunk came from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Provenance {
+    pub source: ContextSource,
+    /// Originating ids; several when the chunk is a summary
+    pub ids: Vec<String>,
+    pub relevance: f64,
+    /// 0 for verbatim chunks, 1+ for summaries (2 = summary of summaries)
+    pub summary_level: u32,
+}
+
+/// A chunk included in the assembled context
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextChunk {
+    pub text: String,
+    pub tokens: usize,
+    pub provenance: Provenance,
+}
+
+/// Context assembled for a query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetrievedContext {
+    pub query: String,
+    pub max_tokens: usize,
+    pub tokens_used: usize,
+    pub chunks: Vec<ContextChunk>,
+    /// Candidates that did not fit, even summarized
+    pub dropped: Vec<String>,
+}
+
+impl RetrievedContext {
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    /// Render as prompt text, each chunk tagged with its source
+    pub fn render(&self) -> String {
+        self.chunks
+            .iter()
+            .map(|chunk| {
+                let kind = if chunk.provenance.summary_level > 0 { " summary" } else { "" };
+                format!("[{}{}] {}", chunk.provenance.source.as_str(), kind, chunk.text)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// How the token budget is split across sources; unused share flows to the others
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextBudget {
+    pub max_tokens: usize,
+    pub working_memory_share: f64,
+    pub episodic_share: f64,
+    pub vector_share: f64,
+    /// Share held back for summaries of what does not fit verbatim
+    pub summary_share: f64,
+    /// Candidates below this relevance are never included
+    pub min_relevance: f64,
+}
+
+impl Default for ContextBudget {
+    fn default() -> Self {
+        Self {
+            max_tokens: 1500,
+            working_memory_share: 0.2,
+            episodic_share: 0.3,
+            vector_share: 0.5,
+            summary_share: 0.25,
+            min_relevance: 0.05,
+        }
+    }
+}
+
+impl ContextBudget {
+    fn share(&self, source: ContextSource) -> f64 {
+        let share = match source {
+            ContextSource::WorkingMemory => self.working_memory_share,
+            ContextSource::Episodic => self.episodic_share,
+            ContextSource::Vector => self.vector_share,
+        };
+        share.max(0.0)
+    }
+
+    /// Tokens for verbatim chunks
+    fn verbatim_tokens(&self) -> usize {
+        self.max_tokens - (self.max_tokens as f64 * self.summary_share.clamp(0.0, 1.0)) as usize
+    }
+
+    /// Verbatim tokens reserved for a source
+    fn allocation(&self, source: ContextSource) -> usize {
+        let total: f64 = ContextSource::ALL.iter().map(|s| self.share(*s)).sum();
+        if total <= 0.0 {
+            return self.verbatim_tokens() / ContextSource::ALL.len();
+        }
+        (self.verbatim_tokens() as f64 * self.share(source) / total) as usize
+    }
+}
+
+// Trait for context sources to avoid circular dependency (implemented over the brain)
+#[async_trait::async_trait]
+pub trait ContextProvider: Send + Sync {
+    /// Candidates for a query from one source, best first
+    async fn candidates(
+        &self,
+        source: ContextSource,
+        query: &str,
+        query_embedding: Option<&[f32]>,
+        limit: usize,
+    ) -> std::result::Result<Vec<ContextCandidate>, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+/// Approximate token count (about four characters per token)
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// Share of query terms found in a text, for sources without embeddings
+pub fn keyword_relevance(query: &str, text: &str) -> f64 {
+    let terms: HashSet<String> = query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|term| term.len() > 2)
+        .map(|term| term.to_lowercase())
+        .collect();
+    if terms.is_empty() {
+        return 0.0;
+    }
+    let text = text.to_lowercase();
+    let found = terms.iter().filter(|term| text.contains(term.as_str())).count();
+    found as f64 / terms.len() as f64
+}
+
+/// Cut a text to roughly `tokens` tokens on a character boundary
+fn truncate_to_tokens(text: &str, tokens: usize) -> String {
+    let max_chars = tokens * 4;
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(max_chars.saturating_sub(3)).collect();
+    truncated.push_str("...");
+    truncated
+}
+
+/// Assembles budgeted context from a provider
+pub struct ContextRetriever {
+    provider: Arc<dyn ContextProvider>,
+    budget: ContextBudget,
+}
+
+impl ContextRetriever {
+    pub fn new(provider: Arc<dyn ContextProvider>, budget: ContextBudget) -> Self {
+        Self { provider, budget }
+    }
+
+    pub fn budget(&self) -> &ContextBudget {
+        &self.budget
+    }
+
+    /// Retrieve context for a query within `max_tokens` (or the configured budget)
+    ///
+    /// Each source first fills its own share with its most relevant chunks, then
+    /// unused share goes to the best remaining chunks of any source. What still
+    /// does not fit is summarized in groups, and the summaries again if needed,
+    /// into the rest of the budget, or dropped.
+    pub async fn retrieve(
+        &self,
+        llm_manager: &LLMManager,
+        query: &str,
+        max_tokens: Option<usize>,
+    ) -> Result<RetrievedContext> {
+        if query.is_empty() {
+            return Err(LLMError::InvalidResponse("Query cannot be empty".to_string()));
+        }
+        if query.len() > 10000 {
+            return Err(LLMError::InvalidResponse("Query too long (max 10000 chars)".to_string()));
+        }
+
+        let budget = ContextBudget {
+            max_tokens: max_tokens.unwrap_or(self.budget.max_tokens).min(100_000),
+            ..self.budget.clone()
+        };
+
+        // Without an embedding provider, sources fall back to keyword matching
+        let query_embedding = match llm_manager.generate_embedding(query, None).await {
+            Ok(embedding) => Some(embedding),
+            Err(e) => {
+                tracing::debug!("Context retrieval without query embedding: {}", e);
+                None
+            }
+        };
+
+        let mut seen = HashSet::new();
+        let mut per_source: Vec<(ContextSource, Vec<ContextCandidate>)> = Vec::new();
+        for source in ContextSource::ALL {
+            let mut candidates = self
+                .provider
+                .candidates(source, query, query_embedding.as_deref(), CANDIDATES_PER_SOURCE)
+                .await
+                .map_err(|e| LLMError::BrainIntegration(e.to_string()))?;
+            candidates.retain(|c| c.relevance >= budget.min_relevance && !c.text.is_empty() && seen.insert(c.id.clone()));
+            candidates.sort_by(|a, b| b.relevance.partial_cmp(&a.relevance).unwrap_or(std::cmp::Ordering::Equal));
+            per_source.push((source, candidates));
+        }
+
+        let mut chunks = Vec::new();
+        let mut used = 0usize;
+
+        // Each source fills its own allocation first
+        let mut overflow: Vec<ContextCandidate> = Vec::new();
+        for (source, candidates) in per_source {
+            let allocation = budget.allocation(source);
+            let mut source_used = 0usize;
+            for candidate in candidates {
+                let tokens = estimate_tokens(&candidate.text);
+                if source_used + tokens <= allocation {
+                    source_used += tokens;
+                    chunks.push(verbatim(candidate, tokens));
+                } else {
+                    overflow.push(candidate);
+                }
+            }
+            used += source_used;
+        }
+
+        // Unused allocation goes to the best remaining chunks of any source
+        overflow.sort_by(|a, b| b.relevance.partial_cmp(&a.relevance).unwrap_or(std::cmp::Ordering::Equal));
+        let mut remaining = Vec::new();
+        for candidate in overflow {
+            let tokens = estimate_tokens(&candidate.text);
+            if used + tokens <= budget.verbatim_tokens() {
+                used += tokens;
+                chunks.push(verbatim(candidate, tokens));
+            } else {
+                remaining.push(candidate);
+            }
+        }
+
+        // Summarize what is left, per source, into what is left of the budget
+        let mut dropped = Vec::new();
+        for source in ContextSource::ALL {
+            let group: Vec<ContextCandidate> = remaining.iter().filter(|c| c.source == source).cloned().collect();
+            if group.is_empty() {
+                continue;
+            }
+            let available = budget.max_tokens.saturating_sub(used);
+            match self.summarize(llm_manager, source, group.clone(), available).await {
+                Some(summaries) => {
+                    used += summaries.iter().map(|s| s.tokens).sum::<usize>();
+                    chunks.extend(summaries);
+                }
+                None => dropped.extend(group.into_iter().map(|c| c.id)),
+            }
+        }
+
+        // Most relevant first, keeping verbatim chunks ahead of summaries
+        chunks.sort_by(|a, b| {
+            a.provenance.summary_level.cmp(&b.provenance.summary_level).then(
+                b.provenance.relevance.partial_cmp(&a.provenance.relevance).unwrap_or(std::cmp::Ordering::Equal),
+            )
+        });
+
+        Ok(RetrievedContext {
+            query: query.to_string(),
+            max_tokens: budget.max_tokens,
+            tokens_used: used,
+            chunks,
+            dropped,
+        })
+    }
+
+    /// Summarize candidates of one source into at most `available` tokens
+    async fn summarize(
+        &self,
+        llm_manager: &LLMManager,
+        source: ContextSource,
+        candidates: Vec<ContextCandidate>,
+        available: usize,
+    ) -> Option<Vec<ContextChunk>> {
+        let mut level: Vec<ContextChunk> = candidates
+            .into_iter()
+            .map(|c| {
+                let tokens = estimate_tokens(&c.text);
+                verbatim(c, tokens)
+            })
+            .collect();
+        if available < MIN_SUMMARY_TOKENS {
+            return None;
+        }
+
+        // Summarize groups of chunks, then groups of summaries, until they fit
+        let mut depth = 0;
+        loop {
+            depth += 1;
+            let groups = level.len().div_ceil(SUMMARY_GROUP_SIZE);
+            let target = (available / groups).max(MIN_SUMMARY_TOKENS);
+
+            let mut next = Vec::with_capacity(groups);
+            for group in level.chunks(SUMMARY_GROUP_SIZE) {
+                let texts: Vec<&str> = group.iter().map(|c| c.text.as_str()).collect();
+                let text = summarize_texts(llm_manager, &texts, target).await;
+                let tokens = estimate_tokens(&text);
+                next.push(ContextChunk {
+                    text,
+                    tokens,
+                    provenance: Provenance {
+                        source,
+                        ids: group.iter().flat_map(|c| c.provenance.ids.iter().cloned()).collect(),
+                        relevance: group.iter().map(|c| c.provenance.relevance).fold(0.0, f64::max),
+                        summary_level: depth,
+                    },
+                });
+            }
+            level = next;
+
+            if level.iter().map(|c| c.tokens).sum::<usize>() <= available {
+                return Some(level);
+            }
+            if level.len() == 1 {
+                return None;
+            }
+        }
+    }
+}
+
+fn verbatim(candidate: ContextCandidate, tokens: usize) -> ContextChunk {
+    ContextChunk {
+        text: candidate.text,
+        tokens,
+        provenance: Provenance {
+            source: candidate.source,
+            ids: vec![candidate.id],
+            relevance: candidate.relevance,
+            summary_level: 0,
+        },
+    }
+}
+
+/// Summarize with the LLM when one is configured, otherwise extract the leading text
+async fn summarize_texts(llm_manager: &LLMManager, texts: &[&str], max_tokens: usize) -> String {
+    let joined = texts.join("\n");
+    if llm_manager.has_provider() {
+        let prompt = format!(
+            "Summarize the following notes in at most {} words, keeping names, numbers and outcomes:\n\n{}",
+            max_tokens * 3 / 4,
+            truncate_to_tokens(&joined, 20_000)
+        );
+        match llm_manager
+            .complete(vec![Message { role: MessageRole::User, content: prompt }], None)
+            .await
+        {
+            Ok(summary) => return truncate_to_tokens(summary.trim(), max_tokens),
+            Err(e) => tracing::warn!("Context summarization failed, extracting instead: {}", e),
+        }
+    }
+
+    let per_text = (max_tokens / texts.len()).max(1);
+    let extract = texts
+        .iter()
+        .map(|text| truncate_to_tokens(text, per_text))
+        .collect::<Vec<_>>()
+        .join("; ");
+    truncate_to_tokens(&extract, max_tokens)
+}
@@ -0,0 +1,111 @@
+#[cfg(test)]
+mod context_tests {
+    use crate::context::*;
+    use crate::manager::LLMManager;
+    use std::sync::Arc;
+
+    /// Serves fixed candidates per source
+    struct FixedProvider {
+        candidates: Vec<ContextCandidate>,
+    }
+
+    #[async_trait::async_trait]
+    impl ContextProvider for FixedProvider {
+        async fn candidates(
+            &self,
+            source: ContextSource,
+            _query: &str,
+            _query_embedding: Option<&[f32]>,
+            limit: usize,
+        ) -> std::result::Result<Vec<ContextCandidate>, Box<dyn std::error::Error + Send + Sync>> {
+            Ok(self.candidates.iter().filter(|c| c.source == source).take(limit).cloned().collect())
+        }
+    }
+
+    fn candidate(id: &str, source: ContextSource, words: usize, relevance: f64) -> ContextCandidate {
+        ContextCandidate {
+            id: id.to_string(),
+            source,
+            text: format!("{} {}", id, "word ".repeat(words)).trim().to_string(),
+            relevance,
+            timestamp: 0,
+        }
+    }
+
+    fn manager(candidates: Vec<ContextCandidate>, max_tokens: usize) -> LLMManager {
+        let budget = ContextBudget { max_tokens, ..ContextBudget::default() };
+        LLMManager::new().with_context_provider(Arc::new(FixedProvider { candidates }), budget)
+    }
+
+    #[test]
+    fn test_keyword_relevance_and_tokens() {
+        assert_eq!(keyword_relevance("red cup on table", "The RED cup"), 2.0 / 3.0);
+        assert_eq!(keyword_relevance("a", "anything"), 0.0);
+        assert_eq!(estimate_tokens("abcdefgh"), 2);
+        assert_eq!(estimate_tokens("abcdefghi"), 3);
+    }
+
+    #[tokio::test]
+    async fn test_fits_budget_with_provenance() {
+        let manager = manager(vec![
+            candidate("goal", ContextSource::WorkingMemory, 4, 0.9),
+            candidate("visit", ContextSource::Episodic, 4, 0.6),
+            candidate("noise", ContextSource::Episodic, 4, 0.01),
+            candidate("fact", ContextSource::Vector, 4, 0.8),
+            candidate("goal", ContextSource::Vector, 4, 0.9),
+        ], 1000);
+
+        let context = manager.retrieve_context("where is the cup", None).await.unwrap();
+        let ids: Vec<&str> = context.chunks.iter().map(|c| c.provenance.ids[0].as_str()).collect();
+        // Below min relevance and duplicate ids are left out; best first
+        assert_eq!(ids, vec!["goal", "fact", "visit"]);
+        assert_eq!(context.chunks[0].provenance.source, ContextSource::WorkingMemory);
+        assert!(context.chunks.iter().all(|c| c.provenance.summary_level == 0));
+        assert_eq!(context.tokens_used, context.chunks.iter().map(|c| c.tokens).sum::<usize>());
+        assert!(context.dropped.is_empty());
+        assert!(context.render().starts_with("[working_memory] goal"));
+    }
+
+    #[tokio::test]
+    async fn test_unused_share_flows_to_other_sources() {
+        // 26 tokens each; vector's own share of the 75 verbatim tokens fits one
+        let candidates = (0..4).map(|i| candidate(&format!("v{}", i), ContextSource::Vector, 20, 0.9 - i as f64 * 0.1)).collect();
+        let manager = manager(candidates, 100);
+
+        let context = manager.retrieve_context("anything", None).await.unwrap();
+        assert!(context.tokens_used <= 100);
+        let ids: Vec<Vec<String>> = context.chunks.iter().map(|c| c.provenance.ids.clone()).collect();
+        assert_eq!(ids, vec![vec!["v0".to_string()], vec!["v1".to_string()], vec!["v2".to_string(), "v3".to_string()]]);
+        assert_eq!(context.chunks[2].provenance.summary_level, 1);
+        assert_eq!(context.chunks[2].provenance.relevance, 0.7);
+    }
+
+    #[tokio::test]
+    async fn test_overflow_is_summarized_hierarchically() {
+        let candidates = (0..40).map(|i| candidate(&format!("e{}", i), ContextSource::Episodic, 5, 0.5)).collect();
+        let manager = manager(candidates, 120);
+
+        let context = manager.retrieve_context("what happened", None).await.unwrap();
+        assert!(context.tokens_used <= 120);
+        let summaries: Vec<&ContextChunk> = context.chunks.iter().filter(|c| c.provenance.summary_level > 0).collect();
+        assert!(!summaries.is_empty());
+        // First-level summaries of the overflow do not fit, so they are summarized again
+        assert!(summaries.iter().any(|c| c.provenance.summary_level == 2));
+
+        // Every candidate is accounted for, verbatim, summarized or dropped
+        let mut covered: Vec<String> = context.chunks.iter().flat_map(|c| c.provenance.ids.clone()).collect();
+        covered.extend(context.dropped.clone());
+        covered.sort();
+        covered.dedup();
+        assert_eq!(covered.len(), 40);
+    }
+
+    #[tokio::test]
+    async fn test_retrieval_requires_provider() {
+        let manager = LLMManager::new();
+        assert!(manager.retrieve_context("anything", None).await.is_err());
+        let with_provider = self::manager(vec![], 100);
+        assert!(with_provider.retrieve_context("", None).await.is_err());
+        assert!(with_provider.retrieve_context("anything", None).await.unwrap().is_empty());
+    }
+}
@@ -3,6 +3,7 @@ pub mod error;
 pub mod manager;
 pub mod providers;
 pub mod rag;
+pub mod context;
 pub mod function_calling;
 pub mod reasoning;
 pub mod planning;
@@ -20,6 +21,8 @@ mod function_calling_tests;
 mod reasoning_tests;
 #[cfg(test)]
 mod planning_tests;
+#[cfg(test)]
+mod context_tests;
 
 pub use config::*;
 pub use error::*;
@@ -27,6 +30,7 @@ pub use manager::LLMManager;
 pub use providers::Provider;
 pub use rag::{Memory as RAGMemory, BrainInterface};
 pub use function_calling::BrainFunctionInterface;
+pub use context::{ContextBudget, ContextCandidate, ContextChunk, ContextProvider, ContextSource, Provenance, RetrievedContext};
 
 #[cfg(test)]
 mod tests {
@@ -3,6 +3,7 @@ use crate::error::{LLMError, Result};
 use crate::providers::trait_impl::Provider as ProviderTrait;
 use crate::providers::{openai::OpenAIProvider, anthropic::AnthropicProvider, google::GoogleProvider, cohere::CohereProvider};
 use crate::rag::{RAGSystem, BrainInterface};
+use crate::context::{ContextBudget, ContextProvider, ContextRetriever, RetrievedContext};
 use crate::function_calling::{FunctionCallingSystem, BrainFunction, BrainFunctionInterface};
 use crate::reasoning::ReasoningSystem;
 use crate::planning::PlanningSystem;
@@ -13,11 +14,12 @@ use parking_lot::RwLock;
 use std::env;
 
 pub struct LLMManager {
-    providers: Arc<RwLock<HashMap<Provider, ProviderBox>>>,
+    providers: Arc<RwLock<HashMap<Provider, Arc<ProviderBox>>>>,
     default_provider: Arc<RwLock<Option<Provider>>>,
     config: Arc<RwLock<LLMConfig>>,
     rag: Option<RAGSystem>,
     function_calling: Option<FunctionCallingSystem>,
+    context: Option<ContextRetriever>,
     reasoning: ReasoningSystem,
     planning: PlanningSystem,
     cache: Arc<ResponseCache>,
@@ -105,6 +107,17 @@ impl ProviderTrait for ProviderBox {
     }
 }
 
+impl ProviderBox {
+    fn base_url(&self) -> &str {
+        match self {
+            ProviderBox::OpenAI(p) => p.base_url(),
+            ProviderBox::Anthropic(p) => p.base_url(),
+            ProviderBox::Google(p) => p.base_url(),
+            ProviderBox::Cohere(p) => p.base_url(),
+        }
+    }
+}
+
 impl LLMManager {
     pub fn new() -> Self {
         let mut manager = Self {
@@ -113,6 +126,7 @@ impl LLMManager {
             config: Arc::new(RwLock::new(LLMConfig::default())),
             rag: None,
             function_calling: None,
+            context: None,
             reasoning: ReasoningSystem::new(),
             planning: PlanningSystem::new(),
             cache: Arc::new(ResponseCache::new(1000)),
@@ -132,6 +146,12 @@ impl LLMManager {
         manager
     }
 
+    /// Attach a context provider; chat then prepends budgeted context automatically
+    pub fn with_context_provider(mut self, provider: Arc<dyn ContextProvider>, budget: ContextBudget) -> Self {
+        self.context = Some(ContextRetriever::new(provider, budget));
+        self
+    }
+
     fn initialize_from_env(&mut self) {
         // Try to load API keys from environment
         for provider in [Provider::OpenAI, Provider::Anthropic, Provider::Google, Provider::Cohere] {
@@ -183,22 +203,22 @@ impl LLMManager {
             Provider::OpenAI => {
                 let mut p = OpenAIProvider::new();
                 p.set_api_key(key);
-                providers.insert(provider, ProviderBox::OpenAI(p));
+                providers.insert(provider, Arc::new(ProviderBox::OpenAI(p)));
             }
             Provider::Anthropic => {
                 let mut p = AnthropicProvider::new();
                 p.set_api_key(key);
-                providers.insert(provider, ProviderBox::Anthropic(p));
+                providers.insert(provider, Arc::new(ProviderBox::Anthropic(p)));
             }
             Provider::Google => {
                 let mut p = GoogleProvider::new();
                 p.set_api_key(key);
-                providers.insert(provider, ProviderBox::Google(p));
+                providers.insert(provider, Arc::new(ProviderBox::Google(p)));
             }
             Provider::Cohere => {
                 let mut p = CohereProvider::new();
                 p.set_api_key(key);
-                providers.insert(provider, ProviderBox::Cohere(p));
+                providers.insert(provider, Arc::new(ProviderBox::Cohere(p)));
             }
         }
 
@@ -208,6 +228,20 @@ impl LLMManager {
         }
     }
 
+    /// Configured providers and the endpoints they call
+    pub fn provider_endpoints(&self) -> Vec<(Provider, String)> {
+        let mut endpoints: Vec<(Provider, String)> = self.providers.read().iter()
+            .map(|(provider, p)| (*provider, p.base_url().to_string()))
+            .collect();
+        endpoints.sort_by_key(|(provider, _)| provider.as_str());
+        endpoints
+    }
+
+    /// Whether any provider is configured
+    pub fn has_provider(&self) -> bool {
+        self.default_provider.read().is_some()
+    }
+
     /// Get the provider to use (default or specified)
     fn get_provider(&self, provider: Option<Provider>) -> Result<Provider> {
         let provider = provider.or_else(|| *self.default_provider.read());
@@ -217,11 +251,59 @@ impl LLMManager {
         })
     }
 
-    /// Chat completion
+    /// Chat completion, with retrieved context when a context provider is attached
     pub async fn chat(
         &self,
         messages: Vec<Message>,
         provider: Option<Provider>,
+    ) -> Result<String> {
+        let messages = self.with_retrieved_context(messages).await;
+        self.complete(messages, provider).await
+    }
+
+    /// Retrieve budgeted context for a query, with provenance for each chunk
+    pub async fn retrieve_context(&self, query: &str, max_tokens: Option<usize>) -> Result<RetrievedContext> {
+        let retriever = self.context.as_ref()
+            .ok_or_else(|| LLMError::BrainIntegration("No context provider configured".to_string()))?;
+        retriever.retrieve(self, query, max_tokens).await
+    }
+
+    /// Prepend context for the latest user message as a system message
+    async fn with_retrieved_context(&self, mut messages: Vec<Message>) -> Vec<Message> {
+        let Some(retriever) = self.context.as_ref() else {
+            return messages;
+        };
+        let Some(query) = messages.iter().rev().find(|m| m.role == MessageRole::User).map(|m| m.content.clone()) else {
+            return messages;
+        };
+        match retriever.retrieve(self, &query, None).await {
+            Ok(context) if !context.is_empty() => {
+                messages.insert(0, Message {
+                    role: MessageRole::System,
+                    content: format!("Relevant context:\n{}", context.render()),
+                });
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!("Context retrieval failed, chatting without it: {}", e),
+        }
+        messages
+    }
+
+    /// Configured provider, cloned out so no lock is held across a request
+    fn provider_box(&self, provider: Option<Provider>) -> Result<Arc<ProviderBox>> {
+        let provider = self.get_provider(provider)?;
+        self.providers
+            .read()
+            .get(&provider)
+            .cloned()
+            .ok_or_else(|| LLMError::MissingApiKey(format!("Provider {:?} not configured", provider)))
+    }
+
+    /// Chat completion without retrieved context
+    pub async fn complete(
+        &self,
+        messages: Vec<Message>,
+        provider: Option<Provider>,
     ) -> Result<String> {
         // Input validation and security checks
         if messages.is_empty() {
@@ -252,7 +334,7 @@ impl LLMManager {
             }
         }
         
-        let config = self.config.read();
+        let config = self.config.read().clone();
         
         // Create cache key more safely (avoid Debug format which could be huge)
         // Use a hash-based approach instead
@@ -271,11 +353,7 @@ impl LLMManager {
             }
         }
 
-        let provider = self.get_provider(provider)?;
-        let providers = self.providers.read();
-        let provider_box = providers
-            .get(&provider)
-            .ok_or_else(|| LLMError::MissingApiKey(format!("Provider {:?} not configured", provider)))?;
+        let provider_box = self.provider_box(provider)?;
         
         let request = ChatRequest {
             messages,
@@ -311,11 +389,7 @@ impl LLMManager {
             return Err(LLMError::InvalidResponse("Text too long for embedding (max 8000 chars)".to_string()));
         }
         
-        let provider = self.get_provider(provider)?;
-        let providers = self.providers.read();
-        let provider_box = providers
-            .get(&provider)
-            .ok_or_else(|| LLMError::MissingApiKey(format!("Provider {:?} not configured", provider)))?;
+        let provider_box = self.provider_box(provider)?;
         
         let request = EmbeddingRequest {
             input: vec![text.to_string()],
@@ -391,10 +465,7 @@ impl LLMManager {
         provider: Option<Provider>,
     ) -> Result<String> {
         let provider = self.get_provider(provider)?;
-        let providers = self.providers.read();
-        let provider_box = providers
-            .get(&provider)
-            .ok_or_else(|| LLMError::MissingApiKey(format!("Provider {:?} not configured", provider)))?;
+        let provider_box = self.provider_box(Some(provider))?;
         
         let function_defs: Vec<FunctionDefinition> = functions
             .iter()
@@ -2,6 +2,7 @@ use async_trait::async_trait;
 use crate::config::*;
 use crate::error::{LLMError, Result};
 use crate::providers::trait_impl::Provider as ProviderTrait;
+use narayana_core::egress::{self, EgressSubsystem};
 use reqwest::Client;
 use serde_json::json;
 use std::sync::Arc;
@@ -22,6 +23,11 @@ impl AnthropicProvider {
         }
     }
 
+    /// API endpoint requests are sent to
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
     pub fn with_api_key(api_key: String) -> Self {
         let mut provider = Self::new();
         provider.set_api_key(api_key);
@@ -133,9 +139,8 @@ impl ProviderTrait for AnthropicProvider {
         }
 
         // Validate base_url
-        if !self.base_url.starts_with("https://") {
-            return Err(LLMError::InvalidResponse("Invalid base URL".to_string()));
-        }
+        egress::check_url(EgressSubsystem::Llm, &self.base_url)
+            .map_err(|e| LLMError::InvalidResponse(format!("Invalid base URL: {}", e)))?;
         
         let response = self
             .client
@@ -2,6 +2,7 @@ use async_trait::async_trait;
 use crate::config::*;
 use crate::error::{LLMError, Result};
 use crate::providers::trait_impl::Provider as ProviderTrait;
+use narayana_core::egress::{self, EgressSubsystem};
 use reqwest::Client;
 use serde_json::json;
 use std::sync::Arc;
@@ -22,6 +23,11 @@ impl GoogleProvider {
         }
     }
 
+    /// API endpoint requests are sent to
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
     pub fn with_api_key(api_key: String) -> Self {
         let mut provider = Self::new();
         provider.set_api_key(api_key);
@@ -86,9 +92,8 @@ impl ProviderTrait for GoogleProvider {
         }
 
         // Validate base_url to prevent SSRF
-        if !self.base_url.starts_with("https://") {
-            return Err(LLMError::InvalidResponse("Invalid base URL".to_string()));
-        }
+        egress::check_url(EgressSubsystem::Llm, &self.base_url)
+            .map_err(|e| LLMError::InvalidResponse(format!("Invalid base URL: {}", e)))?;
         
         // URL encode model name to prevent injection
         let model_encoded = urlencoding::encode(&model);
@@ -158,9 +163,8 @@ impl ProviderTrait for GoogleProvider {
         });
 
         // Validate base_url
-        if !self.base_url.starts_with("https://") {
-            return Err(LLMError::InvalidResponse("Invalid base URL".to_string()));
-        }
+        egress::check_url(EgressSubsystem::Llm, &self.base_url)
+            .map_err(|e| LLMError::InvalidResponse(format!("Invalid base URL: {}", e)))?;
         
         // URL encode model name to prevent injection
         let model_encoded = urlencoding::encode(&model);
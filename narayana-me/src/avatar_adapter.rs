@@ -1,6 +1,6 @@
 //! Avatar adapter for narayana-wld integration
 
-use crate::config::AvatarConfig;
+use crate::config::{AvatarConfig, Emotion};
 use crate::error::AvatarError;
 use crate::avatar_broker::AvatarBroker;
 use narayana_wld::protocol_adapters::ProtocolAdapter;
@@ -20,6 +20,7 @@ pub struct AvatarAdapter {
     event_sender: Arc<SyncRwLock<Option<broadcast::Sender<WorldEvent>>>>,  // Sync for subscribe_events
     is_running: Arc<RwLock<bool>>,
     processing_handle: Arc<RwLock<Option<tokio::task::JoinHandle<()>>>>,
+    emotion_handle: Arc<RwLock<Option<tokio::task::JoinHandle<()>>>>,
 }
 
 impl AvatarAdapter {
@@ -38,10 +39,40 @@ impl AvatarAdapter {
             event_sender: Arc::new(SyncRwLock::new(None)),
             is_running: Arc::new(RwLock::new(false)),
             processing_handle: Arc::new(RwLock::new(None)),
+            emotion_handle: Arc::new(RwLock::new(None)),
         })
     }
 }
 
+/// Intensity change that re-sends an unchanged emotion
+const EMOTION_INTENSITY_STEP: f64 = 0.1;
+
+/// Mirror the shared emotion state on the avatar's expression
+fn follow_emotion(
+    avatar: Arc<RwLock<AvatarBroker>>,
+    mut updates: broadcast::Receiver<narayana_wld::emotion::EmotionUpdate>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut shown = (Emotion::Neutral, 0.0);
+        loop {
+            let update = match updates.recv().await {
+                Ok(update) => update,
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+            let (emotion, intensity) = Emotion::from_state(&update.state);
+            if emotion == shown.0 && (intensity - shown.1).abs() < EMOTION_INTENSITY_STEP {
+                continue;
+            }
+            let avatar = avatar.read().await;
+            match avatar.update_emotion(emotion.clone(), intensity).await {
+                Ok(()) => shown = (emotion, intensity),
+                Err(e) => debug!("Failed to show emotion on avatar: {}", e),
+            }
+        }
+    })
+}
+
 #[async_trait]
 impl ProtocolAdapter for AvatarAdapter {
     fn protocol_name(&self) -> &str {
@@ -113,6 +144,10 @@ impl ProtocolAdapter for AvatarAdapter {
 
         *self.processing_handle.write().await = Some(handle);
 
+        // Expression follows the shared emotion state
+        let emotion_handle = follow_emotion(Arc::clone(&self.broker), broker.emotion_bus().subscribe());
+        *self.emotion_handle.write().await = Some(emotion_handle);
+
         // Start avatar stream if enabled (clone Arc before await)
         {
             let broker_arc = Arc::clone(&self.broker);
@@ -190,6 +225,10 @@ impl ProtocolAdapter for AvatarAdapter {
             // Note: We ignore timeout errors here - task might take longer, but abort() should stop it
         }
 
+        if let Some(handle) = self.emotion_handle.write().await.take() {
+            handle.abort();
+        }
+
         // Clear event sender
         *self.event_sender.write() = None;
 
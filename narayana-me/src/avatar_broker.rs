@@ -1,12 +1,13 @@
 //! Avatar broker - unified API for avatar providers
 
+use crate::capabilities::{GestureFallback, ProviderCapabilities};
 use crate::config::{AvatarConfig, Expression, Gesture, Emotion};
 use crate::error::AvatarError;
 use async_trait::async_trait;
 use bytes::Bytes;
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
 
 /// Avatar stream information
 pub struct AvatarStream {
@@ -38,12 +39,23 @@ pub trait AvatarProvider: Send + Sync {
     fn supports_audio_input(&self) -> bool { false }
     /// Check if provider supports TTS
     fn supports_tts(&self) -> bool { false }
+    /// What this provider can render; the broker degrades other requests
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            vision: self.supports_vision(),
+            audio_input: self.supports_audio_input(),
+            tts: self.supports_tts(),
+            ..ProviderCapabilities::full()
+        }
+    }
 }
 
 /// Avatar broker - unified API facade for avatar providers
 pub struct AvatarBroker {
     provider_type: crate::config::AvatarProviderType,
     provider: Arc<RwLock<Option<Arc<RwLock<Box<dyn AvatarProvider>>>>>>,
+    /// Queried from the provider at initialization
+    capabilities: Arc<RwLock<Option<ProviderCapabilities>>>,
     stream: Arc<RwLock<Option<AvatarStream>>>,
     config: Arc<AvatarConfig>,
 }
@@ -55,6 +67,7 @@ impl AvatarBroker {
         Ok(Self {
             provider_type: config.provider.clone(),
             provider: Arc::new(RwLock::new(None)),
+            capabilities: Arc::new(RwLock::new(None)),
             stream: Arc::new(RwLock::new(None)),
             config: Arc::new(config),
         })
@@ -76,18 +89,33 @@ impl AvatarBroker {
         }
 
         let provider = self.create_provider().await?;
+        self.attach_provider(provider).await?;
+        info!("Avatar provider initialized: {:?}", self.provider_type);
+        Ok(())
+    }
+
+    /// Initialize a provider, record its capabilities and make it current
+    async fn attach_provider(&self, provider: Box<dyn AvatarProvider>) -> Result<(), AvatarError> {
         let provider_arc = Arc::new(RwLock::new(provider));
         
-        {
+        let capabilities = {
             let mut provider_guard = provider_arc.write().await;
             provider_guard.initialize(&self.config).await?;
-        }
+            provider_guard.capabilities()
+        };
+        info!("Avatar provider {:?} capabilities: lip sync {}, {} expressions, {} gestures",
+            self.provider_type, capabilities.lip_sync, capabilities.expressions.len(), capabilities.gestures.len());
         
+        *self.capabilities.write().await = Some(capabilities);
         *self.provider.write().await = Some(provider_arc);
-        info!("Avatar provider initialized: {:?}", self.provider_type);
         Ok(())
     }
 
+    /// Capabilities of the initialized provider
+    pub async fn capabilities(&self) -> Option<ProviderCapabilities> {
+        self.capabilities.read().await.clone()
+    }
+
     /// Start the avatar stream
     pub async fn start_stream(&self) -> Result<String, AvatarError> {
         // Check if stream already started (idempotent)
@@ -155,6 +183,11 @@ impl AvatarBroker {
             return Ok(());
         }
 
+        if self.capabilities.read().await.as_ref().is_some_and(|c| !c.lip_sync) {
+            debug!("Provider {:?} has no lip sync, ignoring audio", self.provider_type);
+            return Ok(());
+        }
+
         // Validate audio data size
         const MAX_AUDIO_SIZE: usize = 10 * 1024 * 1024; // 10MB max
         if audio_data.len() > MAX_AUDIO_SIZE {
@@ -186,6 +219,22 @@ impl AvatarBroker {
         let intensity = intensity.clamp(-10.0, 10.0);
         let intensity = (intensity * self.config.expression_sensitivity).clamp(0.0, 1.0);
 
+        let expression = match self.capabilities.read().await.as_ref() {
+            Some(capabilities) => match capabilities.degrade_expression(expression.clone()) {
+                Some(supported) => {
+                    if supported != expression {
+                        debug!("Provider {:?} lacks expression {:?}, using {:?}", self.provider_type, expression, supported);
+                    }
+                    supported
+                }
+                None => {
+                    debug!("Provider {:?} has no expression like {:?}, skipping", self.provider_type, expression);
+                    return Ok(());
+                }
+            },
+            None => expression,
+        };
+
         let provider_arc = {
             let provider_guard = self.provider.read().await;
             provider_guard.as_ref().map(Arc::clone)
@@ -209,6 +258,27 @@ impl AvatarBroker {
         const MAX_GESTURE_DURATION_MS: u64 = 300_000; // 5 minutes max
         let duration_ms = duration_ms.min(MAX_GESTURE_DURATION_MS);
 
+        let fallback = match self.capabilities.read().await.as_ref() {
+            Some(capabilities) => capabilities.degrade_gesture(gesture.clone()),
+            None => GestureFallback::Gesture(gesture.clone()),
+        };
+        let gesture = match fallback {
+            GestureFallback::Gesture(supported) => {
+                if supported != gesture {
+                    debug!("Provider {:?} lacks gesture {:?}, using {:?}", self.provider_type, gesture, supported);
+                }
+                supported
+            }
+            GestureFallback::Expression(expression) => {
+                debug!("Provider {:?} lacks gesture {:?}, showing {:?} instead", self.provider_type, gesture, expression);
+                return self.set_expression(expression, 1.0).await;
+            }
+            GestureFallback::Skip => {
+                debug!("Provider {:?} has nothing like gesture {:?}, skipping", self.provider_type, gesture);
+                return Ok(());
+            }
+        };
+
         let provider_arc = {
             let provider_guard = self.provider.read().await;
             provider_guard.as_ref().map(Arc::clone)
@@ -291,6 +361,8 @@ mod tests {
     struct MockProvider {
         initialized: bool,
         stream_started: bool,
+        capabilities: ProviderCapabilities,
+        calls: Arc<parking_lot::Mutex<Vec<String>>>,
     }
 
     #[async_trait]
@@ -318,14 +390,17 @@ mod tests {
         }
 
         async fn send_audio(&self, _audio_data: Vec<u8>) -> Result<(), AvatarError> {
+            self.calls.lock().push("audio".to_string());
             Ok(())
         }
 
-        async fn set_expression(&self, _expression: Expression, _intensity: f64) -> Result<(), AvatarError> {
+        async fn set_expression(&self, expression: Expression, _intensity: f64) -> Result<(), AvatarError> {
+            self.calls.lock().push(format!("expression:{:?}", expression));
             Ok(())
         }
 
-        async fn set_gesture(&self, _gesture: Gesture, _duration_ms: u64) -> Result<(), AvatarError> {
+        async fn set_gesture(&self, gesture: Gesture, _duration_ms: u64) -> Result<(), AvatarError> {
+            self.calls.lock().push(format!("gesture:{:?}", gesture));
             Ok(())
         }
 
@@ -333,9 +408,68 @@ mod tests {
             Ok(())
         }
 
+        async fn send_video_frame(&self, _frame_data: Vec<u8>, _width: u32, _height: u32) -> Result<(), AvatarError> {
+            Ok(())
+        }
+
+        async fn get_audio_output(&self) -> Result<Option<Vec<u8>>, AvatarError> {
+            Ok(None)
+        }
+
         fn provider_name(&self) -> &str {
             "MockProvider"
         }
+
+        fn capabilities(&self) -> ProviderCapabilities {
+            self.capabilities.clone()
+        }
+    }
+
+    async fn broker_with(capabilities: ProviderCapabilities) -> (AvatarBroker, Arc<parking_lot::Mutex<Vec<String>>>) {
+        let config = AvatarConfig { expression_sensitivity: 1.0, ..AvatarConfig::default() };
+        let broker = AvatarBroker::new(config).unwrap();
+        let calls = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let provider = MockProvider {
+            initialized: false,
+            stream_started: false,
+            capabilities,
+            calls: Arc::clone(&calls),
+        };
+        broker.attach_provider(Box::new(provider)).await.unwrap();
+        (broker, calls)
+    }
+
+    #[tokio::test]
+    async fn test_broker_degrades_unsupported_requests() {
+        let (broker, calls) = broker_with(ProviderCapabilities::talking_head()).await;
+        assert_eq!(broker.capabilities().await, Some(ProviderCapabilities::talking_head()));
+        broker.set_gesture(Gesture::Shake, 500).await.unwrap();
+        // No hands: a thumbs up becomes a nod
+        broker.set_gesture(Gesture::ThumbsUp, 500).await.unwrap();
+        broker.set_gesture(Gesture::Custom("bow".to_string()), 500).await.unwrap();
+        assert_eq!(*calls.lock(), vec!["gesture:Shake", "gesture:Nod"]);
+
+        let capabilities = ProviderCapabilities {
+            expressions: vec![Expression::Neutral, Expression::Happy, Expression::Sad],
+            gestures: Vec::new(),
+            custom_gestures: false,
+            ..ProviderCapabilities::full()
+        };
+        let (broker, calls) = broker_with(capabilities).await;
+        broker.set_expression(Expression::Excited, 0.8).await.unwrap();
+        broker.update_emotion(Emotion::Thinking, 0.5).await.unwrap();
+        // No gestures at all: a wave is conveyed with a smile
+        broker.set_gesture(Gesture::Wave, 500).await.unwrap();
+        broker.set_gesture(Gesture::Point, 500).await.unwrap();
+        assert_eq!(*calls.lock(), vec!["expression:Happy", "expression:Neutral", "expression:Happy"]);
+
+        let (broker, calls) = broker_with(ProviderCapabilities::lip_sync_only()).await;
+        broker.set_expression(Expression::Angry, 1.0).await.unwrap();
+        broker.send_audio(vec![1, 2, 3]).await.unwrap();
+        assert_eq!(*calls.lock(), vec!["audio"]);
+        let (broker, calls) = broker_with(ProviderCapabilities { lip_sync: false, ..ProviderCapabilities::full() }).await;
+        broker.send_audio(vec![1, 2, 3]).await.unwrap();
+        assert!(calls.lock().is_empty());
     }
 
     #[tokio::test]
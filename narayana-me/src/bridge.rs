@@ -1,15 +1,21 @@
 //! WebSocket bridge for streaming avatar to web clients
 
 use crate::avatar_broker::AvatarBroker;
-use crate::multimodal::MultimodalManager;
+use crate::captions::{self, CaptionConfig, CaptionCue, WordTiming};
+use crate::multimodal::{MultimodalManager, TTSAudio};
+use crate::timeline::TimelineEvent;
 #[cfg(feature = "llm")]
 use narayana_llm::LLMManager;
 use axum::extract::ws::{Message, WebSocket};
-use axum::extract::{State, WebSocketUpgrade};
+use axum::extract::{Query, State, WebSocketUpgrade};
 use axum::response::Response;
 use axum::routing::get;
 use axum::Router;
+use narayana_core::wire::{WireFormat, WireFrame, WireTagged};
 use narayana_core::Error;
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use tokio::sync::{broadcast, RwLock};
 use tracing::{info, debug, warn, error};
@@ -18,11 +24,14 @@ use uuid::Uuid;
 /// WebSocket bridge for avatar streaming
 pub struct AvatarBridge {
     broker: Arc<RwLock<AvatarBroker>>,
-    clients: Arc<RwLock<Vec<broadcast::Sender<BridgeMessage>>>>,
+    clients: Arc<RwLock<Vec<broadcast::Sender<SequencedMessage>>>>,
+    replay: Arc<Mutex<ReplayLog>>,
     multimodal_manager: Arc<MultimodalManager>,
     #[cfg(feature = "llm")]
     llm_manager: Option<Arc<LLMManager>>,
     port: u16,
+    caption_config: CaptionConfig,
+    next_caption_id: AtomicU64,
 }
 
 /// Messages sent to connected clients
@@ -46,12 +55,15 @@ pub enum BridgeMessage {
     StreamUrl {
         url: String,
     },
-    /// Audio data (for client-side lip sync fallback)
+    /// Audio data (for client-side lip sync fallback).
+    /// Byte payloads are a JSON array of numbers, or raw bytes on binary connections.
     Audio {
+        #[serde(with = "serde_bytes")]
         data: Vec<u8>,
     },
     /// TTS audio output (for avatar speech)
     TTSAudio {
+        #[serde(with = "serde_bytes")]
         data: Vec<u8>,
         format: String, // "wav", "pcm", "opus"
     },
@@ -59,6 +71,107 @@ pub enum BridgeMessage {
     TTSRequest {
         text: String,
     },
+    /// Sent after reconnecting with `?last_seq=N`, before the replayed messages
+    Resumed {
+        last_seq: u64,
+        replayed: usize,
+        missed: u64,
+    },
+    /// Timestamped audio, viseme, expression or subtitle entry on the session timeline
+    Timeline(TimelineEvent),
+    /// Caption cue for avatar speech, timed on the session timeline
+    Caption(CaptionCue),
+}
+
+/// Binary frame type tags for bridge -> client messages
+impl WireTagged for BridgeMessage {
+    fn wire_tag(&self) -> u8 {
+        match self {
+            BridgeMessage::Expression { .. } => 0x40,
+            BridgeMessage::Gesture { .. } => 0x41,
+            BridgeMessage::State { .. } => 0x42,
+            BridgeMessage::StreamUrl { .. } => 0x43,
+            BridgeMessage::Audio { .. } => 0x44,
+            BridgeMessage::TTSAudio { .. } => 0x45,
+            BridgeMessage::TTSRequest { .. } => 0x46,
+            BridgeMessage::Resumed { .. } => 0x47,
+            BridgeMessage::Timeline(_) => 0x48,
+            BridgeMessage::Caption(_) => 0x49,
+        }
+    }
+}
+
+/// Bridge message stamped with its position in the broadcast stream.
+/// Serialized as `{"seq": N, "<Variant>": {...}}` so existing clients keep working.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SequencedMessage {
+    pub seq: u64,
+    #[serde(flatten)]
+    pub message: BridgeMessage,
+}
+
+impl WireTagged for SequencedMessage {
+    fn wire_tag(&self) -> u8 {
+        self.message.wire_tag()
+    }
+}
+
+/// Recently broadcast messages kept for clients that reconnect
+const REPLAY_LOG_SIZE: usize = 256;
+
+/// Bounded log of broadcast messages, replayed to clients resuming after a network blip
+struct ReplayLog {
+    next_seq: u64,
+    entries: VecDeque<SequencedMessage>,
+}
+
+impl ReplayLog {
+    fn new() -> Self {
+        Self {
+            next_seq: 1,
+            entries: VecDeque::with_capacity(REPLAY_LOG_SIZE),
+        }
+    }
+
+    fn last_seq(&self) -> u64 {
+        self.next_seq - 1
+    }
+
+    /// Messages after `last_seq` still in the log, and how many were already evicted
+    fn since(&self, last_seq: u64) -> (Vec<SequencedMessage>, u64) {
+        let replay: Vec<_> = self.entries.iter().filter(|m| m.seq > last_seq).cloned().collect();
+        let first_available = self.entries.front().map(|m| m.seq).unwrap_or(self.next_seq);
+        let missed = first_available.saturating_sub(last_seq + 1);
+        (replay, missed)
+    }
+}
+
+/// Stamp `message` with the next sequence number, record it for replay and
+/// send it to every client. The log lock is held while sending so clients
+/// always observe sequence numbers in order. Returns whether any send failed.
+fn publish(
+    replay: &Mutex<ReplayLog>,
+    clients: &[broadcast::Sender<SequencedMessage>],
+    message: BridgeMessage,
+) -> bool {
+    let mut log = replay.lock();
+    let sequenced = SequencedMessage {
+        seq: log.next_seq,
+        message,
+    };
+    log.next_seq += 1;
+    if log.entries.len() >= REPLAY_LOG_SIZE {
+        log.entries.pop_front();
+    }
+    log.entries.push_back(sequenced.clone());
+
+    let mut failed = false;
+    for client in clients {
+        if client.send(sequenced.clone()).is_err() {
+            failed = true;
+        }
+    }
+    failed
 }
 
 /// Messages received from clients
@@ -66,6 +179,7 @@ pub enum BridgeMessage {
 pub enum ClientMessage {
     /// Video frame from camera
     VideoFrame {
+        #[serde(with = "serde_bytes")]
         data: Vec<u8>,
         width: u32,
         height: u32,
@@ -73,6 +187,7 @@ pub enum ClientMessage {
     },
     /// Audio sample from microphone
     AudioSample {
+        #[serde(with = "serde_bytes")]
         data: Vec<u8>,
         sample_rate: u32,
         channels: u8,
@@ -82,6 +197,30 @@ pub enum ClientMessage {
     TTSRequest {
         text: String,
     },
+    /// Session time the client is rendering, for timeline drift correction
+    PlaybackPosition {
+        position_ms: u64,
+    },
+}
+
+/// Binary frame type tags for client -> bridge messages
+impl WireTagged for ClientMessage {
+    fn wire_tag(&self) -> u8 {
+        match self {
+            ClientMessage::VideoFrame { .. } => 0x01,
+            ClientMessage::AudioSample { .. } => 0x02,
+            ClientMessage::TTSRequest { .. } => 0x03,
+            ClientMessage::PlaybackPosition { .. } => 0x04,
+        }
+    }
+}
+
+/// Socket message for an encoded frame
+fn ws_message(frame: WireFrame) -> Message {
+    match frame {
+        WireFrame::Text(text) => Message::Text(text),
+        WireFrame::Binary(bytes) => Message::Binary(bytes),
+    }
 }
 
 impl AvatarBridge {
@@ -95,20 +234,31 @@ impl AvatarBridge {
         Self {
             broker,
             clients: Arc::new(RwLock::new(Vec::new())),
+            replay: Arc::new(Mutex::new(ReplayLog::new())),
             multimodal_manager,
             #[cfg(feature = "llm")]
             llm_manager,
             port,
+            caption_config: CaptionConfig::default(),
+            next_caption_id: AtomicU64::new(1),
         }
     }
 
+    /// Use `config` for caption layout and pacing
+    pub fn with_caption_config(mut self, config: CaptionConfig) -> Self {
+        self.caption_config = config;
+        self
+    }
+
     pub async fn start(&self) -> Result<(), Error> {
         let port = self.port;
+        self.forward_timeline();
         let app = Router::new()
             .route("/avatar/ws", get(websocket_handler))
             .with_state(BridgeState {
                 broker: Arc::clone(&self.broker),
                 clients: Arc::clone(&self.clients),
+                replay: Arc::clone(&self.replay),
                 multimodal_manager: Arc::clone(&self.multimodal_manager),
                 #[cfg(feature = "llm")]
                 llm_manager: self.llm_manager.clone(),
@@ -122,6 +272,64 @@ impl AvatarBridge {
         Ok(())
     }
 
+    /// Queue TTS audio on the session timeline and caption it with `text`
+    ///
+    /// Returns the session time the speech starts at, or `None` if the
+    /// timeline dropped the audio for arriving too late.
+    pub async fn speak(&self, audio: TTSAudio, text: &str, timings: Option<&[WordTiming]>) -> Option<u64> {
+        let duration_ms = captions::audio_duration_ms(&audio)
+            .or_else(|| timings.and_then(|t| t.iter().map(|w| w.end_ms).max()))
+            .unwrap_or_else(|| captions::estimate_speech_ms(text, &self.caption_config));
+        let start_ms = self.multimodal_manager.timeline().schedule_audio(audio, duration_ms, Vec::new())?;
+        self.publish_captions(text, start_ms, duration_ms, timings).await;
+        Some(start_ms)
+    }
+
+    /// Send caption cues for speech starting at session time `start_ms`
+    ///
+    /// Cues follow `timings` when given; otherwise `text` is spread over `duration_ms`.
+    pub async fn publish_captions(
+        &self,
+        text: &str,
+        start_ms: u64,
+        duration_ms: u64,
+        timings: Option<&[WordTiming]>,
+    ) -> Vec<CaptionCue> {
+        let mut cues = match timings {
+            Some(timings) if !timings.is_empty() => captions::cues_from_timings(timings, &self.caption_config),
+            _ => captions::cues_from_text(text, duration_ms, &self.caption_config),
+        };
+        let clients: Vec<_> = self.clients.read().await.clone();
+        for cue in &mut cues {
+            cue.id = self.next_caption_id.fetch_add(1, Ordering::Relaxed);
+            cue.start_ms += start_ms;
+            cue.end_ms += start_ms;
+            publish(&self.replay, &clients, BridgeMessage::Caption(cue.clone()));
+        }
+        cues
+    }
+
+    /// Publish timeline entries to all clients as they are scheduled
+    fn forward_timeline(&self) {
+        let mut timeline = self.multimodal_manager.timeline().subscribe();
+        let clients = Arc::clone(&self.clients);
+        let replay = Arc::clone(&self.replay);
+        tokio::spawn(async move {
+            loop {
+                match timeline.recv().await {
+                    Ok(event) => {
+                        let clients = clients.read().await;
+                        publish(&replay, &clients, BridgeMessage::Timeline(event));
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("Avatar bridge lagged behind the timeline, skipped {} entries", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
     pub async fn broadcast(&self, message: BridgeMessage) {
         const MAX_CLIENTS: usize = 10_000;
         
@@ -135,17 +343,11 @@ impl AvatarBridge {
         };
         
         // Broadcast to all clients without holding lock
-        let mut disconnected_indices = Vec::new();
-        for (idx, client) in client_senders.iter().enumerate() {
-            if client.send(message.clone()).is_err() {
-                disconnected_indices.push(idx);
-            }
-        }
+        let any_disconnected = publish(&self.replay, &client_senders, message);
         
         // Remove disconnected clients by checking receiver_count (race-safe)
-        if !disconnected_indices.is_empty() {
+        if any_disconnected {
             let mut clients_mut = self.clients.write().await;
-            // Remove in reverse order to preserve indices
             clients_mut.retain(|c| c.receiver_count() > 1); // Keep only active receivers (> 1 because we count ourselves)
         }
     }
@@ -155,26 +357,41 @@ impl AvatarBridge {
 #[derive(Clone)]
 struct BridgeState {
     broker: Arc<RwLock<AvatarBroker>>,
-    clients: Arc<RwLock<Vec<broadcast::Sender<BridgeMessage>>>>,
+    clients: Arc<RwLock<Vec<broadcast::Sender<SequencedMessage>>>>,
+    replay: Arc<Mutex<ReplayLog>>,
     multimodal_manager: Arc<MultimodalManager>,
     #[cfg(feature = "llm")]
     llm_manager: Option<Arc<LLMManager>>,
 }
 
+/// Query parameters for the avatar WebSocket
+#[derive(Debug, serde::Deserialize)]
+struct BridgeQuery {
+    /// Last sequence number the client saw before reconnecting
+    last_seq: Option<u64>,
+}
+
 /// WebSocket handler
-async fn websocket_handler(ws: WebSocketUpgrade, State(state): State<BridgeState>) -> Response {
-    ws.on_upgrade(|socket| handle_socket(socket, state))
+async fn websocket_handler(
+    ws: WebSocketUpgrade,
+    Query(query): Query<BridgeQuery>,
+    State(state): State<BridgeState>,
+) -> Response {
+    // Clients may negotiate MessagePack or CBOR for high-rate traffic; JSON otherwise
+    ws.protocols(WireFormat::SUBPROTOCOLS)
+        .on_upgrade(move |socket| handle_socket(socket, state, query.last_seq))
 }
 
 /// Handle individual WebSocket connection
-async fn handle_socket(socket: WebSocket, state: BridgeState) {
+async fn handle_socket(socket: WebSocket, state: BridgeState, last_seq: Option<u64>) {
     use futures_util::StreamExt;
     
     let client_id = Uuid::new_v4();
-    info!("New avatar client connected: {}", client_id);
+    let wire_format = WireFormat::negotiated(socket.protocol().and_then(|p| p.to_str().ok()));
+    info!("New avatar client connected: {} ({})", client_id, wire_format.subprotocol());
 
     // Create channel for this client
-    let (tx, mut rx) = broadcast::channel::<BridgeMessage>(100);
+    let (tx, mut rx) = broadcast::channel::<SequencedMessage>(100);
     
     // Add to clients list (with size limit)
     {
@@ -187,6 +404,16 @@ async fn handle_socket(socket: WebSocket, state: BridgeState) {
         clients.push(tx.clone());
     }
 
+    // Collect missed messages after registering, so nothing published in between is lost;
+    // the overlap with the live channel is dropped by sequence number in the send task
+    let resume = last_seq.map(|last_seq| {
+        let log = state.replay.lock();
+        // A client ahead of the log saw a previous bridge instance; start it from scratch
+        let last_seq = last_seq.min(log.last_seq());
+        let (replay, missed) = log.since(last_seq);
+        (last_seq, replay, missed)
+    });
+
     // Split socket into sender and receiver
     let (mut sender, mut receiver) = socket.split();
     info!("Client {}: Socket split successfully", client_id);
@@ -200,8 +427,8 @@ async fn handle_socket(socket: WebSocket, state: BridgeState) {
         let welcome_msg = BridgeMessage::State {
             state: "connected".to_string(),
         };
-        if let Ok(welcome_json) = serde_json::to_string(&welcome_msg) {
-            match sender.send(axum::extract::ws::Message::Text(welcome_json)).await {
+        if let Ok(welcome_frame) = wire_format.encode(&welcome_msg) {
+            match sender.send(ws_message(welcome_frame)).await {
                 Ok(_) => {
                     info!("Client {}: Welcome message sent successfully", client_id);
                 }
@@ -213,6 +440,29 @@ async fn handle_socket(socket: WebSocket, state: BridgeState) {
         } else {
             warn!("Client {}: Failed to serialize welcome message", client_id);
         }
+
+        // Highest sequence number delivered to this client
+        let mut delivered_seq = 0;
+        if let Some((last_seq, replay, missed)) = resume {
+            info!("Client {}: Resuming after seq {} ({} replayed, {} missed)", client_id, last_seq, replay.len(), missed);
+            delivered_seq = last_seq;
+            let resumed = BridgeMessage::Resumed {
+                last_seq,
+                replayed: replay.len(),
+                missed,
+            };
+            let frames = wire_format.encode(&resumed).into_iter()
+                .chain(replay.iter().filter_map(|m| wire_format.encode(m).ok()));
+            for frame in frames {
+                if sender.send(ws_message(frame)).await.is_err() {
+                    debug!("Client {}: WebSocket send failed during replay, closing sender task", client_id);
+                    return;
+                }
+            }
+            if let Some(last) = replay.last() {
+                delivered_seq = last.seq;
+            }
+        }
         
         loop {
             // Use timeout to prevent hanging on receive
@@ -221,8 +471,12 @@ async fn handle_socket(socket: WebSocket, state: BridgeState) {
                 rx.recv()
             ).await {
                 Ok(Ok(msg)) => {
-                    let json = match serde_json::to_string(&msg) {
-                        Ok(j) => j,
+                    if msg.seq <= delivered_seq {
+                        continue;
+                    }
+                    delivered_seq = msg.seq;
+                    let frame = match wire_format.encode(&msg) {
+                        Ok(frame) => frame,
                         Err(e) => {
                             warn!("Failed to serialize bridge message: {}", e);
                             continue;
@@ -231,12 +485,12 @@ async fn handle_socket(socket: WebSocket, state: BridgeState) {
                     
                     // Validate message size before sending
                     const MAX_WS_MESSAGE_SIZE: usize = 1_000_000; // 1MB max
-                    if json.len() > MAX_WS_MESSAGE_SIZE {
-                        warn!("Message too large to send ({} bytes), skipping", json.len());
+                    if frame.len() > MAX_WS_MESSAGE_SIZE {
+                        warn!("Message too large to send ({} bytes), skipping", frame.len());
                         continue;
                     }
                     
-                    if sender.send(axum::extract::ws::Message::Text(json)).await.is_err() {
+                    if sender.send(ws_message(frame)).await.is_err() {
                         debug!("Client {}: WebSocket send failed, closing sender task", client_id);
                         break;
                     }
@@ -268,6 +522,7 @@ async fn handle_socket(socket: WebSocket, state: BridgeState) {
     #[cfg(feature = "llm")]
     let llm_manager_arc = state.llm_manager.as_ref().map(Arc::clone);
     let clients_for_recv_task = Arc::clone(&state.clients);
+    let replay_for_recv_task = Arc::clone(&state.replay);
     
     let mut recv_task = tokio::spawn(async move {
         loop {
@@ -277,16 +532,22 @@ async fn handle_socket(socket: WebSocket, state: BridgeState) {
             ).await {
                 Ok(Some(Ok(msg))) => {
                     match msg {
-                        Message::Text(text) => {
+                        Message::Text(_) | Message::Binary(_) => {
+                            // JSON text, or a tagged binary frame on a MessagePack/CBOR connection
+                            let frame = match msg {
+                                Message::Text(text) => WireFrame::Text(text),
+                                Message::Binary(data) => WireFrame::Binary(data),
+                                _ => continue,
+                            };
                             const MAX_MESSAGE_SIZE: usize = 10 * 1024 * 1024;
-                            if text.len() > MAX_MESSAGE_SIZE {
-                                warn!("Client {}: WebSocket message too large ({} bytes, max {} bytes), closing connection", client_id, text.len(), MAX_MESSAGE_SIZE);
+                            if frame.len() > MAX_MESSAGE_SIZE {
+                                warn!("Client {}: WebSocket message too large ({} bytes, max {} bytes), closing connection", client_id, frame.len(), MAX_MESSAGE_SIZE);
                                 break;
                             }
                             
-                            debug!("Client {}: Received text message: {} bytes", client_id, text.len());
+                            debug!("Client {}: Received message: {} bytes", client_id, frame.len());
                             
-                            match serde_json::from_str::<ClientMessage>(&text) {
+                            match wire_format.decode::<ClientMessage>(&frame) {
                                 Ok(client_msg) => {
                                     match client_msg {
                                         ClientMessage::VideoFrame { data, width, height, timestamp } => {
@@ -312,6 +573,7 @@ async fn handle_socket(socket: WebSocket, state: BridgeState) {
                                                 let vision_description = format!("Camera frame received: {}x{} pixels, {} bytes", width, height, data.len());
                                                 let llm_clone = Arc::clone(llm);
                                                 let clients_clone = Arc::clone(&clients_for_recv_task);
+                                                let replay_clone = Arc::clone(&replay_for_recv_task);
                                                 tokio::spawn(async move {
                                                     use narayana_llm::MessageRole;
                                                     let messages = vec![
@@ -332,9 +594,7 @@ async fn handle_socket(socket: WebSocket, state: BridgeState) {
                                                                 text: response,
                                                             };
                                                             let clients = clients_clone.read().await;
-                                                            for client in clients.iter() {
-                                                                let _ = client.send(tts_msg.clone());
-                                                            }
+                                                            publish(&replay_clone, &clients, tts_msg);
                                                         }
                                                         Err(e) => {
                                                             warn!("Client {}: LLM chat error: {}", client_id, e);
@@ -370,6 +630,7 @@ async fn handle_socket(socket: WebSocket, state: BridgeState) {
                                                 let audio_text = format!("Audio input received: {}Hz, {}ch, {} bytes", sample_rate, channels, data.len());
                                                 let llm_clone = Arc::clone(llm);
                                                 let clients_clone = Arc::clone(&clients_for_recv_task);
+                                                let replay_clone = Arc::clone(&replay_for_recv_task);
                                                 tokio::spawn(async move {
                                                     use narayana_llm::MessageRole;
                                                     let messages = vec![
@@ -390,9 +651,7 @@ async fn handle_socket(socket: WebSocket, state: BridgeState) {
                                                                 text: response,
                                                             };
                                                             let clients = clients_clone.read().await;
-                                                            for client in clients.iter() {
-                                                                let _ = client.send(tts_msg.clone());
-                                                            }
+                                                            publish(&replay_clone, &clients, tts_msg);
                                                         }
                                                         Err(e) => {
                                                             warn!("Client {}: LLM chat error: {}", client_id, e);
@@ -401,6 +660,10 @@ async fn handle_socket(socket: WebSocket, state: BridgeState) {
                                                 });
                                             }
                                         }
+                                        ClientMessage::PlaybackPosition { position_ms } => {
+                                            let drift = multimodal_manager_arc.timeline().report_playback(position_ms);
+                                            debug!("Client {}: Playback at {}ms, timeline drift {:.0}ms", client_id, position_ms, drift);
+                                        }
                                         ClientMessage::TTSRequest { text } => {
                                             debug!("Client {}: Received TTS request (chat message): {} chars", client_id, text.len());
                                             // Validate text length
@@ -416,6 +679,7 @@ async fn handle_socket(socket: WebSocket, state: BridgeState) {
                                                 let text_clone = text.clone();
                                                 let llm_clone = Arc::clone(llm);
                                                 let clients_clone = Arc::clone(&clients_for_recv_task);
+                                                let replay_clone = Arc::clone(&replay_for_recv_task);
                                                 let client_id_clone = client_id;
                                                 tokio::spawn(async move {
                                                     use narayana_llm::MessageRole;
@@ -437,9 +701,7 @@ async fn handle_socket(socket: WebSocket, state: BridgeState) {
                                                                 text: response,
                                                             };
                                                             let clients = clients_clone.read().await;
-                                                            for client in clients.iter() {
-                                                                let _ = client.send(tts_msg.clone());
-                                                            }
+                                                            publish(&replay_clone, &clients, tts_msg);
                                                         }
                                                         Err(e) => {
                                                             warn!("Client {}: LLM chat error: {}", client_id_clone, e);
@@ -455,9 +717,7 @@ async fn handle_socket(socket: WebSocket, state: BridgeState) {
                                                     text: format!("Echo: {}", text),
                                                 };
                                                 let clients = clients_for_recv_task.read().await;
-                                                for client in clients.iter() {
-                                                    let _ = client.send(echo_msg.clone());
-                                                }
+                                                publish(&replay_for_recv_task, &clients, echo_msg);
                                             }
                                         }
                                     }
@@ -467,14 +727,6 @@ async fn handle_socket(socket: WebSocket, state: BridgeState) {
                                 }
                             }
                         }
-                        Message::Binary(data) => {
-                            const MAX_BINARY_SIZE: usize = 10 * 1024 * 1024;
-                            if data.len() > MAX_BINARY_SIZE {
-                                warn!("Client {}: WebSocket binary message too large ({} bytes, max {} bytes), closing connection", client_id, data.len(), MAX_BINARY_SIZE);
-                                break;
-                            }
-                            debug!("Client {}: Received binary message: {} bytes", client_id, data.len());
-                        }
                         Message::Close(close_frame) => {
                             info!("Client {}: Disconnected gracefully. Close frame: {:?}", client_id, close_frame);
                             break;
@@ -0,0 +1,170 @@
+//! Provider capability descriptors and graceful degradation
+//!
+//! Providers differ in what they can render: full 3D rigs take any gesture,
+//! talking-head video avatars only move the face and head. Each provider describes
+//! itself with `ProviderCapabilities`, and the broker uses it to map
+//! unsupported requests to the nearest behavior the provider does support,
+//! or to skip them, instead of surfacing provider errors.
+
+use crate::config::{Expression, Gesture};
+use serde::{Deserialize, Serialize};
+
+const STANDARD_EXPRESSIONS: [Expression; 10] = [
+    Expression::Neutral,
+    Expression::Happy,
+    Expression::Sad,
+    Expression::Angry,
+    Expression::Surprised,
+    Expression::Thinking,
+    Expression::Confused,
+    Expression::Excited,
+    Expression::Tired,
+    Expression::Recognition,
+];
+
+const STANDARD_GESTURES: [Gesture; 5] = [
+    Gesture::Wave,
+    Gesture::Point,
+    Gesture::Nod,
+    Gesture::Shake,
+    Gesture::ThumbsUp,
+];
+
+/// What a provider can render
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProviderCapabilities {
+    /// Drives the mouth from speech audio
+    pub lip_sync: bool,
+    /// Standard expressions the provider renders
+    pub expressions: Vec<Expression>,
+    /// Accepts `Expression::Custom` identifiers
+    pub custom_expressions: bool,
+    /// Standard gestures the provider renders
+    pub gestures: Vec<Gesture>,
+    /// Accepts `Gesture::Custom` identifiers
+    pub custom_gestures: bool,
+    pub vision: bool,
+    pub audio_input: bool,
+    pub tts: bool,
+}
+
+/// What the broker does with a gesture request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GestureFallback {
+    /// Perform this gesture (the requested one or a stand-in)
+    Gesture(Gesture),
+    /// Convey it with a facial expression instead
+    Expression(Expression),
+    /// Nothing comparable is supported
+    Skip,
+}
+
+impl ProviderCapabilities {
+    /// Everything: full-body rigs
+    pub fn full() -> Self {
+        Self {
+            lip_sync: true,
+            expressions: STANDARD_EXPRESSIONS.to_vec(),
+            custom_expressions: true,
+            gestures: STANDARD_GESTURES.to_vec(),
+            custom_gestures: true,
+            vision: false,
+            audio_input: false,
+            tts: false,
+        }
+    }
+
+    /// Lip sync, facial expressions and head movement: talking-head avatars
+    pub fn talking_head() -> Self {
+        Self {
+            gestures: vec![Gesture::Nod, Gesture::Shake],
+            custom_gestures: false,
+            ..Self::full()
+        }
+    }
+
+    /// Lip sync only
+    pub fn lip_sync_only() -> Self {
+        Self {
+            expressions: Vec::new(),
+            custom_expressions: false,
+            gestures: Vec::new(),
+            custom_gestures: false,
+            ..Self::full()
+        }
+    }
+
+    pub fn supports_expression(&self, expression: &Expression) -> bool {
+        match expression {
+            Expression::Custom(_) => self.custom_expressions,
+            expression => self.expressions.contains(expression),
+        }
+    }
+
+    pub fn supports_gesture(&self, gesture: &Gesture) -> bool {
+        match gesture {
+            Gesture::None => true,
+            Gesture::Custom(_) => self.custom_gestures,
+            gesture => self.gestures.contains(gesture),
+        }
+    }
+
+    /// The requested expression if supported, else the nearest supported one
+    ///
+    /// `None` when the provider has no expression close enough (or none at all).
+    pub fn degrade_expression(&self, expression: Expression) -> Option<Expression> {
+        if self.supports_expression(&expression) {
+            return Some(expression);
+        }
+        expression_fallbacks(&expression)
+            .iter()
+            .find(|candidate| self.supports_expression(candidate))
+            .cloned()
+    }
+
+    /// The requested gesture if supported, else a similar gesture, else an
+    /// expression that conveys the same thing
+    pub fn degrade_gesture(&self, gesture: Gesture) -> GestureFallback {
+        if self.supports_gesture(&gesture) {
+            return GestureFallback::Gesture(gesture);
+        }
+        let (gestures, expression): (&[Gesture], Option<Expression>) = match gesture {
+            Gesture::ThumbsUp => (&[Gesture::Nod], Some(Expression::Happy)),
+            Gesture::Wave => (&[Gesture::Nod], Some(Expression::Happy)),
+            Gesture::Nod => (&[], Some(Expression::Recognition)),
+            Gesture::Shake => (&[], Some(Expression::Confused)),
+            Gesture::Point => (&[Gesture::Nod], None),
+            Gesture::None | Gesture::Custom(_) => (&[], None),
+        };
+        if let Some(gesture) = gestures.iter().find(|g| self.supports_gesture(g)) {
+            return GestureFallback::Gesture(gesture.clone());
+        }
+        match expression.and_then(|e| self.degrade_expression(e)) {
+            // A neutral face conveys nothing; skip instead
+            Some(Expression::Neutral) | None => GestureFallback::Skip,
+            Some(expression) => GestureFallback::Expression(expression),
+        }
+    }
+}
+
+impl Default for ProviderCapabilities {
+    fn default() -> Self {
+        Self::full()
+    }
+}
+
+/// Stand-ins for an expression, closest first
+fn expression_fallbacks(expression: &Expression) -> &'static [Expression] {
+    match expression {
+        Expression::Excited => &[Expression::Happy, Expression::Surprised, Expression::Neutral],
+        Expression::Surprised => &[Expression::Excited, Expression::Happy, Expression::Neutral],
+        Expression::Recognition => &[Expression::Happy, Expression::Thinking, Expression::Neutral],
+        Expression::Confused => &[Expression::Thinking, Expression::Surprised, Expression::Neutral],
+        Expression::Thinking => &[Expression::Confused, Expression::Neutral],
+        Expression::Tired => &[Expression::Sad, Expression::Neutral],
+        Expression::Angry => &[Expression::Sad, Expression::Neutral],
+        Expression::Sad => &[Expression::Tired, Expression::Neutral],
+        Expression::Happy => &[Expression::Excited, Expression::Neutral],
+        Expression::Neutral | Expression::Custom(_) => &[Expression::Neutral],
+    }
+}
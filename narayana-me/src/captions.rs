@@ -0,0 +1,233 @@
+//! Caption cues for avatar speech
+//!
+//! Turns the text behind a TTS utterance into WebVTT-style cues timed against
+//! the session timeline, so web clients can show accessible subtitles in step
+//! with the avatar. Word timings from the TTS engine give exact cues; without
+//! them the text is spread over the audio by length.
+
+use crate::multimodal::{AudioFormat, TTSAudio};
+use serde::{Deserialize, Serialize};
+
+/// Caption layout and pacing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptionConfig {
+    /// Longest cue, in characters
+    pub max_cue_chars: usize,
+    /// Longest line; a cue wraps onto at most one more line
+    pub max_line_chars: usize,
+    /// Longest time one cue stays up (with word timings)
+    pub max_cue_ms: u64,
+    /// Speaking rate assumed when the audio duration is unknown
+    pub chars_per_second: f64,
+}
+
+impl Default for CaptionConfig {
+    fn default() -> Self {
+        Self {
+            max_cue_chars: 84,
+            max_line_chars: 42,
+            max_cue_ms: 7_000,
+            chars_per_second: 15.0,
+        }
+    }
+}
+
+/// When a word is spoken, relative to the start of the utterance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WordTiming {
+    pub word: String,
+    pub start_ms: u64,
+    pub end_ms: u64,
+}
+
+/// One caption, timed in session milliseconds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CaptionCue {
+    pub id: u64,
+    pub start_ms: u64,
+    pub end_ms: u64,
+    /// Caption text; lines are separated by `\n`
+    pub text: String,
+}
+
+impl CaptionCue {
+    /// The cue as a WebVTT cue block
+    pub fn to_webvtt(&self) -> String {
+        format!("{}\n{} --> {}\n{}\n", self.id, vtt_timestamp(self.start_ms), vtt_timestamp(self.end_ms), self.text)
+    }
+}
+
+/// A complete WebVTT document for `cues`
+pub fn webvtt_document(cues: &[CaptionCue]) -> String {
+    let mut document = String::from("WEBVTT\n");
+    for cue in cues {
+        document.push('\n');
+        document.push_str(&cue.to_webvtt());
+    }
+    document
+}
+
+fn vtt_timestamp(ms: u64) -> String {
+    format!("{:02}:{:02}:{:02}.{:03}", ms / 3_600_000, ms / 60_000 % 60, ms / 1_000 % 60, ms % 1_000)
+}
+
+/// Cues spreading `text` over `duration_ms`, in proportion to length
+///
+/// Times are relative to the start of the utterance; ids are left at 0.
+pub fn cues_from_text(text: &str, duration_ms: u64, config: &CaptionConfig) -> Vec<CaptionCue> {
+    let mut phrases = Vec::new();
+    for sentence in text.split_inclusive(['.', '!', '?', '\n']) {
+        let mut layout = CueLayout::default();
+        for word in sentence.split_whitespace() {
+            if !layout.fits(word, config) {
+                phrases.push(layout.take());
+            }
+            layout.push(word, config);
+        }
+        if !layout.is_empty() {
+            phrases.push(layout.take());
+        }
+    }
+    let total_chars: usize = phrases.iter().map(|p| p.chars().count()).sum();
+    if total_chars == 0 {
+        return Vec::new();
+    }
+
+    let mut cues = Vec::with_capacity(phrases.len());
+    let mut chars_before = 0;
+    for text in phrases {
+        let start_ms = duration_ms * chars_before as u64 / total_chars as u64;
+        chars_before += text.chars().count();
+        let end_ms = duration_ms * chars_before as u64 / total_chars as u64;
+        cues.push(CaptionCue { id: 0, start_ms, end_ms, text });
+    }
+    cues
+}
+
+/// Cues following the engine's word timings
+///
+/// A cue ends at a sentence end, or before it would outgrow its layout or
+/// `max_cue_ms`. Times are relative to the start of the utterance.
+pub fn cues_from_timings(timings: &[WordTiming], config: &CaptionConfig) -> Vec<CaptionCue> {
+    let mut cues = Vec::new();
+    let mut layout = CueLayout::default();
+    let mut start_ms = 0;
+    let mut end_ms = 0;
+    for timing in timings {
+        let word = timing.word.trim();
+        if word.is_empty() {
+            continue;
+        }
+        let too_long = !layout.fits(word, config) || timing.end_ms.saturating_sub(start_ms) > config.max_cue_ms;
+        if !layout.is_empty() && too_long {
+            cues.push(CaptionCue { id: 0, start_ms, end_ms, text: layout.take() });
+        }
+        if layout.is_empty() {
+            start_ms = timing.start_ms;
+        }
+        layout.push(word, config);
+        end_ms = end_ms.max(timing.end_ms);
+        if word.ends_with(['.', '!', '?']) {
+            cues.push(CaptionCue { id: 0, start_ms, end_ms, text: layout.take() });
+        }
+    }
+    if !layout.is_empty() {
+        cues.push(CaptionCue { id: 0, start_ms, end_ms, text: layout.take() });
+    }
+    cues
+}
+
+/// Duration of TTS audio, when the format tells
+///
+/// WAV durations come from the header; PCM is taken as 16-bit mono. Opus
+/// needs decoding and returns `None`.
+pub fn audio_duration_ms(audio: &TTSAudio) -> Option<u64> {
+    match audio.format {
+        AudioFormat::Wav => wav_duration_ms(&audio.data),
+        AudioFormat::Pcm if audio.sample_rate > 0 => {
+            Some(audio.data.len() as u64 / 2 * 1_000 / audio.sample_rate as u64)
+        }
+        AudioFormat::Pcm | AudioFormat::Opus => None,
+    }
+}
+
+/// Time to speak `text` at the configured rate
+pub fn estimate_speech_ms(text: &str, config: &CaptionConfig) -> u64 {
+    (text.trim().chars().count() as f64 / config.chars_per_second * 1_000.0).round() as u64
+}
+
+fn wav_duration_ms(data: &[u8]) -> Option<u64> {
+    if data.len() < 12 || &data[0..4] != b"RIFF" || &data[8..12] != b"WAVE" {
+        return None;
+    }
+    let mut byte_rate = None;
+    let mut offset = 12;
+    while offset + 8 <= data.len() {
+        let id = &data[offset..offset + 4];
+        let size = u32::from_le_bytes(data[offset + 4..offset + 8].try_into().ok()?) as usize;
+        let body = offset + 8;
+        match id {
+            b"fmt " if body + 12 <= data.len() => {
+                byte_rate = Some(u32::from_le_bytes(data[body + 8..body + 12].try_into().ok()?) as u64);
+            }
+            b"data" => {
+                // Streamed WAVs may leave the size unset; count what is there
+                let size = size.min(data.len() - body) as u64;
+                return byte_rate.filter(|rate| *rate > 0).map(|rate| size * 1_000 / rate);
+            }
+            _ => {}
+        }
+        // Chunks are padded to an even size
+        offset = body.checked_add(size)?.checked_add(size % 2)?;
+    }
+    None
+}
+
+/// Cue text being filled word by word: at most two lines of
+/// `max_line_chars` and `max_cue_chars` overall
+#[derive(Default)]
+struct CueLayout {
+    text: String,
+    chars: usize,
+    line_chars: usize,
+    lines: usize,
+}
+
+impl CueLayout {
+    fn is_empty(&self) -> bool {
+        self.lines == 0
+    }
+
+    /// Whether `word` still fits in this cue
+    fn fits(&self, word: &str, config: &CaptionConfig) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+        let word_chars = word.chars().count();
+        let same_line = self.line_chars + 1 + word_chars <= config.max_line_chars;
+        self.chars + 1 + word_chars <= config.max_cue_chars && (same_line || self.lines < 2)
+    }
+
+    fn push(&mut self, word: &str, config: &CaptionConfig) {
+        let word_chars = word.chars().count();
+        if self.is_empty() {
+            self.lines = 1;
+        } else if self.line_chars + 1 + word_chars <= config.max_line_chars {
+            self.text.push(' ');
+            self.chars += 1;
+            self.line_chars += 1;
+        } else {
+            self.text.push('\n');
+            self.chars += 1;
+            self.line_chars = 0;
+            self.lines += 1;
+        }
+        self.text.push_str(word);
+        self.chars += word_chars;
+        self.line_chars += word_chars;
+    }
+
+    fn take(&mut self) -> String {
+        std::mem::take(self).text
+    }
+}
@@ -259,6 +259,21 @@ impl Emotion {
             _ => Expression::Neutral,
         }
     }
+
+    /// Emotion and intensity for a point on the shared emotion bus
+    pub fn from_state(state: &narayana_wld::emotion::EmotionState) -> (Emotion, f64) {
+        use narayana_wld::emotion::EmotionLabel;
+        let emotion = match state.label() {
+            EmotionLabel::Neutral => Emotion::Neutral,
+            EmotionLabel::Joy | EmotionLabel::Contentment => Emotion::Joy,
+            EmotionLabel::Interest => Emotion::Interest,
+            EmotionLabel::Surprise => Emotion::Surprise,
+            EmotionLabel::Anger => Emotion::Anger,
+            EmotionLabel::Fear => Emotion::Fear,
+            EmotionLabel::Sadness => Emotion::Sadness,
+        };
+        (emotion, state.intensity())
+    }
 }
 
 
@@ -10,16 +10,22 @@
 pub mod error;
 pub mod config;
 pub mod avatar_broker;
+pub mod capabilities;
 pub mod providers;
 pub mod avatar_adapter;
 pub mod cpl_integration;
 pub mod bridge;
 pub mod multimodal;
+pub mod timeline;
+pub mod captions;
 
 pub use error::AvatarError;
 pub use config::{AvatarConfig, AvatarProviderType, Expression, Gesture, Emotion};
 pub use avatar_broker::{AvatarBroker, AvatarProvider, AvatarStream};
+pub use capabilities::{ProviderCapabilities, GestureFallback};
 pub use avatar_adapter::AvatarAdapter;
 pub use cpl_integration::{avatar_config_from_cpl, create_avatar_adapter_from_cpl};
 pub use bridge::AvatarBridge; // Export bridge for external use
 pub use multimodal::MultimodalManager; // Export multimodal manager for external use
+pub use captions::{CaptionConfig, CaptionCue, WordTiming};
+pub use timeline::{MultimodalTimeline, TimelineConfig, TimelineEvent, TimelineTrack, LatePolicy, LatePolicies, VisemeFrame};
@@ -1,6 +1,7 @@
 //! Multimodal capabilities for avatar (vision, audio input, TTS)
 
 use crate::error::AvatarError;
+use crate::timeline::{MultimodalTimeline, TimelineConfig};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tokio::sync::broadcast;
@@ -33,7 +34,8 @@ pub struct TTSAudio {
 }
 
 /// Audio format
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum AudioFormat {
     Wav,
     Pcm,
@@ -45,10 +47,20 @@ pub struct MultimodalManager {
     vision_sender: broadcast::Sender<VisionFrame>,
     audio_input_sender: broadcast::Sender<AudioSample>,
     tts_audio_sender: broadcast::Sender<TTSAudio>,
+    timeline: Arc<MultimodalTimeline>,
 }
 
 impl MultimodalManager {
     pub fn new() -> Self {
+        Self::with_timeline(Arc::new(MultimodalTimeline::default()))
+    }
+
+    /// Create a manager whose timeline uses `config`
+    pub fn with_timeline_config(config: TimelineConfig) -> Result<Self, AvatarError> {
+        Ok(Self::with_timeline(Arc::new(MultimodalTimeline::new(config)?)))
+    }
+
+    fn with_timeline(timeline: Arc<MultimodalTimeline>) -> Self {
         let (vision_sender, _) = broadcast::channel(100);
         let (audio_input_sender, _) = broadcast::channel(1000);
         let (tts_audio_sender, _) = broadcast::channel(100);
@@ -57,8 +69,14 @@ impl MultimodalManager {
             vision_sender,
             audio_input_sender,
             tts_audio_sender,
+            timeline,
         }
     }
+
+    /// Session timeline that keeps audio, visemes, expressions and subtitles in sync
+    pub fn timeline(&self) -> Arc<MultimodalTimeline> {
+        Arc::clone(&self.timeline)
+    }
     
     /// Send TTS audio output
     pub fn send_tts_audio(&self, audio: TTSAudio) -> Result<(), AvatarError> {
@@ -1,6 +1,7 @@
 //! Beyond Presence avatar provider implementation
 
 use crate::avatar_broker::{AvatarProvider, AvatarStream};
+use crate::capabilities::ProviderCapabilities;
 use crate::config::{AvatarConfig, Expression, Gesture, Emotion};
 use crate::error::AvatarError;
 use async_trait::async_trait;
@@ -526,6 +527,16 @@ impl AvatarProvider for BeyondPresenceProvider {
                false // Not yet implemented
            }
 
+           fn capabilities(&self) -> ProviderCapabilities {
+               // Talking-head video: no hands or body
+               ProviderCapabilities {
+                   vision: self.supports_vision(),
+                   audio_input: self.supports_audio_input(),
+                   tts: self.supports_tts(),
+                   ..ProviderCapabilities::talking_head()
+               }
+           }
+
            fn provider_name(&self) -> &str {
                "Beyond Presence Genesis 1.0"
            }
@@ -1,6 +1,7 @@
 //! LiveAvatar provider implementation
 
 use crate::avatar_broker::{AvatarProvider, AvatarStream};
+use crate::capabilities::ProviderCapabilities;
 use crate::config::{AvatarConfig, Expression, Gesture, Emotion};
 use crate::error::AvatarError;
 use async_trait::async_trait;
@@ -491,6 +492,16 @@ impl AvatarProvider for LiveAvatarProvider {
            fn supports_audio_input(&self) -> bool { false }
            fn supports_tts(&self) -> bool { false }
 
+           fn capabilities(&self) -> ProviderCapabilities {
+               // Talking-head video: no hands or body
+               ProviderCapabilities {
+                   vision: self.supports_vision(),
+                   audio_input: self.supports_audio_input(),
+                   tts: self.supports_tts(),
+                   ..ProviderCapabilities::talking_head()
+               }
+           }
+
            fn provider_name(&self) -> &str {
                "LiveAvatar 1.0"
            }
@@ -0,0 +1,335 @@
+//! Synchronized timeline for avatar audio, visemes, expressions and subtitles
+//!
+//! Every output is stamped against one session clock (milliseconds since the
+//! timeline was created) and scheduled at least `lead_ms` ahead, so the web
+//! client can buffer it and render all tracks in step. Audio chunks are laid
+//! back to back on an audio cursor and carry their viseme frames with them,
+//! which keeps lip sync exact. Output that arrives too late for its slot is
+//! handled per track (`LatePolicy`), and clients report their playback
+//! position so accumulated drift can be absorbed by scheduling later.
+
+use crate::error::AvatarError;
+use crate::multimodal::{AudioFormat, TTSAudio};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+use tokio::sync::broadcast;
+use tracing::debug;
+
+/// What to do with output that can no longer start on time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LatePolicy {
+    /// Discard it
+    Drop,
+    /// Start it as soon as possible, in full
+    Shift,
+    /// Start it as soon as possible, skipping the part that is already past
+    Trim,
+}
+
+/// Late-arrival policy per track
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct LatePolicies {
+    pub audio: LatePolicy,
+    pub expression: LatePolicy,
+    pub subtitle: LatePolicy,
+}
+
+impl Default for LatePolicies {
+    fn default() -> Self {
+        Self {
+            // A gap in speech is better than cutting words off
+            audio: LatePolicy::Shift,
+            expression: LatePolicy::Shift,
+            subtitle: LatePolicy::Trim,
+        }
+    }
+}
+
+/// Timeline configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineConfig {
+    /// How far ahead of the session clock output is scheduled
+    pub lead_ms: u64,
+    /// Drift tolerated before the schedule is corrected
+    pub max_drift_ms: u64,
+    /// Weight of each new playback report in the drift estimate (0.0-1.0]
+    pub drift_smoothing: f64,
+    pub late_policies: LatePolicies,
+}
+
+impl Default for TimelineConfig {
+    fn default() -> Self {
+        Self {
+            lead_ms: 150,
+            max_drift_ms: 80,
+            drift_smoothing: 0.2,
+            late_policies: LatePolicies::default(),
+        }
+    }
+}
+
+impl TimelineConfig {
+    pub fn validate(&self) -> Result<(), AvatarError> {
+        if self.lead_ms > 10_000 {
+            return Err(AvatarError::Config("Timeline lead_ms must be at most 10000".to_string()));
+        }
+        if self.max_drift_ms == 0 {
+            return Err(AvatarError::Config("Timeline max_drift_ms must be greater than 0".to_string()));
+        }
+        if !(self.drift_smoothing > 0.0 && self.drift_smoothing <= 1.0) {
+            return Err(AvatarError::Config("Timeline drift_smoothing must be in (0.0, 1.0]".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Mouth shape at an offset into an audio chunk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VisemeFrame {
+    /// Offset from the start of the audio chunk
+    pub offset_ms: u64,
+    pub viseme: String,
+    pub weight: f64,
+}
+
+/// Content of a timeline entry
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "track", rename_all = "snake_case")]
+pub enum TimelineTrack {
+    Audio {
+        #[serde(with = "serde_bytes")]
+        data: Vec<u8>,
+        format: AudioFormat,
+        sample_rate: u32,
+    },
+    Viseme {
+        viseme: String,
+        weight: f64,
+    },
+    Expression {
+        emotion: String,
+        intensity: f64,
+    },
+    Subtitle {
+        text: String,
+    },
+    /// Discard everything scheduled from `start_ms` on (e.g. the avatar was interrupted)
+    Clear,
+}
+
+/// A timestamped entry on the session timeline
+#[derive(Debug, Clone, Serialize)]
+pub struct TimelineEvent {
+    pub seq: u64,
+    /// Session time to start rendering at
+    pub start_ms: u64,
+    pub duration_ms: u64,
+    /// Part of the content to skip when it was trimmed for arriving late
+    pub skip_ms: u64,
+    /// Session time the event was scheduled at, for client clock alignment
+    pub sent_ms: u64,
+    #[serde(flatten)]
+    pub track: TimelineTrack,
+}
+
+struct TimelineState {
+    next_seq: u64,
+    /// End of the last scheduled audio chunk
+    audio_cursor_ms: u64,
+    /// Smoothed client playback lag
+    drift_ms: f64,
+    /// Delay currently added to new schedules to absorb drift
+    correction_ms: u64,
+}
+
+/// Placement of an entry after the late-arrival policy
+struct Slot {
+    start_ms: u64,
+    duration_ms: u64,
+    skip_ms: u64,
+}
+
+/// Session timeline shared by all avatar output
+pub struct MultimodalTimeline {
+    config: TimelineConfig,
+    clock: Instant,
+    state: Mutex<TimelineState>,
+    sender: broadcast::Sender<TimelineEvent>,
+}
+
+impl MultimodalTimeline {
+    pub fn new(config: TimelineConfig) -> Result<Self, AvatarError> {
+        config.validate()?;
+        let (sender, _) = broadcast::channel(1000);
+        Ok(Self {
+            config,
+            clock: Instant::now(),
+            state: Mutex::new(TimelineState {
+                next_seq: 1,
+                audio_cursor_ms: 0,
+                drift_ms: 0.0,
+                correction_ms: 0,
+            }),
+            sender,
+        })
+    }
+
+    /// Current session time in milliseconds
+    pub fn now_ms(&self) -> u64 {
+        self.clock.elapsed().as_millis() as u64
+    }
+
+    /// Queue an audio chunk right after the previous one, with its visemes
+    ///
+    /// Returns the chunk's start time, or `None` if the late policy dropped it.
+    pub fn schedule_audio(&self, audio: TTSAudio, duration_ms: u64, visemes: Vec<VisemeFrame>) -> Option<u64> {
+        let mut state = self.state.lock();
+        let now = self.now_ms();
+        // A chunk continues the previous one while that is still playing;
+        // otherwise it starts a new stretch of speech. The cursor is already
+        // corrected for drift.
+        let requested = if state.audio_cursor_ms > now {
+            state.audio_cursor_ms
+        } else {
+            self.earliest(&state, now)
+        };
+        let slot = self.place(&state, now, requested, duration_ms, self.config.late_policies.audio)?;
+        state.audio_cursor_ms = slot.start_ms + slot.duration_ms;
+
+        self.emit(&mut state, now, &slot, TimelineTrack::Audio {
+            data: audio.data,
+            format: audio.format,
+            sample_rate: audio.sample_rate,
+        });
+        for frame in visemes {
+            // Frames in the trimmed part are never seen
+            let Some(offset) = frame.offset_ms.checked_sub(slot.skip_ms) else {
+                continue;
+            };
+            if offset >= slot.duration_ms {
+                continue;
+            }
+            let frame_slot = Slot { start_ms: slot.start_ms + offset, duration_ms: 0, skip_ms: 0 };
+            self.emit(&mut state, now, &frame_slot, TimelineTrack::Viseme {
+                viseme: frame.viseme,
+                weight: frame.weight.clamp(0.0, 1.0),
+            });
+        }
+        Some(slot.start_ms)
+    }
+
+    /// Schedule an expression change at session time `at_ms` (`None` for as soon as possible)
+    pub fn schedule_expression(&self, emotion: &str, intensity: f64, at_ms: Option<u64>, duration_ms: u64) -> Option<u64> {
+        let track = TimelineTrack::Expression {
+            emotion: emotion.to_string(),
+            intensity: intensity.clamp(0.0, 1.0),
+        };
+        self.schedule_at(track, at_ms, duration_ms, self.config.late_policies.expression)
+    }
+
+    /// Schedule a subtitle at session time `at_ms` (`None` for as soon as possible)
+    pub fn schedule_subtitle(&self, text: &str, at_ms: Option<u64>, duration_ms: u64) -> Option<u64> {
+        let track = TimelineTrack::Subtitle { text: text.to_string() };
+        self.schedule_at(track, at_ms, duration_ms, self.config.late_policies.subtitle)
+    }
+
+    /// Drop everything still queued: clients discard entries from now on and
+    /// the next audio chunk starts fresh
+    pub fn clear(&self) {
+        let mut state = self.state.lock();
+        let now = self.now_ms();
+        state.audio_cursor_ms = 0;
+        let slot = Slot { start_ms: now, duration_ms: 0, skip_ms: 0 };
+        self.emit(&mut state, now, &slot, TimelineTrack::Clear);
+    }
+
+    /// Record the session time the client is rendering right now
+    ///
+    /// Updates the drift estimate and returns it. Once it moves more than
+    /// `max_drift_ms` away from the current correction, new output is
+    /// scheduled that much later (or earlier, as the client catches up).
+    pub fn report_playback(&self, position_ms: u64) -> f64 {
+        let mut state = self.state.lock();
+        let lag = self.now_ms() as f64 - position_ms as f64;
+        state.drift_ms += self.config.drift_smoothing * (lag - state.drift_ms);
+        if (state.drift_ms - state.correction_ms as f64).abs() > self.config.max_drift_ms as f64 {
+            // Only ever delay; a client ahead of the clock just buffers more
+            state.correction_ms = state.drift_ms.max(0.0).round() as u64;
+            debug!("Timeline drift {:.0}ms, correcting by {}ms", state.drift_ms, state.correction_ms);
+        }
+        state.drift_ms
+    }
+
+    /// Smoothed client playback lag in milliseconds
+    pub fn drift_ms(&self) -> f64 {
+        self.state.lock().drift_ms
+    }
+
+    /// Delay currently added to new output to absorb drift
+    pub fn correction_ms(&self) -> u64 {
+        self.state.lock().correction_ms
+    }
+
+    /// Subscribe to scheduled timeline entries
+    pub fn subscribe(&self) -> broadcast::Receiver<TimelineEvent> {
+        self.sender.subscribe()
+    }
+
+    fn schedule_at(&self, track: TimelineTrack, at_ms: Option<u64>, duration_ms: u64, policy: LatePolicy) -> Option<u64> {
+        let mut state = self.state.lock();
+        let now = self.now_ms();
+        let start = match at_ms {
+            Some(at_ms) => at_ms + state.correction_ms,
+            None => self.earliest(&state, now),
+        };
+        let slot = self.place(&state, now, start, duration_ms, policy)?;
+        self.emit(&mut state, now, &slot, track);
+        Some(slot.start_ms)
+    }
+
+    /// First start time clients can still render on time
+    fn earliest(&self, state: &TimelineState, now: u64) -> u64 {
+        now + self.config.lead_ms + state.correction_ms
+    }
+
+    /// Apply the late policy to a drift-corrected start time
+    fn place(&self, state: &TimelineState, now: u64, start: u64, duration_ms: u64, policy: LatePolicy) -> Option<Slot> {
+        let earliest = self.earliest(state, now);
+        if start >= earliest {
+            return Some(Slot { start_ms: start, duration_ms, skip_ms: 0 });
+        }
+        let late_by = earliest - start;
+        match policy {
+            LatePolicy::Drop => None,
+            LatePolicy::Shift => Some(Slot { start_ms: earliest, duration_ms, skip_ms: 0 }),
+            LatePolicy::Trim => {
+                if late_by >= duration_ms {
+                    return None;
+                }
+                Some(Slot { start_ms: earliest, duration_ms: duration_ms - late_by, skip_ms: late_by })
+            }
+        }
+    }
+
+    fn emit(&self, state: &mut TimelineState, now: u64, slot: &Slot, track: TimelineTrack) {
+        let event = TimelineEvent {
+            seq: state.next_seq,
+            start_ms: slot.start_ms,
+            duration_ms: slot.duration_ms,
+            skip_ms: slot.skip_ms,
+            sent_ms: now,
+            track,
+        };
+        state.next_seq += 1;
+        // No subscribers is fine
+        let _ = self.sender.send(event);
+    }
+}
+
+impl Default for MultimodalTimeline {
+    fn default() -> Self {
+        Self::new(TimelineConfig::default()).expect("default timeline config is valid")
+    }
+}
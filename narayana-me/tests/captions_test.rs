@@ -0,0 +1,80 @@
+//! Caption cue tests for narayana-me
+
+use narayana_me::captions::{self, webvtt_document};
+use narayana_me::multimodal::{AudioFormat, TTSAudio};
+use narayana_me::{CaptionConfig, CaptionCue, WordTiming};
+
+fn wav(data_bytes: usize, byte_rate: u32) -> Vec<u8> {
+    let mut wav = Vec::new();
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_bytes as u32).to_le_bytes());
+    wav.extend_from_slice(b"WAVE");
+    wav.extend_from_slice(b"fmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    wav.extend_from_slice(&1u16.to_le_bytes()); // mono
+    wav.extend_from_slice(&(byte_rate / 2).to_le_bytes());
+    wav.extend_from_slice(&byte_rate.to_le_bytes());
+    wav.extend_from_slice(&2u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&(data_bytes as u32).to_le_bytes());
+    wav.resize(wav.len() + data_bytes, 0);
+    wav
+}
+
+#[test]
+fn test_cues_from_text_split_and_timed_by_length() {
+    let config = CaptionConfig::default();
+    let cues = captions::cues_from_text("Hi there. This is a longer second sentence!", 3_000, &config);
+    assert_eq!(cues.len(), 2);
+    assert_eq!(cues[0].text, "Hi there.");
+    assert_eq!(cues[0].start_ms, 0);
+    // Contiguous, and the longer sentence stays up longer
+    assert_eq!(cues[0].end_ms, cues[1].start_ms);
+    assert_eq!(cues[1].end_ms, 3_000);
+    assert!(cues[1].end_ms - cues[1].start_ms > cues[0].end_ms - cues[0].start_ms);
+
+    // Long sentences are split into cues of at most two lines
+    let long = "word ".repeat(40);
+    let cues = captions::cues_from_text(&long, 10_000, &config);
+    assert!(cues.len() > 1);
+    for cue in &cues {
+        assert!(cue.text.chars().count() <= config.max_cue_chars);
+        assert!(cue.text.lines().count() <= 2);
+        assert!(cue.text.lines().all(|line| line.chars().count() <= config.max_line_chars));
+    }
+    assert!(captions::cues_from_text("   ", 1_000, &config).is_empty());
+}
+
+#[test]
+fn test_cues_from_word_timings() {
+    let words = [("Hello", 0, 400), ("world.", 450, 900), ("How", 1_200, 1_400), ("are", 1_450, 1_600), ("you?", 1_650, 2_000)];
+    let timings: Vec<WordTiming> = words
+        .iter()
+        .map(|(word, start_ms, end_ms)| WordTiming { word: word.to_string(), start_ms: *start_ms, end_ms: *end_ms })
+        .collect();
+    let cues = captions::cues_from_timings(&timings, &CaptionConfig::default());
+    let spans: Vec<(u64, u64, &str)> = cues.iter().map(|c| (c.start_ms, c.end_ms, c.text.as_str())).collect();
+    assert_eq!(spans, vec![(0, 900, "Hello world."), (1_200, 2_000, "How are you?")]);
+
+    // A cue never stays up longer than max_cue_ms
+    let config = CaptionConfig { max_cue_ms: 1_000, ..Default::default() };
+    let cues = captions::cues_from_timings(&timings[2..], &CaptionConfig { max_cue_ms: 500, ..config });
+    assert_eq!(cues.len(), 2);
+}
+
+#[test]
+fn test_webvtt_and_audio_duration() {
+    let cue = CaptionCue { id: 7, start_ms: 3_723_004, end_ms: 3_725_500, text: "Hello".to_string() };
+    assert_eq!(cue.to_webvtt(), "7\n01:02:03.004 --> 01:02:05.500\nHello\n");
+    assert_eq!(webvtt_document(&[cue]), "WEBVTT\n\n7\n01:02:03.004 --> 01:02:05.500\nHello\n");
+
+    let audio = TTSAudio { data: wav(32_000, 32_000), format: AudioFormat::Wav, sample_rate: 16_000 };
+    assert_eq!(captions::audio_duration_ms(&audio), Some(1_000));
+    let audio = TTSAudio { data: vec![0; 16_000], format: AudioFormat::Pcm, sample_rate: 16_000 };
+    assert_eq!(captions::audio_duration_ms(&audio), Some(500));
+    let audio = TTSAudio { data: vec![0; 100], format: AudioFormat::Opus, sample_rate: 48_000 };
+    assert_eq!(captions::audio_duration_ms(&audio), None);
+    assert_eq!(captions::estimate_speech_ms("fifteen chars!!", &CaptionConfig::default()), 1_000);
+}
@@ -0,0 +1,103 @@
+//! Multimodal timeline tests for narayana-me
+
+use narayana_me::multimodal::{AudioFormat, TTSAudio};
+use narayana_me::{LatePolicy, MultimodalManager, MultimodalTimeline, TimelineConfig, TimelineEvent, TimelineTrack, VisemeFrame};
+use tokio::sync::broadcast;
+
+fn audio() -> TTSAudio {
+    TTSAudio { data: vec![0u8; 16], format: AudioFormat::Pcm, sample_rate: 16_000 }
+}
+
+fn viseme(offset_ms: u64, viseme: &str) -> VisemeFrame {
+    VisemeFrame { offset_ms, viseme: viseme.to_string(), weight: 1.0 }
+}
+
+fn drain(rx: &mut broadcast::Receiver<TimelineEvent>) -> Vec<TimelineEvent> {
+    let mut events = Vec::new();
+    while let Ok(event) = rx.try_recv() {
+        events.push(event);
+    }
+    events
+}
+
+#[tokio::test]
+async fn test_audio_chunks_and_visemes_share_the_clock() {
+    let timeline = MultimodalTimeline::new(TimelineConfig { lead_ms: 100, ..Default::default() }).unwrap();
+    let mut rx = timeline.subscribe();
+
+    let first = timeline.schedule_audio(audio(), 500, vec![viseme(0, "aa"), viseme(200, "oh"), viseme(600, "past_end")]).unwrap();
+    assert!(first >= timeline.now_ms() + 90);
+    // The next chunk follows immediately, whatever time it arrives at
+    let second = timeline.schedule_audio(audio(), 300, vec![viseme(100, "ee")]).unwrap();
+    assert_eq!(second, first + 500);
+
+    let events = drain(&mut rx);
+    let starts: Vec<(u64, &str)> = events.iter().map(|e| match &e.track {
+        TimelineTrack::Audio { .. } => (e.start_ms, "audio"),
+        TimelineTrack::Viseme { viseme, .. } => (e.start_ms, viseme.as_str()),
+        _ => (e.start_ms, "other"),
+    }).collect();
+    assert_eq!(starts, vec![(first, "audio"), (first, "aa"), (first + 200, "oh"), (second, "audio"), (second + 100, "ee")]);
+    assert!(events.windows(2).all(|w| w[1].seq == w[0].seq + 1));
+
+    // Subtitles can be pinned to the audio
+    assert_eq!(timeline.schedule_subtitle("Hello", Some(first), 500), Some(first));
+}
+
+#[tokio::test]
+async fn test_late_arrival_policies() {
+    let timeline = MultimodalTimeline::new(TimelineConfig { lead_ms: 200, ..Default::default() }).unwrap();
+    let mut rx = timeline.subscribe();
+    let now = timeline.now_ms();
+
+    // Subtitles are trimmed: the part that is already past is skipped
+    let start = timeline.schedule_subtitle("late", Some(now), 1_000).unwrap();
+    let event = drain(&mut rx).pop().unwrap();
+    assert_eq!(event.start_ms, start);
+    assert!(event.skip_ms >= 200 && event.skip_ms + event.duration_ms == 1_000);
+    // ...and dropped if nothing is left
+    assert_eq!(timeline.schedule_subtitle("gone", Some(now), 50), None);
+
+    // Expressions are shifted whole
+    let start = timeline.schedule_expression("joy", 2.0, Some(0), 300).unwrap();
+    let event = drain(&mut rx).pop().unwrap();
+    assert!(start >= now + 200);
+    assert_eq!((event.duration_ms, event.skip_ms), (300, 0));
+    assert!(matches!(event.track, TimelineTrack::Expression { intensity, .. } if intensity == 1.0));
+
+    let config = TimelineConfig { lead_ms: 200, late_policies: narayana_me::LatePolicies { expression: LatePolicy::Drop, ..Default::default() }, ..Default::default() };
+    let strict = MultimodalTimeline::new(config).unwrap();
+    assert_eq!(strict.schedule_expression("joy", 0.5, Some(0), 300), None);
+    assert!(strict.schedule_expression("joy", 0.5, None, 300).is_some());
+}
+
+#[tokio::test]
+async fn test_drift_correction_and_clear() {
+    let timeline = MultimodalTimeline::new(TimelineConfig { lead_ms: 100, max_drift_ms: 50, drift_smoothing: 1.0, ..Default::default() }).unwrap();
+    let mut rx = timeline.subscribe();
+    // Let the session clock run far enough for a client to be behind it
+    tokio::time::sleep(std::time::Duration::from_millis(350)).await;
+
+    // Client within tolerance: no correction
+    timeline.report_playback(timeline.now_ms().saturating_sub(20));
+    assert_eq!(timeline.correction_ms(), 0);
+
+    // Client 300ms behind: new output is scheduled that much later
+    let now = timeline.now_ms();
+    timeline.report_playback(now.saturating_sub(300));
+    assert!(timeline.drift_ms() >= 290.0);
+    let correction = timeline.correction_ms();
+    assert!(correction >= 290);
+    let start = timeline.schedule_subtitle("behind", None, 100).unwrap();
+    assert!(start >= now + 100 + correction);
+
+    // Clearing restarts the audio cursor and tells clients to flush
+    timeline.schedule_audio(audio(), 10_000, Vec::new()).unwrap();
+    timeline.clear();
+    let next = timeline.schedule_audio(audio(), 100, Vec::new()).unwrap();
+    assert!(next < timeline.now_ms() + 100 + correction + 1_000);
+    assert!(drain(&mut rx).iter().any(|e| matches!(e.track, TimelineTrack::Clear)));
+
+    assert!(MultimodalTimeline::new(TimelineConfig { drift_smoothing: 0.0, ..Default::default() }).is_err());
+    assert!(MultimodalManager::with_timeline_config(TimelineConfig { max_drift_ms: 0, ..Default::default() }).is_err());
+}
@@ -0,0 +1,149 @@
+"""NarayanaDB for Python.
+
+``connect()`` returns a :class:`Client` backed either by the embedded engine
+running in this process or by a NarayanaDB server over HTTP; both expose the
+same table, query and vector-search methods. Query results are columnar and
+convert to pandas DataFrames with :meth:`Result.to_pandas`.
+
+    >>> import narayana
+    >>> db = narayana.connect("./robot-data")            # embedded, on disk
+    >>> db = narayana.connect(":memory:")                # embedded, in memory
+    >>> db = narayana.connect("http://localhost:8080", token="...")
+"""
+
+from ._native import Connection, NarayanaError, connect_http, open_embedded
+
+__all__ = ["Client", "Connection", "NarayanaError", "Result", "connect"]
+
+_FILTER_OPS = {"=", "==", "!=", ">", "<", ">=", "<=", "in"}
+
+
+def connect(target=":memory:", *, token=None, api_key=None):
+    """Open an embedded database (path or ``":memory:"``) or connect to a server URL."""
+    if target.startswith(("http://", "https://")):
+        return Client(connect_http(target, token=token, api_key=api_key))
+    if token is not None or api_key is not None:
+        raise ValueError("token and api_key only apply to HTTP connections")
+    return Client(open_embedded(None if target == ":memory:" else target))
+
+
+def _field(spec):
+    """Normalise ("name", "Int64"[, nullable]) tuples and dicts to field dicts."""
+    if isinstance(spec, dict):
+        field = {"nullable": False, "default_value": None}
+        field.update(spec)
+        if "type" in field:
+            field["data_type"] = field.pop("type")
+        return field
+    name, data_type, *rest = spec
+    return {
+        "name": name,
+        "data_type": data_type,
+        "nullable": bool(rest[0]) if rest else False,
+        "default_value": None,
+    }
+
+
+def _filters(where):
+    """``{"col": value}`` means equality; otherwise a list of (column, op, value)."""
+    if where is None:
+        return None
+    if isinstance(where, dict):
+        return [(column, "=", value) for column, value in where.items()]
+    filters = [tuple(item) for item in where]
+    for column, op, _ in filters:
+        if op not in _FILTER_OPS:
+            raise ValueError(f"unknown filter operator {op!r} for column {column!r}")
+    return filters
+
+
+def _rows(rows):
+    """Accept a DataFrame, a single row dict or an iterable of row dicts."""
+    if hasattr(rows, "to_dict") and hasattr(rows, "columns"):
+        return rows.to_dict(orient="records")
+    if isinstance(rows, dict):
+        return [rows]
+    return list(rows)
+
+
+class Result:
+    """Columnar query result: ``{column: [values...]}`` in selection order."""
+
+    def __init__(self, data):
+        self.data = data
+
+    @property
+    def columns(self):
+        return list(self.data)
+
+    def __len__(self):
+        return len(next(iter(self.data.values()), []))
+
+    def __iter__(self):
+        return iter(self.rows())
+
+    def __repr__(self):
+        return f"Result(columns={self.columns!r}, rows={len(self)})"
+
+    def rows(self):
+        """Rows as a list of dicts."""
+        names = self.columns
+        return [dict(zip(names, values)) for values in zip(*self.data.values())]
+
+    def to_dict(self):
+        return dict(self.data)
+
+    def to_pandas(self):
+        """Build a ``pandas.DataFrame`` (pandas is imported on first use)."""
+        try:
+            import pandas as pd
+        except ImportError as exc:  # pragma: no cover - depends on environment
+            raise ImportError("to_pandas() needs pandas: pip install 'narayana[pandas]'") from exc
+        return pd.DataFrame(self.data, columns=self.columns)
+
+
+class Client:
+    """Same API for the embedded engine and the HTTP server."""
+
+    def __init__(self, connection):
+        self._conn = connection
+
+    @property
+    def is_remote(self):
+        return self._conn.is_remote
+
+    def tables(self):
+        return self._conn.tables()
+
+    def create_table(self, name, fields):
+        """Create a table; ``fields`` is a list of ("name", "Int64") tuples or field dicts,
+        or a ``{name: type}`` dict. Returns the table id."""
+        if isinstance(fields, dict):
+            fields = list(fields.items())
+        return self._conn.create_table(name, [_field(spec) for spec in fields])
+
+    def insert(self, table, rows):
+        """Insert rows (list of dicts or a DataFrame); returns the number written."""
+        return self._conn.insert(table, _rows(rows))
+
+    def query(self, table, columns=None, where=None, offset=0, limit=None):
+        """Select ``columns`` (all by default) from rows matching ``where``."""
+        data = self._conn.query(
+            table,
+            columns=list(columns) if columns is not None else None,
+            filters=_filters(where),
+            offset=offset,
+            limit=limit,
+        )
+        return Result(data)
+
+    def query_df(self, table, **kwargs):
+        """Shorthand for ``query(...).to_pandas()``."""
+        return self.query(table, **kwargs).to_pandas()
+
+    def vector_add(self, index, id, vector, metadata=None):
+        self._conn.vector_add(index, int(id), [float(v) for v in vector], metadata)
+
+    def vector_search(self, index, vector, k=10):
+        """Nearest vectors as dicts with ``id``, ``score`` and ``metadata``, best first."""
+        return self._conn.vector_search(index, [float(v) for v in vector], k)
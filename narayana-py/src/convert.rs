@@ -0,0 +1,138 @@
+// Python <-> JSON conversion
+// Rows and metadata cross the boundary as JSON values; results go back as
+// plain Python lists and dicts so pandas can build frames from them directly.
+
+use narayana_core::column::Column;
+use narayana_embedded::Filter;
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyBytes, PyDict, PyFloat, PyList, PyLong, PyString, PyTuple};
+use serde_json::{Map, Number, Value};
+
+pub fn json_to_py(py: Python<'_>, value: &Value) -> PyResult<PyObject> {
+    Ok(match value {
+        Value::Null => py.None(),
+        Value::Bool(b) => b.into_py(py),
+        Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+            (Some(i), _) => i.into_py(py),
+            (None, Some(u)) => u.into_py(py),
+            _ => n.as_f64().unwrap_or(f64::NAN).into_py(py),
+        },
+        Value::String(s) => s.into_py(py),
+        Value::Array(items) => {
+            let list = PyList::empty(py);
+            for item in items {
+                list.append(json_to_py(py, item)?)?;
+            }
+            list.into_py(py)
+        }
+        Value::Object(map) => {
+            let dict = PyDict::new(py);
+            for (key, item) in map {
+                dict.set_item(key, json_to_py(py, item)?)?;
+            }
+            dict.into_py(py)
+        }
+    })
+}
+
+pub fn py_to_json(obj: &PyAny) -> PyResult<Value> {
+    if obj.is_none() {
+        return Ok(Value::Null);
+    }
+    // bool is a subclass of int, so it goes first
+    if let Ok(b) = obj.downcast::<PyBool>() {
+        return Ok(Value::Bool(b.is_true()));
+    }
+    if obj.downcast::<PyLong>().is_ok() {
+        if let Ok(i) = obj.extract::<i64>() {
+            return Ok(Value::from(i));
+        }
+        return Ok(Value::from(obj.extract::<u64>()?));
+    }
+    if let Ok(f) = obj.downcast::<PyFloat>() {
+        // NaN is how pandas spells a missing value
+        return Ok(Number::from_f64(f.value()).map(Value::Number).unwrap_or(Value::Null));
+    }
+    if let Ok(s) = obj.downcast::<PyString>() {
+        return Ok(Value::String(s.to_str()?.to_string()));
+    }
+    if let Ok(bytes) = obj.downcast::<PyBytes>() {
+        return Ok(Value::from(bytes.as_bytes().to_vec()));
+    }
+    if let Ok(dict) = obj.downcast::<PyDict>() {
+        let mut map = Map::new();
+        for (key, item) in dict {
+            let key = key
+                .downcast::<PyString>()
+                .map_err(|_| PyTypeError::new_err("dict keys must be strings"))?;
+            map.insert(key.to_str()?.to_string(), py_to_json(item)?);
+        }
+        return Ok(Value::Object(map));
+    }
+    if let Ok(list) = obj.downcast::<PyList>() {
+        return list.iter().map(py_to_json).collect::<PyResult<Vec<_>>>().map(Value::Array);
+    }
+    if let Ok(tuple) = obj.downcast::<PyTuple>() {
+        return tuple.iter().map(py_to_json).collect::<PyResult<Vec<_>>>().map(Value::Array);
+    }
+    // numpy scalars and arrays
+    if obj.hasattr("tolist")? {
+        return py_to_json(obj.call_method0("tolist")?);
+    }
+    Err(PyTypeError::new_err(format!(
+        "cannot convert {} to a NarayanaDB value",
+        obj.get_type().name()?
+    )))
+}
+
+/// Build an AND of `(column, op, value)` triples
+pub fn filter_from_py(filters: &PyAny) -> PyResult<Option<Filter>> {
+    let mut combined: Option<Filter> = None;
+    for item in filters.iter()? {
+        let (column, op, value): (String, String, &PyAny) = item?.extract()?;
+        let value = py_to_json(value)?;
+        let filter = match op.as_str() {
+            "=" | "==" => Filter::Eq { column, value },
+            "!=" => Filter::Ne { column, value },
+            ">" => Filter::Gt { column, value },
+            "<" => Filter::Lt { column, value },
+            ">=" => Filter::Gte { column, value },
+            "<=" => Filter::Lte { column, value },
+            "in" => match value {
+                Value::Array(values) => Filter::In { column, values },
+                _ => return Err(PyValueError::new_err("'in' needs a list of values")),
+            },
+            other => return Err(PyValueError::new_err(format!("unknown filter operator '{}'", other))),
+        };
+        combined = Some(match combined {
+            None => filter,
+            Some(left) => Filter::And {
+                left: Box::new(left),
+                right: Box::new(filter),
+            },
+        });
+    }
+    Ok(combined)
+}
+
+/// Values of a column as JSON, via its serialized `{"Type": [...]}` form
+fn column_values(column: &Column) -> Vec<Value> {
+    match serde_json::to_value(column) {
+        Ok(Value::Object(map)) => match map.into_iter().next() {
+            Some((_, Value::Array(values))) => values,
+            _ => Vec::new(),
+        },
+        _ => Vec::new(),
+    }
+}
+
+/// `{name: [values...]}` in column order, ready for `pandas.DataFrame`
+pub fn columns_to_py(py: Python<'_>, names: &[String], columns: &[Column]) -> PyResult<PyObject> {
+    let dict = PyDict::new(py);
+    for (idx, name) in names.iter().enumerate() {
+        let values = columns.get(idx).map(column_values).unwrap_or_default();
+        dict.set_item(name, json_to_py(py, &Value::Array(values))?)?;
+    }
+    Ok(dict.into_py(py))
+}
@@ -0,0 +1,218 @@
+//! narayana-py: Python bindings for NarayanaDB
+//!
+//! Provides the `narayana._native` extension module:
+//! - `open_embedded(path)` for an in-process database (`narayana-embedded`)
+//! - `connect_http(url, token, api_key)` for a running server
+//! - One `Connection` type with the same table, query and vector methods for both
+//!
+//! The pure-Python `narayana` package wraps this with `connect()` and pandas
+//! conversion.
+
+mod convert;
+mod remote;
+
+use narayana_core::column::Column;
+use narayana_core::schema::{Field, Schema};
+use narayana_embedded::{Database, Filter, VectorMatch};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+
+use crate::convert::{columns_to_py, filter_from_py, json_to_py, py_to_json};
+use crate::remote::RemoteClient;
+
+pyo3::create_exception!(_native, NarayanaError, pyo3::exceptions::PyException);
+
+fn to_py_err(e: narayana_core::Error) -> PyErr {
+    NarayanaError::new_err(e.to_string())
+}
+
+enum Backend {
+    Embedded(Database),
+    Remote(RemoteClient),
+}
+
+/// A database handle; embedded or remote behave the same
+#[pyclass(module = "narayana._native")]
+pub struct Connection {
+    backend: Backend,
+    runtime: tokio::runtime::Runtime,
+}
+
+fn runtime() -> PyResult<tokio::runtime::Runtime> {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_all()
+        .build()
+        .map_err(|e| NarayanaError::new_err(format!("Failed to start runtime: {}", e)))
+}
+
+impl Connection {
+    /// Run a future with the GIL released
+    fn block_on<F, T>(&self, py: Python<'_>, future: F) -> PyResult<T>
+    where
+        F: Future<Output = narayana_core::Result<T>> + Send,
+        T: Send,
+    {
+        py.allow_threads(|| self.runtime.block_on(future)).map_err(to_py_err)
+    }
+}
+
+/// Open an embedded database at `path`, or in memory when `path` is None
+#[pyfunction]
+#[pyo3(signature = (path=None))]
+fn open_embedded(py: Python<'_>, path: Option<String>) -> PyResult<Connection> {
+    let runtime = runtime()?;
+    let db = match path {
+        Some(path) => py
+            .allow_threads(|| runtime.block_on(Database::open(path)))
+            .map_err(to_py_err)?,
+        None => Database::in_memory(),
+    };
+    Ok(Connection {
+        backend: Backend::Embedded(db),
+        runtime,
+    })
+}
+
+/// Connect to a NarayanaDB server over HTTP
+#[pyfunction]
+#[pyo3(signature = (url, token=None, api_key=None))]
+fn connect_http(url: &str, token: Option<String>, api_key: Option<String>) -> PyResult<Connection> {
+    Ok(Connection {
+        backend: Backend::Remote(RemoteClient::new(url, token, api_key)),
+        runtime: runtime()?,
+    })
+}
+
+#[pymethods]
+impl Connection {
+    #[getter]
+    fn is_remote(&self) -> bool {
+        matches!(self.backend, Backend::Remote(_))
+    }
+
+    /// Table names
+    fn tables(&self, py: Python<'_>) -> PyResult<Vec<String>> {
+        match &self.backend {
+            Backend::Embedded(db) => Ok(db.table_names()),
+            Backend::Remote(client) => self.block_on(py, client.tables()),
+        }
+    }
+
+    /// Create a table from a list of field dicts (`name`, `data_type`, `nullable`, `default_value`)
+    fn create_table(&self, py: Python<'_>, name: &str, fields: &PyAny) -> PyResult<u64> {
+        let fields: Vec<Field> = serde_json::from_value(py_to_json(fields)?)
+            .map_err(|e| NarayanaError::new_err(format!("Invalid fields: {}", e)))?;
+        let schema = Schema::new(fields);
+        match &self.backend {
+            Backend::Embedded(db) => self.block_on(py, async { Ok(db.create_table(name, schema).await?.id().0) }),
+            Backend::Remote(client) => self.block_on(py, client.create_table(name, schema)),
+        }
+    }
+
+    /// Insert a list of row dicts; returns the number of rows written
+    fn insert(&self, py: Python<'_>, table: &str, rows: &PyAny) -> PyResult<usize> {
+        let rows = match py_to_json(rows)? {
+            Value::Array(rows) => rows,
+            _ => return Err(pyo3::exceptions::PyTypeError::new_err("rows must be a list of dicts")),
+        };
+        match &self.backend {
+            Backend::Embedded(db) => self.block_on(py, async { db.table(table).await?.insert(&rows).await }),
+            Backend::Remote(client) => self.block_on(py, client.insert(table, &rows)),
+        }
+    }
+
+    /// Query a table; returns `{column: [values...]}`
+    #[pyo3(signature = (table, columns=None, filters=None, offset=0, limit=None))]
+    fn query(
+        &self,
+        py: Python<'_>,
+        table: &str,
+        columns: Option<Vec<String>>,
+        filters: Option<&PyAny>,
+        offset: usize,
+        limit: Option<usize>,
+    ) -> PyResult<PyObject> {
+        let filter = match filters {
+            Some(filters) => filter_from_py(filters)?,
+            None => None,
+        };
+        let (names, data) = match &self.backend {
+            Backend::Embedded(db) => self.block_on(py, embedded_query(db, table, columns, filter, offset, limit))?,
+            Backend::Remote(client) => self.block_on(py, client.query(table, columns, filter, offset, limit))?,
+        };
+        columns_to_py(py, &names, &data)
+    }
+
+    /// Add (or replace) a vector; the index is created on first use
+    #[pyo3(signature = (index, id, vector, metadata=None))]
+    fn vector_add(&self, py: Python<'_>, index: &str, id: u64, vector: Vec<f32>, metadata: Option<&PyAny>) -> PyResult<()> {
+        let metadata: HashMap<String, Value> = match metadata {
+            Some(metadata) => serde_json::from_value(py_to_json(metadata)?)
+                .map_err(|e| NarayanaError::new_err(format!("metadata must be a dict: {}", e)))?,
+            None => HashMap::new(),
+        };
+        match &self.backend {
+            Backend::Embedded(db) => {
+                if !db.vector_indexes().contains_key(index) {
+                    db.create_vector_index(index, vector.len()).map_err(to_py_err)?;
+                }
+                db.add_vector(index, id, vector, metadata).map_err(to_py_err)
+            }
+            Backend::Remote(client) => self.block_on(py, client.vector_add(index, id, vector, metadata)),
+        }
+    }
+
+    /// The `k` nearest vectors as dicts with `id`, `score` and `metadata`
+    #[pyo3(signature = (index, vector, k=10))]
+    fn vector_search(&self, py: Python<'_>, index: &str, vector: Vec<f32>, k: usize) -> PyResult<Vec<PyObject>> {
+        let matches: Vec<VectorMatch> = match &self.backend {
+            Backend::Embedded(db) => db.search_vectors(index, &vector, k).map_err(to_py_err)?,
+            Backend::Remote(client) => self.block_on(py, client.vector_search(index, vector, k))?,
+        };
+        matches
+            .into_iter()
+            .map(|m| {
+                let dict = PyDict::new(py);
+                dict.set_item("id", m.id)?;
+                dict.set_item("score", m.score)?;
+                dict.set_item("metadata", json_to_py(py, &serde_json::to_value(m.metadata).unwrap_or(Value::Null))?)?;
+                Ok(dict.into_py(py))
+            })
+            .collect()
+    }
+}
+
+async fn embedded_query(
+    db: &Database,
+    table: &str,
+    columns: Option<Vec<String>>,
+    filter: Option<Filter>,
+    offset: usize,
+    limit: Option<usize>,
+) -> narayana_core::Result<(Vec<String>, Vec<Column>)> {
+    let table = db.table(table).await?;
+    let names = columns.unwrap_or_else(|| table.schema().fields.iter().map(|f| f.name.clone()).collect());
+    let selected: Vec<&str> = names.iter().map(String::as_str).collect();
+    let mut query = table.query().select(&selected).offset(offset);
+    if let Some(filter) = filter {
+        query = query.filter(filter);
+    }
+    if let Some(limit) = limit {
+        query = query.limit(limit);
+    }
+    let data = query.columns().await?;
+    Ok((names, data))
+}
+
+#[pymodule]
+fn _native(py: Python<'_>, m: &PyModule) -> PyResult<()> {
+    m.add("NarayanaError", py.get_type::<NarayanaError>())?;
+    m.add_class::<Connection>()?;
+    m.add_function(wrap_pyfunction!(open_embedded, m)?)?;
+    m.add_function(wrap_pyfunction!(connect_http, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,376 @@
+// HTTP API backend
+// Tables are addressed by name: the id and schema come from the table list.
+// The REST query endpoint serves whole columns a page (at most 10,000 rows)
+// at a time, so queries page through the table and apply filters, offset and
+// limit here with the query engine's filter operator, the same one the
+// embedded engine uses.
+
+use narayana_core::column::Column;
+use narayana_core::schema::Schema;
+use narayana_core::{Error, Result};
+use narayana_embedded::rows::rows_to_columns;
+use narayana_embedded::{Filter, VectorMatch};
+use narayana_query::operators::FilterOperator;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// Largest page the REST query endpoint serves
+const MAX_QUERY_ROWS: usize = 10_000;
+
+pub struct RemoteClient {
+    base_url: String,
+    client: reqwest::Client,
+    token: Option<String>,
+    api_key: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TablesResponse {
+    tables: Vec<RemoteTable>,
+}
+
+#[derive(Deserialize)]
+struct RemoteTable {
+    id: u64,
+    name: String,
+    schema: Option<Schema>,
+}
+
+#[derive(Deserialize)]
+struct CreateTableResponse {
+    table_id: u64,
+}
+
+#[derive(Deserialize)]
+struct InsertResponse {
+    rows_inserted: usize,
+}
+
+#[derive(Deserialize)]
+struct QueryResponse {
+    #[serde(default)]
+    columns: Vec<Column>,
+    #[serde(default)]
+    output_profile: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct VectorSearchResponse {
+    results: Vec<VectorSearchResult>,
+}
+
+#[derive(Deserialize)]
+struct VectorSearchResult {
+    id: u64,
+    similarity: f32,
+    #[serde(default)]
+    metadata: HashMap<String, Value>,
+}
+
+#[derive(Deserialize)]
+struct ErrorResponse {
+    error: String,
+    code: String,
+}
+
+impl RemoteClient {
+    pub fn new(base_url: &str, token: Option<String>, api_key: Option<String>) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            client: reqwest::Client::new(),
+            token,
+            api_key,
+        }
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        let mut request = self.client.request(method, format!("{}{}", self.base_url, path));
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        if let Some(api_key) = &self.api_key {
+            request = request.header("x-api-key", api_key);
+        }
+        request
+    }
+
+    async fn send<T: DeserializeOwned>(&self, request: reqwest::RequestBuilder) -> Result<T> {
+        let response = request
+            .send()
+            .await
+            .map_err(|e| Error::Query(format!("Request failed: {}", e)))?;
+        let status = response.status();
+        let body = response
+            .bytes()
+            .await
+            .map_err(|e| Error::Query(format!("Failed to read response: {}", e)))?;
+        if !status.is_success() {
+            return Err(Error::Query(match serde_json::from_slice::<ErrorResponse>(&body) {
+                Ok(err) => format!("{} ({}, HTTP {})", err.error, err.code, status.as_u16()),
+                Err(_) => format!("HTTP {}: {}", status.as_u16(), String::from_utf8_lossy(&body)),
+            }));
+        }
+        serde_json::from_slice(&body)
+            .map_err(|e| Error::Deserialization(format!("Unexpected response: {}", e)))
+    }
+
+    pub async fn tables(&self) -> Result<Vec<String>> {
+        let response: TablesResponse = self.send(self.request(reqwest::Method::GET, "/api/v1/tables")).await?;
+        Ok(response.tables.into_iter().map(|t| t.name).collect())
+    }
+
+    async fn table(&self, name: &str) -> Result<(u64, Schema)> {
+        let response: TablesResponse = self.send(self.request(reqwest::Method::GET, "/api/v1/tables")).await?;
+        let table = response
+            .tables
+            .into_iter()
+            .find(|t| t.name == name)
+            .ok_or_else(|| Error::Storage(format!("Table '{}' not found", name)))?;
+        let schema = table
+            .schema
+            .ok_or_else(|| Error::Storage(format!("Server did not return a schema for '{}'", name)))?;
+        Ok((table.id, schema))
+    }
+
+    pub async fn create_table(&self, name: &str, schema: Schema) -> Result<u64> {
+        let request = self
+            .request(reqwest::Method::POST, "/api/v1/tables")
+            .json(&json!({ "table_name": name, "schema": schema }));
+        let response: CreateTableResponse = self.send(request).await?;
+        Ok(response.table_id)
+    }
+
+    pub async fn insert(&self, name: &str, rows: &[Value]) -> Result<usize> {
+        if rows.is_empty() {
+            return Ok(0);
+        }
+        let (id, schema) = self.table(name).await?;
+        let columns = rows_to_columns(&schema, rows)?;
+        let request = self
+            .request(reqwest::Method::POST, &format!("/api/v1/tables/{}/insert", id))
+            .json(&json!({ "columns": columns }));
+        let response: InsertResponse = self.send(request).await?;
+        Ok(response.rows_inserted)
+    }
+
+    pub async fn query(
+        &self,
+        name: &str,
+        columns: Option<Vec<String>>,
+        filter: Option<Filter>,
+        offset: usize,
+        limit: Option<usize>,
+    ) -> Result<(Vec<String>, Vec<Column>)> {
+        let (id, schema) = self.table(name).await?;
+        let names = columns.unwrap_or_else(|| schema.fields.iter().map(|f| f.name.clone()).collect());
+        let selected = names
+            .iter()
+            .map(|n| schema.field_index(n).ok_or_else(|| Error::ColumnNotFound(n.clone())))
+            .collect::<Result<Vec<_>>>()?;
+
+        // The filter operator addresses columns by schema position, so it needs all of them
+        let fetch: Vec<usize> = if filter.is_some() { (0..schema.fields.len()).collect() } else { selected.clone() };
+        let positions: Vec<usize> = if filter.is_some() { selected } else { (0..fetch.len()).collect() };
+        let ids = fetch.iter().map(|i| i.to_string()).collect::<Vec<_>>().join(",");
+        let filter = filter.map(|filter| FilterOperator::new(filter, schema));
+        let wanted = limit.map(|limit| offset.saturating_add(limit));
+
+        // Page through the table until enough rows have matched. Pages are
+        // addressed by table row, so one that row policies thin out still
+        // moves the next one a whole page on; only an empty page ends the table.
+        let mut pages: Vec<Vec<Column>> = Vec::new();
+        let mut matched = 0;
+        let mut page_start = 0;
+        while wanted.is_none_or(|wanted| matched < wanted) {
+            let request = self
+                .request(reqwest::Method::GET, &format!("/api/v1/tables/{}/query", id))
+                .query(&[
+                    ("columns", ids.clone()),
+                    ("offset", page_start.to_string()),
+                    ("limit", MAX_QUERY_ROWS.to_string()),
+                ]);
+            let response: QueryResponse = self.send(request).await?;
+            if let Some(profile) = response.output_profile {
+                return Err(Error::Query(format!(
+                    "Reads of '{}' go through output profile '{}', which the client can't filter or page",
+                    name, profile
+                )));
+            }
+            // Nothing written yet, or past the last row
+            if response.columns.len() < fetch.len() || response.columns[0].len() == 0 {
+                break;
+            }
+            let page = match &filter {
+                Some(filter) => filter.apply(&response.columns)?,
+                None => response.columns,
+            };
+            matched += page.first().map(Column::len).unwrap_or(0);
+            pages.push(page);
+            page_start += MAX_QUERY_ROWS;
+        }
+
+        // Cut offset and limit out of the matched rows, page by page
+        let mut skip = offset;
+        let mut take = limit.unwrap_or(usize::MAX);
+        let mut columns: Vec<Option<Column>> = vec![None; positions.len()];
+        for page in &pages {
+            let rows = page.first().map(Column::len).unwrap_or(0);
+            let start = skip.min(rows);
+            let count = take.min(rows - start);
+            skip -= start;
+            take -= count;
+            // Empty slices still give the columns their types
+            if count == 0 && columns.iter().all(Option::is_some) {
+                continue;
+            }
+            for (column, &pos) in columns.iter_mut().zip(&positions) {
+                let part = page[pos].slice(start, count)?;
+                *column = Some(match column.take() {
+                    Some(head) => head.append(&part)?,
+                    None => part,
+                });
+            }
+        }
+        Ok((names, columns.into_iter().flatten().collect()))
+    }
+
+    pub async fn vector_add(&self, index: &str, id: u64, vector: Vec<f32>, metadata: HashMap<String, Value>) -> Result<()> {
+        let request = self
+            .request(reqwest::Method::POST, &format!("/api/v1/vector/{}/add", index))
+            .json(&json!({ "id": id, "vector": vector, "metadata": metadata }));
+        self.send::<Value>(request).await?;
+        Ok(())
+    }
+
+    pub async fn vector_search(&self, index: &str, vector: Vec<f32>, k: usize) -> Result<Vec<VectorMatch>> {
+        let request = self
+            .request(reqwest::Method::POST, "/api/v1/vector/search")
+            .json(&json!({ "index": index, "vector": vector, "k": k }));
+        let response: VectorSearchResponse = self.send(request).await?;
+        Ok(response
+            .results
+            .into_iter()
+            .map(|r| VectorMatch {
+                id: r.id,
+                score: r.similarity,
+                metadata: r.metadata,
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use narayana_core::schema::{DataType, Field};
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    const TABLE_ROWS: i64 = 25_000;
+
+    /// Local stand-in for the server: table 1 is `n` = 0..25000 and `label`;
+    /// returns its URL and the offsets the query endpoint was asked for
+    async fn mock_server(output_profile: Option<&'static str>) -> (String, Arc<Mutex<Vec<usize>>>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let offsets = Arc::new(Mutex::new(Vec::new()));
+        let seen = offsets.clone();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let seen = seen.clone();
+                tokio::spawn(async move {
+                    let mut data = Vec::new();
+                    let mut chunk = [0u8; 4096];
+                    while !data.windows(4).any(|w| w == b"\r\n\r\n") {
+                        let n = socket.read(&mut chunk).await.unwrap();
+                        data.extend_from_slice(&chunk[..n]);
+                    }
+                    let head = String::from_utf8_lossy(&data).to_string();
+                    let target = head.split(' ').nth(1).unwrap().to_string();
+                    let (path, query) = target.split_once('?').unwrap_or((&target, ""));
+                    let params: HashMap<&str, &str> = query.split('&').filter_map(|p| p.split_once('=')).collect();
+                    let body = match path {
+                        "/api/v1/tables" => {
+                            let schema = Schema::new(vec![
+                                Field { name: "n".to_string(), data_type: DataType::Int64, nullable: false, default_value: None },
+                                Field { name: "label".to_string(), data_type: DataType::String, nullable: false, default_value: None },
+                            ]);
+                            json!({"tables": [{"id": 1, "name": "numbers", "schema": schema}]})
+                        }
+                        "/api/v1/tables/1/query" if output_profile.is_some() => {
+                            json!({"columns": [], "row_count": 1, "query_id": 1, "rows": [{"n": 0}], "output_profile": output_profile})
+                        }
+                        "/api/v1/tables/1/query" => {
+                            let offset: usize = params["offset"].parse().unwrap();
+                            let limit: usize = params["limit"].parse().unwrap();
+                            seen.lock().unwrap().push(offset);
+                            let rows: Vec<i64> = (offset as i64..TABLE_ROWS).take(limit).collect();
+                            let columns: Vec<Column> = params["columns"]
+                                .split("%2C")
+                                .map(|id| match id {
+                                    "0" => Column::Int64(rows.clone()),
+                                    _ => Column::String(rows.iter().map(|n| format!("row-{}", n)).collect()),
+                                })
+                                .collect();
+                            json!({"columns": columns, "row_count": rows.len(), "query_id": 1})
+                        }
+                        _ => json!({"error": "Not found", "code": "NOT_FOUND"}),
+                    };
+                    let body = body.to_string();
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    );
+                    socket.write_all(response.as_bytes()).await.unwrap();
+                });
+            }
+        });
+        (url, offsets)
+    }
+
+    #[tokio::test]
+    async fn test_query_pages_until_the_limit_is_met() {
+        let (url, offsets) = mock_server(None).await;
+        let client = RemoteClient::new(&url, None, None);
+        let filter = Filter::Gte { column: "n".to_string(), value: json!(12_000) };
+        let (names, columns) = client
+            .query("numbers", Some(vec!["label".to_string()]), Some(filter), 5, Some(3))
+            .await
+            .unwrap();
+        assert_eq!(names, vec!["label".to_string()]);
+        match &columns[..] {
+            [Column::String(labels)] => assert_eq!(labels, &["row-12005", "row-12006", "row-12007"]),
+            other => panic!("unexpected columns {:?}", other),
+        }
+        // The second page holds enough matches; the third is never fetched
+        assert_eq!(*offsets.lock().unwrap(), vec![0, MAX_QUERY_ROWS]);
+    }
+
+    #[tokio::test]
+    async fn test_query_reads_past_the_page_size() {
+        let (url, offsets) = mock_server(None).await;
+        let client = RemoteClient::new(&url, None, None);
+        let (_, columns) = client.query("numbers", Some(vec!["n".to_string()]), None, 19_998, None).await.unwrap();
+        match &columns[..] {
+            [Column::Int64(n)] => assert_eq!(n, &(19_998..TABLE_ROWS).collect::<Vec<_>>()),
+            other => panic!("unexpected columns {:?}", other),
+        }
+        assert_eq!(*offsets.lock().unwrap(), vec![0, 10_000, 20_000, 30_000]);
+
+        // No match keeps the columns, without rows
+        let filter = Filter::Lt { column: "n".to_string(), value: json!(0) };
+        let (_, columns) = client.query("numbers", None, Some(filter), 0, Some(10)).await.unwrap();
+        assert!(matches!(&columns[..], [Column::Int64(n), Column::String(l)] if n.is_empty() && l.is_empty()));
+    }
+
+    #[tokio::test]
+    async fn test_query_refuses_profiled_reads() {
+        let (url, _) = mock_server(Some("masked")).await;
+        let client = RemoteClient::new(&url, None, None);
+        let err = client.query("numbers", None, None, 0, Some(1)).await.unwrap_err();
+        assert!(err.to_string().contains("masked"), "{}", err);
+    }
+}
@@ -0,0 +1,83 @@
+"""Tests for the Python client against the embedded engine."""
+
+import pytest
+
+import narayana
+
+FIELDS = [("id", "Int64"), ("sensor", "String"), ("value", "Float64", True)]
+
+
+@pytest.fixture
+def db():
+    client = narayana.connect(":memory:")
+    client.create_table("readings", FIELDS)
+    client.insert(
+        "readings",
+        [{"id": i, "sensor": "lidar" if i % 2 == 0 else "imu", "value": i / 10} for i in range(6)],
+    )
+    return client
+
+
+def test_query_filters_and_paging(db):
+    result = db.query("readings", columns=["id"], where={"sensor": "lidar"})
+    assert result.to_dict() == {"id": [0, 2, 4]}
+
+    page = db.query("readings", where=[("value", ">", 0.1)], offset=1, limit=2)
+    assert [row["id"] for row in page.rows()] == [3, 4]
+    assert len(db.query("readings", offset=10)) == 0
+
+    with pytest.raises(ValueError):
+        db.query("readings", where=[("id", "~", 1)])
+    with pytest.raises(narayana.NarayanaError):
+        db.query("readings", columns=["missing"])
+
+
+def test_insert_validation(db):
+    with pytest.raises(narayana.NarayanaError):
+        db.insert("readings", {"id": "one", "sensor": "gps"})
+    assert db.insert("readings", {"id": 6, "sensor": "gps"}) == 1
+    assert db.query("readings", where={"id": 6}).rows() == [{"id": 6, "sensor": "gps", "value": 0.0}]
+
+
+def test_persistence(tmp_path):
+    first = narayana.connect(str(tmp_path))
+    first.create_table("events", {"id": "Int64", "kind": "String"})
+    first.insert("events", [{"id": 1, "kind": "boot"}])
+    first.vector_add("places", 1, [1.0, 0.0], {"name": "dock"})
+    del first
+
+    reopened = narayana.connect(str(tmp_path))
+    assert reopened.tables() == ["events"]
+    assert reopened.query("events").rows() == [{"id": 1, "kind": "boot"}]
+    assert reopened.vector_search("places", [0.9, 0.1], k=1)[0]["metadata"] == {"name": "dock"}
+
+
+def test_vector_search(db):
+    db.vector_add("faces", 1, [1.0, 0.0, 0.0])
+    db.vector_add("faces", 2, [0.0, 1.0, 0.0], {"name": "bob"})
+    hits = db.vector_search("faces", [0.1, 0.9, 0.0], k=2)
+    assert [hit["id"] for hit in hits] == [2, 1]
+    assert hits[0]["score"] > hits[1]["score"]
+    with pytest.raises(narayana.NarayanaError):
+        db.vector_search("faces", [1.0, 0.0], k=1)
+
+
+def test_pandas_round_trip(db):
+    pd = pytest.importorskip("pandas")
+    frame = db.query_df("readings", columns=["id", "value"])
+    assert list(frame.columns) == ["id", "value"]
+    assert frame["id"].tolist() == [0, 1, 2, 3, 4, 5]
+
+    extra = pd.DataFrame({"id": [10, 11], "sensor": ["gps", "gps"], "value": [1.5, float("nan")]})
+    assert db.insert("readings", extra) == 2
+    gps = db.query_df("readings", where={"sensor": "gps"})
+    assert gps["value"].tolist() == [1.5, 0.0]
+
+
+def test_remote_connection_options():
+    with pytest.raises(ValueError):
+        narayana.connect(":memory:", token="secret")
+    client = narayana.connect("http://127.0.0.1:1", token="secret")
+    assert client.is_remote
+    with pytest.raises(narayana.NarayanaError):
+        client.tables()
@@ -90,6 +90,14 @@ impl StatisticsCollector {
                         }
                     }
                 }
+                Filter::Match { .. } => {
+                    // Text matches are usually selective
+                    return 0.05;
+                }
+                Filter::Spatial { .. } => {
+                    // Radius/box predicates cover a small area
+                    return 0.05;
+                }
                 Filter::And { left, right } => {
                     return self.estimate_selectivity(table_id, left) 
                         * self.estimate_selectivity(table_id, right);
@@ -169,6 +177,10 @@ impl CostModel {
                 // Limit reduces cost
                 *limit as f64 * 0.01
             }
+            PlanNode::Predict { input, .. } => {
+                // Model inference is CPU heavy per row
+                self.estimate_cost(input, stats) * 2.0
+            }
         }
     }
 }
@@ -73,6 +73,101 @@ impl Default for AutocompleteContext {
     }
 }
 
+impl AutocompleteContext {
+    /// Context for completing the word that ends at byte offset `cursor`
+    /// (clamped to the query and to a character boundary)
+    pub fn at_cursor(query: &str, cursor: usize) -> Self {
+        let mut cursor = cursor.min(query.len());
+        while !query.is_char_boundary(cursor) {
+            cursor -= 1;
+        }
+        let start = word_start(query, cursor);
+        let previous_words = words(&query[..start]);
+        let after = &query[cursor..];
+        let next_words = words(after.trim_start_matches(is_word_char));
+
+        Self {
+            current_query: query.to_string(),
+            cursor_position: cursor,
+            current_word: query[start..cursor].to_string(),
+            current_clause: clause_at(&previous_words),
+            current_table: referenced_tables(&previous_words, &next_words).into_iter().next(),
+            previous_words,
+            next_words,
+            ..Self::default()
+        }
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Byte offset where the word ending at `cursor` begins
+fn word_start(query: &str, cursor: usize) -> usize {
+    query[..cursor]
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| is_word_char(*c))
+        .last()
+        .map_or(cursor, |(i, _)| i)
+}
+
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !is_word_char(c))
+        .filter(|w| !w.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Clause of the last clause keyword before the cursor
+fn clause_at(previous_words: &[String]) -> Option<ClauseType> {
+    let mut clause = None;
+    let mut last = String::new();
+    for word in previous_words {
+        let word = word.to_lowercase();
+        clause = match word.as_str() {
+            "select" => Some(ClauseType::Select),
+            "from" => Some(ClauseType::From),
+            "where" | "on" => Some(ClauseType::Where),
+            "join" => Some(ClauseType::Join),
+            "by" if last == "group" => Some(ClauseType::GroupBy),
+            "by" if last == "order" => Some(ClauseType::OrderBy),
+            "having" => Some(ClauseType::Having),
+            "limit" => Some(ClauseType::Limit),
+            "insert" => Some(ClauseType::Insert),
+            "update" => Some(ClauseType::Update),
+            "delete" => Some(ClauseType::Delete),
+            "create" => Some(ClauseType::Create),
+            "alter" => Some(ClauseType::Alter),
+            "drop" => Some(ClauseType::Drop),
+            _ => clause,
+        };
+        last = word;
+    }
+    clause
+}
+
+/// Names following FROM, JOIN, INTO or UPDATE anywhere in the query
+fn referenced_tables(previous_words: &[String], next_words: &[String]) -> Vec<String> {
+    let all: Vec<&String> = previous_words.iter().chain(next_words).collect();
+    all.windows(2)
+        .filter(|pair| matches!(pair[0].to_lowercase().as_str(), "from" | "join" | "into" | "update"))
+        .map(|pair| pair[1].clone())
+        .collect()
+}
+
+/// Ranked completions for the word under the cursor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Completions {
+    /// Byte range of the query a chosen suggestion replaces
+    pub replace_start: usize,
+    pub replace_end: usize,
+    pub current_word: String,
+    pub clause: Option<ClauseType>,
+    pub suggestions: Vec<Suggestion>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ClauseType {
     Select,
@@ -243,53 +338,123 @@ impl AutocompleteManager {
 
     /// Get autocomplete suggestions - the best ever
     pub fn get_suggestions(&self, context: AutocompleteContext) -> Result<Vec<Suggestion>> {
+        let mut suggestions = self.collect_suggestions(&context)?;
+
+        // Sort by relevance
+        suggestions.sort_by(|a, b| {
+            b.relevance_score.partial_cmp(&a.relevance_score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        // Filter by minimum relevance
+        suggestions.retain(|s| s.relevance_score >= self.config.min_relevance_score);
+
+        // Limit results
+        suggestions.truncate(self.config.max_suggestions);
+
+        self.record_suggestions(&suggestions);
+        Ok(suggestions)
+    }
+
+    /// Complete the word ending at byte offset `cursor` of a partial query.
+    /// Only suggestions extending that word are kept, one per text, ranked by
+    /// relevance; columns come from the tables the query references (all
+    /// registered tables when it references none) and carry their type.
+    pub fn complete(&self, query: &str, cursor: usize) -> Result<Completions> {
+        let mut context = AutocompleteContext::at_cursor(query, cursor);
+        let mut tables: Vec<String> = self.schemas.iter().map(|s| s.key().clone()).collect();
+        tables.sort();
+
+        // Resolve referenced names case-insensitively to registered tables
+        let referenced: Vec<String> = referenced_tables(&context.previous_words, &context.next_words)
+            .iter()
+            .filter_map(|name| tables.iter().find(|t| t.eq_ignore_ascii_case(name)).cloned())
+            .collect();
+        context.current_table = referenced.first().cloned();
+        let column_sources = if referenced.is_empty() { &tables } else { &referenced };
+        for table in column_sources {
+            if let Some(schema) = self.schemas.get(table) {
+                for field in &schema.fields {
+                    if !context.available_columns.contains(&field.name) {
+                        context.available_columns.push(field.name.clone());
+                    }
+                }
+            }
+        }
+        context.available_tables = tables;
+
+        let word = context.current_word.to_lowercase();
+        let mut best: HashMap<String, Suggestion> = HashMap::new();
+        for suggestion in self.collect_suggestions(&context)? {
+            if !suggestion.text.to_lowercase().starts_with(&word)
+                || suggestion.relevance_score < self.config.min_relevance_score
+            {
+                continue;
+            }
+            match best.get(&suggestion.text) {
+                Some(kept) if kept.relevance_score >= suggestion.relevance_score => {}
+                _ => {
+                    best.insert(suggestion.text.clone(), suggestion);
+                }
+            }
+        }
+        let mut suggestions: Vec<Suggestion> = best.into_values().collect();
+        suggestions.sort_by(|a, b| {
+            b.relevance_score.partial_cmp(&a.relevance_score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.text.cmp(&b.text))
+        });
+        suggestions.truncate(self.config.max_suggestions);
+        self.record_suggestions(&suggestions);
+
+        let replace_end = context.cursor_position;
+        Ok(Completions {
+            replace_start: replace_end - context.current_word.len(),
+            replace_end,
+            current_word: context.current_word,
+            clause: context.current_clause,
+            suggestions,
+        })
+    }
+
+    fn record_suggestions(&self, suggestions: &[Suggestion]) {
+        let mut stats = self.stats.write();
+        stats.total_suggestions += suggestions.len() as u64;
+        if !suggestions.is_empty() {
+            let avg_relevance: f64 = suggestions.iter().map(|s| s.relevance_score).sum::<f64>() / suggestions.len() as f64;
+            stats.average_relevance = (stats.average_relevance + avg_relevance) / 2.0;
+        }
+    }
+
+    /// Every candidate for `context`, unranked
+    fn collect_suggestions(&self, context: &AutocompleteContext) -> Result<Vec<Suggestion>> {
         let mut suggestions = Vec::new();
-        
+
         // 1. Context-aware suggestions based on current clause
         if self.config.enable_context_aware {
-            suggestions.extend(self.get_context_aware_suggestions(&context)?);
+            suggestions.extend(self.get_context_aware_suggestions(context)?);
         }
         
         // 2. Keyword suggestions
-        suggestions.extend(self.get_keyword_suggestions(&context)?);
+        suggestions.extend(self.get_keyword_suggestions(context)?);
         
         // 3. Table/column suggestions
-        suggestions.extend(self.get_schema_suggestions(&context)?);
+        suggestions.extend(self.get_schema_suggestions(context)?);
         
         // 4. Function suggestions
-        suggestions.extend(self.get_function_suggestions(&context)?);
+        suggestions.extend(self.get_function_suggestions(context)?);
         
         // 5. Smart suggestions based on user patterns
         if self.config.enable_learning {
-            suggestions.extend(self.get_learning_suggestions(&context)?);
+            suggestions.extend(self.get_learning_suggestions(context)?);
         }
         
         // 6. Popular query suggestions
-        suggestions.extend(self.get_popular_query_suggestions(&context)?);
+        suggestions.extend(self.get_popular_query_suggestions(context)?);
         
         // 7. Fuzzy matching suggestions
         if self.config.enable_fuzzy_matching {
-            suggestions.extend(self.get_fuzzy_suggestions(&context)?);
-        }
-        
-        // Sort by relevance
-        suggestions.sort_by(|a, b| {
-            b.relevance_score.partial_cmp(&a.relevance_score)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
-        
-        // Filter by minimum relevance
-        suggestions.retain(|s| s.relevance_score >= self.config.min_relevance_score);
-        
-        // Limit results
-        suggestions.truncate(self.config.max_suggestions);
-        
-        // Update statistics
-        let mut stats = self.stats.write();
-        stats.total_suggestions += suggestions.len() as u64;
-        if !suggestions.is_empty() {
-            let avg_relevance: f64 = suggestions.iter().map(|s| s.relevance_score).sum::<f64>() / suggestions.len() as f64;
-            stats.average_relevance = (stats.average_relevance + avg_relevance) / 2.0;
+            suggestions.extend(self.get_fuzzy_suggestions(context)?);
         }
         
         Ok(suggestions)
@@ -305,14 +470,18 @@ impl AutocompleteManager {
                 if let Some(ref table) = context.current_table {
                     if let Some(schema) = self.schemas.get(table) {
                         for field in &schema.fields {
+                            let data_type = self.format_type(&field.data_type);
                             suggestions.push(Suggestion {
                                 text: field.name.clone(),
-                                display_text: format!("{} ({})", field.name, self.format_type(&field.data_type)),
+                                display_text: format!("{} ({})", field.name, data_type),
                                 suggestion_type: SuggestionType::Column,
                                 relevance_score: 0.9,
                                 description: Some(format!("Column from {}", table)),
                                 icon: Some("column".to_string()),
-                                metadata: HashMap::new(),
+                                metadata: HashMap::from([
+                                    ("table".to_string(), table.clone()),
+                                    ("data_type".to_string(), data_type),
+                                ]),
                             });
                         }
                     }
@@ -421,7 +590,7 @@ impl AutocompleteManager {
                     0.65
                 };
                 
-                suggestions.push(Suggestion {
+                let mut suggestion = Suggestion {
                     text: column.clone(),
                     display_text: format!("📋 {}", column),
                     suggestion_type: SuggestionType::Column,
@@ -429,7 +598,15 @@ impl AutocompleteManager {
                     description: None,
                     icon: Some("column".to_string()),
                     metadata: HashMap::new(),
-                });
+                };
+                if let Some((table, data_type)) = self.column_source(context, column) {
+                    let data_type = self.format_type(&data_type);
+                    suggestion.display_text = format!("📋 {} ({})", column, data_type);
+                    suggestion.description = Some(format!("Column from {}", table));
+                    suggestion.metadata.insert("table".to_string(), table);
+                    suggestion.metadata.insert("data_type".to_string(), data_type);
+                }
+                suggestions.push(suggestion);
             }
         }
         
@@ -856,6 +1033,34 @@ impl AutocompleteManager {
         self.schemas.insert(table_name, schema);
     }
 
+    /// Replace the registered schemas, forgetting tables not in `schemas`
+    pub fn replace_schemas(&self, schemas: Vec<(String, Schema)>) {
+        let names: HashSet<&String> = schemas.iter().map(|(name, _)| name).collect();
+        self.schemas.retain(|name, _| names.contains(name));
+        for (name, schema) in schemas {
+            self.schemas.insert(name, schema);
+        }
+    }
+
+    /// Table and type of `column`, looked up in the current table first
+    fn column_source(&self, context: &AutocompleteContext, column: &str) -> Option<(String, DataType)> {
+        let find = |table: &String, schema: &Schema| {
+            schema.fields.iter()
+                .find(|f| f.name == column)
+                .map(|f| (table.clone(), f.data_type.clone()))
+        };
+        if let Some(found) = context.current_table.as_ref()
+            .and_then(|table| self.schemas.get(table).and_then(|schema| find(table, &schema)))
+        {
+            return Some(found);
+        }
+        let mut found: Vec<(String, DataType)> = self.schemas.iter()
+            .filter_map(|entry| find(entry.key(), entry.value()))
+            .collect();
+        found.sort_by(|a, b| a.0.cmp(&b.0));
+        found.into_iter().next()
+    }
+
     /// Helper methods
     fn get_keyword_description(&self, keyword: &str) -> String {
         match keyword.to_lowercase().as_str() {
@@ -950,3 +1155,88 @@ impl AutocompleteManager {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(name: &str, data_type: DataType) -> Field {
+        Field { name: name.to_string(), data_type, nullable: false, default_value: None }
+    }
+
+    fn manager() -> AutocompleteManager {
+        let manager = AutocompleteManager::new(AutocompleteConfig::default());
+        manager.register_schema("users".to_string(), Schema::new(vec![
+            field("id", DataType::Int64),
+            field("name", DataType::String),
+        ]));
+        manager.register_schema("orders".to_string(), Schema::new(vec![
+            field("id", DataType::Int64),
+            field("amount", DataType::Float64),
+        ]));
+        manager
+    }
+
+    fn texts(completions: &Completions) -> Vec<&str> {
+        completions.suggestions.iter().map(|s| s.text.as_str()).collect()
+    }
+
+    #[test]
+    fn test_context_at_cursor() {
+        let query = "SELECT na FROM users WHERE id > 3 ORDER BY ";
+        let context = AutocompleteContext::at_cursor(query, 9);
+        assert_eq!(context.current_word, "na");
+        assert_eq!(context.current_clause, Some(ClauseType::Select));
+        assert_eq!(context.current_table.as_deref(), Some("users"));
+        assert_eq!(context.next_words[0], "FROM");
+
+        let context = AutocompleteContext::at_cursor(query, query.len());
+        assert_eq!(context.current_word, "");
+        assert_eq!(context.current_clause, Some(ClauseType::OrderBy));
+
+        // Cursors past the end or inside a character are clamped
+        let context = AutocompleteContext::at_cursor("SELECT é", 100);
+        assert_eq!(context.current_word, "é");
+        let context = AutocompleteContext::at_cursor("SELECT é", 8);
+        assert_eq!(context.cursor_position, 7);
+    }
+
+    #[test]
+    fn test_complete_keywords_tables_and_columns() {
+        let manager = manager();
+
+        let completions = manager.complete("sel", 3).unwrap();
+        assert_eq!(texts(&completions)[0], "SELECT");
+        assert_eq!((completions.replace_start, completions.replace_end), (0, 3));
+
+        let completions = manager.complete("SELECT * FROM o", 15).unwrap();
+        assert_eq!(texts(&completions), vec!["orders", "OFFSET", "ON", "OR", "ORDER", "OUTER"]);
+        assert_eq!(completions.suggestions[0].suggestion_type, SuggestionType::Table);
+
+        // Columns come from the referenced table, with their type
+        let completions = manager.complete("SELECT a FROM users", 8).unwrap();
+        assert!(!texts(&completions).contains(&"amount"));
+        let completions = manager.complete("SELECT a FROM orders", 8).unwrap();
+        let amount = completions.suggestions.iter().find(|s| s.text == "amount").unwrap();
+        assert_eq!(amount.suggestion_type, SuggestionType::Column);
+        assert_eq!(amount.metadata["data_type"], "Float64");
+        assert_eq!(amount.metadata["table"], "orders");
+        assert_eq!(completions.clause, Some(ClauseType::Select));
+
+        // Every suggestion extends the word, once
+        let completions = manager.complete("SELECT * FROM users WHERE n", 27).unwrap();
+        assert!(texts(&completions).iter().all(|t| t.to_lowercase().starts_with('n')));
+        assert_eq!(texts(&completions).iter().filter(|t| **t == "name").count(), 1);
+        assert_eq!(completions.suggestions[0].text, "name");
+        assert_eq!(completions.suggestions[0].metadata["data_type"], "String");
+    }
+
+    #[test]
+    fn test_replace_schemas_forgets_dropped_tables() {
+        let manager = manager();
+        manager.replace_schemas(vec![("users".to_string(), Schema::new(vec![field("id", DataType::Int64)]))]);
+        let completions = manager.complete("SELECT * FROM ", 14).unwrap();
+        assert!(texts(&completions).contains(&"users"));
+        assert!(!texts(&completions).contains(&"orders"));
+    }
+}
@@ -0,0 +1,303 @@
+// Query registry and cooperative cancellation
+// Running statements get an id, report progress, and can be cancelled; the
+// executor checks the context between plan nodes and row chunks.
+
+use narayana_core::{Error, Result};
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::watch;
+
+pub type QueryId = u64;
+
+/// Cancellation flag shared between a running query and whoever may cancel it
+#[derive(Debug, Clone)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    notify: Arc<watch::Sender<bool>>,
+}
+
+impl Default for CancellationToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        let (notify, _) = watch::channel(false);
+        Self {
+            cancelled: Arc::new(AtomicBool::new(false)),
+            notify: Arc::new(notify),
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        self.notify.send_replace(true);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Resolves once the token is cancelled
+    pub async fn cancelled(&self) {
+        let mut receiver = self.notify.subscribe();
+        if self.is_cancelled() {
+            return;
+        }
+        // The sender lives as long as the token, so this only ends on cancel
+        let _ = receiver.wait_for(|cancelled| *cancelled).await;
+    }
+}
+
+/// Progress counters updated by the executor
+#[derive(Debug, Default)]
+pub struct QueryProgress {
+    rows_processed: AtomicU64,
+    nodes_completed: AtomicUsize,
+    nodes_total: AtomicUsize,
+    stage: Mutex<String>,
+}
+
+impl QueryProgress {
+    pub fn add_rows(&self, rows: usize) {
+        self.rows_processed.fetch_add(rows as u64, Ordering::Relaxed);
+    }
+
+    pub fn set_stage(&self, stage: impl Into<String>) {
+        *self.stage.lock() = stage.into();
+    }
+
+    pub fn set_total_nodes(&self, nodes: usize) {
+        self.nodes_total.store(nodes, Ordering::Relaxed);
+    }
+
+    pub fn node_completed(&self) {
+        self.nodes_completed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn rows_processed(&self) -> u64 {
+        self.rows_processed.load(Ordering::Relaxed)
+    }
+
+    /// Fraction of plan nodes finished, when the plan size is known
+    pub fn fraction(&self) -> Option<f64> {
+        let total = self.nodes_total.load(Ordering::Relaxed);
+        (total > 0).then(|| (self.nodes_completed.load(Ordering::Relaxed) as f64 / total as f64).min(1.0))
+    }
+}
+
+/// Per-execution state passed through the executor
+#[derive(Debug, Clone)]
+pub struct QueryContext {
+    pub id: QueryId,
+    pub token: CancellationToken,
+    pub progress: Arc<QueryProgress>,
+}
+
+impl Default for QueryContext {
+    fn default() -> Self {
+        Self::detached()
+    }
+}
+
+impl QueryContext {
+    /// A context that is not tracked by any registry
+    pub fn detached() -> Self {
+        Self {
+            id: 0,
+            token: CancellationToken::new(),
+            progress: Arc::new(QueryProgress::default()),
+        }
+    }
+
+    /// Cooperative cancellation point
+    pub fn check(&self) -> Result<()> {
+        if self.token.is_cancelled() {
+            return Err(cancelled_error(self.id));
+        }
+        Ok(())
+    }
+
+    /// Run `future`, abandoning it if the query is cancelled first
+    pub async fn run<T>(&self, future: impl std::future::Future<Output = Result<T>>) -> Result<T> {
+        self.check()?;
+        tokio::select! {
+            result = future => result,
+            _ = self.token.cancelled() => Err(cancelled_error(self.id)),
+        }
+    }
+}
+
+fn cancelled_error(id: QueryId) -> Error {
+    Error::Query(format!("Query {} was cancelled", id))
+}
+
+/// Whether an error came from a cancellation check
+pub fn is_cancelled_error(error: &Error) -> bool {
+    matches!(error, Error::Query(message) if message.starts_with("Query ") && message.ends_with(" was cancelled"))
+}
+
+/// Snapshot of a running query
+#[derive(Debug, Clone, Serialize)]
+pub struct QueryInfo {
+    pub id: QueryId,
+    pub description: String,
+    pub user: Option<String>,
+    /// Unix milliseconds
+    pub started_at: u64,
+    pub elapsed_ms: u64,
+    pub stage: String,
+    pub rows_processed: u64,
+    pub progress: Option<f64>,
+    pub cancelling: bool,
+}
+
+struct RunningQuery {
+    context: QueryContext,
+    description: String,
+    user: Option<String>,
+    started_at: u64,
+    started: Instant,
+}
+
+/// Active queries by id
+#[derive(Default)]
+pub struct QueryRegistry {
+    next_id: AtomicU64,
+    running: RwLock<HashMap<QueryId, RunningQuery>>,
+}
+
+/// Registration of a running query; unregisters on drop
+pub struct QueryHandle {
+    registry: Arc<QueryRegistry>,
+    context: QueryContext,
+}
+
+impl QueryHandle {
+    pub fn id(&self) -> QueryId {
+        self.context.id
+    }
+
+    pub fn context(&self) -> &QueryContext {
+        &self.context
+    }
+}
+
+impl Drop for QueryHandle {
+    fn drop(&mut self) {
+        self.registry.running.write().remove(&self.context.id);
+    }
+}
+
+impl QueryRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Track a new query until the returned handle is dropped
+    pub fn register(self: &Arc<Self>, description: impl Into<String>, user: Option<String>) -> QueryHandle {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let context = QueryContext {
+            id,
+            token: CancellationToken::new(),
+            progress: Arc::new(QueryProgress::default()),
+        };
+        let started_at = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64;
+        self.running.write().insert(id, RunningQuery {
+            context: context.clone(),
+            description: description.into(),
+            user,
+            started_at,
+            started: Instant::now(),
+        });
+        QueryHandle { registry: self.clone(), context }
+    }
+
+    pub fn get(&self, id: QueryId) -> Option<QueryInfo> {
+        self.running.read().get(&id).map(|q| Self::info(id, q))
+    }
+
+    /// Running queries, oldest first
+    pub fn list(&self) -> Vec<QueryInfo> {
+        let mut queries: Vec<QueryInfo> = self.running.read().iter().map(|(id, q)| Self::info(*id, q)).collect();
+        queries.sort_by_key(|q| q.id);
+        queries
+    }
+
+    /// Request cancellation; returns false if no such query is running
+    pub fn cancel(&self, id: QueryId) -> bool {
+        match self.running.read().get(&id) {
+            Some(query) => {
+                query.context.token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn info(id: QueryId, query: &RunningQuery) -> QueryInfo {
+        QueryInfo {
+            id,
+            description: query.description.clone(),
+            user: query.user.clone(),
+            started_at: query.started_at,
+            elapsed_ms: query.started.elapsed().as_millis() as u64,
+            stage: query.context.progress.stage.lock().clone(),
+            rows_processed: query.context.progress.rows_processed(),
+            progress: query.context.progress.fraction(),
+            cancelling: query.context.token.is_cancelled(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register_list_and_drop() {
+        let registry = Arc::new(QueryRegistry::new());
+        let first = registry.register("scan t1", Some("alice".to_string()));
+        let second = registry.register("scan t2", None);
+        first.context().progress.add_rows(10);
+
+        let listed = registry.list();
+        assert_eq!(listed.iter().map(|q| q.id).collect::<Vec<_>>(), vec![first.id(), second.id()]);
+        assert_eq!(listed[0].rows_processed, 10);
+        assert_eq!(listed[0].user.as_deref(), Some("alice"));
+
+        drop(first);
+        assert_eq!(registry.list().len(), 1);
+        assert!(registry.get(second.id()).is_some());
+    }
+
+    #[test]
+    fn test_cancel_marks_context() {
+        let registry = Arc::new(QueryRegistry::new());
+        let handle = registry.register("scan", None);
+        assert!(handle.context().check().is_ok());
+        assert!(registry.cancel(handle.id()));
+        assert!(registry.get(handle.id()).unwrap().cancelling);
+        let error = handle.context().check().unwrap_err();
+        assert!(is_cancelled_error(&error));
+        assert!(!registry.cancel(999));
+    }
+
+    #[tokio::test]
+    async fn test_run_aborts_pending_future() {
+        let context = QueryContext::detached();
+        let token = context.token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            token.cancel();
+        });
+        let result: Result<()> = context.run(std::future::pending()).await;
+        assert!(is_cancelled_error(&result.unwrap_err()));
+    }
+}
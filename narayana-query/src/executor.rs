@@ -1,8 +1,13 @@
 use async_trait::async_trait;
 use narayana_core::{Error, Result, column::Column, schema::Schema, types::TableId};
-use narayana_storage::ColumnStore;
+use narayana_core::schema::DataType;
+use narayana_storage::{BlockPredicate, ColumnStore, ZoneValue};
 use crate::plan::{QueryPlan, PlanNode, Filter};
 use crate::operators::{FilterOperator, ProjectOperator};
+use crate::ml_integration::PredictEngine;
+use crate::cancellation::QueryContext;
+use std::ops::Range;
+use std::sync::Arc;
 use tracing::{info, debug};
 
 #[async_trait]
@@ -12,53 +17,88 @@ pub trait QueryExecutor: Send + Sync {
 
 pub struct DefaultQueryExecutor<S: ColumnStore> {
     pub store: S,
+    predictor: Option<Arc<PredictEngine>>,
 }
 
 impl<S: ColumnStore> DefaultQueryExecutor<S> {
     pub fn new(store: S) -> Self {
-        Self { store }
+        Self { store, predictor: None }
+    }
+
+    /// Enable PREDICT plan nodes
+    pub fn with_predictor(mut self, predictor: Arc<PredictEngine>) -> Self {
+        self.predictor = Some(predictor);
+        self
     }
 }
 
+/// Rows filtered between cancellation checks
+const ROW_CHUNK: usize = 64 * 1024;
+
 #[async_trait]
 impl<S: ColumnStore> QueryExecutor for DefaultQueryExecutor<S> {
     async fn execute(&self, plan: QueryPlan) -> Result<Vec<Column>> {
-        info!("Executing query plan");
-        self.execute_node(&plan.root, TableId(0)).await
+        self.execute_with_context(plan, &QueryContext::detached()).await
     }
 }
 
 impl<S: ColumnStore> DefaultQueryExecutor<S> {
-    fn execute_node<'a>(&'a self, node: &'a PlanNode, table_id: TableId) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Vec<Column>>> + Send + 'a>> {
+    /// Execute a plan, reporting progress to and honouring cancellation of `context`
+    pub async fn execute_with_context(&self, plan: QueryPlan, context: &QueryContext) -> Result<Vec<Column>> {
+        info!("Executing query plan (query {})", context.id);
+        context.progress.set_total_nodes(count_nodes(&plan.root));
+        self.execute_node(&plan.root, TableId(0), context).await
+    }
+
+    fn execute_node<'a>(&'a self, node: &'a PlanNode, table_id: TableId, context: &'a QueryContext) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<Vec<Column>>> + Send + 'a>> {
         let self_ref = self;
         let node_ref = node;
         Box::pin(async move {
-            match node_ref {
+            context.check()?;
+            let result = self_ref.execute_node_inner(node_ref, table_id, context).await;
+            if result.is_ok() {
+                context.progress.node_completed();
+            }
+            result
+        })
+    }
+
+    async fn execute_node_inner(&self, node: &PlanNode, table_id: TableId, context: &QueryContext) -> Result<Vec<Column>> {
+        let self_ref = self;
+        match node {
             PlanNode::Scan { table_id, column_ids, filter: _ } => {
                 debug!("Executing scan on table {} for columns {:?}", table_id, column_ids);
-                let columns = self_ref.store
-                    .read_columns(narayana_core::types::TableId(*table_id), column_ids.clone(), 0, usize::MAX)
+                context.progress.set_stage(format!("scan table {}", table_id));
+                let columns = context.run(self_ref.store
+                    .read_columns(narayana_core::types::TableId(*table_id), column_ids.clone(), 0, usize::MAX))
                     .await?;
+                context.progress.add_rows(columns.first().map(|c| c.len()).unwrap_or(0));
                 Ok(columns)
             }
             PlanNode::Filter { predicate, input } => {
                 debug!("Executing filter");
-                // Recursive call - need to box it
-                let input_columns = Self::execute_node(self_ref, input, table_id).await?;
+                let input_columns = match input.as_ref() {
+                    PlanNode::Scan { table_id: scan_table, column_ids, .. } => {
+                        self_ref.scan_for_filter(TableId(*scan_table), column_ids, predicate, input, table_id, context).await?
+                    }
+                    // Recursive call - need to box it
+                    _ => Self::execute_node(self_ref, input, table_id, context).await?,
+                };
+                context.progress.set_stage("filter");
                 let schema = self_ref.store.get_schema(table_id).await?;
                 let filter_op = FilterOperator::new(predicate.clone(), schema);
-                filter_op.apply(&input_columns)
+                filter_in_chunks(&filter_op, &input_columns, context)
             }
             PlanNode::Project { columns, input } => {
                 debug!("Executing project on columns {:?}", columns);
-                let input_columns = Self::execute_node(self_ref, input, table_id).await?;
+                let input_columns = Self::execute_node(self_ref, input, table_id, context).await?;
                 let schema = self_ref.store.get_schema(table_id).await?;
                 let project_op = ProjectOperator::new(columns.clone(), schema)?;
                 Ok(project_op.apply(&input_columns))
             }
             PlanNode::Limit { limit, offset: _, input } => {
                 debug!("Executing limit: {}", limit);
-                let mut columns = Self::execute_node(self_ref, input, table_id).await?;
+                let mut columns = Self::execute_node(self_ref, input, table_id, context).await?;
                 // Apply limit to all columns
                 for col in &mut columns {
                     match col {
@@ -85,9 +125,220 @@ impl<S: ColumnStore> DefaultQueryExecutor<S> {
                 }
                 Ok(columns)
             }
+            PlanNode::Predict { model, features, output, input } => {
+                debug!("Executing PREDICT with model {}", model);
+                let predictor = self_ref.predictor.as_ref()
+                    .ok_or_else(|| Error::Query("PREDICT requires a model registry".to_string()))?;
+                let mut columns = Self::execute_node(self_ref, input, table_id, context).await?;
+                context.progress.set_stage(format!("predict {}", model));
+                // Input columns are the scanned column ids, in scan order
+                let (scan_table, column_ids) = scanned_columns(input)
+                    .ok_or_else(|| Error::Query("PREDICT input must read from a single table scan".to_string()))?;
+                let schema = self_ref.store.get_schema(scan_table).await?;
+                let feature_columns = features.iter()
+                    .map(|name| schema.field_index(name)
+                        .and_then(|idx| column_ids.iter().position(|id| *id as usize == idx))
+                        .and_then(|pos| columns.get(pos))
+                        .ok_or_else(|| Error::ColumnNotFound(name.clone())))
+                    .collect::<Result<Vec<&Column>>>()?;
+                let predicted = predictor.predict(model, &feature_columns, *output)?;
+                columns.push(predicted);
+                Ok(columns)
+            }
             _ => Err(Error::Query("Unsupported plan node".to_string())),
+        }
+    }
+}
+
+impl<S: ColumnStore> DefaultQueryExecutor<S> {
+    /// Scan beneath a filter, reading only the blocks whose zone maps admit it
+    async fn scan_for_filter(
+        &self,
+        scan_table: TableId,
+        column_ids: &[u32],
+        predicate: &Filter,
+        scan: &PlanNode,
+        table_id: TableId,
+        context: &QueryContext,
+    ) -> Result<Vec<Column>> {
+        context.check()?;
+        let Some(ranges) = filter_row_ranges(&self.store, scan_table, predicate).await? else {
+            return Self::execute_node(self, scan, table_id, context).await;
+        };
+        debug!("Executing pruned scan on table {} over {} row ranges", scan_table.0, ranges.len());
+        context.progress.set_stage(format!("scan table {}", scan_table.0));
+        // An empty read still yields correctly typed, empty columns
+        let ranges = if ranges.is_empty() { vec![Range { start: 0, end: 0 }] } else { ranges };
+        let mut columns: Option<Vec<Column>> = None;
+        for range in &ranges {
+            let part = context.run(self.store
+                .read_columns(scan_table, column_ids.to_vec(), range.start, range.len()))
+                .await?;
+            context.progress.add_rows(part.first().map(|c| c.len()).unwrap_or(0));
+            columns = Some(match columns {
+                None => part,
+                Some(done) => done.iter().zip(&part).map(|(a, b)| a.append(b)).collect::<Result<Vec<Column>>>()?,
+            });
+        }
+        context.progress.node_completed();
+        Ok(columns.unwrap_or_default())
+    }
+}
+
+/// Row ranges of the blocks that may hold rows matching every prunable
+/// conjunct of `predicate`; None when no conjunct could be pruned on
+pub async fn filter_row_ranges(
+    store: &dyn ColumnStore,
+    table_id: TableId,
+    predicate: &Filter,
+) -> Result<Option<Vec<Range<usize>>>> {
+    let schema = store.get_schema(table_id).await?;
+    let mut conjuncts = Vec::new();
+    collect_conjuncts(predicate, &mut conjuncts);
+    let mut ranges: Option<Vec<Range<usize>>> = None;
+    for conjunct in conjuncts {
+        let Some((column_id, block_predicate)) = zone_predicate(conjunct, &schema) else {
+            continue;
+        };
+        let Some(matching) = store.matching_row_ranges(table_id, column_id, &block_predicate).await? else {
+            continue;
+        };
+        ranges = Some(match ranges {
+            None => matching,
+            Some(current) => intersect_ranges(&current, &matching),
+        });
+    }
+    Ok(ranges)
+}
+
+fn collect_conjuncts<'a>(filter: &'a Filter, out: &mut Vec<&'a Filter>) {
+    match filter {
+        Filter::And { left, right } => {
+            collect_conjuncts(left, out);
+            collect_conjuncts(right, out);
+        }
+        other => out.push(other),
+    }
+}
+
+/// Block predicate equivalent to `filter` on a single column. Only columns
+/// whose comparisons are exact are pruned on: float equality is
+/// epsilon-tolerant, so Float64 never qualifies
+fn zone_predicate(filter: &Filter, schema: &Schema) -> Option<(u32, BlockPredicate)> {
+    let column_zone = |column: &str, value: &serde_json::Value| {
+        let idx = schema.field_index(column)?;
+        zone_value(&schema.fields[idx].data_type, value).map(|v| (idx as u32, v))
+    };
+    match filter {
+        Filter::Eq { column, value } => column_zone(column, value).map(|(id, v)| (id, BlockPredicate::Eq(v))),
+        Filter::Ne { column, value } => column_zone(column, value).map(|(id, v)| (id, BlockPredicate::Ne(v))),
+        Filter::Gt { column, value } => column_zone(column, value).map(|(id, v)| (id, BlockPredicate::Gt(v))),
+        Filter::Gte { column, value } => column_zone(column, value).map(|(id, v)| (id, BlockPredicate::Gte(v))),
+        Filter::Lt { column, value } => column_zone(column, value).map(|(id, v)| (id, BlockPredicate::Lt(v))),
+        Filter::Lte { column, value } => column_zone(column, value).map(|(id, v)| (id, BlockPredicate::Lte(v))),
+        Filter::Between { column, low, high } => {
+            let (id, low) = column_zone(column, low)?;
+            let (_, high) = column_zone(column, high)?;
+            Some((id, BlockPredicate::Between(low, high)))
+        }
+        Filter::In { column, values } => {
+            let id = schema.field_index(column)? as u32;
+            let values = values.iter()
+                .map(|value| column_zone(column, value).map(|(_, v)| v))
+                .collect::<Option<Vec<_>>>()?;
+            Some((id, BlockPredicate::In(values)))
+        }
+        Filter::And { left, right } | Filter::Or { left, right } => {
+            let (left_id, left_pred) = zone_predicate(left, schema)?;
+            let (right_id, right_pred) = zone_predicate(right, schema)?;
+            if left_id != right_id {
+                return None;
             }
-        })
+            let (l, r) = (Box::new(left_pred), Box::new(right_pred));
+            Some((left_id, match filter {
+                Filter::And { .. } => BlockPredicate::And(l, r),
+                _ => BlockPredicate::Or(l, r),
+            }))
+        }
+        Filter::Not { .. } | Filter::Match { .. } | Filter::Spatial { .. } => None,
     }
 }
 
+/// Zone value of a filter literal, when it compares against `data_type`
+/// exactly as the vectorized operators do
+fn zone_value(data_type: &DataType, value: &serde_json::Value) -> Option<ZoneValue> {
+    match (data_type, value) {
+        (DataType::Int64, serde_json::Value::Number(n)) => n.as_i64().map(ZoneValue::Int),
+        // Int32 comparisons truncate the literal, so only in-range literals are exact
+        (DataType::Int32, serde_json::Value::Number(n)) => n.as_i64()
+            .filter(|v| i32::try_from(*v).is_ok())
+            .map(ZoneValue::Int),
+        (DataType::UInt64, serde_json::Value::Number(n)) => n.as_u64().map(ZoneValue::UInt),
+        (DataType::String, serde_json::Value::String(s)) => Some(ZoneValue::String(s.clone())),
+        _ => None,
+    }
+}
+
+/// Intersection of two sorted, non-overlapping range lists
+fn intersect_ranges(a: &[Range<usize>], b: &[Range<usize>]) -> Vec<Range<usize>> {
+    let (mut i, mut j) = (0, 0);
+    let mut out = Vec::new();
+    while i < a.len() && j < b.len() {
+        let start = a[i].start.max(b[j].start);
+        let end = a[i].end.min(b[j].end);
+        if start < end {
+            out.push(start..end);
+        }
+        if a[i].end < b[j].end {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    out
+}
+
+/// Apply a filter chunk by chunk so long filters stay cancellable
+fn filter_in_chunks(filter_op: &FilterOperator, input: &[Column], context: &QueryContext) -> Result<Vec<Column>> {
+    let rows = input.first().map(|c| c.len()).unwrap_or(0);
+    if rows <= ROW_CHUNK {
+        return filter_op.apply(input);
+    }
+    let mut output: Option<Vec<Column>> = None;
+    for start in (0..rows).step_by(ROW_CHUNK) {
+        context.check()?;
+        let count = ROW_CHUNK.min(rows - start);
+        let chunk = input.iter().map(|c| c.slice(start, count)).collect::<Result<Vec<Column>>>()?;
+        let filtered = filter_op.apply(&chunk)?;
+        output = Some(match output {
+            None => filtered,
+            Some(done) => done.iter().zip(&filtered).map(|(a, b)| a.append(b)).collect::<Result<Vec<Column>>>()?,
+        });
+    }
+    Ok(output.unwrap_or_default())
+}
+
+fn count_nodes(node: &PlanNode) -> usize {
+    1 + match node {
+        PlanNode::Scan { .. } => 0,
+        PlanNode::Filter { input, .. }
+        | PlanNode::Project { input, .. }
+        | PlanNode::Aggregate { input, .. }
+        | PlanNode::Sort { input, .. }
+        | PlanNode::Limit { input, .. }
+        | PlanNode::Predict { input, .. } => count_nodes(input),
+        PlanNode::Join { left, right, .. } => count_nodes(left) + count_nodes(right),
+    }
+}
+
+/// Table and column ids of the scan beneath a chain of row-preserving nodes
+fn scanned_columns(node: &PlanNode) -> Option<(TableId, &[u32])> {
+    match node {
+        PlanNode::Scan { table_id, column_ids, .. } => Some((TableId(*table_id), column_ids)),
+        PlanNode::Filter { input, .. }
+        | PlanNode::Sort { input, .. }
+        | PlanNode::Limit { input, .. } => scanned_columns(input),
+        // Projections and earlier predictions change the column layout
+        _ => None,
+    }
+}
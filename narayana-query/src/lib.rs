@@ -10,8 +10,10 @@ pub mod advanced_analytics;
 pub mod ai_analytics;
 pub mod ml_integration;
 pub mod autocomplete;
+pub mod cancellation;
 
 pub use executor::QueryExecutor;
 pub use plan::{QueryPlan, PlanNode};
 pub use optimizer::QueryOptimizer;
+pub use cancellation::{QueryContext, QueryRegistry};
 
@@ -1,6 +1,7 @@
 // Machine learning integration - ClickHouse limitation
 
 use narayana_core::column::Column;
+use narayana_storage::feature_store::{EntityRow, FeatureStore};
 use serde_json::Value;
 
 /// ML model integration
@@ -19,6 +20,34 @@ impl MLIntegration {
         Ok(())
     }
 
+    /// Point-in-time-correct training matrix from the feature store; missing values become `fill`
+    pub fn training_set(
+        &self,
+        store: &FeatureStore,
+        rows: &[EntityRow],
+        features: &[String],
+        fill: f64,
+    ) -> Result<Vec<Vec<f64>>> {
+        Ok(store.get_historical_features(rows, features)?
+            .into_iter()
+            .map(|row| row.into_iter().map(|v| v.unwrap_or(fill)).collect())
+            .collect())
+    }
+
+    /// Current feature vector of an entity, for model inference inside queries
+    pub fn online_feature_vector(
+        &self,
+        store: &FeatureStore,
+        entity: &str,
+        features: &[String],
+        fill: f64,
+    ) -> Result<Vec<f64>> {
+        Ok(store.get_online_features(entity, features)?
+            .into_iter()
+            .map(|v| v.unwrap_or(fill))
+            .collect())
+    }
+
     /// Feature extraction from columns
     pub fn extract_features(&self, columns: &[Column]) -> Vec<Vec<f64>> {
         // Extract features from columns for ML
@@ -98,6 +127,331 @@ impl ArrayOps {
     }
 }
 
+/// Scores a row-major batch of `f32` features; returns one output vector per row
+pub trait BatchScorer: Send + Sync {
+    fn score(&self, batch: &[f32], rows: usize, features: usize) -> Result<Vec<Vec<f32>>>;
+}
+
+/// Builds a scorer from artifact bytes of one model format
+pub type ScorerFactory = Arc<dyn Fn(&[u8]) -> Result<Arc<dyn BatchScorer>> + Send + Sync>;
+
+/// How PREDICT turns model outputs into a column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PredictOutput {
+    /// First output as Float64 (regression, or a binary-class score)
+    #[default]
+    Value,
+    /// Index of the largest output as Int64 (multi-class classification)
+    Class,
+}
+
+/// ONNX Runtime scorer; the model's first input takes a `[rows, features]` f32 tensor
+#[cfg(feature = "onnx")]
+pub struct OnnxScorer {
+    session: parking_lot::Mutex<ort::session::Session>,
+    input_name: String,
+}
+
+#[cfg(feature = "onnx")]
+impl OnnxScorer {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let session = ort::session::Session::builder()
+            .and_then(|mut builder| builder.commit_from_memory(bytes))
+            .map_err(|e| Error::Query(format!("Failed to load ONNX model: {}", e)))?;
+        let input_name = session.inputs().first()
+            .map(|input| input.name().to_string())
+            .ok_or_else(|| Error::Query("ONNX model has no inputs".to_string()))?;
+        Ok(Self { session: parking_lot::Mutex::new(session), input_name })
+    }
+}
+
+#[cfg(feature = "onnx")]
+impl BatchScorer for OnnxScorer {
+    fn score(&self, batch: &[f32], rows: usize, features: usize) -> Result<Vec<Vec<f32>>> {
+        let tensor = ort::value::Tensor::from_array(([rows, features], batch.to_vec()))
+            .map_err(|e| Error::Query(format!("Failed to build ONNX input: {}", e)))?;
+        let mut session = self.session.lock();
+        let outputs = session.run(ort::inputs![self.input_name.as_str() => tensor])
+            .map_err(|e| Error::Query(format!("ONNX inference failed: {}", e)))?;
+        let (_, values) = outputs[0].try_extract_tensor::<f32>()
+            .map_err(|e| Error::Query(format!("ONNX output is not an f32 tensor: {}", e)))?;
+        if rows == 0 || values.len() % rows != 0 {
+            return Err(Error::Query(format!("ONNX output of {} values does not split into {} rows", values.len(), rows)));
+        }
+        Ok(values.chunks(values.len() / rows).map(|row| row.to_vec()).collect())
+    }
+}
+
+/// PREDICT: scores rows with the serving version of a registry model, in batches
+pub struct PredictEngine {
+    artifacts: Arc<ModelArtifactRegistry>,
+    factories: RwLock<HashMap<String, ScorerFactory>>,
+    /// Loaded scorers by model name, with the version they were built from
+    scorers: RwLock<HashMap<String, (ModelVersion, Arc<dyn BatchScorer>)>>,
+    batch_size: usize,
+}
+
+impl PredictEngine {
+    pub fn new(artifacts: Arc<ModelArtifactRegistry>) -> Self {
+        let engine = Self {
+            artifacts,
+            factories: RwLock::new(HashMap::new()),
+            scorers: RwLock::new(HashMap::new()),
+            batch_size: 1024,
+        };
+        #[cfg(feature = "onnx")]
+        engine.register_format("onnx", Arc::new(|bytes: &[u8]| {
+            Ok(Arc::new(OnnxScorer::from_bytes(bytes)?) as Arc<dyn BatchScorer>)
+        }));
+        engine
+    }
+
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Register a scorer for an artifact format (the `format` of a model version)
+    pub fn register_format(&self, format: &str, factory: ScorerFactory) {
+        self.factories.write().insert(format.to_string(), factory);
+    }
+
+    /// Score each row of `features` (equal-length numeric columns, in model input order)
+    pub fn predict(&self, model: &str, features: &[&Column], output: PredictOutput) -> Result<Column> {
+        let rows = features.first().map(|c| c.len()).unwrap_or(0);
+        if features.iter().any(|c| c.len() != rows) {
+            return Err(Error::Query("PREDICT feature columns must have equal lengths".to_string()));
+        }
+        let scorer = self.scorer(model)?;
+        let width = features.len();
+        let mut values = Vec::with_capacity(rows);
+        let mut batch = Vec::with_capacity(self.batch_size.min(rows) * width);
+        for start in (0..rows).step_by(self.batch_size) {
+            let end = (start + self.batch_size).min(rows);
+            batch.clear();
+            for row in start..end {
+                for column in features {
+                    batch.push(feature_f32(column, row)?);
+                }
+            }
+            let scored = scorer.score(&batch, end - start, width)?;
+            if scored.len() != end - start {
+                return Err(Error::Query(format!(
+                    "Model {} returned {} predictions for {} rows", model, scored.len(), end - start
+                )));
+            }
+            values.extend(scored);
+        }
+
+        Ok(match output {
+            PredictOutput::Value => Column::Float64(
+                values.iter().map(|v| v.first().map(|x| *x as f64).unwrap_or(f64::NAN)).collect(),
+            ),
+            PredictOutput::Class => Column::Int64(
+                values.iter()
+                    .map(|v| v.iter().enumerate()
+                        .max_by(|a, b| a.1.total_cmp(b.1))
+                        .map(|(idx, _)| idx as i64)
+                        .unwrap_or(-1))
+                    .collect(),
+            ),
+        })
+    }
+
+    /// Scorer for the serving version, reloaded when a new version is promoted
+    fn scorer(&self, model: &str) -> Result<Arc<dyn BatchScorer>> {
+        let version = self.artifacts.resolve_serving(model)
+            .ok_or_else(|| Error::Query(format!("Model {} has no Production or Staging version", model)))?;
+        if let Some((loaded, scorer)) = self.scorers.read().get(model) {
+            if loaded.version == version.version && loaded.checksum == version.checksum {
+                return Ok(scorer.clone());
+            }
+        }
+        let factory = self.factories.read().get(&version.format).cloned()
+            .ok_or_else(|| Error::Query(format!(
+                "No scorer for model format '{}' (ONNX requires the `onnx` feature)", version.format
+            )))?;
+        let bytes = self.artifacts.load_artifact(model, &version.version.to_string())?;
+        let scorer = factory(&bytes)?;
+        debug!("Loaded model {} version {} for PREDICT", model, version.version);
+        self.scorers.write().insert(model.to_string(), (version, scorer.clone()));
+        Ok(scorer)
+    }
+}
+
+fn feature_f32(column: &Column, row: usize) -> Result<f32> {
+    let value = match column {
+        Column::Int8(v) => v[row] as f32,
+        Column::Int16(v) => v[row] as f32,
+        Column::Int32(v) => v[row] as f32,
+        Column::Int64(v) | Column::Timestamp(v) => v[row] as f32,
+        Column::UInt8(v) => v[row] as f32,
+        Column::UInt16(v) => v[row] as f32,
+        Column::UInt32(v) => v[row] as f32,
+        Column::UInt64(v) => v[row] as f32,
+        Column::Float32(v) => v[row],
+        Column::Float64(v) => v[row] as f32,
+        Column::Boolean(v) => if v[row] { 1.0 } else { 0.0 },
+        Column::Date(v) => v[row] as f32,
+        Column::String(_) | Column::Binary(_) => {
+            return Err(Error::InvalidDataType {
+                expected: "numeric".to_string(),
+                actual: "String/Binary".to_string(),
+            });
+        }
+    };
+    Ok(value)
+}
+
 use narayana_core::Error;
 use narayana_core::Result;
+use narayana_storage::model_registry::{ModelArtifactRegistry, ModelVersion};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::debug;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use narayana_storage::model_registry::{ModelStage, ModelType};
+
+    /// Linear model stored as JSON weights, one output per row
+    struct LinearScorer(Vec<f32>);
+
+    impl BatchScorer for LinearScorer {
+        fn score(&self, batch: &[f32], _rows: usize, features: usize) -> Result<Vec<Vec<f32>>> {
+            Ok(batch.chunks(features)
+                .map(|row| vec![row.iter().zip(&self.0).map(|(x, w)| x * w).sum()])
+                .collect())
+        }
+    }
+
+    #[test]
+    fn test_predict_scores_in_batches_with_serving_version() {
+        let artifacts = Arc::new(ModelArtifactRegistry::new(None).unwrap());
+        artifacts.register_version("price", "1.0.0", ModelType::Planning, "linear-json",
+            serde_json::to_vec(&vec![2.0f32, 1.0]).unwrap(), HashMap::new()).unwrap();
+        artifacts.transition_stage("price", "1.0.0", ModelStage::Staging).unwrap();
+
+        let engine = PredictEngine::new(artifacts.clone()).with_batch_size(2);
+        engine.register_format("linear-json", Arc::new(|bytes: &[u8]| {
+            let weights: Vec<f32> = serde_json::from_slice(bytes)
+                .map_err(|e| Error::Deserialization(e.to_string()))?;
+            Ok(Arc::new(LinearScorer(weights)) as Arc<dyn BatchScorer>)
+        }));
+
+        let rooms = Column::Int32(vec![1, 2, 3]);
+        let area = Column::Float64(vec![10.0, 20.0, 30.0]);
+        let predicted = engine.predict("price", &[&rooms, &area], PredictOutput::Value).unwrap();
+        match predicted {
+            Column::Float64(values) => assert_eq!(values, vec![12.0, 24.0, 36.0]),
+            other => panic!("unexpected column {:?}", other),
+        }
+        assert!(engine.predict("missing", &[&rooms], PredictOutput::Value).is_err());
+        assert!(engine.predict("price", &[&Column::String(vec!["x".into()])], PredictOutput::Value).is_err());
+    }
+
+    #[cfg(feature = "onnx")]
+    #[test]
+    fn test_onnx_format_is_registered() {
+        let artifacts = Arc::new(ModelArtifactRegistry::new(None).unwrap());
+        artifacts.register_version("churn", "1.0.0", ModelType::Planning, "onnx",
+            b"not an onnx model".to_vec(), HashMap::new()).unwrap();
+        artifacts.transition_stage("churn", "1.0.0", ModelStage::Staging).unwrap();
+
+        // The artifact reaches ONNX Runtime rather than failing for want of a scorer
+        let engine = PredictEngine::new(artifacts);
+        let error = engine.predict("churn", &[&Column::Float64(vec![1.0])], PredictOutput::Value).unwrap_err();
+        assert!(error.to_string().contains("Failed to load ONNX model"), "{}", error);
+    }
 
+    /// A hand-encoded ONNX model run through ONNX Runtime
+    #[cfg(feature = "onnx")]
+    mod onnx_model {
+        use super::*;
+
+        /// Protobuf varint
+        fn varint(mut value: u64) -> Vec<u8> {
+            let mut out = Vec::new();
+            while value >= 0x80 {
+                out.push(value as u8 | 0x80);
+                value >>= 7;
+            }
+            out.push(value as u8);
+            out
+        }
+
+        fn int_field(number: u64, value: u64) -> Vec<u8> {
+            [varint(number << 3), varint(value)].concat()
+        }
+
+        fn bytes_field(number: u64, bytes: &[u8]) -> Vec<u8> {
+            [varint((number << 3) | 2), varint(bytes.len() as u64), bytes.to_vec()].concat()
+        }
+
+        /// ValueInfoProto for a float tensor; `None` dims are symbolic
+        fn float_tensor_info(name: &str, dims: &[Option<u64>]) -> Vec<u8> {
+            let shape: Vec<u8> = dims.iter()
+                .map(|dim| bytes_field(1, &match dim {
+                    Some(size) => int_field(1, *size),
+                    None => bytes_field(2, b"rows"),
+                }))
+                .collect::<Vec<_>>()
+                .concat();
+            let tensor_type = [int_field(1, 1), bytes_field(2, &shape)].concat();
+            [bytes_field(1, name.as_bytes()), bytes_field(2, &bytes_field(1, &tensor_type))].concat()
+        }
+
+        /// ONNX model computing `y = x · weights` for `x` of shape [rows, weights.len()]
+        fn linear_onnx_model(weights: &[f32]) -> Vec<u8> {
+            let node = [
+                bytes_field(1, b"x"),
+                bytes_field(1, b"w"),
+                bytes_field(2, b"y"),
+                bytes_field(4, b"MatMul"),
+            ].concat();
+            let raw: Vec<u8> = weights.iter().flat_map(|w| w.to_le_bytes()).collect();
+            let initializer = [
+                int_field(1, weights.len() as u64),
+                int_field(1, 1),
+                int_field(2, 1),
+                bytes_field(8, b"w"),
+                bytes_field(9, &raw),
+            ].concat();
+            let graph = [
+                bytes_field(1, &node),
+                bytes_field(2, b"linear"),
+                bytes_field(5, &initializer),
+                bytes_field(11, &float_tensor_info("x", &[None, Some(weights.len() as u64)])),
+                bytes_field(12, &float_tensor_info("y", &[None, Some(1)])),
+            ].concat();
+            [
+                int_field(1, 8),
+                bytes_field(7, &graph),
+                bytes_field(8, &int_field(2, 13)),
+            ].concat()
+        }
+
+        #[test]
+        fn test_onnx_model_scores_rows() {
+            let model = linear_onnx_model(&[2.0, 1.0]);
+            let scorer = OnnxScorer::from_bytes(&model).unwrap();
+            let scored = scorer.score(&[1.0, 10.0, 2.0, 20.0, 3.0, 30.0], 3, 2).unwrap();
+            assert_eq!(scored, vec![vec![12.0], vec![24.0], vec![36.0]]);
+
+            let artifacts = Arc::new(ModelArtifactRegistry::new(None).unwrap());
+            artifacts.register_version("price", "1.0.0", ModelType::Planning, "onnx", model, HashMap::new()).unwrap();
+            artifacts.transition_stage("price", "1.0.0", ModelStage::Staging).unwrap();
+            let engine = PredictEngine::new(artifacts).with_batch_size(2);
+            let rooms = Column::Int32(vec![1, 2, 3]);
+            let area = Column::Float64(vec![10.0, 20.0, 30.0]);
+            match engine.predict("price", &[&rooms, &area], PredictOutput::Value).unwrap() {
+                Column::Float64(values) => assert_eq!(values, vec![12.0, 24.0, 36.0]),
+                other => panic!("unexpected column {:?}", other),
+            }
+        }
+    }
+}
@@ -1,6 +1,8 @@
 use narayana_core::{Error, Result, column::Column, schema::Schema};
 use crate::plan::{PlanNode, Filter};
 use crate::vectorized::VectorizedOps;
+use narayana_storage::full_text::{FullTextQuery, TextAnalyzer};
+use narayana_storage::geospatial::Geometry;
 
 pub struct ScanOperator {
     table_id: u64,
@@ -59,6 +61,7 @@ impl FilterOperator {
                 let column = &columns[col_idx];
                 Ok(VectorizedOps::compare_lt(column, value))
             }
+            Filter::Match { .. } | Filter::Spatial { .. } => self.evaluate_predicate_for_filter(&self.predicate, columns),
             Filter::And { left, right } => {
                 let left_mask = self.evaluate_predicate_for_filter(left, columns)?;
                 let right_mask = self.evaluate_predicate_for_filter(right, columns)?;
@@ -69,7 +72,7 @@ impl FilterOperator {
                 let right_mask = self.evaluate_predicate_for_filter(right, columns)?;
                 Ok(left_mask.iter().zip(right_mask.iter()).map(|(a, b)| *a || *b).collect())
             }
-            _ => Err(Error::Query("Unsupported filter predicate".to_string())),
+            other => self.evaluate_predicate_for_filter(other, columns),
         }
     }
 
@@ -96,7 +99,79 @@ impl FilterOperator {
                 let column = &columns[col_idx];
                 Ok(VectorizedOps::compare_lt(column, value))
             }
-            _ => Err(Error::Query("Unsupported filter predicate".to_string())),
+            Filter::Match { column, query, language } => {
+                let col_idx = self.input_schema
+                    .field_index(column)
+                    .ok_or_else(|| Error::Query(format!("Column not found: {}", column)))?;
+                let analyzer = TextAnalyzer::new(*language);
+                let query = FullTextQuery::parse(query, &analyzer)?;
+                match &columns[col_idx] {
+                    Column::String(values) => Ok(values.iter().map(|text| query.matches_text(&analyzer, text)).collect()),
+                    other => Err(Error::Query(format!("MATCH requires a String column, got {:?}", other.data_type()))),
+                }
+            }
+            Filter::Spatial { column, predicate } => {
+                let col_idx = self.input_schema
+                    .field_index(column)
+                    .ok_or_else(|| Error::Query(format!("Column not found: {}", column)))?;
+                match &columns[col_idx] {
+                    // Empty values are nulls and never match
+                    Column::Binary(values) => values
+                        .iter()
+                        .map(|bytes| {
+                            if bytes.is_empty() {
+                                Ok(false)
+                            } else {
+                                predicate.matches(&Geometry::decode(bytes)?)
+                            }
+                        })
+                        .collect(),
+                    other => Err(Error::Query(format!("Spatial predicate requires a geometry column, got {:?}", other.data_type()))),
+                }
+            }
+            Filter::And { left, right } => {
+                let left_mask = self.evaluate_predicate_for_filter(left, columns)?;
+                let right_mask = self.evaluate_predicate_for_filter(right, columns)?;
+                Ok(left_mask.iter().zip(right_mask.iter()).map(|(a, b)| *a && *b).collect())
+            }
+            Filter::Or { left, right } => {
+                let left_mask = self.evaluate_predicate_for_filter(left, columns)?;
+                let right_mask = self.evaluate_predicate_for_filter(right, columns)?;
+                Ok(left_mask.iter().zip(right_mask.iter()).map(|(a, b)| *a || *b).collect())
+            }
+            // The rest are composed from Eq, Gt and Lt
+            Filter::Ne { column, value } => {
+                let eq = self.evaluate_predicate_for_filter(&Filter::Eq { column: column.clone(), value: value.clone() }, columns)?;
+                Ok(eq.into_iter().map(|m| !m).collect())
+            }
+            Filter::Gte { column, value } | Filter::Lte { column, value } => {
+                let eq = Filter::Eq { column: column.clone(), value: value.clone() };
+                let strict = match filter {
+                    Filter::Gte { .. } => Filter::Gt { column: column.clone(), value: value.clone() },
+                    _ => Filter::Lt { column: column.clone(), value: value.clone() },
+                };
+                self.evaluate_predicate_for_filter(&Filter::Or { left: Box::new(strict), right: Box::new(eq) }, columns)
+            }
+            Filter::Not { expr } => {
+                let mask = self.evaluate_predicate_for_filter(expr, columns)?;
+                Ok(mask.into_iter().map(|m| !m).collect())
+            }
+            Filter::In { column, values } => {
+                let col_idx = self.input_schema
+                    .field_index(column)
+                    .ok_or_else(|| Error::Query(format!("Column not found: {}", column)))?;
+                let mut mask = vec![false; columns[col_idx].len()];
+                for value in values {
+                    let eq = VectorizedOps::compare_eq(&columns[col_idx], value);
+                    mask.iter_mut().zip(eq).for_each(|(m, e)| *m |= e);
+                }
+                Ok(mask)
+            }
+            Filter::Between { column, low, high } => {
+                let low = Filter::Gte { column: column.clone(), value: low.clone() };
+                let high = Filter::Lte { column: column.clone(), value: high.clone() };
+                self.evaluate_predicate_for_filter(&Filter::And { left: Box::new(low), right: Box::new(high) }, columns)
+            }
         }
     }
 }
@@ -71,6 +71,15 @@ impl QueryOptimizer {
                 }
             }
             
+            PlanNode::Predict { model, features, output, input } => {
+                PlanNode::Predict {
+                    model,
+                    features,
+                    output,
+                    input: Box::new(Self::optimize_node(*input)),
+                }
+            }
+
             // Default: optimize children
             node => {
                 // Recursively optimize children
@@ -94,6 +103,7 @@ impl QueryOptimizer {
             PlanNode::Sort { .. } => 200.0,
             PlanNode::Aggregate { .. } => 150.0,
             PlanNode::Join { .. } => 500.0,
+            PlanNode::Predict { features, .. } => 100.0 + features.len() as f64 * 10.0,
         }
     }
 }
@@ -36,6 +36,14 @@ pub enum PlanNode {
         offset: usize,
         input: Box<PlanNode>,
     },
+    /// PREDICT: append a column scored by a registry model from `features`
+    Predict {
+        model: String,
+        features: Vec<String>,
+        #[serde(default)]
+        output: crate::ml_integration::PredictOutput,
+        input: Box<PlanNode>,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,6 +59,18 @@ pub enum Filter {
     Not { expr: Box<Filter> },
     In { column: String, values: Vec<serde_json::Value> },
     Between { column: String, low: serde_json::Value, high: serde_json::Value },
+    /// Full-text MATCH predicate (terms, "phrases" and prefix* clauses)
+    Match {
+        column: String,
+        query: String,
+        #[serde(default)]
+        language: narayana_storage::full_text::Language,
+    },
+    /// Geospatial predicate on a Point/Geometry column (radius or bounding box)
+    Spatial {
+        column: String,
+        predicate: narayana_storage::geospatial::SpatialPredicate,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -0,0 +1,48 @@
+// Anomaly events into RDE
+// Publishes detector output from narayana-storage as "anomaly-detector:anomaly" events
+
+use crate::actor::{Actor, ActorId, ActorType};
+use crate::RdeManager;
+use async_trait::async_trait;
+use narayana_core::Result;
+use narayana_storage::anomaly_detection::{AnomalyEvent, AnomalySink};
+use std::sync::Arc;
+
+/// Source actor used for anomaly events
+pub const ANOMALY_ACTOR_ID: &str = "anomaly-detector";
+/// Event name (subscribe to `anomaly-detector:anomaly`)
+pub const ANOMALY_EVENT_NAME: &str = "anomaly";
+
+/// Anomaly sink that publishes into RDE as a system source actor
+pub struct RdeAnomalySink {
+    manager: Arc<RdeManager>,
+    actor_id: ActorId,
+    auth_token: String,
+}
+
+impl RdeAnomalySink {
+    /// Register the system source actor with a random token
+    pub async fn register(manager: Arc<RdeManager>) -> Result<Self> {
+        let auth_token = format!("{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple());
+        let mut actor = Actor::new(
+            ANOMALY_ACTOR_ID,
+            "Anomaly Detection".to_string(),
+            ActorType::Source,
+            auth_token.clone(),
+        );
+        actor.metadata = serde_json::json!({ "system": true });
+        let actor_id = manager.register_actor(actor).await?;
+        Ok(Self { manager, actor_id, auth_token })
+    }
+}
+
+#[async_trait]
+impl AnomalySink for RdeAnomalySink {
+    async fn emit(&self, event: &AnomalyEvent) -> Result<()> {
+        let payload = serde_json::to_value(event)
+            .map_err(|e| narayana_core::Error::Serialization(e.to_string()))?;
+        self.manager
+            .publish_event(&self.actor_id, &self.auth_token, ANOMALY_EVENT_NAME, payload)
+            .await
+    }
+}
@@ -1,29 +1,255 @@
 // Authentication for RDE
+// An actor can hold several API tokens at once, each with an optional expiry.
+// Tokens are rotated by issuing a replacement and letting the old one lapse
+// after a grace period, and can be revoked immediately - all without
+// re-registering the actor, so its subscriptions stay in place. Only SHA-256
+// hashes of the tokens are kept.
 
 use crate::actor::{Actor, ActorId};
+use crate::registry::{JournalEntry, Registry};
 use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::sync::Arc;
-use narayana_core::Result;
+use narayana_core::{Error, Result};
+
+/// Most tokens an actor may hold, active or not; revoked and expired ones
+/// are pruned first when the limit is reached
+const MAX_TOKENS_PER_ACTOR: usize = 16;
+
+/// One of an actor's API tokens
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActorToken {
+    pub id: String,
+    pub created_at: u64,
+    /// Unix seconds after which the token is refused
+    pub expires_at: Option<u64>,
+    pub revoked_at: Option<u64>,
+    #[serde(skip)]
+    hash: [u8; 32],
+}
+
+impl ActorToken {
+    fn new(token: &str, expires_at: Option<u64>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            created_at: now(),
+            expires_at,
+            revoked_at: None,
+            hash: hash_token(token),
+        }
+    }
+
+    /// Whether the token is accepted at `now` (unix seconds)
+    pub fn is_active(&self, now: u64) -> bool {
+        self.revoked_at.is_none() && self.expires_at.is_none_or(|expires_at| now < expires_at)
+    }
+}
+
+/// An actor token as journaled by the registry, hash included
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct StoredToken {
+    #[serde(flatten)]
+    info: ActorToken,
+    hash: String,
+}
+
+impl StoredToken {
+    fn from_token(token: &ActorToken) -> Self {
+        Self { info: token.clone(), hash: hex::encode(token.hash) }
+    }
+
+    fn into_token(self) -> Option<ActorToken> {
+        let hash = hex::decode(&self.hash).ok()?.try_into().ok()?;
+        Some(ActorToken { hash, ..self.info })
+    }
+}
+
+/// A newly issued token. This is the only time the secret is returned.
+#[derive(Debug, Clone, Serialize)]
+pub struct IssuedToken {
+    pub token: String,
+    #[serde(flatten)]
+    pub info: ActorToken,
+}
 
 /// Authentication Manager
 pub struct AuthManager {
     actors: Arc<DashMap<ActorId, Actor>>,
+    tokens: DashMap<ActorId, Vec<ActorToken>>,
+    /// Journals token changes, when the registry is persisted
+    registry: Option<Arc<Registry>>,
 }
 
 impl AuthManager {
     /// Create new auth manager with shared actors map
     pub fn new(actors: Arc<DashMap<ActorId, Actor>>) -> Self {
-        Self { actors }
+        Self { actors, tokens: DashMap::new(), registry: None }
+    }
+
+    /// Journal token changes to the registry
+    pub(crate) fn with_registry(mut self, registry: Arc<Registry>) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    /// The actor's tokens as the registry journals them
+    pub(crate) fn stored_tokens(&self, actor_id: &ActorId) -> Option<Vec<StoredToken>> {
+        self.with_tokens(actor_id, |tokens| tokens.iter().map(StoredToken::from_token).collect())
     }
 
-    /// Authenticate actor by token
+    /// Put back tokens replayed from the registry; unreadable ones are dropped
+    pub(crate) fn restore_tokens(&self, actor_id: ActorId, tokens: Vec<StoredToken>) {
+        let tokens = tokens.into_iter().filter_map(StoredToken::into_token).collect();
+        self.tokens.insert(actor_id, tokens);
+    }
+
+    /// Authenticate actor by token. Any of the actor's active tokens is accepted.
     pub fn authenticate(&self, actor_id: &ActorId, token: &str) -> Result<bool> {
-        if let Some(actor) = self.actors.get(actor_id) {
-            Ok(actor.verify_token(token))
-        } else {
-            Ok(false)
+        Ok(self.token_id(actor_id, token).is_some())
+    }
+
+    /// Id of the active token `token` is, if any
+    pub fn token_id(&self, actor_id: &ActorId, token: &str) -> Option<String> {
+        let hash = hash_token(token);
+        let now = now();
+        let find = |tokens: &Vec<ActorToken>| {
+            tokens.iter()
+                .find(|t| t.is_active(now) && t.hash == hash)
+                .map(|t| t.id.clone())
+        };
+        // Every publish authenticates, so avoid the write lock when we can
+        if let Some(tokens) = self.tokens.get(actor_id) {
+            return find(&tokens);
+        }
+        self.with_tokens(actor_id, |tokens| find(tokens)).flatten()
+    }
+
+    /// The actor's tokens, oldest first (hashes are never exposed)
+    pub fn list_tokens(&self, actor_id: &ActorId) -> Option<Vec<ActorToken>> {
+        self.with_tokens(actor_id, |tokens| tokens.clone())
+    }
+
+    /// Issue an additional token for the actor
+    pub fn issue_token(&self, actor_id: &ActorId, expires_at: Option<u64>) -> Result<IssuedToken> {
+        let now = now();
+        if expires_at.is_some_and(|expires_at| expires_at <= now) {
+            return Err(Error::Storage("Token expiry must be in the future".to_string()));
+        }
+        let token = format!("{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple());
+        let info = ActorToken::new(&token, expires_at);
+        self.update_tokens(actor_id, |tokens| {
+            if tokens.len() >= MAX_TOKENS_PER_ACTOR {
+                tokens.retain(|t| t.is_active(now));
+            }
+            if tokens.len() >= MAX_TOKENS_PER_ACTOR {
+                return Err(Error::Storage(format!(
+                    "Actor already has {} active tokens (max {})",
+                    tokens.len(), MAX_TOKENS_PER_ACTOR
+                )));
+            }
+            tokens.push(info.clone());
+            Ok(())
+        })
+        .ok_or_else(|| Error::Storage("Actor not found".to_string()))??;
+        Ok(IssuedToken { token, info })
+    }
+
+    /// Issue a new token and retire the old ones `grace_secs` from now
+    /// (immediately when 0). With `retiring` only that token is retired,
+    /// otherwise every other active token is.
+    pub fn rotate_token(
+        &self,
+        actor_id: &ActorId,
+        retiring: Option<&str>,
+        grace_secs: u64,
+        expires_at: Option<u64>,
+    ) -> Result<IssuedToken> {
+        if let Some(id) = retiring {
+            let now = now();
+            let active = self.with_tokens(actor_id, |tokens| tokens.iter().any(|t| t.id == id && t.is_active(now)));
+            if active != Some(true) {
+                return Err(Error::Storage("Token not found or no longer active".to_string()));
+            }
         }
+
+        let issued = self.issue_token(actor_id, expires_at)?;
+        let now = now();
+        let retire_at = now.saturating_add(grace_secs);
+        self.update_tokens(actor_id, |tokens| {
+            for token in tokens.iter_mut() {
+                let selected = match retiring {
+                    Some(id) => token.id == id,
+                    None => token.id != issued.info.id,
+                };
+                if !selected || !token.is_active(now) {
+                    continue;
+                }
+                if grace_secs == 0 {
+                    token.revoked_at = Some(now);
+                } else {
+                    token.expires_at = Some(token.expires_at.map_or(retire_at, |expires_at| expires_at.min(retire_at)));
+                }
+            }
+        });
+        Ok(issued)
+    }
+
+    /// Revoke a token; requests using it are refused from now on
+    pub fn revoke_token(&self, actor_id: &ActorId, token_id: &str) -> Result<ActorToken> {
+        let now = now();
+        self.update_tokens(actor_id, |tokens| {
+            let token = tokens.iter_mut()
+                .find(|t| t.id == token_id)
+                .ok_or_else(|| Error::Storage("Token not found".to_string()))?;
+            if token.revoked_at.is_some() {
+                return Err(Error::Storage("Token already revoked".to_string()));
+            }
+            token.revoked_at = Some(now);
+            Ok(token.clone())
+        })
+        .ok_or_else(|| Error::Storage("Actor not found".to_string()))?
+    }
+
+    /// Run `f` on the actor's tokens. An actor's registration token becomes
+    /// its first token the first time its tokens are used. `None` if the
+    /// actor is not registered.
+    fn with_tokens<T>(&self, actor_id: &ActorId, f: impl FnOnce(&mut Vec<ActorToken>) -> T) -> Option<T> {
+        if let Some(mut tokens) = self.tokens.get_mut(actor_id) {
+            return Some(f(&mut tokens));
+        }
+        // Actors replayed from the registry have no registration token; their
+        // tokens were restored before they were
+        let registration = self.actors.get(actor_id)?.auth_token.clone();
+        let initial: Vec<ActorToken> = (!registration.is_empty())
+            .then(|| ActorToken::new(&registration, None))
+            .into_iter()
+            .collect();
+        let mut tokens = self.tokens.entry(actor_id.clone()).or_insert(initial);
+        Some(f(&mut tokens))
+    }
+
+    /// `with_tokens` for changes, journaling the actor's tokens afterwards
+    /// (while still holding them, so journal order follows change order)
+    fn update_tokens<T>(&self, actor_id: &ActorId, f: impl FnOnce(&mut Vec<ActorToken>) -> T) -> Option<T> {
+        self.with_tokens(actor_id, |tokens| {
+            let result = f(tokens);
+            let system = self.actors.get(actor_id).is_some_and(|a| crate::registry::is_system_actor(&a));
+            if let (Some(registry), false) = (&self.registry, system) {
+                registry.record_later(JournalEntry::Tokens {
+                    actor_id: actor_id.clone(),
+                    tokens: tokens.iter().map(StoredToken::from_token).collect(),
+                });
+            }
+            result
+        })
     }
 }
 
+fn hash_token(token: &str) -> [u8; 32] {
+    Sha256::digest(token.as_bytes()).into()
+}
 
+fn now() -> u64 {
+    chrono::Utc::now().timestamp() as u64
+}
@@ -0,0 +1,172 @@
+// Backfill subscriptions - replay history, then go live
+// A subscription created with `from` first receives the retained events of
+// its streams, then switches to live delivery. Live events published while
+// history is streaming are held back and handed over by event id, so the
+// switch has no gaps and no duplicates.
+
+use crate::delivery::Dispatcher;
+use crate::events::EventName;
+use crate::subscriptions::{Subscription, SubscriptionId};
+use narayana_core::{Error, Result};
+use narayana_storage::native_events::{EventId, NativeEventsSystem, StreamName};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Events read from a stream per page
+const PAGE_SIZE: usize = 500;
+/// Live events held per subscription while history streams; beyond this the
+/// held events are dropped and re-read from the streams instead
+const MAX_HELD_EVENTS: usize = 10_000;
+/// How long to wait for an SSE/gRPC subscriber to attach before replaying
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Where a backfill starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackfillStart {
+    /// Oldest retained event
+    Earliest,
+    /// First event at or after this Unix time in milliseconds
+    Timestamp(u64),
+}
+
+impl BackfillStart {
+    /// Parse the subscription's `from` option: "earliest", "latest" (no
+    /// backfill), Unix milliseconds, or an RFC 3339 timestamp
+    pub fn from_config(config: &serde_json::Value) -> Result<Option<Self>> {
+        let invalid = || Error::Storage("from must be 'earliest', 'latest', Unix milliseconds or an RFC 3339 timestamp".to_string());
+        match config.get("from") {
+            None | Some(serde_json::Value::Null) => Ok(None),
+            Some(serde_json::Value::Number(ms)) => ms.as_u64().map(|ms| Some(BackfillStart::Timestamp(ms))).ok_or_else(invalid),
+            Some(serde_json::Value::String(from)) => match from.as_str() {
+                "earliest" => Ok(Some(BackfillStart::Earliest)),
+                "latest" => Ok(None),
+                other => chrono::DateTime::parse_from_rfc3339(other)
+                    .ok()
+                    .and_then(|t| u64::try_from(t.timestamp_millis()).ok())
+                    .map(|ms| Some(BackfillStart::Timestamp(ms)))
+                    .ok_or_else(invalid),
+            },
+            Some(_) => Err(invalid()),
+        }
+    }
+
+    /// First event id to replay (RDE event ids are publish times in milliseconds)
+    fn first_id(&self) -> u64 {
+        match self {
+            BackfillStart::Earliest => 0,
+            BackfillStart::Timestamp(ms) => *ms,
+        }
+    }
+}
+
+struct HeldEvent {
+    event_name: EventName,
+    id: u64,
+    payload: serde_json::Value,
+}
+
+#[derive(Default)]
+struct Held {
+    events: Vec<HeldEvent>,
+    /// Events whose payloads were dropped for exceeding MAX_HELD_EVENTS
+    dropped: HashSet<EventName>,
+    live: bool,
+}
+
+/// Handover state for one backfilling subscription
+#[derive(Default)]
+pub(crate) struct Backfill {
+    held: parking_lot::Mutex<Held>,
+}
+
+impl Backfill {
+    /// Hold a live event until history has been replayed
+    /// Returns false once the subscription is live (deliver directly).
+    pub(crate) fn hold(&self, event_name: &EventName, id: u64, payload: &serde_json::Value) -> bool {
+        let mut held = self.held.lock();
+        if held.live {
+            return false;
+        }
+        if held.events.len() >= MAX_HELD_EVENTS {
+            // Everything held is also in its stream; re-read it from there
+            let events = std::mem::take(&mut held.events);
+            held.dropped.extend(events.into_iter().map(|e| e.event_name));
+            held.dropped.insert(event_name.clone());
+        } else {
+            held.events.push(HeldEvent { event_name: event_name.clone(), id, payload: payload.clone() });
+        }
+        true
+    }
+}
+
+pub(crate) fn stream_name(event_name: &EventName) -> StreamName {
+    StreamName(format!("rde:{}", event_name.0))
+}
+
+/// Replay history for `subscription`, then hand over to live delivery
+///
+/// `event_names` are the published events the subscription covered when it
+/// was registered; events first published later arrive through `hold`.
+pub(crate) async fn run(
+    dispatcher: Dispatcher,
+    native_events: Arc<NativeEventsSystem>,
+    backfills: Arc<dashmap::DashMap<SubscriptionId, Arc<Backfill>>>,
+    backfill: Arc<Backfill>,
+    subscription: Subscription,
+    event_names: Vec<EventName>,
+    start: BackfillStart,
+) {
+    // Stream transports need their connection before there is anywhere to replay to
+    let deadline = tokio::time::Instant::now() + CONNECT_TIMEOUT;
+    while !dispatcher.connected(&subscription) && tokio::time::Instant::now() < deadline {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+
+    // Next id to deliver per event
+    let mut cursors: HashMap<EventName, u64> = event_names.into_iter().map(|name| (name, start.first_id())).collect();
+    let mut to_read: Vec<EventName> = cursors.keys().cloned().collect();
+    let mut replayed = 0;
+    loop {
+        for event_name in to_read.drain(..) {
+            let cursor = cursors.entry(event_name.clone()).or_insert(start.first_id());
+            loop {
+                let page = native_events.read_stream(&stream_name(&event_name), EventId(*cursor), PAGE_SIZE);
+                let Some(last) = page.last().map(|e| e.id.0) else {
+                    break;
+                };
+                replayed += page.len();
+                for event in page {
+                    dispatcher.deliver_or_queue(&subscription, &event_name, event.id.0, &event.payload).await;
+                }
+                *cursor = last + 1;
+            }
+        }
+
+        // Deliver what was held meanwhile; go live once nothing is left
+        let mut events = {
+            let mut held = backfill.held.lock();
+            if !held.dropped.is_empty() {
+                to_read = held.dropped.drain().collect();
+                continue;
+            }
+            if held.events.is_empty() {
+                held.live = true;
+                break;
+            }
+            std::mem::take(&mut held.events)
+        };
+        events.sort_by_key(|e| e.id);
+        for event in events {
+            let cursor = cursors.entry(event.event_name.clone()).or_insert(start.first_id());
+            // Older ids were already replayed from the stream
+            if event.id >= *cursor {
+                replayed += 1;
+                dispatcher.deliver_or_queue(&subscription, &event.event_name, event.id, &event.payload).await;
+                *cursor = event.id + 1;
+            }
+        }
+    }
+    backfills.remove(&subscription.id);
+    tracing::info!("Backfill delivered {} events, subscription is live", replayed);
+}
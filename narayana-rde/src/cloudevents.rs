@@ -0,0 +1,104 @@
+// CloudEvents envelopes for delivered events
+// Subscriptions created with `envelope: "cloudevents"` get each event wrapped
+// in a CloudEvents 1.0 structured-mode JSON document instead of the Narayana
+// envelope, so Knative, Event Grid and other CloudEvents consumers can take
+// them as they are. Supported on the webhook, SSE and Kafka transports, for
+// JSON payloads only.
+
+use narayana_core::{Error, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Content type of a structured-mode CloudEvent
+pub const CONTENT_TYPE: &str = "application/cloudevents+json";
+/// CloudEvents specification version produced
+pub const SPEC_VERSION: &str = "1.0";
+
+const DEFAULT_SOURCE: &str = "/narayana/rde/{actor}";
+const DEFAULT_TYPE: &str = "narayana.rde.{actor}.{event}";
+const MAX_SOURCE_LEN: usize = 1024;
+const MAX_TYPE_LEN: usize = 256;
+
+/// A CloudEvents 1.0 event carrying a JSON payload
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CloudEvent {
+    pub specversion: String,
+    /// Unique per source: the published event name and its stream sequence
+    pub id: String,
+    pub source: String,
+    #[serde(rename = "type")]
+    pub event_type: String,
+    /// RFC 3339 time the event was handed to the transport
+    pub time: String,
+    pub datacontenttype: String,
+    pub data: Value,
+}
+
+/// Envelope a subscription wraps delivered payloads in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Envelope {
+    /// `{"event_name", "payload", "timestamp"}` for webhooks, the bare payload elsewhere
+    #[default]
+    Narayana,
+    #[serde(rename = "cloudevents")]
+    CloudEvents,
+}
+
+/// CloudEvents attributes of a subscription
+///
+/// Config: `envelope` ("narayana" by default, or "cloudevents"),
+/// `cloudevents_source` (URI reference, default "/narayana/rde/{actor}") and
+/// `cloudevents_type` (default "narayana.rde.{actor}.{event}"); `{actor}` and
+/// `{event}` are replaced from the published event.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct CloudEventsTarget {
+    source: String,
+    event_type: String,
+}
+
+impl CloudEventsTarget {
+    /// None unless the subscription asked for CloudEvents
+    pub(crate) fn from_config(config: &Value) -> Result<Option<Self>> {
+        let envelope = match config.get("envelope") {
+            None | Some(Value::Null) => Envelope::Narayana,
+            Some(value) => serde_json::from_value(value.clone())
+                .map_err(|_| Error::Storage(format!("Unsupported envelope: {} (use narayana or cloudevents)", value)))?,
+        };
+        let source = template(config, "cloudevents_source", DEFAULT_SOURCE, MAX_SOURCE_LEN)?;
+        let event_type = template(config, "cloudevents_type", DEFAULT_TYPE, MAX_TYPE_LEN)?;
+        Ok((envelope == Envelope::CloudEvents).then_some(Self { source, event_type }))
+    }
+
+    /// Wrap a payload published as `event_name` ("actor:event") with stream
+    /// sequence `event_id`
+    pub(crate) fn wrap(&self, event_name: &str, event_id: u64, payload: &Value) -> CloudEvent {
+        let (actor, event) = event_name.split_once(':').unwrap_or(("", event_name));
+        let fill = |template: &str| template.replace("{actor}", actor).replace("{event}", event);
+        CloudEvent {
+            specversion: SPEC_VERSION.to_string(),
+            id: format!("{}:{}", event_name, event_id),
+            source: fill(&self.source),
+            event_type: fill(&self.event_type),
+            time: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            datacontenttype: "application/json".to_string(),
+            data: payload.clone(),
+        }
+    }
+}
+
+/// Read an attribute template; whitespace and control characters aren't allowed
+fn template(config: &Value, key: &str, default: &str, max_len: usize) -> Result<String> {
+    let value = match config.get(key) {
+        None | Some(Value::Null) => return Ok(default.to_string()),
+        Some(Value::String(value)) => value,
+        Some(_) => return Err(Error::Storage(format!("{} must be a string", key))),
+    };
+    if value.is_empty() || value.len() > max_len || value.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(Error::Storage(format!(
+            "Invalid {} '{}' (non-empty, no whitespace, max {} chars)",
+            key, value, max_len
+        )));
+    }
+    Ok(value.clone())
+}
@@ -0,0 +1,330 @@
+// Per-subscription event delivery
+// Rate limiting, transformation, envelopes and transport dispatch for one subscription.
+// Holds only shared handles, so backfill tasks can take their own copy.
+// Failed deliveries are handed to the durable queue for retries, or retried
+// in place for ordered subscriptions; without retries they are dead-lettered.
+
+use crate::cloudevents::CloudEventsTarget;
+use crate::durable::{self, DeliveryQueue, RetryPolicy};
+use crate::encoding::{self, EncodedPayload};
+use crate::events::EventName;
+use crate::filter::SubscriptionFilter;
+use crate::groups::{self, ConsumerGroups};
+use crate::metrics::DeliveryMetrics;
+use crate::ordered;
+use crate::schema_registry::SchemaRegistry;
+use crate::subscriptions::{Subscription, SubscriptionId, TransportType};
+use crate::transports;
+use crate::{rate_limiter, WebSocketBroadcaster};
+use narayana_core::Result;
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub(crate) struct Dispatcher {
+    pub(crate) rate_limiter: Arc<rate_limiter::SubscriptionRateLimiter>,
+    pub(crate) websocket_manager: Option<Arc<dyn WebSocketBroadcaster + Send + Sync>>,
+    pub(crate) sse_connections: Arc<dashmap::DashMap<SubscriptionId, tokio::sync::mpsc::Sender<String>>>,
+    pub(crate) grpc_streams: Arc<dashmap::DashMap<SubscriptionId, tokio::sync::mpsc::Sender<serde_json::Value>>>,
+    pub(crate) worker_invoker: Option<Arc<dyn transports::worker::WorkerInvoker>>,
+    pub(crate) worker_stats: Arc<dashmap::DashMap<SubscriptionId, transports::worker::WorkerDeliveryStats>>,
+    pub(crate) table_sink: Option<Arc<transports::table::TableSink>>,
+    pub(crate) kafka_producer: Option<Arc<dyn transports::kafka::KafkaProducer>>,
+    pub(crate) kafka_stats: Arc<dashmap::DashMap<SubscriptionId, transports::kafka::KafkaDeliveryStats>>,
+    pub(crate) mqtt_connections: Option<Arc<transports::mqtt::MqttConnections>>,
+    pub(crate) mqtt_stats: Arc<dashmap::DashMap<SubscriptionId, transports::mqtt::MqttDeliveryStats>>,
+    pub(crate) amqp_connections: Option<Arc<transports::amqp::AmqpConnections>>,
+    pub(crate) amqp_stats: Arc<dashmap::DashMap<SubscriptionId, transports::amqp::AmqpDeliveryStats>>,
+    pub(crate) schema_registry: Arc<SchemaRegistry>,
+    pub(crate) delivery_queue: Arc<DeliveryQueue>,
+    pub(crate) metrics: Arc<DeliveryMetrics>,
+    pub(crate) groups: Arc<ConsumerGroups>,
+}
+
+impl Dispatcher {
+    /// Whether the subscription's stream connection (SSE or gRPC) is attached;
+    /// always true for transports that don't need one
+    pub(crate) fn connected(&self, subscription: &Subscription) -> bool {
+        match subscription.transport {
+            TransportType::Sse => self.sse_connections.contains_key(&subscription.id),
+            TransportType::Grpc => self.grpc_streams.contains_key(&subscription.id),
+            _ => true,
+        }
+    }
+
+    /// Deliver one event, queueing it for retries if that fails (or
+    /// dead-lettering it, if the subscription turned retries off); events its
+    /// filter rejects are skipped.
+    /// Ordered subscriptions are retried in place instead, so nothing after
+    /// the event overtakes it.
+    pub(crate) async fn deliver_or_queue(
+        &self,
+        subscription: &Subscription,
+        event_name: &EventName,
+        event_id: u64,
+        payload: &serde_json::Value,
+    ) {
+        // Filters are validated on subscribe
+        if let Ok(Some(filter)) = SubscriptionFilter::from_config(&subscription.config) {
+            if !filter.matches(payload) {
+                self.metrics.filtered(&subscription.id);
+                return;
+            }
+        }
+        // Group members count as busy until the delivery and its in-place retries end
+        let _in_flight = groups::from_config(&subscription.config)
+            .ok()
+            .flatten()
+            .map(|_| self.groups.start(&subscription.id));
+        let Err(e) = self.deliver(subscription, event_name, event_id, payload).await else {
+            return;
+        };
+        // SECURITY: Don't log subscription ID to prevent information disclosure
+        tracing::warn!("Failed to deliver event to subscription: {}", e);
+        match RetryPolicy::from_config(&subscription.config) {
+            Ok(Some(policy)) if ordered::from_config(&subscription.config).unwrap_or(false) => {
+                self.retry_in_place(subscription, &policy, event_name, event_id, payload, e.to_string()).await;
+            }
+            _ => self.queue_failure(subscription, event_name, event_id, payload, &e.to_string()).await,
+        }
+    }
+
+    /// Queue a failed delivery for retries, or dead-letter it when the
+    /// subscription has retries off
+    async fn queue_failure(
+        &self,
+        subscription: &Subscription,
+        event_name: &EventName,
+        event_id: u64,
+        payload: &serde_json::Value,
+        error: &str,
+    ) {
+        match RetryPolicy::from_config(&subscription.config) {
+            Ok(Some(policy)) => {
+                self.delivery_queue
+                    .enqueue(&subscription.id, &policy, event_name, event_id, payload, error)
+                    .await;
+            }
+            _ => {
+                self.delivery_queue
+                    .give_up(&subscription.id, event_name, event_id, payload, 1, durable::now_ms(), error)
+                    .await;
+            }
+        }
+    }
+
+    /// Retry a failed delivery with the policy's backoff until it goes through
+    /// or runs out of attempts, then dead-letter it
+    async fn retry_in_place(
+        &self,
+        subscription: &Subscription,
+        policy: &RetryPolicy,
+        event_name: &EventName,
+        event_id: u64,
+        payload: &serde_json::Value,
+        mut error: String,
+    ) {
+        let first_failed_at = durable::now_ms();
+        for failures in 1..policy.max_attempts {
+            tokio::time::sleep(policy.backoff(failures)).await;
+            match self.deliver(subscription, event_name, event_id, payload).await {
+                Ok(()) => {
+                    self.delivery_queue.retried(&subscription.id, None);
+                    return;
+                }
+                Err(e) => {
+                    error = e.to_string();
+                    self.delivery_queue.retried(&subscription.id, Some(&error));
+                }
+            }
+        }
+        self.delivery_queue
+            .give_up(&subscription.id, event_name, event_id, payload, policy.max_attempts, first_failed_at, &error)
+            .await;
+    }
+
+    /// Dead-letter an event an ordered subscription's full queue refused
+    pub(crate) async fn overflow(
+        &self,
+        subscription: &Subscription,
+        event_name: &EventName,
+        event_id: u64,
+        payload: &serde_json::Value,
+    ) {
+        let error = format!("Ordered delivery queue full ({} events)", ordered::MAX_QUEUED_EVENTS);
+        // SECURITY: Don't log subscription ID to prevent information disclosure
+        tracing::warn!("{}, event dead-lettered", error);
+        let result = Err(narayana_core::Error::Storage(error.clone()));
+        self.metrics.delivered(&subscription.id, event_name, &result);
+        self.delivery_queue
+            .give_up(&subscription.id, event_name, event_id, payload, 0, durable::now_ms(), &error)
+            .await;
+    }
+
+    /// Deliver one event to one subscription, counting the outcome
+    pub(crate) async fn deliver(
+        &self,
+        subscription: &Subscription,
+        event_name: &EventName,
+        event_id: u64,
+        payload: &serde_json::Value,
+    ) -> Result<()> {
+        let result = self.send(subscription, event_name, event_id, payload).await;
+        self.metrics.delivered(&subscription.id, event_name, &result);
+        result
+    }
+
+    async fn send(
+        &self,
+        subscription: &Subscription,
+        event_name: &EventName,
+        event_id: u64,
+        payload: &serde_json::Value,
+    ) -> Result<()> {
+        // Check rate limit for this subscription
+        let rate_limit = subscription.config
+            .get("rate_limit_per_second")
+            .and_then(|v| v.as_f64());
+
+        let delay = self.rate_limiter.check_and_record(
+            &subscription.id.0,
+            rate_limit,
+        ).await;
+
+        // Wait if rate limited
+        if !delay.is_zero() {
+            self.metrics.rate_limited(&subscription.id);
+            tokio::time::sleep(delay).await;
+        }
+
+        // Apply transformation if configured (continue on error)
+        let transformed_payload = match crate::transformations::apply_transformation(subscription, payload) {
+            Ok(transformed) => {
+                if crate::transformations::has_transformation(subscription) {
+                    self.metrics.transformed(&subscription.id, true);
+                }
+                transformed
+            }
+            Err(e) => {
+                self.metrics.transformed(&subscription.id, false);
+                // SECURITY: Don't log subscription ID to prevent information disclosure
+                tracing::warn!("Transformation failed, using original payload: {}", e);
+                payload.clone() // Use original payload if transformation fails
+            }
+        };
+
+        // Validated on subscribe (webhook, SSE and Kafka only)
+        if let Ok(Some(target)) = CloudEventsTarget::from_config(&subscription.config) {
+            let event = target.wrap(&event_name.0, event_id, &transformed_payload);
+            match subscription.transport {
+                TransportType::Webhook => return transports::http::deliver_cloudevent(subscription, &event).await,
+                TransportType::Sse => {
+                    let event = serde_json::to_value(&event)
+                        .map_err(|e| narayana_core::Error::Storage(format!("Failed to serialize CloudEvent: {}", e)))?;
+                    return transports::sse::deliver_sse(
+                        subscription,
+                        &event,
+                        self.sse_connections.get(&subscription.id).map(|s| s.clone()),
+                    ).await;
+                }
+                TransportType::Kafka => {
+                    return transports::kafka::deliver_kafka_cloudevent(
+                        subscription,
+                        &event_name.0,
+                        &transformed_payload,
+                        &event,
+                        self.kafka_producer.clone(),
+                        &self.kafka_stats,
+                    ).await;
+                }
+                _ => {}
+            }
+        }
+
+        match subscription.transport {
+            TransportType::Webhook => {
+                let encoded = self.encode_for(subscription, event_name, &transformed_payload)?;
+                transports::http::deliver_webhook_encoded(
+                    subscription,
+                    &event_name.0,
+                    &transformed_payload,
+                    &encoded,
+                ).await
+            }
+            TransportType::WebSocket => {
+                transports::websocket::deliver_websocket(
+                    subscription,
+                    &transformed_payload,
+                    self.websocket_manager.clone(),
+                ).await
+            }
+            TransportType::Grpc => {
+                transports::grpc::deliver_grpc(
+                    subscription,
+                    &transformed_payload,
+                    self.grpc_streams.get(&subscription.id).map(|s| s.clone()),
+                ).await
+            }
+            TransportType::Sse => {
+                transports::sse::deliver_sse(
+                    subscription,
+                    &transformed_payload,
+                    self.sse_connections.get(&subscription.id).map(|s| s.clone()),
+                ).await
+            }
+            TransportType::Worker => {
+                let encoded = self.encode_for(subscription, event_name, &transformed_payload)?;
+                transports::worker::deliver_worker_encoded(
+                    subscription,
+                    &encoded,
+                    self.worker_invoker.clone(),
+                    &self.worker_stats,
+                ).await
+            }
+            TransportType::Table => match &self.table_sink {
+                Some(sink) => sink.deliver(subscription, &transformed_payload).await,
+                None => Err(narayana_core::Error::Storage("Table sink not available".to_string())),
+            },
+            TransportType::Kafka => {
+                let encoded = self.encode_for(subscription, event_name, &transformed_payload)?;
+                transports::kafka::deliver_kafka_encoded(
+                    subscription,
+                    &event_name.0,
+                    &transformed_payload,
+                    &encoded,
+                    self.kafka_producer.clone(),
+                    &self.kafka_stats,
+                ).await
+            }
+            TransportType::Mqtt => {
+                let encoded = self.encode_for(subscription, event_name, &transformed_payload)?;
+                transports::mqtt::deliver_mqtt_encoded(
+                    subscription,
+                    &event_name.0,
+                    &encoded,
+                    self.mqtt_connections.clone(),
+                    &self.mqtt_stats,
+                ).await
+            }
+            TransportType::Amqp => {
+                let encoded = self.encode_for(subscription, event_name, &transformed_payload)?;
+                transports::amqp::deliver_amqp_encoded(
+                    subscription,
+                    &event_name.0,
+                    &encoded,
+                    self.amqp_connections.clone(),
+                    &self.amqp_stats,
+                ).await
+            }
+        }
+    }
+
+    /// Encode a payload as the subscription's negotiated content type
+    fn encode_for(
+        &self,
+        subscription: &Subscription,
+        event_name: &EventName,
+        payload: &serde_json::Value,
+    ) -> Result<EncodedPayload> {
+        encoding::encode_for_subscription(&subscription.config, &event_name.0, payload, &self.schema_registry)
+    }
+}
@@ -0,0 +1,597 @@
+// Durable delivery - at-least-once retries and dead letters
+// A failed delivery is queued and retried with the subscription's backoff
+// until it goes through or runs out of attempts; then it moves to the
+// subscription's dead-letter stream, where the subscriber can read it back and
+// queue it for redelivery; subscriptions without retries dead-letter a failed
+// delivery right away. Queue changes are journaled to a native_events stream,
+// so a manager over the same event system can recover what was still pending.
+
+use crate::events::EventName;
+use crate::subscriptions::SubscriptionId;
+use narayana_core::{Error, Result};
+use narayana_storage::native_events::{Event as NativeEvent, EventId, EventStream, NativeEventsSystem, StreamName};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+const DEFAULT_MAX_ATTEMPTS: u32 = 5;
+const MAX_ATTEMPTS_LIMIT: u32 = 100;
+const DEFAULT_INITIAL_BACKOFF_MS: u64 = 1_000;
+const DEFAULT_MAX_BACKOFF_MS: u64 = 5 * 60 * 1_000;
+const MAX_BACKOFF_LIMIT_MS: u64 = 24 * 60 * 60 * 1_000;
+/// Deliveries queued per subscription; failures beyond this are dead-lettered
+/// right away so a dead endpoint can't grow the queue without bound
+const MAX_PENDING_PER_SUBSCRIPTION: u64 = 10_000;
+const JOURNAL_STREAM: &str = "rde-deliveries";
+const JOURNAL_PAGE: usize = 1_000;
+const DEAD_LETTER_STREAM_PREFIX: &str = "rde-dead-letters:";
+
+/// Retry policy of a subscription
+///
+/// Config: `retry` is false to dead-letter failed deliveries right away, or an
+/// object with `max_attempts` (deliveries before dead-lettering, the live one
+/// included; default 5), `initial_backoff_ms` (default 1000) and
+/// `max_backoff_ms` (default 300000). The delay doubles after every failed retry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            initial_backoff_ms: DEFAULT_INITIAL_BACKOFF_MS,
+            max_backoff_ms: DEFAULT_MAX_BACKOFF_MS,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Parse the subscription's `retry` option; None when retries are off
+    pub fn from_config(config: &Value) -> Result<Option<Self>> {
+        let retry = match config.get("retry") {
+            None | Some(Value::Null) | Some(Value::Bool(true)) => return Ok(Some(Self::default())),
+            Some(Value::Bool(false)) => return Ok(None),
+            Some(Value::Object(retry)) => retry,
+            Some(_) => return Err(Error::Storage("retry must be a boolean or an object".to_string())),
+        };
+        let field = |name: &str, default: u64| match retry.get(name) {
+            None => Ok(default),
+            Some(value) => value.as_u64()
+                .ok_or_else(|| Error::Storage(format!("retry.{} must be a non-negative integer", name))),
+        };
+
+        let max_attempts = field("max_attempts", DEFAULT_MAX_ATTEMPTS as u64)?;
+        if max_attempts == 0 || max_attempts > MAX_ATTEMPTS_LIMIT as u64 {
+            return Err(Error::Storage(format!("retry.max_attempts must be between 1 and {}", MAX_ATTEMPTS_LIMIT)));
+        }
+        let initial_backoff_ms = field("initial_backoff_ms", DEFAULT_INITIAL_BACKOFF_MS)?;
+        let max_backoff_ms = field("max_backoff_ms", DEFAULT_MAX_BACKOFF_MS.max(initial_backoff_ms))?;
+        if initial_backoff_ms == 0 || max_backoff_ms > MAX_BACKOFF_LIMIT_MS {
+            return Err(Error::Storage("retry backoff must be between 1 ms and 24 hours".to_string()));
+        }
+        if max_backoff_ms < initial_backoff_ms {
+            return Err(Error::Storage("retry.max_backoff_ms must not be below retry.initial_backoff_ms".to_string()));
+        }
+        Ok(Some(Self { max_attempts: max_attempts as u32, initial_backoff_ms, max_backoff_ms }))
+    }
+
+    /// Delay before the next delivery after `failures` failed ones
+    pub fn backoff(&self, failures: u32) -> Duration {
+        let factor = 1u64.checked_shl(failures.saturating_sub(1)).unwrap_or(u64::MAX);
+        Duration::from_millis(self.initial_backoff_ms.saturating_mul(factor).min(self.max_backoff_ms))
+    }
+}
+
+/// A delivery waiting for its next retry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingDelivery {
+    pub id: String,
+    pub subscription_id: SubscriptionId,
+    pub event_name: EventName,
+    pub event_id: u64,
+    pub payload: Value,
+    /// Failed deliveries so far
+    pub attempts: u32,
+    /// Unix milliseconds
+    pub next_attempt_at: u64,
+    pub first_failed_at: u64,
+    pub last_error: String,
+}
+
+/// An event that could not be delivered, as kept in the dead-letter stream
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetter {
+    /// Position in the dead-letter stream (pass the last one + 1 to page)
+    #[serde(default)]
+    pub sequence: u64,
+    pub event_name: EventName,
+    pub event_id: u64,
+    pub payload: Value,
+    pub attempts: u32,
+    pub first_failed_at: u64,
+    pub dead_at: u64,
+    pub last_error: String,
+}
+
+/// Dead letters picked for redelivery
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeadLetterSelection {
+    /// Every dead letter from this sequence on, oldest first
+    From(u64),
+    /// Just the dead letters at these sequences
+    Sequences(Vec<u64>),
+}
+
+/// Outcome of a redelivery
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RedeliveryReport {
+    /// Queued with fresh attempts; these left the dead-letter stream
+    pub queued: usize,
+    /// Selected but left dead: past the per-call cap, or not queued because
+    /// the subscription's retry queue filled up
+    pub skipped: usize,
+    /// Requested sequences with no dead letter (never written, already
+    /// redelivered or past retention)
+    pub missing: Vec<u64>,
+}
+
+/// Dead letters kept for one subscription
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetterSummary {
+    pub subscription_id: SubscriptionId,
+    /// Dead letters retained
+    pub count: usize,
+    pub first_sequence: Option<u64>,
+    pub last_sequence: Option<u64>,
+}
+
+/// Retry metrics for one subscription
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DurableDeliveryStats {
+    /// Deliveries waiting for a retry
+    pub pending: u64,
+    /// Retries made
+    pub retries: u64,
+    /// Deliveries that went through on a retry
+    pub recovered: u64,
+    /// Deliveries moved to the dead-letter stream
+    pub dead_lettered: u64,
+    pub last_error: Option<String>,
+}
+
+/// Queue change, as journaled
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum JournalEntry {
+    Queued { delivery: PendingDelivery },
+    Failed { id: String, attempts: u32, next_attempt_at: u64, last_error: String },
+    /// Delivered, dead-lettered or discarded
+    Done { id: String },
+}
+
+impl JournalEntry {
+    fn op(&self) -> &'static str {
+        match self {
+            JournalEntry::Queued { .. } => "queued",
+            JournalEntry::Failed { .. } => "failed",
+            JournalEntry::Done { .. } => "done",
+        }
+    }
+}
+
+fn dead_letter_stream(subscription_id: &SubscriptionId) -> StreamName {
+    StreamName(format!("{}{}", DEAD_LETTER_STREAM_PREFIX, subscription_id.0))
+}
+
+pub(crate) fn now_ms() -> u64 {
+    u64::try_from(chrono::Utc::now().timestamp_millis()).unwrap_or_default()
+}
+
+/// Failed deliveries of all subscriptions
+pub(crate) struct DeliveryQueue {
+    native_events: Arc<NativeEventsSystem>,
+    pending: parking_lot::Mutex<HashMap<String, PendingDelivery>>,
+    stats: dashmap::DashMap<SubscriptionId, DurableDeliveryStats>,
+}
+
+impl DeliveryQueue {
+    pub(crate) fn new(native_events: Arc<NativeEventsSystem>) -> Self {
+        Self {
+            native_events,
+            pending: parking_lot::Mutex::new(HashMap::new()),
+            stats: dashmap::DashMap::new(),
+        }
+    }
+
+    /// Queue a delivery that just failed for the first time
+    pub(crate) async fn enqueue(
+        &self,
+        subscription_id: &SubscriptionId,
+        policy: &RetryPolicy,
+        event_name: &EventName,
+        event_id: u64,
+        payload: &Value,
+        error: &str,
+    ) {
+        let now = now_ms();
+        let delivery = PendingDelivery {
+            id: uuid::Uuid::new_v4().to_string(),
+            subscription_id: subscription_id.clone(),
+            event_name: event_name.clone(),
+            event_id,
+            payload: payload.clone(),
+            attempts: 1,
+            next_attempt_at: now + policy.backoff(1).as_millis() as u64,
+            first_failed_at: now,
+            last_error: error.to_string(),
+        };
+        let queued = {
+            let mut stats = self.stats.entry(subscription_id.clone()).or_default();
+            stats.last_error = Some(error.to_string());
+            let queued = policy.max_attempts > 1 && stats.pending < MAX_PENDING_PER_SUBSCRIPTION;
+            if queued {
+                stats.pending += 1;
+            }
+            queued
+        };
+        if queued {
+            self.insert(delivery).await;
+        } else {
+            self.dead_letter(delivery).await;
+        }
+    }
+
+    /// Queue a dead letter for delivery with a fresh set of attempts
+    /// Returns false when the subscription's queue is full.
+    pub(crate) async fn requeue(&self, subscription_id: &SubscriptionId, letter: DeadLetter) -> bool {
+        {
+            let mut stats = self.stats.entry(subscription_id.clone()).or_default();
+            if stats.pending >= MAX_PENDING_PER_SUBSCRIPTION {
+                return false;
+            }
+            stats.pending += 1;
+        }
+        self.insert(PendingDelivery {
+            id: uuid::Uuid::new_v4().to_string(),
+            subscription_id: subscription_id.clone(),
+            event_name: letter.event_name,
+            event_id: letter.event_id,
+            payload: letter.payload,
+            attempts: 0,
+            next_attempt_at: now_ms(),
+            first_failed_at: letter.first_failed_at,
+            last_error: letter.last_error,
+        })
+        .await;
+        true
+    }
+
+    /// Requeue dead letters in order, dropping each from the dead-letter
+    /// stream once queued; stops when the subscription's queue is full.
+    /// Returns how many were queued.
+    pub(crate) async fn redeliver(&self, subscription_id: &SubscriptionId, letters: Vec<DeadLetter>) -> usize {
+        let mut queued = Vec::new();
+        for letter in letters {
+            let sequence = EventId(letter.sequence);
+            if !self.requeue(subscription_id, letter).await {
+                break;
+            }
+            queued.push(sequence);
+        }
+        if let Err(e) = self.native_events.remove_events(&dead_letter_stream(subscription_id), &queued).await {
+            tracing::warn!("Failed to drop redelivered dead letters, they may be delivered again: {}", e);
+        }
+        queued.len()
+    }
+
+    /// Take the deliveries due at `now`, oldest event first; each must be
+    /// handed back through `succeeded`, `failed` or `discard`
+    pub(crate) fn take_due(&self, now: u64, limit: usize) -> Vec<PendingDelivery> {
+        let mut pending = self.pending.lock();
+        let mut due: Vec<(u64, u64, String)> = pending
+            .values()
+            .filter(|d| d.next_attempt_at <= now)
+            .map(|d| (d.next_attempt_at, d.event_id, d.id.clone()))
+            .collect();
+        due.sort();
+        due.into_iter()
+            .take(limit)
+            .filter_map(|(_, _, id)| pending.remove(&id))
+            .collect()
+    }
+
+    async fn insert(&self, delivery: PendingDelivery) {
+        self.pending.lock().insert(delivery.id.clone(), delivery.clone());
+        self.journal(JournalEntry::Queued { delivery }).await;
+    }
+
+    pub(crate) async fn succeeded(&self, delivery: PendingDelivery) {
+        if let Some(mut stats) = self.stats.get_mut(&delivery.subscription_id) {
+            stats.pending = stats.pending.saturating_sub(1);
+            stats.retries += 1;
+            stats.recovered += 1;
+            stats.last_error = None;
+        }
+        self.journal(JournalEntry::Done { id: delivery.id }).await;
+    }
+
+    pub(crate) async fn failed(&self, mut delivery: PendingDelivery, policy: &RetryPolicy, error: &str) {
+        delivery.attempts += 1;
+        delivery.last_error = error.to_string();
+        if let Some(mut stats) = self.stats.get_mut(&delivery.subscription_id) {
+            stats.retries += 1;
+            stats.last_error = Some(error.to_string());
+            if delivery.attempts >= policy.max_attempts {
+                stats.pending = stats.pending.saturating_sub(1);
+            }
+        }
+        if delivery.attempts >= policy.max_attempts {
+            let id = delivery.id.clone();
+            self.dead_letter(delivery).await;
+            self.journal(JournalEntry::Done { id }).await;
+            return;
+        }
+        delivery.next_attempt_at = now_ms() + policy.backoff(delivery.attempts).as_millis() as u64;
+        let entry = JournalEntry::Failed {
+            id: delivery.id.clone(),
+            attempts: delivery.attempts,
+            next_attempt_at: delivery.next_attempt_at,
+            last_error: delivery.last_error.clone(),
+        };
+        self.pending.lock().insert(delivery.id.clone(), delivery);
+        self.journal(entry).await;
+    }
+
+    /// Count a retry made in place rather than through the queue (ordered
+    /// subscriptions); `error` is None when it went through
+    pub(crate) fn retried(&self, subscription_id: &SubscriptionId, error: Option<&str>) {
+        let mut stats = self.stats.entry(subscription_id.clone()).or_default();
+        stats.retries += 1;
+        match error {
+            Some(error) => stats.last_error = Some(error.to_string()),
+            None => {
+                stats.recovered += 1;
+                stats.last_error = None;
+            }
+        }
+    }
+
+    /// Dead-letter a delivery that was never queued (retried in place, or
+    /// refused by a full ordered queue)
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn give_up(
+        &self,
+        subscription_id: &SubscriptionId,
+        event_name: &EventName,
+        event_id: u64,
+        payload: &Value,
+        attempts: u32,
+        first_failed_at: u64,
+        error: &str,
+    ) {
+        self.stats.entry(subscription_id.clone()).or_default().last_error = Some(error.to_string());
+        self.dead_letter(PendingDelivery {
+            id: uuid::Uuid::new_v4().to_string(),
+            subscription_id: subscription_id.clone(),
+            event_name: event_name.clone(),
+            event_id,
+            payload: payload.clone(),
+            attempts,
+            next_attempt_at: 0,
+            first_failed_at,
+            last_error: error.to_string(),
+        })
+        .await;
+    }
+
+    /// Drop a delivery whose subscription is gone
+    pub(crate) async fn discard(&self, delivery: PendingDelivery) {
+        if let Some(mut stats) = self.stats.get_mut(&delivery.subscription_id) {
+            stats.pending = stats.pending.saturating_sub(1);
+        }
+        self.journal(JournalEntry::Done { id: delivery.id }).await;
+    }
+
+    /// Drop everything queued for a removed subscription
+    pub(crate) async fn remove_subscription(&self, subscription_id: &SubscriptionId) {
+        let removed: Vec<String> = {
+            let mut pending = self.pending.lock();
+            let ids: Vec<String> = pending
+                .values()
+                .filter(|d| d.subscription_id == *subscription_id)
+                .map(|d| d.id.clone())
+                .collect();
+            ids.into_iter().filter(|id| pending.remove(id).is_some()).collect()
+        };
+        self.stats.remove(subscription_id);
+        for id in removed {
+            self.journal(JournalEntry::Done { id }).await;
+        }
+    }
+
+    pub(crate) fn stats(&self, subscription_id: &SubscriptionId) -> Option<DurableDeliveryStats> {
+        self.stats.get(subscription_id).map(|s| s.value().clone())
+    }
+
+    /// Dead letters of a subscription from `sequence` on, oldest first
+    pub(crate) fn dead_letters(&self, subscription_id: &SubscriptionId, sequence: u64, limit: usize) -> Vec<DeadLetter> {
+        self.native_events
+            .read_stream(&dead_letter_stream(subscription_id), EventId(sequence), limit)
+            .into_iter()
+            .filter_map(|event| {
+                let mut letter: DeadLetter = serde_json::from_value(event.payload).ok()?;
+                letter.sequence = event.id.0;
+                Some(letter)
+            })
+            .collect()
+    }
+
+    /// Number of dead letters of a subscription from `sequence` on
+    pub(crate) fn dead_letter_count(&self, subscription_id: &SubscriptionId, sequence: u64) -> usize {
+        self.native_events.count_stream_from(&dead_letter_stream(subscription_id), EventId(sequence))
+    }
+
+    /// The dead letter at `sequence`, if it is still retained
+    pub(crate) fn dead_letter_at(&self, subscription_id: &SubscriptionId, sequence: u64) -> Option<DeadLetter> {
+        self.dead_letters(subscription_id, sequence, 1)
+            .into_iter()
+            .find(|letter| letter.sequence == sequence)
+    }
+
+    /// Subscriptions with retained dead letters, removed ones included
+    pub(crate) fn dead_letter_summaries(&self) -> Vec<DeadLetterSummary> {
+        let mut summaries: Vec<DeadLetterSummary> = self.native_events
+            .stream_names()
+            .into_iter()
+            .filter_map(|stream| {
+                let subscription_id = SubscriptionId(stream.0.strip_prefix(DEAD_LETTER_STREAM_PREFIX)?.to_string());
+                let stats = self.native_events.get_stream_stats(&stream).ok()?;
+                (stats.event_count > 0).then(|| DeadLetterSummary {
+                    subscription_id,
+                    count: stats.event_count,
+                    first_sequence: stats.first_event_id.map(|id| id.0),
+                    last_sequence: stats.last_event_id.map(|id| id.0),
+                })
+            })
+            .collect();
+        summaries.sort_by(|a, b| a.subscription_id.0.cmp(&b.subscription_id.0));
+        summaries
+    }
+
+    /// Rebuild the queue from the journal; returns the deliveries recovered
+    ///
+    /// Deliveries whose `queued` entry was evicted from the journal can't be
+    /// rebuilt and are lost.
+    pub(crate) async fn recover(&self) -> usize {
+        let stream = StreamName(JOURNAL_STREAM.to_string());
+        let mut recovered: HashMap<String, PendingDelivery> = HashMap::new();
+        let mut cursor = 0;
+        loop {
+            let page = self.native_events.read_stream(&stream, EventId(cursor), JOURNAL_PAGE);
+            let Some(last) = page.last().map(|e| e.id.0) else {
+                break;
+            };
+            for event in page {
+                match serde_json::from_value(event.payload) {
+                    Ok(JournalEntry::Queued { delivery }) => {
+                        recovered.insert(delivery.id.clone(), delivery);
+                    }
+                    Ok(JournalEntry::Failed { id, attempts, next_attempt_at, last_error }) => {
+                        if let Some(delivery) = recovered.get_mut(&id) {
+                            delivery.attempts = attempts;
+                            delivery.next_attempt_at = next_attempt_at;
+                            delivery.last_error = last_error;
+                        }
+                    }
+                    Ok(JournalEntry::Done { id }) => {
+                        recovered.remove(&id);
+                    }
+                    Err(e) => tracing::warn!("Skipping unreadable delivery journal entry: {}", e),
+                }
+            }
+            cursor = last + 1;
+        }
+
+        let mut pending = self.pending.lock();
+        let mut count = 0;
+        for (id, delivery) in recovered {
+            if pending.contains_key(&id) {
+                continue;
+            }
+            self.stats.entry(delivery.subscription_id.clone()).or_default().pending += 1;
+            pending.insert(id, delivery);
+            count += 1;
+        }
+        count
+    }
+
+    async fn dead_letter(&self, delivery: PendingDelivery) {
+        if let Some(mut stats) = self.stats.get_mut(&delivery.subscription_id) {
+            stats.dead_lettered += 1;
+        }
+        let stream = dead_letter_stream(&delivery.subscription_id);
+        self.ensure_stream(&stream, Duration::from_secs(30 * 24 * 60 * 60), 100_000).await;
+        let letter = DeadLetter {
+            sequence: 0,
+            event_name: delivery.event_name,
+            event_id: delivery.event_id,
+            payload: delivery.payload,
+            attempts: delivery.attempts,
+            first_failed_at: delivery.first_failed_at,
+            dead_at: now_ms(),
+            last_error: delivery.last_error,
+        };
+        let payload = match serde_json::to_value(&letter) {
+            Ok(payload) => payload,
+            Err(e) => {
+                tracing::warn!("Failed to serialize dead letter: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = self.native_events.publish_event(event(stream, "dead_letter", payload)).await {
+            // SECURITY: Don't log subscription ID to prevent information disclosure
+            tracing::warn!("Failed to store dead letter, event dropped: {}", e);
+        }
+    }
+
+    async fn journal(&self, entry: JournalEntry) {
+        let stream = StreamName(JOURNAL_STREAM.to_string());
+        self.ensure_stream(&stream, Duration::from_secs(7 * 24 * 60 * 60), 1_000_000).await;
+        let op = entry.op();
+        let payload = match serde_json::to_value(&entry) {
+            Ok(payload) => payload,
+            Err(e) => {
+                tracing::warn!("Failed to serialize delivery journal entry: {}", e);
+                return;
+            }
+        };
+        // The queue itself stays in memory, so a lost entry only affects recovery
+        if let Err(e) = self.native_events.publish_event(event(stream, op, payload)).await {
+            tracing::warn!("Failed to journal delivery: {}", e);
+        }
+    }
+
+    async fn ensure_stream(&self, name: &StreamName, retention: Duration, max_events: u64) {
+        let stream = EventStream {
+            name: name.clone(),
+            partitions: 1,
+            retention: Some(retention),
+            replication_factor: 1,
+            compression: true,
+            encryption: false,
+            max_size: None,
+            max_events: Some(max_events),
+        };
+        if let Err(e) = self.native_events.create_stream(stream).await {
+            if !e.to_string().contains("already exists") {
+                tracing::warn!("Failed to create stream: {}", e);
+            }
+        }
+    }
+}
+
+/// Stream event with a sequence-assigned id
+pub(crate) fn event(stream: StreamName, event_type: &str, payload: Value) -> NativeEvent {
+    NativeEvent {
+        id: EventId(0),
+        stream,
+        topic: None,
+        queue: None,
+        event_type: event_type.to_string(),
+        payload,
+        headers: HashMap::new(),
+        timestamp: 0,
+        correlation_id: None,
+        causation_id: None,
+        partition_key: None,
+        ttl: None,
+        priority: 0,
+    }
+}
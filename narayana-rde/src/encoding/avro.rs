@@ -0,0 +1,383 @@
+// Avro binary encoding
+// Covers the Avro 1.11 type system except recursive named types; bytes and
+// fixed values are base64 strings on the JSON side.
+
+use super::{decode_base64, encode_base64, float_value, unzigzag, write_varint, zigzag, Reader, MAX_DEPTH};
+use narayana_core::{Error, Result};
+use serde_json::Value;
+use std::collections::HashMap;
+
+const MAX_BLOCK_ITEMS: u64 = 1 << 20;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AvroSchema {
+    Null,
+    Boolean,
+    Int,
+    Long,
+    Float,
+    Double,
+    Bytes,
+    String,
+    Record { name: String, fields: Vec<AvroField> },
+    Enum { name: String, symbols: Vec<String> },
+    Array(Box<AvroSchema>),
+    Map(Box<AvroSchema>),
+    Union(Vec<AvroSchema>),
+    Fixed { name: String, size: usize },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AvroField {
+    pub name: String,
+    pub schema: AvroSchema,
+    pub default: Option<Value>,
+}
+
+fn invalid(message: impl std::fmt::Display) -> Error {
+    Error::Storage(format!("Invalid Avro schema: {}", message))
+}
+
+fn mismatch(expected: &str, value: &Value) -> Error {
+    Error::Storage(format!("Expected {}, found {}", expected, value))
+}
+
+impl AvroSchema {
+    /// Parse a JSON schema definition
+    pub fn parse(definition: &Value) -> Result<Self> {
+        Self::parse_inner(definition, &mut HashMap::new(), 0)
+    }
+
+    fn parse_inner(definition: &Value, named: &mut HashMap<String, AvroSchema>, depth: usize) -> Result<Self> {
+        if depth > MAX_DEPTH {
+            return Err(invalid("nested too deeply"));
+        }
+        match definition {
+            Value::String(name) => Self::primitive(name)
+                .or_else(|| named.get(name).cloned())
+                .ok_or_else(|| invalid(format!("unknown type '{}'", name))),
+            Value::Array(branches) => {
+                let branches = branches.iter()
+                    .map(|b| Self::parse_inner(b, named, depth + 1))
+                    .collect::<Result<Vec<_>>>()?;
+                if branches.is_empty() || branches.iter().any(|b| matches!(b, AvroSchema::Union(_))) {
+                    return Err(invalid("unions must be non-empty and cannot nest"));
+                }
+                Ok(AvroSchema::Union(branches))
+            }
+            Value::Object(object) => {
+                let kind = object.get("type").ok_or_else(|| invalid("missing 'type'"))?;
+                let Some(kind) = kind.as_str() else {
+                    return Self::parse_inner(kind, named, depth + 1);
+                };
+                let name = || {
+                    object.get("name")
+                        .and_then(|n| n.as_str())
+                        .filter(|n| !n.is_empty())
+                        .map(str::to_string)
+                        .ok_or_else(|| invalid(format!("{} requires a name", kind)))
+                };
+                let schema = match kind {
+                    "record" | "error" => {
+                        let name = name()?;
+                        let fields = object.get("fields")
+                            .and_then(|f| f.as_array())
+                            .ok_or_else(|| invalid(format!("record '{}' requires fields", name)))?;
+                        let mut parsed: Vec<AvroField> = Vec::with_capacity(fields.len());
+                        for field in fields {
+                            let field_name = field.get("name")
+                                .and_then(|n| n.as_str())
+                                .ok_or_else(|| invalid("field requires a name"))?;
+                            if parsed.iter().any(|f| f.name == field_name) {
+                                return Err(invalid(format!("duplicate field '{}'", field_name)));
+                            }
+                            let field_type = field.get("type")
+                                .ok_or_else(|| invalid(format!("field '{}' requires a type", field_name)))?;
+                            parsed.push(AvroField {
+                                name: field_name.to_string(),
+                                schema: Self::parse_inner(field_type, named, depth + 1)?,
+                                default: field.get("default").cloned(),
+                            });
+                        }
+                        AvroSchema::Record { name, fields: parsed }
+                    }
+                    "enum" => {
+                        let symbols: Vec<String> = object.get("symbols")
+                            .and_then(|s| s.as_array())
+                            .map(|s| s.iter().filter_map(|s| s.as_str().map(str::to_string)).collect())
+                            .unwrap_or_default();
+                        if symbols.is_empty() {
+                            return Err(invalid("enum requires symbols"));
+                        }
+                        AvroSchema::Enum { name: name()?, symbols }
+                    }
+                    "array" => AvroSchema::Array(Box::new(Self::parse_inner(
+                        object.get("items").ok_or_else(|| invalid("array requires items"))?,
+                        named,
+                        depth + 1,
+                    )?)),
+                    "map" => AvroSchema::Map(Box::new(Self::parse_inner(
+                        object.get("values").ok_or_else(|| invalid("map requires values"))?,
+                        named,
+                        depth + 1,
+                    )?)),
+                    "fixed" => AvroSchema::Fixed {
+                        name: name()?,
+                        size: object.get("size")
+                            .and_then(|s| s.as_u64())
+                            .ok_or_else(|| invalid("fixed requires a size"))? as usize,
+                    },
+                    // Logical types annotate a primitive; the underlying type decides the encoding
+                    other => Self::primitive(other)
+                        .or_else(|| named.get(other).cloned())
+                        .ok_or_else(|| invalid(format!("unknown type '{}'", other)))?,
+                };
+                if let AvroSchema::Record { name, .. } | AvroSchema::Enum { name, .. } | AvroSchema::Fixed { name, .. } = &schema {
+                    named.insert(name.clone(), schema.clone());
+                }
+                Ok(schema)
+            }
+            other => Err(invalid(format!("unexpected {}", other))),
+        }
+    }
+
+    fn primitive(name: &str) -> Option<Self> {
+        Some(match name {
+            "null" => AvroSchema::Null,
+            "boolean" => AvroSchema::Boolean,
+            "int" => AvroSchema::Int,
+            "long" => AvroSchema::Long,
+            "float" => AvroSchema::Float,
+            "double" => AvroSchema::Double,
+            "bytes" => AvroSchema::Bytes,
+            "string" => AvroSchema::String,
+            _ => return None,
+        })
+    }
+
+    pub fn encode(&self, value: &Value) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+        self.write(value, &mut out, 0)?;
+        Ok(out)
+    }
+
+    pub fn decode(&self, data: &[u8]) -> Result<Value> {
+        let mut reader = Reader::new(data);
+        let value = self.read(&mut reader, 0)?;
+        if !reader.is_empty() {
+            return Err(Error::Storage("Trailing bytes after Avro payload".to_string()));
+        }
+        Ok(value)
+    }
+
+    /// Whether `value` can be written with this schema (used to pick union branches)
+    fn accepts(&self, value: &Value) -> bool {
+        match (self, value) {
+            (AvroSchema::Null, Value::Null) => true,
+            (AvroSchema::Boolean, Value::Bool(_)) => true,
+            (AvroSchema::Int, Value::Number(n)) => n.as_i64().is_some_and(|n| i32::try_from(n).is_ok()),
+            (AvroSchema::Long, Value::Number(n)) => n.as_i64().is_some(),
+            (AvroSchema::Float | AvroSchema::Double, Value::Number(_)) => true,
+            (AvroSchema::String | AvroSchema::Bytes, Value::String(_)) => true,
+            (AvroSchema::Enum { symbols, .. }, Value::String(s)) => symbols.contains(s),
+            (AvroSchema::Fixed { size, .. }, Value::String(s)) => decode_base64(s).is_ok_and(|b| b.len() == *size),
+            (AvroSchema::Array(_), Value::Array(_)) => true,
+            (AvroSchema::Record { .. } | AvroSchema::Map(_), Value::Object(_)) => true,
+            _ => false,
+        }
+    }
+
+    fn write(&self, value: &Value, out: &mut Vec<u8>, depth: usize) -> Result<()> {
+        if depth > MAX_DEPTH {
+            return Err(Error::Storage("Payload nested too deeply".to_string()));
+        }
+        match self {
+            AvroSchema::Null => {
+                if !value.is_null() {
+                    return Err(mismatch("null", value));
+                }
+            }
+            AvroSchema::Boolean => out.push(value.as_bool().ok_or_else(|| mismatch("boolean", value))? as u8),
+            AvroSchema::Int => {
+                let n = value.as_i64()
+                    .and_then(|n| i32::try_from(n).ok())
+                    .ok_or_else(|| mismatch("int", value))?;
+                write_varint(out, zigzag(n as i64));
+            }
+            AvroSchema::Long => write_varint(out, zigzag(value.as_i64().ok_or_else(|| mismatch("long", value))?)),
+            AvroSchema::Float => {
+                let n = value.as_f64().ok_or_else(|| mismatch("float", value))?;
+                out.extend_from_slice(&(n as f32).to_le_bytes());
+            }
+            AvroSchema::Double => {
+                let n = value.as_f64().ok_or_else(|| mismatch("double", value))?;
+                out.extend_from_slice(&n.to_le_bytes());
+            }
+            AvroSchema::Bytes => {
+                let bytes = decode_base64(value.as_str().ok_or_else(|| mismatch("bytes", value))?)?;
+                write_varint(out, zigzag(bytes.len() as i64));
+                out.extend_from_slice(&bytes);
+            }
+            AvroSchema::String => {
+                let s = value.as_str().ok_or_else(|| mismatch("string", value))?;
+                write_varint(out, zigzag(s.len() as i64));
+                out.extend_from_slice(s.as_bytes());
+            }
+            AvroSchema::Record { name, fields } => {
+                let object = value.as_object().ok_or_else(|| mismatch(name, value))?;
+                for field in fields {
+                    let field_value = object.get(&field.name)
+                        .or(field.default.as_ref())
+                        .or(field.schema.accepts(&Value::Null).then_some(&Value::Null))
+                        .ok_or_else(|| Error::Storage(format!("Missing field '{}' of {}", field.name, name)))?;
+                    field.schema.write(field_value, out, depth + 1)?;
+                }
+            }
+            AvroSchema::Enum { name, symbols } => {
+                let index = value.as_str()
+                    .and_then(|s| symbols.iter().position(|symbol| symbol == s))
+                    .ok_or_else(|| mismatch(name, value))?;
+                write_varint(out, zigzag(index as i64));
+            }
+            AvroSchema::Array(items) => {
+                let values = value.as_array().ok_or_else(|| mismatch("array", value))?;
+                if !values.is_empty() {
+                    write_varint(out, zigzag(values.len() as i64));
+                    for item in values {
+                        items.write(item, out, depth + 1)?;
+                    }
+                }
+                out.push(0);
+            }
+            AvroSchema::Map(values) => {
+                let object = value.as_object().ok_or_else(|| mismatch("map", value))?;
+                if !object.is_empty() {
+                    write_varint(out, zigzag(object.len() as i64));
+                    for (key, item) in object {
+                        AvroSchema::String.write(&Value::String(key.clone()), out, depth + 1)?;
+                        values.write(item, out, depth + 1)?;
+                    }
+                }
+                out.push(0);
+            }
+            AvroSchema::Union(branches) => {
+                let index = branches.iter()
+                    .position(|branch| branch.accepts(value))
+                    .ok_or_else(|| mismatch("a union branch", value))?;
+                write_varint(out, zigzag(index as i64));
+                branches[index].write(value, out, depth + 1)?;
+            }
+            AvroSchema::Fixed { name, size } => {
+                let bytes = value.as_str()
+                    .and_then(|s| decode_base64(s).ok())
+                    .filter(|b| b.len() == *size)
+                    .ok_or_else(|| mismatch(name, value))?;
+                out.extend_from_slice(&bytes);
+            }
+        }
+        Ok(())
+    }
+
+    fn read(&self, reader: &mut Reader<'_>, depth: usize) -> Result<Value> {
+        if depth > MAX_DEPTH {
+            return Err(Error::Storage("Payload nested too deeply".to_string()));
+        }
+        let long = |reader: &mut Reader<'_>| reader.varint().map(unzigzag);
+        let length = |reader: &mut Reader<'_>| {
+            let len = long(reader)?;
+            usize::try_from(len)
+                .ok()
+                .filter(|len| *len <= reader.remaining())
+                .ok_or_else(|| Error::Storage(format!("Invalid length {}", len)))
+        };
+        Ok(match self {
+            AvroSchema::Null => Value::Null,
+            AvroSchema::Boolean => match reader.bytes(1)?[0] {
+                0 => Value::Bool(false),
+                1 => Value::Bool(true),
+                b => return Err(Error::Storage(format!("Invalid boolean byte {}", b))),
+            },
+            AvroSchema::Int => {
+                let n = long(reader)?;
+                i32::try_from(n).map_err(|_| Error::Storage(format!("Int out of range: {}", n)))?.into()
+            }
+            AvroSchema::Long => long(reader)?.into(),
+            AvroSchema::Float => float_value(f32::from_le_bytes(reader.array()?) as f64),
+            AvroSchema::Double => float_value(f64::from_le_bytes(reader.array()?)),
+            AvroSchema::Bytes => {
+                let len = length(reader)?;
+                encode_base64(reader.bytes(len)?)
+            }
+            AvroSchema::String => {
+                let len = length(reader)?;
+                Value::String(
+                    String::from_utf8(reader.bytes(len)?.to_vec())
+                        .map_err(|_| Error::Storage("Invalid UTF-8 in string".to_string()))?,
+                )
+            }
+            AvroSchema::Record { fields, .. } => {
+                let mut object = serde_json::Map::with_capacity(fields.len());
+                for field in fields {
+                    object.insert(field.name.clone(), field.schema.read(reader, depth + 1)?);
+                }
+                Value::Object(object)
+            }
+            AvroSchema::Enum { name, symbols } => {
+                let index = long(reader)?;
+                let symbol = usize::try_from(index)
+                    .ok()
+                    .and_then(|i| symbols.get(i))
+                    .ok_or_else(|| Error::Storage(format!("Invalid {} index {}", name, index)))?;
+                Value::String(symbol.clone())
+            }
+            AvroSchema::Array(items) => {
+                let mut values = Vec::new();
+                while let Some(count) = Self::block(reader)? {
+                    for _ in 0..count {
+                        values.push(items.read(reader, depth + 1)?);
+                    }
+                }
+                Value::Array(values)
+            }
+            AvroSchema::Map(values) => {
+                let mut object = serde_json::Map::new();
+                while let Some(count) = Self::block(reader)? {
+                    for _ in 0..count {
+                        let Value::String(key) = AvroSchema::String.read(reader, depth + 1)? else {
+                            unreachable!("string schema reads strings");
+                        };
+                        object.insert(key, values.read(reader, depth + 1)?);
+                    }
+                }
+                Value::Object(object)
+            }
+            AvroSchema::Union(branches) => {
+                let index = long(reader)?;
+                let branch = usize::try_from(index)
+                    .ok()
+                    .and_then(|i| branches.get(i))
+                    .ok_or_else(|| Error::Storage(format!("Invalid union index {}", index)))?;
+                branch.read(reader, depth + 1)?
+            }
+            AvroSchema::Fixed { size, .. } => encode_base64(reader.bytes(*size)?),
+        })
+    }
+
+    /// Item count of the next array/map block, None at the end marker
+    fn block(reader: &mut Reader<'_>) -> Result<Option<usize>> {
+        let count = unzigzag(reader.varint()?);
+        if count == 0 {
+            return Ok(None);
+        }
+        if count < 0 {
+            // Negative counts are followed by the block's byte size
+            reader.varint()?;
+        }
+        // Bound the count so a corrupt header can't spin through billions of empty items
+        let count = count.unsigned_abs();
+        if count > MAX_BLOCK_ITEMS {
+            return Err(Error::Storage(format!("Invalid block count {}", count)));
+        }
+        Ok(Some(count as usize))
+    }
+}
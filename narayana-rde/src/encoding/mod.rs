@@ -0,0 +1,246 @@
+// Payload encoding for RDE events
+// Events travel as JSON internally; subscriptions may ask for Avro or Protobuf
+// bodies encoded against the event's schema in the schema registry. Anything
+// without a usable schema (and every legacy subscriber) gets JSON.
+
+pub mod avro;
+pub mod protobuf;
+
+use crate::schema_registry::{RegisteredSchema, SchemaFormat, SchemaRegistry};
+use narayana_core::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Maximum nesting depth for schemas and encoded values
+pub(crate) const MAX_DEPTH: usize = 32;
+
+/// Wire encoding of an event payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PayloadEncoding {
+    Json,
+    Avro,
+    Protobuf,
+}
+
+impl PayloadEncoding {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            PayloadEncoding::Json => "application/json",
+            PayloadEncoding::Avro => "application/avro",
+            PayloadEncoding::Protobuf => "application/x-protobuf",
+        }
+    }
+
+    /// Parse a content type (parameters and case are ignored)
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        let mime = content_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+        match mime.as_str() {
+            "application/json" | "json" => Some(PayloadEncoding::Json),
+            "application/avro" | "avro/binary" | "application/vnd.apache.avro+binary" | "avro" => {
+                Some(PayloadEncoding::Avro)
+            }
+            "application/x-protobuf" | "application/protobuf" | "application/vnd.google.protobuf" | "protobuf" => {
+                Some(PayloadEncoding::Protobuf)
+            }
+            _ => None,
+        }
+    }
+
+    /// Schema format needed to produce this encoding
+    pub fn schema_format(&self) -> Option<SchemaFormat> {
+        match self {
+            PayloadEncoding::Json => None,
+            PayloadEncoding::Avro => Some(SchemaFormat::Avro),
+            PayloadEncoding::Protobuf => Some(SchemaFormat::Protobuf),
+        }
+    }
+}
+
+impl std::fmt::Display for PayloadEncoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.content_type())
+    }
+}
+
+/// Compiled schema used to encode and decode payloads
+#[derive(Debug, Clone)]
+pub enum Codec {
+    Avro(avro::AvroSchema),
+    Protobuf(protobuf::MessageDescriptor),
+}
+
+impl Codec {
+    /// Validate and compile a schema definition
+    pub fn compile(format: SchemaFormat, definition: &serde_json::Value) -> Result<Self> {
+        match format {
+            SchemaFormat::Avro => avro::AvroSchema::parse(definition).map(Codec::Avro),
+            SchemaFormat::Protobuf => protobuf::MessageDescriptor::parse(definition).map(Codec::Protobuf),
+        }
+    }
+
+    pub fn encode(&self, value: &serde_json::Value) -> Result<Vec<u8>> {
+        match self {
+            Codec::Avro(schema) => schema.encode(value),
+            Codec::Protobuf(message) => message.encode(value),
+        }
+    }
+
+    pub fn decode(&self, data: &[u8]) -> Result<serde_json::Value> {
+        match self {
+            Codec::Avro(schema) => schema.decode(data),
+            Codec::Protobuf(message) => message.decode(data),
+        }
+    }
+}
+
+/// A payload encoded for one subscription
+#[derive(Debug, Clone)]
+pub struct EncodedPayload {
+    pub encoding: PayloadEncoding,
+    /// Schema the payload was encoded with (None for JSON)
+    pub schema: Option<Arc<RegisteredSchema>>,
+    pub data: Vec<u8>,
+}
+
+impl EncodedPayload {
+    pub fn json(payload: &serde_json::Value) -> Result<Self> {
+        Ok(Self {
+            encoding: PayloadEncoding::Json,
+            schema: None,
+            data: serde_json::to_vec(payload)
+                .map_err(|e| Error::Storage(format!("Failed to serialize payload: {}", e)))?,
+        })
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        self.encoding.content_type()
+    }
+}
+
+/// Encodings a subscription accepts, most preferred first
+///
+/// Config: `content_type` as a single content type or a list in preference
+/// order. Subscriptions without it accept JSON only.
+pub fn accepted_encodings(config: &serde_json::Value) -> Result<Vec<PayloadEncoding>> {
+    let parse = |value: &serde_json::Value| {
+        value.as_str()
+            .and_then(PayloadEncoding::from_content_type)
+            .ok_or_else(|| Error::Storage(format!("Unsupported content_type: {}", value)))
+    };
+    match config.get("content_type") {
+        None | Some(serde_json::Value::Null) => Ok(vec![PayloadEncoding::Json]),
+        Some(serde_json::Value::Array(types)) if !types.is_empty() => types.iter().map(parse).collect(),
+        Some(value) => Ok(vec![parse(value)?]),
+    }
+}
+
+/// Encode a payload in the first accepted encoding that has a schema
+/// registered for `event_name` and fits the payload
+///
+/// Falls back to JSON when nothing else works and JSON is accepted (or when
+/// the subscription named no encoding at all).
+pub fn encode_for_subscription(
+    config: &serde_json::Value,
+    event_name: &str,
+    payload: &serde_json::Value,
+    registry: &SchemaRegistry,
+) -> Result<EncodedPayload> {
+    let accepted = accepted_encodings(config)?;
+    for encoding in &accepted {
+        let Some(format) = encoding.schema_format() else {
+            return EncodedPayload::json(payload);
+        };
+        let Some(schema) = registry.latest(event_name, format) else {
+            continue;
+        };
+        match schema.codec().encode(payload) {
+            Ok(data) => return Ok(EncodedPayload { encoding: *encoding, schema: Some(schema), data }),
+            Err(e) => tracing::warn!("Payload does not match {} schema v{}: {}", format, schema.version, e),
+        }
+    }
+    Err(Error::Storage(format!(
+        "No accepted encoding ({}) available for event",
+        accepted.iter().map(|e| e.content_type()).collect::<Vec<_>>().join(", ")
+    )))
+}
+
+pub(crate) fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+pub(crate) fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+pub(crate) fn unzigzag(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+/// Cursor over an encoded payload
+pub(crate) struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+
+    pub(crate) fn remaining(&self) -> usize {
+        self.data.len() - self.pos
+    }
+
+    pub(crate) fn varint(&mut self) -> Result<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.bytes(1)?[0];
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(Error::Storage("Malformed varint".to_string()))
+    }
+
+    pub(crate) fn bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        if len > self.remaining() {
+            return Err(Error::Storage("Unexpected end of payload".to_string()));
+        }
+        let slice = &self.data[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(slice)
+    }
+
+    pub(crate) fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let mut out = [0u8; N];
+        out.copy_from_slice(self.bytes(N)?);
+        Ok(out)
+    }
+}
+
+/// JSON number for a decoded float (NaN and infinities become null)
+pub(crate) fn float_value(value: f64) -> serde_json::Value {
+    serde_json::Number::from_f64(value).map(serde_json::Value::Number).unwrap_or(serde_json::Value::Null)
+}
+
+pub(crate) fn decode_base64(value: &str) -> Result<Vec<u8>> {
+    use base64::Engine;
+    base64::engine::general_purpose::STANDARD
+        .decode(value)
+        .map_err(|e| Error::Storage(format!("Invalid base64 bytes: {}", e)))
+}
+
+pub(crate) fn encode_base64(data: &[u8]) -> serde_json::Value {
+    use base64::Engine;
+    serde_json::Value::String(base64::engine::general_purpose::STANDARD.encode(data))
+}
@@ -0,0 +1,313 @@
+// Protocol Buffers wire encoding
+// Messages are described in JSON (name, fields with number/type/repeated),
+// so schemas can be registered without running protoc. Repeated scalars are
+// written packed and read in either form; unknown fields are skipped.
+
+use super::{decode_base64, encode_base64, float_value, unzigzag, write_varint, zigzag, Reader, MAX_DEPTH};
+use narayana_core::{Error, Result};
+use serde_json::Value;
+
+const MAX_FIELD_NUMBER: u32 = (1 << 29) - 1;
+const RESERVED_FIELD_NUMBERS: std::ops::RangeInclusive<u32> = 19000..=19999;
+
+const WIRE_VARINT: u8 = 0;
+const WIRE_FIXED64: u8 = 1;
+const WIRE_LEN: u8 = 2;
+const WIRE_FIXED32: u8 = 5;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldType {
+    Double,
+    Float,
+    Int32,
+    Int64,
+    Uint32,
+    Uint64,
+    Sint32,
+    Sint64,
+    Bool,
+    String,
+    Bytes,
+    Message(Box<MessageDescriptor>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldDescriptor {
+    pub name: String,
+    pub number: u32,
+    pub field_type: FieldType,
+    pub repeated: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessageDescriptor {
+    pub name: String,
+    pub fields: Vec<FieldDescriptor>,
+}
+
+fn invalid(message: impl std::fmt::Display) -> Error {
+    Error::Storage(format!("Invalid Protobuf schema: {}", message))
+}
+
+fn mismatch(field: &FieldDescriptor, value: &Value) -> Error {
+    Error::Storage(format!("Field '{}' cannot hold {}", field.name, value))
+}
+
+impl FieldType {
+    fn wire_type(&self) -> u8 {
+        match self {
+            FieldType::Double => WIRE_FIXED64,
+            FieldType::Float => WIRE_FIXED32,
+            FieldType::String | FieldType::Bytes | FieldType::Message(_) => WIRE_LEN,
+            _ => WIRE_VARINT,
+        }
+    }
+
+    /// Scalars that are packed when repeated
+    fn packable(&self) -> bool {
+        self.wire_type() != WIRE_LEN
+    }
+}
+
+impl MessageDescriptor {
+    /// Parse a JSON message description:
+    /// `{"name": "Order", "fields": [{"name": "id", "number": 1, "type": "int64"}, ...]}`;
+    /// nested messages use `"type": "message"` with their own `fields`
+    pub fn parse(definition: &Value) -> Result<Self> {
+        Self::parse_inner(definition, 0)
+    }
+
+    fn parse_inner(definition: &Value, depth: usize) -> Result<Self> {
+        if depth > MAX_DEPTH {
+            return Err(invalid("nested too deeply"));
+        }
+        let name = definition.get("name").and_then(|n| n.as_str()).unwrap_or("message").to_string();
+        let fields = definition.get("fields")
+            .and_then(|f| f.as_array())
+            .ok_or_else(|| invalid(format!("message '{}' requires fields", name)))?;
+        let mut parsed: Vec<FieldDescriptor> = Vec::with_capacity(fields.len());
+        for field in fields {
+            let field_name = field.get("name")
+                .and_then(|n| n.as_str())
+                .filter(|n| !n.is_empty())
+                .ok_or_else(|| invalid("field requires a name"))?;
+            let number = field.get("number")
+                .and_then(|n| n.as_u64())
+                .and_then(|n| u32::try_from(n).ok())
+                .filter(|n| (1..=MAX_FIELD_NUMBER).contains(n) && !RESERVED_FIELD_NUMBERS.contains(n))
+                .ok_or_else(|| invalid(format!("field '{}' needs a valid number", field_name)))?;
+            if parsed.iter().any(|f| f.name == field_name || f.number == number) {
+                return Err(invalid(format!("duplicate field '{}' or number {}", field_name, number)));
+            }
+            let field_type = match field.get("type").and_then(|t| t.as_str()) {
+                Some("double") => FieldType::Double,
+                Some("float") => FieldType::Float,
+                Some("int32") => FieldType::Int32,
+                Some("int64") => FieldType::Int64,
+                Some("uint32") => FieldType::Uint32,
+                Some("uint64") => FieldType::Uint64,
+                Some("sint32") => FieldType::Sint32,
+                Some("sint64") => FieldType::Sint64,
+                Some("bool") => FieldType::Bool,
+                Some("string") => FieldType::String,
+                Some("bytes") => FieldType::Bytes,
+                Some("message") => FieldType::Message(Box::new(Self::parse_inner(field, depth + 1)?)),
+                other => return Err(invalid(format!("field '{}' has unsupported type {:?}", field_name, other))),
+            };
+            parsed.push(FieldDescriptor {
+                name: field_name.to_string(),
+                number,
+                field_type,
+                repeated: field.get("repeated").and_then(|r| r.as_bool()).unwrap_or(false),
+            });
+        }
+        Ok(Self { name, fields: parsed })
+    }
+
+    pub fn encode(&self, value: &Value) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+        self.write(value, &mut out, 0)?;
+        Ok(out)
+    }
+
+    pub fn decode(&self, data: &[u8]) -> Result<Value> {
+        self.read(data, 0)
+    }
+
+    fn write(&self, value: &Value, out: &mut Vec<u8>, depth: usize) -> Result<()> {
+        if depth > MAX_DEPTH {
+            return Err(Error::Storage("Payload nested too deeply".to_string()));
+        }
+        let object = value.as_object()
+            .ok_or_else(|| Error::Storage(format!("Expected object for {}, found {}", self.name, value)))?;
+        for field in &self.fields {
+            let Some(field_value) = object.get(&field.name).filter(|v| !v.is_null()) else {
+                continue; // Absent fields are simply not written
+            };
+            if !field.repeated {
+                write_key(out, field.number, field.field_type.wire_type());
+                write_value(field, field_value, out, depth)?;
+                continue;
+            }
+            let items = field_value.as_array().ok_or_else(|| mismatch(field, field_value))?;
+            if field.field_type.packable() {
+                if items.is_empty() {
+                    continue;
+                }
+                let mut packed = Vec::new();
+                for item in items {
+                    write_value(field, item, &mut packed, depth)?;
+                }
+                write_key(out, field.number, WIRE_LEN);
+                write_varint(out, packed.len() as u64);
+                out.extend_from_slice(&packed);
+            } else {
+                for item in items {
+                    write_key(out, field.number, WIRE_LEN);
+                    write_value(field, item, out, depth)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn read(&self, data: &[u8], depth: usize) -> Result<Value> {
+        if depth > MAX_DEPTH {
+            return Err(Error::Storage("Payload nested too deeply".to_string()));
+        }
+        let mut reader = Reader::new(data);
+        let mut object = serde_json::Map::new();
+        while !reader.is_empty() {
+            let key = reader.varint()?;
+            let wire_type = (key & 0x7) as u8;
+            let number = u32::try_from(key >> 3).map_err(|_| Error::Storage("Invalid field number".to_string()))?;
+            let Some(field) = self.fields.iter().find(|f| f.number == number) else {
+                skip(&mut reader, wire_type)?;
+                continue;
+            };
+            if field.repeated && field.field_type.packable() && wire_type == WIRE_LEN {
+                let len = length(&mut reader)?;
+                let mut packed = Reader::new(reader.bytes(len)?);
+                let entry = object.entry(field.name.clone()).or_insert_with(|| Value::Array(Vec::new()));
+                while !packed.is_empty() {
+                    let item = read_value(field, &mut packed, depth)?;
+                    if let Value::Array(items) = entry {
+                        items.push(item);
+                    }
+                }
+                continue;
+            }
+            if wire_type != field.field_type.wire_type() {
+                return Err(Error::Storage(format!("Wire type {} does not match field '{}'", wire_type, field.name)));
+            }
+            let item = read_value(field, &mut reader, depth)?;
+            if field.repeated {
+                if let Value::Array(items) = object.entry(field.name.clone()).or_insert_with(|| Value::Array(Vec::new())) {
+                    items.push(item);
+                }
+            } else {
+                object.insert(field.name.clone(), item); // Last occurrence wins
+            }
+        }
+        Ok(Value::Object(object))
+    }
+}
+
+fn write_key(out: &mut Vec<u8>, number: u32, wire_type: u8) {
+    write_varint(out, ((number as u64) << 3) | wire_type as u64);
+}
+
+fn write_value(field: &FieldDescriptor, value: &Value, out: &mut Vec<u8>, depth: usize) -> Result<()> {
+    let int = |min: i64, max: i64| {
+        value.as_i64().filter(|n| (min..=max).contains(n)).ok_or_else(|| mismatch(field, value))
+    };
+    let uint = |max: u64| value.as_u64().filter(|n| *n <= max).ok_or_else(|| mismatch(field, value));
+    match &field.field_type {
+        FieldType::Double => out.extend_from_slice(&value.as_f64().ok_or_else(|| mismatch(field, value))?.to_le_bytes()),
+        FieldType::Float => {
+            out.extend_from_slice(&(value.as_f64().ok_or_else(|| mismatch(field, value))? as f32).to_le_bytes())
+        }
+        // Negative int32/int64 values are sign-extended to ten bytes
+        FieldType::Int32 => write_varint(out, int(i32::MIN as i64, i32::MAX as i64)? as u64),
+        FieldType::Int64 => write_varint(out, int(i64::MIN, i64::MAX)? as u64),
+        FieldType::Uint32 => write_varint(out, uint(u32::MAX as u64)?),
+        FieldType::Uint64 => write_varint(out, uint(u64::MAX)?),
+        FieldType::Sint32 => write_varint(out, zigzag(int(i32::MIN as i64, i32::MAX as i64)?)),
+        FieldType::Sint64 => write_varint(out, zigzag(int(i64::MIN, i64::MAX)?)),
+        FieldType::Bool => write_varint(out, value.as_bool().ok_or_else(|| mismatch(field, value))? as u64),
+        FieldType::String => {
+            let s = value.as_str().ok_or_else(|| mismatch(field, value))?;
+            write_varint(out, s.len() as u64);
+            out.extend_from_slice(s.as_bytes());
+        }
+        FieldType::Bytes => {
+            let bytes = decode_base64(value.as_str().ok_or_else(|| mismatch(field, value))?)?;
+            write_varint(out, bytes.len() as u64);
+            out.extend_from_slice(&bytes);
+        }
+        FieldType::Message(message) => {
+            let mut nested = Vec::new();
+            message.write(value, &mut nested, depth + 1)?;
+            write_varint(out, nested.len() as u64);
+            out.extend_from_slice(&nested);
+        }
+    }
+    Ok(())
+}
+
+fn read_value(field: &FieldDescriptor, reader: &mut Reader<'_>, depth: usize) -> Result<Value> {
+    Ok(match &field.field_type {
+        FieldType::Double => float_value(f64::from_le_bytes(reader.array()?)),
+        FieldType::Float => float_value(f32::from_le_bytes(reader.array()?) as f64),
+        FieldType::Int32 => (reader.varint()? as i64 as i32).into(),
+        FieldType::Int64 => (reader.varint()? as i64).into(),
+        FieldType::Uint32 => (reader.varint()? as u32).into(),
+        FieldType::Uint64 => reader.varint()?.into(),
+        FieldType::Sint32 => (unzigzag(reader.varint()?) as i32).into(),
+        FieldType::Sint64 => unzigzag(reader.varint()?).into(),
+        FieldType::Bool => Value::Bool(reader.varint()? != 0),
+        FieldType::String => {
+            let len = length(reader)?;
+            Value::String(
+                String::from_utf8(reader.bytes(len)?.to_vec())
+                    .map_err(|_| Error::Storage(format!("Invalid UTF-8 in field '{}'", field.name)))?,
+            )
+        }
+        FieldType::Bytes => {
+            let len = length(reader)?;
+            encode_base64(reader.bytes(len)?)
+        }
+        FieldType::Message(message) => {
+            let len = length(reader)?;
+            message.read(reader.bytes(len)?, depth + 1)?
+        }
+    })
+}
+
+fn length(reader: &mut Reader<'_>) -> Result<usize> {
+    let len = reader.varint()?;
+    usize::try_from(len)
+        .ok()
+        .filter(|len| *len <= reader.remaining())
+        .ok_or_else(|| Error::Storage(format!("Invalid length {}", len)))
+}
+
+fn skip(reader: &mut Reader<'_>, wire_type: u8) -> Result<()> {
+    match wire_type {
+        WIRE_VARINT => {
+            reader.varint()?;
+        }
+        WIRE_FIXED64 => {
+            reader.bytes(8)?;
+        }
+        WIRE_LEN => {
+            let len = length(reader)?;
+            reader.bytes(len)?;
+        }
+        WIRE_FIXED32 => {
+            reader.bytes(4)?;
+        }
+        other => return Err(Error::Storage(format!("Unsupported wire type {}", other))),
+    }
+    Ok(())
+}
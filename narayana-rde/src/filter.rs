@@ -0,0 +1,433 @@
+// Subscription filters - deliver only events whose payload matches
+// A subscription's `filter` is a predicate over the event payload, checked
+// before transformation and transport delivery, so subscribers don't have to
+// receive the whole stream and filter client-side.
+//
+// Syntax: paths (`$.reading.temp`, `tags[0]`; the `$.` is optional), string,
+// number, boolean and null literals, comparisons (== != > >= < <=), `in`
+// against a literal list, `!`, `&&`, `||` and parentheses. A bare path is true
+// when the field exists and isn't null or false. Missing fields compare as
+// null, and ordering only holds between two numbers or two strings.
+//
+//   $.level == 'error' && ($.temp >= 80.5 || $.zone in ['a', 'b'])
+
+use narayana_core::{Error, Result};
+use serde_json::Value;
+
+/// Longest filter expression accepted
+const MAX_FILTER_LENGTH: usize = 4096;
+/// Deepest nesting of `!` and parentheses
+const MAX_DEPTH: usize = 32;
+
+/// A parsed subscription filter
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubscriptionFilter {
+    expr: Expr,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Or(Vec<Expr>),
+    And(Vec<Expr>),
+    Not(Box<Expr>),
+    Compare(Operand, CompareOp, Operand),
+    In(Operand, Vec<Value>),
+    Truthy(Vec<Segment>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Operand {
+    Path(Vec<Segment>),
+    Literal(Value),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Field(String),
+    Index(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+impl SubscriptionFilter {
+    /// Parse a filter expression
+    pub fn parse(expression: &str) -> Result<Self> {
+        if expression.len() > MAX_FILTER_LENGTH {
+            return Err(Error::Storage(format!("filter must be at most {} bytes", MAX_FILTER_LENGTH)));
+        }
+        let mut parser = Parser { tokens: tokenize(expression)?, pos: 0, depth: 0 };
+        if parser.tokens.is_empty() {
+            return Err(Error::Storage("filter cannot be empty".to_string()));
+        }
+        let expr = parser.or()?;
+        if let Some(token) = parser.tokens.get(parser.pos) {
+            return Err(Error::Storage(format!("Invalid filter: unexpected {}", token)));
+        }
+        Ok(Self { expr })
+    }
+
+    /// Parse the subscription's `filter` option; None when there is none
+    pub fn from_config(config: &Value) -> Result<Option<Self>> {
+        match config.get("filter") {
+            None | Some(Value::Null) => Ok(None),
+            Some(Value::String(expression)) => Self::parse(expression).map(Some),
+            Some(_) => Err(Error::Storage("filter must be a string".to_string())),
+        }
+    }
+
+    /// Whether an event payload passes the filter
+    pub fn matches(&self, payload: &Value) -> bool {
+        self.expr.eval(payload)
+    }
+}
+
+impl Expr {
+    fn eval(&self, payload: &Value) -> bool {
+        match self {
+            Expr::Or(exprs) => exprs.iter().any(|e| e.eval(payload)),
+            Expr::And(exprs) => exprs.iter().all(|e| e.eval(payload)),
+            Expr::Not(expr) => !expr.eval(payload),
+            Expr::Compare(left, op, right) => compare(left.resolve(payload), *op, right.resolve(payload)),
+            Expr::In(operand, values) => {
+                let value = operand.resolve(payload);
+                values.iter().any(|v| equal(value, v))
+            }
+            Expr::Truthy(path) => !matches!(lookup(payload, path), None | Some(Value::Null) | Some(Value::Bool(false))),
+        }
+    }
+}
+
+impl Operand {
+    fn resolve<'a>(&'a self, payload: &'a Value) -> &'a Value {
+        match self {
+            Operand::Path(path) => lookup(payload, path).unwrap_or(&Value::Null),
+            Operand::Literal(value) => value,
+        }
+    }
+}
+
+fn lookup<'a>(payload: &'a Value, path: &[Segment]) -> Option<&'a Value> {
+    path.iter().try_fold(payload, |value, segment| match segment {
+        Segment::Field(name) => value.get(name.as_str()),
+        Segment::Index(index) => value.get(*index),
+    })
+}
+
+/// JSON equality, except that numbers compare by value (1 == 1.0)
+fn equal(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Number(l), Value::Number(r)) => l.as_f64() == r.as_f64(),
+        _ => left == right,
+    }
+}
+
+fn compare(left: &Value, op: CompareOp, right: &Value) -> bool {
+    use std::cmp::Ordering;
+
+    let ordering = match (left, right) {
+        (Value::Number(l), Value::Number(r)) => l.as_f64().zip(r.as_f64()).and_then(|(l, r)| l.partial_cmp(&r)),
+        (Value::String(l), Value::String(r)) => Some(l.cmp(r)),
+        _ => None,
+    };
+    match op {
+        CompareOp::Eq => equal(left, right),
+        CompareOp::Ne => !equal(left, right),
+        CompareOp::Gt => ordering == Some(Ordering::Greater),
+        CompareOp::Ge => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+        CompareOp::Lt => ordering == Some(Ordering::Less),
+        CompareOp::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Path(Vec<Segment>),
+    Literal(Value),
+    Op(CompareOp),
+    In,
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Comma,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Path(_) => write!(f, "path"),
+            Token::Literal(value) => write!(f, "{}", value),
+            Token::Op(_) => write!(f, "comparison"),
+            Token::In => write!(f, "'in'"),
+            Token::And => write!(f, "'&&'"),
+            Token::Or => write!(f, "'||'"),
+            Token::Not => write!(f, "'!'"),
+            Token::LParen => write!(f, "'('"),
+            Token::RParen => write!(f, "')'"),
+            Token::LBracket => write!(f, "'['"),
+            Token::RBracket => write!(f, "']'"),
+            Token::Comma => write!(f, "','"),
+        }
+    }
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>> {
+    let invalid = |message: String| Error::Storage(format!("Invalid filter: {}", message));
+    let chars: Vec<char> = expression.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        let token = match c {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            ',' => Token::Comma,
+            ']' => Token::RBracket,
+            '[' => Token::LBracket,
+            '&' if next == Some('&') => Token::And,
+            '|' if next == Some('|') => Token::Or,
+            '=' if next == Some('=') => Token::Op(CompareOp::Eq),
+            '!' if next == Some('=') => Token::Op(CompareOp::Ne),
+            '>' if next == Some('=') => Token::Op(CompareOp::Ge),
+            '<' if next == Some('=') => Token::Op(CompareOp::Le),
+            '!' => Token::Not,
+            '>' => Token::Op(CompareOp::Gt),
+            '<' => Token::Op(CompareOp::Lt),
+            '\'' | '"' => {
+                let mut value = String::new();
+                let mut j = i + 1;
+                loop {
+                    match chars.get(j) {
+                        None => return Err(invalid("unterminated string".to_string())),
+                        Some('\\') => {
+                            value.extend(chars.get(j + 1));
+                            j += 2;
+                        }
+                        Some(&q) if q == c => break,
+                        Some(&other) => {
+                            value.push(other);
+                            j += 1;
+                        }
+                    }
+                }
+                tokens.push(Token::Literal(Value::String(value)));
+                i = j + 1;
+                continue;
+            }
+            c if c.is_ascii_digit() || (c == '-' && next.is_some_and(|n| n.is_ascii_digit())) => {
+                let start = i;
+                i += 1;
+                while i < chars.len() && (chars[i].is_ascii_alphanumeric() || matches!(chars[i], '.' | '-' | '+')) {
+                    i += 1;
+                }
+                let text: String = chars[start..i].iter().collect();
+                let number = text.parse::<serde_json::Number>()
+                    .map_err(|_| invalid(format!("invalid number '{}'", text)))?;
+                tokens.push(Token::Literal(Value::Number(number)));
+                continue;
+            }
+            c if c == '$' || c == '_' || c.is_alphabetic() => {
+                let (token, end) = tokenize_word(&chars, i).map_err(invalid)?;
+                tokens.push(token);
+                i = end;
+                continue;
+            }
+            other => return Err(invalid(format!("unexpected character '{}'", other))),
+        };
+        // Two-character operators
+        i += if matches!(token, Token::And | Token::Or) || (matches!(token, Token::Op(_)) && next == Some('=')) { 2 } else { 1 };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+/// A keyword, literal or path starting at `start`; returns the index after it
+fn tokenize_word(chars: &[char], start: usize) -> std::result::Result<(Token, usize), String> {
+    let is_ident = |c: char| c == '_' || c == '-' || c.is_alphanumeric();
+    let ident = |from: usize| {
+        let mut end = from;
+        while end < chars.len() && is_ident(chars[end]) {
+            end += 1;
+        }
+        end
+    };
+
+    let mut i = start;
+    let mut segments = Vec::new();
+    let rooted = chars[i] == '$';
+    if rooted {
+        i += 1;
+    } else {
+        let end = ident(i);
+        let word: String = chars[i..end].iter().collect();
+        let keyword = match word.as_str() {
+            "true" => Some(Token::Literal(Value::Bool(true))),
+            "false" => Some(Token::Literal(Value::Bool(false))),
+            "null" => Some(Token::Literal(Value::Null)),
+            "in" => Some(Token::In),
+            _ => None,
+        };
+        if let Some(keyword) = keyword {
+            return Ok((keyword, end));
+        }
+        segments.push(Segment::Field(word));
+        i = end;
+    }
+
+    loop {
+        match chars.get(i) {
+            Some('.') => {
+                let end = ident(i + 1);
+                if end == i + 1 {
+                    return Err("expected a field name after '.'".to_string());
+                }
+                segments.push(Segment::Field(chars[i + 1..end].iter().collect()));
+                i = end;
+            }
+            // An index directly after a path; a spaced '[' starts an `in` list
+            Some('[') if chars.get(i + 1).is_some_and(|c| c.is_ascii_digit()) => {
+                let mut end = i + 1;
+                while end < chars.len() && chars[end].is_ascii_digit() {
+                    end += 1;
+                }
+                if chars.get(end) != Some(&']') {
+                    return Err("expected ']' after an index".to_string());
+                }
+                let index: String = chars[i + 1..end].iter().collect();
+                let index = index.parse().map_err(|_| format!("invalid index '{}'", index))?;
+                segments.push(Segment::Index(index));
+                i = end + 1;
+            }
+            _ => break,
+        }
+    }
+    if segments.is_empty() {
+        return Err("'$' must be followed by a path".to_string());
+    }
+    Ok((Token::Path(segments), i))
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Result<Token> {
+        let token = self.tokens.get(self.pos).cloned()
+            .ok_or_else(|| Error::Storage("Invalid filter: unexpected end of expression".to_string()))?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<()> {
+        let token = self.next()?;
+        if token != expected {
+            return Err(Error::Storage(format!("Invalid filter: expected {}, found {}", expected, token)));
+        }
+        Ok(())
+    }
+
+    fn or(&mut self) -> Result<Expr> {
+        let mut exprs = vec![self.and()?];
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            exprs.push(self.and()?);
+        }
+        Ok(if exprs.len() == 1 { exprs.remove(0) } else { Expr::Or(exprs) })
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        let mut exprs = vec![self.unary()?];
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            exprs.push(self.unary()?);
+        }
+        Ok(if exprs.len() == 1 { exprs.remove(0) } else { Expr::And(exprs) })
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(Error::Storage(format!("filter nests deeper than {} levels", MAX_DEPTH)));
+        }
+        let expr = match self.peek() {
+            Some(Token::Not) => {
+                self.pos += 1;
+                Expr::Not(Box::new(self.unary()?))
+            }
+            Some(Token::LParen) => {
+                self.pos += 1;
+                let expr = self.or()?;
+                self.expect(Token::RParen)?;
+                expr
+            }
+            _ => self.comparison()?,
+        };
+        self.depth -= 1;
+        Ok(expr)
+    }
+
+    fn comparison(&mut self) -> Result<Expr> {
+        let left = match self.next()? {
+            Token::Path(path) => Operand::Path(path),
+            Token::Literal(value) => Operand::Literal(value),
+            token => return Err(Error::Storage(format!("Invalid filter: expected a path or value, found {}", token))),
+        };
+        match (self.peek().cloned(), left) {
+            (Some(Token::Op(op)), left) => {
+                self.pos += 1;
+                let right = match self.next()? {
+                    Token::Path(path) => Operand::Path(path),
+                    Token::Literal(value) => Operand::Literal(value),
+                    token => return Err(Error::Storage(format!("Invalid filter: expected a path or value, found {}", token))),
+                };
+                Ok(Expr::Compare(left, op, right))
+            }
+            (Some(Token::In), left) => {
+                self.pos += 1;
+                self.expect(Token::LBracket)?;
+                let mut values = Vec::new();
+                if self.peek() == Some(&Token::RBracket) {
+                    self.pos += 1;
+                    return Ok(Expr::In(left, values));
+                }
+                loop {
+                    match self.next()? {
+                        Token::Literal(value) => values.push(value),
+                        token => return Err(Error::Storage(format!("Invalid filter: 'in' lists take values, found {}", token))),
+                    }
+                    match self.next()? {
+                        Token::Comma => continue,
+                        Token::RBracket => break,
+                        token => return Err(Error::Storage(format!("Invalid filter: expected ',' or ']', found {}", token))),
+                    }
+                }
+                Ok(Expr::In(left, values))
+            }
+            (_, Operand::Path(path)) => Ok(Expr::Truthy(path)),
+            (_, Operand::Literal(_)) => Err(Error::Storage("Invalid filter: a value must be compared with something".to_string())),
+        }
+    }
+}
@@ -0,0 +1,154 @@
+// Consumer groups - one delivery per event per group
+// Subscriptions created with the same `group` on the same event pattern share
+// its events: each event goes to one member, so replicas of a consumer don't
+// all process it. Members can belong to different origin actors. The member
+// is picked round-robin, or with `group_strategy: "least_loaded"` as the one
+// with the fewest deliveries in flight, queued in order or waiting for a
+// retry. A member's failed deliveries stay with it. Members whose SSE/gRPC
+// stream isn't attached are passed over while others are.
+
+use crate::subscriptions::{Subscription, SubscriptionId};
+use narayana_core::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Longest group name
+const MAX_GROUP_NAME_LENGTH: usize = 128;
+
+/// How a group picks the member for each event
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum GroupStrategy {
+    /// Members take turns
+    #[default]
+    RoundRobin,
+    /// The member with the least work outstanding
+    LeastLoaded,
+}
+
+/// A subscription's group membership
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct GroupConfig {
+    pub(crate) name: String,
+    pub(crate) strategy: GroupStrategy,
+}
+
+/// Parse the subscription's `group` and `group_strategy` options
+pub(crate) fn from_config(config: &serde_json::Value) -> Result<Option<GroupConfig>> {
+    let name = match config.get("group") {
+        None | Some(serde_json::Value::Null) => {
+            if config.get("group_strategy").is_some_and(|s| !s.is_null()) {
+                return Err(Error::Storage("group_strategy needs a group".to_string()));
+            }
+            return Ok(None);
+        }
+        Some(serde_json::Value::String(name)) => name,
+        Some(_) => return Err(Error::Storage("group must be a string".to_string())),
+    };
+    let valid = !name.is_empty()
+        && name.len() <= MAX_GROUP_NAME_LENGTH
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        return Err(Error::Storage(format!(
+            "group must be 1-{} letters, digits, '-', '_' or '.'",
+            MAX_GROUP_NAME_LENGTH
+        )));
+    }
+    let strategy = match config.get("group_strategy") {
+        None | Some(serde_json::Value::Null) => GroupStrategy::default(),
+        Some(strategy) => serde_json::from_value(strategy.clone())
+            .map_err(|_| Error::Storage("group_strategy must be 'round_robin' or 'least_loaded'".to_string()))?,
+    };
+    Ok(Some(GroupConfig { name: name.clone(), strategy }))
+}
+
+/// Turn and in-flight counts of every group
+#[derive(Default)]
+pub(crate) struct ConsumerGroups {
+    /// Next turn per (event pattern, group)
+    turns: dashmap::DashMap<(String, String), usize>,
+    /// Deliveries running per member
+    in_flight: dashmap::DashMap<SubscriptionId, usize>,
+}
+
+/// Counts a delivery as in flight until dropped
+pub(crate) struct InFlight<'a> {
+    groups: &'a ConsumerGroups,
+    subscription_id: SubscriptionId,
+}
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.groups.in_flight.remove_if_mut(&self.subscription_id, |_, count| {
+            *count -= 1;
+            *count == 0
+        });
+    }
+}
+
+impl ConsumerGroups {
+    /// Keep one member of each group among the subscriptions an event
+    /// matched; subscriptions outside groups are kept as they are.
+    /// `load` is a member's outstanding work, None when it can't take events.
+    pub(crate) fn pick(
+        &self,
+        subscriptions: Vec<Subscription>,
+        load: impl Fn(&Subscription) -> Option<usize>,
+    ) -> Vec<Subscription> {
+        let mut picked = Vec::with_capacity(subscriptions.len());
+        let mut groups: BTreeMap<(String, String), (GroupStrategy, Vec<Subscription>)> = BTreeMap::new();
+        for subscription in subscriptions {
+            // Validated on subscribe
+            match from_config(&subscription.config).ok().flatten() {
+                Some(group) => groups
+                    .entry((subscription.event_name.0.clone(), group.name))
+                    .or_insert_with(|| (group.strategy, Vec::new()))
+                    .1
+                    .push(subscription),
+                None => picked.push(subscription),
+            }
+        }
+
+        for (key, (strategy, mut members)) in groups {
+            members.sort_by(|a, b| a.id.0.cmp(&b.id.0));
+            let loads: Vec<Option<usize>> = members.iter().map(&load).collect();
+            // With every member unavailable, they still take turns
+            let mut available: Vec<usize> = (0..members.len()).filter(|&i| loads[i].is_some()).collect();
+            if available.is_empty() {
+                available = (0..members.len()).collect();
+            }
+            let turn = {
+                let mut turn = self.turns.entry(key).or_insert(0);
+                let current = *turn;
+                *turn = turn.wrapping_add(1);
+                current
+            };
+            // Ties go to whoever's turn comes first
+            let mut rotated = (0..available.len()).map(|i| available[(turn + i) % available.len()]);
+            let chosen = match strategy {
+                GroupStrategy::RoundRobin => rotated.next(),
+                GroupStrategy::LeastLoaded => rotated.min_by_key(|&i| loads[i].unwrap_or(usize::MAX)),
+            };
+            if let Some(chosen) = chosen {
+                picked.push(members.swap_remove(chosen));
+            }
+        }
+        picked
+    }
+
+    /// Count a delivery to a member as in flight until the guard is dropped
+    pub(crate) fn start(&self, subscription_id: &SubscriptionId) -> InFlight<'_> {
+        *self.in_flight.entry(subscription_id.clone()).or_insert(0) += 1;
+        InFlight { groups: self, subscription_id: subscription_id.clone() }
+    }
+
+    /// Deliveries to a member running now
+    pub(crate) fn in_flight(&self, subscription_id: &SubscriptionId) -> usize {
+        self.in_flight.get(subscription_id).map_or(0, |count| *count)
+    }
+
+    /// Forget a group's turn once it has no members left
+    pub(crate) fn remove_group(&self, event_pattern: &str, group: &str) {
+        self.turns.remove(&(event_pattern.to_string(), group.to_string()));
+    }
+}
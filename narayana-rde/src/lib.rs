@@ -2,16 +2,40 @@
 // Event-driven pub/sub system with multiple transport mechanisms
 
 pub mod actor;
+pub mod anomaly;
 pub mod auth;
+pub mod backfill;
+pub mod cloudevents;
+mod delivery;
+pub mod durable;
+pub mod encoding;
 pub mod events;
+pub mod filter;
+mod groups;
+pub mod metrics;
+mod ordered;
+pub mod patterns;
+pub mod quotas;
+mod registry;
+pub mod schema_registry;
 pub mod subscriptions;
 pub mod transformations;
 pub mod transports;
 pub mod rate_limiter;
 
 pub use actor::{Actor, ActorId, ActorType};
+pub use auth::{ActorToken, IssuedToken};
+pub use cloudevents::CloudEvent;
+pub use durable::{DeadLetter, DeadLetterSelection, DeadLetterSummary, DurableDeliveryStats, RedeliveryReport, RetryPolicy};
+pub use encoding::PayloadEncoding;
 pub use events::{Event, EventName, EventSchema, RdeEvent};
+pub use filter::SubscriptionFilter;
+pub use metrics::{EventMetrics, RdeMetrics, SubscriptionMetrics};
+pub use patterns::EventPattern;
+pub use schema_registry::{RegisteredSchema, SchemaFormat, SchemaRegistry};
 pub use subscriptions::{Subscription, SubscriptionId, TransportType};
+pub use transformations::{TransformPipeline, TransformStage};
+pub use rate_limiter::{ActorQuota, ActorQuotaStatus};
 
 use std::sync::Arc;
 use narayana_core::Result;
@@ -22,12 +46,42 @@ pub struct RdeManager {
     actors: Arc<dashmap::DashMap<ActorId, Actor>>,
     events: dashmap::DashMap<EventName, EventSchema>,
     subscriptions: dashmap::DashMap<SubscriptionId, Subscription>,
+    /// Subscription patterns, matched against each published event name
+    subscription_index: parking_lot::RwLock<patterns::PatternIndex<SubscriptionId>>,
     native_events: Arc<NativeEventsSystem>,
     auth: Arc<auth::AuthManager>,
     rate_limiter: Arc<rate_limiter::SubscriptionRateLimiter>,
+    actor_quotas: rate_limiter::ActorQuotaLimiter, // Publish rate and stored bytes per source actor
     websocket_manager: Option<Arc<dyn WebSocketBroadcaster + Send + Sync>>,
     sse_connections: Arc<dashmap::DashMap<SubscriptionId, tokio::sync::mpsc::Sender<String>>>,
     grpc_streams: Arc<dashmap::DashMap<SubscriptionId, tokio::sync::mpsc::Sender<serde_json::Value>>>,
+    worker_invoker: Option<Arc<dyn transports::worker::WorkerInvoker>>,
+    worker_stats: Arc<dashmap::DashMap<SubscriptionId, transports::worker::WorkerDeliveryStats>>,
+    table_sink: Option<Arc<transports::table::TableSink>>,
+    kafka_producer: Option<Arc<dyn transports::kafka::KafkaProducer>>,
+    kafka_stats: Arc<dashmap::DashMap<SubscriptionId, transports::kafka::KafkaDeliveryStats>>,
+    mqtt_connections: Option<Arc<transports::mqtt::MqttConnections>>,
+    mqtt_stats: Arc<dashmap::DashMap<SubscriptionId, transports::mqtt::MqttDeliveryStats>>,
+    amqp_connections: Option<Arc<transports::amqp::AmqpConnections>>,
+    amqp_stats: Arc<dashmap::DashMap<SubscriptionId, transports::amqp::AmqpDeliveryStats>>,
+    schema_registry: Arc<SchemaRegistry>,
+    backfills: Arc<dashmap::DashMap<SubscriptionId, Arc<backfill::Backfill>>>,
+    delivery_queue: Arc<durable::DeliveryQueue>,
+    metrics: Arc<metrics::DeliveryMetrics>,
+    ordered: ordered::OrderedQueues,
+    groups: Arc<groups::ConsumerGroups>,
+    stream_clocks: dashmap::DashMap<StreamName, Arc<tokio::sync::Mutex<u64>>>, // Last event id per stream
+    /// Journal of actors, tokens and subscriptions; None when they aren't persisted
+    registry: Option<Arc<registry::Registry>>,
+}
+
+/// Where a published event goes, decided while its stream clock is held
+#[derive(Default)]
+struct Routing {
+    /// Delivered by the publishing task
+    direct: Vec<Subscription>,
+    /// Ordered subscriptions whose queue was full
+    overflowed: Vec<Subscription>,
 }
 
 /// Trait for WebSocket broadcasting (to avoid direct dependency on WebSocketManager)
@@ -37,18 +91,144 @@ pub trait WebSocketBroadcaster {
 
 impl RdeManager {
     /// Create new RDE Manager
+    ///
+    /// Actors, their tokens and subscriptions are journaled to the native
+    /// events system, and those an earlier manager journaled there are loaded
+    /// back. Subscriptions whose config no longer validates are skipped, and
+    /// backfills are not re-run.
     pub fn new(native_events: Arc<NativeEventsSystem>) -> Self {
+        let registry = Arc::new(registry::Registry::new(native_events.clone()));
+        let manager = Self::build(native_events, Some(registry));
+        manager.load_registry();
+        manager
+    }
+
+    /// Create an RDE Manager whose actors and subscriptions live in memory
+    /// only: nothing is journaled or loaded back
+    pub fn new_in_memory(native_events: Arc<NativeEventsSystem>) -> Self {
+        Self::build(native_events, None)
+    }
+
+    fn build(native_events: Arc<NativeEventsSystem>, registry: Option<Arc<registry::Registry>>) -> Self {
         let actors = Arc::new(dashmap::DashMap::new());
+        let delivery_queue = Arc::new(durable::DeliveryQueue::new(native_events.clone()));
+        let auth = auth::AuthManager::new(actors.clone());
+        let auth = match registry {
+            Some(ref registry) => auth.with_registry(registry.clone()),
+            None => auth,
+        };
         Self {
-            actors: actors.clone(),
+            actors,
             events: dashmap::DashMap::new(),
             subscriptions: dashmap::DashMap::new(),
+            subscription_index: parking_lot::RwLock::new(patterns::PatternIndex::new()),
             native_events,
-            auth: Arc::new(auth::AuthManager::new(actors)),
+            auth: Arc::new(auth),
             rate_limiter: Arc::new(rate_limiter::SubscriptionRateLimiter::new()),
+            actor_quotas: rate_limiter::ActorQuotaLimiter::new(),
             websocket_manager: None,
             sse_connections: Arc::new(dashmap::DashMap::new()),
             grpc_streams: Arc::new(dashmap::DashMap::new()),
+            worker_invoker: None,
+            worker_stats: Arc::new(dashmap::DashMap::new()),
+            table_sink: None,
+            kafka_producer: None,
+            kafka_stats: Arc::new(dashmap::DashMap::new()),
+            mqtt_connections: None,
+            mqtt_stats: Arc::new(dashmap::DashMap::new()),
+            amqp_connections: None,
+            amqp_stats: Arc::new(dashmap::DashMap::new()),
+            schema_registry: Arc::new(SchemaRegistry::new()),
+            backfills: Arc::new(dashmap::DashMap::new()),
+            delivery_queue,
+            metrics: Arc::new(metrics::DeliveryMetrics::default()),
+            ordered: ordered::OrderedQueues::default(),
+            groups: Arc::new(groups::ConsumerGroups::default()),
+            stream_clocks: dashmap::DashMap::new(),
+            registry,
+        }
+    }
+
+    /// Load the actors, tokens and subscriptions journaled by earlier managers
+    fn load_registry(&self) {
+        let Some(ref registry) = self.registry else {
+            return;
+        };
+        let replayed = registry.replay();
+        let mut tokens = replayed.tokens;
+        for (id, actor) in replayed.actors {
+            // Without its tokens the actor could never authenticate
+            let Some(actor_tokens) = tokens.remove(&id) else {
+                tracing::warn!("Skipping journaled actor without tokens");
+                continue;
+            };
+            self.auth.restore_tokens(id.clone(), actor_tokens);
+            self.actors.insert(id, actor);
+        }
+
+        let mut subscriptions: Vec<Subscription> = replayed.subscriptions.into_values().collect();
+        subscriptions.sort_by(|a, b| (a.created_at, &a.id.0).cmp(&(b.created_at, &b.id.0)));
+        for subscription in subscriptions {
+            if !self.actors.contains_key(&subscription.actor_id) {
+                continue;
+            }
+            let pattern = match EventPattern::parse(&subscription.event_name.0) {
+                Ok(pattern) => pattern,
+                Err(e) => {
+                    tracing::warn!("Skipping journaled subscription: {}", e);
+                    continue;
+                }
+            };
+            // Configs are checked again, as validation may have tightened since
+            // they were journaled; a backfill start only applied on subscribe
+            if let Err(e) = Self::validate_config(subscription.transport, &subscription.config, &subscription.id)
+                .and_then(|_| self.check_group(&subscription.event_name, &subscription.config, &subscription.id))
+            {
+                // SECURITY: Don't log subscription ID to prevent information disclosure
+                tracing::warn!("Skipping journaled subscription whose config is no longer valid: {}", e);
+                continue;
+            }
+            self.subscription_index.write().insert(&pattern, subscription.id.clone());
+            self.subscriptions.insert(subscription.id.clone(), subscription);
+        }
+    }
+
+    /// Journal the current actors, tokens and subscriptions as a snapshot that
+    /// supersedes everything journaled before it. This is the migration path
+    /// for registries whose changes weren't all journaled (set up before the
+    /// registry was persisted, or while journaling failed), and compacts what
+    /// the next manager has to replay. Changes made while it
+    /// runs may be left out; call it when the registry is quiet. Does nothing
+    /// for managers created with `new_in_memory`.
+    pub async fn snapshot_registry(&self) {
+        let Some(ref registry) = self.registry else {
+            return;
+        };
+        registry.record_later(registry::JournalEntry::Snapshot);
+        let actors: Vec<Actor> = self.actors.iter().map(|a| a.value().clone()).collect();
+        for mut actor in actors {
+            if registry::is_system_actor(&actor) {
+                continue;
+            }
+            let Some(tokens) = self.auth.stored_tokens(&actor.id) else {
+                continue;
+            };
+            let actor_id = actor.id.clone();
+            actor.auth_token = String::new();
+            registry.record_later(registry::JournalEntry::Actor { actor });
+            registry.record_later(registry::JournalEntry::Tokens { actor_id, tokens });
+        }
+        for subscription in self.subscriptions.iter() {
+            registry.record_later(registry::JournalEntry::Subscribed { subscription: subscription.value().clone() });
+        }
+        registry.flush().await;
+    }
+
+    /// Wait until every registry change made so far is journaled (token
+    /// changes are journaled in the background)
+    pub async fn flush_registry(&self) {
+        if let Some(ref registry) = self.registry {
+            registry.flush().await;
         }
     }
     
@@ -58,6 +238,76 @@ impl RdeManager {
         self
     }
     
+    /// Set worker invoker for the worker transport
+    pub fn with_worker_invoker(mut self, invoker: Arc<dyn transports::worker::WorkerInvoker>) -> Self {
+        self.worker_invoker = Some(invoker);
+        self
+    }
+    
+    /// Delivery and execution metrics of a worker subscription
+    pub fn get_worker_stats(&self, subscription_id: &SubscriptionId) -> Option<transports::worker::WorkerDeliveryStats> {
+        self.worker_stats.get(subscription_id).map(|s| s.value().clone())
+    }
+    
+    /// Set table sink for the table transport
+    pub fn with_table_sink(mut self, sink: Arc<transports::table::TableSink>) -> Self {
+        self.table_sink = Some(sink);
+        self
+    }
+    
+    /// The table transport's sink, if set
+    pub fn table_sink(&self) -> Option<&Arc<transports::table::TableSink>> {
+        self.table_sink.as_ref()
+    }
+    
+    /// Write and dedupe counters of a table subscription
+    pub fn get_table_sink_stats(&self, subscription_id: &SubscriptionId) -> Option<transports::table::TableSinkStats> {
+        self.table_sink.as_ref().and_then(|sink| sink.stats(subscription_id))
+    }
+    
+    /// Set Kafka producer for the kafka transport
+    pub fn with_kafka_producer(mut self, producer: Arc<dyn transports::kafka::KafkaProducer>) -> Self {
+        self.kafka_producer = Some(producer);
+        self
+    }
+    
+    /// Produce counters and last offset of a Kafka subscription
+    pub fn get_kafka_stats(&self, subscription_id: &SubscriptionId) -> Option<transports::kafka::KafkaDeliveryStats> {
+        self.kafka_stats.get(subscription_id).map(|s| s.value().clone())
+    }
+    
+    /// Set the MQTT connector for the mqtt transport
+    pub fn with_mqtt_connector(mut self, connector: Arc<dyn transports::mqtt::MqttConnector>) -> Self {
+        self.mqtt_connections = Some(Arc::new(transports::mqtt::MqttConnections::new(connector)));
+        self
+    }
+    
+    /// Publish counters and connection count of an MQTT subscription
+    pub fn get_mqtt_stats(&self, subscription_id: &SubscriptionId) -> Option<transports::mqtt::MqttDeliveryStats> {
+        self.mqtt_stats.get(subscription_id).map(|s| s.value().clone())
+    }
+    
+    /// Set the AMQP connector for the amqp transport
+    pub fn with_amqp_connector(mut self, connector: Arc<dyn transports::amqp::AmqpConnector>) -> Self {
+        self.amqp_connections = Some(Arc::new(transports::amqp::AmqpConnections::new(connector)));
+        self
+    }
+    
+    /// Publish counters and connection count of an AMQP subscription
+    pub fn get_amqp_stats(&self, subscription_id: &SubscriptionId) -> Option<transports::amqp::AmqpDeliveryStats> {
+        self.amqp_stats.get(subscription_id).map(|s| s.value().clone())
+    }
+    
+    /// Delivery counters of every subscription and event name
+    pub fn metrics(&self) -> RdeMetrics {
+        self.metrics.snapshot(&self.subscriptions)
+    }
+    
+    /// Delivery counters of one subscription; None until something was delivered to it
+    pub fn get_subscription_metrics(&self, subscription_id: &SubscriptionId) -> Option<SubscriptionMetrics> {
+        self.metrics.subscription(subscription_id)
+    }
+    
     /// Register SSE connection for a subscription
     pub fn register_sse_connection(&self, subscription_id: SubscriptionId, sender: tokio::sync::mpsc::Sender<String>) {
         self.sse_connections.insert(subscription_id, sender);
@@ -83,6 +333,40 @@ impl RdeManager {
         self.websocket_manager.clone()
     }
 
+    /// Get the payload schema registry
+    pub fn schema_registry(&self) -> Arc<SchemaRegistry> {
+        self.schema_registry.clone()
+    }
+
+    /// Token store, for trusted callers (e.g. admin APIs) that manage actor
+    /// tokens without presenting one
+    pub fn auth_manager(&self) -> Arc<auth::AuthManager> {
+        self.auth.clone()
+    }
+    
+    /// Register an Avro or Protobuf schema for one of the source actor's events
+    /// SECURITY: Requires authentication token
+    pub async fn register_schema(
+        &self,
+        actor_id: &ActorId,
+        auth_token: &str,
+        event_name: &str,
+        format: SchemaFormat,
+        definition: serde_json::Value,
+    ) -> Result<Arc<RegisteredSchema>> {
+        if !self.auth.authenticate(actor_id, auth_token)? {
+            return Err(narayana_core::Error::Storage("Authentication failed".to_string()));
+        }
+        if event_name.is_empty() || event_name.len() > 256 || event_name.contains(':') || event_name == "*" {
+            return Err(narayana_core::Error::Storage("Invalid event name".to_string()));
+        }
+        let is_source = self.actors.get(actor_id).is_some_and(|a| a.actor_type == ActorType::Source);
+        if !is_source {
+            return Err(narayana_core::Error::Storage("Actor is not a source actor or authentication failed".to_string()));
+        }
+        self.schema_registry.register(&format!("{}:{}", actor_id, event_name), format, definition)
+    }
+    
     /// Register a new actor
     pub async fn register_actor(&self, actor: Actor) -> Result<ActorId> {
         // Validate actor ID
@@ -129,9 +413,19 @@ impl RdeManager {
         // SECURITY: Atomic check-and-insert to prevent TOCTOU race condition
         // DashMap's insert returns Some(old_value) if key already exists
         let id = actor.id.clone();
+        let journaled = self.registry.as_ref()
+            .filter(|_| !registry::is_system_actor(&actor))
+            .map(|_| Actor { auth_token: String::new(), ..actor.clone() });
         if self.actors.insert(id.clone(), actor).is_some() {
             return Err(narayana_core::Error::Storage("Actor already exists".to_string()));
         }
+
+        // Only the token's hash is journaled, with the rest of the actor's tokens
+        if let (Some(registry), Some(actor)) = (&self.registry, journaled) {
+            let tokens = self.auth.stored_tokens(&id).unwrap_or_default();
+            registry.record_later(registry::JournalEntry::Actor { actor });
+            registry.record(registry::JournalEntry::Tokens { actor_id: id.clone(), tokens }).await;
+        }
         
         Ok(id)
     }
@@ -146,6 +440,49 @@ impl RdeManager {
         })
     }
 
+    /// Issue the actor an additional token, optionally expiring at a unix time
+    /// SECURITY: Requires authentication token
+    pub fn issue_token(&self, actor_id: &ActorId, auth_token: &str, expires_at: Option<u64>) -> Result<IssuedToken> {
+        if !self.auth.authenticate(actor_id, auth_token)? {
+            return Err(narayana_core::Error::Storage("Authentication failed".to_string()));
+        }
+        self.auth.issue_token(actor_id, expires_at)
+    }
+
+    /// Replace the presented token: a new one is issued and the presented one
+    /// stops working after `grace_secs` (immediately when 0). Subscriptions
+    /// are untouched.
+    /// SECURITY: Requires authentication token
+    pub fn rotate_token(
+        &self,
+        actor_id: &ActorId,
+        auth_token: &str,
+        grace_secs: u64,
+        expires_at: Option<u64>,
+    ) -> Result<IssuedToken> {
+        let token_id = self.auth.token_id(actor_id, auth_token)
+            .ok_or_else(|| narayana_core::Error::Storage("Authentication failed".to_string()))?;
+        self.auth.rotate_token(actor_id, Some(&token_id), grace_secs, expires_at)
+    }
+
+    /// Revoke one of the actor's tokens (possibly the presented one) with immediate effect
+    /// SECURITY: Requires authentication token
+    pub fn revoke_token(&self, actor_id: &ActorId, auth_token: &str, token_id: &str) -> Result<ActorToken> {
+        if !self.auth.authenticate(actor_id, auth_token)? {
+            return Err(narayana_core::Error::Storage("Authentication failed".to_string()));
+        }
+        self.auth.revoke_token(actor_id, token_id)
+    }
+
+    /// The actor's tokens with their expiry and revocation times (never the secrets)
+    /// SECURITY: Requires authentication token
+    pub fn list_tokens(&self, actor_id: &ActorId, auth_token: &str) -> Result<Vec<ActorToken>> {
+        if !self.auth.authenticate(actor_id, auth_token)? {
+            return Err(narayana_core::Error::Storage("Authentication failed".to_string()));
+        }
+        Ok(self.auth.list_tokens(actor_id).unwrap_or_default())
+    }
+
     /// Publish an event
     /// SECURITY: Requires authentication token
     pub async fn publish_event(
@@ -156,10 +493,135 @@ impl RdeManager {
         payload: serde_json::Value,
     ) -> Result<()> {
         // SECURITY: Authenticate first
+        self.authenticate_source(actor_id, auth_token)?;
+        let bytes = Self::validate_event(event_name, &payload)?;
+        self.admit_publish(actor_id, 1, bytes)?;
+
+        let (event_name_key, stream_name) = self.prepare_event_stream(actor_id, event_name, &payload).await?;
+
+        // Event ids are publish times in milliseconds, kept strictly increasing per
+        // stream; the stream's clock stays locked until the event is stored so
+        // stream order matches id order (backfills rely on this)
+        let clock = self.stream_clocks.entry(stream_name.clone()).or_default().clone();
+        let mut last_id = clock.lock().await;
+        let event_id = Self::next_event_id(&mut last_id);
+        let native_event = Self::native_event(event_id, &stream_name, event_name, payload.clone());
+
+        // Publish to native events system
+        // Continue even if publish fails (best effort)
+        match self.native_events.publish_event(native_event).await {
+            Ok(_) => {}
+            Err(e) => {
+                tracing::warn!("Failed to publish event to native events system: {}, continuing with delivery", e);
+                // Continue with delivery even if storage fails
+            }
+        }
+        // Ordered subscriptions queue the event before the clock is released
+        let routing = self.route_event(&event_name_key, event_id.0, &payload);
+        drop(last_id);
+
+        // Deliver to subscribers
+        // Don't fail entire publish if delivery fails; event was published
+        self.deliver_routed(&event_name_key, event_id.0, &payload, routing).await;
+
+        Ok(())
+    }
+
+    /// Publish many events from one source actor in a single call
+    ///
+    /// The actor is authenticated once, each event name's stream is set up
+    /// once and all events are written to the native events system in one
+    /// batched call before being delivered in order. Every event is validated
+    /// first; an invalid one rejects the whole batch. Returns the number of
+    /// events published.
+    /// SECURITY: Requires authentication token
+    pub async fn publish_events_batch(
+        &self,
+        actor_id: &ActorId,
+        auth_token: &str,
+        events: Vec<(String, serde_json::Value)>,
+    ) -> Result<usize> {
+        const MAX_BATCH_EVENTS: usize = 10_000;
+
+        self.authenticate_source(actor_id, auth_token)?;
+        if events.len() > MAX_BATCH_EVENTS {
+            return Err(narayana_core::Error::Storage(format!(
+                "Batch too large: {} events (max: {})",
+                events.len(), MAX_BATCH_EVENTS
+            )));
+        }
+        let mut bytes = 0;
+        for (event_name, payload) in &events {
+            bytes += Self::validate_event(event_name, payload)?;
+        }
+        if events.is_empty() {
+            return Ok(0);
+        }
+        self.admit_publish(actor_id, events.len(), bytes)?;
+
+        // One stream per event name, set up on its first event
+        let mut streams: std::collections::HashMap<&str, (EventName, StreamName)> = std::collections::HashMap::new();
+        for (event_name, payload) in &events {
+            if !streams.contains_key(event_name.as_str()) {
+                let prepared = self.prepare_event_stream(actor_id, event_name, payload).await?;
+                streams.insert(event_name.as_str(), prepared);
+            }
+        }
+
+        // Hold every involved stream clock until the batch is stored, locking in
+        // name order so concurrent batches cannot deadlock
+        let mut stream_names: Vec<StreamName> = streams.values().map(|(_, stream)| stream.clone()).collect();
+        stream_names.sort_by(|a, b| a.0.cmp(&b.0));
+        stream_names.dedup();
+        let clocks: Vec<_> = stream_names.iter()
+            .map(|stream| self.stream_clocks.entry(stream.clone()).or_default().clone())
+            .collect();
+        let mut guards = std::collections::HashMap::new();
+        for (stream, clock) in stream_names.iter().zip(&clocks) {
+            guards.insert(stream.clone(), clock.lock().await);
+        }
+
+        let mut native_events = Vec::with_capacity(events.len());
+        for (event_name, payload) in &events {
+            let (_, stream_name) = &streams[event_name.as_str()];
+            let last_id = guards.get_mut(stream_name).expect("every stream clock is held");
+            let event_id = Self::next_event_id(last_id);
+            native_events.push(Self::native_event(event_id, stream_name, event_name, payload.clone()));
+        }
+        let ids: Vec<u64> = native_events.iter().map(|event| event.id.0).collect();
+
+        // Continue with delivery even if storage fails (best effort, as for single events)
+        if let Err(e) = self.native_events.publish_events(native_events).await {
+            tracing::warn!("Failed to publish event batch to native events system: {}, continuing with delivery", e);
+        }
+        let routings: Vec<Routing> = events.iter().zip(&ids)
+            .map(|((event_name, payload), event_id)| self.route_event(&streams[event_name.as_str()].0, *event_id, payload))
+            .collect();
+        drop(guards);
+
+        for (((event_name, payload), event_id), routing) in events.iter().zip(ids).zip(routings) {
+            let (event_name_key, _) = &streams[event_name.as_str()];
+            self.deliver_routed(event_name_key, event_id, payload, routing).await;
+        }
+        Ok(events.len())
+    }
+
+    /// Authenticate an actor and check it may publish
+    fn authenticate_source(&self, actor_id: &ActorId, auth_token: &str) -> Result<()> {
         if !self.auth.authenticate(actor_id, auth_token)? {
             return Err(narayana_core::Error::Storage("Authentication failed".to_string()));
         }
-        // Validate event name
+        // SECURITY: Use generic error message to prevent actor enumeration
+        let actor = self.actors.get(actor_id)
+            .ok_or_else(|| narayana_core::Error::Storage("Actor not found or authentication failed".to_string()))?;
+        if actor.actor_type != ActorType::Source {
+            return Err(narayana_core::Error::Storage("Actor is not a source actor or authentication failed".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Check an event name and payload size; returns the payload's size in bytes
+    fn validate_event(event_name: &str, payload: &serde_json::Value) -> Result<u64> {
         if event_name.is_empty() {
             return Err(narayana_core::Error::Storage("Event name cannot be empty".to_string()));
         }
@@ -178,10 +640,10 @@ impl RdeManager {
         if event_name == ":" || event_name == "*" {
             return Err(narayana_core::Error::Storage("Event name cannot be ':' or '*'".to_string()));
         }
-        
+
         // Validate payload size (prevent memory exhaustion)
         const MAX_PAYLOAD_SIZE: usize = 10 * 1024 * 1024; // 10MB
-        let payload_size = serde_json::to_string(&payload)
+        let payload_size = serde_json::to_string(payload)
             .map_err(|e| narayana_core::Error::Storage(format!("Failed to serialize payload: {}", e)))?
             .len();
         if payload_size > MAX_PAYLOAD_SIZE {
@@ -190,28 +652,84 @@ impl RdeManager {
                 payload_size, MAX_PAYLOAD_SIZE
             )));
         }
-        
-        // Verify actor exists and is source type (check again after validation to prevent race condition)
-        // SECURITY: Use generic error message to prevent actor enumeration
-        let actor = self.actors.get(actor_id)
-            .ok_or_else(|| narayana_core::Error::Storage("Actor not found or authentication failed".to_string()))?;
-        
-        if actor.actor_type != ActorType::Source {
-            return Err(narayana_core::Error::Storage("Actor is not a source actor or authentication failed".to_string()));
+        Ok(payload_size as u64)
+    }
+
+    /// Take published events from the actor's quota, if it has one
+    fn admit_publish(&self, actor_id: &ActorId, events: usize, bytes: u64) -> Result<()> {
+        self.actor_quotas
+            .admit_or_recount(&actor_id.0, events, bytes, || self.retained_bytes(actor_id))
+            .map_err(|refusal| narayana_core::Error::QuotaExceeded(refusal.to_string()))
+    }
+
+    /// Payload bytes of the actor's events still retained in its streams
+    fn retained_bytes(&self, actor_id: &ActorId) -> u64 {
+        const READ_CHUNK: usize = 10_000;
+        let prefix = format!("rde:{}:", actor_id);
+        let mut bytes = 0;
+        for stream in self.native_events.stream_names() {
+            // Event names have no ':', so a longer suffix is another actor's stream
+            if stream.0.strip_prefix(&prefix).is_none_or(|event| event.contains(':')) {
+                continue;
+            }
+            let mut from = narayana_storage::native_events::EventId(0);
+            loop {
+                let events = self.native_events.read_stream(&stream, from, READ_CHUNK);
+                bytes += events.iter()
+                    .map(|event| serde_json::to_string(&event.payload).map_or(0, |json| json.len() as u64))
+                    .sum::<u64>();
+                match events.last() {
+                    Some(last) if events.len() == READ_CHUNK => from = narayana_storage::native_events::EventId(last.id.0 + 1),
+                    _ => break,
+                }
+            }
+        }
+        bytes
+    }
+
+    /// Limit what a source actor may publish: its event rate with a burst
+    /// allowance, and the bytes its retained events take up (counted now)
+    pub fn set_actor_quota(&self, actor_id: &ActorId, quota: ActorQuota) -> Result<()> {
+        let is_source = self.actors.get(actor_id)
+            .ok_or_else(|| narayana_core::Error::Storage("Actor not found".to_string()))?
+            .actor_type == ActorType::Source;
+        if !is_source {
+            return Err(narayana_core::Error::Storage("Quotas apply to source actors only".to_string()));
         }
+        self.actor_quotas.set_quota(&actor_id.0, quota)?;
+        self.actor_quotas.set_stored_bytes(&actor_id.0, self.retained_bytes(actor_id));
+        Ok(())
+    }
 
+    /// Lift a source actor's quota; returns whether it had one
+    pub fn remove_actor_quota(&self, actor_id: &ActorId) -> bool {
+        self.actor_quotas.remove_quota(&actor_id.0)
+    }
+
+    /// A source actor's quota and what is left of it, if it has one
+    pub fn actor_quota(&self, actor_id: &ActorId) -> Option<ActorQuotaStatus> {
+        self.actor_quotas.status(&actor_id.0)
+    }
+
+    /// Record the event's schema on first use and make sure its stream exists
+    async fn prepare_event_stream(
+        &self,
+        actor_id: &ActorId,
+        event_name: &str,
+        payload: &serde_json::Value,
+    ) -> Result<(EventName, StreamName)> {
         // Create full event name (namespaced)
         let full_event_name = format!("{}:{}", actor_id, event_name);
-        let event_name_key = EventName::from(full_event_name.clone());
+        let event_name_key = EventName::from(full_event_name);
 
         // Extract schema from first event
         if !self.events.contains_key(&event_name_key) {
-            let schema = events::extract_schema(&payload)?;
+            let schema = events::extract_schema(payload)?;
             self.events.insert(event_name_key.clone(), schema);
         }
 
         // Ensure stream exists
-        let stream_name = StreamName(format!("rde:{}", full_event_name));
+        let stream_name = backfill::stream_name(&event_name_key);
         let stream = EventStream {
             name: stream_name.clone(),
             partitions: 1,
@@ -222,7 +740,7 @@ impl RdeManager {
             max_size: None,
             max_events: Some(1_000_000),
         };
-        
+
         // Create stream if it doesn't exist (idempotent)
         if let Err(e) = self.native_events.create_stream(stream).await {
             // Stream might already exist, that's ok - but log other errors
@@ -230,30 +748,43 @@ impl RdeManager {
                 tracing::warn!("Failed to create stream: {}", e);
             }
         }
+        Ok((event_name_key, stream_name))
+    }
 
-        // Create native event
+    /// Event ids are publish times in milliseconds, kept strictly increasing
+    /// per stream; the caller holds the stream's clock
+    fn next_event_id(last_id: &mut u64) -> narayana_storage::native_events::EventId {
         // Handle timestamp edge cases (negative, overflow, etc.)
         let timestamp_ms = chrono::Utc::now().timestamp_millis();
-        let event_id = if timestamp_ms > 0 && timestamp_ms < i64::MAX as i64 {
-            narayana_storage::native_events::EventId(timestamp_ms as u64)
+        let now_ms = if timestamp_ms > 0 {
+            timestamp_ms as u64
         } else {
-            // Fallback if timestamp is negative or overflow (shouldn't happen, but handle edge case)
+            // Fallback if timestamp is negative (shouldn't happen, but handle edge case)
             let fallback_ms = std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap_or_default()
                 .as_millis();
             // Prevent overflow
-            let safe_ms = fallback_ms.min(u64::MAX as u128) as u64;
-            narayana_storage::native_events::EventId(safe_ms)
+            fallback_ms.min(u64::MAX as u128) as u64
         };
-        
-        let native_event = NativeEvent {
-            id: event_id,
-            stream: stream_name.clone(),
+        let event_id = narayana_storage::native_events::EventId(now_ms.max(last_id.saturating_add(1)));
+        *last_id = event_id.0;
+        event_id
+    }
+
+    fn native_event(
+        id: narayana_storage::native_events::EventId,
+        stream: &StreamName,
+        event_name: &str,
+        payload: serde_json::Value,
+    ) -> NativeEvent {
+        NativeEvent {
+            id,
+            stream: stream.clone(),
             topic: None,
             queue: None,
             event_type: event_name.to_string(),
-            payload: payload.clone(),
+            payload,
             headers: std::collections::HashMap::new(),
             timestamp: chrono::Utc::now().timestamp() as u64,
             correlation_id: None,
@@ -261,26 +792,44 @@ impl RdeManager {
             partition_key: None,
             ttl: None,
             priority: 0,
-        };
-
-        // Publish to native events system
-        // Continue even if publish fails (best effort)
-        match self.native_events.publish_event(native_event).await {
-            Ok(_) => {}
-            Err(e) => {
-                tracing::warn!("Failed to publish event to native events system: {}, continuing with delivery", e);
-                // Continue with delivery even if storage fails
-            }
         }
+    }
 
-        // Deliver to subscribers
-        // Don't fail entire publish if delivery fails
-        if let Err(e) = self.deliver_to_subscribers(&event_name_key, &payload).await {
-            tracing::warn!("Failed to deliver event to some subscribers: {}", e);
-            // Event was published, so we return success even if delivery partially failed
+    /// Publish an event whose payload is encoded as `content_type`
+    ///
+    /// Avro and Protobuf payloads are decoded with the event's registered
+    /// schema (the latest one unless `schema_version` is given) and published
+    /// as JSON, so subscribers get them in whatever encoding they accept.
+    /// SECURITY: Requires authentication token
+    pub async fn publish_encoded_event(
+        &self,
+        actor_id: &ActorId,
+        auth_token: &str,
+        event_name: &str,
+        content_type: &str,
+        schema_version: Option<u32>,
+        data: &[u8],
+    ) -> Result<()> {
+        // SECURITY: Authenticate before touching the schema registry
+        if !self.auth.authenticate(actor_id, auth_token)? {
+            return Err(narayana_core::Error::Storage("Authentication failed".to_string()));
         }
-
-        Ok(())
+        let encoding = PayloadEncoding::from_content_type(content_type)
+            .ok_or_else(|| narayana_core::Error::Storage(format!("Unsupported content type: {}", content_type)))?;
+        let payload = match encoding.schema_format() {
+            None => serde_json::from_slice(data)
+                .map_err(|e| narayana_core::Error::Storage(format!("Invalid JSON payload: {}", e)))?,
+            Some(format) => {
+                let full_event_name = format!("{}:{}", actor_id, event_name);
+                let schema = match schema_version {
+                    Some(version) => self.schema_registry.version(&full_event_name, format, version),
+                    None => self.schema_registry.latest(&full_event_name, format),
+                }
+                .ok_or_else(|| narayana_core::Error::Storage(format!("No {} schema registered for event", format)))?;
+                schema.codec().decode(data)?
+            }
+        };
+        self.publish_event(actor_id, auth_token, event_name, payload).await
     }
 
     /// Subscribe to an event
@@ -311,11 +860,14 @@ impl RdeManager {
             return Err(narayana_core::Error::Storage("Actor is not an origin actor".to_string()));
         }
         
+        // Event name can be either "actor_id:event_name" or just "event_name"
+        // (any actor's event_name), with glob wildcards on either side
+        let pattern = EventPattern::parse(event_name)?;
+        
         // SECURITY: Restrict wildcard subscriptions to prevent privacy leaks
-        // Only allow wildcard if explicitly permitted in actor metadata
-        // Wildcard is when event_name doesn't contain ':' (will become "*:event_name")
-        let is_wildcard = !event_name.contains(':');
-        if is_wildcard {
+        // Only allow patterns that reach other actors' events if explicitly
+        // permitted in actor metadata
+        if pattern.spans_actors() {
             let allow_wildcard = actor.metadata
                 .get("allow_wildcard_subscriptions")
                 .and_then(|v| v.as_bool())
@@ -341,85 +893,522 @@ impl RdeManager {
             )));
         }
 
-        // Validate event name format
-        if event_name == "*" || event_name == ":" || event_name == "*:*" {
-            return Err(narayana_core::Error::Storage("Invalid event name pattern".to_string()));
-        }
-        
-        // Validate subscription config size
-        if let Some(ref config) = config {
-            let config_size = serde_json::to_string(config)
-                .map_err(|e| narayana_core::Error::Storage(format!("Failed to serialize config: {}", e)))?
-                .len();
-            const MAX_CONFIG_SIZE: usize = 1024 * 1024; // 1MB
-            if config_size > MAX_CONFIG_SIZE {
-                return Err(narayana_core::Error::Storage(format!(
-                    "Subscription config too large: {} bytes (max: {} bytes)",
-                    config_size, MAX_CONFIG_SIZE
-                )));
-            }
-        }
-        
-        // Use full namespaced event name for subscription
-        // Event name can be either "actor_id:event_name" (full) or just "event_name" (will match any actor)
-        let full_event_name = if event_name.contains(':') {
-            // Validate that it's a proper namespaced format
-            let parts: Vec<&str> = event_name.split(':').collect();
-            if parts.len() != 2 {
-                return Err(narayana_core::Error::Storage("Invalid namespaced event name format (expected 'actor:event')".to_string()));
-            }
-            event_name.to_string() // Already namespaced
-        } else {
-            // Subscribe to all actors with this event name - use wildcard pattern
-            format!("*:{}", event_name) // Wildcard pattern
-        };
-        
+        // Validate the config before anything is registered
         let subscription_id = SubscriptionId::new();
+        let backfill_start = Self::validate_config(transport, config.as_ref().unwrap_or(&serde_json::Value::Null), &subscription_id)?;
+        let event_pattern = EventName::from(pattern.to_string());
+        self.check_group(&event_pattern, config.as_ref().unwrap_or(&serde_json::Value::Null), &subscription_id)?;
+        
         let subscription = Subscription {
             id: subscription_id.clone(),
             actor_id: actor_id.clone(),
-            event_name: EventName::from(full_event_name),
+            event_name: event_pattern,
             transport,
             config: config.unwrap_or_default(),
             created_at: chrono::Utc::now().timestamp() as u64,
         };
 
-        self.subscriptions.insert(subscription_id.clone(), subscription);
+        // Backfill: live events are held from the moment the subscription is
+        // visible, and history is read after that, so nothing falls in between
+        if let Some(start) = backfill_start {
+            let state = Arc::new(backfill::Backfill::default());
+            self.backfills.insert(subscription_id.clone(), state.clone());
+            self.subscriptions.insert(subscription_id.clone(), subscription.clone());
+            self.subscription_index.write().insert(&pattern, subscription_id.clone());
+            self.journal(registry::JournalEntry::Subscribed { subscription: subscription.clone() }).await;
+            let event_names = self.events
+                .iter()
+                .filter(|e| pattern.matches(&e.key().0))
+                .map(|e| e.key().clone())
+                .collect();
+            tokio::spawn(backfill::run(
+                self.dispatcher(),
+                self.native_events.clone(),
+                self.backfills.clone(),
+                state,
+                subscription,
+                event_names,
+                start,
+            ));
+            return Ok(subscription_id);
+        }
+
+        self.subscriptions.insert(subscription_id.clone(), subscription.clone());
+        self.subscription_index.write().insert(&pattern, subscription_id.clone());
+        self.journal(registry::JournalEntry::Subscribed { subscription }).await;
 
         // If event doesn't exist yet, subscription is stored and will be delivered when event is published
-        // This is handled in deliver_to_subscribers
+        // This is handled in route_event
 
         Ok(subscription_id)
     }
 
-    /// Deliver event to all subscribers
-    async fn deliver_to_subscribers(
+    /// Whether a subscription is still replaying history
+    pub fn is_backfilling(&self, subscription_id: &SubscriptionId) -> bool {
+        self.backfills.contains_key(subscription_id)
+    }
+
+    /// Remove a subscription
+    /// SECURITY: Requires authentication token; only the owning actor can unsubscribe
+    pub async fn unsubscribe(
+        &self,
+        actor_id: &ActorId,
+        auth_token: &str,
+        subscription_id: &SubscriptionId,
+    ) -> Result<bool> {
+        if !self.auth.authenticate(actor_id, auth_token)? {
+            return Err(narayana_core::Error::Storage("Authentication failed".to_string()));
+        }
+
+        let removed = self.subscriptions.remove_if(subscription_id, |_, s| s.actor_id == *actor_id);
+        let Some((_, subscription)) = removed else {
+            return Ok(false);
+        };
+        if let Ok(pattern) = EventPattern::parse(&subscription.event_name.0) {
+            self.subscription_index.write().remove(&pattern, |id| id == subscription_id);
+        }
+        self.journal(registry::JournalEntry::Unsubscribed { id: subscription_id.clone() }).await;
+        self.backfills.remove(subscription_id);
+        self.sse_connections.remove(subscription_id);
+        self.grpc_streams.remove(subscription_id);
+        self.worker_stats.remove(subscription_id);
+        self.kafka_stats.remove(subscription_id);
+        self.mqtt_stats.remove(subscription_id);
+        self.amqp_stats.remove(subscription_id);
+        self.metrics.remove_subscription(subscription_id);
+        self.rate_limiter.remove(&subscription_id.0).await;
+        self.ordered.remove(subscription_id);
+        if let Ok(Some(group)) = groups::from_config(&subscription.config) {
+            let members_left = self.subscriptions.iter().any(|s| {
+                s.event_name == subscription.event_name
+                    && groups::from_config(&s.config).ok().flatten().is_some_and(|g| g.name == group.name)
+            });
+            if !members_left {
+                self.groups.remove_group(&subscription.event_name.0, &group.name);
+            }
+        }
+        if let Some(ref connections) = self.mqtt_connections {
+            connections.disconnect(subscription_id);
+        }
+        if let Some(ref connections) = self.amqp_connections {
+            connections.disconnect(subscription_id);
+        }
+        self.delivery_queue.remove_subscription(subscription_id).await;
+        Ok(true)
+    }
+
+    /// An actor's subscriptions, oldest first
+    /// SECURITY: Requires authentication token; actors only see their own
+    pub fn list_subscriptions(&self, actor_id: &ActorId, auth_token: &str) -> Result<Vec<Subscription>> {
+        if !self.auth.authenticate(actor_id, auth_token)? {
+            return Err(narayana_core::Error::Storage("Authentication failed".to_string()));
+        }
+        let mut subscriptions: Vec<Subscription> = self.subscriptions
+            .iter()
+            .filter(|s| s.value().actor_id == *actor_id)
+            .map(|s| s.value().clone())
+            .collect();
+        subscriptions.sort_by(|a, b| (a.created_at, &a.id.0).cmp(&(b.created_at, &b.id.0)));
+        Ok(subscriptions)
+    }
+
+    /// Replace a subscription's config, validated as on subscribe; its event
+    /// pattern and transport stay. Events published from now on are delivered
+    /// with the new config, including to ordered subscriptions (events already
+    /// queued keep the old one); a table subscription's buffered rows are
+    /// written first, and MQTT and AMQP subscriptions reconnect. `from` can't
+    /// be set: backfills only run on subscribe.
+    /// SECURITY: Requires authentication token; only the owning actor can update
+    pub async fn update_subscription_config(
+        &self,
+        actor_id: &ActorId,
+        auth_token: &str,
+        subscription_id: &SubscriptionId,
+        config: serde_json::Value,
+    ) -> Result<Subscription> {
+        self.authorize_subscription(actor_id, auth_token, subscription_id)?;
+        let (transport, event_pattern) = self.subscriptions
+            .get(subscription_id)
+            .map(|s| (s.transport, s.event_name.clone()))
+            .ok_or_else(|| narayana_core::Error::Storage("Subscription not found".to_string()))?;
+        if Self::validate_config(transport, &config, subscription_id)?.is_some() {
+            return Err(narayana_core::Error::Storage("from can only be set when subscribing".to_string()));
+        }
+        self.check_group(&event_pattern, &config, subscription_id)?;
+
+        // Rows already buffered for a table go to the table they were meant for
+        if transport == TransportType::Table {
+            if let Some(ref sink) = self.table_sink {
+                if let Err(e) = sink.flush(subscription_id).await {
+                    tracing::warn!("Table sink flush failed: {}", e);
+                }
+            }
+        }
+        let updated = {
+            let mut subscription = self.subscriptions
+                .get_mut(subscription_id)
+                .filter(|s| s.actor_id == *actor_id)
+                .ok_or_else(|| narayana_core::Error::Storage("Subscription not found".to_string()))?;
+            subscription.config = config;
+            subscription.clone()
+        };
+        self.journal(registry::JournalEntry::Updated { id: subscription_id.clone(), config: updated.config.clone() }).await;
+        // The connection was opened for the old broker and topic
+        if transport == TransportType::Mqtt {
+            if let Some(ref connections) = self.mqtt_connections {
+                connections.disconnect(subscription_id);
+            }
+        }
+        if transport == TransportType::Amqp {
+            if let Some(ref connections) = self.amqp_connections {
+                connections.disconnect(subscription_id);
+            }
+        }
+        Ok(updated)
+    }
+
+    /// Events waiting in an ordered subscription's queue; None for other
+    /// subscriptions and before the first event
+    pub fn ordered_backlog(&self, subscription_id: &SubscriptionId) -> Option<usize> {
+        self.ordered.backlog(subscription_id)
+    }
+
+    /// Retry counters of a subscription; None until a delivery to it failed
+    pub fn get_delivery_stats(&self, subscription_id: &SubscriptionId) -> Option<DurableDeliveryStats> {
+        self.delivery_queue.stats(subscription_id)
+    }
+
+    /// Events that exhausted their retries, from dead-letter `sequence` on
+    /// SECURITY: Requires authentication token; only the owning actor can read them
+    pub async fn dead_letters(
+        &self,
+        actor_id: &ActorId,
+        auth_token: &str,
+        subscription_id: &SubscriptionId,
+        sequence: u64,
+        limit: usize,
+    ) -> Result<Vec<DeadLetter>> {
+        self.authorize_subscription(actor_id, auth_token, subscription_id)?;
+        Ok(self.subscription_dead_letters(subscription_id, sequence, limit))
+    }
+
+    /// The dead letter at `sequence`; None if there is none or it is past retention
+    /// SECURITY: Requires authentication token; only the owning actor can read it
+    pub async fn dead_letter(
+        &self,
+        actor_id: &ActorId,
+        auth_token: &str,
+        subscription_id: &SubscriptionId,
+        sequence: u64,
+    ) -> Result<Option<DeadLetter>> {
+        self.authorize_subscription(actor_id, auth_token, subscription_id)?;
+        Ok(self.delivery_queue.dead_letter_at(subscription_id, sequence))
+    }
+
+    /// Subscriptions with dead letters, removed ones included (admin use; no
+    /// actor token is checked)
+    pub fn dead_letter_summary(&self) -> Vec<DeadLetterSummary> {
+        self.delivery_queue.dead_letter_summaries()
+    }
+
+    /// Dead letters of any subscription from `sequence` on (admin use; no
+    /// actor token is checked)
+    pub fn subscription_dead_letters(&self, subscription_id: &SubscriptionId, sequence: u64, limit: usize) -> Vec<DeadLetter> {
+        const MAX_DEAD_LETTERS: usize = 1000;
+        self.delivery_queue.dead_letters(subscription_id, sequence, limit.min(MAX_DEAD_LETTERS))
+    }
+
+    /// The dead letter at `sequence` of any subscription (admin use; no actor
+    /// token is checked)
+    pub fn subscription_dead_letter(&self, subscription_id: &SubscriptionId, sequence: u64) -> Option<DeadLetter> {
+        self.delivery_queue.dead_letter_at(subscription_id, sequence)
+    }
+
+    /// Queue the selected dead letters for redelivery with a fresh set of
+    /// attempts, dropping them from the dead-letter stream once queued. At
+    /// most 1000 are queued per call, fewer once the subscription's retry
+    /// queue is full; the report counts the rest as skipped.
+    /// SECURITY: Requires authentication token; only the owning actor can redeliver
+    pub async fn redeliver_dead_letters(
+        &self,
+        actor_id: &ActorId,
+        auth_token: &str,
+        subscription_id: &SubscriptionId,
+        selection: DeadLetterSelection,
+    ) -> Result<RedeliveryReport> {
+        self.authorize_subscription(actor_id, auth_token, subscription_id)?;
+        self.redeliver_subscription_dead_letters(subscription_id, selection).await
+    }
+
+    /// Redeliver dead letters of any current subscription, as
+    /// `redeliver_dead_letters` does (admin use; no actor token is checked)
+    pub async fn redeliver_subscription_dead_letters(
+        &self,
+        subscription_id: &SubscriptionId,
+        selection: DeadLetterSelection,
+    ) -> Result<RedeliveryReport> {
+        const MAX_REDELIVER: usize = 1000;
+        if !self.subscriptions.contains_key(subscription_id) {
+            return Err(narayana_core::Error::Storage("Subscription not found".to_string()));
+        }
+        let mut report = RedeliveryReport::default();
+        let letters = match selection {
+            DeadLetterSelection::From(sequence) => {
+                let letters = self.delivery_queue.dead_letters(subscription_id, sequence, MAX_REDELIVER);
+                report.skipped = self.delivery_queue.dead_letter_count(subscription_id, sequence).saturating_sub(letters.len());
+                letters
+            }
+            DeadLetterSelection::Sequences(mut sequences) => {
+                if sequences.is_empty() {
+                    return Err(narayana_core::Error::Query("No dead-letter sequences given".to_string()));
+                }
+                sequences.sort_unstable();
+                sequences.dedup();
+                if sequences.len() > MAX_REDELIVER {
+                    return Err(narayana_core::Error::Query(format!(
+                        "At most {} dead letters can be redelivered at once",
+                        MAX_REDELIVER
+                    )));
+                }
+                let mut letters = Vec::with_capacity(sequences.len());
+                for sequence in sequences {
+                    match self.delivery_queue.dead_letter_at(subscription_id, sequence) {
+                        Some(letter) => letters.push(letter),
+                        None => report.missing.push(sequence),
+                    }
+                }
+                letters
+            }
+        };
+        let selected = letters.len();
+        report.queued = self.delivery_queue.redeliver(subscription_id, letters).await;
+        report.skipped += selected - report.queued;
+        Ok(report)
+    }
+
+    /// Retry the failed deliveries that are due; returns how many were attempted
+    ///
+    /// Deliveries of removed subscriptions are dropped; ones whose subscription
+    /// turned retries off get this last attempt before being dead-lettered.
+    /// Retries may reach a subscriber after newer events.
+    pub async fn retry_pending_deliveries(&self) -> usize {
+        const RETRY_BATCH: usize = 500;
+        let dispatcher = self.dispatcher();
+        let due = self.delivery_queue.take_due(durable::now_ms(), RETRY_BATCH);
+        let attempted = due.len();
+        for delivery in due {
+            let Some(subscription) = self.subscriptions.get(&delivery.subscription_id).map(|s| s.value().clone()) else {
+                self.delivery_queue.discard(delivery).await;
+                continue;
+            };
+            let policy = durable::RetryPolicy::from_config(&subscription.config)
+                .ok()
+                .flatten()
+                .unwrap_or(durable::RetryPolicy { max_attempts: 1, ..Default::default() });
+            match dispatcher.deliver(&subscription, &delivery.event_name, delivery.event_id, &delivery.payload).await {
+                Ok(()) => self.delivery_queue.succeeded(delivery).await,
+                Err(e) => self.delivery_queue.failed(delivery, &policy, &e.to_string()).await,
+            }
+        }
+        attempted
+    }
+
+    /// Rebuild the retry queue from its journal in the native events system
+    /// (for a manager taking over an event system another one wrote to);
+    /// returns the deliveries recovered
+    pub async fn recover_pending_deliveries(&self) -> usize {
+        self.delivery_queue.recover().await
+    }
+
+    /// Retry due deliveries every `interval` until the manager is dropped
+    pub fn start_delivery_retries(self: &Arc<Self>, interval: std::time::Duration) -> tokio::task::JoinHandle<()> {
+        let manager = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                let Some(manager) = manager.upgrade() else {
+                    break;
+                };
+                manager.retry_pending_deliveries().await;
+            }
+        })
+    }
+
+    /// Journal a registry change, when the registry is persisted
+    async fn journal(&self, entry: registry::JournalEntry) {
+        if let Some(ref registry) = self.registry {
+            registry.record(entry).await;
+        }
+    }
+
+    /// Check a subscription's config for its transport; returns where its
+    /// backfill starts, if it has one
+    fn validate_config(
+        transport: TransportType,
+        config: &serde_json::Value,
+        subscription_id: &SubscriptionId,
+    ) -> Result<Option<backfill::BackfillStart>> {
+        // Validate subscription config size
+        let config_size = serde_json::to_string(config)
+            .map_err(|e| narayana_core::Error::Storage(format!("Failed to serialize config: {}", e)))?
+            .len();
+        const MAX_CONFIG_SIZE: usize = 1024 * 1024; // 1MB
+        if config_size > MAX_CONFIG_SIZE {
+            return Err(narayana_core::Error::Storage(format!(
+                "Subscription config too large: {} bytes (max: {} bytes)",
+                config_size, MAX_CONFIG_SIZE
+            )));
+        }
+        
+        // Webhook signing and client certificates are checked once, here
+        if transport == TransportType::Webhook {
+            transports::http::WebhookSecurity::from_config(config)?;
+        }
+        
+        // Worker subscriptions must name the worker to invoke
+        if transport == TransportType::Worker {
+            let has_worker = config.get("worker_id")
+                .and_then(|v| v.as_str())
+                .is_some_and(|id| !id.is_empty());
+            if !has_worker {
+                return Err(narayana_core::Error::Storage("Worker subscriptions require a worker_id in config".to_string()));
+            }
+        }
+        
+        // Table subscriptions must name the table to write
+        if transport == TransportType::Table {
+            let has_table = config.get("table")
+                .and_then(|v| v.as_str())
+                .is_some_and(|table| !table.is_empty());
+            if !has_table {
+                return Err(narayana_core::Error::Storage("Table subscriptions require a table in config".to_string()));
+            }
+        }
+        
+        // Kafka subscriptions need valid topics and partition key
+        if transport == TransportType::Kafka {
+            transports::kafka::KafkaTarget::from_config(config)?;
+        }
+        
+        // MQTT subscriptions need an allowed broker and a valid topic
+        if transport == TransportType::Mqtt {
+            transports::mqtt::MqttTarget::from_config(config, subscription_id)?;
+        }
+        
+        // AMQP subscriptions need an allowed broker and a valid exchange and routing key
+        if transport == TransportType::Amqp {
+            transports::amqp::AmqpTarget::from_config(config)?;
+        }
+        
+        // Validate the payload filter now rather than on every delivery
+        SubscriptionFilter::from_config(config)?;
+        
+        // Validate the transformation pipeline now rather than on every delivery
+        TransformPipeline::from_config(config)?;
+        
+        // Validate the retry policy now rather than on the first failed delivery
+        durable::RetryPolicy::from_config(config)?;
+        
+        // Validate ordering now rather than on every publish
+        ordered::from_config(config)?;
+        
+        // Validate the consumer group now rather than on every publish
+        let group = groups::from_config(config)?;
+        
+        // Binary encodings need a transport that carries raw bodies
+        let accepted = encoding::accepted_encodings(config)?;
+        let binary = accepted.iter().any(|e| *e != PayloadEncoding::Json);
+        if binary && !matches!(transport, TransportType::Webhook | TransportType::Worker | TransportType::Kafka | TransportType::Mqtt | TransportType::Amqp) {
+            return Err(narayana_core::Error::Storage(format!(
+                "{} subscriptions only support application/json",
+                transport
+            )));
+        }
+        
+        // CloudEvents envelopes are JSON documents, on transports that carry them whole
+        if cloudevents::CloudEventsTarget::from_config(config)?.is_some() {
+            if !matches!(transport, TransportType::Webhook | TransportType::Sse | TransportType::Kafka) {
+                return Err(narayana_core::Error::Storage(format!(
+                    "{} subscriptions don't support the cloudevents envelope",
+                    transport
+                )));
+            }
+            if binary {
+                return Err(narayana_core::Error::Storage("The cloudevents envelope only supports application/json".to_string()));
+            }
+        }
+        
+        // Validate the backfill start before anything is registered; a group
+        // member's history would be replayed to it alone
+        let backfill_start = backfill::BackfillStart::from_config(config)?;
+        if group.is_some() && backfill_start.is_some() {
+            return Err(narayana_core::Error::Storage("from can't be set on a group member".to_string()));
+        }
+        Ok(backfill_start)
+    }
+
+    /// Check a group member agrees with the group's other members (on the same
+    /// event pattern) about how events are shared out
+    fn check_group(&self, event_pattern: &EventName, config: &serde_json::Value, subscription_id: &SubscriptionId) -> Result<()> {
+        let Some(group) = groups::from_config(config)? else {
+            return Ok(());
+        };
+        let conflicting = self.subscriptions.iter().any(|s| {
+            s.key() != subscription_id
+                && s.event_name == *event_pattern
+                && groups::from_config(&s.config)
+                    .ok()
+                    .flatten()
+                    .is_some_and(|g| g.name == group.name && g.strategy != group.strategy)
+        });
+        if conflicting {
+            return Err(narayana_core::Error::Storage(format!(
+                "group '{}' already uses a different group_strategy",
+                group.name
+            )));
+        }
+        Ok(())
+    }
+
+    /// Authenticate `actor_id` and check it owns the subscription
+    fn authorize_subscription(&self, actor_id: &ActorId, auth_token: &str, subscription_id: &SubscriptionId) -> Result<()> {
+        if !self.auth.authenticate(actor_id, auth_token)? {
+            return Err(narayana_core::Error::Storage("Authentication failed".to_string()));
+        }
+        // SECURITY: Same error for missing and foreign subscriptions
+        match self.subscriptions.get(subscription_id) {
+            Some(s) if s.actor_id == *actor_id => Ok(()),
+            _ => Err(narayana_core::Error::Storage("Subscription not found".to_string())),
+        }
+    }
+
+    /// Match an event to its subscriptions: backfilling ones hold it, ordered
+    /// ones queue it, and the rest are returned for the publisher to deliver
+    /// Called with the event's stream clock held, so ordered queues get each
+    /// stream's events in id order.
+    fn route_event(
         &self,
         event_name: &EventName,
+        event_id: u64,
         payload: &serde_json::Value,
-    ) -> Result<()> {
-        // Find all subscriptions for this event
-        // Support wildcard matching: "*:event_name" matches any actor's event
+    ) -> Routing {
+        self.metrics.published(event_name);
+
+        // Find all subscriptions whose pattern matches this event
         // Limit number of subscriptions to prevent memory exhaustion
         const MAX_SUBSCRIPTIONS_TO_DELIVER: usize = 1000;
-        let matching_subscriptions: Vec<Subscription> = self.subscriptions
+        let matching_ids: Vec<SubscriptionId> = self.subscription_index
+            .read()
+            .matching(&event_name.0, MAX_SUBSCRIPTIONS_TO_DELIVER) // Limit to prevent DoS
+            .into_iter()
+            .cloned()
+            .collect();
+        // Subscriptions removed since the lookup are skipped
+        let matching_subscriptions: Vec<Subscription> = matching_ids
             .iter()
-            .filter(|s| {
-                let sub_event = &s.value().event_name.0;
-                let target_event = &event_name.0;
-                
-                // Exact match
-                sub_event == target_event ||
-                // Wildcard match: "*:event_name" matches "actor_id:event_name"
-                (sub_event.starts_with("*:") && 
-                 sub_event.len() > 2 && // Prevent "*:" matching everything
-                 !sub_event[2..].contains(':') && // Prevent nested wildcards like "*:actor:event"
-                 target_event.contains(':') && // Target must be namespaced
-                 target_event.ends_with(&sub_event[2..]) &&
-                 target_event.len() > sub_event.len() - 1) // Ensure there's an actor part
-            })
-            .take(MAX_SUBSCRIPTIONS_TO_DELIVER) // Limit to prevent DoS
-            .map(|s| s.value().clone())
+            .filter_map(|id| self.subscriptions.get(id).map(|s| s.value().clone()))
             .collect();
         
         if matching_subscriptions.len() >= MAX_SUBSCRIPTIONS_TO_DELIVER {
@@ -427,68 +1416,76 @@ impl RdeManager {
             tracing::warn!("Event has more than {} subscriptions, limiting delivery", MAX_SUBSCRIPTIONS_TO_DELIVER);
         }
 
-        // Deliver via appropriate transport
+        // Each consumer group gets the event once; members with the least work
+        // outstanding are those with the fewest deliveries running, queued in
+        // order or waiting for a retry
+        let dispatcher = self.dispatcher();
+        let matching_subscriptions = self.groups.pick(matching_subscriptions, |subscription| {
+            dispatcher.connected(subscription).then(|| {
+                self.groups.in_flight(&subscription.id)
+                    + self.ordered.backlog(&subscription.id).unwrap_or(0)
+                    + self.delivery_queue.stats(&subscription.id).map_or(0, |s| s.pending as usize)
+            })
+        });
+
+        let mut routing = Routing::default();
         for subscription in matching_subscriptions {
-            // Check rate limit for this subscription
-            let rate_limit = subscription.config
-                .get("rate_limit_per_second")
-                .and_then(|v| v.as_f64());
-            
-            let delay = self.rate_limiter.check_and_record(
-                &subscription.id.0,
-                rate_limit,
-            ).await;
-            
-            // Wait if rate limited
-            if !delay.is_zero() {
-                tokio::time::sleep(delay).await;
+            // Backfilling subscriptions get live events after their history
+            let backfill = self.backfills.get(&subscription.id).map(|b| b.clone());
+            if backfill.is_some_and(|b| b.hold(event_name, event_id, payload)) {
+                continue;
             }
-            
-            // Apply transformation if configured (continue on error)
-            let transformed_payload = match crate::transformations::apply_transformation(&subscription, payload) {
-                Ok(transformed) => transformed,
-                Err(e) => {
-                    // SECURITY: Don't log subscription ID to prevent information disclosure
-                    tracing::warn!("Transformation failed, using original payload: {}", e);
-                    payload.clone() // Use original payload if transformation fails
-                }
-            };
-            
-            let result = match subscription.transport {
-                TransportType::Webhook => {
-                    crate::transports::http::deliver_webhook(&subscription, &transformed_payload).await
-                }
-                TransportType::WebSocket => {
-                    crate::transports::websocket::deliver_websocket(
-                        &subscription,
-                        &transformed_payload,
-                        self.get_websocket_manager(),
-                    ).await
-                }
-                TransportType::Grpc => {
-                    crate::transports::grpc::deliver_grpc(
-                        &subscription,
-                        &transformed_payload,
-                        self.get_grpc_sender(&subscription.id),
-                    ).await
-                }
-                TransportType::Sse => {
-                    crate::transports::sse::deliver_sse(
-                        &subscription,
-                        &transformed_payload,
-                        self.get_sse_sender(&subscription.id),
-                    ).await
+            // Validated on subscribe
+            if ordered::from_config(&subscription.config).unwrap_or(false) {
+                if !self.ordered.push(|| self.dispatcher(), &subscription, event_name, event_id, payload) {
+                    routing.overflowed.push(subscription);
                 }
-            };
-            
-            if let Err(e) = result {
-                // SECURITY: Don't log subscription ID to prevent information disclosure
-                tracing::warn!("Failed to deliver event to subscription: {}", e);
-                // Continue with other subscriptions
+                continue;
             }
+            routing.direct.push(subscription);
         }
+        routing
+    }
 
-        Ok(())
+    /// Deliver a routed event to the subscriptions left to the publisher
+    async fn deliver_routed(
+        &self,
+        event_name: &EventName,
+        event_id: u64,
+        payload: &serde_json::Value,
+        routing: Routing,
+    ) {
+        let dispatcher = self.dispatcher();
+        for subscription in routing.overflowed {
+            dispatcher.overflow(&subscription, event_name, event_id, payload).await;
+        }
+        for subscription in routing.direct {
+            // Failures are queued for retries; continue with other subscriptions
+            dispatcher.deliver_or_queue(&subscription, event_name, event_id, payload).await;
+        }
+    }
+
+    /// Shared transport handles for delivering events
+    fn dispatcher(&self) -> delivery::Dispatcher {
+        delivery::Dispatcher {
+            rate_limiter: self.rate_limiter.clone(),
+            websocket_manager: self.websocket_manager.clone(),
+            sse_connections: self.sse_connections.clone(),
+            grpc_streams: self.grpc_streams.clone(),
+            worker_invoker: self.worker_invoker.clone(),
+            worker_stats: self.worker_stats.clone(),
+            table_sink: self.table_sink.clone(),
+            kafka_producer: self.kafka_producer.clone(),
+            kafka_stats: self.kafka_stats.clone(),
+            mqtt_connections: self.mqtt_connections.clone(),
+            mqtt_stats: self.mqtt_stats.clone(),
+            amqp_connections: self.amqp_connections.clone(),
+            amqp_stats: self.amqp_stats.clone(),
+            schema_registry: self.schema_registry.clone(),
+            delivery_queue: self.delivery_queue.clone(),
+            metrics: self.metrics.clone(),
+            groups: self.groups.clone(),
+        }
     }
 }
 
@@ -0,0 +1,235 @@
+// Delivery metrics
+// Counters per subscription and per event name, updated on every delivery
+// attempt (live, backfilled or retried) whatever the transport, so a
+// subscription that keeps failing shows up without digging through each
+// transport's own stats.
+
+use crate::events::EventName;
+use crate::subscriptions::{Subscription, SubscriptionId, TransportType};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Distinct event names tracked; later ones are counted under `OTHER_EVENTS`
+const MAX_TRACKED_EVENTS: usize = 10_000;
+/// Where events past `MAX_TRACKED_EVENTS` are counted
+pub const OTHER_EVENTS: &str = "_other";
+/// Longest error kept per subscription
+const MAX_ERROR_LENGTH: usize = 512;
+
+/// Metric name suffix, help text and value of a Prometheus counter
+type Counter<T> = (&'static str, &'static str, fn(&T) -> u64);
+
+/// Delivery counters of one subscription
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubscriptionMetrics {
+    /// Deliveries that went through, retries included
+    pub delivered: u64,
+    /// Delivery attempts that failed, retries included
+    pub failed: u64,
+    /// Deliveries held back by the subscription's rate limit
+    pub rate_limited: u64,
+    /// Payloads rewritten by the subscription's `output_config`
+    pub transformed: u64,
+    /// Transformations that failed; the original payload was sent instead
+    pub transform_errors: u64,
+    /// Events the subscription's filter skipped
+    pub filtered: u64,
+    pub last_delivered_at: Option<u64>,
+    pub last_failed_at: Option<u64>,
+    pub last_error: Option<String>,
+}
+
+/// Publish and delivery counters of one event name
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventMetrics {
+    pub published: u64,
+    /// Subscriptions the events were delivered to
+    pub delivered: u64,
+    pub failed: u64,
+    pub last_published_at: Option<u64>,
+}
+
+/// A subscription's counters with what identifies it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubscriptionReport {
+    pub subscription_id: String,
+    pub actor_id: String,
+    pub event_pattern: String,
+    pub transport: TransportType,
+    #[serde(flatten)]
+    pub metrics: SubscriptionMetrics,
+}
+
+/// Snapshot returned by `RdeManager::metrics`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RdeMetrics {
+    /// Live subscriptions, by id
+    pub subscriptions: Vec<SubscriptionReport>,
+    pub events: BTreeMap<String, EventMetrics>,
+}
+
+impl RdeMetrics {
+    /// Subscriptions whose last attempt failed, most failures first
+    pub fn failing(&self) -> Vec<&SubscriptionReport> {
+        let mut failing: Vec<_> = self.subscriptions.iter()
+            .filter(|s| s.metrics.last_failed_at > s.metrics.last_delivered_at)
+            .collect();
+        failing.sort_by_key(|s| std::cmp::Reverse(s.metrics.failed));
+        failing
+    }
+
+    /// Prometheus text exposition
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+        let subscription_counters: [Counter<SubscriptionMetrics>; 6] = [
+            ("delivered", "Events delivered to the subscription", |m| m.delivered),
+            ("failed", "Failed delivery attempts", |m| m.failed),
+            ("rate_limited", "Deliveries delayed by the rate limit", |m| m.rate_limited),
+            ("transformed", "Payloads transformed before delivery", |m| m.transformed),
+            ("transform_errors", "Transformations that failed", |m| m.transform_errors),
+            ("filtered", "Events skipped by the subscription filter", |m| m.filtered),
+        ];
+        for (name, help, value) in subscription_counters {
+            let _ = writeln!(out, "# HELP narayana_rde_subscription_{}_total {}", name, help);
+            let _ = writeln!(out, "# TYPE narayana_rde_subscription_{}_total counter", name);
+            for s in &self.subscriptions {
+                let _ = writeln!(
+                    out,
+                    "narayana_rde_subscription_{}_total{{subscription=\"{}\",actor=\"{}\",transport=\"{}\"}} {}",
+                    name,
+                    escape_label(&s.subscription_id),
+                    escape_label(&s.actor_id),
+                    transport_label(&s.transport),
+                    value(&s.metrics)
+                );
+            }
+        }
+        let event_counters: [Counter<EventMetrics>; 3] = [
+            ("published", "Events published", |m| m.published),
+            ("delivered", "Deliveries of the event to subscriptions", |m| m.delivered),
+            ("failed", "Failed deliveries of the event", |m| m.failed),
+        ];
+        for (name, help, value) in event_counters {
+            let _ = writeln!(out, "# HELP narayana_rde_event_{}_total {}", name, help);
+            let _ = writeln!(out, "# TYPE narayana_rde_event_{}_total counter", name);
+            for (event, metrics) in &self.events {
+                let _ = writeln!(out, "narayana_rde_event_{}_total{{event=\"{}\"}} {}", name, escape_label(event), value(metrics));
+            }
+        }
+        out
+    }
+}
+
+/// Live counters, shared by the manager and its dispatchers
+#[derive(Default)]
+pub(crate) struct DeliveryMetrics {
+    subscriptions: DashMap<SubscriptionId, SubscriptionMetrics>,
+    events: DashMap<String, EventMetrics>,
+}
+
+impl DeliveryMetrics {
+    pub(crate) fn published(&self, event_name: &EventName) {
+        let mut event = self.event(event_name);
+        event.published += 1;
+        event.last_published_at = Some(now());
+    }
+
+    pub(crate) fn filtered(&self, subscription_id: &SubscriptionId) {
+        self.subscriptions.entry(subscription_id.clone()).or_default().filtered += 1;
+    }
+
+    pub(crate) fn rate_limited(&self, subscription_id: &SubscriptionId) {
+        self.subscriptions.entry(subscription_id.clone()).or_default().rate_limited += 1;
+    }
+
+    pub(crate) fn transformed(&self, subscription_id: &SubscriptionId, ok: bool) {
+        let mut metrics = self.subscriptions.entry(subscription_id.clone()).or_default();
+        if ok {
+            metrics.transformed += 1;
+        } else {
+            metrics.transform_errors += 1;
+        }
+    }
+
+    pub(crate) fn delivered(&self, subscription_id: &SubscriptionId, event_name: &EventName, result: &narayana_core::Result<()>) {
+        let now = now();
+        {
+            let mut metrics = self.subscriptions.entry(subscription_id.clone()).or_default();
+            match result {
+                Ok(()) => {
+                    metrics.delivered += 1;
+                    metrics.last_delivered_at = Some(now);
+                }
+                Err(e) => {
+                    metrics.failed += 1;
+                    metrics.last_failed_at = Some(now);
+                    let mut error = e.to_string();
+                    if error.len() > MAX_ERROR_LENGTH {
+                        let mut end = MAX_ERROR_LENGTH;
+                        while !error.is_char_boundary(end) {
+                            end -= 1;
+                        }
+                        error.truncate(end);
+                    }
+                    metrics.last_error = Some(error);
+                }
+            }
+        }
+        let mut event = self.event(event_name);
+        match result {
+            Ok(()) => event.delivered += 1,
+            Err(_) => event.failed += 1,
+        }
+    }
+
+    pub(crate) fn subscription(&self, subscription_id: &SubscriptionId) -> Option<SubscriptionMetrics> {
+        self.subscriptions.get(subscription_id).map(|m| m.clone())
+    }
+
+    pub(crate) fn remove_subscription(&self, subscription_id: &SubscriptionId) {
+        self.subscriptions.remove(subscription_id);
+    }
+
+    /// Counters of `subscriptions`, in id order, and of every event name
+    pub(crate) fn snapshot(&self, subscriptions: &DashMap<SubscriptionId, Subscription>) -> RdeMetrics {
+        let mut reports: Vec<SubscriptionReport> = subscriptions
+            .iter()
+            .map(|s| SubscriptionReport {
+                subscription_id: s.id.0.clone(),
+                actor_id: s.actor_id.0.clone(),
+                event_pattern: s.event_name.0.clone(),
+                transport: s.transport,
+                metrics: self.subscription(&s.id).unwrap_or_default(),
+            })
+            .collect();
+        reports.sort_by(|a, b| a.subscription_id.cmp(&b.subscription_id));
+        RdeMetrics {
+            subscriptions: reports,
+            events: self.events.iter().map(|e| (e.key().clone(), e.value().clone())).collect(),
+        }
+    }
+
+    fn event(&self, event_name: &EventName) -> dashmap::mapref::one::RefMut<'_, String, EventMetrics> {
+        if !self.events.contains_key(&event_name.0) && self.events.len() >= MAX_TRACKED_EVENTS {
+            return self.events.entry(OTHER_EVENTS.to_string()).or_default();
+        }
+        self.events.entry(event_name.0.clone()).or_default()
+    }
+}
+
+fn transport_label(transport: &TransportType) -> String {
+    match serde_json::to_value(transport) {
+        Ok(serde_json::Value::String(name)) => name,
+        _ => format!("{:?}", transport).to_lowercase(),
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn now() -> u64 {
+    chrono::Utc::now().timestamp() as u64
+}
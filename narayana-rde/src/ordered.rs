@@ -0,0 +1,103 @@
+// Ordered delivery - one event at a time per subscription
+// A subscription created with `ordered: true` gets its events through a
+// queue of its own, drained by a single task, so events arrive in the order
+// they were published even when a delivery is slow or has to be retried
+// (retries happen in place; later events wait). Events are queued while the
+// publishing stream's clock is still held, so each stream's events enter the
+// queue in id order. Each event carries the subscription as it was when the
+// event was published, so a config update applies from the next event on.
+
+use crate::delivery::Dispatcher;
+use crate::events::EventName;
+use crate::subscriptions::{Subscription, SubscriptionId};
+use narayana_core::{Error, Result};
+use tokio::sync::mpsc::{self, error::TrySendError};
+
+/// Events waiting per ordered subscription; beyond this they are dead-lettered
+pub(crate) const MAX_QUEUED_EVENTS: usize = 10_000;
+
+/// Parse the subscription's `ordered` option (default false)
+pub(crate) fn from_config(config: &serde_json::Value) -> Result<bool> {
+    match config.get("ordered") {
+        None | Some(serde_json::Value::Null) => Ok(false),
+        Some(serde_json::Value::Bool(ordered)) => Ok(*ordered),
+        Some(_) => Err(Error::Storage("ordered must be a boolean".to_string())),
+    }
+}
+
+struct QueuedEvent {
+    subscription: Subscription,
+    event_name: EventName,
+    event_id: u64,
+    payload: serde_json::Value,
+}
+
+/// Queue of one subscription and the task draining it
+struct Lane {
+    sender: mpsc::Sender<QueuedEvent>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl Lane {
+    fn start(dispatcher: Dispatcher) -> Self {
+        let (sender, mut receiver) = mpsc::channel::<QueuedEvent>(MAX_QUEUED_EVENTS);
+        let task = tokio::spawn(async move {
+            while let Some(event) = receiver.recv().await {
+                dispatcher.deliver_or_queue(&event.subscription, &event.event_name, event.event_id, &event.payload).await;
+            }
+        });
+        Self { sender, task }
+    }
+}
+
+/// Delivery queues of all ordered subscriptions
+#[derive(Default)]
+pub(crate) struct OrderedQueues {
+    lanes: dashmap::DashMap<SubscriptionId, Lane>,
+}
+
+impl OrderedQueues {
+    /// Queue an event, starting the subscription's task on its first event
+    /// Returns false when the queue is full.
+    pub(crate) fn push(
+        &self,
+        dispatcher: impl Fn() -> Dispatcher,
+        subscription: &Subscription,
+        event_name: &EventName,
+        event_id: u64,
+        payload: &serde_json::Value,
+    ) -> bool {
+        let mut lane = self.lanes
+            .entry(subscription.id.clone())
+            .or_insert_with(|| Lane::start(dispatcher()));
+        let event = QueuedEvent {
+            subscription: subscription.clone(),
+            event_name: event_name.clone(),
+            event_id,
+            payload: payload.clone(),
+        };
+        match lane.sender.try_send(event) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => false,
+            // The task is gone (it panicked); start over with a fresh one
+            Err(TrySendError::Closed(event)) => {
+                *lane = Lane::start(dispatcher());
+                lane.sender.try_send(event).is_ok()
+            }
+        }
+    }
+
+    /// Events waiting in a subscription's queue; None if it never had one
+    pub(crate) fn backlog(&self, subscription_id: &SubscriptionId) -> Option<usize> {
+        self.lanes
+            .get(subscription_id)
+            .map(|lane| lane.sender.max_capacity() - lane.sender.capacity())
+    }
+
+    /// Stop a removed subscription's task; whatever it still had queued is dropped
+    pub(crate) fn remove(&self, subscription_id: &SubscriptionId) {
+        if let Some((_, lane)) = self.lanes.remove(subscription_id) {
+            lane.task.abort();
+        }
+    }
+}
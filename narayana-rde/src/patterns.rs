@@ -0,0 +1,243 @@
+// Subscription patterns - glob matching of event names
+// A subscription names the events it wants as `actor:event`, where either
+// side may use `*` (any run of characters) and `?` (any one character);
+// neither wildcard crosses the `:`. `\*`, `\?` and `\\` match those
+// characters literally. A bare `event` is shorthand for `*:event`.
+//
+//   robot-*:telemetry.*   sensor-??:reading   *:alarm
+//
+// Patterns are compiled into one trie shared by every subscription, so a
+// published event only walks the branches its name can still match instead
+// of testing each subscription in turn.
+
+use narayana_core::{Error, Result};
+use std::collections::{HashMap, HashSet};
+
+/// Longest pattern accepted
+const MAX_PATTERN_LENGTH: usize = 512;
+/// Most `*` in one pattern; bounds the work of matching it
+const MAX_WILDCARDS: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Token {
+    Char(char),
+    AnyChar,
+    AnyRun,
+}
+
+/// A parsed `actor:event` subscription pattern
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventPattern {
+    tokens: Vec<Token>,
+    /// Where the `:` is in `tokens`
+    separator: usize,
+}
+
+impl EventPattern {
+    /// Parse a pattern; a bare event name becomes `*:event`
+    pub fn parse(pattern: &str) -> Result<Self> {
+        let invalid = |message: &str| Error::Storage(format!("Invalid event pattern: {}", message));
+        if pattern.is_empty() {
+            return Err(invalid("pattern cannot be empty"));
+        }
+        if pattern.len() > MAX_PATTERN_LENGTH {
+            return Err(invalid(&format!("pattern must be at most {} bytes", MAX_PATTERN_LENGTH)));
+        }
+        if pattern.chars().any(|c| c.is_control()) {
+            return Err(invalid("pattern cannot contain control characters"));
+        }
+
+        let mut tokens = Vec::with_capacity(pattern.len() + 2);
+        let mut separator = None;
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            let token = match c {
+                '\\' => match chars.next() {
+                    Some(escaped @ ('*' | '?' | '\\')) => Token::Char(escaped),
+                    _ => return Err(invalid("'\\' only escapes '*', '?' and '\\'")),
+                },
+                '*' => Token::AnyRun,
+                '?' => Token::AnyChar,
+                ':' if separator.is_some() => return Err(invalid("expected 'actor:event'")),
+                ':' => {
+                    separator = Some(tokens.len());
+                    Token::Char(':')
+                }
+                c => Token::Char(c),
+            };
+            // `**` is the same as `*`
+            if token == Token::AnyRun && tokens.last() == Some(&Token::AnyRun) {
+                continue;
+            }
+            tokens.push(token);
+        }
+        let separator = match separator {
+            Some(separator) => separator,
+            None => {
+                tokens.splice(0..0, [Token::AnyRun, Token::Char(':')]);
+                1
+            }
+        };
+
+        let (actor, event) = (&tokens[..separator], &tokens[separator + 1..]);
+        if actor.is_empty() || event.is_empty() {
+            return Err(invalid("actor and event cannot be empty"));
+        }
+        if actor == [Token::AnyRun] && event == [Token::AnyRun] {
+            return Err(invalid("pattern would match every event"));
+        }
+        if tokens.iter().filter(|t| **t == Token::AnyRun).count() > MAX_WILDCARDS {
+            return Err(invalid(&format!("at most {} '*' per pattern", MAX_WILDCARDS)));
+        }
+        Ok(Self { tokens, separator })
+    }
+
+    /// Whether the pattern names one event rather than a family of them
+    pub fn is_literal(&self) -> bool {
+        self.tokens.iter().all(|t| matches!(t, Token::Char(_)))
+    }
+
+    /// Whether the actor side has wildcards, so the pattern can match
+    /// events of actors other than one named one
+    pub fn spans_actors(&self) -> bool {
+        self.tokens[..self.separator].iter().any(|t| !matches!(t, Token::Char(_)))
+    }
+
+    /// Whether a published `actor:event` name matches
+    pub fn matches(&self, event_name: &str) -> bool {
+        let mut index = PatternIndex::new();
+        index.insert(self, ());
+        !index.matching(event_name, 1).is_empty()
+    }
+}
+
+impl std::fmt::Display for EventPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for token in &self.tokens {
+            match token {
+                Token::Char(c @ ('*' | '?' | '\\')) => write!(f, "\\{}", c)?,
+                Token::Char(c) => write!(f, "{}", c)?,
+                Token::AnyChar => write!(f, "?")?,
+                Token::AnyRun => write!(f, "*")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Values keyed by event pattern, looked up by published event name
+#[derive(Debug)]
+pub struct PatternIndex<T> {
+    root: Node<T>,
+    len: usize,
+}
+
+#[derive(Debug)]
+struct Node<T> {
+    children: HashMap<Token, Node<T>>,
+    values: Vec<T>,
+}
+
+impl<T> Default for Node<T> {
+    fn default() -> Self {
+        Self { children: HashMap::new(), values: Vec::new() }
+    }
+}
+
+impl<T> Default for PatternIndex<T> {
+    fn default() -> Self {
+        Self { root: Node::default(), len: 0 }
+    }
+}
+
+impl<T> PatternIndex<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn insert(&mut self, pattern: &EventPattern, value: T) {
+        let node = pattern.tokens.iter().fold(&mut self.root, |node, token| node.children.entry(*token).or_default());
+        node.values.push(value);
+        self.len += 1;
+    }
+
+    /// Remove the first value under `pattern` that `is_match` accepts;
+    /// branches left empty are pruned
+    pub fn remove(&mut self, pattern: &EventPattern, is_match: impl Fn(&T) -> bool) -> Option<T> {
+        let removed = Self::remove_from(&mut self.root, &pattern.tokens, &is_match);
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    fn remove_from(node: &mut Node<T>, tokens: &[Token], is_match: &dyn Fn(&T) -> bool) -> Option<T> {
+        let Some((token, rest)) = tokens.split_first() else {
+            let position = node.values.iter().position(is_match)?;
+            return Some(node.values.remove(position));
+        };
+        let child = node.children.get_mut(token)?;
+        let removed = Self::remove_from(child, rest, is_match);
+        if child.values.is_empty() && child.children.is_empty() {
+            node.children.remove(token);
+        }
+        removed
+    }
+
+    /// Values whose pattern matches `event_name`, at most `limit` of them
+    pub fn matching(&self, event_name: &str, limit: usize) -> Vec<&T> {
+        let name: Vec<char> = event_name.chars().collect();
+        let mut matched = Vec::new();
+        let mut visited = HashSet::new();
+        Self::collect(&self.root, &name, 0, limit, &mut visited, &mut matched);
+        matched
+    }
+
+    fn collect<'a>(
+        node: &'a Node<T>,
+        name: &[char],
+        pos: usize,
+        limit: usize,
+        // Nodes are reached at most once per position, which keeps `*` from
+        // backtracking exponentially and each value from matching twice
+        visited: &mut HashSet<(*const Node<T>, usize)>,
+        matched: &mut Vec<&'a T>,
+    ) {
+        if matched.len() >= limit || !visited.insert((node as *const Node<T>, pos)) {
+            return;
+        }
+        if pos == name.len() {
+            matched.extend(node.values.iter().take(limit - matched.len()));
+        }
+        let next = name.get(pos).copied();
+        if let Some(c) = next {
+            if let Some(child) = node.children.get(&Token::Char(c)) {
+                Self::collect(child, name, pos + 1, limit, visited, matched);
+            }
+            if c != ':' {
+                if let Some(child) = node.children.get(&Token::AnyChar) {
+                    Self::collect(child, name, pos + 1, limit, visited, matched);
+                }
+            }
+        }
+        if let Some(child) = node.children.get(&Token::AnyRun) {
+            // `*` takes zero or more characters, up to the next `:`
+            let mut end = pos;
+            loop {
+                Self::collect(child, name, end, limit, visited, matched);
+                if end == name.len() || name[end] == ':' {
+                    break;
+                }
+                end += 1;
+            }
+        }
+    }
+}
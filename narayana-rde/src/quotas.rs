@@ -0,0 +1,48 @@
+// Storage quota events into RDE
+// Publishes warnings, refused writes and read-only transitions from
+// narayana-storage as "storage-quotas:quota.warning", "storage-quotas:quota.exceeded",
+// "storage-quotas:disk.read_only" and "storage-quotas:disk.writable" events
+
+use crate::actor::{Actor, ActorId, ActorType};
+use crate::RdeManager;
+use async_trait::async_trait;
+use narayana_core::Result;
+use narayana_storage::quotas::{QuotaEvent, QuotaSink};
+use std::sync::Arc;
+
+/// Source actor used for quota events
+pub const QUOTA_ACTOR_ID: &str = "storage-quotas";
+
+/// Quota sink that publishes into RDE as a system source actor
+pub struct RdeQuotaSink {
+    manager: Arc<RdeManager>,
+    actor_id: ActorId,
+    auth_token: String,
+}
+
+impl RdeQuotaSink {
+    /// Register the system source actor with a random token
+    pub async fn register(manager: Arc<RdeManager>) -> Result<Self> {
+        let auth_token = format!("{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple());
+        let mut actor = Actor::new(
+            QUOTA_ACTOR_ID,
+            "Storage Quotas".to_string(),
+            ActorType::Source,
+            auth_token.clone(),
+        );
+        actor.metadata = serde_json::json!({ "system": true });
+        let actor_id = manager.register_actor(actor).await?;
+        Ok(Self { manager, actor_id, auth_token })
+    }
+}
+
+#[async_trait]
+impl QuotaSink for RdeQuotaSink {
+    async fn emit(&self, event: &QuotaEvent) -> Result<()> {
+        let payload = serde_json::to_value(event)
+            .map_err(|e| narayana_core::Error::Serialization(e.to_string()))?;
+        self.manager
+            .publish_event(&self.actor_id, &self.auth_token, event.kind.event_name(), payload)
+            .await
+    }
+}
@@ -1,5 +1,6 @@
-// Rate limiting for webhook deliveries
+// Rate limiting for webhook deliveries, and per-actor publish quotas
 
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -83,6 +84,11 @@ impl SubscriptionRateLimiter {
         Duration::ZERO
     }
 
+    /// Forget a removed subscription's deliveries
+    pub async fn remove(&self, subscription_id: &str) {
+        self.deliveries.write().await.remove(subscription_id);
+    }
+
     /// Clean up old entries (call periodically)
     pub async fn cleanup(&self) {
         let mut deliveries = self.deliveries.write().await;
@@ -104,3 +110,261 @@ impl Default for SubscriptionRateLimiter {
 
 
 
+
+/// Highest sustained publish rate accepted (events per second)
+const MAX_EVENTS_PER_SECOND: f64 = 1_000_000.0;
+/// Stored bytes are recounted from retained events at most this often per actor
+const RECOUNT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Limits on what one source actor may publish
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ActorQuota {
+    /// Sustained publish rate in events per second
+    pub events_per_second: Option<f64>,
+    /// Events that may be published at once above the sustained rate
+    /// (one second's worth by default)
+    pub burst: Option<u32>,
+    /// Payload bytes the actor's retained events may take up
+    pub max_stored_bytes: Option<u64>,
+}
+
+impl ActorQuota {
+    /// Check the limits are usable
+    pub fn validate(&self) -> narayana_core::Result<()> {
+        let invalid = |message: &str| Err(narayana_core::Error::Storage(format!("Invalid actor quota: {}", message)));
+        if let Some(rate) = self.events_per_second {
+            if !rate.is_finite() || rate <= 0.0 || rate > MAX_EVENTS_PER_SECOND {
+                return invalid("events_per_second must be above 0 and at most 1000000");
+            }
+        } else if self.burst.is_some() {
+            return invalid("burst needs events_per_second");
+        }
+        if self.burst == Some(0) {
+            return invalid("burst must be at least 1");
+        }
+        if self.max_stored_bytes == Some(0) {
+            return invalid("max_stored_bytes must be at least 1");
+        }
+        Ok(())
+    }
+
+    /// Events the bucket holds when full
+    fn capacity(&self, rate: f64) -> f64 {
+        self.burst.map(f64::from).unwrap_or_else(|| rate.ceil()).max(1.0)
+    }
+}
+
+/// Why a publish was refused
+#[derive(Debug, Clone, PartialEq)]
+pub enum QuotaRefusal {
+    /// Over the publish rate; enough allowance is back after `retry_after`
+    RateLimited { retry_after: Duration },
+    /// A batch of more events than the burst allowance, which can never go through
+    BurstExceeded { events: usize, burst: u64 },
+    /// The events would take the actor's retained bytes past its limit
+    StorageFull { stored_bytes: u64, max_stored_bytes: u64 },
+}
+
+impl std::fmt::Display for QuotaRefusal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QuotaRefusal::RateLimited { retry_after } => {
+                write!(f, "Publish rate limit exceeded; retry in {} ms", retry_after.as_millis().max(1))
+            }
+            QuotaRefusal::BurstExceeded { events, burst } => {
+                write!(f, "Batch of {} events is larger than the publish burst of {}", events, burst)
+            }
+            QuotaRefusal::StorageFull { stored_bytes, max_stored_bytes } => {
+                write!(f, "Stored event quota exceeded: {} of {} bytes used", stored_bytes, max_stored_bytes)
+            }
+        }
+    }
+}
+
+/// An actor's quota and what is left of it
+#[derive(Debug, Clone, Serialize)]
+pub struct ActorQuotaStatus {
+    pub quota: ActorQuota,
+    /// Events that can be published right now
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub events_available: Option<u64>,
+    /// Payload bytes of the actor's retained events
+    pub stored_bytes: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stored_bytes_remaining: Option<u64>,
+    /// Publishes refused for going over the rate
+    pub rate_limited: u64,
+    /// Publishes refused for going over the stored bytes
+    pub storage_rejected: u64,
+}
+
+struct ActorUsage {
+    quota: ActorQuota,
+    /// Token bucket for the publish rate
+    tokens: f64,
+    refilled: Instant,
+    stored_bytes: u64,
+    counted_at: Option<Instant>,
+    rate_limited: u64,
+    storage_rejected: u64,
+}
+
+impl ActorUsage {
+    fn refill(&mut self, now: Instant) {
+        if let Some(rate) = self.quota.events_per_second {
+            let elapsed = now.duration_since(self.refilled).as_secs_f64();
+            self.tokens = (self.tokens + elapsed * rate).min(self.quota.capacity(rate));
+        }
+        self.refilled = now;
+    }
+}
+
+/// Publish quotas of source actors: a token bucket for the event rate (its
+/// size is the burst allowance) and a cap on the bytes of retained events.
+/// Actors without a quota are not limited or tracked.
+#[derive(Default)]
+pub struct ActorQuotaLimiter {
+    actors: parking_lot::Mutex<HashMap<String, ActorUsage>>,
+}
+
+impl ActorQuotaLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set or replace an actor's quota; a replaced quota keeps its usage
+    pub fn set_quota(&self, actor_id: &str, quota: ActorQuota) -> narayana_core::Result<()> {
+        quota.validate()?;
+        let now = Instant::now();
+        let mut actors = self.actors.lock();
+        match actors.get_mut(actor_id) {
+            Some(usage) => {
+                usage.refill(now);
+                if let Some(rate) = quota.events_per_second {
+                    usage.tokens = usage.tokens.min(quota.capacity(rate));
+                }
+                usage.quota = quota;
+            }
+            None => {
+                let tokens = quota.events_per_second.map_or(0.0, |rate| quota.capacity(rate));
+                actors.insert(actor_id.to_string(), ActorUsage {
+                    quota,
+                    tokens,
+                    refilled: now,
+                    stored_bytes: 0,
+                    counted_at: None,
+                    rate_limited: 0,
+                    storage_rejected: 0,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Drop an actor's quota; returns whether it had one
+    pub fn remove_quota(&self, actor_id: &str) -> bool {
+        self.actors.lock().remove(actor_id).is_some()
+    }
+
+    pub fn quota(&self, actor_id: &str) -> Option<ActorQuota> {
+        self.actors.lock().get(actor_id).map(|usage| usage.quota.clone())
+    }
+
+    /// Take `events` from the actor's rate allowance and add `bytes` to its
+    /// stored bytes, or neither if either limit would be passed
+    pub fn admit(&self, actor_id: &str, events: usize, bytes: u64) -> Result<(), QuotaRefusal> {
+        self.try_admit(actor_id, events, bytes, true)
+    }
+
+    /// `admit`, but when the stored bytes look full they are set from
+    /// `recount` and checked again, since retention may have dropped old
+    /// events. Recounts happen at most once per recount interval.
+    pub fn admit_or_recount(
+        &self,
+        actor_id: &str,
+        events: usize,
+        bytes: u64,
+        recount: impl FnOnce() -> u64,
+    ) -> Result<(), QuotaRefusal> {
+        match self.try_admit(actor_id, events, bytes, false) {
+            Err(QuotaRefusal::StorageFull { .. }) if self.recount_due(actor_id) => {
+                self.set_stored_bytes(actor_id, recount());
+                self.admit(actor_id, events, bytes)
+            }
+            Err(_) => self.admit(actor_id, events, bytes),
+            Ok(()) => Ok(()),
+        }
+    }
+
+    fn try_admit(&self, actor_id: &str, events: usize, bytes: u64, record_refusal: bool) -> Result<(), QuotaRefusal> {
+        let mut actors = self.actors.lock();
+        let Some(usage) = actors.get_mut(actor_id) else {
+            return Ok(());
+        };
+        usage.refill(Instant::now());
+        let count = events;
+        let events = events as f64;
+        if let Some(rate) = usage.quota.events_per_second {
+            let capacity = usage.quota.capacity(rate);
+            let refusal = if events > capacity {
+                Some(QuotaRefusal::BurstExceeded { events: count, burst: capacity as u64 })
+            } else if usage.tokens < events {
+                Some(QuotaRefusal::RateLimited { retry_after: Duration::from_secs_f64((events - usage.tokens) / rate) })
+            } else {
+                None
+            };
+            if let Some(refusal) = refusal {
+                usage.rate_limited += u64::from(record_refusal);
+                return Err(refusal);
+            }
+        }
+        if let Some(max_stored_bytes) = usage.quota.max_stored_bytes {
+            if usage.stored_bytes.saturating_add(bytes) > max_stored_bytes {
+                usage.storage_rejected += u64::from(record_refusal);
+                return Err(QuotaRefusal::StorageFull { stored_bytes: usage.stored_bytes, max_stored_bytes });
+            }
+        }
+        if usage.quota.events_per_second.is_some() {
+            usage.tokens -= events;
+        }
+        usage.stored_bytes = usage.stored_bytes.saturating_add(bytes);
+        Ok(())
+    }
+
+    /// Whether the actor's stored bytes are due a recount; marks it as done
+    fn recount_due(&self, actor_id: &str) -> bool {
+        let now = Instant::now();
+        let mut actors = self.actors.lock();
+        match actors.get_mut(actor_id) {
+            Some(usage) if usage.counted_at.is_none_or(|at| now.duration_since(at) >= RECOUNT_INTERVAL) => {
+                usage.counted_at = Some(now);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Set an actor's stored bytes from a count of its retained events
+    pub fn set_stored_bytes(&self, actor_id: &str, bytes: u64) {
+        if let Some(usage) = self.actors.lock().get_mut(actor_id) {
+            usage.stored_bytes = bytes;
+            usage.counted_at = Some(Instant::now());
+        }
+    }
+
+    /// The actor's quota and remaining allowance
+    pub fn status(&self, actor_id: &str) -> Option<ActorQuotaStatus> {
+        let mut actors = self.actors.lock();
+        let usage = actors.get_mut(actor_id)?;
+        usage.refill(Instant::now());
+        Some(ActorQuotaStatus {
+            quota: usage.quota.clone(),
+            events_available: usage.quota.events_per_second.map(|_| usage.tokens.floor() as u64),
+            stored_bytes: usage.stored_bytes,
+            stored_bytes_remaining: usage.quota.max_stored_bytes.map(|max| max.saturating_sub(usage.stored_bytes)),
+            rate_limited: usage.rate_limited,
+            storage_rejected: usage.storage_rejected,
+        })
+    }
+}
@@ -0,0 +1,210 @@
+// Registry persistence - actors, their tokens and subscriptions
+// Registry changes are journaled to a native_events stream and replayed when a
+// manager is created over the same event system, so actors and subscriptions
+// survive a restart as long as the event system's storage does. Actors are
+// journaled without their registration token and tokens only as SHA-256
+// hashes. System actors (metadata `system: true`) register themselves on
+// every start and are left out.
+//
+// All writes go through one writer task, so the journal keeps the order the
+// changes were made in, including token changes made from sync code.
+
+use crate::actor::{Actor, ActorId};
+use crate::auth::StoredToken;
+use crate::durable;
+use crate::subscriptions::{Subscription, SubscriptionId};
+use narayana_storage::native_events::{EventId, EventStream, NativeEventsSystem, StreamName};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot};
+
+const JOURNAL_STREAM: &str = "rde-registry";
+const JOURNAL_PAGE: usize = 1_000;
+/// Version written into every entry; entries from a newer version are skipped
+/// on replay, entries without one are version 1
+pub(crate) const FORMAT_VERSION: u64 = 1;
+
+/// Registry change, as journaled
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub(crate) enum JournalEntry {
+    /// Everything before this is superseded by the entries that follow
+    Snapshot,
+    Actor { actor: Actor },
+    /// The actor's full token list after a change
+    Tokens { actor_id: ActorId, tokens: Vec<StoredToken> },
+    Subscribed { subscription: Subscription },
+    Updated { id: SubscriptionId, config: Value },
+    Unsubscribed { id: SubscriptionId },
+}
+
+impl JournalEntry {
+    fn op(&self) -> &'static str {
+        match self {
+            JournalEntry::Snapshot => "snapshot",
+            JournalEntry::Actor { .. } => "actor",
+            JournalEntry::Tokens { .. } => "tokens",
+            JournalEntry::Subscribed { .. } => "subscribed",
+            JournalEntry::Updated { .. } => "updated",
+            JournalEntry::Unsubscribed { .. } => "unsubscribed",
+        }
+    }
+}
+
+/// Registry state rebuilt from the journal
+#[derive(Default)]
+pub(crate) struct Replayed {
+    pub(crate) actors: HashMap<ActorId, Actor>,
+    pub(crate) tokens: HashMap<ActorId, Vec<StoredToken>>,
+    pub(crate) subscriptions: HashMap<SubscriptionId, Subscription>,
+}
+
+enum Message {
+    Entry(JournalEntry),
+    /// Answered once every entry sent before it is written
+    Flush(oneshot::Sender<()>),
+}
+
+/// Journal of registry changes
+pub(crate) struct Registry {
+    native_events: Arc<NativeEventsSystem>,
+    writer: parking_lot::Mutex<Option<mpsc::UnboundedSender<Message>>>,
+}
+
+/// Whether an actor is registered by the server itself on every start
+pub(crate) fn is_system_actor(actor: &Actor) -> bool {
+    actor.metadata.get("system").and_then(|v| v.as_bool()).unwrap_or(false)
+}
+
+impl Registry {
+    pub(crate) fn new(native_events: Arc<NativeEventsSystem>) -> Self {
+        Self { native_events, writer: parking_lot::Mutex::new(None) }
+    }
+
+    /// Journal a change and wait until it is written
+    pub(crate) async fn record(&self, entry: JournalEntry) {
+        self.record_later(entry);
+        self.flush().await;
+    }
+
+    /// Journal a change without waiting (for sync callers); entries are still
+    /// written in the order they were recorded
+    pub(crate) fn record_later(&self, entry: JournalEntry) {
+        self.send(Message::Entry(entry));
+    }
+
+    /// Wait until everything recorded so far is written
+    pub(crate) async fn flush(&self) {
+        let (done, written) = oneshot::channel();
+        self.send(Message::Flush(done));
+        let _ = written.await;
+    }
+
+    fn send(&self, message: Message) {
+        let mut writer = self.writer.lock();
+        // The writer is started on first use, and again if its runtime went away
+        if writer.as_ref().is_none_or(|w| w.is_closed()) {
+            let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+                tracing::warn!("No async runtime, registry change not journaled");
+                return;
+            };
+            let (tx, rx) = mpsc::unbounded_channel();
+            runtime.spawn(write(self.native_events.clone(), rx));
+            *writer = Some(tx);
+        }
+        if let Some(ref writer) = *writer {
+            let _ = writer.send(message);
+        }
+    }
+
+    /// Rebuild the registry from the journal, from its last snapshot on
+    pub(crate) fn replay(&self) -> Replayed {
+        let stream = StreamName(JOURNAL_STREAM.to_string());
+        let mut replayed = Replayed::default();
+        let mut cursor = 0;
+        loop {
+            let page = self.native_events.read_stream(&stream, EventId(cursor), JOURNAL_PAGE);
+            let Some(last) = page.last().map(|e| e.id.0) else {
+                break;
+            };
+            for event in page {
+                let version = event.payload.get("version").and_then(|v| v.as_u64()).unwrap_or(1);
+                if version > FORMAT_VERSION {
+                    tracing::warn!("Skipping registry journal entry from newer format version {}", version);
+                    continue;
+                }
+                match serde_json::from_value(event.payload) {
+                    Ok(JournalEntry::Snapshot) => replayed = Replayed::default(),
+                    Ok(JournalEntry::Actor { actor }) => {
+                        replayed.actors.insert(actor.id.clone(), actor);
+                    }
+                    Ok(JournalEntry::Tokens { actor_id, tokens }) => {
+                        replayed.tokens.insert(actor_id, tokens);
+                    }
+                    Ok(JournalEntry::Subscribed { subscription }) => {
+                        replayed.subscriptions.insert(subscription.id.clone(), subscription);
+                    }
+                    Ok(JournalEntry::Updated { id, config }) => {
+                        if let Some(subscription) = replayed.subscriptions.get_mut(&id) {
+                            subscription.config = config;
+                        }
+                    }
+                    Ok(JournalEntry::Unsubscribed { id }) => {
+                        replayed.subscriptions.remove(&id);
+                    }
+                    Err(e) => tracing::warn!("Skipping unreadable registry journal entry: {}", e),
+                }
+            }
+            cursor = last + 1;
+        }
+        replayed
+    }
+}
+
+/// Write journal entries in the order they arrive
+async fn write(native_events: Arc<NativeEventsSystem>, mut messages: mpsc::UnboundedReceiver<Message>) {
+    let stream = StreamName(JOURNAL_STREAM.to_string());
+    // Nothing is evicted: a lost entry would lose an actor or subscription
+    let config = EventStream {
+        name: stream.clone(),
+        partitions: 1,
+        retention: None,
+        replication_factor: 1,
+        compression: true,
+        encryption: false,
+        max_size: None,
+        max_events: None,
+    };
+    if let Err(e) = native_events.create_stream(config).await {
+        if !e.to_string().contains("already exists") {
+            tracing::warn!("Failed to create stream: {}", e);
+        }
+    }
+
+    while let Some(message) = messages.recv().await {
+        let entry = match message {
+            Message::Entry(entry) => entry,
+            Message::Flush(done) => {
+                let _ = done.send(());
+                continue;
+            }
+        };
+        let op = entry.op();
+        let mut payload = match serde_json::to_value(&entry) {
+            Ok(payload) => payload,
+            Err(e) => {
+                tracing::warn!("Failed to serialize registry journal entry: {}", e);
+                continue;
+            }
+        };
+        if let Value::Object(ref mut fields) = payload {
+            fields.insert("version".to_string(), Value::from(FORMAT_VERSION));
+        }
+        // The registry itself stays in memory, so a lost entry only affects the next start
+        if let Err(e) = native_events.publish_event(durable::event(stream.clone(), op, payload)).await {
+            tracing::warn!("Failed to journal registry change: {}", e);
+        }
+    }
+}
@@ -0,0 +1,144 @@
+// Schema registry for encoded event payloads
+// Versioned Avro and Protobuf schemas per (namespaced) event name. Schemas are
+// compiled on registration, so a bad definition is refused up front.
+
+use crate::encoding::Codec;
+use narayana_core::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+/// Maximum versions kept per event and format
+const MAX_VERSIONS: usize = 100;
+/// Maximum serialized size of a schema definition
+const MAX_SCHEMA_SIZE: usize = 64 * 1024;
+
+/// Schema language
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SchemaFormat {
+    Avro,
+    Protobuf,
+}
+
+impl std::fmt::Display for SchemaFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchemaFormat::Avro => write!(f, "avro"),
+            SchemaFormat::Protobuf => write!(f, "protobuf"),
+        }
+    }
+}
+
+/// A registered schema version
+#[derive(Debug, Clone, Serialize)]
+pub struct RegisteredSchema {
+    /// Registry-wide id
+    pub id: u32,
+    pub event_name: String,
+    pub format: SchemaFormat,
+    /// Per event and format, starting at 1
+    pub version: u32,
+    pub definition: serde_json::Value,
+    pub registered_at: u64,
+    #[serde(skip)]
+    codec: Codec,
+}
+
+impl RegisteredSchema {
+    pub fn codec(&self) -> &Codec {
+        &self.codec
+    }
+}
+
+/// Registry of event payload schemas
+pub struct SchemaRegistry {
+    schemas: dashmap::DashMap<(String, SchemaFormat), Vec<Arc<RegisteredSchema>>>,
+    by_id: dashmap::DashMap<u32, Arc<RegisteredSchema>>,
+    next_id: AtomicU32,
+}
+
+impl SchemaRegistry {
+    pub fn new() -> Self {
+        Self {
+            schemas: dashmap::DashMap::new(),
+            by_id: dashmap::DashMap::new(),
+            next_id: AtomicU32::new(1),
+        }
+    }
+
+    /// Register a new schema version for an event
+    /// Re-registering the latest definition returns the existing version.
+    pub fn register(
+        &self,
+        event_name: &str,
+        format: SchemaFormat,
+        definition: serde_json::Value,
+    ) -> Result<Arc<RegisteredSchema>> {
+        let size = serde_json::to_string(&definition)
+            .map_err(|e| Error::Storage(format!("Failed to serialize schema: {}", e)))?
+            .len();
+        if size > MAX_SCHEMA_SIZE {
+            return Err(Error::Storage(format!(
+                "Schema too large: {} bytes (max: {} bytes)",
+                size, MAX_SCHEMA_SIZE
+            )));
+        }
+        let codec = Codec::compile(format, &definition)?;
+
+        let mut versions = self.schemas.entry((event_name.to_string(), format)).or_default();
+        if let Some(latest) = versions.last().filter(|latest| latest.definition == definition) {
+            return Ok(latest.clone());
+        }
+        if versions.len() >= MAX_VERSIONS {
+            return Err(Error::Storage(format!(
+                "Maximum schema versions ({}) reached for event",
+                MAX_VERSIONS
+            )));
+        }
+        let schema = Arc::new(RegisteredSchema {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            event_name: event_name.to_string(),
+            format,
+            version: versions.len() as u32 + 1,
+            definition,
+            registered_at: chrono::Utc::now().timestamp() as u64,
+            codec,
+        });
+        versions.push(schema.clone());
+        self.by_id.insert(schema.id, schema.clone());
+        Ok(schema)
+    }
+
+    /// Latest schema of `format` for an event
+    pub fn latest(&self, event_name: &str, format: SchemaFormat) -> Option<Arc<RegisteredSchema>> {
+        self.schemas
+            .get(&(event_name.to_string(), format))
+            .and_then(|versions| versions.last().cloned())
+    }
+
+    /// Specific version of an event's schema
+    pub fn version(&self, event_name: &str, format: SchemaFormat, version: u32) -> Option<Arc<RegisteredSchema>> {
+        self.schemas
+            .get(&(event_name.to_string(), format))
+            .and_then(|versions| versions.get((version as usize).checked_sub(1)?).cloned())
+    }
+
+    pub fn get(&self, id: u32) -> Option<Arc<RegisteredSchema>> {
+        self.by_id.get(&id).map(|schema| schema.clone())
+    }
+
+    /// All versions registered for an event, oldest first
+    pub fn versions(&self, event_name: &str, format: SchemaFormat) -> Vec<Arc<RegisteredSchema>> {
+        self.schemas
+            .get(&(event_name.to_string(), format))
+            .map(|versions| versions.clone())
+            .unwrap_or_default()
+    }
+}
+
+impl Default for SchemaRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
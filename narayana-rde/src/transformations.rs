@@ -1,39 +1,406 @@
-// Transformations using existing TransformEngine
+// Transformations - reshape payloads before delivery
+// A subscription's `pipeline` is an ordered list of stages run on each event
+// payload, after its filter and before its transport:
+//
+//   rename   {"fields": {"temp": "reading.celsius"}}   move values between paths
+//   flatten  {"separator": "_", "max_depth": 4}        nested objects to top-level keys
+//   project  {"fields": ["id", "reading.celsius"]}     keep only these paths
+//   enrich   {"fields": {"site": "plant-3"}, "overwrite": false}
+//                                                      add static values
+//   template {"target": "summary", "template": "{{ $.id }} at {{ reading.celsius }}C"}
+//                                                      render a string into a field
+//
+// Paths are dot-separated object keys, with an optional leading `$.`. The
+// output of the last stage then goes through `output_config` (the
+// TransformEngine) when that is set too. `dry_run` runs a config on a sample
+// payload and reports each stage's output.
 
 use crate::subscriptions::Subscription;
 use narayana_core::{TransformEngine, OutputConfig, Error, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+/// Most stages in a pipeline
+const MAX_STAGES: usize = 32;
+/// Longest template
+const MAX_TEMPLATE_LENGTH: usize = 4096;
+/// Deepest object `flatten` descends into when `max_depth` isn't set
+const DEFAULT_FLATTEN_DEPTH: usize = 16;
+
+/// One pipeline stage
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TransformStage {
+    /// Move each value from the key path to the value path
+    Rename { fields: BTreeMap<String, String> },
+    /// Replace nested objects with top-level keys joined by `separator`
+    Flatten {
+        #[serde(default = "default_separator")]
+        separator: String,
+        #[serde(default)]
+        max_depth: Option<usize>,
+    },
+    /// Keep only these paths
+    Project { fields: Vec<String> },
+    /// Set static values, leaving existing ones unless `overwrite`
+    Enrich {
+        fields: BTreeMap<String, Value>,
+        #[serde(default)]
+        overwrite: bool,
+    },
+    /// Set `target` to `template` with each `{{ path }}` replaced by its value
+    Template { target: String, template: String },
+}
+
+fn default_separator() -> String {
+    ".".to_string()
+}
+
+impl TransformStage {
+    pub fn name(&self) -> &'static str {
+        match self {
+            TransformStage::Rename { .. } => "rename",
+            TransformStage::Flatten { .. } => "flatten",
+            TransformStage::Project { .. } => "project",
+            TransformStage::Enrich { .. } => "enrich",
+            TransformStage::Template { .. } => "template",
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        let paths: Vec<&str> = match self {
+            TransformStage::Rename { fields } => fields.iter().flat_map(|(from, to)| [from.as_str(), to.as_str()]).collect(),
+            TransformStage::Flatten { separator, max_depth } => {
+                if separator.is_empty() {
+                    return Err(Error::Storage("flatten separator cannot be empty".to_string()));
+                }
+                if *max_depth == Some(0) {
+                    return Err(Error::Storage("flatten max_depth must be at least 1".to_string()));
+                }
+                Vec::new()
+            }
+            TransformStage::Project { fields } => fields.iter().map(String::as_str).collect(),
+            TransformStage::Enrich { fields, .. } => fields.keys().map(String::as_str).collect(),
+            TransformStage::Template { target, template } => {
+                if template.len() > MAX_TEMPLATE_LENGTH {
+                    return Err(Error::Storage(format!("template must be at most {} bytes", MAX_TEMPLATE_LENGTH)));
+                }
+                let mut paths = vec![target.as_str()];
+                paths.extend(placeholders(template)?);
+                paths
+            }
+        };
+        for path in paths {
+            parse_path(path)?;
+        }
+        Ok(())
+    }
+
+    /// Run the stage on a payload
+    pub fn apply(&self, mut payload: Value) -> Result<Value> {
+        match self {
+            TransformStage::Rename { fields } => {
+                let root = as_object(&mut payload, self)?;
+                // Take every source first so renames can swap fields
+                let moved: Vec<(&String, Value)> = fields
+                    .iter()
+                    .filter_map(|(from, to)| take_path(root, &parse_path(from).ok()?).map(|value| (to, value)))
+                    .collect();
+                for (to, value) in moved {
+                    set_path(root, &parse_path(to)?, value, true)?;
+                }
+            }
+            TransformStage::Flatten { separator, max_depth } => {
+                let root = as_object(&mut payload, self)?;
+                let mut flat = Map::new();
+                flatten_into(&mut flat, None, std::mem::take(root), separator, max_depth.unwrap_or(DEFAULT_FLATTEN_DEPTH));
+                *root = flat;
+            }
+            TransformStage::Project { fields } => {
+                let root = as_object(&mut payload, self)?;
+                let mut projected = Map::new();
+                for field in fields {
+                    let path = parse_path(field)?;
+                    if let Some(value) = get_path(root, &path) {
+                        set_path(&mut projected, &path, value.clone(), true)?;
+                    }
+                }
+                *root = projected;
+            }
+            TransformStage::Enrich { fields, overwrite } => {
+                let root = as_object(&mut payload, self)?;
+                for (field, value) in fields {
+                    set_path(root, &parse_path(field)?, value.clone(), *overwrite)?;
+                }
+            }
+            TransformStage::Template { target, template } => {
+                let root = as_object(&mut payload, self)?;
+                let rendered = render(template, root)?;
+                set_path(root, &parse_path(target)?, Value::String(rendered), true)?;
+            }
+        }
+        Ok(payload)
+    }
+}
+
+/// An ordered list of stages
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransformPipeline {
+    stages: Vec<TransformStage>,
+}
+
+impl TransformPipeline {
+    /// Parse and validate a list of stages
+    pub fn parse(stages: &Value) -> Result<Self> {
+        let stages: Vec<TransformStage> = serde_json::from_value(stages.clone())
+            .map_err(|e| Error::Storage(format!("Invalid pipeline: {}", e)))?;
+        if stages.len() > MAX_STAGES {
+            return Err(Error::Storage(format!("pipeline can have at most {} stages", MAX_STAGES)));
+        }
+        for (index, stage) in stages.iter().enumerate() {
+            stage.validate()
+                .map_err(|e| Error::Storage(format!("pipeline stage {} ({}): {}", index, stage.name(), e)))?;
+        }
+        Ok(Self { stages })
+    }
+
+    /// Parse the subscription's `pipeline` option; None when there is none
+    pub fn from_config(config: &Value) -> Result<Option<Self>> {
+        match config.get("pipeline") {
+            None | Some(Value::Null) => Ok(None),
+            Some(stages @ Value::Array(_)) => Self::parse(stages).map(Some),
+            Some(_) => Err(Error::Storage("pipeline must be an array of stages".to_string())),
+        }
+    }
+
+    pub fn stages(&self) -> &[TransformStage] {
+        &self.stages
+    }
+
+    /// Run every stage in order
+    pub fn apply(&self, payload: &Value) -> Result<Value> {
+        self.stages.iter().enumerate().try_fold(payload.clone(), |payload, (index, stage)| {
+            stage.apply(payload)
+                .map_err(|e| Error::Storage(format!("pipeline stage {} ({}): {}", index, stage.name(), e)))
+        })
+    }
+}
+
+/// Whether the subscription transforms its payloads at all
+pub fn has_transformation(subscription: &Subscription) -> bool {
+    ["pipeline", "output_config"]
+        .iter()
+        .any(|key| subscription.config.get(*key).is_some_and(|v| !v.is_null()))
+}
 
 /// Apply transformation to event payload
 pub fn apply_transformation(
     subscription: &Subscription,
     payload: &serde_json::Value,
 ) -> Result<serde_json::Value> {
-    // Check if transformation is configured
-    if let Some(output_config_json) = subscription.config.get("output_config") {
-        // SECURITY: Validate it's an object before deserialization
-        if !output_config_json.is_object() {
-            return Err(Error::Storage("output_config must be an object".to_string()));
-        }
-        
-        // SECURITY: Limit depth and size of config to prevent deserialization attacks
-        let config_str = serde_json::to_string(output_config_json)
-            .map_err(|e| Error::Storage(format!("Failed to serialize config: {}", e)))?;
-        if config_str.len() > 100_000 {
-            return Err(Error::Storage("output_config too large (max 100KB)".to_string()));
-        }
-        
-        // Parse OutputConfig from JSON
-        let output_config: OutputConfig = serde_json::from_value(output_config_json.clone())
-            .map_err(|e| Error::Storage(format!("Failed to parse output_config: {}", e)))?;
-        
-        // Apply transformation using existing TransformEngine
-        let transformed = TransformEngine::apply_config(payload.clone(), &output_config)
-            .map_err(|e| Error::Storage(format!("Transformation failed: {}", e)))?;
-        
-        Ok(transformed)
-    } else {
+    let payload = match TransformPipeline::from_config(&subscription.config)? {
+        Some(pipeline) => pipeline.apply(payload)?,
+        None => payload.clone(),
+    };
+    match parse_output_config(&subscription.config)? {
+        Some(output_config) => apply_output_config(payload, &output_config),
         // No transformation, return original
-        Ok(payload.clone())
+        None => Ok(payload),
     }
 }
 
+/// Parse the subscription's `output_config` option for the TransformEngine
+fn parse_output_config(config: &Value) -> Result<Option<OutputConfig>> {
+    // Check if transformation is configured
+    let Some(output_config_json) = config.get("output_config") else {
+        return Ok(None);
+    };
+    // SECURITY: Validate it's an object before deserialization
+    if !output_config_json.is_object() {
+        return Err(Error::Storage("output_config must be an object".to_string()));
+    }
+
+    // SECURITY: Limit depth and size of config to prevent deserialization attacks
+    let config_str = serde_json::to_string(output_config_json)
+        .map_err(|e| Error::Storage(format!("Failed to serialize config: {}", e)))?;
+    if config_str.len() > 100_000 {
+        return Err(Error::Storage("output_config too large (max 100KB)".to_string()));
+    }
+
+    // Parse OutputConfig from JSON
+    serde_json::from_value(output_config_json.clone())
+        .map(Some)
+        .map_err(|e| Error::Storage(format!("Failed to parse output_config: {}", e)))
+}
+
+fn apply_output_config(payload: Value, output_config: &OutputConfig) -> Result<Value> {
+    // Apply transformation using existing TransformEngine
+    TransformEngine::apply_config(payload, output_config)
+        .map_err(|e| Error::Storage(format!("Transformation failed: {}", e)))
+}
+
+/// Output of one step of a dry run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StepOutput {
+    /// Stage index, or None for `output_config`
+    pub index: Option<usize>,
+    /// Stage type, or "output_config"
+    pub step: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// What a subscription config does to a sample payload
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DryRun {
+    /// Each step until the first that fails
+    pub steps: Vec<StepOutput>,
+    /// Final output; None when a step failed
+    pub output: Option<Value>,
+    /// What would be delivered: the output, or the original payload when a
+    /// step failed (deliveries fall back to it)
+    pub delivered: Value,
+}
+
+/// Run a subscription config's `pipeline` and `output_config` on a sample
+/// payload, stage by stage. Errors when the config itself is invalid.
+pub fn dry_run(config: &Value, payload: &Value) -> Result<DryRun> {
+    let pipeline = TransformPipeline::from_config(config)?;
+    let output_config = parse_output_config(config)?;
+
+    let mut steps = Vec::new();
+    let mut current = payload.clone();
+    let stages = pipeline.as_ref().map_or(&[][..], |pipeline| pipeline.stages());
+    for (index, stage) in stages.iter().enumerate() {
+        match stage.apply(current.clone()) {
+            Ok(output) => {
+                steps.push(StepOutput { index: Some(index), step: stage.name().to_string(), output: Some(output.clone()), error: None });
+                current = output;
+            }
+            Err(e) => {
+                steps.push(StepOutput { index: Some(index), step: stage.name().to_string(), output: None, error: Some(e.to_string()) });
+                return Ok(DryRun { steps, output: None, delivered: payload.clone() });
+            }
+        }
+    }
+    if let Some(output_config) = output_config {
+        match apply_output_config(current, &output_config) {
+            Ok(output) => {
+                steps.push(StepOutput { index: None, step: "output_config".to_string(), output: Some(output.clone()), error: None });
+                current = output;
+            }
+            Err(e) => {
+                steps.push(StepOutput { index: None, step: "output_config".to_string(), output: None, error: Some(e.to_string()) });
+                return Ok(DryRun { steps, output: None, delivered: payload.clone() });
+            }
+        }
+    }
+    Ok(DryRun { steps, output: Some(current.clone()), delivered: current })
+}
+
+fn as_object<'a>(payload: &'a mut Value, stage: &TransformStage) -> Result<&'a mut Map<String, Value>> {
+    payload
+        .as_object_mut()
+        .ok_or_else(|| Error::Storage(format!("{} needs an object payload", stage.name())))
+}
+
+fn parse_path(path: &str) -> Result<Vec<&str>> {
+    let trimmed = path.strip_prefix("$.").unwrap_or(path);
+    let segments: Vec<&str> = trimmed.split('.').collect();
+    if segments.iter().any(|segment| segment.is_empty()) {
+        return Err(Error::Storage(format!("Invalid path '{}'", path)));
+    }
+    Ok(segments)
+}
+
+fn get_path<'a>(root: &'a Map<String, Value>, path: &[&str]) -> Option<&'a Value> {
+    let (last, parents) = path.split_last()?;
+    let mut object = root;
+    for segment in parents {
+        object = object.get(*segment)?.as_object()?;
+    }
+    object.get(*last)
+}
+
+fn take_path(root: &mut Map<String, Value>, path: &[&str]) -> Option<Value> {
+    let (last, parents) = path.split_last()?;
+    let mut object = root;
+    for segment in parents {
+        object = object.get_mut(*segment)?.as_object_mut()?;
+    }
+    object.remove(*last)
+}
+
+/// Set a value, creating intermediate objects; fails when a parent on the
+/// path holds something other than an object
+fn set_path(root: &mut Map<String, Value>, path: &[&str], value: Value, overwrite: bool) -> Result<()> {
+    let Some((last, parents)) = path.split_last() else {
+        return Ok(());
+    };
+    let mut object = root;
+    for segment in parents {
+        object = object
+            .entry(segment.to_string())
+            .or_insert_with(|| Value::Object(Map::new()))
+            .as_object_mut()
+            .ok_or_else(|| Error::Storage(format!("'{}' is not an object", segment)))?;
+    }
+    if overwrite || !object.contains_key(*last) {
+        object.insert(last.to_string(), value);
+    }
+    Ok(())
+}
+
+fn flatten_into(flat: &mut Map<String, Value>, prefix: Option<&str>, object: Map<String, Value>, separator: &str, depth: usize) {
+    for (key, value) in object {
+        let key = match prefix {
+            Some(prefix) => format!("{}{}{}", prefix, separator, key),
+            None => key,
+        };
+        match value {
+            Value::Object(nested) if depth > 0 && !nested.is_empty() => {
+                flatten_into(flat, Some(&key), nested, separator, depth - 1);
+            }
+            value => {
+                flat.insert(key, value);
+            }
+        }
+    }
+}
+
+/// The paths of a template's `{{ path }}` placeholders
+fn placeholders(template: &str) -> Result<Vec<&str>> {
+    let mut paths = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let end = rest[start..]
+            .find("}}")
+            .ok_or_else(|| Error::Storage("template has an unclosed '{{'".to_string()))?;
+        paths.push(rest[start + 2..start + end].trim());
+        rest = &rest[start + end + 2..];
+    }
+    Ok(paths)
+}
+
+/// Replace placeholders with their values: strings as they are, other values
+/// as JSON, missing fields as nothing
+fn render(template: &str, root: &Map<String, Value>) -> Result<String> {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    for path in placeholders(template)? {
+        let start = rest.find("{{").unwrap_or(rest.len());
+        rendered.push_str(&rest[..start]);
+        rest = &rest[start..];
+        rest = &rest[rest.find("}}").map_or(rest.len(), |end| end + 2)..];
+        match get_path(root, &parse_path(path)?) {
+            Some(Value::String(s)) => rendered.push_str(s),
+            Some(Value::Null) | None => {}
+            Some(value) => rendered.push_str(&value.to_string()),
+        }
+    }
+    rendered.push_str(rest);
+    Ok(rendered)
+}
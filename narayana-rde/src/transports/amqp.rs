@@ -0,0 +1,470 @@
+// AMQP transport - publishes subscribed events to a RabbitMQ exchange
+// Lets consumers that already sit behind RabbitMQ (or another AMQP 0-9-1
+// broker) bind queues to RDE events instead of running a webhook endpoint.
+// Routing keys come from the namespaced event name, so topic bindings like
+// "robot.*" or "*.collision" select events. Like MQTT, each subscription
+// names its own broker; connections are opened per subscription and reused
+// until they fail.
+
+use crate::encoding::EncodedPayload;
+use crate::subscriptions::{Subscription, SubscriptionId};
+use narayana_core::egress::{self, EgressSubsystem};
+use narayana_core::{Error, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Default delivery attempts per event (first try included)
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+const MAX_ATTEMPTS_LIMIT: u32 = 10;
+const MAX_DELAY: Duration = Duration::from_secs(30);
+/// Topic exchange every RabbitMQ virtual host declares
+const DEFAULT_EXCHANGE: &str = "amq.topic";
+/// Routing key used when a subscription sets none
+const DEFAULT_ROUTING_KEY: &str = "{actor}.{event}";
+/// AMQP short strings (exchange names, routing keys) are at most 255 bytes;
+/// RabbitMQ caps exchange names at 127
+const MAX_EXCHANGE_LEN: usize = 127;
+const MAX_ROUTING_KEY_LEN: usize = 255;
+const DEFAULT_VHOST: &str = "/";
+const DEFAULT_HEARTBEAT_SECS: u64 = 30;
+const MAX_CREDENTIAL_LEN: usize = 1024;
+
+/// How to reach a subscription's broker
+#[derive(Clone, PartialEq)]
+pub struct AmqpConnectOptions {
+    /// amqp://host[:5672] or amqps://host[:5671]
+    pub broker_url: String,
+    pub tls: bool,
+    pub vhost: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub heartbeat: Duration,
+}
+
+// SECURITY: Keep the password out of debug output and logs
+impl std::fmt::Debug for AmqpConnectOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AmqpConnectOptions")
+            .field("broker_url", &self.broker_url)
+            .field("tls", &self.tls)
+            .field("vhost", &self.vhost)
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "********"))
+            .field("heartbeat", &self.heartbeat)
+            .finish()
+    }
+}
+
+/// One message to publish
+#[derive(Debug, Clone, PartialEq)]
+pub struct AmqpMessage {
+    pub exchange: String,
+    pub routing_key: String,
+    pub payload: Vec<u8>,
+    pub content_type: String,
+    pub headers: Vec<(String, String)>,
+    /// Delivery mode 2: queues that are durable keep the message across broker restarts
+    pub persistent: bool,
+    /// Fail the publish when no queue is bound for the routing key
+    pub mandatory: bool,
+}
+
+/// An open broker connection
+#[async_trait::async_trait]
+pub trait AmqpClient: Send + Sync {
+    /// Publish and wait for the broker to confirm the message
+    async fn publish(&self, message: AmqpMessage) -> anyhow::Result<()>;
+}
+
+/// Opens broker connections on behalf of RDE (to keep the client library out
+/// of the delivery path and swappable)
+#[async_trait::async_trait]
+pub trait AmqpConnector: Send + Sync {
+    async fn connect(&self, options: &AmqpConnectOptions) -> anyhow::Result<Arc<dyn AmqpClient>>;
+}
+
+/// Delivery metrics for one AMQP subscription
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AmqpDeliveryStats {
+    /// Events confirmed by the broker
+    pub delivered: u64,
+    /// Events dropped after the last attempt failed
+    pub failed: u64,
+    /// Publish attempts, retries included
+    pub attempts: u64,
+    /// Connections opened to the broker
+    pub connects: u64,
+    pub last_routing_key: Option<String>,
+    pub last_error: Option<String>,
+}
+
+/// Broker, exchange and publish options of a subscription
+///
+/// Config: `broker_url` (required; amqp:// or amqps://, without credentials),
+/// `vhost` (default "/"), `exchange` (default "amq.topic"; "" is the default
+/// exchange), `routing_key` (default "{actor}.{event}"; `{actor}` and
+/// `{event}` are replaced from the published event), `persistent` (default
+/// true), `mandatory` (default false), `username` and `password`,
+/// `heartbeat_secs` (default 30) and `max_attempts` (default 3).
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AmqpTarget {
+    options: AmqpConnectOptions,
+    exchange: String,
+    routing_key: String,
+    persistent: bool,
+    mandatory: bool,
+    max_attempts: u32,
+}
+
+impl AmqpTarget {
+    /// Parse and validate a subscription's config
+    pub(crate) fn from_config(config: &Value) -> Result<Self> {
+        let broker_url = config.get("broker_url")
+            .and_then(|v| v.as_str())
+            .filter(|url| !url.is_empty())
+            .ok_or_else(|| Error::Storage("AMQP subscriptions require a broker_url in config".to_string()))?;
+        // SECURITY: Brokers are reached through the shared egress policy
+        let target = egress::check_url(EgressSubsystem::Rde, broker_url)?;
+        let tls = match target.scheme.as_str() {
+            "amqp" => false,
+            "amqps" => true,
+            other => return Err(Error::Storage(format!("AMQP broker_url must use amqp:// or amqps://, not {}://", other))),
+        };
+        // SECURITY: Credentials go in username/password, which stay out of logs
+        let authority = broker_url.split_once("://")
+            .and_then(|(_, rest)| rest.split(['/', '?', '#']).next())
+            .unwrap_or_default();
+        if authority.contains('@') {
+            return Err(Error::Storage("AMQP broker_url can't carry credentials; set username and password".to_string()));
+        }
+
+        let vhost = match config.get("vhost") {
+            None => DEFAULT_VHOST.to_string(),
+            Some(Value::String(vhost)) if !vhost.is_empty()
+                && vhost.len() <= MAX_ROUTING_KEY_LEN
+                && !vhost.chars().any(|c| c.is_control()) => vhost.clone(),
+            Some(_) => return Err(Error::Storage("AMQP vhost must be a non-empty string without control characters".to_string())),
+        };
+
+        let exchange = match config.get("exchange") {
+            None => DEFAULT_EXCHANGE.to_string(),
+            Some(Value::String(exchange)) if exchange.len() <= MAX_EXCHANGE_LEN
+                && exchange.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':')) => exchange.clone(),
+            Some(_) => return Err(Error::Storage(format!(
+                "AMQP exchange must be up to {} characters of [a-zA-Z0-9_.:-]",
+                MAX_EXCHANGE_LEN
+            ))),
+        };
+
+        let routing_key = match config.get("routing_key") {
+            None => DEFAULT_ROUTING_KEY.to_string(),
+            Some(Value::String(key)) if key.len() <= MAX_ROUTING_KEY_LEN
+                && !key.chars().any(|c| c.is_control()) => key.clone(),
+            Some(_) => return Err(Error::Storage(format!(
+                "AMQP routing_key must be a string of up to {} bytes without control characters (use {{actor}} and {{event}})",
+                MAX_ROUTING_KEY_LEN
+            ))),
+        };
+
+        let flag = |name: &str, default: bool| match config.get(name) {
+            None => Ok(default),
+            Some(Value::Bool(value)) => Ok(*value),
+            Some(_) => Err(Error::Storage(format!("AMQP {} must be a boolean", name))),
+        };
+        let persistent = flag("persistent", true)?;
+        let mandatory = flag("mandatory", false)?;
+
+        let credential = |name: &str| match config.get(name) {
+            None => Ok(None),
+            Some(Value::String(value)) if !value.is_empty()
+                && value.len() <= MAX_CREDENTIAL_LEN
+                && !value.chars().any(|c| c.is_control()) => Ok(Some(value.clone())),
+            // SECURITY: Don't echo credentials in errors
+            Some(_) => Err(Error::Storage(format!("AMQP {} must be a non-empty string without control characters", name))),
+        };
+        let username = credential("username")?;
+        let password = credential("password")?;
+        if password.is_some() && username.is_none() {
+            return Err(Error::Storage("AMQP password requires a username".to_string()));
+        }
+
+        let heartbeat_secs = match config.get("heartbeat_secs") {
+            None => DEFAULT_HEARTBEAT_SECS,
+            Some(value) => value.as_u64()
+                .filter(|secs| (5..=3600).contains(secs))
+                .ok_or_else(|| Error::Storage("AMQP heartbeat_secs must be between 5 and 3600".to_string()))?,
+        };
+
+        let max_attempts = config.get("max_attempts")
+            .and_then(|v| v.as_u64())
+            .map(|n| (n as u32).clamp(1, MAX_ATTEMPTS_LIMIT))
+            .unwrap_or(DEFAULT_MAX_ATTEMPTS);
+
+        Ok(Self {
+            options: AmqpConnectOptions {
+                broker_url: broker_url.to_string(),
+                tls,
+                vhost,
+                username,
+                password,
+                heartbeat: Duration::from_secs(heartbeat_secs),
+            },
+            exchange,
+            routing_key,
+            persistent,
+            mandatory,
+            max_attempts,
+        })
+    }
+
+    /// Routing key for a published event ("actor:event")
+    pub(crate) fn routing_key_for(&self, event_name: &str) -> String {
+        let (actor, event) = event_name.split_once(':').unwrap_or(("", event_name));
+        let mut key = self.routing_key
+            .replace("{actor}", actor)
+            .replace("{event}", event);
+        if key.len() > MAX_ROUTING_KEY_LEN {
+            let mut end = MAX_ROUTING_KEY_LEN;
+            while !key.is_char_boundary(end) {
+                end -= 1;
+            }
+            key.truncate(end);
+        }
+        key
+    }
+}
+
+/// Open broker connections, one per subscription
+pub struct AmqpConnections {
+    connector: Arc<dyn AmqpConnector>,
+    clients: dashmap::DashMap<SubscriptionId, Arc<dyn AmqpClient>>,
+}
+
+impl AmqpConnections {
+    pub fn new(connector: Arc<dyn AmqpConnector>) -> Self {
+        Self { connector, clients: dashmap::DashMap::new() }
+    }
+
+    /// The subscription's connection, opened on first use
+    async fn client(
+        &self,
+        subscription_id: &SubscriptionId,
+        options: &AmqpConnectOptions,
+        stats: &dashmap::DashMap<SubscriptionId, AmqpDeliveryStats>,
+    ) -> anyhow::Result<Arc<dyn AmqpClient>> {
+        if let Some(client) = self.clients.get(subscription_id) {
+            return Ok(client.clone());
+        }
+        let client = self.connector.connect(options).await?;
+        stats.entry(subscription_id.clone()).or_default().connects += 1;
+        self.clients.insert(subscription_id.clone(), client.clone());
+        Ok(client)
+    }
+
+    /// Close a subscription's connection; returns whether one was open
+    pub fn disconnect(&self, subscription_id: &SubscriptionId) -> bool {
+        self.clients.remove(subscription_id).is_some()
+    }
+
+    /// Open connections
+    pub fn len(&self) -> usize {
+        self.clients.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clients.is_empty()
+    }
+}
+
+/// Deliver an event to AMQP as JSON
+pub async fn deliver_amqp(
+    subscription: &Subscription,
+    event_name: &str,
+    payload: &Value,
+    connections: Option<Arc<AmqpConnections>>,
+    stats: &dashmap::DashMap<SubscriptionId, AmqpDeliveryStats>,
+) -> Result<()> {
+    let encoded = EncodedPayload::json(payload)?;
+    deliver_amqp_encoded(subscription, event_name, &encoded, connections, stats).await
+}
+
+/// Deliver an encoded event by publishing it to the subscription's exchange
+///
+/// Messages carry the content type as a property and the event name and
+/// subscription (plus the schema id and version for Avro and Protobuf) as
+/// headers. A publish the broker doesn't confirm drops the connection and
+/// retries over a new one with exponential backoff.
+pub async fn deliver_amqp_encoded(
+    subscription: &Subscription,
+    event_name: &str,
+    encoded: &EncodedPayload,
+    connections: Option<Arc<AmqpConnections>>,
+    stats: &dashmap::DashMap<SubscriptionId, AmqpDeliveryStats>,
+) -> Result<()> {
+    let Some(connections) = connections else {
+        return Err(Error::Storage("AMQP transport not available".to_string()));
+    };
+    let target = AmqpTarget::from_config(&subscription.config)?;
+    let mut headers = vec![
+        ("narayana-event".to_string(), event_name.to_string()),
+        ("narayana-subscription".to_string(), subscription.id.0.clone()),
+    ];
+    if let Some(schema) = &encoded.schema {
+        headers.push(("narayana-schema-id".to_string(), schema.id.to_string()));
+        headers.push(("narayana-schema-version".to_string(), schema.version.to_string()));
+    }
+    let message = AmqpMessage {
+        exchange: target.exchange.clone(),
+        routing_key: target.routing_key_for(event_name),
+        payload: encoded.data.clone(),
+        content_type: encoded.content_type().to_string(),
+        headers,
+        persistent: target.persistent,
+        mandatory: target.mandatory,
+    };
+
+    let mut delay = Duration::from_millis(100);
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let outcome = match connections.client(&subscription.id, &target.options, stats).await {
+            Ok(client) => client.publish(message.clone()).await,
+            Err(e) => Err(e),
+        };
+        {
+            let mut entry = stats.entry(subscription.id.clone()).or_default();
+            entry.attempts += 1;
+            entry.last_routing_key = Some(message.routing_key.clone());
+            match &outcome {
+                Ok(()) => {
+                    entry.delivered += 1;
+                    entry.last_error = None;
+                    return Ok(());
+                }
+                Err(e) => entry.last_error = Some(e.to_string()),
+            }
+        }
+        // Reconnect on the next attempt
+        connections.disconnect(&subscription.id);
+        if attempt >= target.max_attempts {
+            stats.entry(subscription.id.clone()).or_default().failed += 1;
+            return Err(Error::Storage(format!(
+                "Failed to publish event to AMQP after {} attempt(s)",
+                attempt
+            )));
+        }
+        tracing::warn!("AMQP delivery attempt {} failed, retrying", attempt);
+        tokio::time::sleep(delay).await;
+        delay = (delay * 2).min(MAX_DELAY); // Exponential backoff with cap
+    }
+}
+
+/// Connector backed by lapin
+///
+/// Each connection opens one channel in confirm mode, so a publish returns
+/// once the broker has taken responsibility for the message.
+#[cfg(feature = "amqp")]
+pub struct LapinConnector {
+    pub connect_timeout: Duration,
+}
+
+#[cfg(feature = "amqp")]
+impl Default for LapinConnector {
+    fn default() -> Self {
+        Self { connect_timeout: Duration::from_secs(10) }
+    }
+}
+
+#[cfg(feature = "amqp")]
+#[async_trait::async_trait]
+impl AmqpConnector for LapinConnector {
+    async fn connect(&self, options: &AmqpConnectOptions) -> anyhow::Result<Arc<dyn AmqpClient>> {
+        use lapin::options::ConfirmSelectOptions;
+        use lapin::uri::{AMQPAuthority, AMQPQueryString, AMQPScheme, AMQPUri, AMQPUserInfo};
+        use lapin::{Connection, ConnectionProperties};
+
+        // SECURITY: Resolve and vet the broker's addresses, then connect to a vetted
+        // one (TLS connects by host name so the certificate can be verified)
+        let policy = egress::global();
+        let target = policy.check_url(EgressSubsystem::Rde, &options.broker_url)?;
+        let addrs: Vec<std::net::SocketAddr> = tokio::net::lookup_host((target.host.as_str(), target.port))
+            .await?
+            .collect();
+        let ips: Vec<std::net::IpAddr> = addrs.iter().map(|a| a.ip()).collect();
+        policy.check_resolved(EgressSubsystem::Rde, &target, &ips)?;
+        let host = match (options.tls, addrs.first()) {
+            (false, Some(addr)) => addr.ip().to_string(),
+            _ => target.host.clone(),
+        };
+
+        let mut userinfo = AMQPUserInfo::default();
+        if let Some(username) = &options.username {
+            userinfo.username = username.clone();
+            userinfo.password = options.password.clone().unwrap_or_default();
+        }
+        let uri = AMQPUri {
+            scheme: if options.tls { AMQPScheme::AMQPS } else { AMQPScheme::AMQP },
+            authority: AMQPAuthority { userinfo, host, port: target.port },
+            vhost: options.vhost.clone(),
+            query: AMQPQueryString {
+                heartbeat: Some(options.heartbeat.as_secs().min(u16::MAX as u64) as u16),
+                ..Default::default()
+            },
+        };
+
+        let open = async {
+            let connection = Connection::connect_uri(uri, ConnectionProperties::default()).await?;
+            let channel = connection.create_channel().await?;
+            channel.confirm_select(ConfirmSelectOptions::default()).await?;
+            Ok::<_, lapin::Error>((connection, channel))
+        };
+        let (connection, channel) = tokio::time::timeout(self.connect_timeout, open)
+            .await
+            .map_err(|_| anyhow::anyhow!("AMQP broker did not accept the connection in time"))??;
+        Ok(Arc::new(LapinClient { _connection: connection, channel }))
+    }
+}
+
+/// lapin closes the connection once the client (its last handle) is dropped
+#[cfg(feature = "amqp")]
+struct LapinClient {
+    _connection: lapin::Connection,
+    channel: lapin::Channel,
+}
+
+#[cfg(feature = "amqp")]
+#[async_trait::async_trait]
+impl AmqpClient for LapinClient {
+    async fn publish(&self, message: AmqpMessage) -> anyhow::Result<()> {
+        use lapin::options::BasicPublishOptions;
+        use lapin::publisher_confirm::Confirmation;
+        use lapin::types::{AMQPValue, FieldTable, LongString, ShortString};
+        use lapin::BasicProperties;
+
+        let mut headers = FieldTable::default();
+        for (name, value) in message.headers {
+            headers.insert(ShortString::from(name), AMQPValue::LongString(LongString::from(value)));
+        }
+        let properties = BasicProperties::default()
+            .with_content_type(ShortString::from(message.content_type))
+            .with_delivery_mode(if message.persistent { 2 } else { 1 })
+            .with_headers(headers);
+        let confirm = self.channel
+            .basic_publish(
+                &message.exchange,
+                &message.routing_key,
+                BasicPublishOptions { mandatory: message.mandatory, ..Default::default() },
+                &message.payload,
+                properties,
+            )
+            .await?
+            .await?;
+        match confirm {
+            Confirmation::Ack(None) | Confirmation::NotRequested => Ok(()),
+            Confirmation::Ack(Some(_)) => Err(anyhow::anyhow!("AMQP message was unroutable: no queue bound for {}", message.routing_key)),
+            Confirmation::Nack(_) => Err(anyhow::anyhow!("AMQP broker rejected the message")),
+        }
+    }
+}
@@ -1,123 +1,236 @@
 // HTTP webhook transport
+// Bodies are signed with the subscription's HMAC secret, and deliveries can
+// authenticate to the receiver with a TLS client certificate.
 
+use crate::cloudevents::{self, CloudEvent};
+use crate::encoding::EncodedPayload;
 use crate::subscriptions::Subscription;
+use narayana_core::egress::{self, EgressSubsystem};
 use narayana_core::{Error, Result};
 use reqwest::Client;
-use serde_json::json;
+use serde_json::{json, Value};
 
-/// Deliver event via HTTP webhook
-pub async fn deliver_webhook(
-    subscription: &Subscription,
-    payload: &serde_json::Value,
-) -> Result<()> {
-    let webhook_url = subscription.config.get("webhook_url")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| Error::Storage("webhook_url not configured".to_string()))?;
+/// Signature header: `sha256=<hex HMAC-SHA256>` of the body, or of
+/// `"{timestamp}.{body}"` for timestamped signatures
+pub const SIGNATURE_HEADER: &str = "X-Narayana-Signature";
+/// Unix seconds a timestamped signature was computed at
+pub const TIMESTAMP_HEADER: &str = "X-Narayana-Timestamp";
 
-    // Validate URL
-    let url = reqwest::Url::parse(webhook_url)
-        .map_err(|e| Error::Storage(format!("Invalid webhook URL: {}", e)))?;
-    
-    // Security: Only allow http/https
-    if url.scheme() != "http" && url.scheme() != "https" {
-        return Err(Error::Storage("Webhook URL must use http or https".to_string()));
-    }
-    
-    // Security: Prevent SSRF attacks - block localhost and private IPs
-    // Parse IP address properly to prevent encoding bypasses
-    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
-    
-    if let Some(host) = url.host_str() {
-        let host_lower = host.to_lowercase();
-        
-        // Block localhost domain variations
-        if host_lower == "localhost" {
-            return Err(Error::Storage("Webhook URL cannot point to localhost (SSRF protection)".to_string()));
+const MAX_PEM_LEN: usize = 64 * 1024;
+
+/// What a webhook signature covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignatureScheme {
+    /// The body alone
+    #[default]
+    Body,
+    /// `"{timestamp}.{body}"`, so receivers can refuse replayed deliveries
+    Timestamped,
+}
+
+/// Signing and TLS client authentication of a webhook subscription
+///
+/// Config: `webhook_secret` signs each delivery into `X-Narayana-Signature`,
+/// `webhook_signature` picks what the signature covers ("body" by default, or
+/// "timestamped"). `webhook_client_cert` and `webhook_client_key` (PEM, the key
+/// in PKCS#8) authenticate deliveries with mTLS, and `webhook_ca_cert` (PEM)
+/// trusts a private CA for the receiver's certificate.
+pub(crate) struct WebhookSecurity {
+    secret: Option<String>,
+    scheme: SignatureScheme,
+    identity: Option<reqwest::Identity>,
+    ca_cert: Option<reqwest::Certificate>,
+}
+
+impl WebhookSecurity {
+    pub(crate) fn from_config(config: &Value) -> Result<Self> {
+        let secret = match config.get("webhook_secret") {
+            None | Some(Value::Null) => None,
+            Some(Value::String(secret)) => {
+                validate_secret(secret)?;
+                Some(secret.clone())
+            }
+            Some(_) => return Err(Error::Storage("webhook_secret must be a string".to_string())),
+        };
+        let scheme = match config.get("webhook_signature") {
+            None | Some(Value::Null) => SignatureScheme::default(),
+            Some(value) => serde_json::from_value(value.clone())
+                .map_err(|_| Error::Storage(format!("Unsupported webhook_signature: {} (use body or timestamped)", value)))?,
+        };
+        if scheme == SignatureScheme::Timestamped && secret.is_none() {
+            return Err(Error::Storage("webhook_signature needs a webhook_secret".to_string()));
         }
-        
-        // Try to parse as IP address
-        if let Ok(ip) = host.parse::<IpAddr>() {
-            match ip {
-                IpAddr::V4(ipv4) => {
-                    let octets = ipv4.octets();
-                    // Block 127.x.x.x (localhost)
-                    if octets[0] == 127 {
-                        return Err(Error::Storage("Webhook URL cannot point to localhost (SSRF protection)".to_string()));
-                    }
-                    // Block 0.0.0.0
-                    if octets == [0, 0, 0, 0] {
-                        return Err(Error::Storage("Webhook URL cannot point to 0.0.0.0 (SSRF protection)".to_string()));
-                    }
-                    // Block 10.x.x.x (private)
-                    if octets[0] == 10 {
-                        return Err(Error::Storage("Webhook URL cannot point to private network (SSRF protection)".to_string()));
-                    }
-                    // Block 192.168.x.x (private)
-                    if octets[0] == 192 && octets[1] == 168 {
-                        return Err(Error::Storage("Webhook URL cannot point to private network (SSRF protection)".to_string()));
-                    }
-                    // Block 172.16-31.x.x (private)
-                    if octets[0] == 172 && octets[1] >= 16 && octets[1] <= 31 {
-                        return Err(Error::Storage("Webhook URL cannot point to private network (SSRF protection)".to_string()));
-                    }
-                }
-                IpAddr::V6(ipv6) => {
-                    let segments = ipv6.segments();
-                    // Block ::1 (localhost)
-                    if segments == [0, 0, 0, 0, 0, 0, 0, 1] {
-                        return Err(Error::Storage("Webhook URL cannot point to localhost (SSRF protection)".to_string()));
-                    }
-                    // Block :: (unspecified)
-                    if segments == [0, 0, 0, 0, 0, 0, 0, 0] {
-                        return Err(Error::Storage("Webhook URL cannot point to unspecified address (SSRF protection)".to_string()));
-                    }
-                    // Block link-local (fe80::/10)
-                    if segments[0] == 0xfe80 && (segments[1] & 0xc000) == 0x8000 {
-                        return Err(Error::Storage("Webhook URL cannot point to link-local address (SSRF protection)".to_string()));
-                    }
-                    // Block unique-local (fc00::/7)
-                    if (segments[0] & 0xfe00) == 0xfc00 {
-                        return Err(Error::Storage("Webhook URL cannot point to unique-local address (SSRF protection)".to_string()));
-                    }
-                }
+
+        let identity = match (pem(config, "webhook_client_cert")?, pem(config, "webhook_client_key")?) {
+            (None, None) => None,
+            (Some(cert), Some(key)) => Some(
+                reqwest::Identity::from_pkcs8_pem(cert.as_bytes(), key.as_bytes())
+                    .map_err(|_| Error::Storage("Invalid webhook client certificate or key (PEM, PKCS#8 key)".to_string()))?,
+            ),
+            _ => return Err(Error::Storage("webhook_client_cert and webhook_client_key go together".to_string())),
+        };
+        let ca_cert = pem(config, "webhook_ca_cert")?
+            .map(|ca| reqwest::Certificate::from_pem(ca.as_bytes()))
+            .transpose()
+            .map_err(|_| Error::Storage("Invalid webhook_ca_cert (PEM)".to_string()))?;
+        if identity.is_some() || ca_cert.is_some() {
+            let https = config.get("webhook_url")
+                .and_then(|v| v.as_str())
+                .is_some_and(|url| url.get(..8).is_some_and(|scheme| scheme.eq_ignore_ascii_case("https://")));
+            if !https {
+                return Err(Error::Storage("Webhook TLS certificates need an https webhook_url".to_string()));
             }
         }
-        
-        // Also check for encoded IPs in domain (e.g., 127.1, 0x7f.0.0.1)
-        // Block if it looks like an encoded IP
-        if host_lower.starts_with("127.") ||
-           host_lower.starts_with("192.168.") ||
-           host_lower.starts_with("10.") ||
-           (host_lower.starts_with("172.") && host_lower.len() > 4) {
-            return Err(Error::Storage("Webhook URL cannot point to localhost or private network (SSRF protection)".to_string()));
+
+        Ok(Self { secret, scheme, identity, ca_cert })
+    }
+
+    /// Signature headers for a body sent at `timestamp` (Unix seconds)
+    fn headers(&self, body: &[u8], timestamp: i64) -> Result<Vec<(&'static str, String)>> {
+        let Some(secret) = &self.secret else {
+            return Ok(Vec::new());
+        };
+        Ok(match self.scheme {
+            SignatureScheme::Body => vec![(SIGNATURE_HEADER, generate_hmac(body, secret)?)],
+            SignatureScheme::Timestamped => vec![
+                (SIGNATURE_HEADER, generate_hmac(&timestamped(timestamp, body), secret)?),
+                (TIMESTAMP_HEADER, timestamp.to_string()),
+            ],
+        })
+    }
+
+    /// Add the client certificate and trusted CA to a client
+    fn configure(&self, mut builder: reqwest::ClientBuilder) -> reqwest::ClientBuilder {
+        if let Some(identity) = &self.identity {
+            builder = builder.identity(identity.clone());
+        }
+        if let Some(ca_cert) = &self.ca_cert {
+            builder = builder.add_root_certificate(ca_cert.clone());
         }
+        builder
     }
-    
-    // Validate URL length
-    if webhook_url.len() > 2048 {
-        return Err(Error::Storage("Webhook URL too long (max 2048 chars)".to_string()));
+}
+
+/// Read an optional PEM block from the config
+fn pem<'a>(config: &'a Value, key: &str) -> Result<Option<&'a str>> {
+    match config.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(pem)) if pem.contains("-----BEGIN ") && pem.len() <= MAX_PEM_LEN => Ok(Some(pem.as_str())),
+        Some(_) => Err(Error::Storage(format!("{} must be a PEM string (max {} bytes)", key, MAX_PEM_LEN))),
     }
+}
 
-    // Reuse client with timeout and connection pooling
-    // Note: In production, this should be a shared static client
-    let client = Client::builder()
-        .timeout(std::time::Duration::from_secs(30))
-        .build()
-        .map_err(|e| Error::Storage(format!("Failed to create HTTP client: {}", e)))?;
-    
+/// Message signed by timestamped signatures
+fn timestamped(timestamp: i64, body: &[u8]) -> Vec<u8> {
+    let mut message = format!("{}.", timestamp).into_bytes();
+    message.extend_from_slice(body);
+    message
+}
+
+/// Check a received `X-Narayana-Signature`
+///
+/// Pass the `X-Narayana-Timestamp` value for timestamped signatures; those
+/// more than `tolerance_secs` away from now are refused.
+pub fn verify_signature(secret: &str, body: &[u8], timestamp: Option<i64>, signature: &str, tolerance_secs: u64) -> bool {
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+
+    let Some(expected) = signature.strip_prefix("sha256=").and_then(|hex_sig| hex::decode(hex_sig).ok()) else {
+        return false;
+    };
+    let message = match timestamp {
+        Some(timestamp) => {
+            if chrono::Utc::now().timestamp().abs_diff(timestamp) > tolerance_secs {
+                return false;
+            }
+            timestamped(timestamp, body)
+        }
+        None => body.to_vec(),
+    };
+    let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(secret.as_bytes()) else {
+        return false;
+    };
+    mac.update(&message);
+    mac.verify_slice(&expected).is_ok()
+}
+
+/// Deliver event via HTTP webhook
+pub async fn deliver_webhook(
+    subscription: &Subscription,
+    payload: &serde_json::Value,
+) -> Result<()> {
     // Build webhook payload
     let webhook_payload = json!({
         "event_name": subscription.event_name.to_string(),
         "payload": payload,
         "timestamp": chrono::Utc::now().timestamp(),
     });
+    let body = serde_json::to_vec(&webhook_payload)
+        .map_err(|e| Error::Storage(format!("Failed to serialize payload: {}", e)))?;
+    send_webhook(subscription, body, "application/json", Vec::new()).await
+}
 
-    // Add HMAC signature if secret is configured
-    let mut request = client.post(webhook_url).json(&webhook_payload);
-    
-    if let Some(secret) = subscription.config.get("webhook_secret").and_then(|v| v.as_str()) {
-        let signature = generate_hmac(&webhook_payload, secret)?;
-        request = request.header("X-Narayana-Signature", signature);
+/// Deliver an encoded event via HTTP webhook
+///
+/// JSON payloads keep the usual envelope. Avro/Protobuf payloads are sent
+/// as the raw body, with the envelope fields and schema in headers.
+pub async fn deliver_webhook_encoded(
+    subscription: &Subscription,
+    event_name: &str,
+    payload: &serde_json::Value,
+    encoded: &EncodedPayload,
+) -> Result<()> {
+    let Some(schema) = &encoded.schema else {
+        return deliver_webhook(subscription, payload).await;
+    };
+    let headers = vec![
+        ("X-Narayana-Event", event_name.to_string()),
+        ("X-Narayana-Timestamp", chrono::Utc::now().timestamp().to_string()),
+        ("X-Narayana-Schema-Id", schema.id.to_string()),
+        ("X-Narayana-Schema-Version", schema.version.to_string()),
+    ];
+    send_webhook(subscription, encoded.data.clone(), encoded.content_type(), headers).await
+}
+
+/// Deliver a CloudEvent via HTTP webhook, in structured mode
+pub async fn deliver_cloudevent(subscription: &Subscription, event: &CloudEvent) -> Result<()> {
+    let body = serde_json::to_vec(event)
+        .map_err(|e| Error::Storage(format!("Failed to serialize CloudEvent: {}", e)))?;
+    send_webhook(subscription, body, cloudevents::CONTENT_TYPE, Vec::new()).await
+}
+
+/// Send a webhook body with signature, custom headers and retries
+async fn send_webhook(
+    subscription: &Subscription,
+    body: Vec<u8>,
+    content_type: &str,
+    extra_headers: Vec<(&'static str, String)>,
+) -> Result<()> {
+    let webhook_url = subscription.config.get("webhook_url")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| Error::Storage("webhook_url not configured".to_string()))?;
+    let security = WebhookSecurity::from_config(&subscription.config)?;
+
+    // Security: Prevent SSRF attacks - the shared egress policy checks scheme, allow/deny
+    // lists and private addresses, and pins the client to the vetted resolved addresses
+    let client = egress::pinned_client(
+        EgressSubsystem::Rde,
+        webhook_url,
+        security.configure(Client::builder().timeout(std::time::Duration::from_secs(30))),
+    ).await?;
+
+    // Signed once, so retries carry the same signature
+    let signature_headers = security.headers(&body, chrono::Utc::now().timestamp())?;
+    let mut request = client.post(webhook_url).header("Content-Type", content_type);
+    for (name, value) in extra_headers {
+        // A timestamped signature's timestamp wins
+        if !signature_headers.iter().any(|(signed, _)| *signed == name) {
+            request = request.header(name, value);
+        }
+    }
+    for (name, value) in signature_headers {
+        request = request.header(name, value);
     }
 
     // Add custom headers if configured
@@ -136,6 +249,7 @@ pub async fn deliver_webhook(
             let key_lower = key.to_lowercase();
             if key_lower == "host" || 
                key_lower == "content-length" || 
+               key_lower == "content-type" ||
                key_lower.starts_with("x-forwarded") ||
                key_lower.starts_with("x-real-ip") {
                 continue; // Block dangerous headers
@@ -154,6 +268,7 @@ pub async fn deliver_webhook(
             }
         }
     }
+    let request = request.body(body);
 
     // Send webhook with retry logic
     let mut retries = 3;
@@ -161,50 +276,9 @@ pub async fn deliver_webhook(
     const MAX_DELAY: std::time::Duration = std::time::Duration::from_secs(30);
 
     loop {
-        // Clone request safely
-        let request_clone = match request.try_clone() {
-            Some(req) => req,
-            None => {
-                // If can't clone, create new request
-                let mut new_request = client.post(webhook_url).json(&webhook_payload);
-                if let Some(secret) = subscription.config.get("webhook_secret").and_then(|v| v.as_str()) {
-                    if let Ok(signature) = generate_hmac(&webhook_payload, secret) {
-                        new_request = new_request.header("X-Narayana-Signature", signature);
-                    }
-                }
-                // SECURITY: Re-validate headers when recreating request
-                if let Some(headers) = subscription.config.get("headers").and_then(|v| v.as_object()) {
-                    for (key, value) in headers {
-                        // Validate header name (prevent injection)
-                        if key.is_empty() || key.len() > 256 {
-                            continue;
-                        }
-                        if key.chars().any(|c| c.is_control() || c == ':') {
-                            continue;
-                        }
-                        // Block dangerous headers
-                        let key_lower = key.to_lowercase();
-                        if key_lower == "host" || 
-                           key_lower == "content-length" || 
-                           key_lower.starts_with("x-forwarded") ||
-                           key_lower.starts_with("x-real-ip") {
-                            continue;
-                        }
-                        
-                        if let Some(header_value) = value.as_str() {
-                            if header_value.len() > 4096 {
-                                continue;
-                            }
-                            if header_value.chars().any(|c| c == '\r' || c == '\n') {
-                                continue;
-                            }
-                            new_request = new_request.header(key, header_value);
-                        }
-                    }
-                }
-                new_request
-            }
-        };
+        // Byte bodies can always be cloned
+        let request_clone = request.try_clone()
+            .ok_or_else(|| Error::Storage("Failed to build webhook request".to_string()))?;
         
         match request_clone.send().await {
             Ok(response) => {
@@ -230,13 +304,27 @@ pub async fn deliver_webhook(
 }
 
 /// Generate HMAC signature
-fn generate_hmac(payload: &serde_json::Value, secret: &str) -> Result<String> {
+fn generate_hmac(body: &[u8], secret: &str) -> Result<String> {
     use hmac::{Hmac, Mac};
     use sha2::Sha256;
 
     type HmacSha256 = Hmac<Sha256>;
 
-    // Validate secret
+    validate_secret(secret)?;
+    
+    // SECURITY: Ensure secret never appears in error messages or logs
+    // (Already handled - we only return generic errors)
+
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes())
+        .map_err(|e| Error::Storage(format!("HMAC error: {}", e)))?;
+    mac.update(body);
+    let result = mac.finalize();
+    let signature = hex::encode(result.into_bytes());
+    Ok(format!("sha256={}", signature))
+}
+
+/// Validate an HMAC secret
+fn validate_secret(secret: &str) -> Result<()> {
     if secret.is_empty() {
         return Err(Error::Storage("HMAC secret cannot be empty".to_string()));
     }
@@ -247,17 +335,6 @@ fn generate_hmac(payload: &serde_json::Value, secret: &str) -> Result<String> {
     if secret.chars().any(|c| c.is_control()) {
         return Err(Error::Storage("HMAC secret cannot contain control characters".to_string()));
     }
-    
-    // SECURITY: Ensure secret never appears in error messages or logs
-    // (Already handled - we only return generic errors)
-
-    let payload_str = serde_json::to_string(payload)
-        .map_err(|e| Error::Storage(format!("Failed to serialize payload: {}", e)))?;
-    let mut mac = HmacSha256::new_from_slice(secret.as_bytes())
-        .map_err(|e| Error::Storage(format!("HMAC error: {}", e)))?;
-    mac.update(payload_str.as_bytes());
-    let result = mac.finalize();
-    let signature = hex::encode(result.into_bytes());
-    Ok(format!("sha256={}", signature))
+    Ok(())
 }
 
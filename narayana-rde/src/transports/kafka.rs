@@ -0,0 +1,328 @@
+// Kafka transport - mirrors subscribed events into Kafka topics
+
+use crate::cloudevents::{self, CloudEvent};
+use crate::encoding::EncodedPayload;
+use crate::subscriptions::{Subscription, SubscriptionId};
+use narayana_core::{Error, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Default delivery attempts per event (first try included)
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+const MAX_ATTEMPTS_LIMIT: u32 = 10;
+const MAX_DELAY: Duration = Duration::from_secs(30);
+/// Topic used when a subscription maps none
+const DEFAULT_TOPIC: &str = "narayana.{actor}.{event}";
+/// Kafka's limit on topic name length
+const MAX_TOPIC_LEN: usize = 249;
+
+/// One record to produce
+#[derive(Debug, Clone, PartialEq)]
+pub struct KafkaRecord {
+    pub topic: String,
+    pub key: Option<Vec<u8>>,
+    pub payload: Vec<u8>,
+    pub headers: Vec<(String, String)>,
+}
+
+/// Where a record landed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KafkaDelivery {
+    pub partition: i32,
+    pub offset: i64,
+}
+
+/// Produces records to Kafka on behalf of RDE (to keep the client library out of
+/// the delivery path and swappable)
+#[async_trait::async_trait]
+pub trait KafkaProducer: Send + Sync {
+    async fn send(&self, record: KafkaRecord) -> anyhow::Result<KafkaDelivery>;
+}
+
+/// Delivery metrics for one Kafka subscription
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KafkaDeliveryStats {
+    /// Events acknowledged by the broker
+    pub delivered: u64,
+    /// Events dropped after the last attempt failed
+    pub failed: u64,
+    /// Produce calls, retries included
+    pub attempts: u64,
+    pub last_topic: Option<String>,
+    pub last_partition: Option<i32>,
+    pub last_offset: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+/// Topic mapping and partition key of a subscription
+///
+/// Config: `topic` (default "narayana.{actor}.{event}"; `{actor}` and `{event}`
+/// are replaced from the published event), `topics` (object mapping event names,
+/// short or "actor:event", to topics, checked before `topic`), `partition_key`
+/// (dot path into the payload, or "$actor" / "$event"; without one the
+/// producer's partitioner spreads events) and `max_attempts` (default 3).
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct KafkaTarget {
+    topic: String,
+    topics: Vec<(String, String)>,
+    partition_key: Option<String>,
+    max_attempts: u32,
+}
+
+impl KafkaTarget {
+    pub(crate) fn from_config(config: &Value) -> Result<Self> {
+        let topic = match config.get("topic") {
+            None => DEFAULT_TOPIC.to_string(),
+            Some(Value::String(topic)) => topic.clone(),
+            Some(_) => return Err(Error::Storage("Kafka topic must be a string".to_string())),
+        };
+        validate_topic_template(&topic)?;
+
+        let mut topics = Vec::new();
+        match config.get("topics") {
+            None => {}
+            Some(Value::Object(map)) => {
+                for (event, topic) in map {
+                    let topic = topic.as_str()
+                        .ok_or_else(|| Error::Storage(format!("Kafka topic for {} must be a string", event)))?;
+                    validate_topic_template(topic)?;
+                    topics.push((event.clone(), topic.to_string()));
+                }
+            }
+            Some(_) => return Err(Error::Storage("Kafka topics must map event names to topics".to_string())),
+        }
+
+        let partition_key = match config.get("partition_key") {
+            None => None,
+            Some(Value::String(key)) if !key.is_empty() && key.len() <= 256 => Some(key.clone()),
+            Some(_) => return Err(Error::Storage("Kafka partition_key must be a non-empty string".to_string())),
+        };
+
+        let max_attempts = config.get("max_attempts")
+            .and_then(|v| v.as_u64())
+            .map(|n| (n as u32).clamp(1, MAX_ATTEMPTS_LIMIT))
+            .unwrap_or(DEFAULT_MAX_ATTEMPTS);
+
+        Ok(Self { topic, topics, partition_key, max_attempts })
+    }
+
+    /// Topic for a published event ("actor:event")
+    pub(crate) fn topic_for(&self, event_name: &str) -> String {
+        let (actor, event) = event_name.split_once(':').unwrap_or(("", event_name));
+        let template = self.topics
+            .iter()
+            .find(|(name, _)| name == event_name || name == event)
+            .map(|(_, topic)| topic.as_str())
+            .unwrap_or(&self.topic);
+        let topic: String = template
+            .replace("{actor}", actor)
+            .replace("{event}", event)
+            .chars()
+            .map(|c| if is_topic_char(c) { c } else { '_' })
+            .collect();
+        topic.chars().take(MAX_TOPIC_LEN).collect()
+    }
+
+    /// Partition key for a payload; None when unset or the field is missing
+    pub(crate) fn key_for(&self, event_name: &str, payload: &Value) -> Option<Vec<u8>> {
+        let key = self.partition_key.as_deref()?;
+        let (actor, event) = event_name.split_once(':').unwrap_or(("", event_name));
+        match key {
+            "$actor" => return Some(actor.as_bytes().to_vec()),
+            "$event" => return Some(event.as_bytes().to_vec()),
+            _ => {}
+        }
+        let value = key.split('.').try_fold(payload, |value, field| value.get(field))?;
+        match value {
+            Value::Null => None,
+            Value::String(s) => Some(s.as_bytes().to_vec()),
+            other => Some(other.to_string().into_bytes()),
+        }
+    }
+}
+
+fn is_topic_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-')
+}
+
+/// Topics may only contain [a-zA-Z0-9._-], besides the placeholders
+fn validate_topic_template(topic: &str) -> Result<()> {
+    let literal = topic.replace("{actor}", "").replace("{event}", "");
+    if topic.is_empty() || topic.len() > MAX_TOPIC_LEN || !literal.chars().all(is_topic_char) {
+        return Err(Error::Storage(format!(
+            "Invalid Kafka topic '{}' (use [a-zA-Z0-9._-], {{actor}} and {{event}}, max {} chars)",
+            topic, MAX_TOPIC_LEN
+        )));
+    }
+    Ok(())
+}
+
+/// Deliver an event to Kafka as JSON
+pub async fn deliver_kafka(
+    subscription: &Subscription,
+    event_name: &str,
+    payload: &Value,
+    producer: Option<Arc<dyn KafkaProducer>>,
+    stats: &dashmap::DashMap<SubscriptionId, KafkaDeliveryStats>,
+) -> Result<()> {
+    let encoded = EncodedPayload::json(payload)?;
+    deliver_kafka_encoded(subscription, event_name, payload, &encoded, producer, stats).await
+}
+
+/// Deliver an encoded event by producing it to the subscription's topic
+///
+/// Records carry the event name, subscription and content type (plus the
+/// schema id and version for Avro and Protobuf) as headers. Failed produce
+/// calls are retried with exponential backoff.
+pub async fn deliver_kafka_encoded(
+    subscription: &Subscription,
+    event_name: &str,
+    payload: &Value,
+    encoded: &EncodedPayload,
+    producer: Option<Arc<dyn KafkaProducer>>,
+    stats: &dashmap::DashMap<SubscriptionId, KafkaDeliveryStats>,
+) -> Result<()> {
+    let Some(producer) = producer else {
+        return Err(Error::Storage("Kafka producer not available".to_string()));
+    };
+    let target = KafkaTarget::from_config(&subscription.config)?;
+
+    let mut headers = vec![
+        ("content-type".to_string(), encoded.content_type().to_string()),
+        ("narayana-event".to_string(), event_name.to_string()),
+        ("narayana-subscription".to_string(), subscription.id.0.clone()),
+    ];
+    if let Some(schema) = &encoded.schema {
+        headers.push(("narayana-schema-id".to_string(), schema.id.to_string()));
+        headers.push(("narayana-schema-version".to_string(), schema.version.to_string()));
+    }
+    let record = KafkaRecord {
+        topic: target.topic_for(event_name),
+        key: target.key_for(event_name, payload),
+        payload: encoded.data.clone(),
+        headers,
+    };
+    produce(subscription, &target, record, &producer, stats).await
+}
+
+/// Deliver a CloudEvent to the subscription's topic, in structured mode
+///
+/// Topic and partition key are picked from the event as for plain payloads.
+pub async fn deliver_kafka_cloudevent(
+    subscription: &Subscription,
+    event_name: &str,
+    payload: &Value,
+    event: &CloudEvent,
+    producer: Option<Arc<dyn KafkaProducer>>,
+    stats: &dashmap::DashMap<SubscriptionId, KafkaDeliveryStats>,
+) -> Result<()> {
+    let Some(producer) = producer else {
+        return Err(Error::Storage("Kafka producer not available".to_string()));
+    };
+    let target = KafkaTarget::from_config(&subscription.config)?;
+    let record = KafkaRecord {
+        topic: target.topic_for(event_name),
+        key: target.key_for(event_name, payload),
+        payload: serde_json::to_vec(event)
+            .map_err(|e| Error::Storage(format!("Failed to serialize CloudEvent: {}", e)))?,
+        headers: vec![
+            ("content-type".to_string(), cloudevents::CONTENT_TYPE.to_string()),
+            ("narayana-event".to_string(), event_name.to_string()),
+            ("narayana-subscription".to_string(), subscription.id.0.clone()),
+        ],
+    };
+    produce(subscription, &target, record, &producer, stats).await
+}
+
+/// Produce a record, retrying failed calls with exponential backoff
+async fn produce(
+    subscription: &Subscription,
+    target: &KafkaTarget,
+    record: KafkaRecord,
+    producer: &Arc<dyn KafkaProducer>,
+    stats: &dashmap::DashMap<SubscriptionId, KafkaDeliveryStats>,
+) -> Result<()> {
+    let mut delay = Duration::from_millis(100);
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let outcome = producer.send(record.clone()).await;
+        {
+            let mut entry = stats.entry(subscription.id.clone()).or_default();
+            entry.attempts += 1;
+            entry.last_topic = Some(record.topic.clone());
+            match &outcome {
+                Ok(delivery) => {
+                    entry.delivered += 1;
+                    entry.last_partition = Some(delivery.partition);
+                    entry.last_offset = Some(delivery.offset);
+                    entry.last_error = None;
+                    return Ok(());
+                }
+                Err(e) => entry.last_error = Some(e.to_string()),
+            }
+        }
+        if attempt >= target.max_attempts {
+            stats.entry(subscription.id.clone()).or_default().failed += 1;
+            return Err(Error::Storage(format!(
+                "Failed to produce event to Kafka after {} attempt(s)",
+                attempt
+            )));
+        }
+        tracing::warn!("Kafka delivery attempt {} failed, retrying", attempt);
+        tokio::time::sleep(delay).await;
+        delay = (delay * 2).min(MAX_DELAY); // Exponential backoff with cap
+    }
+}
+
+/// Producer backed by librdkafka
+#[cfg(feature = "kafka")]
+pub struct RdKafkaProducer {
+    producer: rdkafka::producer::FutureProducer,
+    timeout: Duration,
+}
+
+#[cfg(feature = "kafka")]
+impl RdKafkaProducer {
+    /// Connect to `brokers` (comma-separated host:port); `properties` are passed
+    /// to librdkafka as-is (e.g. security.protocol, sasl.username)
+    pub fn new(brokers: &str, properties: &[(String, String)]) -> Result<Self> {
+        let mut config = rdkafka::ClientConfig::new();
+        config
+            .set("bootstrap.servers", brokers)
+            .set("message.timeout.ms", "10000")
+            .set("enable.idempotence", "true");
+        for (key, value) in properties {
+            config.set(key, value);
+        }
+        let producer = config.create()
+            .map_err(|e| Error::Storage(format!("Failed to create Kafka producer: {}", e)))?;
+        Ok(Self { producer, timeout: Duration::from_secs(10) })
+    }
+}
+
+#[cfg(feature = "kafka")]
+#[async_trait::async_trait]
+impl KafkaProducer for RdKafkaProducer {
+    async fn send(&self, record: KafkaRecord) -> anyhow::Result<KafkaDelivery> {
+        use rdkafka::message::{Header, OwnedHeaders};
+        use rdkafka::producer::FutureRecord;
+
+        let headers = record.headers.iter().fold(OwnedHeaders::new(), |headers, (key, value)| {
+            headers.insert(Header { key: key.as_str(), value: Some(value.as_str()) })
+        });
+        let mut future_record = FutureRecord::<[u8], [u8]>::to(&record.topic)
+            .payload(record.payload.as_slice())
+            .headers(headers);
+        if let Some(key) = &record.key {
+            future_record = future_record.key(key.as_slice());
+        }
+        let (partition, offset) = self.producer
+            .send(future_record, self.timeout)
+            .await
+            .map_err(|(e, _)| anyhow::anyhow!("Kafka produce failed: {}", e))?;
+        Ok(KafkaDelivery { partition, offset })
+    }
+}
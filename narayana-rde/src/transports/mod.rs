@@ -4,6 +4,11 @@ pub mod http;
 pub mod websocket;
 pub mod grpc;
 pub mod sse;
+pub mod worker;
+pub mod table;
+pub mod kafka;
+pub mod mqtt;
+pub mod amqp;
 
 /// Transport type
 #[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
@@ -17,6 +22,16 @@ pub enum TransportType {
     Grpc,
     /// Server-Sent Events
     Sse,
+    /// Invoke a deployed worker with the payload as request body
+    Worker,
+    /// Write events into a table (batched, deduplicated)
+    Table,
+    /// Mirror events into Kafka topics
+    Kafka,
+    /// Publish events to an MQTT broker (QoS 0/1)
+    Mqtt,
+    /// Publish events to an AMQP 0-9-1 exchange (RabbitMQ)
+    Amqp,
 }
 
 impl std::fmt::Display for TransportType {
@@ -26,6 +41,11 @@ impl std::fmt::Display for TransportType {
             TransportType::WebSocket => write!(f, "websocket"),
             TransportType::Grpc => write!(f, "grpc"),
             TransportType::Sse => write!(f, "sse"),
+            TransportType::Worker => write!(f, "worker"),
+            TransportType::Table => write!(f, "table"),
+            TransportType::Kafka => write!(f, "kafka"),
+            TransportType::Mqtt => write!(f, "mqtt"),
+            TransportType::Amqp => write!(f, "amqp"),
         }
     }
 }
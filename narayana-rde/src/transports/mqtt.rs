@@ -0,0 +1,460 @@
+// MQTT transport - publishes subscribed events to an MQTT broker
+// Lets embedded robot controllers receive events with a plain MQTT client
+// instead of a WebSocket one. Each subscription names its own broker, so
+// connections are opened per subscription and reused until they fail.
+
+use crate::encoding::EncodedPayload;
+use crate::subscriptions::{Subscription, SubscriptionId};
+use narayana_core::egress::{self, EgressSubsystem};
+use narayana_core::{Error, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Default delivery attempts per event (first try included)
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+const MAX_ATTEMPTS_LIMIT: u32 = 10;
+const MAX_DELAY: Duration = Duration::from_secs(30);
+/// Topic used when a subscription sets none
+const DEFAULT_TOPIC: &str = "narayana/{actor}/{event}";
+/// MQTT's limit on topic length (bytes)
+const MAX_TOPIC_LEN: usize = 65_535;
+/// Client ids up to 23 characters are accepted by every broker
+const MAX_CLIENT_ID_LEN: usize = 23;
+const DEFAULT_KEEP_ALIVE_SECS: u64 = 30;
+const MAX_CREDENTIAL_LEN: usize = 1024;
+
+/// Delivery guarantee of published messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MqttQos {
+    /// QoS 0: fire and forget
+    AtMostOnce,
+    /// QoS 1: acknowledged by the broker, may be redelivered
+    AtLeastOnce,
+}
+
+/// How to reach a subscription's broker
+#[derive(Clone, PartialEq)]
+pub struct MqttConnectOptions {
+    /// mqtt://host[:1883] or mqtts://host[:8883]
+    pub broker_url: String,
+    pub tls: bool,
+    pub client_id: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub keep_alive: Duration,
+}
+
+// SECURITY: Keep the password out of debug output and logs
+impl std::fmt::Debug for MqttConnectOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MqttConnectOptions")
+            .field("broker_url", &self.broker_url)
+            .field("tls", &self.tls)
+            .field("client_id", &self.client_id)
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "********"))
+            .field("keep_alive", &self.keep_alive)
+            .finish()
+    }
+}
+
+/// One message to publish
+#[derive(Debug, Clone, PartialEq)]
+pub struct MqttMessage {
+    pub topic: String,
+    pub payload: Vec<u8>,
+    pub qos: MqttQos,
+    pub retain: bool,
+}
+
+/// An open broker connection
+#[async_trait::async_trait]
+pub trait MqttClient: Send + Sync {
+    async fn publish(&self, message: MqttMessage) -> anyhow::Result<()>;
+}
+
+/// Opens broker connections on behalf of RDE (to keep the client library out
+/// of the delivery path and swappable)
+#[async_trait::async_trait]
+pub trait MqttConnector: Send + Sync {
+    async fn connect(&self, options: &MqttConnectOptions) -> anyhow::Result<Arc<dyn MqttClient>>;
+}
+
+/// Delivery metrics for one MQTT subscription
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MqttDeliveryStats {
+    /// Events handed to the broker
+    pub delivered: u64,
+    /// Events dropped after the last attempt failed
+    pub failed: u64,
+    /// Publish attempts, retries included
+    pub attempts: u64,
+    /// Connections opened to the broker
+    pub connects: u64,
+    pub last_topic: Option<String>,
+    pub last_error: Option<String>,
+}
+
+/// Broker, topic and publish options of a subscription
+///
+/// Config: `broker_url` (required; mqtt:// or mqtts://), `topic` (default
+/// "narayana/{actor}/{event}"; `{actor}` and `{event}` are replaced from the
+/// published event), `qos` (0 or 1, default 1), `retain` (default false),
+/// `client_id` (default derived from the subscription id), `username` and
+/// `password`, `keep_alive_secs` (default 30) and `max_attempts` (default 3).
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct MqttTarget {
+    options: MqttConnectOptions,
+    topic: String,
+    qos: MqttQos,
+    retain: bool,
+    max_attempts: u32,
+}
+
+impl MqttTarget {
+    /// Parse and validate a subscription's config; `subscription_id` names
+    /// the default client id
+    pub(crate) fn from_config(config: &Value, subscription_id: &SubscriptionId) -> Result<Self> {
+        let broker_url = config.get("broker_url")
+            .and_then(|v| v.as_str())
+            .filter(|url| !url.is_empty())
+            .ok_or_else(|| Error::Storage("MQTT subscriptions require a broker_url in config".to_string()))?;
+        // SECURITY: Brokers are reached through the shared egress policy
+        let target = egress::check_url(EgressSubsystem::Rde, broker_url)?;
+        let tls = match target.scheme.as_str() {
+            "mqtt" => false,
+            "mqtts" => true,
+            other => return Err(Error::Storage(format!("MQTT broker_url must use mqtt:// or mqtts://, not {}://", other))),
+        };
+
+        let topic = match config.get("topic") {
+            None => DEFAULT_TOPIC.to_string(),
+            Some(Value::String(topic)) => topic.clone(),
+            Some(_) => return Err(Error::Storage("MQTT topic must be a string".to_string())),
+        };
+        validate_topic_template(&topic)?;
+
+        let qos = match config.get("qos").map(|v| v.as_u64()) {
+            None | Some(Some(1)) => MqttQos::AtLeastOnce,
+            Some(Some(0)) => MqttQos::AtMostOnce,
+            Some(_) => return Err(Error::Storage("MQTT qos must be 0 or 1".to_string())),
+        };
+        let retain = match config.get("retain") {
+            None => false,
+            Some(Value::Bool(retain)) => *retain,
+            Some(_) => return Err(Error::Storage("MQTT retain must be a boolean".to_string())),
+        };
+
+        let client_id = match config.get("client_id") {
+            None => default_client_id(subscription_id),
+            Some(Value::String(id)) if !id.is_empty()
+                && id.len() <= MAX_CLIENT_ID_LEN
+                && id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_')) => id.clone(),
+            Some(_) => return Err(Error::Storage(format!(
+                "MQTT client_id must be 1-{} characters of [a-zA-Z0-9_-]",
+                MAX_CLIENT_ID_LEN
+            ))),
+        };
+
+        let credential = |name: &str| match config.get(name) {
+            None => Ok(None),
+            Some(Value::String(value)) if !value.is_empty()
+                && value.len() <= MAX_CREDENTIAL_LEN
+                && !value.chars().any(|c| c.is_control()) => Ok(Some(value.clone())),
+            // SECURITY: Don't echo credentials in errors
+            Some(_) => Err(Error::Storage(format!("MQTT {} must be a non-empty string without control characters", name))),
+        };
+        let username = credential("username")?;
+        let password = credential("password")?;
+        if password.is_some() && username.is_none() {
+            return Err(Error::Storage("MQTT password requires a username".to_string()));
+        }
+
+        let keep_alive_secs = match config.get("keep_alive_secs") {
+            None => DEFAULT_KEEP_ALIVE_SECS,
+            Some(value) => value.as_u64()
+                .filter(|secs| (5..=3600).contains(secs))
+                .ok_or_else(|| Error::Storage("MQTT keep_alive_secs must be between 5 and 3600".to_string()))?,
+        };
+
+        let max_attempts = config.get("max_attempts")
+            .and_then(|v| v.as_u64())
+            .map(|n| (n as u32).clamp(1, MAX_ATTEMPTS_LIMIT))
+            .unwrap_or(DEFAULT_MAX_ATTEMPTS);
+
+        Ok(Self {
+            options: MqttConnectOptions {
+                broker_url: broker_url.to_string(),
+                tls,
+                client_id,
+                username,
+                password,
+                keep_alive: Duration::from_secs(keep_alive_secs),
+            },
+            topic,
+            qos,
+            retain,
+            max_attempts,
+        })
+    }
+
+    /// Topic for a published event ("actor:event")
+    pub(crate) fn topic_for(&self, event_name: &str) -> String {
+        let (actor, event) = event_name.split_once(':').unwrap_or(("", event_name));
+        // Replaced values must stay within one topic level and can't add wildcards
+        let level = |value: &str| -> String {
+            value.chars().map(|c| if matches!(c, '/' | '+' | '#' | '\0') { '_' } else { c }).collect()
+        };
+        let mut topic = self.topic
+            .replace("{actor}", &level(actor))
+            .replace("{event}", &level(event));
+        if topic.len() > MAX_TOPIC_LEN {
+            let mut end = MAX_TOPIC_LEN;
+            while !topic.is_char_boundary(end) {
+                end -= 1;
+            }
+            topic.truncate(end);
+        }
+        topic
+    }
+}
+
+/// "narayana-" and the first 14 hex digits of the subscription id
+fn default_client_id(subscription_id: &SubscriptionId) -> String {
+    let digits: String = subscription_id.0.chars().filter(|c| c.is_ascii_alphanumeric()).take(14).collect();
+    format!("narayana-{}", digits)
+}
+
+/// Topics can't contain wildcards or NUL, and `$` topics belong to the broker
+fn validate_topic_template(topic: &str) -> Result<()> {
+    let literal = topic.replace("{actor}", "").replace("{event}", "");
+    if topic.is_empty()
+        || topic.len() > MAX_TOPIC_LEN
+        || topic.starts_with('$')
+        || literal.chars().any(|c| matches!(c, '+' | '#' | '\0'))
+    {
+        return Err(Error::Storage(format!(
+            "Invalid MQTT topic '{}' (no wildcards, NUL or leading '$'; use {{actor}} and {{event}})",
+            topic
+        )));
+    }
+    Ok(())
+}
+
+/// Open broker connections, one per subscription
+pub struct MqttConnections {
+    connector: Arc<dyn MqttConnector>,
+    clients: dashmap::DashMap<SubscriptionId, Arc<dyn MqttClient>>,
+}
+
+impl MqttConnections {
+    pub fn new(connector: Arc<dyn MqttConnector>) -> Self {
+        Self { connector, clients: dashmap::DashMap::new() }
+    }
+
+    /// The subscription's connection, opened on first use
+    async fn client(
+        &self,
+        subscription_id: &SubscriptionId,
+        options: &MqttConnectOptions,
+        stats: &dashmap::DashMap<SubscriptionId, MqttDeliveryStats>,
+    ) -> anyhow::Result<Arc<dyn MqttClient>> {
+        if let Some(client) = self.clients.get(subscription_id) {
+            return Ok(client.clone());
+        }
+        let client = self.connector.connect(options).await?;
+        stats.entry(subscription_id.clone()).or_default().connects += 1;
+        self.clients.insert(subscription_id.clone(), client.clone());
+        Ok(client)
+    }
+
+    /// Close a subscription's connection; returns whether one was open
+    pub fn disconnect(&self, subscription_id: &SubscriptionId) -> bool {
+        self.clients.remove(subscription_id).is_some()
+    }
+
+    /// Open connections
+    pub fn len(&self) -> usize {
+        self.clients.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clients.is_empty()
+    }
+}
+
+/// Deliver an event to MQTT as JSON
+pub async fn deliver_mqtt(
+    subscription: &Subscription,
+    event_name: &str,
+    payload: &Value,
+    connections: Option<Arc<MqttConnections>>,
+    stats: &dashmap::DashMap<SubscriptionId, MqttDeliveryStats>,
+) -> Result<()> {
+    let encoded = EncodedPayload::json(payload)?;
+    deliver_mqtt_encoded(subscription, event_name, &encoded, connections, stats).await
+}
+
+/// Deliver an encoded event by publishing it to the subscription's topic
+///
+/// MQTT 3.1.1 has no message headers, so consumers get the bare payload in
+/// the encoding the subscription negotiated. A failed publish drops the
+/// connection and retries over a new one with exponential backoff.
+pub async fn deliver_mqtt_encoded(
+    subscription: &Subscription,
+    event_name: &str,
+    encoded: &EncodedPayload,
+    connections: Option<Arc<MqttConnections>>,
+    stats: &dashmap::DashMap<SubscriptionId, MqttDeliveryStats>,
+) -> Result<()> {
+    let Some(connections) = connections else {
+        return Err(Error::Storage("MQTT transport not available".to_string()));
+    };
+    let target = MqttTarget::from_config(&subscription.config, &subscription.id)?;
+    let message = MqttMessage {
+        topic: target.topic_for(event_name),
+        payload: encoded.data.clone(),
+        qos: target.qos,
+        retain: target.retain,
+    };
+
+    let mut delay = Duration::from_millis(100);
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let outcome = match connections.client(&subscription.id, &target.options, stats).await {
+            Ok(client) => client.publish(message.clone()).await,
+            Err(e) => Err(e),
+        };
+        {
+            let mut entry = stats.entry(subscription.id.clone()).or_default();
+            entry.attempts += 1;
+            entry.last_topic = Some(message.topic.clone());
+            match &outcome {
+                Ok(()) => {
+                    entry.delivered += 1;
+                    entry.last_error = None;
+                    return Ok(());
+                }
+                Err(e) => entry.last_error = Some(e.to_string()),
+            }
+        }
+        // Reconnect on the next attempt
+        connections.disconnect(&subscription.id);
+        if attempt >= target.max_attempts {
+            stats.entry(subscription.id.clone()).or_default().failed += 1;
+            return Err(Error::Storage(format!(
+                "Failed to publish event to MQTT after {} attempt(s)",
+                attempt
+            )));
+        }
+        tracing::warn!("MQTT delivery attempt {} failed, retrying", attempt);
+        tokio::time::sleep(delay).await;
+        delay = (delay * 2).min(MAX_DELAY); // Exponential backoff with cap
+    }
+}
+
+/// Connector backed by rumqttc
+///
+/// QoS 1 publishes are handed to the client's event loop, which retransmits
+/// them until the broker acknowledges, across reconnects.
+#[cfg(feature = "mqtt")]
+pub struct RumqttcConnector {
+    pub connect_timeout: Duration,
+}
+
+#[cfg(feature = "mqtt")]
+impl Default for RumqttcConnector {
+    fn default() -> Self {
+        Self { connect_timeout: Duration::from_secs(10) }
+    }
+}
+
+#[cfg(feature = "mqtt")]
+#[async_trait::async_trait]
+impl MqttConnector for RumqttcConnector {
+    async fn connect(&self, options: &MqttConnectOptions) -> anyhow::Result<Arc<dyn MqttClient>> {
+        use rumqttc::{AsyncClient, ConnectionError, Event, MqttOptions, Outgoing, Packet, Transport};
+
+        // SECURITY: Resolve and vet the broker's addresses, then connect to a vetted
+        // one (TLS connects by host name so the certificate can be verified)
+        let policy = egress::global();
+        let target = policy.check_url(EgressSubsystem::Rde, &options.broker_url)?;
+        let addrs: Vec<std::net::SocketAddr> = tokio::net::lookup_host((target.host.as_str(), target.port))
+            .await?
+            .collect();
+        let ips: Vec<std::net::IpAddr> = addrs.iter().map(|a| a.ip()).collect();
+        policy.check_resolved(EgressSubsystem::Rde, &target, &ips)?;
+        let host = match (options.tls, addrs.first()) {
+            (false, Some(addr)) => addr.ip().to_string(),
+            _ => target.host.clone(),
+        };
+
+        let mut mqtt_options = MqttOptions::new(&options.client_id, host, target.port);
+        mqtt_options.set_keep_alive(options.keep_alive);
+        if let Some(username) = &options.username {
+            mqtt_options.set_credentials(username, options.password.as_deref().unwrap_or_default());
+        }
+        if options.tls {
+            mqtt_options.set_transport(Transport::tls_with_default_config());
+        }
+        let (client, mut event_loop) = AsyncClient::new(mqtt_options, 64);
+
+        // Wait for the broker to accept the connection so bad brokers or credentials fail here
+        let handshake = async {
+            loop {
+                if let Event::Incoming(Packet::ConnAck(_)) = event_loop.poll().await? {
+                    return Ok::<(), ConnectionError>(());
+                }
+            }
+        };
+        tokio::time::timeout(self.connect_timeout, handshake)
+            .await
+            .map_err(|_| anyhow::anyhow!("MQTT broker did not accept the connection in time"))??;
+
+        // The event loop drives acks and reconnects until the client is dropped
+        tokio::spawn(async move {
+            loop {
+                match event_loop.poll().await {
+                    Ok(Event::Outgoing(Outgoing::Disconnect)) | Err(ConnectionError::RequestsDone) => break,
+                    Ok(_) => {}
+                    Err(e) => {
+                        tracing::warn!("MQTT connection error: {}", e);
+                        tokio::time::sleep(Duration::from_secs(1)).await;
+                    }
+                }
+            }
+        });
+        Ok(Arc::new(RumqttcClient { client }))
+    }
+}
+
+#[cfg(feature = "mqtt")]
+struct RumqttcClient {
+    client: rumqttc::AsyncClient,
+}
+
+#[cfg(feature = "mqtt")]
+impl Drop for RumqttcClient {
+    fn drop(&mut self) {
+        // Ends the event loop once the broker is told
+        let _ = self.client.try_disconnect();
+    }
+}
+
+#[cfg(feature = "mqtt")]
+#[async_trait::async_trait]
+impl MqttClient for RumqttcClient {
+    async fn publish(&self, message: MqttMessage) -> anyhow::Result<()> {
+        let qos = match message.qos {
+            MqttQos::AtMostOnce => rumqttc::QoS::AtMostOnce,
+            MqttQos::AtLeastOnce => rumqttc::QoS::AtLeastOnce,
+        };
+        self.client
+            .publish(message.topic, qos, message.retain, message.payload)
+            .await
+            .map_err(|e| anyhow::anyhow!("MQTT publish failed: {}", e))
+    }
+}
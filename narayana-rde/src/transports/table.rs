@@ -0,0 +1,413 @@
+// Table sink - materializes subscribed events into a table
+
+use crate::subscriptions::{Subscription, SubscriptionId};
+use narayana_core::column::Column;
+use narayana_core::schema::{DataType, Field, Schema};
+use narayana_core::types::TableId;
+use narayana_core::{Error, Result};
+use narayana_storage::database_manager::DatabaseManager;
+use narayana_storage::ColumnStore;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Column holding the full event name
+pub const EVENT_COLUMN: &str = "_event";
+/// Column holding the delivery time (Unix milliseconds)
+pub const RECEIVED_AT_COLUMN: &str = "_received_at";
+/// Json column collecting payload fields the table has no column for
+pub const EXTRA_COLUMN: &str = "_extra";
+
+const DEFAULT_BATCH_SIZE: usize = 100;
+const MAX_BATCH_SIZE: usize = 10_000;
+const DEFAULT_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+const DEFAULT_DEDUPE_WINDOW: usize = 10_000;
+
+/// Where and how a subscription's events are written
+///
+/// Config: `table` (required), `database` (default "default"), `batch_size`
+/// (default 100), `flush_interval_ms` (default 1000), `dedupe_key` (payload
+/// field identifying an event; default is the whole payload) and
+/// `dedupe_window` (recent keys remembered, default 10000, 0 disables).
+#[derive(Debug, Clone, PartialEq)]
+struct SinkTarget {
+    database: String,
+    table: String,
+    batch_size: usize,
+    flush_interval: Duration,
+    dedupe_key: Option<String>,
+    dedupe_window: usize,
+}
+
+impl SinkTarget {
+    fn from_config(config: &Value) -> Result<Self> {
+        let table = config.get("table")
+            .and_then(|v| v.as_str())
+            .filter(|t| !t.is_empty())
+            .ok_or_else(|| Error::Storage("table not configured".to_string()))?;
+        let number = |key: &str| config.get(key).and_then(|v| v.as_u64());
+        Ok(Self {
+            database: config.get("database").and_then(|v| v.as_str()).unwrap_or("default").to_string(),
+            table: table.to_string(),
+            batch_size: number("batch_size").map(|n| (n as usize).clamp(1, MAX_BATCH_SIZE)).unwrap_or(DEFAULT_BATCH_SIZE),
+            flush_interval: number("flush_interval_ms").map(Duration::from_millis).unwrap_or(DEFAULT_FLUSH_INTERVAL),
+            dedupe_key: config.get("dedupe_key").and_then(|v| v.as_str()).map(str::to_string),
+            dedupe_window: number("dedupe_window").map(|n| n as usize).unwrap_or(DEFAULT_DEDUPE_WINDOW),
+        })
+    }
+}
+
+struct PendingRow {
+    event: String,
+    received_at: i64,
+    payload: Map<String, Value>,
+}
+
+/// Per-subscription buffer and dedupe window
+#[derive(Default)]
+struct SinkBuffer {
+    target: Option<SinkTarget>,
+    rows: Vec<PendingRow>,
+    recent: VecDeque<String>,
+    recent_set: HashSet<String>,
+    flush_scheduled: bool,
+}
+
+impl SinkBuffer {
+    /// Remember `key`; false when it was already seen within the window
+    fn remember(&mut self, key: String, window: usize) -> bool {
+        if window == 0 {
+            return true;
+        }
+        if self.recent_set.contains(&key) {
+            return false;
+        }
+        self.recent.push_back(key.clone());
+        self.recent_set.insert(key);
+        while self.recent.len() > window {
+            if let Some(old) = self.recent.pop_front() {
+                self.recent_set.remove(&old);
+            }
+        }
+        true
+    }
+}
+
+/// Write counters for one table subscription
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TableSinkStats {
+    /// Rows written to the table
+    pub rows_written: u64,
+    /// Batches written
+    pub batches: u64,
+    /// Events skipped as duplicates
+    pub duplicates: u64,
+    /// Rows lost because their batch could not be written
+    pub rows_failed: u64,
+    /// Rows waiting for the next flush
+    pub pending: usize,
+    pub table_id: Option<u64>,
+    pub last_error: Option<String>,
+}
+
+/// Inserts a batch into a table the way client inserts are made: under the
+/// table's write lock, with embeddings, temporal stamping, replication,
+/// full-text and spatial indexing and the CDC feed
+#[async_trait::async_trait]
+pub trait TableInserter: Send + Sync {
+    /// Rows written
+    async fn insert(&self, table_id: TableId, schema: &Schema, columns: Vec<Column>) -> Result<usize>;
+}
+
+/// Buffers subscribed events and writes them to tables in batches, creating
+/// the table from the first batch's event schema when it does not exist
+pub struct TableSink {
+    storage: Arc<dyn ColumnStore>,
+    db_manager: Arc<DatabaseManager>,
+    inserter: RwLock<Option<Arc<dyn TableInserter>>>,
+    buffers: Mutex<HashMap<SubscriptionId, SinkBuffer>>,
+    stats: dashmap::DashMap<SubscriptionId, TableSinkStats>,
+    /// Serializes table lookup/creation so concurrent flushes create one table
+    tables: tokio::sync::Mutex<()>,
+}
+
+impl TableSink {
+    pub fn new(storage: Arc<dyn ColumnStore>, db_manager: Arc<DatabaseManager>) -> Self {
+        Self {
+            storage,
+            db_manager,
+            inserter: RwLock::new(None),
+            buffers: Mutex::new(HashMap::new()),
+            stats: dashmap::DashMap::new(),
+            tables: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Set the insert path batches go through; until one is set they are
+    /// written straight to the store under the table's write lock
+    pub fn set_inserter(&self, inserter: Arc<dyn TableInserter>) {
+        *self.inserter.write() = Some(inserter);
+    }
+
+    pub fn stats(&self, subscription_id: &SubscriptionId) -> Option<TableSinkStats> {
+        let pending = self.buffers.lock().get(subscription_id).map(|b| b.rows.len());
+        let stats = self.stats.get(subscription_id).map(|s| s.value().clone());
+        if pending.is_none() && stats.is_none() {
+            return None;
+        }
+        Some(TableSinkStats { pending: pending.unwrap_or(0), ..stats.unwrap_or_default() })
+    }
+
+    /// Buffer one event; writes the batch once it is full, otherwise within
+    /// the flush interval
+    pub async fn deliver(self: &Arc<Self>, subscription: &Subscription, payload: &Value) -> Result<()> {
+        let target = SinkTarget::from_config(&subscription.config)?;
+        let payload = match payload {
+            Value::Object(object) => object.clone(),
+            other => Map::from_iter([("value".to_string(), other.clone())]),
+        };
+        let key = match &target.dedupe_key {
+            Some(field) => payload.get(field).map(|v| format!("{}:{}", field, v)),
+            None => None,
+        }
+        .unwrap_or_else(|| content_key(&subscription.event_name.0, &payload));
+
+        let (flush_now, schedule) = {
+            let mut buffers = self.buffers.lock();
+            let buffer = buffers.entry(subscription.id.clone()).or_default();
+            if !buffer.remember(key, target.dedupe_window) {
+                self.stats.entry(subscription.id.clone()).or_default().duplicates += 1;
+                return Ok(());
+            }
+            buffer.rows.push(PendingRow {
+                event: subscription.event_name.0.clone(),
+                received_at: chrono::Utc::now().timestamp_millis(),
+                payload,
+            });
+            let flush_now = buffer.rows.len() >= target.batch_size;
+            let schedule = !flush_now && !buffer.flush_scheduled;
+            buffer.flush_scheduled |= schedule;
+            buffer.target = Some(target.clone());
+            (flush_now, schedule)
+        };
+
+        if flush_now {
+            return self.flush(&subscription.id).await;
+        }
+        if schedule {
+            let sink = self.clone();
+            let id = subscription.id.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(target.flush_interval).await;
+                if let Err(e) = sink.flush(&id).await {
+                    tracing::warn!("Table sink flush failed: {}", e);
+                }
+            });
+        }
+        Ok(())
+    }
+
+    /// Write a subscription's buffered events now
+    pub async fn flush(&self, subscription_id: &SubscriptionId) -> Result<()> {
+        let (target, rows) = {
+            let mut buffers = self.buffers.lock();
+            let Some(buffer) = buffers.get_mut(subscription_id) else {
+                return Ok(());
+            };
+            buffer.flush_scheduled = false;
+            match buffer.target.clone() {
+                Some(target) if !buffer.rows.is_empty() => (target, std::mem::take(&mut buffer.rows)),
+                _ => return Ok(()),
+            }
+        };
+
+        let count = rows.len() as u64;
+        let result = self.write(&target, &rows).await;
+        let mut stats = self.stats.entry(subscription_id.clone()).or_default();
+        match result {
+            Ok(table_id) => {
+                stats.rows_written += count;
+                stats.batches += 1;
+                stats.table_id = Some(table_id.0);
+                stats.last_error = None;
+                Ok(())
+            }
+            Err(e) => {
+                stats.rows_failed += count;
+                stats.last_error = Some(e.to_string());
+                Err(e)
+            }
+        }
+    }
+
+    /// Write every subscription's buffered events (e.g. before shutdown)
+    pub async fn flush_all(&self) -> Result<()> {
+        let ids: Vec<SubscriptionId> = self.buffers.lock().keys().cloned().collect();
+        for id in ids {
+            self.flush(&id).await?;
+        }
+        Ok(())
+    }
+
+    async fn write(&self, target: &SinkTarget, rows: &[PendingRow]) -> Result<TableId> {
+        let (table_id, schema) = self.resolve_table(target, rows).await?;
+        let columns = rows_to_columns(&schema, rows);
+        let inserter = self.inserter.read().clone();
+        match inserter {
+            Some(inserter) => {
+                inserter.insert(table_id, &schema, columns).await?;
+            }
+            None => self.storage.write_columns(table_id, columns).await?,
+        }
+        Ok(table_id)
+    }
+
+    /// Look up the target table, creating it from the batch when missing
+    async fn resolve_table(&self, target: &SinkTarget, rows: &[PendingRow]) -> Result<(TableId, Schema)> {
+        let _guard = self.tables.lock().await;
+        if let Some(table_id) = self.db_manager.get_table_by_name(&target.database, &target.table) {
+            return Ok((table_id, self.storage.get_schema(table_id).await?));
+        }
+        let database_id = match self.db_manager.get_database_by_name(&target.database) {
+            Some(id) => id,
+            None => self.db_manager.create_database(target.database.clone())?,
+        };
+        let schema = infer_schema(rows);
+        let table_id = self.db_manager.create_table(database_id, target.table.clone(), schema.clone())?;
+        if let Err(e) = self.storage.create_table(table_id, schema.clone()).await {
+            let _ = self.db_manager.drop_table(table_id);
+            return Err(e);
+        }
+        tracing::info!("Created table {}.{} for event sink", target.database, target.table);
+        Ok((table_id, schema))
+    }
+}
+
+/// Dedupe key of an event without a configured key field
+fn content_key(event: &str, payload: &Map<String, Value>) -> String {
+    use sha2::{Digest, Sha256};
+    let mut hasher = Sha256::new();
+    hasher.update(event.as_bytes());
+    hasher.update(serde_json::to_vec(payload).unwrap_or_default());
+    hex::encode(hasher.finalize())
+}
+
+/// Table schema from a batch: metadata columns, then one nullable column per
+/// payload field (numbers are Int64 unless a non-integer was seen)
+fn infer_schema(rows: &[PendingRow]) -> Schema {
+    let mut fields = vec![
+        field(EVENT_COLUMN, DataType::String),
+        field(RECEIVED_AT_COLUMN, DataType::Timestamp),
+    ];
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    for row in rows {
+        let Ok(schema) = crate::events::extract_schema(&Value::Object(row.payload.clone())) else {
+            continue;
+        };
+        for schema_field in schema.fields {
+            let data_type = match schema_field.field_type.as_str() {
+                "string" => DataType::String,
+                "boolean" => DataType::Boolean,
+                "number" if row.payload.get(&schema_field.name).is_some_and(|v| v.is_i64()) => DataType::Int64,
+                "number" => DataType::Float64,
+                "null" => continue,
+                _ => DataType::Json,
+            };
+            match seen.get(&schema_field.name) {
+                Some(&idx) => {
+                    // Widen conflicting types: Int64 + Float64 -> Float64, anything else -> Json
+                    let current = &fields[idx].data_type;
+                    if *current != data_type {
+                        fields[idx].data_type = match (current, &data_type) {
+                            (DataType::Int64, DataType::Float64) | (DataType::Float64, DataType::Int64) => DataType::Float64,
+                            _ => DataType::Json,
+                        };
+                    }
+                }
+                None if schema_field.name.starts_with('_') => {}
+                None => {
+                    seen.insert(schema_field.name.clone(), fields.len());
+                    fields.push(field(&schema_field.name, data_type));
+                }
+            }
+        }
+    }
+    fields.push(field(EXTRA_COLUMN, DataType::Json));
+    Schema::new(fields)
+}
+
+fn field(name: &str, data_type: DataType) -> Field {
+    Field {
+        name: name.to_string(),
+        data_type,
+        nullable: true,
+        default_value: None,
+    }
+}
+
+/// Columns for `rows` in schema order; missing or unconvertible values are
+/// stored as the column type's zero value
+fn rows_to_columns(schema: &Schema, rows: &[PendingRow]) -> Vec<Column> {
+    let extras: Vec<Value> = rows.iter()
+        .map(|row| {
+            let extra: Map<String, Value> = row.payload.iter()
+                .filter(|(key, _)| schema.field_index(key).is_none())
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect();
+            if extra.is_empty() { Value::Null } else { Value::Object(extra) }
+        })
+        .collect();
+    let null = Value::Null;
+    schema.fields.iter()
+        .map(|field| match field.name.as_str() {
+            EVENT_COLUMN => Column::String(rows.iter().map(|row| row.event.clone()).collect()),
+            RECEIVED_AT_COLUMN => Column::Timestamp(rows.iter().map(|row| row.received_at).collect()),
+            EXTRA_COLUMN => column_from_values(&field.data_type, &extras.iter().collect::<Vec<_>>()),
+            name => {
+                let values: Vec<&Value> = rows.iter().map(|row| row.payload.get(name).unwrap_or(&null)).collect();
+                column_from_values(&field.data_type, &values)
+            }
+        })
+        .collect()
+}
+
+fn column_from_values(data_type: &DataType, values: &[&Value]) -> Column {
+    fn collect<T: Default>(values: &[&Value], convert: impl Fn(&Value) -> Option<T>) -> Vec<T> {
+        values.iter().map(|v| convert(v).unwrap_or_default()).collect()
+    }
+    fn int<T: TryFrom<i64>>(v: &Value) -> Option<T> {
+        v.as_i64().and_then(|n| T::try_from(n).ok())
+    }
+    fn uint<T: TryFrom<u64>>(v: &Value) -> Option<T> {
+        v.as_u64().and_then(|n| T::try_from(n).ok())
+    }
+    match data_type {
+        DataType::Nullable(inner) => column_from_values(inner, values),
+        DataType::Int8 => Column::Int8(collect(values, int)),
+        DataType::Int16 => Column::Int16(collect(values, int)),
+        DataType::Int32 => Column::Int32(collect(values, int)),
+        DataType::Int64 => Column::Int64(collect(values, int)),
+        DataType::UInt8 => Column::UInt8(collect(values, uint)),
+        DataType::UInt16 => Column::UInt16(collect(values, uint)),
+        DataType::UInt32 => Column::UInt32(collect(values, uint)),
+        DataType::UInt64 => Column::UInt64(collect(values, uint)),
+        DataType::Float32 => Column::Float32(collect(values, |v| v.as_f64().map(|f| f as f32))),
+        DataType::Float64 => Column::Float64(collect(values, Value::as_f64)),
+        DataType::Boolean => Column::Boolean(collect(values, Value::as_bool)),
+        DataType::Timestamp => Column::Timestamp(collect(values, Value::as_i64)),
+        DataType::Date => Column::Date(collect(values, int)),
+        DataType::String => Column::String(collect(values, |v| match v {
+            Value::Null => None,
+            Value::String(s) => Some(s.clone()),
+            other => Some(other.to_string()),
+        })),
+        DataType::Binary | DataType::Point | DataType::Geometry => {
+            Column::Binary(collect(values, |v| v.as_str().map(|s| s.as_bytes().to_vec())))
+        }
+        // Json and nested types are stored as JSON text
+        _ => Column::String(collect(values, |v| (!v.is_null()).then(|| v.to_string()))),
+    }
+}
@@ -0,0 +1,158 @@
+// Worker transport - invokes a deployed worker with the event payload
+
+use crate::encoding::EncodedPayload;
+use crate::subscriptions::{Subscription, SubscriptionId};
+use narayana_core::{Error, Result};
+use narayana_storage::cognitive::CognitiveBrain;
+use narayana_storage::database_manager::DatabaseManager;
+use narayana_storage::workers::{WorkerManager, WorkerRequest, WorkerResponse};
+use narayana_storage::ColumnStore;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Default delivery attempts per event (first try included)
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+const MAX_ATTEMPTS_LIMIT: u32 = 10;
+const MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// Runs workers on behalf of RDE (to avoid tying RDE to the server's resources)
+#[async_trait::async_trait]
+pub trait WorkerInvoker: Send + Sync {
+    async fn invoke(&self, worker_id: &str, request: WorkerRequest) -> anyhow::Result<WorkerResponse>;
+}
+
+/// Invokes workers from a local `WorkerManager` with the given resources
+pub struct LocalWorkerInvoker {
+    pub workers: Arc<WorkerManager>,
+    pub storage: Arc<dyn ColumnStore>,
+    pub db_manager: Arc<DatabaseManager>,
+    pub brain: Option<Arc<CognitiveBrain>>,
+}
+
+#[async_trait::async_trait]
+impl WorkerInvoker for LocalWorkerInvoker {
+    async fn invoke(&self, worker_id: &str, request: WorkerRequest) -> anyhow::Result<WorkerResponse> {
+        self.workers
+            .invoke_worker(worker_id, request, self.storage.clone(), self.db_manager.clone(), self.brain.clone())
+            .await
+    }
+}
+
+/// Delivery and execution metrics for one worker subscription
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorkerDeliveryStats {
+    /// Events the worker handled successfully
+    pub delivered: u64,
+    /// Events dropped after the last attempt failed
+    pub failed: u64,
+    /// Worker invocations, retries included
+    pub attempts: u64,
+    pub last_status: Option<u16>,
+    pub last_error: Option<String>,
+    pub total_execution_time_ms: u64,
+    pub max_execution_time_ms: u64,
+    pub total_cpu_time_ms: u64,
+}
+
+/// Deliver event by invoking the subscription's worker
+///
+/// Config: `worker_id` (required), `path` (request URL seen by the worker,
+/// default "/"), `max_attempts` (default 3). Server errors (5xx) and
+/// invocation failures are retried with exponential backoff; 4xx responses
+/// are final.
+pub async fn deliver_worker(
+    subscription: &Subscription,
+    payload: &serde_json::Value,
+    invoker: Option<Arc<dyn WorkerInvoker>>,
+    stats: &dashmap::DashMap<SubscriptionId, WorkerDeliveryStats>,
+) -> Result<()> {
+    deliver_worker_encoded(subscription, &EncodedPayload::json(payload)?, invoker, stats).await
+}
+
+/// Deliver an encoded event by invoking the subscription's worker
+/// (the body's content type and schema are passed as request headers)
+pub async fn deliver_worker_encoded(
+    subscription: &Subscription,
+    encoded: &EncodedPayload,
+    invoker: Option<Arc<dyn WorkerInvoker>>,
+    stats: &dashmap::DashMap<SubscriptionId, WorkerDeliveryStats>,
+) -> Result<()> {
+    let worker_id = subscription.config.get("worker_id")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| Error::Storage("worker_id not configured".to_string()))?;
+    let Some(invoker) = invoker else {
+        return Err(Error::Storage("Worker invocation not available".to_string()));
+    };
+    let path = subscription.config.get("path").and_then(|v| v.as_str()).unwrap_or("/");
+    let max_attempts = subscription.config.get("max_attempts")
+        .and_then(|v| v.as_u64())
+        .map(|n| (n as u32).clamp(1, MAX_ATTEMPTS_LIMIT))
+        .unwrap_or(DEFAULT_MAX_ATTEMPTS);
+
+    let body = encoded.data.clone();
+    let mut headers = HashMap::from([
+        ("Content-Type".to_string(), encoded.content_type().to_string()),
+        ("X-Narayana-Event".to_string(), subscription.event_name.0.clone()),
+        ("X-Narayana-Subscription".to_string(), subscription.id.0.clone()),
+    ]);
+    if let Some(schema) = &encoded.schema {
+        headers.insert("X-Narayana-Schema-Id".to_string(), schema.id.to_string());
+        headers.insert("X-Narayana-Schema-Version".to_string(), schema.version.to_string());
+    }
+
+    let mut delay = Duration::from_millis(100);
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let request = WorkerRequest {
+            method: "POST".to_string(),
+            url: path.to_string(),
+            headers: headers.clone(),
+            body: Some(body.clone()),
+            query: HashMap::new(),
+            client_ip: None,
+            request_id: uuid::Uuid::new_v4().to_string(),
+            worker_id: worker_id.to_string(),
+            edge_location: None,
+        };
+        let outcome = invoker.invoke(worker_id, request).await;
+
+        let retryable = {
+            let mut entry = stats.entry(subscription.id.clone()).or_default();
+            entry.attempts += 1;
+            match &outcome {
+                Ok(response) => {
+                    let time = response.metrics.execution_time_ms;
+                    entry.total_execution_time_ms += time;
+                    entry.max_execution_time_ms = entry.max_execution_time_ms.max(time);
+                    entry.total_cpu_time_ms += response.metrics.cpu_time_ms;
+                    entry.last_status = Some(response.status);
+                    if response.status < 400 {
+                        entry.delivered += 1;
+                        entry.last_error = None;
+                        return Ok(());
+                    }
+                    entry.last_error = Some(format!("Worker returned status {}", response.status));
+                    response.status >= 500
+                }
+                Err(e) => {
+                    entry.last_status = None;
+                    entry.last_error = Some(e.to_string());
+                    true
+                }
+            }
+        };
+        if !retryable || attempt >= max_attempts {
+            stats.entry(subscription.id.clone()).or_default().failed += 1;
+            return Err(Error::Storage(format!(
+                "Failed to deliver event to worker after {} attempt(s)",
+                attempt
+            )));
+        }
+        tracing::warn!("Worker delivery attempt {} failed, retrying", attempt);
+        tokio::time::sleep(delay).await;
+        delay = (delay * 2).min(MAX_DELAY); // Exponential backoff with cap
+    }
+}
@@ -0,0 +1,96 @@
+// Backfill subscription tests for narayana-rde
+// History replay from the earliest event or a timestamp, and the handover to
+// live delivery without gaps or duplicates
+
+mod common;
+
+use common::*;
+use narayana_rde::*;
+use serde_json::json;
+use std::time::Duration;
+
+async fn publish_seqs(manager: &RdeManager, seqs: std::ops::Range<u64>) {
+    for seq in seqs {
+        publish(manager, "reading", json!({"seq": seq})).await;
+    }
+}
+
+async fn subscribe_from(manager: &RdeManager, from: serde_json::Value) -> Result<SubscriptionId, narayana_core::Error> {
+    subscribe(manager, "robot:reading", json!({"worker_id": "replay", "from": from})).await
+}
+
+async fn wait_live(manager: &RdeManager, id: &SubscriptionId) {
+    for _ in 0..500 {
+        if !manager.is_backfilling(id) {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("backfill did not finish");
+}
+
+#[tokio::test]
+async fn test_backfill_from_earliest_then_live() {
+    let (manager, recorder) = setup(Recorder::new()).await;
+    publish_seqs(&manager, 0..3).await;
+
+    let id = subscribe_from(&manager, json!("earliest")).await.unwrap();
+    wait_live(&manager, &id).await;
+    publish_seqs(&manager, 3..5).await;
+    assert_eq!(recorder.seqs(), vec![0, 1, 2, 3, 4]);
+}
+
+#[tokio::test]
+async fn test_backfill_from_timestamp() {
+    let (manager, recorder) = setup(Recorder::new()).await;
+    publish_seqs(&manager, 0..2).await;
+    tokio::time::sleep(Duration::from_millis(5)).await;
+    let since = chrono::Utc::now();
+    publish_seqs(&manager, 2..4).await;
+
+    // RFC 3339 and Unix milliseconds are both accepted
+    let id = subscribe_from(&manager, json!(since.to_rfc3339())).await.unwrap();
+    wait_live(&manager, &id).await;
+    assert_eq!(recorder.seqs(), vec![2, 3]);
+
+    recorder.clear();
+    let id = subscribe_from(&manager, json!(since.timestamp_millis())).await.unwrap();
+    wait_live(&manager, &id).await;
+    assert_eq!(recorder.seqs(), vec![2, 3]);
+}
+
+#[tokio::test]
+async fn test_backfill_handover_without_gaps_or_duplicates() {
+    // Slow deliveries keep the replay running while new events arrive
+    let (manager, recorder) = setup(Recorder::new().with_delay(Duration::from_millis(2))).await;
+    publish_seqs(&manager, 0..40).await;
+
+    let id = subscribe_from(&manager, json!("earliest")).await.unwrap();
+    assert!(manager.is_backfilling(&id));
+    // Live events published mid-replay are held until history is done
+    publish_seqs(&manager, 40..80).await;
+    wait_live(&manager, &id).await;
+    publish_seqs(&manager, 80..85).await;
+
+    assert_eq!(recorder.seqs(), (0..85).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn test_backfill_options() {
+    let (manager, recorder) = setup(Recorder::new()).await;
+    publish_seqs(&manager, 0..2).await;
+
+    // "latest" is a plain live subscription
+    let id = subscribe_from(&manager, json!("latest")).await.unwrap();
+    assert!(!manager.is_backfilling(&id));
+    publish_seqs(&manager, 2..3).await;
+    assert_eq!(recorder.seqs(), vec![2]);
+
+    for invalid in [json!("yesterday"), json!(-5), json!({"at": 1})] {
+        assert!(subscribe_from(&manager, invalid).await.is_err());
+    }
+
+    // Nothing published yet: the backfill goes live right away
+    let id = subscribe(&manager, "robot:unknown", json!({"worker_id": "replay", "from": "earliest"})).await.unwrap();
+    wait_live(&manager, &id).await;
+}
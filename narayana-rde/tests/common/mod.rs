@@ -0,0 +1,197 @@
+// Shared helpers for the narayana-rde integration tests
+// A worker invoker that records every delivery, and an RDE manager with a
+// source actor ("robot") and an origin actor ("ops") to publish and subscribe
+
+#![allow(dead_code)]
+
+use narayana_rde::*;
+use narayana_storage::native_events::{EventsConfig, NativeEventsSystem};
+use narayana_storage::workers::{ExecutionMetrics, WorkerRequest, WorkerResponse};
+use std::sync::Arc;
+use std::time::Duration;
+
+pub const TOKEN: &str = "token-123456789012";
+pub const OTHER_TOKEN: &str = "other-token-123456789012";
+
+/// Records every successful delivery. Deliveries can be made to wait
+/// (`with_delay`, `slow_worker`, `slow_seq`) or to fail (`broken_worker`,
+/// `fail_once`); seqs are read from the payload's `seq` field
+#[derive(Default)]
+pub struct Recorder {
+    requests: parking_lot::Mutex<Vec<WorkerRequest>>,
+    delay: Duration,
+    slow_workers: Vec<(String, Duration)>,
+    slow_seqs: Vec<(u64, Duration)>,
+    broken_workers: Vec<String>,
+    fail_once: parking_lot::Mutex<Vec<u64>>,
+}
+
+impl Recorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wait before every delivery
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Wait before deliveries to `worker`
+    pub fn slow_worker(mut self, worker: &str, delay: Duration) -> Self {
+        self.slow_workers.push((worker.to_string(), delay));
+        self
+    }
+
+    /// Wait before deliveries of `seq`
+    pub fn slow_seq(mut self, seq: u64, delay: Duration) -> Self {
+        self.slow_seqs.push((seq, delay));
+        self
+    }
+
+    /// Fail every delivery to `worker`
+    pub fn broken_worker(mut self, worker: &str) -> Self {
+        self.broken_workers.push(worker.to_string());
+        self
+    }
+
+    /// Fail the first delivery of each of `seqs`
+    pub fn fail_once(self, seqs: Vec<u64>) -> Self {
+        *self.fail_once.lock() = seqs;
+        self
+    }
+
+    pub fn requests(&self) -> Vec<WorkerRequest> {
+        self.requests.lock().clone()
+    }
+
+    pub fn deliveries(&self) -> usize {
+        self.requests.lock().len()
+    }
+
+    pub fn clear(&self) {
+        self.requests.lock().clear();
+    }
+
+    /// The worker each delivery went to
+    pub fn workers(&self) -> Vec<String> {
+        self.requests.lock().iter().map(|request| request.worker_id.clone()).collect()
+    }
+
+    /// Each delivery's JSON payload
+    pub fn bodies(&self) -> Vec<serde_json::Value> {
+        self.requests.lock().iter().map(body).collect()
+    }
+
+    /// Each delivery's `seq`
+    pub fn seqs(&self) -> Vec<u64> {
+        self.bodies().iter().map(|body| body["seq"].as_u64().unwrap()).collect()
+    }
+}
+
+/// A delivery's JSON payload
+pub fn body(request: &WorkerRequest) -> serde_json::Value {
+    serde_json::from_slice(request.body.as_deref().unwrap_or_default()).unwrap()
+}
+
+#[async_trait::async_trait]
+impl narayana_rde::transports::worker::WorkerInvoker for Recorder {
+    async fn invoke(&self, worker_id: &str, request: WorkerRequest) -> anyhow::Result<WorkerResponse> {
+        let seq = serde_json::from_slice::<serde_json::Value>(request.body.as_deref().unwrap_or_default())
+            .ok()
+            .and_then(|body| body["seq"].as_u64());
+        let delay = self.delay
+            + self.slow_workers.iter().filter(|(w, _)| w == worker_id).map(|(_, delay)| *delay).sum::<Duration>()
+            + self.slow_seqs.iter().filter(|(s, _)| Some(*s) == seq).map(|(_, delay)| *delay).sum::<Duration>();
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+        if self.broken_workers.iter().any(|w| w == worker_id) {
+            anyhow::bail!("worker crashed");
+        }
+        if let Some(seq) = seq {
+            let mut fail_once = self.fail_once.lock();
+            if let Some(position) = fail_once.iter().position(|s| *s == seq) {
+                fail_once.remove(position);
+                anyhow::bail!("temporarily unavailable");
+            }
+        }
+        self.requests.lock().push(request);
+        Ok(ok_response())
+    }
+}
+
+/// An empty 200 response
+pub fn ok_response() -> WorkerResponse {
+    WorkerResponse {
+        status: 200,
+        headers: Default::default(),
+        body: Vec::new(),
+        metrics: ExecutionMetrics {
+            cpu_time_ms: 0,
+            memory_bytes: 0,
+            execution_time_ms: 0,
+            subrequests: 0,
+            request_size: 0,
+            response_size: 0,
+        },
+    }
+}
+
+/// A manager delivering to `recorder`, with no actors
+pub fn manager(recorder: Recorder) -> (Arc<RdeManager>, Arc<Recorder>) {
+    let config = EventsConfig { enable_persistence: false, ..Default::default() };
+    let recorder = Arc::new(recorder);
+    let manager = RdeManager::new(Arc::new(NativeEventsSystem::new(config))).with_worker_invoker(recorder.clone());
+    (Arc::new(manager), recorder)
+}
+
+/// A manager delivering to `recorder`, with the "robot" source and "ops" origin
+pub async fn setup(recorder: Recorder) -> (Arc<RdeManager>, Arc<Recorder>) {
+    let (manager, recorder) = manager(recorder);
+    register(&manager, "robot", ActorType::Source, TOKEN).await;
+    register(&manager, "ops", ActorType::Origin, TOKEN).await;
+    (manager, recorder)
+}
+
+pub async fn register(manager: &RdeManager, id: &str, actor_type: ActorType, token: &str) {
+    manager
+        .register_actor(Actor::new(ActorId::from(id), id.to_string(), actor_type, token.to_string()))
+        .await
+        .unwrap();
+}
+
+/// Subscribe "ops" to `event` with a worker transport
+pub async fn subscribe(manager: &RdeManager, event: &str, config: serde_json::Value) -> Result<SubscriptionId, narayana_core::Error> {
+    subscribe_as(manager, "ops", TOKEN, event, TransportType::Worker, config).await
+}
+
+pub async fn subscribe_as(
+    manager: &RdeManager,
+    actor: &str,
+    token: &str,
+    event: &str,
+    transport: TransportType,
+    config: serde_json::Value,
+) -> Result<SubscriptionId, narayana_core::Error> {
+    manager.subscribe(&ActorId::from(actor), token, event, transport, Some(config)).await
+}
+
+/// Publish `event` as "robot"
+pub async fn publish(manager: &RdeManager, event: &str, payload: serde_json::Value) {
+    publish_as(manager, "robot", event, payload).await;
+}
+
+pub async fn publish_as(manager: &RdeManager, actor: &str, event: &str, payload: serde_json::Value) {
+    manager.publish_event(&ActorId::from(actor), TOKEN, event, payload).await.unwrap();
+}
+
+/// Wait until the recorder has seen `count` deliveries
+pub async fn wait_for_deliveries(recorder: &Recorder, count: usize) {
+    for _ in 0..200 {
+        if recorder.deliveries() >= count {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+}
@@ -0,0 +1,119 @@
+// Consumer group tests for narayana-rde
+// Subscriptions sharing a group get each event once between them, round-robin
+// or least-loaded, while subscriptions outside the group get every event
+
+mod common;
+
+use common::*;
+use narayana_rde::*;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// `setup` plus the group members "replica-a" and "replica-b" and an "audit" subscriber
+async fn setup_group(recorder: Recorder) -> (Arc<RdeManager>, Arc<Recorder>) {
+    let (manager, recorder) = setup(recorder).await;
+    for (id, token) in [("replica-a", TOKEN), ("replica-b", OTHER_TOKEN), ("audit", TOKEN)] {
+        register(&manager, id, ActorType::Origin, token).await;
+    }
+    (manager, recorder)
+}
+
+async fn subscribe_moves(manager: &RdeManager, actor: &str, token: &str, config: serde_json::Value) -> Result<SubscriptionId, narayana_core::Error> {
+    subscribe_as(manager, actor, token, "robot:moved", TransportType::Worker, config).await
+}
+
+async fn publish_move(manager: &RdeManager) {
+    publish(manager, "moved", json!({"x": 1})).await;
+}
+
+fn count(workers: &[String], worker: &str) -> usize {
+    workers.iter().filter(|w| *w == worker).count()
+}
+
+#[tokio::test]
+async fn test_group_members_take_turns() {
+    let (manager, recorder) = setup_group(Recorder::new()).await;
+    subscribe_moves(&manager, "replica-a", TOKEN, json!({"worker_id": "a", "group": "movers"})).await.unwrap();
+    subscribe_moves(&manager, "replica-b", OTHER_TOKEN, json!({"worker_id": "b", "group": "movers"})).await.unwrap();
+    subscribe_moves(&manager, "audit", TOKEN, json!({"worker_id": "audit"})).await.unwrap();
+
+    for _ in 0..6 {
+        publish_move(&manager).await;
+    }
+    wait_for_deliveries(&recorder, 12).await;
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let workers = recorder.workers();
+    assert_eq!(workers.len(), 12, "each event once per group plus once for audit");
+    assert_eq!(count(&workers, "a"), 3);
+    assert_eq!(count(&workers, "b"), 3);
+    assert_eq!(count(&workers, "audit"), 6);
+}
+
+#[tokio::test]
+async fn test_least_loaded_passes_over_a_busy_member() {
+    let (manager, recorder) = setup_group(Recorder::new().slow_worker("a", Duration::from_millis(500))).await;
+    let config = |worker: &str| json!({"worker_id": worker, "group": "movers", "group_strategy": "least_loaded"});
+    subscribe_moves(&manager, "replica-a", TOKEN, config("a")).await.unwrap();
+    subscribe_moves(&manager, "replica-b", OTHER_TOKEN, config("b")).await.unwrap();
+
+    // Publish concurrently so "a" is still busy with its event when the rest arrive
+    let publishers: Vec<_> = (0..5)
+        .map(|i| {
+            let manager = manager.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(20 * i)).await;
+                publish_move(&manager).await;
+            })
+        })
+        .collect();
+    for publisher in publishers {
+        publisher.await.unwrap();
+    }
+    wait_for_deliveries(&recorder, 5).await;
+    let workers = recorder.workers();
+    assert_eq!(workers.len(), 5);
+    assert!(count(&workers, "a") <= 1, "busy member got {:?}", workers);
+}
+
+#[tokio::test]
+async fn test_invalid_group_configs_are_refused() {
+    let (manager, _) = setup_group(Recorder::new()).await;
+    let invalid = [
+        json!({"worker_id": "a", "group": ""}),
+        json!({"worker_id": "a", "group": "has spaces"}),
+        json!({"worker_id": "a", "group": 7}),
+        json!({"worker_id": "a", "group_strategy": "least_loaded"}),
+        json!({"worker_id": "a", "group": "movers", "group_strategy": "random"}),
+        json!({"worker_id": "a", "group": "movers", "from": "earliest"}),
+    ];
+    for config in invalid {
+        assert!(subscribe_moves(&manager, "replica-a", TOKEN, config.clone()).await.is_err(), "{}", config);
+    }
+
+    // Members of a group must share its strategy
+    let member = subscribe_moves(&manager, "replica-a", TOKEN, json!({"worker_id": "a", "group": "movers"})).await.unwrap();
+    let err = subscribe_moves(&manager, "replica-b", OTHER_TOKEN, json!({"worker_id": "b", "group": "movers", "group_strategy": "least_loaded"}))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("group_strategy"));
+    // A lone member can change it
+    manager
+        .update_subscription_config(&ActorId::from("replica-a"), TOKEN, &member, json!({"worker_id": "a", "group": "movers", "group_strategy": "least_loaded"}))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_remaining_members_get_every_event() {
+    let (manager, recorder) = setup_group(Recorder::new()).await;
+    let a = subscribe_moves(&manager, "replica-a", TOKEN, json!({"worker_id": "a", "group": "movers"})).await.unwrap();
+    subscribe_moves(&manager, "replica-b", OTHER_TOKEN, json!({"worker_id": "b", "group": "movers"})).await.unwrap();
+    assert!(manager.unsubscribe(&ActorId::from("replica-a"), TOKEN, &a).await.unwrap());
+
+    for _ in 0..3 {
+        publish_move(&manager).await;
+    }
+    wait_for_deliveries(&recorder, 3).await;
+    assert_eq!(recorder.workers(), vec!["b", "b", "b"]);
+}
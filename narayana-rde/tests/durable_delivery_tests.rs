@@ -0,0 +1,259 @@
+// Durable delivery tests for narayana-rde: retries, dead letters, recovery
+
+use narayana_rde::*;
+use narayana_storage::native_events::{EventsConfig, NativeEventsSystem};
+use std::sync::Arc;
+use std::time::Duration;
+
+const TOKEN: &str = "token-123456789012";
+
+/// Answers worker invocations with canned statuses (then 200s)
+struct FlakyInvoker {
+    statuses: parking_lot::Mutex<Vec<u16>>,
+    calls: parking_lot::Mutex<usize>,
+}
+
+impl FlakyInvoker {
+    fn new(statuses: &[u16]) -> Arc<Self> {
+        Arc::new(Self {
+            statuses: parking_lot::Mutex::new(statuses.iter().rev().copied().collect()),
+            calls: parking_lot::Mutex::new(0),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl narayana_rde::transports::worker::WorkerInvoker for FlakyInvoker {
+    async fn invoke(
+        &self,
+        _worker_id: &str,
+        _request: narayana_storage::workers::WorkerRequest,
+    ) -> anyhow::Result<narayana_storage::workers::WorkerResponse> {
+        *self.calls.lock() += 1;
+        let status = self.statuses.lock().pop().unwrap_or(200);
+        Ok(narayana_storage::workers::WorkerResponse {
+            status,
+            headers: Default::default(),
+            body: Vec::new(),
+            metrics: narayana_storage::workers::ExecutionMetrics {
+                cpu_time_ms: 1,
+                memory_bytes: 0,
+                execution_time_ms: 1,
+                subrequests: 0,
+                request_size: 0,
+                response_size: 0,
+            },
+        })
+    }
+}
+
+async fn setup(invoker: Arc<FlakyInvoker>) -> (RdeManager, Arc<NativeEventsSystem>) {
+    let mut config = EventsConfig::default();
+    config.enable_persistence = false;
+    let native_events = Arc::new(NativeEventsSystem::new(config));
+    let manager = RdeManager::new(native_events.clone()).with_worker_invoker(invoker);
+    let source = Actor::new(ActorId::from("robot"), "Robot".to_string(), ActorType::Source, TOKEN.to_string());
+    let origin = Actor::new(ActorId::from("ops"), "Ops".to_string(), ActorType::Origin, TOKEN.to_string());
+    let other = Actor::new(ActorId::from("other"), "Other".to_string(), ActorType::Origin, TOKEN.to_string());
+    manager.register_actor(source).await.unwrap();
+    manager.register_actor(origin).await.unwrap();
+    manager.register_actor(other).await.unwrap();
+    (manager, native_events)
+}
+
+async fn subscribe(manager: &RdeManager, retry: serde_json::Value) -> Result<SubscriptionId, narayana_core::Error> {
+    // Client errors are final for the worker transport, so each delivery is one call
+    manager
+        .subscribe(
+            &ActorId::from("ops"),
+            TOKEN,
+            "robot:collision",
+            TransportType::Worker,
+            Some(serde_json::json!({"worker_id": "w1", "retry": retry})),
+        )
+        .await
+}
+
+async fn publish(manager: &RdeManager, force: f64) {
+    manager
+        .publish_event(&ActorId::from("robot"), TOKEN, "collision", serde_json::json!({"force": force}))
+        .await
+        .unwrap();
+}
+
+/// Let the backoff pass, then retry what is due
+async fn retry(manager: &RdeManager) -> usize {
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    manager.retry_pending_deliveries().await
+}
+
+#[tokio::test]
+async fn test_failed_delivery_is_retried() {
+    let invoker = FlakyInvoker::new(&[400, 400]);
+    let (manager, _) = setup(invoker.clone()).await;
+    let id = subscribe(&manager, serde_json::json!({"max_attempts": 5, "initial_backoff_ms": 1})).await.unwrap();
+
+    publish(&manager, 1.0).await;
+    let stats = manager.get_delivery_stats(&id).unwrap();
+    assert_eq!((stats.pending, stats.retries), (1, 0));
+
+    assert_eq!(retry(&manager).await, 1);
+    assert_eq!(manager.get_delivery_stats(&id).unwrap().pending, 1);
+    assert_eq!(retry(&manager).await, 1);
+    let stats = manager.get_delivery_stats(&id).unwrap();
+    assert_eq!((stats.pending, stats.retries, stats.recovered, stats.dead_lettered), (0, 2, 1, 0));
+    assert!(stats.last_error.is_none());
+    assert_eq!(*invoker.calls.lock(), 3);
+    assert_eq!(retry(&manager).await, 0);
+}
+
+#[tokio::test]
+async fn test_exhausted_delivery_is_dead_lettered_and_redelivered() {
+    let invoker = FlakyInvoker::new(&[400, 400]);
+    let (manager, _) = setup(invoker.clone()).await;
+    let id = subscribe(&manager, serde_json::json!({"max_attempts": 2, "initial_backoff_ms": 1})).await.unwrap();
+
+    publish(&manager, 2.5).await;
+    retry(&manager).await;
+    let stats = manager.get_delivery_stats(&id).unwrap();
+    assert_eq!((stats.pending, stats.dead_lettered), (0, 1));
+
+    let letters = manager.dead_letters(&ActorId::from("ops"), TOKEN, &id, 0, 10).await.unwrap();
+    assert_eq!(letters.len(), 1);
+    assert_eq!(letters[0].event_name, EventName::from("robot:collision"));
+    assert_eq!(letters[0].payload, serde_json::json!({"force": 2.5}));
+    assert_eq!(letters[0].attempts, 2);
+    assert!(manager.dead_letters(&ActorId::from("ops"), TOKEN, &id, letters[0].sequence + 1, 10).await.unwrap().is_empty());
+
+    // Only the subscription's owner can read or redeliver
+    assert!(manager.dead_letters(&ActorId::from("other"), TOKEN, &id, 0, 10).await.is_err());
+    assert!(manager.redeliver_dead_letters(&ActorId::from("other"), TOKEN, &id, DeadLetterSelection::From(0)).await.is_err());
+
+    let report = manager.redeliver_dead_letters(&ActorId::from("ops"), TOKEN, &id, DeadLetterSelection::From(0)).await.unwrap();
+    assert_eq!(report, RedeliveryReport { queued: 1, skipped: 0, missing: Vec::new() });
+    // Queued letters leave the dead-letter stream
+    assert!(manager.dead_letters(&ActorId::from("ops"), TOKEN, &id, 0, 10).await.unwrap().is_empty());
+    assert_eq!(manager.retry_pending_deliveries().await, 1);
+    assert_eq!(manager.get_delivery_stats(&id).unwrap().recovered, 1);
+    assert_eq!(*invoker.calls.lock(), 3);
+}
+
+#[tokio::test]
+async fn test_dead_letters_are_inspected_and_redelivered() {
+    let invoker = FlakyInvoker::new(&[400, 400, 400]);
+    let (manager, _) = setup(invoker.clone()).await;
+    let id = subscribe(&manager, serde_json::json!(false)).await.unwrap();
+    let ops = ActorId::from("ops");
+    for force in [1.0, 2.0, 3.0] {
+        publish(&manager, force).await;
+    }
+    let letters = manager.dead_letters(&ops, TOKEN, &id, 0, 10).await.unwrap();
+    assert_eq!(letters.len(), 3);
+
+    let letter = manager.dead_letter(&ops, TOKEN, &id, letters[1].sequence).await.unwrap().unwrap();
+    assert_eq!(letter.payload, serde_json::json!({"force": 2.0}));
+    assert!(manager.dead_letter(&ops, TOKEN, &id, 999).await.unwrap().is_none());
+    assert!(manager.dead_letter(&ActorId::from("other"), TOKEN, &id, letter.sequence).await.is_err());
+
+    let summary = manager.dead_letter_summary();
+    assert_eq!(summary.len(), 1);
+    assert_eq!((summary[0].subscription_id.clone(), summary[0].count), (id.clone(), 3));
+
+    // Admins redeliver just some sequences; the others stay dead
+    let selection = DeadLetterSelection::Sequences(vec![letters[2].sequence, letters[2].sequence, 999]);
+    let report = manager.redeliver_subscription_dead_letters(&id, selection).await.unwrap();
+    assert_eq!(report, RedeliveryReport { queued: 1, skipped: 0, missing: vec![999] });
+    let remaining = manager.subscription_dead_letters(&id, 0, 10);
+    assert_eq!(remaining.iter().map(|l| l.sequence).collect::<Vec<_>>(), vec![letters[0].sequence, letters[1].sequence]);
+
+    // ... or everything from a sequence on; the older letter stays dead
+    let report = manager.redeliver_subscription_dead_letters(&id, DeadLetterSelection::From(letters[1].sequence)).await.unwrap();
+    assert_eq!(report.queued, 1);
+    let remaining = manager.subscription_dead_letters(&id, 0, 10);
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].sequence, letters[0].sequence);
+    assert_eq!(manager.dead_letter_summary()[0].count, 1);
+    assert_eq!(manager.retry_pending_deliveries().await, 2);
+    assert_eq!(*invoker.calls.lock(), 5);
+    assert!(manager.redeliver_subscription_dead_letters(&SubscriptionId::new(), DeadLetterSelection::From(0)).await.is_err());
+    let empty = manager.redeliver_subscription_dead_letters(&id, DeadLetterSelection::Sequences(Vec::new())).await;
+    assert!(matches!(empty, Err(narayana_core::Error::Query(_))));
+    let too_many = DeadLetterSelection::Sequences((0..1001).collect());
+    assert!(matches!(manager.redeliver_subscription_dead_letters(&id, too_many).await, Err(narayana_core::Error::Query(_))));
+
+    // A letter failing again is dead-lettered anew, as the subscription has no retries
+    let invoker = FlakyInvoker::new(&[400, 400]);
+    let (manager, _) = setup(invoker).await;
+    let id = subscribe(&manager, serde_json::json!(false)).await.unwrap();
+    publish(&manager, 4.0).await;
+    let sequence = manager.subscription_dead_letters(&id, 0, 10)[0].sequence;
+    let report = manager.redeliver_dead_letters(&ops, TOKEN, &id, DeadLetterSelection::Sequences(vec![sequence])).await.unwrap();
+    assert_eq!(report.queued, 1);
+    assert_eq!(manager.retry_pending_deliveries().await, 1);
+    let letters = manager.subscription_dead_letters(&id, 0, 10);
+    assert_eq!(letters.len(), 1);
+    assert!(letters[0].sequence > sequence);
+    assert_eq!(letters[0].payload, serde_json::json!({"force": 4.0}));
+}
+
+#[tokio::test]
+async fn test_redelivery_reports_letters_past_the_cap() {
+    let invoker = FlakyInvoker::new(&[400; 1001]);
+    let (manager, _) = setup(invoker).await;
+    let id = subscribe(&manager, serde_json::json!(false)).await.unwrap();
+    for force in 0..1001 {
+        publish(&manager, force as f64).await;
+    }
+    let report = manager.redeliver_subscription_dead_letters(&id, DeadLetterSelection::From(0)).await.unwrap();
+    assert_eq!(report, RedeliveryReport { queued: 1000, skipped: 1, missing: Vec::new() });
+    assert_eq!(manager.subscription_dead_letters(&id, 0, 10).len(), 1);
+}
+
+#[tokio::test]
+async fn test_retry_policy_options() {
+    let invoker = FlakyInvoker::new(&[400]);
+    let (manager, _) = setup(invoker).await;
+
+    // Invalid policies are rejected when subscribing
+    assert!(subscribe(&manager, serde_json::json!({"max_attempts": 0})).await.is_err());
+    assert!(subscribe(&manager, serde_json::json!({"initial_backoff_ms": 100, "max_backoff_ms": 10})).await.is_err());
+    assert!(subscribe(&manager, serde_json::json!("often")).await.is_err());
+
+    // Without retries a failed delivery is dead-lettered right away
+    let id = subscribe(&manager, serde_json::json!(false)).await.unwrap();
+    publish(&manager, 1.0).await;
+    let stats = manager.get_delivery_stats(&id).unwrap();
+    assert_eq!((stats.pending, stats.dead_lettered), (0, 1));
+    assert_eq!(retry(&manager).await, 0);
+    let letters = manager.dead_letters(&ActorId::from("ops"), TOKEN, &id, 0, 10).await.unwrap();
+    assert_eq!(letters[0].attempts, 1);
+
+    let policy = RetryPolicy::from_config(&serde_json::json!({"retry": {"initial_backoff_ms": 100, "max_backoff_ms": 250}}))
+        .unwrap()
+        .unwrap();
+    assert_eq!(policy.max_attempts, 5);
+    let delays: Vec<u128> = (1..=4).map(|n| policy.backoff(n).as_millis()).collect();
+    assert_eq!(delays, vec![100, 200, 250, 250]);
+}
+
+#[tokio::test]
+async fn test_pending_deliveries_recover_and_unsubscribe_drops_them() {
+    let invoker = FlakyInvoker::new(&[400]);
+    let (manager, native_events) = setup(invoker.clone()).await;
+    let id = subscribe(&manager, serde_json::json!({"initial_backoff_ms": 1})).await.unwrap();
+    publish(&manager, 1.0).await;
+
+    // A manager over the same event system picks the queue up from the journal
+    let successor = RdeManager::new_in_memory(native_events.clone());
+    assert_eq!(successor.recover_pending_deliveries().await, 1);
+    assert_eq!(successor.get_delivery_stats(&id).unwrap().pending, 1);
+    // It doesn't know the subscription, so the delivery is dropped
+    assert_eq!(retry(&successor).await, 1);
+    assert_eq!(successor.get_delivery_stats(&id).unwrap().pending, 0);
+
+    assert!(manager.unsubscribe(&ActorId::from("ops"), TOKEN, &id).await.unwrap());
+    assert!(manager.get_delivery_stats(&id).is_none());
+    assert_eq!(retry(&manager).await, 0);
+    assert_eq!(RdeManager::new(native_events).recover_pending_deliveries().await, 0);
+    assert_eq!(*invoker.calls.lock(), 1);
+}
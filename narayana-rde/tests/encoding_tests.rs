@@ -0,0 +1,244 @@
+// Payload encoding tests for narayana-rde
+// Avro/Protobuf codecs, the schema registry, per-subscription negotiation and
+// transcoding between publishers and subscribers
+
+mod common;
+
+use common::*;
+use narayana_rde::encoding::{self, Codec, PayloadEncoding};
+use narayana_rde::*;
+
+fn avro_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "record",
+        "name": "Reading",
+        "fields": [
+            {"name": "sensor", "type": "string"},
+            {"name": "value", "type": "double"},
+            {"name": "seq", "type": "long"},
+            {"name": "level", "type": {"type": "enum", "name": "Level", "symbols": ["LOW", "HIGH"]}},
+            {"name": "tags", "type": {"type": "array", "items": "string"}},
+            {"name": "labels", "type": {"type": "map", "values": "int"}},
+            {"name": "note", "type": ["null", "string"], "default": null},
+            {"name": "raw", "type": "bytes"},
+            {"name": "origin", "type": {"type": "record", "name": "Origin", "fields": [
+                {"name": "x", "type": "float"},
+                {"name": "ok", "type": "boolean"}
+            ]}}
+        ]
+    })
+}
+
+fn proto_schema() -> serde_json::Value {
+    serde_json::json!({
+        "name": "Reading",
+        "fields": [
+            {"name": "sensor", "number": 1, "type": "string"},
+            {"name": "value", "number": 2, "type": "double"},
+            {"name": "seq", "number": 3, "type": "int64"},
+            {"name": "offsets", "number": 4, "type": "sint32", "repeated": true},
+            {"name": "delta", "number": 5, "type": "int32"},
+            {"name": "origin", "number": 6, "type": "message", "fields": [
+                {"name": "x", "number": 1, "type": "float"},
+                {"name": "ok", "number": 2, "type": "bool"}
+            ]},
+            {"name": "raw", "number": 7, "type": "bytes"},
+            {"name": "tags", "number": 8, "type": "string", "repeated": true}
+        ]
+    })
+}
+
+#[test]
+fn test_avro_round_trip() {
+    let codec = Codec::compile(SchemaFormat::Avro, &avro_schema()).unwrap();
+    let payload = serde_json::json!({
+        "sensor": "t1",
+        "value": 21.5,
+        "seq": -9_000_000_000i64,
+        "level": "HIGH",
+        "tags": ["a", "b"],
+        "labels": {"zone": 3},
+        "note": "calibrated",
+        "raw": "AAEC",
+        "origin": {"x": 0.5, "ok": true}
+    });
+    let data = codec.encode(&payload).unwrap();
+    assert!(data.len() < serde_json::to_vec(&payload).unwrap().len());
+    assert_eq!(codec.decode(&data).unwrap(), payload);
+
+    // Optional fields fall back to their default
+    let mut partial = payload.clone();
+    partial.as_object_mut().unwrap().remove("note");
+    assert_eq!(codec.decode(&codec.encode(&partial).unwrap()).unwrap()["note"], serde_json::Value::Null);
+
+    // Values that don't fit the schema are refused
+    let mut wrong = payload.clone();
+    wrong["level"] = serde_json::json!("MEDIUM");
+    assert!(codec.encode(&wrong).is_err());
+    wrong = payload.clone();
+    wrong.as_object_mut().unwrap().remove("sensor");
+    assert!(codec.encode(&wrong).is_err());
+
+    // Truncated or padded input is rejected
+    assert!(codec.decode(&data[..data.len() - 1]).is_err());
+    let mut padded = data.clone();
+    padded.push(0);
+    assert!(codec.decode(&padded).is_err());
+}
+
+#[test]
+fn test_protobuf_round_trip() {
+    let codec = Codec::compile(SchemaFormat::Protobuf, &proto_schema()).unwrap();
+    let payload = serde_json::json!({
+        "sensor": "t1",
+        "value": 21.5,
+        "seq": 42,
+        "offsets": [-1, 0, 300],
+        "delta": -7,
+        "origin": {"x": 0.5, "ok": true},
+        "raw": "AAEC",
+        "tags": ["a", "b"]
+    });
+    let data = codec.encode(&payload).unwrap();
+    assert_eq!(codec.decode(&data).unwrap(), payload);
+    // Absent fields stay absent
+    assert_eq!(codec.decode(&codec.encode(&serde_json::json!({"seq": 1})).unwrap()).unwrap(), serde_json::json!({"seq": 1}));
+
+    // Readers with an older schema skip fields they don't know
+    let old = Codec::compile(SchemaFormat::Protobuf, &serde_json::json!({
+        "name": "Reading",
+        "fields": [{"name": "sensor", "number": 1, "type": "string"}, {"name": "seq", "number": 3, "type": "int64"}]
+    }))
+    .unwrap();
+    assert_eq!(old.decode(&data).unwrap(), serde_json::json!({"sensor": "t1", "seq": 42}));
+
+    // Out of range and mistyped values are refused
+    assert!(codec.encode(&serde_json::json!({"delta": 1u64 << 40})).is_err());
+    assert!(codec.encode(&serde_json::json!({"sensor": 5})).is_err());
+}
+
+#[test]
+fn test_invalid_schemas_rejected() {
+    let invalid = [
+        (SchemaFormat::Avro, serde_json::json!({"type": "record", "fields": []})),
+        (SchemaFormat::Avro, serde_json::json!({"type": "record", "name": "R", "fields": [{"name": "a", "type": "Unknown"}]})),
+        (SchemaFormat::Avro, serde_json::json!(["null", ["string"]])),
+        (SchemaFormat::Protobuf, serde_json::json!({"name": "M", "fields": [{"name": "a", "number": 0, "type": "int32"}]})),
+        (SchemaFormat::Protobuf, serde_json::json!({"name": "M", "fields": [{"name": "a", "number": 19500, "type": "int32"}]})),
+        (SchemaFormat::Protobuf, serde_json::json!({"name": "M", "fields": [
+            {"name": "a", "number": 1, "type": "int32"},
+            {"name": "b", "number": 1, "type": "string"}
+        ]})),
+        (SchemaFormat::Protobuf, serde_json::json!({"name": "M", "fields": [{"name": "a", "number": 1, "type": "enum"}]})),
+    ];
+    for (format, definition) in invalid {
+        assert!(Codec::compile(format, &definition).is_err(), "{} schema accepted: {}", format, definition);
+    }
+}
+
+#[test]
+fn test_schema_registry_versions() {
+    let registry = SchemaRegistry::new();
+    let v1 = registry.register("robot:reading", SchemaFormat::Avro, avro_schema()).unwrap();
+    assert_eq!(v1.version, 1);
+    // Re-registering the latest definition is a no-op
+    assert_eq!(registry.register("robot:reading", SchemaFormat::Avro, avro_schema()).unwrap().id, v1.id);
+
+    let v2_definition = serde_json::json!({"type": "record", "name": "Reading", "fields": [{"name": "sensor", "type": "string"}]});
+    let v2 = registry.register("robot:reading", SchemaFormat::Avro, v2_definition).unwrap();
+    assert_eq!(v2.version, 2);
+    assert_eq!(registry.latest("robot:reading", SchemaFormat::Avro).unwrap().id, v2.id);
+    assert_eq!(registry.version("robot:reading", SchemaFormat::Avro, 1).unwrap().id, v1.id);
+    assert!(registry.version("robot:reading", SchemaFormat::Avro, 0).is_none());
+    assert_eq!(registry.get(v2.id).unwrap().version, 2);
+    assert_eq!(registry.versions("robot:reading", SchemaFormat::Avro).len(), 2);
+
+    // Formats are versioned independently
+    assert!(registry.latest("robot:reading", SchemaFormat::Protobuf).is_none());
+    assert!(registry.register("robot:reading", SchemaFormat::Protobuf, serde_json::json!({"fields": "x"})).is_err());
+    assert!(registry.latest("robot:reading", SchemaFormat::Protobuf).is_none());
+}
+
+#[test]
+fn test_content_type_negotiation() {
+    assert_eq!(PayloadEncoding::from_content_type("avro/binary"), Some(PayloadEncoding::Avro));
+    assert_eq!(PayloadEncoding::from_content_type("Application/X-Protobuf; proto=Reading"), Some(PayloadEncoding::Protobuf));
+    assert_eq!(PayloadEncoding::from_content_type("text/csv"), None);
+
+    assert_eq!(encoding::accepted_encodings(&serde_json::json!({})).unwrap(), vec![PayloadEncoding::Json]);
+    assert!(encoding::accepted_encodings(&serde_json::json!({"content_type": "text/csv"})).is_err());
+
+    let registry = SchemaRegistry::new();
+    registry.register("robot:reading", SchemaFormat::Avro, avro_schema()).unwrap();
+    let payload = serde_json::json!({"sensor": "t1"});
+
+    // The preferred encoding has no schema for this event: fall through to JSON
+    let config = serde_json::json!({"content_type": ["application/x-protobuf", "application/json"]});
+    let encoded = encoding::encode_for_subscription(&config, "robot:reading", &payload, &registry).unwrap();
+    assert_eq!(encoded.encoding, PayloadEncoding::Json);
+    assert!(encoded.schema.is_none());
+
+    // The payload doesn't fit the Avro schema, and JSON wasn't accepted
+    let config = serde_json::json!({"content_type": "application/avro"});
+    assert!(encoding::encode_for_subscription(&config, "robot:reading", &payload, &registry).is_err());
+    assert!(encoding::encode_for_subscription(&config, "robot:other", &payload, &registry).is_err());
+}
+
+#[tokio::test]
+async fn test_encoded_publish_transcodes_per_subscription() {
+    let (manager, recorder) = setup(Recorder::new()).await;
+    let robot = ActorId::from("robot");
+    let ops = ActorId::from("ops");
+
+    // Only source actors register schemas for their own events
+    assert!(manager.register_schema(&ops, TOKEN, "reading", SchemaFormat::Avro, avro_schema()).await.is_err());
+    let avro = manager.register_schema(&robot, TOKEN, "reading", SchemaFormat::Avro, avro_schema()).await.unwrap();
+    let proto = manager.register_schema(&robot, TOKEN, "reading", SchemaFormat::Protobuf, proto_schema()).await.unwrap();
+    assert_eq!(avro.event_name, "robot:reading");
+
+    // Binary encodings need a transport that carries raw bodies
+    let avro_only = serde_json::json!({"worker_id": "avro", "content_type": "application/avro"});
+    assert!(manager.subscribe(&ops, TOKEN, "robot:reading", TransportType::Sse, Some(avro_only.clone())).await.is_err());
+    manager.subscribe(&ops, TOKEN, "robot:reading", TransportType::Worker, Some(avro_only)).await.unwrap();
+    manager
+        .subscribe(&ops, TOKEN, "robot:reading", TransportType::Worker, Some(serde_json::json!({"worker_id": "legacy"})))
+        .await
+        .unwrap();
+
+    // Publish protobuf; the Avro subscriber can't encode this payload (the schemas
+    // differ), so only the JSON subscriber gets it
+    let payload = serde_json::json!({"sensor": "t1", "value": 21.5, "seq": 42});
+    let data = proto.codec().encode(&payload).unwrap();
+    manager.publish_encoded_event(&robot, TOKEN, "reading", "application/x-protobuf", None, &data).await.unwrap();
+    {
+        let requests = recorder.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].worker_id, "legacy");
+        assert_eq!(requests[0].headers["Content-Type"], "application/json");
+        let body: serde_json::Value = serde_json::from_slice(requests[0].body.as_ref().unwrap()).unwrap();
+        assert_eq!(body, payload);
+    }
+
+    // A full reading reaches both: Avro bytes with schema headers, and JSON
+    let reading = serde_json::json!({
+        "sensor": "t1", "value": 21.5, "seq": 43, "level": "LOW", "tags": [], "labels": {},
+        "note": null, "raw": "", "origin": {"x": 1.0, "ok": false}
+    });
+    manager.publish_event(&robot, TOKEN, "reading", reading.clone()).await.unwrap();
+    {
+        let requests = recorder.requests();
+        assert_eq!(requests.len(), 3);
+        let avro_request = requests.iter().skip(1).find(|r| r.worker_id == "avro").unwrap();
+        assert_eq!(avro_request.headers["Content-Type"], "application/avro");
+        assert_eq!(avro_request.headers["X-Narayana-Schema-Id"], avro.id.to_string());
+        assert_eq!(avro_request.headers["X-Narayana-Schema-Version"], "1");
+        assert_eq!(avro.codec().decode(avro_request.body.as_ref().unwrap()).unwrap(), reading);
+        let legacy = requests.iter().skip(1).find(|r| r.worker_id == "legacy").unwrap();
+        assert_eq!(serde_json::from_slice::<serde_json::Value>(legacy.body.as_ref().unwrap()).unwrap(), reading);
+    }
+
+    // Encoded publishes need a matching schema
+    assert!(manager.publish_encoded_event(&robot, TOKEN, "reading", "application/avro", Some(2), &data).await.is_err());
+    assert!(manager.publish_encoded_event(&robot, TOKEN, "reading", "text/csv", None, &data).await.is_err());
+    assert!(manager.publish_encoded_event(&robot, "wrong-token-1234567", "reading", "application/json", None, b"{}").await.is_err());
+}
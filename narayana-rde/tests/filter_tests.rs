@@ -0,0 +1,92 @@
+// Subscription filter tests for narayana-rde
+// Expression parsing and matching, and delivery of only the events whose
+// payload passes a subscription's filter
+
+mod common;
+
+use common::*;
+use narayana_rde::*;
+use serde_json::json;
+
+fn matches(expression: &str, payload: serde_json::Value) -> bool {
+    SubscriptionFilter::parse(expression).unwrap().matches(&payload)
+}
+
+#[test]
+fn test_filter_comparisons_and_logic() {
+    let payload = json!({
+        "level": "error",
+        "reading": {"temp": 81.5, "ok": false},
+        "zone": "b",
+        "tags": ["hot", "roof"],
+        "count": 3,
+    });
+
+    assert!(matches("$.level == 'error'", payload.clone()));
+    assert!(matches("level == \"error\"", payload.clone()));
+    assert!(matches("$.reading.temp >= 80.5 && $.count < 4", payload.clone()));
+    assert!(matches("$.count == 3.0", payload.clone()));
+    assert!(matches("$.tags[1] == 'roof'", payload.clone()));
+    assert!(matches("$.zone in ['a', 'b']", payload.clone()));
+    assert!(matches("$.level == 'info' || ($.zone != 'a' && !$.reading.ok)", payload.clone()));
+    assert!(matches("$.tags", payload.clone()));
+
+    assert!(!matches("$.reading.ok", payload.clone()));
+    assert!(!matches("$.missing", payload.clone()));
+    assert!(!matches("$.zone in []", payload.clone()));
+    assert!(!matches("!($.count > 1)", payload.clone()));
+    // Ordering only holds between numbers or between strings
+    assert!(!matches("$.level > 1", payload.clone()));
+    assert!(matches("$.zone > 'a'", payload.clone()));
+    // Missing fields compare as null
+    assert!(matches("$.missing == null", payload.clone()));
+    assert!(matches("$.missing != 'x'", payload));
+}
+
+#[test]
+fn test_filter_rejects_invalid_expressions() {
+    for expression in [
+        "",
+        "$.level = 'error'",
+        "$.level ==",
+        "($.count > 1",
+        "$.count > 1)",
+        "'error'",
+        "$.zone in ['a' 'b']",
+        "$.zone in [$.level]",
+        "$.level == 'open",
+        "$",
+        "$.a && && $.b",
+    ] {
+        assert!(SubscriptionFilter::parse(expression).is_err(), "{:?} should not parse", expression);
+    }
+    // Nesting is bounded
+    assert!(SubscriptionFilter::parse(&format!("{}$.a{}", "(".repeat(100), ")".repeat(100))).is_err());
+    assert!(SubscriptionFilter::parse(&format!("{}$.a", "!".repeat(100))).is_err());
+
+    assert!(SubscriptionFilter::from_config(&json!({})).unwrap().is_none());
+    assert!(SubscriptionFilter::from_config(&json!({"filter": 5})).is_err());
+}
+
+async fn subscribe_filtered(manager: &RdeManager, filter: serde_json::Value) -> Result<SubscriptionId, narayana_core::Error> {
+    subscribe(manager, "robot:reading", json!({"worker_id": "alerts", "filter": filter})).await
+}
+
+#[tokio::test]
+async fn test_only_matching_events_are_delivered() {
+    let (manager, recorder) = setup(Recorder::new()).await;
+    subscribe_filtered(&manager, json!("$.temp > 80 && $.zone in ['roof']")).await.unwrap();
+
+    for (seq, temp, zone) in [(0, 90, "roof"), (1, 70, "roof"), (2, 95, "lab"), (3, 85, "roof")] {
+        publish(&manager, "reading", json!({"seq": seq, "temp": temp, "zone": zone})).await;
+    }
+    assert_eq!(recorder.seqs(), vec![0, 3]);
+}
+
+#[tokio::test]
+async fn test_invalid_filter_rejects_subscription() {
+    let (manager, _recorder) = setup(Recorder::new()).await;
+    assert!(subscribe_filtered(&manager, json!("$.temp >")).await.is_err());
+    assert!(subscribe_filtered(&manager, json!(["temp"])).await.is_err());
+    assert!(subscribe_filtered(&manager, serde_json::Value::Null).await.is_ok());
+}
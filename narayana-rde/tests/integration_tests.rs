@@ -377,3 +377,121 @@ async fn test_get_actor_sanitization() {
     assert_eq!(actor.actor_type, ActorType::Source);
 }
 
+
+#[tokio::test]
+async fn test_anomaly_sink_publishes_to_subscribers() {
+    use narayana_rde::anomaly::{RdeAnomalySink, ANOMALY_ACTOR_ID, ANOMALY_EVENT_NAME};
+    use narayana_storage::anomaly_detection::{AnomalyEvent, AnomalySink};
+
+    let manager = Arc::new(create_test_manager());
+    let sink = RdeAnomalySink::register(manager.clone()).await.unwrap();
+
+    let origin = create_origin_actor("ops-dashboard", "token-ops-dashboard-123456789012");
+    manager.register_actor(origin).await.unwrap();
+    let subscription_id = manager.subscribe(
+        &ActorId::from("ops-dashboard"),
+        "token-ops-dashboard-123456789012",
+        &format!("{}:{}", ANOMALY_ACTOR_ID, ANOMALY_EVENT_NAME),
+        TransportType::Sse,
+        None,
+    ).await.unwrap();
+    let (tx, mut rx) = tokio::sync::mpsc::channel(4);
+    manager.register_sse_connection(subscription_id, tx);
+
+    let event = AnomalyEvent {
+        detector_id: "motor-temp".to_string(),
+        table_id: 7,
+        column: "temperature".to_string(),
+        row_id: 42,
+        value: 120.0,
+        expected: 45.0,
+        score: 9.5,
+        method: "zscore".to_string(),
+        detected_at: 0,
+    };
+    sink.emit(&event).await.unwrap();
+
+    let message = rx.recv().await.unwrap();
+    assert!(message.contains("anomaly-detector:anomaly"));
+    assert!(message.contains("motor-temp"));
+}
+
+#[tokio::test]
+async fn test_unsubscribe_only_by_owner() {
+    let manager = create_test_manager();
+    manager.register_actor(create_origin_actor("robot", "token-robot-123456789012")).await.unwrap();
+    manager.register_actor(create_origin_actor("intruder", "token-intruder-1234567890")).await.unwrap();
+
+    let subscription_id = manager.subscribe(
+        &ActorId::from("robot"),
+        "token-robot-123456789012",
+        "vision:object_detected",
+        TransportType::Webhook,
+        Some(serde_json::json!({ "webhook_url": "https://robot.example.com/hook" })),
+    ).await.unwrap();
+
+    assert!(manager.unsubscribe(&ActorId::from("robot"), "wrong-token", &subscription_id).await.is_err());
+    assert!(!manager.unsubscribe(&ActorId::from("intruder"), "token-intruder-1234567890", &subscription_id).await.unwrap());
+    assert!(manager.unsubscribe(&ActorId::from("robot"), "token-robot-123456789012", &subscription_id).await.unwrap());
+    assert!(!manager.unsubscribe(&ActorId::from("robot"), "token-robot-123456789012", &subscription_id).await.unwrap());
+}
+
+#[tokio::test]
+async fn test_publish_events_batch() {
+    use narayana_storage::native_events::{EventId, StreamName};
+
+    let mut config = EventsConfig::default();
+    config.enable_persistence = false;
+    let native_events = Arc::new(NativeEventsSystem::new(config));
+    let manager = RdeManager::new(native_events.clone());
+    manager.register_actor(create_source_actor("robot", "token-robot-123456789012")).await.unwrap();
+
+    let batch: Vec<(String, serde_json::Value)> = (0..5)
+        .map(|seq| ("reading".to_string(), serde_json::json!({ "seq": seq })))
+        .chain(std::iter::once(("status".to_string(), serde_json::json!({ "ok": true }))))
+        .collect();
+    let published = manager
+        .publish_events_batch(&ActorId::from("robot"), "token-robot-123456789012", batch)
+        .await
+        .unwrap();
+    assert_eq!(published, 6);
+
+    let readings = native_events.read_stream(&StreamName("rde:robot:reading".to_string()), EventId(0), 100);
+    let seqs: Vec<u64> = readings.iter().map(|e| e.payload["seq"].as_u64().unwrap()).collect();
+    assert_eq!(seqs, vec![0, 1, 2, 3, 4]);
+    assert!(readings.windows(2).all(|pair| pair[0].id.0 < pair[1].id.0));
+    assert_eq!(native_events.read_stream(&StreamName("rde:robot:status".to_string()), EventId(0), 100).len(), 1);
+
+    // A single event after the batch still gets a later id
+    manager
+        .publish_event(&ActorId::from("robot"), "token-robot-123456789012", "reading", serde_json::json!({ "seq": 5 }))
+        .await
+        .unwrap();
+    let readings = native_events.read_stream(&StreamName("rde:robot:reading".to_string()), EventId(0), 100);
+    assert_eq!(readings.len(), 6);
+    assert!(readings[5].id.0 > readings[4].id.0);
+}
+
+#[tokio::test]
+async fn test_publish_events_batch_is_validated_up_front() {
+    let mut config = EventsConfig::default();
+    config.enable_persistence = false;
+    let native_events = Arc::new(NativeEventsSystem::new(config));
+    // Not journaling the registry, so every stream would come from the batch
+    let manager = RdeManager::new_in_memory(native_events.clone());
+    manager.register_actor(create_source_actor("robot", "token-robot-123456789012")).await.unwrap();
+    manager.register_actor(create_origin_actor("ops", "token-ops-1234567890123")).await.unwrap();
+    let robot = ActorId::from("robot");
+
+    let batch = vec![
+        ("reading".to_string(), serde_json::json!({ "seq": 0 })),
+        ("bad:name".to_string(), serde_json::json!({ "seq": 1 })),
+    ];
+    assert!(manager.publish_events_batch(&robot, "token-robot-123456789012", batch).await.is_err());
+    assert!(native_events.stream_names().is_empty());
+
+    let batch = vec![("reading".to_string(), serde_json::json!({ "seq": 0 }))];
+    assert!(manager.publish_events_batch(&robot, "wrong-token-123456789012", batch.clone()).await.is_err());
+    assert!(manager.publish_events_batch(&ActorId::from("ops"), "token-ops-1234567890123", batch).await.is_err());
+    assert_eq!(manager.publish_events_batch(&robot, "token-robot-123456789012", Vec::new()).await.unwrap(), 0);
+}
@@ -0,0 +1,92 @@
+// Delivery metrics tests for narayana-rde
+// Per-subscription and per-event counters for delivered, failed, filtered,
+// transformed and rate-limited events, and their Prometheus exposition
+
+mod common;
+
+use common::*;
+use narayana_rde::*;
+use serde_json::json;
+
+/// Accepts deliveries to every worker except "broken"
+fn flaky_workers() -> Recorder {
+    Recorder::new().broken_worker("broken")
+}
+
+#[tokio::test]
+async fn test_counts_delivered_failed_and_filtered_events() {
+    let (manager, _) = setup(flaky_workers()).await;
+    let healthy = subscribe(&manager, "robot:reading", json!({"worker_id": "alerts", "filter": "$.temp > 80"})).await.unwrap();
+    let broken = subscribe(&manager, "robot:reading", json!({"worker_id": "broken", "max_attempts": 1})).await.unwrap();
+
+    for temp in [90, 70, 85] {
+        publish(&manager, "reading", json!({"temp": temp})).await;
+    }
+
+    let healthy_metrics = manager.get_subscription_metrics(&healthy).unwrap();
+    assert_eq!((healthy_metrics.delivered, healthy_metrics.failed, healthy_metrics.filtered), (2, 0, 1));
+    assert!(healthy_metrics.last_delivered_at.is_some() && healthy_metrics.last_error.is_none());
+
+    let broken_metrics = manager.get_subscription_metrics(&broken).unwrap();
+    assert_eq!((broken_metrics.delivered, broken_metrics.failed), (0, 3));
+    assert!(broken_metrics.last_error.is_some());
+
+    let metrics = manager.metrics();
+    assert_eq!(metrics.subscriptions.len(), 2);
+    let failing: Vec<&str> = metrics.failing().iter().map(|s| s.subscription_id.as_str()).collect();
+    assert_eq!(failing, vec![broken.0.as_str()]);
+
+    let event = &metrics.events["robot:reading"];
+    assert_eq!((event.published, event.delivered, event.failed), (3, 2, 3));
+
+    // Counters go away with the subscription
+    assert!(manager.unsubscribe(&ActorId::from("ops"), TOKEN, &broken).await.unwrap());
+    assert!(manager.get_subscription_metrics(&broken).is_none());
+    assert_eq!(manager.metrics().subscriptions.len(), 1);
+}
+
+#[tokio::test]
+async fn test_counts_transformed_and_rate_limited_deliveries() {
+    let (manager, _) = setup(flaky_workers()).await;
+    let transformed = subscribe(
+        &manager,
+        "robot:status",
+        json!({
+            "worker_id": "alerts",
+            "rate_limit_per_second": 1.0,
+            "output_config": {"transforms": [{"type": "field", "source": "state", "target": "status"}]}
+        }),
+    )
+    .await
+    .unwrap();
+
+    publish(&manager, "status", json!({"state": "idle"})).await;
+    publish(&manager, "status", json!({"state": "busy"})).await;
+
+    let metrics = manager.get_subscription_metrics(&transformed).unwrap();
+    assert_eq!(metrics.delivered, 2);
+    assert_eq!(metrics.transformed, 2);
+    assert_eq!(metrics.transform_errors, 0);
+    // The second event waited for the one-per-second window
+    assert_eq!(metrics.rate_limited, 1);
+
+    // Events nobody subscribes to are still counted as published
+    publish(&manager, "heartbeat", json!({})).await;
+    assert_eq!(manager.metrics().events["robot:heartbeat"].published, 1);
+    assert_eq!(manager.metrics().events["robot:heartbeat"].delivered, 0);
+}
+
+#[tokio::test]
+async fn test_prometheus_exposition() {
+    let (manager, _) = setup(flaky_workers()).await;
+    let subscription = subscribe(&manager, "robot:reading", json!({"worker_id": "broken", "max_attempts": 1})).await.unwrap();
+    publish(&manager, "reading", json!({})).await;
+
+    let text = manager.metrics().to_prometheus();
+    assert!(text.contains("# TYPE narayana_rde_subscription_failed_total counter"));
+    assert!(text.contains(&format!(
+        "narayana_rde_subscription_failed_total{{subscription=\"{}\",actor=\"ops\",transport=\"worker\"}} 1",
+        subscription.0
+    )));
+    assert!(text.contains("narayana_rde_event_published_total{event=\"robot:reading\"} 1"));
+}
@@ -0,0 +1,106 @@
+// Ordered delivery tests for narayana-rde
+// Subscriptions with `ordered: true` receive events one at a time in publish
+// order, with failed deliveries retried in place rather than overtaken
+
+mod common;
+
+use common::*;
+use narayana_rde::*;
+use serde_json::json;
+use std::time::Duration;
+
+/// A recorder where seq 0 is slow and the first attempt of each seq in
+/// `fail_once` fails
+fn recorder(fail_once: Vec<u64>) -> Recorder {
+    Recorder::new().slow_seq(0, Duration::from_millis(50)).fail_once(fail_once)
+}
+
+async fn publish_seq(manager: &RdeManager, seq: u64) {
+    publish(manager, "moved", json!({"seq": seq})).await;
+}
+
+/// Wait for `count` deliveries and return their seqs
+async fn delivered(recorder: &Recorder, count: usize) -> Vec<u64> {
+    wait_for_deliveries(recorder, count).await;
+    recorder.seqs()
+}
+
+#[tokio::test]
+async fn test_failed_delivery_is_retried_before_later_events() {
+    let retry = json!({"max_attempts": 3, "initial_backoff_ms": 20});
+    let (manager, recorder) = setup(recorder(vec![1])).await;
+    let subscription = subscribe(&manager, "robot:moved", json!({"worker_id": "moves", "ordered": true, "max_attempts": 1, "retry": retry}))
+        .await
+        .unwrap();
+
+    for seq in 0..4 {
+        publish_seq(&manager, seq).await;
+    }
+    assert_eq!(delivered(&recorder, 4).await, vec![0, 1, 2, 3]);
+    assert_eq!(manager.ordered_backlog(&subscription), Some(0));
+
+    let stats = manager.get_delivery_stats(&subscription).unwrap();
+    assert_eq!((stats.retries, stats.recovered, stats.pending), (1, 1, 0));
+}
+
+#[tokio::test]
+async fn test_unordered_retries_let_later_events_through() {
+    let retry = json!({"max_attempts": 3, "initial_backoff_ms": 20});
+    let (manager, recorder) = setup(recorder(vec![1])).await;
+    subscribe(&manager, "robot:moved", json!({"worker_id": "moves", "max_attempts": 1, "retry": retry})).await.unwrap();
+
+    for seq in 0..4 {
+        publish_seq(&manager, seq).await;
+    }
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    manager.retry_pending_deliveries().await;
+    assert_eq!(delivered(&recorder, 4).await, vec![0, 2, 3, 1]);
+}
+
+#[tokio::test]
+async fn test_concurrent_publishers_keep_their_order() {
+    let (manager, recorder) = setup(recorder(vec![])).await;
+    subscribe(&manager, "robot:moved", json!({"worker_id": "moves", "ordered": true})).await.unwrap();
+
+    let mut publishers = Vec::new();
+    for publisher in ["a", "b", "c", "d"] {
+        let manager = manager.clone();
+        publishers.push(tokio::spawn(async move {
+            for seq in 1..=25 {
+                publish(&manager, "moved", json!({"publisher": publisher, "seq": seq})).await;
+            }
+        }));
+    }
+    for publisher in publishers {
+        publisher.await.unwrap();
+    }
+
+    wait_for_deliveries(&recorder, 100).await;
+    let deliveries = recorder.bodies();
+    assert_eq!(deliveries.len(), 100);
+    for publisher in ["a", "b", "c", "d"] {
+        let own: Vec<u64> = deliveries.iter().filter(|body| body["publisher"] == publisher).map(|body| body["seq"].as_u64().unwrap()).collect();
+        assert_eq!(own, (1..=25).collect::<Vec<_>>(), "publisher {}", publisher);
+    }
+}
+
+#[tokio::test]
+async fn test_ordered_option_is_validated_and_stops_with_subscription() {
+    let (manager, recorder) = setup(recorder(vec![])).await;
+    let err = subscribe(&manager, "robot:moved", json!({"worker_id": "moves", "ordered": "yes"})).await.unwrap_err();
+    assert!(err.to_string().contains("ordered must be a boolean"));
+
+    let subscription = subscribe(&manager, "robot:moved", json!({"worker_id": "moves", "ordered": true})).await.unwrap();
+    assert_eq!(manager.ordered_backlog(&subscription), None);
+
+    // Seq 0 is slow, so the rest are still queued when the subscription goes
+    for seq in 0..5 {
+        publish_seq(&manager, seq).await;
+    }
+    assert!(manager.ordered_backlog(&subscription).unwrap() > 0);
+    assert!(manager.unsubscribe(&ActorId::from("ops"), TOKEN, &subscription).await.unwrap());
+    assert_eq!(manager.ordered_backlog(&subscription), None);
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(recorder.deliveries() < 5);
+}
@@ -0,0 +1,153 @@
+// Subscription pattern tests for narayana-rde
+// Glob parsing and matching, the shared pattern index, and delivery to
+// subscriptions whose pattern matches the published event
+
+mod common;
+
+use common::*;
+use narayana_rde::patterns::PatternIndex;
+use narayana_rde::*;
+use serde_json::json;
+use std::sync::Arc;
+
+fn matches(pattern: &str, event_name: &str) -> bool {
+    EventPattern::parse(pattern).unwrap().matches(event_name)
+}
+
+#[test]
+fn test_pattern_matching() {
+    assert!(matches("robot-*:telemetry.*", "robot-7:telemetry.imu"));
+    assert!(matches("robot-*:telemetry.*", "robot-:telemetry."));
+    assert!(!matches("robot-*:telemetry.*", "robot-7:status"));
+    assert!(!matches("robot-*:telemetry.*", "rover-1:telemetry.imu"));
+    assert!(matches("sensor-??:reading", "sensor-12:reading"));
+    assert!(!matches("sensor-??:reading", "sensor-123:reading"));
+    assert!(matches("robot:*", "robot:anything"));
+    assert!(matches("*-arm:grip*", "left-arm:grip_closed"));
+    assert!(matches("a*b*c:e", "axxbyyc:e"));
+    assert!(!matches("a*b*c:e", "axxbyy:e"));
+
+    // A bare event name matches that event from any actor, and only that event
+    assert!(matches("alarm", "boiler:alarm"));
+    assert!(!matches("alarm", "boiler:false_alarm"));
+    assert_eq!(EventPattern::parse("alarm").unwrap().to_string(), "*:alarm");
+
+    // Wildcards never cross the actor/event separator
+    assert!(!matches("robot*", "robot:x"));
+    assert!(!matches("r?bot:*", "r:bot:x"));
+
+    // Escaped wildcards are literal
+    assert!(matches("cam\\*:frame", "cam*:frame"));
+    assert!(!matches("cam\\*:frame", "cam1:frame"));
+    assert_eq!(EventPattern::parse("cam\\*:frame").unwrap().to_string(), "cam\\*:frame");
+
+    let pattern = EventPattern::parse("robot-1:telemetry.*").unwrap();
+    assert!(!pattern.spans_actors());
+    assert!(!pattern.is_literal());
+    assert!(EventPattern::parse("robot-?:telemetry").unwrap().spans_actors());
+    assert!(EventPattern::parse("robot-1:telemetry").unwrap().is_literal());
+}
+
+#[test]
+fn test_invalid_patterns_are_rejected() {
+    for pattern in ["", ":", "*", "*:*", "**:*", "a:", ":b", "a:b:c", "a\\b:c", "a\u{0}:b"] {
+        assert!(EventPattern::parse(pattern).is_err(), "{:?} should not parse", pattern);
+    }
+    assert!(EventPattern::parse(&format!("{}:e", "*a".repeat(9))).is_err());
+    assert!(EventPattern::parse(&format!("a:{}", "e".repeat(600))).is_err());
+}
+
+#[test]
+fn test_pattern_index_lookup_and_removal() {
+    let mut index = PatternIndex::new();
+    for (id, pattern) in [(1, "robot-*:telemetry.*"), (2, "robot-1:telemetry.imu"), (3, "*:telemetry.imu"), (4, "rover-*:*"), (5, "robot-1:telemetry.imu")] {
+        index.insert(&EventPattern::parse(pattern).unwrap(), id);
+    }
+    assert_eq!(index.len(), 5);
+
+    let mut ids: Vec<i32> = index.matching("robot-1:telemetry.imu", 100).into_iter().copied().collect();
+    ids.sort();
+    assert_eq!(ids, vec![1, 2, 3, 5]);
+    assert_eq!(index.matching("robot-1:telemetry.imu", 2).len(), 2);
+    assert_eq!(index.matching("rover-9:status", 100), vec![&4]);
+    assert!(index.matching("drone:status", 100).is_empty());
+
+    // Removing one of two identical patterns keeps the other
+    let shared = EventPattern::parse("robot-1:telemetry.imu").unwrap();
+    assert_eq!(index.remove(&shared, |id| *id == 2), Some(2));
+    assert_eq!(index.remove(&shared, |id| *id == 2), None);
+    let mut ids: Vec<i32> = index.matching("robot-1:telemetry.imu", 100).into_iter().copied().collect();
+    ids.sort();
+    assert_eq!(ids, vec![1, 3, 5]);
+    assert_eq!(index.remove(&EventPattern::parse("rover-*:*").unwrap(), |_| true), Some(4));
+    assert_eq!(index.len(), 3);
+}
+
+#[test]
+fn test_pattern_index_handles_many_wildcards() {
+    let mut index = PatternIndex::new();
+    index.insert(&EventPattern::parse("*a*a*a*a*a*a*a*b:e").unwrap(), ());
+    // Would backtrack exponentially without sharing work between paths
+    assert!(index.matching(&format!("{}:e", "a".repeat(200)), 10).is_empty());
+}
+
+/// Sources "robot-1", "robot-2" and "rover-1", and "ops" subscribing, with
+/// wildcard subscriptions across actors allowed if `allow_wildcards`
+async fn setup_fleet(allow_wildcards: bool) -> (Arc<RdeManager>, Arc<Recorder>) {
+    let (manager, recorder) = manager(Recorder::new());
+    for id in ["robot-1", "robot-2", "rover-1"] {
+        register(&manager, id, ActorType::Source, TOKEN).await;
+    }
+    let mut ops = Actor::new(ActorId::from("ops"), "Ops".to_string(), ActorType::Origin, TOKEN.to_string());
+    if allow_wildcards {
+        ops.metadata = json!({"allow_wildcard_subscriptions": true});
+    }
+    manager.register_actor(ops).await.unwrap();
+    (manager, recorder)
+}
+
+async fn subscribe_pattern(manager: &RdeManager, pattern: &str) -> Result<SubscriptionId, narayana_core::Error> {
+    subscribe(manager, pattern, json!({"worker_id": "telemetry"})).await
+}
+
+/// Publish `event` as `actor`, with the full event name as the payload's `event`
+async fn publish_from(manager: &RdeManager, actor: &str, event: &str) {
+    publish_as(manager, actor, event, json!({"event": format!("{}:{}", actor, event)})).await;
+}
+
+/// The event name of every delivery
+fn events(recorder: &Recorder) -> Vec<String> {
+    recorder.bodies().iter().map(|body| body["event"].as_str().unwrap().to_string()).collect()
+}
+
+#[tokio::test]
+async fn test_glob_subscriptions_receive_matching_events() {
+    let (manager, recorder) = setup_fleet(true).await;
+    let subscription = subscribe_pattern(&manager, "robot-*:telemetry.*").await.unwrap();
+
+    publish_from(&manager, "robot-1", "telemetry.imu").await;
+    publish_from(&manager, "robot-2", "telemetry.gps").await;
+    publish_from(&manager, "robot-1", "status").await;
+    publish_from(&manager, "rover-1", "telemetry.imu").await;
+    assert_eq!(events(&recorder), vec!["robot-1:telemetry.imu", "robot-2:telemetry.gps"]);
+
+    // Nothing is delivered once unsubscribed
+    assert!(manager.unsubscribe(&ActorId::from("ops"), TOKEN, &subscription).await.unwrap());
+    publish_from(&manager, "robot-1", "telemetry.imu").await;
+    assert_eq!(recorder.deliveries(), 2);
+}
+
+#[tokio::test]
+async fn test_actor_wildcards_need_permission() {
+    let (manager, recorder) = setup_fleet(false).await;
+    let err = subscribe_pattern(&manager, "robot-*:telemetry").await.unwrap_err();
+    assert!(err.to_string().contains("Wildcard subscriptions require"));
+    assert!(subscribe_pattern(&manager, "telemetry").await.is_err());
+    assert!(subscribe_pattern(&manager, "robot-*:*:x").await.is_err());
+
+    // Wildcards within one actor's events need no permission
+    subscribe_pattern(&manager, "robot-1:telemetry.*").await.unwrap();
+    publish_from(&manager, "robot-1", "telemetry.imu").await;
+    publish_from(&manager, "robot-2", "telemetry.imu").await;
+    assert_eq!(events(&recorder), vec!["robot-1:telemetry.imu"]);
+}
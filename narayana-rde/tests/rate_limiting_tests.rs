@@ -372,3 +372,96 @@ async fn test_rate_limiting_string_value() {
     assert!(elapsed.as_secs() < 2, "Should complete quickly with non-numeric rate limit, took: {:?}", elapsed);
 }
 
+
+async fn register_source(manager: &RdeManager, id: &str) {
+    let source = Actor::new(ActorId::from(id), "Source".to_string(), ActorType::Source, "token-123456789012".to_string());
+    manager.register_actor(source).await.unwrap();
+}
+
+async fn publish(manager: &RdeManager, id: &str, payload: serde_json::Value) -> narayana_core::Result<()> {
+    manager.publish_event(&ActorId::from(id), "token-123456789012", "reading", payload).await
+}
+
+#[tokio::test]
+async fn test_actor_quota_limits_publish_rate_with_burst() {
+    let manager = create_test_manager();
+    register_source(&manager, "sensor").await;
+    let quota = ActorQuota { events_per_second: Some(1.0), burst: Some(3), max_stored_bytes: None };
+    manager.set_actor_quota(&ActorId::from("sensor"), quota).unwrap();
+
+    // The burst goes through at once, then the sustained rate applies
+    for _ in 0..3 {
+        publish(&manager, "sensor", serde_json::json!({"v": 1})).await.unwrap();
+    }
+    let refused = publish(&manager, "sensor", serde_json::json!({"v": 1})).await.unwrap_err();
+    assert!(matches!(refused, narayana_core::Error::QuotaExceeded(ref e) if e.contains("retry in")), "{}", refused);
+
+    let status = manager.actor_quota(&ActorId::from("sensor")).unwrap();
+    assert_eq!(status.events_available, Some(0));
+    assert_eq!(status.rate_limited, 1);
+
+    // A batch larger than the burst can never go through
+    let batch = (0..4).map(|i| ("reading".to_string(), serde_json::json!({"v": i}))).collect();
+    let refused = manager.publish_events_batch(&ActorId::from("sensor"), "token-123456789012", batch).await.unwrap_err();
+    assert!(refused.to_string().contains("larger than the publish burst of 3"), "{}", refused);
+
+    tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+    publish(&manager, "sensor", serde_json::json!({"v": 1})).await.unwrap();
+
+    // Other actors are not limited
+    register_source(&manager, "other").await;
+    for _ in 0..10 {
+        publish(&manager, "other", serde_json::json!({"v": 1})).await.unwrap();
+    }
+    assert!(manager.actor_quota(&ActorId::from("other")).is_none());
+}
+
+#[tokio::test]
+async fn test_actor_quota_limits_stored_bytes() {
+    let manager = create_test_manager();
+    register_source(&manager, "sensor").await;
+    // Events published before the quota count towards it
+    publish(&manager, "sensor", serde_json::json!({"v": 1})).await.unwrap();
+    let quota = ActorQuota { events_per_second: None, burst: None, max_stored_bytes: Some(24) };
+    manager.set_actor_quota(&ActorId::from("sensor"), quota).unwrap();
+
+    let status = manager.actor_quota(&ActorId::from("sensor")).unwrap();
+    assert_eq!(status.stored_bytes, 7);
+    assert_eq!(status.stored_bytes_remaining, Some(17));
+    assert_eq!(status.events_available, None);
+
+    publish(&manager, "sensor", serde_json::json!({"v": 22})).await.unwrap();
+    publish(&manager, "sensor", serde_json::json!({"v": 3})).await.unwrap();
+    let refused = publish(&manager, "sensor", serde_json::json!({"v": 4})).await.unwrap_err();
+    assert!(refused.to_string().contains("22 of 24 bytes"), "{}", refused);
+    assert_eq!(manager.actor_quota(&ActorId::from("sensor")).unwrap().storage_rejected, 1);
+
+    assert!(manager.remove_actor_quota(&ActorId::from("sensor")));
+    publish(&manager, "sensor", serde_json::json!({"v": 4})).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_actor_quota_is_validated() {
+    let manager = create_test_manager();
+    register_source(&manager, "sensor").await;
+    let origin = Actor::new(ActorId::from("origin"), "Origin".to_string(), ActorType::Origin, "token-123456789012".to_string());
+    manager.register_actor(origin).await.unwrap();
+
+    let invalid = [
+        ActorQuota { events_per_second: Some(0.0), ..Default::default() },
+        ActorQuota { events_per_second: Some(f64::NAN), ..Default::default() },
+        ActorQuota { burst: Some(5), ..Default::default() },
+        ActorQuota { events_per_second: Some(10.0), burst: Some(0), max_stored_bytes: None },
+        ActorQuota { max_stored_bytes: Some(0), ..Default::default() },
+    ];
+    for quota in invalid {
+        assert!(manager.set_actor_quota(&ActorId::from("sensor"), quota.clone()).is_err(), "{:?}", quota);
+    }
+    let quota = ActorQuota { events_per_second: Some(10.0), ..Default::default() };
+    assert!(manager.set_actor_quota(&ActorId::from("origin"), quota.clone()).is_err());
+    assert!(manager.set_actor_quota(&ActorId::from("missing"), quota.clone()).is_err());
+
+    // The burst defaults to one second's worth of events
+    manager.set_actor_quota(&ActorId::from("sensor"), quota).unwrap();
+    assert_eq!(manager.actor_quota(&ActorId::from("sensor")).unwrap().events_available, Some(10));
+}
@@ -0,0 +1,190 @@
+// Registry persistence tests for narayana-rde: actors, tokens and
+// subscriptions loaded back by a manager over the same event system
+
+use narayana_rde::*;
+use narayana_storage::native_events::{Event as NativeEvent, EventId, EventsConfig, NativeEventsSystem, StreamName};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+const TOKEN: &str = "token-123456789012";
+
+/// Counts worker invocations, answering 200
+#[derive(Default)]
+struct CountingInvoker {
+    calls: parking_lot::Mutex<usize>,
+}
+
+#[async_trait::async_trait]
+impl narayana_rde::transports::worker::WorkerInvoker for CountingInvoker {
+    async fn invoke(
+        &self,
+        _worker_id: &str,
+        _request: narayana_storage::workers::WorkerRequest,
+    ) -> anyhow::Result<narayana_storage::workers::WorkerResponse> {
+        *self.calls.lock() += 1;
+        Ok(narayana_storage::workers::WorkerResponse {
+            status: 200,
+            headers: Default::default(),
+            body: Vec::new(),
+            metrics: narayana_storage::workers::ExecutionMetrics {
+                cpu_time_ms: 1,
+                memory_bytes: 0,
+                execution_time_ms: 1,
+                subrequests: 0,
+                request_size: 0,
+                response_size: 0,
+            },
+        })
+    }
+}
+
+fn native_events() -> Arc<NativeEventsSystem> {
+    Arc::new(NativeEventsSystem::new(EventsConfig { enable_persistence: false, ..Default::default() }))
+}
+
+async fn register(manager: &RdeManager) {
+    manager
+        .register_actor(Actor::new(ActorId::from("robot"), "Robot".to_string(), ActorType::Source, TOKEN.to_string()))
+        .await
+        .unwrap();
+    manager
+        .register_actor(Actor::new(ActorId::from("ops"), "Ops".to_string(), ActorType::Origin, TOKEN.to_string()))
+        .await
+        .unwrap();
+}
+
+async fn subscribe(manager: &RdeManager, worker_id: &str) -> SubscriptionId {
+    manager
+        .subscribe(&ActorId::from("ops"), TOKEN, "robot:collision", TransportType::Worker, Some(serde_json::json!({
+            "worker_id": worker_id,
+        })))
+        .await
+        .unwrap()
+}
+
+/// Append a raw entry to the registry journal
+async fn journal(native_events: &NativeEventsSystem, payload: serde_json::Value) {
+    native_events
+        .publish_event(NativeEvent {
+            id: EventId(0),
+            stream: StreamName("rde-registry".to_string()),
+            topic: None,
+            queue: None,
+            event_type: payload["op"].as_str().unwrap().to_string(),
+            payload,
+            headers: HashMap::new(),
+            timestamp: 0,
+            correlation_id: None,
+            causation_id: None,
+            partition_key: None,
+            ttl: None,
+            priority: 0,
+        })
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_registry_survives_restart() {
+    let native_events = native_events();
+    let manager = RdeManager::new(native_events.clone());
+    register(&manager).await;
+    let ops = ActorId::from("ops");
+    let kept = subscribe(&manager, "first").await;
+    let dropped = subscribe(&manager, "second").await;
+    manager
+        .update_subscription_config(&ops, TOKEN, &kept, serde_json::json!({"worker_id": "updated"}))
+        .await
+        .unwrap();
+    assert!(manager.unsubscribe(&ops, TOKEN, &dropped).await.unwrap());
+    let rotated = manager.rotate_token(&ops, TOKEN, 0, None).unwrap();
+    manager.flush_registry().await;
+    drop(manager);
+
+    let invoker = Arc::new(CountingInvoker::default());
+    let manager = RdeManager::new(native_events).with_worker_invoker(invoker.clone());
+    assert_eq!(manager.get_actor(&ops).unwrap().name, "Ops");
+    assert_eq!(manager.get_actor(&ops).unwrap().auth_token, "");
+
+    // The rotation survived: the old token is refused, the new one works
+    assert!(manager.list_subscriptions(&ops, TOKEN).is_err());
+    let subscriptions = manager.list_subscriptions(&ops, &rotated.token).unwrap();
+    assert_eq!(subscriptions.len(), 1);
+    assert_eq!(subscriptions[0].id, kept);
+    assert_eq!(subscriptions[0].config["worker_id"], "updated");
+    assert_eq!(manager.list_tokens(&ops, &rotated.token).unwrap().len(), 2);
+
+    // Published events reach the reloaded subscription
+    manager
+        .publish_event(&ActorId::from("robot"), TOKEN, "collision", serde_json::json!({"force": 1.5}))
+        .await
+        .unwrap();
+    assert_eq!(*invoker.calls.lock(), 1);
+}
+
+#[tokio::test]
+async fn test_registry_opt_out_and_system_actors() {
+    let native_events = native_events();
+    let ephemeral = RdeManager::new_in_memory(native_events.clone());
+    register(&ephemeral).await;
+    subscribe(&ephemeral, "first").await;
+    ephemeral.snapshot_registry().await;
+    assert!(native_events.stream_names().is_empty());
+
+    let manager = RdeManager::new(native_events.clone());
+    assert!(manager.get_actor(&ActorId::from("ops")).is_none());
+
+    // System actors register themselves on every start
+    let mut system = Actor::new(ActorId::from("narayana-system"), "System".to_string(), ActorType::Source, TOKEN.to_string());
+    system.metadata = serde_json::json!({"system": true});
+    manager.register_actor(system.clone()).await.unwrap();
+    manager.flush_registry().await;
+    let manager = RdeManager::new(native_events);
+    assert!(manager.get_actor(&system.id).is_none());
+    manager.register_actor(system).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_registry_snapshot_and_migration() {
+    let native_events = native_events();
+    let manager = RdeManager::new(native_events.clone());
+    register(&manager).await;
+    let kept = subscribe(&manager, "first").await;
+    manager.snapshot_registry().await;
+
+    // Entries without a version are read as version 1, ones from a newer
+    // format are skipped, and configs that no longer validate are dropped
+    let subscription = |worker: serde_json::Value| serde_json::json!({
+        "id": SubscriptionId::new(),
+        "actor_id": "ops",
+        "event_name": "robot:collision",
+        "transport": TransportType::Worker,
+        "config": {"worker_id": worker},
+        "created_at": 0,
+    });
+    journal(&native_events, serde_json::json!({"op": "subscribed", "subscription": subscription("legacy".into())})).await;
+    journal(&native_events, serde_json::json!({"op": "subscribed", "version": 99, "subscription": subscription("future".into())})).await;
+    journal(&native_events, serde_json::json!({"op": "subscribed", "version": 1, "subscription": subscription("".into())})).await;
+
+    let reloaded = RdeManager::new(native_events.clone());
+    let mut workers: Vec<String> = reloaded
+        .list_subscriptions(&ActorId::from("ops"), TOKEN)
+        .unwrap()
+        .iter()
+        .map(|s| s.config["worker_id"].as_str().unwrap().to_string())
+        .collect();
+    workers.sort();
+    assert_eq!(workers, vec!["first", "legacy"]);
+    assert!(reloaded.list_subscriptions(&ActorId::from("ops"), TOKEN).unwrap().iter().any(|s| s.id == kept));
+
+    // A snapshot supersedes what came before it
+    assert!(reloaded.unsubscribe(&ActorId::from("ops"), TOKEN, &kept).await.unwrap());
+    reloaded.snapshot_registry().await;
+    let workers: Vec<String> = RdeManager::new(native_events)
+        .list_subscriptions(&ActorId::from("ops"), TOKEN)
+        .unwrap()
+        .iter()
+        .map(|s| s.config["worker_id"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(workers, vec!["legacy"]);
+}
@@ -370,3 +370,75 @@ async fn test_hmac_secret_validation() {
     // Note: This might fail for other reasons (URL validation), but secret validation should catch it
 }
 
+
+#[tokio::test]
+async fn test_actor_tokens_issue_expire_and_revoke() {
+    let manager = create_test_manager();
+    let source = ActorId::from("sensor");
+    manager.register_actor(Actor::new(
+        source.clone(),
+        "Sensor".to_string(),
+        ActorType::Source,
+        "sensor-token-123456789012".to_string(),
+    )).await.unwrap();
+
+    let now = chrono::Utc::now().timestamp() as u64;
+    let second = manager.issue_token(&source, "sensor-token-123456789012", Some(now + 3600)).unwrap();
+    assert_eq!(second.info.expires_at, Some(now + 3600));
+    // Both tokens work side by side
+    for token in ["sensor-token-123456789012", second.token.as_str()] {
+        manager.publish_event(&source, token, "reading", serde_json::json!({})).await.unwrap();
+    }
+
+    // Expiry must be in the future, and only token holders can issue
+    assert!(manager.issue_token(&source, "sensor-token-123456789012", Some(now - 1)).is_err());
+    assert!(manager.issue_token(&source, "wrong-token-123456789012", None).is_err());
+
+    // Revocation takes effect immediately
+    manager.revoke_token(&source, "sensor-token-123456789012", &second.info.id).unwrap();
+    assert!(manager.publish_event(&source, &second.token, "reading", serde_json::json!({})).await.is_err());
+    assert!(manager.revoke_token(&source, "sensor-token-123456789012", &second.info.id).is_err());
+
+    let tokens = manager.list_tokens(&source, "sensor-token-123456789012").unwrap();
+    assert_eq!(tokens.len(), 2);
+    assert!(tokens[0].revoked_at.is_none());
+    assert!(tokens[1].revoked_at.is_some());
+
+    // Rotating with a grace period keeps the old token working until it lapses
+    let rotated = manager.rotate_token(&source, "sensor-token-123456789012", 3600, None).unwrap();
+    manager.publish_event(&source, "sensor-token-123456789012", "reading", serde_json::json!({})).await.unwrap();
+    manager.publish_event(&source, &rotated.token, "reading", serde_json::json!({})).await.unwrap();
+    let tokens = manager.list_tokens(&source, &rotated.token).unwrap();
+    assert!(tokens[0].expires_at.is_some());
+}
+
+#[tokio::test]
+async fn test_token_rotation_keeps_subscriptions() {
+    let manager = create_test_manager();
+    manager.register_actor(Actor::new(
+        ActorId::from("sensor"),
+        "Sensor".to_string(),
+        ActorType::Source,
+        "sensor-token-123456789012".to_string(),
+    )).await.unwrap();
+    let ops = ActorId::from("ops");
+    manager.register_actor(Actor::new(
+        ops.clone(),
+        "Ops".to_string(),
+        ActorType::Origin,
+        "ops-token-123456789012".to_string(),
+    )).await.unwrap();
+    manager.publish_event(&ActorId::from("sensor"), "sensor-token-123456789012", "reading", serde_json::json!({})).await.unwrap();
+    let subscription_id = manager
+        .subscribe(&ops, "ops-token-123456789012", "sensor:reading", TransportType::Sse, None)
+        .await
+        .unwrap();
+
+    // A compromised token is replaced without a grace period
+    let rotated = manager.rotate_token(&ops, "ops-token-123456789012", 0, None).unwrap();
+    assert!(manager.list_tokens(&ops, "ops-token-123456789012").is_err());
+    assert!(manager.rotate_token(&ops, "ops-token-123456789012", 0, None).is_err());
+
+    // The subscription made with the old token is still the actor's
+    assert!(manager.unsubscribe(&ops, &rotated.token, &subscription_id).await.unwrap());
+}
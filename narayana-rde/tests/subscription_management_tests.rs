@@ -0,0 +1,121 @@
+// Subscription management tests for narayana-rde
+// Listing an actor's subscriptions, updating a subscription's config in
+// place, and unsubscribing with cleanup of registered SSE/gRPC senders
+
+mod common;
+
+use common::*;
+use narayana_rde::*;
+use serde_json::json;
+use std::sync::Arc;
+
+/// `setup` plus a second origin, "audit", with its own token
+async fn setup_with_audit() -> (Arc<RdeManager>, Arc<Recorder>) {
+    let (manager, recorder) = setup(Recorder::new()).await;
+    register(&manager, "audit", ActorType::Origin, OTHER_TOKEN).await;
+    (manager, recorder)
+}
+
+async fn subscribe_moves(manager: &RdeManager, actor: &str, token: &str, transport: TransportType, config: serde_json::Value) -> SubscriptionId {
+    subscribe_as(manager, actor, token, "robot:moved", transport, config).await.unwrap()
+}
+
+async fn publish_move(manager: &RdeManager) {
+    publish(manager, "moved", json!({"x": 1})).await;
+}
+
+/// Wait for `count` deliveries and return the workers they went to
+async fn delivered(recorder: &Recorder, count: usize) -> Vec<String> {
+    wait_for_deliveries(recorder, count).await;
+    recorder.workers()
+}
+
+#[tokio::test]
+async fn test_actors_list_only_their_own_subscriptions() {
+    let (manager, _) = setup_with_audit().await;
+    let first = subscribe_moves(&manager, "ops", TOKEN, TransportType::Worker, json!({"worker_id": "moves"})).await;
+    let second = subscribe_moves(&manager, "ops", TOKEN, TransportType::Sse, json!({})).await;
+    subscribe_moves(&manager, "audit", OTHER_TOKEN, TransportType::Sse, json!({})).await;
+
+    let listed = manager.list_subscriptions(&ActorId::from("ops"), TOKEN).unwrap();
+    let mut ids: Vec<&SubscriptionId> = listed.iter().map(|s| &s.id).collect();
+    ids.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut expected = vec![&first, &second];
+    expected.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(ids, expected);
+    assert!(listed.iter().all(|s| s.actor_id == ActorId::from("ops")));
+    assert_eq!(manager.list_subscriptions(&ActorId::from("audit"), OTHER_TOKEN).unwrap().len(), 1);
+
+    let err = manager.list_subscriptions(&ActorId::from("ops"), OTHER_TOKEN).unwrap_err();
+    assert!(err.to_string().contains("Authentication failed"));
+}
+
+#[tokio::test]
+async fn test_updated_config_applies_to_the_next_event() {
+    let (manager, recorder) = setup_with_audit().await;
+    let ops = ActorId::from("ops");
+    let subscription = subscribe_moves(&manager, "ops", TOKEN, TransportType::Worker, json!({"worker_id": "moves", "ordered": true})).await;
+    publish_move(&manager).await;
+    assert_eq!(delivered(&recorder, 1).await, vec!["moves"]);
+
+    let updated = manager
+        .update_subscription_config(&ops, TOKEN, &subscription, json!({"worker_id": "moves-v2", "ordered": true}))
+        .await
+        .unwrap();
+    assert_eq!(updated.config["worker_id"], json!("moves-v2"));
+    assert_eq!(updated.event_name, EventName::from("robot:moved"));
+    publish_move(&manager).await;
+    assert_eq!(delivered(&recorder, 2).await, vec!["moves", "moves-v2"]);
+}
+
+#[tokio::test]
+async fn test_invalid_or_foreign_updates_are_refused() {
+    let (manager, _) = setup_with_audit().await;
+    let ops = ActorId::from("ops");
+    let subscription = subscribe_moves(&manager, "ops", TOKEN, TransportType::Worker, json!({"worker_id": "moves"})).await;
+
+    let invalid = [
+        json!({}),
+        json!({"worker_id": "moves", "ordered": "yes"}),
+        json!({"worker_id": "moves", "from": "earliest"}),
+    ];
+    for config in invalid {
+        assert!(manager.update_subscription_config(&ops, TOKEN, &subscription, config).await.is_err());
+    }
+    let err = manager
+        .update_subscription_config(&ActorId::from("audit"), OTHER_TOKEN, &subscription, json!({"worker_id": "stolen"}))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("Subscription not found"));
+
+    // Nothing changed
+    let listed = manager.list_subscriptions(&ops, TOKEN).unwrap();
+    assert_eq!(listed[0].config, json!({"worker_id": "moves"}));
+}
+
+#[tokio::test]
+async fn test_unsubscribe_drops_registered_senders() {
+    let (manager, _) = setup_with_audit().await;
+    let ops = ActorId::from("ops");
+    let sse = subscribe_moves(&manager, "ops", TOKEN, TransportType::Sse, json!({})).await;
+    let grpc = subscribe_moves(&manager, "ops", TOKEN, TransportType::Grpc, json!({})).await;
+    let (sse_sender, mut sse_receiver) = tokio::sync::mpsc::channel(8);
+    let (grpc_sender, mut grpc_receiver) = tokio::sync::mpsc::channel(8);
+    manager.register_sse_connection(sse.clone(), sse_sender);
+    manager.register_grpc_stream(grpc.clone(), grpc_sender);
+
+    // Only the owner can unsubscribe
+    assert!(!manager.unsubscribe(&ActorId::from("audit"), OTHER_TOKEN, &sse).await.unwrap());
+    assert!(manager.get_sse_sender(&sse).is_some());
+
+    assert!(manager.unsubscribe(&ops, TOKEN, &sse).await.unwrap());
+    assert!(manager.unsubscribe(&ops, TOKEN, &grpc).await.unwrap());
+    assert!(manager.get_sse_sender(&sse).is_none());
+    assert!(manager.get_grpc_sender(&grpc).is_none());
+    // The manager held the only senders, so the streams end
+    assert!(sse_receiver.recv().await.is_none());
+    assert!(grpc_receiver.recv().await.is_none());
+
+    assert!(manager.list_subscriptions(&ops, TOKEN).unwrap().is_empty());
+    assert!(!manager.unsubscribe(&ops, TOKEN, &sse).await.unwrap());
+}
@@ -233,5 +233,105 @@ async fn test_transformation_fallback_on_error() {
     assert!(result.is_ok());
 }
 
+#[test]
+fn test_pipeline_stages_run_in_order() {
+    use narayana_rde::transformations;
+
+    let config = serde_json::json!({
+        "pipeline": [
+            {"type": "rename", "fields": {"temp": "reading.celsius", "id": "robot"}},
+            {"type": "enrich", "fields": {"site": "plant-3", "robot": "ignored"}},
+            {"type": "template", "target": "summary", "template": "{{ robot }} at {{ $.reading.celsius }}C ({{ missing }})"},
+            {"type": "project", "fields": ["robot", "site", "summary", "reading.celsius"]},
+            {"type": "flatten", "separator": "_"}
+        ]
+    });
+    let subscription = Subscription {
+        id: SubscriptionId::new(),
+        actor_id: ActorId::from("test"),
+        event_name: EventName::from("test:event"),
+        transport: TransportType::Webhook,
+        config,
+        created_at: 0,
+    };
+    let payload = serde_json::json!({"id": "r2", "temp": 81.5, "debug": {"raw": [1, 2]}});
+
+    let output = transformations::apply_transformation(&subscription, &payload).unwrap();
+    assert_eq!(output, serde_json::json!({
+        "robot": "r2",
+        "site": "plant-3",
+        "summary": "r2 at 81.5C ()",
+        "reading_celsius": 81.5
+    }));
+}
+
+#[test]
+fn test_pipeline_config_is_validated() {
+    for pipeline in [
+        serde_json::json!({"type": "rename"}),
+        serde_json::json!([{"type": "uppercase"}]),
+        serde_json::json!([{"type": "rename", "fields": {"a..b": "c"}}]),
+        serde_json::json!([{"type": "flatten", "separator": ""}]),
+        serde_json::json!([{"type": "flatten", "max_depth": 0}]),
+        serde_json::json!([{"type": "template", "target": "s", "template": "{{ a "}]),
+        serde_json::json!((0..33).map(|_| serde_json::json!({"type": "flatten"})).collect::<Vec<_>>()),
+    ] {
+        assert!(TransformPipeline::from_config(&serde_json::json!({"pipeline": pipeline})).is_err(), "{} should not parse", pipeline);
+    }
+    assert!(TransformPipeline::from_config(&serde_json::json!({})).unwrap().is_none());
+    let pipeline = TransformPipeline::from_config(&serde_json::json!({"pipeline": [{"type": "flatten"}]})).unwrap().unwrap();
+    assert_eq!(pipeline.stages(), &[TransformStage::Flatten { separator: ".".to_string(), max_depth: None }]);
+}
+
+#[test]
+fn test_pipeline_dry_run_reports_each_stage() {
+    use narayana_rde::transformations::dry_run;
+
+    let config = serde_json::json!({
+        "pipeline": [
+            {"type": "enrich", "fields": {"meta.version": 2}},
+            {"type": "flatten", "max_depth": 1}
+        ]
+    });
+    let report = dry_run(&config, &serde_json::json!({"a": {"b": {"c": 1}}})).unwrap();
+    assert_eq!(report.steps.len(), 2);
+    assert_eq!(report.steps[0].output, Some(serde_json::json!({"a": {"b": {"c": 1}}, "meta": {"version": 2}})));
+    assert_eq!(report.steps[1].step, "flatten");
+    let flat = serde_json::json!({"a.b": {"c": 1}, "meta.version": 2});
+    assert_eq!(report.output, Some(flat.clone()));
+    assert_eq!(report.delivered, flat);
+
+    // A failing stage stops the run; deliveries fall back to the original payload
+    let config = serde_json::json!({"pipeline": [{"type": "flatten"}, {"type": "enrich", "fields": {"x": 1}}]});
+    let report = dry_run(&config, &serde_json::json!([1, 2])).unwrap();
+    assert_eq!(report.steps.len(), 1);
+    assert!(report.steps[0].error.as_deref().unwrap().contains("object payload"));
+    assert_eq!(report.output, None);
+    assert_eq!(report.delivered, serde_json::json!([1, 2]));
 
+    assert!(dry_run(&serde_json::json!({"pipeline": "flatten"}), &serde_json::json!({})).is_err());
+}
+
+#[tokio::test]
+async fn test_subscribe_rejects_invalid_pipeline() {
+    let manager = create_test_manager();
+    let origin = Actor::new(
+        ActorId::from("origin1"),
+        "Origin".to_string(),
+        ActorType::Origin,
+        "token-123456789012".to_string(),
+    );
+    manager.register_actor(origin).await.unwrap();
 
+    let result = manager.subscribe(
+        &ActorId::from("origin1"),
+        "token-123456789012",
+        "source1:order_created",
+        TransportType::Webhook,
+        Some(serde_json::json!({
+            "webhook_url": "https://example.com/webhook",
+            "pipeline": [{"type": "project", "fields": ["ok", ""]}]
+        })),
+    ).await;
+    assert!(result.unwrap_err().to_string().contains("pipeline stage 0 (project)"));
+}
@@ -6,8 +6,10 @@ use crate::config::AudioConfig;
 use crate::error::AudioError;
 use crate::llm_integration::LlmAudioProcessor;
 use crate::advanced_features::AdvancedAudioProcessor;
+use crate::event_store::AudioEventStore;
 use bytes::Bytes;
 use narayana_core::Error;
+use narayana_storage::sensory_streams::SensoryStreamManager;
 use narayana_wld::protocol_adapters::ProtocolAdapter;
 use narayana_wld::world_broker::WorldBrokerHandle;
 use narayana_wld::event_transformer::{WorldEvent, WorldAction};
@@ -33,6 +35,7 @@ pub struct AudioAdapter {
     is_running: Arc<RwLock<bool>>,
     processing_handle: Arc<RwLock<Option<tokio::task::JoinHandle<()>>>>,
     audio_receiver: Arc<RwLock<Option<mpsc::Receiver<Bytes>>>>,
+    event_store: Arc<RwLock<Option<Arc<AudioEventStore>>>>,
 }
 
 impl AudioAdapter {
@@ -97,8 +100,26 @@ impl AudioAdapter {
             is_running: Arc::new(RwLock::new(false)),
             processing_handle: Arc::new(RwLock::new(None)),
             audio_receiver: Arc::new(RwLock::new(None)),
+            event_store: Arc::new(RwLock::new(None)),
         })
     }
+
+    /// Persist analysis results into an event table of `streams`
+    /// (requires `persistence.enabled`)
+    pub fn persist_to(&self, streams: Arc<SensoryStreamManager>) -> Result<Arc<AudioEventStore>, Error> {
+        if !self.config.persistence.enabled {
+            return Err(Error::Storage("Audio event persistence is disabled in config".to_string()));
+        }
+        let store = Arc::new(AudioEventStore::new(streams, self.config.persistence.clone())?);
+        info!("Persisting audio events to table {}", store.table());
+        *self.event_store.write() = Some(store.clone());
+        Ok(store)
+    }
+
+    /// Event store in use, if persistence was set up
+    pub fn event_store(&self) -> Option<Arc<AudioEventStore>> {
+        self.event_store.read().clone()
+    }
 }
 
 #[async_trait]
@@ -149,6 +170,7 @@ impl ProtocolAdapter for AudioAdapter {
         let event_sender = self.event_sender.clone();
         let is_running = self.is_running.clone();
         let config = self.config.clone();
+        let event_store = self.event_store.clone();
 
         let handle = tokio::spawn(async move {
             let mut analysis_interval = interval(Duration::from_millis(config.analysis.analysis_interval_ms));
@@ -176,6 +198,7 @@ impl ProtocolAdapter for AudioAdapter {
                                         &analyzer,
                                         &llm_processor,
                                         &event_sender,
+                                        &event_store,
                                         &config,
                                     ).await;
                                     audio_buffer.clear();
@@ -190,6 +213,7 @@ impl ProtocolAdapter for AudioAdapter {
                                     &analyzer,
                                     &llm_processor,
                                     &event_sender,
+                                    &event_store,
                                     &config,
                                 ).await;
                                 audio_buffer.clear();
@@ -213,6 +237,7 @@ impl ProtocolAdapter for AudioAdapter {
                                     &analyzer,
                                     &llm_processor,
                                     &event_sender,
+                                    &event_store,
                                     &config,
                                 ).await;
                                 audio_buffer.clear();
@@ -288,6 +313,7 @@ impl AudioAdapter {
         analyzer: &Arc<RwLock<Option<Arc<AudioAnalyzer>>>>,
         llm_processor: &Arc<LlmAudioProcessor>,
         event_sender: &Arc<RwLock<Option<broadcast::Sender<WorldEvent>>>>,
+        event_store: &Arc<RwLock<Option<Arc<AudioEventStore>>>>,
         config: &Arc<AudioConfig>,
     ) {
         // Combine audio buffer
@@ -325,6 +351,13 @@ impl AudioAdapter {
             }
         };
 
+        // Persist analysis for later recall
+        if let (Ok(analysis), Some(store)) = (&analysis_result, event_store.read().clone()) {
+            if let Err(e) = store.record_analysis(analysis, text.as_deref()) {
+                warn!("Failed to persist audio event: {}", e);
+            }
+        }
+
         // Emit events
         let sender_guard = event_sender.read();
         if let Some(ref sender) = *sender_guard {
@@ -26,6 +26,32 @@ pub struct AudioConfig {
 
     /// Number of audio channels
     pub channels: u16,
+
+    /// Persistence of audio events into sensory_streams tables
+    pub persistence: PersistenceConfig,
+}
+
+/// Audio event persistence configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PersistenceConfig {
+    /// Store audio events and analysis results (off by default)
+    pub enabled: bool,
+
+    /// Event table name
+    pub table: String,
+
+    /// Source name stored with each event
+    pub source: String,
+
+    /// Drop events older than this many seconds (None keeps them regardless of age)
+    pub max_age_secs: Option<u64>,
+
+    /// Keep at most this many events
+    pub max_rows: usize,
+
+    /// Skip analysis results quieter than this (dBFS)
+    pub min_loudness_db: f32,
 }
 
 /// Audio capture configuration - 2025 enhanced
@@ -121,6 +147,20 @@ impl Default for AudioConfig {
             buffer_size: 4096,
             sample_rate: 44100,
             channels: 1,
+            persistence: PersistenceConfig::default(),
+        }
+    }
+}
+
+impl Default for PersistenceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            table: "audio_events".to_string(),
+            source: "microphone".to_string(),
+            max_age_secs: Some(7 * 24 * 60 * 60), // 7 days
+            max_rows: 100_000,
+            min_loudness_db: -60.0,
         }
     }
 }
@@ -198,6 +238,7 @@ impl AudioConfig {
         // Security: Validate nested configs
         self.analysis.validate()?;
         self.capture.validate()?;
+        self.persistence.validate()?;
 
         Ok(())
     }
@@ -291,3 +332,29 @@ impl AnalysisConfig {
     }
 }
 
+impl PersistenceConfig {
+    /// Validate persistence configuration
+    pub fn validate(&self) -> Result<(), String> {
+        if self.table.is_empty() || self.table.len() > 128 {
+            return Err("Event table name must be 1-128 characters".to_string());
+        }
+
+        if self.source.is_empty() || self.source.len() > 128 {
+            return Err("Event source must be 1-128 characters".to_string());
+        }
+
+        if self.max_rows == 0 {
+            return Err("Max rows must be greater than 0".to_string());
+        }
+
+        if self.max_rows > 10_000_000 {
+            return Err("Max rows too large (max 10000000)".to_string());
+        }
+
+        if !self.min_loudness_db.is_finite() {
+            return Err("Min loudness must be a finite number".to_string());
+        }
+
+        Ok(())
+    }
+}
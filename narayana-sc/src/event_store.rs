@@ -0,0 +1,164 @@
+//! Audio event persistence
+//!
+//! Writes detected audio events and analysis results, with their acoustic
+//! features (loudness, pitch, spectral shape) and a coarse classification,
+//! into a sensory_streams event table. The dreaming loop and analytics query
+//! that table to look back over what the robot heard; the table's retention
+//! policy bounds how far back that goes.
+
+use crate::audio_analyzer::AudioAnalysis;
+use crate::config::PersistenceConfig;
+use crate::error::AudioError;
+use crate::streaming::{AudioEvent, AudioEventType};
+use narayana_storage::sensory_streams::{
+    RetentionPolicy, SensoryEventQuery, SensoryEventRecord, SensoryStreamManager,
+};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Loudness reported for digital silence
+const SILENCE_DB: f32 = -100.0;
+
+/// Stores audio events in a sensory_streams event table
+pub struct AudioEventStore {
+    streams: Arc<SensoryStreamManager>,
+    config: PersistenceConfig,
+}
+
+impl AudioEventStore {
+    /// Create the event table (if needed) with the configured retention
+    pub fn new(streams: Arc<SensoryStreamManager>, config: PersistenceConfig) -> Result<Self, AudioError> {
+        config.validate().map_err(AudioError::Config)?;
+        let retention = RetentionPolicy {
+            max_age_secs: config.max_age_secs,
+            max_rows: config.max_rows,
+        };
+        streams.create_event_table(&config.table, retention)?;
+        Ok(Self { streams, config })
+    }
+
+    /// Table the events are stored in
+    pub fn table(&self) -> &str {
+        &self.config.table
+    }
+
+    /// Store a detected audio event, with the features of the analysis it came from
+    pub fn record_event(&self, event: &AudioEvent, analysis: Option<&AudioAnalysis>) -> Result<(), AudioError> {
+        let mut features = analysis.map(analysis_features).unwrap_or_default();
+        let mut labels = BTreeMap::new();
+        features.insert("energy".to_string(), event.energy as f64);
+        features.insert("loudness_db".to_string(), loudness_db(event.energy) as f64);
+
+        let kind = match &event.event_type {
+            AudioEventType::SignificantSound => "significant_sound",
+            AudioEventType::VoiceActivity => "voice_activity",
+            AudioEventType::SoundEvent(name) => {
+                labels.insert("sound".to_string(), name.chars().take(256).collect());
+                "sound_event"
+            }
+            AudioEventType::SpatialEvent(x, y, z) => {
+                features.insert("x".to_string(), *x as f64);
+                features.insert("y".to_string(), *y as f64);
+                features.insert("z".to_string(), *z as f64);
+                "spatial_event"
+            }
+        };
+        if let Some(analysis) = analysis {
+            labels.insert("class".to_string(), classify(analysis).to_string());
+        }
+
+        // AudioEvent carries a monotonic timestamp; place it on the wall clock
+        let timestamp_ms = now_millis().saturating_sub(event.timestamp.elapsed().as_millis() as u64);
+        self.insert(kind, timestamp_ms, features, labels)
+    }
+
+    /// Store an analysis result, with the transcript of the same audio if there is one
+    ///
+    /// Returns false if it was quieter than `min_loudness_db` and skipped.
+    pub fn record_analysis(&self, analysis: &AudioAnalysis, transcript: Option<&str>) -> Result<bool, AudioError> {
+        if loudness_db(analysis.energy) < self.config.min_loudness_db {
+            return Ok(false);
+        }
+        let mut labels = BTreeMap::new();
+        labels.insert("class".to_string(), classify(analysis).to_string());
+        if let Some(text) = transcript {
+            labels.insert("transcript".to_string(), text.chars().take(256).collect());
+        }
+        self.insert("analysis", now_millis(), analysis_features(analysis), labels)?;
+        Ok(true)
+    }
+
+    /// Stored events matching `query`, oldest first
+    pub fn query(&self, query: &SensoryEventQuery) -> Result<Vec<SensoryEventRecord>, AudioError> {
+        Ok(self.streams.query_events(&self.config.table, query)?)
+    }
+
+    fn insert(
+        &self,
+        kind: &str,
+        timestamp_ms: u64,
+        features: BTreeMap<String, f64>,
+        labels: BTreeMap<String, String>,
+    ) -> Result<(), AudioError> {
+        // Analysis can yield NaN on degenerate input; those features are dropped
+        let features = features.into_iter().filter(|(_, v)| v.is_finite()).collect();
+        let record = SensoryEventRecord {
+            timestamp_ms,
+            source: self.config.source.clone(),
+            kind: kind.to_string(),
+            features,
+            labels,
+        };
+        Ok(self.streams.insert_event(&self.config.table, record)?)
+    }
+}
+
+/// Loudness in dBFS of a mean-square energy
+pub fn loudness_db(energy: f32) -> f32 {
+    if energy <= 0.0 || !energy.is_finite() {
+        return SILENCE_DB;
+    }
+    (10.0 * energy.log10()).max(SILENCE_DB)
+}
+
+/// Coarse class of a sound: "silence", "voice", "noise" or "sound"
+///
+/// A heuristic from loudness, pitch and zero-crossing rate, good enough to
+/// filter the auditory history by; sound event detection gives finer labels.
+pub fn classify(analysis: &AudioAnalysis) -> &'static str {
+    if loudness_db(analysis.energy) < -60.0 {
+        return "silence";
+    }
+    let voiced_pitch = analysis.pitch.is_some_and(|pitch| (70.0..=400.0).contains(&pitch));
+    if voiced_pitch && analysis.zero_crossing_rate < 0.15 {
+        "voice"
+    } else if analysis.zero_crossing_rate > 0.3 {
+        "noise"
+    } else {
+        "sound"
+    }
+}
+
+fn analysis_features(analysis: &AudioAnalysis) -> BTreeMap<String, f64> {
+    let mut features = BTreeMap::new();
+    features.insert("energy".to_string(), analysis.energy as f64);
+    features.insert("loudness_db".to_string(), loudness_db(analysis.energy) as f64);
+    features.insert("zero_crossing_rate".to_string(), analysis.zero_crossing_rate as f64);
+    features.insert("spectral_centroid".to_string(), analysis.spectral_centroid as f64);
+    features.insert("spectral_rolloff".to_string(), analysis.spectral_rolloff as f64);
+    if let Some(pitch) = analysis.pitch {
+        features.insert("pitch".to_string(), pitch as f64);
+    }
+    if let Some(frequency) = analysis.dominant_frequencies.first() {
+        features.insert("dominant_frequency".to_string(), *frequency as f64);
+    }
+    features
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
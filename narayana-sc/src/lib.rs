@@ -18,9 +18,10 @@ pub mod cpl_integration;
 pub mod streaming; // 2025: Modern streaming architecture
 pub mod advanced_features; // Advanced audio processing for comprehensive capture
 pub mod comprehensive_capture; // Complete comprehensive capture system
+pub mod event_store; // Audio event persistence into sensory_streams tables
 
 pub use error::AudioError;
-pub use config::{AudioConfig, CaptureConfig, AnalysisConfig};
+pub use config::{AudioConfig, CaptureConfig, AnalysisConfig, PersistenceConfig};
 pub use audio_capture::AudioCapture;
 pub use audio_analyzer::AudioAnalyzer;
 pub use audio_adapter::AudioAdapter;
@@ -28,4 +29,5 @@ pub use llm_integration::LlmAudioProcessor;
 pub use streaming::{AudioStreamBuffer, EventBasedProcessor, AdaptiveStreamController, AudioEvent, AudioEventType};
 pub use advanced_features::AdvancedAudioProcessor;
 pub use comprehensive_capture::{ComprehensiveAudioCapture, CaptureStats, ProcessedAudio};
+pub use event_store::AudioEventStore;
 
@@ -0,0 +1,82 @@
+//! Tests for audio event persistence into sensory_streams tables
+
+use narayana_sc::audio_analyzer::AudioAnalysis;
+use narayana_sc::event_store::{classify, loudness_db, AudioEventStore};
+use narayana_sc::{AudioEvent, AudioEventType, PersistenceConfig};
+use narayana_storage::sensory_streams::{SensoryEventQuery, SensoryStreamManager};
+use std::sync::Arc;
+
+fn analysis(energy: f32, pitch: Option<f32>, zero_crossing_rate: f32) -> AudioAnalysis {
+    AudioAnalysis {
+        spectrum: vec![],
+        dominant_frequencies: vec![220.0],
+        energy,
+        zero_crossing_rate,
+        pitch,
+        spectral_centroid: 900.0,
+        spectral_rolloff: 3_000.0,
+    }
+}
+
+fn store(config: PersistenceConfig) -> (AudioEventStore, Arc<SensoryStreamManager>) {
+    let streams = Arc::new(SensoryStreamManager::new());
+    (AudioEventStore::new(streams.clone(), config).unwrap(), streams)
+}
+
+#[test]
+fn test_analysis_persisted_with_features_and_class() {
+    let (store, streams) = store(PersistenceConfig::default());
+    assert!(store.record_analysis(&analysis(0.01, Some(180.0), 0.05), Some("hello")).unwrap());
+    // Below min_loudness_db
+    assert!(!store.record_analysis(&analysis(1e-9, None, 0.0), None).unwrap());
+
+    let events = store.query(&SensoryEventQuery::default()).unwrap();
+    assert_eq!(events.len(), 1);
+    let event = &events[0];
+    assert_eq!(event.kind, "analysis");
+    assert_eq!(event.source, "microphone");
+    assert_eq!(event.labels["class"], "voice");
+    assert_eq!(event.labels["transcript"], "hello");
+    assert_eq!(event.features["loudness_db"], -20.0);
+    assert_eq!(event.features["pitch"], 180.0);
+    assert_eq!(event.features["dominant_frequency"], 220.0);
+    assert_eq!(streams.event_count("audio_events"), Some(1));
+}
+
+#[test]
+fn test_audio_events_persisted_by_type() {
+    let (store, _) = store(PersistenceConfig::default());
+    let event = |event_type| AudioEvent { timestamp: std::time::Instant::now(), energy: 0.1, event_type };
+    store.record_event(&event(AudioEventType::VoiceActivity), Some(&analysis(0.1, Some(120.0), 0.1))).unwrap();
+    store.record_event(&event(AudioEventType::SoundEvent("door_knock".to_string())), None).unwrap();
+    store.record_event(&event(AudioEventType::SpatialEvent(1.0, 0.5, 0.0)), None).unwrap();
+
+    let query = SensoryEventQuery { kind: Some("sound_event".to_string()), ..Default::default() };
+    let knocks = store.query(&query).unwrap();
+    assert_eq!(knocks.len(), 1);
+    assert_eq!(knocks[0].labels["sound"], "door_knock");
+    assert_eq!(knocks[0].features["loudness_db"], -10.0);
+
+    let all = store.query(&SensoryEventQuery::default()).unwrap();
+    let kinds: Vec<&str> = all.iter().map(|e| e.kind.as_str()).collect();
+    assert!(kinds.contains(&"voice_activity") && kinds.contains(&"spatial_event"));
+    let spatial = all.iter().find(|e| e.kind == "spatial_event").unwrap();
+    assert_eq!(spatial.features["x"], 1.0);
+}
+
+#[test]
+fn test_retention_and_config() {
+    let config = PersistenceConfig { max_rows: 2, ..Default::default() };
+    let (store, _) = store(config);
+    for _ in 0..5 {
+        store.record_analysis(&analysis(0.5, None, 0.4), None).unwrap();
+    }
+    let events = store.query(&SensoryEventQuery::default()).unwrap();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].labels["class"], "noise");
+
+    assert!(PersistenceConfig { max_rows: 0, ..Default::default() }.validate().is_err());
+    assert!(PersistenceConfig { table: String::new(), ..Default::default() }.validate().is_err());
+    assert_eq!(loudness_db(0.0), -100.0);
+    assert_eq!(classify(&analysis(1e-8, Some(150.0), 0.05)), "silence");
+}
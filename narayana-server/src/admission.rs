@@ -0,0 +1,256 @@
+// Per-route admission control for the HTTP API
+// Each configured route group gets a concurrency limit and a bounded wait
+// queue; requests beyond the queue, or that wait past the queue timeout, are
+// refused so heavy analytics work cannot starve latency-critical endpoints.
+
+use narayana_core::config::{ConnectionPoolConfig, NarayanaConfig, RouteLimit};
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::config_manager::ConfigManager;
+
+/// Why a request was refused
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rejection {
+    /// The wait queue was already full
+    QueueFull { retry_after_secs: u64 },
+    /// No slot freed up within the queue timeout
+    QueueTimeout { retry_after_secs: u64 },
+}
+
+impl Rejection {
+    pub fn reason(&self) -> &'static str {
+        match self {
+            Rejection::QueueFull { .. } => "queue_full",
+            Rejection::QueueTimeout { .. } => "queue_timeout",
+        }
+    }
+
+    pub fn retry_after_secs(&self) -> u64 {
+        match self {
+            Rejection::QueueFull { retry_after_secs } | Rejection::QueueTimeout { retry_after_secs } => *retry_after_secs,
+        }
+    }
+}
+
+/// Concurrency slots and queue for one route group
+struct RouteGate {
+    limit: RouteLimit,
+    segments: Vec<String>,
+    semaphore: Arc<Semaphore>,
+    queued: AtomicUsize,
+    admitted: AtomicU64,
+    rejected_queue_full: AtomicU64,
+    rejected_timeout: AtomicU64,
+}
+
+impl RouteGate {
+    fn new(limit: &RouteLimit) -> Self {
+        Self {
+            segments: split(&limit.path).map(str::to_string).collect(),
+            semaphore: Arc::new(Semaphore::new(limit.max_concurrent.max(1))),
+            limit: limit.clone(),
+            queued: AtomicUsize::new(0),
+            admitted: AtomicU64::new(0),
+            rejected_queue_full: AtomicU64::new(0),
+            rejected_timeout: AtomicU64::new(0),
+        }
+    }
+
+    /// Segment-wise prefix match; `*` matches any one segment
+    fn matches(&self, path: &[&str]) -> bool {
+        self.segments.len() <= path.len()
+            && self.segments.iter().zip(path).all(|(pattern, segment)| pattern == "*" || pattern == segment)
+    }
+
+    /// More segments is more specific; literal segments beat wildcards
+    fn specificity(&self) -> (usize, usize) {
+        (self.segments.len(), self.segments.iter().filter(|s| *s != "*").count())
+    }
+
+    fn in_flight(&self) -> usize {
+        self.limit.max_concurrent.max(1).saturating_sub(self.semaphore.available_permits())
+    }
+
+    fn report(&self) {
+        let route = self.limit.path.clone();
+        metrics::gauge!("narayana_http_route_queue_depth", "route" => route.clone())
+            .set(self.queued.load(Ordering::Relaxed) as f64);
+        metrics::gauge!("narayana_http_route_in_flight", "route" => route).set(self.in_flight() as f64);
+    }
+
+    fn reject(&self, rejection: Rejection, path: &str) -> Rejection {
+        let counter = match rejection {
+            Rejection::QueueFull { .. } => &self.rejected_queue_full,
+            Rejection::QueueTimeout { .. } => &self.rejected_timeout,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        metrics::counter!(
+            "narayana_http_admission_rejected_total",
+            "route" => self.limit.path.clone(),
+            "reason" => rejection.reason()
+        )
+        .increment(1);
+        tracing::warn!("Refused {} ({}): {}", path, self.limit.path, rejection.reason());
+        rejection
+    }
+}
+
+/// A reserved queue position, released however the wait ends (including the
+/// client going away mid-wait)
+struct QueuePosition<'a>(&'a RouteGate);
+
+impl Drop for QueuePosition<'_> {
+    fn drop(&mut self) {
+        self.0.queued.fetch_sub(1, Ordering::AcqRel);
+        self.0.report();
+    }
+}
+
+fn split(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter(|s| !s.is_empty())
+}
+
+/// Held for the lifetime of an admitted request; frees the slot on drop
+pub struct Admission {
+    permit: Option<OwnedSemaphorePermit>,
+    gate: Option<Arc<RouteGate>>,
+}
+
+impl Admission {
+    /// The route group this request counted against, if any
+    pub fn route(&self) -> Option<&str> {
+        self.gate.as_ref().map(|gate| gate.limit.path.as_str())
+    }
+}
+
+impl Drop for Admission {
+    fn drop(&mut self) {
+        if let Some(gate) = self.gate.take() {
+            drop(self.permit.take());
+            gate.report();
+        }
+    }
+}
+
+/// Route group state as reported by `stats`
+#[derive(Debug, Clone, Serialize)]
+pub struct RouteAdmissionStats {
+    pub path: String,
+    pub max_concurrent: usize,
+    pub max_queued: usize,
+    pub queue_timeout_ms: u64,
+    pub in_flight: usize,
+    pub queued: usize,
+    pub admitted: u64,
+    pub rejected_queue_full: u64,
+    pub rejected_timeout: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AdmissionStats {
+    pub routes: Vec<RouteAdmissionStats>,
+}
+
+/// Admission controller shared by the router; route groups are rebuilt on
+/// config reload, keeping the live state of groups whose limits are unchanged
+pub struct AdmissionController {
+    gates: RwLock<Arc<Vec<Arc<RouteGate>>>>,
+}
+
+impl AdmissionController {
+    pub fn new(config: &ConnectionPoolConfig) -> Self {
+        Self {
+            gates: RwLock::new(Arc::new(config.route_limits.iter().map(|l| Arc::new(RouteGate::new(l))).collect())),
+        }
+    }
+
+    pub fn update(&self, config: &ConnectionPoolConfig) {
+        let mut gates = self.gates.write();
+        let rebuilt = config.route_limits.iter()
+            .map(|limit| {
+                gates.iter()
+                    .find(|gate| gate.limit == *limit)
+                    .cloned()
+                    .unwrap_or_else(|| Arc::new(RouteGate::new(limit)))
+            })
+            .collect();
+        *gates = Arc::new(rebuilt);
+    }
+
+    /// Rebuild the route gates when `route_limits` change; routes whose limits
+    /// are unchanged keep their gate and whatever is queued on it
+    pub async fn watch(self: &Arc<Self>, manager: &ConfigManager) {
+        let controller = self.clone();
+        manager.subscribe(Box::new(move |config: &NarayanaConfig| {
+            controller.update(&config.connection_pool);
+        })).await;
+    }
+
+    /// Most specific route group governing `path`
+    fn gate_for(&self, path: &str) -> Option<Arc<RouteGate>> {
+        let segments: Vec<&str> = split(path).collect();
+        let gates = self.gates.read().clone();
+        gates.iter()
+            .filter(|gate| gate.matches(&segments))
+            .max_by_key(|gate| gate.specificity())
+            .cloned()
+    }
+
+    /// Wait for a slot on `path`'s route group (immediate for unlimited routes)
+    pub async fn admit(&self, path: &str) -> Result<Admission, Rejection> {
+        let Some(gate) = self.gate_for(path) else {
+            return Ok(Admission { permit: None, gate: None });
+        };
+        let retry_after_secs = gate.limit.queue_timeout.as_secs().max(1);
+        let permit = match gate.semaphore.clone().try_acquire_owned() {
+            Ok(permit) => permit,
+            Err(_) => {
+                // Reserve a queue position, refusing once the queue is full
+                let reserved = gate.queued.fetch_update(Ordering::AcqRel, Ordering::Acquire, |queued| {
+                    (queued < gate.limit.max_queued).then_some(queued + 1)
+                });
+                if reserved.is_err() {
+                    return Err(gate.reject(Rejection::QueueFull { retry_after_secs }, path));
+                }
+                let _position = QueuePosition(&gate);
+                gate.report();
+                match tokio::time::timeout(
+                    gate.limit.queue_timeout.max(Duration::from_millis(1)),
+                    gate.semaphore.clone().acquire_owned(),
+                )
+                .await
+                {
+                    Ok(Ok(permit)) => permit,
+                    _ => return Err(gate.reject(Rejection::QueueTimeout { retry_after_secs }, path)),
+                }
+            }
+        };
+        gate.admitted.fetch_add(1, Ordering::Relaxed);
+        gate.report();
+        Ok(Admission { permit: Some(permit), gate: Some(gate) })
+    }
+
+    pub fn stats(&self) -> AdmissionStats {
+        let gates = self.gates.read().clone();
+        AdmissionStats {
+            routes: gates.iter()
+                .map(|gate| RouteAdmissionStats {
+                    path: gate.limit.path.clone(),
+                    max_concurrent: gate.limit.max_concurrent,
+                    max_queued: gate.limit.max_queued,
+                    queue_timeout_ms: gate.limit.queue_timeout.as_millis() as u64,
+                    in_flight: gate.in_flight(),
+                    queued: gate.queued.load(Ordering::Relaxed),
+                    admitted: gate.admitted.load(Ordering::Relaxed),
+                    rejected_queue_full: gate.rejected_queue_full.load(Ordering::Relaxed),
+                    rejected_timeout: gate.rejected_timeout.load(Ordering::Relaxed),
+                })
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,278 @@
+// Row-oriented bulk insert support
+// Parses NDJSON or JSON-array payloads, validates each row against the table
+// schema and accumulates the valid ones into columnar batches.
+
+use narayana_core::{column::Column, schema::{DataType, Field}};
+use serde::Serialize;
+use serde_json::Value;
+
+/// Most row errors returned in one report (the counts stay exact)
+pub const MAX_REPORTED_ERRORS: usize = 1000;
+
+/// Encodes a Point/Geometry value (WKT, `[lon, lat]`, ...) to its stored bytes
+pub type GeometryEncoder<'a> = &'a (dyn Fn(&DataType, Value) -> Result<Vec<u8>, String> + Sync);
+
+/// Why a single row was rejected
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BulkRowError {
+    /// Zero-based position of the row in the payload
+    pub row: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    pub code: String,
+    pub error: String,
+}
+
+impl BulkRowError {
+    pub fn new(row: usize, field: Option<&str>, code: &str, error: impl Into<String>) -> Self {
+        Self {
+            row,
+            field: field.map(|f| f.to_string()),
+            code: code.to_string(),
+            error: error.into(),
+        }
+    }
+}
+
+/// Split a payload into rows. NDJSON lines that fail to parse become row
+/// errors; a malformed JSON array fails the whole payload.
+pub fn parse_rows(body: &str, ndjson: bool) -> Result<Vec<Result<Value, BulkRowError>>, String> {
+    let trimmed = body.trim_start();
+    if !ndjson || trimmed.starts_with('[') {
+        return match serde_json::from_str::<Value>(body) {
+            Ok(Value::Array(rows)) => Ok(rows.into_iter().map(Ok).collect()),
+            Ok(Value::Object(mut wrapper)) => match wrapper.remove("rows") {
+                Some(Value::Array(rows)) => Ok(rows.into_iter().map(Ok).collect()),
+                _ => Err("Expected a JSON array of rows or an object with a \"rows\" array".to_string()),
+            },
+            Ok(_) => Err("Expected a JSON array of rows".to_string()),
+            Err(e) => Err(format!("Invalid JSON: {}", e)),
+        };
+    }
+    Ok(body.lines()
+        .filter(|line| !line.trim().is_empty())
+        .enumerate()
+        .map(|(row, line)| {
+            serde_json::from_str::<Value>(line)
+                .map_err(|e| BulkRowError::new(row, None, "PARSE_ERROR", format!("Invalid JSON: {}", e)))
+        })
+        .collect())
+}
+
+/// A validated cell, already range-checked for its column type
+#[derive(Debug, Clone)]
+enum Cell {
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    Bool(bool),
+    Str(String),
+    Bytes(Vec<u8>),
+}
+
+/// Storage type for a field; `Nullable` wraps its inner type
+fn storage_type(data_type: &DataType) -> &DataType {
+    match data_type {
+        DataType::Nullable(inner) => storage_type(inner),
+        other => other,
+    }
+}
+
+fn empty_column(data_type: &DataType) -> Column {
+    match storage_type(data_type) {
+        DataType::Int8 => Column::Int8(Vec::new()),
+        DataType::Int16 => Column::Int16(Vec::new()),
+        DataType::Int32 => Column::Int32(Vec::new()),
+        DataType::Int64 => Column::Int64(Vec::new()),
+        DataType::UInt8 => Column::UInt8(Vec::new()),
+        DataType::UInt16 => Column::UInt16(Vec::new()),
+        DataType::UInt32 => Column::UInt32(Vec::new()),
+        DataType::UInt64 => Column::UInt64(Vec::new()),
+        DataType::Float32 => Column::Float32(Vec::new()),
+        DataType::Float64 => Column::Float64(Vec::new()),
+        DataType::Boolean => Column::Boolean(Vec::new()),
+        DataType::Timestamp => Column::Timestamp(Vec::new()),
+        DataType::Date => Column::Date(Vec::new()),
+        DataType::Binary | DataType::Point | DataType::Geometry => Column::Binary(Vec::new()),
+        DataType::String | DataType::Json | DataType::Array(_) | DataType::Map(_, _) | DataType::Nullable(_) => {
+            Column::String(Vec::new())
+        }
+    }
+}
+
+/// Value stored for a null in a nullable column (columns have no null bitmap)
+fn null_cell(data_type: &DataType) -> Cell {
+    match storage_type(data_type) {
+        DataType::Int8 | DataType::Int16 | DataType::Int32 | DataType::Int64
+        | DataType::Timestamp | DataType::Date => Cell::Int(0),
+        DataType::UInt8 | DataType::UInt16 | DataType::UInt32 | DataType::UInt64 => Cell::UInt(0),
+        DataType::Float32 | DataType::Float64 => Cell::Float(0.0),
+        DataType::Boolean => Cell::Bool(false),
+        DataType::Binary | DataType::Point | DataType::Geometry => Cell::Bytes(Vec::new()),
+        _ => Cell::Str(String::new()),
+    }
+}
+
+fn int_in_range(value: &Value, min: i64, max: i64) -> Result<Cell, String> {
+    let n = value.as_i64()
+        .or_else(|| value.as_str().and_then(|s| s.trim().parse().ok()))
+        .ok_or_else(|| format!("expected an integer, got {}", value))?;
+    if n < min || n > max {
+        return Err(format!("{} is out of range [{}, {}]", n, min, max));
+    }
+    Ok(Cell::Int(n))
+}
+
+fn uint_in_range(value: &Value, max: u64) -> Result<Cell, String> {
+    let n = value.as_u64()
+        .or_else(|| value.as_str().and_then(|s| s.trim().parse().ok()))
+        .ok_or_else(|| format!("expected a non-negative integer, got {}", value))?;
+    if n > max {
+        return Err(format!("{} is out of range [0, {}]", n, max));
+    }
+    Ok(Cell::UInt(n))
+}
+
+fn convert(data_type: &DataType, value: Value, geometry: GeometryEncoder<'_>) -> Result<Cell, String> {
+    match storage_type(data_type) {
+        DataType::Int8 => int_in_range(&value, i8::MIN as i64, i8::MAX as i64),
+        DataType::Int16 => int_in_range(&value, i16::MIN as i64, i16::MAX as i64),
+        DataType::Int32 | DataType::Date => int_in_range(&value, i32::MIN as i64, i32::MAX as i64),
+        DataType::Int64 | DataType::Timestamp => int_in_range(&value, i64::MIN, i64::MAX),
+        DataType::UInt8 => uint_in_range(&value, u8::MAX as u64),
+        DataType::UInt16 => uint_in_range(&value, u16::MAX as u64),
+        DataType::UInt32 => uint_in_range(&value, u32::MAX as u64),
+        DataType::UInt64 => uint_in_range(&value, u64::MAX),
+        DataType::Float32 | DataType::Float64 => value.as_f64()
+            .filter(|f| f.is_finite())
+            .map(Cell::Float)
+            .ok_or_else(|| format!("expected a finite number, got {}", value)),
+        DataType::Boolean => value.as_bool()
+            .map(Cell::Bool)
+            .ok_or_else(|| format!("expected a boolean, got {}", value)),
+        DataType::String => match value {
+            Value::String(s) => Ok(Cell::Str(s)),
+            other => Err(format!("expected a string, got {}", other)),
+        },
+        // Semi-structured values are stored as their JSON text
+        DataType::Json | DataType::Array(_) | DataType::Map(_, _) | DataType::Nullable(_) => match value {
+            Value::String(s) => Ok(Cell::Str(s)),
+            other => Ok(Cell::Str(other.to_string())),
+        },
+        DataType::Binary => match value {
+            Value::String(s) => hex::decode(s).map(Cell::Bytes).map_err(|_| "expected hex-encoded bytes".to_string()),
+            Value::Array(items) => serde_json::from_value::<Vec<u8>>(Value::Array(items))
+                .map(Cell::Bytes)
+                .map_err(|_| "expected an array of bytes".to_string()),
+            other => Err(format!("expected bytes, got {}", other)),
+        },
+        inner @ (DataType::Point | DataType::Geometry) => geometry(inner, value).map(Cell::Bytes),
+    }
+}
+
+fn push(column: &mut Column, cell: Cell) {
+    // Cells were range-checked against the column type in `convert`
+    match (column, cell) {
+        (Column::Int8(v), Cell::Int(n)) => v.push(n as i8),
+        (Column::Int16(v), Cell::Int(n)) => v.push(n as i16),
+        (Column::Int32(v), Cell::Int(n)) | (Column::Date(v), Cell::Int(n)) => v.push(n as i32),
+        (Column::Int64(v), Cell::Int(n)) | (Column::Timestamp(v), Cell::Int(n)) => v.push(n),
+        (Column::UInt8(v), Cell::UInt(n)) => v.push(n as u8),
+        (Column::UInt16(v), Cell::UInt(n)) => v.push(n as u16),
+        (Column::UInt32(v), Cell::UInt(n)) => v.push(n as u32),
+        (Column::UInt64(v), Cell::UInt(n)) => v.push(n),
+        (Column::Float32(v), Cell::Float(f)) => v.push(f as f32),
+        (Column::Float64(v), Cell::Float(f)) => v.push(f),
+        (Column::Boolean(v), Cell::Bool(b)) => v.push(b),
+        (Column::String(v), Cell::Str(s)) => v.push(s),
+        (Column::Binary(v), Cell::Bytes(b)) => v.push(b),
+        (column, cell) => unreachable!("cell {:?} does not match column {:?}", cell, column.data_type()),
+    }
+}
+
+/// Accumulates validated rows into columns for the client-supplied fields
+pub struct RowBatchBuilder<'a> {
+    fields: Vec<Field>,
+    geometry: GeometryEncoder<'a>,
+    columns: Vec<Column>,
+    /// Payload positions of the rows currently buffered
+    rows: Vec<usize>,
+}
+
+impl<'a> RowBatchBuilder<'a> {
+    pub fn new(fields: Vec<Field>, geometry: GeometryEncoder<'a>) -> Self {
+        let columns = fields.iter().map(|f| empty_column(&f.data_type)).collect();
+        Self { fields, geometry, columns, rows: Vec::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Validate one row (an object keyed by field name, or an array in field
+    /// order) and buffer it; a rejected row leaves the batch untouched
+    pub fn push_row(&mut self, row: usize, value: Value) -> Result<(), BulkRowError> {
+        let mut values: Vec<Option<Value>> = match value {
+            Value::Object(mut object) => {
+                let values = self.fields.iter().map(|f| object.remove(&f.name)).collect();
+                if let Some(unknown) = object.keys().next() {
+                    return Err(BulkRowError::new(row, Some(unknown), "UNKNOWN_FIELD", format!("Unknown field '{}'", unknown)));
+                }
+                values
+            }
+            Value::Array(items) => {
+                if items.len() != self.fields.len() {
+                    return Err(BulkRowError::new(row, None, "COLUMN_COUNT_MISMATCH", format!(
+                        "Expected {} values, got {}", self.fields.len(), items.len()
+                    )));
+                }
+                items.into_iter().map(Some).collect()
+            }
+            other => {
+                return Err(BulkRowError::new(row, None, "INVALID_ROW", format!(
+                    "Row must be an object or an array, got {}", other
+                )));
+            }
+        };
+
+        let mut cells = Vec::with_capacity(self.fields.len());
+        for (field, value) in self.fields.iter().zip(values.iter_mut()) {
+            let nullable = field.nullable || matches!(field.data_type, DataType::Nullable(_));
+            // Omitted fields take the schema default; an explicit null stays null
+            let value = match value.take() {
+                Some(v) => v,
+                None => field.default_value.clone().unwrap_or(Value::Null),
+            };
+            let cell = if value.is_null() {
+                if !nullable {
+                    return Err(BulkRowError::new(row, Some(&field.name), "MISSING_VALUE", format!(
+                        "Field '{}' is required", field.name
+                    )));
+                }
+                null_cell(&field.data_type)
+            } else {
+                convert(&field.data_type, value, self.geometry).map_err(|e| {
+                    BulkRowError::new(row, Some(&field.name), "INVALID_VALUE", format!("Field '{}': {}", field.name, e))
+                })?
+            };
+            cells.push(cell);
+        }
+
+        for (column, cell) in self.columns.iter_mut().zip(cells) {
+            push(column, cell);
+        }
+        self.rows.push(row);
+        Ok(())
+    }
+
+    /// Take the buffered batch: its columns and the payload positions of its rows
+    pub fn take_batch(&mut self) -> (Vec<Column>, Vec<usize>) {
+        let fresh = self.fields.iter().map(|f| empty_column(&f.data_type)).collect();
+        (std::mem::replace(&mut self.columns, fresh), std::mem::take(&mut self.rows))
+    }
+}
+
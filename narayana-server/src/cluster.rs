@@ -0,0 +1,134 @@
+// Raft cluster plumbing
+// Carries Raft RPCs between nodes over their HTTP API and asks a running
+// cluster to add this node on startup.
+
+use async_trait::async_trait;
+use narayana_api::grpc::{JoinClusterRequest, JoinClusterResponse};
+use narayana_core::config::ClusterConfig;
+use narayana_core::{Error, Result};
+use narayana_storage::consensus::{
+    AppendEntriesRequest, AppendEntriesResponse, RaftTransport, RequestVoteRequest, RequestVoteResponse,
+};
+use parking_lot::RwLock;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Path nodes accept RequestVote RPCs on
+pub const REQUEST_VOTE_PATH: &str = "/api/v1/cluster/raft/request-vote";
+/// Path nodes accept AppendEntries RPCs on
+pub const APPEND_ENTRIES_PATH: &str = "/api/v1/cluster/raft/append-entries";
+/// Path the leader accepts join requests on
+pub const JOIN_PATH: &str = "/api/v1/cluster/join";
+
+/// POSTs Raft RPCs to `{address}/api/v1/cluster/raft/...`. Addresses come
+/// from `cluster.peers` and from the addresses joining nodes register.
+pub struct HttpRaftTransport {
+    client: reqwest::Client,
+    token: Option<String>,
+    peers: RwLock<HashMap<String, String>>,
+}
+
+impl HttpRaftTransport {
+    pub fn new(config: &ClusterConfig) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(config.request_timeout)
+            .build()
+            .map_err(|e| Error::Storage(format!("Failed to build cluster client: {}", e)))?;
+        let peers = config.peers.iter().map(|peer| (peer.node_id.clone(), peer.url.clone())).collect();
+        Ok(Self { client, token: config.token.clone(), peers: RwLock::new(peers) })
+    }
+
+    async fn post<Req: Serialize, Resp: DeserializeOwned>(&self, target: &str, path: &str, body: &Req) -> Result<Resp> {
+        let address = self.peers.read().get(target).cloned()
+            .ok_or_else(|| Error::Storage(format!("No address known for raft peer {}", target)))?;
+        post_json(&self.client, &address, path, self.token.as_deref(), body)
+            .await
+            .map_err(|e| Error::Storage(format!("Raft peer {}: {}", target, e)))
+    }
+}
+
+#[async_trait]
+impl RaftTransport for HttpRaftTransport {
+    async fn request_vote(&self, target: &str, request: RequestVoteRequest) -> Result<RequestVoteResponse> {
+        self.post(target, REQUEST_VOTE_PATH, &request).await
+    }
+
+    async fn append_entries(&self, target: &str, request: AppendEntriesRequest) -> Result<AppendEntriesResponse> {
+        self.post(target, APPEND_ENTRIES_PATH, &request).await
+    }
+
+    fn add_peer(&self, node_id: &str, address: &str) {
+        self.peers.write().insert(node_id.to_string(), address.to_string());
+    }
+
+    fn peer_address(&self, node_id: &str) -> Option<String> {
+        self.peers.read().get(node_id).cloned()
+    }
+}
+
+/// Ask the node at `cluster.join` to add this node. Only the leader
+/// accepts; other nodes answer with the leader's address, which the next
+/// request goes to. Gives up after `cluster.join_attempts` requests.
+pub async fn join_cluster(config: &ClusterConfig, node_id: String) -> Result<()> {
+    let (Some(mut url), Some(address)) = (config.join.clone(), config.advertise_url.clone()) else {
+        return Err(Error::Storage("cluster.join and cluster.advertise_url must both be set".to_string()));
+    };
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .map_err(|e| Error::Storage(format!("Failed to build cluster client: {}", e)))?;
+    let request = JoinClusterRequest { node_id, address };
+    let mut last_error = String::new();
+    for attempt in 1..=config.join_attempts {
+        match post_json::<_, JoinClusterResponse>(&client, &url, JOIN_PATH, config.token.as_deref(), &request).await {
+            Ok(response) if response.accepted => {
+                info!("Joined the cluster through {}", url);
+                return Ok(());
+            }
+            Ok(response) => match response.leader_address {
+                // Straight on to the leader, no need to wait
+                Some(leader) if leader != url => {
+                    info!("{} is not the cluster leader; asking {} ({:?})", url, leader, response.leader_id);
+                    url = leader;
+                    continue;
+                }
+                _ => {
+                    last_error = format!("{} is not the cluster leader and knows no leader address (leader: {:?})", url, response.leader_id);
+                }
+            },
+            Err(e) => last_error = format!("joining through {} failed: {}", url, e),
+        }
+        warn!("Join attempt {}/{}: {}", attempt, config.join_attempts, last_error);
+        // Start over from the configured node, which may know a newer leader
+        url = config.join.clone().unwrap_or(url);
+        tokio::time::sleep(config.join_retry_interval).await;
+    }
+    Err(Error::Storage(format!(
+        "Gave up joining the cluster after {} attempts: {}",
+        config.join_attempts, last_error
+    )))
+}
+
+async fn post_json<Req: Serialize, Resp: DeserializeOwned>(
+    client: &reqwest::Client,
+    base_url: &str,
+    path: &str,
+    token: Option<&str>,
+    body: &Req,
+) -> std::result::Result<Resp, String> {
+    let url = format!("{}{}", base_url.trim_end_matches('/'), path);
+    let mut request = client.post(&url).json(body);
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    let response = request.send().await.map_err(|e| format!("request failed: {}", e))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("answered {}: {}", status, body));
+    }
+    response.json::<Resp>().await.map_err(|e| format!("invalid response: {}", e))
+}
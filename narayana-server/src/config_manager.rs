@@ -50,6 +50,11 @@ impl ConfigManager {
         watchers.push(watcher);
     }
 
+    /// Register a watcher from async code (`watch` blocks and cannot run inside the runtime)
+    pub async fn subscribe(&self, watcher: ConfigWatcherCallback) {
+        self.watchers.write().await.push(watcher);
+    }
+
     async fn notify_watchers(&self, config: &NarayanaConfig) {
         let watchers = self.watchers.read().await;
         for watcher in watchers.iter() {
@@ -57,9 +62,9 @@ impl ConfigManager {
         }
     }
 
-    /// Reload configuration from file
+    /// Reload configuration from file (environment overrides are re-applied)
     pub async fn reload_from_file(&self, path: &str) -> Result<(), String> {
-        let new_config = NarayanaConfig::from_file(path)
+        let new_config = NarayanaConfig::load(Some(path))
             .map_err(|e| e.to_string())?;
         self.update(new_config).await
     }
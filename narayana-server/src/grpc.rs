@@ -0,0 +1,55 @@
+// gRPC endpoint
+// Serves narayana.brain.v1.BrainService (narayana-api/proto/brain.proto) on
+// network.grpc.port. Calls authenticate with the same bearer tokens as the
+// HTTP API, sent as `authorization: Bearer <token>` metadata.
+
+use crate::security::TokenManager;
+use narayana_api::grpc_advanced::brain_proto::brain_service_server::BrainServiceServer;
+use narayana_api::grpc_advanced::BrainGrpcService;
+use narayana_storage::cognitive::CognitiveBrain;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tonic::service::interceptor::InterceptedService;
+use tonic::{Request, Status};
+use tracing::info;
+
+/// Reject calls without a valid bearer token; the token's claims are put in
+/// the request extensions
+pub fn authenticate(token_manager: Arc<TokenManager>) -> impl Fn(Request<()>) -> Result<Request<()>, Status> + Clone {
+    move |mut request: Request<()>| {
+        let header = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .ok_or_else(|| Status::unauthenticated("Missing authorization metadata"))?;
+        let token = header
+            .get(..7)
+            .filter(|prefix| prefix.eq_ignore_ascii_case("bearer "))
+            .map(|_| header[7..].trim())
+            .filter(|token| !token.is_empty())
+            .ok_or_else(|| Status::unauthenticated("Expected a bearer token"))?;
+        let claims = token_manager
+            .verify_token(token)
+            .map_err(|_| Status::unauthenticated("Invalid authentication token"))?;
+        request.extensions_mut().insert(claims);
+        Ok(request)
+    }
+}
+
+/// The brain service behind `authenticate`
+pub fn brain_service(
+    brain: Arc<CognitiveBrain>,
+    token_manager: Arc<TokenManager>,
+) -> InterceptedService<BrainServiceServer<BrainGrpcService>, impl Fn(Request<()>) -> Result<Request<()>, Status> + Clone> {
+    InterceptedService::new(BrainGrpcService::new(brain).into_server(), authenticate(token_manager))
+}
+
+/// Serve the brain service until the listener fails
+pub async fn serve(addr: SocketAddr, brain: Arc<CognitiveBrain>, token_manager: Arc<TokenManager>) -> anyhow::Result<()> {
+    info!("✅ gRPC server listening on {}", addr);
+    tonic::transport::Server::builder()
+        .add_service(brain_service(brain, token_manager))
+        .serve(addr)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,540 @@
+// Liveness and readiness checks
+// Liveness only says the process is serving; readiness runs per-subsystem
+// checks and fails when any check configured as critical is down.
+
+use async_trait::async_trait;
+use narayana_core::config::{HealthChecksConfig, NarayanaConfig};
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::config_manager::ConfigManager;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    Up,
+    Degraded,
+    Down,
+}
+
+/// Result of a single check
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckOutcome {
+    pub status: HealthStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "serde_json::Value::is_null")]
+    pub details: serde_json::Value,
+}
+
+impl CheckOutcome {
+    pub fn up() -> Self {
+        Self { status: HealthStatus::Up, message: None, details: serde_json::Value::Null }
+    }
+
+    pub fn degraded(message: impl Into<String>) -> Self {
+        Self { status: HealthStatus::Degraded, message: Some(message.into()), details: serde_json::Value::Null }
+    }
+
+    pub fn down(message: impl Into<String>) -> Self {
+        Self { status: HealthStatus::Down, message: Some(message.into()), details: serde_json::Value::Null }
+    }
+
+    pub fn with_message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+
+    pub fn with_details(mut self, details: serde_json::Value) -> Self {
+        self.details = details;
+        self
+    }
+}
+
+/// A readiness check for one subsystem
+#[async_trait]
+pub trait HealthCheck: Send + Sync {
+    /// Name used in reports and in `monitoring.health.critical` / `disabled`
+    fn name(&self) -> &str;
+
+    async fn check(&self) -> CheckOutcome;
+
+    /// Whether results may be reused for `cache_ttl_secs` (slow or external checks)
+    fn cacheable(&self) -> bool {
+        false
+    }
+}
+
+/// One check in a readiness report
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckReport {
+    pub name: String,
+    pub critical: bool,
+    #[serde(flatten)]
+    pub outcome: CheckOutcome,
+    pub duration_ms: u64,
+    pub cached: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReadinessReport {
+    pub status: HealthStatus,
+    pub ready: bool,
+    pub version: String,
+    pub uptime_secs: u64,
+    pub checks: Vec<CheckReport>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LivenessReport {
+    pub status: HealthStatus,
+    pub version: String,
+    pub uptime_secs: u64,
+}
+
+/// Registered checks plus the criticality settings from the runtime config
+pub struct HealthRegistry {
+    config: RwLock<HealthChecksConfig>,
+    checks: RwLock<Vec<Arc<dyn HealthCheck>>>,
+    cache: Mutex<HashMap<String, (Instant, CheckOutcome)>>,
+    started: Instant,
+}
+
+impl HealthRegistry {
+    pub fn new(config: &HealthChecksConfig) -> Self {
+        Self {
+            config: RwLock::new(config.clone()),
+            checks: RwLock::new(Vec::new()),
+            cache: Mutex::new(HashMap::new()),
+            started: Instant::now(),
+        }
+    }
+
+    pub fn update(&self, config: &HealthChecksConfig) {
+        *self.config.write() = config.clone();
+        self.cache.lock().clear();
+    }
+
+    /// Reload `[monitoring.health]` on config changes, dropping cached results
+    pub async fn watch(self: &Arc<Self>, manager: &ConfigManager) {
+        let registry = self.clone();
+        manager.subscribe(Box::new(move |config: &NarayanaConfig| {
+            registry.update(&config.monitoring.health);
+        })).await;
+    }
+
+    /// Add a check; a check with the same name replaces the earlier one
+    pub fn register(&self, check: Arc<dyn HealthCheck>) {
+        let mut checks = self.checks.write();
+        checks.retain(|c| c.name() != check.name());
+        checks.push(check);
+    }
+
+    /// Names of the registered checks, in registration order
+    pub fn check_names(&self) -> Vec<String> {
+        self.checks.read().iter().map(|c| c.name().to_string()).collect()
+    }
+
+    fn uptime_secs(&self) -> u64 {
+        self.started.elapsed().as_secs()
+    }
+
+    /// The process is up and serving requests; never runs subsystem checks
+    pub fn liveness(&self) -> LivenessReport {
+        LivenessReport {
+            status: HealthStatus::Up,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            uptime_secs: self.uptime_secs(),
+        }
+    }
+
+    /// Run every enabled check concurrently. Not ready when a critical check
+    /// is down; degraded when a non-critical check is down or any is degraded.
+    pub async fn readiness(&self) -> ReadinessReport {
+        let config = self.config.read().clone();
+        let checks: Vec<Arc<dyn HealthCheck>> = self.checks.read().iter()
+            .filter(|c| !config.disabled.iter().any(|d| d == c.name()))
+            .cloned()
+            .collect();
+
+        let timeout = Duration::from_millis(config.check_timeout_ms.max(1));
+        let ttl = Duration::from_secs(config.cache_ttl_secs);
+        let reports = futures::future::join_all(checks.iter().map(|check| self.run_check(check, timeout, ttl))).await;
+
+        let mut status = HealthStatus::Up;
+        let mut ready = true;
+        let reports: Vec<CheckReport> = reports.into_iter().map(|mut report| {
+            report.critical = config.critical.iter().any(|c| c == &report.name);
+            match (report.outcome.status, report.critical) {
+                (HealthStatus::Down, true) => {
+                    ready = false;
+                    status = HealthStatus::Down;
+                }
+                (HealthStatus::Down, false) | (HealthStatus::Degraded, _) => {
+                    if status == HealthStatus::Up {
+                        status = HealthStatus::Degraded;
+                    }
+                }
+                (HealthStatus::Up, _) => {}
+            }
+            report
+        }).collect();
+
+        ReadinessReport {
+            status,
+            ready,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            uptime_secs: self.uptime_secs(),
+            checks: reports,
+        }
+    }
+
+    async fn run_check(&self, check: &Arc<dyn HealthCheck>, timeout: Duration, ttl: Duration) -> CheckReport {
+        let name = check.name().to_string();
+        if check.cacheable() {
+            if let Some((at, outcome)) = self.cache.lock().get(&name) {
+                if at.elapsed() < ttl {
+                    return CheckReport { name, critical: false, outcome: outcome.clone(), duration_ms: 0, cached: true };
+                }
+            }
+        }
+
+        let started = Instant::now();
+        let outcome = match tokio::time::timeout(timeout, check.check()).await {
+            Ok(outcome) => outcome,
+            Err(_) => CheckOutcome::down(format!("check timed out after {}ms", timeout.as_millis())),
+        };
+        let duration_ms = started.elapsed().as_millis() as u64;
+        if check.cacheable() {
+            self.cache.lock().insert(name.clone(), (Instant::now(), outcome.clone()));
+        }
+        CheckReport { name, critical: false, outcome, duration_ms, cached: false }
+    }
+}
+
+/// Startup recovery phases, in order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StartupPhase {
+    Starting,
+    LoadingTables,
+    LoadingSchema,
+    Ready,
+}
+
+impl StartupPhase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StartupPhase::Starting => "starting",
+            StartupPhase::LoadingTables => "loading_tables",
+            StartupPhase::LoadingSchema => "loading_schema",
+            StartupPhase::Ready => "ready",
+        }
+    }
+}
+
+/// Snapshot of startup recovery, reported by the readiness check
+#[derive(Debug, Clone, Serialize)]
+pub struct RecoveryProgress {
+    pub phase: StartupPhase,
+    pub tables_total: usize,
+    pub tables_loaded: usize,
+    pub tables_failed: usize,
+    /// Time since startup, or total recovery time once ready
+    pub elapsed_ms: u64,
+    /// Whether API requests are served (loaded tables only) before recovery ends
+    pub serving: bool,
+}
+
+struct RecoveryState {
+    phase: StartupPhase,
+    phase_started: Instant,
+    tables_total: usize,
+    tables_loaded: usize,
+    tables_failed: usize,
+    finished_after: Option<Duration>,
+    message: Option<String>,
+}
+
+/// Tracks startup recovery (table load, WAL replay, schema load). Readiness is
+/// down until it completes, or degraded while loading when serving is allowed.
+pub struct RecoveryGate {
+    started: Instant,
+    serve_while_loading: AtomicBool,
+    complete: AtomicBool,
+    state: RwLock<RecoveryState>,
+}
+
+impl Default for RecoveryGate {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RecoveryGate {
+    pub fn new() -> Self {
+        let now = Instant::now();
+        Self {
+            started: now,
+            serve_while_loading: AtomicBool::new(false),
+            complete: AtomicBool::new(false),
+            state: RwLock::new(RecoveryState {
+                phase: StartupPhase::Starting,
+                phase_started: now,
+                tables_total: 0,
+                tables_loaded: 0,
+                tables_failed: 0,
+                finished_after: None,
+                message: None,
+            }),
+        }
+    }
+
+    /// Serve already-loaded tables while the rest are still loading
+    pub fn set_serve_while_loading(&self, serve: bool) {
+        self.serve_while_loading.store(serve, Ordering::SeqCst);
+    }
+
+    /// Move to the next phase, recording how long the previous one took
+    pub fn enter_phase(&self, phase: StartupPhase) {
+        let mut state = self.state.write();
+        if state.phase == phase {
+            return;
+        }
+        let took = state.phase_started.elapsed();
+        metrics::gauge!("narayana_recovery_phase_duration_seconds", "phase" => state.phase.as_str())
+            .set(took.as_secs_f64());
+        tracing::info!("Startup phase {} finished in {:?}", state.phase.as_str(), took);
+        state.phase = phase;
+        state.phase_started = Instant::now();
+    }
+
+    pub fn set_table_progress(&self, loaded: usize, failed: usize, total: usize) {
+        let mut state = self.state.write();
+        state.tables_loaded = loaded;
+        state.tables_failed = failed;
+        state.tables_total = total;
+        metrics::gauge!("narayana_recovery_tables_loaded").set(loaded as f64);
+        metrics::gauge!("narayana_recovery_tables_total").set(total as f64);
+    }
+
+    /// Record that recovery finished, optionally with a note (e.g. skipped tables)
+    pub fn mark_complete(&self, message: Option<String>) {
+        self.enter_phase(StartupPhase::Ready);
+        let took = self.started.elapsed();
+        {
+            let mut state = self.state.write();
+            state.finished_after = Some(took);
+            state.message = message;
+        }
+        metrics::gauge!("narayana_recovery_duration_seconds").set(took.as_secs_f64());
+        self.complete.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.complete.load(Ordering::SeqCst)
+    }
+
+    /// Whether API traffic should be served now
+    pub fn is_serving(&self) -> bool {
+        self.is_complete() || self.serve_while_loading.load(Ordering::SeqCst)
+    }
+
+    pub fn progress(&self) -> RecoveryProgress {
+        let state = self.state.read();
+        RecoveryProgress {
+            phase: state.phase,
+            tables_total: state.tables_total,
+            tables_loaded: state.tables_loaded,
+            tables_failed: state.tables_failed,
+            elapsed_ms: state.finished_after.unwrap_or_else(|| self.started.elapsed()).as_millis() as u64,
+            serving: self.is_serving(),
+        }
+    }
+}
+
+#[async_trait]
+impl HealthCheck for RecoveryGate {
+    fn name(&self) -> &str {
+        "wal_replay"
+    }
+
+    async fn check(&self) -> CheckOutcome {
+        let progress = self.progress();
+        let details = serde_json::to_value(&progress).unwrap_or_default();
+        if self.is_complete() {
+            let outcome = CheckOutcome::up().with_details(details);
+            return match self.state.read().message.clone() {
+                Some(message) => outcome.with_message(message),
+                None => outcome,
+            };
+        }
+        let message = format!(
+            "startup recovery in progress ({}, {}/{} tables loaded)",
+            progress.phase.as_str(), progress.tables_loaded, progress.tables_total
+        );
+        let outcome = if progress.serving {
+            CheckOutcome::degraded(message)
+        } else {
+            CheckOutcome::down(message)
+        };
+        outcome.with_details(details)
+    }
+}
+
+/// Storage engine responds to catalog and schema lookups
+pub struct StorageCheck {
+    pub storage: Arc<dyn narayana_storage::ColumnStore>,
+    pub db_manager: Arc<narayana_storage::database_manager::DatabaseManager>,
+}
+
+#[async_trait]
+impl HealthCheck for StorageCheck {
+    fn name(&self) -> &str {
+        "storage"
+    }
+
+    async fn check(&self) -> CheckOutcome {
+        let Some(database) = self.db_manager.get_database_by_name("default") else {
+            return CheckOutcome::down("default database is missing");
+        };
+        let tables = match self.db_manager.list_tables(database) {
+            Ok(tables) => tables,
+            Err(e) => return CheckOutcome::down(format!("failed to list tables: {}", e)),
+        };
+        if let Some(table) = tables.first() {
+            if let Err(e) = self.storage.get_schema(table.table_id).await {
+                return CheckOutcome::down(format!("failed to read schema of '{}': {}", table.name, e));
+            }
+        }
+        CheckOutcome::up().with_details(serde_json::json!({ "tables": tables.len() }))
+    }
+}
+
+/// Persistence layer accepts a write and returns it on read
+pub struct PersistenceCheck {
+    pub persistence: Arc<narayana_storage::persistence::PersistenceManager>,
+}
+
+#[async_trait]
+impl HealthCheck for PersistenceCheck {
+    fn name(&self) -> &str {
+        "persistence"
+    }
+
+    async fn check(&self) -> CheckOutcome {
+        const PROBE_KEY: &str = "__narayana_health_probe__";
+        let payload = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos()
+            .to_le_bytes();
+        if let Err(e) = self.persistence.write(PROBE_KEY, &payload).await {
+            return CheckOutcome::down(format!("probe write failed: {}", e));
+        }
+        let outcome = match self.persistence.read(PROBE_KEY).await {
+            Ok(Some(data)) if data == payload => CheckOutcome::up(),
+            Ok(_) => CheckOutcome::down("probe read returned different data"),
+            Err(e) => CheckOutcome::down(format!("probe read failed: {}", e)),
+        };
+        let _ = self.persistence.delete(PROBE_KEY).await;
+        outcome
+    }
+}
+
+/// Configured LLM providers accept TCP connections. Only the connection is
+/// attempted, so no tokens are spent; results are cached.
+pub struct LlmCheck {
+    pub llm: Arc<narayana_llm::LLMManager>,
+    pub connect_timeout: Duration,
+}
+
+#[async_trait]
+impl HealthCheck for LlmCheck {
+    fn name(&self) -> &str {
+        "llm"
+    }
+
+    fn cacheable(&self) -> bool {
+        true
+    }
+
+    async fn check(&self) -> CheckOutcome {
+        use narayana_core::egress::{self, EgressSubsystem};
+
+        let endpoints = self.llm.provider_endpoints();
+        if endpoints.is_empty() {
+            return CheckOutcome::up().with_message("no providers configured");
+        }
+
+        let probes = endpoints.iter().map(|(provider, url)| async move {
+            let result = match egress::check_url(EgressSubsystem::Llm, url) {
+                Err(e) => Err(e.to_string()),
+                Ok(target) => {
+                    let connect = tokio::net::TcpStream::connect((target.host.as_str(), target.port));
+                    match tokio::time::timeout(self.connect_timeout, connect).await {
+                        Ok(Ok(_)) => Ok(()),
+                        Ok(Err(e)) => Err(e.to_string()),
+                        Err(_) => Err("connect timed out".to_string()),
+                    }
+                }
+            };
+            (provider.as_str(), result)
+        });
+        let results = futures::future::join_all(probes).await;
+
+        let reachable = results.iter().filter(|(_, r)| r.is_ok()).count();
+        let details: serde_json::Map<String, serde_json::Value> = results.iter()
+            .map(|(provider, result)| {
+                let value = match result {
+                    Ok(()) => serde_json::json!({ "reachable": true }),
+                    Err(e) => serde_json::json!({ "reachable": false, "error": e }),
+                };
+                (provider.to_string(), value)
+            })
+            .collect();
+        let outcome = if reachable == results.len() {
+            CheckOutcome::up()
+        } else if reachable > 0 {
+            CheckOutcome::degraded(format!("{} of {} providers unreachable", results.len() - reachable, results.len()))
+        } else {
+            CheckOutcome::down("no configured provider is reachable")
+        };
+        outcome.with_details(serde_json::Value::Object(details))
+    }
+}
+
+/// WebSocket event bridge tasks are still running
+pub struct WebSocketCheck {
+    pub ws_state: Arc<crate::websocket::WebSocketState>,
+}
+
+#[async_trait]
+impl HealthCheck for WebSocketCheck {
+    fn name(&self) -> &str {
+        "websocket"
+    }
+
+    async fn check(&self) -> CheckOutcome {
+        let (running, total) = self.ws_state.bridge.task_status();
+        let details = serde_json::json!({
+            "bridge_tasks_running": running,
+            "bridge_tasks_total": total,
+            "connections": self.ws_state.manager.connection_count(),
+        });
+        let outcome = if total == 0 {
+            CheckOutcome::down("event bridge was not started")
+        } else if running == total {
+            CheckOutcome::up()
+        } else if running > 0 {
+            CheckOutcome::degraded(format!("{} of {} bridge tasks stopped", total - running, total))
+        } else {
+            CheckOutcome::down("all bridge tasks stopped")
+        };
+        outcome.with_details(details)
+    }
+}
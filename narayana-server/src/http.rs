@@ -3,14 +3,15 @@
 use axum::{
     body::Body,
     extract::{Path, Query, State, Request},
-    http::{Response, StatusCode, Uri, HeaderMap},
+    http::{Method, Response, StatusCode, Uri, HeaderMap},
     middleware::Next,
     response::{IntoResponse, Json},
-    routing::{delete, get, post, MethodRouter},
+    routing::{delete, get, post, put, MethodRouter},
     Router,
 };
 use narayana_storage::{
     ColumnStore,
+    column_store::WriteLocked,
     database_manager::DatabaseManager,
     human_search::HumanSearchEngine,
     webhooks::WebhookManager,
@@ -18,14 +19,15 @@ use narayana_storage::{
     cognitive::{CognitiveBrain, MemoryType, ThoughtState, CognitiveEventWithTimestamp, Conflict, MemoryAccessRecord},
     vector_search::{VectorStore, VectorIndex, Embedding, IndexType, SearchResult},
 };
-use narayana_core::{schema::Schema, types::TableId, column::Column};
+use narayana_core::{schema::{DataType, Schema}, types::TableId, column::Column};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tracing::{info, error, warn};
 
 // Protected system table name - cannot be accessed via normal API
-const PROTECTED_USERS_TABLE: &str = "narayana_ui_users";
+pub const PROTECTED_USERS_TABLE: &str = "narayana_ui_users";
 
 /// Rate limiting middleware for auth endpoints
 async fn auth_rate_limit_middleware(
@@ -33,18 +35,12 @@ async fn auth_rate_limit_middleware(
     request: Request,
     next: Next,
 ) -> Result<Response<Body>, StatusCode> {
-    // Get client IP for rate limiting
+    // Client IP resolved by ip_guard_middleware (trusted-proxy aware)
     let client_ip = request
-        .headers()
-        .get("x-forwarded-for")
-        .or_else(|| request.headers().get("x-real-ip"))
-        .and_then(|h| h.to_str().ok())
-        .unwrap_or("unknown")
-        .split(',')
-        .next()
-        .unwrap_or("unknown")
-        .trim()
-        .to_string();
+        .extensions()
+        .get::<crate::ip_guard::ClientIp>()
+        .map(|ip| ip.0.to_string())
+        .unwrap_or_else(|| "unknown".to_string());
     
     // DEVELOPMENT: Skip rate limiting for localhost/127.0.0.1 to allow robot demo
     let is_localhost = client_ip == "127.0.0.1" 
@@ -82,16 +78,10 @@ async fn api_rate_limit_middleware(
         warn!("API rate limit: No claims found (auth middleware missing?)");
         // Fallback to IP-based rate limiting if no user (not ideal but safe)
         request
-            .headers()
-            .get("x-forwarded-for")
-            .or_else(|| request.headers().get("x-real-ip"))
-            .and_then(|h| h.to_str().ok())
-            .unwrap_or("unknown")
-            .split(',')
-            .next()
-            .unwrap_or("unknown")
-            .trim()
-            .to_string()
+            .extensions()
+            .get::<crate::ip_guard::ClientIp>()
+            .map(|ip| ip.0.to_string())
+            .unwrap_or_else(|| "unknown".to_string())
     };
 
     // Rate limit using api_rate_limiter
@@ -114,6 +104,147 @@ async fn api_rate_limit_middleware(
     Ok(next.run(request).await)
 }
 
+/// Idempotency-Key handling for mutating requests, scoped to the authenticated caller
+async fn idempotency_middleware(
+    State(state): State<ApiState>,
+    request: Request,
+    next: Next,
+) -> axum::response::Response {
+    let scope = if let Some(claims) = request.extensions().get::<crate::security::Claims>() {
+        format!("user:{}", claims.sub)
+    } else if let Some(ip) = request.extensions().get::<crate::ip_guard::ClientIp>() {
+        format!("ip:{}", ip.0)
+    } else {
+        "anonymous".to_string()
+    };
+    state.idempotency.apply(&scope, request, next).await
+}
+
+/// Startup recovery guard - while tables are still loading only tables that
+/// are already loaded are served; everything else gets 503 with Retry-After
+async fn recovery_middleware(
+    State(state): State<ApiState>,
+    request: Request,
+    next: Next,
+) -> axum::response::Response {
+    if state.recovery.is_complete() {
+        return next.run(request).await;
+    }
+    let unavailable = |error: &str, code: &str| {
+        let response = Json(ErrorResponse {
+            error: error.to_string(),
+            code: code.to_string(),
+        });
+        let mut response = (StatusCode::SERVICE_UNAVAILABLE, response).into_response();
+        response.headers_mut().insert(axum::http::header::RETRY_AFTER, RECOVERY_RETRY_AFTER_SECS.into());
+        response
+    };
+    // The user catalog is only complete once the schema has loaded, so setup
+    // must not run against a partial one
+    if !state.recovery.is_serving() || request.uri().path().starts_with("/api/v1/auth/") {
+        return unavailable("Server is recovering. Please try again shortly.", "RECOVERY_IN_PROGRESS");
+    }
+
+    if let Some(rest) = request.uri().path().strip_prefix("/api/v1/tables") {
+        match rest.trim_start_matches('/').split('/').next().unwrap_or("") {
+            // Table ids are not settled until every table has loaded
+            "" if request.method() == axum::http::Method::POST => {
+                return unavailable("Tables cannot be created during startup recovery", "RECOVERY_IN_PROGRESS");
+            }
+            id => {
+                if let Ok(id) = id.parse::<u64>() {
+                    if state.storage.get_schema(TableId(id)).await.is_err() {
+                        return unavailable("Table is still loading. Please try again shortly.", "TABLE_LOADING");
+                    }
+                }
+            }
+        }
+    }
+    next.run(request).await
+}
+
+/// Per-IP guard - resolves the client address, enforces admin allow/deny lists,
+/// sliding-window limits and greylisting, and records auth failures
+async fn ip_guard_middleware(
+    State(state): State<ApiState>,
+    mut request: Request,
+    next: Next,
+) -> axum::response::Response {
+    let peer = request
+        .extensions()
+        .get::<axum::extract::ConnectInfo<std::net::SocketAddr>>()
+        .map(|info| info.0.ip());
+    let forwarded_for = request
+        .headers()
+        .get("x-forwarded-for")
+        .and_then(|h| h.to_str().ok());
+    let Some(ip) = state.ip_guard.client_ip(peer, forwarded_for) else {
+        return next.run(request).await;
+    };
+    request.extensions_mut().insert(crate::ip_guard::ClientIp(ip));
+
+    let path = request.uri().path().to_string();
+    let is_auth_attempt = request.method() == axum::http::Method::POST && path.starts_with("/api/v1/auth/");
+    match state.ip_guard.check(ip, &path, is_auth_attempt) {
+        crate::ip_guard::IpDecision::Allow => {}
+        crate::ip_guard::IpDecision::Forbidden => {
+            let response = Json(ErrorResponse {
+                error: "Access to this endpoint is not allowed from your address".to_string(),
+                code: "IP_FORBIDDEN".to_string(),
+            });
+            return (StatusCode::FORBIDDEN, response).into_response();
+        }
+        crate::ip_guard::IpDecision::RateLimited { retry_after_secs }
+        | crate::ip_guard::IpDecision::Greylisted { retry_after_secs } => {
+            let response = Json(ErrorResponse {
+                error: "Too many requests. Please try again later.".to_string(),
+                code: "RATE_LIMIT_EXCEEDED".to_string(),
+            });
+            let mut response = (StatusCode::TOO_MANY_REQUESTS, response).into_response();
+            response.headers_mut().insert(axum::http::header::RETRY_AFTER, retry_after_secs.into());
+            return response;
+        }
+    }
+
+    let response = next.run(request).await;
+    if response.status() == StatusCode::UNAUTHORIZED {
+        state.ip_guard.record_auth_failure(ip);
+    } else if is_auth_attempt && response.status().is_success() {
+        state.ip_guard.record_auth_success(ip);
+    }
+    response
+}
+
+/// Per-route admission control - holds a concurrency slot for the request's
+/// route group while it runs; saturated groups answer 429 with Retry-After
+async fn admission_middleware(
+    State(state): State<ApiState>,
+    request: Request,
+    next: Next,
+) -> axum::response::Response {
+    match state.admission.admit(request.uri().path()).await {
+        Ok(_admission) => next.run(request).await,
+        Err(rejection) => {
+            let response = Json(ErrorResponse {
+                error: "This endpoint is at capacity. Please try again later.".to_string(),
+                code: "ROUTE_SATURATED".to_string(),
+            });
+            let mut response = (StatusCode::TOO_MANY_REQUESTS, response).into_response();
+            response.headers_mut().insert(axum::http::header::RETRY_AFTER, rejection.retry_after_secs().into());
+            response
+        }
+    }
+}
+
+/// CORS and security headers - outermost layer so preflights never reach auth
+async fn http_headers_middleware(
+    State(state): State<ApiState>,
+    request: Request,
+    next: Next,
+) -> axum::response::Response {
+    state.http_headers.apply(request, next).await
+}
+
 /// Authentication middleware - validates JWT tokens
 async fn auth_middleware(
     State(state): State<ApiState>,
@@ -229,6 +360,35 @@ pub struct ApiState {
     pub api_rate_limiter: Arc<crate::security::RateLimiter>, // For API endpoints
     pub cpl_manager: Option<Arc<narayana_storage::cpl_manager::CPLManager>>, // CPL Manager
     pub vector_store: Arc<VectorStore>, // Vector search store
+    pub embeddings: Option<Arc<narayana_storage::embeddings::EmbeddingsManager>>, // Auto-embedding for "embed" columns
+    pub full_text: Arc<narayana_storage::full_text::FullTextIndexManager>, // Full-text indexes
+    pub spatial: Arc<narayana_storage::geospatial::SpatialIndexManager>, // Spatial indexes
+    pub change_feed: Arc<narayana_storage::cdc::ChangeFeed>, // CDC stream of committed writes
+    pub anomaly: Arc<narayana_storage::anomaly_detection::AnomalyDetectionManager>, // Anomaly detectors
+    pub features: Arc<narayana_storage::feature_store::FeatureStore>, // Feature store for RL/ML
+    pub blobs: Arc<narayana_storage::blob_store::BlobStore>, // Binary artifacts (audio, frames, assets, models)
+    pub http_headers: Arc<crate::http_headers::HttpHeadersPolicy>, // CORS + security headers (hot-reloaded)
+    pub ip_guard: Arc<crate::ip_guard::IpGuard>, // Per-IP limits, admin CIDR lists, greylisting
+    pub admission: Arc<crate::admission::AdmissionController>, // Per-route concurrency limits and queues
+    pub idempotency: Arc<crate::idempotency::IdempotencyStore>, // Idempotency-Key response replay
+    pub queries: Arc<narayana_query::cancellation::QueryRegistry>, // Running queries (list/cancel)
+    pub health: Arc<crate::health::HealthRegistry>, // Liveness/readiness checks
+    pub recovery: Arc<crate::health::RecoveryGate>, // Startup recovery progress (degraded mode)
+    pub static_files: Arc<crate::static_files::StaticFiles>, // Web UI hosting (router fallback)
+    pub skills: Arc<crate::skills::SkillManager>, // Installed skill packages
+    pub jobs: Arc<narayana_storage::jobs::JobScheduler>, // Scheduled maintenance jobs
+    pub connectors: Arc<narayana_storage::connectors::ConnectorManager>, // Ingest connectors (polled by jobs)
+    pub quotas: Arc<narayana_storage::quotas::QuotaManager>, // Storage quotas and low-disk read-only mode
+    pub invariants: Arc<narayana_storage::bug_detection::InvariantChecker>, // Runtime invariant checks
+    pub scaling: Arc<narayana_storage::predictive_scaling::WorkloadScaler>, // Forecast-driven resource sizing
+    pub shards: Arc<narayana_storage::auto_scaling::ShardScaler>, // Shard splits, merges and readers
+    pub rde: Arc<narayana_rde::RdeManager>, // Rapid data events (actor tokens)
+    pub replication: Arc<narayana_storage::replication::ReplicationManager>, // Cross-region multi-primary replication
+    pub gpu: Arc<narayana_storage::gpu_devices::MultiGpuEngine>, // GPU devices, placement and memory pools
+    pub nlq: Arc<crate::nlq::NaturalLanguageQuery>, // Natural-language questions answered via the LLM
+    pub predictor: Arc<narayana_query::ml_integration::PredictEngine>, // Registry models scored by PREDICT
+    pub autocomplete: Arc<narayana_query::autocomplete::AutocompleteManager>, // Query completions for consoles and editors
+    pub transactions: Arc<narayana_storage::transactions::TransactionRegistry>, // Open client transactions
 }
 
 // Statistics tracking
@@ -240,56 +400,67 @@ pub static TOTAL_ROWS_INSERTED: AtomicU64 = AtomicU64::new(0);
 pub static TOTAL_QUERY_TIME_MS: AtomicU64 = AtomicU64::new(0);
 
 // Response types
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct HealthResponse {
     pub status: String,
     pub version: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct TablesResponse {
     pub tables: Vec<TableInfo>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct TableInfo {
     pub id: u64,
     pub name: String,
+    #[schema(value_type = Option<Object>)]
     pub schema: Option<Schema>,
     pub row_count: Option<u64>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct CreateTableRequest {
     pub table_name: String,
+    #[schema(value_type = Object)]
     pub schema: Schema,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct CreateTableResponse {
     pub success: bool,
     pub table_id: u64,
     pub message: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct InsertRequest {
     pub columns: Vec<serde_json::Value>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct InsertResponse {
     pub success: bool,
     pub rows_inserted: usize,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct QueryResponse {
     pub columns: Vec<serde_json::Value>,
     pub row_count: usize,
-}
-
-#[derive(Debug, Serialize)]
+    /// Id the query ran under (see /api/v1/queries)
+    pub query_id: u64,
+    /// Rows as objects keyed by field name, shaped by the output profile
+    /// bound to the caller's token or role; `columns` is empty when set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rows: Option<serde_json::Value>,
+    /// Output profile applied to `rows`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_profile: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct StatsResponse {
     pub total_queries: u64,
     pub avg_duration_ms: f64,
@@ -297,7 +468,7 @@ pub struct StatsResponse {
     pub total_rows_inserted: u64,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ErrorResponse {
     pub error: String,
     pub code: String,
@@ -327,13 +498,13 @@ fn sanitize_error_message(internal_error: &str, error_code: &str) -> String {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct LoginRequest {
     pub username: String,
     pub password: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct LoginResponse {
     pub success: bool,
     pub token: String,
@@ -348,9 +519,16 @@ pub fn create_router(state: ApiState) -> Router {
     let public_routes = Router::new()
         // Health check
         .route("/health", get(health_handler))
+        // Kubernetes probes: liveness never touches subsystems, readiness does
+        .route("/health/live", get(liveness_handler))
+        .route("/health/ready", get(readiness_handler))
         // Metrics (Prometheus format)
         .route("/metrics", get(metrics_handler))
-        .route("/api/v1/health", get(health_handler));
+        .route("/api/v1/health", get(health_handler))
+        .route("/api/v1/health/live", get(liveness_handler))
+        .route("/api/v1/health/ready", get(readiness_handler))
+        // OpenAPI document and Swagger UI
+        .merge(crate::openapi::routes());
     
     // Auth routes - setup check is not rate limited (read-only, called frequently)
     // Only login and setup POST endpoints are rate limited
@@ -364,7 +542,8 @@ pub fn create_router(state: ApiState) -> Router {
         .layer(middleware::from_fn_with_state(state.clone(), auth_rate_limit_middleware));
     
     // Merge rate-limited and non-rate-limited auth routes
-    let auth_routes = setup_check_route.merge(rate_limited_auth_routes);
+    let auth_routes = setup_check_route.merge(rate_limited_auth_routes)
+        .layer(middleware::from_fn_with_state(state.clone(), recovery_middleware));
     
     // Protected routes (authentication required)
     let protected_routes = Router::new()
@@ -373,7 +552,117 @@ pub fn create_router(state: ApiState) -> Router {
         .route("/api/v1/tables", get(get_tables_handler).post(create_table_handler))
         .route("/api/v1/tables/:id", delete(delete_table_handler))
         .route("/api/v1/tables/:id/insert", post(insert_data_handler))
+        .route("/api/v1/tables/:id/bulk", post(bulk_insert_handler)
+            .layer(axum::extract::DefaultBodyLimit::max(BULK_MAX_BODY_BYTES)))
         .route("/api/v1/tables/:id/query", get(query_data_handler))
+        .route("/api/v1/tables/:id/arrow", get(table_arrow_handler))
+        // Running queries
+        .route("/api/v1/transactions", get(list_transactions_handler).post(begin_transaction_handler))
+        .route("/api/v1/transactions/:txn_id", get(get_transaction_handler))
+        .route("/api/v1/transactions/:txn_id/statements", post(execute_statement_handler))
+        .route("/api/v1/transactions/:txn_id/commit", post(commit_transaction_handler))
+        .route("/api/v1/transactions/:txn_id/rollback", post(rollback_transaction_handler))
+        .route("/api/v1/queries", get(list_queries_handler))
+        .route("/api/v1/queries/:query_id", get(get_query_handler).delete(cancel_query_handler))
+        .route("/api/v1/nlq", post(nlq_handler))
+        .route("/api/v1/autocomplete", post(autocomplete_handler))
+        .route("/api/v1/tables/:id/fulltext", post(create_fulltext_index_handler))
+        .route("/api/v1/tables/:id/search", post(fulltext_search_handler))
+        .route("/api/v1/tables/:id/spatial", post(create_spatial_index_handler))
+        .route("/api/v1/tables/:id/spatial/query", post(spatial_query_handler))
+        // Output profiles served per API token or role
+        .route("/api/v1/tables/:id/output-profiles", get(list_output_profiles_handler))
+        .route("/api/v1/tables/:id/output-profiles/:name", put(set_output_profile_handler).delete(remove_output_profile_handler))
+        .route("/api/v1/tables/:id/output-bindings/:kind/:consumer", put(bind_output_profile_handler).delete(unbind_output_profile_handler))
+        // Row-level security: row policies, column masks and tenant-scoped tokens
+        .route("/api/v1/tables/:id/row-policies", get(list_row_policies_handler))
+        .route("/api/v1/tables/:id/row-policies/:name", put(set_row_policy_handler).delete(remove_row_policy_handler))
+        .route("/api/v1/tables/:id/column-masks/:name", put(set_column_mask_handler).delete(remove_column_mask_handler))
+        .route("/api/v1/admin/tokens", post(issue_token_handler))
+        .route("/api/v1/compliance/tags", get(list_compliance_tags_handler))
+        .route("/api/v1/compliance/tables/:id", put(tag_table_handler).delete(untag_table_handler))
+        .route("/api/v1/compliance/vector-indexes/:name", put(tag_vector_index_handler).delete(untag_vector_index_handler))
+        .route("/api/v1/compliance/lineage", get(list_lineage_handler).post(record_lineage_handler))
+        .route("/api/v1/compliance/erasures", get(list_erasures_handler).post(erase_subject_handler))
+        // System-versioned tables
+        .route("/api/v1/tables/:id/versioning", get(get_versioning_handler).put(enable_versioning_handler).delete(disable_versioning_handler))
+        .route("/api/v1/tables/:id/versioning/delete", post(delete_versioned_rows_handler))
+        .route("/api/v1/tables/:id/versioning/purge", post(purge_history_handler))
+        .route("/api/v1/tables/:id/history", get(row_history_handler))
+        // Cross-region replication
+        .route("/api/v1/replication", get(replication_status_handler))
+        .route("/api/v1/replication/tables/:id", put(replicate_table_handler).delete(stop_replicating_table_handler))
+        .route("/api/v1/replication/changes", post(apply_replicated_changes_handler))
+        // Raft cluster membership and RPCs between nodes
+        .route("/api/v1/cluster", get(cluster_status_handler))
+        .route(crate::cluster::JOIN_PATH, post(join_cluster_handler))
+        .route("/api/v1/cluster/leave", post(leave_cluster_handler))
+        .route(crate::cluster::REQUEST_VOTE_PATH, post(raft_request_vote_handler))
+        .route(crate::cluster::APPEND_ENTRIES_PATH, post(raft_append_entries_handler))
+        // Anomaly detection
+        .route("/api/v1/anomaly/detectors", get(list_anomaly_detectors_handler).post(create_anomaly_detector_handler))
+        .route("/api/v1/anomaly/detectors/:id", get(get_anomaly_detector_handler).delete(delete_anomaly_detector_handler))
+        .route("/api/v1/anomaly/detectors/:id/reset", post(reset_anomaly_detector_handler))
+        .route("/api/v1/anomaly/events", get(get_anomaly_events_handler))
+        // Feature store
+        .route("/api/v1/features/views", get(list_feature_views_handler).post(create_feature_view_handler))
+        .route("/api/v1/features/views/:name", get(get_feature_view_handler).delete(delete_feature_view_handler))
+        .route("/api/v1/features/online", post(online_features_handler))
+        .route("/api/v1/features/historical", post(historical_features_handler))
+        // Ingest connectors
+        .route("/api/v1/connectors", get(list_connectors_handler).post(create_connector_handler))
+        .route("/api/v1/connectors/:name", get(get_connector_handler).delete(delete_connector_handler))
+        .route("/api/v1/connectors/:name/poll", post(poll_connector_handler))
+        .route("/api/v1/connectors/:name/pause", post(pause_connector_handler))
+        .route("/api/v1/connectors/:name/resume", post(resume_connector_handler))
+        .route("/api/v1/connectors/:name/reset", post(reset_connector_handler))
+        // Blob storage (uploads stream past the default body limit; BlobStore enforces its own)
+        .route("/api/v1/blobs", get(list_blobs_handler).post(upload_blob_handler)
+            .layer(axum::extract::DefaultBodyLimit::disable()))
+        .route("/api/v1/blobs/:id", get(download_blob_handler).delete(delete_blob_handler))
+        .route("/api/v1/blobs/:id/info", get(get_blob_info_handler))
+        // Skill packages (bundles carry worker code inline)
+        .route("/api/v1/skills", get(list_skills_handler).post(install_skill_handler)
+            .layer(axum::extract::DefaultBodyLimit::max(SKILL_BUNDLE_LIMIT)))
+        .route("/api/v1/skills/:name", get(get_skill_handler).put(upgrade_skill_handler).delete(uninstall_skill_handler)
+            .layer(axum::extract::DefaultBodyLimit::max(SKILL_BUNDLE_LIMIT)))
+        .route("/api/v1/skills/:name/history", get(skill_history_handler))
+        // Maintenance jobs
+        .route("/api/v1/admin/jobs", get(list_jobs_handler))
+        .route("/api/v1/admin/jobs/:name", get(get_job_handler))
+        .route("/api/v1/admin/jobs/:name/trigger", post(trigger_job_handler))
+        .route("/api/v1/admin/jobs/:name/pause", post(pause_job_handler))
+        .route("/api/v1/admin/jobs/:name/resume", post(resume_job_handler))
+        // Runtime invariant checks
+        .route("/api/v1/admin/invariants", get(list_invariant_violations_handler))
+        .route("/api/v1/admin/invariants/check", post(check_invariants_handler))
+        .route("/api/v1/admin/invariants/violations", delete(clear_invariant_violations_handler))
+        // Storage quotas
+        .route("/api/v1/admin/quotas", get(quota_status_handler))
+        .route("/api/v1/admin/quotas/check", post(check_quotas_handler))
+        // Query advisor recommendations
+        .route("/api/v1/admin/recommendations", get(list_recommendations_handler))
+        .route("/api/v1/admin/recommendations/:id", get(get_recommendation_handler))
+        .route("/api/v1/admin/recommendations/:id/apply", post(apply_recommendation_handler))
+        .route("/api/v1/admin/recommendations/:id/dismiss", post(dismiss_recommendation_handler))
+        .route("/api/v1/admin/scaling", get(scaling_status_handler))
+        .route("/api/v1/admin/shards", get(list_shards_handler))
+        .route("/api/v1/admin/shards/events", get(list_shard_events_handler))
+        .route("/api/v1/admin/shards/merge", post(merge_shards_handler))
+        .route("/api/v1/admin/shards/tables/:table_id", post(bootstrap_table_shard_handler))
+        .route("/api/v1/admin/shards/:id/load", put(report_shard_load_handler))
+        .route("/api/v1/admin/shards/:id/split", post(split_shard_handler))
+        .route("/api/v1/admin/shards/:id/readers", post(add_shard_reader_handler))
+        .route("/api/v1/admin/shards/:id/readers/:node_id", delete(retire_shard_reader_handler))
+        .route("/api/v1/admin/rde/actors/:id/tokens", get(list_actor_tokens_handler).post(issue_actor_token_handler))
+        .route("/api/v1/admin/rde/actors/:id/tokens/rotate", post(rotate_actor_tokens_handler))
+        .route("/api/v1/admin/rde/actors/:id/tokens/:token_id", delete(revoke_actor_token_handler))
+        .route("/api/v1/admin/rde/actors/:id/quota", get(get_actor_quota_handler).put(set_actor_quota_handler).delete(remove_actor_quota_handler))
+        .route("/api/v1/admin/rde/dead-letters", get(dead_letter_summary_handler))
+        .route("/api/v1/admin/rde/subscriptions/:id/dead-letters", get(list_dead_letters_handler))
+        .route("/api/v1/admin/rde/subscriptions/:id/dead-letters/redeliver", post(redeliver_dead_letters_handler))
+        .route("/api/v1/admin/rde/subscriptions/:id/dead-letters/:sequence", get(get_dead_letter_handler))
+        .route("/api/v1/rde/transformations/dry-run", post(dry_run_transformation_handler))
         // Cognitive Brain API (Robot endpoints)
         .route("/api/v1/brains", get(get_brains_handler).post(create_brain_handler))
         .route("/api/v1/brains/:brain_id/thoughts", post(create_thought_handler))
@@ -384,18 +673,22 @@ pub fn create_router(state: ApiState) -> Router {
         .route("/api/v1/brains/:brain_id/memory-accesses", get(get_memory_accesses_handler))
         .route("/api/v1/brains/:brain_id/thought-timeline", get(get_thought_timeline_handler))
         .route("/api/v1/brains/:brain_id/conflicts", get(get_conflicts_handler))
+        .route("/api/v1/brains/:brain_id/bundle", get(export_brain_bundle_handler).post(import_brain_bundle_handler))
+        .route("/api/v1/brains/:brain_id/narratives", get(get_narratives_handler))
         // CPL API
         .route("/api/v1/cpls", get(get_cpls_handler).post(create_cpl_handler))
         .route("/api/v1/cpls/:cpl_id/start", post(cpl_start_handler))
         .route("/api/v1/cpls/:cpl_id/stop", post(cpl_stop_handler))
-        .route("/api/v1/cpls/:cpl_id", get(get_cpl_handler))
-        // .route("/api/v1/cpls/:cpl_id/delete", post(delete_cpl_handler))  // TODO: Enable when needed
+        .route("/api/v1/cpls/:cpl_id/pause", post(cpl_pause_handler))
+        .route("/api/v1/cpls/:cpl_id/resume", post(cpl_resume_handler))
+        .route("/api/v1/cpls/:cpl_id", get(get_cpl_handler).delete(delete_cpl_handler))
         // Workers API
         .route("/api/v1/workers", get(get_workers_handler))
         // Webhooks API
         .route("/api/v1/webhooks", get(get_webhooks_handler).post(create_webhook_handler))
         .route("/api/v1/webhooks/:id", get(get_webhook_handler).delete(delete_webhook_handler))
         .route("/api/v1/webhooks/:id/deliveries", get(get_webhook_deliveries_handler))
+        .route("/api/v1/webhooks/:id/deliveries/:delivery_id/redeliver", post(redeliver_webhook_handler))
         .route("/api/v1/webhooks/:id/enable", post(enable_webhook_handler))
         .route("/api/v1/webhooks/:id/disable", post(disable_webhook_handler))
         // Vector Search API
@@ -416,10 +709,17 @@ pub fn create_router(state: ApiState) -> Router {
         .route("/api/v1/sync/status", get(sync_status_handler))
         // System stats
         .route("/api/v1/system/stats", get(get_system_stats_handler))
+        // Per-IP guard state (blocked counts, greylist)
+        .route("/api/v1/security/ip-guard", get(get_ip_guard_stats_handler))
+        // Per-route admission control (in flight, queue depth, rejections)
+        .route("/api/v1/system/admission", get(get_admission_stats_handler))
         // Schema and seeds management (public endpoints for CLI - no auth required)
         .route("/api/v1/schema/load", post(load_schema_handler))
         .route("/api/v1/schema/seeds", post(load_seeds_handler))
         .route("/api/v1/schema/spawn", post(spawn_schema_handler))
+        .layer(middleware::from_fn_with_state(state.clone(), admission_middleware))
+        .layer(middleware::from_fn_with_state(state.clone(), recovery_middleware))
+        .layer(middleware::from_fn_with_state(state.clone(), idempotency_middleware))
         .layer(middleware::from_fn_with_state(state.clone(), api_rate_limit_middleware))
         .layer(middleware::from_fn_with_state(state.clone(), auth_middleware));
     
@@ -446,10 +746,10 @@ pub fn create_router(state: ApiState) -> Router {
                 if let Some(ws_state) = api_state.ws_state {
                     // Manually implement the websocket handler logic
                     // Validate token if provided
-                    let user_id = if let Some(token) = &query.token {
-                        // Verify token and extract user_id
+                    let claims = if let Some(token) = &query.token {
+                        // Verify token and extract its claims
                         match ws_state.token_manager.verify_token(token) {
-                            Ok(claims) => Some(claims.sub),
+                            Ok(claims) => Some(claims),
                             Err(e) => {
                                 warn!("Invalid WebSocket token: {}", e);
                                 None
@@ -460,7 +760,9 @@ pub fn create_router(state: ApiState) -> Router {
                     };
                     
                     // Upgrade the connection
-                    ws.on_upgrade(move |socket| handle_socket(socket, ws_state, user_id))
+                    let resume = query.resume();
+                    ws.protocols(ws_state.manager.supported_protocols().iter().copied())
+                        .on_upgrade(move |socket| handle_socket(socket, ws_state, claims, resume))
                 } else {
                     // Return error if ws_state is not available
                     axum::response::Response::builder()
@@ -476,22 +778,42 @@ pub fn create_router(state: ApiState) -> Router {
     router
         // Static files (UI) - catch all
         .fallback(serve_static_handler)
+        .layer(middleware::from_fn_with_state(state.clone(), ip_guard_middleware))
+        .layer(middleware::from_fn_with_state(state.clone(), http_headers_middleware))
         .with_state(state)
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct SetupCheckResponse {
     setup_required: bool,
     message: String,
 }
 
 /// Redirect GET /auth/setup to /auth/setup/check
+#[utoipa::path(
+    get,
+    path = "/api/v1/auth/setup",
+    tag = "auth",
+    responses(
+        (status = 200, description = "Same as /api/v1/auth/setup/check", body = SetupCheckResponse),
+    ),
+    security(()),
+)]
 async fn redirect_to_setup_check_handler(State(state): State<ApiState>) -> impl IntoResponse {
     // If someone tries to GET /auth/setup, redirect them to /auth/setup/check
     check_setup_handler(State(state)).await
 }
 
 /// Check if setup is required (users table doesn't exist)
+#[utoipa::path(
+    get,
+    path = "/api/v1/auth/setup/check",
+    tag = "auth",
+    responses(
+        (status = 200, description = "Whether the first admin user still has to be created", body = SetupCheckResponse),
+    ),
+    security(()),
+)]
 async fn check_setup_handler(State(state): State<ApiState>) -> impl IntoResponse {
     // Check env vars first - if they're set, setup is not required
     let env_user = std::env::var("NARAYANA_ADMIN_USER").ok();
@@ -559,14 +881,14 @@ async fn check_setup_handler(State(state): State<ApiState>) -> impl IntoResponse
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 struct SetupRequest {
     name: String,
     username: String,
     password: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct SetupResponse {
     success: bool,
     message: String,
@@ -574,6 +896,20 @@ struct SetupResponse {
 }
 
 /// Create first admin user and users table
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/setup",
+    tag = "auth",
+    request_body = SetupRequest,
+    responses(
+        (status = 200, description = "Admin user created", body = SetupResponse),
+        (status = 400, description = "Invalid name, username or password", body = ErrorResponse),
+        (status = 403, description = "Setup already completed or disabled by environment", body = ErrorResponse),
+        (status = 409, description = "Username already exists", body = ErrorResponse),
+        (status = 500, description = "Failed to store the user", body = ErrorResponse),
+    ),
+    security(()),
+)]
 async fn setup_handler(
     State(state): State<ApiState>,
     Json(request): Json<SetupRequest>,
@@ -995,6 +1331,18 @@ async fn setup_handler(
 }
 
 /// Login endpoint
+#[utoipa::path(
+    post,
+    path = "/api/v1/auth/login",
+    tag = "auth",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Bearer token for the protected API", body = LoginResponse),
+        (status = 401, description = "Invalid credentials", body = ErrorResponse),
+        (status = 429, description = "Too many login attempts"),
+    ),
+    security(()),
+)]
 async fn login_handler(
     State(state): State<ApiState>,
     Json(request): Json<LoginRequest>,
@@ -1269,6 +1617,15 @@ async fn login_handler(
 }
 
 /// Health check endpoint
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "health",
+    responses(
+        (status = 200, description = "Server is up", body = HealthResponse),
+    ),
+    security(()),
+)]
 async fn health_handler() -> impl IntoResponse {
     Json(HealthResponse {
         status: "healthy".to_string(),
@@ -1276,10 +1633,53 @@ async fn health_handler() -> impl IntoResponse {
     })
 }
 
+/// Retry-After sent while startup recovery is still running
+const RECOVERY_RETRY_AFTER_SECS: u64 = 5;
+
+/// Liveness probe: 200 while the process is serving
+#[utoipa::path(
+    get,
+    path = "/health/live",
+    tag = "health",
+    responses(
+        (status = 200, description = "Process is alive", body = serde_json::Value),
+    ),
+    security(()),
+)]
+async fn liveness_handler(State(state): State<ApiState>) -> impl IntoResponse {
+    Json(state.health.liveness())
+}
+
+/// Readiness probe: 503 when any critical subsystem check is down
+#[utoipa::path(
+    get,
+    path = "/health/ready",
+    tag = "health",
+    responses(
+        (status = 200, description = "All readiness checks pass", body = serde_json::Value),
+        (status = 503, description = "A readiness check failed or recovery is still running", body = serde_json::Value),
+    ),
+    security(()),
+)]
+async fn readiness_handler(State(state): State<ApiState>) -> impl IntoResponse {
+    let report = state.health.readiness().await;
+    let status = if report.ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(report))
+}
+
 /// Metrics endpoint (Prometheus format)
-async fn metrics_handler() -> impl IntoResponse {
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "health",
+    responses(
+        (status = 200, description = "Prometheus text exposition", body = String, content_type = "text/plain"),
+    ),
+    security(()),
+)]
+async fn metrics_handler(State(state): State<ApiState>) -> impl IntoResponse {
     // Return basic Prometheus metrics
-    let metrics = r#"# HELP narayana_queries_total Total number of queries
+    let mut metrics = String::from(r#"# HELP narayana_queries_total Total number of queries
 # TYPE narayana_queries_total counter
 narayana_queries_total 0
 
@@ -1294,7 +1694,19 @@ narayana_rows_read_total 0
 # HELP narayana_rows_inserted_total Total rows inserted
 # TYPE narayana_rows_inserted_total counter
 narayana_rows_inserted_total 0
-"#;
+"#);
+    // Event delivery counters, per subscription and per event name
+    metrics.push('\n');
+    metrics.push_str(&state.rde.metrics().to_prometheus());
+    // Replication lag and throughput, per peer and per origin region
+    metrics.push('\n');
+    metrics.push_str(&state.replication.status().to_prometheus());
+    // GPU device health, utilization and memory pools
+    metrics.push('\n');
+    metrics.push_str(&state.gpu.metrics().to_prometheus());
+    // Open client transactions and how they ended
+    metrics.push('\n');
+    metrics.push_str(&state.transactions.metrics().to_prometheus());
     
     // SECURITY: Handle response building errors gracefully
     match Response::builder()
@@ -1325,6 +1737,14 @@ narayana_rows_inserted_total 0
 }
 
 /// Get all tables
+#[utoipa::path(
+    get,
+    path = "/api/v1/tables",
+    tag = "tables",
+    responses(
+        (status = 200, description = "All tables with schemas and row counts", body = TablesResponse),
+    ),
+)]
 async fn get_tables_handler(State(state): State<ApiState>) -> impl IntoResponse {
     // List all tables from database manager, but exclude protected system tables
     let db_id = match state.db_manager.get_database_by_name("default") {
@@ -1384,6 +1804,18 @@ async fn get_tables_handler(State(state): State<ApiState>) -> impl IntoResponse
 }
 
 /// Create a new table
+#[utoipa::path(
+    post,
+    path = "/api/v1/tables",
+    tag = "tables",
+    request_body = CreateTableRequest,
+    responses(
+        (status = 200, description = "Table created", body = CreateTableResponse),
+        (status = 400, description = "Invalid table name or schema", body = ErrorResponse),
+        (status = 403, description = "Reserved table name", body = ErrorResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse),
+    ),
+)]
 async fn create_table_handler(
     State(state): State<ApiState>,
     Json(request): Json<CreateTableRequest>,
@@ -1501,6 +1933,31 @@ async fn create_table_handler(
     
     // Create schema from request
     let schema = request.schema;
+
+    // Validate "embed" column annotations before anything is created
+    if !schema.embeddings.is_empty() {
+        if state.embeddings.is_none() {
+            let response = Json(ErrorResponse {
+                error: "Embed columns require an embedding provider to be configured".to_string(),
+                code: "INVALID_SCHEMA".to_string(),
+            });
+            return (StatusCode::BAD_REQUEST, response).into_response();
+        }
+        if let Err(e) = schema.validate_embeddings() {
+            let response = Json(ErrorResponse {
+                error: format!("Invalid embed annotation: {}", e),
+                code: "INVALID_SCHEMA".to_string(),
+            });
+            return (StatusCode::BAD_REQUEST, response).into_response();
+        }
+    }
+    if let Err(e) = schema.validate_ttl() {
+        let response = Json(ErrorResponse {
+            error: format!("Invalid TTL column: {}", e),
+            code: "INVALID_SCHEMA".to_string(),
+        });
+        return (StatusCode::BAD_REQUEST, response).into_response();
+    }
     
     // Get or create default database
     let db_id = match state.db_manager.get_database_by_name("default") {
@@ -1560,6 +2017,12 @@ async fn create_table_handler(
             match state.storage.create_table(final_table_id, schema.clone()).await {
                 Ok(_) => {
                     info!("Table {} created with ID {}", request.table_name, final_table_id.0);
+
+                    if let Some(embeddings) = &state.embeddings {
+                        if let Err(e) = embeddings.register_table(state.storage.as_ref(), final_table_id, &schema).await {
+                            warn!("Failed to register embed columns for table {}: {}", final_table_id.0, e);
+                        }
+                    }
                     
                     // Emit database event
                     // TODO: Implement WebSocket event broadcasting when bridge is available
@@ -1606,6 +2069,21 @@ async fn create_table_handler(
 }
 
 /// Delete a table
+#[utoipa::path(
+    delete,
+    path = "/api/v1/tables/{id}",
+    tag = "tables",
+    params(
+        ("id" = u64, Path, description = "Table id"),
+    ),
+    responses(
+        (status = 200, description = "Table dropped", body = serde_json::Value),
+        (status = 400, description = "Invalid table id", body = ErrorResponse),
+        (status = 403, description = "Protected table", body = ErrorResponse),
+        (status = 404, description = "Table not found", body = ErrorResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse),
+    ),
+)]
 async fn delete_table_handler(
     State(state): State<ApiState>,
     Path(id): Path<u64>,
@@ -1663,9 +2141,17 @@ async fn delete_table_handler(
         return (StatusCode::FORBIDDEN, response).into_response();
     }
     
+    let dropped_schema = find_table_schema(&state, table_id);
+    
     // Delete table from storage
     match state.storage.delete_table(table_id).await {
         Ok(_) => {
+            state.full_text.drop_table(table_id);
+            state.spatial.drop_table(table_id);
+            state.replication.drop_table(table_id);
+            if let Some(schema) = dropped_schema {
+                state.change_feed.publish_drop(table_id, &schema);
+            }
             // Emit database event
             // TODO: Implement WebSocket event broadcasting when bridge is available
             // if let Some(ws_state) = &state.ws_state {
@@ -1696,6 +2182,23 @@ async fn delete_table_handler(
 }
 
 /// Insert data into a table
+#[utoipa::path(
+    post,
+    path = "/api/v1/tables/{id}/insert",
+    tag = "tables",
+    params(
+        ("id" = u64, Path, description = "Table id"),
+    ),
+    request_body = InsertRequest,
+    responses(
+        (status = 200, description = "Rows written", body = InsertResponse),
+        (status = 400, description = "Columns do not match the schema", body = ErrorResponse),
+        (status = 403, description = "Protected table", body = ErrorResponse),
+        (status = 404, description = "Table not found", body = ErrorResponse),
+        (status = 503, description = "Storage is read-only (low disk space)", body = ErrorResponse),
+        (status = 507, description = "Table, database or total storage quota exceeded", body = ErrorResponse),
+    ),
+)]
 async fn insert_data_handler(
     State(state): State<ApiState>,
     Path(id): Path<u64>,
@@ -1718,17 +2221,19 @@ async fn insert_data_handler(
     };
     
     // Check if table exists and get schema
-    let table_info = state.db_manager.list_tables(db_id)
+    let table = match state.db_manager.list_tables(db_id)
         .ok()
-        .and_then(|tables| tables.into_iter().find(|t| t.table_id == table_id));
-    
-    if table_info.is_none() {
-        let response = Json(ErrorResponse {
-            error: "Table not found".to_string(),
-            code: "TABLE_NOT_FOUND".to_string(),
-        });
-        return (StatusCode::NOT_FOUND, response).into_response();
-    }
+        .and_then(|tables| tables.into_iter().find(|t| t.table_id == table_id))
+    {
+        Some(table) => table,
+        None => {
+            let response = Json(ErrorResponse {
+                error: "Table not found".to_string(),
+                code: "TABLE_NOT_FOUND".to_string(),
+            });
+            return (StatusCode::NOT_FOUND, response).into_response();
+        }
+    };
     
     // SECURITY: Prevent modification of protected system tables via normal API
     if is_protected_users_table(&state, table_id) {
@@ -1768,7 +2273,10 @@ async fn insert_data_handler(
         return (StatusCode::BAD_REQUEST, response).into_response();
     }
     
-    for col_json in request.columns {
+    // Fields the client supplies, in order (generated vector columns excluded)
+    let client_fields = client_insert_fields(&table.schema);
+    
+    for (col_idx, col_json) in request.columns.into_iter().enumerate() {
         // SECURITY: Check JSON size and depth before deserialization
         // SECURITY: Limit JSON string size to prevent DoS during serialization
         // EDGE CASE: Handle serialization failures, overflow in size calculation
@@ -1819,8 +2327,15 @@ async fn insert_data_handler(
             }
         };
         
-        // Parse column from JSON - Column already implements Deserialize
-        match serde_json::from_value::<Column>(col_json) {
+        // Parse column from JSON - Column already implements Deserialize;
+        // geospatial columns also accept WKT / [lon, lat] values
+        let parsed = match client_fields.get(col_idx) {
+            Some(field) if matches!(field.data_type, DataType::Point | DataType::Geometry) => {
+                geometry_column_from_json(&field.data_type, col_json).map_err(|e| e.to_string())
+            }
+            _ => serde_json::from_value::<Column>(col_json).map_err(|e| e.to_string()),
+        };
+        match parsed {
             Ok(col) => {
                 // SECURITY: Validate column size
                 // EDGE CASE: Handle overflow in size calculation
@@ -1910,32 +2425,19 @@ async fn insert_data_handler(
     }
     
     // SECURITY: Validate column count matches table schema
-    if let Some(ref table) = table_info {
-        if columns.len() != table.schema.fields.len() {
-            error!("Column count mismatch: expected {}, got {}", table.schema.fields.len(), columns.len());
-            let response = Json(ErrorResponse {
-                error: sanitize_error_message(&format!("Column count mismatch. Expected {} columns, got {}", table.schema.fields.len(), columns.len()), "COLUMN_COUNT_ERROR"),
-                code: "COLUMN_COUNT_MISMATCH".to_string(),
-            });
-            return (StatusCode::BAD_REQUEST, response).into_response();
-        }
+    // Vector columns generated from "embed" annotations are not supplied by the client
+    let expected_columns = client_fields.len();
+    if columns.len() != expected_columns {
+        error!("Column count mismatch: expected {}, got {}", expected_columns, columns.len());
+        let response = Json(ErrorResponse {
+            error: sanitize_error_message(&format!("Column count mismatch. Expected {} columns, got {}", expected_columns, columns.len()), "COLUMN_COUNT_ERROR"),
+            code: "COLUMN_COUNT_MISMATCH".to_string(),
+        });
+        return (StatusCode::BAD_REQUEST, response).into_response();
     }
     
-    match state.storage.write_columns(table_id, columns.clone()).await {
-        Ok(_) => {
-            // EDGE CASE: Handle empty columns, overflow in conversion
-            let row_count = columns.first().map(|c| c.len()).unwrap_or(0);
-            
-            // EDGE CASE: Check for usize to u64 overflow
-            let row_count_u64 = if row_count > u64::MAX as usize {
-                u64::MAX
-            } else {
-                row_count as u64
-            };
-            
-            TOTAL_ROWS_INSERTED.fetch_add(row_count_u64, Ordering::Relaxed);
-            info!("Inserted {} rows into table {}", row_count, id);
-            
+    match write_insert_batch(&state, table_id, &table.schema, columns).await {
+        Ok(row_count) => {
             // Emit database event
             // TODO: Implement WebSocket event broadcasting when bridge is available
             // if let Some(ws_state) = &state.ws_state {
@@ -1955,22 +2457,302 @@ async fn insert_data_handler(
                 rows_inserted: row_count,
             })).into_response()
         }
+        Err((status, response)) => (status, Json(response)).into_response(),
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BulkInsertResponse {
+    pub success: bool,
+    pub rows_received: usize,
+    pub rows_inserted: usize,
+    pub rows_failed: usize,
+    pub batches_written: usize,
+    /// Per-row failures (at most `bulk_insert::MAX_REPORTED_ERRORS`)
+    #[schema(value_type = Vec<Object>)]
+    pub errors: Vec<crate::bulk_insert::BulkRowError>,
+    pub errors_truncated: bool,
+}
+
+/// Largest bulk payload accepted (matches the insert payload limit)
+const BULK_MAX_BODY_BYTES: usize = 100 * 1024 * 1024;
+const BULK_MAX_ROWS: usize = 1_000_000;
+const BULK_DEFAULT_BATCH_SIZE: usize = 5_000;
+const BULK_MAX_BATCH_SIZE: usize = 50_000;
+
+/// Bulk insert rows (NDJSON or a JSON array of objects/arrays).
+/// Rows are validated individually; valid rows are written in batches and
+/// rejected ones reported with their position instead of failing the request.
+#[utoipa::path(
+    post,
+    path = "/api/v1/tables/{id}/bulk",
+    tag = "tables",
+    params(
+        ("id" = u64, Path, description = "Table id"),
+        ("batch_size" = Option<usize>, Query, description = "Rows per storage write"),
+    ),
+    request_body(content = String, description = "JSON array of row objects, or NDJSON with an ndjson/jsonlines content type"),
+    responses(
+        (status = 200, description = "Rows written; per-row failures are reported, not fatal", body = BulkInsertResponse),
+        (status = 400, description = "Body is not a JSON array or NDJSON", body = ErrorResponse),
+        (status = 403, description = "Protected table", body = ErrorResponse),
+        (status = 404, description = "Table not found", body = ErrorResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse),
+        (status = 502, description = "Embedding provider failed", body = ErrorResponse),
+        (status = 503, description = "Embeddings not configured", body = ErrorResponse),
+    ),
+)]
+async fn bulk_insert_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<u64>,
+    Query(params): Query<HashMap<String, String>>,
+    headers: HeaderMap,
+    body: String,
+) -> impl IntoResponse {
+    use crate::bulk_insert::{parse_rows, BulkRowError, RowBatchBuilder, MAX_REPORTED_ERRORS};
+    
+    let table_id = TableId(id);
+    let table = match state.db_manager.get_database_by_name("default")
+        .and_then(|db_id| state.db_manager.list_tables(db_id).ok())
+        .and_then(|tables| tables.into_iter().find(|t| t.table_id == table_id))
+    {
+        Some(table) => table,
+        None => {
+            let response = Json(ErrorResponse {
+                error: "Table not found".to_string(),
+                code: "TABLE_NOT_FOUND".to_string(),
+            });
+            return (StatusCode::NOT_FOUND, response).into_response();
+        }
+    };
+    
+    // SECURITY: Prevent modification of protected system tables via normal API
+    if is_protected_users_table(&state, table_id) {
+        error!("Attempt to bulk insert into protected system table: {}", id);
+        let response = Json(ErrorResponse {
+            error: "Cannot modify protected system table via this endpoint".to_string(),
+            code: "PROTECTED_TABLE".to_string(),
+        });
+        return (StatusCode::FORBIDDEN, response).into_response();
+    }
+    
+    let ndjson = headers.get(axum::http::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|ct| ct.contains("ndjson") || ct.contains("jsonlines") || ct.contains("x-json-stream"))
+        .unwrap_or(false);
+    let rows = match parse_rows(&body, ndjson) {
+        Ok(rows) => rows,
         Err(e) => {
-            error!("Failed to insert data: {}", e);
             let response = Json(ErrorResponse {
+                error: e,
+                code: "PARSE_ERROR".to_string(),
+            });
+            return (StatusCode::BAD_REQUEST, response).into_response();
+        }
+    };
+    drop(body);
+    if rows.len() > BULK_MAX_ROWS {
+        let response = Json(ErrorResponse {
+            error: format!("Too many rows. Maximum is {} per request", BULK_MAX_ROWS),
+            code: "TOO_MANY_ROWS".to_string(),
+        });
+        return (StatusCode::BAD_REQUEST, response).into_response();
+    }
+    let batch_size = params.get("batch_size")
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(BULK_DEFAULT_BATCH_SIZE)
+        .clamp(1, BULK_MAX_BATCH_SIZE);
+    
+    let encode_geometry = |data_type: &DataType, value: serde_json::Value| -> Result<Vec<u8>, String> {
+        match geometry_column_from_json(data_type, serde_json::Value::Array(vec![value])) {
+            Ok(Column::Binary(mut values)) if values.len() == 1 => Ok(values.remove(0)),
+            Ok(_) => Err("invalid geometry".to_string()),
+            Err(e) => Err(e.to_string()),
+        }
+    };
+    let mut builder = RowBatchBuilder::new(client_insert_fields(&table.schema), &encode_geometry);
+    let mut report = BulkInsertResponse {
+        success: true,
+        rows_received: rows.len(),
+        rows_inserted: 0,
+        rows_failed: 0,
+        batches_written: 0,
+        errors: Vec::new(),
+        errors_truncated: false,
+    };
+    fn record_error(report: &mut BulkInsertResponse, error: BulkRowError) {
+        report.rows_failed += 1;
+        if report.errors.len() < MAX_REPORTED_ERRORS {
+            report.errors.push(error);
+        } else {
+            report.errors_truncated = true;
+        }
+    }
+    
+    let total = rows.len();
+    for (row, value) in rows.into_iter().enumerate() {
+        if let Err(error) = value.and_then(|value| builder.push_row(row, value)) {
+            record_error(&mut report, error);
+        }
+        if builder.len() >= batch_size || (row + 1 == total && !builder.is_empty()) {
+            let (columns, positions) = builder.take_batch();
+            match write_insert_batch(&state, table_id, &table.schema, columns).await {
+                Ok(written) => {
+                    report.rows_inserted += written;
+                    report.batches_written += 1;
+                }
+                Err((_, failure)) => {
+                    // The whole batch failed to write; report it against each of its rows
+                    for position in positions {
+                        record_error(&mut report, BulkRowError::new(position, None, &failure.code, failure.error.clone()));
+                    }
+                }
+            }
+        }
+    }
+    
+    report.success = report.rows_failed == 0;
+    info!(
+        "Bulk insert into table {}: {} received, {} inserted, {} failed",
+        id, report.rows_received, report.rows_inserted, report.rows_failed
+    );
+    (StatusCode::OK, Json(report)).into_response()
+}
+
+/// Fields the client supplies on insert, in order (generated vector columns excluded)
+fn client_insert_fields(schema: &Schema) -> Vec<narayana_core::schema::Field> {
+    let generated = schema.generated_field_indexes();
+    schema.fields.iter().enumerate()
+        .filter(|(idx, _)| !generated.contains(idx))
+        .map(|(_, field)| field.clone())
+        .collect()
+}
+
+/// Write validated client columns: compute embeddings, store, then update
+/// full-text/spatial indexes and the change feed. Returns the rows written.
+async fn write_insert_batch(
+    state: &ApiState,
+    table_id: TableId,
+    schema: &Schema,
+    mut columns: Vec<Column>,
+) -> Result<usize, (StatusCode, ErrorResponse)> {
+    // Embeddings are computed before the table's write lock is taken; the
+    // rows get their embedding ids once stored, with the lock still held
+    let mut pending = None;
+    if !schema.embeddings.is_empty() {
+        let embeddings = match &state.embeddings {
+            Some(embeddings) => embeddings,
+            None => {
+                return Err((StatusCode::SERVICE_UNAVAILABLE, ErrorResponse {
+                    error: "Embedding provider not configured".to_string(),
+                    code: "INSERT_ERROR".to_string(),
+                }));
+            }
+        };
+        columns = match embeddings.prepare_insert(table_id, schema, columns).await {
+            Ok((columns, embedded)) => {
+                pending = Some((embeddings, embedded));
+                columns
+            }
+            Err(e) => {
+                error!("Failed to compute embeddings for table {}: {}", table_id.0, e);
+                return Err((StatusCode::BAD_GATEWAY, ErrorResponse {
+                    error: sanitize_error_message(&format!("Failed to compute embeddings: {}", e), "INSERT_ERROR"),
+                    code: "INSERT_ERROR".to_string(),
+                }));
+            }
+        };
+    }
+
+    let guard = state.storage.lock_table(table_id).await;
+    if let Some((embeddings, _)) = &pending {
+        if let Err(e) = embeddings.register_table(state.storage.as_ref(), table_id, schema).await {
+            error!("Failed to register embed columns for table {}: {}", table_id.0, e);
+            return Err((StatusCode::INTERNAL_SERVER_ERROR, ErrorResponse {
                 error: sanitize_error_message(&format!("Failed to insert data: {}", e), "INSERT_ERROR"),
                 code: "INSERT_ERROR".to_string(),
-            });
-            (StatusCode::INTERNAL_SERVER_ERROR, response).into_response()
+            }));
+        }
+    }
+    
+    // Versioned tables stamp each row's period column on the way in, and
+    // replicated ones queue the rows for peer regions
+    let temporal = state.db_manager.temporal();
+    let store = WriteLocked::new(state.storage.as_ref(), &guard);
+    match state.replication.write(&store, temporal, table_id, columns).await {
+        Ok(columns) => {
+            // EDGE CASE: Handle empty columns, overflow in conversion
+            let row_count = columns.first().map(|c| c.len()).unwrap_or(0);
+            
+            // EDGE CASE: Check for usize to u64 overflow
+            let row_count_u64 = if row_count > u64::MAX as usize {
+                u64::MAX
+            } else {
+                row_count as u64
+            };
+            
+            TOTAL_ROWS_INSERTED.fetch_add(row_count_u64, Ordering::Relaxed);
+            info!("Inserted {} rows into table {}", row_count, table_id.0);
+
+            if let Some((embeddings, embedded)) = pending {
+                if let Err(e) = embeddings.publish(embedded) {
+                    warn!("Failed to index embeddings for table {}: {}", table_id.0, e);
+                }
+            }
+            // Keep full-text and spatial indexes up to date
+            if let Err(e) = state.full_text.index_insert(table_id, schema, &columns) {
+                warn!("Failed to update full-text index for table {}: {}", table_id.0, e);
+            }
+            if let Err(e) = state.spatial.index_insert(table_id, schema, &columns) {
+                warn!("Failed to update spatial index for table {}: {}", table_id.0, e);
+            }
+            state.change_feed.publish_insert(table_id, schema, &columns);
+            Ok(row_count)
+        }
+        Err(e) => {
+            if let Some(refused) = quota_error(&e) {
+                warn!("Insert into table {} refused: {}", table_id.0, e);
+                return Err(refused);
+            }
+            error!("Failed to insert data: {}", e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, ErrorResponse {
+                error: sanitize_error_message(&format!("Failed to insert data: {}", e), "INSERT_ERROR"),
+                code: "INSERT_ERROR".to_string(),
+            }))
         }
     }
 }
 
 /// Query data from a table
+#[utoipa::path(
+    get,
+    path = "/api/v1/tables/{id}/query",
+    tag = "tables",
+    params(
+        ("id" = u64, Path, description = "Table id"),
+        ("columns" = Option<String>, Query, description = "Comma-separated column indices (default: all)"),
+        ("limit" = Option<usize>, Query, description = "Maximum rows to return (at most 10000)"),
+        ("offset" = Option<usize>, Query, description = "Rows to skip (default 0); counted before row policies apply, so pages of `limit` rows stay aligned"),
+        ("as_of" = Option<String>, Query, description = "Read a system-versioned table as of this time: Unix ms, RFC 3339 or FOR SYSTEM_TIME AS OF '<ts>'"),
+    ),
+    responses(
+        (status = 200, description = "Column data; an Arrow IPC stream (one record batch) when the Accept header lists application/vnd.apache.arrow.stream, unless an output profile applies", content(
+            (QueryResponse = "application/json"),
+            (Vec<u8> = "application/vnd.apache.arrow.stream")
+        )),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+        (status = 403, description = "Protected table", body = ErrorResponse),
+        (status = 404, description = "Table not found", body = ErrorResponse),
+        (status = 409, description = "Query was cancelled", body = ErrorResponse),
+        (status = 500, description = "Storage error", body = ErrorResponse),
+    ),
+)]
 async fn query_data_handler(
     State(state): State<ApiState>,
     Path(id): Path<u64>,
     Query(params): Query<HashMap<String, String>>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+    headers: HeaderMap,
 ) -> impl IntoResponse {
     // EDGE CASE: Validate table ID is not zero
     if id == 0 {
@@ -2197,6 +2979,16 @@ async fn query_data_handler(
         return (StatusCode::BAD_REQUEST, response).into_response();
     }
     
+    // Rows to skip, counted before row policies hide any so that pages of
+    // `limit` rows line up however many rows each one shows
+    let offset = match params.get("offset").map(|s| s.trim().parse::<usize>()) {
+        None => 0,
+        Some(Ok(offset)) => offset,
+        Some(Err(_)) => {
+            return job_error(StatusCode::BAD_REQUEST, "Offset must be a non-negative integer".to_string(), "INVALID_OFFSET");
+        }
+    };
+    
     // Track query start time
     let query_start = std::time::Instant::now();
     
@@ -2232,8 +3024,49 @@ async fn query_data_handler(
         }
     }
     
+    let temporal = state.db_manager.temporal();
+    let as_of = match params.get("as_of") {
+        Some(text) => match narayana_storage::temporal::parse_as_of(text) {
+            Ok(_) if !temporal.is_versioned(table_id) => {
+                return job_error(StatusCode::BAD_REQUEST, "Table is not system-versioned".to_string(), "NOT_VERSIONED");
+            }
+            Ok(as_of) => Some(as_of),
+            Err(e) => return job_error(StatusCode::BAD_REQUEST, e.to_string(), "INVALID_AS_OF"),
+        },
+        None => None,
+    };
+    
+    // Callers whose token or role is bound to an output profile get their
+    // rows through it (e.g. with PII masked). Fail closed if it can't be resolved.
+    let output_profile = match claims.as_ref() {
+        Some(claims) => match state.db_manager.get_table_output_config_for_consumer(table_id, &claims.sub, &claims.roles) {
+            Ok(profile) => profile,
+            Err(e) => {
+                error!("Failed to resolve output profile for table {}: {}", id, e);
+                return job_error(StatusCode::INTERNAL_SERVER_ERROR, "Output profile unavailable".to_string(), "OUTPUT_PROFILE_ERROR");
+            }
+        },
+        None => None,
+    };
+    
+    // Row policies and column masks for the caller apply to the read itself;
+    // callers without a token read as an anonymous principal with no roles.
+    // Versioned tables show the rows current at `as_of` (now by default).
+    let storage = state.db_manager.row_security().secure(
+        temporal.at(state.storage.clone(), as_of),
+        claims.as_ref().map(|c| c.principal()).unwrap_or_default(),
+    );
+    
+    // Register the read so it can be listed and cancelled while it runs
+    let query = state.queries.register(
+        format!("read table {} ({} columns, offset {}, limit {})", id, column_indices.len(), offset, limit),
+        claims.map(|axum::Extension(claims)| claims.sub),
+    );
+    query.context().progress.set_total_nodes(1);
+    query.context().progress.set_stage(format!("scan table {}", id));
+    
     // Read columns from storage
-    match state.storage.read_columns(table_id, column_indices.clone(), 0, limit).await {
+    match query.context().run(storage.read_columns(table_id, column_indices.clone(), offset, limit)).await {
         Ok(columns) => {
             // Track statistics
             // SECURITY: Safely get row count, handling empty columns gracefully
@@ -2287,6 +3120,63 @@ async fn query_data_handler(
                 }
             }
             
+            query.context().progress.add_rows(row_count);
+            query.context().progress.node_completed();
+            
+            let fields: Vec<String> = column_indices
+                .iter()
+                .map(|&idx| {
+                    table_info.as_ref()
+                        .and_then(|table| table.schema.fields.get(idx as usize))
+                        .map(|field| field.name.clone())
+                        .unwrap_or_else(|| format!("column_{}", idx))
+                })
+                .collect();
+            
+            if let Some((profile, config)) = output_profile {
+                let rows = columns_to_rows(&fields, &columns, row_count);
+                return match narayana_core::transforms::TransformEngine::apply_config(rows, &config) {
+                    Ok(rows) => (StatusCode::OK, Json(QueryResponse {
+                        columns: Vec::new(),
+                        row_count,
+                        query_id: query.id(),
+                        rows: Some(rows),
+                        output_profile: Some(profile),
+                    })).into_response(),
+                    Err(e) => {
+                        // Never fall back to the untransformed rows
+                        error!("Output profile '{}' failed on table {}: {}", profile, id, e);
+                        job_error(
+                            StatusCode::INTERNAL_SERVER_ERROR,
+                            sanitize_error_message(&format!("Output profile failed: {}", e), "OUTPUT_PROFILE_ERROR"),
+                            "OUTPUT_PROFILE_ERROR",
+                        )
+                    }
+                };
+            }
+            
+            // Clients that accept Arrow get the columns as an IPC stream, handed
+            // over without a JSON round trip. Profiled rows above stay JSON.
+            let wants_arrow = headers.get(axum::http::header::ACCEPT)
+                .and_then(|v| v.to_str().ok())
+                .is_some_and(narayana_core::arrow::accepts_arrow);
+            if wants_arrow {
+                return match narayana_core::arrow::columns_to_ipc(&fields, columns) {
+                    Ok(bytes) => Response::builder()
+                        .status(StatusCode::OK)
+                        .header(axum::http::header::CONTENT_TYPE, narayana_core::arrow::ARROW_STREAM_MEDIA_TYPE)
+                        .header("x-query-id", query.id())
+                        .header("x-row-count", row_count)
+                        .body(Body::from(bytes))
+                        .unwrap()
+                        .into_response(),
+                    Err(e) => {
+                        error!("Failed to encode table {} as Arrow: {}", id, e);
+                        job_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to encode result as Arrow".to_string(), "ARROW_ERROR")
+                    }
+                };
+            }
+            
             // Convert columns to JSON - Column already implements Serialize
             let json_columns: Vec<serde_json::Value> = columns
                 .iter()
@@ -2299,8 +3189,19 @@ async fn query_data_handler(
             (StatusCode::OK, Json(QueryResponse {
                 columns: json_columns,
                 row_count,
+                query_id: query.id(),
+                rows: None,
+                output_profile: None,
             })).into_response()
         }
+        Err(e) if narayana_query::cancellation::is_cancelled_error(&e) => {
+            info!("Query {} on table {} was cancelled", query.id(), id);
+            let response = Json(ErrorResponse {
+                error: format!("Query {} was cancelled", query.id()),
+                code: "QUERY_CANCELLED".to_string(),
+            });
+            (StatusCode::CONFLICT, response).into_response()
+        }
         Err(e) => {
             error!("Failed to query table: {}", e);
             let response = Json(ErrorResponse {
@@ -2312,7 +3213,795 @@ async fn query_data_handler(
     }
 }
 
-/// Get query statistics
+/// Largest batch a table extract may ask for, in rows
+const ARROW_EXTRACT_MAX_BATCH_ROWS: usize = narayana_api::arrow_extract::MAX_BATCH_ROWS;
+
+/// Stream a whole table as an Arrow IPC stream. Each batch is read from
+/// storage, encoded and sent before the next, so extracts have no row limit
+/// and never hold the full table. Row policies and column masks apply.
+#[utoipa::path(
+    get,
+    path = "/api/v1/tables/{id}/arrow",
+    tag = "tables",
+    params(
+        ("id" = u64, Path, description = "Table id"),
+        ("columns" = Option<String>, Query, description = "Comma-separated column names (default: all)"),
+        ("batch_rows" = Option<usize>, Query, description = "Rows per record batch (default 65536, at most 1048576)"),
+    ),
+    responses(
+        (status = 200, description = "Arrow IPC stream, one record batch per page", body = Vec<u8>, content_type = "application/vnd.apache.arrow.stream"),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+        (status = 403, description = "Protected table, or the caller is bound to an output profile", body = ErrorResponse),
+        (status = 404, description = "Table or column not found", body = ErrorResponse),
+    ),
+)]
+async fn table_arrow_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<u64>,
+    Query(params): Query<HashMap<String, String>>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+) -> impl IntoResponse {
+    use futures::StreamExt;
+
+    let table_id = TableId(id);
+    let table = state.db_manager.get_database_by_name("default")
+        .and_then(|db_id| state.db_manager.list_tables(db_id).ok())
+        .and_then(|tables| tables.into_iter().find(|t| t.table_id == table_id));
+    let Some(table) = table else {
+        return job_error(StatusCode::NOT_FOUND, "Table not found".to_string(), "TABLE_NOT_FOUND");
+    };
+    if is_protected_users_table(&state, table_id) {
+        return job_error(StatusCode::FORBIDDEN, "Cannot read protected system table via this endpoint".to_string(), "PROTECTED_TABLE");
+    }
+
+    let column_ids: Vec<u32> = match params.get("columns").filter(|s| !s.trim().is_empty()) {
+        None => (0..table.schema.fields.len() as u32).collect(),
+        Some(names) => {
+            let mut ids = Vec::new();
+            for name in names.split(',').map(str::trim) {
+                match table.schema.field_index(name) {
+                    Some(idx) if !ids.contains(&(idx as u32)) => ids.push(idx as u32),
+                    Some(_) => {}
+                    None => return job_error(StatusCode::NOT_FOUND, format!("Column '{}' not found", name), "COLUMN_NOT_FOUND"),
+                }
+            }
+            ids
+        }
+    };
+    if column_ids.is_empty() {
+        return job_error(StatusCode::BAD_REQUEST, "Table has no columns".to_string(), "INVALID_TABLE_SCHEMA");
+    }
+    let batch_rows = match params.get("batch_rows").map(|s| s.trim().parse::<usize>()) {
+        None => narayana_api::arrow_extract::DEFAULT_BATCH_ROWS,
+        Some(Ok(rows)) if (1..=ARROW_EXTRACT_MAX_BATCH_ROWS).contains(&rows) => rows,
+        Some(_) => {
+            return job_error(
+                StatusCode::BAD_REQUEST,
+                format!("batch_rows must be between 1 and {}", ARROW_EXTRACT_MAX_BATCH_ROWS),
+                "INVALID_BATCH_ROWS",
+            )
+        }
+    };
+
+    // Output profiles rewrite rows as JSON; their callers must use /query
+    if let Some(claims) = claims.as_ref() {
+        match state.db_manager.get_table_output_config_for_consumer(table_id, &claims.sub, &claims.roles) {
+            Ok(None) => {}
+            Ok(Some(_)) => {
+                return job_error(StatusCode::FORBIDDEN, "Caller is bound to an output profile; use /query".to_string(), "OUTPUT_PROFILE_BOUND");
+            }
+            Err(e) => {
+                error!("Failed to resolve output profile for table {}: {}", id, e);
+                return job_error(StatusCode::INTERNAL_SERVER_ERROR, "Output profile unavailable".to_string(), "OUTPUT_PROFILE_ERROR");
+            }
+        }
+    }
+
+    let fields: Vec<_> = column_ids.iter().map(|&idx| table.schema.fields[idx as usize].clone()).collect();
+    let schema = match narayana_core::arrow::arrow_schema(&fields) {
+        Ok(schema) => Arc::new(schema),
+        Err(e) => return job_error(StatusCode::BAD_REQUEST, e.to_string(), "ARROW_ERROR"),
+    };
+    let chunks = match narayana_core::arrow::IpcStreamChunks::new(&schema) {
+        Ok(chunks) => chunks,
+        Err(e) => return job_error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string(), "ARROW_ERROR"),
+    };
+    // Versioned tables show their current rows, as /query does by default
+    let storage = state.db_manager.row_security().secure(
+        state.db_manager.temporal().at(state.storage.clone(), None),
+        claims.as_ref().map(|c| c.principal()).unwrap_or_default(),
+    );
+    let batches = narayana_api::arrow_extract::table_batches(storage, table_id, column_ids, schema, batch_rows);
+
+    // The schema goes out with the first batch; finishing adds the end marker
+    let body = futures::stream::unfold((batches, Some(chunks)), move |(mut batches, chunks)| async move {
+        let mut chunks = chunks?;
+        let (piece, chunks) = match batches.next().await {
+            Some(batch) => match batch.and_then(|batch| chunks.push(&batch)) {
+                Ok(bytes) => (Ok(bytes), Some(chunks)),
+                Err(e) => (Err(e), None),
+            },
+            None => (chunks.finish(), None),
+        };
+        let piece = piece.map(axum::body::Bytes::from).map_err(|e| {
+            error!("Arrow extract of table {} failed: {}", id, e);
+            std::io::Error::other(e.to_string())
+        });
+        Some((piece, (batches, chunks)))
+    });
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(axum::http::header::CONTENT_TYPE, narayana_core::arrow::ARROW_STREAM_MEDIA_TYPE)
+        .body(Body::from_stream(body))
+        .unwrap()
+        .into_response()
+}
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct BeginTransactionRequest {
+    /// Idle seconds after which the transaction is rolled back (default: query.transactions.default_timeout)
+    #[serde(default)]
+    pub timeout_secs: Option<f64>,
+}
+
+/// A statement bound to a transaction
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionStatementRequest {
+    /// Stage rows, in the shape of `/tables/{id}/insert`; they are written on commit
+    Insert {
+        table_id: u64,
+        #[schema(value_type = Vec<Object>)]
+        columns: Vec<serde_json::Value>,
+    },
+    /// Read committed rows followed by the rows this transaction staged
+    Query {
+        table_id: u64,
+        /// Column names (default: all)
+        #[serde(default)]
+        columns: Option<Vec<String>>,
+        /// Rows to return (default 1000, at most 10000)
+        #[serde(default)]
+        limit: Option<usize>,
+    },
+}
+
+/// The caller transactions are bound to
+fn transaction_owner(claims: &Option<axum::Extension<crate::security::Claims>>) -> String {
+    claims.as_ref().map(|c| c.sub.clone()).unwrap_or_else(|| "anonymous".to_string())
+}
+
+fn transaction_error(error: &narayana_core::Error) -> axum::response::Response {
+    if let Some((status, response)) = quota_error(error) {
+        return (status, Json(response)).into_response();
+    }
+    let message = error.to_string();
+    let (status, code) = match error {
+        narayana_core::Error::Transaction(m) if m.contains("not found") => (StatusCode::NOT_FOUND, "TRANSACTION_NOT_FOUND"),
+        narayana_core::Error::Transaction(m) if m.contains("timed out") => (StatusCode::GONE, "TRANSACTION_TIMED_OUT"),
+        narayana_core::Error::Transaction(m) if m.contains("Too many open") => (StatusCode::TOO_MANY_REQUESTS, "TOO_MANY_TRANSACTIONS"),
+        // The caller needs to know which tables kept rows of a failed commit
+        narayana_core::Error::Transaction(m) if m.contains("could not be rolled back") => (StatusCode::INTERNAL_SERVER_ERROR, "PARTIAL_COMMIT"),
+        narayana_core::Error::Transaction(_) => (StatusCode::BAD_REQUEST, "TRANSACTION_ERROR"),
+        narayana_core::Error::ColumnNotFound(_) => (StatusCode::NOT_FOUND, "COLUMN_NOT_FOUND"),
+        _ => {
+            error!("Transaction statement failed: {}", message);
+            return job_error(StatusCode::INTERNAL_SERVER_ERROR, sanitize_error_message(&message, "QUERY_ERROR"), "TRANSACTION_ERROR");
+        }
+    };
+    job_error(status, message, code)
+}
+
+/// A table of the default database that transactions may use
+fn transaction_table(state: &ApiState, table_id: TableId) -> Result<narayana_storage::database_manager::TableInfo, axum::response::Response> {
+    let table = state.db_manager.get_database_by_name("default")
+        .and_then(|db_id| state.db_manager.list_tables(db_id).ok())
+        .and_then(|tables| tables.into_iter().find(|t| t.table_id == table_id));
+    let Some(table) = table else {
+        return Err(job_error(StatusCode::NOT_FOUND, "Table not found".to_string(), "TABLE_NOT_FOUND"));
+    };
+    if is_protected_users_table(state, table_id) {
+        return Err(job_error(StatusCode::FORBIDDEN, "Cannot use protected system table in a transaction".to_string(), "PROTECTED_TABLE"));
+    }
+    Ok(table)
+}
+
+/// Begin a transaction. Statements run against it with
+/// `/transactions/{txn_id}/statements` until it is committed or rolled back;
+/// one left idle past its timeout is rolled back.
+#[utoipa::path(
+    post,
+    path = "/api/v1/transactions",
+    tag = "transactions",
+    request_body = BeginTransactionRequest,
+    responses(
+        (status = 201, description = "Transaction begun", body = serde_json::Value),
+        (status = 400, description = "Timeout out of range", body = ErrorResponse),
+        (status = 429, description = "Too many open transactions", body = ErrorResponse),
+    ),
+)]
+async fn begin_transaction_handler(
+    State(state): State<ApiState>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+    request: Option<Json<BeginTransactionRequest>>,
+) -> impl IntoResponse {
+    let request = request.map(|Json(r)| r).unwrap_or_default();
+    let timeout = match request.timeout_secs {
+        None => None,
+        Some(secs) => match std::time::Duration::try_from_secs_f64(secs) {
+            Ok(timeout) => Some(timeout),
+            Err(_) => return job_error(StatusCode::BAD_REQUEST, "timeout_secs must be a positive number".to_string(), "TRANSACTION_ERROR"),
+        },
+    };
+    match state.transactions.begin(&transaction_owner(&claims), timeout) {
+        Ok(info) => (StatusCode::CREATED, Json(serde_json::json!(info))).into_response(),
+        Err(e) => transaction_error(&e),
+    }
+}
+
+/// Open transactions: the caller's own, or everyone's for admins
+#[utoipa::path(
+    get,
+    path = "/api/v1/transactions",
+    tag = "transactions",
+    responses(
+        (status = 200, description = "Open transactions", body = serde_json::Value),
+    ),
+)]
+async fn list_transactions_handler(
+    State(state): State<ApiState>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+) -> impl IntoResponse {
+    let owner = transaction_owner(&claims);
+    let transactions = state.transactions.list((!is_admin(&claims)).then_some(owner.as_str()));
+    Json(serde_json::json!({ "transactions": transactions })).into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/transactions/{txn_id}",
+    tag = "transactions",
+    params(("txn_id" = u64, Path, description = "Transaction id")),
+    responses(
+        (status = 200, description = "Open transaction", body = serde_json::Value),
+        (status = 404, description = "No such open transaction of the caller", body = ErrorResponse),
+        (status = 410, description = "Transaction timed out and was rolled back", body = ErrorResponse),
+    ),
+)]
+async fn get_transaction_handler(
+    State(state): State<ApiState>,
+    Path(txn_id): Path<u64>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+) -> impl IntoResponse {
+    match state.transactions.get(narayana_core::types::TransactionId(txn_id), &transaction_owner(&claims)) {
+        Ok(info) => Json(serde_json::json!(info)).into_response(),
+        Err(e) => transaction_error(&e),
+    }
+}
+
+/// Run a statement in a transaction: `{"insert": {"table_id", "columns"}}`
+/// stages rows, `{"query": {"table_id", "columns", "limit"}}` reads the
+/// table's committed rows followed by the rows the transaction staged
+#[utoipa::path(
+    post,
+    path = "/api/v1/transactions/{txn_id}/statements",
+    tag = "transactions",
+    params(("txn_id" = u64, Path, description = "Transaction id")),
+    request_body = TransactionStatementRequest,
+    responses(
+        (status = 200, description = "Rows staged, or the rows read", body = serde_json::Value),
+        (status = 400, description = "Columns do not match the schema or the transaction", body = ErrorResponse),
+        (status = 403, description = "Protected table, or the caller is bound to an output profile", body = ErrorResponse),
+        (status = 404, description = "Transaction, table or column not found", body = ErrorResponse),
+        (status = 410, description = "Transaction timed out and was rolled back", body = ErrorResponse),
+    ),
+)]
+async fn execute_statement_handler(
+    State(state): State<ApiState>,
+    Path(txn_id): Path<u64>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+    Json(statement): Json<TransactionStatementRequest>,
+) -> impl IntoResponse {
+    let id = narayana_core::types::TransactionId(txn_id);
+    let owner = transaction_owner(&claims);
+    match statement {
+        TransactionStatementRequest::Insert { table_id, columns: values } => {
+            let table_id = TableId(table_id);
+            let table = match transaction_table(&state, table_id) {
+                Ok(table) => table,
+                Err(response) => return response,
+            };
+            // Staged rows are read back as they were written, so generated
+            // vector columns can't be filled in later
+            if !table.schema.generated_field_indexes().is_empty() {
+                return job_error(
+                    StatusCode::BAD_REQUEST,
+                    "Tables with generated embedding columns can't be written in a transaction".to_string(),
+                    "TRANSACTION_ERROR",
+                );
+            }
+            // Rows shipped to peer regions can't be rolled back
+            if state.replication.is_replicated(table_id) {
+                return job_error(
+                    StatusCode::BAD_REQUEST,
+                    "Replicated tables can't be written in a transaction".to_string(),
+                    "TRANSACTION_ERROR",
+                );
+            }
+            if values.len() != table.schema.fields.len() {
+                return job_error(
+                    StatusCode::BAD_REQUEST,
+                    format!("Column count mismatch. Expected {} columns, got {}", table.schema.fields.len(), values.len()),
+                    "COLUMN_COUNT_MISMATCH",
+                );
+            }
+            let mut columns = Vec::with_capacity(values.len());
+            for (field, value) in table.schema.fields.iter().zip(values) {
+                let parsed = match field.data_type {
+                    DataType::Point | DataType::Geometry => geometry_column_from_json(&field.data_type, value).map_err(|e| e.to_string()),
+                    _ => serde_json::from_value::<Column>(value).map_err(|e| e.to_string()),
+                };
+                match parsed {
+                    Ok(column) => columns.push(column),
+                    Err(e) => {
+                        return job_error(
+                            StatusCode::BAD_REQUEST,
+                            sanitize_error_message(&format!("Failed to parse column {}: {}", field.name, e), "PARSE_ERROR"),
+                            "PARSE_ERROR",
+                        )
+                    }
+                }
+            }
+            match state.transactions.stage_insert(id, &owner, table_id, columns) {
+                Ok(rows) => Json(serde_json::json!({ "transaction_id": txn_id, "rows_staged": rows })).into_response(),
+                Err(e) => transaction_error(&e),
+            }
+        }
+        TransactionStatementRequest::Query { table_id, columns: names, limit } => {
+            let table_id = TableId(table_id);
+            let table = match transaction_table(&state, table_id) {
+                Ok(table) => table,
+                Err(response) => return response,
+            };
+            if let Some(claims) = claims.as_ref() {
+                match state.db_manager.get_table_output_config_for_consumer(table_id, &claims.sub, &claims.roles) {
+                    Ok(None) => {}
+                    Ok(Some(_)) => {
+                        return job_error(StatusCode::FORBIDDEN, "Caller is bound to an output profile; use /query".to_string(), "OUTPUT_PROFILE_BOUND");
+                    }
+                    Err(e) => {
+                        error!("Failed to resolve output profile for table {}: {}", table_id.0, e);
+                        return job_error(StatusCode::INTERNAL_SERVER_ERROR, "Output profile unavailable".to_string(), "OUTPUT_PROFILE_ERROR");
+                    }
+                }
+            }
+            let names = names.unwrap_or_else(|| table.schema.fields.iter().map(|f| f.name.clone()).collect());
+            let mut column_ids = Vec::with_capacity(names.len());
+            for name in &names {
+                match table.schema.field_index(name) {
+                    Some(idx) => column_ids.push(idx as u32),
+                    None => return job_error(StatusCode::NOT_FOUND, format!("Column '{}' not found", name), "COLUMN_NOT_FOUND"),
+                }
+            }
+            let limit = limit
+                .unwrap_or(narayana_api::grpc::DEFAULT_TRANSACTION_QUERY_LIMIT)
+                .min(narayana_api::grpc::MAX_TRANSACTION_QUERY_LIMIT);
+            let storage = state.db_manager.row_security().secure(
+                state.db_manager.temporal().at(state.storage.clone(), None),
+                claims.as_ref().map(|c| c.principal()).unwrap_or_default(),
+            );
+            match state.transactions.read(storage.as_ref(), id, &owner, table_id, &column_ids, limit).await {
+                Ok(columns) => {
+                    let row_count = columns.first().map(|c| c.len()).unwrap_or(0);
+                    Json(serde_json::json!({
+                        "transaction_id": txn_id,
+                        "fields": names,
+                        "columns": columns,
+                        "row_count": row_count,
+                    })).into_response()
+                }
+                Err(e) => transaction_error(&e),
+            }
+        }
+    }
+}
+
+/// Commit: write the staged rows table by table, in the order the tables were
+/// first written, with every table's write lock held. Either all tables are
+/// written or, when a write fails, none keep the transaction's rows.
+#[utoipa::path(
+    post,
+    path = "/api/v1/transactions/{txn_id}/commit",
+    tag = "transactions",
+    params(("txn_id" = u64, Path, description = "Transaction id")),
+    responses(
+        (status = 200, description = "Rows written per table", body = serde_json::Value),
+        (status = 404, description = "No such open transaction of the caller", body = ErrorResponse),
+        (status = 410, description = "Transaction timed out and was rolled back", body = ErrorResponse),
+        (status = 400, description = "Staged rows no longer fit a table, or a table is replicated", body = ErrorResponse),
+        (status = 500, description = "A write failed and was rolled back; PARTIAL_COMMIT names the tables the rollback failed on", body = ErrorResponse),
+        (status = 503, description = "Storage is read-only (low disk space)", body = ErrorResponse),
+        (status = 507, description = "Table, database or total storage quota exceeded", body = ErrorResponse),
+    ),
+)]
+async fn commit_transaction_handler(
+    State(state): State<ApiState>,
+    Path(txn_id): Path<u64>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+) -> impl IntoResponse {
+    let id = narayana_core::types::TransactionId(txn_id);
+    let result = state.transactions.commit(id, &transaction_owner(&claims), &InsertCommit::new(state.clone())).await;
+    match result {
+        Ok(committed) => {
+            info!("Committed transaction {}: {} rows", txn_id, committed.rows);
+            Json(serde_json::json!(committed)).into_response()
+        }
+        Err(e) => transaction_error(&e),
+    }
+}
+
+/// Commits write like `/tables/{id}/insert` does, but index the rows and
+/// announce them on the change feed only once every table is written
+pub struct InsertCommit {
+    state: ApiState,
+}
+
+impl InsertCommit {
+    pub fn new(state: ApiState) -> Self {
+        Self { state }
+    }
+}
+
+#[async_trait::async_trait]
+impl narayana_storage::transactions::CommitTarget for InsertCommit {
+    fn store(&self) -> &dyn ColumnStore {
+        self.state.storage.as_ref()
+    }
+
+    /// Rows shipped to peer regions can't be taken back
+    async fn validate(&self, table_id: TableId, _schema: &Schema, _columns: &[Column]) -> narayana_core::Result<()> {
+        if self.state.replication.is_replicated(table_id) {
+            return Err(narayana_core::Error::Transaction(format!(
+                "Table {} is replicated and can't be written in a transaction", table_id.0
+            )));
+        }
+        Ok(())
+    }
+
+    /// Versioned tables stamp each row's period column on the way in
+    async fn write(&self, guard: &narayana_storage::column_store::TableWriteGuard, columns: Vec<Column>) -> narayana_core::Result<Vec<Column>> {
+        let store = WriteLocked::new(self.state.storage.as_ref(), guard);
+        self.state.db_manager.temporal().write(&store, guard.table_id(), columns).await
+    }
+
+    fn publish(&self, table_id: TableId, schema: &Schema, columns: &[Column]) {
+        let row_count = columns.first().map(|c| c.len()).unwrap_or(0);
+        TOTAL_ROWS_INSERTED.fetch_add(row_count as u64, Ordering::Relaxed);
+        if let Err(e) = self.state.full_text.index_insert(table_id, schema, columns) {
+            warn!("Failed to update full-text index for table {}: {}", table_id.0, e);
+        }
+        if let Err(e) = self.state.spatial.index_insert(table_id, schema, columns) {
+            warn!("Failed to update spatial index for table {}: {}", table_id.0, e);
+        }
+        self.state.change_feed.publish_insert(table_id, schema, columns);
+    }
+
+    async fn reverted(&self, table_id: TableId, removed: &[usize]) {
+        self.state.db_manager.temporal().rows_removed(table_id, removed).await;
+    }
+}
+
+/// TransactionService for one caller that works like the REST transaction
+/// endpoints: statements read through the caller's row policies and
+/// commits go through `InsertCommit`
+pub fn transaction_service(
+    state: &ApiState,
+    claims: &Option<axum::Extension<crate::security::Claims>>,
+) -> narayana_api::grpc::RegistryTransactionService {
+    let storage = state.db_manager.row_security().secure(
+        state.db_manager.temporal().at(state.storage.clone(), None),
+        claims.as_ref().map(|c| c.principal()).unwrap_or_default(),
+    );
+    narayana_api::grpc::RegistryTransactionService::new(state.transactions.clone(), storage, transaction_owner(claims))
+        .with_commit_target(Arc::new(InsertCommit::new(state.clone())))
+}
+
+/// Roll back: discard the staged rows
+#[utoipa::path(
+    post,
+    path = "/api/v1/transactions/{txn_id}/rollback",
+    tag = "transactions",
+    params(("txn_id" = u64, Path, description = "Transaction id")),
+    responses(
+        (status = 200, description = "The transaction as it was rolled back", body = serde_json::Value),
+        (status = 404, description = "No such open transaction of the caller", body = ErrorResponse),
+        (status = 410, description = "Transaction had already timed out and was rolled back", body = ErrorResponse),
+    ),
+)]
+async fn rollback_transaction_handler(
+    State(state): State<ApiState>,
+    Path(txn_id): Path<u64>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+) -> impl IntoResponse {
+    match state.transactions.rollback(narayana_core::types::TransactionId(txn_id), &transaction_owner(&claims)) {
+        Ok(info) => Json(serde_json::json!(info)).into_response(),
+        Err(e) => transaction_error(&e),
+    }
+}
+
+/// Pivot columns into row objects keyed by field name
+pub(crate) fn columns_to_rows(fields: &[String], columns: &[Column], row_count: usize) -> serde_json::Value {
+    // A serialized column is `{"<Type>": [values...]}`
+    let values: Vec<Vec<serde_json::Value>> = columns
+        .iter()
+        .map(|col| match serde_json::to_value(col) {
+            Ok(serde_json::Value::Object(tagged)) => match tagged.into_iter().next() {
+                Some((_, serde_json::Value::Array(values))) => values,
+                _ => Vec::new(),
+            },
+            _ => Vec::new(),
+        })
+        .collect();
+    let rows = (0..row_count)
+        .map(|row| {
+            let mut object = serde_json::Map::with_capacity(fields.len());
+            for (field, column) in fields.iter().zip(&values) {
+                object.insert(field.clone(), column.get(row).cloned().unwrap_or(serde_json::Value::Null));
+            }
+            serde_json::Value::Object(object)
+        })
+        .collect();
+    serde_json::Value::Array(rows)
+}
+
+fn is_admin(claims: &Option<axum::Extension<crate::security::Claims>>) -> bool {
+    claims.as_ref().is_some_and(|c| c.roles.iter().any(|r| r == "admin"))
+}
+
+/// Admins see every query; other users only their own
+fn can_see_query(claims: &Option<axum::Extension<crate::security::Claims>>, query: &narayana_query::cancellation::QueryInfo) -> bool {
+    is_admin(claims) || claims.as_ref().is_some_and(|c| query.user.as_deref() == Some(c.sub.as_str()))
+}
+
+/// List running queries with their progress
+#[utoipa::path(
+    get,
+    path = "/api/v1/queries",
+    tag = "queries",
+    responses(
+        (status = 200, description = "Running queries visible to the caller", body = serde_json::Value),
+    ),
+)]
+async fn list_queries_handler(
+    State(state): State<ApiState>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+) -> impl IntoResponse {
+    let queries: Vec<_> = state.queries.list().into_iter()
+        .filter(|q| can_see_query(&claims, q))
+        .collect();
+    Json(serde_json::json!({
+        "count": queries.len(),
+        "queries": queries,
+    }))
+}
+
+/// Get one running query
+#[utoipa::path(
+    get,
+    path = "/api/v1/queries/{query_id}",
+    tag = "queries",
+    params(
+        ("query_id" = u64, Path, description = "Query id"),
+    ),
+    responses(
+        (status = 200, description = "Query status", body = serde_json::Value),
+        (status = 404, description = "Query not found", body = ErrorResponse),
+    ),
+)]
+async fn get_query_handler(
+    State(state): State<ApiState>,
+    Path(query_id): Path<u64>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+) -> impl IntoResponse {
+    match state.queries.get(query_id).filter(|q| can_see_query(&claims, q)) {
+        Some(query) => (StatusCode::OK, Json(query)).into_response(),
+        None => {
+            let response = Json(ErrorResponse {
+                error: format!("Query {} is not running", query_id),
+                code: "QUERY_NOT_FOUND".to_string(),
+            });
+            (StatusCode::NOT_FOUND, response).into_response()
+        }
+    }
+}
+
+/// Cancel a running query; the executor stops at its next cancellation check
+#[utoipa::path(
+    delete,
+    path = "/api/v1/queries/{query_id}",
+    tag = "queries",
+    params(
+        ("query_id" = u64, Path, description = "Query id"),
+    ),
+    responses(
+        (status = 202, description = "Cancellation requested", body = serde_json::Value),
+        (status = 404, description = "Query not found", body = ErrorResponse),
+    ),
+)]
+async fn cancel_query_handler(
+    State(state): State<ApiState>,
+    Path(query_id): Path<u64>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+) -> impl IntoResponse {
+    let visible = state.queries.get(query_id).filter(|q| can_see_query(&claims, q));
+    if visible.is_none() || !state.queries.cancel(query_id) {
+        let response = Json(ErrorResponse {
+            error: format!("Query {} is not running", query_id),
+            code: "QUERY_NOT_FOUND".to_string(),
+        });
+        return (StatusCode::NOT_FOUND, response).into_response();
+    }
+    info!("Cancellation requested for query {}", query_id);
+    (StatusCode::ACCEPTED, Json(serde_json::json!({
+        "success": true,
+        "query_id": query_id,
+        "message": "Cancellation requested",
+    }))).into_response()
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct NlqRequest {
+    /// Question about the tables in the `default` database
+    pub question: String,
+    /// Rows to return (default 100, at most 1000)
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// Also have the LLM phrase an answer from the rows
+    #[serde(default = "default_summarize")]
+    pub summarize: bool,
+}
+
+fn default_summarize() -> bool {
+    true
+}
+
+/// Answer a natural-language question: the LLM translates it into a query over
+/// the table catalog, which runs read-only under the caller's row policies.
+/// The generated query, its plan and SQL are returned with the rows.
+#[utoipa::path(
+    post,
+    path = "/api/v1/nlq",
+    tag = "nlq",
+    request_body = NlqRequest,
+    responses(
+        (status = 200, description = "Rows, generated query and optional answer", body = serde_json::Value),
+        (status = 400, description = "Empty or overlong question", body = ErrorResponse),
+        (status = 403, description = "Table is served through an output profile", body = ErrorResponse),
+        (status = 409, description = "Query was cancelled", body = ErrorResponse),
+        (status = 422, description = "Question could not be translated into a valid query", body = serde_json::Value),
+        (status = 502, description = "LLM request failed", body = ErrorResponse),
+        (status = 503, description = "No LLM provider configured", body = ErrorResponse),
+    ),
+)]
+async fn nlq_handler(
+    State(state): State<ApiState>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+    Json(request): Json<NlqRequest>,
+) -> impl IntoResponse {
+    use crate::nlq::NlqError;
+    let principal = claims.as_ref().map(|c| c.principal()).unwrap_or_default();
+    let query = state.queries.register(
+        format!("natural-language query: {}", request.question.chars().take(80).collect::<String>()),
+        claims.map(|axum::Extension(claims)| claims.sub),
+    );
+    match state.nlq.ask(&request.question, request.limit, request.summarize, principal, query.context()).await {
+        Ok(answer) => {
+            info!("Answered natural-language query {} with {} rows", query.id(), answer.row_count);
+            (StatusCode::OK, Json(answer)).into_response()
+        }
+        Err(NlqError::InvalidQuestion(e)) => job_error(StatusCode::BAD_REQUEST, e, "INVALID_QUESTION"),
+        Err(e @ NlqError::NoProvider) => job_error(StatusCode::SERVICE_UNAVAILABLE, e.to_string(), "LLM_UNAVAILABLE"),
+        Err(e @ NlqError::Model(_)) => {
+            warn!("Natural-language query {} failed: {}", query.id(), e);
+            job_error(StatusCode::BAD_GATEWAY, e.to_string(), "LLM_ERROR")
+        }
+        Err(NlqError::Unanswerable { message, reply }) => (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({
+            "error": message,
+            "code": "UNANSWERABLE_QUESTION",
+            "reply": reply,
+        }))).into_response(),
+        Err(NlqError::Forbidden(e)) => job_error(StatusCode::FORBIDDEN, e, "OUTPUT_PROFILE_APPLIES"),
+        Err(NlqError::Storage(e)) if narayana_query::cancellation::is_cancelled_error(&e) => {
+            job_error(StatusCode::CONFLICT, format!("Query {} was cancelled", query.id()), "QUERY_CANCELLED")
+        }
+        Err(NlqError::Storage(e)) => {
+            error!("Natural-language query {} failed: {}", query.id(), e);
+            job_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                sanitize_error_message(&format!("Failed to run query: {}", e), "QUERY_ERROR"),
+                "QUERY_ERROR",
+            )
+        }
+    }
+}
+
+/// Longest partial query accepted for completion
+const MAX_AUTOCOMPLETE_QUERY_BYTES: usize = 64 * 1024;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AutocompleteRequest {
+    /// Partial query being typed
+    pub query: String,
+    /// Byte offset of the cursor in `query` (defaults to its end)
+    #[serde(default)]
+    pub cursor: Option<usize>,
+    /// Suggestions to return (default 20)
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// Complete the word before the cursor of a partial query: ranked keywords,
+/// tables in the `default` database and their columns with types. Replacing
+/// `replace_start..replace_end` of the query with a suggestion's `text`
+/// applies it.
+#[utoipa::path(
+    post,
+    path = "/api/v1/autocomplete",
+    tag = "queries",
+    request_body = AutocompleteRequest,
+    responses(
+        (status = 200, description = "Replacement range and ranked suggestions", body = serde_json::Value),
+        (status = 400, description = "Query too long", body = ErrorResponse),
+    ),
+)]
+async fn autocomplete_handler(
+    State(state): State<ApiState>,
+    Json(request): Json<AutocompleteRequest>,
+) -> impl IntoResponse {
+    if request.query.len() > MAX_AUTOCOMPLETE_QUERY_BYTES {
+        return job_error(
+            StatusCode::BAD_REQUEST,
+            format!("query must be at most {} bytes", MAX_AUTOCOMPLETE_QUERY_BYTES),
+            "INVALID_QUERY",
+        );
+    }
+
+    // Complete against the current catalog, never the protected users table
+    let tables = state.db_manager.get_database_by_name("default")
+        .and_then(|db_id| state.db_manager.list_tables(db_id).ok())
+        .unwrap_or_default();
+    state.autocomplete.replace_schemas(
+        tables.into_iter()
+            .filter(|t| !is_protected_users_table_name(&t.name))
+            .map(|t| (t.name, t.schema))
+            .collect(),
+    );
+
+    let cursor = request.cursor.unwrap_or(request.query.len());
+    match state.autocomplete.complete(&request.query, cursor) {
+        Ok(mut completions) => {
+            if let Some(limit) = request.limit {
+                completions.suggestions.truncate(limit);
+            }
+            (StatusCode::OK, Json(completions)).into_response()
+        }
+        Err(e) => job_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            sanitize_error_message(&format!("Failed to complete query: {}", e), "AUTOCOMPLETE_ERROR"),
+            "AUTOCOMPLETE_ERROR",
+        ),
+    }
+}
+
+/// Get query statistics
+#[utoipa::path(
+    get,
+    path = "/api/v1/stats",
+    tag = "system",
+    responses(
+        (status = 200, description = "Query statistics", body = StatsResponse),
+    ),
+)]
 async fn stats_handler(State(state): State<ApiState>) -> impl IntoResponse {
     // Get real statistics from atomic counters and query learning engine
     let total_queries = TOTAL_QUERIES.load(Ordering::Relaxed);
@@ -2335,40 +4024,44 @@ async fn stats_handler(State(state): State<ApiState>) -> impl IntoResponse {
 }
 
 /// Serve static files (UI) - fallback handler
-async fn serve_static_handler(uri: Uri) -> impl IntoResponse {
-    use crate::static_files::serve_static;
-    serve_static(uri).await
+async fn serve_static_handler(
+    State(state): State<ApiState>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    state.static_files.serve(&method, &uri, &headers).await
 }
 
 // Cognitive Brain API handlers
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 struct CreateBrainRequest {
     brain_id: String,
     memory_types: Option<Vec<String>>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct CreateBrainResponse {
     success: bool,
     brain_id: String,
     message: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 struct CreateThoughtRequest {
     content: serde_json::Value,
     priority: f64,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct CreateThoughtResponse {
     success: bool,
     thought_id: String,
     message: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 struct StoreExperienceRequest {
     observation: serde_json::Value,
     action: Option<serde_json::Value>,
@@ -2376,14 +4069,14 @@ struct StoreExperienceRequest {
     reward: Option<f64>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct StoreExperienceResponse {
     success: bool,
     experience_id: String,
     message: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct MemoryResponse {
     id: String,
     memory_type: String,
@@ -2391,39 +4084,49 @@ struct MemoryResponse {
     created_at: u64,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct GetMemoriesResponse {
     memories: Vec<MemoryResponse>,
     count: usize,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct GetThoughtsResponse {
     thoughts: Vec<serde_json::Value>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct GetMemoryAccessesResponse {
     accesses: Vec<serde_json::Value>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct GetThoughtTimelineResponse {
     timeline: Vec<serde_json::Value>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct GetConflictsResponse {
+    #[schema(value_type = Vec<Object>)]
     conflicts: Vec<Conflict>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct CancelThoughtResponse {
     success: bool,
     message: String,
 }
 
 /// Create a cognitive brain for a robot
+#[utoipa::path(
+    post,
+    path = "/api/v1/brains",
+    tag = "brains",
+    request_body = CreateBrainRequest,
+    responses(
+        (status = 200, description = "Brain created", body = CreateBrainResponse),
+    ),
+)]
 async fn create_brain_handler(
     State(state): State<ApiState>,
     Json(request): Json<CreateBrainRequest>,
@@ -2441,6 +4144,20 @@ async fn create_brain_handler(
 }
 
 /// Create a thought (robot decision)
+#[utoipa::path(
+    post,
+    path = "/api/v1/brains/{brain_id}/thoughts",
+    tag = "brains",
+    params(
+        ("brain_id" = String, Path, description = "Brain id"),
+    ),
+    request_body = CreateThoughtRequest,
+    responses(
+        (status = 200, description = "Thought created", body = CreateThoughtResponse),
+        (status = 400, description = "Invalid thought", body = ErrorResponse),
+        (status = 500, description = "Brain error", body = ErrorResponse),
+    ),
+)]
 async fn create_thought_handler(
     State(state): State<ApiState>,
     Path(brain_id): Path<String>,
@@ -2524,6 +4241,20 @@ async fn create_thought_handler(
 }
 
 /// Store an experience (robot learning)
+#[utoipa::path(
+    post,
+    path = "/api/v1/brains/{brain_id}/experiences",
+    tag = "brains",
+    params(
+        ("brain_id" = String, Path, description = "Brain id"),
+    ),
+    request_body = StoreExperienceRequest,
+    responses(
+        (status = 200, description = "Experience stored", body = StoreExperienceResponse),
+        (status = 400, description = "Invalid experience", body = ErrorResponse),
+        (status = 500, description = "Brain error", body = ErrorResponse),
+    ),
+)]
 async fn store_experience_handler(
     State(state): State<ApiState>,
     Path(brain_id): Path<String>,
@@ -2605,6 +4336,19 @@ async fn store_experience_handler(
 }
 
 /// Get thoughts (Thought Debugger)
+#[utoipa::path(
+    get,
+    path = "/api/v1/brains/{brain_id}/thoughts/list",
+    tag = "brains",
+    params(
+        ("brain_id" = String, Path, description = "Brain id"),
+        ("state" = Option<String>, Query, description = "Only thoughts in this state"),
+    ),
+    responses(
+        (status = 200, description = "Thoughts", body = GetThoughtsResponse),
+        (status = 400, description = "Invalid brain id or state", body = ErrorResponse),
+    ),
+)]
 async fn get_thoughts_handler(
     State(state): State<ApiState>,
     Path(brain_id): Path<String>,
@@ -2646,6 +4390,18 @@ async fn get_thoughts_handler(
 }
 
 /// Get memory accesses (Thought Debugger)
+#[utoipa::path(
+    get,
+    path = "/api/v1/brains/{brain_id}/memory-accesses",
+    tag = "brains",
+    params(
+        ("brain_id" = String, Path, description = "Brain id"),
+    ),
+    responses(
+        (status = 200, description = "Recent memory accesses", body = GetMemoryAccessesResponse),
+        (status = 400, description = "Invalid brain id", body = ErrorResponse),
+    ),
+)]
 async fn get_memory_accesses_handler(
     State(state): State<ApiState>,
     Path(brain_id): Path<String>,
@@ -2672,6 +4428,18 @@ async fn get_memory_accesses_handler(
 }
 
 /// Get thought timeline (Thought Debugger)
+#[utoipa::path(
+    get,
+    path = "/api/v1/brains/{brain_id}/thought-timeline",
+    tag = "brains",
+    params(
+        ("brain_id" = String, Path, description = "Brain id"),
+    ),
+    responses(
+        (status = 200, description = "Thought lifecycle events", body = GetThoughtTimelineResponse),
+        (status = 400, description = "Invalid brain id", body = ErrorResponse),
+    ),
+)]
 async fn get_thought_timeline_handler(
     State(state): State<ApiState>,
     Path(brain_id): Path<String>,
@@ -2709,6 +4477,18 @@ async fn get_thought_timeline_handler(
 }
 
 /// Get conflicts (Thought Debugger)
+#[utoipa::path(
+    get,
+    path = "/api/v1/brains/{brain_id}/conflicts",
+    tag = "brains",
+    params(
+        ("brain_id" = String, Path, description = "Brain id"),
+    ),
+    responses(
+        (status = 200, description = "Detected conflicts", body = GetConflictsResponse),
+        (status = 400, description = "Invalid brain id", body = ErrorResponse),
+    ),
+)]
 async fn get_conflicts_handler(
     State(state): State<ApiState>,
     Path(brain_id): Path<String>,
@@ -2725,7 +4505,206 @@ async fn get_conflicts_handler(
     Json(GetConflictsResponse { conflicts }).into_response()
 }
 
+/// Persisted first-person narrative summaries, newest first
+#[utoipa::path(
+    get,
+    path = "/api/v1/brains/{brain_id}/narratives",
+    tag = "brains",
+    params(
+        ("brain_id" = String, Path, description = "Brain id"),
+        ("cpl_id" = Option<String>, Query, description = "Read from this CPL's brain instead"),
+        ("since" = Option<u64>, Query, description = "Only summaries of periods ending at or after this unix time"),
+        ("until" = Option<u64>, Query, description = "Only summaries of periods starting at or before this unix time"),
+        ("limit" = Option<usize>, Query, description = "Maximum summaries (default 30, max 1000)"),
+    ),
+    responses(
+        (status = 200, description = "Narrative summaries", body = serde_json::Value),
+        (status = 400, description = "Invalid brain id or query", body = ErrorResponse),
+        (status = 404, description = "CPL not found", body = ErrorResponse),
+    ),
+)]
+async fn get_narratives_handler(
+    State(state): State<ApiState>,
+    Path(brain_id): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> impl IntoResponse {
+    // Validate brain_id
+    if brain_id.trim().is_empty() || brain_id.len() > 255 {
+        return (StatusCode::BAD_REQUEST, Json(ErrorResponse {
+            error: "Invalid brain ID".to_string(),
+            code: "INVALID_BRAIN_ID".to_string(),
+        })).into_response();
+    }
+
+    let mut bounds = [None, None, None];
+    for (slot, name) in bounds.iter_mut().zip(["since", "until", "limit"]) {
+        if let Some(value) = params.get(name) {
+            match value.parse::<u64>() {
+                Ok(value) => *slot = Some(value),
+                Err(_) => {
+                    return (StatusCode::BAD_REQUEST, Json(ErrorResponse {
+                        error: format!("Invalid {}: {}", name, value),
+                        code: "INVALID_QUERY".to_string(),
+                    })).into_response();
+                }
+            }
+        }
+    }
+    let [since, until, limit] = bounds;
+    let limit = limit.unwrap_or(30).min(1000) as usize;
+
+    let brain = match params.get("cpl_id") {
+        Some(cpl_id) => match state.cpl_manager.as_ref().and_then(|manager| manager.get_cpl(cpl_id)) {
+            Some(cpl) => cpl.brain().clone(),
+            None => {
+                return (StatusCode::NOT_FOUND, Json(ErrorResponse {
+                    error: format!("CPL {} not found", cpl_id),
+                    code: "CPL_NOT_FOUND".to_string(),
+                })).into_response();
+            }
+        },
+        None => state.brain.clone(),
+    };
+
+    let narratives = narayana_storage::narrative_generator::narrative_summaries(&brain, since, until, limit);
+    Json(serde_json::json!({ "narratives": narratives })).into_response()
+}
+
+/// Export the brain as a versioned bundle (thoughts, memories, RL policies, traits)
+#[utoipa::path(
+    get,
+    path = "/api/v1/brains/{brain_id}/bundle",
+    tag = "brains",
+    params(
+        ("brain_id" = String, Path, description = "Brain id"),
+        ("label" = Option<String>, Query, description = "Label stored in the bundle"),
+    ),
+    responses(
+        (status = 200, description = "Brain bundle", body = serde_json::Value),
+        (status = 400, description = "Invalid brain id", body = ErrorResponse),
+        (status = 500, description = "Export failed", body = ErrorResponse),
+    ),
+)]
+async fn export_brain_bundle_handler(
+    State(state): State<ApiState>,
+    Path(brain_id): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> impl IntoResponse {
+    // Validate brain_id
+    if brain_id.trim().is_empty() || brain_id.len() > 255 {
+        return (StatusCode::BAD_REQUEST, Json(ErrorResponse {
+            error: "Invalid brain ID".to_string(),
+            code: "INVALID_BRAIN_ID".to_string(),
+        })).into_response();
+    }
+
+    let label = params.get("label").map(|label| label.chars().take(255).collect());
+    match narayana_storage::brain_bundle::BrainBundle::export(&state.brain, None, label) {
+        Ok(bundle) => Json(bundle).into_response(),
+        Err(e) => {
+            error!("Failed to export brain bundle: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ErrorResponse {
+                error: sanitize_error_message(&format!("Failed to export brain bundle: {}", e), "BUNDLE_EXPORT_ERROR"),
+                code: "BUNDLE_EXPORT_ERROR".to_string(),
+            })).into_response()
+        }
+    }
+}
+
+/// Import a brain bundle, resolving ID conflicts by policy
+#[utoipa::path(
+    post,
+    path = "/api/v1/brains/{brain_id}/bundle",
+    tag = "brains",
+    params(
+        ("brain_id" = String, Path, description = "Brain id"),
+        ("conflict" = Option<String>, Query, description = "skip (default), overwrite, remap or fail"),
+        ("fresh_ids" = Option<bool>, Query, description = "Give every imported item a new id"),
+        ("include_rl" = Option<bool>, Query, description = "Import RL policies (default true)"),
+        ("include_traits" = Option<bool>, Query, description = "Import genome and traits (default true)"),
+    ),
+    request_body(content = serde_json::Value, description = "Brain bundle", content_type = "application/json"),
+    responses(
+        (status = 200, description = "Import report", body = serde_json::Value),
+        (status = 400, description = "Invalid brain id, options or bundle", body = ErrorResponse),
+        (status = 409, description = "Conflict with policy fail", body = ErrorResponse),
+    ),
+)]
+async fn import_brain_bundle_handler(
+    State(state): State<ApiState>,
+    Path(brain_id): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+    body: String,
+) -> impl IntoResponse {
+    use narayana_storage::brain_bundle::{BrainBundle, ConflictPolicy, ImportOptions};
+
+    // Validate brain_id
+    if brain_id.trim().is_empty() || brain_id.len() > 255 {
+        return (StatusCode::BAD_REQUEST, Json(ErrorResponse {
+            error: "Invalid brain ID".to_string(),
+            code: "INVALID_BRAIN_ID".to_string(),
+        })).into_response();
+    }
+
+    let invalid = |message: String| (StatusCode::BAD_REQUEST, Json(ErrorResponse {
+        error: message,
+        code: "INVALID_BUNDLE_OPTIONS".to_string(),
+    })).into_response();
+    let flag = |name: &str, default: bool| match params.get(name).map(|v| v.as_str()) {
+        None => Ok(default),
+        Some("true") => Ok(true),
+        Some("false") => Ok(false),
+        Some(other) => Err(format!("Invalid {}: {}", name, other)),
+    };
+    let conflict = match params.get("conflict") {
+        None => ConflictPolicy::default(),
+        Some(conflict) => match serde_json::from_value(serde_json::Value::String(conflict.to_lowercase())) {
+            Ok(conflict) => conflict,
+            Err(_) => return invalid(format!("Invalid conflict policy: {}", conflict)),
+        },
+    };
+    let options = match (flag("fresh_ids", false), flag("include_rl", true), flag("include_traits", true)) {
+        (Ok(fresh_ids), Ok(include_rl), Ok(include_traits)) => ImportOptions { conflict, fresh_ids, include_rl, include_traits },
+        (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => return invalid(e),
+    };
+
+    let bundle = match BrainBundle::from_json(&body) {
+        Ok(bundle) => bundle,
+        Err(e) => {
+            return (StatusCode::BAD_REQUEST, Json(ErrorResponse {
+                error: sanitize_error_message(&e.to_string(), "INVALID_BUNDLE"),
+                code: "INVALID_BUNDLE".to_string(),
+            })).into_response();
+        }
+    };
+
+    match bundle.import_into(&state.brain, None, &options) {
+        Ok(report) => Json(report).into_response(),
+        Err(e) => {
+            warn!("Brain bundle import rejected: {}", e);
+            (StatusCode::CONFLICT, Json(ErrorResponse {
+                error: sanitize_error_message(&format!("Brain bundle import failed: {}", e), "BUNDLE_IMPORT_CONFLICT"),
+                code: "BUNDLE_IMPORT_CONFLICT".to_string(),
+            })).into_response()
+        }
+    }
+}
+
 /// Cancel thought (Thought Debugger)
+#[utoipa::path(
+    post,
+    path = "/api/v1/brains/{brain_id}/thoughts/cancel/{thought_id}",
+    tag = "brains",
+    params(
+        ("brain_id" = String, Path, description = "Brain id"),
+        ("thought_id" = String, Path, description = "Thought id"),
+    ),
+    responses(
+        (status = 200, description = "Thought cancelled", body = CancelThoughtResponse),
+        (status = 400, description = "Invalid id", body = ErrorResponse),
+        (status = 500, description = "Brain error", body = ErrorResponse),
+    ),
+)]
 async fn cancel_thought_handler(
     State(state): State<ApiState>,
     Path((brain_id, thought_id)): Path<(String, String)>,
@@ -2759,6 +4738,19 @@ async fn cancel_thought_handler(
 }
 
 /// Get memories (robot recall)
+#[utoipa::path(
+    get,
+    path = "/api/v1/brains/{brain_id}/memories",
+    tag = "brains",
+    params(
+        ("brain_id" = String, Path, description = "Brain id"),
+        ("type" = Option<String>, Query, description = "Only memories of this type"),
+    ),
+    responses(
+        (status = 200, description = "Stored memories", body = GetMemoriesResponse),
+        (status = 400, description = "Invalid brain id or memory type", body = ErrorResponse),
+    ),
+)]
 async fn get_memories_handler(
     State(state): State<ApiState>,
     Path(brain_id): Path<String>,
@@ -2922,13 +4914,13 @@ async fn get_memories_handler(
     })).into_response()
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct GetBrainsResponse {
     brains: Vec<BrainInfo>,
     count: usize,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, ToSchema)]
 struct BrainInfo {
     brain_id: String,
     memory_types: Vec<String>,
@@ -2936,6 +4928,14 @@ struct BrainInfo {
 }
 
 /// Get all brains
+#[utoipa::path(
+    get,
+    path = "/api/v1/brains",
+    tag = "brains",
+    responses(
+        (status = 200, description = "Registered brains", body = GetBrainsResponse),
+    ),
+)]
 async fn get_brains_handler(State(state): State<ApiState>) -> impl IntoResponse {
     info!("Getting all brains");
     
@@ -2961,13 +4961,13 @@ async fn get_brains_handler(State(state): State<ApiState>) -> impl IntoResponse
     })).into_response()
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct GetWorkersResponse {
     workers: Vec<WorkerInfo>,
     count: usize,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, ToSchema)]
 struct WorkerInfo {
     worker_id: String,
     name: String,
@@ -2977,6 +4977,14 @@ struct WorkerInfo {
 }
 
 /// Get all workers
+#[utoipa::path(
+    get,
+    path = "/api/v1/workers",
+    tag = "workers",
+    responses(
+        (status = 200, description = "Deployed workers", body = GetWorkersResponse),
+    ),
+)]
 async fn get_workers_handler(State(state): State<ApiState>) -> impl IntoResponse {
     info!("Getting all workers");
     
@@ -2992,7 +5000,7 @@ async fn get_workers_handler(State(state): State<ApiState>) -> impl IntoResponse
     })).into_response()
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct SystemStatsResponse {
     tables: u64,
     brains: usize,
@@ -3004,7 +5012,41 @@ struct SystemStatsResponse {
     total_rows_inserted: u64,
 }
 
+/// Get per-IP guard statistics (tracked addresses, greylist, blocked counts)
+#[utoipa::path(
+    get,
+    path = "/api/v1/security/ip-guard",
+    tag = "system",
+    responses(
+        (status = 200, description = "Per-IP limiter and greylist state", body = serde_json::Value),
+    ),
+)]
+async fn get_ip_guard_stats_handler(State(state): State<ApiState>) -> impl IntoResponse {
+    Json(state.ip_guard.stats())
+}
+
+/// Get per-route admission control state (limits, in flight, queue depth, rejections)
+#[utoipa::path(
+    get,
+    path = "/api/v1/system/admission",
+    tag = "system",
+    responses(
+        (status = 200, description = "Concurrency and queue state per route group", body = serde_json::Value),
+    ),
+)]
+async fn get_admission_stats_handler(State(state): State<ApiState>) -> impl IntoResponse {
+    Json(state.admission.stats())
+}
+
 /// Get comprehensive system statistics
+#[utoipa::path(
+    get,
+    path = "/api/v1/system/stats",
+    tag = "system",
+    responses(
+        (status = 200, description = "Table, brain, worker and query totals", body = SystemStatsResponse),
+    ),
+)]
 async fn get_system_stats_handler(State(state): State<ApiState>) -> impl IntoResponse {
     info!("Getting system stats");
     
@@ -3046,13 +5088,17 @@ async fn get_system_stats_handler(State(state): State<ApiState>) -> impl IntoRes
 
 // CPL API handlers
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 struct CreateCPLRequest {
     config: Option<CPLConfigRequest>,
     brain_id: Option<String>, // Optional: use existing brain or create new
+    /// Unique name for the CPL
+    name: Option<String>,
+    /// Trait genome (as returned in a CPL's config) to start from instead of a random one
+    genome: Option<serde_json::Value>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 struct CPLConfigRequest {
     loop_interval_ms: Option<u64>,
     enable_global_workspace: Option<bool>,
@@ -3061,6 +5107,8 @@ struct CPLConfigRequest {
     working_memory_capacity: Option<usize>,
     enable_attention: Option<bool>,
     enable_narrative: Option<bool>,
+    narrative_summary_interval_secs: Option<u64>,
+    narrative_summary_max_words: Option<usize>,
     enable_memory_bridge: Option<bool>,
     enable_persistence: Option<bool>,
     persistence_dir: Option<String>,
@@ -3081,46 +5129,67 @@ struct CPLConfigRequest {
     // Audio configuration
     enable_audio: Option<bool>,
     audio_config: Option<serde_json::Value>,
+    // Resource budget
+    max_iterations: Option<u64>,
+    max_memories: Option<usize>,
+    max_iteration_ms: Option<u64>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct CreateCPLResponse {
     success: bool,
     cpl_id: String,
     message: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct CPLInfo {
     cpl_id: String,
+    name: Option<String>,
     is_running: bool,
+    /// Loop not stalled or out of budget, and every world adapter healthy
+    healthy: bool,
     config: serde_json::Value,
+    /// Lifecycle state, iterations, loop timing and budget usage
+    health: serde_json::Value,
+    world_adapters: serde_json::Value,
+}
+
+fn cpl_info(manager: &narayana_storage::cpl_manager::CPLManager, cpl_id: &str) -> Option<CPLInfo> {
+    let cpl = manager.get_cpl(cpl_id)?;
+    let status = manager.status(cpl_id)?;
+    Some(CPLInfo {
+        cpl_id: status.cpl_id,
+        name: status.name,
+        is_running: cpl.is_running(),
+        healthy: status.healthy,
+        config: serde_json::to_value(cpl.config()).unwrap_or_else(|_| serde_json::json!({})),
+        health: serde_json::to_value(&status.health).unwrap_or_else(|_| serde_json::json!({})),
+        world_adapters: serde_json::to_value(&status.world_adapters).unwrap_or_else(|_| serde_json::json!([])),
+    })
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct GetCPLsResponse {
     cpls: Vec<CPLInfo>,
     count: usize,
 }
 
 /// Get all CPL instances
+#[utoipa::path(
+    get,
+    path = "/api/v1/cpls",
+    tag = "cpls",
+    responses(
+        (status = 200, description = "Conscience persistent loops", body = GetCPLsResponse),
+        (status = 503, description = "CPL manager not configured", body = ErrorResponse),
+    ),
+)]
 async fn get_cpls_handler(State(state): State<ApiState>) -> impl IntoResponse {
     if let Some(ref cpl_manager) = state.cpl_manager {
-        let cpl_ids = cpl_manager.list_cpls();
-        let mut cpls = Vec::new();
-        
-        for cpl_id in cpl_ids {
-            if let Some(cpl) = cpl_manager.get_cpl(&cpl_id) {
-                let is_running = cpl.is_running();
-                // Get config from CPL
-                let config = serde_json::to_value(cpl.config()).unwrap_or_else(|_| serde_json::json!({}));
-                cpls.push(CPLInfo {
-                    cpl_id,
-                    is_running,
-                    config,
-                });
-            }
-        }
+        let cpls: Vec<CPLInfo> = cpl_manager.list_status().iter()
+            .filter_map(|status| cpl_info(cpl_manager, &status.cpl_id))
+            .collect();
         
         let count = cpls.len();
         (StatusCode::OK, Json(GetCPLsResponse {
@@ -3136,6 +5205,17 @@ async fn get_cpls_handler(State(state): State<ApiState>) -> impl IntoResponse {
 }
 
 /// Create a new CPL instance
+#[utoipa::path(
+    post,
+    path = "/api/v1/cpls",
+    tag = "cpls",
+    request_body = CreateCPLRequest,
+    responses(
+        (status = 200, description = "CPL created", body = CreateCPLResponse),
+        (status = 500, description = "Failed to create the CPL", body = ErrorResponse),
+        (status = 503, description = "CPL manager not configured", body = ErrorResponse),
+    ),
+)]
 async fn create_cpl_handler(
     State(state): State<ApiState>,
     Json(request): Json<CreateCPLRequest>,
@@ -3152,6 +5232,8 @@ async fn create_cpl_handler(
             if let Some(v) = config_req.working_memory_capacity { config.working_memory_capacity = v; }
             if let Some(v) = config_req.enable_attention { config.enable_attention = v; }
             if let Some(v) = config_req.enable_narrative { config.enable_narrative = v; }
+            if let Some(v) = config_req.narrative_summary_interval_secs { config.narrative_schedule.interval_secs = v; }
+            if let Some(v) = config_req.narrative_summary_max_words { config.narrative_schedule.max_words = v.clamp(10, 2000); }
             if let Some(v) = config_req.enable_memory_bridge { config.enable_memory_bridge = v; }
             if let Some(v) = config_req.enable_persistence { config.enable_persistence = v; }
             if config_req.persistence_dir.is_some() { config.persistence_dir = config_req.persistence_dir; }
@@ -3172,9 +5254,24 @@ async fn create_cpl_handler(
             // Audio configuration
             if let Some(v) = config_req.enable_audio { config.enable_audio = v; }
             if config_req.audio_config.is_some() { config.audio_config = config_req.audio_config; }
+            // Resource budget
+            if config_req.max_iterations.is_some() { config.budget.max_iterations = config_req.max_iterations; }
+            if config_req.max_memories.is_some() { config.budget.max_memories = config_req.max_memories; }
+            if config_req.max_iteration_ms.is_some() { config.budget.max_iteration_ms = config_req.max_iteration_ms; }
+        }
+        if let Some(genome) = request.genome {
+            match serde_json::from_value(genome) {
+                Ok(genome) => config.genome = Some(genome),
+                Err(e) => {
+                    return (StatusCode::BAD_REQUEST, Json(ErrorResponse {
+                        error: format!("Invalid genome: {}", e),
+                        code: "INVALID_GENOME".to_string(),
+                    })).into_response();
+                }
+            }
         }
         
-        match cpl_manager.spawn_cpl(Some(config)).await {
+        match cpl_manager.create_cpl(request.name, Some(config)).await {
             Ok(cpl_id) => {
                 let cpl_id_clone = cpl_id.clone();
                 (StatusCode::OK, Json(CreateCPLResponse {
@@ -3199,19 +5296,26 @@ async fn create_cpl_handler(
 }
 
 /// Get a specific CPL instance
+#[utoipa::path(
+    get,
+    path = "/api/v1/cpls/{cpl_id}",
+    tag = "cpls",
+    params(
+        ("cpl_id" = String, Path, description = "CPL id"),
+    ),
+    responses(
+        (status = 200, description = "CPL status", body = serde_json::Value),
+        (status = 404, description = "CPL not found", body = ErrorResponse),
+        (status = 503, description = "CPL manager not configured", body = ErrorResponse),
+    ),
+)]
 async fn get_cpl_handler(
     State(state): State<ApiState>,
     Path(cpl_id): Path<String>,
 ) -> impl IntoResponse {
     if let Some(ref cpl_manager) = state.cpl_manager {
-        if let Some(cpl) = cpl_manager.get_cpl(&cpl_id) {
-            let is_running = cpl.is_running();
-            let config = serde_json::to_value(cpl.config()).unwrap_or_else(|_| serde_json::json!({}));
-            (StatusCode::OK, Json(CPLInfo {
-                cpl_id,
-                is_running,
-                config,
-            })).into_response()
+        if let Some(info) = cpl_info(cpl_manager, &cpl_id) {
+            (StatusCode::OK, Json(info)).into_response()
         } else {
             (StatusCode::NOT_FOUND, Json(ErrorResponse {
                 error: format!("CPL {} not found", cpl_id),
@@ -3227,12 +5331,32 @@ async fn get_cpl_handler(
 }
 
 
-/// Delete a CPL instance
+/// Delete a CPL instance, stopping it and its world adapters first
+#[utoipa::path(
+    delete,
+    path = "/api/v1/cpls/{cpl_id}",
+    tag = "cpls",
+    params(
+        ("cpl_id" = String, Path, description = "CPL id"),
+    ),
+    responses(
+        (status = 200, description = "CPL deleted", body = serde_json::Value),
+        (status = 404, description = "CPL not found", body = ErrorResponse),
+        (status = 500, description = "Failed to delete the CPL", body = ErrorResponse),
+        (status = 503, description = "CPL manager not configured", body = ErrorResponse),
+    ),
+)]
 async fn delete_cpl_handler(
     State(state): State<ApiState>,
     Path(cpl_id): Path<String>,
 ) -> impl IntoResponse {
     if let Some(ref cpl_manager) = state.cpl_manager {
+        if cpl_manager.get_cpl(&cpl_id).is_none() {
+            return (StatusCode::NOT_FOUND, Json(ErrorResponse {
+                error: format!("CPL {} not found", cpl_id),
+                code: "CPL_NOT_FOUND".to_string(),
+            })).into_response();
+        }
         match cpl_manager.remove_cpl(&cpl_id).await {
             Ok(_) => {
                 (StatusCode::OK, Json(serde_json::json!({
@@ -3257,13 +5381,13 @@ async fn delete_cpl_handler(
 
 // Webhook API handlers
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct GetWebhooksResponse {
     webhooks: Vec<WebhookInfo>,
     count: usize,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, ToSchema)]
 struct WebhookInfo {
     id: String,
     name: String,
@@ -3280,6 +5404,14 @@ struct WebhookInfo {
 }
 
 /// Get all webhooks
+#[utoipa::path(
+    get,
+    path = "/api/v1/webhooks",
+    tag = "webhooks",
+    responses(
+        (status = 200, description = "Registered webhooks", body = GetWebhooksResponse),
+    ),
+)]
 async fn get_webhooks_handler(State(state): State<ApiState>) -> impl IntoResponse {
     info!("Getting all webhooks");
     
@@ -3296,9 +5428,9 @@ async fn get_webhooks_handler(State(state): State<ApiState>) -> impl IntoRespons
             retry_count: w.retry_count,
             created_at: w.created_at,
             updated_at: w.updated_at,
-            total_deliveries: 0, // TODO: Track in webhook manager
-            successful_deliveries: 0,
-            failed_deliveries: 0,
+            total_deliveries: state.webhook_manager.delivery_stats(&w.id).total,
+            successful_deliveries: state.webhook_manager.delivery_stats(&w.id).succeeded,
+            failed_deliveries: state.webhook_manager.delivery_stats(&w.id).failed,
         })
         .collect();
     
@@ -3308,7 +5440,7 @@ async fn get_webhooks_handler(State(state): State<ApiState>) -> impl IntoRespons
     })).into_response()
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 struct CreateWebhookRequest {
     name: String,
     url: String,
@@ -3319,6 +5451,16 @@ struct CreateWebhookRequest {
 }
 
 /// Create a new webhook
+#[utoipa::path(
+    post,
+    path = "/api/v1/webhooks",
+    tag = "webhooks",
+    request_body = CreateWebhookRequest,
+    responses(
+        (status = 200, description = "Webhook created", body = serde_json::Value),
+        (status = 500, description = "Failed to create the webhook", body = ErrorResponse),
+    ),
+)]
 async fn create_webhook_handler(
     State(state): State<ApiState>,
     Json(request): Json<CreateWebhookRequest>,
@@ -3352,19 +5494,26 @@ async fn create_webhook_handler(
         WebhookScope::Global
     };
     
-    let config = narayana_storage::webhooks::WebhookConfig::new(
+    let mut config = narayana_storage::webhooks::WebhookConfig::new(
         request.name,
         request.url,
         scope,
         events,
         PayloadFormat::Json,
     );
+    config.secret = request.secret;
+    if let Some(retry_count) = request.retry_count {
+        config.retry_count = retry_count.min(10);
+    }
     
     match state.webhook_manager.create_webhook(config) {
         Ok(id) => {
+            // The signing secret is only returned here; receivers verify X-Narayana-Signature with it
+            let secret = state.webhook_manager.get_webhook(&id).and_then(|w| w.secret);
             (StatusCode::OK, Json(serde_json::json!({
                 "success": true,
                 "webhook_id": id,
+                "secret": secret,
                 "message": "Webhook created successfully"
             }))).into_response()
         }
@@ -3380,6 +5529,19 @@ async fn create_webhook_handler(
 }
 
 /// Get a specific webhook
+#[utoipa::path(
+    get,
+    path = "/api/v1/webhooks/{id}",
+    tag = "webhooks",
+    params(
+        ("id" = String, Path, description = "Webhook id"),
+    ),
+    responses(
+        (status = 200, description = "Webhook", body = WebhookInfo),
+        (status = 400, description = "Invalid webhook id", body = ErrorResponse),
+        (status = 404, description = "Webhook not found", body = ErrorResponse),
+    ),
+)]
 async fn get_webhook_handler(
     State(state): State<ApiState>,
     Path(id): Path<String>,
@@ -3433,6 +5595,19 @@ async fn get_webhook_handler(
 }
 
 /// Delete a webhook
+#[utoipa::path(
+    delete,
+    path = "/api/v1/webhooks/{id}",
+    tag = "webhooks",
+    params(
+        ("id" = String, Path, description = "Webhook id"),
+    ),
+    responses(
+        (status = 200, description = "Webhook deleted", body = serde_json::Value),
+        (status = 400, description = "Invalid webhook id", body = ErrorResponse),
+        (status = 500, description = "Failed to delete the webhook", body = ErrorResponse),
+    ),
+)]
 async fn delete_webhook_handler(
     State(state): State<ApiState>,
     Path(id): Path<String>,
@@ -3503,7 +5678,7 @@ async fn delete_webhook_handler(
     }
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, ToSchema)]
 struct DeliveryInfo {
     id: String,
     webhook_id: String,
@@ -3517,7 +5692,7 @@ struct DeliveryInfo {
     duration_ms: Option<u64>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct GetDeliveriesResponse {
     deliveries: Vec<DeliveryInfo>,
     count: usize,
@@ -3525,6 +5700,20 @@ struct GetDeliveriesResponse {
 }
 
 /// Get webhook delivery history
+#[utoipa::path(
+    get,
+    path = "/api/v1/webhooks/{id}/deliveries",
+    tag = "webhooks",
+    params(
+        ("id" = String, Path, description = "Webhook id"),
+        ("limit" = Option<usize>, Query, description = "Maximum deliveries to return (default 50)"),
+    ),
+    responses(
+        (status = 200, description = "Recent deliveries", body = GetDeliveriesResponse),
+        (status = 400, description = "Invalid parameters", body = ErrorResponse),
+        (status = 404, description = "Webhook not found", body = ErrorResponse),
+    ),
+)]
 async fn get_webhook_deliveries_handler(
     State(state): State<ApiState>,
     Path(id): Path<String>,
@@ -3627,19 +5816,95 @@ async fn get_webhook_deliveries_handler(
         })
         .unwrap_or(50);
     
-    // TODO: Implement delivery tracking in webhook manager
-    // For now, return empty list
-    let deliveries = Vec::<DeliveryInfo>::new();
-    
-    let count = deliveries.len();
-    (StatusCode::OK, Json(GetDeliveriesResponse {
-        deliveries,
+    if state.webhook_manager.get_webhook(trimmed_id).is_none() {
+        let response = Json(ErrorResponse {
+            error: "Webhook not found".to_string(),
+            code: "WEBHOOK_NOT_FOUND".to_string(),
+        });
+        return (StatusCode::NOT_FOUND, response).into_response();
+    }
+
+    let deliveries: Vec<DeliveryInfo> = state.webhook_manager
+        .list_deliveries(trimmed_id, limit)
+        .into_iter()
+        .map(delivery_info)
+        .collect();
+    
+    let count = deliveries.len();
+    (StatusCode::OK, Json(GetDeliveriesResponse {
+        deliveries,
         count,
-        total: 0,
+        total: state.webhook_manager.delivery_stats(trimmed_id).total as usize,
     })).into_response()
 }
 
+fn delivery_info(delivery: narayana_storage::webhooks::WebhookDelivery) -> DeliveryInfo {
+    use narayana_storage::webhooks::DeliveryStatus;
+    let last = delivery.attempts.last();
+    DeliveryInfo {
+        status: match delivery.status {
+            DeliveryStatus::Pending if delivery.attempts.is_empty() => "pending",
+            DeliveryStatus::Pending => "processing",
+            DeliveryStatus::Succeeded => "success",
+            DeliveryStatus::Failed => "failed",
+        }.to_string(),
+        attempt: delivery.attempts.len() as u32,
+        max_attempts: delivery.max_attempts,
+        created_at: delivery.created_at,
+        completed_at: delivery.completed_at,
+        error: last.and_then(|a| a.error.clone()),
+        response_status: last.and_then(|a| a.response_status),
+        duration_ms: last.map(|a| a.duration_ms),
+        id: delivery.id,
+        webhook_id: delivery.webhook_id,
+    }
+}
+
+/// Re-send a recorded delivery (same payload, fresh signature)
+#[utoipa::path(
+    post,
+    path = "/api/v1/webhooks/{id}/deliveries/{delivery_id}/redeliver",
+    tag = "webhooks",
+    params(
+        ("id" = String, Path, description = "Webhook id"),
+        ("delivery_id" = String, Path, description = "Delivery id"),
+    ),
+    responses(
+        (status = 200, description = "Delivery queued again", body = serde_json::Value),
+        (status = 404, description = "Webhook or delivery not found", body = ErrorResponse),
+    ),
+)]
+async fn redeliver_webhook_handler(
+    State(state): State<ApiState>,
+    Path((id, delivery_id)): Path<(String, String)>,
+) -> impl IntoResponse {
+    match state.webhook_manager.redeliver(&id, &delivery_id).await {
+        Ok(delivery) => (StatusCode::OK, Json(delivery_info(delivery))).into_response(),
+        Err(e) => {
+            let response = Json(ErrorResponse {
+                error: sanitize_error_message(&format!("Redelivery failed: {}", e), "DELIVERY_NOT_FOUND"),
+                code: "DELIVERY_NOT_FOUND".to_string(),
+            });
+            (StatusCode::NOT_FOUND, response).into_response()
+        }
+    }
+}
+
 /// Start a CPL instance
+#[utoipa::path(
+    post,
+    path = "/api/v1/cpls/{cpl_id}/start",
+    tag = "cpls",
+    params(
+        ("cpl_id" = String, Path, description = "CPL id"),
+    ),
+    responses(
+        (status = 200, description = "CPL started", body = serde_json::Value),
+        (status = 400, description = "Invalid CPL id", body = ErrorResponse),
+        (status = 500, description = "Failed to start", body = ErrorResponse),
+        (status = 503, description = "CPL manager not configured", body = ErrorResponse),
+    ),
+)]
 async fn cpl_start_handler(
     State(state): State<ApiState>,
     Path(cpl_id): Path<String>,
@@ -3711,6 +5976,20 @@ async fn cpl_start_handler(
 }
 
 /// Stop a CPL instance
+#[utoipa::path(
+    post,
+    path = "/api/v1/cpls/{cpl_id}/stop",
+    tag = "cpls",
+    params(
+        ("cpl_id" = String, Path, description = "CPL id"),
+    ),
+    responses(
+        (status = 200, description = "CPL stopped", body = serde_json::Value),
+        (status = 400, description = "Invalid CPL id", body = ErrorResponse),
+        (status = 500, description = "Failed to stop", body = ErrorResponse),
+        (status = 503, description = "CPL manager not configured", body = ErrorResponse),
+    ),
+)]
 async fn cpl_stop_handler(
     State(state): State<ApiState>,
     Path(cpl_id): Path<String>,
@@ -3781,7 +6060,94 @@ async fn cpl_stop_handler(
     }
 }
 
+/// Pause a running CPL's loop
+#[utoipa::path(
+    post,
+    path = "/api/v1/cpls/{cpl_id}/pause",
+    tag = "cpls",
+    params(
+        ("cpl_id" = String, Path, description = "CPL id"),
+    ),
+    responses(
+        (status = 200, description = "CPL paused", body = serde_json::Value),
+        (status = 404, description = "CPL not found", body = ErrorResponse),
+        (status = 409, description = "CPL is not running", body = ErrorResponse),
+        (status = 503, description = "CPL manager not configured", body = ErrorResponse),
+    ),
+)]
+async fn cpl_pause_handler(
+    State(state): State<ApiState>,
+    Path(cpl_id): Path<String>,
+) -> impl IntoResponse {
+    cpl_pause_or_resume(&state, &cpl_id, true)
+}
+
+/// Resume a paused CPL's loop
+#[utoipa::path(
+    post,
+    path = "/api/v1/cpls/{cpl_id}/resume",
+    tag = "cpls",
+    params(
+        ("cpl_id" = String, Path, description = "CPL id"),
+    ),
+    responses(
+        (status = 200, description = "CPL resumed", body = serde_json::Value),
+        (status = 404, description = "CPL not found", body = ErrorResponse),
+        (status = 409, description = "CPL is not running", body = ErrorResponse),
+        (status = 503, description = "CPL manager not configured", body = ErrorResponse),
+    ),
+)]
+async fn cpl_resume_handler(
+    State(state): State<ApiState>,
+    Path(cpl_id): Path<String>,
+) -> impl IntoResponse {
+    cpl_pause_or_resume(&state, &cpl_id, false)
+}
+
+fn cpl_pause_or_resume(state: &ApiState, cpl_id: &str, pause: bool) -> axum::response::Response {
+    let Some(ref cpl_manager) = state.cpl_manager else {
+        return (StatusCode::SERVICE_UNAVAILABLE, Json(ErrorResponse {
+            error: "CPL Manager not available".to_string(),
+            code: "CPL_MANAGER_UNAVAILABLE".to_string(),
+        })).into_response();
+    };
+    if cpl_manager.get_cpl(cpl_id).is_none() {
+        return (StatusCode::NOT_FOUND, Json(ErrorResponse {
+            error: format!("CPL {} not found", cpl_id),
+            code: "CPL_NOT_FOUND".to_string(),
+        })).into_response();
+    }
+    let (result, action) = if pause {
+        (cpl_manager.pause_cpl(cpl_id), "paused")
+    } else {
+        (cpl_manager.resume_cpl(cpl_id), "resumed")
+    };
+    match result {
+        Ok(()) => (StatusCode::OK, Json(serde_json::json!({
+            "success": true,
+            "message": format!("CPL {} {}", cpl_id, action),
+        }))).into_response(),
+        Err(e) => (StatusCode::CONFLICT, Json(ErrorResponse {
+            error: e.to_string(),
+            code: "CPL_NOT_RUNNING".to_string(),
+        })).into_response(),
+    }
+}
+
 /// Enable a webhook
+#[utoipa::path(
+    post,
+    path = "/api/v1/webhooks/{id}/enable",
+    tag = "webhooks",
+    params(
+        ("id" = String, Path, description = "Webhook id"),
+    ),
+    responses(
+        (status = 200, description = "Webhook enabled", body = serde_json::Value),
+        (status = 400, description = "Invalid webhook id", body = ErrorResponse),
+        (status = 500, description = "Failed to update the webhook", body = ErrorResponse),
+    ),
+)]
 async fn enable_webhook_handler(
     State(state): State<ApiState>,
     Path(id): Path<String>,
@@ -3853,6 +6219,19 @@ async fn enable_webhook_handler(
 }
 
 /// Disable a webhook
+#[utoipa::path(
+    post,
+    path = "/api/v1/webhooks/{id}/disable",
+    tag = "webhooks",
+    params(
+        ("id" = String, Path, description = "Webhook id"),
+    ),
+    responses(
+        (status = 200, description = "Webhook disabled", body = serde_json::Value),
+        (status = 400, description = "Invalid webhook id", body = ErrorResponse),
+        (status = 500, description = "Failed to update the webhook", body = ErrorResponse),
+    ),
+)]
 async fn disable_webhook_handler(
     State(state): State<ApiState>,
     Path(id): Path<String>,
@@ -3925,6 +6304,16 @@ async fn disable_webhook_handler(
 
 
 /// Load schema from schema.nyn file
+#[utoipa::path(
+    post,
+    path = "/api/v1/schema/load",
+    tag = "schema",
+    responses(
+        (status = 200, description = "Schema file applied", body = serde_json::Value),
+        (status = 404, description = "No schema file configured", body = ErrorResponse),
+        (status = 500, description = "Failed to apply the schema", body = ErrorResponse),
+    ),
+)]
 async fn load_schema_handler(State(state): State<ApiState>) -> impl IntoResponse {
     use crate::schema_loader;
     use std::path::Path;
@@ -3968,6 +6357,16 @@ async fn load_schema_handler(State(state): State<ApiState>) -> impl IntoResponse
 }
 
 /// Load seeds from seeds.nyn file
+#[utoipa::path(
+    post,
+    path = "/api/v1/schema/seeds",
+    tag = "schema",
+    responses(
+        (status = 200, description = "Seed data loaded", body = serde_json::Value),
+        (status = 404, description = "No seed files found", body = ErrorResponse),
+        (status = 500, description = "Failed to load seeds", body = ErrorResponse),
+    ),
+)]
 async fn load_seeds_handler(State(state): State<ApiState>) -> impl IntoResponse {
     use crate::schema_loader;
     use std::path::Path;
@@ -4037,6 +6436,16 @@ async fn load_seeds_handler(State(state): State<ApiState>) -> impl IntoResponse
 }
 
 /// Load both schema and seeds (spawn)
+#[utoipa::path(
+    post,
+    path = "/api/v1/schema/spawn",
+    tag = "schema",
+    responses(
+        (status = 200, description = "Schema applied and seeds loaded", body = serde_json::Value),
+        (status = 404, description = "No schema file configured", body = ErrorResponse),
+        (status = 500, description = "Failed to spawn the schema", body = ErrorResponse),
+    ),
+)]
 async fn spawn_schema_handler(State(state): State<ApiState>) -> impl IntoResponse {
     use crate::schema_loader;
     use std::path::Path;
@@ -4079,7 +6488,7 @@ async fn spawn_schema_handler(State(state): State<ApiState>) -> impl IntoRespons
 // Vector Search Handlers
 // ============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 struct VectorSearchRequest {
     index: String,
     vector: Vec<f32>,
@@ -4087,12 +6496,12 @@ struct VectorSearchRequest {
     filters: Option<HashMap<String, serde_json::Value>>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct VectorSearchResponse {
     results: Vec<VectorSearchResult>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct VectorSearchResult {
     id: u64,
     similarity: f32,
@@ -4100,6 +6509,17 @@ struct VectorSearchResult {
 }
 
 /// Vector search handler
+#[utoipa::path(
+    post,
+    path = "/api/v1/vector/search",
+    tag = "vectors",
+    request_body = VectorSearchRequest,
+    responses(
+        (status = 200, description = "Nearest vectors, most similar first", body = VectorSearchResponse),
+        (status = 400, description = "Invalid vector or k", body = ErrorResponse),
+        (status = 500, description = "Search failed", body = ErrorResponse),
+    ),
+)]
 async fn vector_search_handler(
     State(state): State<ApiState>,
     Json(request): Json<VectorSearchRequest>,
@@ -4162,20 +6582,34 @@ async fn vector_search_handler(
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 struct VectorAddRequest {
     id: u64,
     vector: Vec<f32>,
     metadata: Option<HashMap<String, serde_json::Value>>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct VectorAddResponse {
     success: bool,
     message: String,
 }
 
 /// Vector add handler
+#[utoipa::path(
+    post,
+    path = "/api/v1/vector/{index}/add",
+    tag = "vectors",
+    params(
+        ("index" = String, Path, description = "Vector index name"),
+    ),
+    request_body = VectorAddRequest,
+    responses(
+        (status = 200, description = "Vector stored", body = VectorAddResponse),
+        (status = 400, description = "Invalid vector", body = ErrorResponse),
+        (status = 500, description = "Index error", body = ErrorResponse),
+    ),
+)]
 async fn vector_add_handler(
     State(state): State<ApiState>,
     Path(index): Path<String>,
@@ -4245,12 +6679,12 @@ async fn vector_add_handler(
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 struct VectorAddBatchRequest {
     vectors: Vec<VectorAddRequest>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct VectorAddBatchResponse {
     success: bool,
     added: usize,
@@ -4258,6 +6692,20 @@ struct VectorAddBatchResponse {
 }
 
 /// Vector batch add handler
+#[utoipa::path(
+    post,
+    path = "/api/v1/vector/{index}/add_batch",
+    tag = "vectors",
+    params(
+        ("index" = String, Path, description = "Vector index name"),
+    ),
+    request_body = VectorAddBatchRequest,
+    responses(
+        (status = 200, description = "Vectors stored", body = VectorAddBatchResponse),
+        (status = 400, description = "Invalid vectors", body = ErrorResponse),
+        (status = 500, description = "Index error", body = ErrorResponse),
+    ),
+)]
 async fn vector_add_batch_handler(
     State(state): State<ApiState>,
     Path(index): Path<String>,
@@ -4363,14 +6811,14 @@ async fn vector_add_batch_handler(
 // ML Operations Handlers
 // ============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 struct MLTrainRequest {
     model_type: String,
     training_data: Option<String>,
     params: Option<HashMap<String, serde_json::Value>>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct MLTrainResponse {
     success: bool,
     model_id: Option<String>,
@@ -4378,6 +6826,15 @@ struct MLTrainResponse {
 }
 
 /// ML train handler
+#[utoipa::path(
+    post,
+    path = "/api/v1/ml/train",
+    tag = "ml",
+    request_body = MLTrainRequest,
+    responses(
+        (status = 501, description = "Not implemented in this build", body = MLTrainResponse),
+    ),
+)]
 async fn ml_train_handler(
     State(_state): State<ApiState>,
     Json(request): Json<MLTrainRequest>,
@@ -4395,41 +6852,131 @@ async fn ml_train_handler(
     (StatusCode::NOT_IMPLEMENTED, response).into_response()
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 struct MLPredictRequest {
-    input: serde_json::Value,
+    /// Table of the default database whose rows are scored
+    table: String,
+    /// Feature columns, in model input order
+    features: Vec<String>,
+    /// "value" (first output as a number) or "class" (index of the largest output)
+    #[serde(default)]
+    #[schema(value_type = String)]
+    output: narayana_query::ml_integration::PredictOutput,
+    /// Rows scored, from the first (default 1000, at most 10000)
+    limit: Option<usize>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct MLPredictResponse {
     success: bool,
     prediction: Option<serde_json::Value>,
     message: String,
 }
 
-/// ML predict handler
+/// Score a table's rows with the serving (Production, else Staging) version
+/// of a registry model, as a PREDICT query run under the caller's row policies
+#[utoipa::path(
+    post,
+    path = "/api/v1/ml/predict/{model_id}",
+    tag = "ml",
+    params(
+        ("model_id" = String, Path, description = "Registry model name"),
+    ),
+    request_body = MLPredictRequest,
+    responses(
+        (status = 200, description = "One prediction per row", body = MLPredictResponse),
+        (status = 400, description = "Unknown feature column, non-numeric feature or unservable model", body = ErrorResponse),
+        (status = 403, description = "Protected table or table served through an output profile", body = ErrorResponse),
+        (status = 404, description = "Table not found", body = ErrorResponse),
+        (status = 409, description = "Query was cancelled", body = ErrorResponse),
+    ),
+)]
 async fn ml_predict_handler(
-    State(_state): State<ApiState>,
+    State(state): State<ApiState>,
     Path(model_id): Path<String>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
     Json(request): Json<MLPredictRequest>,
 ) -> impl IntoResponse {
-    // For now, ML prediction is not fully implemented on the server side
-    warn!("ML prediction requested but not fully implemented: model_id={}", model_id);
-    
-    let response = Json(MLPredictResponse {
-        success: false,
-        prediction: None,
-        message: format!("ML prediction not yet fully implemented. Model '{}' requires ML runtime integration.", model_id),
-    });
-    (StatusCode::NOT_IMPLEMENTED, response).into_response()
+    use narayana_query::executor::DefaultQueryExecutor;
+    use narayana_query::plan::{PlanNode, QueryPlan};
+    const DEFAULT_PREDICT_ROWS: usize = 1000;
+    const MAX_PREDICT_ROWS: usize = 10_000;
+
+    let Some(table_id) = state.db_manager.get_table_by_name("default", &request.table) else {
+        return job_error(StatusCode::NOT_FOUND, format!("Table not found: {}", request.table), "TABLE_NOT_FOUND");
+    };
+    // SECURITY: The users table is never scored
+    if is_protected_users_table(&state, table_id) {
+        return job_error(StatusCode::FORBIDDEN, "Cannot query protected system table".to_string(), "PROTECTED_TABLE");
+    }
+    let principal = claims.as_ref().map(|c| c.principal()).unwrap_or_default();
+    // Rows bound to an output profile must not be scored around it
+    match state.db_manager.get_table_output_config_for_consumer(table_id, &principal.subject, &principal.roles) {
+        Ok(None) => {}
+        Ok(Some(_)) => {
+            return job_error(StatusCode::FORBIDDEN, "Table has an output profile for this caller; use /query".to_string(), "OUTPUT_PROFILE_BOUND");
+        }
+        Err(e) => return job_error(StatusCode::INTERNAL_SERVER_ERROR, sanitize_error_message(&e.to_string(), "PREDICT_ERROR"), "PREDICT_ERROR"),
+    }
+    let schema = match state.storage.get_schema(table_id).await {
+        Ok(schema) => schema,
+        Err(_) => return job_error(StatusCode::NOT_FOUND, format!("Table not found: {}", request.table), "TABLE_NOT_FOUND"),
+    };
+    let mut column_ids = Vec::with_capacity(request.features.len());
+    for feature in &request.features {
+        match schema.field_index(feature) {
+            Some(idx) => column_ids.push(idx as u32),
+            None => return job_error(StatusCode::BAD_REQUEST, format!("Column not found: {}", feature), "PREDICT_ERROR"),
+        }
+    }
+
+    let plan = QueryPlan::new(PlanNode::Predict {
+        model: model_id.clone(),
+        features: request.features.clone(),
+        output: request.output,
+        input: Box::new(PlanNode::Limit {
+            limit: request.limit.unwrap_or(DEFAULT_PREDICT_ROWS).clamp(1, MAX_PREDICT_ROWS),
+            offset: 0,
+            input: Box::new(PlanNode::Scan { table_id: table_id.0, column_ids, filter: None }),
+        }),
+    }, schema);
+    let store = state.db_manager.row_security().secure(state.storage.clone(), principal);
+    let executor = DefaultQueryExecutor::new(crate::nlq::ReadOnlyColumnStore(store)).with_predictor(state.predictor.clone());
+    let query = state.queries.register(
+        format!("predict {} over {}", model_id, request.table),
+        claims.map(|axum::Extension(claims)| claims.sub),
+    );
+    match executor.execute_with_context(plan, query.context()).await {
+        Ok(mut columns) => {
+            let prediction = match columns.pop() {
+                Some(Column::Float64(values)) => serde_json::json!(values),
+                Some(Column::Int64(values)) => serde_json::json!(values),
+                _ => serde_json::Value::Array(Vec::new()),
+            };
+            let rows = prediction.as_array().map_or(0, Vec::len);
+            info!("Scored {} rows of table {} with model {}", rows, table_id.0, model_id);
+            (StatusCode::OK, Json(MLPredictResponse {
+                success: true,
+                prediction: Some(prediction),
+                message: format!("Scored {} rows with model '{}'", rows, model_id),
+            })).into_response()
+        }
+        Err(e) if narayana_query::cancellation::is_cancelled_error(&e) => {
+            job_error(StatusCode::CONFLICT, format!("Query {} was cancelled", query.id()), "QUERY_CANCELLED")
+        }
+        Err(e) => {
+            warn!("PREDICT with model {} failed: {}", model_id, e);
+            job_error(StatusCode::BAD_REQUEST, sanitize_error_message(&e.to_string(), "PREDICT_ERROR"), "PREDICT_ERROR")
+        }
+    }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 struct MLExtractRequest {
     columns: Option<Vec<String>>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct MLExtractResponse {
     success: bool,
     features: Option<Vec<Vec<f32>>>,
@@ -4437,6 +6984,18 @@ struct MLExtractResponse {
 }
 
 /// ML feature extraction handler
+#[utoipa::path(
+    post,
+    path = "/api/v1/ml/extract/{table}",
+    tag = "ml",
+    params(
+        ("table" = String, Path, description = "Table name"),
+    ),
+    request_body = MLExtractRequest,
+    responses(
+        (status = 501, description = "Not implemented in this build", body = MLExtractResponse),
+    ),
+)]
 async fn ml_extract_handler(
     State(_state): State<ApiState>,
     Path(table): Path<String>,
@@ -4457,7 +7016,7 @@ async fn ml_extract_handler(
 // Analytics Operations Handlers
 // ============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 struct AnalyticsWindowRequest {
     table: String,
     function: String,
@@ -4465,7 +7024,7 @@ struct AnalyticsWindowRequest {
     order_by: Option<Vec<String>>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct AnalyticsWindowResponse {
     success: bool,
     results: Option<Vec<serde_json::Value>>,
@@ -4473,6 +7032,15 @@ struct AnalyticsWindowResponse {
 }
 
 /// Analytics window function handler
+#[utoipa::path(
+    post,
+    path = "/api/v1/analytics/window",
+    tag = "analytics",
+    request_body = AnalyticsWindowRequest,
+    responses(
+        (status = 501, description = "Not implemented in this build", body = AnalyticsWindowResponse),
+    ),
+)]
 async fn analytics_window_handler(
     State(_state): State<ApiState>,
     Json(request): Json<AnalyticsWindowRequest>,
@@ -4487,14 +7055,14 @@ async fn analytics_window_handler(
     (StatusCode::NOT_IMPLEMENTED, response).into_response()
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 struct AnalyticsStatisticalRequest {
     table: String,
     function: String,
     column: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct AnalyticsStatisticalResponse {
     success: bool,
     result: Option<f64>,
@@ -4502,6 +7070,15 @@ struct AnalyticsStatisticalResponse {
 }
 
 /// Analytics statistical function handler
+#[utoipa::path(
+    post,
+    path = "/api/v1/analytics/statistical",
+    tag = "analytics",
+    request_body = AnalyticsStatisticalRequest,
+    responses(
+        (status = 501, description = "Not implemented in this build", body = AnalyticsStatisticalResponse),
+    ),
+)]
 async fn analytics_statistical_handler(
     State(_state): State<ApiState>,
     Json(request): Json<AnalyticsStatisticalRequest>,
@@ -4516,7 +7093,7 @@ async fn analytics_statistical_handler(
     (StatusCode::NOT_IMPLEMENTED, response).into_response()
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 struct AnalyticsTimeSeriesRequest {
     table: String,
     time_column: String,
@@ -4524,7 +7101,7 @@ struct AnalyticsTimeSeriesRequest {
     analysis_type: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct AnalyticsTimeSeriesResponse {
     success: bool,
     results: Option<Vec<serde_json::Value>>,
@@ -4532,6 +7109,15 @@ struct AnalyticsTimeSeriesResponse {
 }
 
 /// Analytics time series handler
+#[utoipa::path(
+    post,
+    path = "/api/v1/analytics/timeseries",
+    tag = "analytics",
+    request_body = AnalyticsTimeSeriesRequest,
+    responses(
+        (status = 501, description = "Not implemented in this build", body = AnalyticsTimeSeriesResponse),
+    ),
+)]
 async fn analytics_timeseries_handler(
     State(_state): State<ApiState>,
     Json(request): Json<AnalyticsTimeSeriesRequest>,
@@ -4546,14 +7132,14 @@ async fn analytics_timeseries_handler(
     (StatusCode::NOT_IMPLEMENTED, response).into_response()
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 struct AnalyticsAggregateRequest {
     table: String,
     aggregations: Vec<serde_json::Value>,
     group_by: Option<Vec<String>>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct AnalyticsAggregateResponse {
     success: bool,
     results: Option<Vec<serde_json::Value>>,
@@ -4561,6 +7147,15 @@ struct AnalyticsAggregateResponse {
 }
 
 /// Analytics aggregation handler
+#[utoipa::path(
+    post,
+    path = "/api/v1/analytics/aggregate",
+    tag = "analytics",
+    request_body = AnalyticsAggregateRequest,
+    responses(
+        (status = 501, description = "Not implemented in this build", body = AnalyticsAggregateResponse),
+    ),
+)]
 async fn analytics_aggregate_handler(
     State(_state): State<ApiState>,
     Json(request): Json<AnalyticsAggregateRequest>,
@@ -4576,19 +7171,31 @@ async fn analytics_aggregate_handler(
 }
 
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 struct SyncPeerRequest {
     peer_id: String,
     data: serde_json::Value,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct SyncPeerResponse {
     success: bool,
     message: String,
 }
 
 /// Sync peer handler
+#[utoipa::path(
+    post,
+    path = "/api/v1/sync/peer/{peer_id}",
+    tag = "sync",
+    params(
+        ("peer_id" = String, Path, description = "Peer id"),
+    ),
+    request_body = SyncPeerRequest,
+    responses(
+        (status = 501, description = "Not implemented in this build", body = SyncPeerResponse),
+    ),
+)]
 async fn sync_peer_handler(
     Path(peer_id): Path<String>,
     State(_state): State<ApiState>,
@@ -4603,7 +7210,7 @@ async fn sync_peer_handler(
     (StatusCode::NOT_IMPLEMENTED, response).into_response()
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 struct SyncStatusResponse {
     success: bool,
     status: Option<serde_json::Value>,
@@ -4611,6 +7218,14 @@ struct SyncStatusResponse {
 }
 
 /// Sync status handler
+#[utoipa::path(
+    get,
+    path = "/api/v1/sync/status",
+    tag = "sync",
+    responses(
+        (status = 501, description = "Not implemented in this build", body = SyncStatusResponse),
+    ),
+)]
 async fn sync_status_handler(
     State(_state): State<ApiState>,
 ) -> impl IntoResponse {
@@ -4623,3 +7238,3759 @@ async fn sync_status_handler(
     });
     (StatusCode::NOT_IMPLEMENTED, response).into_response()
 }
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct CreateFullTextIndexRequest {
+    column: String,
+    #[serde(default)]
+    #[schema(value_type = String)]
+    language: narayana_storage::full_text::Language,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct CreateFullTextIndexResponse {
+    success: bool,
+    column: String,
+    documents_indexed: usize,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct FullTextSearchRequest {
+    column: String,
+    query: String,
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct FullTextSearchResponse {
+    #[schema(value_type = Vec<Object>)]
+    hits: Vec<narayana_storage::full_text::FullTextHit>,
+    total: usize,
+}
+
+/// Look up a table's schema in the default database
+fn find_table_schema(state: &ApiState, table_id: TableId) -> Option<Schema> {
+    let db_id = state.db_manager.get_database_by_name("default")?;
+    state.db_manager.list_tables(db_id).ok()?
+        .into_iter()
+        .find(|t| t.table_id == table_id)
+        .map(|t| t.schema)
+}
+
+/// Create a full-text index on a String column (existing rows are backfilled)
+#[utoipa::path(
+    post,
+    path = "/api/v1/tables/{id}/fulltext",
+    tag = "search",
+    params(
+        ("id" = u64, Path, description = "Table id"),
+    ),
+    request_body = CreateFullTextIndexRequest,
+    responses(
+        (status = 200, description = "Index built from existing rows", body = CreateFullTextIndexResponse),
+        (status = 400, description = "Column is not a string column", body = ErrorResponse),
+        (status = 403, description = "Protected table", body = ErrorResponse),
+        (status = 404, description = "Table or column not found", body = ErrorResponse),
+    ),
+)]
+async fn create_fulltext_index_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<u64>,
+    Json(request): Json<CreateFullTextIndexRequest>,
+) -> impl IntoResponse {
+    let table_id = TableId(id);
+    if is_protected_users_table(&state, table_id) {
+        let response = Json(ErrorResponse {
+            error: "Cannot index protected system table".to_string(),
+            code: "PROTECTED_TABLE".to_string(),
+        });
+        return (StatusCode::FORBIDDEN, response).into_response();
+    }
+    let schema = match find_table_schema(&state, table_id) {
+        Some(schema) => schema,
+        None => {
+            let response = Json(ErrorResponse {
+                error: "Table not found".to_string(),
+                code: "TABLE_NOT_FOUND".to_string(),
+            });
+            return (StatusCode::NOT_FOUND, response).into_response();
+        }
+    };
+    let column_idx = match schema.field_index(&request.column) {
+        Some(idx) => idx,
+        None => {
+            let response = Json(ErrorResponse {
+                error: format!("Column '{}' not found", request.column),
+                code: "COLUMN_NOT_FOUND".to_string(),
+            });
+            return (StatusCode::BAD_REQUEST, response).into_response();
+        }
+    };
+
+    // Backfill from the rows already stored
+    let existing = match state.storage.read_columns(table_id, vec![column_idx as u32], 0, usize::MAX).await {
+        Ok(mut columns) => match columns.pop() {
+            Some(Column::String(values)) => values,
+            _ => Vec::new(),
+        },
+        Err(e) => {
+            warn!("Could not read existing rows for full-text backfill on table {}: {}", id, e);
+            Vec::new()
+        }
+    };
+
+    match state.full_text.create_index(table_id, &schema, &request.column, request.language, &existing) {
+        Ok(()) => (StatusCode::OK, Json(CreateFullTextIndexResponse {
+            success: true,
+            column: request.column,
+            documents_indexed: existing.len(),
+        })).into_response(),
+        Err(e) => {
+            let response = Json(ErrorResponse {
+                error: format!("Failed to create full-text index: {}", e),
+                code: "INVALID_INDEX".to_string(),
+            });
+            (StatusCode::BAD_REQUEST, response).into_response()
+        }
+    }
+}
+
+/// Full-text search over an indexed column, ranked by BM25
+#[utoipa::path(
+    post,
+    path = "/api/v1/tables/{id}/search",
+    tag = "search",
+    params(
+        ("id" = u64, Path, description = "Table id"),
+    ),
+    request_body = FullTextSearchRequest,
+    responses(
+        (status = 200, description = "Ranked hits", body = FullTextSearchResponse),
+        (status = 400, description = "No index on the column", body = ErrorResponse),
+        (status = 403, description = "Protected table", body = ErrorResponse),
+    ),
+)]
+async fn fulltext_search_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<u64>,
+    Json(request): Json<FullTextSearchRequest>,
+) -> impl IntoResponse {
+    let table_id = TableId(id);
+    if is_protected_users_table(&state, table_id) {
+        let response = Json(ErrorResponse {
+            error: "Cannot search protected system table".to_string(),
+            code: "PROTECTED_TABLE".to_string(),
+        });
+        return (StatusCode::FORBIDDEN, response).into_response();
+    }
+    let limit = request.limit.unwrap_or(20).min(1000);
+    match state.full_text.search(table_id, &request.column, &request.query, limit) {
+        Ok(hits) => {
+            let total = hits.len();
+            (StatusCode::OK, Json(FullTextSearchResponse { hits, total })).into_response()
+        }
+        Err(e) => {
+            let response = Json(ErrorResponse {
+                error: format!("Search failed: {}", e),
+                code: "SEARCH_ERROR".to_string(),
+            });
+            (StatusCode::BAD_REQUEST, response).into_response()
+        }
+    }
+}
+
+/// Build a Binary column of encoded geometries. Accepts a typed `{"Binary": [...]}`
+/// column (validated) or an array of WKT strings / `[lon, lat]` / GeoJSON-like values.
+fn geometry_column_from_json(data_type: &DataType, value: serde_json::Value) -> narayana_core::Result<Column> {
+    use narayana_storage::geospatial::Geometry;
+
+    let check = |geometry: &Geometry| -> narayana_core::Result<()> {
+        if *data_type == DataType::Point && !matches!(geometry, Geometry::Point(_)) {
+            return Err(narayana_core::Error::InvalidDataType {
+                expected: "Point".to_string(),
+                actual: geometry.kind().to_string(),
+            });
+        }
+        Ok(())
+    };
+    if value.get("Binary").is_some() {
+        let column: Column = serde_json::from_value(value)
+            .map_err(|e| narayana_core::Error::Deserialization(e.to_string()))?;
+        if let Column::Binary(values) = &column {
+            for bytes in values.iter().filter(|b| !b.is_empty()) {
+                check(&Geometry::decode(bytes)?)?;
+            }
+        }
+        return Ok(column);
+    }
+    let items = value.as_array()
+        .ok_or_else(|| narayana_core::Error::Deserialization("Geometry column must be an array".to_string()))?;
+    let mut values = Vec::with_capacity(items.len());
+    for item in items {
+        if item.is_null() {
+            // Nulls are stored as empty values
+            values.push(Vec::new());
+            continue;
+        }
+        let geometry = Geometry::from_json(item)?;
+        check(&geometry)?;
+        values.push(geometry.encode());
+    }
+    Ok(Column::Binary(values))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct CreateSpatialIndexRequest {
+    column: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct CreateSpatialIndexResponse {
+    success: bool,
+    column: String,
+    geometries_indexed: usize,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct SpatialQueryRequest {
+    column: String,
+    #[schema(value_type = Object)]
+    predicate: narayana_storage::geospatial::SpatialPredicate,
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+struct SpatialQueryResponse {
+    #[schema(value_type = Vec<Object>)]
+    hits: Vec<narayana_storage::geospatial::SpatialHit>,
+    total: usize,
+}
+
+/// Create an R-tree index on a Point or Geometry column (existing rows are backfilled)
+#[utoipa::path(
+    post,
+    path = "/api/v1/tables/{id}/spatial",
+    tag = "search",
+    params(
+        ("id" = u64, Path, description = "Table id"),
+    ),
+    request_body = CreateSpatialIndexRequest,
+    responses(
+        (status = 200, description = "Index built from existing rows", body = CreateSpatialIndexResponse),
+        (status = 400, description = "Column does not hold geometries", body = ErrorResponse),
+        (status = 403, description = "Protected table", body = ErrorResponse),
+        (status = 404, description = "Table or column not found", body = ErrorResponse),
+    ),
+)]
+async fn create_spatial_index_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<u64>,
+    Json(request): Json<CreateSpatialIndexRequest>,
+) -> impl IntoResponse {
+    let table_id = TableId(id);
+    if is_protected_users_table(&state, table_id) {
+        let response = Json(ErrorResponse {
+            error: "Cannot index protected system table".to_string(),
+            code: "PROTECTED_TABLE".to_string(),
+        });
+        return (StatusCode::FORBIDDEN, response).into_response();
+    }
+    let schema = match find_table_schema(&state, table_id) {
+        Some(schema) => schema,
+        None => {
+            let response = Json(ErrorResponse {
+                error: "Table not found".to_string(),
+                code: "TABLE_NOT_FOUND".to_string(),
+            });
+            return (StatusCode::NOT_FOUND, response).into_response();
+        }
+    };
+    let column_idx = match schema.field_index(&request.column) {
+        Some(idx) => idx,
+        None => {
+            let response = Json(ErrorResponse {
+                error: format!("Column '{}' not found", request.column),
+                code: "COLUMN_NOT_FOUND".to_string(),
+            });
+            return (StatusCode::BAD_REQUEST, response).into_response();
+        }
+    };
+
+    // Backfill from the rows already stored
+    let existing = match state.storage.read_columns(table_id, vec![column_idx as u32], 0, usize::MAX).await {
+        Ok(mut columns) => match columns.pop() {
+            Some(Column::Binary(values)) => values,
+            _ => Vec::new(),
+        },
+        Err(e) => {
+            warn!("Could not read existing rows for spatial backfill on table {}: {}", id, e);
+            Vec::new()
+        }
+    };
+
+    let geometries_indexed = existing.iter().filter(|b| !b.is_empty()).count();
+    match state.spatial.create_index(table_id, &schema, &request.column, &existing) {
+        Ok(()) => (StatusCode::OK, Json(CreateSpatialIndexResponse {
+            success: true,
+            column: request.column,
+            geometries_indexed,
+        })).into_response(),
+        Err(e) => {
+            let response = Json(ErrorResponse {
+                error: format!("Failed to create spatial index: {}", e),
+                code: "INVALID_INDEX".to_string(),
+            });
+            (StatusCode::BAD_REQUEST, response).into_response()
+        }
+    }
+}
+
+/// Radius, bounding-box or nearest-k query over a spatially indexed column
+#[utoipa::path(
+    post,
+    path = "/api/v1/tables/{id}/spatial/query",
+    tag = "search",
+    params(
+        ("id" = u64, Path, description = "Table id"),
+    ),
+    request_body = SpatialQueryRequest,
+    responses(
+        (status = 200, description = "Matching rows", body = SpatialQueryResponse),
+        (status = 400, description = "No index on the column", body = ErrorResponse),
+        (status = 403, description = "Protected table", body = ErrorResponse),
+    ),
+)]
+async fn spatial_query_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<u64>,
+    Json(request): Json<SpatialQueryRequest>,
+) -> impl IntoResponse {
+    let table_id = TableId(id);
+    if is_protected_users_table(&state, table_id) {
+        let response = Json(ErrorResponse {
+            error: "Cannot query protected system table".to_string(),
+            code: "PROTECTED_TABLE".to_string(),
+        });
+        return (StatusCode::FORBIDDEN, response).into_response();
+    }
+    let limit = request.limit.unwrap_or(100).min(10_000);
+    match state.spatial.query(table_id, &request.column, &request.predicate, limit) {
+        Ok(hits) => {
+            let total = hits.len();
+            (StatusCode::OK, Json(SpatialQueryResponse { hits, total })).into_response()
+        }
+        Err(e) => {
+            let response = Json(ErrorResponse {
+                error: format!("Spatial query failed: {}", e),
+                code: "SPATIAL_QUERY_ERROR".to_string(),
+            });
+            (StatusCode::BAD_REQUEST, response).into_response()
+        }
+    }
+}
+
+/// List anomaly detectors with their learned state
+#[utoipa::path(
+    get,
+    path = "/api/v1/anomaly/detectors",
+    tag = "anomaly",
+    responses(
+        (status = 200, description = "Configured detectors", body = serde_json::Value),
+    ),
+)]
+async fn list_anomaly_detectors_handler(State(state): State<ApiState>) -> impl IntoResponse {
+    let detectors = state.anomaly.list_detectors();
+    Json(serde_json::json!({
+        "total": detectors.len(),
+        "detectors": detectors,
+    }))
+}
+
+/// Create (or replace) an anomaly detector on a numeric column
+#[utoipa::path(
+    post,
+    path = "/api/v1/anomaly/detectors",
+    tag = "anomaly",
+    request_body(content = Object, description = "Detector configuration"),
+    responses(
+        (status = 201, description = "Detector created", body = serde_json::Value),
+        (status = 400, description = "Invalid configuration", body = ErrorResponse),
+        (status = 403, description = "Protected table", body = ErrorResponse),
+        (status = 404, description = "Table or column not found", body = ErrorResponse),
+    ),
+)]
+async fn create_anomaly_detector_handler(
+    State(state): State<ApiState>,
+    Json(config): Json<narayana_storage::anomaly_detection::DetectorConfig>,
+) -> impl IntoResponse {
+    if is_protected_users_table(&state, config.table_id) {
+        let response = Json(ErrorResponse {
+            error: "Cannot attach detectors to protected system table".to_string(),
+            code: "PROTECTED_TABLE".to_string(),
+        });
+        return (StatusCode::FORBIDDEN, response).into_response();
+    }
+    let schema = match find_table_schema(&state, config.table_id) {
+        Some(schema) => schema,
+        None => {
+            let response = Json(ErrorResponse {
+                error: "Table not found".to_string(),
+                code: "TABLE_NOT_FOUND".to_string(),
+            });
+            return (StatusCode::NOT_FOUND, response).into_response();
+        }
+    };
+    let id = config.id.clone();
+    match state.anomaly.create_detector(config, &schema).await {
+        Ok(()) => {
+            info!("Created anomaly detector {}", id);
+            (StatusCode::CREATED, Json(state.anomaly.get_detector(&id))).into_response()
+        }
+        Err(e) => {
+            let response = Json(ErrorResponse {
+                error: sanitize_error_message(&format!("Invalid detector: {}", e), "INVALID_DETECTOR"),
+                code: "INVALID_DETECTOR".to_string(),
+            });
+            (StatusCode::BAD_REQUEST, response).into_response()
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/anomaly/detectors/{id}",
+    tag = "anomaly",
+    params(
+        ("id" = String, Path, description = "Detector id"),
+    ),
+    responses(
+        (status = 200, description = "Detector configuration and state", body = serde_json::Value),
+        (status = 404, description = "Detector not found", body = ErrorResponse),
+    ),
+)]
+async fn get_anomaly_detector_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    match state.anomaly.get_detector(&id) {
+        Some(detector) => (StatusCode::OK, Json(detector)).into_response(),
+        None => {
+            let response = Json(ErrorResponse {
+                error: "Detector not found".to_string(),
+                code: "DETECTOR_NOT_FOUND".to_string(),
+            });
+            (StatusCode::NOT_FOUND, response).into_response()
+        }
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/anomaly/detectors/{id}",
+    tag = "anomaly",
+    params(
+        ("id" = String, Path, description = "Detector id"),
+    ),
+    responses(
+        (status = 200, description = "Detector removed", body = serde_json::Value),
+        (status = 404, description = "Detector not found", body = ErrorResponse),
+        (status = 500, description = "Failed to remove the detector", body = ErrorResponse),
+    ),
+)]
+async fn delete_anomaly_detector_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    match state.anomaly.remove_detector(&id).await {
+        Ok(true) => (StatusCode::OK, Json(serde_json::json!({ "success": true }))).into_response(),
+        Ok(false) => {
+            let response = Json(ErrorResponse {
+                error: "Detector not found".to_string(),
+                code: "DETECTOR_NOT_FOUND".to_string(),
+            });
+            (StatusCode::NOT_FOUND, response).into_response()
+        }
+        Err(e) => {
+            let response = Json(ErrorResponse {
+                error: sanitize_error_message(&format!("Failed to delete detector: {}", e), "INTERNAL_ERROR"),
+                code: "INTERNAL_ERROR".to_string(),
+            });
+            (StatusCode::INTERNAL_SERVER_ERROR, response).into_response()
+        }
+    }
+}
+
+/// Forget a detector's learned baseline
+#[utoipa::path(
+    post,
+    path = "/api/v1/anomaly/detectors/{id}/reset",
+    tag = "anomaly",
+    params(
+        ("id" = String, Path, description = "Detector id"),
+    ),
+    responses(
+        (status = 200, description = "Detector state cleared", body = serde_json::Value),
+        (status = 404, description = "Detector not found", body = ErrorResponse),
+    ),
+)]
+async fn reset_anomaly_detector_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    match state.anomaly.reset_detector(&id).await {
+        Ok(()) => (StatusCode::OK, Json(serde_json::json!({ "success": true }))).into_response(),
+        Err(e) => {
+            let response = Json(ErrorResponse {
+                error: sanitize_error_message(&format!("Failed to reset detector: {}", e), "DETECTOR_NOT_FOUND"),
+                code: "DETECTOR_NOT_FOUND".to_string(),
+            });
+            (StatusCode::NOT_FOUND, response).into_response()
+        }
+    }
+}
+
+/// Recent anomalies (newest first); `?detector=<id>&limit=<n>`
+#[utoipa::path(
+    get,
+    path = "/api/v1/anomaly/events",
+    tag = "anomaly",
+    params(
+        ("detector" = Option<String>, Query, description = "Only events from this detector"),
+        ("limit" = Option<usize>, Query, description = "Maximum events to return"),
+    ),
+    responses(
+        (status = 200, description = "Recent anomaly events, newest first", body = serde_json::Value),
+    ),
+)]
+async fn get_anomaly_events_handler(
+    State(state): State<ApiState>,
+    Query(params): Query<HashMap<String, String>>,
+) -> impl IntoResponse {
+    let limit = params.get("limit")
+        .and_then(|l| l.parse::<usize>().ok())
+        .unwrap_or(100)
+        .min(1000);
+    let events = state.anomaly.recent_anomalies(params.get("detector").map(|d| d.as_str()), limit);
+    Json(serde_json::json!({
+        "total": events.len(),
+        "events": events,
+    }))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct OnlineFeaturesRequest {
+    entity: String,
+    features: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct HistoricalFeaturesRequest {
+    #[schema(value_type = Vec<Object>)]
+    entity_rows: Vec<narayana_storage::feature_store::EntityRow>,
+    features: Vec<String>,
+}
+
+/// Maximum entity rows per historical feature request
+const MAX_HISTORICAL_FEATURE_ROWS: usize = 100_000;
+
+/// Batch-compute a feature view from every row currently in its table
+pub async fn materialize_feature_view(
+    storage: &Arc<dyn ColumnStore>,
+    features: &narayana_storage::feature_store::FeatureStore,
+    name: &str,
+) -> narayana_core::Result<usize> {
+    let status = features.get_view(name)
+        .ok_or_else(|| narayana_core::Error::Storage(format!("Feature view {} not found", name)))?;
+    let column_ids = (0..status.schema.fields.len() as u32).collect();
+    let columns = storage.read_columns(status.view.table_id, column_ids, 0, usize::MAX).await?;
+    features.materialize(name, &columns)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/features/views",
+    tag = "features",
+    responses(
+        (status = 200, description = "Registered feature views", body = serde_json::Value),
+    ),
+)]
+async fn list_feature_views_handler(State(state): State<ApiState>) -> impl IntoResponse {
+    let views = state.features.list_views();
+    Json(serde_json::json!({
+        "total": views.len(),
+        "views": views,
+    }))
+}
+
+/// Create (or replace) a feature view and backfill it from the table
+#[utoipa::path(
+    post,
+    path = "/api/v1/features/views",
+    tag = "features",
+    request_body(content = Object, description = "Feature view definition"),
+    responses(
+        (status = 201, description = "Feature view registered", body = serde_json::Value),
+        (status = 400, description = "Invalid feature view", body = ErrorResponse),
+        (status = 403, description = "Protected table", body = ErrorResponse),
+        (status = 404, description = "Source table not found", body = ErrorResponse),
+    ),
+)]
+async fn create_feature_view_handler(
+    State(state): State<ApiState>,
+    Json(view): Json<narayana_storage::feature_store::FeatureView>,
+) -> impl IntoResponse {
+    if is_protected_users_table(&state, view.table_id) {
+        let response = Json(ErrorResponse {
+            error: "Cannot compute features from protected system table".to_string(),
+            code: "PROTECTED_TABLE".to_string(),
+        });
+        return (StatusCode::FORBIDDEN, response).into_response();
+    }
+    let schema = match find_table_schema(&state, view.table_id) {
+        Some(schema) => schema,
+        None => {
+            let response = Json(ErrorResponse {
+                error: "Table not found".to_string(),
+                code: "TABLE_NOT_FOUND".to_string(),
+            });
+            return (StatusCode::NOT_FOUND, response).into_response();
+        }
+    };
+    let name = view.name.clone();
+    if let Err(e) = state.features.register_view(view, &schema) {
+        let response = Json(ErrorResponse {
+            error: sanitize_error_message(&format!("Invalid feature view: {}", e), "INVALID_FEATURE_VIEW"),
+            code: "INVALID_FEATURE_VIEW".to_string(),
+        });
+        return (StatusCode::BAD_REQUEST, response).into_response();
+    }
+    if let Err(e) = materialize_feature_view(&state.storage, &state.features, &name).await {
+        warn!("Could not backfill feature view {}: {}", name, e);
+    }
+    info!("Created feature view {}", name);
+    (StatusCode::CREATED, Json(state.features.get_view(&name))).into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/features/views/{name}",
+    tag = "features",
+    params(
+        ("name" = String, Path, description = "Feature view name"),
+    ),
+    responses(
+        (status = 200, description = "Feature view definition", body = serde_json::Value),
+        (status = 404, description = "Feature view not found", body = ErrorResponse),
+    ),
+)]
+async fn get_feature_view_handler(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+) -> impl IntoResponse {
+    match state.features.get_view(&name) {
+        Some(view) => (StatusCode::OK, Json(view)).into_response(),
+        None => {
+            let response = Json(ErrorResponse {
+                error: "Feature view not found".to_string(),
+                code: "FEATURE_VIEW_NOT_FOUND".to_string(),
+            });
+            (StatusCode::NOT_FOUND, response).into_response()
+        }
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/features/views/{name}",
+    tag = "features",
+    params(
+        ("name" = String, Path, description = "Feature view name"),
+    ),
+    responses(
+        (status = 200, description = "Feature view removed", body = serde_json::Value),
+        (status = 404, description = "Feature view not found", body = ErrorResponse),
+    ),
+)]
+async fn delete_feature_view_handler(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+) -> impl IntoResponse {
+    if state.features.remove_view(&name) {
+        (StatusCode::OK, Json(serde_json::json!({ "success": true }))).into_response()
+    } else {
+        let response = Json(ErrorResponse {
+            error: "Feature view not found".to_string(),
+            code: "FEATURE_VIEW_NOT_FOUND".to_string(),
+        });
+        (StatusCode::NOT_FOUND, response).into_response()
+    }
+}
+
+/// Online serving: current feature values of one entity
+#[utoipa::path(
+    post,
+    path = "/api/v1/features/online",
+    tag = "features",
+    request_body = OnlineFeaturesRequest,
+    responses(
+        (status = 200, description = "Latest feature values for the entity", body = serde_json::Value),
+        (status = 400, description = "Unknown feature", body = ErrorResponse),
+    ),
+)]
+async fn online_features_handler(
+    State(state): State<ApiState>,
+    Json(request): Json<OnlineFeaturesRequest>,
+) -> impl IntoResponse {
+    match state.features.get_online_features(&request.entity, &request.features) {
+        Ok(values) => {
+            let features: serde_json::Map<String, serde_json::Value> = request.features.into_iter()
+                .zip(values)
+                .map(|(name, value)| (name, serde_json::json!(value)))
+                .collect();
+            (StatusCode::OK, Json(serde_json::json!({
+                "entity": request.entity,
+                "features": features,
+            }))).into_response()
+        }
+        Err(e) => {
+            let response = Json(ErrorResponse {
+                error: sanitize_error_message(&format!("Feature lookup failed: {}", e), "INVALID_FEATURES"),
+                code: "INVALID_FEATURES".to_string(),
+            });
+            (StatusCode::BAD_REQUEST, response).into_response()
+        }
+    }
+}
+
+/// Training retrieval: point-in-time-correct feature values per entity row
+#[utoipa::path(
+    post,
+    path = "/api/v1/features/historical",
+    tag = "features",
+    request_body = HistoricalFeaturesRequest,
+    responses(
+        (status = 200, description = "Point-in-time feature values per entity row", body = serde_json::Value),
+        (status = 400, description = "Unknown feature", body = ErrorResponse),
+    ),
+)]
+async fn historical_features_handler(
+    State(state): State<ApiState>,
+    Json(request): Json<HistoricalFeaturesRequest>,
+) -> impl IntoResponse {
+    if request.entity_rows.len() > MAX_HISTORICAL_FEATURE_ROWS {
+        let response = Json(ErrorResponse {
+            error: format!("At most {} entity rows per request", MAX_HISTORICAL_FEATURE_ROWS),
+            code: "TOO_MANY_ROWS".to_string(),
+        });
+        return (StatusCode::BAD_REQUEST, response).into_response();
+    }
+    match state.features.get_historical_features(&request.entity_rows, &request.features) {
+        Ok(rows) => (StatusCode::OK, Json(serde_json::json!({
+            "features": request.features,
+            "rows": rows,
+        }))).into_response(),
+        Err(e) => {
+            let response = Json(ErrorResponse {
+                error: sanitize_error_message(&format!("Feature lookup failed: {}", e), "INVALID_FEATURES"),
+                code: "INVALID_FEATURES".to_string(),
+            });
+            (StatusCode::BAD_REQUEST, response).into_response()
+        }
+    }
+}
+
+// ===== Blob storage =====
+
+/// Blob chunks are read and sent one store chunk at a time
+const BLOB_STREAM_READ_BYTES: u64 = 1024 * 1024;
+
+/// Error response for a blob request
+fn blob_error(status: StatusCode, error: &str, code: &str) -> axum::response::Response {
+    let response = Json(ErrorResponse {
+        error: error.to_string(),
+        code: code.to_string(),
+    });
+    (status, response).into_response()
+}
+
+/// Upload a blob (raw request body); Content-Type is kept for downloads.
+/// Returns 201 for new content and 200 when identical content already exists.
+#[utoipa::path(
+    post,
+    path = "/api/v1/blobs",
+    tag = "blobs",
+    request_body(content = Vec<u8>, description = "Raw blob content", content_type = "application/octet-stream"),
+    responses(
+        (status = 201, description = "Blob stored", body = serde_json::Value),
+        (status = 200, description = "Blob already existed", body = serde_json::Value),
+        (status = 400, description = "Invalid upload", body = serde_json::Value),
+        (status = 413, description = "Blob exceeds the size limit", body = serde_json::Value),
+        (status = 500, description = "Storage error", body = serde_json::Value),
+    ),
+)]
+async fn upload_blob_handler(
+    State(state): State<ApiState>,
+    headers: HeaderMap,
+    body: Body,
+) -> impl IntoResponse {
+    use futures::StreamExt;
+
+    let content_type = headers.get(axum::http::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_string());
+    let declared = headers.get(axum::http::header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    if declared.is_some_and(|len| len > state.blobs.max_blob_size()) {
+        return blob_error(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            &format!("Blob exceeds the maximum size of {} bytes", state.blobs.max_blob_size()),
+            "PAYLOAD_TOO_LARGE",
+        );
+    }
+
+    let blobs = state.blobs.clone();
+    let mut writer = blobs.writer();
+    let mut stream = body.into_data_stream();
+    while let Some(frame) = stream.next().await {
+        let bytes = match frame {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                warn!("Blob upload aborted: {}", e);
+                return blob_error(StatusCode::BAD_REQUEST, "Failed to read request body", "INVALID_BODY");
+            }
+        };
+        if writer.written() + bytes.len() as u64 > blobs.max_blob_size() {
+            return blob_error(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                &format!("Blob exceeds the maximum size of {} bytes", blobs.max_blob_size()),
+                "PAYLOAD_TOO_LARGE",
+            );
+        }
+        if let Err(e) = writer.write(&bytes) {
+            error!("Blob upload failed: {}", e);
+            return blob_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to store blob", "BLOB_WRITE_FAILED");
+        }
+    }
+    match writer.finish(content_type) {
+        Ok((info, created)) => {
+            let status = if created { StatusCode::CREATED } else { StatusCode::OK };
+            (status, Json(serde_json::json!({
+                "blob": info,
+                "reference": info.reference(),
+                "url": format!("/api/v1/blobs/{}", info.id),
+            }))).into_response()
+        }
+        Err(e) => {
+            error!("Blob upload failed: {}", e);
+            blob_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to store blob", "BLOB_WRITE_FAILED")
+        }
+    }
+}
+
+/// List blob manifests, newest first
+#[utoipa::path(
+    get,
+    path = "/api/v1/blobs",
+    tag = "blobs",
+    responses(
+        (status = 200, description = "Stored blobs", body = serde_json::Value),
+    ),
+)]
+async fn list_blobs_handler(State(state): State<ApiState>) -> impl IntoResponse {
+    let blobs = state.blobs.list();
+    let total_bytes: u64 = blobs.iter().map(|b| b.size).sum();
+    Json(serde_json::json!({
+        "blobs": blobs,
+        "count": blobs.len(),
+        "total_bytes": total_bytes,
+    }))
+}
+
+/// Blob manifest; accepts a bare id or a `blob:<id>` column reference
+#[utoipa::path(
+    get,
+    path = "/api/v1/blobs/{id}/info",
+    tag = "blobs",
+    params(
+        ("id" = String, Path, description = "Blob id (SHA-256 of the content)"),
+    ),
+    responses(
+        (status = 200, description = "Blob metadata", body = serde_json::Value),
+        (status = 404, description = "Blob not found"),
+    ),
+)]
+async fn get_blob_info_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let id = id.strip_prefix(narayana_storage::blob_store::BLOB_REF_PREFIX).unwrap_or(&id);
+    match state.blobs.info(id) {
+        Some(info) => (StatusCode::OK, Json(info)).into_response(),
+        None => blob_error(StatusCode::NOT_FOUND, "Blob not found", "BLOB_NOT_FOUND"),
+    }
+}
+
+/// Download a blob, honouring single `Range` requests and `If-None-Match`
+#[utoipa::path(
+    get,
+    path = "/api/v1/blobs/{id}",
+    tag = "blobs",
+    params(
+        ("id" = String, Path, description = "Blob id (SHA-256 of the content)"),
+        ("Range" = Option<String>, Header, description = "Byte range, e.g. bytes=0-1023"),
+        ("If-None-Match" = Option<String>, Header, description = "ETag from a previous download"),
+    ),
+    responses(
+        (status = 200, description = "Blob content", body = Vec<u8>, content_type = "application/octet-stream"),
+        (status = 206, description = "Requested range", body = Vec<u8>, content_type = "application/octet-stream"),
+        (status = 304, description = "Not modified"),
+        (status = 404, description = "Blob not found"),
+        (status = 416, description = "Range not satisfiable"),
+    ),
+)]
+async fn download_blob_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    use axum::http::header;
+    use narayana_storage::blob_store::{parse_range_header, RangeRequest};
+
+    let id = id.strip_prefix(narayana_storage::blob_store::BLOB_REF_PREFIX).unwrap_or(&id).to_string();
+    let Some(info) = state.blobs.info(&id) else {
+        return blob_error(StatusCode::NOT_FOUND, "Blob not found", "BLOB_NOT_FOUND");
+    };
+    // Content never changes for an id
+    let etag = format!("\"{}\"", info.id);
+    let content_type = info.content_type.clone().unwrap_or_else(|| "application/octet-stream".to_string());
+    let builder = Response::builder()
+        .header(header::ETAG, &etag)
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::CACHE_CONTROL, "private, max-age=31536000, immutable");
+
+    let not_modified = headers.get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.split(',').any(|tag| tag.trim().trim_start_matches("W/") == etag || tag.trim() == "*"));
+    if not_modified {
+        return builder.status(StatusCode::NOT_MODIFIED).body(Body::empty()).unwrap().into_response();
+    }
+
+    let range = headers.get(header::RANGE).and_then(|v| v.to_str().ok());
+    let (status, start, end) = match parse_range_header(range, info.size) {
+        RangeRequest::Partial(start, end) => (StatusCode::PARTIAL_CONTENT, start, end + 1),
+        RangeRequest::Full => (StatusCode::OK, 0, info.size),
+        RangeRequest::Unsatisfiable => {
+            return builder
+                .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                .header(header::CONTENT_RANGE, format!("bytes */{}", info.size))
+                .body(Body::empty())
+                .unwrap()
+                .into_response();
+        }
+    };
+    let mut builder = builder
+        .status(status)
+        .header(header::CONTENT_TYPE, content_type)
+        .header(header::CONTENT_LENGTH, end - start);
+    if status == StatusCode::PARTIAL_CONTENT {
+        builder = builder.header(header::CONTENT_RANGE, format!("bytes {}-{}/{}", start, end - 1, info.size));
+    }
+
+    let blobs = state.blobs.clone();
+    let step = (info.chunk_size as u64).clamp(1, BLOB_STREAM_READ_BYTES);
+    let stream = futures::stream::unfold(start, move |position| {
+        let blobs = blobs.clone();
+        let id = id.clone();
+        async move {
+            if position >= end {
+                return None;
+            }
+            let len = step.min(end - position);
+            let piece = blobs.read_range(&id, position, len).map_err(|e| {
+                error!("Blob read failed: {}", e);
+                std::io::Error::other(e.to_string())
+            });
+            let next = if piece.is_ok() { position + len } else { end };
+            Some((piece.map(axum::body::Bytes::from), next))
+        }
+    });
+    builder.body(Body::from_stream(stream)).unwrap().into_response()
+}
+
+/// Delete a blob. Rows that reference it keep their `blob:` value.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/blobs/{id}",
+    tag = "blobs",
+    params(
+        ("id" = String, Path, description = "Blob id (SHA-256 of the content)"),
+    ),
+    responses(
+        (status = 204, description = "Blob deleted"),
+        (status = 404, description = "Blob not found"),
+    ),
+)]
+async fn delete_blob_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let id = id.strip_prefix(narayana_storage::blob_store::BLOB_REF_PREFIX).unwrap_or(&id);
+    match state.blobs.delete(id) {
+        Ok(()) => StatusCode::NO_CONTENT.into_response(),
+        Err(_) => blob_error(StatusCode::NOT_FOUND, "Blob not found", "BLOB_NOT_FOUND"),
+    }
+}
+
+// ============================================
+// SKILLS
+// ============================================
+
+/// Largest skill bundle accepted (worker code is inlined)
+const SKILL_BUNDLE_LIMIT: usize = 64 * 1024 * 1024;
+
+/// Skill errors are returned as is: they explain what in the bundle was
+/// rejected, and a failed operation has already been rolled back
+fn skill_error(status: StatusCode, error: String, code: &str) -> axum::response::Response {
+    warn!("{}: {}", code, error);
+    let response = Json(ErrorResponse {
+        error,
+        code: code.to_string(),
+    });
+    (status, response).into_response()
+}
+
+/// Installed skills
+#[utoipa::path(
+    get,
+    path = "/api/v1/skills",
+    tag = "skills",
+    responses(
+        (status = 200, description = "Installed skills and their versions", body = serde_json::Value),
+    ),
+)]
+async fn list_skills_handler(State(state): State<ApiState>) -> impl IntoResponse {
+    let skills = state.skills.list();
+    Json(serde_json::json!({
+        "total": skills.len(),
+        "skills": skills,
+    }))
+}
+
+/// Install a skill bundle (tables, workers, subscriptions and behaviors) as a unit
+#[utoipa::path(
+    post,
+    path = "/api/v1/skills",
+    tag = "skills",
+    request_body(content = Object, description = "Skill bundle with worker code and behaviors inlined"),
+    responses(
+        (status = 201, description = "Skill installed", body = serde_json::Value),
+        (status = 400, description = "Invalid bundle or install failed (nothing was applied)", body = ErrorResponse),
+    ),
+)]
+async fn install_skill_handler(
+    State(state): State<ApiState>,
+    Json(bundle): Json<narayana_core::skill::SkillManifest>,
+) -> impl IntoResponse {
+    match state.skills.install(bundle).await {
+        Ok(skill) => (StatusCode::CREATED, Json(skill)).into_response(),
+        Err(e) => skill_error(StatusCode::BAD_REQUEST, format!("Skill install failed: {}", e), "SKILL_INSTALL_FAILED"),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/skills/{name}",
+    tag = "skills",
+    params(
+        ("name" = String, Path, description = "Skill name"),
+    ),
+    responses(
+        (status = 200, description = "Installed skill", body = serde_json::Value),
+        (status = 404, description = "Skill not installed", body = ErrorResponse),
+    ),
+)]
+async fn get_skill_handler(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+) -> impl IntoResponse {
+    match state.skills.get(&name) {
+        Some(skill) => (StatusCode::OK, Json(skill)).into_response(),
+        None => skill_error(StatusCode::NOT_FOUND, "Skill not installed".to_string(), "SKILL_NOT_FOUND"),
+    }
+}
+
+/// Upgrade an installed skill to a newer version of its bundle
+#[utoipa::path(
+    put,
+    path = "/api/v1/skills/{name}",
+    tag = "skills",
+    params(
+        ("name" = String, Path, description = "Skill name"),
+    ),
+    request_body(content = Object, description = "Skill bundle with worker code and behaviors inlined"),
+    responses(
+        (status = 200, description = "Skill upgraded", body = serde_json::Value),
+        (status = 400, description = "Invalid bundle or upgrade failed (previous version kept)", body = ErrorResponse),
+    ),
+)]
+async fn upgrade_skill_handler(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+    Json(bundle): Json<narayana_core::skill::SkillManifest>,
+) -> impl IntoResponse {
+    if bundle.name != name {
+        return skill_error(StatusCode::BAD_REQUEST, format!("Bundle is for skill '{}'", bundle.name), "SKILL_NAME_MISMATCH");
+    }
+    match state.skills.upgrade(bundle).await {
+        Ok(skill) => (StatusCode::OK, Json(skill)).into_response(),
+        Err(e) => skill_error(StatusCode::BAD_REQUEST, format!("Skill upgrade failed: {}", e), "SKILL_UPGRADE_FAILED"),
+    }
+}
+
+/// Uninstall a skill; `purge=true` also drops its tables
+#[utoipa::path(
+    delete,
+    path = "/api/v1/skills/{name}",
+    tag = "skills",
+    params(
+        ("name" = String, Path, description = "Skill name"),
+        ("purge" = Option<bool>, Query, description = "Also drop the skill's tables"),
+    ),
+    responses(
+        (status = 200, description = "Skill uninstalled", body = serde_json::Value),
+        (status = 404, description = "Skill not installed", body = ErrorResponse),
+    ),
+)]
+async fn uninstall_skill_handler(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> impl IntoResponse {
+    let purge = params.get("purge").is_some_and(|v| v == "true");
+    if state.skills.get(&name).is_none() {
+        return skill_error(StatusCode::NOT_FOUND, "Skill not installed".to_string(), "SKILL_NOT_FOUND");
+    }
+    match state.skills.uninstall(&name, purge).await {
+        Ok(skill) => (StatusCode::OK, Json(skill)).into_response(),
+        Err(e) => skill_error(StatusCode::INTERNAL_SERVER_ERROR, format!("Skill uninstall failed: {}", e), "SKILL_UNINSTALL_FAILED"),
+    }
+}
+
+/// Install, upgrade and uninstall history from the skills system table
+#[utoipa::path(
+    get,
+    path = "/api/v1/skills/{name}/history",
+    tag = "skills",
+    params(
+        ("name" = String, Path, description = "Skill name"),
+    ),
+    responses(
+        (status = 200, description = "Recorded skill operations, oldest first", body = serde_json::Value),
+    ),
+)]
+async fn skill_history_handler(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+) -> impl IntoResponse {
+    match state.skills.history(Some(&name)).await {
+        Ok(history) => (StatusCode::OK, Json(serde_json::json!({ "history": history }))).into_response(),
+        Err(e) => skill_error(StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to read skill history: {}", e), "SKILL_HISTORY_FAILED"),
+    }
+}
+
+// ============================================
+// JOBS
+// ============================================
+
+fn job_error(status: StatusCode, error: String, code: &str) -> axum::response::Response {
+    (status, Json(ErrorResponse { error, code: code.to_string() })).into_response()
+}
+
+/// Maintenance jobs with their schedules, pause state and last run
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/jobs",
+    tag = "jobs",
+    responses(
+        (status = 200, description = "Jobs by name", body = serde_json::Value),
+    ),
+)]
+async fn list_jobs_handler(State(state): State<ApiState>) -> impl IntoResponse {
+    let jobs = state.jobs.list();
+    Json(serde_json::json!({
+        "total": jobs.len(),
+        "jobs": jobs,
+    }))
+}
+
+/// A job and its recent runs, newest first
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/jobs/{name}",
+    tag = "jobs",
+    params(
+        ("name" = String, Path, description = "Job name"),
+        ("limit" = Option<usize>, Query, description = "Runs to return (default 20)"),
+    ),
+    responses(
+        (status = 200, description = "Job status and run history", body = serde_json::Value),
+        (status = 404, description = "Job not found", body = ErrorResponse),
+    ),
+)]
+async fn get_job_handler(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> impl IntoResponse {
+    let limit = params.get("limit").and_then(|v| v.parse::<usize>().ok()).unwrap_or(20).min(1000);
+    match (state.jobs.get(&name), state.jobs.runs(&name, limit)) {
+        (Some(job), Some(runs)) => (StatusCode::OK, Json(serde_json::json!({ "job": job, "runs": runs }))).into_response(),
+        _ => job_error(StatusCode::NOT_FOUND, "Job not found".to_string(), "JOB_NOT_FOUND"),
+    }
+}
+
+/// Start a job now, even when paused
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/jobs/{name}/trigger",
+    tag = "jobs",
+    params(
+        ("name" = String, Path, description = "Job name"),
+    ),
+    responses(
+        (status = 202, description = "Run started", body = serde_json::Value),
+        (status = 404, description = "Job not found", body = ErrorResponse),
+        (status = 409, description = "Job already running or concurrency limit reached", body = ErrorResponse),
+    ),
+)]
+async fn trigger_job_handler(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+) -> impl IntoResponse {
+    if state.jobs.get(&name).is_none() {
+        return job_error(StatusCode::NOT_FOUND, "Job not found".to_string(), "JOB_NOT_FOUND");
+    }
+    match state.jobs.trigger(&name) {
+        Ok(run_id) => (StatusCode::ACCEPTED, Json(serde_json::json!({ "job": name, "run_id": run_id }))).into_response(),
+        Err(e) => job_error(StatusCode::CONFLICT, e.to_string(), "JOB_NOT_STARTED"),
+    }
+}
+
+/// Stop running a job on its schedule
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/jobs/{name}/pause",
+    tag = "jobs",
+    params(
+        ("name" = String, Path, description = "Job name"),
+    ),
+    responses(
+        (status = 200, description = "Job paused", body = serde_json::Value),
+        (status = 404, description = "Job not found", body = ErrorResponse),
+    ),
+)]
+async fn pause_job_handler(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+) -> impl IntoResponse {
+    match state.jobs.pause(&name) {
+        Ok(job) => (StatusCode::OK, Json(job)).into_response(),
+        Err(_) => job_error(StatusCode::NOT_FOUND, "Job not found".to_string(), "JOB_NOT_FOUND"),
+    }
+}
+
+/// Put a paused job back on its schedule
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/jobs/{name}/resume",
+    tag = "jobs",
+    params(
+        ("name" = String, Path, description = "Job name"),
+    ),
+    responses(
+        (status = 200, description = "Job resumed", body = serde_json::Value),
+        (status = 404, description = "Job not found", body = ErrorResponse),
+    ),
+)]
+async fn resume_job_handler(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+) -> impl IntoResponse {
+    match state.jobs.resume(&name) {
+        Ok(job) => (StatusCode::OK, Json(job)).into_response(),
+        Err(_) => job_error(StatusCode::NOT_FOUND, "Job not found".to_string(), "JOB_NOT_FOUND"),
+    }
+}
+
+// ============================================
+// CONNECTORS
+// ============================================
+
+/// Writes connector records into tables of the default database through the
+/// same validation as bulk inserts, and event sink batches through the same
+/// insert path
+pub struct TableIngestSink {
+    state: ApiState,
+}
+
+impl TableIngestSink {
+    pub fn new(state: ApiState) -> Self {
+        Self { state }
+    }
+}
+
+#[async_trait::async_trait]
+impl narayana_storage::connectors::IngestSink for TableIngestSink {
+    async fn write(
+        &self,
+        table: &str,
+        records: Vec<serde_json::Value>,
+    ) -> narayana_core::Result<narayana_storage::connectors::WriteReport> {
+        use crate::bulk_insert::RowBatchBuilder;
+        use narayana_storage::connectors::WriteReport;
+
+        let state = &self.state;
+        let table_id = state.db_manager.get_table_by_name("default", table)
+            .ok_or_else(|| narayana_core::Error::Storage(format!("Table not found: {}", table)))?;
+        // SECURITY: Connectors never write the protected users table
+        if is_protected_users_table(state, table_id) {
+            return Err(narayana_core::Error::Storage("Cannot write protected system table".to_string()));
+        }
+        let info = state.db_manager.get_table_info(table_id)
+            .ok_or_else(|| narayana_core::Error::Storage(format!("Table not found: {}", table)))?;
+
+        let encode_geometry = |data_type: &DataType, value: serde_json::Value| -> Result<Vec<u8>, String> {
+            match geometry_column_from_json(data_type, serde_json::Value::Array(vec![value])) {
+                Ok(Column::Binary(mut values)) if values.len() == 1 => Ok(values.remove(0)),
+                Ok(_) => Err("invalid geometry".to_string()),
+                Err(e) => Err(e.to_string()),
+            }
+        };
+        let mut builder = RowBatchBuilder::new(client_insert_fields(&info.schema), &encode_geometry);
+        let mut report = WriteReport::default();
+        for (row, record) in records.into_iter().enumerate() {
+            if let Err(e) = builder.push_row(row, record) {
+                report.rejected += 1;
+                report.errors.push(match e.field {
+                    Some(field) => format!("Record {} field '{}': {}", row, field, e.error),
+                    None => format!("Record {}: {}", row, e.error),
+                });
+            }
+        }
+        if builder.is_empty() {
+            return Ok(report);
+        }
+        let (columns, _) = builder.take_batch();
+        report.written = write_insert_batch(state, table_id, &info.schema, columns).await
+            .map_err(|(_, failure)| narayana_core::Error::Storage(failure.error))?;
+        Ok(report)
+    }
+}
+
+#[async_trait::async_trait]
+impl narayana_rde::transports::table::TableInserter for TableIngestSink {
+    async fn insert(&self, table_id: TableId, schema: &Schema, columns: Vec<Column>) -> narayana_core::Result<usize> {
+        // SECURITY: Event sinks never write the protected users table
+        if is_protected_users_table(&self.state, table_id) {
+            return Err(narayana_core::Error::Storage("Cannot write protected system table".to_string()));
+        }
+        write_insert_batch(&self.state, table_id, schema, columns).await
+            .map_err(|(_, failure)| narayana_core::Error::Storage(failure.error))
+    }
+}
+
+fn connector_error(e: narayana_core::Error) -> axum::response::Response {
+    let error = match e {
+        narayana_core::Error::Configuration(message) => message,
+        e => e.to_string(),
+    };
+    job_error(StatusCode::BAD_REQUEST, error, "INVALID_CONNECTOR")
+}
+
+fn connector_not_found() -> axum::response::Response {
+    job_error(StatusCode::NOT_FOUND, "Connector not found".to_string(), "CONNECTOR_NOT_FOUND")
+}
+
+/// Ingest connectors with their cursors, totals and polling jobs (secrets masked)
+#[utoipa::path(
+    get,
+    path = "/api/v1/connectors",
+    tag = "connectors",
+    responses(
+        (status = 200, description = "Connectors by name", body = serde_json::Value),
+    ),
+)]
+async fn list_connectors_handler(State(state): State<ApiState>) -> impl IntoResponse {
+    let connectors = state.connectors.list();
+    Json(serde_json::json!({
+        "total": connectors.len(),
+        "connectors": connectors,
+    }))
+}
+
+/// Create (or replace) a connector and schedule its polls
+#[utoipa::path(
+    post,
+    path = "/api/v1/connectors",
+    tag = "connectors",
+    request_body(content = Object, description = "Connector: name, table, source (http, mqtt, rss or s3), schedule, dedupe_key, filters, transforms"),
+    responses(
+        (status = 201, description = "Connector created", body = serde_json::Value),
+        (status = 400, description = "Invalid connector or source blocked by the egress policy", body = ErrorResponse),
+        (status = 403, description = "Protected table", body = ErrorResponse),
+        (status = 404, description = "Table not found", body = ErrorResponse),
+    ),
+)]
+async fn create_connector_handler(
+    State(state): State<ApiState>,
+    Json(spec): Json<narayana_storage::connectors::ConnectorSpec>,
+) -> impl IntoResponse {
+    if is_protected_users_table_name(&spec.table) {
+        let response = Json(ErrorResponse {
+            error: "Cannot ingest into protected system table".to_string(),
+            code: "PROTECTED_TABLE".to_string(),
+        });
+        return (StatusCode::FORBIDDEN, response).into_response();
+    }
+    if state.db_manager.get_table_by_name("default", &spec.table).is_none() {
+        return job_error(StatusCode::NOT_FOUND, "Table not found".to_string(), "TABLE_NOT_FOUND");
+    }
+    match state.connectors.add_connector(spec) {
+        Ok(connector) => (StatusCode::CREATED, Json(connector)).into_response(),
+        Err(e) => connector_error(e),
+    }
+}
+
+/// A connector's status, including its polling job and last run
+#[utoipa::path(
+    get,
+    path = "/api/v1/connectors/{name}",
+    tag = "connectors",
+    params(
+        ("name" = String, Path, description = "Connector name"),
+    ),
+    responses(
+        (status = 200, description = "Connector status", body = serde_json::Value),
+        (status = 404, description = "Connector not found", body = ErrorResponse),
+    ),
+)]
+async fn get_connector_handler(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+) -> impl IntoResponse {
+    match state.connectors.get(&name) {
+        Some(connector) => (StatusCode::OK, Json(connector)).into_response(),
+        None => connector_not_found(),
+    }
+}
+
+/// Remove a connector and its polling job; ingested rows stay
+#[utoipa::path(
+    delete,
+    path = "/api/v1/connectors/{name}",
+    tag = "connectors",
+    params(
+        ("name" = String, Path, description = "Connector name"),
+    ),
+    responses(
+        (status = 204, description = "Connector removed"),
+        (status = 404, description = "Connector not found", body = ErrorResponse),
+    ),
+)]
+async fn delete_connector_handler(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+) -> impl IntoResponse {
+    if state.connectors.remove_connector(&name) {
+        StatusCode::NO_CONTENT.into_response()
+    } else {
+        connector_not_found()
+    }
+}
+
+/// Poll a connector now through its job, paused or not
+#[utoipa::path(
+    post,
+    path = "/api/v1/connectors/{name}/poll",
+    tag = "connectors",
+    params(
+        ("name" = String, Path, description = "Connector name"),
+    ),
+    responses(
+        (status = 202, description = "Poll started; its report becomes the job run's output", body = serde_json::Value),
+        (status = 404, description = "Connector not found", body = ErrorResponse),
+        (status = 409, description = "Already polling or concurrency limit reached", body = ErrorResponse),
+    ),
+)]
+async fn poll_connector_handler(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+) -> impl IntoResponse {
+    if state.connectors.get(&name).is_none() {
+        return connector_not_found();
+    }
+    match state.connectors.trigger(&name) {
+        Ok(run_id) => (StatusCode::ACCEPTED, Json(serde_json::json!({
+            "connector": name,
+            "job": narayana_storage::connectors::job_name(&name),
+            "run_id": run_id,
+        }))).into_response(),
+        Err(e) => job_error(StatusCode::CONFLICT, e.to_string(), "POLL_NOT_STARTED"),
+    }
+}
+
+/// Stop polling a connector on its schedule
+#[utoipa::path(
+    post,
+    path = "/api/v1/connectors/{name}/pause",
+    tag = "connectors",
+    params(
+        ("name" = String, Path, description = "Connector name"),
+    ),
+    responses(
+        (status = 200, description = "Connector paused", body = serde_json::Value),
+        (status = 404, description = "Connector not found", body = ErrorResponse),
+    ),
+)]
+async fn pause_connector_handler(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+) -> impl IntoResponse {
+    match state.connectors.pause(&name) {
+        Ok(connector) => (StatusCode::OK, Json(connector)).into_response(),
+        Err(_) => connector_not_found(),
+    }
+}
+
+/// Put a paused connector back on its schedule
+#[utoipa::path(
+    post,
+    path = "/api/v1/connectors/{name}/resume",
+    tag = "connectors",
+    params(
+        ("name" = String, Path, description = "Connector name"),
+    ),
+    responses(
+        (status = 200, description = "Connector resumed", body = serde_json::Value),
+        (status = 404, description = "Connector not found", body = ErrorResponse),
+    ),
+)]
+async fn resume_connector_handler(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+) -> impl IntoResponse {
+    match state.connectors.resume(&name) {
+        Ok(connector) => (StatusCode::OK, Json(connector)).into_response(),
+        Err(_) => connector_not_found(),
+    }
+}
+
+/// Forget a connector's cursor and dedupe keys so it reads its source from the start
+#[utoipa::path(
+    post,
+    path = "/api/v1/connectors/{name}/reset",
+    tag = "connectors",
+    params(
+        ("name" = String, Path, description = "Connector name"),
+    ),
+    responses(
+        (status = 200, description = "Connector reset", body = serde_json::Value),
+        (status = 404, description = "Connector not found", body = ErrorResponse),
+    ),
+)]
+async fn reset_connector_handler(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+) -> impl IntoResponse {
+    match state.connectors.reset(&name) {
+        Ok(connector) => (StatusCode::OK, Json(connector)).into_response(),
+        Err(_) => connector_not_found(),
+    }
+}
+
+// ============================================
+// INVARIANTS
+// ============================================
+
+/// Invariant violations found so far, most recently seen first, with the last pass
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/invariants",
+    tag = "invariants",
+    params(
+        ("invariant" = Option<String>, Query, description = "Only this invariant (row_counts, monotonic_event_ids, schema_consistency)"),
+        ("limit" = Option<usize>, Query, description = "Violations to return (default 100)"),
+    ),
+    responses(
+        (status = 200, description = "Violations and checker status", body = serde_json::Value),
+        (status = 400, description = "Unknown invariant", body = ErrorResponse),
+    ),
+)]
+async fn list_invariant_violations_handler(
+    State(state): State<ApiState>,
+    Query(params): Query<HashMap<String, String>>,
+) -> impl IntoResponse {
+    use narayana_storage::bug_detection::Invariant;
+
+    let invariant = match params.get("invariant") {
+        None => None,
+        Some(name) => match Invariant::parse(name) {
+            Some(invariant) => Some(invariant),
+            None => return job_error(StatusCode::BAD_REQUEST, format!("Unknown invariant '{}'", name), "INVALID_INVARIANT"),
+        },
+    };
+    let limit = params.get("limit").and_then(|v| v.parse::<usize>().ok()).unwrap_or(100).min(1000);
+    let violations: Vec<_> = state.invariants.violations()
+        .into_iter()
+        .filter(|v| invariant.is_none_or(|i| v.invariant == i))
+        .collect();
+    let config = state.invariants.config();
+    let checks: Vec<&str> = config.invariants.iter().map(|i| i.name()).collect();
+    (StatusCode::OK, Json(serde_json::json!({
+        "checks": checks,
+        "sample_rate": config.sample_rate,
+        "interval_secs": config.interval.as_secs(),
+        "last_report": state.invariants.last_report(),
+        "total": violations.len(),
+        "violations": violations.into_iter().take(limit).collect::<Vec<_>>(),
+    }))).into_response()
+}
+
+/// Check the configured invariants now; by default every table and stream is checked
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/invariants/check",
+    tag = "invariants",
+    params(
+        ("sample_rate" = Option<f64>, Query, description = "Chance (0-1) each table or stream is checked (default 1)"),
+    ),
+    responses(
+        (status = 200, description = "Report of the pass", body = serde_json::Value),
+        (status = 400, description = "Invalid sample rate", body = ErrorResponse),
+    ),
+)]
+async fn check_invariants_handler(
+    State(state): State<ApiState>,
+    Query(params): Query<HashMap<String, String>>,
+) -> impl IntoResponse {
+    let sample_rate = match params.get("sample_rate").map(|v| v.parse::<f64>()) {
+        None => 1.0,
+        Some(Ok(rate)) if (0.0..=1.0).contains(&rate) => rate,
+        Some(_) => return job_error(StatusCode::BAD_REQUEST, "sample_rate must be between 0 and 1".to_string(), "INVALID_SAMPLE_RATE"),
+    };
+    let report = state.invariants.check(sample_rate).await;
+    (StatusCode::OK, Json(report)).into_response()
+}
+
+/// Forget the recorded violations (ones still present are found again on the next pass)
+#[utoipa::path(
+    delete,
+    path = "/api/v1/admin/invariants/violations",
+    tag = "invariants",
+    responses(
+        (status = 200, description = "Violations cleared", body = serde_json::Value),
+    ),
+)]
+async fn clear_invariant_violations_handler(State(state): State<ApiState>) -> impl IntoResponse {
+    let cleared = state.invariants.clear();
+    Json(serde_json::json!({ "cleared": cleared }))
+}
+
+// ============================================
+// STORAGE QUOTAS
+// ============================================
+
+/// Usage and limits per table, per database and in total, free disk space,
+/// read-only mode and recent quota events
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/quotas",
+    tag = "quotas",
+    responses(
+        (status = 200, description = "Quota status", body = serde_json::Value),
+    ),
+)]
+async fn quota_status_handler(State(state): State<ApiState>) -> impl IntoResponse {
+    Json(state.quotas.status())
+}
+
+/// Recount usage and measure free disk space now instead of waiting for `check_interval`
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/quotas/check",
+    tag = "quotas",
+    responses(
+        (status = 200, description = "Quota status after the check", body = serde_json::Value),
+    ),
+)]
+async fn check_quotas_handler(State(state): State<ApiState>) -> impl IntoResponse {
+    state.quotas.check().await;
+    Json(state.quotas.status())
+}
+
+/// Status and body for writes refused by a storage quota (507) or low-disk read-only mode (503)
+fn quota_error(error: &narayana_core::Error) -> Option<(StatusCode, ErrorResponse)> {
+    let (status, code) = match error {
+        narayana_core::Error::QuotaExceeded(_) => (StatusCode::INSUFFICIENT_STORAGE, "QUOTA_EXCEEDED"),
+        narayana_core::Error::ReadOnly(_) => (StatusCode::SERVICE_UNAVAILABLE, "READ_ONLY"),
+        _ => return None,
+    };
+    Some((status, ErrorResponse { error: error.to_string(), code: code.to_string() }))
+}
+
+// ============================================
+// RECOMMENDATIONS
+// ============================================
+
+/// Index, sort key and materialized view recommendations, biggest estimated benefit first
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/recommendations",
+    tag = "recommendations",
+    params(
+        ("status" = Option<String>, Query, description = "Only this status (pending, applied, dismissed, failed)"),
+        ("refresh" = Option<bool>, Query, description = "Analyze the recorded workload first (default true)"),
+    ),
+    responses(
+        (status = 200, description = "Recommendations", body = serde_json::Value),
+        (status = 400, description = "Unknown status", body = ErrorResponse),
+    ),
+)]
+async fn list_recommendations_handler(
+    State(state): State<ApiState>,
+    Query(params): Query<HashMap<String, String>>,
+) -> impl IntoResponse {
+    use narayana_storage::query_learning::RecommendationStatus;
+
+    let status = match params.get("status") {
+        None => None,
+        Some(name) => match RecommendationStatus::parse(name) {
+            Some(status) => Some(status),
+            None => return job_error(StatusCode::BAD_REQUEST, format!("Unknown status '{}'", name), "INVALID_STATUS"),
+        },
+    };
+    let recommendations = if params.get("refresh").map(|v| v != "false").unwrap_or(true) {
+        state.query_learning.recommend()
+    } else {
+        state.query_learning.recommendations()
+    };
+    let recommendations: Vec<_> = recommendations
+        .into_iter()
+        .filter(|r| status.is_none_or(|s| r.status == s))
+        .collect();
+    (StatusCode::OK, Json(serde_json::json!({
+        "total": recommendations.len(),
+        "recommendations": recommendations,
+    }))).into_response()
+}
+
+/// A recommendation and, once applied, its measured impact
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/recommendations/{id}",
+    tag = "recommendations",
+    params(
+        ("id" = String, Path, description = "Recommendation ID"),
+    ),
+    responses(
+        (status = 200, description = "Recommendation", body = serde_json::Value),
+        (status = 404, description = "Recommendation not found", body = ErrorResponse),
+    ),
+)]
+async fn get_recommendation_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    match state.query_learning.recommendation(&id) {
+        Some(recommendation) => (StatusCode::OK, Json(recommendation)).into_response(),
+        None => job_error(StatusCode::NOT_FOUND, "Recommendation not found".to_string(), "RECOMMENDATION_NOT_FOUND"),
+    }
+}
+
+/// Apply a recommendation; affected queries are measured against their times before
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/recommendations/{id}/apply",
+    tag = "recommendations",
+    params(
+        ("id" = String, Path, description = "Recommendation ID"),
+    ),
+    responses(
+        (status = 200, description = "Recommendation applied", body = serde_json::Value),
+        (status = 404, description = "Recommendation not found", body = ErrorResponse),
+        (status = 409, description = "Already applied or rejected", body = ErrorResponse),
+    ),
+)]
+async fn apply_recommendation_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    if state.query_learning.recommendation(&id).is_none() {
+        return job_error(StatusCode::NOT_FOUND, "Recommendation not found".to_string(), "RECOMMENDATION_NOT_FOUND");
+    }
+    match state.query_learning.apply_recommendation(&id).await {
+        Ok(recommendation) => (StatusCode::OK, Json(recommendation)).into_response(),
+        Err(e) => job_error(StatusCode::CONFLICT, e.to_string(), "RECOMMENDATION_NOT_APPLIED"),
+    }
+}
+
+/// Set a recommendation aside
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/recommendations/{id}/dismiss",
+    tag = "recommendations",
+    params(
+        ("id" = String, Path, description = "Recommendation ID"),
+    ),
+    responses(
+        (status = 200, description = "Recommendation dismissed", body = serde_json::Value),
+        (status = 404, description = "Recommendation not found", body = ErrorResponse),
+    ),
+)]
+async fn dismiss_recommendation_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    match state.query_learning.dismiss_recommendation(&id) {
+        Some(recommendation) => (StatusCode::OK, Json(recommendation)).into_response(),
+        None => job_error(StatusCode::NOT_FOUND, "Recommendation not found".to_string(), "RECOMMENDATION_NOT_FOUND"),
+    }
+}
+
+// ============================================
+// RDE ACTOR TOKENS
+// ============================================
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct IssueActorTokenRequest {
+    /// Seconds until the token expires (never when omitted)
+    pub expires_in_secs: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RotateActorTokensRequest {
+    /// Seconds the actor's current tokens keep working (0 = revoke now)
+    #[serde(default)]
+    pub grace_secs: u64,
+    /// Seconds until the new token expires (never when omitted)
+    pub expires_in_secs: Option<u64>,
+}
+
+fn token_expiry(expires_in_secs: Option<u64>) -> Option<u64> {
+    expires_in_secs.map(|secs| {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs()
+            .saturating_add(secs)
+    })
+}
+
+fn rde_actor_not_found() -> axum::response::Response {
+    job_error(StatusCode::NOT_FOUND, "Actor not found".to_string(), "RDE_ACTOR_NOT_FOUND")
+}
+
+/// An RDE actor's tokens with expiry and revocation times (never the secrets)
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/rde/actors/{id}/tokens",
+    tag = "rde",
+    params(
+        ("id" = String, Path, description = "Actor ID"),
+    ),
+    responses(
+        (status = 200, description = "Actor tokens, oldest first", body = serde_json::Value),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 404, description = "Actor not found", body = ErrorResponse),
+    ),
+)]
+async fn list_actor_tokens_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+) -> impl IntoResponse {
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    match state.rde.auth_manager().list_tokens(&narayana_rde::ActorId::from(id)) {
+        Some(tokens) => (StatusCode::OK, Json(serde_json::json!({ "tokens": tokens }))).into_response(),
+        None => rde_actor_not_found(),
+    }
+}
+
+/// Issue an RDE actor an additional token; the secret is only returned here
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/rde/actors/{id}/tokens",
+    tag = "rde",
+    params(
+        ("id" = String, Path, description = "Actor ID"),
+    ),
+    request_body = IssueActorTokenRequest,
+    responses(
+        (status = 201, description = "Token issued", body = serde_json::Value),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 404, description = "Actor not found", body = ErrorResponse),
+        (status = 409, description = "Too many active tokens", body = ErrorResponse),
+    ),
+)]
+async fn issue_actor_token_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+    Json(request): Json<IssueActorTokenRequest>,
+) -> impl IntoResponse {
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    let actor_id = narayana_rde::ActorId::from(id);
+    if state.rde.get_actor(&actor_id).is_none() {
+        return rde_actor_not_found();
+    }
+    match state.rde.auth_manager().issue_token(&actor_id, token_expiry(request.expires_in_secs)) {
+        Ok(issued) => (StatusCode::CREATED, Json(issued)).into_response(),
+        Err(e) => job_error(StatusCode::CONFLICT, e.to_string(), "RDE_TOKEN_LIMIT"),
+    }
+}
+
+/// Rotate an RDE actor's tokens: a new token is issued and every other one
+/// stops working after the grace period. Subscriptions are kept.
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/rde/actors/{id}/tokens/rotate",
+    tag = "rde",
+    params(
+        ("id" = String, Path, description = "Actor ID"),
+    ),
+    request_body = RotateActorTokensRequest,
+    responses(
+        (status = 201, description = "Replacement token issued", body = serde_json::Value),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 404, description = "Actor not found", body = ErrorResponse),
+        (status = 409, description = "Too many active tokens", body = ErrorResponse),
+    ),
+)]
+async fn rotate_actor_tokens_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+    Json(request): Json<RotateActorTokensRequest>,
+) -> impl IntoResponse {
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    let actor_id = narayana_rde::ActorId::from(id);
+    if state.rde.get_actor(&actor_id).is_none() {
+        return rde_actor_not_found();
+    }
+    let expires_at = token_expiry(request.expires_in_secs);
+    match state.rde.auth_manager().rotate_token(&actor_id, None, request.grace_secs, expires_at) {
+        Ok(issued) => {
+            info!("Rotated tokens of RDE actor {} (grace {}s)", actor_id, request.grace_secs);
+            (StatusCode::CREATED, Json(issued)).into_response()
+        }
+        Err(e) => job_error(StatusCode::CONFLICT, e.to_string(), "RDE_TOKEN_LIMIT"),
+    }
+}
+
+/// Revoke one of an RDE actor's tokens with immediate effect
+#[utoipa::path(
+    delete,
+    path = "/api/v1/admin/rde/actors/{id}/tokens/{token_id}",
+    tag = "rde",
+    params(
+        ("id" = String, Path, description = "Actor ID"),
+        ("token_id" = String, Path, description = "Token ID"),
+    ),
+    responses(
+        (status = 200, description = "Token revoked", body = serde_json::Value),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 404, description = "Actor or token not found", body = ErrorResponse),
+    ),
+)]
+async fn revoke_actor_token_handler(
+    State(state): State<ApiState>,
+    Path((id, token_id)): Path<(String, String)>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+) -> impl IntoResponse {
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    let actor_id = narayana_rde::ActorId::from(id);
+    if state.rde.get_actor(&actor_id).is_none() {
+        return rde_actor_not_found();
+    }
+    match state.rde.auth_manager().revoke_token(&actor_id, &token_id) {
+        Ok(token) => {
+            info!("Revoked token {} of RDE actor {}", token.id, actor_id);
+            (StatusCode::OK, Json(token)).into_response()
+        }
+        Err(e) => job_error(StatusCode::NOT_FOUND, e.to_string(), "RDE_TOKEN_NOT_FOUND"),
+    }
+}
+
+fn rde_quota_not_found() -> axum::response::Response {
+    job_error(StatusCode::NOT_FOUND, "Actor has no quota".to_string(), "RDE_QUOTA_NOT_FOUND")
+}
+
+/// A source actor's publish quota and what is left of it: events it can
+/// publish right now and stored bytes remaining
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/rde/actors/{id}/quota",
+    tag = "rde",
+    params(
+        ("id" = String, Path, description = "Actor ID"),
+    ),
+    responses(
+        (status = 200, description = "Quota, remaining allowance and refusals", body = serde_json::Value),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 404, description = "Actor not found or has no quota", body = ErrorResponse),
+    ),
+)]
+async fn get_actor_quota_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+) -> impl IntoResponse {
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    let actor_id = narayana_rde::ActorId::from(id);
+    if state.rde.get_actor(&actor_id).is_none() {
+        return rde_actor_not_found();
+    }
+    match state.rde.actor_quota(&actor_id) {
+        Some(status) => (StatusCode::OK, Json(status)).into_response(),
+        None => rde_quota_not_found(),
+    }
+}
+
+/// Set a source actor's publish quota: `events_per_second` with a `burst`
+/// allowance, and `max_stored_bytes` for its retained events. Publishes over
+/// the quota are refused.
+#[utoipa::path(
+    put,
+    path = "/api/v1/admin/rde/actors/{id}/quota",
+    tag = "rde",
+    params(
+        ("id" = String, Path, description = "Actor ID"),
+    ),
+    request_body(content = Object, description = "Quota: events_per_second, burst, max_stored_bytes (all optional)"),
+    responses(
+        (status = 200, description = "Quota set; current usage", body = serde_json::Value),
+        (status = 400, description = "Invalid quota, or actor is not a source", body = ErrorResponse),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 404, description = "Actor not found", body = ErrorResponse),
+    ),
+)]
+async fn set_actor_quota_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+    Json(quota): Json<narayana_rde::ActorQuota>,
+) -> impl IntoResponse {
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    let actor_id = narayana_rde::ActorId::from(id);
+    if state.rde.get_actor(&actor_id).is_none() {
+        return rde_actor_not_found();
+    }
+    if let Err(e) = state.rde.set_actor_quota(&actor_id, quota) {
+        return job_error(StatusCode::BAD_REQUEST, e.to_string(), "INVALID_QUOTA");
+    }
+    info!("Set publish quota of RDE actor {}", actor_id);
+    match state.rde.actor_quota(&actor_id) {
+        Some(status) => (StatusCode::OK, Json(status)).into_response(),
+        None => rde_quota_not_found(),
+    }
+}
+
+/// Lift a source actor's publish quota
+#[utoipa::path(
+    delete,
+    path = "/api/v1/admin/rde/actors/{id}/quota",
+    tag = "rde",
+    params(
+        ("id" = String, Path, description = "Actor ID"),
+    ),
+    responses(
+        (status = 204, description = "Quota removed"),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 404, description = "Actor not found or has no quota", body = ErrorResponse),
+    ),
+)]
+async fn remove_actor_quota_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+) -> impl IntoResponse {
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    if !state.rde.remove_actor_quota(&narayana_rde::ActorId::from(id)) {
+        return rde_quota_not_found();
+    }
+    StatusCode::NO_CONTENT.into_response()
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RedeliverDeadLettersRequest {
+    /// First dead-letter sequence to redeliver (default 0, the oldest)
+    #[serde(default)]
+    pub sequence: u64,
+    /// Redeliver just these sequences instead (at most 1000)
+    #[serde(default)]
+    pub sequences: Option<Vec<u64>>,
+}
+
+/// RDE subscriptions with dead letters: how many are kept and their sequence range
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/rde/dead-letters",
+    tag = "rde",
+    responses(
+        (status = 200, description = "Subscriptions with dead letters, removed ones included", body = serde_json::Value),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+    ),
+)]
+async fn dead_letter_summary_handler(
+    State(state): State<ApiState>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+) -> impl IntoResponse {
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    (StatusCode::OK, Json(serde_json::json!({ "subscriptions": state.rde.dead_letter_summary() }))).into_response()
+}
+
+/// Events an RDE subscription could not deliver, oldest first
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/rde/subscriptions/{id}/dead-letters",
+    tag = "rde",
+    params(
+        ("id" = String, Path, description = "Subscription ID"),
+        ("sequence" = Option<u64>, Query, description = "First sequence to return (last one + 1 to page)"),
+        ("limit" = Option<usize>, Query, description = "Dead letters to return (default 100, at most 1000)"),
+    ),
+    responses(
+        (status = 200, description = "Dead letters with their sequence, payload, attempts and last error", body = serde_json::Value),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+    ),
+)]
+async fn list_dead_letters_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+) -> impl IntoResponse {
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    let sequence = params.get("sequence").and_then(|s| s.parse().ok()).unwrap_or(0);
+    let limit = params.get("limit").and_then(|s| s.parse().ok()).unwrap_or(100);
+    let letters = state.rde.subscription_dead_letters(&narayana_rde::SubscriptionId(id), sequence, limit);
+    (StatusCode::OK, Json(serde_json::json!({ "dead_letters": letters }))).into_response()
+}
+
+/// One dead letter of an RDE subscription
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/rde/subscriptions/{id}/dead-letters/{sequence}",
+    tag = "rde",
+    params(
+        ("id" = String, Path, description = "Subscription ID"),
+        ("sequence" = u64, Path, description = "Dead-letter sequence"),
+    ),
+    responses(
+        (status = 200, description = "Dead letter", body = serde_json::Value),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 404, description = "No dead letter at that sequence", body = ErrorResponse),
+    ),
+)]
+async fn get_dead_letter_handler(
+    State(state): State<ApiState>,
+    Path((id, sequence)): Path<(String, u64)>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+) -> impl IntoResponse {
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    match state.rde.subscription_dead_letter(&narayana_rde::SubscriptionId(id), sequence) {
+        Some(letter) => (StatusCode::OK, Json(letter)).into_response(),
+        None => job_error(StatusCode::NOT_FOUND, "Dead letter not found".to_string(), "RDE_DEAD_LETTER_NOT_FOUND"),
+    }
+}
+
+/// Queue dead letters for redelivery with fresh retries, either from a
+/// sequence on or just the listed sequences; queued letters leave the
+/// dead-letter stream
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/rde/subscriptions/{id}/dead-letters/redeliver",
+    tag = "rde",
+    params(
+        ("id" = String, Path, description = "Subscription ID"),
+    ),
+    request_body = RedeliverDeadLettersRequest,
+    responses(
+        (status = 200, description = "Letters queued (at most 1000 per call), letters skipped past that cap or a full retry queue, and requested sequences with no dead letter", body = serde_json::Value),
+        (status = 400, description = "No sequences, or more than 1000", body = ErrorResponse),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 404, description = "Subscription not found", body = ErrorResponse),
+    ),
+)]
+async fn redeliver_dead_letters_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+    Json(request): Json<RedeliverDeadLettersRequest>,
+) -> impl IntoResponse {
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    let subscription_id = narayana_rde::SubscriptionId(id);
+    let selection = match request.sequences {
+        Some(sequences) => narayana_rde::DeadLetterSelection::Sequences(sequences),
+        None => narayana_rde::DeadLetterSelection::From(request.sequence),
+    };
+    match state.rde.redeliver_subscription_dead_letters(&subscription_id, selection).await {
+        Ok(report) => {
+            info!("Queued {} dead letters for redelivery ({} skipped)", report.queued, report.skipped);
+            (StatusCode::OK, Json(report)).into_response()
+        }
+        Err(narayana_core::Error::Query(message)) => job_error(StatusCode::BAD_REQUEST, message, "INVALID_REDELIVERY"),
+        Err(e) if e.to_string().contains("not found") => {
+            job_error(StatusCode::NOT_FOUND, e.to_string(), "RDE_SUBSCRIPTION_NOT_FOUND")
+        }
+        Err(e) => {
+            error!("Failed to redeliver dead letters of {}: {}", subscription_id.0, e);
+            job_error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string(), "RDE_REDELIVERY_FAILED")
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TransformationDryRunRequest {
+    /// Subscription config; its `pipeline` and `output_config` are run
+    pub config: serde_json::Value,
+    /// Sample event payload
+    pub payload: serde_json::Value,
+}
+
+/// Run a subscription config's transformation `pipeline` and `output_config`
+/// on a sample payload, showing the output of each stage
+#[utoipa::path(
+    post,
+    path = "/api/v1/rde/transformations/dry-run",
+    tag = "rde",
+    request_body = TransformationDryRunRequest,
+    responses(
+        (status = 200, description = "Output of each stage and what would be delivered", body = serde_json::Value),
+        (status = 400, description = "Invalid pipeline or output_config", body = ErrorResponse),
+    ),
+)]
+async fn dry_run_transformation_handler(Json(request): Json<TransformationDryRunRequest>) -> impl IntoResponse {
+    match narayana_rde::transformations::dry_run(&request.config, &request.payload) {
+        Ok(report) => (StatusCode::OK, Json(report)).into_response(),
+        Err(e) => job_error(StatusCode::BAD_REQUEST, e.to_string(), "INVALID_TRANSFORMATION"),
+    }
+}
+
+// ============================================
+// OUTPUT PROFILES
+// ============================================
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BindOutputProfileRequest {
+    /// Profile to serve; must already exist on the table
+    pub profile: String,
+}
+
+fn admin_required() -> axum::response::Response {
+    job_error(StatusCode::FORBIDDEN, "Admin role required".to_string(), "ADMIN_REQUIRED")
+}
+
+fn profile_consumer(kind: &str, id: String) -> Option<narayana_storage::dynamic_output::ProfileConsumer> {
+    use narayana_storage::dynamic_output::ProfileConsumer;
+    match kind {
+        "token" => Some(ProfileConsumer::Token(id)),
+        "role" => Some(ProfileConsumer::Role(id)),
+        _ => None,
+    }
+}
+
+/// Output profiles defined on a table and the tokens and roles they are served to
+#[utoipa::path(
+    get,
+    path = "/api/v1/tables/{id}/output-profiles",
+    tag = "output_profiles",
+    params(
+        ("id" = u64, Path, description = "Table ID"),
+    ),
+    responses(
+        (status = 200, description = "Profiles and bindings", body = serde_json::Value),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 404, description = "Table not found", body = ErrorResponse),
+    ),
+)]
+async fn list_output_profiles_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<u64>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+) -> impl IntoResponse {
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    if state.db_manager.get_table_info(TableId(id)).is_none() {
+        return job_error(StatusCode::NOT_FOUND, "Table not found".to_string(), "TABLE_NOT_FOUND");
+    }
+    let context = narayana_core::transforms::ConfigContext::Database { table_id: id };
+    let profiles = state.db_manager
+        .get_table_output_config(TableId(id))
+        .map(|config| config.profiles)
+        .unwrap_or_default();
+    (StatusCode::OK, Json(serde_json::json!({
+        "profiles": profiles,
+        "bindings": state.db_manager.output_manager().profile_bindings(&context, &id.to_string()),
+    }))).into_response()
+}
+
+/// Add or replace an output profile (filters, field rules, renames, format)
+#[utoipa::path(
+    put,
+    path = "/api/v1/tables/{id}/output-profiles/{name}",
+    tag = "output_profiles",
+    params(
+        ("id" = u64, Path, description = "Table ID"),
+        ("name" = String, Path, description = "Profile name"),
+    ),
+    request_body = serde_json::Value,
+    responses(
+        (status = 200, description = "Profile saved", body = serde_json::Value),
+        (status = 400, description = "Invalid profile", body = ErrorResponse),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 404, description = "Table not found", body = ErrorResponse),
+    ),
+)]
+async fn set_output_profile_handler(
+    State(state): State<ApiState>,
+    Path((id, name)): Path<(u64, String)>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+    Json(config): Json<serde_json::Value>,
+) -> impl IntoResponse {
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    if state.db_manager.get_table_info(TableId(id)).is_none() {
+        return job_error(StatusCode::NOT_FOUND, "Table not found".to_string(), "TABLE_NOT_FOUND");
+    }
+    let config: narayana_core::transforms::OutputConfig = match serde_json::from_value(config) {
+        Ok(config) => config,
+        Err(e) => return job_error(StatusCode::BAD_REQUEST, format!("Invalid output profile: {}", e), "INVALID_OUTPUT_PROFILE"),
+    };
+    let context = narayana_core::transforms::ConfigContext::Database { table_id: id };
+    match state.db_manager.output_manager().set_profile(context, id.to_string(), name, config).await {
+        Ok(result) => (StatusCode::OK, Json(result)).into_response(),
+        Err(e) => job_error(StatusCode::BAD_REQUEST, e.to_string(), "INVALID_OUTPUT_PROFILE"),
+    }
+}
+
+/// Remove an output profile; tokens and roles bound to it get unshaped rows again
+#[utoipa::path(
+    delete,
+    path = "/api/v1/tables/{id}/output-profiles/{name}",
+    tag = "output_profiles",
+    params(
+        ("id" = u64, Path, description = "Table ID"),
+        ("name" = String, Path, description = "Profile name"),
+    ),
+    responses(
+        (status = 200, description = "Profile removed", body = serde_json::Value),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 404, description = "Profile not found", body = ErrorResponse),
+    ),
+)]
+async fn remove_output_profile_handler(
+    State(state): State<ApiState>,
+    Path((id, name)): Path<(u64, String)>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+) -> impl IntoResponse {
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    let context = narayana_core::transforms::ConfigContext::Database { table_id: id };
+    match state.db_manager.output_manager().remove_profile(context, id.to_string(), name).await {
+        Ok(result) => (StatusCode::OK, Json(result)).into_response(),
+        Err(e) => job_error(StatusCode::NOT_FOUND, e.to_string(), "OUTPUT_PROFILE_NOT_FOUND"),
+    }
+}
+
+/// Serve a profile to a token (by subject) or to every token with a role.
+/// A token binding wins over role bindings.
+#[utoipa::path(
+    put,
+    path = "/api/v1/tables/{id}/output-bindings/{kind}/{consumer}",
+    tag = "output_profiles",
+    params(
+        ("id" = u64, Path, description = "Table ID"),
+        ("kind" = String, Path, description = "`token` or `role`"),
+        ("consumer" = String, Path, description = "Token subject or role name"),
+    ),
+    request_body = BindOutputProfileRequest,
+    responses(
+        (status = 200, description = "Binding saved", body = serde_json::Value),
+        (status = 400, description = "Unknown consumer kind or profile", body = ErrorResponse),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+    ),
+)]
+async fn bind_output_profile_handler(
+    State(state): State<ApiState>,
+    Path((id, kind, consumer)): Path<(u64, String, String)>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+    Json(request): Json<BindOutputProfileRequest>,
+) -> impl IntoResponse {
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    let Some(consumer) = profile_consumer(&kind, consumer) else {
+        return job_error(StatusCode::BAD_REQUEST, "Consumer kind must be 'token' or 'role'".to_string(), "INVALID_CONSUMER");
+    };
+    let context = narayana_core::transforms::ConfigContext::Database { table_id: id };
+    match state.db_manager.output_manager().bind_profile(&context, &id.to_string(), consumer.clone(), &request.profile) {
+        Ok(()) => (StatusCode::OK, Json(narayana_storage::dynamic_output::ProfileBinding {
+            consumer,
+            profile: request.profile,
+        })).into_response(),
+        Err(e) => job_error(StatusCode::BAD_REQUEST, e.to_string(), "INVALID_OUTPUT_BINDING"),
+    }
+}
+
+/// Stop serving a profile to a token or role
+#[utoipa::path(
+    delete,
+    path = "/api/v1/tables/{id}/output-bindings/{kind}/{consumer}",
+    tag = "output_profiles",
+    params(
+        ("id" = u64, Path, description = "Table ID"),
+        ("kind" = String, Path, description = "`token` or `role`"),
+        ("consumer" = String, Path, description = "Token subject or role name"),
+    ),
+    responses(
+        (status = 204, description = "Binding removed"),
+        (status = 400, description = "Unknown consumer kind", body = ErrorResponse),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 404, description = "No such binding", body = ErrorResponse),
+    ),
+)]
+async fn unbind_output_profile_handler(
+    State(state): State<ApiState>,
+    Path((id, kind, consumer)): Path<(u64, String, String)>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+) -> impl IntoResponse {
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    let Some(consumer) = profile_consumer(&kind, consumer) else {
+        return job_error(StatusCode::BAD_REQUEST, "Consumer kind must be 'token' or 'role'".to_string(), "INVALID_CONSUMER");
+    };
+    let context = narayana_core::transforms::ConfigContext::Database { table_id: id };
+    if state.db_manager.output_manager().unbind_profile(&context, &id.to_string(), &consumer) {
+        StatusCode::NO_CONTENT.into_response()
+    } else {
+        job_error(StatusCode::NOT_FOUND, "Binding not found".to_string(), "OUTPUT_BINDING_NOT_FOUND")
+    }
+}
+
+// ============================================
+// ROW-LEVEL SECURITY
+// ============================================
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RowPolicyRequest {
+    /// Predicate over the row's columns and `$sub`, `$tenant` and `$roles`
+    pub expression: String,
+    /// Roles the policy applies to; every caller when empty
+    #[serde(default)]
+    pub roles: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct IssueTokenRequest {
+    pub subject: String,
+    #[serde(default)]
+    pub roles: Vec<String>,
+    /// Tenant row policies see as `$tenant`
+    pub tenant: Option<String>,
+}
+
+/// Row policies and column masks on a table
+#[utoipa::path(
+    get,
+    path = "/api/v1/tables/{id}/row-policies",
+    tag = "row_security",
+    params(
+        ("id" = u64, Path, description = "Table ID"),
+    ),
+    responses(
+        (status = 200, description = "Policies and masks", body = serde_json::Value),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 404, description = "Table not found", body = ErrorResponse),
+    ),
+)]
+async fn list_row_policies_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<u64>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+) -> impl IntoResponse {
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    if state.db_manager.get_table_info(TableId(id)).is_none() {
+        return job_error(StatusCode::NOT_FOUND, "Table not found".to_string(), "TABLE_NOT_FOUND");
+    }
+    let security = state.db_manager.row_security();
+    (StatusCode::OK, Json(serde_json::json!({
+        "policies": security.policies(TableId(id)),
+        "masks": security.masks(TableId(id)),
+    }))).into_response()
+}
+
+/// Add or replace a row policy. Once a table has policies, callers see only
+/// the rows an applicable policy admits.
+#[utoipa::path(
+    put,
+    path = "/api/v1/tables/{id}/row-policies/{name}",
+    tag = "row_security",
+    params(
+        ("id" = u64, Path, description = "Table ID"),
+        ("name" = String, Path, description = "Policy name"),
+    ),
+    request_body = RowPolicyRequest,
+    responses(
+        (status = 200, description = "Policy saved", body = serde_json::Value),
+        (status = 400, description = "Invalid policy", body = ErrorResponse),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 404, description = "Table not found", body = ErrorResponse),
+    ),
+)]
+async fn set_row_policy_handler(
+    State(state): State<ApiState>,
+    Path((id, name)): Path<(u64, String)>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+    Json(request): Json<RowPolicyRequest>,
+) -> impl IntoResponse {
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    let Some(table) = state.db_manager.get_table_info(TableId(id)) else {
+        return job_error(StatusCode::NOT_FOUND, "Table not found".to_string(), "TABLE_NOT_FOUND");
+    };
+    let policy = narayana_storage::row_security::RowPolicy {
+        name,
+        expression: request.expression,
+        roles: request.roles,
+    };
+    // Catch typos now rather than failing every read of the table
+    let columns = match policy.columns() {
+        Ok(columns) => columns,
+        Err(e) => return job_error(StatusCode::BAD_REQUEST, e.to_string(), "INVALID_ROW_POLICY"),
+    };
+    if let Some(unknown) = columns.iter().find(|c| table.schema.field_index(c).is_none()) {
+        return job_error(StatusCode::BAD_REQUEST, format!("Unknown column '{}'", unknown), "INVALID_ROW_POLICY");
+    }
+    match state.db_manager.row_security().set_policy(TableId(id), policy.clone()) {
+        Ok(()) => (StatusCode::OK, Json(policy)).into_response(),
+        Err(e) => job_error(StatusCode::BAD_REQUEST, e.to_string(), "INVALID_ROW_POLICY"),
+    }
+}
+
+/// Remove a row policy
+#[utoipa::path(
+    delete,
+    path = "/api/v1/tables/{id}/row-policies/{name}",
+    tag = "row_security",
+    params(
+        ("id" = u64, Path, description = "Table ID"),
+        ("name" = String, Path, description = "Policy name"),
+    ),
+    responses(
+        (status = 204, description = "Policy removed"),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 404, description = "Policy not found", body = ErrorResponse),
+    ),
+)]
+async fn remove_row_policy_handler(
+    State(state): State<ApiState>,
+    Path((id, name)): Path<(u64, String)>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+) -> impl IntoResponse {
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    if state.db_manager.row_security().remove_policy(TableId(id), &name) {
+        StatusCode::NO_CONTENT.into_response()
+    } else {
+        job_error(StatusCode::NOT_FOUND, "Row policy not found".to_string(), "ROW_POLICY_NOT_FOUND")
+    }
+}
+
+/// Add or replace a column mask: `column`, `function` (`hash`, `partial`
+/// with `show_first`/`show_last`, or `redact`) and the `roles` it applies to
+#[utoipa::path(
+    put,
+    path = "/api/v1/tables/{id}/column-masks/{name}",
+    tag = "row_security",
+    params(
+        ("id" = u64, Path, description = "Table ID"),
+        ("name" = String, Path, description = "Mask name"),
+    ),
+    request_body = serde_json::Value,
+    responses(
+        (status = 200, description = "Mask saved", body = serde_json::Value),
+        (status = 400, description = "Invalid mask", body = ErrorResponse),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 404, description = "Table not found", body = ErrorResponse),
+    ),
+)]
+async fn set_column_mask_handler(
+    State(state): State<ApiState>,
+    Path((id, name)): Path<(u64, String)>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+    Json(mut request): Json<serde_json::Value>,
+) -> impl IntoResponse {
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    let Some(table) = state.db_manager.get_table_info(TableId(id)) else {
+        return job_error(StatusCode::NOT_FOUND, "Table not found".to_string(), "TABLE_NOT_FOUND");
+    };
+    if let Some(object) = request.as_object_mut() {
+        object.insert("name".to_string(), serde_json::Value::String(name));
+    }
+    let mask: narayana_storage::row_security::ColumnMask = match serde_json::from_value(request) {
+        Ok(mask) => mask,
+        Err(e) => return job_error(StatusCode::BAD_REQUEST, format!("Invalid column mask: {}", e), "INVALID_COLUMN_MASK"),
+    };
+    if table.schema.field_index(&mask.column).is_none() {
+        return job_error(StatusCode::BAD_REQUEST, format!("Unknown column '{}'", mask.column), "INVALID_COLUMN_MASK");
+    }
+    match state.db_manager.row_security().set_mask(TableId(id), mask.clone()) {
+        Ok(()) => (StatusCode::OK, Json(mask)).into_response(),
+        Err(e) => job_error(StatusCode::BAD_REQUEST, e.to_string(), "INVALID_COLUMN_MASK"),
+    }
+}
+
+/// Remove a column mask
+#[utoipa::path(
+    delete,
+    path = "/api/v1/tables/{id}/column-masks/{name}",
+    tag = "row_security",
+    params(
+        ("id" = u64, Path, description = "Table ID"),
+        ("name" = String, Path, description = "Mask name"),
+    ),
+    responses(
+        (status = 204, description = "Mask removed"),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 404, description = "Mask not found", body = ErrorResponse),
+    ),
+)]
+async fn remove_column_mask_handler(
+    State(state): State<ApiState>,
+    Path((id, name)): Path<(u64, String)>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+) -> impl IntoResponse {
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    if state.db_manager.row_security().remove_mask(TableId(id), &name) {
+        StatusCode::NO_CONTENT.into_response()
+    } else {
+        job_error(StatusCode::NOT_FOUND, "Column mask not found".to_string(), "COLUMN_MASK_NOT_FOUND")
+    }
+}
+
+/// Issue a token for a service or tenant user, carrying the roles and
+/// tenant row policies and masks are evaluated against
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/tokens",
+    tag = "row_security",
+    request_body = IssueTokenRequest,
+    responses(
+        (status = 200, description = "Token issued; valid for an hour", body = serde_json::Value),
+        (status = 400, description = "Invalid subject", body = ErrorResponse),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+    ),
+)]
+async fn issue_token_handler(
+    State(state): State<ApiState>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+    Json(request): Json<IssueTokenRequest>,
+) -> impl IntoResponse {
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    if request.subject.trim().is_empty() || request.subject.len() > 255 {
+        return job_error(StatusCode::BAD_REQUEST, "Subject must be 1-255 characters".to_string(), "INVALID_SUBJECT");
+    }
+    if request.tenant.as_ref().is_some_and(|t| t.is_empty() || t.len() > 255) {
+        return job_error(StatusCode::BAD_REQUEST, "Tenant must be 1-255 characters".to_string(), "INVALID_TENANT");
+    }
+    match state.token_manager.generate_tenant_token(request.subject, request.roles, request.tenant) {
+        Ok(token) => (StatusCode::OK, Json(serde_json::json!({
+            "token": token,
+            "expires_in": 3600,
+        }))).into_response(),
+        Err(e) => {
+            error!("Failed to issue token: {}", e);
+            job_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to issue token".to_string(), "TOKEN_ERROR")
+        }
+    }
+}
+
+// ============================================
+// COMPLIANCE
+// ============================================
+
+/// Personal data tags of every table and vector index
+#[utoipa::path(
+    get,
+    path = "/api/v1/compliance/tags",
+    tag = "compliance",
+    responses(
+        (status = 200, description = "Tagged tables and vector indexes", body = serde_json::Value),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+    ),
+)]
+async fn list_compliance_tags_handler(
+    State(state): State<ApiState>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+) -> impl IntoResponse {
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    let compliance = state.db_manager.compliance();
+    let tables: Vec<serde_json::Value> = compliance.tagged_tables()
+        .into_iter()
+        .map(|(id, tags)| serde_json::json!({ "table_id": id.0, "tags": tags }))
+        .collect();
+    let indexes: Vec<serde_json::Value> = compliance.tagged_vector_indexes()
+        .into_iter()
+        .map(|(name, tags)| serde_json::json!({ "index": name, "tags": tags }))
+        .collect();
+    (StatusCode::OK, Json(serde_json::json!({
+        "tables": tables,
+        "vector_indexes": indexes,
+    }))).into_response()
+}
+
+/// Tag a table's subject key column and personal data columns
+#[utoipa::path(
+    put,
+    path = "/api/v1/compliance/tables/{id}",
+    tag = "compliance",
+    params(
+        ("id" = u64, Path, description = "Table ID"),
+    ),
+    request_body = serde_json::Value,
+    responses(
+        (status = 200, description = "Tags saved", body = serde_json::Value),
+        (status = 400, description = "Invalid tags", body = ErrorResponse),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 404, description = "Table not found", body = ErrorResponse),
+    ),
+)]
+async fn tag_table_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<u64>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+    Json(tags): Json<narayana_storage::compliance::TableTags>,
+) -> impl IntoResponse {
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    let Some(table) = state.db_manager.get_table_info(TableId(id)) else {
+        return job_error(StatusCode::NOT_FOUND, "Table not found".to_string(), "TABLE_NOT_FOUND");
+    };
+    let columns = std::iter::once(&tags.subject_column).chain(tags.columns.iter().map(|c| &c.column));
+    for column in columns {
+        if table.schema.field_index(column).is_none() {
+            return job_error(StatusCode::BAD_REQUEST, format!("Unknown column '{}'", column), "INVALID_COMPLIANCE_TAGS");
+        }
+    }
+    match state.db_manager.compliance().tag_table(TableId(id), tags.clone()) {
+        Ok(()) => (StatusCode::OK, Json(tags)).into_response(),
+        Err(e) => job_error(StatusCode::BAD_REQUEST, e.to_string(), "INVALID_COMPLIANCE_TAGS"),
+    }
+}
+
+/// Remove a table's personal data tags
+#[utoipa::path(
+    delete,
+    path = "/api/v1/compliance/tables/{id}",
+    tag = "compliance",
+    params(
+        ("id" = u64, Path, description = "Table ID"),
+    ),
+    responses(
+        (status = 204, description = "Tags removed"),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 404, description = "Table is not tagged", body = ErrorResponse),
+    ),
+)]
+async fn untag_table_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<u64>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+) -> impl IntoResponse {
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    if state.db_manager.compliance().untag_table(TableId(id)) {
+        StatusCode::NO_CONTENT.into_response()
+    } else {
+        job_error(StatusCode::NOT_FOUND, "Table is not tagged".to_string(), "COMPLIANCE_TAGS_NOT_FOUND")
+    }
+}
+
+/// Tag the embedding metadata field holding the subject key of a vector
+/// index's memories
+#[utoipa::path(
+    put,
+    path = "/api/v1/compliance/vector-indexes/{name}",
+    tag = "compliance",
+    params(
+        ("name" = String, Path, description = "Vector index name"),
+    ),
+    request_body = serde_json::Value,
+    responses(
+        (status = 200, description = "Tags saved", body = serde_json::Value),
+        (status = 400, description = "Invalid tags", body = ErrorResponse),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 404, description = "Vector index not found", body = ErrorResponse),
+    ),
+)]
+async fn tag_vector_index_handler(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+    Json(tags): Json<narayana_storage::compliance::VectorIndexTags>,
+) -> impl IntoResponse {
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    if !state.vector_store.has_index(&name) {
+        return job_error(StatusCode::NOT_FOUND, "Vector index not found".to_string(), "VECTOR_INDEX_NOT_FOUND");
+    }
+    match state.db_manager.compliance().tag_vector_index(&name, tags.clone()) {
+        Ok(()) => (StatusCode::OK, Json(tags)).into_response(),
+        Err(e) => job_error(StatusCode::BAD_REQUEST, e.to_string(), "INVALID_COMPLIANCE_TAGS"),
+    }
+}
+
+/// Remove a vector index's personal data tags
+#[utoipa::path(
+    delete,
+    path = "/api/v1/compliance/vector-indexes/{name}",
+    tag = "compliance",
+    params(
+        ("name" = String, Path, description = "Vector index name"),
+    ),
+    responses(
+        (status = 204, description = "Tags removed"),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 404, description = "Vector index is not tagged", body = ErrorResponse),
+    ),
+)]
+async fn untag_vector_index_handler(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+) -> impl IntoResponse {
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    if state.db_manager.compliance().untag_vector_index(&name) {
+        StatusCode::NO_CONTENT.into_response()
+    } else {
+        job_error(StatusCode::NOT_FOUND, "Vector index is not tagged".to_string(), "COMPLIANCE_TAGS_NOT_FOUND")
+    }
+}
+
+/// Lineage edges, optionally only those touching one table or vector index
+#[utoipa::path(
+    get,
+    path = "/api/v1/compliance/lineage",
+    tag = "compliance",
+    params(
+        ("table" = Option<u64>, Query, description = "Only edges touching this table"),
+        ("vector_index" = Option<String>, Query, description = "Only edges touching this vector index"),
+    ),
+    responses(
+        (status = 200, description = "Lineage edges", body = serde_json::Value),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+    ),
+)]
+async fn list_lineage_handler(
+    State(state): State<ApiState>,
+    Query(params): Query<HashMap<String, String>>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+) -> impl IntoResponse {
+    use narayana_storage::compliance::DataAsset;
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    let asset = match (params.get("table"), params.get("vector_index")) {
+        (Some(table), _) => match table.parse::<u64>() {
+            Ok(id) => Some(DataAsset::Table(id)),
+            Err(_) => return job_error(StatusCode::BAD_REQUEST, "Invalid table id".to_string(), "INVALID_LINEAGE_QUERY"),
+        },
+        (None, Some(name)) => Some(DataAsset::VectorIndex(name.clone())),
+        (None, None) => None,
+    };
+    let compliance = state.db_manager.compliance();
+    let downstream = asset.as_ref().map(|asset| compliance.downstream(asset));
+    (StatusCode::OK, Json(serde_json::json!({
+        "edges": compliance.lineage(asset.as_ref()),
+        "downstream": downstream,
+    }))).into_response()
+}
+
+/// Record that a table or vector index was derived from another
+#[utoipa::path(
+    post,
+    path = "/api/v1/compliance/lineage",
+    tag = "compliance",
+    request_body = serde_json::Value,
+    responses(
+        (status = 200, description = "Edge recorded", body = serde_json::Value),
+        (status = 400, description = "Invalid edge", body = ErrorResponse),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+    ),
+)]
+async fn record_lineage_handler(
+    State(state): State<ApiState>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+    Json(edge): Json<narayana_storage::compliance::LineageEdge>,
+) -> impl IntoResponse {
+    use narayana_storage::compliance::DataAsset;
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    for asset in [&edge.source, &edge.target] {
+        let exists = match asset {
+            DataAsset::Table(id) => state.db_manager.get_table_info(TableId(*id)).is_some(),
+            DataAsset::VectorIndex(name) => state.vector_store.has_index(name),
+        };
+        if !exists {
+            return job_error(StatusCode::BAD_REQUEST, format!("Unknown asset {:?}", asset), "INVALID_LINEAGE_EDGE");
+        }
+    }
+    match state.db_manager.compliance().record_lineage(edge.clone()) {
+        Ok(()) => (StatusCode::OK, Json(edge)).into_response(),
+        Err(e) => job_error(StatusCode::BAD_REQUEST, e.to_string(), "INVALID_LINEAGE_EDGE"),
+    }
+}
+
+/// Erase a data subject: delete or crypto-shred their rows in tagged
+/// tables, their memories in tagged vector indexes and the blobs they
+/// reference. Returns the erasure report.
+#[utoipa::path(
+    post,
+    path = "/api/v1/compliance/erasures",
+    tag = "compliance",
+    request_body = serde_json::Value,
+    responses(
+        (status = 200, description = "Erasure report", body = serde_json::Value),
+        (status = 400, description = "Invalid subject", body = ErrorResponse),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+    ),
+)]
+async fn erase_subject_handler(
+    State(state): State<ApiState>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+    Json(request): Json<narayana_storage::compliance::ErasureRequest>,
+) -> impl IntoResponse {
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    let targets = narayana_storage::compliance::ErasureTargets {
+        store: state.storage.as_ref(),
+        vectors: &state.vector_store,
+        full_text: Some(state.full_text.as_ref()),
+        spatial: Some(state.spatial.as_ref()),
+        embeddings: state.embeddings.as_deref(),
+        blobs: Some(state.blobs.as_ref()),
+        temporal: Some(state.db_manager.temporal().as_ref()),
+    };
+    match state.db_manager.compliance().erase(&request, &targets).await {
+        Ok(report) => (StatusCode::OK, Json(report)).into_response(),
+        Err(e) => job_error(StatusCode::BAD_REQUEST, e.to_string(), "INVALID_ERASURE"),
+    }
+}
+
+/// Recent erasure reports, newest first
+#[utoipa::path(
+    get,
+    path = "/api/v1/compliance/erasures",
+    tag = "compliance",
+    params(
+        ("limit" = Option<usize>, Query, description = "Most recent reports to return (default 20)"),
+    ),
+    responses(
+        (status = 200, description = "Erasure reports", body = serde_json::Value),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+    ),
+)]
+async fn list_erasures_handler(
+    State(state): State<ApiState>,
+    Query(params): Query<HashMap<String, String>>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+) -> impl IntoResponse {
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    let limit = params.get("limit").and_then(|v| v.parse::<usize>().ok()).unwrap_or(20);
+    (StatusCode::OK, Json(serde_json::json!({
+        "reports": state.db_manager.compliance().reports(limit),
+    }))).into_response()
+}
+
+// ============================================
+// TEMPORAL TABLES
+// ============================================
+
+/// A table's versioning configuration
+#[utoipa::path(
+    get,
+    path = "/api/v1/tables/{id}/versioning",
+    tag = "temporal",
+    params(
+        ("id" = u64, Path, description = "Table ID"),
+    ),
+    responses(
+        (status = 200, description = "Versioning configuration and size", body = serde_json::Value),
+        (status = 404, description = "Table is not system-versioned", body = ErrorResponse),
+    ),
+)]
+async fn get_versioning_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<u64>,
+) -> impl IntoResponse {
+    match state.db_manager.temporal().status(TableId(id)).await {
+        Some(status) => (StatusCode::OK, Json(status)).into_response(),
+        None => job_error(StatusCode::NOT_FOUND, "Table is not system-versioned".to_string(), "NOT_VERSIONED"),
+    }
+}
+
+/// Make a table system-versioned, or change its key, period column or retention
+#[utoipa::path(
+    put,
+    path = "/api/v1/tables/{id}/versioning",
+    tag = "temporal",
+    params(
+        ("id" = u64, Path, description = "Table ID"),
+    ),
+    request_body = serde_json::Value,
+    responses(
+        (status = 200, description = "Table is versioned", body = serde_json::Value),
+        (status = 400, description = "Invalid configuration", body = ErrorResponse),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 404, description = "Table not found", body = ErrorResponse),
+    ),
+)]
+async fn enable_versioning_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<u64>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+    Json(config): Json<narayana_storage::temporal::TemporalConfig>,
+) -> impl IntoResponse {
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    if state.db_manager.get_table_info(TableId(id)).is_none() || is_protected_users_table(&state, TableId(id)) {
+        return job_error(StatusCode::NOT_FOUND, "Table not found".to_string(), "TABLE_NOT_FOUND");
+    }
+    match state.db_manager.temporal().enable(state.storage.as_ref(), TableId(id), config).await {
+        Ok(status) => (StatusCode::OK, Json(status)).into_response(),
+        Err(e) => job_error(StatusCode::BAD_REQUEST, e.to_string(), "INVALID_VERSIONING"),
+    }
+}
+
+/// Stop versioning a table; stored versions and tombstones become plain rows
+#[utoipa::path(
+    delete,
+    path = "/api/v1/tables/{id}/versioning",
+    tag = "temporal",
+    params(
+        ("id" = u64, Path, description = "Table ID"),
+    ),
+    responses(
+        (status = 204, description = "Versioning stopped"),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 404, description = "Table is not system-versioned", body = ErrorResponse),
+        (status = 409, description = "Table is replicated", body = ErrorResponse),
+    ),
+)]
+async fn disable_versioning_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<u64>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+) -> impl IntoResponse {
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    if state.replication.is_replicated(TableId(id)) {
+        return job_error(StatusCode::CONFLICT, "Stop replicating the table first".to_string(), "TABLE_REPLICATED");
+    }
+    if state.db_manager.temporal().disable(TableId(id)) {
+        StatusCode::NO_CONTENT.into_response()
+    } else {
+        job_error(StatusCode::NOT_FOUND, "Table is not system-versioned".to_string(), "NOT_VERSIONED")
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct VersionedDeleteRequest {
+    /// Key column values of the rows to delete
+    #[schema(value_type = Vec<Object>)]
+    pub keys: Vec<serde_json::Value>,
+}
+
+/// Delete rows of a system-versioned table by key; their history is kept
+#[utoipa::path(
+    post,
+    path = "/api/v1/tables/{id}/versioning/delete",
+    tag = "temporal",
+    params(
+        ("id" = u64, Path, description = "Table ID"),
+    ),
+    request_body = VersionedDeleteRequest,
+    responses(
+        (status = 200, description = "Rows deleted", body = serde_json::Value),
+        (status = 400, description = "Table is not system-versioned or too many keys", body = ErrorResponse),
+        (status = 404, description = "Table not found", body = ErrorResponse),
+    ),
+)]
+async fn delete_versioned_rows_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<u64>,
+    Json(request): Json<VersionedDeleteRequest>,
+) -> impl IntoResponse {
+    let table_id = TableId(id);
+    let Some(table) = state.db_manager.get_table_info(table_id) else {
+        return job_error(StatusCode::NOT_FOUND, "Table not found".to_string(), "TABLE_NOT_FOUND");
+    };
+    let temporal = state.db_manager.temporal();
+    let tombstones = match state.replication.delete(state.storage.as_ref(), temporal, table_id, &request.keys).await {
+        Ok(tombstones) => tombstones,
+        Err(e) => return job_error(StatusCode::BAD_REQUEST, e.to_string(), "INVALID_VERSIONED_DELETE"),
+    };
+    // Tombstones are stored rows, so indexes and the change feed see them
+    // like any insert
+    if !tombstones.is_empty() {
+        if let Err(e) = state.full_text.index_insert(table_id, &table.schema, &tombstones) {
+            warn!("Failed to update full-text index for table {}: {}", id, e);
+        }
+        if let Err(e) = state.spatial.index_insert(table_id, &table.schema, &tombstones) {
+            warn!("Failed to update spatial index for table {}: {}", id, e);
+        }
+        state.change_feed.publish_insert(table_id, &table.schema, &tombstones);
+    }
+    let deleted = tombstones.first().map(|c| c.len()).unwrap_or(0);
+    (StatusCode::OK, Json(serde_json::json!({ "deleted": deleted }))).into_response()
+}
+
+/// Every version of one row, oldest first
+#[utoipa::path(
+    get,
+    path = "/api/v1/tables/{id}/history",
+    tag = "temporal",
+    params(
+        ("id" = u64, Path, description = "Table ID"),
+        ("key" = String, Query, description = "Key column value of the row"),
+    ),
+    responses(
+        (status = 200, description = "Row versions", body = serde_json::Value),
+        (status = 400, description = "Table is not system-versioned or no key given", body = ErrorResponse),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+    ),
+)]
+async fn row_history_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<u64>,
+    Query(params): Query<HashMap<String, String>>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+) -> impl IntoResponse {
+    // History bypasses row policies and masks, so it is for admins only
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    let Some(key) = params.get("key") else {
+        return job_error(StatusCode::BAD_REQUEST, "key is required".to_string(), "INVALID_HISTORY");
+    };
+    let key = serde_json::Value::String(key.clone());
+    match state.db_manager.temporal().history(state.storage.as_ref(), TableId(id), &key).await {
+        Ok(versions) => (StatusCode::OK, Json(serde_json::json!({ "versions": versions }))).into_response(),
+        Err(e) => job_error(StatusCode::BAD_REQUEST, e.to_string(), "INVALID_HISTORY"),
+    }
+}
+
+/// Drop versions older than the table's retention now rather than on the next sweep
+#[utoipa::path(
+    post,
+    path = "/api/v1/tables/{id}/versioning/purge",
+    tag = "temporal",
+    params(
+        ("id" = u64, Path, description = "Table ID"),
+    ),
+    responses(
+        (status = 200, description = "Expired versions removed", body = serde_json::Value),
+        (status = 400, description = "Table is not system-versioned", body = ErrorResponse),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+    ),
+)]
+async fn purge_history_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<u64>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+) -> impl IntoResponse {
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default();
+    let temporal = state.db_manager.temporal();
+    match temporal.purge(state.storage.as_ref(), TableId(id), Some(state.full_text.as_ref()), now).await {
+        Ok(purged) => (StatusCode::OK, Json(serde_json::json!({ "purged": purged }))).into_response(),
+        Err(e) => job_error(StatusCode::BAD_REQUEST, e.to_string(), "PURGE_FAILED"),
+    }
+}
+
+// ============================================
+// REPLICATION
+// ============================================
+
+/// This region's replicated tables, peers' lag and changes applied per origin
+#[utoipa::path(
+    get,
+    path = "/api/v1/replication",
+    tag = "replication",
+    responses(
+        (status = 200, description = "Replication status", body = serde_json::Value),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+    ),
+)]
+async fn replication_status_handler(
+    State(state): State<ApiState>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+) -> impl IntoResponse {
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    (StatusCode::OK, Json(state.replication.status())).into_response()
+}
+
+/// Replicate a system-versioned table to every peer region, or change its
+/// conflict resolution; the table must have the same name in every region
+#[utoipa::path(
+    put,
+    path = "/api/v1/replication/tables/{id}",
+    tag = "replication",
+    params(
+        ("id" = u64, Path, description = "Table ID"),
+    ),
+    request_body = serde_json::Value,
+    responses(
+        (status = 200, description = "Table is replicated", body = serde_json::Value),
+        (status = 400, description = "Invalid configuration or table not versioned", body = ErrorResponse),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 404, description = "Table not found", body = ErrorResponse),
+    ),
+)]
+async fn replicate_table_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<u64>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+    Json(config): Json<narayana_storage::replication::TableReplication>,
+) -> impl IntoResponse {
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    let table_id = TableId(id);
+    let table = match state.db_manager.get_table_info(table_id) {
+        Some(table) if !is_protected_users_table(&state, table_id) => table,
+        _ => return job_error(StatusCode::NOT_FOUND, "Table not found".to_string(), "TABLE_NOT_FOUND"),
+    };
+    let temporal = state.db_manager.temporal();
+    match state.replication.configure(state.storage.as_ref(), temporal, table_id, table.name, config.clone()).await {
+        Ok(()) => (StatusCode::OK, Json(config)).into_response(),
+        Err(e) => job_error(StatusCode::BAD_REQUEST, e.to_string(), "INVALID_REPLICATION"),
+    }
+}
+
+/// Stop replicating a table; changes not yet shipped still go out
+#[utoipa::path(
+    delete,
+    path = "/api/v1/replication/tables/{id}",
+    tag = "replication",
+    params(
+        ("id" = u64, Path, description = "Table ID"),
+    ),
+    responses(
+        (status = 204, description = "Replication stopped"),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 404, description = "Table is not replicated", body = ErrorResponse),
+    ),
+)]
+async fn stop_replicating_table_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<u64>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+) -> impl IntoResponse {
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    if state.replication.remove(TableId(id)) {
+        StatusCode::NO_CONTENT.into_response()
+    } else {
+        job_error(StatusCode::NOT_FOUND, "Table is not replicated".to_string(), "NOT_REPLICATED")
+    }
+}
+
+/// Apply a batch of changes shipped by a peer region
+#[utoipa::path(
+    post,
+    path = "/api/v1/replication/changes",
+    tag = "replication",
+    request_body = serde_json::Value,
+    responses(
+        (status = 200, description = "Changes applied; acked_seq is the last one the sender can drop", body = serde_json::Value),
+        (status = 400, description = "Invalid batch", body = ErrorResponse),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+    ),
+)]
+async fn apply_replicated_changes_handler(
+    State(state): State<ApiState>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+    Json(batch): Json<narayana_storage::replication::ChangeBatch>,
+) -> impl IntoResponse {
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    let temporal = state.db_manager.temporal();
+    let report = match state.replication.apply(state.storage.as_ref(), temporal, batch).await {
+        Ok(report) => report,
+        Err(e) => return job_error(StatusCode::BAD_REQUEST, e.to_string(), "INVALID_CHANGES"),
+    };
+    // Applied changes are stored rows, so indexes and the change feed see
+    // them like any insert
+    for (table_id, columns) in &report.written {
+        let Some(table) = state.db_manager.get_table_info(*table_id) else { continue };
+        if let Err(e) = state.full_text.index_insert(*table_id, &table.schema, columns) {
+            warn!("Failed to update full-text index for table {}: {}", table_id.0, e);
+        }
+        if let Err(e) = state.spatial.index_insert(*table_id, &table.schema, columns) {
+            warn!("Failed to update spatial index for table {}: {}", table_id.0, e);
+        }
+        state.change_feed.publish_insert(*table_id, &table.schema, columns);
+    }
+    (StatusCode::OK, Json(report)).into_response()
+}
+
+// ============================================
+// CLUSTER
+// ============================================
+
+fn cluster_service(state: &ApiState) -> narayana_api::grpc::RaftClusterService {
+    narayana_api::grpc::RaftClusterService::new(state.shards.consensus().clone())
+}
+
+fn cluster_response<T: Serialize>(result: Result<T, String>) -> axum::response::Response {
+    match result {
+        Ok(response) => (StatusCode::OK, Json(response)).into_response(),
+        Err(e) => job_error(StatusCode::CONFLICT, e, "CLUSTER_ERROR"),
+    }
+}
+
+/// This node's Raft role, term, log position and the cluster membership
+#[utoipa::path(
+    get,
+    path = "/api/v1/cluster",
+    tag = "cluster",
+    responses(
+        (status = 200, description = "Raft status and node addresses", body = serde_json::Value),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+    ),
+)]
+async fn cluster_status_handler(
+    State(state): State<ApiState>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+) -> impl IntoResponse {
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    let raft = state.shards.consensus();
+    Json(serde_json::json!({
+        "status": raft.status(),
+        "nodes": raft.metadata().nodes,
+    })).into_response()
+}
+
+/// Add a node reachable at `address` as a voter; only the leader accepts,
+/// other nodes answer with `accepted: false` and the leader's id
+#[utoipa::path(
+    post,
+    path = "/api/v1/cluster/join",
+    tag = "cluster",
+    request_body = serde_json::Value,
+    responses(
+        (status = 200, description = "Whether the node was added, and the leader", body = serde_json::Value),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 409, description = "Invalid request or the change failed", body = ErrorResponse),
+    ),
+)]
+async fn join_cluster_handler(
+    State(state): State<ApiState>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+    Json(request): Json<narayana_api::grpc::JoinClusterRequest>,
+) -> impl IntoResponse {
+    use narayana_api::grpc::ClusterService;
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    cluster_response(cluster_service(&state).join(request).await)
+}
+
+/// Remove a voter; only the leader accepts
+#[utoipa::path(
+    post,
+    path = "/api/v1/cluster/leave",
+    tag = "cluster",
+    request_body = serde_json::Value,
+    responses(
+        (status = 200, description = "Whether the node was removed, and the leader", body = serde_json::Value),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 409, description = "The change failed", body = ErrorResponse),
+    ),
+)]
+async fn leave_cluster_handler(
+    State(state): State<ApiState>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+    Json(request): Json<narayana_api::grpc::LeaveClusterRequest>,
+) -> impl IntoResponse {
+    use narayana_api::grpc::ClusterService;
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    cluster_response(cluster_service(&state).leave(request).await)
+}
+
+/// RequestVote RPC from a candidate
+#[utoipa::path(
+    post,
+    path = "/api/v1/cluster/raft/request-vote",
+    tag = "cluster",
+    request_body = serde_json::Value,
+    responses(
+        (status = 200, description = "Vote", body = serde_json::Value),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 409, description = "The vote could not be persisted", body = ErrorResponse),
+    ),
+)]
+async fn raft_request_vote_handler(
+    State(state): State<ApiState>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+    Json(request): Json<narayana_storage::consensus::RequestVoteRequest>,
+) -> impl IntoResponse {
+    use narayana_api::grpc::ClusterService;
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    cluster_response(cluster_service(&state).request_vote(request).await)
+}
+
+/// AppendEntries RPC (replication and heartbeats) from the leader
+#[utoipa::path(
+    post,
+    path = "/api/v1/cluster/raft/append-entries",
+    tag = "cluster",
+    request_body = serde_json::Value,
+    responses(
+        (status = 200, description = "Whether the entries were appended", body = serde_json::Value),
+        (status = 403, description = "Admin role required", body = ErrorResponse),
+        (status = 409, description = "The entries could not be persisted", body = ErrorResponse),
+    ),
+)]
+async fn raft_append_entries_handler(
+    State(state): State<ApiState>,
+    claims: Option<axum::Extension<crate::security::Claims>>,
+    Json(request): Json<narayana_storage::consensus::AppendEntriesRequest>,
+) -> impl IntoResponse {
+    use narayana_api::grpc::ClusterService;
+    if !is_admin(&claims) {
+        return admin_required();
+    }
+    cluster_response(cluster_service(&state).append_entries(request).await)
+}
+
+// ============================================
+// SCALING
+// ============================================
+
+/// Load forecast for the day ahead, current resource sizes and recent scaling actions
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/scaling",
+    tag = "scaling",
+    params(
+        ("hours" = Option<u32>, Query, description = "Forecast horizon in hours (default 24, max 168)"),
+        ("limit" = Option<usize>, Query, description = "Most recent actions to return (default 50)"),
+    ),
+    responses(
+        (status = 200, description = "Scaling status", body = serde_json::Value),
+    ),
+)]
+async fn scaling_status_handler(
+    State(state): State<ApiState>,
+    Query(params): Query<HashMap<String, String>>,
+) -> impl IntoResponse {
+    let hours = params.get("hours").and_then(|v| v.parse::<u32>().ok()).unwrap_or(24).min(168);
+    let limit = params.get("limit").and_then(|v| v.parse::<usize>().ok()).unwrap_or(50);
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let config = state.scaling.config();
+    Json(serde_json::json!({
+        "status": state.scaling.status(),
+        "slot_minutes": config.slot_minutes,
+        "lead_minutes": config.lead_minutes,
+        "forecast": state.scaling.forecast_range(now, hours),
+        "actions": state.scaling.actions(limit),
+    }))
+}
+
+// ============================================
+// SHARDS
+// ============================================
+
+/// Two adjacent shards of a table to merge
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct MergeShardsRequest {
+    pub left_id: u64,
+    pub right_id: u64,
+}
+
+/// Load of a shard as measured by the node serving it
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ShardLoadRequest {
+    pub size_bytes: u64,
+    #[serde(default)]
+    pub row_count: u64,
+    #[serde(default)]
+    pub reads_per_second: f64,
+    #[serde(default)]
+    pub writes_per_second: f64,
+}
+
+fn shard_action_response(
+    result: narayana_core::Result<narayana_storage::auto_scaling::ShardScalingEvent>,
+) -> axum::response::Response {
+    match result {
+        Ok(event) => (StatusCode::OK, Json(event)).into_response(),
+        Err(e) => job_error(StatusCode::CONFLICT, e.to_string(), "SHARD_ACTION_FAILED"),
+    }
+}
+
+/// The committed shard map, reported loads and this node's consensus role
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/shards",
+    tag = "shards",
+    responses(
+        (status = 200, description = "Shards and loads", body = serde_json::Value),
+    ),
+)]
+async fn list_shards_handler(State(state): State<ApiState>) -> impl IntoResponse {
+    let shards = state.shards.shards();
+    Json(serde_json::json!({
+        "total": shards.len(),
+        "shards": shards,
+        "loads": state.shards.loads(),
+        "consensus": state.shards.consensus().status(),
+        "thresholds": state.shards.thresholds(),
+    }))
+}
+
+/// Splits, merges and reader changes, newest first
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/shards/events",
+    tag = "shards",
+    params(
+        ("limit" = Option<usize>, Query, description = "Events to return (default 50)"),
+    ),
+    responses(
+        (status = 200, description = "Shard scaling events", body = serde_json::Value),
+    ),
+)]
+async fn list_shard_events_handler(
+    State(state): State<ApiState>,
+    Query(params): Query<HashMap<String, String>>,
+) -> impl IntoResponse {
+    let limit = params.get("limit").and_then(|v| v.parse::<usize>().ok()).unwrap_or(50).min(1000);
+    Json(serde_json::json!({ "events": state.shards.events(limit) }))
+}
+
+/// Give a table its first shard, owning every key
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/shards/tables/{table_id}",
+    tag = "shards",
+    params(
+        ("table_id" = u64, Path, description = "Table ID"),
+    ),
+    responses(
+        (status = 200, description = "Shard created, or null when the table already has shards", body = serde_json::Value),
+        (status = 409, description = "Not the leader or the change did not commit", body = ErrorResponse),
+    ),
+)]
+async fn bootstrap_table_shard_handler(
+    State(state): State<ApiState>,
+    Path(table_id): Path<u64>,
+) -> impl IntoResponse {
+    match state.shards.bootstrap_table(table_id).await {
+        Ok(shard_id) => (StatusCode::OK, Json(serde_json::json!({ "shard_id": shard_id }))).into_response(),
+        Err(e) => job_error(StatusCode::CONFLICT, e.to_string(), "SHARD_ACTION_FAILED"),
+    }
+}
+
+/// Report a shard's load; splits, merges and readers follow on the next check
+#[utoipa::path(
+    put,
+    path = "/api/v1/admin/shards/{id}/load",
+    tag = "shards",
+    params(
+        ("id" = u64, Path, description = "Shard ID"),
+    ),
+    request_body = ShardLoadRequest,
+    responses(
+        (status = 200, description = "Load recorded", body = serde_json::Value),
+        (status = 404, description = "Shard not found", body = ErrorResponse),
+    ),
+)]
+async fn report_shard_load_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<u64>,
+    Json(request): Json<ShardLoadRequest>,
+) -> impl IntoResponse {
+    if !state.shards.shards().iter().any(|shard| shard.shard_id == id) {
+        return job_error(StatusCode::NOT_FOUND, "Shard not found".to_string(), "SHARD_NOT_FOUND");
+    }
+    let load = narayana_storage::auto_scaling::ShardLoad {
+        shard_id: id,
+        size_bytes: request.size_bytes,
+        row_count: request.row_count,
+        reads_per_second: request.reads_per_second,
+        writes_per_second: request.writes_per_second,
+        last_updated: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+    };
+    state.shards.update_load(load.clone());
+    (StatusCode::OK, Json(load)).into_response()
+}
+
+/// Split a shard at the middle of its key range
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/shards/{id}/split",
+    tag = "shards",
+    params(
+        ("id" = u64, Path, description = "Shard ID"),
+    ),
+    responses(
+        (status = 200, description = "Shard split", body = serde_json::Value),
+        (status = 409, description = "Not the leader, unknown shard or the change did not commit", body = ErrorResponse),
+    ),
+)]
+async fn split_shard_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<u64>,
+) -> impl IntoResponse {
+    use narayana_storage::auto_scaling::ShardScalingAction;
+    shard_action_response(state.shards.execute(ShardScalingAction::Split { shard_id: id }, "manual").await)
+}
+
+/// Merge two adjacent shards of a table
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/shards/merge",
+    tag = "shards",
+    request_body = MergeShardsRequest,
+    responses(
+        (status = 200, description = "Shards merged", body = serde_json::Value),
+        (status = 409, description = "Not the leader, shards not adjacent or the change did not commit", body = ErrorResponse),
+    ),
+)]
+async fn merge_shards_handler(
+    State(state): State<ApiState>,
+    Json(request): Json<MergeShardsRequest>,
+) -> impl IntoResponse {
+    use narayana_storage::auto_scaling::ShardScalingAction;
+    let action = ShardScalingAction::Merge { left_id: request.left_id, right_id: request.right_id };
+    shard_action_response(state.shards.execute(action, "manual").await)
+}
+
+/// Spawn a read-only replica of a shard
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/shards/{id}/readers",
+    tag = "shards",
+    params(
+        ("id" = u64, Path, description = "Shard ID"),
+    ),
+    responses(
+        (status = 200, description = "Reader spawned", body = serde_json::Value),
+        (status = 409, description = "Not the leader, unknown shard or too many readers", body = ErrorResponse),
+    ),
+)]
+async fn add_shard_reader_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<u64>,
+) -> impl IntoResponse {
+    use narayana_storage::auto_scaling::ShardScalingAction;
+    shard_action_response(state.shards.execute(ShardScalingAction::AddReader { shard_id: id }, "manual").await)
+}
+
+/// Stop routing reads to a reader and retire it
+#[utoipa::path(
+    delete,
+    path = "/api/v1/admin/shards/{id}/readers/{node_id}",
+    tag = "shards",
+    params(
+        ("id" = u64, Path, description = "Shard ID"),
+        ("node_id" = String, Path, description = "Reader node ID"),
+    ),
+    responses(
+        (status = 200, description = "Reader retired", body = serde_json::Value),
+        (status = 409, description = "Not the leader or not a reader of the shard", body = ErrorResponse),
+    ),
+)]
+async fn retire_shard_reader_handler(
+    State(state): State<ApiState>,
+    Path((id, node_id)): Path<(u64, String)>,
+) -> impl IntoResponse {
+    use narayana_storage::auto_scaling::ShardScalingAction;
+    let action = ShardScalingAction::RetireReader { shard_id: id, node_id };
+    shard_action_response(state.shards.execute(action, "manual").await)
+}
@@ -0,0 +1,227 @@
+// CORS and security-header middleware for the HTTP API
+// Driven by NetworkConfig (cors_origins, cors, security_headers, route_headers)
+// and hot-reloaded through ConfigManager watchers.
+
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{header, HeaderMap, HeaderName, HeaderValue, Method, Response, StatusCode},
+    middleware::Next,
+};
+use narayana_core::config::{CorsPolicyConfig, NarayanaConfig, NetworkConfig, SecurityHeadersConfig};
+use parking_lot::RwLock;
+use std::sync::Arc;
+
+use crate::config_manager::ConfigManager;
+
+/// Effective header settings for one route
+#[derive(Debug, Clone)]
+struct RouteHeaders {
+    enable_cors: bool,
+    origins: Vec<String>,
+    cors: CorsPolicyConfig,
+    security: SecurityHeadersConfig,
+}
+
+#[derive(Debug)]
+struct CompiledHeaders {
+    base: Arc<RouteHeaders>,
+    /// Sorted longest prefix first
+    routes: Vec<(String, Arc<RouteHeaders>)>,
+}
+
+impl CompiledHeaders {
+    fn compile(network: &NetworkConfig) -> Self {
+        let base = RouteHeaders {
+            enable_cors: network.enable_cors,
+            origins: network.cors_origins.clone(),
+            cors: network.cors.clone(),
+            security: network.security_headers.clone(),
+        };
+        let mut routes: Vec<(String, Arc<RouteHeaders>)> = network.route_headers.iter()
+            .map(|route| {
+                let headers = RouteHeaders {
+                    enable_cors: route.enable_cors.unwrap_or(base.enable_cors),
+                    origins: route.cors_origins.clone().unwrap_or_else(|| base.origins.clone()),
+                    cors: route.cors.clone().unwrap_or_else(|| base.cors.clone()),
+                    security: route.security_headers.clone().unwrap_or_else(|| base.security.clone()),
+                };
+                (route.path_prefix.clone(), Arc::new(headers))
+            })
+            .collect();
+        routes.sort_by_key(|(prefix, _)| std::cmp::Reverse(prefix.len()));
+        Self { base: Arc::new(base), routes }
+    }
+
+    fn for_path(&self, path: &str) -> Arc<RouteHeaders> {
+        self.routes.iter()
+            .find(|(prefix, _)| path_has_prefix(path, prefix))
+            .map(|(_, headers)| headers.clone())
+            .unwrap_or_else(|| self.base.clone())
+    }
+}
+
+/// Header policy shared by the router; swapped atomically on config reload
+pub struct HttpHeadersPolicy {
+    compiled: RwLock<Arc<CompiledHeaders>>,
+}
+
+impl HttpHeadersPolicy {
+    pub fn new(network: &NetworkConfig) -> Self {
+        Self {
+            compiled: RwLock::new(Arc::new(CompiledHeaders::compile(network))),
+        }
+    }
+
+    /// Replace the policy (e.g. after a configuration reload)
+    pub fn update(&self, network: &NetworkConfig) {
+        *self.compiled.write() = Arc::new(CompiledHeaders::compile(network));
+    }
+
+    /// Recompile the header policy whenever `[network]` changes
+    pub async fn watch(self: &Arc<Self>, manager: &ConfigManager) {
+        let policy = self.clone();
+        manager.subscribe(Box::new(move |config: &NarayanaConfig| {
+            policy.update(&config.network);
+            tracing::info!("HTTP header policy reloaded");
+        })).await;
+    }
+
+    /// Handle CORS preflights and decorate every response with CORS and security headers
+    pub async fn apply(&self, request: Request, next: Next) -> Response<Body> {
+        let route = self.compiled.read().for_path(request.uri().path());
+        let origin = request.headers().get(header::ORIGIN)
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_string());
+
+        let is_preflight = request.method() == Method::OPTIONS
+            && origin.is_some()
+            && request.headers().contains_key(header::ACCESS_CONTROL_REQUEST_METHOD);
+        if is_preflight {
+            let mut response = preflight_response(&route, origin.as_deref().unwrap_or_default(), request.headers());
+            apply_security_headers(&route.security, response.headers_mut());
+            return response;
+        }
+
+        let mut response = next.run(request).await;
+        if let Some(origin) = origin.as_deref() {
+            apply_cors_headers(&route, origin, response.headers_mut());
+        }
+        apply_security_headers(&route.security, response.headers_mut());
+        response
+    }
+}
+
+fn path_has_prefix(path: &str, prefix: &str) -> bool {
+    path == prefix
+        || (path.starts_with(prefix) && (prefix.ends_with('/') || path[prefix.len()..].starts_with('/')))
+}
+
+/// Match an Origin against the allowed list: `*`, exact origins, or `scheme://*.domain`
+pub fn origin_allowed(allowed: &[String], origin: &str) -> bool {
+    let origin = origin.to_lowercase();
+    allowed.iter().any(|pattern| {
+        let pattern = pattern.trim().trim_end_matches('/').to_lowercase();
+        if pattern == "*" || pattern == origin {
+            return true;
+        }
+        match (pattern.split_once("://*."), origin.split_once("://")) {
+            (Some((scheme, domain)), Some((origin_scheme, host))) => {
+                scheme == origin_scheme && host.ends_with(&format!(".{}", domain))
+            }
+            _ => false,
+        }
+    })
+}
+
+fn header_value(value: &str) -> Option<HeaderValue> {
+    HeaderValue::from_str(value).ok()
+}
+
+/// Set Access-Control-Allow-Origin (and credentials) for an allowed origin
+fn apply_cors_headers(route: &RouteHeaders, origin: &str, headers: &mut HeaderMap) -> bool {
+    if !route.enable_cors || !origin_allowed(&route.origins, origin) {
+        return false;
+    }
+    let wildcard = route.origins.iter().any(|o| o.trim() == "*");
+    if wildcard && !route.cors.allow_credentials {
+        headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, HeaderValue::from_static("*"));
+    } else {
+        // Reflected origins vary per request, so caches must key on Origin
+        let Some(value) = header_value(origin) else { return false };
+        headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, value);
+        headers.append(header::VARY, HeaderValue::from_static("Origin"));
+        if route.cors.allow_credentials && !wildcard {
+            headers.insert(header::ACCESS_CONTROL_ALLOW_CREDENTIALS, HeaderValue::from_static("true"));
+        }
+    }
+    if !route.cors.exposed_headers.is_empty() {
+        if let Some(value) = header_value(&route.cors.exposed_headers.join(", ")) {
+            headers.insert(header::ACCESS_CONTROL_EXPOSE_HEADERS, value);
+        }
+    }
+    true
+}
+
+fn preflight_response(route: &RouteHeaders, origin: &str, request_headers: &HeaderMap) -> Response<Body> {
+    let forbidden = || Response::builder()
+        .status(StatusCode::FORBIDDEN)
+        .body(Body::from("CORS preflight rejected"))
+        .unwrap_or_default();
+
+    let requested_method = request_headers.get(header::ACCESS_CONTROL_REQUEST_METHOD)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    let method_allowed = route.cors.allowed_methods.iter()
+        .any(|m| m == "*" || m.eq_ignore_ascii_case(requested_method));
+
+    let requested_headers: Vec<String> = request_headers.get(header::ACCESS_CONTROL_REQUEST_HEADERS)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.split(',').map(|h| h.trim().to_lowercase()).filter(|h| !h.is_empty()).collect())
+        .unwrap_or_default();
+    let any_header = route.cors.allowed_headers.iter().any(|h| h == "*");
+    let headers_allowed = any_header || requested_headers.iter()
+        .all(|h| route.cors.allowed_headers.iter().any(|a| a.eq_ignore_ascii_case(h)));
+
+    let mut response = Response::builder()
+        .status(StatusCode::NO_CONTENT)
+        .body(Body::empty())
+        .unwrap_or_default();
+    let headers = response.headers_mut();
+    if !method_allowed || !headers_allowed || !apply_cors_headers(route, origin, headers) {
+        return forbidden();
+    }
+
+    if let Some(value) = header_value(&route.cors.allowed_methods.join(", ")) {
+        headers.insert(header::ACCESS_CONTROL_ALLOW_METHODS, value);
+    }
+    let allow_headers = if any_header {
+        requested_headers.join(", ")
+    } else {
+        route.cors.allowed_headers.join(", ")
+    };
+    if let Some(value) = header_value(&allow_headers) {
+        headers.insert(header::ACCESS_CONTROL_ALLOW_HEADERS, value);
+    }
+    headers.insert(header::ACCESS_CONTROL_MAX_AGE, HeaderValue::from(route.cors.max_age_secs));
+    headers.append(header::VARY, HeaderValue::from_static("Access-Control-Request-Method"));
+    headers.append(header::VARY, HeaderValue::from_static("Access-Control-Request-Headers"));
+    response
+}
+
+/// Add configured security headers without overriding ones a handler already set
+fn apply_security_headers(config: &SecurityHeadersConfig, headers: &mut HeaderMap) {
+    let mut set = |name: HeaderName, value: Option<String>| {
+        if let Some(value) = value.as_deref().and_then(header_value) {
+            headers.entry(name).or_insert(value);
+        }
+    };
+    set(header::CONTENT_SECURITY_POLICY, config.content_security_policy.clone());
+    set(header::STRICT_TRANSPORT_SECURITY, config.hsts_max_age_secs.map(|age| format!("max-age={}; includeSubDomains", age)));
+    set(header::X_FRAME_OPTIONS, config.frame_options.clone());
+    set(header::REFERRER_POLICY, config.referrer_policy.clone());
+    set(HeaderName::from_static("permissions-policy"), config.permissions_policy.clone());
+    if config.content_type_nosniff {
+        set(header::X_CONTENT_TYPE_OPTIONS, Some("nosniff".to_string()));
+    }
+}
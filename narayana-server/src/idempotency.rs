@@ -0,0 +1,302 @@
+// Idempotency-Key support for mutating HTTP endpoints
+// The first response for a (caller, key) pair is stored with a fingerprint of
+// the request and replayed for retries within the configured window.
+
+use axum::{
+    body::{Body, Bytes, HttpBody},
+    extract::Request,
+    http::{header, HeaderMap, HeaderValue, Method, Response, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Json},
+};
+use narayana_core::config::{IdempotencyConfig, NarayanaConfig, NetworkConfig};
+use parking_lot::{Mutex, RwLock};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::config_manager::ConfigManager;
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+
+/// Response headers kept with a stored response
+const STORED_HEADERS: &[header::HeaderName] = &[header::CONTENT_TYPE, header::LOCATION];
+
+#[derive(Debug, Clone)]
+struct StoredResponse {
+    status: StatusCode,
+    headers: Vec<(header::HeaderName, HeaderValue)>,
+    body: Bytes,
+}
+
+#[derive(Debug, Clone)]
+enum Entry {
+    InFlight { fingerprint: [u8; 32], started: Instant },
+    Completed { fingerprint: [u8; 32], stored_at: Instant, response: StoredResponse },
+}
+
+impl Entry {
+    fn fingerprint(&self) -> &[u8; 32] {
+        match self {
+            Entry::InFlight { fingerprint, .. } | Entry::Completed { fingerprint, .. } => fingerprint,
+        }
+    }
+
+    fn created(&self) -> Instant {
+        match self {
+            Entry::InFlight { started, .. } => *started,
+            Entry::Completed { stored_at, .. } => *stored_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Settings {
+    config: IdempotencyConfig,
+    max_request_bytes: usize,
+}
+
+/// Stored and in-flight entries, with their keys queued in creation order so
+/// pruning pops expired keys off the front instead of scanning every entry.
+/// Queued keys whose entry was since replaced or removed are stale and skipped.
+#[derive(Default)]
+struct Entries {
+    map: HashMap<String, Entry>,
+    by_age: VecDeque<(Instant, String)>,
+}
+
+impl Entries {
+    fn insert(&mut self, key: String, entry: Entry) {
+        self.by_age.push_back((entry.created(), key.clone()));
+        self.map.insert(key, entry);
+    }
+
+    fn remove(&mut self, key: &str) {
+        self.map.remove(key);
+    }
+
+    /// Drop expired entries and, past `max_entries`, the oldest completed ones
+    fn prune(&mut self, config: &IdempotencyConfig, now: Instant) {
+        let window = Duration::from_secs(config.window_secs);
+        while let Some(&(created, _)) = self.by_age.front() {
+            if now.duration_since(created) < window {
+                break;
+            }
+            if let Some((created, key)) = self.by_age.pop_front() {
+                if self.map.get(&key).is_some_and(|entry| entry.created() == created) {
+                    self.map.remove(&key);
+                }
+            }
+        }
+
+        // In-flight entries are never evicted; they keep their place in the queue
+        let limit = config.max_entries.max(1);
+        let mut index = 0;
+        while self.map.len() >= limit && index < self.by_age.len() {
+            let (created, key) = &self.by_age[index];
+            match self.map.get(key) {
+                Some(Entry::InFlight { started, .. }) if started == created => index += 1,
+                Some(entry) if entry.created() == *created => {
+                    self.map.remove(key);
+                    self.by_age.remove(index);
+                }
+                _ => {
+                    self.by_age.remove(index);
+                }
+            }
+        }
+    }
+}
+
+/// Store of idempotent responses shared by the router
+pub struct IdempotencyStore {
+    settings: RwLock<Settings>,
+    entries: Mutex<Entries>,
+}
+
+/// Releases an in-flight key if the handler never completes (error, panic, cancellation)
+struct InFlightGuard<'a> {
+    store: &'a IdempotencyStore,
+    key: Option<String>,
+}
+
+impl InFlightGuard<'_> {
+    fn complete(mut self, fingerprint: [u8; 32], response: StoredResponse) {
+        if let Some(key) = self.key.take() {
+            self.store.entries.lock().insert(key, Entry::Completed {
+                fingerprint,
+                stored_at: Instant::now(),
+                response,
+            });
+        }
+    }
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            self.store.entries.lock().remove(&key);
+        }
+    }
+}
+
+fn error_response(status: StatusCode, error: &str, code: &str) -> Response<Body> {
+    (status, Json(crate::http::ErrorResponse {
+        error: error.to_string(),
+        code: code.to_string(),
+    })).into_response()
+}
+
+fn is_mutating(method: &Method) -> bool {
+    matches!(*method, Method::POST | Method::PUT | Method::PATCH | Method::DELETE)
+}
+
+fn fingerprint(method: &Method, uri: &str, body: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(method.as_str().as_bytes());
+    hasher.update([0]);
+    hasher.update(uri.as_bytes());
+    hasher.update([0]);
+    hasher.update(body);
+    hasher.finalize().into()
+}
+
+impl IdempotencyStore {
+    pub fn new(network: &NetworkConfig) -> Self {
+        Self {
+            settings: RwLock::new(Settings {
+                config: network.idempotency.clone(),
+                max_request_bytes: network.max_request_size,
+            }),
+            entries: Mutex::new(Entries::default()),
+        }
+    }
+
+    pub fn update(&self, network: &NetworkConfig) {
+        *self.settings.write() = Settings {
+            config: network.idempotency.clone(),
+            max_request_bytes: network.max_request_size,
+        };
+    }
+
+    /// Apply a new window and limits to later requests; stored keys are kept
+    /// and expire under the new window
+    pub async fn watch(self: &Arc<Self>, manager: &ConfigManager) {
+        let store = self.clone();
+        manager.subscribe(Box::new(move |config: &NarayanaConfig| {
+            store.update(&config.network);
+        })).await;
+    }
+
+    /// Number of stored and in-flight keys
+    pub fn len(&self) -> usize {
+        self.entries.lock().map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Run a mutating request at most once per (scope, Idempotency-Key).
+    /// `scope` identifies the caller so keys from different users never collide.
+    pub async fn apply(&self, scope: &str, request: Request, next: Next) -> Response<Body> {
+        let settings = self.settings.read().clone();
+        let key = request.headers().get(IDEMPOTENCY_KEY_HEADER).cloned();
+        let (Some(key), true) = (key, settings.config.enabled && is_mutating(request.method())) else {
+            return next.run(request).await;
+        };
+        let key = match key.to_str() {
+            Ok(k) if !k.is_empty() && k.len() <= settings.config.max_key_length => k.to_string(),
+            _ => {
+                return error_response(
+                    StatusCode::BAD_REQUEST,
+                    &format!("Idempotency-Key must be 1-{} visible ASCII characters", settings.config.max_key_length),
+                    "INVALID_IDEMPOTENCY_KEY",
+                );
+            }
+        };
+
+        // The body is part of the fingerprint, so buffer it and hand a copy to the handler
+        let (parts, body) = request.into_parts();
+        let body = match axum::body::to_bytes(body, settings.max_request_bytes).await {
+            Ok(body) => body,
+            Err(_) => {
+                return error_response(StatusCode::PAYLOAD_TOO_LARGE, "Request body too large", "PAYLOAD_TOO_LARGE");
+            }
+        };
+        let uri = parts.uri.path_and_query().map(|p| p.as_str()).unwrap_or("/").to_string();
+        let fingerprint = fingerprint(&parts.method, &uri, &body);
+        let entry_key = format!("{}\u{0}{}", scope, key);
+
+        {
+            let now = Instant::now();
+            let mut entries = self.entries.lock();
+            entries.prune(&settings.config, now);
+            match entries.map.get(&entry_key) {
+                Some(entry) if entry.fingerprint() != &fingerprint => {
+                    return error_response(
+                        StatusCode::UNPROCESSABLE_ENTITY,
+                        "Idempotency-Key was already used for a different request",
+                        "IDEMPOTENCY_KEY_REUSED",
+                    );
+                }
+                Some(Entry::InFlight { .. }) => {
+                    return error_response(
+                        StatusCode::CONFLICT,
+                        "A request with this Idempotency-Key is still being processed",
+                        "IDEMPOTENCY_KEY_IN_PROGRESS",
+                    );
+                }
+                Some(Entry::Completed { response, .. }) => {
+                    metrics::counter!("narayana_idempotent_replays_total").increment(1);
+                    return replay(response);
+                }
+                None => {
+                    entries.insert(entry_key.clone(), Entry::InFlight { fingerprint, started: now });
+                }
+            }
+        }
+
+        let guard = InFlightGuard { store: self, key: Some(entry_key) };
+        let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+
+        // Server errors are not stored so the client can retry; neither are
+        // streaming or oversized bodies (the guard releases the key)
+        let size = response.body().size_hint().exact();
+        let storable = !response.status().is_server_error()
+            && size.is_some_and(|s| s as usize <= settings.config.max_response_bytes);
+        if !storable {
+            return response;
+        }
+        let (parts, body) = response.into_parts();
+        let Ok(bytes) = axum::body::to_bytes(body, settings.config.max_response_bytes).await else {
+            return error_response(StatusCode::INTERNAL_SERVER_ERROR, "Failed to read response", "INTERNAL_ERROR");
+        };
+        let stored = StoredResponse {
+            status: parts.status,
+            headers: stored_headers(&parts.headers),
+            body: bytes.clone(),
+        };
+        guard.complete(fingerprint, stored);
+        Response::from_parts(parts, Body::from(bytes))
+    }
+}
+
+fn stored_headers(headers: &HeaderMap) -> Vec<(header::HeaderName, HeaderValue)> {
+    STORED_HEADERS.iter()
+        .filter_map(|name| headers.get(name).map(|value| (name.clone(), value.clone())))
+        .collect()
+}
+
+fn replay(stored: &StoredResponse) -> Response<Body> {
+    let mut response = Response::new(Body::from(stored.body.clone()));
+    *response.status_mut() = stored.status;
+    let headers = response.headers_mut();
+    for (name, value) in &stored.headers {
+        headers.insert(name.clone(), value.clone());
+    }
+    headers.insert(IDEMPOTENT_REPLAYED_HEADER, HeaderValue::from_static("true"));
+    response
+}
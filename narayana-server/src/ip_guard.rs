@@ -0,0 +1,340 @@
+// Per-IP access control for the HTTP API
+// Sliding-window rate limits per client address, admin endpoint allow/deny
+// CIDR lists, and greylisting of addresses with repeated auth failures.
+
+use narayana_core::config::{IpAccessConfig, NarayanaConfig};
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::config_manager::ConfigManager;
+
+/// Upper bound on tracked addresses before idle ones are pruned
+const MAX_TRACKED_IPS: usize = 100_000;
+
+/// An IP network (a bare address is a /32 or /128)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IpRange {
+    network: IpAddr,
+    prefix: u8,
+}
+
+impl IpRange {
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let (addr, prefix) = match value.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix.parse::<u8>().ok()?)),
+            None => (value, None),
+        };
+        let network: IpAddr = addr.trim_matches(|c| c == '[' || c == ']').parse().ok()?;
+        let max = if network.is_ipv4() { 32 } else { 128 };
+        let prefix = prefix.unwrap_or(max);
+        (prefix <= max).then_some(Self { network, prefix })
+    }
+
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        let ip = match ip {
+            IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(*ip),
+            IpAddr::V4(_) => *ip,
+        };
+        match (self.network, ip) {
+            (IpAddr::V4(net), IpAddr::V4(addr)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+                u32::from(net) & mask == u32::from(addr) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(addr)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+                u128::from(net) & mask == u128::from(addr) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+fn parse_ranges(values: &[String]) -> Vec<IpRange> {
+    values.iter()
+        .filter_map(|v| {
+            let range = IpRange::parse(v);
+            if range.is_none() {
+                tracing::warn!("Ignoring invalid IP range in ip_access config: {}", v);
+            }
+            range
+        })
+        .collect()
+}
+
+fn in_ranges(ranges: &[IpRange], ip: &IpAddr) -> bool {
+    ranges.iter().any(|r| r.contains(ip))
+}
+
+/// Client address resolved for a request (set as a request extension)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientIp(pub IpAddr);
+
+/// Outcome of checking a request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IpDecision {
+    Allow,
+    /// Admin endpoint refused for this address
+    Forbidden,
+    RateLimited { retry_after_secs: u64 },
+    /// Auth attempt from a greylisted address
+    Greylisted { retry_after_secs: u64 },
+}
+
+impl IpDecision {
+    pub fn reason(&self) -> &'static str {
+        match self {
+            IpDecision::Allow => "allow",
+            IpDecision::Forbidden => "admin_forbidden",
+            IpDecision::RateLimited { .. } => "rate_limited",
+            IpDecision::Greylisted { .. } => "greylisted",
+        }
+    }
+}
+
+#[derive(Debug)]
+struct CompiledAccess {
+    config: IpAccessConfig,
+    exempt: Vec<IpRange>,
+    trusted_proxies: Vec<IpRange>,
+    admin_allow: Vec<IpRange>,
+    admin_deny: Vec<IpRange>,
+}
+
+impl CompiledAccess {
+    fn compile(config: &IpAccessConfig) -> Self {
+        Self {
+            exempt: parse_ranges(&config.exempt),
+            trusted_proxies: parse_ranges(&config.trusted_proxies),
+            admin_allow: parse_ranges(&config.admin_allow),
+            admin_deny: parse_ranges(&config.admin_deny),
+            config: config.clone(),
+        }
+    }
+
+    fn is_admin_path(&self, path: &str) -> bool {
+        self.config.admin_paths.iter().any(|prefix| {
+            path == prefix || (path.starts_with(prefix.as_str()) && path[prefix.len()..].starts_with('/'))
+        })
+    }
+}
+
+/// Sliding-window counter: the previous window's count is weighted by how
+/// much of it still overlaps the sliding window
+#[derive(Debug, Default)]
+struct IpState {
+    window_start: Option<Instant>,
+    current: u32,
+    previous: u32,
+    failures: Vec<Instant>,
+    greylisted_until: Option<Instant>,
+    last_seen: Option<Instant>,
+}
+
+impl IpState {
+    fn roll(&mut self, now: Instant, window: Duration) {
+        match self.window_start {
+            Some(start) if now.duration_since(start) < window => {}
+            Some(start) if now.duration_since(start) < window * 2 => {
+                self.previous = self.current;
+                self.current = 0;
+                self.window_start = Some(start + window);
+            }
+            _ => {
+                self.previous = 0;
+                self.current = 0;
+                self.window_start = Some(now);
+            }
+        }
+    }
+
+    fn estimate(&self, now: Instant, window: Duration) -> f64 {
+        let elapsed = self.window_start.map(|s| now.duration_since(s)).unwrap_or_default();
+        let overlap = 1.0 - (elapsed.as_secs_f64() / window.as_secs_f64()).min(1.0);
+        self.previous as f64 * overlap + self.current as f64
+    }
+
+    fn is_greylisted(&self, now: Instant) -> bool {
+        self.greylisted_until.is_some_and(|until| until > now)
+    }
+}
+
+/// Greylisted address as reported by `stats`
+#[derive(Debug, Clone, Serialize)]
+pub struct GreylistEntry {
+    pub ip: String,
+    pub recent_failures: usize,
+    pub expires_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IpGuardStats {
+    pub tracked_ips: usize,
+    pub greylisted: Vec<GreylistEntry>,
+    pub blocked_rate_limited: u64,
+    pub blocked_admin_forbidden: u64,
+    pub blocked_greylisted: u64,
+}
+
+/// Per-IP guard shared by the router; rules are swapped on config reload
+pub struct IpGuard {
+    access: RwLock<Arc<CompiledAccess>>,
+    states: Mutex<HashMap<IpAddr, IpState>>,
+    rate_limited: AtomicU64,
+    admin_forbidden: AtomicU64,
+    greylisted: AtomicU64,
+}
+
+impl IpGuard {
+    pub fn new(config: &IpAccessConfig) -> Self {
+        Self {
+            access: RwLock::new(Arc::new(CompiledAccess::compile(config))),
+            states: Mutex::new(HashMap::new()),
+            rate_limited: AtomicU64::new(0),
+            admin_forbidden: AtomicU64::new(0),
+            greylisted: AtomicU64::new(0),
+        }
+    }
+
+    pub fn update(&self, config: &IpAccessConfig) {
+        *self.access.write() = Arc::new(CompiledAccess::compile(config));
+    }
+
+    /// Recompile the allow and deny lists when `[security.ip_access]` changes;
+    /// per-client rate and greylist state carries over
+    pub async fn watch(self: &Arc<Self>, manager: &ConfigManager) {
+        let guard = self.clone();
+        manager.subscribe(Box::new(move |config: &NarayanaConfig| {
+            guard.update(&config.security.ip_access);
+        })).await;
+    }
+
+    /// Resolve the client address: the peer, or the nearest untrusted
+    /// X-Forwarded-For hop when the peer is a trusted proxy
+    pub fn client_ip(&self, peer: Option<IpAddr>, forwarded_for: Option<&str>) -> Option<IpAddr> {
+        let access = self.access.read().clone();
+        let Some(peer) = peer else {
+            return forwarded_for.and_then(|f| f.split(',').next()).and_then(|ip| ip.trim().parse().ok());
+        };
+        if !in_ranges(&access.trusted_proxies, &peer) {
+            return Some(peer);
+        }
+        let hops: Vec<IpAddr> = forwarded_for
+            .map(|f| f.split(',').filter_map(|ip| ip.trim().parse().ok()).collect())
+            .unwrap_or_default();
+        Some(hops.into_iter().rev()
+            .find(|ip| !in_ranges(&access.trusted_proxies, ip))
+            .unwrap_or(peer))
+    }
+
+    /// Check (and count) one request from `ip` to `path`
+    pub fn check(&self, ip: IpAddr, path: &str, is_auth_attempt: bool) -> IpDecision {
+        let access = self.access.read().clone();
+        let decision = self.decide(&access, ip, path, is_auth_attempt);
+        let counter = match decision {
+            IpDecision::Allow => return decision,
+            IpDecision::Forbidden => &self.admin_forbidden,
+            IpDecision::RateLimited { .. } => &self.rate_limited,
+            IpDecision::Greylisted { .. } => &self.greylisted,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        metrics::counter!("narayana_http_blocked_requests_total", "reason" => decision.reason()).increment(1);
+        tracing::warn!("Blocked request from {} to {}: {}", ip, path, decision.reason());
+        decision
+    }
+
+    fn decide(&self, access: &CompiledAccess, ip: IpAddr, path: &str, is_auth_attempt: bool) -> IpDecision {
+        if access.is_admin_path(path)
+            && (in_ranges(&access.admin_deny, &ip)
+                || (!access.admin_allow.is_empty() && !in_ranges(&access.admin_allow, &ip)))
+        {
+            return IpDecision::Forbidden;
+        }
+        let config = &access.config;
+        if config.requests_per_window == 0 || in_ranges(&access.exempt, &ip) {
+            return IpDecision::Allow;
+        }
+
+        let now = Instant::now();
+        let window = Duration::from_secs(config.window_secs.max(1));
+        let mut states = self.states.lock();
+        if states.len() >= MAX_TRACKED_IPS && !states.contains_key(&ip) {
+            let idle = window * 2;
+            states.retain(|_, s| s.is_greylisted(now) || s.last_seen.is_some_and(|t| now.duration_since(t) < idle));
+        }
+        let state = states.entry(ip).or_default();
+        state.last_seen = Some(now);
+        state.roll(now, window);
+
+        let greylisted = state.is_greylisted(now);
+        if greylisted && is_auth_attempt {
+            let remaining = state.greylisted_until.map(|u| u.duration_since(now)).unwrap_or_default();
+            return IpDecision::Greylisted { retry_after_secs: remaining.as_secs().max(1) };
+        }
+        let limit = if greylisted {
+            (config.requests_per_window / config.greylist_divisor.max(1)).max(1)
+        } else {
+            config.requests_per_window
+        };
+        if state.estimate(now, window) >= limit as f64 {
+            let elapsed = state.window_start.map(|s| now.duration_since(s)).unwrap_or_default();
+            return IpDecision::RateLimited { retry_after_secs: window.saturating_sub(elapsed).as_secs().max(1) };
+        }
+        state.current += 1;
+        IpDecision::Allow
+    }
+
+    /// Record a failed authentication; enough of them greylist the address
+    pub fn record_auth_failure(&self, ip: IpAddr) {
+        let access = self.access.read().clone();
+        let config = &access.config;
+        if config.greylist_after_failures == 0 || in_ranges(&access.exempt, &ip) {
+            return;
+        }
+        let now = Instant::now();
+        let window = Duration::from_secs(config.greylist_window_secs.max(1));
+        let mut states = self.states.lock();
+        let state = states.entry(ip).or_default();
+        state.last_seen = Some(now);
+        state.failures.retain(|t| now.duration_since(*t) < window);
+        state.failures.push(now);
+        if state.failures.len() >= config.greylist_after_failures as usize && !state.is_greylisted(now) {
+            state.greylisted_until = Some(now + Duration::from_secs(config.greylist_duration_secs));
+            metrics::counter!("narayana_http_greylisted_ips_total").increment(1);
+            tracing::warn!("Greylisted {} after {} auth failures", ip, state.failures.len());
+        }
+    }
+
+    /// A successful login clears the failure history (not an active greylisting)
+    pub fn record_auth_success(&self, ip: IpAddr) {
+        if let Some(state) = self.states.lock().get_mut(&ip) {
+            state.failures.clear();
+        }
+    }
+
+    pub fn stats(&self) -> IpGuardStats {
+        let now = Instant::now();
+        let wall = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let states = self.states.lock();
+        let greylisted = states.iter()
+            .filter(|(_, s)| s.is_greylisted(now))
+            .map(|(ip, s)| GreylistEntry {
+                ip: ip.to_string(),
+                recent_failures: s.failures.len(),
+                expires_at: wall + s.greylisted_until.map(|u| u.duration_since(now).as_secs()).unwrap_or(0),
+            })
+            .collect();
+        IpGuardStats {
+            tracked_ips: states.len(),
+            greylisted,
+            blocked_rate_limited: self.rate_limited.load(Ordering::Relaxed),
+            blocked_admin_forbidden: self.admin_forbidden.load(Ordering::Relaxed),
+            blocked_greylisted: self.greylisted.load(Ordering::Relaxed),
+        }
+    }
+}
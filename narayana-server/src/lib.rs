@@ -9,10 +9,22 @@ pub mod oauth2;
 pub mod startup;
 pub mod config_manager;
 pub mod http;
+pub mod openapi;
+pub mod http_headers;
+pub mod ip_guard;
+pub mod admission;
+pub mod idempotency;
+pub mod bulk_insert;
+pub mod health;
+pub mod resource_scaling;
 pub mod websocket;
 pub mod websocket_manager;
 pub mod websocket_bridge;
 pub mod workers;
 pub mod schema_loader;
+pub mod skills;
 pub mod llm_brain_wrapper;
-
+pub mod replication;
+pub mod cluster;
+pub mod grpc;
+pub mod nlq;
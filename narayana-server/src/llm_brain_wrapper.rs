@@ -2,7 +2,11 @@
 // This avoids circular dependencies by implementing the traits in the server crate
 
 use narayana_llm::{BrainInterface, BrainFunctionInterface, RAGMemory};
+use narayana_llm::context::{keyword_relevance, ContextCandidate, ContextProvider, ContextSource};
 use narayana_storage::cognitive::{CognitiveBrain, Memory as StorageMemory, MemoryType};
+use narayana_storage::infinite_context::InfiniteContextManager;
+use narayana_storage::memory_bridge::BRAIN_MEMORY_TAG;
+use std::collections::HashSet;
 use std::sync::Arc;
 use async_trait::async_trait;
 use serde_json::Value;
@@ -10,11 +14,18 @@ use serde_json::Value;
 /// Wrapper that implements both BrainInterface and BrainFunctionInterface
 pub struct BrainWrapper {
     brain: Arc<CognitiveBrain>,
+    context_store: Option<Arc<InfiniteContextManager>>,
 }
 
 impl BrainWrapper {
     pub fn new(brain: Arc<CognitiveBrain>) -> Self {
-        Self { brain }
+        Self { brain, context_store: None }
+    }
+
+    /// Also search an infinite context store when retrieving vector context
+    pub fn with_context_store(mut self, store: Arc<InfiniteContextManager>) -> Self {
+        self.context_store = Some(store);
+        self
     }
 }
 
@@ -61,6 +72,104 @@ impl BrainInterface for BrainWrapper {
     }
 }
 
+#[async_trait]
+impl ContextProvider for BrainWrapper {
+    async fn candidates(
+        &self,
+        source: ContextSource,
+        query: &str,
+        query_embedding: Option<&[f32]>,
+        limit: usize,
+    ) -> std::result::Result<Vec<ContextCandidate>, Box<dyn std::error::Error + Send + Sync>> {
+        let mut candidates = Vec::new();
+        match source {
+            ContextSource::WorkingMemory => {
+                // Working memory is current by definition; priority counts as much as the query
+                let states = self.brain.get_working_memory();
+                for state in states.iter().rev().take(limit) {
+                    let text = value_text(&state.content);
+                    candidates.push(ContextCandidate {
+                        id: state.id.clone(),
+                        source,
+                        relevance: 0.5 * state.priority.clamp(0.0, 1.0) + 0.5 * keyword_relevance(query, &text),
+                        text,
+                        timestamp: state.timestamp,
+                    });
+                }
+            }
+            ContextSource::Episodic => {
+                let memories = self.brain.retrieve_memories_by_type(MemoryType::Episodic, Some(query), query_embedding, limit)?;
+                candidates.extend(memories.iter().map(|m| memory_candidate(m, source, query, query_embedding)));
+            }
+            ContextSource::Vector => {
+                // Ids of found memories and of the documents they were promoted from
+                let mut seen = HashSet::new();
+                if let Some(embedding) = query_embedding {
+                    let memories = self.brain.retrieve_memories_semantic(embedding, limit, None, None)?;
+                    for memory in memories.iter().filter(|m| !matches!(m.memory_type, MemoryType::Episodic | MemoryType::Working)) {
+                        seen.insert(memory.id.clone());
+                        if let Some(context_id) = memory.context.get("provenance").and_then(|p| p.get("context_id")).and_then(Value::as_str) {
+                            seen.insert(context_id.to_string());
+                        }
+                        candidates.push(memory_candidate(memory, source, query, query_embedding));
+                    }
+                }
+                if let Some(store) = &self.context_store {
+                    // Skip store hits that mirror a memory already found
+                    for hit in store.search(query, query_embedding, limit)? {
+                        let mirrors_memory = hit.metadata.tags.iter().any(|tag| tag == BRAIN_MEMORY_TAG)
+                            && hit.metadata.message_id.as_ref().is_some_and(|id| seen.contains(id));
+                        if mirrors_memory || seen.contains(&hit.id) {
+                            continue;
+                        }
+                        candidates.push(ContextCandidate {
+                            id: hit.id,
+                            source,
+                            text: hit.text,
+                            relevance: hit.score,
+                            timestamp: hit.created_at,
+                        });
+                    }
+                }
+            }
+        }
+        Ok(candidates)
+    }
+}
+
+/// Candidate from a brain memory, scored by embedding when both sides have one
+fn memory_candidate(memory: &StorageMemory, source: ContextSource, query: &str, query_embedding: Option<&[f32]>) -> ContextCandidate {
+    let text = value_text(&memory.content);
+    let relevance = match (query_embedding, memory.embedding.as_deref()) {
+        (Some(a), Some(b)) if a.len() == b.len() => cosine_similarity(a, b),
+        _ => keyword_relevance(query, &text),
+    };
+    ContextCandidate {
+        id: memory.id.clone(),
+        source,
+        text,
+        relevance,
+        timestamp: memory.created_at,
+    }
+}
+
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f64 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    (dot / (norm_a * norm_b)).max(0.0) as f64
+}
+
 impl BrainFunctionInterface for BrainWrapper {
     fn create_thought(&self, content: Value, priority: f64) -> std::result::Result<String, Box<dyn std::error::Error + Send + Sync>> {
         self.brain
@@ -123,3 +232,29 @@ impl BrainFunctionInterface for BrainWrapper {
 
 
 
+
+/// Embedding provider backed by the LLM manager, used for "embed" schema columns
+pub struct LlmEmbeddingProvider {
+    llm_manager: Arc<narayana_llm::LLMManager>,
+}
+
+impl LlmEmbeddingProvider {
+    pub fn new(llm_manager: Arc<narayana_llm::LLMManager>) -> Self {
+        Self { llm_manager }
+    }
+}
+
+#[async_trait]
+impl narayana_storage::embeddings::EmbeddingProvider for LlmEmbeddingProvider {
+    async fn embed(&self, texts: &[String], _model: Option<&str>) -> narayana_core::Result<Vec<Vec<f32>>> {
+        let mut vectors = Vec::with_capacity(texts.len());
+        for text in texts {
+            let vector = self.llm_manager
+                .generate_embedding(text, None)
+                .await
+                .map_err(|e| narayana_core::Error::Storage(format!("Embedding generation failed: {}", e)))?;
+            vectors.push(vector);
+        }
+        Ok(vectors)
+    }
+}
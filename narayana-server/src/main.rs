@@ -12,9 +12,16 @@ use tracing_subscriber;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    // Configuration: NARAYANA_CONFIG file (TOML/YAML/JSON) or defaults, then
+    // environment overrides - everything still works out of the box
+    let config_path = std::env::var("NARAYANA_CONFIG")
+        .or_else(|_| std::env::var("NARAYANA_CONFIG_FILE"))
+        .ok();
+    let config = narayana_core::config::NarayanaConfig::load(config_path.as_deref())?;
+
     // Initialize logging
     tracing_subscriber::fmt()
-        .with_max_level(tracing::Level::INFO)
+        .with_max_level(config.instance.log_level.parse::<tracing::Level>().unwrap_or(tracing::Level::INFO))
         .with_target(false)
         .with_thread_ids(false)
         .init();
@@ -23,49 +30,55 @@ async fn main() -> anyhow::Result<()> {
     banner::print_colored_banner();
 
     info!("🚀 Starting NarayanaDB...");
+    match &config_path {
+        Some(path) => info!("⚙️  Configuration loaded from {}", path),
+        None => info!("⚙️  No configuration file (set NARAYANA_CONFIG); using defaults"),
+    }
+    log_feature_toggles(&config);
+
+    // Runtime configuration (hot-reloaded when loaded from a file)
+    let config_manager = initialize_config_manager(&config, config_path).await?;
+
+    // Outbound requests (webhooks, workers, RDE, LLM, S3) share one egress policy
+    initialize_egress_policy(&config_manager).await?;
 
-    // Create default configuration - everything works out of the box
-    let config = create_default_config();
+    // Startup recovery progress; readiness is down (or degraded when serving
+    // while loading) until tables, schema and seeds are loaded
+    let recovery = Arc::new(narayana_server::health::RecoveryGate::new());
+    recovery.set_serve_while_loading(config_manager.get().await.storage.recovery.serve_while_loading);
 
-    // Initialize storage engine
+    // Initialize storage engine (tables are loaded in the background below)
     info!("📦 Initializing storage engine...");
-    let storage = initialize_storage(&config).await?;
-    info!("✅ Storage engine ready");
+    let persistent_store = initialize_storage(&config).await?;
 
     // Initialize database manager
     info!("🗄️  Initializing database manager...");
     let db_manager = Arc::new(narayana_storage::database_manager::DatabaseManager::new());
     info!("✅ Database manager ready");
 
+    // Databases can be placed on RocksDB or the in-memory store instead of the file store
+    let storage = initialize_storage_backends(&config, persistent_store.clone(), db_manager.clone())?;
+    // Every write is checked against storage.quotas and refused while the disk is nearly full
+    let (storage, quotas) = initialize_quotas(&config_manager, storage, db_manager.clone()).await;
+    info!("✅ Storage engine ready");
+
     // Initialize persistence FIRST (before schema loading to ensure data is persisted)
     info!("💾 Initializing persistence...");
     let persistence = initialize_persistence(&config).await?;
     info!("✅ Persistence ready");
-    
-    // CRITICAL: Load schema and seeds AFTER persistence is ready
-    // This ensures all data is properly persisted to disk
-    let schema_dir = std::path::Path::new("./schema");
-    if schema_dir.exists() {
-        info!("📋 Loading schema and seeds from ./schema...");
-        match narayana_server::schema_loader::load_schema_and_seeds(schema_dir, db_manager.clone(), storage.clone()).await {
-            Ok(_) => {
-                info!("✅ Schema and seeds loaded successfully");
-                // CRITICAL: Force sync all data to disk after loading schema/seeds
-                info!("💾 Syncing all data to disk...");
-                // The storage engine should already sync, but we ensure it here
-                // This is handled by the atomic writes with fsync in persistent_column_store
-            }
-            Err(e) => {
-                warn!("⚠️  Failed to load schema/seeds: {}. Continuing without them.", e);
-            }
-        }
-    } else {
-        info!("ℹ️  No schema directory found. Skipping schema/seed loading.");
-    }
+
+    // Load tables, then schema and seeds, while the rest of the server starts;
+    // the health endpoint reports progress and loaded tables are served early
+    let recovery_task = tokio::spawn(recover_tables_and_schema(
+        persistent_store,
+        storage.clone(),
+        db_manager.clone(),
+        recovery.clone(),
+    ));
 
     // Initialize auto-scaling
     info!("⚖️  Initializing auto-scaling...");
-    let auto_scaler = initialize_auto_scaling(db_manager.clone()).await?;
+    let (auto_scaler, shard_scaler) = initialize_auto_scaling(&config, db_manager.clone()).await?;
     info!("✅ Auto-scaling ready");
 
     // Initialize load balancer
@@ -103,8 +116,13 @@ async fn main() -> anyhow::Result<()> {
     // Initialize LLM manager and connect to cognitive brain
     info!("🤖 Initializing LLM manager...");
     use narayana_server::llm_brain_wrapper::BrainWrapper;
-    let brain_wrapper = Arc::new(BrainWrapper::new(brain.clone()));
-    let llm_manager = Arc::new(narayana_llm::LLMManager::with_brain(brain_wrapper));
+    let context_store = Arc::new(narayana_storage::infinite_context::InfiniteContextManager::new(Default::default()));
+    let brain_wrapper = Arc::new(BrainWrapper::new(brain.clone()).with_context_store(context_store.clone()));
+    // Chats get working memory, episodic memories and vector matches within the context budget
+    let llm_manager = Arc::new(
+        narayana_llm::LLMManager::with_brain(brain_wrapper.clone())
+            .with_context_provider(brain_wrapper, narayana_llm::ContextBudget::default()),
+    );
     
     // Load API keys from environment if available
     if let Ok(key) = std::env::var("OPENAI_API_KEY") {
@@ -143,7 +161,10 @@ async fn main() -> anyhow::Result<()> {
 
     // Initialize webhooks
     info!("🔔 Initializing webhooks...");
-    let webhook_manager = Arc::new(narayana_storage::webhooks::WebhookManager::new());
+    let webhook_manager = Arc::new(
+        narayana_storage::webhooks::WebhookManager::new()
+            .with_history_dir(std::path::PathBuf::from(&config.storage.data_dir).join("webhooks"))?,
+    );
     info!("✅ Webhooks ready");
 
     // Initialize vector store
@@ -151,6 +172,154 @@ async fn main() -> anyhow::Result<()> {
     let vector_store = Arc::new(narayana_storage::vector_search::VectorStore::new());
     info!("✅ Vector store ready");
 
+    // Initialize auto-embedding for "embed" schema columns (backed by the LLM manager)
+    info!("🧬 Initializing embeddings manager...");
+    let embeddings = Arc::new(narayana_storage::embeddings::EmbeddingsManager::new(
+        Arc::new(narayana_server::llm_brain_wrapper::LlmEmbeddingProvider::new(llm_manager.clone())),
+        vector_store.clone(),
+    ));
+    embeddings.clone().start_background_worker(storage.clone(), std::time::Duration::from_secs(1));
+    info!("✅ Embeddings manager ready");
+
+    // Mirror episodic/semantic memories into the RAG context store and promote tagged documents
+    info!("🔗 Initializing brain/RAG memory sync...");
+    let rag_sync = Arc::new(
+        narayana_storage::memory_bridge::RagMemorySync::new(brain.clone(), context_store, Default::default())
+            .with_embedder(Arc::new(narayana_server::llm_brain_wrapper::LlmEmbeddingProvider::new(llm_manager.clone()))),
+    );
+    rag_sync.start_background_sync(std::time::Duration::from_secs(5));
+    info!("✅ Brain/RAG memory sync ready");
+
+    // Initialize workers (before RDE, whose subscriptions can invoke them)
+    info!("⚙️  Initializing workers...");
+    let worker_manager = initialize_workers().await?;
+    info!("✅ Workers ready");
+
+    // Initialize CDC feed and anomaly detection (anomalies are published into RDE)
+    info!("📈 Initializing anomaly detection...");
+    let change_feed = Arc::new(narayana_storage::cdc::ChangeFeed::new(4096));
+    let worker_invoker = Arc::new(narayana_rde::transports::worker::LocalWorkerInvoker {
+        workers: worker_manager.clone(),
+        storage: storage.clone(),
+        db_manager: db_manager.clone(),
+        brain: Some(brain.clone()),
+    });
+    let table_sink = Arc::new(
+        narayana_rde::transports::table::TableSink::new(storage.clone(), db_manager.clone()),
+    );
+    let native_events = Arc::new(narayana_storage::native_events::NativeEventsSystem::new(
+        narayana_storage::native_events::EventsConfig::default(),
+    ));
+    let rde = initialize_rde(native_events.clone(), worker_invoker, table_sink);
+    let anomaly = initialize_anomaly_detection(&config, &change_feed, rde.clone()).await?;
+    info!("✅ Anomaly detection ready");
+    quotas.add_sink(Arc::new(narayana_rde::quotas::RdeQuotaSink::register(rde.clone()).await?));
+
+    // Initialize feature store (batch backfill from tables, online updates from CDC)
+    info!("🧮 Initializing feature store...");
+    let features = initialize_feature_store(&config, &storage, &change_feed).await?;
+    info!("✅ Feature store ready");
+
+    // Initialize blob store (audio clips, video frames, avatar assets, model files)
+    info!("🗃️  Initializing blob store...");
+    let blob_dir = std::path::PathBuf::from(&config.storage.data_dir).join("blobs");
+    let blobs = Arc::new(narayana_storage::blob_store::BlobStore::open(blob_dir, &config.storage.blobs)?);
+    info!("✅ Blob store ready");
+
+    // Model registry scored by PREDICT (ONNX artifacts need the `onnx` feature)
+    info!("🤖 Initializing model registry...");
+    let models = Arc::new(narayana_storage::model_registry::ModelArtifactRegistry::new(Some(
+        std::path::PathBuf::from(&config.storage.data_dir).join("models"),
+    ))?);
+    models.load_persisted()?;
+    let predictor = Arc::new(narayana_query::ml_integration::PredictEngine::new(models));
+    info!("✅ Model registry ready");
+
+    // Spatial indexes live in memory; their definitions are kept under the data directory
+    let spatial = Arc::new(narayana_storage::geospatial::SpatialIndexManager::with_state_dir(
+        std::path::PathBuf::from(&config.storage.data_dir).join("spatial"),
+    )?);
+
+    // Initialize skills (after tables, workers and RDE, which skills install into)
+    info!("🧩 Initializing skills...");
+    let skills = Arc::new(narayana_server::skills::SkillManager::new(
+        storage.clone(),
+        db_manager.clone(),
+        worker_manager.clone(),
+        rde.clone(),
+        std::path::PathBuf::from(&config.storage.data_dir).join("behaviors"),
+    ).await?);
+    // Installed skills live in a system table and spatial indexes are rebuilt
+    // from their tables, so restore both once recovery has loaded them
+    tokio::spawn({
+        let skills = skills.clone();
+        let spatial = spatial.clone();
+        let storage = storage.clone();
+        async move {
+            let _ = recovery_task.await;
+            if let Err(e) = spatial.restore(storage.as_ref()).await {
+                warn!("⚠️  Failed to restore spatial indexes: {}", e);
+            }
+            match skills.restore().await {
+                Ok(count) => info!("✅ Restored {} installed skills", count),
+                Err(e) => warn!("⚠️  Failed to restore installed skills: {}", e),
+            }
+        }
+    });
+    info!("✅ Skills ready");
+
+    // Initialize threading system (maintenance jobs run on its background class)
+    info!("🧵 Initializing threading system...");
+    let thread_manager = initialize_threading(&config).await?;
+    info!("✅ Threading system ready");
+
+    // Compaction removes rows past their TTL column; it reads the raw store
+    // because it rewrites tables by stored row index
+    let full_text = Arc::new(narayana_storage::full_text::FullTextIndexManager::new());
+    let ttl = Arc::new(
+        narayana_storage::ttl::TtlCompactor::new(storage.clone(), db_manager.clone())
+            .with_full_text(full_text.clone())
+            .with_spatial(spatial.clone())
+            .with_embeddings(embeddings.clone(), vector_store.clone()),
+    );
+
+    // Initialize maintenance jobs (compaction, consolidation, backups, analyze)
+    info!("🗓️  Initializing maintenance jobs...");
+    let jobs = initialize_jobs(&config, brain.clone(), query_learning.clone(), thread_manager.scheduler(), ttl);
+    info!("✅ Maintenance jobs ready ({} jobs)", jobs.list().len());
+
+    // Initialize ingest connectors (polled as jobs; the HTTP layer attaches the table sink)
+    info!("📥 Initializing ingest connectors...");
+    let connectors = initialize_connectors(&config, jobs.clone())?;
+    info!("✅ Ingest connectors ready ({} connectors)", connectors.list().len());
+
+    // Initialize runtime invariant checks
+    info!("🔎 Initializing invariant checks...");
+    let invariants = initialize_invariants(&config, storage.clone(), db_manager.clone(), full_text.clone(), native_events);
+    info!("✅ Invariant checks ready ({} known violations)", invariants.violations().len());
+
+    // Drop versions of system-versioned tables that outlived their retention
+    {
+        let temporal = db_manager.temporal().clone();
+        let storage = storage.clone();
+        let full_text = full_text.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(std::time::Duration::from_secs(300));
+            loop {
+                ticker.tick().await;
+                let purged = temporal.purge_expired(storage.as_ref(), Some(full_text.as_ref())).await;
+                if purged > 0 {
+                    info!("🕰️  Purged {} expired row versions", purged);
+                }
+            }
+        });
+    }
+
+    // Initialize cross-region replication (multi-primary mode ships to peers)
+    info!("🌍 Initializing replication...");
+    let replication = initialize_replication(&config, &worker_manager, &storage, &db_manager, &brain)?;
+    info!("✅ Replication ready ({} peers)", replication.status().peers.len());
+
     // Initialize self-healing
     info!("🏥 Initializing self-healing...");
     let self_healing = initialize_self_healing().await?;
@@ -166,15 +335,15 @@ async fn main() -> anyhow::Result<()> {
     let optimizer = initialize_optimization_algorithms().await?;
     info!("✅ Advanced optimization algorithms ready (quantum-inspired search)");
 
-    // Initialize workers
-    info!("⚙️  Initializing workers...");
-    let worker_manager = initialize_workers().await?;
-    info!("✅ Workers ready");
+    // Initialize predictive resource scaling
+    info!("📈 Initializing predictive scaling...");
+    let scaling = initialize_predictive_scaling(&config, thread_manager.clone(), jobs.clone())?;
+    info!("✅ Predictive scaling ready");
 
-    // Initialize threading system
-    info!("🧵 Initializing threading system...");
-    let thread_manager = initialize_threading(&config).await?;
-    info!("✅ Threading system ready");
+    // Initialize GPU devices
+    info!("🎮 Initializing GPU devices...");
+    let gpu = initialize_gpu(&config)?;
+    info!("✅ GPU devices ready ({} devices)", gpu.devices().len());
 
     // Initialize WebSocket manager
     info!("🔌 Initializing WebSocket manager...");
@@ -185,6 +354,8 @@ async fn main() -> anyhow::Result<()> {
     // Initialize WebSocket bridge
     info!("🌉 Initializing WebSocket event bridge...");
     let stream_manager = Arc::new(narayana_storage::sensory_streams::SensoryStreamManager::new());
+    // Flag declared streams that stop sending
+    stream_manager.clone().start_dropout_monitor(std::time::Duration::from_secs(1));
     let ws_bridge = Arc::new({
         let mut bridge = narayana_server::websocket_bridge::WebSocketBridge::new(
             ws_manager.clone(),
@@ -197,9 +368,9 @@ async fn main() -> anyhow::Result<()> {
     info!("✅ WebSocket event bridge ready");
 
     // Initialize token manager for WebSocket authentication
-    // Load JWT secret from environment variable or generate a secure one
-    let jwt_secret = std::env::var("NARAYANA_JWT_SECRET")
-        .unwrap_or_else(|_| {
+    // JWT secret from security.jwt_secret (or NARAYANA_JWT_SECRET), else a random one
+    let jwt_secret = config.security.jwt_secret.clone()
+        .unwrap_or_else(|| {
             // Generate a secure random secret if not provided
             use rand::Rng;
             let mut rng = rand::thread_rng();
@@ -220,8 +391,12 @@ async fn main() -> anyhow::Result<()> {
 
     // Initialize Avatar Bridge (if narayana-me is available)
     #[cfg(feature = "avatar")]
-    let avatar_bridge_handle: Option<tokio::task::JoinHandle<()>> = {
+    let avatar_bridge_handle: Option<tokio::task::JoinHandle<()>> = if !config.features.me {
+        info!("🎭 Avatar Bridge disabled (features.me = false)");
+        None
+    } else {
         info!("🎭 Initializing Avatar Bridge...");
+        let avatar_port = config.features.me_port;
         use narayana_me::{AvatarBroker, AvatarConfig, AvatarProviderType, MultimodalManager, AvatarBridge};
         use std::sync::Arc;
         use tokio::sync::RwLock;
@@ -233,7 +408,7 @@ async fn main() -> anyhow::Result<()> {
             avatar_id: None,
             expression_sensitivity: 0.7,
             animation_speed: 1.0,
-            websocket_port: Some(8081),
+            websocket_port: Some(avatar_port),
             enable_lip_sync: true,
             enable_gestures: true,
             provider_config: None,
@@ -263,21 +438,21 @@ async fn main() -> anyhow::Result<()> {
                     multimodal_manager,
                     #[cfg(feature = "llm")]
                     avatar_llm_manager,
-                    8081, // Avatar WebSocket port
+                    avatar_port, // Avatar WebSocket port
                 ));
                 
                 // Start avatar bridge in a separate task
                 let bridge_clone = Arc::clone(&avatar_bridge);
                 let handle = tokio::spawn(async move {
-                    info!("🎭 Avatar bridge starting on 0.0.0.0:8081...");
+                    info!("🎭 Avatar bridge starting on 0.0.0.0:{}...", avatar_port);
                     match bridge_clone.start().await {
                         Ok(_) => {
                             info!("✅ Avatar bridge stopped gracefully");
                         }
                         Err(e) => {
                             error!("❌ Avatar bridge crashed: {}", e);
-                            error!("   This means WebSocket connections to ws://localhost:8081/avatar/ws will fail!");
-                            error!("   Check if port 8081 is already in use or if there's a binding error.");
+                            error!("   This means WebSocket connections to ws://localhost:{}/avatar/ws will fail!", avatar_port);
+                            error!("   Check if port {} is already in use or if there's a binding error.", avatar_port);
                         }
                     }
                 });
@@ -286,9 +461,9 @@ async fn main() -> anyhow::Result<()> {
                 tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
                 
                 // Verify bridge is actually listening
-                match tokio::net::TcpStream::connect("127.0.0.1:8081").await {
+                match tokio::net::TcpStream::connect(("127.0.0.1", avatar_port)).await {
                     Ok(_) => {
-                        info!("✅ Avatar Bridge is listening on port 8081");
+                        info!("✅ Avatar Bridge is listening on port {}", avatar_port);
                     }
                     Err(e) => {
                         warn!("⚠️  Avatar Bridge might not be listening yet: {}", e);
@@ -318,9 +493,67 @@ async fn main() -> anyhow::Result<()> {
     });
 
     // Start HTTP server
-    info!("🌐 Starting HTTP server on {}...", config.http_port);
+    info!("🌐 Starting HTTP server on {}:{}...", config.network.bind_address, config.network.bind_port);
+    // CORS and security headers follow the runtime config
+    let http_headers = Arc::new(narayana_server::http_headers::HttpHeadersPolicy::new(
+        &config_manager.get().await.network,
+    ));
+    http_headers.watch(&config_manager).await;
+    // Per-IP limits, admin CIDR lists and greylisting
+    let ip_guard = Arc::new(narayana_server::ip_guard::IpGuard::new(
+        &config_manager.get().await.security.ip_access,
+    ));
+    ip_guard.watch(&config_manager).await;
+    // Per-route concurrency limits so heavy queries cannot starve robot endpoints
+    let admission = Arc::new(narayana_server::admission::AdmissionController::new(
+        &config_manager.get().await.connection_pool,
+    ));
+    admission.watch(&config_manager).await;
+    // Idempotency-Key replay window for mutating endpoints
+    let idempotency = Arc::new(narayana_server::idempotency::IdempotencyStore::new(
+        &config_manager.get().await.network,
+    ));
+    idempotency.watch(&config_manager).await;
+    // Built web UI served as the router fallback; compress its assets up front
+    let static_files = Arc::new(narayana_server::static_files::StaticFiles::new(
+        &config_manager.get().await.network,
+    ));
+    static_files.watch(&config_manager).await;
+    {
+        let static_files = static_files.clone();
+        tokio::spawn(async move {
+            static_files.precompress_all().await;
+        });
+    }
+    // Per-subsystem readiness checks behind /health/ready
+    let health = Arc::new(narayana_server::health::HealthRegistry::new(
+        &config_manager.get().await.monitoring.health,
+    ));
+    health.register(recovery.clone());
+    health.register(Arc::new(narayana_server::health::StorageCheck {
+        storage: storage.clone(),
+        db_manager: db_manager.clone(),
+    }));
+    health.register(Arc::new(narayana_server::health::PersistenceCheck {
+        persistence: persistence.clone(),
+    }));
+    health.register(Arc::new(narayana_server::health::LlmCheck {
+        llm: llm_manager.clone(),
+        connect_timeout: std::time::Duration::from_secs(1),
+    }));
+    health.register(Arc::new(narayana_server::health::WebSocketCheck {
+        ws_state: ws_state.clone(),
+    }));
+    health.watch(&config_manager).await;
+
+    // Natural-language questions, grounded in the catalog (UI users stay hidden)
+    let nlq = Arc::new(
+        narayana_server::nlq::NaturalLanguageQuery::new(llm_manager.clone(), storage.clone(), db_manager.clone())
+            .with_hidden_tables(&[narayana_server::http::PROTECTED_USERS_TABLE]),
+    );
+
     let http_server = start_http_server(
-        config.http_port,
+        &config,
         storage.clone(),
         db_manager.clone(),
         search_engine.clone(),
@@ -331,8 +564,34 @@ async fn main() -> anyhow::Result<()> {
         Some(ws_state.clone()),
         Some(cpl_manager.clone()),
         vector_store.clone(),
+        Some(embeddings.clone()),
+        change_feed.clone(),
+        anomaly.clone(),
+        features.clone(),
+        blobs,
+        http_headers,
+        ip_guard,
+        admission,
+        idempotency,
+        health,
+        recovery,
+        static_files,
+        skills,
+        jobs,
+        connectors,
+        quotas,
+        full_text,
+        spatial,
+        invariants,
+        scaling,
+        shard_scaler,
+        rde,
+        replication,
+        nlq,
+        gpu,
+        predictor,
     ).await?;
-    info!("✅ HTTP server ready on http://localhost:{}", config.http_port);
+    info!("✅ HTTP server ready on http://localhost:{}", config.network.bind_port);
 
     // HTTP API provides full functionality - gRPC and GraphQL not needed for robot demo
 
@@ -373,64 +632,238 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
-/// Default configuration - everything works out of the box
-struct ServerConfig {
-    http_port: u16,
-    data_dir: String,
-}
-
-fn create_default_config() -> ServerConfig {
-    ServerConfig {
-        http_port: 8080,
-        data_dir: "./data".to_string(),
+/// Report subsystem toggles that this binary does not act on itself
+fn log_feature_toggles(config: &narayana_core::config::NarayanaConfig) {
+    info!("   Subsystems enabled: {}", config.features.enabled().join(", "));
+    #[cfg(not(feature = "avatar"))]
+    if config.features.me {
+        info!("   features.me is set but narayana-server was built without the `avatar` feature");
+    }
+    for (name, enabled) in [("wld", config.features.wld), ("sc", config.features.sc), ("spk", config.features.spk), ("eye", config.features.eye)] {
+        if enabled {
+            info!("   features.{} is set; the {} subsystem runs in the process that embeds narayana-{}", name, name, name);
+        }
+    }
+    if config.network.enable_tls {
+        warn!("⚠️  network.enable_tls is set but the HTTP listener serves plain HTTP; terminate TLS in front of it");
     }
 }
 
 /// Initialize storage engine
-async fn initialize_storage(config: &ServerConfig) -> anyhow::Result<Arc<dyn narayana_storage::ColumnStore>> {
+async fn initialize_storage(
+    config: &narayana_core::config::NarayanaConfig,
+) -> anyhow::Result<Arc<narayana_storage::persistent_column_store::PersistentColumnStore>> {
     use narayana_storage::persistent_column_store::PersistentColumnStore;
     use narayana_core::types::CompressionType;
     
     // Use persistent storage with compression
-    let data_path = std::path::PathBuf::from(&config.data_dir).join("columnar");
-    let store = Arc::new(PersistentColumnStore::new(data_path, CompressionType::LZ4)?);
+    let data_path = std::path::PathBuf::from(&config.storage.data_dir).join("columnar");
+    // Writes are logged to storage.wal_dir before their blocks are flushed;
+    // tables replay what their metadata misses as they load at startup
+    let wal = Arc::new(narayana_storage::wal::WriteAheadLog::open(&config.storage.wal_dir).await?);
+    wal.clone().start_checkpoints(config.storage.checkpoint_interval);
+    let store = Arc::new(PersistentColumnStore::new(data_path, CompressionType::LZ4)?.with_wal(wal));
+    
+    // Corrupted blocks are quarantined on read and rebuilt from these copies;
+    // retry now and then for blocks no source had yet
+    let healer = store.block_healer();
+    for source in &config.storage.recovery.rebuild_sources {
+        healer.add_source(Arc::new(narayana_storage::self_healing::DirectoryBlockSource::new(source)));
+    }
+    if !config.storage.recovery.rebuild_sources.is_empty() {
+        let store = store.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(std::time::Duration::from_secs(300));
+            loop {
+                ticker.tick().await;
+                let rebuilt = store.rebuild_quarantined_blocks().await;
+                if rebuilt > 0 {
+                    info!("🩹 Rebuilt {} quarantined blocks", rebuilt);
+                }
+            }
+        });
+    }
     
-    // Load all tables from disk - handle errors gracefully to allow startup
-    // TEMPORARY: Skip loading tables if it fails - allows server to start even with corrupted data
-    match store.load_all_tables().await {
+    info!("✅ Persistent columnar storage initialized at {}", config.storage.data_dir);
+    
+    Ok(store)
+}
+
+/// Route databases to the backends selected in `storage.backends`
+fn initialize_storage_backends(
+    config: &narayana_core::config::NarayanaConfig,
+    filesystem: Arc<narayana_storage::persistent_column_store::PersistentColumnStore>,
+    db_manager: Arc<narayana_storage::database_manager::DatabaseManager>,
+) -> anyhow::Result<Arc<dyn narayana_storage::ColumnStore>> {
+    use narayana_core::config::StorageBackendKind;
+    use narayana_core::types::CompressionType;
+    use narayana_storage::storage_backends::StorageBackends;
+
+    let backends = &config.storage.backends;
+    let kinds = backends.kinds();
+    if kinds == [StorageBackendKind::FileSystem] {
+        return Ok(filesystem);
+    }
+
+    let data_dir = std::path::PathBuf::from(&config.storage.data_dir);
+    let mut router = StorageBackends::new(filesystem, db_manager, backends);
+    if kinds.contains(&StorageBackendKind::RocksDb) {
+        let path = data_dir.join("rocksdb");
+        let store = narayana_storage::rocksdb_column_store::RocksDbColumnStore::open(&path, CompressionType::LZ4)?;
+        router = router.with_rocksdb(Arc::new(store));
+        info!("✅ RocksDB storage backend at {}", path.display());
+    }
+    if kinds.contains(&StorageBackendKind::Memory) {
+        let snapshot = data_dir.join("memory.snapshot");
+        let store = Arc::new(narayana_storage::InMemoryColumnStore::restore(&snapshot)?);
+        if backends.memory_snapshot_interval_secs > 0 {
+            let interval = std::time::Duration::from_secs(backends.memory_snapshot_interval_secs);
+            store.start_snapshots(snapshot.clone(), interval);
+            info!("✅ In-memory storage backend (snapshot to {} every {:?})", snapshot.display(), interval);
+        } else {
+            info!("✅ In-memory storage backend (no snapshots)");
+        }
+        router = router.with_memory(store);
+    }
+    Ok(Arc::new(router))
+}
+
+/// Wrap the storage so writes are checked against `storage.quotas`, recounting
+/// usage and free disk space in the background and following config reloads
+async fn initialize_quotas(
+    config_manager: &narayana_server::config_manager::ConfigManager,
+    storage: Arc<dyn narayana_storage::ColumnStore>,
+    db_manager: Arc<narayana_storage::database_manager::DatabaseManager>,
+) -> (Arc<dyn narayana_storage::ColumnStore>, Arc<narayana_storage::quotas::QuotaManager>) {
+    use narayana_storage::quotas::QuotaManager;
+
+    let config = config_manager.get().await;
+    let data_dir = std::path::PathBuf::from(&config.storage.data_dir);
+    let quotas = Arc::new(QuotaManager::new(config.storage.quotas.clone(), storage.clone(), db_manager, data_dir));
+    quotas.start();
+    config_manager.subscribe(Box::new({
+        let quotas = quotas.clone();
+        move |config: &narayana_core::config::NarayanaConfig| quotas.update(&config.storage.quotas)
+    })).await;
+    (quotas.wrap(storage), quotas)
+}
+
+/// Startup recovery: load tables from disk, then schema and seeds
+async fn recover_tables_and_schema(
+    store: Arc<narayana_storage::persistent_column_store::PersistentColumnStore>,
+    storage: Arc<dyn narayana_storage::ColumnStore>,
+    db_manager: Arc<narayana_storage::database_manager::DatabaseManager>,
+    recovery: Arc<narayana_server::health::RecoveryGate>,
+) {
+    use narayana_server::health::StartupPhase;
+
+    recovery.enter_phase(StartupPhase::LoadingTables);
+    info!("📦 Loading tables from disk...");
+    // Handle errors gracefully to allow startup - server can start fresh
+    let result = store.load_all_tables_with_progress(|progress| {
+        recovery.set_table_progress(progress.loaded, progress.failed, progress.total);
+    }).await;
+    match result {
         Ok(_) => info!("✅ Loaded tables from disk"),
         Err(e) => {
             warn!("⚠️  Warning: Failed to load tables from disk: {}. Starting with empty database.", e);
-            // Continue anyway - server can start fresh
         }
     }
-    
-    info!("✅ Persistent columnar storage initialized with {} tables", 
-          std::fs::read_dir(&config.data_dir).ok()
-              .map(|entries| entries.count())
-              .unwrap_or(0));
-    
-    Ok(store)
+
+    // CRITICAL: Load schema and seeds AFTER persistence is ready
+    // This ensures all data is properly persisted to disk
+    recovery.enter_phase(StartupPhase::LoadingSchema);
+    let schema_dir = std::path::Path::new("./schema");
+    if schema_dir.exists() {
+        info!("📋 Loading schema and seeds from ./schema...");
+        match narayana_server::schema_loader::load_schema_and_seeds(schema_dir, db_manager, storage).await {
+            Ok(_) => {
+                info!("✅ Schema and seeds loaded successfully");
+                // CRITICAL: Force sync all data to disk after loading schema/seeds
+                info!("💾 Syncing all data to disk...");
+                // The storage engine should already sync, but we ensure it here
+                // This is handled by the atomic writes with fsync in persistent_column_store
+            }
+            Err(e) => {
+                warn!("⚠️  Failed to load schema/seeds: {}. Continuing without them.", e);
+            }
+        }
+    } else {
+        info!("ℹ️  No schema directory found. Skipping schema/seed loading.");
+    }
+
+    let failed_tables = recovery.progress().tables_failed;
+    let note = (failed_tables > 0).then(|| format!("{} tables could not be loaded", failed_tables));
+    recovery.mark_complete(note);
+    info!("✅ Startup recovery complete in {}ms", recovery.progress().elapsed_ms);
 }
 
-/// Initialize auto-scaling
+/// Initialize auto-scaling; shard map changes go through this node's Raft
+/// group. Its term, vote and log live in a log under `{data_dir}/raft`, and
+/// it reaches the nodes of `cluster.peers` and those that join over HTTP.
 async fn initialize_auto_scaling(
+    config: &narayana_core::config::NarayanaConfig,
     db_manager: Arc<narayana_storage::database_manager::DatabaseManager>,
-) -> anyhow::Result<Arc<narayana_storage::auto_scaling::AutoScalingManager>> {
+) -> anyhow::Result<(
+    Arc<narayana_storage::auto_scaling::AutoScalingManager>,
+    Arc<narayana_storage::auto_scaling::ShardScaler>,
+)> {
     use narayana_storage::auto_scaling::*;
+    use narayana_storage::consensus::{RaftConfig, RaftConsensus};
+    use narayana_storage::wal::WriteAheadLog;
     use std::time::Duration;
 
+    let node_id = format!("node-{}", config.instance.node_id);
+    // A joining node starts without voters and waits to be added
+    let voters = if config.cluster.join.is_some() {
+        Vec::new()
+    } else {
+        std::iter::once(node_id.clone())
+            .chain(config.cluster.peers.iter().map(|peer| peer.node_id.clone()))
+            .collect()
+    };
+    let transport = Arc::new(narayana_server::cluster::HttpRaftTransport::new(&config.cluster)?);
+    let raft_log = Arc::new(WriteAheadLog::open(std::path::PathBuf::from(&config.storage.data_dir).join("raft")).await?);
+    let raft = Arc::new(RaftConsensus::open(node_id.clone(), voters, RaftConfig::default(), transport, raft_log).await?);
+    // A node that has yet to be added waits for the leader to reach it
+    // instead of standing for election; ticks take over once it is a voter
+    if raft.status().membership.contains(&node_id) {
+        raft.start_election().await?;
+    } else if config.cluster.join.is_some() {
+        let cluster = config.cluster.clone();
+        tokio::spawn(async move {
+            if let Err(e) = narayana_server::cluster::join_cluster(&cluster, node_id).await {
+                error!("Failed to join the cluster: {}", e);
+            }
+        });
+    }
+    let ticker = raft.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_millis(50));
+        loop {
+            interval.tick().await;
+            if let Err(e) = ticker.tick().await {
+                warn!("Raft tick failed: {}", e);
+            }
+        }
+    });
+
+    let shard_scaler = Arc::new(ShardScaler::new(
+        raft,
+        Arc::new(DatabaseShardProvisioner::new(db_manager.clone())),
+        ShardScalingThresholds::default(),
+    ));
     let thresholds = DatabaseThresholds::default();
     let auto_scaler = Arc::new(AutoScalingManager::new(
-        Arc::new(SimpleDatabaseManager::new()),
+        db_manager,
         thresholds,
         Duration::from_secs(10),
-    ));
+    ).with_shard_scaler(shard_scaler.clone()));
 
     // Start monitoring
     auto_scaler.start().await;
 
-    Ok(auto_scaler)
+    Ok((auto_scaler, shard_scaler))
 }
 
 /// Initialize load balancer
@@ -450,13 +883,13 @@ async fn initialize_load_balancer() -> anyhow::Result<Arc<narayana_storage::adva
 }
 
 /// Initialize persistence
-async fn initialize_persistence(config: &ServerConfig) -> anyhow::Result<Arc<narayana_storage::persistence::PersistenceManager>> {
+async fn initialize_persistence(config: &narayana_core::config::NarayanaConfig) -> anyhow::Result<Arc<narayana_storage::persistence::PersistenceManager>> {
     use narayana_storage::persistence::*;
     use std::path::PathBuf;
 
     let persistence_config = PersistenceConfig {
         strategy: PersistenceStrategy::FileSystem,
-        path: Some(PathBuf::from(&config.data_dir)),
+        path: Some(PathBuf::from(&config.storage.data_dir)),
         connection_string: None,
         credentials: None,
         compression: None,
@@ -475,6 +908,265 @@ async fn initialize_persistence(config: &ServerConfig) -> anyhow::Result<Arc<nar
     Ok(persistence)
 }
 
+/// Share the loaded configuration at runtime, watching its file for changes
+async fn initialize_config_manager(
+    config: &narayana_core::config::NarayanaConfig,
+    path: Option<String>,
+) -> anyhow::Result<Arc<narayana_server::config_manager::ConfigManager>> {
+    use narayana_server::config_manager::{ConfigManager, ConfigWatcher};
+
+    let manager = Arc::new(ConfigManager::new(config.clone()));
+    if let Some(path) = path {
+        ConfigWatcher::new(path, std::time::Duration::from_secs(5)).start(manager.clone()).await;
+    }
+    Ok(manager)
+}
+
+/// Install the process-wide egress (SSRF) policy and reinstall it on config reload
+async fn initialize_egress_policy(
+    config_manager: &narayana_server::config_manager::ConfigManager,
+) -> anyhow::Result<()> {
+    use narayana_core::egress::{self, EgressPolicy};
+
+    egress::install(EgressPolicy::new(config_manager.get().await.egress)?);
+    config_manager.subscribe(Box::new(|config: &narayana_core::config::NarayanaConfig| match EgressPolicy::new(config.egress.clone()) {
+        Ok(policy) => egress::install(policy),
+        Err(e) => warn!("Keeping previous egress policy, reloaded one is invalid: {}", e),
+    })).await;
+    Ok(())
+}
+
+/// Initialize RDE, whose subscriptions can invoke workers, write tables and
+/// produce to Kafka, MQTT or AMQP brokers
+fn initialize_rde(
+    native_events: Arc<narayana_storage::native_events::NativeEventsSystem>,
+    worker_invoker: Arc<dyn narayana_rde::transports::worker::WorkerInvoker>,
+    table_sink: Arc<narayana_rde::transports::table::TableSink>,
+) -> Arc<narayana_rde::RdeManager> {
+    // Actors and subscriptions are journaled to the event system unless
+    // NARAYANA_RDE_PERSIST_REGISTRY=false
+    let persist_registry = !matches!(std::env::var("NARAYANA_RDE_PERSIST_REGISTRY").as_deref(), Ok("false" | "0"));
+    let manager = if persist_registry {
+        narayana_rde::RdeManager::new(native_events)
+    } else {
+        info!("RDE registry persistence disabled");
+        narayana_rde::RdeManager::new_in_memory(native_events)
+    };
+    let manager = manager
+        .with_worker_invoker(worker_invoker)
+        .with_table_sink(table_sink);
+
+    // Kafka subscriptions mirror events to the brokers in NARAYANA_KAFKA_BROKERS
+    #[cfg(feature = "kafka")]
+    let manager = match std::env::var("NARAYANA_KAFKA_BROKERS") {
+        Ok(brokers) if !brokers.is_empty() => match narayana_rde::transports::kafka::RdKafkaProducer::new(&brokers, &[]) {
+            Ok(producer) => {
+                info!("RDE Kafka transport enabled");
+                manager.with_kafka_producer(Arc::new(producer))
+            }
+            Err(e) => {
+                warn!("RDE Kafka transport disabled: {}", e);
+                manager
+            }
+        },
+        _ => manager,
+    };
+
+    // MQTT subscriptions name their own broker, so the client needs no setup
+    #[cfg(feature = "mqtt")]
+    let manager = manager.with_mqtt_connector(Arc::new(narayana_rde::transports::mqtt::RumqttcConnector::default()));
+
+    // So do AMQP subscriptions
+    #[cfg(feature = "amqp")]
+    let manager = manager.with_amqp_connector(Arc::new(narayana_rde::transports::amqp::LapinConnector::default()));
+
+    // Failed deliveries are retried in the background until they dead-letter
+    let manager = Arc::new(manager);
+    manager.start_delivery_retries(std::time::Duration::from_secs(1));
+    manager
+}
+
+/// Build the invariant checker over storage, full-text indexes and event
+/// streams, and start its sampled background passes unless disabled
+fn initialize_replication(
+    config: &narayana_core::config::NarayanaConfig,
+    worker_manager: &Arc<narayana_storage::workers::WorkerManager>,
+    storage: &Arc<dyn narayana_storage::ColumnStore>,
+    db_manager: &Arc<narayana_storage::database_manager::DatabaseManager>,
+    brain: &Arc<narayana_storage::cognitive::CognitiveBrain>,
+) -> anyhow::Result<Arc<narayana_storage::replication::ReplicationManager>> {
+    use narayana_core::config::ReplicationMode;
+    use narayana_storage::replication::ReplicationManager;
+
+    let replication_config = &config.replication;
+    // Peers only take changes in multi-primary mode
+    let peers = match replication_config.mode {
+        ReplicationMode::MultiPrimary => replication_config.peers.clone(),
+        _ => Vec::new(),
+    };
+    let manager = ReplicationManager::new(config.instance.region.clone(), peers, replication_config.max_pending_changes)
+        .with_transport(Arc::new(narayana_server::replication::HttpReplicationTransport::new(replication_config.sync_timeout)?))
+        .with_merge_resolver(Arc::new(narayana_server::replication::WorkerMergeResolver {
+            workers: worker_manager.clone(),
+            storage: storage.clone(),
+            db_manager: db_manager.clone(),
+            brain: Some(brain.clone()),
+        }));
+    let manager = Arc::new(manager);
+
+    if !manager.status().peers.is_empty() {
+        let manager = manager.clone();
+        let interval = replication_config.ship_interval;
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                manager.ship().await;
+            }
+        });
+    }
+    Ok(manager)
+}
+
+fn initialize_invariants(
+    config: &narayana_core::config::NarayanaConfig,
+    storage: Arc<dyn narayana_storage::ColumnStore>,
+    db_manager: Arc<narayana_storage::database_manager::DatabaseManager>,
+    full_text: Arc<narayana_storage::full_text::FullTextIndexManager>,
+    native_events: Arc<narayana_storage::native_events::NativeEventsSystem>,
+) -> Arc<narayana_storage::bug_detection::InvariantChecker> {
+    use narayana_storage::bug_detection::*;
+
+    let invariants_config = &config.storage.invariants;
+    // Names are checked by config validation
+    let checks = invariants_config.checks.iter().filter_map(|name| Invariant::parse(name)).collect();
+    let checker = InvariantChecker::new(
+        InvariantCheckerConfig {
+            invariants: checks,
+            sample_rate: invariants_config.sample_rate,
+            interval: invariants_config.interval,
+            report_path: Some(std::path::PathBuf::from(&config.storage.data_dir).join("invariant_violations.json")),
+            ..Default::default()
+        },
+        storage,
+        db_manager,
+    )
+    .with_full_text(full_text)
+    .with_native_events(native_events);
+    let checker = Arc::new(checker);
+    if invariants_config.enabled {
+        checker.start();
+    }
+    checker
+}
+
+/// Register the built-in job types and their configured jobs, then start the
+/// scheduler (with `storage.jobs.enabled` off, jobs start out paused)
+fn initialize_jobs(
+    config: &narayana_core::config::NarayanaConfig,
+    brain: Arc<narayana_storage::cognitive::CognitiveBrain>,
+    query_learning: Arc<narayana_storage::query_learning::QueryLearningEngine>,
+    task_scheduler: Option<Arc<narayana_storage::priority_scheduler::PriorityScheduler>>,
+    ttl: Arc<narayana_storage::ttl::TtlCompactor>,
+) -> Arc<narayana_storage::jobs::JobScheduler> {
+    use narayana_storage::jobs::*;
+
+    let jobs_config = &config.storage.jobs;
+    let scheduler = Arc::new(JobScheduler::new(JobSchedulerConfig {
+        max_concurrent: jobs_config.max_concurrent,
+        ..Default::default()
+    }));
+    // Consolidation runs the daemon's passes on demand; nothing listens to its events here
+    let (event_sender, _) = tokio::sync::broadcast::channel(16);
+    let daemon = Arc::new(narayana_storage::background_daemon::BackgroundDaemon::new(brain.clone(), event_sender));
+    scheduler.register(JobKind::Compaction, Arc::new(CompactionJob { brain: brain.clone(), scheduler: task_scheduler, ttl: Some(ttl) }), 1);
+    scheduler.register(JobKind::Consolidation, Arc::new(ConsolidationJob { daemon }), 1);
+    scheduler.register(JobKind::Backup, Arc::new(BackupJob {
+        brain,
+        dir: std::path::PathBuf::from(&config.storage.data_dir).join("backups"),
+        retention: jobs_config.backup_retention,
+    }), 1);
+    scheduler.register(JobKind::Analyze, Arc::new(AnalyzeJob { query_learning }), 1);
+
+    let schedules = [
+        (JobKind::Compaction, &jobs_config.compaction_schedule),
+        (JobKind::Consolidation, &jobs_config.consolidation_schedule),
+        (JobKind::Backup, &jobs_config.backup_schedule),
+        (JobKind::Analyze, &jobs_config.analyze_schedule),
+    ];
+    for (kind, schedule) in schedules {
+        let spec = JobSpec {
+            name: kind.to_string(),
+            kind,
+            schedule: Some(schedule.trim().to_string()).filter(|s| !s.is_empty()),
+            retry: RetryPolicy { max_attempts: jobs_config.max_attempts, ..Default::default() },
+            timeout_secs: None,
+            params: serde_json::Value::Null,
+        };
+        match scheduler.add_job(spec) {
+            Ok(job) if !jobs_config.enabled => {
+                let _ = scheduler.pause(&job.spec.name);
+            }
+            Ok(_) => {}
+            Err(e) => warn!("⚠️  {} job not scheduled: {}", kind, e),
+        }
+    }
+    scheduler.start();
+    scheduler
+}
+
+/// Register the `ingest` job type and reload persisted connectors with their
+/// cursors and dedupe keys
+fn initialize_connectors(
+    config: &narayana_core::config::NarayanaConfig,
+    jobs: Arc<narayana_storage::jobs::JobScheduler>,
+) -> anyhow::Result<Arc<narayana_storage::connectors::ConnectorManager>> {
+    use narayana_storage::connectors::ConnectorManager;
+
+    let state_dir = std::path::PathBuf::from(&config.storage.data_dir).join("connectors");
+    let connectors = ConnectorManager::new(jobs, Some(state_dir), config.storage.jobs.ingest_concurrency)?;
+    connectors.load_persisted()?;
+    Ok(connectors)
+}
+
+/// Initialize anomaly detection over the CDC feed, publishing anomalies into RDE
+async fn initialize_anomaly_detection(
+    config: &narayana_core::config::NarayanaConfig,
+    change_feed: &narayana_storage::cdc::ChangeFeed,
+    rde: Arc<narayana_rde::RdeManager>,
+) -> anyhow::Result<Arc<narayana_storage::anomaly_detection::AnomalyDetectionManager>> {
+    use narayana_storage::anomaly_detection::AnomalyDetectionManager;
+
+    let state_dir = std::path::PathBuf::from(&config.storage.data_dir).join("anomaly");
+    let anomaly = Arc::new(AnomalyDetectionManager::new(Some(state_dir))?);
+    anomaly.load_persisted().await?;
+
+    anomaly.add_sink(Arc::new(narayana_rde::anomaly::RdeAnomalySink::register(rde).await?));
+
+    anomaly.clone().start(change_feed);
+    Ok(anomaly)
+}
+
+/// Initialize the feature store, rebuilding persisted views from their tables
+async fn initialize_feature_store(
+    config: &narayana_core::config::NarayanaConfig,
+    storage: &Arc<dyn narayana_storage::ColumnStore>,
+    change_feed: &narayana_storage::cdc::ChangeFeed,
+) -> anyhow::Result<Arc<narayana_storage::feature_store::FeatureStore>> {
+    use narayana_storage::feature_store::FeatureStore;
+
+    let state_dir = std::path::PathBuf::from(&config.storage.data_dir).join("features");
+    let features = Arc::new(FeatureStore::new(Some(state_dir))?);
+    features.load_persisted()?;
+    for status in features.list_views() {
+        if let Err(e) = narayana_server::http::materialize_feature_view(storage, &features, &status.view.name).await {
+            warn!("Could not materialize feature view {}: {}", status.view.name, e);
+        }
+    }
+    features.clone().start(change_feed);
+    Ok(features)
+}
+
 /// Initialize self-healing
 async fn initialize_self_healing() -> anyhow::Result<Arc<dyn std::any::Any + Send + Sync>> {
     // Self-healing is handled by health monitoring components
@@ -483,7 +1175,7 @@ async fn initialize_self_healing() -> anyhow::Result<Arc<dyn std::any::Any + Sen
 }
 
 /// Initialize distributed sync (vector clocks + CRDTs for multi-instance synchronization)
-async fn initialize_distributed_sync(config: &ServerConfig) -> anyhow::Result<Arc<narayana_storage::quantum_sync::QuantumSyncManager>> {
+async fn initialize_distributed_sync(config: &narayana_core::config::NarayanaConfig) -> anyhow::Result<Arc<narayana_storage::quantum_sync::QuantumSyncManager>> {
     use narayana_storage::quantum_sync::*;
 
     let node_id = format!("node-{}", uuid::Uuid::new_v4());
@@ -549,12 +1241,10 @@ async fn initialize_workers() -> anyhow::Result<Arc<narayana_storage::workers::W
 }
 
 /// Initialize threading system
-async fn initialize_threading(config: &ServerConfig) -> anyhow::Result<Arc<narayana_storage::threading::ThreadManager>> {
+async fn initialize_threading(config: &narayana_core::config::NarayanaConfig) -> anyhow::Result<Arc<narayana_storage::threading::ThreadManager>> {
     use narayana_storage::threading::*;
-    use narayana_core::config::ThreadingConfig;
     
-    // Create threading configuration from config or use defaults
-    let threading_config = ThreadingConfig::default();
+    let threading_config = config.threading.clone();
     
     // Create thread manager
     let thread_manager = Arc::new(ThreadManager::from_core_config(threading_config)?);
@@ -562,9 +1252,53 @@ async fn initialize_threading(config: &ServerConfig) -> anyhow::Result<Arc<naray
     Ok(thread_manager)
 }
 
+/// Build the workload forecaster over the query pool and compaction jobs, and
+/// start sampling load unless `performance.predictive_scaling.enabled` is off
+fn initialize_predictive_scaling(
+    config: &narayana_core::config::NarayanaConfig,
+    thread_manager: Arc<narayana_storage::threading::ThreadManager>,
+    jobs: Arc<narayana_storage::jobs::JobScheduler>,
+) -> anyhow::Result<Arc<narayana_storage::predictive_scaling::WorkloadScaler>> {
+    use narayana_storage::predictive_scaling::*;
+
+    let scaling_config = &config.performance.predictive_scaling;
+    let scaler = WorkloadScaler::new(WorkloadScalingConfig {
+        slot_minutes: scaling_config.slot_minutes,
+        lead_minutes: scaling_config.lead_minutes,
+        min_threads: scaling_config.min_threads,
+        max_threads: scaling_config.max_threads,
+        min_cache_bytes: scaling_config.min_cache_size,
+        max_cache_bytes: scaling_config.max_cache_size,
+        profile_path: Some(std::path::PathBuf::from(&config.storage.data_dir).join("load_profile.json")),
+        ..Default::default()
+    })?;
+    if !scaling_config.enabled {
+        // Forecasts stay visible through the admin API, but nothing is resized
+        return Ok(Arc::new(scaler));
+    }
+    let actuator = narayana_server::resource_scaling::ServerResourceActuator::new(thread_manager, jobs);
+    let scaler = Arc::new(scaler.with_actuator(Arc::new(actuator)));
+    narayana_server::resource_scaling::spawn_sampler(scaler.clone(), scaling_config.interval);
+    Ok(scaler)
+}
+
+/// Build the multi-GPU engine over every device found, or over the CPU
+/// backend alone when `performance.gpu.enabled` is off
+fn initialize_gpu(
+    config: &narayana_core::config::NarayanaConfig,
+) -> anyhow::Result<Arc<narayana_storage::gpu_devices::MultiGpuEngine>> {
+    use narayana_storage::gpu_devices::*;
+
+    let gpu_config = MultiGpuConfig::from_config(&config.performance.gpu)?;
+    if !config.performance.gpu.enabled {
+        return Ok(Arc::new(MultiGpuEngine::cpu_only(gpu_config)));
+    }
+    Ok(Arc::new(MultiGpuEngine::new(gpu_config)?))
+}
+
 /// Start HTTP server
 async fn start_http_server(
-    port: u16,
+    config: &narayana_core::config::NarayanaConfig,
     storage: Arc<dyn narayana_storage::ColumnStore>,
     db_manager: Arc<narayana_storage::database_manager::DatabaseManager>,
     search_engine: Arc<narayana_storage::human_search::HumanSearchEngine>,
@@ -575,14 +1309,40 @@ async fn start_http_server(
     ws_state: Option<Arc<narayana_server::websocket::WebSocketState>>,
     cpl_manager: Option<Arc<narayana_storage::cpl_manager::CPLManager>>,
     vector_store: Arc<narayana_storage::vector_search::VectorStore>,
+    embeddings: Option<Arc<narayana_storage::embeddings::EmbeddingsManager>>,
+    change_feed: Arc<narayana_storage::cdc::ChangeFeed>,
+    anomaly: Arc<narayana_storage::anomaly_detection::AnomalyDetectionManager>,
+    features: Arc<narayana_storage::feature_store::FeatureStore>,
+    blobs: Arc<narayana_storage::blob_store::BlobStore>,
+    http_headers: Arc<narayana_server::http_headers::HttpHeadersPolicy>,
+    ip_guard: Arc<narayana_server::ip_guard::IpGuard>,
+    admission: Arc<narayana_server::admission::AdmissionController>,
+    idempotency: Arc<narayana_server::idempotency::IdempotencyStore>,
+    health: Arc<narayana_server::health::HealthRegistry>,
+    recovery: Arc<narayana_server::health::RecoveryGate>,
+    static_files: Arc<narayana_server::static_files::StaticFiles>,
+    skills: Arc<narayana_server::skills::SkillManager>,
+    jobs: Arc<narayana_storage::jobs::JobScheduler>,
+    connectors: Arc<narayana_storage::connectors::ConnectorManager>,
+    quotas: Arc<narayana_storage::quotas::QuotaManager>,
+    full_text: Arc<narayana_storage::full_text::FullTextIndexManager>,
+    spatial: Arc<narayana_storage::geospatial::SpatialIndexManager>,
+    invariants: Arc<narayana_storage::bug_detection::InvariantChecker>,
+    scaling: Arc<narayana_storage::predictive_scaling::WorkloadScaler>,
+    shards: Arc<narayana_storage::auto_scaling::ShardScaler>,
+    rde: Arc<narayana_rde::RdeManager>,
+    replication: Arc<narayana_storage::replication::ReplicationManager>,
+    nlq: Arc<narayana_server::nlq::NaturalLanguageQuery>,
+    gpu: Arc<narayana_storage::gpu_devices::MultiGpuEngine>,
+    predictor: Arc<narayana_query::ml_integration::PredictEngine>,
 ) -> anyhow::Result<tokio::task::JoinHandle<()>> {
     use narayana_server::http::*;
     use std::net::SocketAddr;
     
     // Initialize token manager for API authentication
     // SECURITY: Use environment variable for secret key, or generate a random one
-    let jwt_secret = std::env::var("NARAYANA_JWT_SECRET")
-        .unwrap_or_else(|_| {
+    let jwt_secret = config.security.jwt_secret.clone()
+        .unwrap_or_else(|| {
             // Generate a random secret if not set (not secure for production!)
             use std::time::{SystemTime, UNIX_EPOCH};
             let timestamp = SystemTime::now()
@@ -593,11 +1353,21 @@ async fn start_http_server(
         });
     let api_token_manager = Arc::new(narayana_server::security::TokenManager::new(jwt_secret));
     
-    // SECURITY: Initialize rate limiter for auth endpoints (5 attempts per 15 minutes)
-    let rate_limiter = Arc::new(narayana_server::security::RateLimiter::new(5, 900)); // 5 requests per 15 minutes
+    // SECURITY: Initialize rate limiter for auth endpoints (max_login_attempts per lockout_duration)
+    let rate_limiter = Arc::new(narayana_server::security::RateLimiter::new(
+        config.security.max_login_attempts,
+        config.security.lockout_duration.as_secs(),
+    ));
     
-    // SECURITY: Initialize rate limiter for API endpoints (1000 requests per minute)
-    let api_rate_limiter = Arc::new(narayana_server::security::RateLimiter::new(1000, 60));
+    // SECURITY: Initialize rate limiter for API endpoints (api_requests_per_minute)
+    let api_rate_limiter = Arc::new(narayana_server::security::RateLimiter::new(
+        config.security.api_requests_per_minute,
+        60,
+    ));
+
+    // Explicit client transactions; idle ones are rolled back
+    let transactions = Arc::new(narayana_storage::transactions::TransactionRegistry::new(config.query.transactions.clone()));
+    transactions.clone().start_reaper(config.query.transactions.reap_interval);
 
     // Create API state
     let state = ApiState {
@@ -614,13 +1384,65 @@ async fn start_http_server(
         api_rate_limiter,
         cpl_manager,
         vector_store,
+        embeddings,
+        full_text,
+        spatial,
+        change_feed,
+        anomaly,
+        features,
+        blobs,
+        http_headers,
+        ip_guard,
+        admission,
+        idempotency,
+        queries: Arc::new(narayana_query::cancellation::QueryRegistry::new()),
+        health,
+        recovery,
+        static_files,
+        skills,
+        jobs,
+        connectors,
+        quotas,
+        invariants,
+        scaling,
+        shards,
+        rde,
+        replication,
+        nlq,
+        gpu,
+        predictor,
+        autocomplete: Arc::new(narayana_query::autocomplete::AutocompleteManager::new(Default::default())),
+        transactions,
     };
+    state.connectors.set_sink(Arc::new(TableIngestSink::new(state.clone())));
+    // Event table subscriptions write through the same insert path
+    if let Some(table_sink) = state.rde.table_sink() {
+        table_sink.set_inserter(Arc::new(TableIngestSink::new(state.clone())));
+    }
+    
+    // Brain service over gRPC, authenticated with the HTTP API's tokens
+    if config.network.grpc.enabled {
+        let grpc_addr: SocketAddr = tokio::net::lookup_host((config.network.bind_address.as_str(), config.network.grpc.port))
+            .await?
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("network.bind_address {} did not resolve", config.network.bind_address))?;
+        let brain = state.brain.clone();
+        let token_manager = state.token_manager.clone();
+        tokio::spawn(async move {
+            if let Err(e) = narayana_server::grpc::serve(grpc_addr, brain, token_manager).await {
+                error!("gRPC server on {} failed: {}", grpc_addr, e);
+            }
+        });
+    }
     
     // Create router
     let app = create_router(state);
     
-    // Bind to address
-    let addr = SocketAddr::from(([0, 0, 0, 0], port));
+    // Bind to address (network.bind_address may be a host name)
+    let addr: SocketAddr = tokio::net::lookup_host((config.network.bind_address.as_str(), config.network.bind_port))
+        .await?
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("network.bind_address {} did not resolve", config.network.bind_address))?;
     
     info!("🌐 Starting HTTP server on {}", addr);
     
@@ -630,7 +1452,8 @@ async fn start_http_server(
         
         info!("✅ HTTP server listening on http://{}", addr);
         
-        axum::serve(listener, app)
+        // Peer addresses feed the per-IP guard
+        axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
             .await
             .expect("HTTP server failed");
     });
@@ -653,16 +1476,16 @@ async fn start_background_services(
 }
 
 /// Print ready message
-fn print_ready_message(config: &ServerConfig) {
+fn print_ready_message(config: &narayana_core::config::NarayanaConfig) {
     println!();
     println!("╔═══════════════════════════════════════════════════════════════╗");
     println!("║                                                               ║");
     println!("║     ✅  NARAYANADB IS READY!  ✅                             ║");
     println!("║                                                               ║");
-    println!("║     🌐 HTTP API:     http://localhost:{}                    ║", config.http_port);
+    println!("║     🌐 HTTP API:     http://localhost:{}                    ║", config.network.bind_port);
     println!("║                                                               ║");
     println!("║                                                               ║");
-    println!("║     💾 Data Directory: {}                                    ║", config.data_dir);
+    println!("║     💾 Data Directory: {}                                    ║", config.storage.data_dir);
     println!("║                                                               ║");
     println!("║     🚀 Ready to handle millions of transactions!             ║");
     println!("║                                                               ║");
@@ -0,0 +1,728 @@
+// Natural-language queries
+// Questions about the tables are translated by the LLM into a JSON query spec,
+// grounded in the catalog of the `default` database (the tables the caller may
+// query, with their columns and types). The spec is checked against the
+// catalog and run by the server itself - scan, filter, aggregate, sort,
+// project, limit - on a read-only view of storage with the caller's row
+// policies, so whatever the model replies can only read what the caller could.
+// The response carries the spec, the query plan and equivalent SQL next to the
+// rows, and optionally a short answer phrased by a second LLM call.
+
+use async_trait::async_trait;
+use narayana_core::column::Column;
+use narayana_core::schema::Schema;
+use narayana_core::types::TableId;
+use narayana_query::cancellation::QueryContext;
+use narayana_query::executor::filter_row_ranges;
+use narayana_query::operators::{AggregateFunction, AggregateOperator, FilterOperator};
+use narayana_query::plan::{AggregateExpr, Filter, OrderBy, PlanNode};
+use narayana_storage::block::{BlockMetadata, BlockPredicate};
+use narayana_storage::database_manager::{DatabaseManager, TableInfo};
+use narayana_storage::row_security::Principal;
+use narayana_storage::ColumnStore;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::cmp::Ordering;
+use std::ops::Range;
+use std::sync::Arc;
+
+/// Database questions are asked about
+const NLQ_DATABASE: &str = "default";
+/// Longest question accepted
+pub const MAX_QUESTION_LENGTH: usize = 2000;
+/// Rows returned when neither the caller nor the model set a limit
+pub const DEFAULT_LIMIT: usize = 100;
+/// Most rows returned
+pub const MAX_LIMIT: usize = 1000;
+/// Most rows scanned per question; larger tables are answered from their first rows
+pub const MAX_SCAN_ROWS: usize = 100_000;
+/// Result rows shown to the model when it phrases the answer
+const SUMMARY_ROWS: usize = 50;
+
+/// The language model behind natural-language queries
+#[async_trait]
+pub trait QueryModel: Send + Sync {
+    /// Whether a provider is configured
+    fn available(&self) -> bool;
+    /// One completion for a system prompt and a user message
+    async fn complete(&self, system: &str, user: &str) -> Result<String, String>;
+}
+
+#[async_trait]
+impl QueryModel for narayana_llm::LLMManager {
+    fn available(&self) -> bool {
+        self.has_provider()
+    }
+
+    async fn complete(&self, system: &str, user: &str) -> Result<String, String> {
+        use narayana_llm::{Message, MessageRole};
+        let messages = vec![
+            Message { role: MessageRole::System, content: system.to_string() },
+            Message { role: MessageRole::User, content: user.to_string() },
+        ];
+        // No brain context: the catalog is the grounding
+        narayana_llm::LLMManager::complete(self, messages, None).await.map_err(|e| e.to_string())
+    }
+}
+
+/// Why a question wasn't answered
+#[derive(Debug)]
+pub enum NlqError {
+    InvalidQuestion(String),
+    NoProvider,
+    Model(String),
+    /// The model's reply wasn't a usable query, or it said the question
+    /// can't be answered from the catalog; `reply` is what it said
+    Unanswerable { message: String, reply: String },
+    Forbidden(String),
+    Storage(narayana_core::Error),
+}
+
+impl std::fmt::Display for NlqError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NlqError::InvalidQuestion(e) => write!(f, "Invalid question: {}", e),
+            NlqError::NoProvider => write!(f, "No LLM provider is configured"),
+            NlqError::Model(e) => write!(f, "LLM request failed: {}", e),
+            NlqError::Unanswerable { message, .. } => write!(f, "{}", message),
+            NlqError::Forbidden(e) => write!(f, "{}", e),
+            NlqError::Storage(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for NlqError {}
+
+impl From<narayana_core::Error> for NlqError {
+    fn from(error: narayana_core::Error) -> Self {
+        NlqError::Storage(error)
+    }
+}
+
+/// A table as the model sees it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CatalogTable {
+    pub name: String,
+    pub columns: Vec<CatalogColumn>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CatalogColumn {
+    pub name: String,
+    pub data_type: String,
+}
+
+/// The query the model answers with
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct QuerySpec {
+    pub table: String,
+    /// Output columns, in order (all by default)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub columns: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<Filter>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub group_by: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aggregates: Vec<AggregateExpr>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub order_by: Vec<OrderBy>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+/// A question's answer and how it was computed
+#[derive(Debug, Clone, Serialize)]
+pub struct NlqAnswer {
+    pub question: String,
+    /// The spec the model produced
+    pub query: QuerySpec,
+    /// The same query as SQL
+    pub sql: String,
+    /// The same query as a query plan
+    pub plan: PlanNode,
+    pub columns: Vec<String>,
+    pub rows: Vec<Value>,
+    pub row_count: usize,
+    /// Only the first `MAX_SCAN_ROWS` rows of the table were read
+    pub truncated: bool,
+    /// The model's answer from the rows, when asked for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+}
+
+/// Translates and runs natural-language questions
+pub struct NaturalLanguageQuery {
+    model: Arc<dyn QueryModel>,
+    storage: Arc<dyn ColumnStore>,
+    db_manager: Arc<DatabaseManager>,
+    hidden_tables: Vec<String>,
+}
+
+impl NaturalLanguageQuery {
+    pub fn new(model: Arc<dyn QueryModel>, storage: Arc<dyn ColumnStore>, db_manager: Arc<DatabaseManager>) -> Self {
+        Self { model, storage, db_manager, hidden_tables: Vec::new() }
+    }
+
+    /// Leave tables out of the catalog and refuse queries on them
+    pub fn with_hidden_tables(mut self, tables: &[&str]) -> Self {
+        self.hidden_tables = tables.iter().map(|t| t.to_string()).collect();
+        self
+    }
+
+    fn tables(&self) -> Vec<TableInfo> {
+        let Some(db_id) = self.db_manager.get_database_by_name(NLQ_DATABASE) else {
+            return Vec::new();
+        };
+        let mut tables: Vec<TableInfo> = self.db_manager.list_tables(db_id)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|t| !self.hidden_tables.contains(&t.name) && !t.schema.fields.is_empty())
+            .collect();
+        tables.sort_by(|a, b| a.name.cmp(&b.name));
+        tables
+    }
+
+    /// Tables the model is told about
+    pub fn catalog(&self) -> Vec<CatalogTable> {
+        self.tables().iter().map(|table| CatalogTable {
+            name: table.name.clone(),
+            columns: table.schema.fields.iter().map(|field| CatalogColumn {
+                name: field.name.clone(),
+                data_type: format!("{:?}", field.data_type),
+            }).collect(),
+        }).collect()
+    }
+
+    /// Answer a question for `principal`, returning at most `limit` rows
+    pub async fn ask(
+        &self,
+        question: &str,
+        limit: Option<usize>,
+        summarize: bool,
+        principal: Principal,
+        context: &QueryContext,
+    ) -> Result<NlqAnswer, NlqError> {
+        let question = question.trim();
+        if question.is_empty() {
+            return Err(NlqError::InvalidQuestion("question cannot be empty".to_string()));
+        }
+        if question.len() > MAX_QUESTION_LENGTH {
+            return Err(NlqError::InvalidQuestion(format!("question must be at most {} bytes", MAX_QUESTION_LENGTH)));
+        }
+        if !self.model.available() {
+            return Err(NlqError::NoProvider);
+        }
+        let catalog = self.catalog();
+        if catalog.is_empty() {
+            return Err(NlqError::InvalidQuestion("there are no tables to query".to_string()));
+        }
+
+        context.progress.set_stage("translate");
+        let reply = self.model.complete(&system_prompt(&catalog), question).await.map_err(NlqError::Model)?;
+        let unanswerable = |message: String| NlqError::Unanswerable { message, reply: reply.clone() };
+        let spec = parse_reply(&reply).map_err(unanswerable)?;
+        let table = self.tables()
+            .into_iter()
+            .find(|t| t.name == spec.table)
+            .ok_or_else(|| unanswerable(format!("Unknown table '{}'", spec.table)))?;
+        let outputs = spec.validate(&table.schema).map_err(unanswerable)?;
+        let limit = limit.or(spec.limit).unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+        // Rows bound to an output profile (e.g. masked) must not be aggregated around it
+        if self.db_manager.get_table_output_config_for_consumer(table.table_id, &principal.subject, &principal.roles)?.is_some() {
+            return Err(NlqError::Forbidden(format!(
+                "Table '{}' has an output profile for this caller; use the query endpoint",
+                table.name
+            )));
+        }
+
+        let store = self.db_manager.row_security().secure(Arc::new(ReadOnlyColumnStore(self.storage.clone())), principal);
+        let (rows, truncated) = execute(store.as_ref(), &table, &spec, &outputs, limit, context).await?;
+        let columns = spec.output_columns(&outputs);
+        let sql = spec.to_sql(&outputs, limit);
+        let plan = spec.to_plan(&table, limit);
+
+        let summary = if summarize {
+            context.progress.set_stage("summarize");
+            let shown: Vec<&Value> = rows.iter().take(SUMMARY_ROWS).collect();
+            let prompt = format!(
+                "Question: {}\nSQL: {}\nResult ({} rows{}): {}",
+                question,
+                sql,
+                rows.len(),
+                if rows.len() > SUMMARY_ROWS { format!(", first {}", SUMMARY_ROWS) } else { String::new() },
+                serde_json::to_string(&shown).unwrap_or_default(),
+            );
+            match self.model.complete(SUMMARY_PROMPT, &prompt).await {
+                Ok(summary) => Some(summary.trim().to_string()),
+                Err(e) => {
+                    tracing::warn!("Failed to summarize natural-language query result: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        Ok(NlqAnswer {
+            question: question.to_string(),
+            query: spec,
+            sql,
+            plan,
+            row_count: rows.len(),
+            columns,
+            rows,
+            truncated,
+            summary,
+        })
+    }
+}
+
+const SUMMARY_PROMPT: &str = "Answer the question in one or two sentences using only the query result given. \
+If the result is empty, say that nothing matched. Do not describe the SQL.";
+
+fn system_prompt(catalog: &[CatalogTable]) -> String {
+    let tables: Vec<String> = catalog.iter().map(|table| {
+        let columns: Vec<String> = table.columns.iter().map(|c| format!("{} {}", c.name, c.data_type)).collect();
+        format!("- {} ({})", table.name, columns.join(", "))
+    }).collect();
+    format!(
+        "You translate questions about a database into a JSON query. The tables are:\n{}\n\n\
+Reply with one JSON object and nothing else. Keys: \"table\" (a table above), \"columns\" (columns to return, in order; omit for all), \
+\"filter\" (optional predicate), \"group_by\" (columns), \"aggregates\", \"order_by\" ([{{\"column\": name, \"ascending\": bool}}]) and \"limit\" (rows).\n\
+Predicates: {{\"Eq\": {{\"column\": c, \"value\": v}}}}, and likewise Ne, Gt, Gte, Lt, Lte; {{\"In\": {{\"column\": c, \"values\": [v]}}}}; \
+{{\"Between\": {{\"column\": c, \"low\": v, \"high\": v}}}}; {{\"And\": {{\"left\": p, \"right\": p}}}}, {{\"Or\": {{\"left\": p, \"right\": p}}}}; {{\"Not\": {{\"expr\": p}}}}.\n\
+Aggregates: {{\"Count\": {{\"column\": null}}}}, {{\"Sum\": {{\"column\": c}}}}, and likewise Avg, Min, Max. With aggregates or group_by, \
+the result has the group_by columns and then one column per aggregate named count, count_<c>, sum_<c>, avg_<c>, min_<c> or max_<c>; \
+\"columns\" and \"order_by\" refer to those names.\n\
+Use only the tables and columns above. If the question cannot be answered from them, reply {{\"error\": \"<why>\"}}.",
+        tables.join("\n")
+    )
+}
+
+/// Parse the model's reply: one JSON object, possibly in a code fence
+pub fn parse_reply(reply: &str) -> Result<QuerySpec, String> {
+    let start = reply.find('{');
+    let end = reply.rfind('}');
+    let json = match (start, end) {
+        (Some(start), Some(end)) if start < end => &reply[start..=end],
+        _ => return Err("The model did not reply with a query".to_string()),
+    };
+    let value: Value = serde_json::from_str(json).map_err(|e| format!("The model's query is not valid JSON: {}", e))?;
+    if let Some(error) = value.get("error") {
+        let reason = error.as_str().map(str::to_string).unwrap_or_else(|| error.to_string());
+        return Err(format!("The question can't be answered from the catalog: {}", reason));
+    }
+    serde_json::from_value(value).map_err(|e| format!("The model's query is invalid: {}", e))
+}
+
+/// A result column: its name and SQL expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputColumn {
+    pub name: String,
+    pub sql: String,
+}
+
+impl QuerySpec {
+    fn aggregated(&self) -> bool {
+        !self.aggregates.is_empty() || !self.group_by.is_empty()
+    }
+
+    /// Check every column exists; returns the columns the query produces
+    /// before projection
+    pub fn validate(&self, schema: &Schema) -> Result<Vec<OutputColumn>, String> {
+        let known = |column: &str| -> Result<(), String> {
+            match schema.field_index(column) {
+                Some(_) => Ok(()),
+                None => Err(format!("Unknown column '{}' in table '{}'", column, self.table)),
+            }
+        };
+        if let Some(filter) = &self.filter {
+            let mut columns = Vec::new();
+            filter_columns(filter, &mut columns)?;
+            columns.into_iter().try_for_each(known)?;
+        }
+        self.group_by.iter().try_for_each(|c| known(c))?;
+        for aggregate in &self.aggregates {
+            if let Some(column) = aggregate_column(aggregate) {
+                known(column)?;
+            }
+        }
+
+        let outputs: Vec<OutputColumn> = if self.aggregated() {
+            self.group_by.iter()
+                .map(|c| OutputColumn { name: c.clone(), sql: quote_identifier(c) })
+                .chain(self.aggregates.iter().map(|a| OutputColumn { name: aggregate_name(a), sql: aggregate_sql(a) }))
+                .collect()
+        } else {
+            schema.fields.iter().map(|f| OutputColumn { name: f.name.clone(), sql: quote_identifier(&f.name) }).collect()
+        };
+        let produced = |column: &str| -> Result<(), String> {
+            match outputs.iter().any(|o| o.name == column) {
+                true => Ok(()),
+                false => Err(format!("Unknown result column '{}'", column)),
+            }
+        };
+        self.columns.iter().try_for_each(|c| produced(c))?;
+        self.order_by.iter().try_for_each(|o| produced(&o.column))?;
+        Ok(outputs)
+    }
+
+    /// Names of the returned columns
+    pub fn output_columns(&self, outputs: &[OutputColumn]) -> Vec<String> {
+        self.selected(outputs).into_iter().map(|o| o.name.clone()).collect()
+    }
+
+    fn selected<'a>(&self, outputs: &'a [OutputColumn]) -> Vec<&'a OutputColumn> {
+        if self.columns.is_empty() {
+            return outputs.iter().collect();
+        }
+        self.columns.iter().filter_map(|c| outputs.iter().find(|o| &o.name == c)).collect()
+    }
+
+    /// The query as SQL, for display
+    pub fn to_sql(&self, outputs: &[OutputColumn], limit: usize) -> String {
+        let select: Vec<String> = self.selected(outputs).into_iter().map(|o| {
+            if o.sql == quote_identifier(&o.name) {
+                o.sql.clone()
+            } else {
+                format!("{} AS {}", o.sql, quote_identifier(&o.name))
+            }
+        }).collect();
+        let mut sql = format!("SELECT {} FROM {}", select.join(", "), quote_identifier(&self.table));
+        if let Some(filter) = &self.filter {
+            sql.push_str(&format!(" WHERE {}", filter_sql(filter)));
+        }
+        if !self.group_by.is_empty() {
+            let group_by: Vec<String> = self.group_by.iter().map(|c| quote_identifier(c)).collect();
+            sql.push_str(&format!(" GROUP BY {}", group_by.join(", ")));
+        }
+        if !self.order_by.is_empty() {
+            let order_by: Vec<String> = self.order_by.iter()
+                .map(|o| format!("{} {}", quote_identifier(&o.column), if o.ascending { "ASC" } else { "DESC" }))
+                .collect();
+            sql.push_str(&format!(" ORDER BY {}", order_by.join(", ")));
+        }
+        sql.push_str(&format!(" LIMIT {}", limit));
+        sql
+    }
+
+    /// The query as a plan: scan, filter, aggregate, sort, project, limit
+    pub fn to_plan(&self, table: &TableInfo, limit: usize) -> PlanNode {
+        let mut node = PlanNode::Scan {
+            table_id: table.table_id.0,
+            column_ids: (0..table.schema.fields.len() as u32).collect(),
+            filter: None,
+        };
+        if let Some(filter) = &self.filter {
+            node = PlanNode::Filter { predicate: filter.clone(), input: Box::new(node) };
+        }
+        if self.aggregated() {
+            node = PlanNode::Aggregate {
+                group_by: self.group_by.clone(),
+                aggregates: self.aggregates.clone(),
+                input: Box::new(node),
+            };
+        }
+        if !self.order_by.is_empty() {
+            node = PlanNode::Sort { order_by: self.order_by.clone(), input: Box::new(node) };
+        }
+        if !self.columns.is_empty() {
+            node = PlanNode::Project { columns: self.columns.clone(), input: Box::new(node) };
+        }
+        PlanNode::Limit { limit, offset: 0, input: Box::new(node) }
+    }
+}
+
+/// Scan, filter and aggregate in storage, then sort, project and limit the rows
+async fn execute(
+    store: &dyn ColumnStore,
+    table: &TableInfo,
+    spec: &QuerySpec,
+    outputs: &[OutputColumn],
+    limit: usize,
+    context: &QueryContext,
+) -> narayana_core::Result<(Vec<Value>, bool)> {
+    context.progress.set_total_nodes(1);
+    context.progress.set_stage(format!("scan table {}", table.name));
+    let column_ids: Vec<u32> = (0..table.schema.fields.len() as u32).collect();
+    // Only the blocks whose zone maps admit the filter are read
+    let ranges = match &spec.filter {
+        Some(filter) => filter_row_ranges(store, table.table_id, filter).await?,
+        None => None,
+    };
+    let (mut columns, truncated) = match ranges {
+        None => {
+            let columns = context.run(store.read_columns(table.table_id, column_ids, 0, MAX_SCAN_ROWS)).await?;
+            let truncated = columns.first().map_or(0, Column::len) >= MAX_SCAN_ROWS;
+            (columns, truncated)
+        }
+        Some(ranges) => {
+            let truncated = ranges.iter().any(|range| range.end > MAX_SCAN_ROWS);
+            let mut ranges: Vec<Range<usize>> = ranges
+                .into_iter()
+                .filter(|range| range.start < MAX_SCAN_ROWS)
+                .map(|range| range.start..range.end.min(MAX_SCAN_ROWS))
+                .collect();
+            // An empty read still yields the columns' types
+            if ranges.is_empty() {
+                ranges.push(0..0);
+            }
+            let mut columns: Option<Vec<Column>> = None;
+            for range in ranges {
+                let part = context.run(store.read_columns(table.table_id, column_ids.clone(), range.start, range.len())).await?;
+                columns = Some(match columns {
+                    None => part,
+                    Some(done) => done.iter().zip(&part).map(|(a, b)| a.append(b)).collect::<narayana_core::Result<Vec<Column>>>()?,
+                });
+            }
+            (columns.unwrap_or_default(), truncated)
+        }
+    };
+    if columns.len() < table.schema.fields.len() {
+        // Nothing written yet
+        return Ok((Vec::new(), false));
+    }
+    context.progress.add_rows(columns.first().map_or(0, Column::len));
+    context.check()?;
+
+    if let Some(filter) = &spec.filter {
+        context.progress.set_stage("filter");
+        columns = FilterOperator::new(filter.clone(), table.schema.clone()).apply(&columns)?;
+    }
+    if spec.aggregated() {
+        context.progress.set_stage("aggregate");
+        let functions = spec.aggregates.iter().map(aggregate_function).collect();
+        columns = AggregateOperator::new(spec.group_by.clone(), functions, table.schema.clone())?.apply(&columns)?;
+    }
+    context.check()?;
+
+    let names: Vec<String> = outputs.iter().map(|o| o.name.clone()).collect();
+    let row_count = columns.first().map_or(0, Column::len);
+    let Value::Array(mut rows) = crate::http::columns_to_rows(&names, &columns, row_count) else {
+        return Ok((Vec::new(), false));
+    };
+    sort_rows(&mut rows, &spec.order_by);
+    rows.truncate(limit);
+    if !spec.columns.is_empty() {
+        for row in &mut rows {
+            if let Value::Object(object) = row {
+                let mut projected = Map::new();
+                for column in &spec.columns {
+                    projected.insert(column.clone(), object.remove(column).unwrap_or(Value::Null));
+                }
+                *object = projected;
+            }
+        }
+    }
+    context.progress.node_completed();
+    Ok((rows, truncated))
+}
+
+/// Sort rows by the order-by columns: numbers, then strings, then booleans;
+/// nulls last either way
+pub fn sort_rows(rows: &mut [Value], order_by: &[OrderBy]) {
+    if order_by.is_empty() {
+        return;
+    }
+    rows.sort_by(|a, b| {
+        for order in order_by {
+            let (x, y) = (&a[order.column.as_str()], &b[order.column.as_str()]);
+            let ordering = match (x.is_null(), y.is_null()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) if order.ascending => compare_values(x, y),
+                (false, false) => compare_values(y, x),
+            };
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+        Ordering::Equal
+    });
+}
+
+fn compare_values(a: &Value, b: &Value) -> Ordering {
+    fn rank(value: &Value) -> u8 {
+        match value {
+            Value::Number(_) => 0,
+            Value::String(_) => 1,
+            Value::Bool(_) => 2,
+            _ => 3,
+        }
+    }
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => x.as_f64().partial_cmp(&y.as_f64()).unwrap_or(Ordering::Equal),
+        (Value::String(x), Value::String(y)) => x.cmp(y),
+        (Value::Bool(x), Value::Bool(y)) => x.cmp(y),
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+fn filter_columns<'a>(filter: &'a Filter, columns: &mut Vec<&'a str>) -> Result<(), String> {
+    match filter {
+        Filter::Eq { column, .. }
+        | Filter::Ne { column, .. }
+        | Filter::Gt { column, .. }
+        | Filter::Lt { column, .. }
+        | Filter::Gte { column, .. }
+        | Filter::Lte { column, .. }
+        | Filter::In { column, .. }
+        | Filter::Between { column, .. } => columns.push(column),
+        Filter::And { left, right } | Filter::Or { left, right } => {
+            filter_columns(left, columns)?;
+            filter_columns(right, columns)?;
+        }
+        Filter::Not { expr } => filter_columns(expr, columns)?,
+        Filter::Match { .. } | Filter::Spatial { .. } => {
+            return Err("Full-text and spatial predicates are not supported here".to_string());
+        }
+    }
+    Ok(())
+}
+
+fn filter_sql(filter: &Filter) -> String {
+    let compare = |column: &str, op: &str, value: &Value| format!("{} {} {}", quote_identifier(column), op, literal_sql(value));
+    match filter {
+        Filter::Eq { column, value } if value.is_null() => format!("{} IS NULL", quote_identifier(column)),
+        Filter::Ne { column, value } if value.is_null() => format!("{} IS NOT NULL", quote_identifier(column)),
+        Filter::Eq { column, value } => compare(column, "=", value),
+        Filter::Ne { column, value } => compare(column, "<>", value),
+        Filter::Gt { column, value } => compare(column, ">", value),
+        Filter::Lt { column, value } => compare(column, "<", value),
+        Filter::Gte { column, value } => compare(column, ">=", value),
+        Filter::Lte { column, value } => compare(column, "<=", value),
+        Filter::In { column, values } => {
+            let values: Vec<String> = values.iter().map(literal_sql).collect();
+            format!("{} IN ({})", quote_identifier(column), values.join(", "))
+        }
+        Filter::Between { column, low, high } => {
+            format!("{} BETWEEN {} AND {}", quote_identifier(column), literal_sql(low), literal_sql(high))
+        }
+        Filter::And { left, right } => format!("({} AND {})", filter_sql(left), filter_sql(right)),
+        Filter::Or { left, right } => format!("({} OR {})", filter_sql(left), filter_sql(right)),
+        Filter::Not { expr } => format!("NOT {}", filter_sql(expr)),
+        Filter::Match { column, query, .. } => format!("MATCH({}, {})", quote_identifier(column), literal_sql(&Value::String(query.clone()))),
+        Filter::Spatial { column, .. } => format!("SPATIAL({})", quote_identifier(column)),
+    }
+}
+
+fn literal_sql(value: &Value) -> String {
+    match value {
+        Value::Null => "NULL".to_string(),
+        Value::Bool(b) => if *b { "TRUE" } else { "FALSE" }.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => format!("'{}'", s.replace('\'', "''")),
+        other => format!("'{}'", other.to_string().replace('\'', "''")),
+    }
+}
+
+/// Identifiers that aren't plain words are double-quoted
+fn quote_identifier(name: &str) -> String {
+    let plain = name.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if plain {
+        name.to_string()
+    } else {
+        format!("\"{}\"", name.replace('"', "\"\""))
+    }
+}
+
+fn aggregate_column(aggregate: &AggregateExpr) -> Option<&str> {
+    match aggregate {
+        AggregateExpr::Count { column } => column.as_deref(),
+        AggregateExpr::Sum { column }
+        | AggregateExpr::Avg { column }
+        | AggregateExpr::Min { column }
+        | AggregateExpr::Max { column } => Some(column),
+    }
+}
+
+/// Result column name of an aggregate: count, count_<c>, sum_<c>, ...
+pub fn aggregate_name(aggregate: &AggregateExpr) -> String {
+    let function = match aggregate {
+        AggregateExpr::Count { .. } => "count",
+        AggregateExpr::Sum { .. } => "sum",
+        AggregateExpr::Avg { .. } => "avg",
+        AggregateExpr::Min { .. } => "min",
+        AggregateExpr::Max { .. } => "max",
+    };
+    match aggregate_column(aggregate) {
+        Some(column) => format!("{}_{}", function, column),
+        None => function.to_string(),
+    }
+}
+
+fn aggregate_sql(aggregate: &AggregateExpr) -> String {
+    let function = aggregate_name(aggregate)
+        .split('_')
+        .next()
+        .unwrap_or_default()
+        .to_uppercase();
+    match aggregate_column(aggregate) {
+        Some(column) => format!("{}({})", function, quote_identifier(column)),
+        None => format!("{}(*)", function),
+    }
+}
+
+fn aggregate_function(aggregate: &AggregateExpr) -> AggregateFunction {
+    match aggregate.clone() {
+        AggregateExpr::Count { column } => AggregateFunction::Count { column },
+        AggregateExpr::Sum { column } => AggregateFunction::Sum { column },
+        AggregateExpr::Avg { column } => AggregateFunction::Avg { column },
+        AggregateExpr::Min { column } => AggregateFunction::Min { column },
+        AggregateExpr::Max { column } => AggregateFunction::Max { column },
+    }
+}
+
+/// Storage as read-only queries (natural-language, PREDICT) see it
+pub(crate) struct ReadOnlyColumnStore(pub(crate) Arc<dyn ColumnStore>);
+
+#[async_trait]
+impl ColumnStore for ReadOnlyColumnStore {
+    async fn create_table(&self, _table_id: TableId, _schema: Schema) -> narayana_core::Result<()> {
+        Err(read_only())
+    }
+
+    async fn write_columns(&self, _table_id: TableId, _columns: Vec<Column>) -> narayana_core::Result<()> {
+        Err(read_only())
+    }
+
+    async fn read_columns(
+        &self,
+        table_id: TableId,
+        column_ids: Vec<u32>,
+        row_start: usize,
+        row_count: usize,
+    ) -> narayana_core::Result<Vec<Column>> {
+        self.0.read_columns(table_id, column_ids, row_start, row_count).await
+    }
+
+    async fn get_schema(&self, table_id: TableId) -> narayana_core::Result<Schema> {
+        self.0.get_schema(table_id).await
+    }
+
+    async fn get_block_metadata(&self, table_id: TableId, column_id: u32) -> narayana_core::Result<Vec<BlockMetadata>> {
+        self.0.get_block_metadata(table_id, column_id).await
+    }
+
+    async fn matching_row_ranges(
+        &self,
+        table_id: TableId,
+        column_id: u32,
+        predicate: &BlockPredicate,
+    ) -> narayana_core::Result<Option<Vec<Range<usize>>>> {
+        self.0.matching_row_ranges(table_id, column_id, predicate).await
+    }
+
+    async fn delete_table(&self, _table_id: TableId) -> narayana_core::Result<()> {
+        Err(read_only())
+    }
+}
+
+fn read_only() -> narayana_core::Error {
+    narayana_core::Error::ReadOnly("this query can only read".to_string())
+}
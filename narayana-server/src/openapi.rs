@@ -0,0 +1,273 @@
+// OpenAPI document for the REST API
+// Generated at compile time from the `#[utoipa::path]` annotations on the
+// handlers in `http`, served as JSON at /api/openapi.json with Swagger UI at
+// /api/docs. Client SDKs can be generated from the JSON document.
+
+use crate::http::{self, ApiState};
+use axum::Router;
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+use utoipa_swagger_ui::SwaggerUi;
+
+pub const OPENAPI_JSON_PATH: &str = "/api/openapi.json";
+pub const SWAGGER_UI_PATH: &str = "/api/docs";
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "NarayanaDB API",
+        description = "REST API of the NarayanaDB server. Routes other than health, metrics and auth require a bearer token from /api/v1/auth/login."
+    ),
+    paths(
+        http::redirect_to_setup_check_handler,
+        http::check_setup_handler,
+        http::setup_handler,
+        http::login_handler,
+        http::health_handler,
+        http::liveness_handler,
+        http::readiness_handler,
+        http::metrics_handler,
+        http::get_tables_handler,
+        http::create_table_handler,
+        http::delete_table_handler,
+        http::insert_data_handler,
+        http::bulk_insert_handler,
+        http::query_data_handler,
+        http::table_arrow_handler,
+        http::begin_transaction_handler,
+        http::list_transactions_handler,
+        http::get_transaction_handler,
+        http::execute_statement_handler,
+        http::commit_transaction_handler,
+        http::rollback_transaction_handler,
+        http::list_queries_handler,
+        http::get_query_handler,
+        http::cancel_query_handler,
+        http::nlq_handler,
+        http::autocomplete_handler,
+        http::stats_handler,
+        http::create_brain_handler,
+        http::create_thought_handler,
+        http::store_experience_handler,
+        http::get_thoughts_handler,
+        http::get_memory_accesses_handler,
+        http::get_thought_timeline_handler,
+        http::get_conflicts_handler,
+        http::get_narratives_handler,
+        http::export_brain_bundle_handler,
+        http::import_brain_bundle_handler,
+        http::cancel_thought_handler,
+        http::get_memories_handler,
+        http::get_brains_handler,
+        http::get_workers_handler,
+        http::get_ip_guard_stats_handler,
+        http::get_admission_stats_handler,
+        http::get_system_stats_handler,
+        http::get_cpls_handler,
+        http::create_cpl_handler,
+        http::get_cpl_handler,
+        http::get_webhooks_handler,
+        http::create_webhook_handler,
+        http::get_webhook_handler,
+        http::delete_webhook_handler,
+        http::get_webhook_deliveries_handler,
+        http::redeliver_webhook_handler,
+        http::cpl_start_handler,
+        http::cpl_stop_handler,
+        http::cpl_pause_handler,
+        http::cpl_resume_handler,
+        http::delete_cpl_handler,
+        http::enable_webhook_handler,
+        http::disable_webhook_handler,
+        http::load_schema_handler,
+        http::load_seeds_handler,
+        http::spawn_schema_handler,
+        http::vector_search_handler,
+        http::vector_add_handler,
+        http::vector_add_batch_handler,
+        http::ml_train_handler,
+        http::ml_predict_handler,
+        http::ml_extract_handler,
+        http::analytics_window_handler,
+        http::analytics_statistical_handler,
+        http::analytics_timeseries_handler,
+        http::analytics_aggregate_handler,
+        http::sync_peer_handler,
+        http::sync_status_handler,
+        http::create_fulltext_index_handler,
+        http::fulltext_search_handler,
+        http::create_spatial_index_handler,
+        http::spatial_query_handler,
+        http::list_output_profiles_handler,
+        http::set_output_profile_handler,
+        http::remove_output_profile_handler,
+        http::bind_output_profile_handler,
+        http::unbind_output_profile_handler,
+        http::list_row_policies_handler,
+        http::set_row_policy_handler,
+        http::remove_row_policy_handler,
+        http::set_column_mask_handler,
+        http::remove_column_mask_handler,
+        http::issue_token_handler,
+        http::list_compliance_tags_handler,
+        http::tag_table_handler,
+        http::untag_table_handler,
+        http::tag_vector_index_handler,
+        http::untag_vector_index_handler,
+        http::list_lineage_handler,
+        http::record_lineage_handler,
+        http::erase_subject_handler,
+        http::list_erasures_handler,
+        http::get_versioning_handler,
+        http::enable_versioning_handler,
+        http::disable_versioning_handler,
+        http::delete_versioned_rows_handler,
+        http::row_history_handler,
+        http::purge_history_handler,
+        http::replication_status_handler,
+        http::replicate_table_handler,
+        http::stop_replicating_table_handler,
+        http::apply_replicated_changes_handler,
+        http::cluster_status_handler,
+        http::join_cluster_handler,
+        http::leave_cluster_handler,
+        http::raft_request_vote_handler,
+        http::raft_append_entries_handler,
+        http::list_anomaly_detectors_handler,
+        http::create_anomaly_detector_handler,
+        http::get_anomaly_detector_handler,
+        http::delete_anomaly_detector_handler,
+        http::reset_anomaly_detector_handler,
+        http::get_anomaly_events_handler,
+        http::list_feature_views_handler,
+        http::create_feature_view_handler,
+        http::get_feature_view_handler,
+        http::delete_feature_view_handler,
+        http::online_features_handler,
+        http::historical_features_handler,
+        http::upload_blob_handler,
+        http::list_blobs_handler,
+        http::get_blob_info_handler,
+        http::download_blob_handler,
+        http::delete_blob_handler,
+        http::list_skills_handler,
+        http::install_skill_handler,
+        http::get_skill_handler,
+        http::upgrade_skill_handler,
+        http::uninstall_skill_handler,
+        http::skill_history_handler,
+        http::list_jobs_handler,
+        http::get_job_handler,
+        http::trigger_job_handler,
+        http::pause_job_handler,
+        http::resume_job_handler,
+        http::list_connectors_handler,
+        http::create_connector_handler,
+        http::get_connector_handler,
+        http::delete_connector_handler,
+        http::poll_connector_handler,
+        http::pause_connector_handler,
+        http::resume_connector_handler,
+        http::reset_connector_handler,
+        http::list_invariant_violations_handler,
+        http::check_invariants_handler,
+        http::clear_invariant_violations_handler,
+        http::quota_status_handler,
+        http::check_quotas_handler,
+        http::list_recommendations_handler,
+        http::get_recommendation_handler,
+        http::apply_recommendation_handler,
+        http::dismiss_recommendation_handler,
+        http::scaling_status_handler,
+        http::list_shards_handler,
+        http::list_shard_events_handler,
+        http::bootstrap_table_shard_handler,
+        http::report_shard_load_handler,
+        http::split_shard_handler,
+        http::merge_shards_handler,
+        http::add_shard_reader_handler,
+        http::retire_shard_reader_handler,
+        http::list_actor_tokens_handler,
+        http::issue_actor_token_handler,
+        http::rotate_actor_tokens_handler,
+        http::revoke_actor_token_handler,
+        http::get_actor_quota_handler,
+        http::set_actor_quota_handler,
+        http::remove_actor_quota_handler,
+        http::dead_letter_summary_handler,
+        http::list_dead_letters_handler,
+        http::get_dead_letter_handler,
+        http::redeliver_dead_letters_handler,
+        http::dry_run_transformation_handler,
+    ),
+    modifiers(&BearerAuth),
+    security(("bearer_auth" = [])),
+    tags(
+        (name = "auth", description = "First-run setup and login"),
+        (name = "health", description = "Liveness, readiness and metrics"),
+        (name = "tables", description = "Tables, inserts and column queries"),
+        (name = "transactions", description = "Explicit transactions: begin, statements, commit and rollback"),
+        (name = "queries", description = "Running queries and query autocompletion"),
+        (name = "nlq", description = "Natural-language questions translated into queries by the LLM"),
+        (name = "search", description = "Full-text and spatial indexes"),
+        (name = "output_profiles", description = "Per-token and per-role response masking and reshaping"),
+        (name = "row_security", description = "Row policies, column masks and tenant-scoped tokens"),
+        (name = "compliance", description = "Personal data tags, lineage and subject erasure"),
+        (name = "temporal", description = "System-versioned tables, row history and AS OF reads"),
+        (name = "replication", description = "Multi-primary replication between regions and conflict resolution"),
+        (name = "cluster", description = "Raft cluster membership and the RPCs between nodes"),
+        (name = "anomaly", description = "Streaming anomaly detectors"),
+        (name = "features", description = "Feature store"),
+        (name = "blobs", description = "Content-addressed binary storage"),
+        (name = "skills", description = "Skill packages: tables, workers, subscriptions and behaviors"),
+        (name = "brains", description = "Cognitive brains, thoughts and memories"),
+        (name = "cpls", description = "Conscience persistent loops"),
+        (name = "workers", description = "Edge workers"),
+        (name = "webhooks", description = "Webhooks and deliveries"),
+        (name = "vectors", description = "Vector indexes and similarity search"),
+        (name = "ml", description = "Model training and inference"),
+        (name = "analytics", description = "Analytical queries"),
+        (name = "sync", description = "Peer synchronization"),
+        (name = "system", description = "Server statistics"),
+        (name = "schema", description = "Schema files and seed data"),
+        (name = "jobs", description = "Scheduled maintenance jobs"),
+        (name = "connectors", description = "Ingest connectors polling HTTP, MQTT, RSS and S3 sources into tables"),
+        (name = "invariants", description = "Runtime invariant checks and violation reports"),
+        (name = "quotas", description = "Storage quotas, usage and low-disk read-only mode"),
+        (name = "recommendations", description = "Index, sort key and materialized view advisor"),
+        (name = "scaling", description = "Load forecasts and predictive resource scaling"),
+        (name = "shards", description = "Shard splits, merges and reader replicas"),
+        (name = "rde", description = "Rapid data event actors, their API tokens and publish quotas, and transformation dry runs"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Registers the JWT bearer scheme referenced by `security`
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+    }
+}
+
+/// The OpenAPI 3.1 document for every REST route
+pub fn document() -> utoipa::openapi::OpenApi {
+    ApiDoc::openapi()
+}
+
+/// Routes serving the document and Swagger UI (public, like /health)
+pub fn routes() -> Router<ApiState> {
+    SwaggerUi::new(SWAGGER_UI_PATH)
+        .url(OPENAPI_JSON_PATH, document())
+        .into()
+}
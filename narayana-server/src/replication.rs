@@ -0,0 +1,99 @@
+// Cross-region replication plumbing
+// Ships change batches to peer deployments over their HTTP API and runs the
+// merge workers of tables that resolve conflicts with one.
+
+use async_trait::async_trait;
+use narayana_core::config::ReplicationPeer;
+use narayana_core::{Error, Result};
+use narayana_storage::cognitive::CognitiveBrain;
+use narayana_storage::column_store::ColumnStore;
+use narayana_storage::database_manager::DatabaseManager;
+use narayana_storage::replication::{ApplyReport, ChangeBatch, MergeResolver, ReplicationTransport};
+use narayana_storage::workers::{WorkerManager, WorkerRequest};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Path peers accept change batches on
+pub const CHANGES_PATH: &str = "/api/v1/replication/changes";
+
+/// POSTs change batches to `{peer.url}/api/v1/replication/changes`
+pub struct HttpReplicationTransport {
+    client: reqwest::Client,
+}
+
+impl HttpReplicationTransport {
+    pub fn new(timeout: Duration) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| Error::Storage(format!("Failed to build replication client: {}", e)))?;
+        Ok(Self { client })
+    }
+}
+
+#[async_trait]
+impl ReplicationTransport for HttpReplicationTransport {
+    async fn send(&self, peer: &ReplicationPeer, batch: &ChangeBatch) -> Result<ApplyReport> {
+        let url = format!("{}{}", peer.url.trim_end_matches('/'), CHANGES_PATH);
+        let mut request = self.client.post(&url).json(batch);
+        if let Some(token) = &peer.token {
+            request = request.bearer_auth(token);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| Error::Storage(format!("Request to {} failed: {}", peer.region, e)))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Storage(format!("{} answered {}: {}", peer.region, status, body)));
+        }
+        response
+            .json::<ApplyReport>()
+            .await
+            .map_err(|e| Error::Storage(format!("Invalid response from {}: {}", peer.region, e)))
+    }
+}
+
+/// Runs a table's merge worker with `{"table", "local", "remote"}` as the
+/// JSON body; the worker answers with the merged row as a JSON object
+pub struct WorkerMergeResolver {
+    pub workers: Arc<WorkerManager>,
+    pub storage: Arc<dyn ColumnStore>,
+    pub db_manager: Arc<DatabaseManager>,
+    pub brain: Option<Arc<CognitiveBrain>>,
+}
+
+#[async_trait]
+impl MergeResolver for WorkerMergeResolver {
+    async fn merge(&self, worker_id: &str, table: &str, local: &Map<String, Value>, remote: &Map<String, Value>) -> Result<Map<String, Value>> {
+        let body = json!({"table": table, "local": local, "remote": remote});
+        let request = WorkerRequest {
+            method: "POST".to_string(),
+            url: "/".to_string(),
+            headers: HashMap::from([
+                ("Content-Type".to_string(), "application/json".to_string()),
+                ("X-Narayana-Table".to_string(), table.to_string()),
+            ]),
+            body: Some(body.to_string().into_bytes()),
+            query: HashMap::new(),
+            client_ip: None,
+            request_id: uuid::Uuid::new_v4().to_string(),
+            worker_id: worker_id.to_string(),
+            edge_location: None,
+        };
+        let response = self.workers
+            .invoke_worker(worker_id, request, self.storage.clone(), self.db_manager.clone(), self.brain.clone())
+            .await
+            .map_err(|e| Error::Storage(format!("Merge worker '{}' failed: {}", worker_id, e)))?;
+        if response.status >= 400 {
+            return Err(Error::Storage(format!("Merge worker '{}' returned status {}", worker_id, response.status)));
+        }
+        match serde_json::from_slice(&response.body) {
+            Ok(Value::Object(merged)) => Ok(merged),
+            _ => Err(Error::Storage(format!("Merge worker '{}' must return a JSON object", worker_id))),
+        }
+    }
+}
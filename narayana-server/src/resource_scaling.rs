@@ -0,0 +1,149 @@
+// Predictive resource scaling for the server
+// Samples query and insert counters into the workload forecaster and carries
+// out its plans: the query thread pool is resized, compaction jobs are held
+// back ahead of peaks and run early in quiet periods, and the cache budget is
+// published for caches to size against. Forecasts and actions are exported as
+// metrics.
+
+use narayana_core::{Error, Result};
+use narayana_storage::jobs::{JobKind, JobScheduler};
+use narayana_storage::predictive_scaling::{
+    CompactionMode, ResourceActuator, ScalingResource, WorkloadSample, WorkloadScaler,
+};
+use narayana_storage::threading::{ThreadManager, ThreadPoolType};
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::http::{TOTAL_QUERIES, TOTAL_ROWS_INSERTED};
+
+/// Applies scaling plans to the server's thread pools and maintenance jobs
+pub struct ServerResourceActuator {
+    threads: Arc<ThreadManager>,
+    jobs: Arc<JobScheduler>,
+    cache_budget: AtomicU64,
+    /// Compaction jobs this actuator paused (operator-paused ones stay paused)
+    paused: Mutex<Vec<String>>,
+}
+
+impl ServerResourceActuator {
+    pub fn new(threads: Arc<ThreadManager>, jobs: Arc<JobScheduler>) -> Self {
+        Self {
+            threads,
+            jobs,
+            cache_budget: AtomicU64::new(0),
+            paused: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Bytes caches may use; 0 until the first plan
+    pub fn cache_budget(&self) -> u64 {
+        self.cache_budget.load(Ordering::Relaxed)
+    }
+
+    fn resume_paused(&self) -> Result<()> {
+        for name in self.paused.lock().drain(..) {
+            self.jobs.resume(&name)?;
+        }
+        Ok(())
+    }
+}
+
+impl ResourceActuator for ServerResourceActuator {
+    fn resize_thread_pool(&self, threads: usize) -> Result<()> {
+        let mut config = self.threads
+            .get_pool(ThreadPoolType::Query)
+            .map(|pool| pool.config().clone())
+            .unwrap_or_else(narayana_storage::threading::ThreadPoolConfig::query);
+        config.initial_threads = threads;
+        config.min_threads = config.min_threads.min(threads);
+        config.max_threads = config.max_threads.max(threads);
+        self.threads
+            .update_pool_config(ThreadPoolType::Query, config)
+            .map_err(|e| Error::Storage(format!("Failed to resize query pool: {}", e)))?;
+        metrics::gauge!("narayana_scaling_query_threads").set(threads as f64);
+        Ok(())
+    }
+
+    fn set_cache_budget(&self, bytes: u64) -> Result<()> {
+        self.cache_budget.store(bytes, Ordering::Relaxed);
+        metrics::gauge!("narayana_scaling_cache_budget_bytes").set(bytes as f64);
+        Ok(())
+    }
+
+    fn set_compaction_mode(&self, mode: CompactionMode) -> Result<()> {
+        let compaction_jobs: Vec<_> = self.jobs.list()
+            .into_iter()
+            .filter(|job| job.spec.kind == JobKind::Compaction)
+            .collect();
+        match mode {
+            CompactionMode::Deferred => {
+                let mut paused = self.paused.lock();
+                for job in compaction_jobs.iter().filter(|job| !job.paused) {
+                    self.jobs.pause(&job.spec.name)?;
+                    paused.push(job.spec.name.clone());
+                }
+            }
+            CompactionMode::Normal => self.resume_paused()?,
+            CompactionMode::Aggressive => {
+                self.resume_paused()?;
+                // Compact now rather than at the next scheduled run
+                for job in compaction_jobs.iter().filter(|job| !job.paused && job.running.is_none()) {
+                    if let Err(e) = self.jobs.trigger(&job.spec.name) {
+                        tracing::debug!("Compaction job {} not started early: {}", job.spec.name, e);
+                    }
+                }
+            }
+        }
+        let level = match mode {
+            CompactionMode::Deferred => 0.0,
+            CompactionMode::Normal => 1.0,
+            CompactionMode::Aggressive => 2.0,
+        };
+        metrics::gauge!("narayana_scaling_compaction_level").set(level);
+        Ok(())
+    }
+}
+
+/// Every `interval`, record the queries and inserted rows since the last
+/// sample, then let the scaler act on its forecast
+pub fn spawn_sampler(scaler: Arc<WorkloadScaler>, interval: Duration) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        // The first tick completes immediately
+        ticker.tick().await;
+        let mut last = (TOTAL_QUERIES.load(Ordering::Relaxed), TOTAL_ROWS_INSERTED.load(Ordering::Relaxed));
+        loop {
+            ticker.tick().await;
+            let current = (TOTAL_QUERIES.load(Ordering::Relaxed), TOTAL_ROWS_INSERTED.load(Ordering::Relaxed));
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+            scaler.record(WorkloadSample {
+                timestamp: now,
+                interval_secs: interval.as_secs().max(1),
+                queries: current.0.saturating_sub(last.0),
+                ingested_rows: current.1.saturating_sub(last.1),
+            });
+            last = current;
+
+            for action in scaler.tick(now) {
+                let resource = match action.resource {
+                    ScalingResource::ThreadPool => "thread_pool",
+                    ScalingResource::CacheBudget => "cache_budget",
+                    ScalingResource::Compaction => "compaction",
+                };
+                let outcome = if action.error.is_some() { "failed" } else { "applied" };
+                metrics::counter!("narayana_scaling_actions_total", "resource" => resource, "outcome" => outcome).increment(1);
+            }
+            let status = scaler.status();
+            if let (Some(current), Some(ahead)) = (status.now, status.ahead) {
+                metrics::gauge!("narayana_forecast_queries_per_minute", "horizon" => "now").set(current.queries_per_minute);
+                metrics::gauge!("narayana_forecast_queries_per_minute", "horizon" => "lead").set(ahead.queries_per_minute);
+                metrics::gauge!("narayana_forecast_ingest_rows_per_minute", "horizon" => "now").set(current.ingest_rows_per_minute);
+                metrics::gauge!("narayana_forecast_ingest_rows_per_minute", "horizon" => "lead").set(ahead.ingest_rows_per_minute);
+                metrics::gauge!("narayana_forecast_load_ratio", "horizon" => "now").set(current.load_ratio);
+                metrics::gauge!("narayana_forecast_load_ratio", "horizon" => "lead").set(ahead.load_ratio);
+            }
+        }
+    })
+}
@@ -69,6 +69,8 @@ fn parse_data_type(s: &str) -> Result<DataType> {
         "Timestamp" => Ok(DataType::Timestamp),
         "Date" => Ok(DataType::Date),
         "Json" => Ok(DataType::Json),
+        "Point" => Ok(DataType::Point),
+        "Geometry" => Ok(DataType::Geometry),
         _ => {
             // Handle Nullable(Type), Array(Type), Map(Key, Value)
             if s.starts_with("Nullable(") && s.ends_with(")") {
@@ -414,6 +416,16 @@ pub async fn load_seeds(
                         }).collect();
                         Column::String(string_values)
                     }
+                    DataType::Point | DataType::Geometry => {
+                        // Accept WKT strings or [lon, lat] arrays; encode as geometry bytes
+                        let mut binary_values = Vec::with_capacity(values.len());
+                        for v in values {
+                            let geometry = narayana_storage::geospatial::Geometry::from_json(&toml_to_json(v.clone()))
+                                .map_err(|e| anyhow::anyhow!("Invalid geometry in field '{}': {}", field.name, e))?;
+                            binary_values.push(geometry.encode());
+                        }
+                        Column::Binary(binary_values)
+                    }
                     DataType::Array(_) | DataType::Map(_, _) => {
                         // For complex types, serialize to JSON string
                         let string_values: Vec<String> = values.iter().map(|v| {
@@ -19,6 +19,17 @@ pub struct Claims {
     pub exp: usize,  // Expiration time
     pub iat: usize, // Issued at
     pub roles: Vec<String>, // User roles
+    /// Tenant the caller belongs to; row policies compare it as `$tenant`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+}
+
+impl Claims {
+    /// The caller as row policies and column masks see it
+    pub fn principal(&self) -> narayana_storage::row_security::Principal {
+        narayana_storage::row_security::Principal::new(self.sub.clone(), self.roles.clone())
+            .with_tenant(self.tenant.clone())
+    }
 }
 
 /// Secure token manager for authentication
@@ -42,6 +53,16 @@ impl TokenManager {
 
     /// Generate a secure JWT token
     pub fn generate_token(&self, user_id: String, roles: Vec<String>) -> Result<String, SecurityError> {
+        self.generate_tenant_token(user_id, roles, None)
+    }
+
+    /// Generate a JWT token scoped to a tenant
+    pub fn generate_tenant_token(
+        &self,
+        user_id: String,
+        roles: Vec<String>,
+        tenant: Option<String>,
+    ) -> Result<String, SecurityError> {
         let now = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap_or_default()
@@ -52,6 +73,7 @@ impl TokenManager {
             exp: now + 3600, // 1 hour expiration
             iat: now,
             roles,
+            tenant,
         };
 
         let encoding_key = self.encoding_key.lock().unwrap();
@@ -0,0 +1,483 @@
+// Skill installation
+// Applies skill bundles (tables, workers, RDE subscriptions, behavior trees)
+// as a unit: each step records how to undo it, and a failure part-way rolls
+// the finished steps back in reverse. Installs, upgrades and uninstalls are
+// recorded in the `narayana_skills` system table, which is also what restores
+// skills after a restart (workers and subscriptions only live in memory).
+
+use anyhow::{anyhow, bail, Context, Result};
+use narayana_cns::behavior::{install_definition, remove_definition};
+use narayana_cns::BehaviorTreeDef;
+use narayana_core::column::Column;
+use narayana_core::schema::{DataType, Field, Schema};
+use narayana_core::skill::{SkillManifest, SkillVersion};
+use narayana_core::types::TableId;
+use narayana_rde::{Actor, ActorId, ActorType, RdeManager, SubscriptionId, TransportType};
+use narayana_storage::database_manager::DatabaseManager;
+use narayana_storage::workers::WorkerManager;
+use narayana_storage::ColumnStore;
+use parking_lot::RwLock;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// System table recording skill installs, upgrades and uninstalls
+pub const SKILLS_TABLE: &str = "narayana_skills";
+
+/// RDE actor that owns skill subscriptions
+const SKILLS_ACTOR: &str = "narayana_skills";
+
+/// Database skill tables are created in
+const SKILLS_DATABASE: &str = "default";
+
+/// An installed skill and what it created
+#[derive(Debug, Clone, Serialize)]
+pub struct InstalledSkill {
+    pub name: String,
+    pub version: String,
+    pub description: Option<String>,
+    pub installed_at: i64,
+    pub tables: Vec<String>,
+    /// Worker name in the skill -> deployed worker id
+    pub workers: HashMap<String, String>,
+    pub subscriptions: Vec<String>,
+    pub behaviors: Vec<String>,
+    #[serde(skip)]
+    bundle: SkillManifest,
+}
+
+/// One row of the skills system table
+#[derive(Debug, Clone, Serialize)]
+pub struct SkillHistoryEntry {
+    pub name: String,
+    pub version: String,
+    pub action: String,
+    pub recorded_at: i64,
+}
+
+/// Undo for one applied step
+enum Undo {
+    Table(TableId),
+    Worker(String),
+    Subscription(SubscriptionId),
+    /// Restore a behavior file to its previous content, or remove it
+    Behavior { name: String, previous: Option<Vec<u8>> },
+}
+
+/// What applying a bundle created
+#[derive(Default)]
+struct Applied {
+    workers: HashMap<String, String>,
+    subscriptions: Vec<SubscriptionId>,
+    undo: Vec<Undo>,
+}
+
+/// Installs, upgrades and uninstalls skills
+pub struct SkillManager {
+    storage: Arc<dyn ColumnStore>,
+    db_manager: Arc<DatabaseManager>,
+    workers: Arc<WorkerManager>,
+    rde: Arc<RdeManager>,
+    /// Behavior trees are installed here for the CNS behavior engine
+    behaviors_dir: PathBuf,
+    actor_token: String,
+    installed: RwLock<HashMap<String, InstalledSkill>>,
+    /// One skill operation at a time
+    operation: tokio::sync::Mutex<()>,
+}
+
+impl SkillManager {
+    pub async fn new(
+        storage: Arc<dyn ColumnStore>,
+        db_manager: Arc<DatabaseManager>,
+        workers: Arc<WorkerManager>,
+        rde: Arc<RdeManager>,
+        behaviors_dir: PathBuf,
+    ) -> Result<Self> {
+        let actor_token = format!("{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple());
+        let mut actor = Actor::new(
+            ActorId::from(SKILLS_ACTOR),
+            "Skill subscriptions".to_string(),
+            ActorType::Origin,
+            actor_token.clone(),
+        );
+        actor.metadata = serde_json::json!({ "allow_wildcard_subscriptions": true });
+        rde.register_actor(actor).await.map_err(|e| anyhow!("Failed to register skills actor: {}", e))?;
+
+        Ok(Self {
+            storage,
+            db_manager,
+            workers,
+            rde,
+            behaviors_dir,
+            actor_token,
+            installed: RwLock::new(HashMap::new()),
+            operation: tokio::sync::Mutex::new(()),
+        })
+    }
+
+    /// Re-apply the skills recorded as installed in the system table
+    pub async fn restore(&self) -> Result<usize> {
+        let _operation = self.operation.lock().await;
+        let mut latest: HashMap<String, (String, String, i64)> = HashMap::new();
+        for (name, version, action, bundle, recorded_at) in self.read_history().await? {
+            if action == "uninstall" {
+                latest.remove(&name);
+            } else {
+                latest.insert(name, (version, bundle, recorded_at));
+            }
+        }
+
+        let mut restored = 0;
+        for (name, (version, bundle, installed_at)) in latest {
+            let bundle: SkillManifest = match serde_json::from_str(&bundle) {
+                Ok(bundle) => bundle,
+                Err(e) => {
+                    warn!("Skipping skill {} {}: unreadable bundle: {}", name, version, e);
+                    continue;
+                }
+            };
+            match self.apply(&bundle, true).await {
+                Ok(applied) => {
+                    self.installed.write().insert(name, installed(bundle, applied, installed_at));
+                    restored += 1;
+                }
+                Err(e) => warn!("Failed to restore skill {} {}: {}", name, version, e),
+            }
+        }
+        info!("Restored {} skills", restored);
+        Ok(restored)
+    }
+
+    pub async fn install(&self, bundle: SkillManifest) -> Result<InstalledSkill> {
+        bundle.validate()?;
+        let _operation = self.operation.lock().await;
+        if let Some(current) = self.installed.read().get(&bundle.name) {
+            bail!("Skill '{}' {} is already installed; upgrade it instead", current.name, current.version);
+        }
+
+        let applied = self.apply(&bundle, false).await?;
+        let recorded_at = match self.record(&bundle, "install").await {
+            Ok(at) => at,
+            Err(e) => {
+                self.rollback(applied.undo).await;
+                return Err(e);
+            }
+        };
+        let skill = installed(bundle, applied, recorded_at);
+        info!("SKILL: action=install, name={}, version={}", skill.name, skill.version);
+        self.installed.write().insert(skill.name.clone(), skill.clone());
+        Ok(skill)
+    }
+
+    /// Replace an installed skill with a newer version. The new version is
+    /// applied next to the old one and the old workers, subscriptions and
+    /// behaviors are only retired once it is recorded. Tables the new version
+    /// no longer declares are kept, with their data.
+    pub async fn upgrade(&self, bundle: SkillManifest) -> Result<InstalledSkill> {
+        bundle.validate()?;
+        let _operation = self.operation.lock().await;
+        let current = self.installed.read().get(&bundle.name).cloned()
+            .ok_or_else(|| anyhow!("Skill '{}' is not installed", bundle.name))?;
+        let (from, to) = (SkillVersion::parse(&current.version)?, bundle.parsed_version()?);
+        if to <= from {
+            bail!("Skill '{}' {} is not newer than installed {}", bundle.name, to, from);
+        }
+
+        let applied = self.apply(&bundle, false).await?;
+        let recorded_at = match self.record(&bundle, "upgrade").await {
+            Ok(at) => at,
+            Err(e) => {
+                self.rollback(applied.undo).await;
+                return Err(e);
+            }
+        };
+
+        let kept_behaviors: HashSet<String> = bundle.behavior_names().into_iter().collect();
+        self.retire(&current, &kept_behaviors).await;
+        let skill = installed(bundle, applied, recorded_at);
+        info!("SKILL: action=upgrade, name={}, from={}, to={}", skill.name, from, to);
+        self.installed.write().insert(skill.name.clone(), skill.clone());
+        Ok(skill)
+    }
+
+    /// Remove an installed skill; its tables are only dropped with `purge`
+    pub async fn uninstall(&self, name: &str, purge: bool) -> Result<InstalledSkill> {
+        let _operation = self.operation.lock().await;
+        let current = self.installed.read().get(name).cloned()
+            .ok_or_else(|| anyhow!("Skill '{}' is not installed", name))?;
+
+        self.record(&current.bundle, "uninstall").await?;
+        self.retire(&current, &HashSet::new()).await;
+        if purge {
+            for table in &current.tables {
+                if let Some(table_id) = self.db_manager.get_table_by_name(SKILLS_DATABASE, table) {
+                    if let Err(e) = self.drop_table(table_id).await {
+                        warn!("Failed to drop table {} of skill {}: {}", table, name, e);
+                    }
+                }
+            }
+        }
+        info!("SKILL: action=uninstall, name={}, version={}, purge={}", name, current.version, purge);
+        self.installed.write().remove(name);
+        Ok(current)
+    }
+
+    pub fn get(&self, name: &str) -> Option<InstalledSkill> {
+        self.installed.read().get(name).cloned()
+    }
+
+    pub fn list(&self) -> Vec<InstalledSkill> {
+        let mut skills: Vec<InstalledSkill> = self.installed.read().values().cloned().collect();
+        skills.sort_by(|a, b| a.name.cmp(&b.name));
+        skills
+    }
+
+    /// Recorded installs, upgrades and uninstalls, oldest first
+    pub async fn history(&self, name: Option<&str>) -> Result<Vec<SkillHistoryEntry>> {
+        Ok(self.read_history().await?
+            .into_iter()
+            .filter(|(skill, ..)| name.is_none_or(|name| name == skill))
+            .map(|(name, version, action, _, recorded_at)| SkillHistoryEntry { name, version, action, recorded_at })
+            .collect())
+    }
+
+    /// Apply every part of a bundle, undoing the finished steps on failure.
+    /// When `restoring`, behavior trees already on disk are left alone so
+    /// their persisted state survives the restart.
+    async fn apply(&self, bundle: &SkillManifest, restoring: bool) -> Result<Applied> {
+        let mut applied = Applied::default();
+        match self.apply_steps(bundle, restoring, &mut applied).await {
+            Ok(()) => Ok(applied),
+            Err(e) => {
+                warn!("Rolling back skill {} {}: {}", bundle.name, bundle.version, e);
+                self.rollback(applied.undo).await;
+                Err(e)
+            }
+        }
+    }
+
+    async fn apply_steps(&self, bundle: &SkillManifest, restoring: bool, applied: &mut Applied) -> Result<()> {
+        // Parse everything up front so a bad definition fails before any change
+        let behaviors = bundle.behaviors.iter()
+            .filter_map(|b| b.definition.clone())
+            .map(|definition| {
+                let def: BehaviorTreeDef = serde_json::from_value(definition).context("Invalid behavior tree")?;
+                def.validate().map_err(|e| anyhow!("Invalid behavior tree '{}': {}", def.name, e))?;
+                Ok(def)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let transports = bundle.subscriptions.iter()
+            .map(|s| serde_json::from_value::<TransportType>(serde_json::json!(s.transport))
+                .map_err(|_| anyhow!("Unknown transport '{}'", s.transport)))
+            .collect::<Result<Vec<_>>>()?;
+
+        for table in &bundle.tables {
+            match self.db_manager.get_table_by_name(SKILLS_DATABASE, &table.name) {
+                // Tables survive upgrades and restarts; reuse them if they match
+                Some(table_id) => {
+                    let existing = self.storage.get_schema(table_id).await?;
+                    if !same_schema(&existing, &table.schema) {
+                        bail!("Table '{}' already exists with a different schema", table.name);
+                    }
+                }
+                None => {
+                    let db_id = self.database()?;
+                    let table_id = self.db_manager.create_table(db_id, table.name.clone(), table.schema.clone())?;
+                    if let Err(e) = self.storage.create_table(table_id, table.schema.clone()).await {
+                        let _ = self.db_manager.drop_table(table_id);
+                        return Err(e.into());
+                    }
+                    applied.undo.push(Undo::Table(table_id));
+                }
+            }
+        }
+
+        for worker in &bundle.workers {
+            let worker_id = self.workers.deploy_worker(
+                format!("{}.{}", bundle.name, worker.name),
+                worker.code.clone().unwrap_or_default(),
+                worker.route.clone(),
+                HashMap::new(),
+                None,
+                Vec::new(),
+                Some(worker.allowed_urls.clone()),
+            ).await.with_context(|| format!("Failed to deploy worker '{}'", worker.name))?;
+            applied.undo.push(Undo::Worker(worker_id.clone()));
+            applied.workers.insert(worker.name.clone(), worker_id);
+        }
+
+        for (subscription, transport) in bundle.subscriptions.iter().zip(transports) {
+            let mut config = subscription.config.clone();
+            if let Some(worker) = &subscription.worker {
+                let worker_id = &applied.workers[worker];
+                match config.as_object_mut() {
+                    Some(config) => {
+                        config.insert("worker_id".to_string(), serde_json::json!(worker_id));
+                    }
+                    None => config = serde_json::json!({ "worker_id": worker_id }),
+                }
+            }
+            let config = (!config.is_null()).then_some(config);
+            let subscription_id = self.rde
+                .subscribe(&ActorId::from(SKILLS_ACTOR), &self.actor_token, &subscription.event, transport, config)
+                .await
+                .map_err(|e| anyhow!("Failed to subscribe to '{}': {}", subscription.event, e))?;
+            applied.undo.push(Undo::Subscription(subscription_id.clone()));
+            applied.subscriptions.push(subscription_id);
+        }
+
+        for def in &behaviors {
+            let path = self.behaviors_dir.join(format!("{}.json", def.name));
+            if restoring && path.exists() {
+                continue;
+            }
+            let previous = std::fs::read(&path).ok();
+            install_definition(&self.behaviors_dir, def)
+                .map_err(|e| anyhow!("Failed to install behavior '{}': {}", def.name, e))?;
+            applied.undo.push(Undo::Behavior { name: def.name.clone(), previous });
+        }
+        Ok(())
+    }
+
+    async fn rollback(&self, undo: Vec<Undo>) {
+        for step in undo.into_iter().rev() {
+            let result = match step {
+                Undo::Table(table_id) => self.drop_table(table_id).await,
+                Undo::Worker(worker_id) => self.workers.delete_worker(&worker_id).await,
+                Undo::Subscription(id) => self.unsubscribe(&id).await,
+                Undo::Behavior { name, previous: Some(bytes) } => {
+                    std::fs::write(self.behaviors_dir.join(format!("{}.json", name)), bytes).map_err(Into::into)
+                }
+                Undo::Behavior { name, previous: None } => {
+                    remove_definition(&self.behaviors_dir, &name).map(|_| ()).map_err(|e| anyhow!("{}", e))
+                }
+            };
+            if let Err(e) = result {
+                warn!("Skill rollback step failed: {}", e);
+            }
+        }
+    }
+
+    /// Remove an installed version's workers, subscriptions and behaviors
+    /// (except behaviors in `keep`, which a newer version has replaced)
+    async fn retire(&self, skill: &InstalledSkill, keep: &HashSet<String>) {
+        for id in &skill.subscriptions {
+            if let Err(e) = self.unsubscribe(&SubscriptionId(id.clone())).await {
+                warn!("Failed to remove subscription {} of skill {}: {}", id, skill.name, e);
+            }
+        }
+        for worker_id in skill.workers.values() {
+            if let Err(e) = self.workers.delete_worker(worker_id).await {
+                warn!("Failed to delete worker {} of skill {}: {}", worker_id, skill.name, e);
+            }
+        }
+        for name in skill.behaviors.iter().filter(|name| !keep.contains(*name)) {
+            if let Err(e) = remove_definition(&self.behaviors_dir, name) {
+                warn!("Failed to remove behavior {} of skill {}: {}", name, skill.name, e);
+            }
+        }
+    }
+
+    async fn unsubscribe(&self, id: &SubscriptionId) -> Result<()> {
+        self.rde.unsubscribe(&ActorId::from(SKILLS_ACTOR), &self.actor_token, id).await
+            .map(|_| ())
+            .map_err(|e| anyhow!("{}", e))
+    }
+
+    async fn drop_table(&self, table_id: TableId) -> Result<()> {
+        self.storage.delete_table(table_id).await?;
+        self.db_manager.drop_table(table_id)?;
+        Ok(())
+    }
+
+    fn database(&self) -> Result<narayana_storage::database_manager::DatabaseId> {
+        match self.db_manager.get_database_by_name(SKILLS_DATABASE) {
+            Some(id) => Ok(id),
+            None => Ok(self.db_manager.create_database(SKILLS_DATABASE.to_string())?),
+        }
+    }
+
+    /// The skills system table, created on first use
+    async fn skills_table(&self) -> Result<TableId> {
+        if let Some(table_id) = self.db_manager.get_table_by_name(SKILLS_DATABASE, SKILLS_TABLE) {
+            return Ok(table_id);
+        }
+        let schema = Schema::new(vec![
+            string_field("name"),
+            string_field("version"),
+            string_field("action"),
+            string_field("bundle"),
+            Field { name: "recorded_at".to_string(), data_type: DataType::Int64, nullable: false, default_value: None },
+        ]);
+        let table_id = self.db_manager.create_table(self.database()?, SKILLS_TABLE.to_string(), schema.clone())?;
+        if let Err(e) = self.storage.create_table(table_id, schema).await {
+            let _ = self.db_manager.drop_table(table_id);
+            return Err(e.into());
+        }
+        Ok(table_id)
+    }
+
+    /// Append a row to the skills system table
+    async fn record(&self, bundle: &SkillManifest, action: &str) -> Result<i64> {
+        let table_id = self.skills_table().await?;
+        let recorded_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64;
+        self.storage.write_columns(table_id, vec![
+            Column::String(vec![bundle.name.clone()]),
+            Column::String(vec![bundle.version.clone()]),
+            Column::String(vec![action.to_string()]),
+            Column::String(vec![serde_json::to_string(bundle)?]),
+            Column::Int64(vec![recorded_at]),
+        ]).await.context("Failed to record skill in system table")?;
+        Ok(recorded_at)
+    }
+
+    /// Rows of the skills system table: (name, version, action, bundle, recorded_at)
+    async fn read_history(&self) -> Result<Vec<(String, String, String, String, i64)>> {
+        let Some(table_id) = self.db_manager.get_table_by_name(SKILLS_DATABASE, SKILLS_TABLE) else {
+            return Ok(Vec::new());
+        };
+        let columns = self.storage.read_columns(table_id, vec![0, 1, 2, 3, 4], 0, usize::MAX).await?;
+        match columns.as_slice() {
+            [Column::String(names), Column::String(versions), Column::String(actions), Column::String(bundles), Column::Int64(times)] => {
+                Ok(names.iter().zip(versions).zip(actions).zip(bundles).zip(times)
+                    .map(|((((name, version), action), bundle), at)| {
+                        (name.clone(), version.clone(), action.clone(), bundle.clone(), *at)
+                    })
+                    .collect())
+            }
+            // Empty columns are not returned
+            [] => Ok(Vec::new()),
+            _ => bail!("Skills table has an unexpected layout"),
+        }
+    }
+}
+
+fn installed(bundle: SkillManifest, applied: Applied, installed_at: i64) -> InstalledSkill {
+    InstalledSkill {
+        name: bundle.name.clone(),
+        version: bundle.version.clone(),
+        description: bundle.description.clone(),
+        installed_at,
+        tables: bundle.tables.iter().map(|t| t.name.clone()).collect(),
+        workers: applied.workers,
+        subscriptions: applied.subscriptions.into_iter().map(|id| id.0).collect(),
+        behaviors: bundle.behavior_names(),
+        bundle,
+    }
+}
+
+fn string_field(name: &str) -> Field {
+    Field { name: name.to_string(), data_type: DataType::String, nullable: false, default_value: None }
+}
+
+fn same_schema(a: &Schema, b: &Schema) -> bool {
+    a.fields.len() == b.fields.len()
+        && a.fields.iter().zip(&b.fields)
+            .all(|(a, b)| a.name == b.name && a.data_type == b.data_type && a.nullable == b.nullable)
+}
@@ -1,73 +1,538 @@
+// Static file hosting for the web UI
+// Serves a built single-page app from the configured directory with ETag
+// revalidation, brotli/gzip variants (precompressed `.br`/`.gz` files or
+// compressed once and cached) and index fallback for client-side routes.
+// Without a directory, `/` redirects to the UI dev server.
+
 use axum::{
-    body::Body,
-    extract::Request,
-    http::{Response, StatusCode, Uri},
-    response::IntoResponse,
+    body::{Body, Bytes},
+    http::{header, HeaderMap, HeaderValue, Method, Response, StatusCode, Uri},
 };
+use narayana_core::config::{NarayanaConfig, NetworkConfig, StaticFilesConfig};
+use parking_lot::{Mutex, RwLock};
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+use tracing::{debug, warn};
+
+use crate::config_manager::ConfigManager;
+
+/// Path prefixes owned by the API; unmatched requests under them are 404s, never the UI
+const RESERVED_PREFIXES: &[&str] = &["api/", "metrics", "health"];
+
+/// Cache-Control for assets with a content hash in their file name
+const IMMUTABLE_CACHE_CONTROL: &str = "public, max-age=31536000, immutable";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Encoding {
+    Identity,
+    Brotli,
+    Gzip,
+}
+
+impl Encoding {
+    /// Preference order for compressed responses
+    const COMPRESSED: [Encoding; 2] = [Encoding::Brotli, Encoding::Gzip];
+
+    fn token(self) -> &'static str {
+        match self {
+            Encoding::Identity => "identity",
+            Encoding::Brotli => "br",
+            Encoding::Gzip => "gzip",
+        }
+    }
+
+    /// Extension of a precompressed file next to the asset
+    fn extension(self) -> &'static str {
+        match self {
+            Encoding::Identity => "",
+            Encoding::Brotli => "br",
+            Encoding::Gzip => "gz",
+        }
+    }
+}
+
+/// Where the response body comes from
+enum Variant {
+    Identity,
+    /// `.br`/`.gz` file shipped next to the asset
+    Precompressed(Encoding, PathBuf),
+    /// Compressed by the server, kept in memory
+    Cached(Encoding),
+}
+
+/// Compressed variants kept in memory, evicted oldest first past `max_bytes`
+#[derive(Default)]
+struct CompressedCache {
+    entries: HashMap<(PathBuf, Encoding), (String, Bytes)>,
+    order: VecDeque<(PathBuf, Encoding)>,
+    bytes: usize,
+}
+
+impl CompressedCache {
+    fn get(&self, file: &Path, encoding: Encoding, etag: &str) -> Option<Bytes> {
+        self.entries
+            .get(&(file.to_path_buf(), encoding))
+            .filter(|(cached_etag, _)| cached_etag == etag)
+            .map(|(_, body)| body.clone())
+    }
+
+    fn insert(&mut self, file: PathBuf, encoding: Encoding, etag: String, body: Bytes, max_bytes: usize) {
+        if body.len() > max_bytes {
+            return;
+        }
+        let key = (file, encoding);
+        if let Some((_, old)) = self.entries.remove(&key) {
+            self.bytes -= old.len();
+            self.order.retain(|k| k != &key);
+        }
+        while self.bytes + body.len() > max_bytes {
+            let Some(oldest) = self.order.pop_front() else { break };
+            if let Some((_, old)) = self.entries.remove(&oldest) {
+                self.bytes -= old.len();
+            }
+        }
+        self.bytes += body.len();
+        self.order.push_back(key.clone());
+        self.entries.insert(key, (etag, body));
+    }
+
+    fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Web UI file server used as the router fallback
+pub struct StaticFiles {
+    config: RwLock<StaticFilesConfig>,
+    cache: Mutex<CompressedCache>,
+}
+
+impl StaticFiles {
+    pub fn new(network: &NetworkConfig) -> Self {
+        Self {
+            config: RwLock::new(network.static_files.clone()),
+            cache: Mutex::new(CompressedCache::default()),
+        }
+    }
+
+    pub fn update(&self, network: &NetworkConfig) {
+        let mut config = self.config.write();
+        if config.directory != network.static_files.directory {
+            self.cache.lock().clear();
+        }
+        *config = network.static_files.clone();
+    }
+
+    /// Serve from a new `static_files.directory` as soon as it is configured;
+    /// compressed copies from the old directory are discarded
+    pub async fn watch(self: &Arc<Self>, manager: &ConfigManager) {
+        let files = self.clone();
+        manager.subscribe(Box::new(move |config: &NarayanaConfig| {
+            files.update(&config.network);
+        })).await;
+    }
+
+    /// Bytes held by compressed variants
+    pub fn cached_bytes(&self) -> usize {
+        self.cache.lock().bytes
+    }
+
+    /// Compress every eligible asset ahead of the first request.
+    /// Returns the number of variants added to the cache.
+    pub async fn precompress_all(&self) -> usize {
+        let config = self.config.read().clone();
+        let Some(directory) = config.directory.clone() else { return 0 };
+        if !config.precompress {
+            return 0;
+        }
+        let files = tokio::task::spawn_blocking(move || {
+            let mut files = Vec::new();
+            collect_files(Path::new(&directory), &mut files);
+            files
+        })
+        .await
+        .unwrap_or_default();
+
+        let mut added = 0;
+        for file in files {
+            let Ok(metadata) = tokio::fs::metadata(&file).await else { continue };
+            if !is_compressible(&content_type(&file)) || (metadata.len() as usize) < config.precompress_min_bytes {
+                continue;
+            }
+            let base = etag_base(&metadata);
+            for encoding in Encoding::COMPRESSED {
+                if is_file(&precompressed_path(&file, encoding)).await {
+                    continue;
+                }
+                if self.compressed_body(&file, encoding, &base, &config).await.is_ok() {
+                    added += 1;
+                }
+            }
+        }
+        debug!("Precompressed {} static asset variants", added);
+        added
+    }
+
+    /// Answer a request that no API route matched
+    pub async fn serve(&self, method: &Method, uri: &Uri, headers: &HeaderMap) -> Response<Body> {
+        let Ok(path) = urlencoding::decode(uri.path().trim_start_matches('/')) else {
+            return plain(StatusCode::BAD_REQUEST, "Invalid path");
+        };
+
+        // SECURITY: Prevent path traversal attacks
+        if !is_safe_path(&path) {
+            return plain(StatusCode::BAD_REQUEST, "Invalid path");
+        }
+        if RESERVED_PREFIXES.iter().any(|prefix| path.starts_with(prefix)) {
+            return plain(StatusCode::NOT_FOUND, "Not found");
+        }
+        if method != Method::GET && method != Method::HEAD {
+            let mut response = plain(StatusCode::METHOD_NOT_ALLOWED, "Method not allowed");
+            response.headers_mut().insert(header::ALLOW, HeaderValue::from_static("GET, HEAD"));
+            return response;
+        }
+
+        let config = self.config.read().clone();
+        let Some(directory) = config.directory.as_deref() else {
+            return dev_server_redirect(&config.dev_server_url);
+        };
+        let root = match tokio::fs::canonicalize(directory).await {
+            Ok(root) => root,
+            Err(e) => {
+                warn!("Static files directory {} is not available: {}", directory, e);
+                return plain(StatusCode::NOT_FOUND, "Not found");
+            }
+        };
+
+        let file = match resolve(&root, &path, &config.index).await {
+            Some(file) => file,
+            // Client-side routes (no file extension) get the app shell
+            None if config.spa_fallback && !last_segment(&path).contains('.') => {
+                match resolve(&root, &config.index, &config.index).await {
+                    Some(index) => index,
+                    None => return plain(StatusCode::NOT_FOUND, "Not found"),
+                }
+            }
+            None => return plain(StatusCode::NOT_FOUND, "Not found"),
+        };
+        let is_index = file.file_name().and_then(|n| n.to_str()) == Some(config.index.as_str());
+
+        match self.serve_file(&file, is_index, method, headers, &config).await {
+            Ok(response) => response,
+            Err(e) => {
+                warn!("Failed to serve static file {}: {}", file.display(), e);
+                plain(StatusCode::INTERNAL_SERVER_ERROR, "Internal server error")
+            }
+        }
+    }
+
+    async fn serve_file(
+        &self,
+        file: &Path,
+        is_index: bool,
+        method: &Method,
+        headers: &HeaderMap,
+        config: &StaticFilesConfig,
+    ) -> std::io::Result<Response<Body>> {
+        let metadata = tokio::fs::metadata(file).await?;
+        let content_type = content_type(file);
+        let compressible = is_compressible(&content_type);
+        let base = etag_base(&metadata);
+
+        let variant = choose_variant(file, compressible, metadata.len(), headers, config).await;
+        let encoding = match &variant {
+            Variant::Identity => Encoding::Identity,
+            Variant::Precompressed(encoding, _) | Variant::Cached(encoding) => *encoding,
+        };
+        let etag = variant_etag(&base, encoding);
+
+        // The index must be revalidated so new deployments are picked up;
+        // hashed asset names change with their content
+        let cache_control = if is_index {
+            "no-cache".to_string()
+        } else if is_fingerprinted(file) {
+            IMMUTABLE_CACHE_CONTROL.to_string()
+        } else {
+            format!("public, max-age={}", config.max_age_secs)
+        };
+
+        let mut builder = Response::builder()
+            .header(header::ETAG, &etag)
+            .header(header::CACHE_CONTROL, cache_control);
+        if compressible {
+            builder = builder.header(header::VARY, "Accept-Encoding");
+        }
+
+        if etag_matches(headers, &etag) {
+            return Ok(builder
+                .status(StatusCode::NOT_MODIFIED)
+                .body(Body::empty())
+                .unwrap_or_else(|_| plain(StatusCode::INTERNAL_SERVER_ERROR, "Internal server error")));
+        }
+
+        let body = match variant {
+            Variant::Identity => Bytes::from(tokio::fs::read(file).await?),
+            Variant::Precompressed(_, path) => Bytes::from(tokio::fs::read(path).await?),
+            Variant::Cached(encoding) => self.compressed_body(file, encoding, &base, config).await?,
+        };
+        if encoding != Encoding::Identity {
+            builder = builder.header(header::CONTENT_ENCODING, encoding.token());
+        }
+        builder = builder
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, content_type)
+            .header(header::CONTENT_LENGTH, body.len());
+
+        let body = if method == Method::HEAD { Body::empty() } else { Body::from(body) };
+        Ok(builder
+            .body(body)
+            .unwrap_or_else(|_| plain(StatusCode::INTERNAL_SERVER_ERROR, "Internal server error")))
+    }
+
+    /// Compressed body from the cache, compressing (off the async runtime) on a miss
+    async fn compressed_body(
+        &self,
+        file: &Path,
+        encoding: Encoding,
+        base: &str,
+        config: &StaticFilesConfig,
+    ) -> std::io::Result<Bytes> {
+        let etag = variant_etag(base, encoding);
+        if let Some(body) = self.cache.lock().get(file, encoding, &etag) {
+            return Ok(body);
+        }
+        let data = tokio::fs::read(file).await?;
+        let body = tokio::task::spawn_blocking(move || compress(&data, encoding))
+            .await
+            .map_err(std::io::Error::other)??;
+        let body = Bytes::from(body);
+        self.cache.lock().insert(file.to_path_buf(), encoding, etag, body.clone(), config.max_cache_bytes);
+        Ok(body)
+    }
+}
+
+/// Pick the best representation the client accepts
+async fn choose_variant(
+    file: &Path,
+    compressible: bool,
+    len: u64,
+    headers: &HeaderMap,
+    config: &StaticFilesConfig,
+) -> Variant {
+    let accepted: Vec<Encoding> = Encoding::COMPRESSED
+        .into_iter()
+        .filter(|encoding| accepts_encoding(headers, encoding.token()))
+        .collect();
+    for encoding in &accepted {
+        let path = precompressed_path(file, *encoding);
+        if is_file(&path).await {
+            return Variant::Precompressed(*encoding, path);
+        }
+    }
+    match accepted.first() {
+        Some(encoding) if config.precompress && compressible && len as usize >= config.precompress_min_bytes => {
+            Variant::Cached(*encoding)
+        }
+        _ => Variant::Identity,
+    }
+}
+
+/// Find `path` under `root`: the file itself, or `index` inside a directory.
+/// Symlinks that lead outside `root` are rejected.
+async fn resolve(root: &Path, path: &str, index: &str) -> Option<PathBuf> {
+    let mut candidate = root.join(path);
+    if path.is_empty() || path.ends_with('/') || tokio::fs::metadata(&candidate).await.ok()?.is_dir() {
+        candidate = candidate.join(index);
+    }
+    let candidate = tokio::fs::canonicalize(&candidate).await.ok()?;
+    if !candidate.starts_with(root) || !is_file(&candidate).await {
+        return None;
+    }
+    Some(candidate)
+}
+
+fn is_safe_path(path: &str) -> bool {
+    let segments: Vec<&str> = path.split('/').collect();
+    segments.iter().enumerate().all(|(i, segment)| {
+        // Only a trailing empty segment ("dir/") is allowed
+        (!segment.is_empty() || i == segments.len() - 1)
+            && *segment != ".."
+            && *segment != "."
+            && !segment.contains('\\')
+            && !segment.contains('\0')
+    })
+}
+
+fn last_segment(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or("")
+}
+
+async fn is_file(path: &Path) -> bool {
+    tokio::fs::metadata(path).await.map(|m| m.is_file()).unwrap_or(false)
+}
+
+fn precompressed_path(file: &Path, encoding: Encoding) -> PathBuf {
+    let mut path = file.as_os_str().to_owned();
+    path.push(".");
+    path.push(encoding.extension());
+    PathBuf::from(path)
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        match entry.file_type() {
+            Ok(t) if t.is_dir() => collect_files(&path, files),
+            Ok(t) if t.is_file() => files.push(path),
+            _ => {}
+        }
+    }
+}
+
+/// Size and modification time; changes whenever the file is replaced
+fn etag_base(metadata: &std::fs::Metadata) -> String {
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    format!("{:x}-{:x}", metadata.len(), modified)
+}
 
-pub async fn serve_static(uri: Uri) -> impl IntoResponse {
-    let path = uri.path().trim_start_matches('/');
-    
-    // SECURITY: Prevent path traversal attacks
-    if path.contains("..") || path.contains("//") || path.contains("\\") {
-        return Response::builder()
-            .status(StatusCode::BAD_REQUEST)
-            .body(Body::from("Invalid path"))
-            .unwrap_or_else(|_| {
-                Response::builder()
-                    .status(StatusCode::INTERNAL_SERVER_ERROR)
-                    .body(Body::from("Internal server error"))
-                    .unwrap()
-            });
-    }
-    
-    // Serve UI - in production, this would read from dist/ directory
-    // For development, redirect to the Vite dev server
-    // SECURITY: Only handle non-API routes - API routes should be handled by the router
-    // If we reach this fallback handler, it means no route matched, so we should only
-    // serve static files for non-API paths
-    if path.is_empty() || (!path.starts_with("api/") && !path.starts_with("metrics") && !path.starts_with("health")) {
-        // Return a simple HTML page that redirects to the UI dev server
-        // In production, this would serve the actual built files
-        let html = r#"
-<!DOCTYPE html>
+fn variant_etag(base: &str, encoding: Encoding) -> String {
+    match encoding {
+        Encoding::Identity => format!("\"{}\"", base),
+        _ => format!("\"{}-{}\"", base, encoding.token()),
+    }
+}
+
+/// `If-None-Match` uses weak comparison
+fn etag_matches(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|tag| tag.trim())
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
+
+/// Whether `Accept-Encoding` allows `encoding` (an explicit `q=0` refuses it)
+fn accepts_encoding(headers: &HeaderMap, encoding: &str) -> bool {
+    let mut wildcard = false;
+    for value in headers.get_all(header::ACCEPT_ENCODING).iter().filter_map(|v| v.to_str().ok()) {
+        for part in value.split(',') {
+            let mut params = part.split(';');
+            let name = params.next().unwrap_or("").trim();
+            let q = params
+                .find_map(|p| p.trim().strip_prefix("q="))
+                .and_then(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            if name.eq_ignore_ascii_case(encoding) {
+                return q > 0.0;
+            }
+            if name == "*" {
+                wildcard = q > 0.0;
+            }
+        }
+    }
+    wildcard
+}
+
+fn content_type(file: &Path) -> String {
+    let mime = mime_guess::from_path(file).first_or_octet_stream();
+    let essence = mime.essence_str();
+    if mime.type_() == mime_guess::mime::TEXT || essence == "application/javascript" || essence == "application/json" {
+        format!("{}; charset=utf-8", essence)
+    } else {
+        essence.to_string()
+    }
+}
+
+fn is_compressible(content_type: &str) -> bool {
+    let essence = content_type.split(';').next().unwrap_or("");
+    essence.starts_with("text/")
+        || matches!(
+            essence,
+            "application/javascript" | "application/json" | "application/xml" | "application/wasm" | "image/svg+xml"
+        )
+}
+
+/// Build tools name assets like `index-BQ3k2x9a.js`; those never change in place
+fn is_fingerprinted(file: &Path) -> bool {
+    let Some(stem) = file.file_stem().and_then(|s| s.to_str()) else { return false };
+    let Some(split) = stem.rfind(['-', '.']) else { return false };
+    let hash = &stem[split + 1..];
+    hash.len() >= 8
+        && hash.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && hash.chars().any(|c| c.is_ascii_digit())
+}
+
+fn compress(data: &[u8], encoding: Encoding) -> std::io::Result<Vec<u8>> {
+    match encoding {
+        Encoding::Identity => Ok(data.to_vec()),
+        Encoding::Gzip => {
+            let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::best());
+            encoder.write_all(data)?;
+            encoder.finish()
+        }
+        Encoding::Brotli => {
+            let mut out = Vec::new();
+            {
+                let mut writer = brotli::CompressorWriter::new(&mut out, 4096, 11, 22);
+                writer.write_all(data)?;
+            }
+            Ok(out)
+        }
+    }
+}
+
+fn plain(status: StatusCode, message: &'static str) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .body(Body::from(message))
+        .unwrap_or_else(|_| {
+            Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .body(Body::from("Internal server error"))
+                .unwrap()
+        })
+}
+
+/// No built UI configured: send browsers to the dev server
+fn dev_server_redirect(url: &str) -> Response<Body> {
+    let url: String = url
+        .chars()
+        .map(|c| match c {
+            '"' => "&quot;".to_string(),
+            '<' => "&lt;".to_string(),
+            '>' => "&gt;".to_string(),
+            '&' => "&amp;".to_string(),
+            c => c.to_string(),
+        })
+        .collect();
+    let html = format!(
+        r#"<!DOCTYPE html>
 <html>
 <head>
     <title>NarayanaDB UI</title>
-    <meta http-equiv="refresh" content="0; url=http://localhost:3000">
-    <script>
-        window.location.href = 'http://localhost:3000';
-    </script>
+    <meta http-equiv="refresh" content="0; url={url}">
 </head>
 <body>
-    <p>Redirecting to NarayanaDB UI... <a href="http://localhost:3000">Click here</a></p>
+    <p>Redirecting to NarayanaDB UI... <a href="{url}">Click here</a></p>
 </body>
 </html>
-        "#;
-        
-        // SECURITY: Handle builder errors gracefully instead of unwrap
-        Response::builder()
-            .status(StatusCode::OK)
-            .header("content-type", "text/html")
-            .body(Body::from(html))
-            .unwrap_or_else(|_| {
-                Response::builder()
-                    .status(StatusCode::INTERNAL_SERVER_ERROR)
-                    .body(Body::from("Internal server error"))
-                    .unwrap()
-            })
-    } else {
-        // API routes that don't match should return 404
-        // This should not happen if routes are properly registered
-        Response::builder()
-            .status(StatusCode::NOT_FOUND)
-            .body(Body::from("Not found"))
-            .unwrap_or_else(|_| {
-                Response::builder()
-                    .status(StatusCode::INTERNAL_SERVER_ERROR)
-                    .body(Body::from("Internal server error"))
-                    .unwrap()
-            })
-    }
+"#
+    );
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "text/html; charset=utf-8")
+        .header(header::CACHE_CONTROL, "no-cache")
+        .body(Body::from(html))
+        .unwrap_or_else(|_| plain(StatusCode::INTERNAL_SERVER_ERROR, "Internal server error"))
 }
-
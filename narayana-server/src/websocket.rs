@@ -3,8 +3,9 @@
 use narayana_api::websocket::{ConnectionId, WsMessage};
 use crate::websocket_manager::WebSocketManager;
 use crate::websocket_bridge::WebSocketBridge;
-use crate::security::TokenManager;
+use crate::security::{Claims, TokenManager};
 use narayana_storage::{ColumnStore, database_manager::DatabaseManager};
+use narayana_core::wire::{WireFormat, WireFrame};
 use axum::{
     extract::{
         ws::{Message, WebSocket, WebSocketUpgrade},
@@ -33,6 +34,17 @@ pub struct WebSocketState {
 #[derive(Deserialize)]
 pub struct WsQueryParams {
     pub token: Option<String>,
+    /// Resume a previous session (token from its `session` message)
+    pub resume_token: Option<String>,
+    /// Last event sequence number the client received
+    pub last_seq: Option<u64>,
+}
+
+impl WsQueryParams {
+    /// Resume request, if the client asked for one
+    pub fn resume(&self) -> Option<(String, u64)> {
+        self.resume_token.clone().map(|token| (token, self.last_seq.unwrap_or(0)))
+    }
 }
 
 /// WebSocket upgrade handler
@@ -42,10 +54,10 @@ pub async fn websocket_handler(
     State(state): State<Arc<WebSocketState>>,
 ) -> Response {
     // Validate token if provided
-    let user_id = if let Some(token) = &params.token {
-        // Verify token and extract user_id
+    let claims = if let Some(token) = &params.token {
+        // Verify token and extract its claims
         match state.token_manager.verify_token(token) {
-            Ok(claims) => Some(claims.sub),
+            Ok(claims) => Some(claims),
             Err(e) => {
                 warn!("Invalid WebSocket token: {}", e);
                 None
@@ -55,30 +67,57 @@ pub async fn websocket_handler(
         None
     };
 
-    ws.on_upgrade(move |socket| handle_socket(socket, state, user_id))
+    let resume = params.resume();
+    ws.protocols(state.manager.supported_protocols().iter().copied())
+        .on_upgrade(move |socket| handle_socket(socket, state, claims, resume))
 }
 
 /// Handle WebSocket connection
 pub(crate) async fn handle_socket(
     socket: WebSocket,
     state: Arc<WebSocketState>,
-    user_id: Option<String>,
+    claims: Option<Claims>,
+    resume: Option<(String, u64)>,
 ) {
-    let connection_id = Uuid::new_v4().to_string();
-    info!("WebSocket connection established: {} (user: {:?})", connection_id, user_id);
+    let user_id = claims.as_ref().map(|c| c.sub.clone());
+    // JSON text unless the client negotiated a binary sub-protocol
+    let wire_format = WireFormat::negotiated(socket.protocol().and_then(|p| p.to_str().ok()));
 
     // Create channel for sending messages to this connection
     let (tx, mut rx) = mpsc::unbounded_channel::<WsMessage>();
 
-    // Register connection with manager
-    if let Err(e) = state.manager.register_connection(
-        connection_id.clone(),
-        user_id.clone(),
-        tx.clone(),
-    ) {
-        error!("Failed to register WebSocket connection {}: {}", connection_id, e);
-        return;
-    }
+    // Resume the previous session if possible; the manager queues `resumed`
+    // and the missed events on `tx`. Otherwise start a new session.
+    let resumed = resume.and_then(|(token, last_seq)| {
+        match state.manager.resume_connection(&token, &user_id, last_seq, tx.clone()) {
+            Ok(outcome) => Some(outcome.connection_id),
+            Err(e) => {
+                debug!("WebSocket session resume rejected: {}", e);
+                None
+            }
+        }
+    });
+    let connection_id = match resumed {
+        Some(connection_id) => connection_id,
+        None => {
+            let connection_id = Uuid::new_v4().to_string();
+            // Register connection with manager
+            if let Err(e) = state.manager.register_connection(
+                connection_id.clone(),
+                user_id.clone(),
+                tx.clone(),
+            ) {
+                error!("Failed to register WebSocket connection {}: {}", connection_id, e);
+                return;
+            }
+            if let Some(session) = state.manager.session_message(&connection_id) {
+                state.manager.send_to_connection(&connection_id, session);
+            }
+            connection_id
+        }
+    };
+    state.manager.set_wire_format(&connection_id, wire_format);
+    info!("WebSocket connection established: {} (user: {:?}, protocol: {})", connection_id, user_id, wire_format.subprotocol());
 
     // Split socket into sender and receiver using futures_util
     let (mut sender, mut receiver) = socket.split();
@@ -88,15 +127,16 @@ pub(crate) async fn handle_socket(
     let manager_clone = state.manager.clone();
     let send_task = tokio::spawn(async move {
         while let Some(message) = rx.recv().await {
-            let json = match message.to_json() {
-                Ok(json) => json,
+            let frame = match wire_format.encode(&message) {
+                Ok(WireFrame::Text(json)) => Message::Text(json),
+                Ok(WireFrame::Binary(bytes)) => Message::Binary(bytes),
                 Err(e) => {
                     error!("Failed to serialize WebSocket message: {}", e);
                     continue;
                 }
             };
 
-            if let Err(e) = sender.send(Message::Text(json)).await {
+            if let Err(e) = sender.send(frame).await {
                 warn!("Failed to send WebSocket message to {}: {}", connection_id_clone, e);
                 break;
             }
@@ -106,22 +146,29 @@ pub(crate) async fn handle_socket(
     // Handle incoming messages from client
     let connection_id_clone2 = connection_id.clone();
     let manager_clone2 = state.manager.clone();
-    let storage_clone = state.storage.clone();
+    // Table queries on this connection see the rows and masks the caller's
+    // claims allow
+    let storage_clone = state.db_manager.row_security().secure(
+        state.storage.clone(),
+        claims.as_ref().map(|c| c.principal()).unwrap_or_default(),
+    );
     let db_manager_clone = state.db_manager.clone();
     let recv_task = tokio::spawn(async move {
         while let Some(msg) = receiver.next().await {
             match msg {
                 Ok(Message::Text(text)) => {
-                    if let Err(e) = handle_message(&text, &connection_id_clone2, &manager_clone2, storage_clone.clone(), db_manager_clone.clone()).await {
+                    if let Err(e) = handle_message(WireFrame::Text(text), wire_format, &connection_id_clone2, &manager_clone2, storage_clone.clone(), db_manager_clone.clone()).await {
                         error!("Error handling message from {}: {}", connection_id_clone2, e);
                     }
                 }
-                Ok(Message::Binary(_)) => {
-                    warn!("Received binary message from {}, ignoring", connection_id_clone2);
+                Ok(Message::Binary(bytes)) => {
+                    if let Err(e) = handle_message(WireFrame::Binary(bytes), wire_format, &connection_id_clone2, &manager_clone2, storage_clone.clone(), db_manager_clone.clone()).await {
+                        error!("Error handling message from {}: {}", connection_id_clone2, e);
+                    }
                 }
                 Ok(Message::Close(_)) => {
                     debug!("WebSocket connection {} closed by client", connection_id_clone2);
-                    break;
+                    return true;
                 }
                 Ok(Message::Ping(_data)) => {
                     // Respond to ping with pong
@@ -137,26 +184,30 @@ pub(crate) async fn handle_socket(
                 }
             }
         }
+        false
     });
 
     // Wait for either task to complete
-    tokio::select! {
+    let closed_by_client = tokio::select! {
         _ = send_task => {
             debug!("Send task completed for connection {}", connection_id);
+            false
         }
-        _ = recv_task => {
+        closed = recv_task => {
             debug!("Receive task completed for connection {}", connection_id);
+            closed.unwrap_or(false)
         }
-    }
+    };
 
-    // Clean up connection
-    state.manager.unregister_connection(&connection_id);
+    // Clean up connection; unless the client closed it, the session stays resumable
+    state.manager.release_connection(&connection_id, &tx, closed_by_client);
     info!("WebSocket connection closed: {}", connection_id);
 }
 
 /// Handle incoming WebSocket message
 async fn handle_message(
-    text: &str,
+    frame: WireFrame,
+    wire_format: WireFormat,
     connection_id: &ConnectionId,
     manager: &Arc<WebSocketManager>,
     storage: Arc<dyn ColumnStore>,
@@ -166,7 +217,7 @@ async fn handle_message(
     manager.update_activity(connection_id);
 
     // Parse message
-    let message = match WsMessage::from_json(text) {
+    let message = match wire_format.decode::<WsMessage>(&frame) {
         Ok(msg) => msg,
         Err(e) => {
             error!("Failed to parse WebSocket message from {}: {}", connection_id, e);
@@ -212,6 +263,9 @@ async fn handle_message(
                 }
             }
         }
+        WsMessage::Ack { seq } => {
+            manager.acknowledge(connection_id, seq);
+        }
         WsMessage::Ping { id } => {
             debug!("Received ping from {} (id: {:?})", connection_id, id);
             let pong_msg = WsMessage::Pong { id };
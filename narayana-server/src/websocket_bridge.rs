@@ -229,6 +229,22 @@ impl WebSocketBridge {
                                     .as_secs();
                                 (format!("streams:{}:events", stream_id_safe), ts)
                             }
+                            StreamEvent::Gap { stream_id, .. } | StreamEvent::Dropout { stream_id, .. } => {
+                                let stream_id_safe: String = stream_id
+                                    .chars()
+                                    .filter(|c| !c.is_control() && *c != ':' && *c != '/' && *c != '\\')
+                                    .take(256)
+                                    .collect();
+                                if stream_id_safe.is_empty() {
+                                    warn!("Stream ID became empty after sanitization");
+                                    continue;
+                                }
+                                let ts = std::time::SystemTime::now()
+                                    .duration_since(std::time::UNIX_EPOCH)
+                                    .unwrap_or_default()
+                                    .as_secs();
+                                (format!("streams:{}:health", stream_id_safe), ts)
+                            }
                             StreamEvent::Error { stream_id, error: _ } => {
                                 let stream_id_safe: String = stream_id
                                     .chars()
@@ -535,6 +551,13 @@ impl WebSocketBridge {
         self.handles.write().push(handle);
     }
 
+    /// Number of bridge tasks still running, and the number started
+    pub fn task_status(&self) -> (usize, usize) {
+        let handles = self.handles.read();
+        let running = handles.iter().filter(|h| !h.is_finished()).count();
+        (running, handles.len())
+    }
+
     /// Shutdown all bridges
     pub fn shutdown(&self) {
         info!("Shutting down WebSocket event bridges...");
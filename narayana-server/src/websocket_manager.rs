@@ -2,9 +2,10 @@
 // Tracks active connections, subscriptions, and routes messages
 
 use narayana_api::websocket::{ConnectionId, Channel, WsMessage, EventFilter};
-use std::collections::{HashMap, HashSet};
+use narayana_core::wire::{WireFormat, JSON_SUBPROTOCOL};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use tokio::sync::mpsc;
 use tracing::{info, warn, error, debug};
 use uuid::Uuid;
@@ -17,6 +18,29 @@ pub struct ConnectionState {
     pub subscriptions: HashSet<Channel>,
     pub created_at: u64,
     pub last_activity: u64,
+    /// Encoding negotiated for the connection's socket
+    pub wire_format: WireFormat,
+}
+
+/// Resumable session for one logical client. Survives the socket for
+/// `session_ttl_secs` so a reconnecting client gets the events it missed.
+struct Session {
+    resume_token: String,
+    user_id: Option<String>,
+    /// Sequence number of the last event stamped for this session
+    last_seq: u64,
+    /// Stamped events, oldest first, bounded by `replay_buffer_size`
+    buffer: VecDeque<WsMessage>,
+    /// Set while no socket is attached
+    detached_at: Option<u64>,
+}
+
+/// Result of resuming a session on a new socket
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResumeOutcome {
+    pub connection_id: ConnectionId,
+    pub replayed: usize,
+    pub missed: u64,
 }
 
 /// WebSocket connection manager
@@ -33,6 +57,12 @@ pub struct WebSocketManager {
     /// Per-connection message senders: connection_id -> sender
     message_senders: Arc<RwLock<HashMap<ConnectionId, mpsc::UnboundedSender<WsMessage>>>>,
     
+    /// Resumable sessions: connection_id -> session (attached or detached)
+    sessions: Arc<Mutex<HashMap<ConnectionId, Session>>>,
+    
+    /// Resume tokens: token -> connection_id
+    resume_tokens: Arc<RwLock<HashMap<String, ConnectionId>>>,
+    
     /// Configuration
     config: WebSocketConfig,
 }
@@ -45,6 +75,12 @@ pub struct WebSocketConfig {
     pub ping_interval_secs: u64,
     pub connection_timeout_secs: u64,
     pub enable_compression: bool,
+    /// Events kept per session for replay after a reconnect (0 disables resumption)
+    pub replay_buffer_size: usize,
+    /// How long a disconnected session can be resumed
+    pub session_ttl_secs: u64,
+    /// Offer the MessagePack/CBOR sub-protocols (JSON is always available)
+    pub binary_protocols: bool,
 }
 
 impl Default for WebSocketConfig {
@@ -55,6 +91,9 @@ impl Default for WebSocketConfig {
             ping_interval_secs: 30,
             connection_timeout_secs: 300,
             enable_compression: true,
+            replay_buffer_size: 256,
+            session_ttl_secs: 60,
+            binary_protocols: true,
         }
     }
 }
@@ -67,10 +106,23 @@ impl WebSocketManager {
             connection_subscriptions: Arc::new(RwLock::new(HashMap::new())),
             channel_subscriptions: Arc::new(RwLock::new(HashMap::new())),
             message_senders: Arc::new(RwLock::new(HashMap::new())),
+            sessions: Arc::new(Mutex::new(HashMap::new())),
+            resume_tokens: Arc::new(RwLock::new(HashMap::new())),
             config,
         }
     }
 
+    fn now_secs() -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs()
+    }
+
+    fn resumable(&self) -> bool {
+        self.config.replay_buffer_size > 0 && self.config.session_ttl_secs > 0
+    }
+
     /// Register a new connection
     pub fn register_connection(
         &self,
@@ -100,6 +152,7 @@ impl WebSocketManager {
             subscriptions: HashSet::new(),
             created_at: now,
             last_activity: now,
+            wire_format: WireFormat::Json,
         };
 
         self.connections.write().insert(connection_id.clone(), state);
@@ -108,12 +161,156 @@ impl WebSocketManager {
             .insert(connection_id.clone(), HashSet::new());
         self.message_senders.write().insert(connection_id.clone(), sender);
 
+        // Every connection starts a fresh session that a later socket may resume
+        let resume_token = Uuid::new_v4().simple().to_string();
+        let previous = self.sessions.lock().insert(connection_id.clone(), Session {
+            resume_token: resume_token.clone(),
+            user_id,
+            last_seq: 0,
+            buffer: VecDeque::new(),
+            detached_at: None,
+        });
+        let mut tokens = self.resume_tokens.write();
+        if let Some(previous) = previous {
+            tokens.remove(&previous.resume_token);
+        }
+        tokens.insert(resume_token, connection_id.clone());
+        drop(tokens);
+
         info!("WebSocket connection registered: {}", connection_id);
         Ok(())
     }
 
-    /// Unregister a connection
+    /// Session announcement for a connection: its resume token and last sequence number
+    pub fn session_message(&self, connection_id: &ConnectionId) -> Option<WsMessage> {
+        self.sessions.lock().get(connection_id).map(|session| WsMessage::Session {
+            resume_token: session.resume_token.clone(),
+            last_seq: session.last_seq,
+        })
+    }
+
+    /// Attach a new socket to a detached (or still attached but stale) session.
+    /// Sends `resumed` and then every buffered event after `last_seq` to `sender`.
+    pub fn resume_connection(
+        &self,
+        resume_token: &str,
+        user_id: &Option<String>,
+        last_seq: u64,
+        sender: mpsc::UnboundedSender<WsMessage>,
+    ) -> Result<ResumeOutcome, String> {
+        let connection_id = self.resume_tokens.read().get(resume_token).cloned()
+            .ok_or_else(|| "Unknown or expired resume token".to_string())?;
+
+        // Lock order: sessions, then connections/senders (same as broadcast)
+        let mut sessions = self.sessions.lock();
+        let session = sessions.get_mut(&connection_id)
+            .ok_or_else(|| "Unknown or expired resume token".to_string())?;
+        if &session.user_id != user_id {
+            return Err("Resume token belongs to a different user".to_string());
+        }
+        if last_seq > session.last_seq {
+            return Err(format!("last_seq {} is ahead of the session ({})", last_seq, session.last_seq));
+        }
+        let attached = self.connections.read().contains_key(&connection_id);
+        if !attached && self.connections.read().len() >= self.config.max_connections {
+            return Err(format!("Maximum connections ({}) reached", self.config.max_connections));
+        }
+
+        // Drop what the client already has; what is left gets replayed
+        while session.buffer.front().and_then(message_seq).is_some_and(|seq| seq <= last_seq) {
+            session.buffer.pop_front();
+        }
+        let oldest = session.buffer.front().and_then(message_seq).unwrap_or(session.last_seq + 1);
+        let missed = oldest.saturating_sub(last_seq + 1);
+        let replayed = session.buffer.len();
+
+        let _ = sender.send(WsMessage::Resumed { last_seq: session.last_seq, replayed, missed });
+        for message in &session.buffer {
+            let _ = sender.send(message.clone());
+        }
+        session.detached_at = None;
+
+        let now = Self::now_secs();
+        self.connections.write().entry(connection_id.clone())
+            .and_modify(|state| state.last_activity = now)
+            .or_insert_with(|| ConnectionState {
+                id: connection_id.clone(),
+                user_id: user_id.clone(),
+                subscriptions: HashSet::new(),
+                created_at: now,
+                last_activity: now,
+                wire_format: WireFormat::Json,
+            });
+        // Replaces the sender of a stale socket that has not noticed it is gone
+        self.message_senders.write().insert(connection_id.clone(), sender);
+
+        info!("WebSocket session resumed: {} (replayed {}, missed {})", connection_id, replayed, missed);
+        Ok(ResumeOutcome { connection_id, replayed, missed })
+    }
+
+    /// Drop buffered events the client has confirmed
+    pub fn acknowledge(&self, connection_id: &ConnectionId, seq: u64) {
+        if let Some(session) = self.sessions.lock().get_mut(connection_id) {
+            while session.buffer.front().and_then(message_seq).is_some_and(|s| s <= seq) {
+                session.buffer.pop_front();
+            }
+        }
+    }
+
+    /// Called when a socket ends. Only acts if `sender` is still the connection's
+    /// sender (a resumed socket may have taken over). A client-initiated close
+    /// ends the session; anything else keeps it resumable.
+    pub fn release_connection(
+        &self,
+        connection_id: &ConnectionId,
+        sender: &mpsc::UnboundedSender<WsMessage>,
+        closed_by_client: bool,
+    ) {
+        let current = self.message_senders.read().get(connection_id)
+            .is_some_and(|s| s.same_channel(sender));
+        if !current {
+            return;
+        }
+        if closed_by_client {
+            self.remove_connection(connection_id);
+        } else {
+            self.unregister_connection(connection_id);
+        }
+    }
+
+    /// Unregister a connection. When resumption is enabled the session and its
+    /// subscriptions are kept for `session_ttl_secs` and keep buffering events.
     pub fn unregister_connection(&self, connection_id: &ConnectionId) {
+        if !self.resumable() || !self.sessions.lock().contains_key(connection_id) {
+            self.remove_connection(connection_id);
+            return;
+        }
+
+        self.connections.write().remove(connection_id);
+        self.message_senders.write().remove(connection_id);
+        let now = Self::now_secs();
+        let evict = {
+            let mut sessions = self.sessions.lock();
+            if let Some(session) = sessions.get_mut(connection_id) {
+                session.detached_at = Some(now);
+            }
+            // Bound detached sessions so reconnect storms cannot grow memory without limit
+            let mut detached: Vec<(u64, ConnectionId)> = sessions.iter()
+                .filter_map(|(id, s)| s.detached_at.map(|at| (at, id.clone())))
+                .collect();
+            let excess = detached.len().saturating_sub(self.config.max_connections);
+            detached.sort();
+            detached.into_iter().take(excess).map(|(_, id)| id).collect::<Vec<_>>()
+        };
+        for id in evict {
+            self.remove_connection(&id);
+        }
+
+        info!("WebSocket connection detached: {}", connection_id);
+    }
+
+    /// Remove a connection and its session entirely
+    pub fn remove_connection(&self, connection_id: &ConnectionId) {
         // Remove from all channels
         let subscriptions = {
             let subs = self.connection_subscriptions.read();
@@ -130,10 +327,34 @@ impl WebSocketManager {
         self.connections.write().remove(connection_id);
         self.connection_subscriptions.write().remove(connection_id);
         self.message_senders.write().remove(connection_id);
+        if let Some(session) = self.sessions.lock().remove(connection_id) {
+            self.resume_tokens.write().remove(&session.resume_token);
+        }
 
         info!("WebSocket connection unregistered: {}", connection_id);
     }
 
+    /// Stamp events with the session's next sequence number and buffer them.
+    /// Returns the message to send, or None if the session is detached (buffered only).
+    fn sequence(&self, session: Option<&mut Session>, message: WsMessage) -> Option<WsMessage> {
+        let Some(session) = session else {
+            return Some(message);
+        };
+        let message = match message {
+            WsMessage::Event { channel, event, timestamp, .. } if self.resumable() => {
+                session.last_seq += 1;
+                let stamped = WsMessage::Event { channel, event, timestamp, seq: Some(session.last_seq) };
+                if session.buffer.len() >= self.config.replay_buffer_size {
+                    session.buffer.pop_front();
+                }
+                session.buffer.push_back(stamped.clone());
+                stamped
+            }
+            other => other,
+        };
+        session.detached_at.is_none().then_some(message)
+    }
+
     /// Subscribe connection to a channel
     pub fn subscribe(
         &self,
@@ -293,6 +514,10 @@ impl WebSocketManager {
             return false;
         }
 
+        let mut sessions = self.sessions.lock();
+        let Some(message) = self.sequence(sessions.get_mut(connection_id), message) else {
+            return false;
+        };
         let senders = self.message_senders.read();
         if let Some(sender) = senders.get(connection_id) {
             // Try to send - if channel is closed, send will fail
@@ -308,7 +533,8 @@ impl WebSocketManager {
         }
     }
 
-    /// Broadcast message to all subscribers of a channel
+    /// Broadcast message to all subscribers of a channel. Detached sessions
+    /// subscribed to the channel buffer the event for replay.
     pub fn broadcast_to_channel(&self, channel: &Channel, message: WsMessage) -> usize {
         // Limit broadcast size to prevent memory exhaustion
         const MAX_BROADCAST_SUBSCRIBERS: usize = 10_000;
@@ -332,21 +558,25 @@ impl WebSocketManager {
             warn!("Channel {} has more than {} subscribers, limiting broadcast", channel, MAX_BROADCAST_SUBSCRIBERS);
         }
 
+        // Sessions are locked for the whole broadcast so sequence numbers and
+        // replays on resume stay in order
+        let mut sessions = self.sessions.lock();
         let senders = self.message_senders.read();
-        let connections = self.connections.read();
         let mut sent_count = 0;
         let mut dead_connections = Vec::new();
 
         for connection_id in subscribers_to_process {
-            // Check if connection still exists
-            if !connections.contains_key(&connection_id) {
+            let session = sessions.get_mut(&connection_id);
+            if session.is_none() {
                 dead_connections.push(connection_id.clone());
                 continue;
             }
+            // Clone message for each subscriber
+            let Some(msg) = self.sequence(session, message.clone()) else {
+                continue;
+            };
 
             if let Some(sender) = senders.get(&connection_id) {
-                // Clone message for each subscriber
-                let msg = message.clone();
                 match sender.send(msg) {
                     Ok(_) => {
                         sent_count += 1;
@@ -362,7 +592,7 @@ impl WebSocketManager {
         // Clean up dead connections (but don't hold locks while doing so)
         if !dead_connections.is_empty() {
             drop(senders);
-            drop(connections);
+            drop(sessions);
             for conn_id in dead_connections {
                 self.unregister_connection(&conn_id);
             }
@@ -396,11 +626,41 @@ impl WebSocketManager {
             .unwrap_or_default()
     }
 
+    /// Sub-protocols to offer during the WebSocket upgrade
+    pub fn supported_protocols(&self) -> &'static [&'static str] {
+        if self.config.binary_protocols {
+            &WireFormat::SUBPROTOCOLS
+        } else {
+            &[JSON_SUBPROTOCOL]
+        }
+    }
+
+    /// Record the encoding negotiated for a connection's current socket
+    pub fn set_wire_format(&self, connection_id: &ConnectionId, format: WireFormat) {
+        if let Some(state) = self.connections.write().get_mut(connection_id) {
+            state.wire_format = format;
+        }
+    }
+
+    /// Connections per negotiated encoding
+    pub fn wire_format_counts(&self) -> HashMap<WireFormat, usize> {
+        let mut counts = HashMap::new();
+        for state in self.connections.read().values() {
+            *counts.entry(state.wire_format).or_insert(0) += 1;
+        }
+        counts
+    }
+
     /// Get connection count
     pub fn connection_count(&self) -> usize {
         self.connections.read().len()
     }
 
+    /// Number of disconnected sessions that can still be resumed
+    pub fn detached_session_count(&self) -> usize {
+        self.sessions.lock().values().filter(|s| s.detached_at.is_some()).count()
+    }
+
     /// Get subscription count for a channel
     pub fn channel_subscription_count(&self, channel: &Channel) -> usize {
         let subs = self.channel_subscriptions.read();
@@ -440,8 +700,19 @@ impl WebSocketManager {
             self.unregister_connection(connection_id);
         }
 
-        if !stale_connections.is_empty() {
-            info!("Cleaned up {} stale connections", stale_connections.len());
+        // Sessions nobody resumed in time
+        let ttl = self.config.session_ttl_secs;
+        let expired: Vec<ConnectionId> = self.sessions.lock()
+            .iter()
+            .filter(|(_, s)| s.detached_at.is_some_and(|at| now.saturating_sub(at) > ttl))
+            .map(|(id, _)| id.clone())
+            .collect();
+        for connection_id in &expired {
+            self.remove_connection(connection_id);
+        }
+
+        if !stale_connections.is_empty() || !expired.is_empty() {
+            info!("Cleaned up {} stale connections and {} expired sessions", stale_connections.len(), expired.len());
         }
 
         stale_connections.len()
@@ -497,3 +768,9 @@ impl WebSocketManager {
     }
 }
 
+fn message_seq(message: &WsMessage) -> Option<u64> {
+    match message {
+        WsMessage::Event { seq, .. } => *seq,
+        _ => None,
+    }
+}
@@ -39,6 +39,35 @@ pub struct SpeechConfig {
 
     /// Queue size for speech requests
     pub queue_size: usize,
+
+    /// Adjust rate, pitch and volume to the shared emotion state
+    pub emotional_prosody: bool,
+
+    /// Local audio playback
+    pub playback: PlaybackConfig,
+}
+
+/// Audio playback configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlaybackConfig {
+    /// Play synthesized speech on this machine (off by default)
+    pub enabled: bool,
+
+    /// Output device id (None for the system default)
+    pub device: Option<String>,
+
+    /// Playback volume used when an utterance doesn't set one (0.0-1.0, default 1.0)
+    pub volume: f32,
+
+    /// Lower background audio while speaking
+    pub ducking: bool,
+
+    /// Background audio gain while ducked (0.0-1.0, default 0.3)
+    pub duck_level: f32,
+
+    /// Quiet time after speech before background audio is restored
+    pub duck_release_ms: u64,
 }
 
 /// TTS Engine type
@@ -142,8 +171,64 @@ impl Default for SpeechConfig {
             enable_cache: true,
             max_cache_size_mb: 100,
             queue_size: 100,
+            emotional_prosody: true,
+            playback: PlaybackConfig::default(),
+        }
+    }
+}
+
+impl Default for PlaybackConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            device: None,
+            volume: 1.0,
+            ducking: true,
+            duck_level: 0.3,
+            duck_release_ms: 300,
+        }
+    }
+}
+
+impl PlaybackConfig {
+    /// Validate playback configuration
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0.0..=1.0).contains(&self.volume) {
+            return Err("Playback volume must be between 0.0 and 1.0".to_string());
+        }
+
+        if !(0.0..=1.0).contains(&self.duck_level) {
+            return Err("Duck level must be between 0.0 and 1.0".to_string());
         }
+
+        if self.duck_release_ms > 60_000 {
+            return Err("Duck release too large (max 60000 ms)".to_string());
+        }
+
+        if let Some(ref device) = self.device {
+            validate_device_id(device)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Validate an output device id before it is passed to a system player
+pub(crate) fn validate_device_id(device: &str) -> Result<(), String> {
+    if device.is_empty() {
+        return Err("Output device id cannot be empty".to_string());
+    }
+
+    if device.len() > 256 {
+        return Err("Output device id too long (max 256 chars)".to_string());
+    }
+
+    // Ids become command-line arguments; keep them from reading as options
+    if device.starts_with('-') || device.chars().any(|c| c == '\0' || c.is_control()) {
+        return Err("Output device id contains invalid characters".to_string());
     }
+
+    Ok(())
 }
 
 impl Default for VoiceConfig {
@@ -264,6 +349,8 @@ impl SpeechConfig {
         // Validate voice config
         self.voice.validate()?;
 
+        self.playback.validate()?;
+
         if let Some(api_config) = &self.api_config {
             if api_config.endpoint.is_empty() {
                 return Err("API endpoint cannot be empty".to_string());
@@ -0,0 +1,33 @@
+//! Emotional prosody
+//!
+//! Maps the shared emotion state from narayana-wld onto speech rate, pitch
+//! and volume: arousal speeds speech up and raises pitch and volume, positive
+//! valence lifts pitch, dominance adds volume.
+
+use crate::config::SpeechConfig;
+use narayana_wld::emotion::EmotionState;
+
+/// Prosody changes smaller than these don't rebuild the synthesizer
+const RATE_STEP: u32 = 5;
+const PITCH_STEP: f32 = 0.05;
+const VOLUME_STEP: f32 = 0.05;
+
+/// Rate, pitch and volume adjusted for an emotion state
+pub fn prosody_for_emotion(base: &SpeechConfig, emotion: &EmotionState) -> SpeechConfig {
+    let arousal = emotion.arousal.clamp(-1.0, 1.0) as f32;
+    let valence = emotion.valence.clamp(-1.0, 1.0) as f32;
+    let dominance = emotion.dominance.clamp(-1.0, 1.0) as f32;
+
+    let mut config = base.clone();
+    config.rate = ((base.rate as f32) * (1.0 + 0.25 * arousal)).round().clamp(0.0, 500.0) as u32;
+    config.pitch = (base.pitch + 0.2 * valence + 0.15 * arousal).clamp(-1.0, 1.0);
+    config.volume = (base.volume * (1.0 + 0.15 * arousal + 0.1 * dominance)).clamp(0.0, 1.0);
+    config
+}
+
+/// Whether two configs differ audibly in prosody
+pub(crate) fn prosody_changed(a: &SpeechConfig, b: &SpeechConfig) -> bool {
+    a.rate.abs_diff(b.rate) >= RATE_STEP
+        || (a.pitch - b.pitch).abs() >= PITCH_STEP
+        || (a.volume - b.volume).abs() >= VOLUME_STEP
+}
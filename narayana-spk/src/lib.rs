@@ -4,6 +4,7 @@
 //! - Native TTS engines (platform-specific)
 //! - Optional API-based TTS providers
 //! - Integration with narayana-wld for brain-controlled speech
+//! - Local playback with device selection and ducking
 //! - Configurable and off by default
 
 pub mod error;
@@ -12,11 +13,15 @@ pub mod engines;
 pub mod speech_adapter;
 pub mod synthesizer;
 pub mod cpl_integration;
+pub mod emotion;
+pub mod playback;
 
 pub use error::SpeechError;
-pub use config::{SpeechConfig, VoiceConfig, TtsEngine};
+pub use config::{SpeechConfig, VoiceConfig, TtsEngine, PlaybackConfig};
 pub use speech_adapter::SpeechAdapter;
 pub use synthesizer::SpeechSynthesizer;
+pub use emotion::prosody_for_emotion;
+pub use playback::{AudioPlayer, AudioOutput, AudioDevice, PlaybackProgress, PlaybackState};
 pub use cpl_integration::{speech_config_from_cpl, create_speech_adapter_from_cpl};
 pub use engines::TtsEngine as TtsEngineTrait;
 
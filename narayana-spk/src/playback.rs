@@ -0,0 +1,676 @@
+//! Audio playback
+//!
+//! Plays synthesized speech through the system's command-line players
+//! (PulseAudio or ALSA on Linux, afplay on macOS, PowerShell on Windows) on a
+//! selectable output device, one utterance after another, each at its own
+//! volume. While speech plays, background audio is ducked: registered
+//! `DuckingTarget`s and `ducking_gain()` watchers drop to `duck_level` and
+//! come back once speech has been quiet for `duck_release_ms`. Every
+//! utterance's progress can be queried, so the avatar and CPL know exactly
+//! when speech finished.
+
+use crate::config::{validate_device_id, PlaybackConfig};
+use crate::error::SpeechError;
+use async_trait::async_trait;
+use bytes::Bytes;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::ops::Range;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc, watch};
+use tracing::{debug, warn};
+
+/// Finished utterances kept for progress queries
+const MAX_TRACKED: usize = 64;
+
+/// An audio output device
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AudioDevice {
+    /// Id to select the device with
+    pub id: String,
+    /// Human-readable name
+    pub name: String,
+    pub is_default: bool,
+}
+
+/// Where an utterance is in its playback
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlaybackState {
+    /// Waiting for earlier utterances
+    Queued,
+    Playing,
+    Finished,
+    /// Cut off by `stop()`
+    Stopped,
+    Failed,
+}
+
+impl PlaybackState {
+    /// Whether playback is over, one way or another
+    pub fn is_done(&self) -> bool {
+        matches!(self, PlaybackState::Finished | PlaybackState::Stopped | PlaybackState::Failed)
+    }
+}
+
+/// Playback progress of one utterance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlaybackProgress {
+    pub id: u64,
+    pub state: PlaybackState,
+    /// Time played so far
+    pub position_ms: u64,
+    /// Length of the audio, when the format tells
+    pub duration_ms: Option<u64>,
+}
+
+/// Something that can play audio
+#[async_trait]
+pub trait AudioOutput: Send + Sync {
+    /// Output devices, the default marked
+    async fn devices(&self) -> Result<Vec<AudioDevice>, SpeechError>;
+
+    /// Play `audio` on `device` (`None` for the system default) at `volume`
+    /// (0.0-1.0), returning once playback has finished
+    ///
+    /// Dropping the future must stop playback.
+    async fn play(&self, audio: Bytes, device: Option<&str>, volume: f32) -> Result<(), SpeechError>;
+}
+
+/// Background audio that is lowered while speech plays
+pub trait DuckingTarget: Send + Sync {
+    /// Set the gain (0.0-1.0) of the background audio this target controls
+    fn set_gain(&self, gain: f32);
+}
+
+struct Job {
+    id: u64,
+    audio: Bytes,
+    volume: f32,
+}
+
+struct Tracked {
+    state: PlaybackState,
+    duration_ms: Option<u64>,
+    started: Option<Instant>,
+    /// Position when playback ended
+    position_ms: u64,
+}
+
+struct PlayerState {
+    utterances: HashMap<u64, Tracked>,
+    /// Tracked ids, oldest first
+    order: VecDeque<u64>,
+    /// Utterances queued or playing
+    active: usize,
+    /// Bumped whenever speech starts, so stale duck releases are ignored
+    duck_generation: u64,
+}
+
+struct Inner {
+    config: PlaybackConfig,
+    output: Arc<dyn AudioOutput>,
+    device: Mutex<Option<String>>,
+    next_id: AtomicU64,
+    state: Mutex<PlayerState>,
+    queue: Mutex<Option<mpsc::UnboundedSender<Job>>>,
+    /// Bumped by `stop()` to cut off the utterance playing
+    stop: watch::Sender<u64>,
+    events: broadcast::Sender<PlaybackProgress>,
+    gain: watch::Sender<f32>,
+    ducking_targets: Mutex<Vec<Arc<dyn DuckingTarget>>>,
+}
+
+/// Sequential speech playback with device selection, ducking and progress
+pub struct AudioPlayer {
+    inner: Arc<Inner>,
+}
+
+impl AudioPlayer {
+    /// Create a player on `output`
+    pub fn new(config: PlaybackConfig, output: Arc<dyn AudioOutput>) -> Result<Self, SpeechError> {
+        config.validate().map_err(SpeechError::Config)?;
+        let (events, _) = broadcast::channel(256);
+        Ok(Self {
+            inner: Arc::new(Inner {
+                device: Mutex::new(config.device.clone()),
+                config,
+                output,
+                next_id: AtomicU64::new(1),
+                state: Mutex::new(PlayerState {
+                    utterances: HashMap::new(),
+                    order: VecDeque::new(),
+                    active: 0,
+                    duck_generation: 0,
+                }),
+                queue: Mutex::new(None),
+                stop: watch::channel(0).0,
+                events,
+                gain: watch::channel(1.0).0,
+                ducking_targets: Mutex::new(Vec::new()),
+            }),
+        })
+    }
+
+    /// Create a player on the system's audio output
+    pub fn system(config: PlaybackConfig) -> Result<Self, SpeechError> {
+        Self::new(config, Arc::new(SystemOutput::new()))
+    }
+
+    /// Available output devices
+    pub async fn devices(&self) -> Result<Vec<AudioDevice>, SpeechError> {
+        self.inner.output.devices().await
+    }
+
+    /// Play on `device` from the next utterance on (`None` for the system default)
+    pub async fn select_device(&self, device: Option<String>) -> Result<(), SpeechError> {
+        if let Some(ref id) = device {
+            validate_device_id(id).map_err(SpeechError::Config)?;
+            let devices = self.devices().await?;
+            if !devices.iter().any(|d| &d.id == id) {
+                return Err(SpeechError::Config(format!("Unknown output device: {}", id)));
+            }
+        }
+        *self.inner.device.lock() = device;
+        Ok(())
+    }
+
+    /// Selected output device (`None` for the system default)
+    pub fn device(&self) -> Option<String> {
+        self.inner.device.lock().clone()
+    }
+
+    /// Queue `audio` after anything already playing, at `volume` (0.0-1.0,
+    /// `None` for the configured volume)
+    ///
+    /// Returns the utterance id to query progress with. Must be called within
+    /// a Tokio runtime.
+    pub fn play(&self, audio: Bytes, volume: Option<f32>) -> Result<u64, SpeechError> {
+        let volume = volume.unwrap_or(self.inner.config.volume);
+        if !(0.0..=1.0).contains(&volume) {
+            return Err(SpeechError::Config("Volume must be between 0.0 and 1.0".to_string()));
+        }
+        if audio.is_empty() {
+            return Err(SpeechError::Synthesizer("No audio to play".to_string()));
+        }
+
+        let id = self.inner.next_id.fetch_add(1, Ordering::Relaxed);
+        {
+            let mut state = self.inner.state.lock();
+            state.active += 1;
+            state.order.push_back(id);
+            state.utterances.insert(id, Tracked {
+                state: PlaybackState::Queued,
+                duration_ms: audio_duration_ms(&audio),
+                started: None,
+                position_ms: 0,
+            });
+            // Forget the oldest finished utterances
+            while state.order.len() > MAX_TRACKED {
+                let oldest = state.order[0];
+                if !state.utterances.get(&oldest).is_some_and(|u| u.state.is_done()) {
+                    break;
+                }
+                state.order.pop_front();
+                state.utterances.remove(&oldest);
+            }
+        }
+
+        let job = Job { id, audio, volume };
+        let mut queue = self.inner.queue.lock();
+        let sender = queue.get_or_insert_with(|| spawn_worker(&self.inner));
+        if sender.send(job).is_err() {
+            // Unreachable while the player is alive
+            return Err(SpeechError::Synthesizer("Playback worker stopped".to_string()));
+        }
+        Ok(id)
+    }
+
+    /// Stop the utterance playing and drop everything queued
+    pub fn stop(&self) {
+        let stopped: Vec<PlaybackProgress> = {
+            let mut state = self.inner.state.lock();
+            state
+                .utterances
+                .iter_mut()
+                .filter(|(_, u)| !u.state.is_done())
+                .map(|(id, u)| {
+                    u.position_ms = position_ms(u);
+                    u.state = PlaybackState::Stopped;
+                    progress(*id, u)
+                })
+                .collect()
+        };
+        self.inner.stop.send_modify(|generation| *generation += 1);
+        for progress in stopped {
+            let _ = self.inner.events.send(progress);
+        }
+    }
+
+    /// Progress of utterance `id`, if it is still tracked
+    pub fn progress(&self, id: u64) -> Option<PlaybackProgress> {
+        let state = self.inner.state.lock();
+        state.utterances.get(&id).map(|u| progress(id, u))
+    }
+
+    /// Progress of the utterance playing right now
+    pub fn current(&self) -> Option<PlaybackProgress> {
+        let state = self.inner.state.lock();
+        state
+            .utterances
+            .iter()
+            .find(|(_, u)| u.state == PlaybackState::Playing)
+            .map(|(id, u)| progress(*id, u))
+    }
+
+    /// Whether anything is queued or playing
+    pub fn is_busy(&self) -> bool {
+        self.inner.state.lock().active > 0
+    }
+
+    /// Wait until utterance `id` is done playing, returning its final progress
+    ///
+    /// `None` if the id is unknown.
+    pub async fn wait(&self, id: u64) -> Option<PlaybackProgress> {
+        let mut events = self.inner.events.subscribe();
+        loop {
+            let progress = self.progress(id)?;
+            if progress.state.is_done() {
+                return Some(progress);
+            }
+            if let Err(broadcast::error::RecvError::Closed) = events.recv().await {
+                return self.progress(id);
+            }
+        }
+    }
+
+    /// Subscribe to playback state changes
+    pub fn subscribe(&self) -> broadcast::Receiver<PlaybackProgress> {
+        self.inner.events.subscribe()
+    }
+
+    /// Background audio gain: 1.0 normally, `duck_level` while speaking
+    pub fn ducking_gain(&self) -> watch::Receiver<f32> {
+        self.inner.gain.subscribe()
+    }
+
+    /// Lower `target` while speech plays
+    pub fn add_ducking_target(&self, target: Arc<dyn DuckingTarget>) {
+        target.set_gain(*self.inner.gain.borrow());
+        self.inner.ducking_targets.lock().push(target);
+    }
+}
+
+impl Inner {
+    /// Record how playback of `id` ended, unless `stop()` got there first
+    fn finish(&self, id: u64, update: impl FnOnce(&mut Tracked)) {
+        let progress = {
+            let mut state = self.state.lock();
+            let Some(utterance) = state.utterances.get_mut(&id).filter(|u| !u.state.is_done()) else {
+                return;
+            };
+            update(utterance);
+            progress(id, utterance)
+        };
+        let _ = self.events.send(progress);
+    }
+
+    fn set_gain(&self, gain: f32) {
+        if *self.gain.borrow() == gain {
+            return;
+        }
+        debug!("Background audio gain {:.2}", gain);
+        self.gain.send_replace(gain);
+        for target in self.ducking_targets.lock().iter() {
+            target.set_gain(gain);
+        }
+    }
+
+    fn duck(&self) {
+        self.state.lock().duck_generation += 1;
+        if self.config.ducking {
+            self.set_gain(self.config.duck_level);
+        }
+    }
+
+    /// Restore background audio once nothing has played for `duck_release_ms`
+    fn release(self: &Arc<Self>) {
+        let generation = self.state.lock().duck_generation;
+        let inner = Arc::downgrade(self);
+        let delay = Duration::from_millis(self.config.duck_release_ms);
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            let Some(inner) = inner.upgrade() else {
+                return;
+            };
+            let quiet = {
+                let state = inner.state.lock();
+                state.active == 0 && state.duck_generation == generation
+            };
+            if quiet {
+                inner.set_gain(1.0);
+            }
+        });
+    }
+}
+
+fn spawn_worker(inner: &Arc<Inner>) -> mpsc::UnboundedSender<Job> {
+    let (sender, mut receiver) = mpsc::unbounded_channel::<Job>();
+    let weak: Weak<Inner> = Arc::downgrade(inner);
+    let mut stop = inner.stop.subscribe();
+    tokio::spawn(async move {
+        // Ends when the player, and with it the sender, is dropped
+        while let Some(job) = receiver.recv().await {
+            let Some(inner) = weak.upgrade() else {
+                break;
+            };
+            play_job(&inner, job, &mut stop).await;
+            let quiet = {
+                let mut state = inner.state.lock();
+                state.active -= 1;
+                state.active == 0
+            };
+            if quiet {
+                inner.release();
+            }
+        }
+    });
+    sender
+}
+
+async fn play_job(inner: &Arc<Inner>, job: Job, stop: &mut watch::Receiver<u64>) {
+    // Stops from here on cut this utterance off
+    stop.borrow_and_update();
+    let started = {
+        let mut state = inner.state.lock();
+        // Skip it if it was stopped while queued
+        let Some(utterance) = state.utterances.get_mut(&job.id).filter(|u| u.state == PlaybackState::Queued) else {
+            return;
+        };
+        utterance.state = PlaybackState::Playing;
+        utterance.started = Some(Instant::now());
+        progress(job.id, utterance)
+    };
+    inner.duck();
+    let _ = inner.events.send(started);
+
+    let device = inner.device.lock().clone();
+    let result = tokio::select! {
+        result = inner.output.play(job.audio, device.as_deref(), job.volume) => Some(result),
+        _ = stop.changed() => None,
+    };
+    match result {
+        // `stop()` already recorded it
+        None => debug!("Playback {} stopped", job.id),
+        Some(Ok(())) => inner.finish(job.id, |u| {
+            u.position_ms = u.duration_ms.unwrap_or_else(|| position_ms(u));
+            u.state = PlaybackState::Finished;
+        }),
+        Some(Err(e)) => {
+            warn!("Playback {} failed: {}", job.id, e);
+            inner.finish(job.id, |u| {
+                u.position_ms = position_ms(u);
+                u.state = PlaybackState::Failed;
+            });
+        }
+    }
+}
+
+fn position_ms(utterance: &Tracked) -> u64 {
+    match (utterance.state, utterance.started) {
+        (PlaybackState::Playing, Some(started)) => {
+            let elapsed = started.elapsed().as_millis() as u64;
+            utterance.duration_ms.map_or(elapsed, |duration| elapsed.min(duration))
+        }
+        _ => utterance.position_ms,
+    }
+}
+
+fn progress(id: u64, utterance: &Tracked) -> PlaybackProgress {
+    PlaybackProgress {
+        id,
+        state: utterance.state,
+        position_ms: position_ms(utterance),
+        duration_ms: utterance.duration_ms,
+    }
+}
+
+/// Parsed WAV header
+struct WavInfo {
+    format_tag: u16,
+    bits_per_sample: u16,
+    byte_rate: u32,
+    data: Range<usize>,
+}
+
+fn wav_info(data: &[u8]) -> Option<WavInfo> {
+    if data.len() < 12 || &data[0..4] != b"RIFF" || &data[8..12] != b"WAVE" {
+        return None;
+    }
+    let mut format = None;
+    let mut offset = 12;
+    while offset + 8 <= data.len() {
+        let id = &data[offset..offset + 4];
+        let size = u32::from_le_bytes(data[offset + 4..offset + 8].try_into().ok()?) as usize;
+        let body = offset + 8;
+        match id {
+            b"fmt " if body + 16 <= data.len() => {
+                let format_tag = u16::from_le_bytes(data[body..body + 2].try_into().ok()?);
+                let byte_rate = u32::from_le_bytes(data[body + 8..body + 12].try_into().ok()?);
+                let bits_per_sample = u16::from_le_bytes(data[body + 14..body + 16].try_into().ok()?);
+                format = Some((format_tag, byte_rate, bits_per_sample));
+            }
+            b"data" => {
+                let (format_tag, byte_rate, bits_per_sample) = format?;
+                // Streamed WAVs may leave the size unset; take what is there
+                let end = body + size.min(data.len() - body);
+                return Some(WavInfo { format_tag, bits_per_sample, byte_rate, data: body..end });
+            }
+            _ => {}
+        }
+        // Chunks are padded to an even size
+        offset = body.checked_add(size)?.checked_add(size % 2)?;
+    }
+    None
+}
+
+/// Duration of WAV audio, from its header
+pub fn audio_duration_ms(audio: &[u8]) -> Option<u64> {
+    let info = wav_info(audio)?;
+    if info.byte_rate == 0 {
+        return None;
+    }
+    Some(info.data.len() as u64 * 1_000 / info.byte_rate as u64)
+}
+
+/// Scale 16-bit PCM WAV samples by `volume` in place
+///
+/// Returns false, leaving the audio untouched, for any other format.
+pub fn scale_wav_volume(audio: &mut [u8], volume: f32) -> bool {
+    let Some(info) = wav_info(audio) else {
+        return false;
+    };
+    if info.format_tag != 1 || info.bits_per_sample != 16 {
+        return false;
+    }
+    let volume = volume.clamp(0.0, 1.0);
+    for sample in audio[info.data].chunks_exact_mut(2) {
+        let value = i16::from_le_bytes([sample[0], sample[1]]);
+        let scaled = (value as f32 * volume).round() as i16;
+        sample.copy_from_slice(&scaled.to_le_bytes());
+    }
+    true
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Backend {
+    PulseAudio,
+    Alsa,
+    MacOs,
+    Windows,
+    Unavailable,
+}
+
+/// Playback through the platform's command-line audio players
+///
+/// Device selection needs PulseAudio (`pactl`/`paplay`) or ALSA (`aplay`);
+/// macOS and Windows play on the default device only.
+pub struct SystemOutput {
+    backend: Backend,
+}
+
+impl SystemOutput {
+    pub fn new() -> Self {
+        let available = |program: &str, arg: &str| std::process::Command::new(program).arg(arg).output().is_ok();
+        let backend = if cfg!(target_os = "macos") {
+            Backend::MacOs
+        } else if cfg!(target_os = "windows") {
+            Backend::Windows
+        } else if available("paplay", "--version") && available("pactl", "--version") {
+            Backend::PulseAudio
+        } else if available("aplay", "--version") {
+            Backend::Alsa
+        } else {
+            Backend::Unavailable
+        };
+        Self { backend }
+    }
+
+    async fn run(program: &str, args: &[&str]) -> Result<String, SpeechError> {
+        let output = tokio::process::Command::new(program)
+            .args(args)
+            .kill_on_drop(true)
+            .output()
+            .await
+            .map_err(|e| SpeechError::Engine(format!("Failed to run {}: {}", program, e)))?;
+        if !output.status.success() {
+            return Err(SpeechError::Engine(format!(
+                "{} failed: {}",
+                program,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+impl Default for SystemOutput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl AudioOutput for SystemOutput {
+    async fn devices(&self) -> Result<Vec<AudioDevice>, SpeechError> {
+        match self.backend {
+            Backend::PulseAudio => {
+                let default = Self::run("pactl", &["get-default-sink"]).await.unwrap_or_default();
+                let sinks = Self::run("pactl", &["list", "short", "sinks"]).await?;
+                Ok(parse_pactl_sinks(&sinks, default.trim()))
+            }
+            Backend::Alsa => Ok(parse_aplay_devices(&Self::run("aplay", &["-L"]).await?)),
+            Backend::MacOs | Backend::Windows => Ok(vec![AudioDevice {
+                id: "default".to_string(),
+                name: "System default".to_string(),
+                is_default: true,
+            }]),
+            Backend::Unavailable => Err(SpeechError::Engine("No audio player available".to_string())),
+        }
+    }
+
+    async fn play(&self, audio: Bytes, device: Option<&str>, volume: f32) -> Result<(), SpeechError> {
+        if let Some(device) = device {
+            validate_device_id(device).map_err(SpeechError::Config)?;
+            if matches!(self.backend, Backend::MacOs | Backend::Windows) && device != "default" {
+                return Err(SpeechError::Config(
+                    "Output device selection is not supported on this platform".to_string(),
+                ));
+            }
+        }
+
+        let mut audio = audio.to_vec();
+        // WAV is scaled here; other formats rely on the player's volume option
+        let volume = if scale_wav_volume(&mut audio, volume) { 1.0 } else { volume };
+        let suffix = match audio.get(0..4) {
+            Some(b"RIFF") => ".wav",
+            Some(b"OggS") => ".ogg",
+            _ => ".mp3",
+        };
+        let file = tempfile::Builder::new().prefix("narayana-spk-").suffix(suffix).tempfile()?;
+        std::fs::write(file.path(), &audio)?;
+        let path = file.path().to_string_lossy().into_owned();
+
+        match self.backend {
+            Backend::PulseAudio => {
+                let mut args = vec![format!("--volume={}", (volume * 65_536.0).round() as u32)];
+                if let Some(device) = device {
+                    args.push(format!("--device={}", device));
+                }
+                args.push(path);
+                let args: Vec<&str> = args.iter().map(String::as_str).collect();
+                Self::run("paplay", &args).await?;
+            }
+            Backend::Alsa => {
+                let mut args = vec!["-q"];
+                if let Some(device) = device {
+                    args.extend(["-D", device]);
+                }
+                args.push(&path);
+                Self::run("aplay", &args).await?;
+            }
+            Backend::MacOs => {
+                let volume = format!("{:.2}", volume);
+                Self::run("afplay", &["-v", &volume, &path]).await?;
+            }
+            Backend::Windows => {
+                let script = format!("(New-Object Media.SoundPlayer '{}').PlaySync()", path.replace('\'', "''"));
+                Self::run("powershell", &["-NoProfile", "-Command", &script]).await?;
+            }
+            Backend::Unavailable => return Err(SpeechError::Engine("No audio player available".to_string())),
+        }
+        Ok(())
+    }
+}
+
+/// Parse `pactl list short sinks`: index, name, driver, format, state per line
+fn parse_pactl_sinks(output: &str, default: &str) -> Vec<AudioDevice> {
+    output
+        .lines()
+        .filter_map(|line| line.split('\t').nth(1))
+        .filter(|name| validate_device_id(name).is_ok())
+        .map(|name| AudioDevice {
+            id: name.to_string(),
+            name: name.to_string(),
+            is_default: name == default,
+        })
+        .collect()
+}
+
+/// Parse `aplay -L`: a device id per unindented line, its description indented below
+fn parse_aplay_devices(output: &str) -> Vec<AudioDevice> {
+    let mut devices: Vec<AudioDevice> = Vec::new();
+    for line in output.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if !line.starts_with(char::is_whitespace) {
+            if validate_device_id(line).is_ok() {
+                devices.push(AudioDevice {
+                    id: line.to_string(),
+                    name: line.to_string(),
+                    is_default: line == "default",
+                });
+            }
+        } else if let Some(device) = devices.last_mut() {
+            // First description line names the device
+            if device.name == device.id {
+                device.name = line.trim().to_string();
+            }
+        }
+    }
+    devices
+}
@@ -2,8 +2,11 @@
 
 use crate::config::{SpeechConfig, VoiceConfig};
 use crate::error::SpeechError;
+use crate::emotion::{prosody_for_emotion, prosody_changed};
+use crate::playback::AudioPlayer;
 use crate::synthesizer::SpeechSynthesizer;
 use bytes::Bytes;
+use narayana_wld::emotion::EmotionBus;
 use narayana_wld::protocol_adapters::ProtocolAdapter;
 use narayana_wld::world_broker::WorldBrokerHandle;
 use narayana_wld::event_transformer::{WorldEvent, WorldAction};
@@ -24,6 +27,8 @@ pub struct SpeechAdapter {
     is_running: Arc<RwLock<bool>>,
     processing_handle: Arc<RwLock<Option<tokio::task::JoinHandle<()>>>>,
     request_receiver: Arc<RwLock<Option<mpsc::Receiver<SpeechRequest>>>>,
+    emotion_task: Arc<RwLock<Option<tokio::task::JoinHandle<()>>>>,
+    player: Option<Arc<AudioPlayer>>,
 }
 
 struct SpeechRequest {
@@ -59,6 +64,18 @@ impl SpeechAdapter {
             None
         };
 
+        let player = if config.enabled && config.playback.enabled {
+            match AudioPlayer::system(config.playback.clone()) {
+                Ok(player) => Some(Arc::new(player)),
+                Err(e) => {
+                    warn!("Failed to initialize audio playback: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
         Ok(Self {
             config: Arc::new(config),
             synthesizer: Arc::new(RwLock::new(synthesizer)),
@@ -66,8 +83,59 @@ impl SpeechAdapter {
             is_running: Arc::new(RwLock::new(false)),
             processing_handle: Arc::new(RwLock::new(None)),
             request_receiver: Arc::new(RwLock::new(None)),
+            emotion_task: Arc::new(RwLock::new(None)),
+            player,
         })
     }
+
+    /// Local playback, when enabled in the config
+    pub fn player(&self) -> Option<Arc<AudioPlayer>> {
+        self.player.clone()
+    }
+
+    /// Follow the shared emotion state, rebuilding the synthesizer whenever
+    /// the emotional prosody changes audibly
+    pub fn follow_emotion(&self, bus: Arc<EmotionBus>) {
+        if !self.config.enabled {
+            return;
+        }
+
+        let base = self.config.clone();
+        let synthesizer = self.synthesizer.clone();
+        let initial = bus.current();
+        let mut updates = bus.subscribe();
+
+        let task = tokio::spawn(async move {
+            let mut applied = (*base).clone();
+            let mut apply = |emotion| {
+                let target = prosody_for_emotion(&base, &emotion);
+                if !prosody_changed(&target, &applied) {
+                    return;
+                }
+                match SpeechSynthesizer::new(target.clone()) {
+                    Ok(synth) => {
+                        debug!("Speech prosody now rate={} pitch={:.2} volume={:.2}", target.rate, target.pitch, target.volume);
+                        *synthesizer.write() = Some(Arc::new(synth));
+                        applied = target;
+                    }
+                    Err(e) => warn!("Failed to apply emotional prosody: {}", e),
+                }
+            };
+
+            apply(initial);
+            loop {
+                match updates.recv().await {
+                    Ok(update) => apply(update.state),
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+
+        if let Some(previous) = self.emotion_task.write().replace(task) {
+            previous.abort();
+        }
+    }
 }
 
 #[async_trait]
@@ -76,7 +144,7 @@ impl ProtocolAdapter for SpeechAdapter {
         "speech"
     }
 
-    async fn start(&self, _broker: WorldBrokerHandle) -> Result<(), Error> {
+    async fn start(&self, broker: WorldBrokerHandle) -> Result<(), Error> {
         // Check if already running
         {
             let mut is_running = self.is_running.write();
@@ -100,6 +168,10 @@ impl ProtocolAdapter for SpeechAdapter {
             info!("Speech synthesizer ready");
         }
 
+        if self.config.emotional_prosody {
+            self.follow_emotion(broker.emotion_bus());
+        }
+
         info!("Speech adapter started successfully");
         Ok(())
     }
@@ -127,6 +199,14 @@ impl ProtocolAdapter for SpeechAdapter {
             ).await;
         }
 
+        if let Some(task) = self.emotion_task.write().take() {
+            task.abort();
+        }
+
+        if let Some(ref player) = self.player {
+            player.stop();
+        }
+
         // Clear event sender
         *self.event_sender.write() = None;
 
@@ -201,6 +281,18 @@ impl ProtocolAdapter for SpeechAdapter {
                             match audio_result {
                                 Ok(audio) => {
                                     info!("Speech synthesized successfully: {} bytes", audio.len());
+
+                                    // Optional per-utterance playback volume
+                                    let volume = command.get("volume").and_then(|v| v.as_f64()).map(|v| v as f32);
+                                    let playback_id = self.player.as_ref().and_then(|player| {
+                                        match player.play(audio.clone(), volume) {
+                                            Ok(id) => Some(id),
+                                            Err(e) => {
+                                                warn!("Failed to play speech: {}", e);
+                                                None
+                                            }
+                                        }
+                                    });
                                     
                                     // Send event
                                     let event_opt = {
@@ -216,17 +308,7 @@ impl ProtocolAdapter for SpeechAdapter {
                                             .take(1000) // Limit in event
                                             .collect();
                                         
-                                        // Safely convert timestamp to u64, handling overflow and negative values
-                                        let timestamp = chrono::Utc::now()
-                                            .timestamp_nanos_opt()
-                                            .and_then(|ts| {
-                                                if ts >= 0 {
-                                                    ts.try_into().ok() // Convert i64 to u64
-                                                } else {
-                                                    None // Negative timestamps not supported
-                                                }
-                                            })
-                                            .unwrap_or(0u64);
+                                        let timestamp = event_timestamp();
                                         
                                         let event = WorldEvent::SensorData {
                                             source: "speech".to_string(),
@@ -236,6 +318,7 @@ impl ProtocolAdapter for SpeechAdapter {
                                                 "text": sanitized_text,
                                                 "text_length": text_to_speak.len(),
                                                 "audio_size": audio.len(),
+                                                "playback_id": playback_id,
                                                 "timestamp": timestamp,
                                             }),
                                             timestamp,
@@ -245,6 +328,10 @@ impl ProtocolAdapter for SpeechAdapter {
                                         if sender.send(event).is_err() {
                                             warn!("Failed to send speech event (channel full)");
                                         }
+
+                                        if let (Some(player), Some(id)) = (self.player.clone(), playback_id) {
+                                            report_playback_end(player, id, sender);
+                                        }
                                     }
                                 }
                                 Err(e) => {
@@ -278,3 +365,35 @@ impl ProtocolAdapter for SpeechAdapter {
     }
 }
 
+/// Event timestamp in nanoseconds (0 if the clock is out of range)
+fn event_timestamp() -> u64 {
+    chrono::Utc::now()
+        .timestamp_nanos_opt()
+        .and_then(|ts| ts.try_into().ok())
+        .unwrap_or(0u64)
+}
+
+/// Send a speech event once utterance `id` is done playing
+fn report_playback_end(player: Arc<AudioPlayer>, id: u64, sender: broadcast::Sender<WorldEvent>) {
+    tokio::spawn(async move {
+        let Some(progress) = player.wait(id).await else {
+            return;
+        };
+        let timestamp = event_timestamp();
+        let event = WorldEvent::SensorData {
+            source: "speech".to_string(),
+            data: json!({
+                "type": "playback",
+                "status": progress.state,
+                "playback_id": id,
+                "position_ms": progress.position_ms,
+                "duration_ms": progress.duration_ms,
+                "timestamp": timestamp,
+            }),
+            timestamp,
+        };
+        if sender.send(event).is_err() {
+            debug!("No subscribers for playback event");
+        }
+    });
+}
@@ -0,0 +1,36 @@
+//! Tests for emotional prosody
+
+use narayana_spk::config::SpeechConfig;
+use narayana_spk::emotion::prosody_for_emotion;
+use narayana_wld::emotion::EmotionState;
+
+#[test]
+fn test_neutral_emotion_keeps_base_prosody() {
+    let base = SpeechConfig::default();
+    let config = prosody_for_emotion(&base, &EmotionState::default());
+    assert_eq!(config.rate, base.rate);
+    assert_eq!(config.pitch, base.pitch);
+    assert_eq!(config.volume, base.volume);
+}
+
+#[test]
+fn test_arousal_speeds_up_and_raises_pitch() {
+    let base = SpeechConfig::default();
+    let excited = prosody_for_emotion(&base, &EmotionState::new(0.5, 1.0, 0.5));
+    let subdued = prosody_for_emotion(&base, &EmotionState::new(-0.5, -1.0, -0.5));
+
+    assert!(excited.rate > base.rate && subdued.rate < base.rate);
+    assert!(excited.pitch > base.pitch && subdued.pitch < base.pitch);
+    assert!(excited.volume > base.volume && subdued.volume < base.volume);
+}
+
+#[test]
+fn test_emotional_prosody_stays_valid() {
+    let mut base = SpeechConfig::default();
+    base.rate = 480;
+    base.pitch = 0.9;
+    base.volume = 1.0;
+    for state in [EmotionState::new(1.0, 1.0, 1.0), EmotionState::new(-1.0, -1.0, -1.0)] {
+        assert!(prosody_for_emotion(&base, &state).validate().is_ok());
+    }
+}
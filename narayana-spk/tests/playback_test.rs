@@ -0,0 +1,148 @@
+//! Tests for audio playback: sequencing, progress, stop, ducking and volume
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use narayana_spk::config::PlaybackConfig;
+use narayana_spk::error::SpeechError;
+use narayana_spk::playback::{
+    audio_duration_ms, scale_wav_volume, AudioDevice, AudioOutput, AudioPlayer, PlaybackState,
+};
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Pretends to play for as long as the WAV lasts, recording what it played
+#[derive(Default)]
+struct MockOutput {
+    played: Mutex<Vec<(usize, Option<String>, f32)>>,
+}
+
+#[async_trait]
+impl AudioOutput for MockOutput {
+    async fn devices(&self) -> Result<Vec<AudioDevice>, SpeechError> {
+        Ok(vec![
+            AudioDevice { id: "speakers".to_string(), name: "Speakers".to_string(), is_default: true },
+            AudioDevice { id: "headset".to_string(), name: "Headset".to_string(), is_default: false },
+        ])
+    }
+
+    async fn play(&self, audio: Bytes, device: Option<&str>, volume: f32) -> Result<(), SpeechError> {
+        self.played.lock().push((audio.len(), device.map(str::to_string), volume));
+        let duration = audio_duration_ms(&audio).unwrap_or(0);
+        tokio::time::sleep(Duration::from_millis(duration)).await;
+        Ok(())
+    }
+}
+
+/// 16-bit mono WAV at 8 kHz lasting `duration_ms`, every sample `value`
+fn wav(duration_ms: u32, value: i16) -> Vec<u8> {
+    let data_bytes = 16 * duration_ms;
+    let mut wav = Vec::new();
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_bytes).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&8_000u32.to_le_bytes());
+    wav.extend_from_slice(&16_000u32.to_le_bytes());
+    wav.extend_from_slice(&2u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_bytes.to_le_bytes());
+    for _ in 0..data_bytes / 2 {
+        wav.extend_from_slice(&value.to_le_bytes());
+    }
+    wav
+}
+
+fn player(config: PlaybackConfig) -> (AudioPlayer, Arc<MockOutput>) {
+    let output = Arc::new(MockOutput::default());
+    (AudioPlayer::new(config, output.clone()).unwrap(), output)
+}
+
+#[tokio::test]
+async fn test_utterances_play_in_order_with_progress() {
+    let (player, output) = player(PlaybackConfig::default());
+    let first = player.play(Bytes::from(wav(100, 1)), None).unwrap();
+    let second = player.play(Bytes::from(wav(50, 1)), Some(0.5)).unwrap();
+    assert!(player.is_busy());
+
+    let done = player.wait(first).await.unwrap();
+    assert_eq!(done.state, PlaybackState::Finished);
+    assert_eq!(done.duration_ms, Some(100));
+    assert_eq!(done.position_ms, 100);
+    let done = player.wait(second).await.unwrap();
+    assert_eq!(done.state, PlaybackState::Finished);
+
+    let played = output.played.lock().clone();
+    assert_eq!(played.iter().map(|p| p.2).collect::<Vec<_>>(), vec![1.0, 0.5]);
+    assert!(!player.is_busy());
+    assert!(player.wait(999).await.is_none());
+}
+
+#[tokio::test]
+async fn test_stop_cuts_off_playing_and_queued() {
+    let (player, output) = player(PlaybackConfig::default());
+    let first = player.play(Bytes::from(wav(2_000, 1)), None).unwrap();
+    let queued = player.play(Bytes::from(wav(2_000, 1)), None).unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let current = player.current().unwrap();
+    assert_eq!(current.id, first);
+    assert!(current.position_ms > 0 && current.position_ms < 2_000);
+
+    player.stop();
+    assert_eq!(player.wait(first).await.unwrap().state, PlaybackState::Stopped);
+    assert_eq!(player.wait(queued).await.unwrap().state, PlaybackState::Stopped);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(output.played.lock().len(), 1);
+    assert!(!player.is_busy());
+}
+
+#[tokio::test]
+async fn test_background_audio_ducked_while_speaking() {
+    let config = PlaybackConfig { duck_level: 0.25, duck_release_ms: 50, ..Default::default() };
+    let (player, _) = player(config);
+    let gain = player.ducking_gain();
+    assert_eq!(*gain.borrow(), 1.0);
+
+    let id = player.play(Bytes::from(wav(100, 1)), None).unwrap();
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    assert_eq!(*gain.borrow(), 0.25);
+    player.wait(id).await.unwrap();
+    // Held through the release time, then restored
+    assert_eq!(*gain.borrow(), 0.25);
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    assert_eq!(*gain.borrow(), 1.0);
+}
+
+#[tokio::test]
+async fn test_device_selection_and_validation() {
+    let (player, output) = player(PlaybackConfig::default());
+    assert_eq!(player.devices().await.unwrap().len(), 2);
+    player.select_device(Some("headset".to_string())).await.unwrap();
+    assert!(player.select_device(Some("missing".to_string())).await.is_err());
+    assert!(player.select_device(Some("--help".to_string())).await.is_err());
+    assert_eq!(player.device().as_deref(), Some("headset"));
+
+    let id = player.play(Bytes::from(wav(10, 1)), None).unwrap();
+    player.wait(id).await.unwrap();
+    assert_eq!(output.played.lock()[0].1.as_deref(), Some("headset"));
+
+    assert!(player.play(Bytes::from(wav(10, 1)), Some(1.5)).is_err());
+    assert!(player.play(Bytes::new(), None).is_err());
+    let config = PlaybackConfig { duck_level: 2.0, ..Default::default() };
+    assert!(AudioPlayer::new(config, output).is_err());
+}
+
+#[test]
+fn test_wav_volume_scaling() {
+    let mut audio = wav(10, 1_000);
+    assert!(scale_wav_volume(&mut audio, 0.5));
+    assert_eq!(i16::from_le_bytes([audio[44], audio[45]]), 500);
+    assert_eq!(audio_duration_ms(&audio), Some(10));
+
+    let mut mp3 = vec![0xFF, 0xFB, 0x90, 0x00];
+    assert!(!scale_wav_volume(&mut mp3, 0.5));
+    assert_eq!(mp3, vec![0xFF, 0xFB, 0x90, 0x00]);
+}
@@ -5,7 +5,7 @@ use narayana_core::{Error, Result};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::hash::{Hasher, Hash};
 use parking_lot::RwLock;
 use dashmap::DashMap;
@@ -202,6 +202,14 @@ pub struct AdvancedLoadBalancerConfig {
     pub circuit_breaker_timeout: Duration,
     pub circuit_breaker_half_open_max_requests: usize,
     
+    // Consistent hashing settings
+    #[serde(default = "default_virtual_nodes")]
+    pub consistent_hash_virtual_nodes: usize,
+    
+    // Outlier ejection settings
+    #[serde(default)]
+    pub outlier_ejection: OutlierEjectionConfig,
+    
     // Sticky session settings
     pub enable_sticky_sessions: bool,
     pub sticky_session_duration: Duration,
@@ -239,6 +247,46 @@ pub struct AdvancedLoadBalancerConfig {
     pub enable_auto_weight_adjustment: bool,
 }
 
+fn default_virtual_nodes() -> usize {
+    100
+}
+
+/// Outlier ejection - takes backends that fail far more than they should out
+/// of rotation for a while, without the probing a circuit breaker does
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OutlierEjectionConfig {
+    pub enabled: bool,
+    /// Eject after this many failures in a row (0 disables)
+    pub consecutive_failures: u64,
+    /// Eject when the error rate within an interval reaches this (0.0-1.0)
+    pub error_rate_threshold: f64,
+    /// Requests an interval needs before its error rate counts
+    pub min_requests: u64,
+    /// Length of the error-rate window
+    pub interval: Duration,
+    /// First ejection lasts this long, each repeat ejection of a node adds it again
+    pub base_ejection_time: Duration,
+    pub max_ejection_time: Duration,
+    /// Most of the backends (percent) ejected at once; never all of them
+    pub max_ejection_percent: f64,
+}
+
+impl Default for OutlierEjectionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            consecutive_failures: 5,
+            error_rate_threshold: 0.5,
+            min_requests: 20,
+            interval: Duration::from_secs(10),
+            base_ejection_time: Duration::from_secs(30),
+            max_ejection_time: Duration::from_secs(300),
+            max_ejection_percent: 50.0,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum BackoffStrategy {
     None,
@@ -267,6 +315,8 @@ impl Default for AdvancedLoadBalancerConfig {
             circuit_breaker_success_threshold: 2,
             circuit_breaker_timeout: Duration::from_secs(60),
             circuit_breaker_half_open_max_requests: 3,
+            consistent_hash_virtual_nodes: default_virtual_nodes(),
+            outlier_ejection: OutlierEjectionConfig::default(),
             enable_sticky_sessions: false,
             sticky_session_duration: Duration::from_secs(3600),
             sticky_session_cookie_name: "LB_SESSION".to_string(),
@@ -309,10 +359,32 @@ pub struct RequestContext {
     pub zone: Option<String>,
     pub session_id: Option<String>,
     pub user_id: Option<String>,
+    /// Caller-supplied affinity key (e.g. a robot ID) for the hashing strategies;
+    /// takes precedence over session, user and client IP
+    #[serde(default)]
+    pub routing_key: Option<String>,
     pub priority: Option<u32>,
     pub custom_attributes: HashMap<String, serde_json::Value>,
 }
 
+impl RequestContext {
+    /// Context that routes on an affinity key alone
+    pub fn with_routing_key(key: impl Into<String>) -> Self {
+        Self {
+            routing_key: Some(key.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Key the hashing strategies route on
+    fn hash_key(&self) -> Option<&String> {
+        self.routing_key.as_ref()
+            .or(self.session_id.as_ref())
+            .or(self.user_id.as_ref())
+            .or(self.client_ip.as_ref())
+    }
+}
+
 impl Default for RequestContext {
     fn default() -> Self {
         Self {
@@ -327,6 +399,7 @@ impl Default for RequestContext {
             zone: None,
             session_id: None,
             user_id: None,
+            routing_key: None,
             priority: None,
             custom_attributes: HashMap::new(),
         }
@@ -343,6 +416,7 @@ pub struct AdvancedLoadBalancer {
     stats: Arc<RwLock<LoadBalancerStats>>,
     health_checker: Arc<HealthChecker>,
     circuit_breakers: Arc<DashMap<String, CircuitBreaker>>,
+    outliers: Arc<DashMap<String, OutlierState>>,
     weight_adjuster: Arc<WeightAdjuster>,
     ml_predictor: Option<Arc<MLPredictor>>,
 }
@@ -351,6 +425,7 @@ pub struct AdvancedLoadBalancer {
 struct ConsistentHashRing {
     nodes: Vec<(u64, String)>, // (hash, node_id)
     virtual_nodes_per_node: usize,
+    weighted: bool, // virtual nodes scale with node weight
 }
 
 /// Circuit breaker
@@ -360,7 +435,34 @@ struct CircuitBreaker {
     success_count: u64,
     last_failure: Option<Instant>,
     next_attempt: Option<Instant>,
-    half_open_requests: usize,
+    half_open_requests: usize, // probes in flight
+}
+
+/// Outlier detection state of one node
+struct OutlierState {
+    consecutive_failures: u64,
+    interval_requests: u64,
+    interval_errors: u64,
+    interval_started: Instant,
+    ejected_until: Option<Instant>,
+    ejections: u32,
+}
+
+impl OutlierState {
+    fn new() -> Self {
+        Self {
+            consecutive_failures: 0,
+            interval_requests: 0,
+            interval_errors: 0,
+            interval_started: Instant::now(),
+            ejected_until: None,
+            ejections: 0,
+        }
+    }
+
+    fn is_ejected(&self, now: Instant) -> bool {
+        self.ejected_until.is_some_and(|until| now < until)
+    }
 }
 
 /// Load balancer statistics
@@ -375,6 +477,8 @@ pub struct LoadBalancerStats {
     pub nodes_healthy: usize,
     pub nodes_unhealthy: usize,
     pub circuit_breakers_open: usize,
+    #[serde(default)]
+    pub nodes_ejected: usize,
     pub load_distribution: HashMap<String, u64>,
 }
 
@@ -415,7 +519,8 @@ impl AdvancedLoadBalancer {
             sticky_sessions: Arc::new(DashMap::new()),
             consistent_hash_ring: Arc::new(RwLock::new(ConsistentHashRing {
                 nodes: Vec::new(),
-                virtual_nodes_per_node: 100,
+                virtual_nodes_per_node: config.consistent_hash_virtual_nodes.max(1),
+                weighted: config.strategy == LoadBalancingStrategy::ConsistentHashingWeighted,
             })),
             stats: Arc::new(RwLock::new(LoadBalancerStats {
                 total_requests: 0,
@@ -427,10 +532,12 @@ impl AdvancedLoadBalancer {
                 nodes_healthy: 0,
                 nodes_unhealthy: 0,
                 circuit_breakers_open: 0,
+                nodes_ejected: 0,
                 load_distribution: HashMap::new(),
             })),
             health_checker: health_checker.clone(),
             circuit_breakers: Arc::new(DashMap::new()),
+            outliers: Arc::new(DashMap::new()),
             weight_adjuster: weight_adjuster.clone(),
             ml_predictor: None,
         }
@@ -452,6 +559,9 @@ impl AdvancedLoadBalancer {
                 half_open_requests: 0,
             });
         }
+        if self.config.outlier_ejection.enabled {
+            self.outliers.insert(node.id.clone(), OutlierState::new());
+        }
         
         info!("Added node {} to load balancer", node.id);
     }
@@ -462,6 +572,7 @@ impl AdvancedLoadBalancer {
         self.sticky_sessions.retain(|_, id| id != node_id);
         self.update_consistent_hash_ring();
         self.circuit_breakers.remove(node_id);
+        self.outliers.remove(node_id);
         info!("Removed node {} from load balancer", node_id);
     }
 
@@ -505,13 +616,13 @@ impl AdvancedLoadBalancer {
                 self.select_weighted_least_connections(&available_nodes)
             }
             LoadBalancingStrategy::ConsistentHashing => {
-                self.select_consistent_hash(context.as_ref())
+                self.select_consistent_hash(&available_nodes, context.as_ref())
             }
             LoadBalancingStrategy::ConsistentHashingWeighted => {
-                self.select_weighted_consistent_hash(context.as_ref())
+                self.select_weighted_consistent_hash(&available_nodes, context.as_ref())
             }
             LoadBalancingStrategy::RendezvousHashing => {
-                self.select_rendezvous_hash(context.as_ref())
+                self.select_rendezvous_hash(&available_nodes, context.as_ref())
             }
             LoadBalancingStrategy::GeographicProximity => {
                 self.select_geographic_proximity(&available_nodes, context.as_ref())
@@ -565,6 +676,8 @@ impl AdvancedLoadBalancer {
         };
 
         if let Some(node_id) = &selected {
+            self.start_probe(node_id);
+
             // Update node usage
             if let Some(mut node) = self.nodes.get_mut(node_id) {
                 node.current_requests += 1;
@@ -584,30 +697,83 @@ impl AdvancedLoadBalancer {
         Ok(selected)
     }
 
-    /// Get available nodes (healthy, enabled, circuit breaker closed)
+    /// Get available nodes (healthy, enabled, not ejected, admitted by circuit breaker)
     fn get_available_nodes(&self) -> Vec<String> {
-        self.nodes.iter()
+        // Collected first: the breaker checks update node state
+        let candidates: Vec<String> = self.nodes.iter()
             .filter(|entry| {
                 let node = entry.value();
-                node.enabled
-                    && node.health_status == HealthStatus::Healthy
-                    && self.is_circuit_breaker_closed(&node.id)
+                node.enabled && node.health_status == HealthStatus::Healthy
             })
             .map(|entry| entry.key().clone())
+            .collect();
+        candidates.into_iter()
+            .filter(|node_id| !self.is_ejected(node_id) && self.circuit_breaker_admits(node_id))
             .collect()
     }
 
-    /// Check if circuit breaker is closed
-    fn is_circuit_breaker_closed(&self, node_id: &str) -> bool {
+    /// Whether the circuit breaker lets a request through: closed breakers
+    /// always do, open ones turn half-open once their timeout passes, and
+    /// half-open ones admit a limited number of probes at a time
+    fn circuit_breaker_admits(&self, node_id: &str) -> bool {
         if !self.config.enable_circuit_breaker {
             return true;
         }
-        
-        if let Some(breaker) = self.circuit_breakers.get(node_id) {
-            matches!(breaker.state, CircuitBreakerState::Closed | CircuitBreakerState::HalfOpen)
-        } else {
-            true
+        let Some(mut breaker) = self.circuit_breakers.get_mut(node_id) else {
+            return true;
+        };
+        match breaker.state {
+            CircuitBreakerState::Closed => true,
+            CircuitBreakerState::Open => {
+                if breaker.next_attempt.is_none_or(|at| Instant::now() < at) {
+                    return false;
+                }
+                breaker.state = CircuitBreakerState::HalfOpen;
+                breaker.success_count = 0;
+                breaker.half_open_requests = 0;
+                self.sync_circuit_breaker_state(node_id, CircuitBreakerState::HalfOpen);
+                info!("Circuit breaker half-open for node {}", node_id);
+                self.config.circuit_breaker_half_open_max_requests > 0
+            }
+            CircuitBreakerState::HalfOpen => {
+                breaker.half_open_requests < self.config.circuit_breaker_half_open_max_requests
+            }
+        }
+    }
+
+    /// Count a request routed to a half-open node as a probe
+    fn start_probe(&self, node_id: &str) {
+        if let Some(mut breaker) = self.circuit_breakers.get_mut(node_id) {
+            if breaker.state == CircuitBreakerState::HalfOpen {
+                breaker.half_open_requests += 1;
+            }
+        }
+    }
+
+    /// Mirror a breaker transition on the node
+    fn sync_circuit_breaker_state(&self, node_id: &str, state: CircuitBreakerState) {
+        if let Some(mut node) = self.nodes.get_mut(node_id) {
+            node.circuit_breaker_state = state;
+        }
+    }
+
+    /// Get the circuit breaker state of a node
+    pub fn circuit_breaker_state(&self, node_id: &str) -> Option<CircuitBreakerState> {
+        self.circuit_breakers.get(node_id).map(|b| b.state.clone())
+    }
+
+    /// Whether a node is ejected as an outlier; expired ejections are lifted
+    pub fn is_ejected(&self, node_id: &str) -> bool {
+        let Some(mut state) = self.outliers.get_mut(node_id) else {
+            return false;
+        };
+        if state.is_ejected(Instant::now()) {
+            return true;
+        }
+        if state.ejected_until.take().is_some() {
+            info!("Outlier node {} returned to rotation", node_id);
         }
+        false
     }
 
     /// Round-robin selection
@@ -791,45 +957,41 @@ impl AdvancedLoadBalancer {
         }
     }
 
-    /// Consistent hashing selection
-    fn select_consistent_hash(&self, context: Option<&RequestContext>) -> Option<String> {
-        let ring = self.consistent_hash_ring.read();
-        if ring.nodes.is_empty() {
-            return None;
-        }
-
+    /// Consistent hashing selection (routing key, session, user or client IP)
+    fn select_consistent_hash(&self, nodes: &[String], context: Option<&RequestContext>) -> Option<String> {
         let key = context
-            .and_then(|c| c.session_id.as_ref())
-            .or_else(|| context.and_then(|c| c.user_id.as_ref()))
-            .or_else(|| context.and_then(|c| c.client_ip.as_ref()))
-            .cloned()
-            .unwrap_or_else(|| "default".to_string());
+            .and_then(|c| c.hash_key())
+            .map(String::as_str)
+            .unwrap_or("default");
+        self.ring_lookup(key, nodes)
+    }
 
-        let hash = self.hash_key(&key);
-        
-        // Find first node with hash >= key hash
-        ring.nodes.iter()
-            .find(|(node_hash, _)| *node_hash >= hash)
-            .map(|(_, node_id)| node_id.clone())
-            .or_else(|| ring.nodes.first().map(|(_, node_id)| node_id.clone()))
+    /// Weighted consistent hashing (the ring gives heavier nodes more virtual nodes)
+    fn select_weighted_consistent_hash(&self, nodes: &[String], context: Option<&RequestContext>) -> Option<String> {
+        self.select_consistent_hash(nodes, context)
     }
 
-    /// Weighted consistent hashing
-    fn select_weighted_consistent_hash(&self, context: Option<&RequestContext>) -> Option<String> {
-        // Similar to consistent hashing but considers weights
-        self.select_consistent_hash(context)
+    /// First available node clockwise from the key's ring position; keys of
+    /// unavailable nodes move to their ring successors, every other key stays put
+    fn ring_lookup(&self, key: &str, nodes: &[String]) -> Option<String> {
+        let ring = self.consistent_hash_ring.read();
+        let available: HashSet<&str> = nodes.iter().map(String::as_str).collect();
+        let hash = self.hash_key(key);
+        let start = ring.nodes.partition_point(|(node_hash, _)| *node_hash < hash);
+        ring.nodes[start..].iter()
+            .chain(&ring.nodes[..start])
+            .find(|(_, node_id)| available.contains(node_id.as_str()))
+            .map(|(_, node_id)| node_id.clone())
     }
 
     /// Rendezvous hashing (highest random weight)
-    fn select_rendezvous_hash(&self, context: Option<&RequestContext>) -> Option<String> {
+    fn select_rendezvous_hash(&self, nodes: &[String], context: Option<&RequestContext>) -> Option<String> {
         let key = context
-            .and_then(|c| c.session_id.as_ref())
-            .or_else(|| context.and_then(|c| c.user_id.as_ref()))
+            .and_then(|c| c.hash_key())
             .cloned()
             .unwrap_or_else(|| "default".to_string());
 
-        let available_nodes = self.get_available_nodes();
-        available_nodes.iter()
+        nodes.iter()
             .max_by(|a, b| {
                 let score_a = self.rendezvous_score(&key, a);
                 let score_b = self.rendezvous_score(&key, b);
@@ -1042,12 +1204,7 @@ impl AdvancedLoadBalancer {
         if let Some(ctx) = context {
             if let Some(ref ip) = ctx.client_ip {
                 // Hash IP to consistent node
-                let hash = self.hash_key(ip);
-                let ring = self.consistent_hash_ring.read();
-                ring.nodes.iter()
-                    .find(|(node_hash, _)| *node_hash >= hash)
-                    .map(|(_, node_id)| node_id.clone())
-                    .or_else(|| nodes.first().cloned())
+                self.ring_lookup(ip, nodes)
             } else {
                 self.select_least_connections(nodes)
             }
@@ -1119,7 +1276,13 @@ impl AdvancedLoadBalancer {
             let node = entry.value();
             
             // Create virtual nodes
-            for i in 0..ring.virtual_nodes_per_node {
+            let virtual_nodes = if ring.weighted {
+                let weight = node.weight.clamp(self.config.min_weight, self.config.max_weight);
+                ((ring.virtual_nodes_per_node as f64 * weight).round() as usize).max(1)
+            } else {
+                ring.virtual_nodes_per_node
+            };
+            for i in 0..virtual_nodes {
                 let virtual_key = format!("{}-{}", node_id, i);
                 let hash = self.hash_key(&virtual_key);
                 ring.nodes.push((hash, node_id.clone()));
@@ -1150,6 +1313,9 @@ impl AdvancedLoadBalancer {
         if self.config.enable_circuit_breaker {
             self.update_circuit_breaker(node_id, success);
         }
+        if self.config.outlier_ejection.enabled {
+            self.update_outlier_state(node_id, success);
+        }
 
         // Update statistics
         let mut stats = self.stats.write();
@@ -1172,6 +1338,7 @@ impl AdvancedLoadBalancer {
                             breaker.state = CircuitBreakerState::Open;
                             breaker.last_failure = Some(Instant::now());
                             breaker.next_attempt = Some(Instant::now() + self.config.circuit_breaker_timeout);
+                            self.sync_circuit_breaker_state(node_id, CircuitBreakerState::Open);
                             warn!("Circuit breaker opened for node {}", node_id);
                         }
                     } else {
@@ -1179,16 +1346,10 @@ impl AdvancedLoadBalancer {
                     }
                 }
                 CircuitBreakerState::Open => {
-                    // Check if timeout expired
-                    if let Some(next_attempt) = breaker.next_attempt {
-                        if Instant::now() >= next_attempt {
-                            breaker.state = CircuitBreakerState::HalfOpen;
-                            breaker.half_open_requests = 0;
-                        }
-                    }
+                    // Late result of a request sent before the breaker opened
                 }
                 CircuitBreakerState::HalfOpen => {
-                    breaker.half_open_requests += 1;
+                    breaker.half_open_requests = breaker.half_open_requests.saturating_sub(1);
                     if success {
                         breaker.success_count += 1;
                         if breaker.success_count >= self.config.circuit_breaker_success_threshold {
@@ -1196,23 +1357,83 @@ impl AdvancedLoadBalancer {
                             breaker.failure_count = 0;
                             breaker.success_count = 0;
                             breaker.half_open_requests = 0;
+                            self.sync_circuit_breaker_state(node_id, CircuitBreakerState::Closed);
                             info!("Circuit breaker closed for node {}", node_id);
                         }
                     } else {
+                        // A failed probe reopens the breaker for another timeout
                         breaker.state = CircuitBreakerState::Open;
+                        breaker.success_count = 0;
                         breaker.last_failure = Some(Instant::now());
                         breaker.next_attempt = Some(Instant::now() + self.config.circuit_breaker_timeout);
-                    }
-                    
-                    if breaker.half_open_requests >= self.config.circuit_breaker_half_open_max_requests {
-                        breaker.state = CircuitBreakerState::Open;
-                        breaker.next_attempt = Some(Instant::now() + self.config.circuit_breaker_timeout);
+                        self.sync_circuit_breaker_state(node_id, CircuitBreakerState::Open);
+                        warn!("Circuit breaker reopened for node {}", node_id);
                     }
                 }
             }
         }
     }
 
+    /// Update outlier detection, ejecting the node if it crossed a threshold
+    fn update_outlier_state(&self, node_id: &str, success: bool) {
+        let settings = &self.config.outlier_ejection;
+        let now = Instant::now();
+        // Counted before taking the node's entry, which the count would wait on
+        let ejected = self.outliers.iter().filter(|entry| entry.value().is_ejected(now)).count();
+        let total = self.nodes.len();
+
+        let Some(mut state) = self.outliers.get_mut(node_id) else {
+            return;
+        };
+        if state.is_ejected(now) {
+            // Late result of a request sent before the ejection
+            return;
+        }
+        if now.duration_since(state.interval_started) >= settings.interval {
+            // A clean interval outside ejection lets repeat ejections shorten again
+            if state.interval_errors == 0 {
+                state.ejections = state.ejections.saturating_sub(1);
+            }
+            state.interval_started = now;
+            state.interval_requests = 0;
+            state.interval_errors = 0;
+        }
+        state.interval_requests += 1;
+        if success {
+            state.consecutive_failures = 0;
+        } else {
+            state.consecutive_failures += 1;
+            state.interval_errors += 1;
+        }
+
+        let consecutive = settings.consecutive_failures > 0
+            && state.consecutive_failures >= settings.consecutive_failures;
+        let error_rate = state.interval_requests >= settings.min_requests.max(1)
+            && state.interval_errors as f64 / state.interval_requests as f64 >= settings.error_rate_threshold;
+        if !consecutive && !error_rate {
+            return;
+        }
+
+        let max_ejected = ((total as f64 * settings.max_ejection_percent / 100.0).floor() as usize)
+            .max(1)
+            .min(total.saturating_sub(1));
+        if ejected >= max_ejected {
+            debug!("Outlier node {} not ejected: {} of {} nodes already are", node_id, ejected, total);
+            return;
+        }
+
+        state.ejections += 1;
+        let duration = settings.base_ejection_time
+            .saturating_mul(state.ejections)
+            .min(settings.max_ejection_time);
+        state.ejected_until = Some(now + duration);
+        state.consecutive_failures = 0;
+        state.interval_started = now;
+        state.interval_requests = 0;
+        state.interval_errors = 0;
+        warn!("Ejected outlier node {} for {:?}", node_id, duration);
+    }
+
     /// Start health checking
     pub fn start_health_checks(&self) {
         let health_checker = self.health_checker.clone();
@@ -1260,6 +1481,10 @@ impl AdvancedLoadBalancer {
         stats.circuit_breakers_open = self.circuit_breakers.iter()
             .filter(|entry| matches!(entry.value().state, CircuitBreakerState::Open))
             .count();
+        let now = Instant::now();
+        stats.nodes_ejected = self.outliers.iter()
+            .filter(|entry| entry.value().is_ejected(now))
+            .count();
         
         stats
     }
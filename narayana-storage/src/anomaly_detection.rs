@@ -0,0 +1,531 @@
+// Anomaly detection jobs over streaming tables
+// Per-column z-score / EWMA / seasonal detectors fed by the CDC stream, with persisted state
+
+use crate::cdc::{ChangeEvent, ChangeFeed, ChangeOperation};
+use async_trait::async_trait;
+use narayana_core::{column::Column, schema::{DataType, Schema}, types::TableId, Error, Result};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, info, warn};
+
+/// Recent anomalies kept for the management API
+const MAX_RECENT_ANOMALIES: usize = 1000;
+
+/// Detection algorithm of a detector
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum DetectionMethod {
+    /// Running mean/variance (Welford); flags |z| > threshold
+    ZScore { threshold: f64 },
+    /// Exponentially weighted mean/variance; adapts to drift
+    Ewma { alpha: f64, threshold: f64 },
+    /// Per-phase seasonal baseline (period in rows) plus residual variance
+    Seasonal { period: usize, alpha: f64, threshold: f64 },
+}
+
+impl DetectionMethod {
+    fn validate(&self) -> Result<()> {
+        let (alpha, threshold) = match self {
+            DetectionMethod::ZScore { threshold } => (0.5, *threshold),
+            DetectionMethod::Ewma { alpha, threshold } => (*alpha, *threshold),
+            DetectionMethod::Seasonal { period, alpha, threshold } => {
+                if *period < 2 || *period > 100_000 {
+                    return Err(Error::Configuration(format!("Seasonal period must be 2..=100000, got {}", period)));
+                }
+                (*alpha, *threshold)
+            }
+        };
+        if !(alpha > 0.0 && alpha < 1.0) {
+            return Err(Error::Configuration(format!("alpha must be in (0, 1), got {}", alpha)));
+        }
+        if !(threshold.is_finite() && threshold > 0.0) {
+            return Err(Error::Configuration(format!("threshold must be positive, got {}", threshold)));
+        }
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            DetectionMethod::ZScore { .. } => "zscore",
+            DetectionMethod::Ewma { .. } => "ewma",
+            DetectionMethod::Seasonal { .. } => "seasonal",
+        }
+    }
+}
+
+fn default_warmup() -> u64 {
+    30
+}
+
+/// Detector configuration (one numeric column of one table)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DetectorConfig {
+    pub id: String,
+    pub table_id: TableId,
+    pub column: String,
+    #[serde(flatten)]
+    pub method: DetectionMethod,
+    /// Observations before anomalies are reported
+    #[serde(default = "default_warmup")]
+    pub warmup: u64,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Learned detector state (persisted between restarts)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DetectorState {
+    pub observations: u64,
+    pub anomalies: u64,
+    pub last_row_id: Option<u64>,
+    mean: f64,
+    m2: f64,
+    variance: f64,
+    seasonal_levels: Vec<Option<f64>>,
+}
+
+impl DetectorState {
+    /// Observe one value; returns (score, expected) when it is anomalous
+    fn observe(&mut self, config: &DetectorConfig, value: f64) -> Option<(f64, f64)> {
+        let warmed_up = self.observations >= config.warmup;
+        let result = match &config.method {
+            DetectionMethod::ZScore { threshold } => {
+                let expected = self.mean;
+                let std_dev = if self.observations > 1 { (self.m2 / (self.observations - 1) as f64).sqrt() } else { 0.0 };
+                // Welford update
+                self.observations += 1;
+                let delta = value - self.mean;
+                self.mean += delta / self.observations as f64;
+                self.m2 += delta * (value - self.mean);
+                score_if_anomalous(warmed_up, value, expected, std_dev, *threshold)
+            }
+            DetectionMethod::Ewma { alpha, threshold } => {
+                let expected = self.mean;
+                let std_dev = self.variance.sqrt();
+                if self.observations == 0 {
+                    self.mean = value;
+                } else {
+                    let diff = value - self.mean;
+                    let incr = alpha * diff;
+                    self.mean += incr;
+                    self.variance = (1.0 - alpha) * (self.variance + diff * incr);
+                }
+                self.observations += 1;
+                score_if_anomalous(warmed_up, value, expected, std_dev, *threshold)
+            }
+            DetectionMethod::Seasonal { period, alpha, threshold } => {
+                if self.seasonal_levels.len() != *period {
+                    self.seasonal_levels = vec![None; *period];
+                }
+                let phase = (self.observations % *period as u64) as usize;
+                let expected = self.seasonal_levels[phase].unwrap_or(value);
+                let residual = value - expected;
+                let std_dev = self.variance.sqrt();
+                // A full season must be seen before the baseline means anything
+                let seasonal_warm = warmed_up && self.observations >= 2 * *period as u64;
+                let result = score_if_anomalous(seasonal_warm, value, expected, std_dev, *threshold);
+                self.seasonal_levels[phase] = Some(match self.seasonal_levels[phase] {
+                    Some(level) => level + alpha * (value - level),
+                    None => value,
+                });
+                if self.observations >= *period as u64 {
+                    self.variance = (1.0 - alpha) * self.variance + alpha * residual * residual;
+                }
+                self.observations += 1;
+                result
+            }
+        };
+        if result.is_some() {
+            self.anomalies += 1;
+        }
+        result
+    }
+}
+
+fn score_if_anomalous(warmed_up: bool, value: f64, expected: f64, std_dev: f64, threshold: f64) -> Option<(f64, f64)> {
+    if !warmed_up || std_dev <= f64::EPSILON {
+        return None;
+    }
+    let score = (value - expected) / std_dev;
+    (score.abs() > threshold).then_some((score, expected))
+}
+
+/// Anomaly found by a detector
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnomalyEvent {
+    pub detector_id: String,
+    pub table_id: u64,
+    pub column: String,
+    pub row_id: u64,
+    pub value: f64,
+    pub expected: f64,
+    pub score: f64,
+    pub method: String,
+    pub detected_at: u64,
+}
+
+/// Destination for anomaly events (e.g. narayana-rde)
+#[async_trait]
+pub trait AnomalySink: Send + Sync {
+    async fn emit(&self, event: &AnomalyEvent) -> Result<()>;
+}
+
+/// Detector configuration plus current state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectorStatus {
+    pub config: DetectorConfig,
+    pub state: DetectorState,
+}
+
+/// Runs anomaly detectors over the CDC stream
+pub struct AnomalyDetectionManager {
+    detectors: RwLock<HashMap<String, DetectorStatus>>,
+    recent: RwLock<VecDeque<AnomalyEvent>>,
+    sinks: RwLock<Vec<Arc<dyn AnomalySink>>>,
+    state_dir: Option<PathBuf>,
+}
+
+impl AnomalyDetectionManager {
+    /// `state_dir` stores one JSON file per detector; `None` keeps state in memory only
+    pub fn new(state_dir: Option<PathBuf>) -> Result<Self> {
+        if let Some(dir) = &state_dir {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| Error::Storage(format!("Failed to create anomaly state directory: {}", e)))?;
+        }
+        Ok(Self {
+            detectors: RwLock::new(HashMap::new()),
+            recent: RwLock::new(VecDeque::new()),
+            sinks: RwLock::new(Vec::new()),
+            state_dir,
+        })
+    }
+
+    pub fn add_sink(&self, sink: Arc<dyn AnomalySink>) {
+        self.sinks.write().push(sink);
+    }
+
+    /// Load detectors persisted by a previous run
+    pub async fn load_persisted(&self) -> Result<usize> {
+        let Some(dir) = &self.state_dir else { return Ok(0) };
+        let mut entries = tokio::fs::read_dir(dir).await
+            .map_err(|e| Error::Storage(format!("Failed to read anomaly state directory: {}", e)))?;
+        let mut loaded = 0;
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            match tokio::fs::read(&path).await.map(|bytes| serde_json::from_slice::<DetectorStatus>(&bytes)) {
+                Ok(Ok(status)) => {
+                    self.detectors.write().insert(status.config.id.clone(), status);
+                    loaded += 1;
+                }
+                Ok(Err(e)) => warn!("Skipping corrupt detector state {:?}: {}", path, e),
+                Err(e) => warn!("Failed to read detector state {:?}: {}", path, e),
+            }
+        }
+        info!("Loaded {} anomaly detectors", loaded);
+        Ok(loaded)
+    }
+
+    /// Create (or replace) a detector on a numeric column of `schema`
+    pub async fn create_detector(&self, config: DetectorConfig, schema: &Schema) -> Result<()> {
+        validate_detector_id(&config.id)?;
+        config.method.validate()?;
+        let field = schema.field(&config.column).ok_or_else(|| Error::ColumnNotFound(config.column.clone()))?;
+        if !is_numeric(&field.data_type) {
+            return Err(Error::InvalidDataType {
+                expected: "numeric column".to_string(),
+                actual: format!("{:?}", field.data_type),
+            });
+        }
+        let id = config.id.clone();
+        self.detectors.write().insert(id.clone(), DetectorStatus { config, state: DetectorState::default() });
+        self.persist(&id).await
+    }
+
+    pub async fn remove_detector(&self, id: &str) -> Result<bool> {
+        let removed = self.detectors.write().remove(id).is_some();
+        if removed {
+            if let Some(path) = self.state_path(id) {
+                let _ = tokio::fs::remove_file(path).await;
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Forget learned state (e.g. after a known regime change)
+    pub async fn reset_detector(&self, id: &str) -> Result<()> {
+        {
+            let mut detectors = self.detectors.write();
+            let status = detectors.get_mut(id)
+                .ok_or_else(|| Error::Storage(format!("Detector not found: {}", id)))?;
+            status.state = DetectorState::default();
+        }
+        self.persist(id).await
+    }
+
+    pub fn get_detector(&self, id: &str) -> Option<DetectorStatus> {
+        self.detectors.read().get(id).cloned()
+    }
+
+    pub fn list_detectors(&self) -> Vec<DetectorStatus> {
+        let mut detectors: Vec<DetectorStatus> = self.detectors.read().values().cloned().collect();
+        detectors.sort_by(|a, b| a.config.id.cmp(&b.config.id));
+        detectors
+    }
+
+    /// Most recent anomalies first, optionally for one detector
+    pub fn recent_anomalies(&self, detector_id: Option<&str>, limit: usize) -> Vec<AnomalyEvent> {
+        self.recent.read()
+            .iter()
+            .rev()
+            .filter(|e| detector_id.is_none_or(|id| e.detector_id == id))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// Run every detector of the changed table over the new rows
+    pub async fn process_change(&self, change: &ChangeEvent) -> Vec<AnomalyEvent> {
+        if change.operation == ChangeOperation::DropTable {
+            let dropped: Vec<String> = self.detectors.read().values()
+                .filter(|s| s.config.table_id == change.table_id)
+                .map(|s| s.config.id.clone())
+                .collect();
+            for id in dropped {
+                let _ = self.remove_detector(&id).await;
+            }
+            return Vec::new();
+        }
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let mut anomalies = Vec::new();
+        let mut touched = Vec::new();
+        {
+            let mut detectors = self.detectors.write();
+            for status in detectors.values_mut() {
+                let config = &status.config;
+                if !config.enabled || config.table_id != change.table_id {
+                    continue;
+                }
+                let Some(column) = change.schema.field_index(&config.column).and_then(|idx| change.columns.get(idx)) else {
+                    continue;
+                };
+                for row in 0..column.len() {
+                    let Some(value) = numeric_value(column, row) else { continue };
+                    if !value.is_finite() {
+                        continue;
+                    }
+                    let row_id = change.first_row_id + row as u64;
+                    status.state.last_row_id = Some(row_id);
+                    if let Some((score, expected)) = status.state.observe(config, value) {
+                        anomalies.push(AnomalyEvent {
+                            detector_id: config.id.clone(),
+                            table_id: change.table_id.0,
+                            column: config.column.clone(),
+                            row_id,
+                            value,
+                            expected,
+                            score,
+                            method: config.method.name().to_string(),
+                            detected_at: now,
+                        });
+                    }
+                }
+                touched.push(config.id.clone());
+            }
+        }
+
+        if !anomalies.is_empty() {
+            let mut recent = self.recent.write();
+            for event in &anomalies {
+                if recent.len() >= MAX_RECENT_ANOMALIES {
+                    recent.pop_front();
+                }
+                recent.push_back(event.clone());
+            }
+        }
+        let sinks: Vec<Arc<dyn AnomalySink>> = self.sinks.read().clone();
+        for event in &anomalies {
+            for sink in &sinks {
+                if let Err(e) = sink.emit(event).await {
+                    warn!("Failed to emit anomaly from detector {}: {}", event.detector_id, e);
+                }
+            }
+        }
+        for id in touched {
+            if let Err(e) = self.persist(&id).await {
+                warn!("Failed to persist detector {} state: {}", id, e);
+            }
+        }
+        anomalies
+    }
+
+    /// Consume the CDC stream in the background
+    pub fn start(self: Arc<Self>, feed: &ChangeFeed) -> tokio::task::JoinHandle<()> {
+        let mut receiver = feed.subscribe();
+        tokio::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(change) => {
+                        let anomalies = self.process_change(&change).await;
+                        if !anomalies.is_empty() {
+                            debug!("Detected {} anomalies in table {}", anomalies.len(), change.table_id.0);
+                        }
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("Anomaly detection lagged behind the change feed, skipped {} changes", skipped);
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    fn state_path(&self, id: &str) -> Option<PathBuf> {
+        self.state_dir.as_ref().map(|dir| dir.join(format!("{}.json", id)))
+    }
+
+    async fn persist(&self, id: &str) -> Result<()> {
+        let Some(path) = self.state_path(id) else { return Ok(()) };
+        let Some(status) = self.get_detector(id) else { return Ok(()) };
+        let bytes = serde_json::to_vec(&status)
+            .map_err(|e| Error::Serialization(format!("Failed to serialize detector state: {}", e)))?;
+        // Write to a temp file and rename so a crash never leaves a torn state file
+        let temp_path = path.with_extension("json.tmp");
+        tokio::fs::write(&temp_path, &bytes).await
+            .map_err(|e| Error::Storage(format!("Failed to write detector state: {}", e)))?;
+        tokio::fs::rename(&temp_path, &path).await
+            .map_err(|e| Error::Storage(format!("Failed to write detector state: {}", e)))?;
+        Ok(())
+    }
+}
+
+/// Detector ids become file names, so keep them to a safe alphabet
+fn validate_detector_id(id: &str) -> Result<()> {
+    if id.is_empty() || id.len() > 128 {
+        return Err(Error::Configuration("Detector id must be 1-128 characters".to_string()));
+    }
+    if !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(Error::Configuration("Detector id may only contain letters, digits, '-' and '_'".to_string()));
+    }
+    Ok(())
+}
+
+pub(crate) fn is_numeric(data_type: &DataType) -> bool {
+    matches!(
+        data_type,
+        DataType::Int8 | DataType::Int16 | DataType::Int32 | DataType::Int64
+            | DataType::UInt8 | DataType::UInt16 | DataType::UInt32 | DataType::UInt64
+            | DataType::Float32 | DataType::Float64 | DataType::Timestamp
+    )
+}
+
+pub(crate) fn numeric_value(column: &Column, row: usize) -> Option<f64> {
+    match column {
+        Column::Int8(v) => v.get(row).map(|x| *x as f64),
+        Column::Int16(v) => v.get(row).map(|x| *x as f64),
+        Column::Int32(v) => v.get(row).map(|x| *x as f64),
+        Column::Int64(v) | Column::Timestamp(v) => v.get(row).map(|x| *x as f64),
+        Column::UInt8(v) => v.get(row).map(|x| *x as f64),
+        Column::UInt16(v) => v.get(row).map(|x| *x as f64),
+        Column::UInt32(v) => v.get(row).map(|x| *x as f64),
+        Column::UInt64(v) => v.get(row).map(|x| *x as f64),
+        Column::Float32(v) => v.get(row).map(|x| *x as f64),
+        Column::Float64(v) => v.get(row).copied(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use narayana_core::schema::Field;
+
+    fn schema() -> Schema {
+        Schema::new(vec![Field {
+            name: "temperature".to_string(),
+            data_type: DataType::Float64,
+            nullable: false,
+            default_value: None,
+        }])
+    }
+
+    fn config(id: &str, method: DetectionMethod) -> DetectorConfig {
+        DetectorConfig {
+            id: id.to_string(),
+            table_id: TableId(1),
+            column: "temperature".to_string(),
+            method,
+            warmup: 20,
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn test_zscore_and_ewma_flag_spike() {
+        for method in [
+            DetectionMethod::ZScore { threshold: 4.0 },
+            DetectionMethod::Ewma { alpha: 0.1, threshold: 4.0 },
+        ] {
+            let config = config("d", method);
+            let mut state = DetectorState::default();
+            for i in 0..100 {
+                let value = 20.0 + (i % 5) as f64 * 0.1;
+                assert!(state.observe(&config, value).is_none());
+            }
+            let (score, expected) = state.observe(&config, 80.0).unwrap();
+            assert!(score > 4.0);
+            assert!((expected - 20.2).abs() < 1.0);
+        }
+    }
+
+    #[test]
+    fn test_seasonal_baseline() {
+        let config = config("s", DetectionMethod::Seasonal { period: 4, alpha: 0.3, threshold: 5.0 });
+        let pattern = [10.0, 50.0, 10.0, -30.0];
+        let mut state = DetectorState::default();
+        for i in 0..200 {
+            // Large seasonal swings with small noise are not anomalies
+            let value = pattern[i % 4] + ((i * 7) % 3) as f64 * 0.1;
+            assert!(state.observe(&config, value).is_none(), "row {}", i);
+        }
+        // 10.0 is normal overall but wrong for the phase that expects 50.0
+        assert!(state.observe(&config, 10.0).is_none());
+        assert!(state.observe(&config, 10.0).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_change_feed_detection_and_persistence() {
+        let dir = std::env::temp_dir().join(format!("narayana_anomaly_{}", uuid::Uuid::new_v4()));
+        let manager = AnomalyDetectionManager::new(Some(dir.clone())).unwrap();
+        let schema = schema();
+        manager.create_detector(config("temp", DetectionMethod::ZScore { threshold: 4.0 }), &schema).await.unwrap();
+
+        let feed = ChangeFeed::new(16);
+        let mut receiver = feed.subscribe();
+        let mut values: Vec<f64> = (0..50).map(|i| 20.0 + (i % 3) as f64).collect();
+        values.push(500.0);
+        feed.publish_insert(TableId(1), &schema, &[Column::Float64(values)]);
+        let change = receiver.recv().await.unwrap();
+        let anomalies = manager.process_change(&change).await;
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].row_id, 50);
+        assert_eq!(manager.recent_anomalies(Some("temp"), 10).len(), 1);
+
+        let reloaded = AnomalyDetectionManager::new(Some(dir.clone())).unwrap();
+        assert_eq!(reloaded.load_persisted().await.unwrap(), 1);
+        assert_eq!(reloaded.get_detector("temp").unwrap().state.observations, 51);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
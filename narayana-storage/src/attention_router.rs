@@ -10,7 +10,7 @@ use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 use parking_lot::RwLock;
 use tokio::sync::broadcast;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use tracing::{debug, info};
 
 /// Attention Router - Allocates cognitive resources
@@ -312,3 +312,458 @@ impl AttentionRouter {
     }
 }
 
+
+/// How CPLs sharing one body may use a capability
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CapabilityAccess {
+    /// Motors and other actuators: one CPL at a time, under a lease
+    Exclusive,
+    /// Sensory streams: any number of CPLs read concurrently
+    Shared,
+}
+
+/// Preemption rules for exclusive leases
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArbitrationConfig {
+    /// Lease length when the request does not set one
+    pub default_lease_ms: u64,
+    /// Longest lease a CPL can hold without renewing
+    pub max_lease_ms: u64,
+    /// A lease cannot be preempted until held this long, so control does not flap
+    pub min_hold_ms: u64,
+    /// Priority a requester needs above the holder's to preempt it
+    pub preempt_margin: f64,
+    /// Access for capabilities that were never registered
+    pub default_access: CapabilityAccess,
+}
+
+impl Default for ArbitrationConfig {
+    fn default() -> Self {
+        Self {
+            default_lease_ms: 5_000,
+            max_lease_ms: 60_000,
+            min_hold_ms: 500,
+            preempt_margin: 0.1,
+            default_access: CapabilityAccess::Exclusive,
+        }
+    }
+}
+
+/// Request for a capability
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaseRequest {
+    pub capability: String,
+    pub cpl_id: String,
+    /// 0.0-1.0
+    pub priority: f64,
+    #[serde(default)]
+    pub duration_ms: Option<u64>,
+    /// Whether a higher-priority CPL may take the capability over
+    #[serde(default = "default_preemptible")]
+    pub preemptible: bool,
+}
+
+fn default_preemptible() -> bool {
+    true
+}
+
+/// Granted access to a capability
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceLease {
+    pub lease_id: String,
+    pub capability: String,
+    pub cpl_id: String,
+    pub access: CapabilityAccess,
+    pub priority: f64,
+    pub preemptible: bool,
+    pub acquired_at_ms: u64,
+    pub expires_at_ms: u64,
+}
+
+/// Result of a lease request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum LeaseOutcome {
+    Granted { lease: ResourceLease },
+    /// Granted by taking the capability from a lower-priority CPL
+    Preempted { lease: ResourceLease, preempted: ResourceLease },
+    /// Another CPL holds the capability and may not be preempted
+    Denied { holder: ResourceLease },
+}
+
+impl LeaseOutcome {
+    pub fn lease(&self) -> Option<&ResourceLease> {
+        match self {
+            LeaseOutcome::Granted { lease } | LeaseOutcome::Preempted { lease, .. } => Some(lease),
+            LeaseOutcome::Denied { .. } => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictResolution {
+    Denied,
+    Preempted,
+}
+
+/// Contention over a capability, sent to both CPLs involved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceConflict {
+    pub capability: String,
+    pub holder_cpl: String,
+    pub holder_priority: f64,
+    pub requester_cpl: String,
+    pub requester_priority: f64,
+    pub resolution: ConflictResolution,
+    pub timestamp_ms: u64,
+}
+
+/// Arbitrates capabilities of one body between the CPLs running on it
+pub struct ResourceArbiter {
+    config: ArbitrationConfig,
+    access: RwLock<HashMap<String, CapabilityAccess>>,
+    // Capability -> exclusive lease
+    exclusive: RwLock<HashMap<String, ResourceLease>>,
+    // Capability -> shared leases by CPL
+    shared: RwLock<HashMap<String, HashMap<String, ResourceLease>>>,
+    // CPL -> its event channel, for conflict events
+    cpls: RwLock<HashMap<String, broadcast::Sender<CPLEvent>>>,
+    conflicts: RwLock<VecDeque<ResourceConflict>>,
+}
+
+/// Conflicts kept for inspection
+const MAX_CONFLICT_HISTORY: usize = 1000;
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+impl ResourceArbiter {
+    pub fn new(config: ArbitrationConfig) -> Self {
+        Self {
+            config,
+            access: RwLock::new(HashMap::new()),
+            exclusive: RwLock::new(HashMap::new()),
+            shared: RwLock::new(HashMap::new()),
+            cpls: RwLock::new(HashMap::new()),
+            conflicts: RwLock::new(VecDeque::new()),
+        }
+    }
+
+    /// Declare whether a capability is exclusive (actuator) or shared (sensor)
+    pub fn register_capability(&self, capability: &str, access: CapabilityAccess) {
+        self.access.write().insert(capability.to_string(), access);
+    }
+
+    pub fn capability_access(&self, capability: &str) -> CapabilityAccess {
+        self.access.read().get(capability).copied().unwrap_or(self.config.default_access)
+    }
+
+    /// Route conflict events for a CPL to its event channel
+    pub fn attach_cpl(&self, cpl_id: &str, events: broadcast::Sender<CPLEvent>) {
+        self.cpls.write().insert(cpl_id.to_string(), events);
+    }
+
+    /// Forget a CPL and release everything it holds
+    pub fn detach_cpl(&self, cpl_id: &str) {
+        self.cpls.write().remove(cpl_id);
+        self.release_all(cpl_id);
+    }
+
+    /// Request a capability
+    ///
+    /// Shared capabilities are always granted. An exclusive capability goes to
+    /// the requester if it is free, expired or already held by the requester;
+    /// otherwise it is preempted when the holder allows it, has held it for
+    /// `min_hold_ms` and the requester's priority exceeds the holder's by
+    /// `preempt_margin`. Denials and preemptions are surfaced to both CPLs.
+    pub fn acquire(&self, request: LeaseRequest) -> Result<LeaseOutcome> {
+        if request.capability.is_empty() || request.capability.len() > 256 {
+            return Err(Error::Storage("Invalid capability name".to_string()));
+        }
+        if request.cpl_id.is_empty() || request.cpl_id.len() > 256 {
+            return Err(Error::Storage("Invalid CPL id".to_string()));
+        }
+        if !request.priority.is_finite() {
+            return Err(Error::Storage("Invalid lease priority (NaN/Inf)".to_string()));
+        }
+
+        let now = now_ms();
+        let duration = request.duration_ms.unwrap_or(self.config.default_lease_ms).clamp(1, self.config.max_lease_ms);
+        let access = self.capability_access(&request.capability);
+        let lease = ResourceLease {
+            lease_id: uuid::Uuid::new_v4().to_string(),
+            capability: request.capability.clone(),
+            cpl_id: request.cpl_id.clone(),
+            access,
+            priority: request.priority.clamp(0.0, 1.0),
+            preemptible: request.preemptible,
+            acquired_at_ms: now,
+            expires_at_ms: now.saturating_add(duration),
+        };
+
+        if access == CapabilityAccess::Shared {
+            self.shared.write()
+                .entry(request.capability.clone())
+                .or_default()
+                .insert(request.cpl_id.clone(), lease.clone());
+            return Ok(LeaseOutcome::Granted { lease });
+        }
+
+        let outcome = {
+            let mut exclusive = self.exclusive.write();
+            match exclusive.get(&request.capability) {
+                Some(holder) if holder.expires_at_ms > now && holder.cpl_id != request.cpl_id => {
+                    let can_preempt = holder.preemptible
+                        && now.saturating_sub(holder.acquired_at_ms) >= self.config.min_hold_ms
+                        && lease.priority >= holder.priority + self.config.preempt_margin;
+                    if can_preempt {
+                        let preempted = holder.clone();
+                        exclusive.insert(request.capability.clone(), lease.clone());
+                        LeaseOutcome::Preempted { lease: lease.clone(), preempted }
+                    } else {
+                        LeaseOutcome::Denied { holder: holder.clone() }
+                    }
+                }
+                _ => {
+                    exclusive.insert(request.capability.clone(), lease.clone());
+                    LeaseOutcome::Granted { lease: lease.clone() }
+                }
+            }
+        };
+
+        match &outcome {
+            LeaseOutcome::Preempted { preempted, .. } => {
+                info!("CPL {} preempted {} from CPL {}", lease.cpl_id, lease.capability, preempted.cpl_id);
+                self.surface_conflict(preempted, &lease, ConflictResolution::Preempted);
+            }
+            LeaseOutcome::Denied { holder } => {
+                debug!("CPL {} denied {} held by CPL {}", lease.cpl_id, lease.capability, holder.cpl_id);
+                self.surface_conflict(holder, &lease, ConflictResolution::Denied);
+            }
+            LeaseOutcome::Granted { .. } => {}
+        }
+        Ok(outcome)
+    }
+
+    /// Extend a lease held by a CPL; fails if it expired or was preempted
+    pub fn renew(&self, lease_id: &str, duration_ms: Option<u64>) -> Result<ResourceLease> {
+        let now = now_ms();
+        let duration = duration_ms.unwrap_or(self.config.default_lease_ms).clamp(1, self.config.max_lease_ms);
+
+        let mut exclusive = self.exclusive.write();
+        if let Some(lease) = exclusive.values_mut().find(|lease| lease.lease_id == lease_id) {
+            if lease.expires_at_ms <= now {
+                return Err(Error::Storage(format!("Lease {} expired", lease_id)));
+            }
+            lease.expires_at_ms = now.saturating_add(duration);
+            return Ok(lease.clone());
+        }
+        drop(exclusive);
+
+        let mut shared = self.shared.write();
+        for readers in shared.values_mut() {
+            if let Some(lease) = readers.values_mut().find(|lease| lease.lease_id == lease_id) {
+                lease.expires_at_ms = now.saturating_add(duration);
+                return Ok(lease.clone());
+            }
+        }
+        Err(Error::Storage(format!("Lease {} not found (expired or preempted)", lease_id)))
+    }
+
+    /// Release a lease; returns whether it was still held
+    pub fn release(&self, lease_id: &str) -> bool {
+        let mut exclusive = self.exclusive.write();
+        let before = exclusive.len();
+        exclusive.retain(|_, lease| lease.lease_id != lease_id);
+        if exclusive.len() != before {
+            return true;
+        }
+        drop(exclusive);
+
+        let mut shared = self.shared.write();
+        let mut released = false;
+        for readers in shared.values_mut() {
+            let before = readers.len();
+            readers.retain(|_, lease| lease.lease_id != lease_id);
+            released |= readers.len() != before;
+        }
+        shared.retain(|_, readers| !readers.is_empty());
+        released
+    }
+
+    /// Release every lease held by a CPL
+    pub fn release_all(&self, cpl_id: &str) -> usize {
+        let mut exclusive = self.exclusive.write();
+        let before = exclusive.len();
+        exclusive.retain(|_, lease| lease.cpl_id != cpl_id);
+        let mut released = before - exclusive.len();
+        drop(exclusive);
+
+        let mut shared = self.shared.write();
+        for readers in shared.values_mut() {
+            released += readers.remove(cpl_id).map_or(0, |_| 1);
+        }
+        shared.retain(|_, readers| !readers.is_empty());
+        released
+    }
+
+    /// Current exclusive holder of a capability
+    pub fn holder(&self, capability: &str) -> Option<ResourceLease> {
+        let now = now_ms();
+        self.exclusive.read().get(capability).filter(|lease| lease.expires_at_ms > now).cloned()
+    }
+
+    /// CPLs currently reading a shared capability
+    pub fn readers(&self, capability: &str) -> Vec<String> {
+        let now = now_ms();
+        let mut readers: Vec<String> = self.shared.read()
+            .get(capability)
+            .map(|readers| readers.values().filter(|lease| lease.expires_at_ms > now).map(|lease| lease.cpl_id.clone()).collect())
+            .unwrap_or_default();
+        readers.sort();
+        readers
+    }
+
+    /// Live leases, dropping expired ones
+    pub fn leases(&self) -> Vec<ResourceLease> {
+        let now = now_ms();
+        let mut exclusive = self.exclusive.write();
+        exclusive.retain(|_, lease| lease.expires_at_ms > now);
+        let mut leases: Vec<ResourceLease> = exclusive.values().cloned().collect();
+        drop(exclusive);
+
+        let mut shared = self.shared.write();
+        for readers in shared.values_mut() {
+            readers.retain(|_, lease| lease.expires_at_ms > now);
+            leases.extend(readers.values().cloned());
+        }
+        shared.retain(|_, readers| !readers.is_empty());
+        leases.sort_by(|a, b| a.capability.cmp(&b.capability).then_with(|| a.cpl_id.cmp(&b.cpl_id)));
+        leases
+    }
+
+    /// Recent conflicts, oldest first
+    pub fn conflicts(&self) -> Vec<ResourceConflict> {
+        self.conflicts.read().iter().cloned().collect()
+    }
+
+    fn surface_conflict(&self, holder: &ResourceLease, requester: &ResourceLease, resolution: ConflictResolution) {
+        let conflict = ResourceConflict {
+            capability: holder.capability.clone(),
+            holder_cpl: holder.cpl_id.clone(),
+            holder_priority: holder.priority,
+            requester_cpl: requester.cpl_id.clone(),
+            requester_priority: requester.priority,
+            resolution,
+            timestamp_ms: now_ms(),
+        };
+
+        {
+            let cpls = self.cpls.read();
+            for cpl_id in [&holder.cpl_id, &requester.cpl_id] {
+                if let Some(events) = cpls.get(cpl_id) {
+                    let _ = events.send(CPLEvent::ResourceConflict { conflict: conflict.clone() });
+                }
+            }
+        }
+
+        let mut conflicts = self.conflicts.write();
+        conflicts.push_back(conflict);
+        while conflicts.len() > MAX_CONFLICT_HISTORY {
+            conflicts.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(capability: &str, cpl_id: &str, priority: f64) -> LeaseRequest {
+        LeaseRequest {
+            capability: capability.to_string(),
+            cpl_id: cpl_id.to_string(),
+            priority,
+            duration_ms: None,
+            preemptible: true,
+        }
+    }
+
+    fn arbiter(min_hold_ms: u64) -> ResourceArbiter {
+        let arbiter = ResourceArbiter::new(ArbitrationConfig { min_hold_ms, ..ArbitrationConfig::default() });
+        arbiter.register_capability("camera", CapabilityAccess::Shared);
+        arbiter
+    }
+
+    #[test]
+    fn test_sensors_are_shared_and_actuators_exclusive() {
+        let arbiter = arbiter(0);
+        assert!(matches!(arbiter.acquire(request("camera", "a", 0.5)).unwrap(), LeaseOutcome::Granted { .. }));
+        assert!(matches!(arbiter.acquire(request("camera", "b", 0.1)).unwrap(), LeaseOutcome::Granted { .. }));
+        assert_eq!(arbiter.readers("camera"), vec!["a", "b"]);
+
+        // Unregistered capabilities default to exclusive
+        let arm = arbiter.acquire(request("arm", "a", 0.5)).unwrap();
+        assert_eq!(arm.lease().unwrap().access, CapabilityAccess::Exclusive);
+        assert!(matches!(arbiter.acquire(request("arm", "b", 0.55)).unwrap(), LeaseOutcome::Denied { .. }));
+        assert_eq!(arbiter.holder("arm").unwrap().cpl_id, "a");
+        assert_eq!(arbiter.conflicts().len(), 1);
+
+        assert!(arbiter.release(&arm.lease().unwrap().lease_id));
+        assert!(matches!(arbiter.acquire(request("arm", "b", 0.2)).unwrap(), LeaseOutcome::Granted { .. }));
+        assert_eq!(arbiter.release_all("b"), 2);
+        assert_eq!(arbiter.readers("camera"), vec!["a"]);
+    }
+
+    #[test]
+    fn test_preemption_rules_and_conflict_events() {
+        let arbiter = arbiter(0);
+        let (a_events, mut a_rx) = broadcast::channel(8);
+        let (b_events, mut b_rx) = broadcast::channel(8);
+        arbiter.attach_cpl("a", a_events);
+        arbiter.attach_cpl("b", b_events);
+
+        let held = arbiter.acquire(LeaseRequest { preemptible: false, ..request("wheels", "a", 0.2) }).unwrap();
+        assert!(matches!(arbiter.acquire(request("wheels", "b", 0.9)).unwrap(), LeaseOutcome::Denied { .. }));
+        arbiter.release(&held.lease().unwrap().lease_id);
+
+        let held = arbiter.acquire(request("wheels", "a", 0.2)).unwrap();
+        match arbiter.acquire(request("wheels", "b", 0.9)).unwrap() {
+            LeaseOutcome::Preempted { lease, preempted } => {
+                assert_eq!(lease.cpl_id, "b");
+                assert_eq!(preempted.lease_id, held.lease().unwrap().lease_id);
+            }
+            other => panic!("expected preemption, got {:?}", other),
+        }
+        assert!(arbiter.renew(&held.lease().unwrap().lease_id, None).is_err());
+
+        // Both CPLs saw the denial and then the preemption
+        for rx in [&mut a_rx, &mut b_rx] {
+            let resolutions: Vec<ConflictResolution> = std::iter::from_fn(|| rx.try_recv().ok())
+                .filter_map(|event| match event {
+                    CPLEvent::ResourceConflict { conflict } => Some(conflict.resolution),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(resolutions, vec![ConflictResolution::Denied, ConflictResolution::Preempted]);
+        }
+    }
+
+    #[test]
+    fn test_minimum_hold_and_margin_prevent_flapping() {
+        let arbiter = arbiter(60_000);
+        arbiter.acquire(request("gripper", "a", 0.2)).unwrap();
+        assert!(matches!(arbiter.acquire(request("gripper", "b", 1.0)).unwrap(), LeaseOutcome::Denied { .. }));
+
+        let arbiter = self::arbiter(0);
+        arbiter.acquire(request("gripper", "a", 0.5)).unwrap();
+        assert!(matches!(arbiter.acquire(request("gripper", "b", 0.55)).unwrap(), LeaseOutcome::Denied { .. }));
+        assert!(matches!(arbiter.acquire(request("gripper", "b", 0.65)).unwrap(), LeaseOutcome::Preempted { .. }));
+    }
+}
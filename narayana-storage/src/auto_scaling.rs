@@ -38,6 +38,26 @@ impl DatabaseManagerTrait for SimpleDatabaseManager {
         self.databases.read().clone()
     }
 }
+impl DatabaseManagerTrait for crate::database_manager::DatabaseManager {
+    fn create_database(&self, name: &str) -> Result<String> {
+        crate::database_manager::DatabaseManager::create_database(self, name.to_string())?;
+        Ok(name.to_string())
+    }
+
+    fn delete_database(&self, name: &str) -> Result<()> {
+        let id = self.get_database_by_name(name)
+            .ok_or_else(|| Error::Storage(format!("Database '{}' not found", name)))?;
+        self.drop_database(id)
+    }
+
+    fn list_databases(&self) -> Vec<String> {
+        crate::database_manager::DatabaseManager::list_databases(self)
+            .into_iter()
+            .map(|db| db.name)
+            .collect()
+    }
+}
+
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
@@ -102,6 +122,7 @@ pub struct AutoScalingManager {
     load_balancer: Arc<LoadBalancer>,
     stats: Arc<RwLock<AutoScalingStats>>,
     predictive_engine: Option<Arc<PredictiveScalingEngine>>,
+    shard_scaler: Option<Arc<ShardScaler>>,
 }
 
 /// Spawn event
@@ -192,9 +213,16 @@ impl AutoScalingManager {
                 load_balanced_queries: 0,
             })),
             predictive_engine: Some(predictive_engine),
+            shard_scaler: None,
         }
     }
 
+    /// Also split, merge and add readers to shards on each check
+    pub fn with_shard_scaler(mut self, shard_scaler: Arc<ShardScaler>) -> Self {
+        self.shard_scaler = Some(shard_scaler);
+        self
+    }
+
     /// Start monitoring and auto-scaling
     pub async fn start(&self) {
         let metrics = self.metrics.clone();
@@ -205,12 +233,17 @@ impl AutoScalingManager {
         let load_balancer = self.load_balancer.clone();
         let check_interval = self.check_interval;
         let predictive_engine = self.predictive_engine.clone();
+        let shard_scaler = self.shard_scaler.clone();
 
         tokio::spawn(async move {
             let mut interval_timer = interval(check_interval);
             loop {
                 interval_timer.tick().await;
 
+                if let Some(ref shard_scaler) = shard_scaler {
+                    shard_scaler.run_once().await;
+                }
+
                 // Use predictive scaling if available
                 if let Some(ref predictive) = predictive_engine {
                     // Record metrics for prediction - collect entries to avoid holding iter across await
@@ -463,6 +496,11 @@ impl AutoScalingManager {
     pub fn load_balancer(&self) -> Arc<LoadBalancer> {
         self.load_balancer.clone()
     }
+
+    /// Get shard scaler
+    pub fn shard_scaler(&self) -> Option<Arc<ShardScaler>> {
+        self.shard_scaler.clone()
+    }
 }
 
 impl LoadBalancer {
@@ -561,3 +599,477 @@ impl LoadBalancer {
 use uuid;
 use crate::predictive_scaling::*;
 
+// ============================================
+// Shard scaling - splits, merges and readers agreed through consensus
+// ============================================
+
+use crate::consensus::{MetadataCommand, RaftConsensus, ShardPlacement};
+use std::collections::VecDeque;
+
+/// Load of one shard, reported by the node serving it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShardLoad {
+    pub shard_id: u64,
+    pub size_bytes: u64,
+    pub row_count: u64,
+    pub reads_per_second: f64,
+    pub writes_per_second: f64,
+    pub last_updated: u64,
+}
+
+/// When shards are split, merged or given readers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShardScalingThresholds {
+    /// A shard reaching any of these is split in two
+    pub split_size_bytes: u64,
+    pub split_row_count: u64,
+    pub split_writes_per_second: f64,
+    /// Adjacent shards both below these are merged
+    pub merge_size_bytes: u64,
+    pub merge_ops_per_second: f64,
+    /// Reads per second one serving node (primary or reader) should take
+    pub reads_per_node: f64,
+    pub max_readers: usize,
+    /// Time a shard is left alone after it changed
+    pub cooldown: Duration,
+    /// Events kept
+    pub max_events: usize,
+}
+
+impl Default for ShardScalingThresholds {
+    fn default() -> Self {
+        Self {
+            split_size_bytes: 1024 * 1024 * 1024, // 1GB
+            split_row_count: 50_000_000,
+            split_writes_per_second: 5_000.0,
+            merge_size_bytes: 64 * 1024 * 1024, // 64MB
+            merge_ops_per_second: 50.0,
+            reads_per_node: 2_000.0,
+            max_readers: 4,
+            cooldown: Duration::from_secs(300),
+            max_events: 500,
+        }
+    }
+}
+
+/// A change to the shard map
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ShardScalingAction {
+    Split { shard_id: u64 },
+    Merge { left_id: u64, right_id: u64 },
+    AddReader { shard_id: u64 },
+    RetireReader { shard_id: u64, node_id: String },
+}
+
+/// An executed (or failed) shard scaling action
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShardScalingEvent {
+    pub timestamp: u64,
+    pub action: ShardScalingAction,
+    pub reason: String,
+    /// Shards the action created
+    pub new_shards: Vec<u64>,
+    /// Reader spawned or retired
+    pub reader: Option<String>,
+    /// Raft log index of the committed change
+    pub log_index: Option<u64>,
+    pub duration_ms: u64,
+    pub error: Option<String>,
+}
+
+/// Moves shard data and runs reader replicas; the shard map itself only
+/// changes through consensus once these succeed
+#[async_trait::async_trait]
+pub trait ShardProvisioner: Send + Sync {
+    /// Fill the storage of `targets` from `sources` before the map switches to them
+    async fn prepare_shards(&self, sources: &[ShardPlacement], targets: &[ShardPlacement]) -> Result<()>;
+    /// Drop the storage of shards that left the map
+    async fn release_shards(&self, shard_ids: &[u64]) -> Result<()>;
+    /// Start a read-only replica of a shard, returning its node id
+    async fn spawn_reader(&self, shard: &ShardPlacement) -> Result<String>;
+    async fn retire_reader(&self, shard: &ShardPlacement, node_id: &str) -> Result<()>;
+}
+
+/// Keeps each shard and each reader in a database of its own
+pub struct DatabaseShardProvisioner {
+    database_manager: Arc<dyn DatabaseManagerTrait>,
+}
+
+impl DatabaseShardProvisioner {
+    pub fn new(database_manager: Arc<dyn DatabaseManagerTrait>) -> Self {
+        Self { database_manager }
+    }
+
+    /// Database holding a shard's rows
+    pub fn shard_database(shard_id: u64) -> String {
+        format!("shard-{}", shard_id)
+    }
+}
+
+#[async_trait::async_trait]
+impl ShardProvisioner for DatabaseShardProvisioner {
+    async fn prepare_shards(&self, _sources: &[ShardPlacement], targets: &[ShardPlacement]) -> Result<()> {
+        let existing = self.database_manager.list_databases();
+        for target in targets {
+            let name = Self::shard_database(target.shard_id);
+            if !existing.contains(&name) {
+                self.database_manager.create_database(&name)?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn release_shards(&self, shard_ids: &[u64]) -> Result<()> {
+        let existing = self.database_manager.list_databases();
+        for shard_id in shard_ids {
+            let name = Self::shard_database(*shard_id);
+            if existing.contains(&name) {
+                self.database_manager.delete_database(&name)?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn spawn_reader(&self, shard: &ShardPlacement) -> Result<String> {
+        let suffix = uuid::Uuid::new_v4().simple().to_string();
+        let name = format!("{}-reader-{}", Self::shard_database(shard.shard_id), &suffix[..8]);
+        self.database_manager.create_database(&name)
+    }
+
+    async fn retire_reader(&self, _shard: &ShardPlacement, node_id: &str) -> Result<()> {
+        self.database_manager.delete_database(node_id)
+    }
+}
+
+/// Splits hot shards, merges cold neighbours and sizes reader replicas.
+/// Only the Raft leader acts; every change is a committed metadata command.
+pub struct ShardScaler {
+    consensus: Arc<RaftConsensus>,
+    provisioner: Arc<dyn ShardProvisioner>,
+    thresholds: ShardScalingThresholds,
+    loads: DashMap<u64, ShardLoad>,
+    last_change: DashMap<u64, Instant>,
+    events: RwLock<VecDeque<ShardScalingEvent>>,
+    /// One action at a time, so shard ids and ranges are computed on a settled map
+    acting: tokio::sync::Mutex<()>,
+}
+
+impl ShardScaler {
+    pub fn new(
+        consensus: Arc<RaftConsensus>,
+        provisioner: Arc<dyn ShardProvisioner>,
+        thresholds: ShardScalingThresholds,
+    ) -> Self {
+        Self {
+            consensus,
+            provisioner,
+            thresholds,
+            loads: DashMap::new(),
+            last_change: DashMap::new(),
+            events: RwLock::new(VecDeque::new()),
+            acting: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    pub fn thresholds(&self) -> &ShardScalingThresholds {
+        &self.thresholds
+    }
+
+    pub fn consensus(&self) -> &Arc<RaftConsensus> {
+        &self.consensus
+    }
+
+    pub fn update_load(&self, load: ShardLoad) {
+        self.loads.insert(load.shard_id, load);
+    }
+
+    pub fn loads(&self) -> Vec<ShardLoad> {
+        let mut loads: Vec<_> = self.loads.iter().map(|entry| entry.value().clone()).collect();
+        loads.sort_by_key(|load| load.shard_id);
+        loads
+    }
+
+    /// The committed shard map, by table then key range
+    pub fn shards(&self) -> Vec<ShardPlacement> {
+        let mut shards: Vec<_> = self.consensus.metadata().shards.into_values().collect();
+        shards.sort_by_key(|shard| (shard.table_id, shard.key_start));
+        shards
+    }
+
+    /// Most recent events first
+    pub fn events(&self, limit: usize) -> Vec<ShardScalingEvent> {
+        self.events.read().iter().rev().take(limit).cloned().collect()
+    }
+
+    /// Give a table its first shard (owning every key) unless it has one
+    pub async fn bootstrap_table(&self, table_id: u64) -> Result<Option<u64>> {
+        let _acting = self.acting.lock().await;
+        let metadata = self.consensus.metadata();
+        if metadata.shards.values().any(|shard| shard.table_id == table_id) {
+            return Ok(None);
+        }
+        let shard_id = Self::next_shard_id(&metadata.shards);
+        let placement = ShardPlacement::new(shard_id, table_id, self.consensus.node_id().to_string());
+        self.provisioner.prepare_shards(&[], std::slice::from_ref(&placement)).await?;
+        self.commit(MetadataCommand::AssignShard(placement), shard_id).await?;
+        Ok(Some(shard_id))
+    }
+
+    /// Actions the current loads call for, with the reason for each
+    pub fn plan(&self) -> Vec<(ShardScalingAction, String)> {
+        let thresholds = &self.thresholds;
+        let shards = self.shards();
+        let mut actions = Vec::new();
+        let mut claimed = std::collections::HashSet::new();
+
+        for shard in &shards {
+            let Some(load) = self.loads.get(&shard.shard_id).map(|l| l.clone()) else { continue };
+            if self.cooling_down(shard.shard_id) {
+                continue;
+            }
+            let split_reason = if load.size_bytes >= thresholds.split_size_bytes {
+                Some(format!("size {} bytes >= {}", load.size_bytes, thresholds.split_size_bytes))
+            } else if load.row_count >= thresholds.split_row_count {
+                Some(format!("{} rows >= {}", load.row_count, thresholds.split_row_count))
+            } else if load.writes_per_second >= thresholds.split_writes_per_second {
+                Some(format!("{:.0} writes/s >= {:.0}", load.writes_per_second, thresholds.split_writes_per_second))
+            } else {
+                None
+            };
+            if let Some(reason) = split_reason {
+                if shard.split_point().is_some() {
+                    claimed.insert(shard.shard_id);
+                    actions.push((ShardScalingAction::Split { shard_id: shard.shard_id }, reason));
+                    continue;
+                }
+            }
+
+            let serving = 1 + shard.readers.len();
+            let per_node = load.reads_per_second / serving as f64;
+            if per_node > thresholds.reads_per_node && shard.readers.len() < thresholds.max_readers {
+                claimed.insert(shard.shard_id);
+                actions.push((
+                    ShardScalingAction::AddReader { shard_id: shard.shard_id },
+                    format!("{:.0} reads/s per node > {:.0}", per_node, thresholds.reads_per_node),
+                ));
+            } else if let Some(reader) = shard.readers.last() {
+                // Retire only when one node fewer would still be comfortably under the target
+                let per_node_after = load.reads_per_second / (serving - 1) as f64;
+                if per_node_after < thresholds.reads_per_node / 2.0 {
+                    claimed.insert(shard.shard_id);
+                    actions.push((
+                        ShardScalingAction::RetireReader { shard_id: shard.shard_id, node_id: reader.clone() },
+                        format!("{:.0} reads/s per node without it", per_node_after),
+                    ));
+                }
+            }
+        }
+
+        // Shards are sorted by table and range, so neighbours are consecutive
+        for pair in shards.windows(2) {
+            let (left, right) = (&pair[0], &pair[1]);
+            if left.table_id != right.table_id
+                || left.key_end.checked_add(1) != Some(right.key_start)
+                || claimed.contains(&left.shard_id)
+                || claimed.contains(&right.shard_id)
+                || self.cooling_down(left.shard_id)
+                || self.cooling_down(right.shard_id)
+            {
+                continue;
+            }
+            let loads = (self.loads.get(&left.shard_id).map(|l| l.clone()), self.loads.get(&right.shard_id).map(|l| l.clone()));
+            let (Some(left_load), Some(right_load)) = loads else {
+                continue;
+            };
+            let cold = |load: &ShardLoad| {
+                load.size_bytes < thresholds.merge_size_bytes
+                    && load.reads_per_second + load.writes_per_second < thresholds.merge_ops_per_second
+            };
+            if cold(&left_load) && cold(&right_load) {
+                claimed.insert(left.shard_id);
+                claimed.insert(right.shard_id);
+                actions.push((
+                    ShardScalingAction::Merge { left_id: left.shard_id, right_id: right.shard_id },
+                    format!("{} and {} bytes, both below {}", left_load.size_bytes, right_load.size_bytes, thresholds.merge_size_bytes),
+                ));
+            }
+        }
+        actions
+    }
+
+    /// Plan and execute on the leader; followers do nothing
+    pub async fn run_once(&self) -> Vec<ShardScalingEvent> {
+        if !self.consensus.is_leader() {
+            return Vec::new();
+        }
+        let mut events = Vec::new();
+        for (action, reason) in self.plan() {
+            match self.execute(action, &reason).await {
+                Ok(event) => events.push(event),
+                Err(e) => warn!("Shard scaling action failed: {}", e),
+            }
+        }
+        events
+    }
+
+    /// Carry out an action; the outcome is recorded either way
+    pub async fn execute(&self, action: ShardScalingAction, reason: &str) -> Result<ShardScalingEvent> {
+        let _acting = self.acting.lock().await;
+        let start = Instant::now();
+        let result = if self.consensus.is_leader() {
+            self.apply(&action).await
+        } else {
+            Err(Error::Storage(format!(
+                "Node {} is not the leader (leader: {:?})",
+                self.consensus.node_id(),
+                self.consensus.leader_id()
+            )))
+        };
+        let mut event = ShardScalingEvent {
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+            action: action.clone(),
+            reason: reason.to_string(),
+            new_shards: Vec::new(),
+            reader: None,
+            log_index: None,
+            duration_ms: start.elapsed().as_millis() as u64,
+            error: None,
+        };
+        match &result {
+            Ok((new_shards, reader, log_index)) => {
+                event.new_shards = new_shards.clone();
+                event.reader = reader.clone();
+                event.log_index = Some(*log_index);
+                info!("Shard scaling {:?} done ({})", action, reason);
+            }
+            Err(e) => event.error = Some(e.to_string()),
+        }
+        {
+            let mut events = self.events.write();
+            events.push_back(event.clone());
+            while events.len() > self.thresholds.max_events {
+                events.pop_front();
+            }
+        }
+        result.map(|_| event)
+    }
+
+    /// Returns the created shards, the reader involved and the log index
+    async fn apply(&self, action: &ShardScalingAction) -> Result<(Vec<u64>, Option<String>, u64)> {
+        let metadata = self.consensus.metadata();
+        let shard = |id: &u64| metadata.shards.get(id).cloned()
+            .ok_or_else(|| Error::Storage(format!("Shard {} not found", id)));
+        match action {
+            ShardScalingAction::Split { shard_id } => {
+                let source = shard(shard_id)?;
+                let mid = source.split_point()
+                    .ok_or_else(|| Error::Storage(format!("Shard {} owns a single key", shard_id)))?;
+                let next = Self::next_shard_id(&metadata.shards);
+                let left = ShardPlacement { shard_id: next, key_end: mid, readers: Vec::new(), ..source.clone() };
+                let right = ShardPlacement { shard_id: next + 1, key_start: mid + 1, readers: Vec::new(), ..source.clone() };
+                self.provisioner.prepare_shards(std::slice::from_ref(&source), &[left.clone(), right.clone()]).await?;
+                let command = MetadataCommand::SplitShard { shard_id: *shard_id, left, right };
+                let index = match self.commit(command, next).await {
+                    Ok(index) => index,
+                    Err(e) => {
+                        let _ = self.provisioner.release_shards(&[next, next + 1]).await;
+                        return Err(e);
+                    }
+                };
+                self.retire_old(&[source]).await;
+                self.last_change.insert(next + 1, Instant::now());
+                Ok((vec![next, next + 1], None, index))
+            }
+            ShardScalingAction::Merge { left_id, right_id } => {
+                let (left, right) = (shard(left_id)?, shard(right_id)?);
+                if left.table_id != right.table_id || left.key_end.checked_add(1) != Some(right.key_start) {
+                    return Err(Error::Storage(format!("Shards {} and {} are not adjacent", left_id, right_id)));
+                }
+                let next = Self::next_shard_id(&metadata.shards);
+                let merged = ShardPlacement { shard_id: next, key_end: right.key_end, readers: Vec::new(), ..left.clone() };
+                self.provisioner.prepare_shards(&[left.clone(), right.clone()], std::slice::from_ref(&merged)).await?;
+                let command = MetadataCommand::MergeShards { left_id: *left_id, right_id: *right_id, merged };
+                let index = match self.commit(command, next).await {
+                    Ok(index) => index,
+                    Err(e) => {
+                        let _ = self.provisioner.release_shards(&[next]).await;
+                        return Err(e);
+                    }
+                };
+                self.retire_old(&[left, right]).await;
+                Ok((vec![next], None, index))
+            }
+            ShardScalingAction::AddReader { shard_id } => {
+                let target = shard(shard_id)?;
+                if target.readers.len() >= self.thresholds.max_readers {
+                    return Err(Error::Storage(format!(
+                        "Shard {} already has {} readers", shard_id, target.readers.len()
+                    )));
+                }
+                let node_id = self.provisioner.spawn_reader(&target).await?;
+                let command = MetadataCommand::AddReader { shard_id: *shard_id, node_id: node_id.clone() };
+                match self.commit(command, *shard_id).await {
+                    Ok(index) => Ok((Vec::new(), Some(node_id), index)),
+                    Err(e) => {
+                        let _ = self.provisioner.retire_reader(&target, &node_id).await;
+                        Err(e)
+                    }
+                }
+            }
+            ShardScalingAction::RetireReader { shard_id, node_id } => {
+                let target = shard(shard_id)?;
+                if !target.readers.contains(node_id) {
+                    return Err(Error::Storage(format!("{} is not a reader of shard {}", node_id, shard_id)));
+                }
+                // Stop routing reads to it before it goes away
+                let command = MetadataCommand::RemoveReader { shard_id: *shard_id, node_id: node_id.clone() };
+                let index = self.commit(command, *shard_id).await?;
+                self.provisioner.retire_reader(&target, node_id).await?;
+                Ok((Vec::new(), Some(node_id.clone()), index))
+            }
+        }
+    }
+
+    /// Propose a change and wait for it to commit; `shard_id` starts its cooldown
+    async fn commit(&self, command: MetadataCommand, shard_id: u64) -> Result<u64> {
+        let index = self.consensus.propose(command).await?;
+        if self.consensus.status().commit_index < index {
+            // Followers learn the commit on the next heartbeat; a leader without
+            // a quorum never gets here
+            self.consensus.replicate().await?;
+        }
+        if self.consensus.status().commit_index < index {
+            return Err(Error::Storage(format!("Shard map change at log index {} did not reach a quorum", index)));
+        }
+        self.last_change.insert(shard_id, Instant::now());
+        Ok(index)
+    }
+
+    /// Drop the storage and readers of shards replaced in the map
+    async fn retire_old(&self, shards: &[ShardPlacement]) {
+        for shard in shards {
+            for reader in &shard.readers {
+                if let Err(e) = self.provisioner.retire_reader(shard, reader).await {
+                    warn!("Failed to retire reader {} of shard {}: {}", reader, shard.shard_id, e);
+                }
+            }
+            self.loads.remove(&shard.shard_id);
+            self.last_change.remove(&shard.shard_id);
+        }
+        let ids: Vec<u64> = shards.iter().map(|shard| shard.shard_id).collect();
+        if let Err(e) = self.provisioner.release_shards(&ids).await {
+            warn!("Failed to release storage of shards {:?}: {}", ids, e);
+        }
+    }
+
+    fn cooling_down(&self, shard_id: u64) -> bool {
+        self.last_change.get(&shard_id)
+            .map(|at| at.elapsed() < self.thresholds.cooldown)
+            .unwrap_or(false)
+    }
+
+    fn next_shard_id(shards: &HashMap<u64, ShardPlacement>) -> u64 {
+        shards.keys().max().map(|id| id + 1).unwrap_or(1)
+    }
+}
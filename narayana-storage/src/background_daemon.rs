@@ -13,6 +13,14 @@ use tokio::sync::broadcast;
 use std::collections::HashMap;
 use tracing::{debug, info, warn};
 
+/// What one consolidation pass did
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConsolidationReport {
+    pub memories_consolidated: usize,
+    pub patterns_detected: usize,
+    pub associations_formed: usize,
+}
+
 /// Background Daemon - Unconscious cognitive processes
 pub struct BackgroundDaemon {
     brain: Arc<CognitiveBrain>,
@@ -102,8 +110,26 @@ impl BackgroundDaemon {
         Ok(())
     }
     
+    /// Run consolidation, pattern detection and association formation now,
+    /// regardless of their intervals (used by the consolidation job)
+    pub async fn consolidate_now(&self) -> Result<ConsolidationReport> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let report = ConsolidationReport {
+            memories_consolidated: self.consolidate_memories().await?,
+            patterns_detected: self.detect_patterns().await?,
+            associations_formed: self.form_associations().await?,
+        };
+        *self.last_memory_consolidation.write() = now;
+        *self.last_pattern_detection.write() = now;
+        *self.last_association_formation.write() = now;
+        Ok(report)
+    }
+    
     /// Consolidate memories (update strength, apply forgetting curves)
-    async fn consolidate_memories(&self) -> Result<()> {
+    async fn consolidate_memories(&self) -> Result<usize> {
         let memories = self.brain.memories.read();
         let now = SystemTime::now()
             .duration_since(UNIX_EPOCH)
@@ -113,7 +139,7 @@ impl BackgroundDaemon {
         // Edge case: Handle clock going backwards
         if now == 0 {
             warn!("System time is 0, skipping consolidation");
-            return Ok(());
+            return Ok(0);
         }
         
         let mut consolidated_count = 0;
@@ -169,11 +195,11 @@ impl BackgroundDaemon {
             });
         }
         
-        Ok(())
+        Ok(consolidated_count)
     }
     
     /// Detect patterns from experiences
-    async fn detect_patterns(&self) -> Result<()> {
+    async fn detect_patterns(&self) -> Result<usize> {
         // Use existing pattern detection from brain
         match self.brain.detect_patterns_from_experiences() {
             Ok(pattern_ids) => {
@@ -183,7 +209,7 @@ impl BackgroundDaemon {
                         process_type: "pattern_detection".to_string(),
                     });
                 }
-                Ok(())
+                Ok(pattern_ids.len())
             }
             Err(e) => {
                 warn!("Pattern detection failed: {}", e);
@@ -193,7 +219,7 @@ impl BackgroundDaemon {
     }
     
     /// Form associations between related memories/thoughts
-    async fn form_associations(&self) -> Result<()> {
+    async fn form_associations(&self) -> Result<usize> {
         let memories = self.brain.memories.read();
         let experiences = self.brain.experiences.read();
         
@@ -249,7 +275,7 @@ impl BackgroundDaemon {
             });
         }
         
-        Ok(())
+        Ok(associations_formed)
     }
     
     /// Compute similarity between two memories
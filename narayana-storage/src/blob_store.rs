@@ -0,0 +1,722 @@
+// Blob store for binary artifacts
+// Audio clips, video frames, avatar assets and model files, stored by content
+// (SHA-256) and split into fixed-size chunks so identical chunks are kept once.
+// Each chunk file starts with a one-byte header recording its compression and
+// whether it is encrypted, so settings can change without rewriting old blobs.
+
+use crate::compression::{create_compressor, create_decompressor};
+use crate::encryption::{EncryptionAlgorithm, EncryptionConfig, EncryptionKey, EncryptionScope, OnTheFlyEncryptor};
+use narayana_core::config::BlobStoreConfig;
+use narayana_core::{types::CompressionType, Error, Result};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+/// Prefix of blob references stored in table columns
+pub const BLOB_REF_PREFIX: &str = "blob:";
+
+const ENCRYPTION_SCOPE: &str = "blobs";
+const ENCRYPTED_FLAG: u8 = 0x80;
+
+/// Reference to a blob from a String (or Binary) column: `blob:<sha256>`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct BlobRef {
+    id: String,
+}
+
+impl BlobRef {
+    pub fn new(id: &str) -> Result<Self> {
+        if !is_valid_id(id) {
+            return Err(Error::Storage(format!("Invalid blob id: {}", id)));
+        }
+        Ok(Self { id: id.to_string() })
+    }
+
+    /// Parse a column value; None unless it is a well-formed reference
+    pub fn parse(value: &str) -> Option<Self> {
+        value.strip_prefix(BLOB_REF_PREFIX).and_then(|id| Self::new(id).ok())
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl fmt::Display for BlobRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", BLOB_REF_PREFIX, self.id)
+    }
+}
+
+impl TryFrom<String> for BlobRef {
+    type Error = String;
+
+    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+        Self::parse(&value).ok_or_else(|| format!("Invalid blob reference: {}", value))
+    }
+}
+
+impl From<BlobRef> for String {
+    fn from(blob: BlobRef) -> Self {
+        blob.to_string()
+    }
+}
+
+/// One chunk of a blob
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChunkRef {
+    /// SHA-256 of the plain chunk bytes
+    pub hash: String,
+    pub size: usize,
+}
+
+/// Blob manifest
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlobInfo {
+    /// SHA-256 of the whole content
+    pub id: String,
+    pub size: u64,
+    pub content_type: Option<String>,
+    pub created_at: u64,
+    /// Size of every chunk but the last
+    pub chunk_size: usize,
+    pub chunks: Vec<ChunkRef>,
+}
+
+impl BlobInfo {
+    pub fn reference(&self) -> BlobRef {
+        BlobRef { id: self.id.clone() }
+    }
+}
+
+/// Content-addressed, chunked blob storage under one directory
+pub struct BlobStore {
+    root: PathBuf,
+    chunk_size: usize,
+    max_blob_size: u64,
+    compression: CompressionType,
+    encryptor: Option<OnTheFlyEncryptor>,
+    blobs: RwLock<HashMap<String, BlobInfo>>,
+    /// Chunk hash -> number of manifests and in-progress uploads using it.
+    /// Chunk files are written and removed under this lock.
+    chunk_refs: RwLock<HashMap<String, usize>>,
+}
+
+impl BlobStore {
+    /// Open (or create) a store at `root`, loading manifests and removing
+    /// chunks left behind by interrupted uploads
+    pub fn open(root: impl Into<PathBuf>, config: &BlobStoreConfig) -> Result<Self> {
+        let root = root.into();
+        for dir in [root.join("chunks"), root.join("manifests")] {
+            std::fs::create_dir_all(&dir)
+                .map_err(|e| Error::Storage(format!("Failed to create blob directory {:?}: {}", dir, e)))?;
+        }
+        let encryptor = if config.encrypt {
+            let key_path = config.key_file.as_ref().map(PathBuf::from).unwrap_or_else(|| root.join("blob.key"));
+            let encryptor = OnTheFlyEncryptor::new();
+            encryptor.add_key(EncryptionKey {
+                key: load_or_create_key(&key_path)?,
+                algorithm: EncryptionAlgorithm::Aes256Gcm,
+                key_id: ENCRYPTION_SCOPE.to_string(),
+                created_at: now_secs(),
+                rotated_at: None,
+            });
+            encryptor.configure(
+                ENCRYPTION_SCOPE.to_string(),
+                EncryptionConfig::new(EncryptionScope::Database, EncryptionAlgorithm::Aes256Gcm, ENCRYPTION_SCOPE.to_string()),
+            );
+            Some(encryptor)
+        } else {
+            None
+        };
+
+        let store = Self {
+            root,
+            chunk_size: config.chunk_size.max(1),
+            max_blob_size: config.max_blob_size,
+            compression: parse_compression(&config.compression)?,
+            encryptor,
+            blobs: RwLock::new(HashMap::new()),
+            chunk_refs: RwLock::new(HashMap::new()),
+        };
+        store.load()?;
+        Ok(store)
+    }
+
+    pub fn max_blob_size(&self) -> u64 {
+        self.max_blob_size
+    }
+
+    /// Store `data` in one call
+    pub fn put(&self, data: &[u8], content_type: Option<String>) -> Result<(BlobInfo, bool)> {
+        let mut writer = self.writer();
+        writer.write(data)?;
+        writer.finish(content_type)
+    }
+
+    /// Start a streaming upload
+    pub fn writer(&self) -> BlobWriter<'_> {
+        BlobWriter {
+            store: self,
+            hasher: Sha256::new(),
+            buffer: Vec::new(),
+            chunks: Vec::new(),
+            size: 0,
+            finished: false,
+        }
+    }
+
+    pub fn info(&self, id: &str) -> Option<BlobInfo> {
+        self.blobs.read().get(id).cloned()
+    }
+
+    /// All blobs, newest first
+    pub fn list(&self) -> Vec<BlobInfo> {
+        let mut blobs: Vec<BlobInfo> = self.blobs.read().values().cloned().collect();
+        blobs.sort_by(|a, b| b.created_at.cmp(&a.created_at).then_with(|| a.id.cmp(&b.id)));
+        blobs
+    }
+
+    pub fn read(&self, id: &str) -> Result<Vec<u8>> {
+        let size = self.require(id)?.size;
+        self.read_range(id, 0, size)
+    }
+
+    /// Read `len` bytes starting at `offset` (clamped to the blob's end)
+    pub fn read_range(&self, id: &str, offset: u64, len: u64) -> Result<Vec<u8>> {
+        let info = self.require(id)?;
+        if offset > info.size {
+            return Err(Error::Storage(format!(
+                "Range start {} is beyond the end of blob {} ({} bytes)",
+                offset, id, info.size
+            )));
+        }
+        let end = offset.saturating_add(len).min(info.size);
+        let mut out = Vec::with_capacity((end - offset) as usize);
+        let mut chunk_start = 0u64;
+        for chunk in &info.chunks {
+            let chunk_end = chunk_start + chunk.size as u64;
+            if chunk_end > offset && chunk_start < end {
+                let data = self.read_chunk(chunk)?;
+                let from = offset.saturating_sub(chunk_start) as usize;
+                let to = (end.min(chunk_end) - chunk_start) as usize;
+                out.extend_from_slice(&data[from..to]);
+            }
+            if chunk_end >= end {
+                break;
+            }
+            chunk_start = chunk_end;
+        }
+        Ok(out)
+    }
+
+    /// Delete a blob; chunks no other blob uses are removed
+    pub fn delete(&self, id: &str) -> Result<()> {
+        let info = self
+            .blobs
+            .write()
+            .remove(id)
+            .ok_or_else(|| Error::Storage(format!("Blob {} not found", id)))?;
+        if let Err(e) = std::fs::remove_file(self.manifest_path(id)) {
+            warn!("Failed to remove manifest of blob {}: {}", id, e);
+        }
+        self.release(info.chunks.iter().map(|c| c.hash.as_str()));
+        info!("Deleted blob {}", id);
+        Ok(())
+    }
+
+    fn require(&self, id: &str) -> Result<BlobInfo> {
+        self.info(id).ok_or_else(|| Error::Storage(format!("Blob {} not found", id)))
+    }
+
+    fn manifest_path(&self, id: &str) -> PathBuf {
+        self.root.join("manifests").join(format!("{}.json", id))
+    }
+
+    fn chunk_path(&self, hash: &str) -> PathBuf {
+        self.root.join("chunks").join(&hash[..2]).join(hash)
+    }
+
+    fn load(&self) -> Result<()> {
+        let dir = self.root.join("manifests");
+        let entries = std::fs::read_dir(&dir)
+            .map_err(|e| Error::Storage(format!("Failed to read {:?}: {}", dir, e)))?;
+        let mut blobs = self.blobs.write();
+        let mut refs = self.chunk_refs.write();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let info = match std::fs::read(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|bytes| serde_json::from_slice::<BlobInfo>(&bytes).map_err(|e| e.to_string()))
+            {
+                Ok(info) if is_valid_id(&info.id) => info,
+                Ok(_) => continue,
+                Err(e) => {
+                    warn!("Skipping unreadable blob manifest {:?}: {}", path, e);
+                    continue;
+                }
+            };
+            for chunk in &info.chunks {
+                *refs.entry(chunk.hash.clone()).or_insert(0) += 1;
+            }
+            blobs.insert(info.id.clone(), info);
+        }
+
+        // Chunks nobody references are leftovers of interrupted uploads
+        let mut orphans = 0;
+        if let Ok(prefixes) = std::fs::read_dir(self.root.join("chunks")) {
+            for prefix in prefixes.flatten() {
+                for chunk in std::fs::read_dir(prefix.path()).into_iter().flatten().flatten() {
+                    let name = chunk.file_name().to_string_lossy().to_string();
+                    if !refs.contains_key(&name) && std::fs::remove_file(chunk.path()).is_ok() {
+                        orphans += 1;
+                    }
+                }
+            }
+        }
+        info!("Loaded {} blobs ({} chunks, {} orphaned chunks removed) from {:?}", blobs.len(), refs.len(), orphans, self.root);
+        Ok(())
+    }
+
+    /// Take a reference on a chunk, writing it if no one has yet
+    fn retain(&self, data: &[u8]) -> Result<ChunkRef> {
+        let hash = hex::encode(Sha256::digest(data));
+        let encoded = self.encode_chunk(data)?;
+        let mut refs = self.chunk_refs.write();
+        let path = self.chunk_path(&hash);
+        if !refs.contains_key(&hash) || !path.exists() {
+            write_atomic(&path, &encoded)?;
+        }
+        *refs.entry(hash.clone()).or_insert(0) += 1;
+        Ok(ChunkRef { hash, size: data.len() })
+    }
+
+    /// Drop references, removing chunk files that are no longer used
+    fn release<'a>(&self, hashes: impl Iterator<Item = &'a str>) {
+        let mut refs = self.chunk_refs.write();
+        for hash in hashes {
+            let Some(count) = refs.get_mut(hash) else { continue };
+            *count -= 1;
+            if *count == 0 {
+                refs.remove(hash);
+                if let Err(e) = std::fs::remove_file(self.chunk_path(hash)) {
+                    warn!("Failed to remove blob chunk {}: {}", hash, e);
+                }
+            }
+        }
+    }
+
+    fn encode_chunk(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let mut header = compression_code(self.compression);
+        let mut payload = create_compressor(self.compression).compress(data)?;
+        if let Some(encryptor) = &self.encryptor {
+            payload = encryptor.encrypt(&payload, ENCRYPTION_SCOPE)?;
+            header |= ENCRYPTED_FLAG;
+        }
+        let mut encoded = Vec::with_capacity(payload.len() + 1);
+        encoded.push(header);
+        encoded.extend_from_slice(&payload);
+        Ok(encoded)
+    }
+
+    fn read_chunk(&self, chunk: &ChunkRef) -> Result<Vec<u8>> {
+        let path = self.chunk_path(&chunk.hash);
+        let encoded = std::fs::read(&path)
+            .map_err(|e| Error::Storage(format!("Failed to read blob chunk {}: {}", chunk.hash, e)))?;
+        let (&header, payload) = encoded
+            .split_first()
+            .ok_or_else(|| Error::Storage(format!("Empty blob chunk {}", chunk.hash)))?;
+        let compressed = if header & ENCRYPTED_FLAG != 0 {
+            let encryptor = self.encryptor.as_ref().ok_or_else(|| {
+                Error::Storage(format!("Blob chunk {} is encrypted but blob encryption is disabled", chunk.hash))
+            })?;
+            encryptor.decrypt(payload, ENCRYPTION_SCOPE)?
+        } else {
+            payload.to_vec()
+        };
+        let compression = compression_from_code(header & !ENCRYPTED_FLAG)
+            .ok_or_else(|| Error::Storage(format!("Blob chunk {} has an unknown header {:#04x}", chunk.hash, header)))?;
+        let data = create_decompressor(compression).decompress(&compressed, chunk.size)?;
+        if hex::encode(Sha256::digest(&data)) != chunk.hash {
+            return Err(Error::Storage(format!("Blob chunk {} failed its integrity check", chunk.hash)));
+        }
+        Ok(data)
+    }
+}
+
+/// Streaming upload; dropping it without `finish` discards what was written
+pub struct BlobWriter<'a> {
+    store: &'a BlobStore,
+    hasher: Sha256,
+    buffer: Vec<u8>,
+    chunks: Vec<ChunkRef>,
+    size: u64,
+    finished: bool,
+}
+
+impl BlobWriter<'_> {
+    pub fn write(&mut self, mut data: &[u8]) -> Result<()> {
+        let size = self.size + data.len() as u64;
+        if size > self.store.max_blob_size {
+            return Err(Error::Storage(format!(
+                "Blob exceeds the maximum size of {} bytes",
+                self.store.max_blob_size
+            )));
+        }
+        self.size = size;
+        self.hasher.update(data);
+        let chunk_size = self.store.chunk_size;
+        while !data.is_empty() {
+            let take = (chunk_size - self.buffer.len()).min(data.len());
+            self.buffer.extend_from_slice(&data[..take]);
+            data = &data[take..];
+            if self.buffer.len() == chunk_size {
+                let chunk = self.store.retain(&self.buffer)?;
+                self.chunks.push(chunk);
+                self.buffer.clear();
+            }
+        }
+        Ok(())
+    }
+
+    pub fn written(&self) -> u64 {
+        self.size
+    }
+
+    /// Complete the upload. Returns the manifest and whether the blob is new
+    /// (false when identical content was already stored).
+    pub fn finish(mut self, content_type: Option<String>) -> Result<(BlobInfo, bool)> {
+        if !self.buffer.is_empty() {
+            let chunk = self.store.retain(&self.buffer)?;
+            self.chunks.push(chunk);
+            self.buffer.clear();
+        }
+        let id = hex::encode(std::mem::take(&mut self.hasher).finalize());
+        let store = self.store;
+
+        let mut blobs = store.blobs.write();
+        if let Some(existing) = blobs.get(&id) {
+            return Ok((existing.clone(), false));
+        }
+        let info = BlobInfo {
+            id: id.clone(),
+            size: self.size,
+            content_type,
+            created_at: now_secs(),
+            chunk_size: store.chunk_size,
+            chunks: std::mem::take(&mut self.chunks),
+        };
+        let manifest = serde_json::to_vec_pretty(&info)
+            .map_err(|e| Error::Serialization(format!("Failed to serialize blob manifest: {}", e)))?;
+        if let Err(e) = write_atomic(&store.manifest_path(&id), &manifest) {
+            self.chunks = info.chunks;
+            return Err(e);
+        }
+        self.finished = true;
+        blobs.insert(id.clone(), info.clone());
+        info!("Stored blob {} ({} bytes, {} chunks)", id, info.size, info.chunks.len());
+        Ok((info, true))
+    }
+}
+
+impl Drop for BlobWriter<'_> {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.store.release(self.chunks.iter().map(|c| c.hash.as_str()));
+        }
+    }
+}
+
+/// What an HTTP `Range` header asks of a blob
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeRequest {
+    /// No header, or a form that is answered with the whole blob (e.g. multiple ranges)
+    Full,
+    /// Inclusive byte range
+    Partial(u64, u64),
+    Unsatisfiable,
+}
+
+/// Interpret a `Range` header for a blob of `size` bytes
+pub fn parse_range_header(header: Option<&str>, size: u64) -> RangeRequest {
+    let Some(spec) = header.and_then(|h| h.trim().strip_prefix("bytes=")) else {
+        return RangeRequest::Full;
+    };
+    let Some((start, end)) = spec.split_once('-').filter(|_| !spec.contains(',')) else {
+        return RangeRequest::Full;
+    };
+    let last = size.saturating_sub(1);
+    let range = match (start.trim(), end.trim()) {
+        ("", suffix) => suffix.parse::<u64>().ok().filter(|&n| n > 0).map(|n| (size.saturating_sub(n), last)),
+        (start, "") => start.parse().ok().map(|start| (start, last)),
+        (start, end) => match (start.parse::<u64>(), end.parse::<u64>()) {
+            (Ok(start), Ok(end)) if start <= end => Some((start, end.min(last))),
+            _ => None,
+        },
+    };
+    match range {
+        Some((start, end)) if start < size => RangeRequest::Partial(start, end),
+        _ => RangeRequest::Unsatisfiable,
+    }
+}
+
+fn is_valid_id(id: &str) -> bool {
+    id.len() == 64 && id.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+fn parse_compression(name: &str) -> Result<CompressionType> {
+    match name.to_lowercase().as_str() {
+        "none" => Ok(CompressionType::None),
+        "lz4" => Ok(CompressionType::LZ4),
+        "zstd" => Ok(CompressionType::Zstd),
+        "snappy" => Ok(CompressionType::Snappy),
+        other => Err(Error::Configuration(format!("Unknown blob compression: {}", other))),
+    }
+}
+
+fn compression_code(compression: CompressionType) -> u8 {
+    match compression {
+        CompressionType::None => 0,
+        CompressionType::LZ4 => 1,
+        CompressionType::Zstd => 2,
+        CompressionType::Snappy => 3,
+    }
+}
+
+fn compression_from_code(code: u8) -> Option<CompressionType> {
+    match code {
+        0 => Some(CompressionType::None),
+        1 => Some(CompressionType::LZ4),
+        2 => Some(CompressionType::Zstd),
+        3 => Some(CompressionType::Snappy),
+        _ => None,
+    }
+}
+
+fn load_or_create_key(path: &Path) -> Result<Vec<u8>> {
+    match std::fs::read(path) {
+        Ok(key) if key.len() == 32 => return Ok(key),
+        Ok(key) => {
+            return Err(Error::Configuration(format!(
+                "Blob key file {:?} must hold 32 bytes, found {}",
+                path,
+                key.len()
+            )))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(Error::Storage(format!("Failed to read blob key file {:?}: {}", path, e))),
+    }
+
+    use rand::RngCore;
+    let mut key = vec![0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut key);
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    options
+        .open(path)
+        .and_then(|mut file| {
+            file.write_all(&key)?;
+            file.sync_all()
+        })
+        .map_err(|e| Error::Storage(format!("Failed to create blob key file {:?}: {}", path, e)))?;
+    info!("Created blob encryption key at {:?}", path);
+    Ok(key)
+}
+
+fn write_atomic(path: &Path, bytes: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| Error::Storage(format!("Failed to create {:?}: {}", parent, e)))?;
+    }
+    let temp_path = path.with_extension(format!("tmp-{}", uuid::Uuid::new_v4()));
+    let written = std::fs::File::create(&temp_path)
+        .and_then(|mut file| {
+            file.write_all(bytes)?;
+            file.sync_all()
+        })
+        .and_then(|_| std::fs::rename(&temp_path, path));
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(Error::Storage(format!("Failed to write {:?}: {}", path, e)));
+    }
+    Ok(())
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_root() -> PathBuf {
+        std::env::temp_dir().join(format!("narayana_blobs_{}", uuid::Uuid::new_v4()))
+    }
+
+    fn config(chunk_size: usize) -> BlobStoreConfig {
+        BlobStoreConfig { chunk_size, ..BlobStoreConfig::default() }
+    }
+
+    fn content(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 31 % 251) as u8).collect()
+    }
+
+    #[test]
+    fn test_round_trip_and_dedup() {
+        let root = temp_root();
+        let store = BlobStore::open(&root, &config(4096)).unwrap();
+        let data = content(10_000);
+
+        let (info, created) = store.put(&data, Some("audio/wav".to_string())).unwrap();
+        assert!(created);
+        assert_eq!(info.size, 10_000);
+        assert_eq!(info.chunks.len(), 3);
+        assert_eq!(info.id, hex::encode(Sha256::digest(&data)));
+        assert_eq!(store.read(&info.id).unwrap(), data);
+
+        // Same content, streamed in odd pieces, is the same blob
+        let mut writer = store.writer();
+        for piece in data.chunks(777) {
+            writer.write(piece).unwrap();
+        }
+        let (again, created) = writer.finish(None).unwrap();
+        assert!(!created);
+        assert_eq!(again.id, info.id);
+        assert_eq!(store.list().len(), 1);
+        assert_eq!(*store.chunk_refs.read().get(&info.chunks[0].hash).unwrap(), 1);
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_range_reads_cross_chunks() {
+        let root = temp_root();
+        let store = BlobStore::open(&root, &config(4096)).unwrap();
+        let data = content(10_000);
+        let (info, _) = store.put(&data, None).unwrap();
+
+        assert_eq!(store.read_range(&info.id, 4000, 200).unwrap(), data[4000..4200]);
+        assert_eq!(store.read_range(&info.id, 9_990, 100).unwrap(), data[9_990..]);
+        assert_eq!(store.read_range(&info.id, 8192, 0).unwrap(), Vec::<u8>::new());
+        assert!(store.read_range(&info.id, 10_001, 1).is_err());
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_compressed_and_encrypted_chunks_survive_reopen() {
+        let root = temp_root();
+        let mut cfg = config(4096);
+        cfg.compression = "zstd".to_string();
+        cfg.encrypt = true;
+        let data = b"narayana avatar frame ".repeat(1000);
+        let id = {
+            let store = BlobStore::open(&root, &cfg).unwrap();
+            store.put(&data, None).unwrap().0.id
+        };
+
+        let store = BlobStore::open(&root, &cfg).unwrap();
+        let info = store.info(&id).unwrap();
+        assert_eq!(store.read(&id).unwrap(), data);
+        let raw = std::fs::read(store.chunk_path(&info.chunks[0].hash)).unwrap();
+        assert_eq!(raw[0], ENCRYPTED_FLAG | 2);
+        assert!(!raw.windows(7).any(|w| w == b"avatar "));
+
+        // Without the key the chunks cannot be read
+        let plain = BlobStore::open(&root, &config(4096)).unwrap();
+        assert!(plain.read(&id).is_err());
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_delete_keeps_shared_chunks() {
+        let root = temp_root();
+        let store = BlobStore::open(&root, &config(4096)).unwrap();
+        let shared = content(4096);
+        let mut longer = shared.clone();
+        longer.extend_from_slice(b"tail");
+        let (a, _) = store.put(&shared, None).unwrap();
+        let (b, _) = store.put(&longer, None).unwrap();
+        assert_eq!(a.chunks[0].hash, b.chunks[0].hash);
+
+        store.delete(&a.id).unwrap();
+        assert!(store.info(&a.id).is_none());
+        assert_eq!(store.read(&b.id).unwrap(), longer);
+        store.delete(&b.id).unwrap();
+        assert!(!store.chunk_path(&b.chunks[0].hash).exists());
+        assert!(store.delete(&b.id).is_err());
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_abandoned_and_oversized_uploads_leave_nothing() {
+        let root = temp_root();
+        let mut cfg = config(4096);
+        cfg.max_blob_size = 10_000;
+        let store = BlobStore::open(&root, &cfg).unwrap();
+
+        let mut writer = store.writer();
+        writer.write(&content(8192)).unwrap();
+        assert!(writer.write(&content(4096)).is_err());
+        drop(writer);
+        assert!(store.chunk_refs.read().is_empty());
+        let chunk_files = std::fs::read_dir(root.join("chunks")).unwrap()
+            .flatten()
+            .flat_map(|dir| std::fs::read_dir(dir.path()).unwrap().flatten())
+            .count();
+        assert_eq!(chunk_files, 0);
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_parse_range_header() {
+        assert_eq!(parse_range_header(None, 100), RangeRequest::Full);
+        assert_eq!(parse_range_header(Some("bytes=0-9"), 100), RangeRequest::Partial(0, 9));
+        assert_eq!(parse_range_header(Some("bytes=90-"), 100), RangeRequest::Partial(90, 99));
+        assert_eq!(parse_range_header(Some("bytes=-10"), 100), RangeRequest::Partial(90, 99));
+        assert_eq!(parse_range_header(Some("bytes=50-500"), 100), RangeRequest::Partial(50, 99));
+        assert_eq!(parse_range_header(Some("bytes=0-1,5-6"), 100), RangeRequest::Full);
+        assert_eq!(parse_range_header(Some("items=0-1"), 100), RangeRequest::Full);
+        assert_eq!(parse_range_header(Some("bytes=100-"), 100), RangeRequest::Unsatisfiable);
+        assert_eq!(parse_range_header(Some("bytes=9-3"), 100), RangeRequest::Unsatisfiable);
+        assert_eq!(parse_range_header(Some("bytes=-0"), 100), RangeRequest::Unsatisfiable);
+        assert_eq!(parse_range_header(Some("bytes=0-0"), 0), RangeRequest::Unsatisfiable);
+    }
+
+    #[test]
+    fn test_blob_refs() {
+        let id = "a".repeat(64);
+        let blob = BlobRef::parse(&format!("blob:{}", id)).unwrap();
+        assert_eq!(blob.id(), id);
+        assert_eq!(blob.to_string(), format!("blob:{}", id));
+        assert_eq!(serde_json::to_value(&blob).unwrap(), serde_json::json!(format!("blob:{}", id)));
+        assert!(BlobRef::parse("blob:../../etc/passwd").is_none());
+        assert!(BlobRef::parse(&id).is_none());
+        assert!(serde_json::from_value::<BlobRef>(serde_json::json!("blob:xyz")).is_err());
+    }
+}
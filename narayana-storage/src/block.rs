@@ -1,6 +1,8 @@
 use narayana_core::{types::CompressionType, schema::DataType};
 use serde::{Deserialize, Serialize};
 use bytes::Bytes;
+use std::cmp::Ordering;
+use std::collections::HashMap;
 
 /// A block of columnar data
 // Note: Block is not serializable because Bytes doesn't implement Serialize/Deserialize
@@ -27,9 +29,14 @@ pub struct BlockMetadata {
     pub compression: CompressionType,
     pub uncompressed_size: usize,
     pub compressed_size: usize,
+    /// Zone map bounds: little-endian numbers, a 0/1 byte for booleans,
+    /// UTF-8 for strings. None when the block has no non-null values, holds
+    /// a NaN, or its type keeps no statistics
     pub min_value: Option<Vec<u8>>,
     pub max_value: Option<Vec<u8>>,
     pub null_count: usize,
+    /// Estimated distinct non-null values
+    pub distinct_count: Option<u64>,
 }
 
 impl BlockMetadata {
@@ -39,5 +46,343 @@ impl BlockMetadata {
         }
         self.compressed_size as f64 / self.uncompressed_size as f64
     }
+
+    pub fn min(&self) -> Option<ZoneValue> {
+        ZoneValue::decode(&self.data_type, self.min_value.as_deref()?)
+    }
+
+    pub fn max(&self) -> Option<ZoneValue> {
+        ZoneValue::decode(&self.data_type, self.max_value.as_deref()?)
+    }
+
+    /// Whether any row of the block could satisfy `predicate`; false only
+    /// when the zone map rules every row out
+    pub fn may_match(&self, predicate: &BlockPredicate) -> bool {
+        let bounds = self.min().zip(self.max());
+        let compare = |value: &ZoneValue| {
+            bounds.as_ref().and_then(|(min, max)| Some((value.compare(min)?, value.compare(max)?)))
+        };
+        match predicate {
+            BlockPredicate::And(left, right) => self.may_match(left) && self.may_match(right),
+            BlockPredicate::Or(left, right) => self.may_match(left) || self.may_match(right),
+            BlockPredicate::In(values) => values.iter().any(|value| self.may_match(&BlockPredicate::Eq(value.clone()))),
+            BlockPredicate::Between(low, high) => {
+                self.may_match(&BlockPredicate::Gte(low.clone())) && self.may_match(&BlockPredicate::Lte(high.clone()))
+            }
+            // Nulls never match a comparison, so a block of nulls only can go
+            BlockPredicate::Eq(value) => match compare(value) {
+                Some((to_min, to_max)) => to_min != Ordering::Less && to_max != Ordering::Greater,
+                None => bounds.is_some() || self.null_count < self.row_count,
+            },
+            // Only a block holding nothing but `value` can go
+            BlockPredicate::Ne(value) => compare(value)
+                .is_none_or(|bounds| bounds != (Ordering::Equal, Ordering::Equal)),
+            BlockPredicate::Lt(value) => compare(value).is_none_or(|(to_min, _)| to_min == Ordering::Greater),
+            BlockPredicate::Lte(value) => compare(value).is_none_or(|(to_min, _)| to_min != Ordering::Less),
+            BlockPredicate::Gt(value) => compare(value).is_none_or(|(_, to_max)| to_max == Ordering::Less),
+            BlockPredicate::Gte(value) => compare(value).is_none_or(|(_, to_max)| to_max != Ordering::Greater),
+        }
+    }
+}
+
+/// A value compared against block zone maps
+#[derive(Debug, Clone, PartialEq)]
+pub enum ZoneValue {
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    Boolean(bool),
+    String(String),
+}
+
+impl ZoneValue {
+    fn decode(data_type: &DataType, bytes: &[u8]) -> Option<Self> {
+        fn array<const N: usize>(bytes: &[u8]) -> Option<[u8; N]> {
+            bytes.try_into().ok()
+        }
+        Some(match data_type {
+            DataType::Int8 => Self::Int(i8::from_le_bytes(array(bytes)?) as i64),
+            DataType::Int16 => Self::Int(i16::from_le_bytes(array(bytes)?) as i64),
+            DataType::Int32 | DataType::Date => Self::Int(i32::from_le_bytes(array(bytes)?) as i64),
+            DataType::Int64 | DataType::Timestamp => Self::Int(i64::from_le_bytes(array(bytes)?)),
+            DataType::UInt8 => Self::UInt(u8::from_le_bytes(array(bytes)?) as u64),
+            DataType::UInt16 => Self::UInt(u16::from_le_bytes(array(bytes)?) as u64),
+            DataType::UInt32 => Self::UInt(u32::from_le_bytes(array(bytes)?) as u64),
+            DataType::UInt64 => Self::UInt(u64::from_le_bytes(array(bytes)?)),
+            DataType::Float32 => Self::Float(f32::from_le_bytes(array(bytes)?) as f64),
+            DataType::Float64 => Self::Float(f64::from_le_bytes(array(bytes)?)),
+            DataType::Boolean => Self::Boolean(*bytes.first()? != 0),
+            DataType::String => Self::String(String::from_utf8(bytes.to_vec()).ok()?),
+            _ => return None,
+        })
+    }
+
+    /// Order of `self` against a zone map bound; None when the two can't be
+    /// compared, which keeps the block
+    fn compare(&self, bound: &ZoneValue) -> Option<Ordering> {
+        match (self, bound) {
+            (Self::Int(a), Self::Int(b)) => Some(a.cmp(b)),
+            (Self::UInt(a), Self::UInt(b)) => Some(a.cmp(b)),
+            (Self::Int(a), Self::UInt(b)) => Some((*a as i128).cmp(&(*b as i128))),
+            (Self::UInt(a), Self::Int(b)) => Some((*a as i128).cmp(&(*b as i128))),
+            (Self::Boolean(a), Self::Boolean(b)) => Some(a.cmp(b)),
+            (Self::String(a), Self::String(b)) => Some(a.as_str().cmp(b.as_str())),
+            (a, b) => a.as_f64()?.partial_cmp(&b.as_f64()?),
+        }
+    }
+
+    fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Int(v) => Some(*v as f64),
+            Self::UInt(v) => Some(*v as f64),
+            Self::Float(v) => Some(*v),
+            _ => None,
+        }
+    }
 }
 
+/// A filter on one column, checked against each block's zone map
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlockPredicate {
+    Eq(ZoneValue),
+    Ne(ZoneValue),
+    Lt(ZoneValue),
+    Lte(ZoneValue),
+    Gt(ZoneValue),
+    Gte(ZoneValue),
+    Between(ZoneValue, ZoneValue),
+    In(Vec<ZoneValue>),
+    And(Box<BlockPredicate>, Box<BlockPredicate>),
+    Or(Box<BlockPredicate>, Box<BlockPredicate>),
+}
+
+/// Zone map statistics of a block, as the writer gathers them
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BlockStats {
+    pub min_value: Option<Vec<u8>>,
+    pub max_value: Option<Vec<u8>>,
+    pub distinct_count: Option<u64>,
+}
+
+impl BlockStats {
+    /// Statistics of values ordered by `compare`; `encode` gives a value as
+    /// stored in the zone map and `hash` feeds the distinct estimate
+    pub fn gather<T>(
+        values: &[T],
+        compare: impl Fn(&T, &T) -> Ordering,
+        encode: impl Fn(&T) -> Vec<u8>,
+        hash: impl Fn(&T) -> u64,
+    ) -> Self {
+        let mut min: Option<&T> = None;
+        let mut max: Option<&T> = None;
+        let mut distinct = DistinctEstimator::new(values.len());
+        for value in values {
+            if min.is_none_or(|m| compare(value, m) == Ordering::Less) {
+                min = Some(value);
+            }
+            if max.is_none_or(|m| compare(value, m) == Ordering::Greater) {
+                max = Some(value);
+            }
+            distinct.insert(hash(value));
+        }
+        Self {
+            min_value: min.map(&encode),
+            max_value: max.map(&encode),
+            distinct_count: Some(distinct.estimate().min(values.len() as u64)),
+        }
+    }
+}
+
+/// Linear counting over a bitmap sized to the block: exact-ish for small
+/// blocks, within a few percent up to several times the bitmap's bits
+struct DistinctEstimator {
+    bits: Vec<u64>,
+}
+
+impl DistinctEstimator {
+    const MAX_BITS: usize = 1 << 16;
+
+    fn new(rows: usize) -> Self {
+        let bits = (rows.max(1) * 2).next_power_of_two().clamp(1024, Self::MAX_BITS);
+        Self { bits: vec![0; bits / 64] }
+    }
+
+    fn insert(&mut self, hash: u64) {
+        let hash = mix(hash);
+        let bit = (hash as usize) & (self.bits.len() * 64 - 1);
+        self.bits[bit / 64] |= 1 << (bit % 64);
+    }
+
+    fn estimate(&self) -> u64 {
+        let m = (self.bits.len() * 64) as f64;
+        let empty: u32 = self.bits.iter().map(|word| word.count_zeros()).sum();
+        if empty == 0 {
+            // Saturated: the bitmap can't tell; report what it can't exceed
+            return u64::MAX;
+        }
+        (-m * (empty as f64 / m).ln()).round() as u64
+    }
+}
+
+/// splitmix64 finaliser, spreading integer keys over the bitmap
+fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
+
+/// Prefix of bincode table metadata written with block statistics; metadata
+/// without it predates `distinct_count` and is read as `BlockMetadataV1`
+pub(crate) const STATS_FORMAT_MAGIC: &[u8; 8] = b"NZMAP\x00\x00\x01";
+
+/// Block metadata as persisted before `distinct_count`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct BlockMetadataV1 {
+    block_id: u64,
+    column_id: u32,
+    row_start: usize,
+    row_count: usize,
+    data_type: DataType,
+    compression: CompressionType,
+    uncompressed_size: usize,
+    compressed_size: usize,
+    min_value: Option<Vec<u8>>,
+    max_value: Option<Vec<u8>>,
+    null_count: usize,
+}
+
+impl From<BlockMetadataV1> for BlockMetadata {
+    fn from(v1: BlockMetadataV1) -> Self {
+        Self {
+            block_id: v1.block_id,
+            column_id: v1.column_id,
+            row_start: v1.row_start,
+            row_count: v1.row_count,
+            data_type: v1.data_type,
+            compression: v1.compression,
+            uncompressed_size: v1.uncompressed_size,
+            compressed_size: v1.compressed_size,
+            min_value: v1.min_value,
+            max_value: v1.max_value,
+            null_count: v1.null_count,
+            distinct_count: None,
+        }
+    }
+}
+
+pub(crate) fn upgrade_blocks(blocks: HashMap<u32, Vec<BlockMetadataV1>>) -> HashMap<u32, Vec<BlockMetadata>> {
+    blocks
+        .into_iter()
+        .map(|(column_id, blocks)| (column_id, blocks.into_iter().map(BlockMetadata::from).collect()))
+        .collect()
+}
+
+/// Bincode `value` behind the statistics format prefix
+pub(crate) fn encode_with_stats<T: Serialize>(value: &T) -> bincode::Result<Vec<u8>> {
+    let mut bytes = STATS_FORMAT_MAGIC.to_vec();
+    bincode::serialize_into(&mut bytes, value)?;
+    Ok(bytes)
+}
+
+/// Decode what `encode_with_stats` wrote, or upgrade metadata written before
+/// it from its `V1` form
+pub(crate) fn decode_with_stats<T, V1>(bytes: &[u8], upgrade: impl FnOnce(V1) -> T) -> bincode::Result<T>
+where
+    T: serde::de::DeserializeOwned,
+    V1: serde::de::DeserializeOwned,
+{
+    match bytes.strip_prefix(STATS_FORMAT_MAGIC.as_slice()) {
+        Some(current) => bincode::deserialize(current),
+        None => bincode::deserialize::<V1>(bytes).map(upgrade),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn int_block(values: &[i64]) -> BlockMetadata {
+        let stats = BlockStats::gather(values, Ord::cmp, |v| v.to_le_bytes().to_vec(), |v| *v as u64);
+        BlockMetadata {
+            block_id: 0,
+            column_id: 0,
+            row_start: 0,
+            row_count: values.len(),
+            data_type: DataType::Int64,
+            compression: CompressionType::None,
+            uncompressed_size: 0,
+            compressed_size: 0,
+            min_value: stats.min_value,
+            max_value: stats.max_value,
+            null_count: 0,
+            distinct_count: stats.distinct_count,
+        }
+    }
+
+    #[test]
+    fn test_zone_map_pruning() {
+        let block = int_block(&[10, 20, 30, 20]);
+        assert_eq!((block.min(), block.max()), (Some(ZoneValue::Int(10)), Some(ZoneValue::Int(30))));
+        assert_eq!(block.distinct_count, Some(3));
+
+        assert!(block.may_match(&BlockPredicate::Eq(ZoneValue::Int(20))));
+        assert!(!block.may_match(&BlockPredicate::Eq(ZoneValue::Int(31))));
+        assert!(!block.may_match(&BlockPredicate::Gt(ZoneValue::Int(30))));
+        assert!(block.may_match(&BlockPredicate::Gte(ZoneValue::Int(30))));
+        assert!(!block.may_match(&BlockPredicate::Lt(ZoneValue::Int(10))));
+        assert!(block.may_match(&BlockPredicate::Lte(ZoneValue::Float(10.0))));
+        assert!(!block.may_match(&BlockPredicate::Between(ZoneValue::Int(31), ZoneValue::Int(40))));
+        assert!(block.may_match(&BlockPredicate::In(vec![ZoneValue::Int(1), ZoneValue::UInt(25)])));
+        assert!(!block.may_match(&BlockPredicate::And(
+            Box::new(BlockPredicate::Gt(ZoneValue::Int(15))),
+            Box::new(BlockPredicate::Lt(ZoneValue::Int(5))),
+        )));
+        // Values of another kind can't be compared, so the block stays
+        assert!(block.may_match(&BlockPredicate::Eq(ZoneValue::String("x".to_string()))));
+
+        let constant = int_block(&[7, 7]);
+        assert!(!constant.may_match(&BlockPredicate::Ne(ZoneValue::Int(7))));
+        assert!(constant.may_match(&BlockPredicate::Ne(ZoneValue::Int(8))));
+    }
+
+    #[test]
+    fn test_blocks_without_statistics_are_kept() {
+        let mut block = int_block(&[1, 2]);
+        block.min_value = None;
+        block.max_value = None;
+        assert!(block.may_match(&BlockPredicate::Eq(ZoneValue::Int(100))));
+        assert!(block.may_match(&BlockPredicate::Gt(ZoneValue::Int(100))));
+    }
+
+    #[test]
+    fn test_distinct_estimate() {
+        let values: Vec<i64> = (0..50_000).map(|v| v % 20_000).collect();
+        let estimate = int_block(&values).distinct_count.unwrap() as f64;
+        assert!((estimate - 20_000.0).abs() / 20_000.0 < 0.05, "estimate {}", estimate);
+    }
+
+    #[test]
+    fn test_metadata_written_before_statistics_is_upgraded() {
+        let v1 = BlockMetadataV1 {
+            block_id: 3,
+            column_id: 1,
+            row_start: 0,
+            row_count: 2,
+            data_type: DataType::Int64,
+            compression: CompressionType::None,
+            uncompressed_size: 16,
+            compressed_size: 16,
+            min_value: None,
+            max_value: None,
+            null_count: 0,
+        };
+        let old = bincode::serialize(&vec![v1]).unwrap();
+        let upgrade = |blocks: Vec<BlockMetadataV1>| blocks.into_iter().map(BlockMetadata::from).collect::<Vec<_>>();
+        let blocks: Vec<BlockMetadata> = decode_with_stats(&old, upgrade).unwrap();
+        assert_eq!((blocks[0].block_id, blocks[0].distinct_count), (3, None));
+
+        let current = encode_with_stats(&vec![int_block(&[1, 2])]).unwrap();
+        let blocks: Vec<BlockMetadata> = decode_with_stats(&current, upgrade).unwrap();
+        assert_eq!(blocks[0].distinct_count, Some(2));
+    }
+}
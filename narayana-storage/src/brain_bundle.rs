@@ -0,0 +1,651 @@
+// Brain State Bundles
+// Export a brain's thoughts, memories, cognitive graph, RL policies and traits into a
+// versioned file; import with ID remapping and conflict policies (backup, clone, inspect)
+
+use crate::cognitive::*;
+use crate::cognitive_graph::{CognitiveGraph, Concept, Relationship};
+use crate::genetics::{GeneticConfig, GeneticSystem, Genome};
+use crate::reinforcement_learning::RLState;
+use crate::traits_equations::{EnvironmentalFactor, TraitCalculator, TraitType};
+use narayana_core::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Format marker written into every bundle
+pub const BRAIN_BUNDLE_FORMAT: &str = "narayana-brain-bundle";
+/// Current bundle version; bundles from newer versions are rejected
+pub const BRAIN_BUNDLE_VERSION: u32 = 1;
+
+/// Concepts and relationships of a cognitive graph
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphState {
+    pub concepts: Vec<Concept>,
+    pub relationships: Vec<Relationship>,
+}
+
+/// Genome, environment and resulting trait values
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraitState {
+    pub genome: Genome,
+    pub genetic_config: GeneticConfig,
+    pub environmental_weight: f64,
+    pub environmental_factors: Vec<EnvironmentalFactor>,
+    pub interactions: Vec<(TraitType, TraitType, f64)>,
+    /// Trait values at export time, for offline inspection; recomputed on import
+    pub values: BTreeMap<String, f64>,
+}
+
+/// Complete brain state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrainBundle {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: u64,
+    /// Free-form label, e.g. the unit the bundle was taken from
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub thoughts: Vec<Thought>,
+    #[serde(default)]
+    pub memories: Vec<Memory>,
+    #[serde(default)]
+    pub experiences: Vec<Experience>,
+    #[serde(default)]
+    pub patterns: Vec<Pattern>,
+    #[serde(default)]
+    pub working_memory: Vec<CognitiveState>,
+    #[serde(default)]
+    pub graph: Option<GraphState>,
+    #[serde(default)]
+    pub rl: Option<RLState>,
+    #[serde(default)]
+    pub traits: Option<TraitState>,
+}
+
+/// What to do when an imported item already exists in the target brain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictPolicy {
+    /// Keep the target's item
+    #[default]
+    Skip,
+    /// Replace the target's item
+    Overwrite,
+    /// Import under a new ID and keep both; traits, one per brain, are kept
+    Remap,
+    /// Abort before anything is imported
+    Fail,
+}
+
+/// Import options
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportOptions {
+    #[serde(default)]
+    pub conflict: ConflictPolicy,
+    /// Give every imported item a new ID (cloning a mind into another unit)
+    #[serde(default)]
+    pub fresh_ids: bool,
+    #[serde(default = "default_true")]
+    pub include_rl: bool,
+    #[serde(default = "default_true")]
+    pub include_traits: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        Self {
+            conflict: ConflictPolicy::Skip,
+            fresh_ids: false,
+            include_rl: true,
+            include_traits: true,
+        }
+    }
+}
+
+/// Outcome of an import
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportReport {
+    /// Imported items per kind
+    pub imported: BTreeMap<String, usize>,
+    pub skipped: usize,
+    pub overwritten: usize,
+    pub traits_imported: bool,
+    /// Bundle ID -> ID in the target brain, for items whose ID changed
+    pub id_map: BTreeMap<String, String>,
+}
+
+/// Per-item import decisions, made before anything is written
+#[derive(Default)]
+struct ImportPlan {
+    id_map: HashMap<String, String>,
+    skip: HashSet<String>,
+    overwritten: usize,
+}
+
+impl ImportPlan {
+    fn decide(&mut self, kind: &str, id: &str, exists: bool, options: &ImportOptions, new_id: impl FnOnce() -> String) -> Result<()> {
+        if options.fresh_ids {
+            self.id_map.insert(id.to_string(), new_id());
+            return Ok(());
+        }
+        if !exists {
+            return Ok(());
+        }
+        match options.conflict {
+            ConflictPolicy::Skip => {
+                self.skip.insert(id.to_string());
+            }
+            ConflictPolicy::Overwrite => self.overwritten += 1,
+            ConflictPolicy::Remap => {
+                self.id_map.insert(id.to_string(), new_id());
+            }
+            ConflictPolicy::Fail => {
+                return Err(Error::Storage(format!("{} {} already exists in the target brain", kind, id)));
+            }
+        }
+        Ok(())
+    }
+
+    fn skipped(&self, id: &str) -> bool {
+        self.skip.contains(id)
+    }
+
+    /// ID in the target brain; skipped items resolve to the target's own copy
+    fn map(&self, id: &str) -> String {
+        self.id_map.get(id).cloned().unwrap_or_else(|| id.to_string())
+    }
+
+    fn map_all(&self, ids: &[String]) -> Vec<String> {
+        ids.iter().map(|id| self.map(id)).collect()
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn new_uuid() -> String {
+    Uuid::new_v4().to_string()
+}
+
+/// New name for a named item (policy, value function) kept alongside an existing one
+fn suffixed(name: &str) -> String {
+    format!("{}-{}", name, &Uuid::new_v4().simple().to_string()[..8])
+}
+
+impl BrainBundle {
+    /// Export a brain, with its cognitive graph if it has one
+    pub fn export(brain: &CognitiveBrain, graph: Option<&CognitiveGraph>, label: Option<String>) -> Result<Self> {
+        let mut thoughts: Vec<Thought> = brain.thoughts.read().values().cloned().collect();
+        thoughts.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+        let mut memories: Vec<Memory> = brain.memories.read().values().cloned().collect();
+        memories.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+        let mut experiences: Vec<Experience> = brain.experiences.read().values().cloned().collect();
+        experiences.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then_with(|| a.id.cmp(&b.id)));
+        let mut patterns: Vec<Pattern> = brain.patterns.read().values().cloned().collect();
+        patterns.sort_by(|a, b| a.id.cmp(&b.id));
+
+        let graph = graph.map(|graph| {
+            let mut concepts = graph.concepts();
+            concepts.sort_by(|a, b| a.id.cmp(&b.id));
+            let mut relationships = graph.relationships();
+            relationships.sort_by(|a, b| a.id.cmp(&b.id));
+            GraphState { concepts, relationships }
+        });
+
+        let rl = brain.get_rl_engine().map(|engine| engine.export_state()).transpose()?;
+
+        let traits = match (brain.get_genetic_system(), brain.get_trait_calculator()) {
+            (Some(genetics), Some(calculator)) => Some(TraitState {
+                genome: genetics.get_genome(),
+                genetic_config: genetics.get_config(),
+                environmental_weight: calculator.environmental_weight(),
+                environmental_factors: calculator.environmental_factors(),
+                interactions: calculator.trait_interactions(),
+                values: calculator.get_all_traits()?
+                    .into_iter()
+                    .map(|(trait_type, value)| (trait_type.as_str().to_string(), value.value))
+                    .collect(),
+            }),
+            _ => None,
+        };
+
+        Ok(Self {
+            format: BRAIN_BUNDLE_FORMAT.to_string(),
+            version: BRAIN_BUNDLE_VERSION,
+            exported_at: now(),
+            label,
+            thoughts,
+            memories,
+            experiences,
+            patterns,
+            working_memory: brain.get_working_memory(),
+            graph,
+            rl,
+            traits,
+        })
+    }
+
+    /// Parse and check a bundle
+    pub fn from_json(json: &str) -> Result<Self> {
+        let bundle: Self = serde_json::from_str(json)
+            .map_err(|e| Error::Deserialization(format!("Invalid brain bundle: {}", e)))?;
+        bundle.check_version()?;
+        Ok(bundle)
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(self)
+            .map_err(|e| Error::Serialization(format!("Failed to serialize brain bundle: {}", e)))
+    }
+
+    /// Write the bundle to a file atomically
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, self.to_json()?)
+            .map_err(|e| Error::Storage(format!("Failed to write brain bundle {}: {}", tmp.display(), e)))?;
+        std::fs::rename(&tmp, path)
+            .map_err(|e| Error::Storage(format!("Failed to write brain bundle {}: {}", path.display(), e)))?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| Error::Storage(format!("Failed to read brain bundle {}: {}", path.display(), e)))?;
+        Self::from_json(&json)
+    }
+
+    fn check_version(&self) -> Result<()> {
+        if self.format != BRAIN_BUNDLE_FORMAT {
+            return Err(Error::Deserialization(format!("Not a brain bundle (format '{}')", self.format)));
+        }
+        if self.version == 0 || self.version > BRAIN_BUNDLE_VERSION {
+            return Err(Error::Deserialization(format!(
+                "Unsupported brain bundle version {} (supported up to {})", self.version, BRAIN_BUNDLE_VERSION
+            )));
+        }
+        Ok(())
+    }
+
+    /// Import into a brain (and its cognitive graph, if the bundle has one)
+    ///
+    /// All conflicts are resolved before anything is written, so `Fail` leaves
+    /// the brain untouched. References between items (associations, memory
+    /// accesses, spawned thoughts, graph edges) follow remapped IDs.
+    pub fn import_into(
+        &self,
+        brain: &CognitiveBrain,
+        graph: Option<&CognitiveGraph>,
+        options: &ImportOptions,
+    ) -> Result<ImportReport> {
+        self.check_version()?;
+        let mut plan = ImportPlan::default();
+        let mut report = ImportReport::default();
+
+        {
+            let thoughts = brain.thoughts.read();
+            for thought in &self.thoughts {
+                plan.decide("Thought", &thought.id, thoughts.contains_key(&thought.id), options, new_uuid)?;
+            }
+        }
+        {
+            let memories = brain.memories.read();
+            for memory in &self.memories {
+                plan.decide("Memory", &memory.id, memories.contains_key(&memory.id), options, new_uuid)?;
+            }
+        }
+        {
+            let experiences = brain.experiences.read();
+            for experience in &self.experiences {
+                plan.decide("Experience", &experience.id, experiences.contains_key(&experience.id), options, new_uuid)?;
+            }
+        }
+        {
+            let patterns = brain.patterns.read();
+            for pattern in &self.patterns {
+                plan.decide("Pattern", &pattern.id, patterns.contains_key(&pattern.id), options, new_uuid)?;
+            }
+        }
+        let working_ids: HashSet<String> = brain.get_working_memory().into_iter().map(|state| state.id).collect();
+        for state in &self.working_memory {
+            plan.decide("Working memory state", &state.id, working_ids.contains(&state.id), options, new_uuid)?;
+        }
+
+        let graph_state = match (&self.graph, graph) {
+            (Some(state), Some(graph)) => {
+                for concept in &state.concepts {
+                    plan.decide("Concept", &concept.id, graph.get_concept(&concept.id).is_some(), options, new_uuid)?;
+                }
+                for relationship in &state.relationships {
+                    let exists = graph.get_relationship(&relationship.id).is_some();
+                    plan.decide("Relationship", &relationship.id, exists, options, new_uuid)?;
+                }
+                Some((state, graph))
+            }
+            (Some(_), None) => {
+                warn!("Brain bundle has a cognitive graph but the target has none; skipping it");
+                None
+            }
+            _ => None,
+        };
+
+        let rl = match (&self.rl, brain.get_rl_engine()) {
+            (Some(state), Some(engine)) if options.include_rl => {
+                for policy_id in state.policies.keys() {
+                    plan.decide("Policy", policy_id, engine.has_policy(policy_id), options, || suffixed(policy_id))?;
+                }
+                for function_id in state.value_functions.keys() {
+                    let exists = engine.has_value_function(function_id);
+                    plan.decide("Value function", function_id, exists, options, || suffixed(function_id))?;
+                }
+                Some((state, engine))
+            }
+            (Some(_), None) if options.include_rl => {
+                warn!("Brain bundle has RL state but the target brain has no RL engine; skipping it");
+                None
+            }
+            _ => None,
+        };
+
+        // Traits are one per brain, so only a missing set or Overwrite replaces them
+        let mut traits_skipped = false;
+        let traits = match &self.traits {
+            Some(traits) if options.include_traits => {
+                let present = brain.get_trait_calculator().is_some();
+                match (present, options.conflict) {
+                    (false, _) | (true, ConflictPolicy::Overwrite) => Some(traits),
+                    (true, ConflictPolicy::Fail) => {
+                        return Err(Error::Storage("Traits already exist in the target brain".to_string()));
+                    }
+                    (true, _) => {
+                        traits_skipped = true;
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+
+        // Thoughts that move keep their threads together under new thread IDs
+        let mut thread_map: HashMap<String, String> = HashMap::new();
+        let mut count = |kind: &str| *report.imported.entry(kind.to_string()).or_insert(0) += 1;
+
+        for memory in &self.memories {
+            if plan.skipped(&memory.id) {
+                continue;
+            }
+            brain.restore_memory(Memory {
+                id: plan.map(&memory.id),
+                associations: plan.map_all(&memory.associations),
+                ..memory.clone()
+            });
+            count("memories");
+        }
+
+        for thought in &self.thoughts {
+            if plan.skipped(&thought.id) {
+                continue;
+            }
+            let id = plan.map(&thought.id);
+            let thread_id = if id == thought.id {
+                thought.thread_id.clone()
+            } else {
+                thread_map.entry(thought.thread_id.clone()).or_insert_with(new_uuid).clone()
+            };
+            brain.restore_thought(Thought {
+                id,
+                thread_id,
+                associations: plan.map_all(&thought.associations),
+                spawned_thoughts: plan.map_all(&thought.spawned_thoughts),
+                memory_accesses: thought.memory_accesses
+                    .iter()
+                    .map(|access| MemoryAccessRecord { memory_id: plan.map(&access.memory_id), ..access.clone() })
+                    .collect(),
+                ..thought.clone()
+            });
+            count("thoughts");
+        }
+
+        for pattern in &self.patterns {
+            if plan.skipped(&pattern.id) {
+                continue;
+            }
+            let id = plan.map(&pattern.id);
+            brain.patterns.write().insert(id.clone(), Pattern { id, ..pattern.clone() });
+            count("patterns");
+        }
+
+        for experience in &self.experiences {
+            if plan.skipped(&experience.id) {
+                continue;
+            }
+            let id = plan.map(&experience.id);
+            let patterns = experience.patterns
+                .iter()
+                .map(|pattern| Pattern { id: plan.map(&pattern.id), ..pattern.clone() })
+                .collect();
+            brain.experiences.write().insert(id.clone(), Experience { id, patterns, ..experience.clone() });
+            count("experiences");
+        }
+
+        for state in &self.working_memory {
+            if plan.skipped(&state.id) {
+                continue;
+            }
+            brain.add_to_working_memory(CognitiveState {
+                id: plan.map(&state.id),
+                thought_id: plan.map(&state.thought_id),
+                associations: plan.map_all(&state.associations),
+                ..state.clone()
+            });
+            count("working_memory");
+        }
+
+        if let Some((state, graph)) = graph_state {
+            for concept in &state.concepts {
+                if plan.skipped(&concept.id) {
+                    continue;
+                }
+                graph.add_concept(Concept { id: plan.map(&concept.id), ..concept.clone() })?;
+                count("concepts");
+            }
+            for relationship in &state.relationships {
+                if plan.skipped(&relationship.id) {
+                    continue;
+                }
+                graph.restore_relationship(Relationship {
+                    id: plan.map(&relationship.id),
+                    from_concept: plan.map(&relationship.from_concept),
+                    to_concept: plan.map(&relationship.to_concept),
+                    ..relationship.clone()
+                })?;
+                count("relationships");
+            }
+        }
+
+        if let Some((state, engine)) = rl {
+            for (policy_id, policy) in &state.policies {
+                if plan.skipped(policy_id) {
+                    continue;
+                }
+                engine.import_policy(&plan.map(policy_id), policy)?;
+                count("policies");
+            }
+            for (function_id, values) in &state.value_functions {
+                if plan.skipped(function_id) {
+                    continue;
+                }
+                engine.import_value_function(&plan.map(function_id), values.clone());
+                count("value_functions");
+            }
+        }
+
+        if let Some(traits) = traits {
+            let genetics = Arc::new(GeneticSystem::from_genome(traits.genome.clone(), traits.genetic_config.clone()));
+            let calculator = Arc::new(TraitCalculator::new(genetics.clone(), traits.environmental_weight));
+            for (from, to, strength) in &traits.interactions {
+                calculator.set_trait_interaction(from.clone(), to.clone(), *strength);
+            }
+            for factor in &traits.environmental_factors {
+                calculator.restore_environmental_factor(factor.clone())?;
+            }
+            brain.set_genetics(genetics, calculator);
+            report.traits_imported = true;
+        }
+
+        report.skipped = plan.skip.len() + usize::from(traits_skipped);
+        report.overwritten = plan.overwritten;
+        report.id_map = plan.id_map.into_iter().collect();
+        info!(
+            "Imported brain bundle{}: {:?}, {} skipped, {} overwritten, {} remapped",
+            self.label.as_ref().map(|label| format!(" '{}'", label)).unwrap_or_default(),
+            report.imported, report.skipped, report.overwritten, report.id_map.len()
+        );
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cognitive_graph::{ConceptType, RelationshipType};
+    use crate::reinforcement_learning::{RLAlgorithm, RLConfig, RLEngine};
+
+    fn rl_config() -> RLConfig {
+        RLConfig {
+            learning_rate: 0.01,
+            discount_factor: 0.99,
+            epsilon: 0.1,
+            batch_size: 32,
+            replay_buffer_size: 10000,
+            update_frequency: 100,
+            algorithm: RLAlgorithm::QLearning,
+        }
+    }
+
+    fn concept(name: &str) -> Concept {
+        Concept {
+            id: Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            description: String::new(),
+            concept_type: ConceptType::Entity,
+            properties: HashMap::new(),
+            created_at: 0,
+            last_accessed: 0,
+            access_count: 0,
+        }
+    }
+
+    /// A brain with linked memories, a thought that read one of them, a graph edge and a policy
+    fn populated() -> (Arc<CognitiveBrain>, CognitiveGraph, String, String, String) {
+        let brain = Arc::new(CognitiveBrain::new());
+        let thought = brain.create_thought(serde_json::json!({"task": "find cup"}), 0.8).unwrap();
+        let kitchen = brain
+            .store_memory(MemoryType::Spatial, serde_json::json!("kitchen"), None, vec!["room".to_string()], Some(&thought))
+            .unwrap();
+        let cup = brain
+            .store_memory(MemoryType::Semantic, serde_json::json!("cup is in the kitchen"), None, vec![], None)
+            .unwrap();
+        brain.memories.write().get_mut(&cup).unwrap().associations.push(kitchen.clone());
+        brain.store_experience("grasp".to_string(), serde_json::json!({"object": "cup"}), None, None, Some(1.0), None).unwrap();
+
+        let engine = Arc::new(RLEngine::new(brain.clone(), rl_config()));
+        engine.create_policy("grasping", &serde_json::json!({"state": "initial"})).unwrap();
+        brain.set_rl_engine(engine);
+
+        let graph = CognitiveGraph::new();
+        let cup_concept = graph.add_concept(concept("cup")).unwrap();
+        let kitchen_concept = graph.add_concept(concept("kitchen")).unwrap();
+        graph.create_relationship(&cup_concept, &kitchen_concept, RelationshipType::LocatedAt, 0.9).unwrap();
+
+        (brain, graph, thought, kitchen, cup)
+    }
+
+    #[test]
+    fn test_round_trip_into_fresh_brain() {
+        let (brain, graph, thought, kitchen, cup) = populated();
+        let bundle = BrainBundle::export(&brain, Some(&graph), Some("unit-7".to_string())).unwrap();
+
+        let path = std::env::temp_dir().join(format!("brain-bundle-{}.json", Uuid::new_v4()));
+        bundle.save(&path).unwrap();
+        let loaded = BrainBundle::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.label.as_deref(), Some("unit-7"));
+
+        let target = Arc::new(CognitiveBrain::new());
+        target.set_rl_engine(Arc::new(RLEngine::new(target.clone(), rl_config())));
+        let target_graph = CognitiveGraph::new();
+        let report = loaded.import_into(&target, Some(&target_graph), &ImportOptions::default()).unwrap();
+
+        assert_eq!(report.imported["memories"], 2);
+        assert_eq!(report.imported["thoughts"], 1);
+        assert_eq!(report.imported["experiences"], 1);
+        assert_eq!(report.imported["relationships"], 1);
+        assert_eq!(report.skipped, 0);
+        assert!(report.id_map.is_empty());
+        assert_eq!(target.memories.read()[&cup].associations, vec![kitchen.clone()]);
+        assert_eq!(target.thoughts.read()[&thought].memory_accesses[0].memory_id, kitchen);
+        assert_eq!(target_graph.relationships().len(), 1);
+        assert!(target.get_rl_engine().unwrap().has_policy("grasping"));
+    }
+
+    #[test]
+    fn test_remap_keeps_both_copies_linked() {
+        let (brain, graph, thought, kitchen, cup) = populated();
+        let bundle = BrainBundle::export(&brain, Some(&graph), None).unwrap();
+
+        let options = ImportOptions { conflict: ConflictPolicy::Remap, ..ImportOptions::default() };
+        let report = bundle.import_into(&brain, Some(&graph), &options).unwrap();
+        assert_eq!(brain.memories.read().len(), 4);
+        assert_eq!(brain.thoughts.read().len(), 2);
+        assert_eq!(graph.relationships().len(), 2);
+
+        // References inside the imported copy follow the new IDs
+        let new_cup = &report.id_map[&cup];
+        let new_kitchen = &report.id_map[&kitchen];
+        assert_eq!(&brain.memories.read()[new_cup].associations, &vec![new_kitchen.clone()]);
+        let new_thought = &brain.thoughts.read()[&report.id_map[&thought]];
+        assert_eq!(&new_thought.memory_accesses[0].memory_id, new_kitchen);
+        assert_ne!(new_thought.thread_id, brain.thoughts.read()[&thought].thread_id);
+
+        let engine = brain.get_rl_engine().unwrap();
+        assert!(engine.has_policy("grasping"));
+        assert!(engine.has_policy(&report.id_map["grasping"]));
+    }
+
+    #[test]
+    fn test_skip_and_fail_conflicts() {
+        let (brain, graph, _, _, _) = populated();
+        let bundle = BrainBundle::export(&brain, Some(&graph), None).unwrap();
+
+        let report = bundle.import_into(&brain, Some(&graph), &ImportOptions::default()).unwrap();
+        assert!(report.imported.is_empty());
+        assert_eq!(report.skipped, 8);
+
+        // Only one item is new, but Fail rejects the whole bundle
+        brain.memories.write().clear();
+        let options = ImportOptions { conflict: ConflictPolicy::Fail, ..ImportOptions::default() };
+        assert!(bundle.import_into(&brain, Some(&graph), &options).is_err());
+        assert!(brain.memories.read().is_empty());
+    }
+
+    #[test]
+    fn test_rejects_unknown_versions() {
+        let mut bundle = BrainBundle::export(&CognitiveBrain::new(), None, None).unwrap();
+        bundle.version = BRAIN_BUNDLE_VERSION + 1;
+        assert!(BrainBundle::from_json(&bundle.to_json().unwrap()).is_err());
+        assert!(BrainBundle::from_json(r#"{"format": "other", "version": 1, "exported_at": 0}"#).is_err());
+    }
+}
@@ -1,12 +1,19 @@
 // Comprehensive Bug Detection and Edge Case Prevention
 // Systematic detection of bugs, race conditions, memory safety issues, etc.
 
-use narayana_core::{Error, Result};
-use std::time::SystemTime;
+use narayana_core::{Error, Result, schema::Schema, types::TableId};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use parking_lot::RwLock;
 use std::collections::HashMap;
-use tracing::{warn, error};
+use tracing::{debug, info, warn, error};
+
+use crate::column_store::ColumnStore;
+use crate::database_manager::DatabaseManager;
+use crate::full_text::FullTextIndexManager;
+use crate::native_events::NativeEventsSystem;
 
 /// Bug detector - finds potential bugs and edge cases
 pub struct BugDetector {
@@ -23,7 +30,7 @@ pub struct BugReport {
     pub suggested_fix: String,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum BugSeverity {
     Critical,  // Security vulnerability or data corruption
     High,      // Potential crash or incorrect behavior
@@ -246,6 +253,422 @@ impl BugDetector {
     }
 }
 
+/// Runtime invariant checked against live data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Invariant {
+    /// All columns of a table hold the same number of rows, and full-text
+    /// indexes have seen as many rows as the table holds
+    RowCounts,
+    /// Event ids within a stream strictly increase
+    MonotonicEventIds,
+    /// Block metadata agrees with the schema: column types, contiguous row
+    /// ranges, and nulls only in nullable columns
+    SchemaConsistency,
+}
+
+impl Invariant {
+    pub const ALL: [Invariant; 3] = [Invariant::RowCounts, Invariant::MonotonicEventIds, Invariant::SchemaConsistency];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Invariant::RowCounts => "row_counts",
+            Invariant::MonotonicEventIds => "monotonic_event_ids",
+            Invariant::SchemaConsistency => "schema_consistency",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|i| i.name() == name)
+    }
+}
+
+/// A broken invariant, as persisted and reported
+///
+/// The same finding on later passes bumps `occurrences` and `last_seen`
+/// instead of adding a report.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvariantViolation {
+    pub id: String,
+    pub invariant: Invariant,
+    pub severity: BugSeverity,
+    /// What it was found on ("table default.users", "stream orders")
+    pub subject: String,
+    pub description: String,
+    /// Unix seconds
+    pub first_seen: u64,
+    pub last_seen: u64,
+    pub occurrences: u64,
+}
+
+/// Outcome of one checking pass
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InvariantCheckReport {
+    /// Unix seconds
+    pub started_at: u64,
+    pub duration_ms: u64,
+    /// Subjects (a table or stream, per invariant) checked
+    pub checked: usize,
+    /// Subjects left out by sampling
+    pub skipped: usize,
+    /// Violations found in this pass
+    pub violations: Vec<InvariantViolation>,
+}
+
+/// Invariant checker configuration
+#[derive(Debug, Clone)]
+pub struct InvariantCheckerConfig {
+    pub invariants: Vec<Invariant>,
+    /// Chance (0-1) that a subject is checked on a background pass
+    pub sample_rate: f64,
+    pub interval: Duration,
+    /// JSON file the violations are kept in; None keeps them in memory only
+    pub report_path: Option<PathBuf>,
+    /// Violations kept; the least recently seen go first
+    pub max_violations: usize,
+}
+
+impl Default for InvariantCheckerConfig {
+    fn default() -> Self {
+        Self {
+            invariants: Invariant::ALL.to_vec(),
+            sample_rate: 0.1,
+            interval: Duration::from_secs(600),
+            report_path: None,
+            max_violations: 1_000,
+        }
+    }
+}
+
+/// (invariant, severity, subject, description)
+type Finding = (Invariant, BugSeverity, String, String);
+
+/// Checks runtime invariants over tables and event streams
+///
+/// Each pass visits every table (and stream) once per enabled invariant,
+/// checking it with probability `sample_rate`, so a background pass costs a
+/// fraction of a full scan while every subject is still covered over time.
+pub struct InvariantChecker {
+    config: InvariantCheckerConfig,
+    storage: Arc<dyn ColumnStore>,
+    db_manager: Arc<DatabaseManager>,
+    full_text: Option<Arc<FullTextIndexManager>>,
+    native_events: Option<Arc<NativeEventsSystem>>,
+    violations: RwLock<Vec<InvariantViolation>>,
+    last_report: RwLock<Option<InvariantCheckReport>>,
+}
+
+impl InvariantChecker {
+    /// Create a checker, loading the violations persisted at `report_path`
+    pub fn new(config: InvariantCheckerConfig, storage: Arc<dyn ColumnStore>, db_manager: Arc<DatabaseManager>) -> Self {
+        let violations = match &config.report_path {
+            Some(path) => load_violations(path).unwrap_or_else(|e| {
+                warn!("Failed to load invariant violations: {}", e);
+                Vec::new()
+            }),
+            None => Vec::new(),
+        };
+        Self {
+            config,
+            storage,
+            db_manager,
+            full_text: None,
+            native_events: None,
+            violations: RwLock::new(violations),
+            last_report: RwLock::new(None),
+        }
+    }
+
+    /// Compare full-text indexes with their tables
+    pub fn with_full_text(mut self, full_text: Arc<FullTextIndexManager>) -> Self {
+        self.full_text = Some(full_text);
+        self
+    }
+
+    /// Check the ids of event streams
+    pub fn with_native_events(mut self, native_events: Arc<NativeEventsSystem>) -> Self {
+        self.native_events = Some(native_events);
+        self
+    }
+
+    pub fn config(&self) -> &InvariantCheckerConfig {
+        &self.config
+    }
+
+    /// Known violations, most recently seen first
+    pub fn violations(&self) -> Vec<InvariantViolation> {
+        let mut violations = self.violations.read().clone();
+        violations.sort_by_key(|v| std::cmp::Reverse(v.last_seen));
+        violations
+    }
+
+    pub fn last_report(&self) -> Option<InvariantCheckReport> {
+        self.last_report.read().clone()
+    }
+
+    /// Forget all violations; returns how many there were
+    pub fn clear(&self) -> usize {
+        let cleared = std::mem::take(&mut *self.violations.write()).len();
+        self.persist();
+        cleared
+    }
+
+    /// Run one pass, checking each subject with probability `sample_rate`
+    /// (1.0 checks everything)
+    pub async fn check(&self, sample_rate: f64) -> InvariantCheckReport {
+        let started = std::time::Instant::now();
+        let mut report = InvariantCheckReport { started_at: SafeSystemTime::now_seconds(), ..Default::default() };
+        let mut findings = Vec::new();
+        let sampled = |report: &mut InvariantCheckReport| {
+            let pick = sample_rate >= 1.0 || rand::random::<f64>() < sample_rate;
+            if pick { report.checked += 1 } else { report.skipped += 1 }
+            pick
+        };
+
+        let table_checks: Vec<Invariant> = self.config.invariants
+            .iter()
+            .copied()
+            .filter(|i| *i != Invariant::MonotonicEventIds)
+            .collect();
+        if !table_checks.is_empty() {
+            for database in self.db_manager.list_databases() {
+                let Ok(tables) = self.db_manager.list_tables(database.id) else { continue };
+                for table in tables {
+                    let subject = format!("table {}.{}", database.name, table.name);
+                    for invariant in &table_checks {
+                        if !sampled(&mut report) {
+                            continue;
+                        }
+                        let outcome = match invariant {
+                            Invariant::RowCounts => self.check_row_counts(table.table_id, &subject, &mut findings).await,
+                            _ => self.check_schema_consistency(table.table_id, &subject, &mut findings).await,
+                        };
+                        // Tables dropped mid-pass or held by another backend
+                        if let Err(e) = outcome {
+                            debug!("Skipped invariant {} on {}: {}", invariant.name(), subject, e);
+                        }
+                    }
+                }
+            }
+        }
+
+        if let (true, Some(native_events)) = (self.config.invariants.contains(&Invariant::MonotonicEventIds), &self.native_events) {
+            for stream in native_events.stream_names() {
+                if !sampled(&mut report) {
+                    continue;
+                }
+                if let Some((previous, next)) = native_events.find_id_regression(&stream) {
+                    findings.push((
+                        Invariant::MonotonicEventIds,
+                        BugSeverity::High,
+                        format!("stream {}", stream.0),
+                        format!("event id {} follows event id {}", next.0, previous.0),
+                    ));
+                }
+            }
+        }
+
+        report.violations = self.record(findings, report.started_at);
+        report.duration_ms = started.elapsed().as_millis() as u64;
+        if !report.violations.is_empty() {
+            warn!("Invariant check found {} violation(s)", report.violations.len());
+        }
+        *self.last_report.write() = Some(report.clone());
+        report
+    }
+
+    /// Run a sampled pass every `interval`
+    pub fn start(self: &Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let checker = Arc::downgrade(self);
+        let interval = self.config.interval;
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let Some(checker) = checker.upgrade() else { break };
+                let sample_rate = checker.config.sample_rate;
+                checker.check(sample_rate).await;
+            }
+        })
+    }
+
+    async fn check_row_counts(&self, table_id: TableId, subject: &str, findings: &mut Vec<Finding>) -> Result<()> {
+        let schema = self.storage.get_schema(table_id).await?;
+        let indexed_before = self.full_text.as_ref().and_then(|ft| ft.indexed_rows(table_id));
+        let counts = self.row_counts(table_id, &schema).await?;
+        let indexed = self.full_text.as_ref().and_then(|ft| ft.indexed_rows(table_id));
+        let Some((first, rows)) = counts.first().map(|(name, rows)| (name.clone(), *rows)) else {
+            return Ok(());
+        };
+
+        let consistent = counts.iter().all(|(_, n)| *n == rows)
+            && indexed.is_none_or(|n| n == rows as u64);
+        // A write landing between reads looks like a mismatch; only report what a second read agrees on
+        if consistent || indexed != indexed_before || self.row_counts(table_id, &schema).await? != counts {
+            return Ok(());
+        }
+        for (name, n) in counts.iter().filter(|(_, n)| *n != rows) {
+            findings.push((
+                Invariant::RowCounts,
+                BugSeverity::Critical,
+                subject.to_string(),
+                format!("column '{}' holds {} rows, column '{}' holds {}", name, n, first, rows),
+            ));
+        }
+        if let Some(n) = indexed.filter(|n| *n != rows as u64) {
+            findings.push((
+                Invariant::RowCounts,
+                BugSeverity::High,
+                subject.to_string(),
+                format!("full-text indexes have seen {} rows, the table holds {}", n, rows),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Rows per column, from block metadata or (for stores without blocks) the column itself
+    async fn row_counts(&self, table_id: TableId, schema: &Schema) -> Result<Vec<(String, usize)>> {
+        let mut counts = Vec::with_capacity(schema.fields.len());
+        for (column_id, field) in schema.fields.iter().enumerate() {
+            let column_id = column_id as u32;
+            let blocks = self.storage.get_block_metadata(table_id, column_id).await?;
+            let rows = if blocks.is_empty() {
+                self.storage.read_columns(table_id, vec![column_id], 0, usize::MAX).await?
+                    .first()
+                    .map(|c| c.len())
+                    .unwrap_or(0)
+            } else {
+                blocks.iter().map(|b| b.row_count).sum()
+            };
+            counts.push((field.name.clone(), rows));
+        }
+        Ok(counts)
+    }
+
+    async fn check_schema_consistency(&self, table_id: TableId, subject: &str, findings: &mut Vec<Finding>) -> Result<()> {
+        let schema = self.storage.get_schema(table_id).await?;
+        let mut found = |description: String| {
+            findings.push((Invariant::SchemaConsistency, BugSeverity::High, subject.to_string(), description));
+        };
+        for (column_id, field) in schema.fields.iter().enumerate() {
+            let mut blocks = self.storage.get_block_metadata(table_id, column_id as u32).await?;
+            blocks.sort_by_key(|b| b.row_start);
+            let mut next_row = 0;
+            for block in &blocks {
+                if block.data_type != field.data_type {
+                    found(format!(
+                        "block {} of column '{}' holds {:?}, the schema says {:?}",
+                        block.block_id, field.name, block.data_type, field.data_type
+                    ));
+                }
+                if block.row_start != next_row {
+                    found(format!(
+                        "column '{}' has a {} at row {} (block {} starts at row {})",
+                        field.name,
+                        if block.row_start > next_row { "gap" } else { "overlap" },
+                        next_row, block.block_id, block.row_start
+                    ));
+                }
+                if block.null_count > block.row_count || (!field.nullable && block.null_count > 0) {
+                    found(format!(
+                        "block {} of column '{}' counts {} nulls in {} rows (nullable: {})",
+                        block.block_id, field.name, block.null_count, block.row_count, field.nullable
+                    ));
+                }
+                next_row = block.row_start + block.row_count;
+            }
+        }
+        let extra = self.storage.get_block_metadata(table_id, schema.fields.len() as u32).await?;
+        if !extra.is_empty() {
+            found(format!(
+                "{} block(s) stored for column {}, the schema has {} fields",
+                extra.len(), schema.fields.len(), schema.fields.len()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Merge findings into the known violations; returns this pass's
+    fn record(&self, findings: Vec<Finding>, now: u64) -> Vec<InvariantViolation> {
+        if findings.is_empty() {
+            return Vec::new();
+        }
+        let mut found = Vec::with_capacity(findings.len());
+        {
+            let mut violations = self.violations.write();
+            for (invariant, severity, subject, description) in findings {
+                let existing = violations.iter_mut().find(|v| {
+                    v.invariant == invariant && v.subject == subject && v.description == description
+                });
+                let violation = match existing {
+                    Some(violation) => {
+                        violation.last_seen = now;
+                        violation.occurrences += 1;
+                        violation.clone()
+                    }
+                    None => {
+                        let violation = InvariantViolation {
+                            id: uuid::Uuid::new_v4().to_string(),
+                            invariant,
+                            severity,
+                            subject,
+                            description,
+                            first_seen: now,
+                            last_seen: now,
+                            occurrences: 1,
+                        };
+                        error!("Invariant {} violated on {}: {}", invariant.name(), violation.subject, violation.description);
+                        violations.push(violation.clone());
+                        violation
+                    }
+                };
+                found.push(violation);
+            }
+            if violations.len() > self.config.max_violations {
+                violations.sort_by_key(|v| std::cmp::Reverse(v.last_seen));
+                violations.truncate(self.config.max_violations);
+            }
+        }
+        self.persist();
+        found
+    }
+
+    fn persist(&self) {
+        let Some(path) = &self.config.report_path else { return };
+        let violations = self.violations.read().clone();
+        if let Err(e) = save_violations(path, &violations) {
+            warn!("Failed to persist invariant violations: {}", e);
+        }
+    }
+}
+
+fn load_violations(path: &Path) -> Result<Vec<InvariantViolation>> {
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(Error::Storage(format!("Failed to read {:?}: {}", path, e))),
+    };
+    let violations: Vec<InvariantViolation> = serde_json::from_slice(&bytes)
+        .map_err(|e| Error::Deserialization(format!("Corrupted violation report {:?}: {}", path, e)))?;
+    info!("Loaded {} invariant violation(s) from {:?}", violations.len(), path);
+    Ok(violations)
+}
+
+/// Write the report through a temp file so a crash can't leave it half written
+fn save_violations(path: &Path, violations: &[InvariantViolation]) -> Result<()> {
+    let json = serde_json::to_vec_pretty(violations)
+        .map_err(|e| Error::Serialization(e.to_string()))?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| Error::Storage(format!("Failed to create {:?}: {}", parent, e)))?;
+    }
+    let temp_path = path.with_extension("tmp");
+    std::fs::write(&temp_path, json)
+        .and_then(|_| std::fs::rename(&temp_path, path))
+        .map_err(|e| Error::Storage(format!("Failed to write {:?}: {}", path, e)))
+}
+
 /// Safe math utilities to prevent overflow/underflow
 pub struct SafeMath;
 
@@ -426,3 +849,191 @@ impl EdgeCaseValidator {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::BlockMetadata;
+    use crate::column_store::InMemoryColumnStore;
+    use crate::native_events::{Event, EventId, EventsConfig, StreamName};
+    use narayana_core::column::Column;
+    use narayana_core::schema::{DataType, Field};
+    use narayana_core::types::CompressionType;
+
+    fn schema() -> Schema {
+        let field = |name: &str, data_type| Field { name: name.to_string(), data_type, nullable: false, default_value: None };
+        Schema::new(vec![field("id", DataType::Int64), field("name", DataType::String)])
+    }
+
+    async fn table(storage: &dyn ColumnStore, db_manager: &DatabaseManager) -> TableId {
+        let database = db_manager.create_database("inv".to_string()).unwrap();
+        let table_id = db_manager.create_table(database, "users".to_string(), schema()).unwrap();
+        storage.create_table(table_id, schema()).await.unwrap();
+        table_id
+    }
+
+    fn config(report_path: Option<PathBuf>) -> InvariantCheckerConfig {
+        InvariantCheckerConfig { report_path, ..Default::default() }
+    }
+
+    /// In-memory store that reports the given blocks for every column
+    struct BlockStore {
+        inner: InMemoryColumnStore,
+        blocks: Vec<BlockMetadata>,
+    }
+
+    #[async_trait::async_trait]
+    impl ColumnStore for BlockStore {
+        async fn create_table(&self, table_id: TableId, schema: Schema) -> Result<()> {
+            self.inner.create_table(table_id, schema).await
+        }
+        async fn write_columns(&self, table_id: TableId, columns: Vec<Column>) -> Result<()> {
+            self.inner.write_columns(table_id, columns).await
+        }
+        async fn read_columns(&self, table_id: TableId, column_ids: Vec<u32>, row_start: usize, row_count: usize) -> Result<Vec<Column>> {
+            self.inner.read_columns(table_id, column_ids, row_start, row_count).await
+        }
+        async fn get_schema(&self, table_id: TableId) -> Result<Schema> {
+            self.inner.get_schema(table_id).await
+        }
+        async fn get_block_metadata(&self, _table_id: TableId, column_id: u32) -> Result<Vec<BlockMetadata>> {
+            Ok(self.blocks.iter().filter(|b| b.column_id == column_id).cloned().collect())
+        }
+        async fn delete_table(&self, table_id: TableId) -> Result<()> {
+            self.inner.delete_table(table_id).await
+        }
+    }
+
+    fn block(column_id: u32, block_id: u64, data_type: DataType, row_start: usize, null_count: usize) -> BlockMetadata {
+        BlockMetadata {
+            block_id,
+            column_id,
+            row_start,
+            row_count: 10,
+            data_type,
+            compression: CompressionType::None,
+            uncompressed_size: 0,
+            compressed_size: 0,
+            min_value: None,
+            max_value: None,
+            null_count,
+            distinct_count: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_row_count_mismatch_is_reported_and_persisted() {
+        let path = std::env::temp_dir().join(format!("narayana_invariants_{}.json", uuid::Uuid::new_v4()));
+        let storage: Arc<dyn ColumnStore> = Arc::new(InMemoryColumnStore::new());
+        let db_manager = Arc::new(DatabaseManager::new());
+        let table_id = table(storage.as_ref(), &db_manager).await;
+        let checker = InvariantChecker::new(config(Some(path.clone())), storage.clone(), db_manager.clone());
+
+        storage.write_columns(table_id, vec![Column::Int64(vec![1, 2]), Column::String(vec!["a".to_string(), "b".to_string()])]).await.unwrap();
+        assert!(checker.check(1.0).await.violations.is_empty());
+
+        storage.write_columns(table_id, vec![Column::Int64(vec![3])]).await.unwrap();
+        let report = checker.check(1.0).await;
+        assert_eq!(report.checked, 2);
+        assert_eq!(report.violations.len(), 1);
+        assert_eq!(report.violations[0].invariant, Invariant::RowCounts);
+        assert_eq!(report.violations[0].subject, "table inv.users");
+        assert!(report.violations[0].description.contains("holds 2 rows"));
+
+        // Found again: same report, one more occurrence
+        checker.check(1.0).await;
+        let reloaded = InvariantChecker::new(config(Some(path.clone())), storage.clone(), db_manager);
+        let violations = reloaded.violations();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].occurrences, 2);
+
+        assert_eq!(reloaded.clear(), 1);
+        assert!(load_violations(&path).unwrap().is_empty());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_full_text_index_behind_table_is_reported() {
+        let storage: Arc<dyn ColumnStore> = Arc::new(InMemoryColumnStore::new());
+        let db_manager = Arc::new(DatabaseManager::new());
+        let table_id = table(storage.as_ref(), &db_manager).await;
+        let full_text = Arc::new(FullTextIndexManager::new());
+        full_text.create_index(table_id, &schema(), "name", crate::full_text::Language::English, &[]).unwrap();
+        let checker = InvariantChecker::new(config(None), storage.clone(), db_manager).with_full_text(full_text.clone());
+
+        let columns = vec![Column::Int64(vec![1]), Column::String(vec!["robot".to_string()])];
+        storage.write_columns(table_id, columns.clone()).await.unwrap();
+        full_text.index_insert(table_id, &schema(), &columns).unwrap();
+        assert!(checker.check(1.0).await.violations.is_empty());
+
+        // Written without going through the index
+        storage.write_columns(table_id, columns).await.unwrap();
+        let report = checker.check(1.0).await;
+        assert_eq!(report.violations.len(), 1);
+        assert!(report.violations[0].description.contains("have seen 1 rows, the table holds 2"));
+    }
+
+    #[tokio::test]
+    async fn test_blocks_disagreeing_with_schema_are_reported() {
+        let storage: Arc<dyn ColumnStore> = Arc::new(BlockStore {
+            inner: InMemoryColumnStore::new(),
+            blocks: vec![
+                block(0, 1, DataType::Int64, 0, 0),
+                block(0, 2, DataType::Int64, 10, 0),
+                block(1, 3, DataType::Int64, 0, 0),
+                block(1, 4, DataType::String, 15, 2),
+                block(2, 5, DataType::Int64, 0, 0),
+            ],
+        });
+        let db_manager = Arc::new(DatabaseManager::new());
+        table(storage.as_ref(), &db_manager).await;
+        let config = InvariantCheckerConfig { invariants: vec![Invariant::SchemaConsistency], ..config(None) };
+        let checker = InvariantChecker::new(config, storage, db_manager);
+
+        let report = checker.check(1.0).await;
+        let mut descriptions: Vec<String> = report.violations.iter().map(|v| v.description.clone()).collect();
+        descriptions.sort();
+        assert_eq!(descriptions, vec![
+            "1 block(s) stored for column 2, the schema has 2 fields".to_string(),
+            "block 3 of column 'name' holds Int64, the schema says String".to_string(),
+            "block 4 of column 'name' counts 2 nulls in 10 rows (nullable: false)".to_string(),
+            "column 'name' has a gap at row 10 (block 4 starts at row 15)".to_string(),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_event_id_regression_and_sampling() {
+        let storage: Arc<dyn ColumnStore> = Arc::new(InMemoryColumnStore::new());
+        let native_events = Arc::new(NativeEventsSystem::new(EventsConfig::default()));
+        let config = InvariantCheckerConfig { invariants: vec![Invariant::MonotonicEventIds], ..config(None) };
+        let checker = InvariantChecker::new(config, storage, Arc::new(DatabaseManager::new()))
+            .with_native_events(native_events.clone());
+
+        for id in [0, 0, 2] {
+            let event = Event {
+                id: EventId(id),
+                stream: StreamName("orders".to_string()),
+                topic: None,
+                queue: None,
+                event_type: "created".to_string(),
+                payload: serde_json::json!({}),
+                headers: HashMap::new(),
+                timestamp: 0,
+                correlation_id: None,
+                causation_id: None,
+                partition_key: None,
+                ttl: None,
+                priority: 0,
+            };
+            native_events.publish_event(event).await.unwrap();
+        }
+
+        let report = checker.check(0.0).await;
+        assert_eq!((report.checked, report.skipped), (0, 1));
+        let report = checker.check(1.0).await;
+        assert_eq!(report.violations.len(), 1);
+        assert_eq!(report.violations[0].subject, "stream orders");
+        assert_eq!(report.violations[0].description, "event id 2 follows event id 2");
+        assert_eq!(Invariant::parse("monotonic_event_ids"), Some(Invariant::MonotonicEventIds));
+    }
+}
@@ -0,0 +1,244 @@
+// Camera Calibration Store - intrinsics and extrinsics per camera
+// Calibrations outlive the process that computed them, so vision pipelines
+// can undistort frames from the first one after a restart.
+
+use narayana_core::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use parking_lot::RwLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+/// World-to-camera transform, x_cam = R * x_world + t
+/// (Rodrigues rotation vector, translation in meters)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CameraExtrinsics {
+    pub rotation: [f64; 3],
+    pub translation: [f64; 3],
+}
+
+/// Calibration of one camera
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CameraCalibration {
+    pub camera_id: String,
+    /// Image size the intrinsics were computed at
+    pub image_size: (u32, u32),
+    /// Row-major 3x3 camera matrix
+    pub camera_matrix: [f64; 9],
+    /// Distortion coefficients (k1, k2, p1, p2[, k3, ...])
+    pub distortion: Vec<f64>,
+    pub extrinsics: Option<CameraExtrinsics>,
+    /// RMS reprojection error of the calibration, in pixels
+    pub reprojection_error: f64,
+    pub calibrated_at: u64,
+}
+
+impl CameraCalibration {
+    /// Check the calibration is usable for undistortion
+    pub fn validate(&self) -> Result<()> {
+        validate_camera_id(&self.camera_id)?;
+        if self.image_size.0 == 0 || self.image_size.1 == 0 {
+            return Err(Error::Storage("Calibration image size must be non-zero".to_string()));
+        }
+        if self.camera_matrix.iter().any(|v| !v.is_finite()) || self.camera_matrix[0] <= 0.0 || self.camera_matrix[4] <= 0.0 {
+            return Err(Error::Storage("Calibration camera matrix must be finite with positive focal lengths".to_string()));
+        }
+        if !matches!(self.distortion.len(), 4 | 5 | 8 | 12 | 14) || self.distortion.iter().any(|v| !v.is_finite()) {
+            return Err(Error::Storage("Calibration needs 4, 5, 8, 12 or 14 finite distortion coefficients".to_string()));
+        }
+        if let Some(extrinsics) = &self.extrinsics {
+            if extrinsics.rotation.iter().chain(&extrinsics.translation).any(|v| !v.is_finite()) {
+                return Err(Error::Storage("Calibration extrinsics must be finite".to_string()));
+            }
+        }
+        if !(self.reprojection_error >= 0.0 && self.reprojection_error.is_finite()) {
+            return Err(Error::Storage("Calibration reprojection error must be finite and non-negative".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Latest calibration per camera.
+/// With a root directory, each camera's calibration is kept at
+/// `<root>/<camera_id>.json`; without one they are kept in memory.
+pub struct CameraCalibrationStore {
+    root: Option<std::path::PathBuf>,
+    calibrations: Arc<RwLock<HashMap<String, CameraCalibration>>>,
+}
+
+impl CameraCalibrationStore {
+    pub fn new(root: Option<std::path::PathBuf>) -> Result<Self> {
+        if let Some(root) = &root {
+            std::fs::create_dir_all(root)
+                .map_err(|e| Error::Storage(format!("Failed to create calibration directory: {}", e)))?;
+        }
+        Ok(Self {
+            root,
+            calibrations: Arc::new(RwLock::new(HashMap::new())),
+        })
+    }
+
+    /// Load calibrations written by a previous run
+    pub fn load_persisted(&self) -> Result<usize> {
+        let Some(root) = &self.root else { return Ok(0) };
+        let entries = std::fs::read_dir(root)
+            .map_err(|e| Error::Storage(format!("Failed to read calibration directory: {}", e)))?;
+        let mut loaded = 0;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let parsed = std::fs::read(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|bytes| serde_json::from_slice::<CameraCalibration>(&bytes).map_err(|e| e.to_string()))
+                .and_then(|calibration| calibration.validate().map(|_| calibration).map_err(|e| e.to_string()));
+            match parsed {
+                Ok(calibration) => {
+                    self.calibrations.write().insert(calibration.camera_id.clone(), calibration);
+                    loaded += 1;
+                }
+                Err(e) => warn!("Skipping corrupt camera calibration {:?}: {}", path, e),
+            }
+        }
+        info!("Loaded {} camera calibrations", loaded);
+        Ok(loaded)
+    }
+
+    /// Store a calibration, replacing the camera's previous one
+    pub fn save(&self, mut calibration: CameraCalibration) -> Result<CameraCalibration> {
+        calibration.validate()?;
+        if calibration.calibrated_at == 0 {
+            calibration.calibrated_at = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        }
+        if let Some(path) = self.calibration_path(&calibration.camera_id) {
+            let bytes = serde_json::to_vec_pretty(&calibration)
+                .map_err(|e| Error::Serialization(format!("Failed to serialize camera calibration: {}", e)))?;
+            write_atomic(&path, &bytes)?;
+        }
+        info!(
+            "Saved calibration for camera {} (reprojection error {:.3}px)",
+            calibration.camera_id, calibration.reprojection_error
+        );
+        self.calibrations.write().insert(calibration.camera_id.clone(), calibration.clone());
+        Ok(calibration)
+    }
+
+    /// Update the pose of an already calibrated camera
+    pub fn set_extrinsics(&self, camera_id: &str, extrinsics: CameraExtrinsics) -> Result<CameraCalibration> {
+        let mut calibration = self.get(camera_id)
+            .ok_or_else(|| Error::Storage(format!("Camera {} has no calibration", camera_id)))?;
+        calibration.extrinsics = Some(extrinsics);
+        self.save(calibration)
+    }
+
+    pub fn get(&self, camera_id: &str) -> Option<CameraCalibration> {
+        self.calibrations.read().get(camera_id).cloned()
+    }
+
+    pub fn list_cameras(&self) -> Vec<String> {
+        let mut cameras: Vec<String> = self.calibrations.read().keys().cloned().collect();
+        cameras.sort();
+        cameras
+    }
+
+    pub fn remove(&self, camera_id: &str) -> Result<bool> {
+        let removed = self.calibrations.write().remove(camera_id).is_some();
+        if let Some(path) = self.calibration_path(camera_id) {
+            match std::fs::remove_file(&path) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(Error::Storage(format!("Failed to remove {:?}: {}", path, e))),
+            }
+        }
+        Ok(removed)
+    }
+
+    fn calibration_path(&self, camera_id: &str) -> Option<std::path::PathBuf> {
+        self.root.as_ref().map(|root| root.join(format!("{}.json", camera_id)))
+    }
+}
+
+/// Camera ids become file names, so keep them to a safe alphabet
+fn validate_camera_id(camera_id: &str) -> Result<()> {
+    if camera_id.is_empty() || camera_id.len() > 128 {
+        return Err(Error::Storage("Camera id must be 1-128 characters".to_string()));
+    }
+    if !camera_id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.')) || camera_id.starts_with('.') {
+        return Err(Error::Storage(format!("Invalid camera id: {}", camera_id)));
+    }
+    Ok(())
+}
+
+fn write_atomic(path: &std::path::Path, bytes: &[u8]) -> Result<()> {
+    let temp_path = path.with_extension("tmp");
+    std::fs::write(&temp_path, bytes)
+        .map_err(|e| Error::Storage(format!("Failed to write {:?}: {}", path, e)))?;
+    std::fs::rename(&temp_path, path)
+        .map_err(|e| {
+            let _ = std::fs::remove_file(&temp_path);
+            Error::Storage(format!("Failed to write {:?}: {}", path, e))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn calibration(camera_id: &str) -> CameraCalibration {
+        CameraCalibration {
+            camera_id: camera_id.to_string(),
+            image_size: (640, 480),
+            camera_matrix: [500.0, 0.0, 320.0, 0.0, 500.0, 240.0, 0.0, 0.0, 1.0],
+            distortion: vec![-0.2, 0.05, 0.0, 0.0, 0.0],
+            extrinsics: None,
+            reprojection_error: 0.3,
+            calibrated_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_calibration_persists() {
+        let dir = std::env::temp_dir().join(format!("narayana_calibration_{}", Uuid::new_v4()));
+        let store = CameraCalibrationStore::new(Some(dir.clone())).unwrap();
+        let saved = store.save(calibration("camera_0")).unwrap();
+        assert!(saved.calibrated_at > 0);
+        store.set_extrinsics("camera_0", CameraExtrinsics {
+            rotation: [0.0, 0.1, 0.0],
+            translation: [0.0, 0.0, 1.5],
+        }).unwrap();
+
+        let reloaded = CameraCalibrationStore::new(Some(dir.clone())).unwrap();
+        assert_eq!(reloaded.load_persisted().unwrap(), 1);
+        let loaded = reloaded.get("camera_0").unwrap();
+        assert_eq!(loaded.camera_matrix, saved.camera_matrix);
+        assert_eq!(loaded.extrinsics.unwrap().translation, [0.0, 0.0, 1.5]);
+
+        assert!(reloaded.remove("camera_0").unwrap());
+        assert!(reloaded.get("camera_0").is_none());
+        assert_eq!(CameraCalibrationStore::new(Some(dir.clone())).unwrap().load_persisted().unwrap(), 0);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_invalid_calibrations_rejected() {
+        let store = CameraCalibrationStore::new(None).unwrap();
+        assert!(store.save(calibration("../camera")).is_err());
+
+        let mut bad = calibration("camera_1");
+        bad.distortion = vec![0.0; 3];
+        assert!(store.save(bad).is_err());
+
+        let mut bad = calibration("camera_1");
+        bad.camera_matrix[0] = f64::NAN;
+        assert!(store.save(bad).is_err());
+
+        assert!(store.set_extrinsics("camera_2", CameraExtrinsics {
+            rotation: [0.0; 3],
+            translation: [0.0; 3],
+        }).is_err());
+        assert!(store.list_cameras().is_empty());
+    }
+}
@@ -0,0 +1,107 @@
+// Change data capture feed
+// Broadcasts committed table writes to in-process consumers (detectors, indexes, sinks)
+
+use narayana_core::{column::Column, schema::Schema, types::TableId};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::broadcast;
+
+/// Kind of change captured
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChangeOperation {
+    Insert,
+    DropTable,
+}
+
+/// One committed change. Inserts carry the written columns; row ids are
+/// `first_row_id..first_row_id + row_count` in write order.
+#[derive(Debug, Clone)]
+pub struct ChangeEvent {
+    pub sequence: u64,
+    pub table_id: TableId,
+    pub operation: ChangeOperation,
+    pub first_row_id: u64,
+    pub schema: Arc<Schema>,
+    pub columns: Arc<Vec<Column>>,
+    pub timestamp: u64,
+}
+
+impl ChangeEvent {
+    pub fn row_count(&self) -> usize {
+        self.columns.first().map(|c| c.len()).unwrap_or(0)
+    }
+}
+
+/// In-process CDC stream. Slow consumers lag rather than block writers.
+pub struct ChangeFeed {
+    sender: broadcast::Sender<Arc<ChangeEvent>>,
+    sequence: AtomicU64,
+    row_counters: RwLock<HashMap<TableId, u64>>,
+}
+
+impl ChangeFeed {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self {
+            sender,
+            sequence: AtomicU64::new(0),
+            row_counters: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<ChangeEvent>> {
+        self.sender.subscribe()
+    }
+
+    /// Seed the row counter of a table that already holds rows
+    pub fn set_row_count(&self, table_id: TableId, rows: u64) {
+        self.row_counters.write().insert(table_id, rows);
+    }
+
+    /// Publish a committed insert (call after the write succeeded)
+    pub fn publish_insert(&self, table_id: TableId, schema: &Schema, columns: &[Column]) -> u64 {
+        let row_count = columns.first().map(|c| c.len()).unwrap_or(0) as u64;
+        let first_row_id = {
+            let mut counters = self.row_counters.write();
+            let counter = counters.entry(table_id).or_insert(0);
+            let first = *counter;
+            *counter += row_count;
+            first
+        };
+        self.publish(table_id, ChangeOperation::Insert, first_row_id, schema, columns.to_vec())
+    }
+
+    pub fn publish_drop(&self, table_id: TableId, schema: &Schema) -> u64 {
+        self.row_counters.write().remove(&table_id);
+        self.publish(table_id, ChangeOperation::DropTable, 0, schema, Vec::new())
+    }
+
+    fn publish(&self, table_id: TableId, operation: ChangeOperation, first_row_id: u64, schema: &Schema, columns: Vec<Column>) -> u64 {
+        let sequence = self.sequence.fetch_add(1, Ordering::SeqCst) + 1;
+        let event = ChangeEvent {
+            sequence,
+            table_id,
+            operation,
+            first_row_id,
+            schema: Arc::new(schema.clone()),
+            columns: Arc::new(columns),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        };
+        // No receivers is fine - nothing is consuming changes yet
+        let _ = self.sender.send(Arc::new(event));
+        sequence
+    }
+}
+
+impl Default for ChangeFeed {
+    fn default() -> Self {
+        Self::new(1024)
+    }
+}
@@ -262,8 +262,8 @@ impl CognitiveBrain {
         }
     }
     
-    /// Cleanup old/completed thoughts to prevent memory leaks
-    pub fn cleanup_thoughts(&self) {
+    /// Cleanup old/completed thoughts to prevent memory leaks; returns how many were removed
+    pub fn cleanup_thoughts(&self) -> usize {
         let mut thoughts = self.thoughts.write();
         let mut to_remove = Vec::new();
         let now = SystemTime::now()
@@ -279,9 +279,11 @@ impl CognitiveBrain {
             }
         }
         
+        let removed = to_remove.len();
         for id in to_remove {
             thoughts.remove(&id);
         }
+        removed
     }
     
     /// Set RL engine for learning from experiences
@@ -1371,6 +1373,50 @@ impl CognitiveBrain {
     pub fn get_working_memory(&self) -> Vec<CognitiveState> {
         self.working_memory.read().clone()
     }
+
+    /// Insert a memory as-is, replacing and reindexing any with the same id (bundle import)
+    pub(crate) fn restore_memory(&self, memory: Memory) {
+        let replaced = self.memories.write().insert(memory.id.clone(), memory.clone()).is_some();
+
+        let mut guard = self.memory_index.write();
+        let index = &mut *guard;
+        if replaced {
+            for ids in index.by_type.values_mut().chain(index.by_tag.values_mut()) {
+                ids.retain(|id| *id != memory.id);
+            }
+            index.temporal_index.retain(|(_, id)| *id != memory.id);
+        }
+        index.by_type
+            .entry(memory.memory_type.clone())
+            .or_default()
+            .push(memory.id.clone());
+        for tag in &memory.tags {
+            index.by_tag
+                .entry(tag.clone())
+                .or_default()
+                .push(memory.id.clone());
+        }
+        let associations = index.by_association.entry(memory.id.clone()).or_default();
+        *associations = memory.associations.clone();
+        index.temporal_index.push((memory.created_at, memory.id));
+    }
+
+    /// Insert a thought as-is, joining or creating its thread (bundle import)
+    pub(crate) fn restore_thought(&self, thought: Thought) {
+        {
+            let mut threads = self.thought_threads.write();
+            let thread = threads.entry(thought.thread_id.clone()).or_insert_with(|| ThoughtThread {
+                id: thought.thread_id.clone(),
+                thoughts: Vec::new(),
+                state: thought.state.clone(),
+                priority: thought.priority,
+            });
+            if !thread.thoughts.contains(&thought.id) {
+                thread.thoughts.push(thought.id.clone());
+            }
+        }
+        self.thoughts.write().insert(thought.id.clone(), thought);
+    }
     
     /// Track event for timeline
     fn track_event(&self, event: CognitiveEvent) {
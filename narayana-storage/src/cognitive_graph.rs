@@ -244,6 +244,49 @@ impl CognitiveGraph {
         self.concepts.read().get(concept_id).cloned()
     }
 
+    /// All concepts
+    pub fn concepts(&self) -> Vec<Concept> {
+        self.concepts.read().values().cloned().collect()
+    }
+
+    /// Get relationship by ID
+    pub fn get_relationship(&self, relationship_id: &str) -> Option<Relationship> {
+        self.relationships.read().get(relationship_id).cloned()
+    }
+
+    /// All relationships
+    pub fn relationships(&self) -> Vec<Relationship> {
+        self.relationships.read().values().cloned().collect()
+    }
+
+    /// Insert a relationship as-is (e.g. from a brain bundle), replacing any with the same ID
+    pub fn restore_relationship(&self, relationship: Relationship) -> Result<String> {
+        {
+            let concepts = self.concepts.read();
+            for concept_id in [&relationship.from_concept, &relationship.to_concept] {
+                if !concepts.contains_key(concept_id) {
+                    return Err(Error::Storage(format!("Concept {} not found", concept_id)));
+                }
+            }
+        }
+
+        let relationship_id = relationship.id.clone();
+        let previous = self.relationships.write().insert(relationship_id.clone(), relationship.clone());
+
+        let mut index = self.concept_index.write();
+        if let Some(previous) = previous {
+            for concept_id in [&previous.from_concept, &previous.to_concept] {
+                if let Some(ids) = index.get_mut(concept_id) {
+                    ids.remove(&relationship_id);
+                }
+            }
+        }
+        for concept_id in [relationship.from_concept, relationship.to_concept] {
+            index.entry(concept_id).or_default().insert(relationship_id.clone());
+        }
+        Ok(relationship_id)
+    }
+
     /// Search concepts by pattern
     pub fn search_concepts(&self, pattern: &str) -> Vec<Concept> {
         let concepts = self.concepts.read();
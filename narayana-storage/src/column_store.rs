@@ -1,11 +1,16 @@
 use async_trait::async_trait;
 use narayana_core::{Error, Result, schema::Schema, types::TableId, column::Column};
 use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use tracing::{info, warn};
 
-use crate::block::BlockMetadata;
+use crate::block::{BlockMetadata, BlockPredicate};
 use crate::writer::ColumnWriter;
 use crate::reader::ColumnReader;
 
@@ -36,26 +41,322 @@ pub trait ColumnStore: Send + Sync {
         column_id: u32,
     ) -> Result<Vec<BlockMetadata>>;
 
+    /// Row ranges of a column's blocks whose zone maps admit `predicate`, in
+    /// row order with adjacent blocks merged. None when the store keeps no
+    /// block metadata for the column, so every row has to be read
+    async fn matching_row_ranges(
+        &self,
+        table_id: TableId,
+        column_id: u32,
+        predicate: &BlockPredicate,
+    ) -> Result<Option<Vec<Range<usize>>>> {
+        let mut blocks = self.get_block_metadata(table_id, column_id).await?;
+        if blocks.is_empty() {
+            return Ok(None);
+        }
+        blocks.sort_by_key(|block| block.row_start);
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+        for block in blocks.iter().filter(|block| block.may_match(predicate)) {
+            let range = block.row_start..block.row_start + block.row_count;
+            match ranges.last_mut() {
+                Some(last) if last.end == range.start => last.end = range.end,
+                _ => ranges.push(range),
+            }
+        }
+        Ok(Some(ranges))
+    }
+
     /// Delete a table
     async fn delete_table(&self, table_id: TableId) -> Result<()>;
+
+    /// Take a table's write lock. Until the guard is dropped, other writes,
+    /// replacements and deletes of the table wait, while writes made with the
+    /// guard go ahead: hold it from reading a table until everything derived
+    /// from that read is written. Stores that don't serialize writes hand out
+    /// a guard that locks nothing.
+    async fn lock_table(&self, table_id: TableId) -> TableWriteGuard {
+        TableWriteGuard::unlocked(table_id)
+    }
+
+    /// `write_columns` for the holder of the table's write lock
+    async fn write_locked(&self, guard: &TableWriteGuard, columns: Vec<Column>) -> Result<()> {
+        self.write_columns(guard.table_id(), columns).await
+    }
+
+    /// Replace every row of the table with `columns`, keeping its schema, for
+    /// the holder of its write lock. Stores that lock writes make this atomic:
+    /// after a failure or a crash the table holds either its old rows or the
+    /// new ones. The default deletes and recreates the table.
+    async fn replace_rows(&self, guard: &TableWriteGuard, columns: Vec<Column>) -> Result<()> {
+        let table_id = guard.table_id();
+        let schema = self.get_schema(table_id).await?;
+        self.delete_table(table_id).await?;
+        self.create_table(table_id, schema).await?;
+        if columns.first().is_some_and(|c| c.len() > 0) {
+            self.write_columns(table_id, columns).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Per-table write locks of a store
+#[derive(Default)]
+pub struct TableLocks {
+    locks: parking_lot::Mutex<HashMap<TableId, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+impl TableLocks {
+    pub async fn lock(&self, table_id: TableId) -> TableWriteGuard {
+        let lock = self.locks.lock().entry(table_id).or_default().clone();
+        TableWriteGuard { table_id, _lock: Some(lock.lock_owned().await) }
+    }
+
+    /// Release a deleted table's lock, forgetting it unless another writer
+    /// holds or waits for it (they clone the lock under the map's mutex)
+    pub fn release(&self, guard: TableWriteGuard) {
+        let table_id = guard.table_id;
+        drop(guard);
+        let mut locks = self.locks.lock();
+        if locks.get(&table_id).is_some_and(|lock| Arc::strong_count(lock) == 1) {
+            locks.remove(&table_id);
+        }
+    }
+}
+
+/// A table's write lock, released when dropped (see `ColumnStore::lock_table`)
+pub struct TableWriteGuard {
+    table_id: TableId,
+    _lock: Option<tokio::sync::OwnedMutexGuard<()>>,
+}
+
+impl TableWriteGuard {
+    /// A guard for a store that doesn't lock writes
+    pub(crate) fn unlocked(table_id: TableId) -> Self {
+        Self { table_id, _lock: None }
+    }
+
+    pub fn table_id(&self) -> TableId {
+        self.table_id
+    }
+}
+
+/// A store as seen by the holder of a table's write lock, for code that takes
+/// a `&dyn ColumnStore`: its writes to that table are made with the guard
+pub struct WriteLocked<'a> {
+    store: &'a dyn ColumnStore,
+    guard: &'a TableWriteGuard,
+}
+
+impl<'a> WriteLocked<'a> {
+    pub fn new(store: &'a dyn ColumnStore, guard: &'a TableWriteGuard) -> Self {
+        Self { store, guard }
+    }
+}
+
+#[async_trait]
+impl ColumnStore for WriteLocked<'_> {
+    async fn create_table(&self, table_id: TableId, schema: Schema) -> Result<()> {
+        self.store.create_table(table_id, schema).await
+    }
+
+    async fn write_columns(&self, table_id: TableId, columns: Vec<Column>) -> Result<()> {
+        if table_id == self.guard.table_id() {
+            self.store.write_locked(self.guard, columns).await
+        } else {
+            self.store.write_columns(table_id, columns).await
+        }
+    }
+
+    async fn read_columns(
+        &self,
+        table_id: TableId,
+        column_ids: Vec<u32>,
+        row_start: usize,
+        row_count: usize,
+    ) -> Result<Vec<Column>> {
+        self.store.read_columns(table_id, column_ids, row_start, row_count).await
+    }
+
+    async fn get_schema(&self, table_id: TableId) -> Result<Schema> {
+        self.store.get_schema(table_id).await
+    }
+
+    async fn get_block_metadata(&self, table_id: TableId, column_id: u32) -> Result<Vec<BlockMetadata>> {
+        self.store.get_block_metadata(table_id, column_id).await
+    }
+
+    async fn matching_row_ranges(
+        &self,
+        table_id: TableId,
+        column_id: u32,
+        predicate: &BlockPredicate,
+    ) -> Result<Option<Vec<Range<usize>>>> {
+        self.store.matching_row_ranges(table_id, column_id, predicate).await
+    }
+
+    async fn lock_table(&self, table_id: TableId) -> TableWriteGuard {
+        if table_id == self.guard.table_id() {
+            TableWriteGuard::unlocked(table_id)
+        } else {
+            self.store.lock_table(table_id).await
+        }
+    }
+
+    async fn replace_rows(&self, guard: &TableWriteGuard, columns: Vec<Column>) -> Result<()> {
+        if guard.table_id() == self.guard.table_id() {
+            self.store.replace_rows(self.guard, columns).await
+        } else {
+            self.store.replace_rows(guard, columns).await
+        }
+    }
+
+    async fn delete_table(&self, table_id: TableId) -> Result<()> {
+        if table_id == self.guard.table_id() {
+            return Err(Error::Storage(format!("Table {} is locked for writing", table_id.0)));
+        }
+        self.store.delete_table(table_id).await
+    }
+}
+
+/// Rows stored in a table, from its first column's block metadata or (for
+/// stores without blocks) the column itself
+pub async fn stored_row_count(store: &dyn ColumnStore, table_id: TableId) -> Result<usize> {
+    let blocks = store.get_block_metadata(table_id, 0).await?;
+    if !blocks.is_empty() {
+        return Ok(blocks.iter().map(|b| b.row_count).sum());
+    }
+    Ok(store
+        .read_columns(table_id, vec![0], 0, usize::MAX)
+        .await?
+        .first()
+        .map(|c| c.len())
+        .unwrap_or(0))
 }
 
 pub struct InMemoryColumnStore {
     tables: Arc<RwLock<HashMap<TableId, TableMetadata>>>,
+    /// Bumped on every change so snapshots are only written when needed
+    generation: AtomicU64,
+    locks: TableLocks,
 }
 
+#[derive(Serialize, Deserialize)]
 struct TableMetadata {
+    #[serde(with = "schema_json")]
     schema: Schema,
     columns: HashMap<u32, Vec<Column>>,
     block_metadata: HashMap<u32, Vec<BlockMetadata>>,
 }
 
+/// Schemas go through JSON inside bincode snapshots: field defaults are
+/// `serde_json::Value`s, which bincode cannot decode on its own
+pub(crate) mod schema_json {
+    use narayana_core::schema::Schema;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(schema: &Schema, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serde_json::to_string(schema)
+            .map_err(serde::ser::Error::custom)?
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Schema, D::Error> {
+        let json = String::deserialize(deserializer)?;
+        serde_json::from_str(&json).map_err(serde::de::Error::custom)
+    }
+}
+
 impl InMemoryColumnStore {
     pub fn new() -> Self {
         Self {
             tables: Arc::new(RwLock::new(HashMap::new())),
+            generation: AtomicU64::new(0),
+            locks: TableLocks::default(),
         }
     }
+
+    /// Restore a store from a snapshot file; a missing file gives an empty store
+    pub fn restore(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let store = Self::new();
+        let bytes = match std::fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(store),
+            Err(e) => return Err(Error::Storage(format!("Failed to read snapshot {:?}: {}", path, e))),
+        };
+        let tables: HashMap<TableId, TableMetadata> = bincode::deserialize(&bytes)
+            .map_err(|e| Error::Deserialization(format!("Corrupted snapshot {:?}: {}", path, e)))?;
+        info!("Restored {} in-memory tables from {:?}", tables.len(), path);
+        *store.tables.write() = tables;
+        Ok(store)
+    }
+
+    /// Write every table to `path` (temp file, fsync, rename). Returns the
+    /// number of tables written.
+    pub fn snapshot(&self, path: impl AsRef<Path>) -> Result<usize> {
+        use std::io::Write;
+
+        let path = path.as_ref();
+        let (bytes, count) = {
+            let tables = self.tables.read();
+            let bytes = bincode::serialize(&*tables)
+                .map_err(|e| Error::Serialization(format!("Failed to serialize snapshot: {}", e)))?;
+            (bytes, tables.len())
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| Error::Storage(format!("Failed to create snapshot directory: {}", e)))?;
+        }
+        let temp_path = path.with_extension("tmp");
+        let written = std::fs::File::create(&temp_path)
+            .and_then(|mut file| {
+                file.write_all(&bytes)?;
+                file.sync_all()
+            })
+            .and_then(|_| std::fs::rename(&temp_path, path));
+        if let Err(e) = written {
+            let _ = std::fs::remove_file(&temp_path);
+            return Err(Error::Storage(format!("Failed to write snapshot {:?}: {}", path, e)));
+        }
+        Ok(count)
+    }
+
+    /// Snapshot to `path` every `interval` while there are unsaved changes
+    pub fn start_snapshots(self: &Arc<Self>, path: PathBuf, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let store = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut saved = None;
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let Some(strong) = store.upgrade() else { break };
+                let generation = strong.generation();
+                if saved == Some(generation) {
+                    continue;
+                }
+                let path = path.clone();
+                match tokio::task::spawn_blocking(move || strong.snapshot(&path)).await {
+                    Ok(Ok(_)) => saved = Some(generation),
+                    Ok(Err(e)) => warn!("In-memory snapshot failed: {}", e),
+                    Err(e) => warn!("In-memory snapshot task failed: {}", e),
+                }
+            }
+        })
+    }
+
+    /// Change counter; equal values mean no writes in between
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    pub fn table_ids(&self) -> Vec<TableId> {
+        self.tables.read().keys().copied().collect()
+    }
+
+    fn touch(&self) {
+        self.generation.fetch_add(1, Ordering::AcqRel);
+    }
 }
 
 #[async_trait]
@@ -74,12 +375,23 @@ impl ColumnStore for InMemoryColumnStore {
                 block_metadata: HashMap::new(),
             },
         );
+        self.touch();
 
         info!("Created table {}", table_id.0);
         Ok(())
     }
 
     async fn write_columns(&self, table_id: TableId, columns: Vec<Column>) -> Result<()> {
+        let guard = self.locks.lock(table_id).await;
+        self.write_locked(&guard, columns).await
+    }
+
+    async fn lock_table(&self, table_id: TableId) -> TableWriteGuard {
+        self.locks.lock(table_id).await
+    }
+
+    async fn write_locked(&self, guard: &TableWriteGuard, columns: Vec<Column>) -> Result<()> {
+        let table_id = guard.table_id();
         let mut tables = self.tables.write();
         let table = tables
             .get_mut(&table_id)
@@ -95,6 +407,7 @@ impl ColumnStore for InMemoryColumnStore {
                 table.columns.insert(column_id, vec![column]);
             }
         }
+        self.touch();
 
         Ok(())
     }
@@ -263,7 +576,9 @@ impl ColumnStore for InMemoryColumnStore {
                 
                 // Slice to requested range
                 if row_start > 0 || row_count < merged_column.len() {
-                    match merged_column.slice(row_start, row_count) {
+                    let offset = row_start.min(merged_column.len());
+                    let count = row_count.min(merged_column.len() - offset);
+                    match merged_column.slice(offset, count) {
                         Ok(sliced) => result.push(sliced),
                         Err(e) => {
                             warn!("Failed to slice column: {}", e);
@@ -305,9 +620,27 @@ impl ColumnStore for InMemoryColumnStore {
             .unwrap_or_default())
     }
 
-    async fn delete_table(&self, table_id: TableId) -> Result<()> {
+    async fn replace_rows(&self, guard: &TableWriteGuard, columns: Vec<Column>) -> Result<()> {
+        let table_id = guard.table_id();
         let mut tables = self.tables.write();
-        tables.remove(&table_id);
+        let table = tables
+            .get_mut(&table_id)
+            .ok_or_else(|| Error::Storage(format!("Table {} not found", table_id.0)))?;
+        table.columns = columns.into_iter()
+            .enumerate()
+            .filter(|(_, column)| column.len() > 0)
+            .map(|(idx, column)| (idx as u32, vec![column]))
+            .collect();
+        table.block_metadata.clear();
+        self.touch();
+        Ok(())
+    }
+
+    async fn delete_table(&self, table_id: TableId) -> Result<()> {
+        let guard = self.locks.lock(table_id).await;
+        self.tables.write().remove(&table_id);
+        self.locks.release(guard);
+        self.touch();
         info!("Deleted table {}", table_id.0);
         Ok(())
     }
@@ -5,6 +5,9 @@ use narayana_core::{Error, Result, schema::DataType};
 use std::mem;
 use bytes::{Bytes, BytesMut};
 
+#[cfg(feature = "parquet")]
+pub mod parquet;
+
 /// True column-oriented storage - raw bytes, no metadata
 pub struct ColumnarFormat;
 
@@ -0,0 +1,479 @@
+// Apache Parquet import/export
+// Tables go to Parquet through Arrow record batches, so files load in pandas,
+// Spark, DuckDB and friends, and files written by them bulk-load into narayana.
+// Each field's narayana DataType is kept in the Arrow field metadata, so Json,
+// Point and Geometry survive a round trip; files without it are mapped from
+// their Parquet logical types. Columns have no validity bitmap, so nulls read
+// back as the type's zero value (the field stays nullable).
+
+use crate::column_store::ColumnStore;
+use ::parquet::arrow::arrow_reader::{ParquetRecordBatchReader, ParquetRecordBatchReaderBuilder};
+use ::parquet::arrow::ArrowWriter;
+use ::parquet::basic::{Compression, ZstdLevel};
+use ::parquet::file::properties::WriterProperties;
+use ::parquet::file::reader::ChunkReader;
+use arrow_array::cast::AsArray;
+use arrow_array::types::{
+    Date32Type, Float32Type, Float64Type, Int16Type, Int32Type, Int64Type, Int8Type,
+    TimestampMillisecondType, UInt16Type, UInt32Type, UInt64Type, UInt8Type,
+};
+use arrow_array::{Array, ArrayRef, ArrowPrimitiveType, RecordBatch};
+use arrow_schema::{DataType as ArrowType, Field as ArrowField, Schema as ArrowSchema, SchemaRef};
+use bytes::Bytes;
+use narayana_core::column::Column;
+use narayana_core::schema::{DataType, Field, Schema};
+use narayana_core::types::{CompressionType, TableId};
+use narayana_core::{Error, Result};
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::Arc;
+
+/// Arrow field metadata key holding the narayana DataType as JSON
+pub const DATA_TYPE_METADATA_KEY: &str = "narayana.data_type";
+
+/// Rows per record batch when reading
+pub const DEFAULT_BATCH_SIZE: usize = 8192;
+
+/// How tables are written to Parquet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParquetWriteOptions {
+    pub compression: CompressionType,
+    pub max_row_group_size: usize,
+}
+
+impl Default for ParquetWriteOptions {
+    fn default() -> Self {
+        Self {
+            compression: CompressionType::Snappy,
+            max_row_group_size: 1024 * 1024,
+        }
+    }
+}
+
+impl ParquetWriteOptions {
+    fn properties(&self) -> WriterProperties {
+        let compression = match self.compression {
+            CompressionType::None => Compression::UNCOMPRESSED,
+            CompressionType::LZ4 => Compression::LZ4_RAW,
+            CompressionType::Zstd => Compression::ZSTD(ZstdLevel::default()),
+            CompressionType::Snappy => Compression::SNAPPY,
+        };
+        WriterProperties::builder()
+            .set_compression(compression)
+            .set_max_row_group_size(self.max_row_group_size.max(1))
+            .build()
+    }
+}
+
+/// Arrow type a narayana column of `data_type` is written as. Json is a UTF-8
+/// string, Point and Geometry their encoded bytes; arrays and maps have no
+/// Column representation and are rejected.
+pub fn to_parquet_type(data_type: &DataType) -> Result<ArrowType> {
+    narayana_core::arrow::field_arrow_type(data_type)
+}
+
+/// narayana DataType for a Parquet column written by another tool. Timestamps
+/// of any unit become milliseconds, Date64 becomes days, decimals Float64 and
+/// dictionary-encoded columns their value type.
+pub fn from_parquet_type(arrow_type: &ArrowType) -> Result<DataType> {
+    Ok(match arrow_type {
+        ArrowType::Int8 => DataType::Int8,
+        ArrowType::Int16 => DataType::Int16,
+        ArrowType::Int32 => DataType::Int32,
+        ArrowType::Int64 => DataType::Int64,
+        ArrowType::UInt8 => DataType::UInt8,
+        ArrowType::UInt16 => DataType::UInt16,
+        ArrowType::UInt32 => DataType::UInt32,
+        ArrowType::UInt64 => DataType::UInt64,
+        ArrowType::Float16 | ArrowType::Float32 => DataType::Float32,
+        ArrowType::Float64 | ArrowType::Decimal128(_, _) | ArrowType::Decimal256(_, _) => DataType::Float64,
+        ArrowType::Boolean => DataType::Boolean,
+        ArrowType::Utf8 | ArrowType::LargeUtf8 | ArrowType::Utf8View => DataType::String,
+        ArrowType::Binary | ArrowType::LargeBinary | ArrowType::BinaryView | ArrowType::FixedSizeBinary(_) => {
+            DataType::Binary
+        }
+        ArrowType::Timestamp(_, _) => DataType::Timestamp,
+        ArrowType::Date32 | ArrowType::Date64 => DataType::Date,
+        ArrowType::Dictionary(_, value) => from_parquet_type(value)?,
+        other => {
+            return Err(Error::SchemaMismatch(format!(
+                "Parquet: {} columns are not supported",
+                other
+            )))
+        }
+    })
+}
+
+/// Arrow schema a narayana schema is written with
+pub fn to_parquet_schema(schema: &Schema) -> Result<ArrowSchema> {
+    let fields = schema
+        .fields
+        .iter()
+        .map(|field| {
+            let data_type = serde_json::to_string(&field.data_type)
+                .map_err(|e| Error::Serialization(format!("Parquet: {}", e)))?;
+            let nullable = field.nullable || matches!(field.data_type, DataType::Nullable(_));
+            Ok(ArrowField::new(field.name.as_str(), to_parquet_type(&field.data_type)?, nullable)
+                .with_metadata(HashMap::from([(DATA_TYPE_METADATA_KEY.to_string(), data_type)])))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(ArrowSchema::new(fields))
+}
+
+/// narayana schema for a Parquet file's Arrow schema. A stored DataType is used
+/// when it still matches the column's physical type.
+pub fn from_parquet_schema(schema: &ArrowSchema) -> Result<Schema> {
+    let fields = schema
+        .fields()
+        .iter()
+        .map(|field| {
+            let stored = field
+                .metadata()
+                .get(DATA_TYPE_METADATA_KEY)
+                .and_then(|json| serde_json::from_str::<DataType>(json).ok())
+                .filter(|data_type| to_parquet_type(data_type).ok().as_ref() == Some(field.data_type()));
+            let data_type = match stored {
+                Some(data_type) => data_type,
+                None => from_parquet_type(field.data_type())?,
+            };
+            Ok(Field {
+                name: field.name().clone(),
+                data_type,
+                nullable: field.is_nullable(),
+                default_value: None,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Schema::new(fields))
+}
+
+fn parquet_error(e: impl std::fmt::Display) -> Error {
+    Error::Storage(format!("Parquet: {}", e))
+}
+
+/// Streams columns of one table into a Parquet file
+pub struct ParquetTableWriter<W: Write + Send> {
+    writer: ArrowWriter<W>,
+    schema: SchemaRef,
+}
+
+impl<W: Write + Send> ParquetTableWriter<W> {
+    pub fn new(sink: W, schema: &Schema, options: ParquetWriteOptions) -> Result<Self> {
+        let schema = Arc::new(to_parquet_schema(schema)?);
+        let writer = ArrowWriter::try_new(sink, schema.clone(), Some(options.properties())).map_err(parquet_error)?;
+        Ok(Self { writer, schema })
+    }
+
+    /// Write one batch of columns, in schema order
+    pub fn write(&mut self, columns: Vec<Column>) -> Result<()> {
+        let fields = self.schema.fields();
+        if columns.len() != fields.len() {
+            return Err(Error::SchemaMismatch(format!(
+                "{} columns for {} Parquet fields",
+                columns.len(),
+                fields.len()
+            )));
+        }
+        for (field, column) in fields.iter().zip(&columns) {
+            let arrow_type = narayana_core::arrow::arrow_type(column);
+            if &arrow_type != field.data_type() {
+                return Err(Error::SchemaMismatch(format!(
+                    "column {} is {}, expected {}",
+                    field.name(),
+                    arrow_type,
+                    field.data_type()
+                )));
+            }
+        }
+        let arrays = columns.into_iter().map(narayana_core::arrow::to_array).collect();
+        let batch = RecordBatch::try_new(self.schema.clone(), arrays).map_err(parquet_error)?;
+        self.writer.write(&batch).map_err(parquet_error)
+    }
+
+    /// Flush the last row group and the footer, returning the sink
+    pub fn finish(self) -> Result<W> {
+        self.writer.into_inner().map_err(parquet_error)
+    }
+}
+
+/// Reads a Parquet file as batches of narayana columns
+pub struct ParquetTableReader {
+    schema: Schema,
+    batches: ParquetRecordBatchReader,
+}
+
+impl ParquetTableReader {
+    /// Open a Parquet source (a `File` or in-memory `Bytes`), reading `batch_size` rows at a time
+    pub fn open<R: ChunkReader + 'static>(source: R, batch_size: usize) -> Result<Self> {
+        let builder = ParquetRecordBatchReaderBuilder::try_new(source).map_err(parquet_error)?;
+        let schema = from_parquet_schema(builder.schema())?;
+        let batches = builder.with_batch_size(batch_size.max(1)).build().map_err(parquet_error)?;
+        Ok(Self { schema, batches })
+    }
+
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+}
+
+impl Iterator for ParquetTableReader {
+    type Item = Result<Vec<Column>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let batch = match self.batches.next()? {
+            Ok(batch) => batch,
+            Err(e) => return Some(Err(parquet_error(e))),
+        };
+        Some(
+            batch
+                .columns()
+                .iter()
+                .zip(&self.schema.fields)
+                .map(|(array, field)| to_column(array, &field.data_type))
+                .collect(),
+        )
+    }
+}
+
+fn primitive<T: ArrowPrimitiveType>(array: &ArrayRef) -> Vec<T::Native> {
+    let array = array.as_primitive::<T>();
+    if array.null_count() == 0 {
+        array.values().to_vec()
+    } else {
+        array.iter().map(Option::unwrap_or_default).collect()
+    }
+}
+
+/// Convert an Arrow array into the column `data_type` is stored as, casting
+/// foreign physical types (nanosecond timestamps, large strings, ...) first
+fn to_column(array: &ArrayRef, data_type: &DataType) -> Result<Column> {
+    let target = to_parquet_type(data_type)?;
+    let array = if array.data_type() == &target {
+        array.clone()
+    } else {
+        arrow_cast::cast(array, &target).map_err(parquet_error)?
+    };
+    Ok(match target {
+        ArrowType::Int8 => Column::Int8(primitive::<Int8Type>(&array)),
+        ArrowType::Int16 => Column::Int16(primitive::<Int16Type>(&array)),
+        ArrowType::Int32 => Column::Int32(primitive::<Int32Type>(&array)),
+        ArrowType::Int64 => Column::Int64(primitive::<Int64Type>(&array)),
+        ArrowType::UInt8 => Column::UInt8(primitive::<UInt8Type>(&array)),
+        ArrowType::UInt16 => Column::UInt16(primitive::<UInt16Type>(&array)),
+        ArrowType::UInt32 => Column::UInt32(primitive::<UInt32Type>(&array)),
+        ArrowType::UInt64 => Column::UInt64(primitive::<UInt64Type>(&array)),
+        ArrowType::Float32 => Column::Float32(primitive::<Float32Type>(&array)),
+        ArrowType::Float64 => Column::Float64(primitive::<Float64Type>(&array)),
+        ArrowType::Timestamp(_, _) => Column::Timestamp(primitive::<TimestampMillisecondType>(&array)),
+        ArrowType::Date32 => Column::Date(primitive::<Date32Type>(&array)),
+        ArrowType::Boolean => Column::Boolean(array.as_boolean().iter().map(Option::unwrap_or_default).collect()),
+        ArrowType::Utf8 => Column::String(
+            array
+                .as_string::<i32>()
+                .iter()
+                .map(|value| value.unwrap_or_default().to_string())
+                .collect(),
+        ),
+        _ => Column::Binary(
+            array
+                .as_binary::<i32>()
+                .iter()
+                .map(|value| value.unwrap_or_default().to_vec())
+                .collect(),
+        ),
+    })
+}
+
+/// Write a whole table as an in-memory Parquet file
+pub fn write_table(schema: &Schema, columns: Vec<Column>, options: ParquetWriteOptions) -> Result<Vec<u8>> {
+    let mut writer = ParquetTableWriter::new(Vec::new(), schema, options)?;
+    writer.write(columns)?;
+    writer.finish()
+}
+
+/// Read a whole in-memory Parquet file
+pub fn read_table(data: Bytes) -> Result<(Schema, Vec<Column>)> {
+    let mut reader = ParquetTableReader::open(data, DEFAULT_BATCH_SIZE)?;
+    let mut columns: Option<Vec<Column>> = None;
+    for batch in reader.by_ref() {
+        let batch = batch?;
+        columns = Some(match columns {
+            None => batch,
+            Some(read) => read
+                .iter()
+                .zip(&batch)
+                .map(|(read, more)| read.append(more))
+                .collect::<Result<_>>()?,
+        });
+    }
+    let schema = reader.schema;
+    let columns = match columns {
+        Some(columns) => columns,
+        None => schema
+            .fields
+            .iter()
+            .map(|field| to_column(&arrow_array::new_empty_array(&to_parquet_type(&field.data_type)?), &field.data_type))
+            .collect::<Result<_>>()?,
+    };
+    Ok((schema, columns))
+}
+
+/// Bulk-load a Parquet source into a new table created with the file's schema.
+/// Returns the number of rows written.
+pub async fn import_table<R: ChunkReader + 'static>(
+    store: &dyn ColumnStore,
+    table_id: TableId,
+    source: R,
+) -> Result<usize> {
+    let reader = ParquetTableReader::open(source, DEFAULT_BATCH_SIZE)?;
+    store.create_table(table_id, reader.schema().clone()).await?;
+    let mut rows = 0;
+    for columns in reader {
+        let columns = columns?;
+        let count = columns.first().map(Column::len).unwrap_or(0);
+        if count == 0 {
+            continue;
+        }
+        store.write_columns(table_id, columns).await?;
+        rows += count;
+    }
+    Ok(rows)
+}
+
+/// Export every row of a table into `sink` as Parquet, returning the sink
+pub async fn export_table<W: Write + Send>(
+    store: &dyn ColumnStore,
+    table_id: TableId,
+    sink: W,
+    options: ParquetWriteOptions,
+) -> Result<W> {
+    let schema = store.get_schema(table_id).await?;
+    let mut writer = ParquetTableWriter::new(sink, &schema, options)?;
+    if !schema.fields.is_empty() {
+        let column_ids = (0..schema.fields.len() as u32).collect();
+        let columns = store.read_columns(table_id, column_ids, 0, usize::MAX).await?;
+        writer.write(columns)?;
+    }
+    writer.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::column_store::InMemoryColumnStore;
+
+    fn field(name: &str, data_type: DataType, nullable: bool) -> Field {
+        Field { name: name.to_string(), data_type, nullable, default_value: None }
+    }
+
+    /// Column has no PartialEq; Debug output compares every value
+    fn same(a: &[Column], b: &[Column]) -> bool {
+        format!("{:?}", a) == format!("{:?}", b)
+    }
+
+    #[test]
+    fn test_round_trip_keeps_narayana_types() {
+        let schema = Schema::new(vec![
+            field("id", DataType::UInt64, false),
+            field("score", DataType::Float32, false),
+            field("name", DataType::Nullable(Box::new(DataType::String)), true),
+            field("payload", DataType::Json, false),
+            field("location", DataType::Point, false),
+            field("seen_at", DataType::Timestamp, false),
+            field("day", DataType::Date, false),
+            field("active", DataType::Boolean, false),
+        ]);
+        let columns = vec![
+            Column::UInt64(vec![1, 2]),
+            Column::Float32(vec![0.5, 1.5]),
+            Column::String(vec!["ada".to_string(), "bob".to_string()]),
+            Column::String(vec![r#"{"a":1}"#.to_string(), "[]".to_string()]),
+            Column::Binary(vec![vec![1, 2, 3], vec![]]),
+            Column::Timestamp(vec![1_700_000_000_000, -1]),
+            Column::Date(vec![19_000, 0]),
+            Column::Boolean(vec![true, false]),
+        ];
+
+        for compression in [CompressionType::None, CompressionType::Snappy, CompressionType::Zstd, CompressionType::LZ4] {
+            let options = ParquetWriteOptions { compression, ..Default::default() };
+            let bytes = write_table(&schema, columns.clone(), options).unwrap();
+            let (read_schema, read_columns) = read_table(Bytes::from(bytes)).unwrap();
+            let types: Vec<_> = read_schema.fields.iter().map(|f| f.data_type.clone()).collect();
+            let expected: Vec<_> = schema.fields.iter().map(|f| f.data_type.clone()).collect();
+            assert_eq!(types, expected);
+            assert!(read_schema.fields[2].nullable);
+            assert!(same(&read_columns, &columns));
+        }
+    }
+
+    #[test]
+    fn test_foreign_types_map_to_narayana() {
+        use arrow_array::{Date64Array, LargeStringArray, StringArray, TimestampNanosecondArray};
+        use arrow_schema::TimeUnit;
+
+        let schema = ArrowSchema::new(vec![
+            ArrowField::new("at", ArrowType::Timestamp(TimeUnit::Nanosecond, Some("UTC".into())), false),
+            ArrowField::new("day", ArrowType::Date64, false),
+            ArrowField::new("note", ArrowType::LargeUtf8, true),
+            ArrowField::new("tag", ArrowType::Utf8, true),
+        ]);
+        let batch = RecordBatch::try_new(
+            Arc::new(schema),
+            vec![
+                Arc::new(TimestampNanosecondArray::from(vec![1_500_000_000, 3_000_000_000]).with_timezone("UTC")),
+                Arc::new(Date64Array::from(vec![86_400_000, 0])),
+                Arc::new(LargeStringArray::from(vec![Some("x"), None])),
+                Arc::new(StringArray::from(vec![None, Some("y")])),
+            ],
+        )
+        .unwrap();
+        let mut writer = ArrowWriter::try_new(Vec::new(), batch.schema(), None).unwrap();
+        writer.write(&batch).unwrap();
+        let bytes = writer.into_inner().unwrap();
+
+        let (schema, columns) = read_table(Bytes::from(bytes)).unwrap();
+        let types: Vec<_> = schema.fields.iter().map(|f| f.data_type.clone()).collect();
+        assert_eq!(types, vec![DataType::Timestamp, DataType::Date, DataType::String, DataType::String]);
+        let expected = vec![
+            Column::Timestamp(vec![1_500, 3_000]),
+            Column::Date(vec![1, 0]),
+            Column::String(vec!["x".to_string(), String::new()]),
+            Column::String(vec![String::new(), "y".to_string()]),
+        ];
+        assert!(same(&columns, &expected));
+        assert!(schema.fields[2].nullable);
+    }
+
+    #[test]
+    fn test_rejects_unsupported_and_mismatched_columns() {
+        assert!(to_parquet_type(&DataType::Array(Box::new(DataType::Int32))).is_err());
+
+        let schema = Schema::new(vec![field("id", DataType::Int64, false)]);
+        let mut writer = ParquetTableWriter::new(Vec::new(), &schema, ParquetWriteOptions::default()).unwrap();
+        assert!(writer.write(vec![Column::Int32(vec![1])]).is_err());
+        assert!(writer.write(vec![]).is_err());
+        writer.write(vec![Column::Int64(vec![1])]).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_import_and_export_tables() {
+        let schema = Schema::new(vec![
+            field("id", DataType::Int64, false),
+            field("name", DataType::String, false),
+        ]);
+        let rows = DEFAULT_BATCH_SIZE + 10;
+        let ids: Vec<i64> = (0..rows as i64).collect();
+        let names: Vec<String> = ids.iter().map(|id| format!("row-{}", id)).collect();
+        let options = ParquetWriteOptions { max_row_group_size: 4096, ..Default::default() };
+        let bytes = write_table(&schema, vec![Column::Int64(ids.clone()), Column::String(names.clone())], options).unwrap();
+
+        let store = InMemoryColumnStore::new();
+        let imported = import_table(&store, TableId(1), Bytes::from(bytes)).await.unwrap();
+        assert_eq!(imported, rows);
+        assert_eq!(store.get_schema(TableId(1)).await.unwrap().fields[1].name, "name");
+
+        let exported = export_table(&store, TableId(1), Vec::new(), ParquetWriteOptions::default()).await.unwrap();
+        let (_, columns) = read_table(Bytes::from(exported)).unwrap();
+        assert!(same(&columns, &[Column::Int64(ids), Column::String(names)]));
+    }
+}
@@ -0,0 +1,632 @@
+// Compliance - personal data tagging, lineage and subject erasure
+// Tables are tagged with the column holding each row's data subject key (a
+// user id, an email, ...) and the columns carrying personal data. Vector
+// indexes holding RAG memories are tagged with the embedding metadata field
+// the subject key is stored under. Lineage edges record which tables and
+// indexes were derived from which, so an erasure can name the copies of
+// personal data nothing tags yet.
+//
+// Erasing a subject visits every tagged table and index:
+//
+//   delete  the subject's rows are removed (the table is rewritten and the
+//           rows after them move up) and their embeddings dropped
+//   shred   rows stay, but their personal columns are encrypted under a key
+//           made for this erasure and discarded straight away; columns that
+//           can't hold ciphertext are zeroed. Their embeddings and indexed
+//           geometries are dropped.
+//
+// Indexes generated from a table's `embed` columns and its full-text and
+// spatial indexes follow its rows. Blobs the subject's personal columns reference are
+// deleted unless another row still references them. Each erasure produces
+// an `ErasureReport`, which names the subject only by hash.
+
+use narayana_core::{column::Column, types::TableId, Error, Result};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::info;
+
+use crate::blob_store::{BlobRef, BlobStore};
+use crate::column_store::ColumnStore;
+use crate::embeddings::EmbeddingsManager;
+use crate::encryption::{EncryptionAlgorithm, EncryptionConfig, EncryptionKey, EncryptionScope, OnTheFlyEncryptor};
+use crate::full_text::FullTextIndexManager;
+use crate::geospatial::SpatialIndexManager;
+use crate::row_security::{cell, take_rows};
+use crate::temporal::TemporalManager;
+use crate::vector_search::VectorStore;
+
+const MAX_NAME_LENGTH: usize = 256;
+const MAX_PERSONAL_COLUMNS: usize = 256;
+const MAX_SUBJECT_LENGTH: usize = 1024;
+const MAX_LINEAGE_EDGES: usize = 10_000;
+/// Erasure reports kept, newest first
+const MAX_REPORTS: usize = 100;
+/// What a shredded string value starts with
+pub const SHREDDED_PREFIX: &str = "shredded:";
+
+/// A column holding personal data
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PersonalColumn {
+    pub column: String,
+    /// What kind of personal data it is (email, name, location, ...)
+    #[serde(default)]
+    pub category: Option<String>,
+}
+
+/// Personal data tags of a table
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableTags {
+    /// Column holding the key of the subject a row belongs to
+    pub subject_column: String,
+    /// Columns carrying personal data; shredding rewrites these
+    #[serde(default)]
+    pub columns: Vec<PersonalColumn>,
+}
+
+/// Personal data tags of a vector index holding RAG memories
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VectorIndexTags {
+    /// Embedding metadata field holding the subject key
+    pub subject_field: String,
+}
+
+/// Somewhere personal data can be kept
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "id", rename_all = "snake_case")]
+pub enum DataAsset {
+    Table(u64),
+    VectorIndex(String),
+}
+
+/// `target` was derived from `source`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineageEdge {
+    pub source: DataAsset,
+    pub target: DataAsset,
+    /// How it was derived (a query, a job, an embedding model, ...)
+    #[serde(default)]
+    pub transform: Option<String>,
+    #[serde(default)]
+    pub recorded_at: u64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErasureMode {
+    /// Remove the subject's rows and embeddings
+    #[default]
+    Delete,
+    /// Keep rows but make their personal columns unreadable
+    Shred,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErasureRequest {
+    pub subject: String,
+    #[serde(default)]
+    pub mode: ErasureMode,
+    /// Report what would be erased without changing anything
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableErasure {
+    pub table_id: u64,
+    pub rows: usize,
+    /// Columns shredded; empty when rows were deleted
+    pub columns: Vec<String>,
+    /// Embeddings dropped from indexes generated from the table
+    pub embeddings: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexErasure {
+    pub index: String,
+    pub embeddings: usize,
+}
+
+/// What an erasure did (or, for a dry run, would do)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErasureReport {
+    pub id: String,
+    /// Hex SHA-256 of the subject key; the key itself is not kept
+    pub subject_hash: String,
+    pub mode: ErasureMode,
+    pub dry_run: bool,
+    pub started_at: u64,
+    pub completed_at: u64,
+    pub tables: Vec<TableErasure>,
+    pub vector_indexes: Vec<IndexErasure>,
+    pub blobs_deleted: Vec<String>,
+    /// Blobs the subject referenced that other rows still use
+    pub blobs_retained: Vec<String>,
+    /// Untagged assets derived from tagged ones; they may still hold the
+    /// subject's data
+    pub untracked: Vec<DataAsset>,
+    pub errors: Vec<String>,
+}
+
+impl ErasureReport {
+    /// Whether every copy of the subject's data known to lineage was reached
+    pub fn is_complete(&self) -> bool {
+        self.errors.is_empty() && self.untracked.is_empty()
+    }
+}
+
+/// The stores an erasure reaches into
+pub struct ErasureTargets<'a> {
+    pub store: &'a dyn ColumnStore,
+    pub vectors: &'a VectorStore,
+    pub full_text: Option<&'a FullTextIndexManager>,
+    pub spatial: Option<&'a SpatialIndexManager>,
+    pub embeddings: Option<&'a EmbeddingsManager>,
+    pub blobs: Option<&'a BlobStore>,
+    pub temporal: Option<&'a TemporalManager>,
+}
+
+/// Personal data tags, lineage and erasure reports
+#[derive(Default)]
+pub struct ComplianceManager {
+    tables: RwLock<HashMap<TableId, TableTags>>,
+    vector_indexes: RwLock<HashMap<String, VectorIndexTags>>,
+    lineage: RwLock<Vec<LineageEdge>>,
+    reports: RwLock<VecDeque<ErasureReport>>,
+    /// Erasures run one at a time so table rewrites never interleave
+    erasing: tokio::sync::Mutex<()>,
+}
+
+impl ComplianceManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tag a table, replacing its previous tags
+    pub fn tag_table(&self, table_id: TableId, tags: TableTags) -> Result<()> {
+        validate_name("Subject column", &tags.subject_column)?;
+        if tags.columns.len() > MAX_PERSONAL_COLUMNS {
+            return Err(Error::Storage(format!("At most {} personal columns per table", MAX_PERSONAL_COLUMNS)));
+        }
+        let mut seen = HashSet::new();
+        for column in &tags.columns {
+            validate_name("Column", &column.column)?;
+            if !seen.insert(column.column.as_str()) {
+                return Err(Error::Storage(format!("Column '{}' is listed twice", column.column)));
+            }
+        }
+        self.tables.write().insert(table_id, tags);
+        Ok(())
+    }
+
+    /// Remove a table's tags; false if it had none
+    pub fn untag_table(&self, table_id: TableId) -> bool {
+        self.tables.write().remove(&table_id).is_some()
+    }
+
+    pub fn table_tags(&self, table_id: TableId) -> Option<TableTags> {
+        self.tables.read().get(&table_id).cloned()
+    }
+
+    /// Tagged tables, by id
+    pub fn tagged_tables(&self) -> Vec<(TableId, TableTags)> {
+        let mut tables: Vec<_> = self.tables.read().iter().map(|(id, tags)| (*id, tags.clone())).collect();
+        tables.sort_by_key(|(id, _)| id.0);
+        tables
+    }
+
+    /// Tag a vector index, replacing its previous tags
+    pub fn tag_vector_index(&self, name: &str, tags: VectorIndexTags) -> Result<()> {
+        validate_name("Index name", name)?;
+        validate_name("Subject field", &tags.subject_field)?;
+        self.vector_indexes.write().insert(name.to_string(), tags);
+        Ok(())
+    }
+
+    /// Remove a vector index's tags; false if it had none
+    pub fn untag_vector_index(&self, name: &str) -> bool {
+        self.vector_indexes.write().remove(name).is_some()
+    }
+
+    /// Tagged vector indexes, by name
+    pub fn tagged_vector_indexes(&self) -> Vec<(String, VectorIndexTags)> {
+        let mut indexes: Vec<_> = self.vector_indexes.read().iter().map(|(n, t)| (n.clone(), t.clone())).collect();
+        indexes.sort_by(|a, b| a.0.cmp(&b.0));
+        indexes
+    }
+
+    /// Record that `edge.target` was derived from `edge.source`. Recording
+    /// the same edge again only refreshes its time.
+    pub fn record_lineage(&self, mut edge: LineageEdge) -> Result<()> {
+        if edge.source == edge.target {
+            return Err(Error::Storage("An asset cannot be derived from itself".to_string()));
+        }
+        for asset in [&edge.source, &edge.target] {
+            if let DataAsset::VectorIndex(name) = asset {
+                validate_name("Index name", name)?;
+            }
+        }
+        edge.recorded_at = now();
+        let mut lineage = self.lineage.write();
+        if let Some(existing) = lineage.iter_mut()
+            .find(|e| e.source == edge.source && e.target == edge.target && e.transform == edge.transform)
+        {
+            existing.recorded_at = edge.recorded_at;
+            return Ok(());
+        }
+        if lineage.len() >= MAX_LINEAGE_EDGES {
+            return Err(Error::Storage(format!("Lineage already has {} edges (max {})", lineage.len(), MAX_LINEAGE_EDGES)));
+        }
+        lineage.push(edge);
+        Ok(())
+    }
+
+    /// Lineage edges touching `asset`, or all of them
+    pub fn lineage(&self, asset: Option<&DataAsset>) -> Vec<LineageEdge> {
+        self.lineage.read()
+            .iter()
+            .filter(|e| asset.is_none_or(|a| e.source == *a || e.target == *a))
+            .cloned()
+            .collect()
+    }
+
+    /// Everything derived from `asset`, directly or not
+    pub fn downstream(&self, asset: &DataAsset) -> Vec<DataAsset> {
+        let lineage = self.lineage.read();
+        let mut found: Vec<DataAsset> = Vec::new();
+        let mut queue = VecDeque::from([asset.clone()]);
+        while let Some(current) = queue.pop_front() {
+            for edge in lineage.iter().filter(|e| e.source == current) {
+                if edge.target != *asset && !found.contains(&edge.target) {
+                    found.push(edge.target.clone());
+                    queue.push_back(edge.target.clone());
+                }
+            }
+        }
+        found
+    }
+
+    /// Forget a dropped table's tags and lineage
+    pub fn drop_table(&self, table_id: TableId) {
+        self.tables.write().remove(&table_id);
+        let asset = DataAsset::Table(table_id.0);
+        self.lineage.write().retain(|e| e.source != asset && e.target != asset);
+    }
+
+    /// Most recent erasure reports, newest first
+    pub fn reports(&self, limit: usize) -> Vec<ErasureReport> {
+        self.reports.read().iter().take(limit).cloned().collect()
+    }
+
+    /// Erase every record of `request.subject` from the tagged tables and
+    /// vector indexes. Failures on one table or index are recorded in the
+    /// report and don't stop the rest.
+    pub async fn erase(&self, request: &ErasureRequest, targets: &ErasureTargets<'_>) -> Result<ErasureReport> {
+        if request.subject.is_empty() || request.subject.len() > MAX_SUBJECT_LENGTH {
+            return Err(Error::Storage(format!("Subject must be 1-{} bytes", MAX_SUBJECT_LENGTH)));
+        }
+        let _erasing = self.erasing.lock().await;
+        let mut report = ErasureReport {
+            id: uuid::Uuid::new_v4().to_string(),
+            subject_hash: hex::encode(Sha256::digest(request.subject.as_bytes())),
+            mode: request.mode,
+            dry_run: request.dry_run,
+            started_at: now(),
+            completed_at: 0,
+            tables: Vec::new(),
+            vector_indexes: Vec::new(),
+            blobs_deleted: Vec::new(),
+            blobs_retained: Vec::new(),
+            untracked: Vec::new(),
+            errors: Vec::new(),
+        };
+        // Dropped, key and all, when the erasure ends
+        let shredder = match request.mode {
+            ErasureMode::Shred => Some(Shredder::new()?),
+            ErasureMode::Delete => None,
+        };
+
+        let mut blob_refs = BlobRefs::default();
+        let tables = self.tagged_tables();
+        for (table_id, tags) in &tables {
+            match erase_table(*table_id, tags, request, shredder.as_ref(), targets, &mut blob_refs).await {
+                Ok(Some(erased)) => report.tables.push(erased),
+                Ok(None) => {}
+                Err(e) => report.errors.push(format!("table {}: {}", table_id.0, e)),
+            }
+        }
+
+        let indexes = self.tagged_vector_indexes();
+        for (name, tags) in &indexes {
+            let is_subject = |embedding: &crate::vector_search::Embedding| {
+                embedding.metadata.get(&tags.subject_field).is_some_and(|v| subject_matches(v, &request.subject))
+            };
+            let result = if request.dry_run {
+                targets.vectors.embedding_ids(name, is_subject)
+            } else {
+                targets.vectors.remove_embeddings(name, is_subject)
+            };
+            match result {
+                Ok(ids) if ids.is_empty() => {}
+                Ok(ids) => report.vector_indexes.push(IndexErasure { index: name.clone(), embeddings: ids.len() }),
+                Err(e) => report.errors.push(format!("vector index {}: {}", name, e)),
+            }
+        }
+
+        let mut subject_blobs: Vec<String> = blob_refs.subject.into_iter().collect();
+        subject_blobs.sort();
+        for id in subject_blobs {
+            if blob_refs.others.contains(&id) {
+                report.blobs_retained.push(id);
+                continue;
+            }
+            let Some(blobs) = targets.blobs else {
+                report.errors.push(format!("blob {}: no blob store to delete it from", id));
+                continue;
+            };
+            if blobs.info(&id).is_none() {
+                continue;
+            }
+            if request.dry_run {
+                report.blobs_deleted.push(id);
+                continue;
+            }
+            match blobs.delete(&id) {
+                Ok(()) => report.blobs_deleted.push(id),
+                Err(e) => report.errors.push(format!("blob {}: {}", id, e)),
+            }
+        }
+
+        let tagged: HashSet<DataAsset> = tables.iter().map(|(id, _)| DataAsset::Table(id.0))
+            .chain(indexes.iter().map(|(name, _)| DataAsset::VectorIndex(name.clone())))
+            .collect();
+        let mut untracked: Vec<DataAsset> = tagged.iter()
+            .flat_map(|asset| self.downstream(asset))
+            .filter(|asset| !tagged.contains(asset))
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        untracked.sort();
+        report.untracked = untracked;
+
+        report.completed_at = now();
+        info!(
+            "Erasure {} ({:?}{}): {} tables, {} vector indexes, {} blobs, {} errors",
+            report.id,
+            report.mode,
+            if report.dry_run { ", dry run" } else { "" },
+            report.tables.len(),
+            report.vector_indexes.len(),
+            report.blobs_deleted.len(),
+            report.errors.len()
+        );
+        let mut reports = self.reports.write();
+        reports.push_front(report.clone());
+        reports.truncate(MAX_REPORTS);
+        Ok(report)
+    }
+}
+
+/// Blob ids found in personal columns
+#[derive(Default)]
+struct BlobRefs {
+    /// Referenced by the subject's rows
+    subject: HashSet<String>,
+    /// Referenced by anyone else's
+    others: HashSet<String>,
+}
+
+async fn erase_table(
+    table_id: TableId,
+    tags: &TableTags,
+    request: &ErasureRequest,
+    shredder: Option<&Shredder>,
+    targets: &ErasureTargets<'_>,
+    blob_refs: &mut BlobRefs,
+) -> Result<Option<TableErasure>> {
+    // No write may land between reading the rows and renumbering what
+    // depends on their offsets
+    let guard = targets.store.lock_table(table_id).await;
+    let schema = targets.store.get_schema(table_id).await?;
+    let subject_idx = schema.field_index(&tags.subject_column)
+        .ok_or_else(|| Error::ColumnNotFound(tags.subject_column.clone()))?;
+    let personal = tags.columns.iter()
+        .map(|c| schema.field_index(&c.column).ok_or_else(|| Error::ColumnNotFound(c.column.clone())))
+        .collect::<Result<Vec<usize>>>()?;
+
+    let column_ids: Vec<u32> = (0..schema.fields.len() as u32).collect();
+    let columns = targets.store.read_columns(table_id, column_ids, 0, usize::MAX).await?;
+    if columns.len() != schema.fields.len() {
+        // Stores skip columns that hold no data
+        if columns.iter().all(|c| c.len() == 0) {
+            return Ok(None);
+        }
+        return Err(Error::Storage(format!("Read returned {} of {} columns", columns.len(), schema.fields.len())));
+    }
+    let subject_column = &columns[subject_idx];
+    let matched: Vec<usize> = (0..subject_column.len())
+        .filter(|&row| subject_matches(&cell(subject_column, row), &request.subject))
+        .collect();
+
+    for &idx in &personal {
+        let Column::String(values) = &columns[idx] else { continue };
+        for (row, value) in values.iter().enumerate() {
+            if let Some(blob) = BlobRef::parse(value) {
+                let refs = if matched.binary_search(&row).is_ok() { &mut blob_refs.subject } else { &mut blob_refs.others };
+                refs.insert(blob.id().to_string());
+            }
+        }
+    }
+    if matched.is_empty() {
+        return Ok(None);
+    }
+
+    let embed_indexes: Vec<(String, usize)> = schema.embeddings.iter()
+        .filter_map(|annotation| {
+            let name = EmbeddingsManager::index_name(table_id, annotation);
+            let vector_idx = schema.field_index(&annotation.vector_column)?;
+            targets.vectors.has_index(&name).then_some((name, vector_idx))
+        })
+        .collect();
+    let mut erased = TableErasure {
+        table_id: table_id.0,
+        rows: matched.len(),
+        columns: match request.mode {
+            ErasureMode::Shred => tags.columns.iter().map(|c| c.column.clone()).collect(),
+            ErasureMode::Delete => Vec::new(),
+        },
+        embeddings: 0,
+    };
+    let is_matched = |id: u64| matched.binary_search(&(id as usize)).is_ok();
+    if request.dry_run {
+        for (name, _) in &embed_indexes {
+            erased.embeddings += targets.vectors.embedding_ids(name, |e| is_matched(e.id))?.len();
+        }
+        return Ok(Some(erased));
+    }
+
+    let rewritten: Vec<Column> = match shredder {
+        None => {
+            let kept: Vec<usize> = (0..subject_column.len()).filter(|row| matched.binary_search(row).is_err()).collect();
+            columns.iter().map(|column| take_rows(column, &kept)).collect()
+        }
+        Some(shredder) => {
+            let mut rewritten = columns.clone();
+            for &idx in &personal {
+                shredder.shred(&mut rewritten[idx], &matched)?;
+            }
+            // Stored vectors were computed from the row, so they go too
+            for (_, vector_idx) in &embed_indexes {
+                if let Column::Binary(values) = &mut rewritten[*vector_idx] {
+                    for &row in &matched {
+                        if let Some(value) = values.get_mut(row) {
+                            value.clear();
+                        }
+                    }
+                }
+            }
+            rewritten
+        }
+    };
+    targets.store.replace_rows(&guard, rewritten.clone()).await?;
+
+    // Embedding and spatial ids are row offsets, so after a delete the rows
+    // behind the deleted ones are renumbered to match their new offsets
+    let remap = |id: u64| {
+        if is_matched(id) {
+            None
+        } else if shredder.is_some() {
+            Some(id)
+        } else {
+            Some(id - matched.partition_point(|&row| (row as u64) < id) as u64)
+        }
+    };
+    for (name, _) in &embed_indexes {
+        erased.embeddings += targets.vectors.retain(name, |embedding| remap(embedding.id))?;
+    }
+    if let Some(spatial) = targets.spatial {
+        let rows = rewritten.first().map(|c| c.len()).unwrap_or(0) as u64;
+        spatial.retain(table_id, rows, remap);
+    }
+    if shredder.is_none() {
+        if let Some(embeddings) = targets.embeddings {
+            embeddings.rows_removed(table_id, &matched);
+        }
+        if let Some(temporal) = targets.temporal {
+            temporal.rows_removed(table_id, &matched).await;
+        }
+    }
+    if let Some(full_text) = targets.full_text {
+        full_text.reindex_table(table_id, &schema, &rewritten)?;
+    }
+    Ok(Some(erased))
+}
+
+/// Encrypts values under a key that only lives as long as the shredder
+struct Shredder {
+    encryptor: OnTheFlyEncryptor,
+}
+
+impl Shredder {
+    const SCOPE: &'static str = "erasure";
+
+    fn new() -> Result<Self> {
+        let encryptor = OnTheFlyEncryptor::new();
+        let key_id = uuid::Uuid::new_v4().to_string();
+        encryptor.add_key(EncryptionKey::new(EncryptionAlgorithm::Aes256Gcm, key_id.clone())?);
+        encryptor.configure(
+            Self::SCOPE.to_string(),
+            EncryptionConfig::new(EncryptionScope::Record, EncryptionAlgorithm::Aes256Gcm, key_id),
+        );
+        Ok(Self { encryptor })
+    }
+
+    /// Shred the values at `rows`
+    fn shred(&self, column: &mut Column, rows: &[usize]) -> Result<()> {
+        macro_rules! zero {
+            ($values:expr) => {
+                for &row in rows {
+                    if let Some(value) = $values.get_mut(row) {
+                        *value = Default::default();
+                    }
+                }
+            };
+        }
+        match column {
+            Column::String(values) => {
+                for &row in rows {
+                    if let Some(value) = values.get_mut(row) {
+                        let sealed = self.encryptor.encrypt(value.as_bytes(), Self::SCOPE)?;
+                        *value = format!("{}{}", SHREDDED_PREFIX, hex::encode(sealed));
+                    }
+                }
+            }
+            Column::Binary(values) => {
+                for &row in rows {
+                    if let Some(value) = values.get_mut(row) {
+                        *value = self.encryptor.encrypt(value, Self::SCOPE)?;
+                    }
+                }
+            }
+            Column::Int8(v) => zero!(v),
+            Column::Int16(v) => zero!(v),
+            Column::Int32(v) => zero!(v),
+            Column::Int64(v) => zero!(v),
+            Column::UInt8(v) => zero!(v),
+            Column::UInt16(v) => zero!(v),
+            Column::UInt32(v) => zero!(v),
+            Column::UInt64(v) => zero!(v),
+            Column::Float32(v) => zero!(v),
+            Column::Float64(v) => zero!(v),
+            Column::Boolean(v) => zero!(v),
+            Column::Timestamp(v) => zero!(v),
+            Column::Date(v) => zero!(v),
+        }
+        Ok(())
+    }
+}
+
+/// Whether a stored key is `subject`; numbers and booleans compare by their
+/// text
+fn subject_matches(value: &Value, subject: &str) -> bool {
+    match value {
+        Value::String(s) => s == subject,
+        Value::Number(n) => n.to_string() == subject,
+        Value::Bool(b) => b.to_string() == subject,
+        _ => false,
+    }
+}
+
+fn validate_name(what: &str, name: &str) -> Result<()> {
+    if name.is_empty() || name.len() > MAX_NAME_LENGTH || name.chars().any(|c| c.is_control()) {
+        return Err(Error::Storage(format!("{} must be 1-{} characters", what, MAX_NAME_LENGTH)));
+    }
+    Ok(())
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
@@ -8,8 +8,8 @@ use crate::global_workspace::GlobalWorkspace;
 use crate::background_daemon::BackgroundDaemon;
 use crate::working_memory::WorkingMemoryScratchpad;
 use crate::memory_bridge::MemoryBridge;
-use crate::narrative_generator::NarrativeGenerator;
-use crate::attention_router::AttentionRouter;
+use crate::narrative_generator::{NarrativeGenerator, NarrativeScheduleConfig};
+use crate::attention_router::{AttentionRouter, ResourceConflict};
 use crate::dreaming_loop::DreamingLoop;
 use crate::genetics::GeneticSystem;
 use crate::traits_equations::TraitCalculator;
@@ -46,6 +46,9 @@ pub struct CPLConfig {
     pub enable_attention: bool,
     /// Narrative generator enabled
     pub enable_narrative: bool,
+    /// Cadence and length of scheduled narrative summaries
+    #[serde(default)]
+    pub narrative_schedule: NarrativeScheduleConfig,
     /// Memory bridge enabled
     pub enable_memory_bridge: bool,
     /// Persistence enabled
@@ -82,6 +85,64 @@ pub struct CPLConfig {
     pub audio_config: Option<serde_json::Value>,
     /// Arrow of Time configuration
     pub aot_config: Option<AOTConfig>,
+    /// Trait genome to start from instead of a random one (a persisted genome still wins)
+    #[serde(default)]
+    pub genome: Option<crate::genetics::Genome>,
+    /// Limits after which the loop stops itself
+    #[serde(default)]
+    pub budget: CPLResourceBudget,
+}
+
+/// Resource budget for one CPL
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CPLResourceBudget {
+    /// Stop after this many loop iterations
+    pub max_iterations: Option<u64>,
+    /// Stop once the brain holds more memories than this
+    pub max_memories: Option<usize>,
+    /// Iterations slower than this are counted as overruns in the CPL's health
+    pub max_iteration_ms: Option<u64>,
+}
+
+/// Lifecycle state of a CPL
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CPLLifecycleState {
+    /// Initialized, never started
+    Created,
+    Running,
+    /// Loop alive but skipping iterations
+    Paused,
+    Stopped,
+    /// Stopped itself after exceeding its resource budget
+    Exhausted,
+}
+
+/// Health snapshot of a CPL
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CPLHealth {
+    pub state: CPLLifecycleState,
+    pub iterations: u64,
+    pub started_at: Option<u64>,
+    pub last_iteration_at: Option<u64>,
+    /// Moving average of iteration time
+    pub avg_iteration_ms: f64,
+    /// Iterations slower than the budget's `max_iteration_ms`
+    pub budget_overruns: u64,
+    pub memories: usize,
+    /// Budget limit that stopped the loop
+    pub exhausted: Option<String>,
+    /// Running (or paused) without stalling, and within budget
+    pub healthy: bool,
+}
+
+#[derive(Debug, Default)]
+struct LoopStats {
+    started_at: Option<u64>,
+    last_iteration_at: Option<u64>,
+    avg_iteration_ms: f64,
+    budget_overruns: u64,
+    exhausted: Option<String>,
 }
 
 /// Arrow of Time configuration for CPL
@@ -187,6 +248,7 @@ impl Default for CPLConfig {
             working_memory_capacity: 7, // Miller's magic number
             enable_attention: true,
             enable_narrative: true,
+            narrative_schedule: NarrativeScheduleConfig::default(),
             enable_memory_bridge: true,
             enable_persistence: true,
             persistence_dir: Some("data/cpl".to_string()),
@@ -205,6 +267,8 @@ impl Default for CPLConfig {
             enable_audio: false, // Off by default
             audio_config: None,
             aot_config: None, // Arrow of Time disabled by default
+            genome: None,
+            budget: CPLResourceBudget::default(),
         }
     }
 }
@@ -238,6 +302,9 @@ pub struct ConsciencePersistentLoop {
     
     // State management
     is_running: Arc<RwLock<bool>>,
+    is_paused: Arc<RwLock<bool>>,
+    loop_generation: Arc<RwLock<u64>>, // Bumped on start so a stopped loop never outlives a restart
+    stats: Arc<RwLock<LoopStats>>,
     loop_count: Arc<RwLock<u64>>,
     last_persist: Arc<RwLock<u64>>,
     
@@ -255,10 +322,12 @@ pub enum CPLEvent {
     GlobalWorkspaceBroadcast { content_id: String, priority: f64 },
     MemoryConsolidated { memory_id: String },
     NarrativeUpdated { narrative_id: String },
+    NarrativeSummarized { memory_id: String, period_end: u64 },
     AttentionShifted { from: String, to: String },
     DreamingCycle { experiences_replayed: usize },
     BackgroundProcessCompleted { process_type: String },
     TalkingCricketAssessment { action_id: String, moral_score: f64, should_veto: bool },
+    ResourceConflict { conflict: ResourceConflict },
 }
 
 impl ConsciencePersistentLoop {
@@ -290,6 +359,9 @@ impl ConsciencePersistentLoop {
             temporal_accelerator: Arc::new(RwLock::new(None)),
             complexity_range_simulator: Arc::new(RwLock::new(None)),
             is_running: Arc::new(RwLock::new(false)),
+            is_paused: Arc::new(RwLock::new(false)),
+            loop_generation: Arc::new(RwLock::new(0)),
+            stats: Arc::new(RwLock::new(LoopStats::default())),
             loop_count: Arc::new(RwLock::new(0)),
             last_persist: Arc::new(RwLock::new(0)),
             event_sender: sender,
@@ -342,7 +414,7 @@ impl ConsciencePersistentLoop {
             let narrative = Arc::new(NarrativeGenerator::new(
                 self.brain.clone(),
                 self.event_sender.clone(),
-            ));
+            ).with_schedule(self.config.narrative_schedule.clone()));
             *self.narrative_generator.write() = Some(narrative);
             info!("Narrative Generator initialized");
         }
@@ -477,7 +549,10 @@ impl ConsciencePersistentLoop {
                 selection_pressure: 0.5,
                 enable_evolution: true,
             };
-            let genetic_system = Arc::new(GeneticSystem::new(genetic_config));
+            let genetic_system = Arc::new(match self.config.genome.clone() {
+                Some(genome) => GeneticSystem::from_genome(genome, genetic_config),
+                None => GeneticSystem::new(genetic_config),
+            });
             
             // Create trait calculator
             let trait_calculator = Arc::new(TraitCalculator::new(
@@ -547,15 +622,26 @@ impl ConsciencePersistentLoop {
         }
         
         *self.is_running.write() = true;
+        *self.is_paused.write() = false;
+        let generation = {
+            let mut generation = self.loop_generation.write();
+            *generation += 1;
+            *generation
+        };
+        {
+            let mut stats = self.stats.write();
+            stats.started_at = Some(now_secs());
+            stats.exhausted = None;
+        }
         info!("Starting CPL {}", self.id);
         
         let interval_duration = Duration::from_millis(self.config.loop_interval_ms);
-        let mut interval_timer = interval(interval_duration);
+        let interval_timer = interval(interval_duration);
         
         // Spawn the main loop
         let cpl_for_loop = self.clone();
         tokio::spawn(async move {
-            cpl_for_loop.run_loop(interval_timer).await;
+            cpl_for_loop.run_loop(interval_timer, generation).await;
         });
         
         Ok(())
@@ -564,6 +650,7 @@ impl ConsciencePersistentLoop {
     /// Stop the persistent loop
     pub async fn stop(&self) -> Result<()> {
         *self.is_running.write() = false;
+        *self.is_paused.write() = false;
         info!("Stopping CPL {}", self.id);
         
         // Persist state before stopping
@@ -576,6 +663,77 @@ impl ConsciencePersistentLoop {
         Ok(())
     }
     
+    /// Pause the loop; it keeps ticking but skips iterations until resumed
+    pub fn pause(&self) -> Result<()> {
+        if !*self.is_running.read() {
+            return Err(Error::Storage(format!("CPL {} is not running", self.id)));
+        }
+        *self.is_paused.write() = true;
+        info!("Paused CPL {}", self.id);
+        Ok(())
+    }
+
+    /// Resume a paused loop
+    pub fn resume(&self) -> Result<()> {
+        if !*self.is_running.read() {
+            return Err(Error::Storage(format!("CPL {} is not running", self.id)));
+        }
+        *self.is_paused.write() = false;
+        info!("Resumed CPL {}", self.id);
+        Ok(())
+    }
+
+    /// Check if CPL is paused
+    pub fn is_paused(&self) -> bool {
+        *self.is_paused.read()
+    }
+
+    /// Lifecycle state, loop timing and budget usage
+    pub fn health(&self) -> CPLHealth {
+        let stats = self.stats.read();
+        let running = *self.is_running.read();
+        let paused = *self.is_paused.read();
+        let state = match (running, paused) {
+            (true, true) => CPLLifecycleState::Paused,
+            (true, false) => CPLLifecycleState::Running,
+            _ if stats.exhausted.is_some() => CPLLifecycleState::Exhausted,
+            _ if stats.started_at.is_some() => CPLLifecycleState::Stopped,
+            _ => CPLLifecycleState::Created,
+        };
+        // A running loop that hasn't iterated for ten intervals (at least 5s) is stalled
+        let stall_secs = (self.config.loop_interval_ms.saturating_mul(10) / 1000).max(5);
+        let last_activity = stats.last_iteration_at.or(stats.started_at).unwrap_or(0);
+        let stalled = state == CPLLifecycleState::Running && now_secs().saturating_sub(last_activity) > stall_secs;
+        CPLHealth {
+            state,
+            iterations: *self.loop_count.read(),
+            started_at: stats.started_at,
+            last_iteration_at: stats.last_iteration_at,
+            avg_iteration_ms: stats.avg_iteration_ms,
+            budget_overruns: stats.budget_overruns,
+            memories: self.brain.memories.read().len(),
+            exhausted: stats.exhausted.clone(),
+            healthy: !stalled && stats.exhausted.is_none(),
+        }
+    }
+
+    /// Budget limit reached before the next iteration, if any
+    fn exhausted_budget(&self) -> Option<String> {
+        let budget = &self.config.budget;
+        if let Some(max) = budget.max_iterations {
+            if *self.loop_count.read() >= max {
+                return Some(format!("max_iterations {} reached", max));
+            }
+        }
+        if let Some(max) = budget.max_memories {
+            let memories = self.brain.memories.read().len();
+            if memories > max {
+                return Some(format!("{} memories exceed max_memories {}", memories, max));
+            }
+        }
+        None
+    }
+
     /// Attach Talking Cricket to this CPL
     pub async fn attach_talking_cricket(&self, tc: Arc<TalkingCricket>) -> Result<()> {
         // Set trait calculator and genetic system if available
@@ -612,9 +770,20 @@ impl ConsciencePersistentLoop {
     }
     
     /// Main loop execution
-    async fn run_loop(&self, mut interval_timer: tokio::time::Interval) {
-        while *self.is_running.read() {
+    async fn run_loop(&self, mut interval_timer: tokio::time::Interval, generation: u64) {
+        while *self.is_running.read() && *self.loop_generation.read() == generation {
             interval_timer.tick().await;
+            if *self.is_paused.read() || !*self.is_running.read() || *self.loop_generation.read() != generation {
+                continue;
+            }
+            
+            if let Some(reason) = self.exhausted_budget() {
+                warn!("CPL {} exhausted its budget ({}), stopping", self.id, reason);
+                self.stats.write().exhausted = Some(reason);
+                *self.is_running.write() = false;
+                break;
+            }
+            let iteration_start = std::time::Instant::now();
             
             let iteration = {
                 let mut count = self.loop_count.write();
@@ -762,6 +931,9 @@ impl ConsciencePersistentLoop {
                     if let Err(e) = narrative.update_narrative().await {
                         warn!("Narrative generator error: {}", e);
                     }
+                    if let Err(e) = narrative.maybe_summarize().await {
+                        warn!("Narrative summary error: {}", e);
+                    }
                 }
             }
             
@@ -778,6 +950,19 @@ impl ConsciencePersistentLoop {
                 }
             }
             
+            {
+                let elapsed_ms = iteration_start.elapsed().as_secs_f64() * 1000.0;
+                let mut stats = self.stats.write();
+                stats.avg_iteration_ms = match stats.last_iteration_at {
+                    Some(_) => stats.avg_iteration_ms * 0.9 + elapsed_ms * 0.1,
+                    None => elapsed_ms,
+                };
+                stats.last_iteration_at = Some(now);
+                if self.config.budget.max_iteration_ms.is_some_and(|max| elapsed_ms > max as f64) {
+                    stats.budget_overruns += 1;
+                }
+            }
+            
             // Periodic persistence
             if self.config.enable_persistence {
                 let should_persist = {
@@ -938,6 +1123,11 @@ impl ConsciencePersistentLoop {
     pub fn subscribe_events(&self) -> broadcast::Receiver<CPLEvent> {
         self.event_sender.subscribe()
     }
+
+    /// Sender for events surfaced to this CPL from outside its loop
+    pub(crate) fn event_sender(&self) -> broadcast::Sender<CPLEvent> {
+        self.event_sender.clone()
+    }
     
     /// Check if CPL is running
     pub fn is_running(&self) -> bool {
@@ -1132,6 +1322,13 @@ impl ConsciencePersistentLoop {
     }
 }
 
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
 /// CPL state for persistence
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct CPLState {
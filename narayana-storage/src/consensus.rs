@@ -1,75 +1,1114 @@
 // Advanced consensus algorithms for quantum-like synchronization
 
 use narayana_core::{Error, Result};
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
 use parking_lot::RwLock;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
 
-/// Raft consensus for strong consistency
+/// Raft timing and batching configuration
+#[derive(Debug, Clone)]
+pub struct RaftConfig {
+    pub election_timeout_min: Duration,
+    pub election_timeout_max: Duration,
+    pub heartbeat_interval: Duration,
+    pub max_entries_per_append: usize,
+}
+
+impl Default for RaftConfig {
+    fn default() -> Self {
+        Self {
+            election_timeout_min: Duration::from_millis(150),
+            election_timeout_max: Duration::from_millis(300),
+            heartbeat_interval: Duration::from_millis(50),
+            max_entries_per_append: 256,
+        }
+    }
+}
+
+/// Role of a Raft node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RaftRole {
+    Follower,
+    Candidate,
+    Leader,
+}
+
+/// Cluster membership. During a membership change the cluster runs in joint
+/// consensus, where every decision needs a majority of both the old and the
+/// new voter sets.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ClusterMembership {
+    Stable { voters: Vec<String> },
+    Joint { old: Vec<String>, new: Vec<String> },
+}
+
+impl ClusterMembership {
+    /// All nodes that take part in elections and replication
+    pub fn all_voters(&self) -> Vec<String> {
+        match self {
+            ClusterMembership::Stable { voters } => voters.clone(),
+            ClusterMembership::Joint { old, new } => {
+                let mut all = old.clone();
+                for node in new {
+                    if !all.contains(node) {
+                        all.push(node.clone());
+                    }
+                }
+                all
+            }
+        }
+    }
+
+    pub fn contains(&self, node_id: &str) -> bool {
+        self.all_voters().iter().any(|n| n == node_id)
+    }
+
+    pub fn is_joint(&self) -> bool {
+        matches!(self, ClusterMembership::Joint { .. })
+    }
+
+    /// Check whether the granted set forms a quorum (majority of each voter set)
+    pub fn has_quorum(&self, granted: &HashSet<String>) -> bool {
+        fn majority(voters: &[String], granted: &HashSet<String>) -> bool {
+            if voters.is_empty() {
+                return true;
+            }
+            let count = voters.iter().filter(|v| granted.contains(*v)).count();
+            count * 2 > voters.len()
+        }
+        match self {
+            ClusterMembership::Stable { voters } => majority(voters, granted),
+            ClusterMembership::Joint { old, new } => majority(old, granted) && majority(new, granted),
+        }
+    }
+}
+
+/// Replica role of a node for a shard or the catalog
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReplicaRole {
+    Primary,
+    Replica,
+    Learner,
+}
+
+/// Table catalog entry replicated through Raft
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableCatalogEntry {
+    pub table_id: u64,
+    pub name: String,
+    pub database: String,
+    pub schema_json: String,
+}
+
+/// Shard placement replicated through Raft
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShardPlacement {
+    pub shard_id: u64,
+    pub table_id: u64,
+    pub primary: String,
+    pub replicas: Vec<String>,
+    /// Inclusive range of row key hashes this shard owns
+    #[serde(default)]
+    pub key_start: u64,
+    #[serde(default = "max_key")]
+    pub key_end: u64,
+    /// Read-only replicas serving queries for this shard
+    #[serde(default)]
+    pub readers: Vec<String>,
+}
+
+fn max_key() -> u64 {
+    u64::MAX
+}
+
+impl ShardPlacement {
+    /// A shard owning every key of a table
+    pub fn new(shard_id: u64, table_id: u64, primary: String) -> Self {
+        Self {
+            shard_id,
+            table_id,
+            primary,
+            replicas: Vec::new(),
+            key_start: 0,
+            key_end: u64::MAX,
+            readers: Vec::new(),
+        }
+    }
+
+    pub fn contains_key(&self, key_hash: u64) -> bool {
+        (self.key_start..=self.key_end).contains(&key_hash)
+    }
+
+    /// Middle of the key range, or None when the range holds a single key
+    pub fn split_point(&self) -> Option<u64> {
+        (self.key_start < self.key_end).then(|| self.key_start + (self.key_end - self.key_start) / 2)
+    }
+}
+
+/// Metadata mutations applied by the Raft state machine
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum MetadataCommand {
+    CreateTable(TableCatalogEntry),
+    DropTable { table_id: u64 },
+    AssignShard(ShardPlacement),
+    RemoveShard { shard_id: u64 },
+    SetReplicaRole { node_id: String, role: ReplicaRole },
+    /// Replace a shard with two shards covering its key range
+    SplitShard { shard_id: u64, left: ShardPlacement, right: ShardPlacement },
+    /// Replace two adjacent shards with one covering both ranges
+    MergeShards { left_id: u64, right_id: u64, merged: ShardPlacement },
+    AddReader { shard_id: u64, node_id: String },
+    RemoveReader { shard_id: u64, node_id: String },
+    /// Base URL other nodes reach a node's API on
+    RegisterNode { node_id: String, address: String },
+}
+
+/// Payload of a Raft log entry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RaftCommand {
+    /// Appended by a new leader to commit entries from earlier terms
+    Noop,
+    Metadata(MetadataCommand),
+    Membership(ClusterMembership),
+}
+
+impl RaftCommand {
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        bincode::serialize(self)
+            .map_err(|e| Error::Serialization(format!("Failed to encode raft command: {}", e)))
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        bincode::deserialize(data)
+            .map_err(|e| Error::Deserialization(format!("Failed to decode raft command: {}", e)))
+    }
+}
+
+/// Cluster metadata state machine (table catalog, shard map, replica roles)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ClusterMetadata {
+    pub tables: HashMap<u64, TableCatalogEntry>,
+    pub shards: HashMap<u64, ShardPlacement>,
+    pub replica_roles: HashMap<String, ReplicaRole>,
+    /// Node addresses, by node id
+    #[serde(default)]
+    pub nodes: HashMap<String, String>,
+}
+
+impl ClusterMetadata {
+    fn apply(&mut self, command: &MetadataCommand) {
+        match command {
+            MetadataCommand::CreateTable(entry) => {
+                self.tables.insert(entry.table_id, entry.clone());
+            }
+            MetadataCommand::DropTable { table_id } => {
+                self.tables.remove(table_id);
+                self.shards.retain(|_, shard| shard.table_id != *table_id);
+            }
+            MetadataCommand::AssignShard(placement) => {
+                self.shards.insert(placement.shard_id, placement.clone());
+            }
+            MetadataCommand::RemoveShard { shard_id } => {
+                self.shards.remove(shard_id);
+            }
+            MetadataCommand::SetReplicaRole { node_id, role } => {
+                self.replica_roles.insert(node_id.clone(), *role);
+            }
+            MetadataCommand::SplitShard { shard_id, left, right } => {
+                // Proposals race with other changes; a shard already gone is left alone
+                if self.shards.remove(shard_id).is_some() {
+                    self.shards.insert(left.shard_id, left.clone());
+                    self.shards.insert(right.shard_id, right.clone());
+                }
+            }
+            MetadataCommand::MergeShards { left_id, right_id, merged } => {
+                if self.shards.contains_key(left_id) && self.shards.contains_key(right_id) {
+                    self.shards.remove(left_id);
+                    self.shards.remove(right_id);
+                    self.shards.insert(merged.shard_id, merged.clone());
+                }
+            }
+            MetadataCommand::AddReader { shard_id, node_id } => {
+                if let Some(shard) = self.shards.get_mut(shard_id) {
+                    if !shard.readers.contains(node_id) {
+                        shard.readers.push(node_id.clone());
+                    }
+                }
+            }
+            MetadataCommand::RemoveReader { shard_id, node_id } => {
+                if let Some(shard) = self.shards.get_mut(shard_id) {
+                    shard.readers.retain(|reader| reader != node_id);
+                }
+            }
+            MetadataCommand::RegisterNode { node_id, address } => {
+                self.nodes.insert(node_id.clone(), address.clone());
+            }
+        }
+    }
+
+    /// The shard of a table owning a row key hash
+    pub fn shard_for_key(&self, table_id: u64, key_hash: u64) -> Option<&ShardPlacement> {
+        self.shards.values().find(|shard| shard.table_id == table_id && shard.contains_key(key_hash))
+    }
+
+    /// Shards of a table ordered by key range
+    pub fn table_shards(&self, table_id: u64) -> Vec<ShardPlacement> {
+        let mut shards: Vec<_> = self.shards.values().filter(|shard| shard.table_id == table_id).cloned().collect();
+        shards.sort_by_key(|shard| shard.key_start);
+        shards
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestVoteRequest {
+    pub term: u64,
+    pub candidate_id: String,
+    pub last_log_index: u64,
+    pub last_log_term: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestVoteResponse {
+    pub term: u64,
+    pub vote_granted: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppendEntriesRequest {
+    pub term: u64,
+    pub leader_id: String,
+    pub prev_log_index: u64,
+    pub prev_log_term: u64,
+    pub entries: Vec<ConsensusEntry>,
+    pub leader_commit: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppendEntriesResponse {
+    pub term: u64,
+    pub success: bool,
+    /// Highest log index known to match the leader (valid when success is true)
+    pub match_index: u64,
+}
+
+/// Transport used by Raft nodes to talk to their peers
+#[async_trait]
+pub trait RaftTransport: Send + Sync {
+    async fn request_vote(&self, target: &str, request: RequestVoteRequest) -> Result<RequestVoteResponse>;
+    async fn append_entries(&self, target: &str, request: AppendEntriesRequest) -> Result<AppendEntriesResponse>;
+
+    /// Learn where a node is reached; called for every `RegisterNode` entry
+    /// a node appends, recovers or receives
+    fn add_peer(&self, _node_id: &str, _address: &str) {}
+
+    /// Where a node is reached, if the transport knows
+    fn peer_address(&self, _node_id: &str) -> Option<String> {
+        None
+    }
+}
+
+/// Term, vote and log of a node as last persisted
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RaftPersistentState {
+    pub term: u64,
+    pub voted_for: Option<String>,
+    pub log: Vec<ConsensusEntry>,
+}
+
+/// Durable storage for a node's term, vote and log. Each change is written
+/// before the node acts on it: before a vote or an append is acknowledged and
+/// before a candidate or leader sends its first request.
+#[async_trait]
+pub trait RaftStorage: Send + Sync {
+    /// State written by earlier runs
+    async fn load(&self) -> Result<RaftPersistentState>;
+    async fn save_hard_state(&self, term: u64, voted_for: Option<&str>) -> Result<()>;
+    /// Entries appended after the current last entry
+    async fn append_entries(&self, entries: &[ConsensusEntry]) -> Result<()>;
+    /// Drop the entries from `from_index` on
+    async fn truncate_log(&self, from_index: u64) -> Result<()>;
+}
+
+/// In-process transport routing RPCs between nodes registered on the same network.
+/// Used for embedded clusters and tests; disconnected nodes simulate partitions.
+pub struct LocalRaftNetwork {
+    nodes: RwLock<HashMap<String, Weak<RaftConsensus>>>,
+    disconnected: RwLock<HashSet<String>>,
+}
+
+impl LocalRaftNetwork {
+    pub fn new() -> Self {
+        Self {
+            nodes: RwLock::new(HashMap::new()),
+            disconnected: RwLock::new(HashSet::new()),
+        }
+    }
+
+    pub fn register(&self, node: Arc<RaftConsensus>) {
+        self.nodes.write().insert(node.node_id().to_string(), Arc::downgrade(&node));
+    }
+
+    pub fn disconnect(&self, node_id: &str) {
+        self.disconnected.write().insert(node_id.to_string());
+    }
+
+    pub fn reconnect(&self, node_id: &str) {
+        self.disconnected.write().remove(node_id);
+    }
+
+    fn route(&self, target: &str) -> Result<Arc<RaftConsensus>> {
+        if self.disconnected.read().contains(target) {
+            return Err(Error::Storage(format!("Raft peer {} is unreachable", target)));
+        }
+        self.nodes.read().get(target).and_then(Weak::upgrade)
+            .ok_or_else(|| Error::Storage(format!("Raft peer {} is not registered", target)))
+    }
+}
+
+#[async_trait]
+impl RaftTransport for LocalRaftNetwork {
+    async fn request_vote(&self, target: &str, request: RequestVoteRequest) -> Result<RequestVoteResponse> {
+        if self.disconnected.read().contains(&request.candidate_id) {
+            return Err(Error::Storage(format!("Raft peer {} is unreachable", request.candidate_id)));
+        }
+        self.route(target)?.handle_request_vote(request).await
+    }
+
+    async fn append_entries(&self, target: &str, request: AppendEntriesRequest) -> Result<AppendEntriesResponse> {
+        if self.disconnected.read().contains(&request.leader_id) {
+            return Err(Error::Storage(format!("Raft peer {} is unreachable", request.leader_id)));
+        }
+        self.route(target)?.handle_append_entries(request).await
+    }
+}
+
+/// Snapshot of a node's Raft status
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RaftStatus {
+    pub node_id: String,
+    pub role: RaftRole,
+    pub term: u64,
+    pub leader_id: Option<String>,
+    pub commit_index: u64,
+    pub last_applied: u64,
+    pub last_log_index: u64,
+    pub membership: ClusterMembership,
+}
+
+struct RaftState {
+    role: RaftRole,
+    current_term: u64,
+    voted_for: Option<String>,
+    leader_id: Option<String>,
+    /// Log entries, index 1 is stored at position 0
+    log: Vec<ConsensusEntry>,
+    commit_index: u64,
+    last_applied: u64,
+    /// Latest membership in the log (takes effect as soon as it is appended)
+    membership: ClusterMembership,
+    /// Last committed membership, used to roll back on log truncation
+    committed_membership: ClusterMembership,
+    next_index: HashMap<String, u64>,
+    match_index: HashMap<String, u64>,
+    election_deadline: Instant,
+    last_heartbeat: Instant,
+}
+
+impl RaftState {
+    fn last_log_index(&self) -> u64 {
+        self.log.len() as u64
+    }
+
+    fn last_log_term(&self) -> u64 {
+        self.log.last().map(|e| e.term).unwrap_or(0)
+    }
+
+    fn term_at(&self, index: u64) -> Option<u64> {
+        if index == 0 {
+            return Some(0);
+        }
+        self.log.get((index - 1) as usize).map(|e| e.term)
+    }
+
+    /// Recompute the active membership from the log tail
+    fn refresh_membership(&mut self) {
+        let mut membership = self.committed_membership.clone();
+        for entry in self.log.iter().skip(self.commit_index as usize) {
+            if let Ok(RaftCommand::Membership(m)) = RaftCommand::decode(&entry.data) {
+                membership = m;
+            }
+        }
+        self.membership = membership;
+    }
+}
+
+/// Change to a node's term, vote or log, persisted before the node acts on it
+enum RaftWrite {
+    HardState { term: u64, voted_for: Option<String> },
+    Truncate { from_index: u64 },
+    Append(Vec<ConsensusEntry>),
+}
+
+/// What a follower does with an AppendEntries request, decided before it is persisted
+enum AppendOutcome {
+    /// The log doesn't hold the previous entry; carries the follower's hint
+    Mismatch(u64),
+    /// The request would truncate this committed entry
+    Refused(u64),
+    Accept { truncate_from: Option<u64>, entries: Vec<ConsensusEntry> },
+}
+
+/// Raft consensus for strong consistency of cluster metadata
 pub struct RaftConsensus {
     node_id: String,
+    config: RaftConfig,
     state: Arc<RwLock<RaftState>>,
-    peers: Arc<RwLock<Vec<String>>>,
-}
-
-#[derive(Debug, Clone)]
-enum RaftState {
-    Follower { leader: Option<String>, term: u64 },
-    Candidate { term: u64, votes: usize },
-    Leader { term: u64 },
+    metadata: Arc<RwLock<ClusterMetadata>>,
+    transport: Arc<dyn RaftTransport>,
+    /// Where term, vote and log are persisted; None keeps them in memory only
+    storage: Option<Arc<dyn RaftStorage>>,
+    /// Held while term, vote or log change and are persisted, so storage sees
+    /// the changes in the order they were made
+    persist_lock: tokio::sync::Mutex<()>,
 }
 
 impl RaftConsensus {
+    /// Create a single-node cluster with no peers, kept in memory only
     pub fn new(node_id: String) -> Self {
+        let voters = vec![node_id.clone()];
+        Self::with_config(node_id, voters, RaftConfig::default(), Arc::new(LocalRaftNetwork::new()))
+    }
+
+    /// Create a node that is part of the given initial voter set. Its term,
+    /// vote and log are kept in memory only; see `open` for a durable node.
+    pub fn with_config(
+        node_id: String,
+        initial_voters: Vec<String>,
+        config: RaftConfig,
+        transport: Arc<dyn RaftTransport>,
+    ) -> Self {
+        let membership = ClusterMembership::Stable { voters: initial_voters };
+        let now = Instant::now();
+        let deadline = now + Self::random_timeout(&config);
         Self {
             node_id,
-            state: Arc::new(RwLock::new(RaftState::Follower {
-                leader: None,
-                term: 0,
+            config,
+            state: Arc::new(RwLock::new(RaftState {
+                role: RaftRole::Follower,
+                current_term: 0,
+                voted_for: None,
+                leader_id: None,
+                log: Vec::new(),
+                commit_index: 0,
+                last_applied: 0,
+                membership: membership.clone(),
+                committed_membership: membership,
+                next_index: HashMap::new(),
+                match_index: HashMap::new(),
+                election_deadline: deadline,
+                last_heartbeat: now,
             })),
-            peers: Arc::new(RwLock::new(Vec::new())),
+            metadata: Arc::new(RwLock::new(ClusterMetadata::default())),
+            transport,
+            storage: None,
+            persist_lock: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Create a node whose term, vote and log are persisted to `storage`,
+    /// resuming from what earlier runs left there. Recovered entries are
+    /// applied to the metadata again once a leader commits them.
+    pub async fn open(
+        node_id: String,
+        initial_voters: Vec<String>,
+        config: RaftConfig,
+        transport: Arc<dyn RaftTransport>,
+        storage: Arc<dyn RaftStorage>,
+    ) -> Result<Self> {
+        let persisted = storage.load().await?;
+        let mut node = Self::with_config(node_id, initial_voters, config, transport);
+        node.learn_addresses(&persisted.log);
+        {
+            let mut state = node.state.write();
+            if !persisted.log.is_empty() {
+                info!(
+                    "Node {} recovered term {} and {} raft log entries",
+                    node.node_id, persisted.term, persisted.log.len()
+                );
+            }
+            state.current_term = persisted.term;
+            state.voted_for = persisted.voted_for;
+            state.log = persisted.log;
+            state.refresh_membership();
+        }
+        node.storage = Some(storage);
+        Ok(node)
+    }
+
+    fn random_timeout(config: &RaftConfig) -> Duration {
+        use rand::Rng;
+        let min = config.election_timeout_min.as_millis() as u64;
+        let max = (config.election_timeout_max.as_millis() as u64).max(min + 1);
+        Duration::from_millis(rand::thread_rng().gen_range(min..max))
+    }
+
+    pub fn node_id(&self) -> &str {
+        &self.node_id
+    }
+
+    pub fn is_leader(&self) -> bool {
+        self.state.read().role == RaftRole::Leader
+    }
+
+    pub fn leader_id(&self) -> Option<String> {
+        self.state.read().leader_id.clone()
+    }
+
+    pub fn status(&self) -> RaftStatus {
+        let state = self.state.read();
+        RaftStatus {
+            node_id: self.node_id.clone(),
+            role: state.role,
+            term: state.current_term,
+            leader_id: state.leader_id.clone(),
+            commit_index: state.commit_index,
+            last_applied: state.last_applied,
+            last_log_index: state.last_log_index(),
+            membership: state.membership.clone(),
+        }
+    }
+
+    /// Address of a node: from the transport, which knows the configured
+    /// peers, or else as registered through the log
+    pub fn peer_address(&self, node_id: &str) -> Option<String> {
+        self.transport.peer_address(node_id)
+            .or_else(|| self.metadata.read().nodes.get(node_id).cloned())
+    }
+
+    /// Snapshot of the replicated cluster metadata
+    pub fn metadata(&self) -> ClusterMetadata {
+        self.metadata.read().clone()
+    }
+
+    async fn persist(&self, writes: Vec<RaftWrite>) -> Result<()> {
+        let Some(storage) = &self.storage else {
+            return Ok(());
+        };
+        for write in writes {
+            match write {
+                RaftWrite::HardState { term, voted_for } => storage.save_hard_state(term, voted_for.as_deref()).await?,
+                RaftWrite::Truncate { from_index } => storage.truncate_log(from_index).await?,
+                RaftWrite::Append(entries) => storage.append_entries(&entries).await?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Hand the node addresses registered in `entries` to the transport
+    fn learn_addresses(&self, entries: &[ConsensusEntry]) {
+        for entry in entries {
+            if let Ok(RaftCommand::Metadata(MetadataCommand::RegisterNode { node_id, address })) =
+                RaftCommand::decode(&entry.data)
+            {
+                self.transport.add_peer(&node_id, &address);
+            }
+        }
+    }
+
+    /// Drive timers: start an election when the deadline passed, or send
+    /// heartbeats when leading. Call periodically (e.g. every 10ms).
+    pub async fn tick(&self) -> Result<()> {
+        let (role, election_due, heartbeat_due) = {
+            let state = self.state.read();
+            let now = Instant::now();
+            (
+                state.role,
+                now >= state.election_deadline,
+                now.duration_since(state.last_heartbeat) >= self.config.heartbeat_interval,
+            )
+        };
+        match role {
+            RaftRole::Leader if heartbeat_due => self.replicate().await,
+            RaftRole::Follower | RaftRole::Candidate if election_due => self.start_election().await,
+            _ => Ok(()),
+        }
+    }
+
+    /// Become a candidate and request votes from all voters
+    pub async fn start_election(&self) -> Result<()> {
+        let persist = self.persist_lock.lock().await;
+        let (request, peers, membership) = {
+            let mut state = self.state.write();
+            if !state.membership.contains(&self.node_id) {
+                // Removed nodes must not disrupt the cluster
+                state.election_deadline = Instant::now() + Self::random_timeout(&self.config);
+                return Ok(());
+            }
+            state.role = RaftRole::Candidate;
+            state.current_term += 1;
+            state.voted_for = Some(self.node_id.clone());
+            state.leader_id = None;
+            state.election_deadline = Instant::now() + Self::random_timeout(&self.config);
+            info!("Node {} starting election for term {}", self.node_id, state.current_term);
+            let peers: Vec<String> = state.membership.all_voters().into_iter()
+                .filter(|n| n != &self.node_id)
+                .collect();
+            (
+                RequestVoteRequest {
+                    term: state.current_term,
+                    candidate_id: self.node_id.clone(),
+                    last_log_index: state.last_log_index(),
+                    last_log_term: state.last_log_term(),
+                },
+                peers,
+                state.membership.clone(),
+            )
+        };
+        self.persist(vec![RaftWrite::HardState { term: request.term, voted_for: Some(self.node_id.clone()) }]).await?;
+        drop(persist);
+
+        let mut granted: HashSet<String> = HashSet::new();
+        granted.insert(self.node_id.clone());
+
+        // Ask every peer at once, so an unreachable one doesn't hold up the rest
+        let mut votes = tokio::task::JoinSet::new();
+        for peer in peers {
+            let transport = self.transport.clone();
+            let request = request.clone();
+            votes.spawn(async move {
+                let response = transport.request_vote(&peer, request).await;
+                (peer, response)
+            });
+        }
+        while !membership.has_quorum(&granted) {
+            let Some(joined) = votes.join_next().await else { break };
+            let Ok((peer, response)) = joined else { continue };
+            match response {
+                Ok(response) => {
+                    if response.term > request.term {
+                        return self.step_down(response.term).await;
+                    }
+                    if response.vote_granted {
+                        granted.insert(peer);
+                    }
+                }
+                Err(e) => debug!("Vote request to {} failed: {}", peer, e),
+            }
+        }
+
+        if membership.has_quorum(&granted) && self.become_leader(request.term).await? {
+            self.replicate().await?;
+        }
+        Ok(())
+    }
+
+    /// Take the leadership won in `term`; false when the node has moved on since
+    async fn become_leader(&self, term: u64) -> Result<bool> {
+        let _persist = self.persist_lock.lock().await;
+        let noop = {
+            let mut state = self.state.write();
+            if state.role != RaftRole::Candidate || state.current_term != term {
+                return Ok(false);
+            }
+            state.role = RaftRole::Leader;
+            state.leader_id = Some(self.node_id.clone());
+            let next = state.last_log_index() + 1;
+            let voters = state.membership.all_voters();
+            state.next_index = voters.iter().map(|n| (n.clone(), next)).collect();
+            state.match_index = voters.iter().map(|n| (n.clone(), 0)).collect();
+            // Commit a no-op so entries from previous terms become committed
+            let noop = ConsensusEntry { term, index: next, data: RaftCommand::Noop.encode()? };
+            state.log.push(noop.clone());
+            noop
+        };
+        info!("Node {} became leader for term {}", self.node_id, term);
+        self.persist(vec![RaftWrite::Append(vec![noop])]).await?;
+        Ok(true)
+    }
+
+    async fn step_down(&self, term: u64) -> Result<()> {
+        let _persist = self.persist_lock.lock().await;
+        let write = {
+            let mut state = self.state.write();
+            let mut write = None;
+            if term > state.current_term {
+                state.current_term = term;
+                state.voted_for = None;
+                write = Some(RaftWrite::HardState { term, voted_for: None });
+            }
+            if state.role != RaftRole::Follower {
+                debug!("Node {} stepping down in term {}", self.node_id, state.current_term);
+            }
+            state.role = RaftRole::Follower;
+            state.election_deadline = Instant::now() + Self::random_timeout(&self.config);
+            write
+        };
+        self.persist(write.into_iter().collect()).await
+    }
+
+    /// Propose a metadata change; returns the log index on success (leader only)
+    pub async fn propose(&self, command: MetadataCommand) -> Result<u64> {
+        let index = self.append_local(RaftCommand::Metadata(command)).await?;
+        self.replicate().await?;
+        Ok(index)
+    }
+
+    /// Append an entry to the leader's log; it is persisted before it can be
+    /// replicated
+    async fn append_local(&self, command: RaftCommand) -> Result<u64> {
+        let data = command.encode()?;
+        let _persist = self.persist_lock.lock().await;
+        let entry = {
+            let state = self.state.read();
+            if state.role != RaftRole::Leader {
+                return Err(Error::Storage(format!(
+                    "Node {} is not the leader (leader: {:?})",
+                    self.node_id, state.leader_id
+                )));
+            }
+            ConsensusEntry { term: state.current_term, index: state.last_log_index() + 1, data }
+        };
+        // The log only changes under the persist lock, so the index stays free
+        self.persist(vec![RaftWrite::Append(vec![entry.clone()])]).await?;
+        self.learn_addresses(std::slice::from_ref(&entry));
+
+        let index = entry.index;
+        let mut state = self.state.write();
+        state.log.push(entry);
+        if let RaftCommand::Membership(membership) = command {
+            // Configuration entries take effect as soon as they are appended
+            for node in membership.all_voters() {
+                state.next_index.entry(node.clone()).or_insert(index);
+                state.match_index.entry(node).or_insert(0);
+            }
+            state.membership = membership;
+        }
+        Ok(index)
+    }
+
+    /// Add a voter reachable at `address`. The address is replicated first,
+    /// so every node can reach the new voter, then the voter is added.
+    pub async fn join(&self, node_id: String, address: String) -> Result<()> {
+        self.propose(MetadataCommand::RegisterNode { node_id: node_id.clone(), address }).await?;
+        self.add_node(node_id).await
+    }
+
+    /// Add a voter using joint consensus (C_old -> C_old,new -> C_new)
+    pub async fn add_node(&self, node_id: String) -> Result<()> {
+        let current = self.stable_voters()?;
+        if current.contains(&node_id) {
+            return Ok(());
+        }
+        let mut new = current.clone();
+        new.push(node_id);
+        self.change_membership(current, new).await
+    }
+
+    /// Remove a voter using joint consensus
+    pub async fn remove_node(&self, node_id: &str) -> Result<()> {
+        let current = self.stable_voters()?;
+        if !current.iter().any(|n| n == node_id) {
+            return Ok(());
         }
+        let new: Vec<String> = current.iter().filter(|n| *n != node_id).cloned().collect();
+        if new.is_empty() {
+            return Err(Error::Storage("Cannot remove the last voter from the cluster".to_string()));
+        }
+        self.change_membership(current, new).await
+    }
+
+    fn stable_voters(&self) -> Result<Vec<String>> {
+        match &self.state.read().membership {
+            ClusterMembership::Stable { voters } => Ok(voters.clone()),
+            ClusterMembership::Joint { .. } => Err(Error::Storage(
+                "A membership change is already in progress".to_string(),
+            )),
+        }
+    }
+
+    async fn change_membership(&self, old: Vec<String>, new: Vec<String>) -> Result<()> {
+        info!("Node {} starting membership change {:?} -> {:?}", self.node_id, old, new);
+        self.append_local(RaftCommand::Membership(ClusterMembership::Joint { old, new })).await?;
+        // The final configuration is appended once the joint entry commits (see apply_committed)
+        self.replicate().await
+    }
+
+    /// Send AppendEntries to all peers and advance the commit index
+    pub async fn replicate(&self) -> Result<()> {
+        let (term, requests) = {
+            let mut state = self.state.write();
+            if state.role != RaftRole::Leader {
+                return Ok(());
+            }
+            state.last_heartbeat = Instant::now();
+            let last = state.last_log_index();
+            state.match_index.insert(self.node_id.clone(), last);
+            let mut requests = Vec::new();
+            for peer in state.membership.all_voters() {
+                if peer == self.node_id {
+                    continue;
+                }
+                let next = *state.next_index.get(&peer).unwrap_or(&(last + 1));
+                let prev_log_index = next.saturating_sub(1);
+                let prev_log_term = state.term_at(prev_log_index).unwrap_or(0);
+                let end = (prev_log_index as usize + self.config.max_entries_per_append).min(state.log.len());
+                let entries = state.log[prev_log_index as usize..end].to_vec();
+                requests.push((peer, AppendEntriesRequest {
+                    term: state.current_term,
+                    leader_id: self.node_id.clone(),
+                    prev_log_index,
+                    prev_log_term,
+                    entries,
+                    leader_commit: state.commit_index,
+                }));
+            }
+            (state.current_term, requests)
+        };
+
+        let mut sends = tokio::task::JoinSet::new();
+        for (peer, request) in requests {
+            let transport = self.transport.clone();
+            sends.spawn(async move {
+                let sent = request.prev_log_index + request.entries.len() as u64;
+                let response = transport.append_entries(&peer, request).await;
+                (peer, sent, response)
+            });
+        }
+        while let Some(joined) = sends.join_next().await {
+            let Ok((peer, sent, response)) = joined else { continue };
+            match response {
+                Ok(response) => {
+                    if response.term > term {
+                        return self.step_down(response.term).await;
+                    }
+                    let mut state = self.state.write();
+                    if response.success {
+                        state.match_index.insert(peer.clone(), sent);
+                        state.next_index.insert(peer, sent + 1);
+                    } else {
+                        // Back off using the follower's hint and retry on the next round
+                        let next = state.next_index.entry(peer).or_insert(1);
+                        *next = (*next).saturating_sub(1).min(response.match_index + 1).max(1);
+                    }
+                }
+                Err(e) => debug!("AppendEntries to {} failed: {}", peer, e),
+            }
+        }
+
+        self.advance_commit_index();
+        self.apply_committed().await
     }
 
-    /// Request vote (election)
-    pub async fn request_vote(&self, term: u64) -> bool {
+    fn advance_commit_index(&self) {
         let mut state = self.state.write();
-        match *state {
-            RaftState::Follower { term: t, .. } | RaftState::Candidate { term: t, .. } => {
-                if term > t {
-                    *state = RaftState::Follower {
-                        leader: None,
-                        term,
-                    };
-                    return true;
+        if state.role != RaftRole::Leader {
+            return;
+        }
+        let mut candidate = state.last_log_index();
+        while candidate > state.commit_index {
+            // Only entries from the current term are committed by counting replicas
+            if state.term_at(candidate) == Some(state.current_term) {
+                let replicated: HashSet<String> = state.match_index.iter()
+                    .filter(|(_, m)| **m >= candidate)
+                    .map(|(n, _)| n.clone())
+                    .collect();
+                if state.membership.has_quorum(&replicated) {
+                    state.commit_index = candidate;
+                    break;
                 }
             }
-            _ => {}
+            candidate -= 1;
         }
-        false
     }
 
-    /// Append entries (replication)
-    pub async fn append_entries(&self, term: u64, entries: Vec<ConsensusEntry>) -> bool {
+    /// Apply newly committed entries to the metadata state machine
+    async fn apply_committed(&self) -> Result<()> {
+        let mut finalize: Option<Vec<String>> = None;
+        {
+            let mut state = self.state.write();
+            while state.last_applied < state.commit_index {
+                state.last_applied += 1;
+                let entry = state.log[(state.last_applied - 1) as usize].clone();
+                match RaftCommand::decode(&entry.data)? {
+                    RaftCommand::Noop => {}
+                    RaftCommand::Metadata(command) => self.metadata.write().apply(&command),
+                    RaftCommand::Membership(membership) => {
+                        state.committed_membership = membership.clone();
+                        match membership {
+                            ClusterMembership::Joint { new, .. } => {
+                                if state.role == RaftRole::Leader && state.membership.is_joint() {
+                                    finalize = Some(new);
+                                }
+                            }
+                            ClusterMembership::Stable { voters } => {
+                                if state.role == RaftRole::Leader && !voters.contains(&self.node_id) {
+                                    info!("Leader {} removed from cluster, stepping down", self.node_id);
+                                    state.role = RaftRole::Follower;
+                                    state.leader_id = None;
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        if let Some(voters) = finalize {
+            self.append_local(RaftCommand::Membership(ClusterMembership::Stable { voters })).await?;
+            Box::pin(self.replicate()).await?;
+        }
+        Ok(())
+    }
+
+    /// Handle an incoming RequestVote RPC; a granted vote is persisted before
+    /// it is returned
+    pub async fn handle_request_vote(&self, request: RequestVoteRequest) -> Result<RequestVoteResponse> {
+        let _persist = self.persist_lock.lock().await;
+        let (term, voted_for, granted, changed) = {
+            let mut state = self.state.write();
+            let (mut term, mut voted_for) = (state.current_term, state.voted_for.clone());
+            if request.term > term {
+                term = request.term;
+                voted_for = None;
+                state.role = RaftRole::Follower;
+            }
+            if request.term < term {
+                return Ok(RequestVoteResponse { term, vote_granted: false });
+            }
+            let log_ok = request.last_log_term > state.last_log_term()
+                || (request.last_log_term == state.last_log_term()
+                    && request.last_log_index >= state.last_log_index());
+            let can_vote = voted_for.is_none() || voted_for.as_deref() == Some(request.candidate_id.as_str());
+            let granted = log_ok && can_vote;
+            if granted {
+                voted_for = Some(request.candidate_id.clone());
+            }
+            let changed = term != state.current_term || voted_for != state.voted_for;
+            (term, voted_for, granted, changed)
+        };
+        if changed {
+            self.persist(vec![RaftWrite::HardState { term, voted_for: voted_for.clone() }]).await?;
+        }
         let mut state = self.state.write();
-        match *state {
-            RaftState::Follower { term: t, .. } => {
-                if term >= t {
-                    *state = RaftState::Follower {
-                        leader: None,
-                        term,
-                    };
-                    return true;
+        state.current_term = term;
+        state.voted_for = voted_for;
+        if granted {
+            state.election_deadline = Instant::now() + Self::random_timeout(&self.config);
+        }
+        Ok(RequestVoteResponse { term, vote_granted: granted })
+    }
+
+    /// Handle an incoming AppendEntries RPC (replication and heartbeats); new
+    /// entries are persisted before they are acknowledged
+    pub async fn handle_append_entries(&self, request: AppendEntriesRequest) -> Result<AppendEntriesResponse> {
+        let _persist = self.persist_lock.lock().await;
+        let last_new_index = request.prev_log_index + request.entries.len() as u64;
+        let mut writes = Vec::new();
+        let outcome = {
+            let mut state = self.state.write();
+            if request.term < state.current_term {
+                return Ok(AppendEntriesResponse { term: state.current_term, success: false, match_index: 0 });
+            }
+            if request.term > state.current_term {
+                writes.push(RaftWrite::HardState { term: request.term, voted_for: None });
+            }
+            state.role = RaftRole::Follower;
+            state.leader_id = Some(request.leader_id.clone());
+            state.election_deadline = Instant::now() + Self::random_timeout(&self.config);
+
+            if state.term_at(request.prev_log_index) != Some(request.prev_log_term) {
+                AppendOutcome::Mismatch(state.last_log_index().min(request.prev_log_index.saturating_sub(1)))
+            } else {
+                let mut truncate_from = None;
+                let mut entries = Vec::new();
+                let mut refused = None;
+                for entry in request.entries {
+                    if entries.is_empty() {
+                        match state.term_at(entry.index) {
+                            Some(term) if term == entry.term => continue,
+                            // Conflict: drop the entry and everything after it
+                            Some(_) if entry.index <= state.commit_index => {
+                                refused = Some(entry.index);
+                                break;
+                            }
+                            Some(_) => truncate_from = Some(entry.index),
+                            None => {}
+                        }
+                    }
+                    entries.push(entry);
                 }
+                match refused {
+                    Some(index) => AppendOutcome::Refused(index),
+                    None => {
+                        if let Some(from_index) = truncate_from {
+                            writes.push(RaftWrite::Truncate { from_index });
+                        }
+                        if !entries.is_empty() {
+                            writes.push(RaftWrite::Append(entries.clone()));
+                        }
+                        AppendOutcome::Accept { truncate_from, entries }
+                    }
+                }
+            }
+        };
+        self.persist(writes).await?;
+
+        let mut state = self.state.write();
+        if request.term > state.current_term {
+            state.current_term = request.term;
+            state.voted_for = None;
+        }
+        let (truncate_from, entries) = match outcome {
+            AppendOutcome::Mismatch(hint) => {
+                return Ok(AppendEntriesResponse { term: state.current_term, success: false, match_index: hint });
+            }
+            AppendOutcome::Refused(index) => {
+                warn!("Refusing to truncate committed entry {} on {}", index, self.node_id);
+                return Ok(AppendEntriesResponse { term: state.current_term, success: false, match_index: state.commit_index });
             }
-            _ => {}
+            AppendOutcome::Accept { truncate_from, entries } => (truncate_from, entries),
+        };
+        if let Some(from_index) = truncate_from {
+            state.log.truncate((from_index - 1) as usize);
         }
-        false
+        let membership_changed = truncate_from.is_some()
+            || entries.iter().any(|entry| {
+                RaftCommand::decode(&entry.data).map(|c| matches!(c, RaftCommand::Membership(_))).unwrap_or(false)
+            });
+        self.learn_addresses(&entries);
+        state.log.extend(entries);
+        if membership_changed {
+            state.refresh_membership();
+        }
+
+        if request.leader_commit > state.commit_index {
+            state.commit_index = request.leader_commit.min(last_new_index);
+        }
+
+        // Apply committed entries on followers
+        while state.last_applied < state.commit_index {
+            state.last_applied += 1;
+            let entry = state.log[(state.last_applied - 1) as usize].clone();
+            match RaftCommand::decode(&entry.data) {
+                Ok(RaftCommand::Metadata(command)) => self.metadata.write().apply(&command),
+                Ok(RaftCommand::Membership(membership)) => state.committed_membership = membership,
+                Ok(RaftCommand::Noop) => {}
+                Err(e) => warn!("Skipping undecodable raft entry {}: {}", entry.index, e),
+            }
+        }
+
+        Ok(AppendEntriesResponse {
+            term: state.current_term,
+            success: true,
+            match_index: last_new_index,
+        })
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ConsensusEntry {
     pub term: u64,
     pub index: u64,
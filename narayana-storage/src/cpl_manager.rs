@@ -1,21 +1,71 @@
 // CPL Manager - Multi-instance support
 // Manages multiple CPL instances with isolated state
 
+use crate::attention_router::{ArbitrationConfig, ResourceArbiter};
 use crate::cognitive::CognitiveBrain;
-use crate::conscience_persistent_loop::{ConsciencePersistentLoop, CPLConfig, CPLEvent};
+use crate::conscience_persistent_loop::{ConsciencePersistentLoop, CPLConfig, CPLEvent, CPLHealth};
+use async_trait::async_trait;
 use narayana_core::{Error, Result};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 use parking_lot::RwLock;
 use std::collections::HashMap;
 use tracing::{info, warn, error, debug};
 use uuid::Uuid;
 
+/// Bridge between a CPL and the outside world (e.g. a world broker and its protocol adapters)
+///
+/// Attached adapters are started and stopped with their CPL; pausing the CPL leaves them running.
+#[async_trait]
+pub trait CPLWorldAdapter: Send + Sync {
+    fn name(&self) -> String;
+    async fn start(&self) -> Result<()>;
+    async fn stop(&self) -> Result<()>;
+    fn is_healthy(&self) -> bool {
+        true
+    }
+}
+
+/// A CPL with the metadata and adapters the manager keeps for it
+struct ManagedCPL {
+    cpl: Arc<ConsciencePersistentLoop>,
+    name: Option<String>,
+    created_at: u64,
+    world_adapters: RwLock<Vec<Arc<dyn CPLWorldAdapter>>>,
+}
+
+impl ManagedCPL {
+    fn adapters(&self) -> Vec<Arc<dyn CPLWorldAdapter>> {
+        self.world_adapters.read().clone()
+    }
+}
+
+/// Health of an attached world adapter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorldAdapterStatus {
+    pub name: String,
+    pub healthy: bool,
+}
+
+/// CPL as listed by the manager
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CPLStatus {
+    pub cpl_id: String,
+    pub name: Option<String>,
+    pub created_at: u64,
+    pub health: CPLHealth,
+    pub world_adapters: Vec<WorldAdapterStatus>,
+    /// Loop healthy and every attached adapter healthy
+    pub healthy: bool,
+}
+
 /// CPL Manager - Manages multiple CPL instances
 pub struct CPLManager {
-    cpls: Arc<RwLock<HashMap<String, Arc<ConsciencePersistentLoop>>>>,
+    cpls: Arc<RwLock<HashMap<String, Arc<ManagedCPL>>>>,
     shared_brain: Option<Arc<CognitiveBrain>>, // Optional shared brain
     default_config: CPLConfig,
+    arbiter: Arc<ResourceArbiter>, // Actuator/sensor arbitration across CPLs on one body
 }
 
 impl CPLManager {
@@ -25,9 +75,20 @@ impl CPLManager {
             cpls: Arc::new(RwLock::new(HashMap::new())),
             shared_brain: None,
             default_config,
+            arbiter: Arc::new(ResourceArbiter::new(ArbitrationConfig::default())),
         }
     }
     
+    /// Set arbitration rules for capabilities shared by the CPLs
+    pub fn set_arbitration_config(&mut self, config: ArbitrationConfig) {
+        self.arbiter = Arc::new(ResourceArbiter::new(config));
+    }
+
+    /// Arbiter for actuator leases and sensor access across CPLs
+    pub fn arbiter(&self) -> Arc<ResourceArbiter> {
+        self.arbiter.clone()
+    }
+    
     /// Set shared brain (optional - CPLs can share or have separate brains)
     pub fn set_shared_brain(&mut self, brain: Arc<CognitiveBrain>) {
         self.shared_brain = Some(brain);
@@ -35,6 +96,18 @@ impl CPLManager {
     
     /// Spawn a new CPL instance
     pub async fn spawn_cpl(&self, config: Option<CPLConfig>) -> Result<String> {
+        self.create_cpl(None, config).await
+    }
+
+    /// Create and initialize a CPL, optionally under a unique name
+    ///
+    /// The config carries the CPL's trait genome and resource budget.
+    pub async fn create_cpl(&self, name: Option<String>, config: Option<CPLConfig>) -> Result<String> {
+        if let Some(name) = &name {
+            if self.cpls.read().values().any(|managed| managed.name.as_deref() == Some(name.as_str())) {
+                return Err(Error::Storage(format!("CPL named {} already exists", name)));
+            }
+        }
         let cpl_id = Uuid::new_v4().to_string();
         let config = config.unwrap_or_else(|| self.default_config.clone());
         
@@ -55,57 +128,133 @@ impl CPLManager {
         }
         
         // Store
-        self.cpls.write().insert(cpl_id.clone(), cpl);
+        self.arbiter.attach_cpl(&cpl_id, cpl.event_sender());
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        self.cpls.write().insert(cpl_id.clone(), Arc::new(ManagedCPL {
+            cpl,
+            name,
+            created_at,
+            world_adapters: RwLock::new(Vec::new()),
+        }));
         
         info!("Spawned CPL {}", cpl_id);
         Ok(cpl_id)
     }
     
-    /// Start a CPL instance
+    /// Clone the managed entry so no lock is held across await
+    fn managed(&self, cpl_id: &str) -> Result<Arc<ManagedCPL>> {
+        self.cpls.read().get(cpl_id).cloned()
+            .ok_or_else(|| Error::Storage(format!("CPL {} not found", cpl_id)))
+    }
+
+    /// Start a CPL instance and its world adapters
     pub async fn start_cpl(&self, cpl_id: &str) -> Result<()> {
-        // Clone the Arc before dropping the lock to avoid holding lock across await
-        let cpl = {
-            let cpls = self.cpls.read();
-            cpls.get(cpl_id).cloned()
-        };
-        
-        if let Some(cpl) = cpl {
-            cpl.start().await
-        } else {
-            Err(Error::Storage(format!("CPL {} not found", cpl_id)))
+        let managed = self.managed(cpl_id)?;
+        managed.cpl.clone().start().await?;
+        for adapter in managed.adapters() {
+            if let Err(e) = adapter.start().await {
+                warn!("Failed to start world adapter {} of CPL {}: {}", adapter.name(), cpl_id, e);
+            }
         }
+        Ok(())
     }
     
-    /// Stop a CPL instance
+    /// Stop a CPL instance and its world adapters
     pub async fn stop_cpl(&self, cpl_id: &str) -> Result<()> {
-        // Clone the Arc before dropping the lock to avoid holding lock across await
-        let cpl = {
-            let cpls = self.cpls.read();
-            cpls.get(cpl_id).cloned()
+        let managed = self.managed(cpl_id)?;
+        for adapter in managed.adapters() {
+            if let Err(e) = adapter.stop().await {
+                warn!("Failed to stop world adapter {} of CPL {}: {}", adapter.name(), cpl_id, e);
+            }
+        }
+        managed.cpl.stop().await
+    }
+
+    /// Pause a running CPL's loop
+    pub fn pause_cpl(&self, cpl_id: &str) -> Result<()> {
+        self.managed(cpl_id)?.cpl.pause()
+    }
+
+    /// Resume a paused CPL's loop
+    pub fn resume_cpl(&self, cpl_id: &str) -> Result<()> {
+        self.managed(cpl_id)?.cpl.resume()
+    }
+
+    /// Attach a world adapter, starting it right away if the CPL is running
+    pub async fn attach_world_adapter(&self, cpl_id: &str, adapter: Arc<dyn CPLWorldAdapter>) -> Result<()> {
+        let managed = self.managed(cpl_id)?;
+        let name = adapter.name();
+        {
+            let mut adapters = managed.world_adapters.write();
+            if adapters.iter().any(|attached| attached.name() == name) {
+                return Err(Error::Storage(format!("World adapter {} already attached to CPL {}", name, cpl_id)));
+            }
+            adapters.push(adapter.clone());
+        }
+        if managed.cpl.is_running() {
+            adapter.start().await?;
+        }
+        info!("Attached world adapter {} to CPL {}", name, cpl_id);
+        Ok(())
+    }
+
+    /// Detach a world adapter, stopping it if the CPL is running
+    pub async fn detach_world_adapter(&self, cpl_id: &str, name: &str) -> Result<()> {
+        let managed = self.managed(cpl_id)?;
+        let adapter = {
+            let mut adapters = managed.world_adapters.write();
+            let index = adapters.iter().position(|attached| attached.name() == name)
+                .ok_or_else(|| Error::Storage(format!("World adapter {} not attached to CPL {}", name, cpl_id)))?;
+            adapters.remove(index)
         };
-        
-        if let Some(cpl) = cpl {
-            cpl.stop().await
-        } else {
-            Err(Error::Storage(format!("CPL {} not found", cpl_id)))
+        if managed.cpl.is_running() {
+            adapter.stop().await?;
+        }
+        info!("Detached world adapter {} from CPL {}", name, cpl_id);
+        Ok(())
+    }
+
+    /// Lifecycle state, health and adapters of a CPL
+    pub fn status(&self, cpl_id: &str) -> Option<CPLStatus> {
+        let managed = self.cpls.read().get(cpl_id).cloned()?;
+        Some(Self::status_of(cpl_id, &managed))
+    }
+
+    /// Status of every CPL, oldest first
+    pub fn list_status(&self) -> Vec<CPLStatus> {
+        let cpls: Vec<(String, Arc<ManagedCPL>)> = self.cpls.read().iter()
+            .map(|(id, managed)| (id.clone(), managed.clone()))
+            .collect();
+        let mut statuses: Vec<CPLStatus> = cpls.iter().map(|(id, managed)| Self::status_of(id, managed)).collect();
+        statuses.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.cpl_id.cmp(&b.cpl_id)));
+        statuses
+    }
+
+    fn status_of(cpl_id: &str, managed: &ManagedCPL) -> CPLStatus {
+        let health = managed.cpl.health();
+        let world_adapters: Vec<WorldAdapterStatus> = managed.adapters().iter()
+            .map(|adapter| WorldAdapterStatus { name: adapter.name(), healthy: adapter.is_healthy() })
+            .collect();
+        CPLStatus {
+            cpl_id: cpl_id.to_string(),
+            name: managed.name.clone(),
+            created_at: managed.created_at,
+            healthy: health.healthy && world_adapters.iter().all(|adapter| adapter.healthy),
+            health,
+            world_adapters,
         }
     }
     
-    /// Remove a CPL instance
+    /// Remove (destroy) a CPL instance, stopping it and its world adapters first
     pub async fn remove_cpl(&self, cpl_id: &str) -> Result<()> {
-        // Clone the Arc before dropping the lock to avoid holding lock across await
-        let (cpl, was_running) = {
-            let cpls = self.cpls.read();
-            if let Some(cpl) = cpls.get(cpl_id) {
-                (cpl.clone(), cpl.is_running())
-            } else {
-                return Err(Error::Storage(format!("CPL {} not found", cpl_id)));
-            }
-        };
+        let managed = self.managed(cpl_id)?;
         
         // Stop before removing (if running)
-        if was_running {
-            if let Err(e) = cpl.stop().await {
+        if managed.cpl.is_running() {
+            if let Err(e) = self.stop_cpl(cpl_id).await {
                 warn!("Failed to stop CPL before removal: {}", e);
             }
         }
@@ -113,6 +262,7 @@ impl CPLManager {
         // Now remove from the map
         let mut cpls = self.cpls.write();
         if cpls.remove(cpl_id).is_some() {
+            self.arbiter.detach_cpl(cpl_id);
             info!("Removed CPL {}", cpl_id);
             Ok(())
         } else {
@@ -123,7 +273,7 @@ impl CPLManager {
     
     /// Get a CPL instance
     pub fn get_cpl(&self, cpl_id: &str) -> Option<Arc<ConsciencePersistentLoop>> {
-        self.cpls.read().get(cpl_id).cloned()
+        self.cpls.read().get(cpl_id).map(|managed| managed.cpl.clone())
     }
     
     /// List all CPL IDs
@@ -138,15 +288,9 @@ impl CPLManager {
     
     /// Start all CPLs
     pub async fn start_all(&self) -> Result<()> {
-        // Clone all CPLs before dropping the lock to avoid holding lock across await
-        let cpls_to_start: Vec<(String, Arc<ConsciencePersistentLoop>)> = {
-            let cpls = self.cpls.read();
-            cpls.iter().map(|(id, cpl)| (id.clone(), cpl.clone())).collect()
-        };
-        
         let mut errors = Vec::new();
-        for (id, cpl) in cpls_to_start {
-            if let Err(e) = cpl.start().await {
+        for id in self.list_cpls() {
+            if let Err(e) = self.start_cpl(&id).await {
                 error!("Failed to start CPL {}: {}", id, e);
                 errors.push((id, e));
             }
@@ -161,15 +305,9 @@ impl CPLManager {
     
     /// Stop all CPLs
     pub async fn stop_all(&self) -> Result<()> {
-        // Clone all CPLs before dropping the lock to avoid holding lock across await
-        let cpls_to_stop: Vec<(String, Arc<ConsciencePersistentLoop>)> = {
-            let cpls = self.cpls.read();
-            cpls.iter().map(|(id, cpl)| (id.clone(), cpl.clone())).collect()
-        };
-        
         let mut errors = Vec::new();
-        for (id, cpl) in cpls_to_stop {
-            if let Err(e) = cpl.stop().await {
+        for id in self.list_cpls() {
+            if let Err(e) = self.stop_cpl(&id).await {
                 error!("Failed to stop CPL {}: {}", id, e);
                 errors.push((id, e));
             }
@@ -196,3 +334,105 @@ impl CPLManager {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conscience_persistent_loop::{CPLLifecycleState, CPLResourceBudget};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::Duration;
+
+    struct FlagAdapter {
+        running: AtomicBool,
+    }
+
+    #[async_trait]
+    impl CPLWorldAdapter for FlagAdapter {
+        fn name(&self) -> String {
+            "flag".to_string()
+        }
+        async fn start(&self) -> Result<()> {
+            self.running.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+        async fn stop(&self) -> Result<()> {
+            self.running.store(false, Ordering::SeqCst);
+            Ok(())
+        }
+        fn is_healthy(&self) -> bool {
+            self.running.load(Ordering::SeqCst)
+        }
+    }
+
+    fn config() -> CPLConfig {
+        CPLConfig {
+            loop_interval_ms: 5,
+            enable_persistence: false,
+            persistence_dir: None,
+            ..CPLConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lifecycle() {
+        let manager = CPLManager::new(config());
+        let cpl_id = manager.create_cpl(Some("rover".to_string()), None).await.unwrap();
+        assert!(manager.create_cpl(Some("rover".to_string()), None).await.is_err());
+        assert_eq!(manager.status(&cpl_id).unwrap().health.state, CPLLifecycleState::Created);
+        assert!(manager.pause_cpl(&cpl_id).is_err());
+
+        let adapter = Arc::new(FlagAdapter { running: AtomicBool::new(false) });
+        manager.attach_world_adapter(&cpl_id, adapter.clone()).await.unwrap();
+        assert!(!manager.status(&cpl_id).unwrap().healthy);
+
+        manager.start_cpl(&cpl_id).await.unwrap();
+        assert!(adapter.running.load(Ordering::SeqCst));
+        manager.pause_cpl(&cpl_id).unwrap();
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        let paused = manager.status(&cpl_id).unwrap();
+        assert_eq!(paused.health.state, CPLLifecycleState::Paused);
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert_eq!(manager.status(&cpl_id).unwrap().health.iterations, paused.health.iterations);
+
+        manager.resume_cpl(&cpl_id).unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let running = manager.status(&cpl_id).unwrap();
+        assert_eq!(running.health.state, CPLLifecycleState::Running);
+        assert!(running.health.iterations > paused.health.iterations);
+        assert!(running.healthy);
+        assert_eq!(running.name.as_deref(), Some("rover"));
+
+        manager.stop_cpl(&cpl_id).await.unwrap();
+        assert!(!adapter.running.load(Ordering::SeqCst));
+        assert_eq!(manager.status(&cpl_id).unwrap().health.state, CPLLifecycleState::Stopped);
+
+        manager.remove_cpl(&cpl_id).await.unwrap();
+        assert!(manager.status(&cpl_id).is_none());
+        assert!(manager.list_status().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_budget_stops_loop() {
+        let manager = CPLManager::new(config());
+        let budget = CPLResourceBudget { max_iterations: Some(3), ..Default::default() };
+        let cpl_id = manager.create_cpl(None, Some(CPLConfig { budget, ..config() })).await.unwrap();
+        manager.start_cpl(&cpl_id).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let status = manager.status(&cpl_id).unwrap();
+        assert_eq!(status.health.state, CPLLifecycleState::Exhausted);
+        assert_eq!(status.health.iterations, 3);
+        assert!(status.health.exhausted.is_some());
+        assert!(!status.healthy);
+    }
+
+    #[tokio::test]
+    async fn test_genome_from_config() {
+        let manager = CPLManager::new(config());
+        let genome = crate::genetics::Genome::new();
+        let cpl_id = manager.create_cpl(None, Some(CPLConfig { genome: Some(genome.clone()), ..config() })).await.unwrap();
+        let cpl = manager.get_cpl(&cpl_id).unwrap();
+        let genetics = cpl.brain().get_genetic_system().unwrap();
+        assert_eq!(genetics.get_genome().id, genome.id);
+    }
+}
@@ -6,6 +6,9 @@ use narayana_core::{
     transforms::OutputConfig,
 };
 use crate::dynamic_output::DynamicOutputManager;
+use crate::row_security::RowSecurity;
+use crate::compliance::ComplianceManager;
+use crate::temporal::TemporalManager;
 use std::sync::Arc;
 use parking_lot::RwLock;
 use std::collections::HashMap;
@@ -35,6 +38,9 @@ pub struct DatabaseManager {
     next_table_id: Arc<std::sync::atomic::AtomicU64>,
     // NEW: Transform & Filter System
     output_manager: Arc<DynamicOutputManager>,
+    row_security: Arc<RowSecurity>,
+    compliance: Arc<ComplianceManager>,
+    temporal: Arc<TemporalManager>,
 }
 
 #[derive(Debug, Clone)]
@@ -58,6 +64,9 @@ impl DatabaseManager {
             next_db_id: Arc::new(std::sync::atomic::AtomicU64::new(1)),
             next_table_id: Arc::new(std::sync::atomic::AtomicU64::new(1)),
             output_manager: Arc::new(DynamicOutputManager::new()),
+            row_security: Arc::new(RowSecurity::new()),
+            compliance: Arc::new(ComplianceManager::new()),
+            temporal: Arc::new(TemporalManager::new()),
         }
     }
     
@@ -66,6 +75,21 @@ impl DatabaseManager {
         &self.output_manager
     }
 
+    /// Row policies and column masks for every table
+    pub fn row_security(&self) -> &Arc<RowSecurity> {
+        &self.row_security
+    }
+
+    /// Personal data tags, lineage and subject erasure
+    pub fn compliance(&self) -> &Arc<ComplianceManager> {
+        &self.compliance
+    }
+
+    /// System-versioned tables and their history
+    pub fn temporal(&self) -> &Arc<TemporalManager> {
+        &self.temporal
+    }
+
     /// Create database at runtime (no restart needed)
     pub fn create_database(&self, name: String) -> Result<DatabaseId> {
         let mut name_to_db = self.name_to_db.write();
@@ -242,6 +266,10 @@ impl DatabaseManager {
         );
         name_to_table.remove(&full_name);
 
+        self.row_security.drop_table(table_id);
+        self.compliance.drop_table(table_id);
+        self.temporal.drop_table(table_id);
+
         Ok(())
     }
 
@@ -284,5 +312,17 @@ impl DatabaseManager {
         let context = ConfigContext::Database { table_id: table_id.0 };
         self.output_manager.get_config_with_profile(&context, &table_id.0.to_string(), profile)
     }
+
+    /// Get the output profile bound to a caller's token or roles, if any
+    pub fn get_table_output_config_for_consumer(
+        &self,
+        table_id: TableId,
+        subject: &str,
+        roles: &[String],
+    ) -> Result<Option<(String, OutputConfig)>> {
+        use narayana_core::transforms::ConfigContext;
+        let context = ConfigContext::Database { table_id: table_id.0 };
+        self.output_manager.get_config_for_consumer(&context, &table_id.0.to_string(), subject, roles)
+    }
 }
 
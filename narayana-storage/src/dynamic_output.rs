@@ -19,10 +19,28 @@ use std::time::{SystemTime, UNIX_EPOCH};
 pub struct DynamicOutputManager {
     configs: Arc<RwLock<HashMap<String, TableOutputConfig>>>,
     change_history: Arc<RwLock<Vec<OutputChangeHistory>>>,
+    /// Profile served to each token subject or role, per entity
+    profile_bindings: Arc<RwLock<HashMap<String, HashMap<ProfileConsumer, String>>>>,
     validation_enabled: bool,
     auto_backup: bool,
 }
 
+/// API consumer an output profile is served to
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfileConsumer {
+    /// A single API token, by its subject
+    Token(String),
+    /// Every token carrying the role
+    Role(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileBinding {
+    pub consumer: ProfileConsumer,
+    pub profile: String,
+}
+
 #[derive(Debug, Clone)]
 struct TableOutputConfig {
     context: ConfigContext,
@@ -79,6 +97,7 @@ impl DynamicOutputManager {
         Self {
             configs: Arc::new(RwLock::new(HashMap::new())),
             change_history: Arc::new(RwLock::new(Vec::new())),
+            profile_bindings: Arc::new(RwLock::new(HashMap::new())),
             validation_enabled: true,
             auto_backup: true,
         }
@@ -768,6 +787,219 @@ impl DynamicOutputManager {
         None
     }
 
+    /// Add or replace a named profile. Entities without a config get an
+    /// empty one, so profiles can mask data that is otherwise served as-is.
+    pub async fn set_profile(
+        &self,
+        context: ConfigContext,
+        entity_id: String,
+        profile_name: String,
+        config: OutputConfig,
+    ) -> Result<OutputChangeResult> {
+        Self::validate_entity_id(&entity_id)?;
+        Self::validate_profile_name(&profile_name)?;
+        if !config.profiles.is_empty() {
+            return Err(Error::Storage("Profiles cannot contain nested profiles".to_string()));
+        }
+        
+        // SECURITY: Validate config size to prevent DoS
+        let config_size = serde_json::to_string(&config)
+            .map_err(|e| Error::Storage(format!("Failed to serialize config: {}", e)))?
+            .len();
+        const MAX_CONFIG_SIZE: usize = 10 * 1024 * 1024; // 10MB
+        if config_size > MAX_CONFIG_SIZE {
+            return Err(Error::Storage(format!(
+                "Config too large: {} bytes (max: {})",
+                config_size, MAX_CONFIG_SIZE
+            )));
+        }
+        
+        let start_time = SystemTime::now();
+        let now = start_time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        
+        let mut configs = self.configs.write();
+        let table_config = configs
+            .entry(Self::make_key(&context, &entity_id))
+            .or_insert_with(|| TableOutputConfig {
+                context: context.clone(),
+                entity_id: entity_id.clone(),
+                output_config: OutputConfig::default(),
+                version: 1,
+                last_modified: now,
+            });
+        
+        // The transform engine refuses configs with more profiles than this
+        const MAX_PROFILES: usize = 100;
+        let profiles = &table_config.output_config.profiles;
+        if !profiles.contains_key(&profile_name) && profiles.len() >= MAX_PROFILES {
+            return Err(Error::Storage(format!(
+                "Too many profiles: {} (max: {})",
+                profiles.len(), MAX_PROFILES
+            )));
+        }
+        
+        let snapshot = self.auto_backup.then(|| OutputConfigSnapshot {
+            config: table_config.output_config.clone(),
+            timestamp: now,
+        });
+        table_config.output_config.profiles.insert(profile_name.clone(), config.clone());
+        table_config.version += 1;
+        table_config.last_modified = now;
+        drop(configs);
+        
+        info!("Set output profile for {:?} entity", context);
+        Ok(self.record_change(
+            context,
+            entity_id,
+            OutputChange::AddProfile { profile_name, config },
+            snapshot,
+            start_time,
+        ))
+    }
+
+    /// Remove a named profile along with the bindings that serve it
+    pub async fn remove_profile(
+        &self,
+        context: ConfigContext,
+        entity_id: String,
+        profile_name: String,
+    ) -> Result<OutputChangeResult> {
+        Self::validate_entity_id(&entity_id)?;
+        
+        let start_time = SystemTime::now();
+        let key = Self::make_key(&context, &entity_id);
+        
+        let mut configs = self.configs.write();
+        let table_config = configs.get_mut(&key)
+            .ok_or_else(|| Error::Storage("Config not found".to_string()))?;
+        if !table_config.output_config.profiles.contains_key(&profile_name) {
+            return Err(Error::Storage(format!("Profile '{}' not found", profile_name)));
+        }
+        
+        let snapshot = self.auto_backup.then(|| OutputConfigSnapshot {
+            config: table_config.output_config.clone(),
+            timestamp: start_time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+        });
+        table_config.output_config.profiles.remove(&profile_name);
+        table_config.version += 1;
+        table_config.last_modified = start_time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        drop(configs);
+        
+        if let Some(bindings) = self.profile_bindings.write().get_mut(&key) {
+            bindings.retain(|_, profile| *profile != profile_name);
+        }
+        
+        info!("Removed output profile from {:?} entity", context);
+        Ok(self.record_change(
+            context,
+            entity_id,
+            OutputChange::RemoveProfile { profile_name },
+            snapshot,
+            start_time,
+        ))
+    }
+
+    /// Serve a profile to a token or role. The profile must exist.
+    pub fn bind_profile(
+        &self,
+        context: &ConfigContext,
+        entity_id: &str,
+        consumer: ProfileConsumer,
+        profile_name: &str,
+    ) -> Result<()> {
+        Self::validate_entity_id(entity_id)?;
+        let (ProfileConsumer::Token(id) | ProfileConsumer::Role(id)) = &consumer;
+        if id.is_empty() || id.len() > 1_024 || id.chars().any(|c| c.is_control()) {
+            return Err(Error::Storage(format!("Invalid profile consumer: '{}'", id)));
+        }
+        
+        let key = Self::make_key(context, entity_id);
+        let has_profile = self.configs.read()
+            .get(&key)
+            .is_some_and(|c| c.output_config.profiles.contains_key(profile_name));
+        if !has_profile {
+            return Err(Error::Storage(format!("Profile '{}' not found", profile_name)));
+        }
+        
+        let mut all_bindings = self.profile_bindings.write();
+        let bindings = all_bindings.entry(key).or_default();
+        // SECURITY: Limit bindings per entity
+        const MAX_BINDINGS: usize = 10_000;
+        if !bindings.contains_key(&consumer) && bindings.len() >= MAX_BINDINGS {
+            return Err(Error::Storage(format!(
+                "Too many profile bindings: {} (max: {})",
+                bindings.len(), MAX_BINDINGS
+            )));
+        }
+        bindings.insert(consumer, profile_name.to_string());
+        Ok(())
+    }
+
+    /// Stop serving a profile to a token or role; false if none was bound
+    pub fn unbind_profile(
+        &self,
+        context: &ConfigContext,
+        entity_id: &str,
+        consumer: &ProfileConsumer,
+    ) -> bool {
+        let key = Self::make_key(context, entity_id);
+        self.profile_bindings.write()
+            .get_mut(&key)
+            .is_some_and(|bindings| bindings.remove(consumer).is_some())
+    }
+
+    /// Profile bindings for an entity, tokens before roles
+    pub fn profile_bindings(&self, context: &ConfigContext, entity_id: &str) -> Vec<ProfileBinding> {
+        let key = Self::make_key(context, entity_id);
+        let mut bindings: Vec<ProfileBinding> = self.profile_bindings.read()
+            .get(&key)
+            .map(|bindings| {
+                bindings.iter()
+                    .map(|(consumer, profile)| ProfileBinding {
+                        consumer: consumer.clone(),
+                        profile: profile.clone(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        bindings.sort_by(|a, b| a.consumer.cmp(&b.consumer));
+        bindings
+    }
+
+    /// Profile served to a caller: the one bound to its token, else the one
+    /// bound to the first of its roles that has a binding. `None` when
+    /// nothing is bound. A binding whose profile has gone (e.g. after a
+    /// rollback) is an error so callers fail closed rather than serving
+    /// unmasked data.
+    pub fn get_config_for_consumer(
+        &self,
+        context: &ConfigContext,
+        entity_id: &str,
+        subject: &str,
+        roles: &[String],
+    ) -> Result<Option<(String, OutputConfig)>> {
+        let key = Self::make_key(context, entity_id);
+        let profile = {
+            let all_bindings = self.profile_bindings.read();
+            let Some(bindings) = all_bindings.get(&key) else {
+                return Ok(None);
+            };
+            let by_token = bindings.get(&ProfileConsumer::Token(subject.to_string()));
+            let by_role = || roles.iter().find_map(|role| bindings.get(&ProfileConsumer::Role(role.clone())));
+            match by_token.or_else(by_role) {
+                Some(profile) => profile.clone(),
+                None => return Ok(None),
+            }
+        };
+        
+        self.configs.read()
+            .get(&key)
+            .and_then(|c| c.output_config.profiles.get(&profile))
+            .cloned()
+            .map(|config| Some((profile.clone(), config)))
+            .ok_or_else(|| Error::Storage(format!("Bound output profile '{}' no longer exists", profile)))
+    }
+
     /// Get change history
     pub fn get_change_history(
         &self,
@@ -877,6 +1109,66 @@ impl DynamicOutputManager {
         Ok(())
     }
 
+    /// SECURITY: Validate profile name (same rules as profile lookup)
+    fn validate_profile_name(profile_name: &str) -> Result<()> {
+        if profile_name.is_empty()
+            || profile_name.len() > 1_024
+            || profile_name.contains("..")
+            || profile_name.contains("/")
+            || profile_name.contains("\\")
+            || profile_name.chars().any(|c| c.is_control()) {
+            return Err(Error::Storage(format!("Invalid profile name: '{}'", profile_name)));
+        }
+        Ok(())
+    }
+
+    /// Helper: record a successful change in the history
+    fn record_change(
+        &self,
+        context: ConfigContext,
+        entity_id: String,
+        change: OutputChange,
+        snapshot: Option<OutputConfigSnapshot>,
+        start_time: SystemTime,
+    ) -> OutputChangeResult {
+        let duration = start_time.elapsed()
+            .map(|d| d.as_millis().min(u64::MAX as u128) as f64)
+            .unwrap_or(0.0);
+        
+        let result = OutputChangeResult {
+            success: true,
+            change_type: change.clone(),
+            affected_queries: 0,
+            errors: vec![],
+            rollback_available: snapshot.is_some(),
+            duration_ms: duration,
+        };
+        
+        let mut history = self.change_history.write();
+        
+        // SECURITY: Limit history size
+        const MAX_HISTORY_SIZE: usize = 100_000;
+        if history.len() >= MAX_HISTORY_SIZE {
+            let to_remove = history.len() - MAX_HISTORY_SIZE + 1;
+            history.drain(0..to_remove);
+        }
+        
+        history.push(OutputChangeHistory {
+            context,
+            entity_id,
+            change,
+            result: result.clone(),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            rolled_back: false,
+            rollback_data: snapshot,
+        });
+        
+        result
+    }
+
     /// Helper: make key from context and entity_id
     /// SECURITY: This creates a key for HashMap lookup - entity_id is already validated
     fn make_key(context: &ConfigContext, entity_id: &str) -> String {
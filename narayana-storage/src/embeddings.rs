@@ -0,0 +1,506 @@
+// Automatic embedding generation for columns annotated with "embed"
+// Inserts compute embeddings into the paired vector column and vector index.
+// Embedding ids are row offsets: the caller holds the table's write lock from
+// before the write until the insert's vectors are published.
+
+use narayana_core::{
+    column::Column,
+    schema::{EmbedAnnotation, EmbedMode, Schema},
+    types::TableId,
+    Error, Result,
+};
+use crate::column_store::{stored_row_count, ColumnStore, TableWriteGuard};
+use crate::vector_search::{Embedding, IndexType, VectorStore};
+use async_trait::async_trait;
+use parking_lot::{Mutex, RwLock};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{debug, info, warn};
+
+/// Source of embeddings (LLM provider, local model, ...)
+#[async_trait]
+pub trait EmbeddingProvider: Send + Sync {
+    /// Embed a batch of texts; must return one vector per input
+    async fn embed(&self, texts: &[String], model: Option<&str>) -> Result<Vec<Vec<f32>>>;
+}
+
+/// Pending background embedding work for one insert
+#[derive(Debug, Clone)]
+struct EmbeddingJob {
+    seq: u64,
+    table_id: TableId,
+    annotation: EmbedAnnotation,
+    texts: Vec<String>,
+    /// Each text's stored row, or None once the row is deleted
+    rows: Vec<Option<u64>>,
+}
+
+/// Embeddings computed for an insert, published once its rows are stored
+pub struct PendingEmbeddings {
+    table_id: TableId,
+    row_count: u64,
+    computed: Vec<(EmbedAnnotation, Vec<Vec<f32>>)>,
+    queued: Vec<(EmbedAnnotation, Vec<String>)>,
+}
+
+/// Embeddings manager: computes embeddings for annotated columns on insert
+pub struct EmbeddingsManager {
+    provider: Arc<dyn EmbeddingProvider>,
+    vector_store: Arc<VectorStore>,
+    /// Next row id per table (its stored row count), used as the embedding id in the vector index
+    row_counters: RwLock<HashMap<TableId, u64>>,
+    queue: Mutex<VecDeque<EmbeddingJob>>,
+    next_job: AtomicU64,
+    max_batch_size: usize,
+}
+
+impl EmbeddingsManager {
+    pub fn new(provider: Arc<dyn EmbeddingProvider>, vector_store: Arc<VectorStore>) -> Self {
+        Self {
+            provider,
+            vector_store,
+            row_counters: RwLock::new(HashMap::new()),
+            queue: Mutex::new(VecDeque::new()),
+            next_job: AtomicU64::new(0),
+            max_batch_size: 64,
+        }
+    }
+
+    /// Name of the vector index backing an annotated column
+    pub fn index_name(table_id: TableId, annotation: &EmbedAnnotation) -> String {
+        format!("table_{}_{}", table_id.0, annotation.vector_column)
+    }
+
+    /// Validate annotations, create vector indexes and, the first time a table
+    /// is seen, start its row ids after the rows already in `store`
+    pub async fn register_table(&self, store: &dyn ColumnStore, table_id: TableId, schema: &Schema) -> Result<()> {
+        schema.validate_embeddings()?;
+        for annotation in &schema.embeddings {
+            let name = Self::index_name(table_id, annotation);
+            if !self.vector_store.has_index(&name) {
+                self.vector_store.create_index(name.clone(), annotation.dimensions, IndexType::Flat);
+                info!("Created vector index {} for embed column {}", name, annotation.source_column);
+            }
+        }
+        if !self.row_counters.read().contains_key(&table_id) {
+            let stored = stored_row_count(store, table_id).await? as u64;
+            self.row_counters.write().entry(table_id).or_insert(stored);
+        }
+        Ok(())
+    }
+
+    /// Take the columns supplied by the client (all fields except generated vector
+    /// columns, in schema order) and return the full column list to write, with
+    /// the embeddings to `publish` once it is written
+    pub async fn prepare_insert(
+        &self,
+        table_id: TableId,
+        schema: &Schema,
+        columns: Vec<Column>,
+    ) -> Result<(Vec<Column>, PendingEmbeddings)> {
+        let row_count = columns.first().map(|c| c.len()).unwrap_or(0);
+        let mut pending = PendingEmbeddings {
+            table_id,
+            row_count: row_count as u64,
+            computed: Vec::new(),
+            queued: Vec::new(),
+        };
+        if schema.embeddings.is_empty() {
+            return Ok((columns, pending));
+        }
+        let generated = schema.generated_field_indexes();
+        let expected = schema.fields.len() - generated.len();
+        if columns.len() != expected {
+            return Err(Error::SchemaMismatch(format!(
+                "Expected {} columns (excluding {} generated vector columns), got {}",
+                expected, generated.len(), columns.len()
+            )));
+        }
+
+        // Re-insert placeholders for generated columns in schema order
+        let mut supplied = columns.into_iter();
+        let mut full: Vec<Column> = Vec::with_capacity(schema.fields.len());
+        for idx in 0..schema.fields.len() {
+            if generated.contains(&idx) {
+                full.push(Column::Binary(vec![Vec::new(); row_count]));
+            } else {
+                full.push(supplied.next().ok_or_else(|| {
+                    Error::SchemaMismatch("Missing column for insert".to_string())
+                })?);
+            }
+        }
+
+        for annotation in &schema.embeddings {
+            let source_idx = schema.field_index(&annotation.source_column)
+                .ok_or_else(|| Error::ColumnNotFound(annotation.source_column.clone()))?;
+            let vector_idx = schema.field_index(&annotation.vector_column)
+                .ok_or_else(|| Error::ColumnNotFound(annotation.vector_column.clone()))?;
+            let texts = match &full[source_idx] {
+                Column::String(values) => values.clone(),
+                other => {
+                    return Err(Error::InvalidDataType {
+                        expected: "String".to_string(),
+                        actual: format!("{:?}", other.data_type()),
+                    })
+                }
+            };
+
+            match annotation.mode {
+                EmbedMode::Sync => {
+                    let vectors = self.embed_texts(annotation, &texts).await?;
+                    full[vector_idx] = Column::Binary(vectors.iter().map(|v| encode_vector(v)).collect());
+                    pending.computed.push((annotation.clone(), vectors));
+                }
+                EmbedMode::Async => pending.queued.push((annotation.clone(), texts)),
+            }
+        }
+        Ok((full, pending))
+    }
+
+    /// The insert's rows were stored: give them the next row ids, index the
+    /// computed vectors and queue the rest. Call with the table's write lock
+    /// still held and after `register_table`.
+    pub fn publish(&self, pending: PendingEmbeddings) -> Result<()> {
+        let PendingEmbeddings { table_id, row_count, computed, queued } = pending;
+        let first_row_id = {
+            let mut counters = self.row_counters.write();
+            let counter = counters.entry(table_id).or_insert(0);
+            let first = *counter;
+            *counter += row_count;
+            first
+        };
+        let ids: Vec<u64> = (first_row_id..first_row_id + row_count).collect();
+        for (annotation, vectors) in &computed {
+            self.index_vectors(table_id, annotation, &ids, vectors)?;
+        }
+        let mut queue = self.queue.lock();
+        for (annotation, texts) in queued {
+            queue.push_back(EmbeddingJob {
+                seq: self.next_job.fetch_add(1, Ordering::Relaxed),
+                table_id,
+                annotation,
+                texts,
+                rows: ids.iter().map(|&id| Some(id)).collect(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Rows `removed` (sorted) were deleted from the table and the rest moved
+    /// up; queued rows follow them and later inserts continue from its new length
+    pub fn rows_removed(&self, table_id: TableId, removed: &[usize]) {
+        if let Some(counter) = self.row_counters.write().get_mut(&table_id) {
+            *counter = counter.saturating_sub(removed.len() as u64);
+        }
+        for job in self.queue.lock().iter_mut().filter(|job| job.table_id == table_id) {
+            for row in job.rows.iter_mut() {
+                *row = row.and_then(|id| {
+                    let offset = id as usize;
+                    if removed.binary_search(&offset).is_ok() {
+                        None
+                    } else {
+                        Some(id - removed.partition_point(|&r| r < offset) as u64)
+                    }
+                });
+            }
+        }
+    }
+
+    /// Number of queued background jobs
+    pub fn pending_jobs(&self) -> usize {
+        self.queue.lock().len()
+    }
+
+    /// Process all queued background jobs, writing their vectors into the
+    /// stored vector columns of `store`; returns the number of rows embedded
+    pub async fn process_pending(&self, store: &dyn ColumnStore) -> Result<usize> {
+        let mut tables: HashMap<TableId, Vec<EmbeddingJob>> = HashMap::new();
+        for job in self.queue.lock().iter() {
+            tables.entry(job.table_id).or_default().push(job.clone());
+        }
+        let mut embedded = 0;
+        let mut failure = None;
+        'tables: for (table_id, jobs) in tables {
+            // Embed outside the table's write lock
+            let mut computed = Vec::with_capacity(jobs.len());
+            for job in jobs {
+                match self.embed_texts(&job.annotation, &job.texts).await {
+                    Ok(vectors) => computed.push((job.seq, job.annotation, vectors)),
+                    Err(e) => {
+                        // The table's jobs stay queued for the next pass
+                        warn!("Background embedding for table {} failed, will retry: {}", table_id.0, e);
+                        failure = Some(e);
+                        continue 'tables;
+                    }
+                }
+            }
+
+            // Rows may have moved while embedding: pair the vectors with each
+            // job's current rows, which can't change again under the lock
+            let guard = store.lock_table(table_id).await;
+            let mut updates = Vec::with_capacity(computed.len());
+            {
+                let queue = self.queue.lock();
+                for (seq, annotation, vectors) in computed {
+                    let Some(job) = queue.iter().find(|job| job.seq == seq) else {
+                        continue;
+                    };
+                    let (ids, vectors): (Vec<u64>, Vec<Vec<f32>>) = job.rows.iter()
+                        .zip(vectors)
+                        .filter_map(|(row, vector)| row.map(|id| (id, vector)))
+                        .unzip();
+                    updates.push((seq, annotation, ids, vectors));
+                }
+            }
+            if updates.iter().any(|(_, _, ids, _)| !ids.is_empty()) {
+                self.write_back(store, &guard, &updates).await?;
+            }
+            for (_, annotation, ids, vectors) in &updates {
+                self.index_vectors(table_id, annotation, ids, vectors)?;
+                embedded += ids.len();
+            }
+            self.queue.lock().retain(|job| !updates.iter().any(|(seq, ..)| *seq == job.seq));
+        }
+        match failure {
+            Some(e) => Err(e),
+            None => Ok(embedded),
+        }
+    }
+
+    /// Spawn a background task draining the async queue into `store`
+    pub fn start_background_worker(
+        self: Arc<Self>,
+        store: Arc<dyn ColumnStore>,
+        interval: Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if self.pending_jobs() == 0 {
+                    continue;
+                }
+                match self.process_pending(store.as_ref()).await {
+                    Ok(n) => debug!("Embedded {} queued rows", n),
+                    Err(e) => warn!("Embedding worker error: {}", e),
+                }
+            }
+        })
+    }
+
+    async fn embed_texts(&self, annotation: &EmbedAnnotation, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let mut vectors = Vec::with_capacity(texts.len());
+        for chunk in texts.chunks(self.max_batch_size) {
+            let batch = self.provider.embed(chunk, annotation.model.as_deref()).await?;
+            if batch.len() != chunk.len() {
+                return Err(Error::Storage(format!(
+                    "Embedding provider returned {} vectors for {} texts",
+                    batch.len(), chunk.len()
+                )));
+            }
+            for vector in &batch {
+                if vector.len() != annotation.dimensions {
+                    return Err(Error::Storage(format!(
+                        "Embedding dimension mismatch for '{}': expected {}, got {}",
+                        annotation.source_column, annotation.dimensions, vector.len()
+                    )));
+                }
+            }
+            vectors.extend(batch);
+        }
+        Ok(vectors)
+    }
+
+    /// Fill queued rows' placeholders in the stored vector columns
+    async fn write_back(
+        &self,
+        store: &dyn ColumnStore,
+        guard: &TableWriteGuard,
+        updates: &[(u64, EmbedAnnotation, Vec<u64>, Vec<Vec<f32>>)],
+    ) -> Result<()> {
+        let table_id = guard.table_id();
+        let schema = store.get_schema(table_id).await?;
+        let column_ids: Vec<u32> = (0..schema.fields.len() as u32).collect();
+        let mut columns = store.read_columns(table_id, column_ids, 0, usize::MAX).await?;
+        if columns.len() != schema.fields.len() {
+            return Err(Error::Storage(format!(
+                "Read returned {} of {} columns", columns.len(), schema.fields.len()
+            )));
+        }
+        for (_, annotation, ids, vectors) in updates {
+            let vector_idx = schema.field_index(&annotation.vector_column)
+                .ok_or_else(|| Error::ColumnNotFound(annotation.vector_column.clone()))?;
+            let Column::Binary(values) = &mut columns[vector_idx] else {
+                return Err(Error::Storage(format!("Vector column '{}' is not Binary", annotation.vector_column)));
+            };
+            for (&id, vector) in ids.iter().zip(vectors) {
+                let value = values.get_mut(id as usize).ok_or_else(|| {
+                    Error::Storage(format!("Row {} of table {} is not stored", id, table_id.0))
+                })?;
+                *value = encode_vector(vector);
+            }
+        }
+        store.replace_rows(guard, columns).await
+    }
+
+    fn index_vectors(&self, table_id: TableId, annotation: &EmbedAnnotation, ids: &[u64], vectors: &[Vec<f32>]) -> Result<()> {
+        let index_name = Self::index_name(table_id, annotation);
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64;
+        for (&id, vector) in ids.iter().zip(vectors) {
+            let mut metadata = HashMap::new();
+            metadata.insert("table_id".to_string(), serde_json::json!(table_id.0));
+            metadata.insert("column".to_string(), serde_json::json!(annotation.source_column));
+            self.vector_store.add_embedding(&index_name, Embedding {
+                id,
+                vector: vector.clone(),
+                metadata,
+                timestamp,
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// Encode a vector as little-endian f32 bytes for the Binary vector column
+pub fn encode_vector(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+/// Decode a Binary vector column value
+pub fn decode_vector(bytes: &[u8]) -> Result<Vec<f32>> {
+    if bytes.len() % 4 != 0 {
+        return Err(Error::Deserialization(format!("Vector byte length {} is not a multiple of 4", bytes.len())));
+    }
+    Ok(bytes.chunks_exact(4).map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]])).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::column_store::InMemoryColumnStore;
+    use narayana_core::schema::{DataType, Field};
+
+    struct LengthProvider;
+
+    #[async_trait]
+    impl EmbeddingProvider for LengthProvider {
+        async fn embed(&self, texts: &[String], _model: Option<&str>) -> Result<Vec<Vec<f32>>> {
+            Ok(texts.iter().map(|t| vec![t.len() as f32, 1.0]).collect())
+        }
+    }
+
+    fn field(name: &str, data_type: DataType) -> Field {
+        Field { name: name.to_string(), data_type, nullable: false, default_value: None }
+    }
+
+    fn schema(mode: EmbedMode) -> Schema {
+        Schema::new(vec![
+            field("id", DataType::Int64),
+            field("body", DataType::String),
+            field("body_vec", DataType::Binary),
+        ]).with_embedding(EmbedAnnotation {
+            source_column: "body".to_string(),
+            vector_column: "body_vec".to_string(),
+            dimensions: 2,
+            model: None,
+            mode,
+        })
+    }
+
+    fn rows(ids: &[i64], bodies: &[&str]) -> Vec<Column> {
+        vec![
+            Column::Int64(ids.to_vec()),
+            Column::String(bodies.iter().map(|b| b.to_string()).collect()),
+        ]
+    }
+
+    /// Insert the way the server does: prepare, then write and publish under the table's lock
+    async fn insert(manager: &EmbeddingsManager, store: &dyn ColumnStore, table_id: TableId, schema: &Schema, columns: Vec<Column>) {
+        let (full, pending) = manager.prepare_insert(table_id, schema, columns).await.unwrap();
+        let guard = store.lock_table(table_id).await;
+        manager.register_table(store, table_id, schema).await.unwrap();
+        store.write_locked(&guard, full).await.unwrap();
+        manager.publish(pending).unwrap();
+    }
+
+    async fn stored_vectors(store: &dyn ColumnStore, table_id: TableId) -> Vec<Vec<u8>> {
+        match store.read_columns(table_id, vec![2], 0, usize::MAX).await.unwrap().remove(0) {
+            Column::Binary(values) => values,
+            other => panic!("unexpected column {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sync_embedding_fills_vector_column() {
+        let vectors = Arc::new(VectorStore::new());
+        let manager = EmbeddingsManager::new(Arc::new(LengthProvider), vectors.clone());
+        let store = InMemoryColumnStore::new();
+        let schema = schema(EmbedMode::Sync);
+        store.create_table(TableId(1), schema.clone()).await.unwrap();
+        insert(&manager, &store, TableId(1), &schema, rows(&[1, 2], &["abc", "hello"])).await;
+
+        let stored = stored_vectors(&store, TableId(1)).await;
+        assert_eq!(decode_vector(&stored[1]).unwrap(), vec![5.0, 1.0]);
+        let results = vectors.search("table_1_body_vec", &[5.0, 1.0], 1).unwrap();
+        assert_eq!(results[0].id, 1);
+    }
+
+    #[tokio::test]
+    async fn test_async_embedding_is_written_back() {
+        let vectors = Arc::new(VectorStore::new());
+        let manager = EmbeddingsManager::new(Arc::new(LengthProvider), vectors.clone());
+        let store = InMemoryColumnStore::new();
+        let schema = schema(EmbedMode::Async);
+        store.create_table(TableId(2), schema.clone()).await.unwrap();
+        insert(&manager, &store, TableId(2), &schema, rows(&[1, 2, 3], &["a", "bb", "ccc"])).await;
+        assert_eq!(manager.pending_jobs(), 1);
+        assert!(stored_vectors(&store, TableId(2)).await[0].is_empty());
+
+        // The first row is deleted before the worker runs; the others move up
+        manager.rows_removed(TableId(2), &[0]);
+        let kept = store.read_columns(TableId(2), vec![0, 1, 2], 1, 2).await.unwrap();
+        let guard = store.lock_table(TableId(2)).await;
+        store.replace_rows(&guard, kept).await.unwrap();
+        drop(guard);
+
+        assert_eq!(manager.process_pending(&store).await.unwrap(), 2);
+        assert_eq!(manager.pending_jobs(), 0);
+        let stored = stored_vectors(&store, TableId(2)).await;
+        assert_eq!(decode_vector(&stored[0]).unwrap(), vec![2.0, 1.0]);
+        assert_eq!(decode_vector(&stored[1]).unwrap(), vec![3.0, 1.0]);
+        let results = vectors.search("table_2_body_vec", &[3.0, 1.0], 1).unwrap();
+        assert_eq!(results[0].id, 1);
+    }
+
+    #[tokio::test]
+    async fn test_row_ids_continue_after_stored_rows() {
+        // A restarted server sees the table's stored rows before its first insert
+        let store = InMemoryColumnStore::new();
+        let schema = schema(EmbedMode::Sync);
+        store.create_table(TableId(3), schema.clone()).await.unwrap();
+        let before = EmbeddingsManager::new(Arc::new(LengthProvider), Arc::new(VectorStore::new()));
+        insert(&before, &store, TableId(3), &schema, rows(&[1, 2], &["a", "bb"])).await;
+
+        let vectors = Arc::new(VectorStore::new());
+        let manager = EmbeddingsManager::new(Arc::new(LengthProvider), vectors.clone());
+        insert(&manager, &store, TableId(3), &schema, rows(&[3], &["ccc"])).await;
+        let results = vectors.search("table_3_body_vec", &[3.0, 1.0], 1).unwrap();
+        assert_eq!(results[0].id, 2);
+
+        // Vectors of an insert that was never written aren't published
+        let (_, _unwritten) = manager.prepare_insert(TableId(3), &schema, rows(&[4], &["dddd"])).await.unwrap();
+        assert_eq!(vectors.search("table_3_body_vec", &[4.0, 1.0], 5).unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_wrong_column_count() {
+        let manager = EmbeddingsManager::new(Arc::new(LengthProvider), Arc::new(VectorStore::new()));
+        let columns = vec![Column::Int64(vec![1])];
+        assert!(manager.prepare_insert(TableId(4), &schema(EmbedMode::Sync), columns).await.is_err());
+    }
+}
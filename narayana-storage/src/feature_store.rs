@@ -0,0 +1,514 @@
+// Feature store for RL and ML integration
+// Feature views computed from tables (batch backfill) and the CDC stream (online),
+// with point-in-time-correct training retrieval and low-latency online serving
+
+use crate::anomaly_detection::{is_numeric, numeric_value};
+use crate::cdc::{ChangeEvent, ChangeFeed, ChangeOperation};
+use narayana_core::{column::Column, schema::{DataType, Schema}, types::TableId, Error, Result};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, info, warn};
+
+/// Default number of observations kept per entity
+fn default_max_history() -> usize {
+    10_000
+}
+
+/// How a feature is computed from the observations of one entity
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeatureAggregation {
+    /// Most recent value
+    #[default]
+    Latest,
+    Sum,
+    Mean,
+    Count,
+    Min,
+    Max,
+}
+
+/// One feature of a view, derived from a numeric column
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeatureDefinition {
+    pub name: String,
+    pub column: String,
+    #[serde(default)]
+    pub aggregation: FeatureAggregation,
+    /// Only observations within this many milliseconds before the lookup time count
+    #[serde(default)]
+    pub window_ms: Option<i64>,
+}
+
+/// A group of features of one table, keyed by an entity column.
+/// Event timestamps are Unix milliseconds; without a timestamp column
+/// the ingestion time is used.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeatureView {
+    pub name: String,
+    pub table_id: TableId,
+    pub entity_column: String,
+    #[serde(default)]
+    pub timestamp_column: Option<String>,
+    pub features: Vec<FeatureDefinition>,
+    /// Latest values older than this are served as missing
+    #[serde(default)]
+    pub ttl_ms: Option<i64>,
+    #[serde(default = "default_max_history")]
+    pub max_history: usize,
+}
+
+/// Entity and lookup time of one training example
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntityRow {
+    pub entity: String,
+    pub timestamp_ms: i64,
+}
+
+/// View definition plus the schema it was validated against (persisted together)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureViewStatus {
+    pub view: FeatureView,
+    pub schema: Schema,
+    pub entities: usize,
+    pub observations: usize,
+}
+
+/// One row of the source table, reduced to the view's feature columns
+#[derive(Debug, Clone)]
+struct Observation {
+    timestamp_ms: i64,
+    values: Vec<Option<f64>>,
+}
+
+struct ViewState {
+    view: FeatureView,
+    schema: Schema,
+    entity_idx: usize,
+    timestamp_idx: Option<usize>,
+    feature_idx: Vec<usize>,
+    /// Per entity, ordered by timestamp
+    history: HashMap<String, Vec<Observation>>,
+}
+
+impl ViewState {
+    fn new(view: FeatureView, schema: Schema) -> Result<Self> {
+        validate_view_name(&view.name)?;
+        if view.features.is_empty() {
+            return Err(Error::Configuration(format!("Feature view {} has no features", view.name)));
+        }
+        let entity_idx = schema.field_index(&view.entity_column)
+            .ok_or_else(|| Error::ColumnNotFound(view.entity_column.clone()))?;
+        let timestamp_idx = match &view.timestamp_column {
+            Some(column) => {
+                let idx = schema.field_index(column).ok_or_else(|| Error::ColumnNotFound(column.clone()))?;
+                if !is_numeric(&schema.fields[idx].data_type) {
+                    return Err(Error::InvalidDataType {
+                        expected: "Timestamp or integer".to_string(),
+                        actual: format!("{:?}", schema.fields[idx].data_type),
+                    });
+                }
+                Some(idx)
+            }
+            None => None,
+        };
+        let mut feature_idx = Vec::with_capacity(view.features.len());
+        for (i, feature) in view.features.iter().enumerate() {
+            if view.features[..i].iter().any(|f| f.name == feature.name) {
+                return Err(Error::Configuration(format!("Duplicate feature {} in view {}", feature.name, view.name)));
+            }
+            let idx = schema.field_index(&feature.column)
+                .ok_or_else(|| Error::ColumnNotFound(feature.column.clone()))?;
+            let data_type = &schema.fields[idx].data_type;
+            if !is_numeric(data_type) && *data_type != DataType::Boolean {
+                return Err(Error::InvalidDataType {
+                    expected: "numeric".to_string(),
+                    actual: format!("{:?}", data_type),
+                });
+            }
+            feature_idx.push(idx);
+        }
+        Ok(Self { view, schema, entity_idx, timestamp_idx, feature_idx, history: HashMap::new() })
+    }
+
+    /// Append rows of the source table; returns the number of observations added
+    fn ingest(&mut self, columns: &[Column], ingested_at_ms: i64) -> usize {
+        let Some(entity_column) = columns.get(self.entity_idx) else { return 0 };
+        let mut added = 0;
+        for row in 0..entity_column.len() {
+            let Some(entity) = entity_key(entity_column, row) else { continue };
+            let timestamp_ms = self.timestamp_idx
+                .and_then(|idx| columns.get(idx))
+                .and_then(|c| numeric_value(c, row))
+                .map(|t| t as i64)
+                .unwrap_or(ingested_at_ms);
+            let values = self.feature_idx.iter()
+                .map(|idx| columns.get(*idx).and_then(|c| feature_value(c, row)))
+                .collect();
+
+            let history = self.history.entry(entity).or_default();
+            // Rows usually arrive in time order; keep the vector sorted when they don't
+            let pos = history.partition_point(|o| o.timestamp_ms <= timestamp_ms);
+            history.insert(pos, Observation { timestamp_ms, values });
+            if history.len() > self.view.max_history.max(1) {
+                let excess = history.len() - self.view.max_history.max(1);
+                history.drain(..excess);
+            }
+            added += 1;
+        }
+        added
+    }
+
+    /// Value of feature `feature` for `entity` as known at `as_of_ms` (inclusive)
+    fn value_at(&self, entity: &str, feature: usize, as_of_ms: i64) -> Option<f64> {
+        let history = self.history.get(entity)?;
+        let definition = &self.view.features[feature];
+        let end = history.partition_point(|o| o.timestamp_ms <= as_of_ms);
+        let start = match definition.window_ms {
+            Some(window) => history[..end].partition_point(|o| o.timestamp_ms <= as_of_ms.saturating_sub(window)),
+            None => 0,
+        };
+        let observations = &history[start..end];
+
+        if definition.aggregation == FeatureAggregation::Latest {
+            let latest = observations.iter().rev().find(|o| o.values[feature].is_some())?;
+            if let Some(ttl) = self.view.ttl_ms {
+                if as_of_ms.saturating_sub(latest.timestamp_ms) > ttl {
+                    return None;
+                }
+            }
+            return latest.values[feature];
+        }
+
+        let values = observations.iter().filter_map(|o| o.values[feature]);
+        match definition.aggregation {
+            FeatureAggregation::Count => Some(values.count() as f64),
+            FeatureAggregation::Sum => Some(values.sum()),
+            FeatureAggregation::Mean => {
+                let (sum, count) = values.fold((0.0, 0usize), |(s, c), v| (s + v, c + 1));
+                (count > 0).then(|| sum / count as f64)
+            }
+            FeatureAggregation::Min => values.reduce(f64::min),
+            FeatureAggregation::Max => values.reduce(f64::max),
+            FeatureAggregation::Latest => unreachable!("handled above"),
+        }
+    }
+
+    fn status(&self) -> FeatureViewStatus {
+        FeatureViewStatus {
+            view: self.view.clone(),
+            schema: self.schema.clone(),
+            entities: self.history.len(),
+            observations: self.history.values().map(|h| h.len()).sum(),
+        }
+    }
+}
+
+/// Feature store. Features are referenced as `view:feature`.
+pub struct FeatureStore {
+    views: RwLock<HashMap<String, ViewState>>,
+    state_dir: Option<PathBuf>,
+}
+
+impl FeatureStore {
+    /// Create a feature store; view definitions are persisted under `state_dir` when given
+    pub fn new(state_dir: Option<PathBuf>) -> Result<Self> {
+        if let Some(dir) = &state_dir {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| Error::Storage(format!("Failed to create feature store directory: {}", e)))?;
+        }
+        Ok(Self { views: RwLock::new(HashMap::new()), state_dir })
+    }
+
+    /// Load view definitions written by a previous run. Their values are
+    /// empty until the views are materialized again.
+    pub fn load_persisted(&self) -> Result<usize> {
+        let Some(dir) = &self.state_dir else { return Ok(0) };
+        let entries = std::fs::read_dir(dir)
+            .map_err(|e| Error::Storage(format!("Failed to read feature store directory: {}", e)))?;
+        let mut loaded = 0;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let status = match std::fs::read(&path).map(|bytes| serde_json::from_slice::<FeatureViewStatus>(&bytes)) {
+                Ok(Ok(status)) => status,
+                Ok(Err(e)) => {
+                    warn!("Skipping corrupt feature view {:?}: {}", path, e);
+                    continue;
+                }
+                Err(e) => {
+                    warn!("Failed to read feature view {:?}: {}", path, e);
+                    continue;
+                }
+            };
+            match ViewState::new(status.view, status.schema) {
+                Ok(state) => {
+                    self.views.write().insert(state.view.name.clone(), state);
+                    loaded += 1;
+                }
+                Err(e) => warn!("Skipping invalid feature view {:?}: {}", path, e),
+            }
+        }
+        info!("Loaded {} feature views", loaded);
+        Ok(loaded)
+    }
+
+    /// Register (or replace) a feature view over `schema`
+    pub fn register_view(&self, view: FeatureView, schema: &Schema) -> Result<()> {
+        let state = ViewState::new(view, schema.clone())?;
+        let name = state.view.name.clone();
+        self.persist(&state.status())?;
+        self.views.write().insert(name.clone(), state);
+        info!("Registered feature view {}", name);
+        Ok(())
+    }
+
+    pub fn remove_view(&self, name: &str) -> bool {
+        let removed = self.views.write().remove(name).is_some();
+        if removed {
+            if let Some(dir) = &self.state_dir {
+                let _ = std::fs::remove_file(dir.join(format!("{}.json", name)));
+            }
+        }
+        removed
+    }
+
+    pub fn get_view(&self, name: &str) -> Option<FeatureViewStatus> {
+        self.views.read().get(name).map(|s| s.status())
+    }
+
+    pub fn list_views(&self) -> Vec<FeatureViewStatus> {
+        let mut views: Vec<FeatureViewStatus> = self.views.read().values().map(|s| s.status()).collect();
+        views.sort_by(|a, b| a.view.name.cmp(&b.view.name));
+        views
+    }
+
+    /// Batch computation: rebuild a view from the full contents of its table
+    pub fn materialize(&self, name: &str, columns: &[Column]) -> Result<usize> {
+        let mut views = self.views.write();
+        let state = views.get_mut(name)
+            .ok_or_else(|| Error::Storage(format!("Feature view {} not found", name)))?;
+        if columns.len() != state.schema.fields.len() {
+            return Err(Error::Storage(format!(
+                "Feature view {} expects {} columns, got {}", name, state.schema.fields.len(), columns.len()
+            )));
+        }
+        state.history.clear();
+        let added = state.ingest(columns, now_ms());
+        info!("Materialized feature view {} ({} observations)", name, added);
+        Ok(added)
+    }
+
+    /// Online computation: apply one committed change from the CDC feed
+    pub fn process_change(&self, change: &ChangeEvent) -> usize {
+        if change.operation == ChangeOperation::DropTable {
+            let dropped: Vec<String> = self.views.read().values()
+                .filter(|s| s.view.table_id == change.table_id)
+                .map(|s| s.view.name.clone())
+                .collect();
+            for name in dropped {
+                self.remove_view(&name);
+            }
+            return 0;
+        }
+        let ingested_at = (change.timestamp as i64).saturating_mul(1000);
+        let mut views = self.views.write();
+        views.values_mut()
+            .filter(|s| s.view.table_id == change.table_id)
+            .map(|s| s.ingest(&change.columns, ingested_at))
+            .sum()
+    }
+
+    /// Keep online features current from the change feed
+    pub fn start(self: Arc<Self>, feed: &ChangeFeed) -> tokio::task::JoinHandle<()> {
+        let mut receiver = feed.subscribe();
+        tokio::spawn(async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(change) => {
+                        let added = self.process_change(&change);
+                        if added > 0 {
+                            debug!("Feature store ingested {} observations from table {}", added, change.table_id.0);
+                        }
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!("Feature store lagged behind the change feed, skipped {} changes", skipped);
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+        })
+    }
+
+    /// Online serving: current values of `features` for one entity
+    pub fn get_online_features(&self, entity: &str, features: &[String]) -> Result<Vec<Option<f64>>> {
+        let views = self.views.read();
+        let refs = resolve_features(&views, features)?;
+        let now = now_ms();
+        Ok(refs.iter().map(|(view, idx)| views[*view].value_at(entity, *idx, now)).collect())
+    }
+
+    /// Training retrieval: each row sees only observations at or before its timestamp
+    pub fn get_historical_features(&self, rows: &[EntityRow], features: &[String]) -> Result<Vec<Vec<Option<f64>>>> {
+        let views = self.views.read();
+        let refs = resolve_features(&views, features)?;
+        Ok(rows.iter()
+            .map(|row| refs.iter()
+                .map(|(view, idx)| views[*view].value_at(&row.entity, *idx, row.timestamp_ms))
+                .collect())
+            .collect())
+    }
+
+    fn persist(&self, status: &FeatureViewStatus) -> Result<()> {
+        let Some(dir) = &self.state_dir else { return Ok(()) };
+        let path = dir.join(format!("{}.json", status.view.name));
+        let bytes = serde_json::to_vec(status)
+            .map_err(|e| Error::Serialization(format!("Failed to serialize feature view: {}", e)))?;
+        // Write to a temp file and rename so a crash never leaves a torn definition
+        let temp_path = path.with_extension("json.tmp");
+        std::fs::write(&temp_path, &bytes)
+            .map_err(|e| Error::Storage(format!("Failed to write feature view: {}", e)))?;
+        std::fs::rename(&temp_path, &path)
+            .map_err(|e| Error::Storage(format!("Failed to write feature view: {}", e)))?;
+        Ok(())
+    }
+}
+
+/// Resolve `view:feature` references to (view name, feature index)
+fn resolve_features<'a>(views: &HashMap<String, ViewState>, features: &'a [String]) -> Result<Vec<(&'a str, usize)>> {
+    features.iter()
+        .map(|reference| {
+            let (view, feature) = reference.split_once(':')
+                .ok_or_else(|| Error::Query(format!("Feature reference must be 'view:feature': {}", reference)))?;
+            let state = views.get(view)
+                .ok_or_else(|| Error::Query(format!("Feature view {} not found", view)))?;
+            let idx = state.view.features.iter().position(|f| f.name == feature)
+                .ok_or_else(|| Error::Query(format!("Feature {} not found in view {}", feature, view)))?;
+            Ok((view, idx))
+        })
+        .collect()
+}
+
+fn entity_key(column: &Column, row: usize) -> Option<String> {
+    match column {
+        Column::String(v) => v.get(row).cloned(),
+        Column::Boolean(v) => v.get(row).map(|b| b.to_string()),
+        Column::Binary(_) => None,
+        other => numeric_value(other, row).map(|v| {
+            if v.fract() == 0.0 { format!("{}", v as i64) } else { v.to_string() }
+        }),
+    }
+}
+
+fn feature_value(column: &Column, row: usize) -> Option<f64> {
+    match column {
+        Column::Boolean(v) => v.get(row).map(|b| if *b { 1.0 } else { 0.0 }),
+        other => numeric_value(other, row).filter(|v| v.is_finite()),
+    }
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as i64
+}
+
+/// View names become file names, so keep them to a safe alphabet
+fn validate_view_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.len() > 128 {
+        return Err(Error::Configuration("Feature view name must be 1-128 characters".to_string()));
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(Error::Configuration("Feature view name may only contain letters, digits, '-' and '_'".to_string()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use narayana_core::schema::Field;
+
+    fn field(name: &str, data_type: DataType) -> Field {
+        Field {
+            name: name.to_string(),
+            data_type,
+            nullable: false,
+            default_value: None,
+        }
+    }
+
+    fn setup() -> (FeatureStore, Schema) {
+        let schema = Schema::new(vec![
+            field("robot", DataType::String),
+            field("ts", DataType::Timestamp),
+            field("battery", DataType::Float64),
+        ]);
+        let store = FeatureStore::new(None).unwrap();
+        store.register_view(FeatureView {
+            name: "robot_stats".to_string(),
+            table_id: TableId(7),
+            entity_column: "robot".to_string(),
+            timestamp_column: Some("ts".to_string()),
+            features: vec![
+                FeatureDefinition { name: "battery".to_string(), column: "battery".to_string(), aggregation: FeatureAggregation::Latest, window_ms: None },
+                FeatureDefinition { name: "battery_avg_1s".to_string(), column: "battery".to_string(), aggregation: FeatureAggregation::Mean, window_ms: Some(1000) },
+            ],
+            ttl_ms: None,
+            max_history: 100,
+        }, &schema).unwrap();
+        (store, schema)
+    }
+
+    #[test]
+    fn test_point_in_time_retrieval() {
+        let (store, _) = setup();
+        store.materialize("robot_stats", &[
+            Column::String(vec!["r1".into(), "r1".into(), "r1".into(), "r2".into()]),
+            Column::Timestamp(vec![1000, 1500, 3000, 1200]),
+            Column::Float64(vec![90.0, 80.0, 50.0, 70.0]),
+        ]).unwrap();
+
+        let features = vec!["robot_stats:battery".to_string(), "robot_stats:battery_avg_1s".to_string()];
+        let rows = vec![
+            EntityRow { entity: "r1".into(), timestamp_ms: 999 },
+            EntityRow { entity: "r1".into(), timestamp_ms: 1600 },
+            EntityRow { entity: "r1".into(), timestamp_ms: 3000 },
+            EntityRow { entity: "r2".into(), timestamp_ms: 5000 },
+        ];
+        let values = store.get_historical_features(&rows, &features).unwrap();
+        assert_eq!(values[0], vec![None, None]);
+        assert_eq!(values[1], vec![Some(80.0), Some(85.0)]);
+        // The 3000ms value must not leak into earlier rows, and the window drops old ones
+        assert_eq!(values[2], vec![Some(50.0), Some(50.0)]);
+        assert_eq!(values[3], vec![Some(70.0), None]);
+        assert!(store.get_historical_features(&rows, &["robot_stats:missing".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_online_features_from_change_feed() {
+        let (store, schema) = setup();
+        let feed = ChangeFeed::new(16);
+        let mut receiver = feed.subscribe();
+        let now = now_ms();
+        feed.publish_insert(TableId(7), &schema, &[
+            Column::String(vec!["r1".into()]),
+            Column::Timestamp(vec![now - 10]),
+            Column::Float64(vec![42.0]),
+        ]);
+        let change = receiver.try_recv().unwrap();
+        assert_eq!(store.process_change(&change), 1);
+
+        let online = store.get_online_features("r1", &["robot_stats:battery".to_string()]).unwrap();
+        assert_eq!(online, vec![Some(42.0)]);
+
+        feed.publish_drop(TableId(7), &schema);
+        store.process_change(&receiver.try_recv().unwrap());
+        assert!(store.get_view("robot_stats").is_none());
+    }
+}
@@ -0,0 +1,585 @@
+// Full-text search index with language-aware tokenization
+// Inverted index with positions, stemming, stop words, phrase/prefix queries and BM25 ranking
+
+use narayana_core::{column::Column, schema::{DataType, Schema}, types::TableId, Error, Result};
+use crate::human_search::Stemmer;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use parking_lot::RwLock;
+use tracing::info;
+
+/// Language used for stop words and stemming
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    English,
+    Spanish,
+    Portuguese,
+    French,
+    German,
+    /// Lowercasing only, no stop words or stemming
+    Simple,
+}
+
+/// Token produced by the analyzer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub term: String,
+    pub position: u32,
+}
+
+/// Tokenizer + stop words + stemmer for one language
+pub struct TextAnalyzer {
+    language: Language,
+    stop_words: HashSet<&'static str>,
+    stemmer: Option<Stemmer>,
+}
+
+impl TextAnalyzer {
+    pub fn new(language: Language) -> Self {
+        Self {
+            language,
+            stop_words: Self::stop_words(language).iter().copied().collect(),
+            stemmer: match language {
+                Language::English => Some(Stemmer::new("en")),
+                _ => None,
+            },
+        }
+    }
+
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    /// Split text into terms. Positions count every word (including stop words)
+    /// so phrase queries keep their original spacing.
+    pub fn analyze(&self, text: &str) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        for (position, word) in text
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .enumerate()
+        {
+            let lower = word.to_lowercase();
+            if self.stop_words.contains(lower.as_str()) {
+                continue;
+            }
+            tokens.push(Token {
+                term: self.normalize(&lower),
+                position: position as u32,
+            });
+        }
+        tokens
+    }
+
+    /// Normalize a single lowercase word (stemming)
+    pub fn normalize(&self, word: &str) -> String {
+        match self.language {
+            Language::English => {
+                // The Porter stemmer works on ASCII words only
+                match &self.stemmer {
+                    Some(stemmer) if word.is_ascii() => stemmer.stem(word),
+                    _ => word.to_string(),
+                }
+            }
+            Language::Simple => word.to_string(),
+            _ => Self::light_stem(self.language, word),
+        }
+    }
+
+    /// Light suffix stripping for Romance and Germanic languages
+    fn light_stem(language: Language, word: &str) -> String {
+        let suffixes: &[&str] = match language {
+            Language::Spanish => &["amientos", "imientos", "amiento", "imiento", "aciones", "ación", "mente", "es", "s"],
+            Language::Portuguese => &["amentos", "imentos", "amento", "imento", "ações", "ação", "mente", "es", "s"],
+            Language::French => &["issements", "issement", "ements", "ement", "ations", "ation", "euses", "euse", "es", "s"],
+            Language::German => &["ungen", "heiten", "keiten", "ung", "heit", "keit", "en", "er", "es", "e", "n", "s"],
+            _ => &[],
+        };
+        let char_count = word.chars().count();
+        for suffix in suffixes {
+            let suffix_len = suffix.chars().count();
+            // Keep at least three characters of stem
+            if word.ends_with(suffix) && char_count >= suffix_len + 3 {
+                return word[..word.len() - suffix.len()].to_string();
+            }
+        }
+        word.to_string()
+    }
+
+    fn stop_words(language: Language) -> &'static [&'static str] {
+        match language {
+            Language::English => &[
+                "a", "an", "and", "are", "as", "at", "be", "but", "by", "for", "if", "in", "into",
+                "is", "it", "no", "not", "of", "on", "or", "such", "that", "the", "their", "then",
+                "there", "these", "they", "this", "to", "was", "will", "with",
+            ],
+            Language::Spanish => &[
+                "de", "la", "que", "el", "en", "y", "a", "los", "del", "se", "las", "por", "un",
+                "para", "con", "no", "una", "su", "al", "lo", "como", "más", "pero", "sus", "le", "o",
+            ],
+            Language::Portuguese => &[
+                "de", "a", "o", "que", "e", "do", "da", "em", "um", "para", "com", "não", "uma",
+                "os", "no", "se", "na", "por", "mais", "as", "dos", "como", "mas", "ao", "ele", "das",
+            ],
+            Language::French => &[
+                "le", "la", "les", "de", "des", "du", "un", "une", "et", "en", "à", "au", "aux",
+                "ce", "que", "qui", "dans", "pour", "pas", "par", "sur", "se", "ne", "il", "elle",
+            ],
+            Language::German => &[
+                "der", "die", "das", "und", "in", "zu", "den", "von", "mit", "ist", "des", "sich",
+                "auf", "für", "im", "dem", "nicht", "ein", "eine", "als", "auch", "es", "an", "am",
+            ],
+            Language::Simple => &[],
+        }
+    }
+}
+
+/// One clause of a full-text query
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum QueryClause {
+    Term(String),
+    /// Terms that must appear at consecutive positions
+    Phrase(Vec<String>),
+    /// Matches any indexed term starting with the prefix (`robo*`)
+    Prefix(String),
+}
+
+/// How clauses combine
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MatchOperator {
+    And,
+    Or,
+}
+
+/// Parsed full-text query
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FullTextQuery {
+    pub clauses: Vec<QueryClause>,
+    pub operator: MatchOperator,
+}
+
+impl FullTextQuery {
+    /// Parse `quick "brown fox" jump*` into term, phrase and prefix clauses.
+    /// Clauses are normalized with the same analyzer used for indexing.
+    pub fn parse(query: &str, analyzer: &TextAnalyzer) -> Result<Self> {
+        if query.len() > 4096 {
+            return Err(Error::Query("Full-text query too long (max 4096 bytes)".to_string()));
+        }
+        let mut clauses = Vec::new();
+        let mut rest = query;
+        while !rest.is_empty() {
+            rest = rest.trim_start();
+            if rest.is_empty() {
+                break;
+            }
+            if let Some(stripped) = rest.strip_prefix('"') {
+                let end = stripped.find('"').unwrap_or(stripped.len());
+                let terms: Vec<String> = analyzer.analyze(&stripped[..end]).into_iter().map(|t| t.term).collect();
+                match terms.len() {
+                    0 => {}
+                    1 => clauses.push(QueryClause::Term(terms[0].clone())),
+                    _ => clauses.push(QueryClause::Phrase(terms)),
+                }
+                rest = stripped.get(end + 1..).unwrap_or("");
+                continue;
+            }
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            let word = &rest[..end];
+            rest = &rest[end..];
+            if let Some(prefix) = word.strip_suffix('*') {
+                let prefix: String = prefix.chars().filter(|c| c.is_alphanumeric()).collect::<String>().to_lowercase();
+                if !prefix.is_empty() {
+                    clauses.push(QueryClause::Prefix(prefix));
+                }
+            } else {
+                for token in analyzer.analyze(word) {
+                    clauses.push(QueryClause::Term(token.term));
+                }
+            }
+        }
+        Ok(Self { clauses, operator: MatchOperator::And })
+    }
+
+    pub fn with_operator(mut self, operator: MatchOperator) -> Self {
+        self.operator = operator;
+        self
+    }
+
+    /// Evaluate the query against a single text without an index (used by the
+    /// MATCH filter when no index is available)
+    pub fn matches_text(&self, analyzer: &TextAnalyzer, text: &str) -> bool {
+        if self.clauses.is_empty() {
+            return false;
+        }
+        let tokens = analyzer.analyze(text);
+        let mut positions: HashMap<&str, Vec<u32>> = HashMap::new();
+        for token in &tokens {
+            positions.entry(token.term.as_str()).or_default().push(token.position);
+        }
+        let clause_matches = |clause: &QueryClause| match clause {
+            QueryClause::Term(term) => positions.contains_key(term.as_str()),
+            QueryClause::Prefix(prefix) => positions.keys().any(|t| t.starts_with(prefix.as_str())),
+            QueryClause::Phrase(terms) => {
+                let lists: Option<Vec<&Vec<u32>>> = terms.iter().map(|t| positions.get(t.as_str())).collect();
+                match lists {
+                    Some(lists) => phrase_positions(&lists) > 0,
+                    None => false,
+                }
+            }
+        };
+        match self.operator {
+            MatchOperator::And => self.clauses.iter().all(clause_matches),
+            MatchOperator::Or => self.clauses.iter().any(clause_matches),
+        }
+    }
+}
+
+/// Count start positions where the lists form a consecutive phrase
+fn phrase_positions(lists: &[&Vec<u32>]) -> usize {
+    let Some(first) = lists.first() else { return 0 };
+    first.iter().filter(|&&start| {
+        lists.iter().enumerate().skip(1).all(|(offset, list)| list.contains(&(start + offset as u32)))
+    }).count()
+}
+
+/// Ranked search hit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FullTextHit {
+    pub doc_id: u64,
+    pub score: f64,
+}
+
+/// BM25 parameters
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Bm25Params {
+    pub k1: f64,
+    pub b: f64,
+}
+
+impl Default for Bm25Params {
+    fn default() -> Self {
+        Self { k1: 1.2, b: 0.75 }
+    }
+}
+
+/// Inverted index over one text column
+pub struct FullTextIndex {
+    analyzer: TextAnalyzer,
+    params: Bm25Params,
+    /// term -> doc_id -> positions (BTreeMap keeps terms sorted for prefix scans)
+    postings: BTreeMap<String, HashMap<u64, Vec<u32>>>,
+    /// doc_id -> (terms in doc, document length)
+    documents: HashMap<u64, (Vec<String>, u32)>,
+    total_length: u64,
+}
+
+impl FullTextIndex {
+    pub fn new(language: Language) -> Self {
+        Self::with_params(language, Bm25Params::default())
+    }
+
+    pub fn with_params(language: Language, params: Bm25Params) -> Self {
+        Self {
+            analyzer: TextAnalyzer::new(language),
+            params,
+            postings: BTreeMap::new(),
+            documents: HashMap::new(),
+            total_length: 0,
+        }
+    }
+
+    pub fn analyzer(&self) -> &TextAnalyzer {
+        &self.analyzer
+    }
+
+    pub fn document_count(&self) -> usize {
+        self.documents.len()
+    }
+
+    pub fn term_count(&self) -> usize {
+        self.postings.len()
+    }
+
+    /// Index (or re-index) a document
+    pub fn index_document(&mut self, doc_id: u64, text: &str) {
+        self.remove_document(doc_id);
+        let tokens = self.analyzer.analyze(text);
+        let length = tokens.len() as u32;
+        let mut terms = Vec::new();
+        for token in tokens {
+            let docs = self.postings.entry(token.term.clone()).or_default();
+            let positions = docs.entry(doc_id).or_default();
+            if positions.is_empty() {
+                terms.push(token.term);
+            }
+            positions.push(token.position);
+        }
+        self.total_length += length as u64;
+        self.documents.insert(doc_id, (terms, length));
+    }
+
+    /// Remove a document from the index
+    pub fn remove_document(&mut self, doc_id: u64) -> bool {
+        let Some((terms, length)) = self.documents.remove(&doc_id) else { return false };
+        self.total_length = self.total_length.saturating_sub(length as u64);
+        for term in terms {
+            if let Some(docs) = self.postings.get_mut(&term) {
+                docs.remove(&doc_id);
+                if docs.is_empty() {
+                    self.postings.remove(&term);
+                }
+            }
+        }
+        true
+    }
+
+    /// Parse and run a query string
+    pub fn search_str(&self, query: &str, limit: usize) -> Result<Vec<FullTextHit>> {
+        let query = FullTextQuery::parse(query, &self.analyzer)?;
+        Ok(self.search(&query, limit))
+    }
+
+    /// Run a parsed query; results are ordered by descending BM25 score
+    pub fn search(&self, query: &FullTextQuery, limit: usize) -> Vec<FullTextHit> {
+        if query.clauses.is_empty() || self.documents.is_empty() {
+            return Vec::new();
+        }
+        let mut scores: HashMap<u64, f64> = HashMap::new();
+        let mut matched_clauses: HashMap<u64, usize> = HashMap::new();
+
+        for clause in &query.clauses {
+            // doc_id -> term frequency contributions for this clause
+            let mut clause_hits: HashMap<u64, f64> = HashMap::new();
+            match clause {
+                QueryClause::Term(term) => {
+                    if let Some(docs) = self.postings.get(term) {
+                        let idf = self.idf(docs.len());
+                        for (doc_id, positions) in docs {
+                            clause_hits.insert(*doc_id, self.bm25(*doc_id, positions.len() as f64, idf));
+                        }
+                    }
+                }
+                QueryClause::Prefix(prefix) => {
+                    for (_, docs) in self.postings.range(prefix.clone()..).take_while(|(t, _)| t.starts_with(prefix.as_str())) {
+                        let idf = self.idf(docs.len());
+                        for (doc_id, positions) in docs {
+                            *clause_hits.entry(*doc_id).or_insert(0.0) += self.bm25(*doc_id, positions.len() as f64, idf);
+                        }
+                    }
+                }
+                QueryClause::Phrase(terms) => {
+                    let lists: Option<Vec<&HashMap<u64, Vec<u32>>>> = terms.iter().map(|t| self.postings.get(t)).collect();
+                    if let Some(lists) = lists {
+                        let first = lists[0];
+                        let mut phrase_docs: Vec<(u64, usize)> = Vec::new();
+                        for doc_id in first.keys() {
+                            let doc_lists: Option<Vec<&Vec<u32>>> = lists.iter().map(|l| l.get(doc_id)).collect();
+                            if let Some(doc_lists) = doc_lists {
+                                let freq = phrase_positions(&doc_lists);
+                                if freq > 0 {
+                                    phrase_docs.push((*doc_id, freq));
+                                }
+                            }
+                        }
+                        let idf = self.idf(phrase_docs.len());
+                        for (doc_id, freq) in phrase_docs {
+                            clause_hits.insert(doc_id, self.bm25(doc_id, freq as f64, idf));
+                        }
+                    }
+                }
+            }
+            for (doc_id, score) in clause_hits {
+                *scores.entry(doc_id).or_insert(0.0) += score;
+                *matched_clauses.entry(doc_id).or_insert(0) += 1;
+            }
+        }
+
+        let required = match query.operator {
+            MatchOperator::And => query.clauses.len(),
+            MatchOperator::Or => 1,
+        };
+        let mut hits: Vec<FullTextHit> = scores.into_iter()
+            .filter(|(doc_id, _)| matched_clauses.get(doc_id).copied().unwrap_or(0) >= required)
+            .map(|(doc_id, score)| FullTextHit { doc_id, score })
+            .collect();
+        hits.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal)
+            .then(a.doc_id.cmp(&b.doc_id)));
+        hits.truncate(limit);
+        hits
+    }
+
+    fn idf(&self, doc_freq: usize) -> f64 {
+        let n = self.documents.len() as f64;
+        let df = doc_freq as f64;
+        ((n - df + 0.5) / (df + 0.5) + 1.0).ln()
+    }
+
+    fn bm25(&self, doc_id: u64, term_freq: f64, idf: f64) -> f64 {
+        let doc_len = self.documents.get(&doc_id).map(|(_, l)| *l as f64).unwrap_or(0.0);
+        let avg_len = if self.documents.is_empty() {
+            1.0
+        } else {
+            (self.total_length as f64 / self.documents.len() as f64).max(1.0)
+        };
+        let Bm25Params { k1, b } = self.params;
+        idf * (term_freq * (k1 + 1.0)) / (term_freq + k1 * (1.0 - b + b * doc_len / avg_len))
+    }
+}
+
+type IndexMap = HashMap<(TableId, String), Arc<RwLock<FullTextIndex>>>;
+
+/// Full-text indexes for table columns, updated incrementally on write
+#[derive(Default)]
+pub struct FullTextIndexManager {
+    indexes: RwLock<IndexMap>,
+    /// Next row id per table (row ids are the document ids)
+    row_counters: RwLock<HashMap<TableId, u64>>,
+}
+
+impl FullTextIndexManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a full-text index on a String column, backfilling the values already
+    /// stored (row ids are storage offsets, so `existing` must be the full column)
+    pub fn create_index(&self, table_id: TableId, schema: &Schema, column: &str, language: Language, existing: &[String]) -> Result<()> {
+        let field = schema.field(column).ok_or_else(|| Error::ColumnNotFound(column.to_string()))?;
+        if !matches!(field.data_type, DataType::String) {
+            return Err(Error::Index(format!("Full-text index requires a String column, '{}' is {:?}", column, field.data_type)));
+        }
+        let mut index = FullTextIndex::new(language);
+        for (row_id, text) in existing.iter().enumerate() {
+            index.index_document(row_id as u64, text);
+        }
+        self.indexes.write().insert((table_id, column.to_string()), Arc::new(RwLock::new(index)));
+        self.row_counters.write().insert(table_id, existing.len() as u64);
+        info!("Created full-text index on table {} column {} ({:?})", table_id.0, column, language);
+        Ok(())
+    }
+
+    pub fn drop_index(&self, table_id: TableId, column: &str) -> bool {
+        self.indexes.write().remove(&(table_id, column.to_string())).is_some()
+    }
+
+    /// Drop all indexes of a deleted table
+    pub fn drop_table(&self, table_id: TableId) {
+        self.indexes.write().retain(|(t, _), _| *t != table_id);
+        self.row_counters.write().remove(&table_id);
+    }
+
+    pub fn get_index(&self, table_id: TableId, column: &str) -> Option<Arc<RwLock<FullTextIndex>>> {
+        self.indexes.read().get(&(table_id, column.to_string())).cloned()
+    }
+
+    pub fn indexed_columns(&self, table_id: TableId) -> Vec<String> {
+        self.indexes.read().keys().filter(|(t, _)| *t == table_id).map(|(_, c)| c.clone()).collect()
+    }
+
+    /// Rows the indexes of a table have seen; None when it has no index
+    pub fn indexed_rows(&self, table_id: TableId) -> Option<u64> {
+        if self.indexed_columns(table_id).is_empty() {
+            return None;
+        }
+        self.row_counters.read().get(&table_id).copied()
+    }
+
+    /// Index newly written rows (call after a successful write)
+    pub fn index_insert(&self, table_id: TableId, schema: &Schema, columns: &[Column]) -> Result<()> {
+        let row_count = columns.first().map(|c| c.len()).unwrap_or(0) as u64;
+        let first_row_id = {
+            let mut counters = self.row_counters.write();
+            let counter = counters.entry(table_id).or_insert(0);
+            let first = *counter;
+            *counter += row_count;
+            first
+        };
+        for column_name in self.indexed_columns(table_id) {
+            let Some(idx) = schema.field_index(&column_name) else { continue };
+            let Some(Column::String(values)) = columns.get(idx) else { continue };
+            if let Some(index) = self.get_index(table_id, &column_name) {
+                let mut index = index.write();
+                for (offset, text) in values.iter().enumerate() {
+                    index.index_document(first_row_id + offset as u64, text);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Rebuild a table's indexes after its rows were rewritten or removed;
+    /// `columns` is the table's full content in schema order
+    pub fn reindex_table(&self, table_id: TableId, schema: &Schema, columns: &[Column]) -> Result<()> {
+        for column_name in self.indexed_columns(table_id) {
+            let Some(index) = self.get_index(table_id, &column_name) else { continue };
+            let language = index.read().analyzer().language();
+            let existing = match schema.field_index(&column_name).and_then(|idx| columns.get(idx)) {
+                Some(Column::String(values)) => values.clone(),
+                _ => Vec::new(),
+            };
+            self.create_index(table_id, schema, &column_name, language, &existing)?;
+        }
+        Ok(())
+    }
+
+    /// Search an indexed column
+    pub fn search(&self, table_id: TableId, column: &str, query: &str, limit: usize) -> Result<Vec<FullTextHit>> {
+        let index = self.get_index(table_id, column)
+            .ok_or_else(|| Error::Index(format!("No full-text index on table {} column {}", table_id.0, column)))?;
+        let index = index.read();
+        index.search_str(query, limit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index() -> FullTextIndex {
+        let mut index = FullTextIndex::new(Language::English);
+        index.index_document(1, "The robot is walking to the kitchen");
+        index.index_document(2, "Kitchen robots cook dinner for the family");
+        index.index_document(3, "A quick brown fox jumps over the lazy dog");
+        index
+    }
+
+    #[test]
+    fn test_stemmed_term_search() {
+        let index = index();
+        let hits = index.search_str("robots", 10).unwrap();
+        let ids: Vec<u64> = hits.iter().map(|h| h.doc_id).collect();
+        assert!(ids.contains(&1) && ids.contains(&2));
+    }
+
+    #[test]
+    fn test_phrase_and_prefix() {
+        let index = index();
+        let hits = index.search_str("\"brown fox\"", 10).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].doc_id, 3);
+        assert!(index.search_str("\"fox brown\"", 10).unwrap().is_empty());
+
+        let hits = index.search_str("kitch*", 10).unwrap();
+        assert_eq!(hits.len(), 2);
+    }
+
+    #[test]
+    fn test_incremental_update_and_remove() {
+        let mut index = index();
+        index.index_document(3, "kitchen cleanup");
+        assert_eq!(index.search_str("kitchen", 10).unwrap().len(), 3);
+        assert!(index.search_str("fox", 10).unwrap().is_empty());
+        index.remove_document(3);
+        assert_eq!(index.document_count(), 2);
+    }
+
+    #[test]
+    fn test_matches_text() {
+        let analyzer = TextAnalyzer::new(Language::English);
+        let query = FullTextQuery::parse("walking kitchen", &analyzer).unwrap();
+        assert!(query.matches_text(&analyzer, "walked into the kitchen"));
+        assert!(!query.matches_text(&analyzer, "the kitchen is empty"));
+    }
+}
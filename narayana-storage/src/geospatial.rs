@@ -0,0 +1,992 @@
+// Geospatial data types and spatial index
+// Point/Geometry values are stored in Binary columns; an R-tree answers radius, bounding box and nearest-k queries
+// Indexes live in memory: their definitions are persisted and `restore` rebuilds them from storage on startup
+
+use narayana_core::{column::Column, schema::{DataType, Schema}, types::TableId, Error, Result};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::path::PathBuf;
+use std::sync::Arc;
+use parking_lot::RwLock;
+use tracing::{info, warn};
+
+use crate::column_store::ColumnStore;
+
+/// Mean Earth radius used for great-circle distances
+pub const EARTH_RADIUS_METERS: f64 = 6_371_008.8;
+
+/// Maximum vertices in a line string or polygon (prevents oversized values)
+const MAX_VERTICES: usize = 100_000;
+
+/// WGS84 point (degrees)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Point {
+    pub lon: f64,
+    pub lat: f64,
+}
+
+impl Point {
+    pub fn new(lon: f64, lat: f64) -> Result<Self> {
+        if !lon.is_finite() || !(-180.0..=180.0).contains(&lon) {
+            return Err(Error::Query(format!("Longitude out of range: {}", lon)));
+        }
+        if !lat.is_finite() || !(-90.0..=90.0).contains(&lat) {
+            return Err(Error::Query(format!("Latitude out of range: {}", lat)));
+        }
+        Ok(Self { lon, lat })
+    }
+
+    /// Great-circle (haversine) distance in meters
+    pub fn distance_meters(&self, other: &Point) -> f64 {
+        let (lat1, lat2) = (self.lat.to_radians(), other.lat.to_radians());
+        let d_lat = lat2 - lat1;
+        let d_lon = (other.lon - self.lon).to_radians();
+        let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS_METERS * a.sqrt().min(1.0).asin()
+    }
+}
+
+/// Axis-aligned bounding box in degrees (does not wrap the antimeridian)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BoundingBox {
+    pub min_lon: f64,
+    pub min_lat: f64,
+    pub max_lon: f64,
+    pub max_lat: f64,
+}
+
+impl BoundingBox {
+    pub fn new(min_lon: f64, min_lat: f64, max_lon: f64, max_lat: f64) -> Result<Self> {
+        Point::new(min_lon, min_lat)?;
+        Point::new(max_lon, max_lat)?;
+        if min_lon > max_lon || min_lat > max_lat {
+            return Err(Error::Query("Bounding box min must not exceed max".to_string()));
+        }
+        Ok(Self { min_lon, min_lat, max_lon, max_lat })
+    }
+
+    pub fn from_point(point: &Point) -> Self {
+        Self { min_lon: point.lon, min_lat: point.lat, max_lon: point.lon, max_lat: point.lat }
+    }
+
+    /// Box covering every point within `radius_meters` of `center`
+    pub fn around(center: &Point, radius_meters: f64) -> Self {
+        let lat_delta = (radius_meters / EARTH_RADIUS_METERS).to_degrees();
+        let min_lat = (center.lat - lat_delta).max(-90.0);
+        let max_lat = (center.lat + lat_delta).min(90.0);
+        // Longitude degrees shrink towards the poles; near a pole take the full range
+        let max_abs_lat = min_lat.abs().max(max_lat.abs());
+        let (min_lon, max_lon) = if max_abs_lat >= 89.0 {
+            (-180.0, 180.0)
+        } else {
+            let lon_delta = lat_delta / max_abs_lat.to_radians().cos();
+            ((center.lon - lon_delta).max(-180.0), (center.lon + lon_delta).min(180.0))
+        };
+        Self { min_lon, min_lat, max_lon, max_lat }
+    }
+
+    pub fn contains_point(&self, point: &Point) -> bool {
+        point.lon >= self.min_lon && point.lon <= self.max_lon
+            && point.lat >= self.min_lat && point.lat <= self.max_lat
+    }
+
+    pub fn intersects(&self, other: &BoundingBox) -> bool {
+        self.min_lon <= other.max_lon && self.max_lon >= other.min_lon
+            && self.min_lat <= other.max_lat && self.max_lat >= other.min_lat
+    }
+
+    pub fn union(&self, other: &BoundingBox) -> BoundingBox {
+        BoundingBox {
+            min_lon: self.min_lon.min(other.min_lon),
+            min_lat: self.min_lat.min(other.min_lat),
+            max_lon: self.max_lon.max(other.max_lon),
+            max_lat: self.max_lat.max(other.max_lat),
+        }
+    }
+
+    pub fn area(&self) -> f64 {
+        (self.max_lon - self.min_lon) * (self.max_lat - self.min_lat)
+    }
+
+    fn enlargement(&self, other: &BoundingBox) -> f64 {
+        self.union(other).area() - self.area()
+    }
+
+    /// Distance from `point` to the closest point of the box (0 when inside)
+    pub fn min_distance_meters(&self, point: &Point) -> f64 {
+        let clamped = Point {
+            lon: point.lon.clamp(self.min_lon, self.max_lon),
+            lat: point.lat.clamp(self.min_lat, self.max_lat),
+        };
+        point.distance_meters(&clamped)
+    }
+}
+
+/// Geometry value of a Point or Geometry column
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "coordinates")]
+pub enum Geometry {
+    Point(Point),
+    LineString(Vec<Point>),
+    /// Exterior ring; closing vertex is optional
+    Polygon(Vec<Point>),
+}
+
+const TAG_POINT: u8 = 1;
+const TAG_LINESTRING: u8 = 2;
+const TAG_POLYGON: u8 = 3;
+
+impl Geometry {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Geometry::Point(_) => "Point",
+            Geometry::LineString(_) => "LineString",
+            Geometry::Polygon(_) => "Polygon",
+        }
+    }
+
+    pub fn bbox(&self) -> BoundingBox {
+        match self {
+            Geometry::Point(p) => BoundingBox::from_point(p),
+            Geometry::LineString(points) | Geometry::Polygon(points) => {
+                points.iter().skip(1).fold(BoundingBox::from_point(&points[0]), |bbox, p| {
+                    bbox.union(&BoundingBox::from_point(p))
+                })
+            }
+        }
+    }
+
+    /// Binary encoding stored in the column: tag byte, then little-endian
+    /// (lon, lat) f64 pairs (line strings and polygons prefix a u32 count)
+    pub fn encode(&self) -> Vec<u8> {
+        let (tag, points): (u8, &[Point]) = match self {
+            Geometry::Point(p) => (TAG_POINT, std::slice::from_ref(p)),
+            Geometry::LineString(points) => (TAG_LINESTRING, points),
+            Geometry::Polygon(points) => (TAG_POLYGON, points),
+        };
+        let mut bytes = Vec::with_capacity(5 + points.len() * 16);
+        bytes.push(tag);
+        if tag != TAG_POINT {
+            bytes.extend_from_slice(&(points.len() as u32).to_le_bytes());
+        }
+        for p in points {
+            bytes.extend_from_slice(&p.lon.to_le_bytes());
+            bytes.extend_from_slice(&p.lat.to_le_bytes());
+        }
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let (&tag, rest) = bytes.split_first()
+            .ok_or_else(|| Error::Deserialization("Empty geometry value".to_string()))?;
+        let read_points = |data: &[u8], count: usize| -> Result<Vec<Point>> {
+            if data.len() != count * 16 {
+                return Err(Error::Deserialization(format!(
+                    "Geometry payload has {} bytes, expected {}", data.len(), count * 16
+                )));
+            }
+            data.chunks_exact(16)
+                .map(|chunk| {
+                    let lon = f64::from_le_bytes(chunk[..8].try_into().unwrap());
+                    let lat = f64::from_le_bytes(chunk[8..].try_into().unwrap());
+                    Point::new(lon, lat)
+                })
+                .collect()
+        };
+        match tag {
+            TAG_POINT => Ok(Geometry::Point(read_points(rest, 1)?[0])),
+            TAG_LINESTRING | TAG_POLYGON => {
+                if rest.len() < 4 {
+                    return Err(Error::Deserialization("Truncated geometry value".to_string()));
+                }
+                let count = u32::from_le_bytes(rest[..4].try_into().unwrap()) as usize;
+                if count > MAX_VERTICES {
+                    return Err(Error::Deserialization(format!("Geometry has too many vertices: {}", count)));
+                }
+                let points = read_points(&rest[4..], count)?;
+                if tag == TAG_LINESTRING {
+                    Geometry::line_string(points)
+                } else {
+                    Geometry::polygon(points)
+                }
+            }
+            other => Err(Error::Deserialization(format!("Unknown geometry tag: {}", other))),
+        }
+    }
+
+    pub fn line_string(points: Vec<Point>) -> Result<Self> {
+        if points.len() < 2 || points.len() > MAX_VERTICES {
+            return Err(Error::Query(format!("LineString needs 2..={} points, got {}", MAX_VERTICES, points.len())));
+        }
+        Ok(Geometry::LineString(points))
+    }
+
+    pub fn polygon(points: Vec<Point>) -> Result<Self> {
+        if points.len() < 3 || points.len() > MAX_VERTICES {
+            return Err(Error::Query(format!("Polygon needs 3..={} points, got {}", MAX_VERTICES, points.len())));
+        }
+        Ok(Geometry::Polygon(points))
+    }
+
+    /// Parse WKT: `POINT(lon lat)`, `LINESTRING(lon lat, ...)`, `POLYGON((lon lat, ...))`
+    pub fn from_wkt(wkt: &str) -> Result<Self> {
+        let wkt = wkt.trim();
+        let open = wkt.find('(').ok_or_else(|| Error::Query(format!("Invalid WKT: {}", wkt)))?;
+        let kind = wkt[..open].trim().to_ascii_uppercase();
+        let body = wkt[open..].trim_start_matches('(').trim_end_matches(')');
+        let points = body
+            .split(',')
+            .map(|pair| {
+                let mut parts = pair.split_whitespace().map(str::parse::<f64>);
+                match (parts.next(), parts.next(), parts.next()) {
+                    (Some(Ok(lon)), Some(Ok(lat)), None) => Point::new(lon, lat),
+                    _ => Err(Error::Query(format!("Invalid WKT coordinate: '{}'", pair.trim()))),
+                }
+            })
+            .collect::<Result<Vec<Point>>>()?;
+        match kind.as_str() {
+            "POINT" if points.len() == 1 => Ok(Geometry::Point(points[0])),
+            "LINESTRING" => Geometry::line_string(points),
+            "POLYGON" => Geometry::polygon(points),
+            _ => Err(Error::Query(format!("Unsupported WKT geometry: {}", wkt))),
+        }
+    }
+
+    /// Accepts `[lon, lat]`, `{"lon": .., "lat": ..}`, a WKT string, or the
+    /// tagged form `{"type": "Polygon", "coordinates": [...]}`
+    pub fn from_json(value: &serde_json::Value) -> Result<Self> {
+        match value {
+            serde_json::Value::String(wkt) => Geometry::from_wkt(wkt),
+            serde_json::Value::Array(coords) if coords.len() == 2 => {
+                match (coords[0].as_f64(), coords[1].as_f64()) {
+                    (Some(lon), Some(lat)) => Ok(Geometry::Point(Point::new(lon, lat)?)),
+                    _ => Err(Error::Query("Point coordinates must be numbers".to_string())),
+                }
+            }
+            serde_json::Value::Object(map) if map.contains_key("lon") && map.contains_key("lat") => {
+                match (map["lon"].as_f64(), map["lat"].as_f64()) {
+                    (Some(lon), Some(lat)) => Ok(Geometry::Point(Point::new(lon, lat)?)),
+                    _ => Err(Error::Query("Point coordinates must be numbers".to_string())),
+                }
+            }
+            other => {
+                let geometry: Geometry = serde_json::from_value(other.clone())
+                    .map_err(|e| Error::Query(format!("Invalid geometry: {}", e)))?;
+                // Re-validate coordinates and vertex counts
+                Geometry::decode(&geometry.encode())
+            }
+        }
+    }
+
+    /// Ray-casting point-in-polygon (planar in lon/lat)
+    pub fn contains_point(&self, point: &Point) -> bool {
+        match self {
+            Geometry::Point(p) => p == point,
+            Geometry::LineString(_) => false,
+            Geometry::Polygon(ring) => {
+                let mut inside = false;
+                let mut j = ring.len() - 1;
+                for i in 0..ring.len() {
+                    let (a, b) = (&ring[i], &ring[j]);
+                    if (a.lat > point.lat) != (b.lat > point.lat)
+                        && point.lon < (b.lon - a.lon) * (point.lat - a.lat) / (b.lat - a.lat) + a.lon
+                    {
+                        inside = !inside;
+                    }
+                    j = i;
+                }
+                inside
+            }
+        }
+    }
+
+    /// Distance in meters from `point` (0 inside a polygon; vertex distance otherwise)
+    pub fn distance_meters(&self, point: &Point) -> f64 {
+        match self {
+            Geometry::Point(p) => p.distance_meters(point),
+            Geometry::Polygon(_) if self.contains_point(point) => 0.0,
+            Geometry::LineString(points) | Geometry::Polygon(points) => points
+                .iter()
+                .map(|p| p.distance_meters(point))
+                .fold(f64::INFINITY, f64::min),
+        }
+    }
+}
+
+/// Spatial query predicate
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SpatialPredicate {
+    WithinRadius { lon: f64, lat: f64, radius_meters: f64 },
+    WithinBox { min_lon: f64, min_lat: f64, max_lon: f64, max_lat: f64 },
+    Nearest { lon: f64, lat: f64, k: usize },
+}
+
+impl SpatialPredicate {
+    /// Row-level check for radius and box predicates (nearest-k needs the index)
+    pub fn matches(&self, geometry: &Geometry) -> Result<bool> {
+        match self {
+            SpatialPredicate::WithinRadius { lon, lat, radius_meters } => {
+                Ok(geometry.distance_meters(&Point::new(*lon, *lat)?) <= *radius_meters)
+            }
+            SpatialPredicate::WithinBox { min_lon, min_lat, max_lon, max_lat } => {
+                let bbox = BoundingBox::new(*min_lon, *min_lat, *max_lon, *max_lat)?;
+                Ok(bbox.intersects(&geometry.bbox()))
+            }
+            SpatialPredicate::Nearest { .. } => Err(Error::Query(
+                "Nearest-k requires a spatial index".to_string(),
+            )),
+        }
+    }
+}
+
+/// Result of a spatial index query
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpatialHit {
+    pub doc_id: u64,
+    pub distance_meters: f64,
+}
+
+const MAX_NODE_ENTRIES: usize = 16;
+
+enum RTreeNode {
+    Leaf(Vec<(BoundingBox, u64)>),
+    Internal(Vec<(BoundingBox, Box<RTreeNode>)>),
+}
+
+impl RTreeNode {
+    fn bbox(&self) -> Option<BoundingBox> {
+        let mut boxes: Box<dyn Iterator<Item = &BoundingBox>> = match self {
+            RTreeNode::Leaf(entries) => Box::new(entries.iter().map(|(b, _)| b)),
+            RTreeNode::Internal(children) => Box::new(children.iter().map(|(b, _)| b)),
+        };
+        let first = *boxes.next()?;
+        Some(boxes.fold(first, |acc, b| acc.union(b)))
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            RTreeNode::Leaf(entries) => entries.len(),
+            RTreeNode::Internal(children) => children.len(),
+        }
+    }
+
+    /// Insert and return a split-off sibling when the node overflows
+    fn insert(&mut self, bbox: BoundingBox, id: u64) -> Option<RTreeNode> {
+        match self {
+            RTreeNode::Leaf(entries) => {
+                entries.push((bbox, id));
+                if entries.len() > MAX_NODE_ENTRIES {
+                    let (keep, split) = quadratic_split(std::mem::take(entries));
+                    *entries = keep;
+                    return Some(RTreeNode::Leaf(split));
+                }
+                None
+            }
+            RTreeNode::Internal(children) => {
+                // Choose the child needing the least enlargement (ties: smaller area)
+                let best = children
+                    .iter()
+                    .enumerate()
+                    .min_by(|(_, (a, _)), (_, (b, _))| {
+                        a.enlargement(&bbox)
+                            .partial_cmp(&b.enlargement(&bbox))
+                            .unwrap_or(Ordering::Equal)
+                            .then(a.area().partial_cmp(&b.area()).unwrap_or(Ordering::Equal))
+                    })
+                    .map(|(i, _)| i)
+                    .unwrap_or(0);
+                let split = children[best].1.insert(bbox, id);
+                children[best].0 = children[best].0.union(&bbox);
+                if let Some(sibling) = split {
+                    children[best].0 = children[best].1.bbox().unwrap_or(bbox);
+                    let sibling_bbox = sibling.bbox().unwrap_or(bbox);
+                    children.push((sibling_bbox, Box::new(sibling)));
+                    if children.len() > MAX_NODE_ENTRIES {
+                        let (keep, split) = quadratic_split(std::mem::take(children));
+                        *children = keep;
+                        return Some(RTreeNode::Internal(split));
+                    }
+                }
+                None
+            }
+        }
+    }
+
+    /// Remove an entry; bounding boxes stay conservative (never shrink)
+    fn remove(&mut self, bbox: &BoundingBox, id: u64) -> bool {
+        match self {
+            RTreeNode::Leaf(entries) => {
+                let before = entries.len();
+                entries.retain(|(_, entry_id)| *entry_id != id);
+                entries.len() != before
+            }
+            RTreeNode::Internal(children) => {
+                let mut removed = false;
+                for (child_bbox, child) in children.iter_mut() {
+                    if child_bbox.intersects(bbox) && child.remove(bbox, id) {
+                        removed = true;
+                        break;
+                    }
+                }
+                children.retain(|(_, child)| child.len() > 0);
+                removed
+            }
+        }
+    }
+
+    fn search(&self, query: &BoundingBox, out: &mut Vec<u64>) {
+        match self {
+            RTreeNode::Leaf(entries) => {
+                out.extend(entries.iter().filter(|(b, _)| b.intersects(query)).map(|(_, id)| *id));
+            }
+            RTreeNode::Internal(children) => {
+                for (bbox, child) in children {
+                    if bbox.intersects(query) {
+                        child.search(query, out);
+                    }
+                }
+            }
+        }
+    }
+}
+
+type NodeEntries<T> = Vec<(BoundingBox, T)>;
+
+/// Guttman's quadratic split: seed with the most wasteful pair, then assign
+/// each remaining entry to the group whose box grows least
+fn quadratic_split<T>(mut entries: Vec<(BoundingBox, T)>) -> (NodeEntries<T>, NodeEntries<T>) {
+    let mut seeds = (0, 1);
+    let mut worst = f64::NEG_INFINITY;
+    for i in 0..entries.len() {
+        for j in (i + 1)..entries.len() {
+            let waste = entries[i].0.union(&entries[j].0).area() - entries[i].0.area() - entries[j].0.area();
+            if waste > worst {
+                worst = waste;
+                seeds = (i, j);
+            }
+        }
+    }
+    // Remove the higher index first so the lower one stays valid
+    let second = entries.swap_remove(seeds.1);
+    let first = entries.swap_remove(seeds.0);
+    let (mut bbox_a, mut bbox_b) = (first.0, second.0);
+    let (mut group_a, mut group_b) = (vec![first], vec![second]);
+    let min_fill = MAX_NODE_ENTRIES / 2;
+    while let Some(entry) = entries.pop() {
+        let remaining = entries.len() + 1;
+        let to_a = if group_a.len() + remaining <= min_fill {
+            true
+        } else if group_b.len() + remaining <= min_fill {
+            false
+        } else {
+            bbox_a.enlargement(&entry.0) <= bbox_b.enlargement(&entry.0)
+        };
+        if to_a {
+            bbox_a = bbox_a.union(&entry.0);
+            group_a.push(entry);
+        } else {
+            bbox_b = bbox_b.union(&entry.0);
+            group_b.push(entry);
+        }
+    }
+    (group_a, group_b)
+}
+
+/// Heap entry for best-first nearest-neighbour search (min-heap by distance)
+enum NearestCandidate<'a> {
+    Node(f64, &'a RTreeNode),
+    Item(f64, u64),
+}
+
+impl NearestCandidate<'_> {
+    fn distance(&self) -> f64 {
+        match self {
+            NearestCandidate::Node(d, _) | NearestCandidate::Item(d, _) => *d,
+        }
+    }
+}
+
+impl PartialEq for NearestCandidate<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.distance() == other.distance()
+    }
+}
+
+impl Eq for NearestCandidate<'_> {}
+
+impl PartialOrd for NearestCandidate<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for NearestCandidate<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.distance().partial_cmp(&self.distance()).unwrap_or(Ordering::Equal)
+    }
+}
+
+/// R-tree over the geometries of one column, keyed by row id
+pub struct SpatialIndex {
+    root: RTreeNode,
+    geometries: HashMap<u64, Geometry>,
+}
+
+impl SpatialIndex {
+    pub fn new() -> Self {
+        Self {
+            root: RTreeNode::Leaf(Vec::new()),
+            geometries: HashMap::new(),
+        }
+    }
+
+    /// Insert or replace the geometry of a row
+    pub fn insert(&mut self, id: u64, geometry: Geometry) {
+        self.remove(id);
+        let bbox = geometry.bbox();
+        if let Some(sibling) = self.root.insert(bbox, id) {
+            let old_root = std::mem::replace(&mut self.root, RTreeNode::Internal(Vec::new()));
+            let children = [old_root, sibling]
+                .into_iter()
+                .map(|node| (node.bbox().unwrap_or(bbox), Box::new(node)))
+                .collect();
+            self.root = RTreeNode::Internal(children);
+        }
+        self.geometries.insert(id, geometry);
+    }
+
+    pub fn remove(&mut self, id: u64) -> bool {
+        match self.geometries.remove(&id) {
+            Some(geometry) => {
+                self.root.remove(&geometry.bbox(), id);
+                if self.root.len() == 0 {
+                    self.root = RTreeNode::Leaf(Vec::new());
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.geometries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.geometries.is_empty()
+    }
+
+    /// Rows whose geometry intersects the box
+    pub fn within_box(&self, bbox: &BoundingBox, limit: usize) -> Vec<SpatialHit> {
+        let center = Point {
+            lon: (bbox.min_lon + bbox.max_lon) / 2.0,
+            lat: (bbox.min_lat + bbox.max_lat) / 2.0,
+        };
+        let mut candidates = Vec::new();
+        self.root.search(bbox, &mut candidates);
+        let mut hits: Vec<SpatialHit> = candidates
+            .into_iter()
+            .filter_map(|id| {
+                let geometry = self.geometries.get(&id)?;
+                Some(SpatialHit { doc_id: id, distance_meters: geometry.distance_meters(&center) })
+            })
+            .collect();
+        sort_hits(&mut hits);
+        hits.truncate(limit);
+        hits
+    }
+
+    /// Rows within `radius_meters` of `center`, closest first
+    pub fn within_radius(&self, center: &Point, radius_meters: f64, limit: usize) -> Vec<SpatialHit> {
+        let mut candidates = Vec::new();
+        self.root.search(&BoundingBox::around(center, radius_meters), &mut candidates);
+        let mut hits: Vec<SpatialHit> = candidates
+            .into_iter()
+            .filter_map(|id| {
+                let distance = self.geometries.get(&id)?.distance_meters(center);
+                (distance <= radius_meters).then_some(SpatialHit { doc_id: id, distance_meters: distance })
+            })
+            .collect();
+        sort_hits(&mut hits);
+        hits.truncate(limit);
+        hits
+    }
+
+    /// The `k` rows closest to `center` (best-first traversal)
+    pub fn nearest(&self, center: &Point, k: usize) -> Vec<SpatialHit> {
+        let mut hits = Vec::with_capacity(k.min(self.len()));
+        let mut heap = BinaryHeap::new();
+        heap.push(NearestCandidate::Node(0.0, &self.root));
+        while let Some(candidate) = heap.pop() {
+            if hits.len() >= k {
+                break;
+            }
+            match candidate {
+                NearestCandidate::Item(distance, id) => {
+                    hits.push(SpatialHit { doc_id: id, distance_meters: distance });
+                }
+                NearestCandidate::Node(_, RTreeNode::Leaf(entries)) => {
+                    for (_, id) in entries {
+                        if let Some(geometry) = self.geometries.get(id) {
+                            heap.push(NearestCandidate::Item(geometry.distance_meters(center), *id));
+                        }
+                    }
+                }
+                NearestCandidate::Node(_, RTreeNode::Internal(children)) => {
+                    for (bbox, child) in children {
+                        heap.push(NearestCandidate::Node(bbox.min_distance_meters(center), child));
+                    }
+                }
+            }
+        }
+        hits
+    }
+
+    pub fn query(&self, predicate: &SpatialPredicate, limit: usize) -> Result<Vec<SpatialHit>> {
+        match predicate {
+            SpatialPredicate::WithinRadius { lon, lat, radius_meters } => {
+                if !radius_meters.is_finite() || *radius_meters < 0.0 {
+                    return Err(Error::Query(format!("Invalid radius: {}", radius_meters)));
+                }
+                Ok(self.within_radius(&Point::new(*lon, *lat)?, *radius_meters, limit))
+            }
+            SpatialPredicate::WithinBox { min_lon, min_lat, max_lon, max_lat } => {
+                Ok(self.within_box(&BoundingBox::new(*min_lon, *min_lat, *max_lon, *max_lat)?, limit))
+            }
+            SpatialPredicate::Nearest { lon, lat, k } => {
+                Ok(self.nearest(&Point::new(*lon, *lat)?, (*k).min(limit)))
+            }
+        }
+    }
+}
+
+impl Default for SpatialIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn sort_hits(hits: &mut [SpatialHit]) {
+    hits.sort_by(|a, b| a.distance_meters.partial_cmp(&b.distance_meters).unwrap_or(Ordering::Equal));
+}
+
+type IndexMap = HashMap<(TableId, String), Arc<RwLock<SpatialIndex>>>;
+
+/// File under the state directory listing the indexed columns
+const DEFINITIONS_FILE: &str = "indexes.json";
+
+/// An indexed column, as persisted
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct SpatialIndexDef {
+    table_id: u64,
+    column: String,
+}
+
+/// Spatial indexes for Point/Geometry columns, updated incrementally on write
+#[derive(Default)]
+pub struct SpatialIndexManager {
+    indexes: RwLock<IndexMap>,
+    /// Next row id per table (row ids are the document ids)
+    row_counters: RwLock<HashMap<TableId, u64>>,
+    /// Index definitions are persisted here when set
+    state_dir: Option<PathBuf>,
+}
+
+impl SpatialIndexManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A manager that persists its index definitions under `state_dir`
+    pub fn with_state_dir(state_dir: PathBuf) -> Result<Self> {
+        std::fs::create_dir_all(&state_dir)
+            .map_err(|e| Error::Storage(format!("Failed to create spatial index directory: {}", e)))?;
+        Ok(Self { state_dir: Some(state_dir), ..Self::default() })
+    }
+
+    /// Create a spatial index on a Point or Geometry column, backfilling the
+    /// encoded values already stored (`existing` must be the full column)
+    pub fn create_index(&self, table_id: TableId, schema: &Schema, column: &str, existing: &[Vec<u8>]) -> Result<()> {
+        self.build_index(table_id, schema, column, existing)?;
+        self.persist()?;
+        info!("Created spatial index on table {} column {}", table_id.0, column);
+        Ok(())
+    }
+
+    fn build_index(&self, table_id: TableId, schema: &Schema, column: &str, existing: &[Vec<u8>]) -> Result<()> {
+        let field = schema.field(column).ok_or_else(|| Error::ColumnNotFound(column.to_string()))?;
+        if !matches!(field.data_type, DataType::Point | DataType::Geometry) {
+            return Err(Error::Index(format!(
+                "Spatial index requires a Point or Geometry column, '{}' is {:?}", column, field.data_type
+            )));
+        }
+        let mut index = SpatialIndex::new();
+        for (row_id, bytes) in existing.iter().enumerate() {
+            // Empty values are nulls
+            if !bytes.is_empty() {
+                index.insert(row_id as u64, Geometry::decode(bytes)?);
+            }
+        }
+        self.indexes.write().insert((table_id, column.to_string()), Arc::new(RwLock::new(index)));
+        self.row_counters.write().insert(table_id, existing.len() as u64);
+        Ok(())
+    }
+
+    /// Rebuild the persisted indexes from the rows in `store` and start each
+    /// table's row ids after its stored rows. Run once tables are loaded;
+    /// returns how many indexes were rebuilt.
+    pub async fn restore(&self, store: &dyn ColumnStore) -> Result<usize> {
+        let Some(dir) = &self.state_dir else { return Ok(0) };
+        let path = dir.join(DEFINITIONS_FILE);
+        if !path.exists() {
+            return Ok(0);
+        }
+        let bytes = std::fs::read(&path)
+            .map_err(|e| Error::Storage(format!("Failed to read spatial index definitions: {}", e)))?;
+        let definitions: Vec<SpatialIndexDef> = serde_json::from_slice(&bytes)
+            .map_err(|e| Error::Serialization(format!("Failed to parse spatial index definitions: {}", e)))?;
+        let mut restored = 0;
+        for def in definitions {
+            let table_id = TableId(def.table_id);
+            // No write may land between reading the column and seeding the row ids
+            let _guard = store.lock_table(table_id).await;
+            let result = async {
+                let schema = store.get_schema(table_id).await?;
+                let idx = schema.field_index(&def.column)
+                    .ok_or_else(|| Error::ColumnNotFound(def.column.clone()))?;
+                let existing = match store.read_columns(table_id, vec![idx as u32], 0, usize::MAX).await?.pop() {
+                    Some(Column::Binary(values)) => values,
+                    _ => Vec::new(),
+                };
+                self.build_index(table_id, &schema, &def.column, &existing)
+            }.await;
+            match result {
+                Ok(()) => restored += 1,
+                Err(e) => warn!("Could not rebuild spatial index on table {} column {}: {}", table_id.0, def.column, e),
+            }
+        }
+        info!("Rebuilt {} spatial indexes", restored);
+        Ok(restored)
+    }
+
+    pub fn drop_index(&self, table_id: TableId, column: &str) -> bool {
+        let dropped = self.indexes.write().remove(&(table_id, column.to_string())).is_some();
+        if dropped {
+            if let Err(e) = self.persist() {
+                warn!("Failed to persist spatial index definitions: {}", e);
+            }
+        }
+        dropped
+    }
+
+    /// Drop all indexes of a deleted table
+    pub fn drop_table(&self, table_id: TableId) {
+        self.indexes.write().retain(|(t, _), _| *t != table_id);
+        self.row_counters.write().remove(&table_id);
+        if let Err(e) = self.persist() {
+            warn!("Failed to persist spatial index definitions: {}", e);
+        }
+    }
+
+    pub fn get_index(&self, table_id: TableId, column: &str) -> Option<Arc<RwLock<SpatialIndex>>> {
+        self.indexes.read().get(&(table_id, column.to_string())).cloned()
+    }
+
+    pub fn indexed_columns(&self, table_id: TableId) -> Vec<String> {
+        self.indexes.read().keys().filter(|(t, _)| *t == table_id).map(|(_, c)| c.clone()).collect()
+    }
+
+    /// Index newly written rows (call after a successful write)
+    pub fn index_insert(&self, table_id: TableId, schema: &Schema, columns: &[Column]) -> Result<()> {
+        let row_count = columns.first().map(|c| c.len()).unwrap_or(0) as u64;
+        let first_row_id = {
+            let mut counters = self.row_counters.write();
+            let counter = counters.entry(table_id).or_insert(0);
+            let first = *counter;
+            *counter += row_count;
+            first
+        };
+        for column_name in self.indexed_columns(table_id) {
+            let Some(idx) = schema.field_index(&column_name) else { continue };
+            let Some(Column::Binary(values)) = columns.get(idx) else { continue };
+            if let Some(index) = self.get_index(table_id, &column_name) {
+                let mut index = index.write();
+                for (offset, bytes) in values.iter().enumerate() {
+                    if !bytes.is_empty() {
+                        index.insert(first_row_id + offset as u64, Geometry::decode(bytes)?);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Rows of a table were removed or erased: keep each indexed row that
+    /// `remap` gives an id, under that id, and continue row ids from
+    /// `row_count`, the rows the table holds now
+    pub fn retain(&self, table_id: TableId, row_count: u64, remap: impl Fn(u64) -> Option<u64>) {
+        for column_name in self.indexed_columns(table_id) {
+            let Some(index) = self.get_index(table_id, &column_name) else { continue };
+            let mut index = index.write();
+            let mut kept = SpatialIndex::new();
+            for (id, geometry) in index.geometries.drain() {
+                if let Some(new_id) = remap(id) {
+                    kept.insert(new_id, geometry);
+                }
+            }
+            *index = kept;
+        }
+        self.row_counters.write().insert(table_id, row_count);
+    }
+
+    /// Query an indexed column
+    pub fn query(&self, table_id: TableId, column: &str, predicate: &SpatialPredicate, limit: usize) -> Result<Vec<SpatialHit>> {
+        let index = self.get_index(table_id, column)
+            .ok_or_else(|| Error::Index(format!("No spatial index on table {} column {}", table_id.0, column)))?;
+        let index = index.read();
+        index.query(predicate, limit)
+    }
+
+    fn persist(&self) -> Result<()> {
+        let Some(dir) = &self.state_dir else { return Ok(()) };
+        let mut definitions: Vec<SpatialIndexDef> = self.indexes.read()
+            .keys()
+            .map(|(table_id, column)| SpatialIndexDef { table_id: table_id.0, column: column.clone() })
+            .collect();
+        definitions.sort_by(|a, b| (a.table_id, &a.column).cmp(&(b.table_id, &b.column)));
+        let bytes = serde_json::to_vec(&definitions)
+            .map_err(|e| Error::Serialization(format!("Failed to serialize spatial index definitions: {}", e)))?;
+        // Write to a temp file and rename so a crash never leaves a torn list
+        let path = dir.join(DEFINITIONS_FILE);
+        let temp_path = path.with_extension("json.tmp");
+        std::fs::write(&temp_path, &bytes)
+            .map_err(|e| Error::Storage(format!("Failed to write spatial index definitions: {}", e)))?;
+        std::fs::rename(&temp_path, &path)
+            .map_err(|e| Error::Storage(format!("Failed to write spatial index definitions: {}", e)))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(lon: f64, lat: f64) -> Geometry {
+        Geometry::Point(Point::new(lon, lat).unwrap())
+    }
+
+    #[test]
+    fn test_encode_decode_roundtrip() {
+        let polygon = Geometry::from_wkt("POLYGON((0 0, 10 0, 10 10, 0 10))").unwrap();
+        assert_eq!(Geometry::decode(&polygon.encode()).unwrap(), polygon);
+        let p = point(-46.63, -23.55);
+        assert_eq!(Geometry::decode(&p.encode()).unwrap(), p);
+        assert!(Geometry::decode(&[TAG_POINT, 1, 2]).is_err());
+        assert!(polygon.contains_point(&Point::new(5.0, 5.0).unwrap()));
+        assert!(!polygon.contains_point(&Point::new(15.0, 5.0).unwrap()));
+    }
+
+    #[test]
+    fn test_radius_box_and_nearest() {
+        let mut index = SpatialIndex::new();
+        // A grid large enough to force several node splits
+        for i in 0..40 {
+            for j in 0..40 {
+                index.insert(i * 40 + j, point(i as f64 * 0.01, j as f64 * 0.01));
+            }
+        }
+        assert_eq!(index.len(), 1600);
+
+        let origin = Point::new(0.0, 0.0).unwrap();
+        // 0.01 degrees is ~1.1 km at the equator
+        let hits = index.within_radius(&origin, 1200.0, 100);
+        let ids: Vec<u64> = hits.iter().map(|h| h.doc_id).collect();
+        assert_eq!(hits[0].doc_id, 0);
+        assert!(ids.contains(&1) && ids.contains(&40));
+        assert!(!ids.contains(&41));
+
+        let bbox = BoundingBox::new(0.095, 0.095, 0.115, 0.115).unwrap();
+        assert_eq!(index.within_box(&bbox, 100).len(), 4);
+
+        let nearest = index.nearest(&Point::new(0.2, 0.2).unwrap(), 5);
+        assert_eq!(nearest.len(), 5);
+        assert_eq!(nearest[0].doc_id, 20 * 40 + 20);
+        assert!(nearest.windows(2).all(|w| w[0].distance_meters <= w[1].distance_meters));
+    }
+
+    #[test]
+    fn test_replace_and_remove() {
+        let mut index = SpatialIndex::new();
+        index.insert(1, point(10.0, 10.0));
+        index.insert(1, point(20.0, 20.0));
+        let bbox = BoundingBox::new(9.0, 9.0, 11.0, 11.0).unwrap();
+        assert!(index.within_box(&bbox, 10).is_empty());
+        assert!(index.remove(1));
+        assert!(index.is_empty());
+        assert!(index.nearest(&Point::new(0.0, 0.0).unwrap(), 3).is_empty());
+    }
+
+    #[test]
+    fn test_retain_renumbers_rows() {
+        let schema = Schema::new(vec![narayana_core::schema::Field {
+            name: "location".to_string(),
+            data_type: DataType::Point,
+            nullable: false,
+            default_value: None,
+        }]);
+        let manager = SpatialIndexManager::new();
+        let existing: Vec<Vec<u8>> = (0..4).map(|i| point(i as f64, 0.0).encode()).collect();
+        manager.create_index(TableId(1), &schema, "location", &existing).unwrap();
+
+        // Row 1 is removed and the rows behind it move up
+        manager.retain(TableId(1), 3, |row| match row {
+            1 => None,
+            row => Some(row - (row > 1) as u64),
+        });
+        let near = SpatialPredicate::Nearest { lon: 3.1, lat: 0.0, k: 10 };
+        let ids = |manager: &SpatialIndexManager| -> Vec<u64> {
+            manager.query(TableId(1), "location", &near, 10).unwrap().iter().map(|h| h.doc_id).collect()
+        };
+        assert_eq!(ids(&manager), vec![2, 1, 0]);
+
+        manager.index_insert(TableId(1), &schema, &[Column::Binary(vec![point(4.0, 0.0).encode()])]).unwrap();
+        assert_eq!(ids(&manager), vec![2, 3, 1, 0]);
+    }
+
+    #[tokio::test]
+    async fn test_restore_rebuilds_index_and_row_ids() {
+        use crate::column_store::InMemoryColumnStore;
+        use narayana_core::schema::Field;
+
+        let schema = Schema::new(vec![
+            Field { name: "id".to_string(), data_type: DataType::Int64, nullable: false, default_value: None },
+            Field { name: "location".to_string(), data_type: DataType::Point, nullable: true, default_value: None },
+        ]);
+        let store = InMemoryColumnStore::new();
+        store.create_table(TableId(1), schema.clone()).await.unwrap();
+        let rows = vec![Column::Int64(vec![1, 2]), Column::Binary(vec![point(10.0, 10.0).encode(), Vec::new()])];
+        store.write_columns(TableId(1), rows.clone()).await.unwrap();
+
+        let dir = std::env::temp_dir().join(format!("narayana_spatial_{}", uuid::Uuid::new_v4()));
+        let manager = SpatialIndexManager::with_state_dir(dir.clone()).unwrap();
+        manager.create_index(TableId(1), &schema, "location", &[point(10.0, 10.0).encode(), Vec::new()]).unwrap();
+        drop(manager);
+
+        // A restarted manager knows nothing until it is restored
+        let manager = SpatialIndexManager::with_state_dir(dir.clone()).unwrap();
+        assert!(manager.get_index(TableId(1), "location").is_none());
+        assert_eq!(manager.restore(&store).await.unwrap(), 1);
+        let near = SpatialPredicate::Nearest { lon: 10.0, lat: 10.0, k: 5 };
+        let hits = manager.query(TableId(1), "location", &near, 10).unwrap();
+        assert_eq!(hits.iter().map(|h| h.doc_id).collect::<Vec<_>>(), vec![0]);
+
+        // New rows continue after the stored ones
+        let more = vec![Column::Int64(vec![3]), Column::Binary(vec![point(10.1, 10.1).encode()])];
+        manager.index_insert(TableId(1), &schema, &more).unwrap();
+        let hits = manager.query(TableId(1), "location", &near, 10).unwrap();
+        assert_eq!(hits.iter().map(|h| h.doc_id).collect::<Vec<_>>(), vec![0, 2]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,953 @@
+// Multi-GPU execution for Narayana
+// Enumerates the devices of every compiled-in backend, places tasks on them by
+// policy, keeps a memory pool per device and tracks device health and utilization
+
+use crate::gpu_execution::{Backend, CpuBackend, CudaBackend, GpuBackend, GpuTensor, MetalBackend, VulkanBackend};
+use narayana_core::{Error, Result};
+use parking_lot::Mutex;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Utilization is reported over windows of this length
+const UTILIZATION_WINDOW: Duration = Duration::from_secs(10);
+
+/// Pools account in f32 elements, the unit of every GPU buffer
+const ELEMENT_BYTES: usize = std::mem::size_of::<f32>();
+
+/// A device of one backend
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GpuDeviceInfo {
+    /// Position in the engine's device list
+    pub id: usize,
+    pub backend: Backend,
+    /// Index among the backend's own devices
+    pub ordinal: usize,
+    pub name: String,
+    /// Device memory, when the backend reports it
+    pub memory_bytes: Option<u64>,
+}
+
+/// Every device of the compiled-in backends, GPUs first and the CPU backend last
+///
+/// A Vulkan adapter named like an already listed device is the same GPU seen
+/// through another API and is left out.
+pub fn enumerate_devices() -> Vec<GpuDeviceInfo> {
+    let mut devices: Vec<GpuDeviceInfo> = Vec::new();
+    let backends = [
+        (Backend::Metal, MetalBackend::devices()),
+        (Backend::CUDA, CudaBackend::devices()),
+        (Backend::Vulkan, VulkanBackend::devices()),
+    ];
+    for (backend, found) in backends {
+        for (ordinal, (name, memory_bytes)) in found.into_iter().enumerate() {
+            if backend == Backend::Vulkan && devices.iter().any(|device| device.name == name) {
+                continue;
+            }
+            devices.push(GpuDeviceInfo { id: devices.len(), backend, ordinal, name, memory_bytes });
+        }
+    }
+    devices.push(GpuDeviceInfo {
+        id: devices.len(),
+        backend: Backend::CPU,
+        ordinal: 0,
+        name: "cpu".to_string(),
+        memory_bytes: None,
+    });
+    devices
+}
+
+/// Start the backend of an enumerated device
+pub fn open_device(device: &GpuDeviceInfo) -> Result<Box<dyn GpuBackend>> {
+    let mut backend: Box<dyn GpuBackend> = match device.backend {
+        Backend::CPU => Box::new(CpuBackend::new()),
+        Backend::Metal => Box::new(MetalBackend::on_device(device.ordinal)?),
+        Backend::CUDA => Box::new(CudaBackend::on_device(device.ordinal)?),
+        Backend::Vulkan => Box::new(VulkanBackend::on_device(device.ordinal)?),
+    };
+    backend.initialize()?;
+    Ok(backend)
+}
+
+/// Which device a task runs on
+///
+/// Every policy but `Pinned` chooses among healthy GPUs, falls back to the CPU
+/// backend when there are none, and skips devices whose pool can't hold the
+/// task's memory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlacementPolicy {
+    /// Devices in turn
+    RoundRobin,
+    /// Device running the fewest tasks
+    LeastLoaded,
+    /// Device with the most free pool memory
+    MostFreeMemory,
+    /// Same key, same device while it stays healthy, so data placed for a key
+    /// is found again
+    Affinity(u64),
+    /// One device; fails while it's unhealthy
+    Pinned(usize),
+}
+
+impl FromStr for PlacementPolicy {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value {
+            "round_robin" => Ok(Self::RoundRobin),
+            "least_loaded" => Ok(Self::LeastLoaded),
+            "most_free_memory" => Ok(Self::MostFreeMemory),
+            _ => value
+                .strip_prefix("pinned:")
+                .and_then(|device| device.parse().ok())
+                .map(Self::Pinned)
+                .ok_or_else(|| Error::Storage(format!("Unknown GPU placement policy: {}", value))),
+        }
+    }
+}
+
+/// Multi-GPU engine settings
+#[derive(Debug, Clone)]
+pub struct MultiGpuConfig {
+    /// Placement of tasks that don't bring their own
+    pub placement: PlacementPolicy,
+    /// Share of a device's memory its pool may hold
+    pub pool_memory_fraction: f64,
+    /// Pool size for devices that don't report their memory
+    pub pool_bytes: u64,
+    /// Consecutive failed tasks before a device takes no tasks
+    pub unhealthy_after_failures: u32,
+    /// How long an unhealthy device is left alone before a task probes it
+    pub health_cooldown: Duration,
+}
+
+impl Default for MultiGpuConfig {
+    fn default() -> Self {
+        Self {
+            placement: PlacementPolicy::LeastLoaded,
+            pool_memory_fraction: 0.5,
+            pool_bytes: 256 * 1024 * 1024,
+            unhealthy_after_failures: 3,
+            health_cooldown: Duration::from_secs(30),
+        }
+    }
+}
+
+impl MultiGpuConfig {
+    pub fn from_config(config: &narayana_core::config::GpuConfig) -> Result<Self> {
+        Ok(Self {
+            placement: config.placement.parse()?,
+            pool_memory_fraction: config.pool_memory_fraction,
+            pool_bytes: config.pool_size,
+            unhealthy_after_failures: config.unhealthy_after_failures.max(1),
+            health_cooldown: config.health_cooldown,
+        })
+    }
+
+    fn pool_bytes_for(&self, device: &GpuDeviceInfo) -> u64 {
+        match device.memory_bytes {
+            Some(memory) if device.backend != Backend::CPU => (memory as f64 * self.pool_memory_fraction) as u64,
+            _ => self.pool_bytes,
+        }
+    }
+}
+
+/// Memory pool of one device: a single arena carved into buffers
+///
+/// The arena grows to the high-water mark of what was allocated and is never
+/// given back, so released space is reused instead of allocated again. When a
+/// request fits in the free space but not in any one gap, live buffers are
+/// moved together first.
+pub struct DeviceMemoryPool {
+    device: usize,
+    /// Capacity in elements
+    capacity: usize,
+    state: Mutex<PoolState>,
+}
+
+#[derive(Default)]
+struct PoolState {
+    arena: Vec<f32>,
+    /// Blocks by offset, covering the whole capacity
+    blocks: BTreeMap<usize, Block>,
+    /// Offset of every live buffer
+    live: HashMap<u64, usize>,
+    next_id: u64,
+    allocations: u64,
+    reused: u64,
+    releases: u64,
+    defragmentations: u64,
+    failures: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Block {
+    len: usize,
+    owner: Option<u64>,
+}
+
+/// Occupancy and activity of a device memory pool
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PoolStats {
+    pub capacity_bytes: u64,
+    pub used_bytes: u64,
+    pub free_bytes: u64,
+    /// Largest request that fits without moving buffers
+    pub largest_free_bytes: u64,
+    /// Arena actually held in memory
+    pub resident_bytes: u64,
+    pub live_buffers: usize,
+    pub allocations: u64,
+    /// Allocations served from space earlier buffers released
+    pub reused: u64,
+    pub releases: u64,
+    pub defragmentations: u64,
+    /// Requests the pool couldn't hold
+    pub failures: u64,
+}
+
+impl PoolStats {
+    /// Share of the free space outside the largest gap
+    pub fn fragmentation(&self) -> f64 {
+        if self.free_bytes == 0 {
+            0.0
+        } else {
+            1.0 - self.largest_free_bytes as f64 / self.free_bytes as f64
+        }
+    }
+}
+
+impl DeviceMemoryPool {
+    pub fn new(device: usize, capacity_bytes: u64) -> Self {
+        let capacity = capacity_bytes as usize / ELEMENT_BYTES;
+        let mut state = PoolState::default();
+        if capacity > 0 {
+            state.blocks.insert(0, Block { len: capacity, owner: None });
+        }
+        Self { device, capacity, state: Mutex::new(state) }
+    }
+
+    pub fn device(&self) -> usize {
+        self.device
+    }
+
+    /// A zeroed buffer of `len` elements, released when dropped
+    pub fn allocate(self: &Arc<Self>, len: usize) -> Result<PoolBuffer> {
+        if len == 0 {
+            return Err(Error::Storage("Cannot allocate an empty GPU buffer".to_string()));
+        }
+        let mut state = self.state.lock();
+        let offset = match state.first_fit(len) {
+            Some(offset) => offset,
+            None if state.free() >= len => {
+                state.defragment(self.capacity);
+                state.first_fit(len).ok_or_else(|| Error::Storage("GPU memory pool is corrupted".to_string()))?
+            }
+            None => {
+                state.failures += 1;
+                return Err(Error::Storage(format!(
+                    "GPU device {} memory pool exhausted: {} bytes requested, {} free",
+                    self.device,
+                    len * ELEMENT_BYTES,
+                    state.free() * ELEMENT_BYTES
+                )));
+            }
+        };
+        let id = state.next_id;
+        state.next_id += 1;
+        state.carve(offset, len, id);
+        Ok(PoolBuffer { pool: self.clone(), id, len })
+    }
+
+    /// Move live buffers to the front of the arena, leaving one free gap;
+    /// returns how many buffers moved
+    pub fn defragment(&self) -> usize {
+        self.state.lock().defragment(self.capacity)
+    }
+
+    pub fn stats(&self) -> PoolStats {
+        let state = self.state.lock();
+        let free = state.free();
+        let largest = state.blocks.values().filter(|b| b.owner.is_none()).map(|b| b.len).max().unwrap_or(0);
+        let bytes = |elements: usize| (elements * ELEMENT_BYTES) as u64;
+        PoolStats {
+            capacity_bytes: bytes(self.capacity),
+            used_bytes: bytes(self.capacity - free),
+            free_bytes: bytes(free),
+            largest_free_bytes: bytes(largest),
+            resident_bytes: bytes(state.arena.len()),
+            live_buffers: state.live.len(),
+            allocations: state.allocations,
+            reused: state.reused,
+            releases: state.releases,
+            defragmentations: state.defragmentations,
+            failures: state.failures,
+        }
+    }
+
+    fn with_slice<R>(&self, id: u64, len: usize, f: impl FnOnce(&mut [f32]) -> R) -> R {
+        let mut state = self.state.lock();
+        let offset = state.live[&id];
+        f(&mut state.arena[offset..offset + len])
+    }
+
+    fn release(&self, id: u64) {
+        self.state.lock().release(id);
+    }
+}
+
+impl PoolState {
+    fn free(&self) -> usize {
+        self.blocks.values().filter(|b| b.owner.is_none()).map(|b| b.len).sum()
+    }
+
+    fn first_fit(&self, len: usize) -> Option<usize> {
+        self.blocks
+            .iter()
+            .find(|(_, block)| block.owner.is_none() && block.len >= len)
+            .map(|(offset, _)| *offset)
+    }
+
+    fn carve(&mut self, offset: usize, len: usize, id: u64) {
+        let gap = self.blocks[&offset].len;
+        self.blocks.insert(offset, Block { len, owner: Some(id) });
+        if gap > len {
+            self.blocks.insert(offset + len, Block { len: gap - len, owner: None });
+        }
+        self.live.insert(id, offset);
+        self.allocations += 1;
+        let end = offset + len;
+        if end > self.arena.len() {
+            self.arena.resize(end, 0.0);
+        } else {
+            self.reused += 1;
+        }
+        self.arena[offset..end].fill(0.0);
+    }
+
+    fn release(&mut self, id: u64) {
+        let Some(mut offset) = self.live.remove(&id) else {
+            return;
+        };
+        self.releases += 1;
+        let mut len = self.blocks[&offset].len;
+        // Merge with the free neighbours
+        if let Some(next) = self.blocks.get(&(offset + len)).copied() {
+            if next.owner.is_none() {
+                self.blocks.remove(&(offset + len));
+                len += next.len;
+            }
+        }
+        if let Some((prev_offset, prev)) = self.blocks.range(..offset).next_back().map(|(o, b)| (*o, *b)) {
+            if prev.owner.is_none() {
+                self.blocks.remove(&offset);
+                len += prev.len;
+                offset = prev_offset;
+            }
+        }
+        self.blocks.insert(offset, Block { len, owner: None });
+    }
+
+    fn defragment(&mut self, capacity: usize) -> usize {
+        let mut compacted = BTreeMap::new();
+        let mut cursor = 0;
+        let mut moved = 0;
+        for (offset, block) in std::mem::take(&mut self.blocks) {
+            let Some(id) = block.owner else {
+                continue;
+            };
+            if offset != cursor {
+                self.arena.copy_within(offset..offset + block.len, cursor);
+                self.live.insert(id, cursor);
+                moved += 1;
+            }
+            compacted.insert(cursor, block);
+            cursor += block.len;
+        }
+        if cursor < capacity {
+            compacted.insert(cursor, Block { len: capacity - cursor, owner: None });
+        }
+        self.blocks = compacted;
+        self.defragmentations += 1;
+        moved
+    }
+}
+
+/// A buffer in a device memory pool
+pub struct PoolBuffer {
+    pool: Arc<DeviceMemoryPool>,
+    id: u64,
+    len: usize,
+}
+
+impl PoolBuffer {
+    pub fn device(&self) -> usize {
+        self.pool.device
+    }
+
+    /// Length in elements
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Copy `data` to the start of the buffer
+    pub fn write(&self, data: &[f32]) -> Result<()> {
+        if data.len() > self.len {
+            return Err(Error::Storage(format!(
+                "{} elements don't fit a GPU buffer of {}",
+                data.len(),
+                self.len
+            )));
+        }
+        self.pool.with_slice(self.id, self.len, |slice| slice[..data.len()].copy_from_slice(data));
+        Ok(())
+    }
+
+    pub fn read(&self) -> Vec<f32> {
+        self.pool.with_slice(self.id, self.len, |slice| slice.to_vec())
+    }
+
+    pub fn to_tensor(&self, shape: Vec<usize>) -> GpuTensor {
+        GpuTensor::new(self.read(), shape)
+    }
+}
+
+impl Drop for PoolBuffer {
+    fn drop(&mut self) {
+        self.pool.release(self.id);
+    }
+}
+
+/// Health and utilization of one device
+#[derive(Debug, Clone)]
+pub struct DeviceMetrics {
+    pub device: GpuDeviceInfo,
+    pub healthy: bool,
+    pub active_tasks: usize,
+    pub tasks: u64,
+    pub failures: u64,
+    pub consecutive_failures: u32,
+    /// Time spent running tasks; overlapping tasks each count
+    pub busy_us: u64,
+    /// Share of the last utilization window spent running tasks, at most 1
+    pub utilization: f64,
+    pub last_error: Option<String>,
+    pub memory: PoolStats,
+}
+
+/// Metrics of every device
+#[derive(Debug, Clone, Default)]
+pub struct GpuMetrics {
+    pub devices: Vec<DeviceMetrics>,
+}
+
+impl GpuMetrics {
+    /// Prometheus text exposition, one series per device
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+        let device_metrics: [Metric; 14] = [
+            ("device_healthy", "gauge", "Whether a device takes tasks", |d| if d.healthy { 1.0 } else { 0.0 }),
+            ("device_active_tasks", "gauge", "Tasks running on a device", |d| d.active_tasks as f64),
+            ("device_tasks_total", "counter", "Tasks run on a device", |d| d.tasks as f64),
+            ("device_failures_total", "counter", "Tasks failed on a device", |d| d.failures as f64),
+            ("device_busy_seconds_total", "counter", "Time a device spent running tasks", |d| d.busy_us as f64 / 1e6),
+            ("device_utilization", "gauge", "Share of recent time a device spent running tasks", |d| d.utilization),
+            ("pool_capacity_bytes", "gauge", "Memory pool capacity of a device", |d| d.memory.capacity_bytes as f64),
+            ("pool_used_bytes", "gauge", "Memory held by live buffers", |d| d.memory.used_bytes as f64),
+            ("pool_resident_bytes", "gauge", "Memory pool arena held in memory", |d| d.memory.resident_bytes as f64),
+            ("pool_fragmentation_ratio", "gauge", "Share of free pool memory outside the largest gap", |d| d.memory.fragmentation()),
+            ("pool_allocations_total", "counter", "Buffers allocated from a pool", |d| d.memory.allocations as f64),
+            ("pool_reused_total", "counter", "Allocations served from released pool memory", |d| d.memory.reused as f64),
+            ("pool_defragmentations_total", "counter", "Pool defragmentations", |d| d.memory.defragmentations as f64),
+            ("pool_failures_total", "counter", "Allocations a pool couldn't hold", |d| d.memory.failures as f64),
+        ];
+        for (name, kind, help, value) in device_metrics {
+            let _ = writeln!(out, "# HELP narayana_gpu_{} {}", name, help);
+            let _ = writeln!(out, "# TYPE narayana_gpu_{} {}", name, kind);
+            for device in &self.devices {
+                let _ = writeln!(
+                    out,
+                    "narayana_gpu_{}{{device=\"{}\",backend=\"{}\",name=\"{}\"}} {}",
+                    name,
+                    device.device.id,
+                    format!("{:?}", device.device.backend).to_lowercase(),
+                    escape_label(&device.device.name),
+                    value(device)
+                );
+            }
+        }
+        out
+    }
+}
+
+/// Name, type, help and value of a per-device metric
+type Metric = (&'static str, &'static str, &'static str, fn(&DeviceMetrics) -> f64);
+
+struct DeviceSlot {
+    info: GpuDeviceInfo,
+    backend: Box<dyn GpuBackend>,
+    pool: Arc<DeviceMemoryPool>,
+    active: AtomicUsize,
+    tasks: AtomicU64,
+    failures: AtomicU64,
+    consecutive_failures: AtomicU32,
+    busy_us: AtomicU64,
+    health: Mutex<DeviceHealth>,
+}
+
+struct DeviceHealth {
+    unhealthy_since: Option<Instant>,
+    last_error: Option<String>,
+    window_start: Instant,
+    window_busy_us: u64,
+    /// Utilization of the last complete window
+    utilization: Option<f64>,
+}
+
+impl DeviceHealth {
+    fn roll_window(&mut self, now: Instant) {
+        let elapsed = now.duration_since(self.window_start);
+        if elapsed >= UTILIZATION_WINDOW {
+            self.utilization = Some((self.window_busy_us as f64 / elapsed.as_micros() as f64).min(1.0));
+            self.window_start = now;
+            self.window_busy_us = 0;
+        }
+    }
+
+    fn utilization(&self, now: Instant) -> f64 {
+        self.utilization.unwrap_or_else(|| {
+            let elapsed = now.duration_since(self.window_start).as_micros().max(1) as f64;
+            (self.window_busy_us as f64 / elapsed).min(1.0)
+        })
+    }
+}
+
+impl DeviceSlot {
+    fn available(&self, now: Instant, cooldown: Duration) -> bool {
+        self.health.lock().unhealthy_since.is_none_or(|since| now.duration_since(since) >= cooldown)
+    }
+
+    fn record(&self, elapsed: Duration, error: Option<&Error>, config: &MultiGpuConfig) {
+        let now = Instant::now();
+        let busy = elapsed.as_micros() as u64;
+        self.tasks.fetch_add(1, Ordering::Relaxed);
+        self.busy_us.fetch_add(busy, Ordering::Relaxed);
+        let mut health = self.health.lock();
+        health.window_busy_us += busy;
+        health.roll_window(now);
+        match error {
+            None => {
+                self.consecutive_failures.store(0, Ordering::Relaxed);
+                if health.unhealthy_since.take().is_some() {
+                    info!("GPU device {} ({}) is healthy again", self.info.id, self.info.name);
+                }
+            }
+            Some(error) => {
+                self.failures.fetch_add(1, Ordering::Relaxed);
+                health.last_error = Some(error.to_string());
+                let failed = self.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
+                if failed >= config.unhealthy_after_failures {
+                    // A failed probe after the cooldown starts another one
+                    if health.unhealthy_since.replace(now).is_none() {
+                        warn!(
+                            "GPU device {} ({}) marked unhealthy after {} failed tasks: {}",
+                            self.info.id, self.info.name, failed, error
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn metrics(&self, now: Instant) -> DeviceMetrics {
+        let mut health = self.health.lock();
+        health.roll_window(now);
+        DeviceMetrics {
+            device: self.info.clone(),
+            healthy: health.unhealthy_since.is_none(),
+            active_tasks: self.active.load(Ordering::Relaxed),
+            tasks: self.tasks.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+            consecutive_failures: self.consecutive_failures.load(Ordering::Relaxed),
+            busy_us: self.busy_us.load(Ordering::Relaxed),
+            utilization: health.utilization(now),
+            last_error: health.last_error.clone(),
+            memory: self.pool.stats(),
+        }
+    }
+}
+
+/// Counts a task as running on a device until dropped
+struct ActiveTask<'a>(&'a AtomicUsize);
+
+impl Drop for ActiveTask<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// GPU execution over every device, each with its own backend and memory pool
+pub struct MultiGpuEngine {
+    devices: Vec<DeviceSlot>,
+    config: MultiGpuConfig,
+    next: AtomicUsize,
+}
+
+impl MultiGpuEngine {
+    /// Engine over every device found; devices whose backend fails to start
+    /// are left out
+    pub fn new(config: MultiGpuConfig) -> Result<Self> {
+        let mut backends = Vec::new();
+        for device in enumerate_devices() {
+            match open_device(&device) {
+                Ok(backend) => backends.push((device, backend)),
+                Err(e) => warn!("GPU device {} ({:?} {}) unavailable: {}", device.id, device.backend, device.name, e),
+            }
+        }
+        Self::with_backends(backends, config)
+    }
+
+    /// Engine over the CPU backend alone
+    pub fn cpu_only(config: MultiGpuConfig) -> Self {
+        let device = GpuDeviceInfo {
+            id: 0,
+            backend: Backend::CPU,
+            ordinal: 0,
+            name: "cpu".to_string(),
+            memory_bytes: None,
+        };
+        Self::build(vec![(device, Box::new(CpuBackend::new()))], config)
+    }
+
+    /// Engine over started backends; devices are numbered in the given order
+    pub fn with_backends(backends: Vec<(GpuDeviceInfo, Box<dyn GpuBackend>)>, config: MultiGpuConfig) -> Result<Self> {
+        if backends.is_empty() {
+            return Err(Error::Storage("No GPU devices to run on".to_string()));
+        }
+        let engine = Self::build(backends, config);
+        for device in engine.devices() {
+            info!("GPU device {}: {:?} {} (pool {} bytes)", device.id, device.backend, device.name, engine.config.pool_bytes_for(&device));
+        }
+        Ok(engine)
+    }
+
+    fn build(backends: Vec<(GpuDeviceInfo, Box<dyn GpuBackend>)>, config: MultiGpuConfig) -> Self {
+        let now = Instant::now();
+        let devices = backends
+            .into_iter()
+            .enumerate()
+            .map(|(id, (mut info, backend))| {
+                info.id = id;
+                DeviceSlot {
+                    pool: Arc::new(DeviceMemoryPool::new(id, config.pool_bytes_for(&info))),
+                    info,
+                    backend,
+                    active: AtomicUsize::new(0),
+                    tasks: AtomicU64::new(0),
+                    failures: AtomicU64::new(0),
+                    consecutive_failures: AtomicU32::new(0),
+                    busy_us: AtomicU64::new(0),
+                    health: Mutex::new(DeviceHealth {
+                        unhealthy_since: None,
+                        last_error: None,
+                        window_start: now,
+                        window_busy_us: 0,
+                        utilization: None,
+                    }),
+                }
+            })
+            .collect();
+        Self { devices, config, next: AtomicUsize::new(0) }
+    }
+
+    pub fn config(&self) -> &MultiGpuConfig {
+        &self.config
+    }
+
+    pub fn devices(&self) -> Vec<GpuDeviceInfo> {
+        self.devices.iter().map(|slot| slot.info.clone()).collect()
+    }
+
+    /// Device for a task needing `memory_bytes` of pool memory
+    pub fn place(&self, policy: &PlacementPolicy, memory_bytes: u64) -> Result<usize> {
+        let now = Instant::now();
+        let cooldown = self.config.health_cooldown;
+        if let PlacementPolicy::Pinned(id) = policy {
+            let slot = self.slot(*id)?;
+            if !slot.available(now, cooldown) {
+                return Err(Error::Storage(format!("GPU device {} is unhealthy", id)));
+            }
+            return Ok(*id);
+        }
+
+        let available: Vec<&DeviceSlot> = self.devices.iter().filter(|slot| slot.available(now, cooldown)).collect();
+        let gpus: Vec<&DeviceSlot> = available.iter().copied().filter(|slot| slot.info.backend != Backend::CPU).collect();
+        let candidates = match (gpus.is_empty(), available.is_empty()) {
+            (false, _) => gpus,
+            (true, false) => available,
+            // Everything is failing: keep trying rather than refuse work
+            (true, true) => self.devices.iter().collect(),
+        };
+        let stats: Vec<(&DeviceSlot, PoolStats)> = candidates
+            .into_iter()
+            .map(|slot| (slot, slot.pool.stats()))
+            .filter(|(_, stats)| stats.free_bytes >= memory_bytes)
+            .collect();
+        if stats.is_empty() {
+            return Err(Error::Storage(format!("No GPU device has {} bytes of pool memory free", memory_bytes)));
+        }
+
+        let chosen = match policy {
+            PlacementPolicy::RoundRobin => stats[self.next.fetch_add(1, Ordering::Relaxed) % stats.len()].0,
+            PlacementPolicy::LeastLoaded => stats
+                .iter()
+                .min_by_key(|(slot, _)| (slot.active.load(Ordering::Relaxed), slot.busy_us.load(Ordering::Relaxed)))
+                .map(|(slot, _)| *slot)
+                .unwrap_or(stats[0].0),
+            PlacementPolicy::MostFreeMemory => stats
+                .iter()
+                .max_by_key(|(_, stats)| stats.free_bytes)
+                .map(|(slot, _)| *slot)
+                .unwrap_or(stats[0].0),
+            // Rendezvous hashing: a key only moves when its own device drops out
+            PlacementPolicy::Affinity(key) => stats
+                .iter()
+                .max_by_key(|(slot, _)| {
+                    let mut hasher = DefaultHasher::new();
+                    (key, slot.info.id).hash(&mut hasher);
+                    hasher.finish()
+                })
+                .map(|(slot, _)| *slot)
+                .unwrap_or(stats[0].0),
+            PlacementPolicy::Pinned(_) => unreachable!("pinned placement is resolved above"),
+        };
+        Ok(chosen.info.id)
+    }
+
+    /// Run `f` on a device chosen by the configured placement
+    pub fn execute<R>(&self, f: impl FnOnce(&dyn GpuBackend) -> Result<R>) -> Result<R> {
+        self.execute_placed(&self.config.placement, f)
+    }
+
+    /// Run `f` on a device chosen by `policy`
+    pub fn execute_placed<R>(&self, policy: &PlacementPolicy, f: impl FnOnce(&dyn GpuBackend) -> Result<R>) -> Result<R> {
+        let device = self.place(policy, 0)?;
+        self.execute_on(device, f)
+    }
+
+    /// Run `f` on one device, counting its outcome toward the device's health
+    pub fn execute_on<R>(&self, device: usize, f: impl FnOnce(&dyn GpuBackend) -> Result<R>) -> Result<R> {
+        let slot = self.slot(device)?;
+        slot.active.fetch_add(1, Ordering::Relaxed);
+        let _active = ActiveTask(&slot.active);
+        let started = Instant::now();
+        let result = f(slot.backend.as_ref());
+        slot.record(started.elapsed(), result.as_ref().err(), &self.config);
+        result
+    }
+
+    /// A buffer of `len` elements in a device's pool
+    pub fn allocate(&self, device: usize, len: usize) -> Result<PoolBuffer> {
+        self.slot(device)?.pool.allocate(len)
+    }
+
+    /// A buffer of `len` elements on a device chosen by `policy`
+    pub fn allocate_placed(&self, policy: &PlacementPolicy, len: usize) -> Result<PoolBuffer> {
+        let device = self.place(policy, (len * ELEMENT_BYTES) as u64)?;
+        self.allocate(device, len)
+    }
+
+    pub fn pool(&self, device: usize) -> Result<Arc<DeviceMemoryPool>> {
+        Ok(self.slot(device)?.pool.clone())
+    }
+
+    /// Defragment every pool; returns how many buffers moved
+    pub fn defragment(&self) -> usize {
+        self.devices.iter().map(|slot| slot.pool.defragment()).sum()
+    }
+
+    pub fn metrics(&self) -> GpuMetrics {
+        let now = Instant::now();
+        GpuMetrics { devices: self.devices.iter().map(|slot| slot.metrics(now)).collect() }
+    }
+
+    fn slot(&self, device: usize) -> Result<&DeviceSlot> {
+        self.devices
+            .get(device)
+            .ok_or_else(|| Error::Storage(format!("Unknown GPU device: {}", device)))
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device(backend: Backend, name: &str) -> (GpuDeviceInfo, Box<dyn GpuBackend>) {
+        let info = GpuDeviceInfo { id: 0, backend, ordinal: 0, name: name.to_string(), memory_bytes: Some(1024) };
+        (info, Box::new(CpuBackend::new()))
+    }
+
+    /// Two "GPUs" (CPU backends under GPU labels) and the CPU fallback
+    fn engine(config: MultiGpuConfig) -> MultiGpuEngine {
+        MultiGpuEngine::with_backends(
+            vec![device(Backend::CUDA, "gpu0"), device(Backend::CUDA, "gpu1"), device(Backend::CPU, "cpu")],
+            config,
+        )
+        .unwrap()
+    }
+
+    fn fail(_: &dyn GpuBackend) -> Result<()> {
+        Err(Error::Storage("device lost".to_string()))
+    }
+
+    #[test]
+    fn test_enumeration_ends_with_cpu() {
+        let devices = enumerate_devices();
+        let last = devices.last().unwrap();
+        assert_eq!(last.backend, Backend::CPU);
+        assert!(devices.iter().enumerate().all(|(i, d)| d.id == i));
+    }
+
+    #[test]
+    fn test_placement_policies() {
+        let engine = engine(MultiGpuConfig::default());
+        assert_eq!(engine.devices().len(), 3);
+
+        // Round robin spreads over the GPUs only
+        let placed: Vec<usize> = (0..4).map(|_| engine.place(&PlacementPolicy::RoundRobin, 0).unwrap()).collect();
+        assert_eq!(placed, vec![0, 1, 0, 1]);
+
+        // The same key keeps landing on the same device
+        let first = engine.place(&PlacementPolicy::Affinity(42), 0).unwrap();
+        assert!((0..5).all(|_| engine.place(&PlacementPolicy::Affinity(42), 0).unwrap() == first));
+
+        assert_eq!(engine.place(&PlacementPolicy::Pinned(2), 0).unwrap(), 2);
+        assert!(engine.place(&PlacementPolicy::Pinned(7), 0).is_err());
+
+        // GPU 0 holds a buffer, so GPU 1 has the most free memory
+        let _buffer = engine.allocate(0, 16).unwrap();
+        assert_eq!(engine.place(&PlacementPolicy::MostFreeMemory, 0).unwrap(), 1);
+        assert!(engine.place(&PlacementPolicy::LeastLoaded, 1 << 20).is_err());
+
+        assert_eq!("pinned:1".parse::<PlacementPolicy>().unwrap(), PlacementPolicy::Pinned(1));
+        assert!("fastest".parse::<PlacementPolicy>().is_err());
+    }
+
+    #[test]
+    fn test_execute_runs_on_device_backend() {
+        let engine = engine(MultiGpuConfig::default());
+        let a = GpuTensor::from_vec(vec![1.0, 2.0, 3.0]);
+        let b = GpuTensor::from_vec(vec![4.0, 5.0, 6.0]);
+        assert_eq!(engine.execute(|backend| backend.dot(&a, &b)).unwrap(), 32.0);
+        assert_eq!(engine.execute_on(1, |backend| backend.reduce_sum(&a)).unwrap(), 6.0);
+
+        let metrics = engine.metrics();
+        assert_eq!(metrics.devices.iter().map(|d| d.tasks).sum::<u64>(), 2);
+        assert!(metrics.devices.iter().all(|d| d.active_tasks == 0 && d.healthy));
+    }
+
+    #[test]
+    fn test_failing_device_is_taken_out_and_probed_after_cooldown() {
+        let engine = engine(MultiGpuConfig {
+            unhealthy_after_failures: 2,
+            health_cooldown: Duration::from_millis(50),
+            ..Default::default()
+        });
+        assert!(engine.execute_on(0, fail).is_err());
+        assert!(engine.metrics().devices[0].healthy);
+        assert!(engine.execute_on(0, fail).is_err());
+        let metrics = engine.metrics();
+        assert!(!metrics.devices[0].healthy);
+        assert_eq!(metrics.devices[0].failures, 2);
+        assert_eq!(metrics.devices[0].last_error.as_deref(), Some("Storage error: device lost"));
+
+        // Placement avoids it; pinning to it fails
+        assert!((0..4).all(|_| engine.place(&PlacementPolicy::RoundRobin, 0).unwrap() == 1));
+        assert!(engine.place(&PlacementPolicy::Pinned(0), 0).is_err());
+
+        // With both GPUs out, tasks fall back to the CPU backend
+        engine.execute_on(1, fail).unwrap_err();
+        engine.execute_on(1, fail).unwrap_err();
+        assert_eq!(engine.place(&PlacementPolicy::LeastLoaded, 0).unwrap(), 2);
+
+        // After the cooldown a successful probe brings the device back
+        std::thread::sleep(Duration::from_millis(60));
+        engine.execute_on(0, |_| Ok(())).unwrap();
+        assert!(engine.metrics().devices[0].healthy);
+    }
+
+    #[test]
+    fn test_pool_reuses_released_memory() {
+        let pool = Arc::new(DeviceMemoryPool::new(0, 64 * ELEMENT_BYTES as u64));
+        let buffer = pool.allocate(16).unwrap();
+        buffer.write(&[1.0, 2.0]).unwrap();
+        assert_eq!(&buffer.read()[..3], &[1.0, 2.0, 0.0]);
+        assert!(buffer.write(&[0.0; 17]).is_err());
+        drop(buffer);
+
+        // Released memory is handed out again, zeroed
+        let buffer = pool.allocate(8).unwrap();
+        assert_eq!(buffer.read(), vec![0.0; 8]);
+        let stats = pool.stats();
+        assert_eq!((stats.allocations, stats.reused, stats.releases), (2, 1, 1));
+        assert_eq!(stats.resident_bytes, 16 * ELEMENT_BYTES as u64);
+        assert_eq!(stats.used_bytes, 8 * ELEMENT_BYTES as u64);
+        assert!(pool.allocate(57).is_err());
+        assert_eq!(pool.stats().failures, 1);
+    }
+
+    #[test]
+    fn test_pool_defragments_when_fragmented() {
+        let pool = Arc::new(DeviceMemoryPool::new(0, 40 * ELEMENT_BYTES as u64));
+        let buffers: Vec<PoolBuffer> = (0..4).map(|_| pool.allocate(10).unwrap()).collect();
+        for (i, buffer) in buffers.iter().enumerate() {
+            buffer.write(&[i as f32; 10]).unwrap();
+        }
+        let mut buffers = buffers.into_iter();
+        let first = buffers.next().unwrap();
+        let second = buffers.next().unwrap();
+        let third = buffers.next().unwrap();
+        let fourth = buffers.next().unwrap();
+        drop(first);
+        drop(third);
+
+        // 20 elements are free, but in two gaps of 10
+        let stats = pool.stats();
+        assert_eq!(stats.free_bytes, 20 * ELEMENT_BYTES as u64);
+        assert!((stats.fragmentation() - 0.5).abs() < 1e-9);
+
+        let big = pool.allocate(20).unwrap();
+        assert_eq!(pool.stats().defragmentations, 1);
+        assert_eq!(pool.stats().fragmentation(), 0.0);
+        // Moved buffers keep their contents
+        assert_eq!(second.read(), vec![1.0; 10]);
+        assert_eq!(fourth.read(), vec![3.0; 10]);
+        assert_eq!(big.read(), vec![0.0; 20]);
+    }
+
+    #[test]
+    fn test_prometheus_output() {
+        let engine = engine(MultiGpuConfig::default());
+        engine.execute_on(0, |_| Ok(())).unwrap();
+        let _buffer = engine.allocate_placed(&PlacementPolicy::Pinned(1), 4).unwrap();
+        let text = engine.metrics().to_prometheus();
+        assert!(text.contains("# TYPE narayana_gpu_device_tasks_total counter"));
+        assert!(text.contains("narayana_gpu_device_tasks_total{device=\"0\",backend=\"cuda\",name=\"gpu0\"} 1"));
+        assert!(text.contains("narayana_gpu_pool_used_bytes{device=\"1\",backend=\"cuda\",name=\"gpu1\"} 16"));
+        assert!(text.contains("narayana_gpu_device_healthy{device=\"2\",backend=\"cpu\",name=\"cpu\"} 1"));
+    }
+}
@@ -417,18 +417,7 @@ impl MetalBackend {
         {
             let device = Device::system_default()
                 .ok_or_else(|| Error::Storage("No Metal device found".to_string()))?;
-            
-            let command_queue = device.new_command_queue();
-            let library = device.new_default_library()
-                .ok_or_else(|| Error::Storage("Failed to create Metal library".to_string()))?;
-
-            info!("Metal backend created with device: {:?}", device.name());
-            
-            Ok(Self {
-                device: Arc::new(device),
-                command_queue: Arc::new(command_queue),
-                library: Arc::new(library),
-            })
+            Self::from_device(device)
         }
         #[cfg(not(target_os = "macos"))]
         {
@@ -436,6 +425,53 @@ impl MetalBackend {
         }
     }
 
+    /// Backend on the `index`th Metal device
+    pub fn on_device(index: usize) -> Result<Self> {
+        #[cfg(target_os = "macos")]
+        {
+            let device = Device::all()
+                .into_iter()
+                .nth(index)
+                .ok_or_else(|| Error::Storage(format!("No Metal device {}", index)))?;
+            Self::from_device(device)
+        }
+        #[cfg(not(target_os = "macos"))]
+        {
+            let _ = index;
+            Err(Error::Storage("Metal backend only available on macOS".to_string()))
+        }
+    }
+
+    /// Name and recommended working set size of every Metal device
+    pub fn devices() -> Vec<(String, Option<u64>)> {
+        #[cfg(target_os = "macos")]
+        {
+            Device::all()
+                .iter()
+                .map(|device| (device.name().to_string(), Some(device.recommended_max_working_set_size())))
+                .collect()
+        }
+        #[cfg(not(target_os = "macos"))]
+        {
+            Vec::new()
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    fn from_device(device: Device) -> Result<Self> {
+        let command_queue = device.new_command_queue();
+        let library = device.new_default_library()
+            .ok_or_else(|| Error::Storage("Failed to create Metal library".to_string()))?;
+
+        info!("Metal backend created with device: {:?}", device.name());
+
+        Ok(Self {
+            device: Arc::new(device),
+            command_queue: Arc::new(command_queue),
+            library: Arc::new(library),
+        })
+    }
+
     pub fn is_available() -> bool {
         #[cfg(target_os = "macos")]
         {
@@ -703,6 +739,14 @@ impl MetalBackend {
         Err(Error::Storage("Metal feature not enabled".to_string()))
     }
 
+    pub fn on_device(_index: usize) -> Result<Self> {
+        Err(Error::Storage("Metal feature not enabled".to_string()))
+    }
+
+    pub fn devices() -> Vec<(String, Option<u64>)> {
+        Vec::new()
+    }
+
     pub fn is_available() -> bool {
         false
     }
@@ -767,8 +811,13 @@ pub struct CudaBackend {
 #[cfg(feature = "cuda")]
 impl CudaBackend {
     pub fn new() -> Result<Self> {
+        Self::on_device(0)
+    }
+
+    /// Backend on the `index`th CUDA device
+    pub fn on_device(index: usize) -> Result<Self> {
         rustacuda::init(CudaFlags::empty())?;
-        let device = Device::get_device(0)?;
+        let device = Device::get_device(index as u32)?;
         let context = Context::create_and_push(
             ContextFlags::MAP_HOST | ContextFlags::SCHED_AUTO,
             device,
@@ -782,6 +831,20 @@ impl CudaBackend {
     pub fn is_available() -> bool {
         rustacuda::init(CudaFlags::empty()).is_ok() && Device::num_devices().unwrap_or(0) > 0
     }
+
+    /// Name and total memory of every CUDA device
+    pub fn devices() -> Vec<(String, Option<u64>)> {
+        if rustacuda::init(CudaFlags::empty()).is_err() {
+            return Vec::new();
+        }
+        match Device::devices() {
+            Ok(devices) => devices
+                .filter_map(|device| device.ok())
+                .map(|device| (device.name().unwrap_or_default(), device.total_memory().ok().map(|bytes| bytes as u64)))
+                .collect(),
+            Err(_) => Vec::new(),
+        }
+    }
 }
 
 #[cfg(feature = "cuda")]
@@ -968,6 +1031,14 @@ impl CudaBackend {
         Err(Error::Storage("CUDA feature not enabled".to_string()))
     }
 
+    pub fn on_device(_index: usize) -> Result<Self> {
+        Err(Error::Storage("CUDA feature not enabled".to_string()))
+    }
+
+    pub fn devices() -> Vec<(String, Option<u64>)> {
+        Vec::new()
+    }
+
     pub fn is_available() -> bool {
         false
     }
@@ -1044,7 +1115,39 @@ impl VulkanBackend {
             force_fallback_adapter: false,
         }))
         .ok_or_else(|| Error::Storage("No Vulkan adapter found".to_string()))?;
+        Self::from_adapter(adapter)
+    }
 
+    /// Backend on the `index`th Vulkan adapter
+    pub fn on_device(index: usize) -> Result<Self> {
+        let instance = Instance::new(InstanceDescriptor {
+            backends: Backends::VULKAN,
+            ..Default::default()
+        })?;
+        let adapter = instance
+            .enumerate_adapters(Backends::VULKAN)
+            .into_iter()
+            .nth(index)
+            .ok_or_else(|| Error::Storage(format!("No Vulkan adapter {}", index)))?;
+        Self::from_adapter(adapter)
+    }
+
+    /// Name of every Vulkan adapter; wgpu doesn't report adapter memory
+    pub fn devices() -> Vec<(String, Option<u64>)> {
+        match Instance::new(InstanceDescriptor {
+            backends: Backends::VULKAN,
+            ..Default::default()
+        }) {
+            Ok(instance) => instance
+                .enumerate_adapters(Backends::VULKAN)
+                .into_iter()
+                .map(|adapter| (adapter.get_info().name, None))
+                .collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    fn from_adapter(adapter: Adapter) -> Result<Self> {
         let (device, queue) = block_on(adapter.request_device(
             &DeviceDescriptor {
                 required_features: Features::empty(),
@@ -1292,6 +1395,14 @@ impl VulkanBackend {
         Err(Error::Storage("Vulkan feature not enabled".to_string()))
     }
 
+    pub fn on_device(_index: usize) -> Result<Self> {
+        Err(Error::Storage("Vulkan feature not enabled".to_string()))
+    }
+
+    pub fn devices() -> Vec<(String, Option<u64>)> {
+        Vec::new()
+    }
+
     pub fn is_available() -> bool {
         false
     }
@@ -237,7 +237,7 @@ struct Tokenizer {
     stemmer: Option<Stemmer>,
 }
 
-struct Stemmer {
+pub(crate) struct Stemmer {
     language: String,
 }
 
@@ -1180,13 +1180,13 @@ impl Tokenizer {
 }
 
 impl Stemmer {
-    fn new(language: &str) -> Self {
+    pub(crate) fn new(language: &str) -> Self {
         Self {
             language: language.to_string(),
         }
     }
 
-    fn stem(&self, word: &str) -> String {
+    pub(crate) fn stem(&self, word: &str) -> String {
         // Porter stemmer implementation (simplified but functional)
         let word = word.to_lowercase();
         
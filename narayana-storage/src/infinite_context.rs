@@ -84,6 +84,18 @@ pub struct InfiniteContextManager {
     event_sender: broadcast::Sender<ContextEvent>,
 }
 
+/// A context entry ranked by `search`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextHit {
+    pub id: String,
+    pub text: String,
+    pub metadata: ContextMetadata,
+    /// Relevance to the query, 0.0-1.0
+    pub score: f64,
+    pub tokens: Option<usize>,
+    pub created_at: u64,
+}
+
 /// Context statistics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ContextStats {
@@ -229,7 +241,9 @@ impl InfiniteContextManager {
         // Compress if needed
         let stored_content = if self.config.enable_compression 
             && content.len() >= self.config.compression_threshold_bytes {
-            self.compress(&content)?
+            let compressed = self.compress(&content)?;
+            self.compressed_contexts.insert(context_id.clone(), compressed.clone());
+            compressed
         } else {
             content.clone()
         };
@@ -348,6 +362,58 @@ impl InfiniteContextManager {
         }
     }
 
+    /// Context entry with its content decompressed, without touching access statistics
+    pub fn get_entry(&self, context_id: &str) -> Result<Option<ContextEntry>> {
+        let Some(content) = self.content_of(context_id)? else {
+            return Ok(None);
+        };
+        Ok(self.contexts.get(context_id).map(|entry| ContextEntry { content, ..entry.clone() }))
+    }
+
+    /// Ids of the contexts carrying `tag`
+    pub fn ids_with_tag(&self, tag: &str) -> Vec<String> {
+        self.by_tag.get(tag).map(|ids| ids.clone()).unwrap_or_default()
+    }
+
+    /// Remove a context and its index entries; false when it didn't exist
+    pub fn remove_context(&self, context_id: &str) -> bool {
+        let size = self.content_of(context_id).ok().flatten().map(|content| content.len()).unwrap_or(0);
+        let Some((_, entry)) = self.contexts.remove(context_id) else {
+            return false;
+        };
+        self.hot_cache.remove(context_id);
+        self.compressed_contexts.remove(context_id);
+        self.embedding_index.write().remove(context_id);
+        if let Some(agent_id) = &entry.metadata.agent_id {
+            Self::unindex(&self.by_agent, agent_id, context_id);
+        }
+        if let Some(conversation_id) = &entry.metadata.conversation_id {
+            Self::unindex(&self.by_conversation, conversation_id, context_id);
+        }
+        Self::unindex(&self.by_type, &entry.metadata.context_type, context_id);
+        for tag in &entry.metadata.tags {
+            Self::unindex(&self.by_tag, tag, context_id);
+        }
+        self.temporal_index.write().retain(|(_, id)| id != context_id);
+
+        let mut stats = self.stats.write();
+        stats.total_contexts = stats.total_contexts.saturating_sub(1);
+        stats.total_size_bytes = stats.total_size_bytes.saturating_sub(size as u64);
+        if let Some(tokens) = entry.tokens {
+            stats.total_tokens = stats.total_tokens.saturating_sub(tokens as u64);
+        }
+        let _ = self.event_sender.send(ContextEvent::ContextDeleted {
+            context_id: context_id.to_string(),
+        });
+        true
+    }
+
+    fn unindex<K: Eq + Hash>(index: &DashMap<K, Vec<String>>, key: &K, context_id: &str) {
+        if let Some(mut ids) = index.get_mut(key) {
+            ids.retain(|id| id != context_id);
+        }
+    }
+
     /// Retrieve contexts by agent - instant batch retrieval
     pub fn retrieve_by_agent(&self, agent_id: &str) -> Result<Vec<Bytes>> {
         if let Some(context_ids) = self.by_agent.get(agent_id) {
@@ -413,6 +479,90 @@ impl InfiniteContextManager {
         Ok(results)
     }
 
+    /// Rank contexts against a query, for assembling prompt context
+    ///
+    /// Scores by embedding similarity when an embedding is given and indexed
+    /// contexts match its dimension, otherwise by the share of query terms
+    /// found in the most recent contexts.
+    pub fn search(&self, query: &str, query_embedding: Option<&[f32]>, k: usize) -> Result<Vec<ContextHit>> {
+        // SECURITY: Bound result size and the number of contexts scanned by keyword
+        const MAX_K: usize = 1_000;
+        const KEYWORD_SCAN_LIMIT: usize = 10_000;
+        let k = k.min(MAX_K);
+
+        let mut scored: Vec<(String, f64)> = Vec::new();
+        if let Some(query_embedding) = query_embedding {
+            let index = self.embedding_index.read();
+            for (context_id, embedding) in index.iter() {
+                if embedding.len() == query_embedding.len() {
+                    let similarity = Self::cosine_similarity(query_embedding, embedding)?;
+                    scored.push((context_id.clone(), similarity.max(0.0)));
+                }
+            }
+        }
+
+        if scored.is_empty() {
+            let terms: Vec<String> = query
+                .split(|c: char| !c.is_alphanumeric())
+                .filter(|term| term.len() > 2)
+                .map(|term| term.to_lowercase())
+                .collect();
+            if terms.is_empty() {
+                return Ok(Vec::new());
+            }
+            let recent: Vec<String> = self.temporal_index.read()
+                .iter()
+                .rev()
+                .take(KEYWORD_SCAN_LIMIT)
+                .map(|(_, id)| id.clone())
+                .collect();
+            for context_id in recent {
+                let Some(content) = self.content_of(&context_id)? else {
+                    continue;
+                };
+                let text = String::from_utf8_lossy(&content).to_lowercase();
+                let found = terms.iter().filter(|term| text.contains(term.as_str())).count();
+                if found > 0 {
+                    scored.push((context_id, found as f64 / terms.len() as f64));
+                }
+            }
+        }
+
+        scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        scored.truncate(k);
+
+        let mut hits = Vec::with_capacity(scored.len());
+        for (context_id, score) in scored {
+            let Some(content) = self.retrieve_context(&context_id)? else {
+                continue;
+            };
+            let Some(entry) = self.contexts.get(&context_id) else {
+                continue;
+            };
+            hits.push(ContextHit {
+                id: context_id,
+                text: String::from_utf8_lossy(&content).into_owned(),
+                metadata: entry.metadata.clone(),
+                score,
+                tokens: entry.tokens,
+                created_at: entry.created_at,
+            });
+        }
+        Ok(hits)
+    }
+
+    /// Context content without touching access statistics
+    fn content_of(&self, context_id: &str) -> Result<Option<Bytes>> {
+        if let Some(content) = self.hot_cache.get(context_id) {
+            return Ok(Some(content.clone()));
+        }
+        match self.contexts.get(context_id) {
+            Some(entry) if self.compressed_contexts.contains_key(context_id) => Ok(Some(self.decompress(&entry.content)?)),
+            Some(entry) => Ok(Some(entry.content.clone())),
+            None => Ok(None),
+        }
+    }
+
     /// Retrieve contexts temporally - instant temporal retrieval
     pub fn retrieve_temporal(&self, start_time: u64, end_time: u64) -> Result<Vec<Bytes>> {
         let index = self.temporal_index.read();
@@ -670,3 +820,53 @@ use std::io::{Read, Write};
 use tracing::debug;
 use uuid;
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata(context_type: ContextType) -> ContextMetadata {
+        ContextMetadata {
+            agent_id: Some("robot".to_string()),
+            conversation_id: None,
+            message_id: None,
+            context_type,
+            tags: Vec::new(),
+            priority: 0.5,
+            importance: 0.5,
+            related_ids: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_search_by_embedding_and_keywords() {
+        let config = InfiniteContextConfig { compression_threshold_bytes: 16, ..Default::default() };
+        let manager = InfiniteContextManager::new(config);
+        let cup = manager.add_context(
+            Bytes::from("the red cup is on the kitchen table"),
+            metadata(ContextType::Observation),
+            Some(vec![1.0, 0.0]),
+            Some(9),
+        ).unwrap();
+        let door = manager.add_context(
+            Bytes::from("the door was closed"),
+            metadata(ContextType::Observation),
+            Some(vec![0.0, 1.0]),
+            None,
+        ).unwrap();
+
+        let hits = manager.search("cup", Some(&[0.9, 0.1]), 1).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].id, cup);
+        assert_eq!(hits[0].text, "the red cup is on the kitchen table");
+        assert!(hits[0].score > 0.9);
+
+        // Compressed content stays readable once evicted from the hot cache
+        manager.hot_cache.clear();
+        let hits = manager.search("closed door", None, 5).unwrap();
+        assert_eq!(hits[0].id, door);
+        assert_eq!(hits[0].text, "the door was closed");
+        assert_eq!(hits[0].score, 1.0);
+        assert!(manager.search("xy", None, 5).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,888 @@
+// Maintenance Jobs - scheduled, retried background work
+// Job types register a handler once; jobs bind a type to a cron schedule,
+// retry policy and parameters. The scheduler starts due jobs within per-type
+// and global concurrency limits and keeps a short run history per job.
+
+use crate::background_daemon::BackgroundDaemon;
+use crate::brain_bundle::BrainBundle;
+use crate::cognitive::CognitiveBrain;
+use crate::priority_scheduler::{PriorityScheduler, TaskClass};
+use crate::query_learning::QueryLearningEngine;
+use crate::ttl::TtlCompactor;
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, NaiveDate, Timelike, Utc};
+use narayana_core::{Error, Result};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+/// Built-in job types
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    /// Prune finished thoughts and other transient state
+    Compaction,
+    /// Memory consolidation, pattern detection and association formation
+    Consolidation,
+    /// Brain bundle snapshots with retention
+    Backup,
+    /// Query pattern analysis for the query optimizer
+    Analyze,
+    /// Ingest connector polls (see `connectors`)
+    Ingest,
+}
+
+impl std::fmt::Display for JobKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JobKind::Compaction => write!(f, "compaction"),
+            JobKind::Consolidation => write!(f, "consolidation"),
+            JobKind::Backup => write!(f, "backup"),
+            JobKind::Analyze => write!(f, "analyze"),
+            JobKind::Ingest => write!(f, "ingest"),
+        }
+    }
+}
+
+/// Cron schedule in UTC: "minute hour day-of-month month day-of-week"
+///
+/// Fields accept `*`, numbers, ranges (`1-5`), lists (`0,30`) and steps
+/// (`*/15`, `10-50/20`). Day-of-week is 0-7 with Sunday as 0 or 7. As in cron,
+/// when both day fields are restricted a day matching either one runs.
+/// `@hourly`, `@daily`, `@weekly`, `@monthly` and `@yearly` are shorthands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    expr: String,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl CronSchedule {
+    pub fn parse(expr: &str) -> Result<Self> {
+        let expanded = match expr.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(Error::Storage(format!(
+                "Invalid cron expression '{}': expected 5 fields (minute hour day month weekday)",
+                expr
+            )));
+        }
+
+        let mut weekdays = Self::parse_field(fields[4], 0, 7, expr)?;
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays | 1) & !(1 << 7); // 7 is Sunday too
+        }
+        Ok(Self {
+            expr: expr.trim().to_string(),
+            minutes: Self::parse_field(fields[0], 0, 59, expr)?,
+            hours: Self::parse_field(fields[1], 0, 23, expr)?,
+            days: Self::parse_field(fields[2], 1, 31, expr)?,
+            months: Self::parse_field(fields[3], 1, 12, expr)?,
+            weekdays,
+            any_day: fields[2].starts_with('*'),
+            any_weekday: fields[4].starts_with('*'),
+        })
+    }
+
+    fn parse_field(field: &str, min: u32, max: u32, expr: &str) -> Result<u64> {
+        let invalid = || Error::Storage(format!("Invalid cron field '{}' in '{}' (allowed {}-{})", field, expr, min, max));
+        let mut bits = 0u64;
+        for part in field.split(',') {
+            let (range, step) = match part.split_once('/') {
+                Some((range, step)) => (range, Some(step.parse::<u32>().map_err(|_| invalid())?)),
+                None => (part, None),
+            };
+            let (lo, hi) = match range {
+                "*" => (min, max),
+                _ => match range.split_once('-') {
+                    Some((lo, hi)) => (
+                        lo.parse::<u32>().map_err(|_| invalid())?,
+                        hi.parse::<u32>().map_err(|_| invalid())?,
+                    ),
+                    None => {
+                        let value = range.parse::<u32>().map_err(|_| invalid())?;
+                        // "5/10" means from 5 to the end in steps of 10
+                        (value, if step.is_some() { max } else { value })
+                    }
+                },
+            };
+            if lo < min || hi > max || lo > hi || step == Some(0) {
+                return Err(invalid());
+            }
+            for value in (lo..=hi).step_by(step.unwrap_or(1) as usize) {
+                bits |= 1 << value;
+            }
+        }
+        Ok(bits)
+    }
+
+    /// The expression this schedule was parsed from
+    pub fn expression(&self) -> &str {
+        &self.expr
+    }
+
+    /// First matching minute strictly after `after` (Unix seconds); None if
+    /// nothing matches within five years (e.g. "0 0 31 2 *")
+    pub fn next_after(&self, after: u64) -> Option<u64> {
+        let start = DateTime::<Utc>::from_timestamp(after as i64, 0)?;
+        let mut t = start.with_second(0)?.with_nanosecond(0)? + ChronoDuration::minutes(1);
+        let limit = t + ChronoDuration::days(5 * 366);
+
+        while t < limit {
+            if self.months & (1 << t.month()) == 0 {
+                let (year, month) = if t.month() == 12 { (t.year() + 1, 1) } else { (t.year(), t.month() + 1) };
+                t = NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)?.and_utc();
+                continue;
+            }
+            if !self.day_matches(&t) {
+                t = (t.date_naive() + ChronoDuration::days(1)).and_hms_opt(0, 0, 0)?.and_utc();
+                continue;
+            }
+            if self.hours & (1 << t.hour()) == 0 {
+                t = t.with_minute(0)? + ChronoDuration::hours(1);
+                continue;
+            }
+            if self.minutes & (1 << t.minute()) == 0 {
+                t += ChronoDuration::minutes(1);
+                continue;
+            }
+            return Some(t.timestamp() as u64);
+        }
+        None
+    }
+
+    fn day_matches(&self, t: &DateTime<Utc>) -> bool {
+        let dom = self.days & (1 << t.day()) != 0;
+        let dow = self.weekdays & (1 << t.weekday().num_days_from_sunday()) != 0;
+        match (self.any_day, self.any_weekday) {
+            (true, true) => true,
+            (true, false) => dow,
+            (false, true) => dom,
+            (false, false) => dom || dow,
+        }
+    }
+}
+
+/// How failed runs are retried
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// Attempts per run, the first one included
+    pub max_attempts: u32,
+    /// Delay before the first retry
+    pub initial_backoff_secs: u64,
+    /// Upper bound on the delay
+    pub max_backoff_secs: u64,
+    /// Delay growth per attempt
+    pub multiplier: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff_secs: 30,
+            max_backoff_secs: 3600,
+            multiplier: 2.0,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before retrying after failed attempt `attempt` (1-based)
+    pub fn backoff_secs(&self, attempt: u32) -> u64 {
+        let factor = self.multiplier.max(1.0).powi(attempt.saturating_sub(1).min(32) as i32);
+        ((self.initial_backoff_secs as f64 * factor) as u64).min(self.max_backoff_secs)
+    }
+}
+
+/// A job: a registered type run on a schedule or on demand
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobSpec {
+    /// Unique name ([a-zA-Z0-9_-], max 64 chars)
+    pub name: String,
+    pub kind: JobKind,
+    /// Cron expression; None runs only when triggered
+    #[serde(default)]
+    pub schedule: Option<String>,
+    #[serde(default)]
+    pub retry: RetryPolicy,
+    /// Attempts running longer fail
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    /// Passed to the handler
+    #[serde(default)]
+    pub params: Value,
+}
+
+/// What started a run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobTrigger {
+    Schedule,
+    Manual,
+    Retry,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    Running,
+    Succeeded,
+    Failed,
+}
+
+/// One attempt of a job
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobRun {
+    pub id: String,
+    pub job: String,
+    pub kind: JobKind,
+    pub trigger: JobTrigger,
+    /// 1 for the first attempt, counting up through retries
+    pub attempt: u32,
+    pub status: RunStatus,
+    pub started_at: u64,
+    pub finished_at: Option<u64>,
+    /// Handler result on success
+    pub output: Option<Value>,
+    pub error: Option<String>,
+}
+
+/// Status of a job
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobInfo {
+    #[serde(flatten)]
+    pub spec: JobSpec,
+    /// Paused jobs are skipped by the schedule but can still be triggered
+    pub paused: bool,
+    /// Id of the run in progress
+    pub running: Option<String>,
+    pub next_run_at: Option<u64>,
+    /// When the pending retry of a failed run starts
+    pub retry_at: Option<u64>,
+    pub last_run: Option<JobRun>,
+    pub runs_total: u64,
+    pub failures_total: u64,
+}
+
+/// What a handler is given for one attempt
+#[derive(Debug, Clone)]
+pub struct JobContext {
+    pub run_id: String,
+    pub job: String,
+    pub kind: JobKind,
+    pub attempt: u32,
+    pub params: Value,
+}
+
+/// Runs one type of job; the returned value is kept as the run's output
+#[async_trait::async_trait]
+pub trait JobHandler: Send + Sync {
+    async fn run(&self, ctx: &JobContext) -> Result<Value>;
+}
+
+/// Scheduler configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobSchedulerConfig {
+    /// Runs in progress at once across all jobs
+    pub max_concurrent: usize,
+    /// Finished runs kept per job
+    pub history_per_job: usize,
+    /// How often due jobs are checked
+    pub tick_interval: Duration,
+}
+
+impl Default for JobSchedulerConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent: 2,
+            history_per_job: 50,
+            tick_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+struct Registration {
+    handler: Arc<dyn JobHandler>,
+    max_concurrent: usize,
+    running: usize,
+}
+
+struct JobState {
+    spec: JobSpec,
+    schedule: Option<CronSchedule>,
+    paused: bool,
+    running: Option<String>,
+    next_run_at: Option<u64>,
+    /// (when, attempt) of the pending retry
+    retry: Option<(u64, u32)>,
+    history: VecDeque<JobRun>,
+    runs_total: u64,
+    failures_total: u64,
+}
+
+impl JobState {
+    fn info(&self) -> JobInfo {
+        JobInfo {
+            spec: self.spec.clone(),
+            paused: self.paused,
+            running: self.running.clone(),
+            next_run_at: self.next_run_at,
+            retry_at: self.retry.map(|(at, _)| at),
+            last_run: self.history.back().cloned(),
+            runs_total: self.runs_total,
+            failures_total: self.failures_total,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    handlers: HashMap<JobKind, Registration>,
+    jobs: HashMap<String, JobState>,
+    running: usize,
+}
+
+/// Starts due jobs, retries failed runs and tracks their status
+pub struct JobScheduler {
+    config: JobSchedulerConfig,
+    inner: Mutex<Inner>,
+}
+
+impl JobScheduler {
+    pub fn new(config: JobSchedulerConfig) -> Self {
+        Self {
+            config,
+            inner: Mutex::new(Inner::default()),
+        }
+    }
+
+    /// Register the handler for a job type; at most `max_concurrent` of its
+    /// runs are in progress at once
+    pub fn register(&self, kind: JobKind, handler: Arc<dyn JobHandler>, max_concurrent: usize) {
+        self.inner.lock().handlers.insert(kind, Registration {
+            handler,
+            max_concurrent: max_concurrent.max(1),
+            running: 0,
+        });
+    }
+
+    /// Add or replace a job (a replaced job keeps its history and pause state)
+    pub fn add_job(&self, spec: JobSpec) -> Result<JobInfo> {
+        if spec.name.is_empty()
+            || spec.name.len() > 64
+            || !spec.name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(Error::Storage(format!("Invalid job name '{}'", spec.name)));
+        }
+        let schedule = spec.schedule.as_deref().map(CronSchedule::parse).transpose()?;
+        let next_run_at = schedule.as_ref().and_then(|s| s.next_after(now_secs()));
+
+        let mut inner = self.inner.lock();
+        if !inner.handlers.contains_key(&spec.kind) {
+            return Err(Error::Storage(format!("No handler registered for {} jobs", spec.kind)));
+        }
+        let state = match inner.jobs.remove(&spec.name) {
+            Some(mut existing) => {
+                existing.spec = spec.clone();
+                existing.schedule = schedule;
+                existing.next_run_at = next_run_at;
+                existing
+            }
+            None => JobState {
+                spec: spec.clone(),
+                schedule,
+                paused: false,
+                running: None,
+                next_run_at,
+                retry: None,
+                history: VecDeque::new(),
+                runs_total: 0,
+                failures_total: 0,
+            },
+        };
+        let info = state.info();
+        inner.jobs.insert(spec.name, state);
+        Ok(info)
+    }
+
+    /// Remove a job; a run in progress finishes but is no longer tracked
+    pub fn remove_job(&self, name: &str) -> bool {
+        self.inner.lock().jobs.remove(name).is_some()
+    }
+
+    pub fn get(&self, name: &str) -> Option<JobInfo> {
+        self.inner.lock().jobs.get(name).map(JobState::info)
+    }
+
+    /// All jobs, by name
+    pub fn list(&self) -> Vec<JobInfo> {
+        let mut jobs: Vec<JobInfo> = self.inner.lock().jobs.values().map(JobState::info).collect();
+        jobs.sort_by(|a, b| a.spec.name.cmp(&b.spec.name));
+        jobs
+    }
+
+    /// Recent runs of a job, newest first
+    pub fn runs(&self, name: &str, limit: usize) -> Option<Vec<JobRun>> {
+        let inner = self.inner.lock();
+        let job = inner.jobs.get(name)?;
+        Some(job.history.iter().rev().take(limit).cloned().collect())
+    }
+
+    /// Stop scheduling a job (runs in progress finish, pending retries wait)
+    pub fn pause(&self, name: &str) -> Result<JobInfo> {
+        let mut inner = self.inner.lock();
+        let job = inner.jobs.get_mut(name).ok_or_else(|| not_found(name))?;
+        job.paused = true;
+        Ok(job.info())
+    }
+
+    /// Resume scheduling from now on; runs missed while paused are skipped
+    pub fn resume(&self, name: &str) -> Result<JobInfo> {
+        let mut inner = self.inner.lock();
+        let job = inner.jobs.get_mut(name).ok_or_else(|| not_found(name))?;
+        job.paused = false;
+        job.next_run_at = job.schedule.as_ref().and_then(|s| s.next_after(now_secs()));
+        Ok(job.info())
+    }
+
+    /// Start a job now, paused or not; replaces a pending retry. Returns the run id.
+    pub fn trigger(self: &Arc<Self>, name: &str) -> Result<String> {
+        self.start_run(name, JobTrigger::Manual, 1)
+    }
+
+    /// Start every job due at `now` (Unix seconds) that fits the concurrency
+    /// limits. Jobs that don't fit stay due; a job due several times while
+    /// busy or over the limit runs once. Returns the started run ids.
+    pub fn tick(self: &Arc<Self>, now: u64) -> Vec<String> {
+        let mut due: Vec<(u64, String, JobTrigger, u32)> = {
+            let inner = self.inner.lock();
+            inner.jobs.values()
+                .filter(|job| !job.paused && job.running.is_none())
+                .filter_map(|job| match (job.retry, job.next_run_at) {
+                    (Some((at, attempt)), _) if at <= now => Some((at, job.spec.name.clone(), JobTrigger::Retry, attempt)),
+                    (_, Some(at)) if at <= now => Some((at, job.spec.name.clone(), JobTrigger::Schedule, 1)),
+                    _ => None,
+                })
+                .collect()
+        };
+        due.sort_by(|a, b| (a.0, &a.1).cmp(&(b.0, &b.1)));
+
+        let mut started = Vec::new();
+        for (_, name, trigger, attempt) in due {
+            match self.start_run(&name, trigger, attempt) {
+                Ok(run_id) => {
+                    if trigger == JobTrigger::Schedule {
+                        if let Some(job) = self.inner.lock().jobs.get_mut(&name) {
+                            job.next_run_at = job.schedule.as_ref().and_then(|s| s.next_after(now));
+                        }
+                    }
+                    started.push(run_id);
+                }
+                Err(e) => tracing::debug!("Job {} stays due: {}", name, e),
+            }
+        }
+        started
+    }
+
+    /// Tick every `tick_interval` until the scheduler is dropped
+    pub fn start(self: &Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let scheduler = Arc::downgrade(self);
+        let interval = self.config.tick_interval;
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let Some(scheduler) = scheduler.upgrade() else { break };
+                scheduler.tick(now_secs());
+            }
+        })
+    }
+
+    fn start_run(self: &Arc<Self>, name: &str, trigger: JobTrigger, attempt: u32) -> Result<String> {
+        let (handler, ctx, timeout) = {
+            let mut guard = self.inner.lock();
+            let inner = &mut *guard;
+            let job = inner.jobs.get_mut(name).ok_or_else(|| not_found(name))?;
+            if job.running.is_some() {
+                return Err(Error::Storage(format!("Job {} is already running", name)));
+            }
+            if inner.running >= self.config.max_concurrent {
+                return Err(Error::Storage(format!(
+                    "Concurrency limit reached ({} jobs running)",
+                    inner.running
+                )));
+            }
+            let registration = inner.handlers.get_mut(&job.spec.kind)
+                .ok_or_else(|| Error::Storage(format!("No handler registered for {} jobs", job.spec.kind)))?;
+            if registration.running >= registration.max_concurrent {
+                return Err(Error::Storage(format!(
+                    "Concurrency limit reached for {} jobs ({} running)",
+                    job.spec.kind, registration.running
+                )));
+            }
+
+            let run = JobRun {
+                id: uuid::Uuid::new_v4().to_string(),
+                job: name.to_string(),
+                kind: job.spec.kind,
+                trigger,
+                attempt,
+                status: RunStatus::Running,
+                started_at: now_secs(),
+                finished_at: None,
+                output: None,
+                error: None,
+            };
+            let ctx = JobContext {
+                run_id: run.id.clone(),
+                job: name.to_string(),
+                kind: job.spec.kind,
+                attempt,
+                params: job.spec.params.clone(),
+            };
+            job.running = Some(run.id.clone());
+            job.retry = None;
+            job.runs_total += 1;
+            job.history.push_back(run);
+            while job.history.len() > self.config.history_per_job.max(1) {
+                job.history.pop_front();
+            }
+            registration.running += 1;
+            inner.running += 1;
+            (registration.handler.clone(), ctx, job.spec.timeout_secs)
+        };
+
+        let run_id = ctx.run_id.clone();
+        let scheduler = self.clone();
+        tokio::spawn(async move {
+            let result = match timeout {
+                Some(secs) => tokio::time::timeout(Duration::from_secs(secs), handler.run(&ctx))
+                    .await
+                    .unwrap_or_else(|_| Err(Error::Storage(format!("Timed out after {}s", secs)))),
+                None => handler.run(&ctx).await,
+            };
+            scheduler.finish_run(&ctx, result);
+        });
+        Ok(run_id)
+    }
+
+    fn finish_run(&self, ctx: &JobContext, result: Result<Value>) {
+        let now = now_secs();
+        let mut guard = self.inner.lock();
+        let inner = &mut *guard;
+        inner.running = inner.running.saturating_sub(1);
+        if let Some(registration) = inner.handlers.get_mut(&ctx.kind) {
+            registration.running = registration.running.saturating_sub(1);
+        }
+
+        // The job may have been removed or replaced while running
+        let Some(job) = inner.jobs.get_mut(&ctx.job).filter(|job| job.running.as_deref() == Some(ctx.run_id.as_str())) else {
+            return;
+        };
+        job.running = None;
+        let failed = result.is_err();
+        if let Some(run) = job.history.iter_mut().rev().find(|run| run.id == ctx.run_id) {
+            run.finished_at = Some(now);
+            match result {
+                Ok(output) => {
+                    run.status = RunStatus::Succeeded;
+                    run.output = Some(output);
+                }
+                Err(e) => {
+                    run.status = RunStatus::Failed;
+                    run.error = Some(e.to_string());
+                }
+            }
+        }
+        if failed {
+            job.failures_total += 1;
+            if ctx.attempt < job.spec.retry.max_attempts {
+                let at = now + job.spec.retry.backoff_secs(ctx.attempt);
+                job.retry = Some((at, ctx.attempt + 1));
+                warn!("Job {} failed (attempt {}), retrying at {}", ctx.job, ctx.attempt, at);
+            } else {
+                warn!("Job {} failed after {} attempt(s)", ctx.job, ctx.attempt);
+            }
+        } else {
+            info!("Job {} finished", ctx.job);
+        }
+    }
+}
+
+fn not_found(name: &str) -> Error {
+    Error::Storage(format!("Job not found: {}", name))
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Prunes finished thoughts from a brain, as a background task on `scheduler`
+/// when one is given so it never competes with realtime work, and removes
+/// expired rows from tables with a TTL column when `ttl` is set
+pub struct CompactionJob {
+    pub brain: Arc<CognitiveBrain>,
+    pub scheduler: Option<Arc<PriorityScheduler>>,
+    pub ttl: Option<Arc<TtlCompactor>>,
+}
+
+#[async_trait::async_trait]
+impl JobHandler for CompactionJob {
+    async fn run(&self, _ctx: &JobContext) -> Result<Value> {
+        let removed = match self.scheduler {
+            Some(ref scheduler) => {
+                let brain = self.brain.clone();
+                scheduler
+                    .submit(TaskClass::Background, move || brain.cleanup_thoughts())
+                    .await
+                    .map_err(|_| Error::Storage("Compaction task did not complete".to_string()))?
+            }
+            None => self.brain.cleanup_thoughts(),
+        };
+        let expired = match self.ttl {
+            Some(ref ttl) => ttl.compact().await.rows_removed,
+            None => 0,
+        };
+        Ok(serde_json::json!({ "thoughts_removed": removed, "expired_rows_removed": expired }))
+    }
+}
+
+/// Runs a background daemon's consolidation passes regardless of their intervals
+pub struct ConsolidationJob {
+    pub daemon: Arc<BackgroundDaemon>,
+}
+
+#[async_trait::async_trait]
+impl JobHandler for ConsolidationJob {
+    async fn run(&self, _ctx: &JobContext) -> Result<Value> {
+        let report = self.daemon.consolidate_now().await?;
+        serde_json::to_value(report).map_err(|e| Error::Serialization(e.to_string()))
+    }
+}
+
+/// Writes brain bundles to `dir` as `brain-<unix secs>.json`, keeping the newest
+/// `retention` (a `retention` param overrides it per job)
+pub struct BackupJob {
+    pub brain: Arc<CognitiveBrain>,
+    pub dir: PathBuf,
+    pub retention: usize,
+}
+
+#[async_trait::async_trait]
+impl JobHandler for BackupJob {
+    async fn run(&self, ctx: &JobContext) -> Result<Value> {
+        let retention = ctx.params.get("retention")
+            .and_then(|v| v.as_u64())
+            .map(|n| n as usize)
+            .unwrap_or(self.retention)
+            .max(1);
+        std::fs::create_dir_all(&self.dir)
+            .map_err(|e| Error::Storage(format!("Failed to create backup directory: {}", e)))?;
+
+        let path = self.dir.join(format!("brain-{}.json", now_secs()));
+        let bundle = BrainBundle::export(&self.brain, None, Some(format!("backup {}", ctx.run_id)))?;
+        bundle.save(&path)?;
+
+        // File names sort by time
+        let mut backups: Vec<PathBuf> = std::fs::read_dir(&self.dir)
+            .map_err(|e| Error::Storage(format!("Failed to list backups: {}", e)))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| p.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with("brain-") && n.ends_with(".json")))
+            .collect();
+        backups.sort();
+        let excess = backups.len().saturating_sub(retention);
+        let mut removed = 0;
+        for old in &backups[..excess] {
+            match std::fs::remove_file(old) {
+                Ok(()) => removed += 1,
+                Err(e) => warn!("Failed to remove old backup {:?}: {}", old, e),
+            }
+        }
+        Ok(serde_json::json!({ "path": path.display().to_string(), "removed": removed }))
+    }
+}
+
+/// Analyzes recorded queries for optimization hints and refreshes the
+/// advisor's recommendations
+pub struct AnalyzeJob {
+    pub query_learning: Arc<QueryLearningEngine>,
+}
+
+#[async_trait::async_trait]
+impl JobHandler for AnalyzeJob {
+    async fn run(&self, _ctx: &JobContext) -> Result<Value> {
+        self.query_learning.analyze_queries()?;
+        let recommendations = self.query_learning.recommend();
+        let pending = recommendations.iter()
+            .filter(|r| r.status == crate::query_learning::RecommendationStatus::Pending)
+            .count();
+        Ok(serde_json::json!({ "recommendations": recommendations.len(), "pending": pending }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Fails its first `failures` runs
+    struct FlakyHandler {
+        failures: AtomicU32,
+        runs: AtomicU32,
+    }
+
+    #[async_trait::async_trait]
+    impl JobHandler for FlakyHandler {
+        async fn run(&self, ctx: &JobContext) -> Result<Value> {
+            self.runs.fetch_add(1, Ordering::SeqCst);
+            if ctx.params.get("sleep_ms").is_some() {
+                tokio::time::sleep(Duration::from_millis(200)).await;
+            }
+            if self.failures.load(Ordering::SeqCst) > 0 {
+                self.failures.fetch_sub(1, Ordering::SeqCst);
+                return Err(Error::Storage("disk busy".to_string()));
+            }
+            Ok(serde_json::json!({ "attempt": ctx.attempt }))
+        }
+    }
+
+    fn scheduler(failures: u32, max_concurrent: usize) -> (Arc<JobScheduler>, Arc<FlakyHandler>) {
+        let scheduler = Arc::new(JobScheduler::new(JobSchedulerConfig { max_concurrent, ..Default::default() }));
+        let handler = Arc::new(FlakyHandler { failures: AtomicU32::new(failures), runs: AtomicU32::new(0) });
+        scheduler.register(JobKind::Compaction, handler.clone(), 1);
+        scheduler.register(JobKind::Analyze, handler.clone(), 2);
+        (scheduler, handler)
+    }
+
+    fn spec(name: &str, kind: JobKind, schedule: Option<&str>) -> JobSpec {
+        JobSpec {
+            name: name.to_string(),
+            kind,
+            schedule: schedule.map(str::to_string),
+            retry: RetryPolicy { max_attempts: 2, initial_backoff_secs: 10, ..Default::default() },
+            timeout_secs: None,
+            params: Value::Null,
+        }
+    }
+
+    async fn wait_idle(scheduler: &JobScheduler, name: &str) -> JobInfo {
+        for _ in 0..200 {
+            let info = scheduler.get(name).unwrap();
+            if info.running.is_none() {
+                return info;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        panic!("job {} did not finish", name);
+    }
+
+    #[test]
+    fn test_cron_next_after() {
+        // 2024-01-01 00:00:00 UTC was a Monday
+        let monday = 1_704_067_200;
+        let every_15 = CronSchedule::parse("*/15 * * * *").unwrap();
+        assert_eq!(every_15.next_after(monday), Some(monday + 15 * 60));
+        assert_eq!(every_15.next_after(monday + 61), Some(monday + 15 * 60));
+
+        let daily = CronSchedule::parse("@daily").unwrap();
+        assert_eq!(daily.next_after(monday), Some(monday + 86_400));
+        let weekdays = CronSchedule::parse("30 9 * * 1-5").unwrap();
+        assert_eq!(weekdays.next_after(monday + 4 * 86_400 + 10 * 3600), Some(monday + 7 * 86_400 + 9 * 3600 + 1800));
+        // Sunday as 7, and day-of-month or weekday when both are restricted
+        let sundays = CronSchedule::parse("0 0 * * 7").unwrap();
+        assert_eq!(sundays.next_after(monday), Some(monday + 6 * 86_400));
+        let either = CronSchedule::parse("0 0 15 * 0").unwrap();
+        assert_eq!(either.next_after(monday), Some(monday + 6 * 86_400));
+
+        assert_eq!(CronSchedule::parse("0 0 31 2 *").unwrap().next_after(monday), None);
+        for bad in ["* * * *", "60 * * * *", "*/0 * * * *", "5-1 * * * *", "a * * * *"] {
+            assert!(CronSchedule::parse(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_runs_are_retried_with_backoff() {
+        let (scheduler, handler) = scheduler(1, 2);
+        let job = scheduler.add_job(spec("nightly", JobKind::Compaction, Some("0 3 * * *"))).unwrap();
+        let due = job.next_run_at.unwrap();
+        assert!(scheduler.tick(due - 1).is_empty());
+
+        assert_eq!(scheduler.tick(due).len(), 1);
+        let info = wait_idle(&scheduler, "nightly").await;
+        assert_eq!(info.last_run.as_ref().unwrap().status, RunStatus::Failed);
+        assert_eq!(info.next_run_at, Some(due + 86_400));
+        let retry_at = info.retry_at.unwrap();
+
+        // Retry waits for its backoff, then runs as attempt 2
+        assert!(scheduler.tick(retry_at - 1).is_empty());
+        assert_eq!(scheduler.tick(retry_at).len(), 1);
+        let info = wait_idle(&scheduler, "nightly").await;
+        let last = info.last_run.unwrap();
+        assert_eq!((last.status, last.trigger, last.attempt), (RunStatus::Succeeded, JobTrigger::Retry, 2));
+        assert_eq!(last.output, Some(serde_json::json!({ "attempt": 2 })));
+        assert_eq!((info.runs_total, info.failures_total, info.retry_at), (2, 1, None));
+        assert_eq!(handler.runs.load(Ordering::SeqCst), 2);
+        assert_eq!(scheduler.runs("nightly", 10).unwrap()[0].attempt, 2);
+
+        // No retries past max_attempts
+        handler.failures.store(5, Ordering::SeqCst);
+        scheduler.trigger("nightly").unwrap();
+        let info = wait_idle(&scheduler, "nightly").await;
+        scheduler.tick(info.retry_at.unwrap());
+        assert_eq!(wait_idle(&scheduler, "nightly").await.retry_at, None);
+    }
+
+    #[tokio::test]
+    async fn test_concurrency_limits_and_pause() {
+        let (scheduler, _) = scheduler(0, 2);
+        let mut slow = spec("compact-a", JobKind::Compaction, None);
+        slow.params = serde_json::json!({ "sleep_ms": 200 });
+        scheduler.add_job(slow.clone()).unwrap();
+        scheduler.add_job(JobSpec { name: "compact-b".to_string(), ..slow.clone() }).unwrap();
+        scheduler.add_job(JobSpec { name: "analyze-a".to_string(), kind: JobKind::Analyze, ..slow.clone() }).unwrap();
+        scheduler.add_job(JobSpec { name: "analyze-b".to_string(), kind: JobKind::Analyze, ..slow }).unwrap();
+
+        scheduler.trigger("compact-a").unwrap();
+        assert!(scheduler.trigger("compact-a").is_err(), "a job never overlaps itself");
+        assert!(scheduler.trigger("compact-b").is_err(), "one compaction at a time");
+        scheduler.trigger("analyze-a").unwrap();
+        assert!(scheduler.trigger("analyze-b").is_err(), "two jobs in total");
+        wait_idle(&scheduler, "compact-a").await;
+        wait_idle(&scheduler, "analyze-a").await;
+        scheduler.trigger("analyze-b").unwrap();
+
+        // Paused jobs are skipped by the schedule but can be triggered
+        let job = scheduler.add_job(spec("hourly", JobKind::Compaction, Some("@hourly"))).unwrap();
+        scheduler.pause("hourly").unwrap();
+        assert!(scheduler.tick(job.next_run_at.unwrap()).is_empty());
+        scheduler.trigger("hourly").unwrap();
+        wait_idle(&scheduler, "hourly").await;
+        assert!(!scheduler.resume("hourly").unwrap().paused);
+
+        assert!(scheduler.add_job(spec("backup", JobKind::Backup, None)).is_err(), "no backup handler");
+        assert!(scheduler.add_job(spec("bad name", JobKind::Analyze, None)).is_err());
+        assert!(scheduler.add_job(spec("bad-cron", JobKind::Analyze, Some("every hour"))).is_err());
+        assert_eq!(scheduler.list().len(), 5);
+    }
+}
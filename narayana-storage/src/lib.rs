@@ -1,5 +1,9 @@
 pub mod column_store;
 pub mod persistent_column_store;
+pub mod wal;
+pub mod rocksdb_column_store;
+pub mod storage_backends;
+pub mod blob_store;
 pub mod compression;
 pub mod block;
 pub mod writer;
@@ -23,6 +27,7 @@ pub mod advanced_indexing;
 pub mod advanced_indexing_impl;
 pub mod ai_optimized;
 pub mod vector_search;
+pub mod embeddings;
 pub mod small_writes;
 pub mod advanced_joins;
 pub mod auto_increment;
@@ -39,10 +44,20 @@ pub mod auto_scaling;
 pub mod advanced_load_balancer;
 pub mod persistence;
 pub mod human_search;
+pub mod full_text;
+pub mod geospatial;
+pub mod cdc;
+pub mod anomaly_detection;
+pub mod feature_store;
 pub mod query_learning;
 pub mod predictive_scaling;
 pub mod dynamic_schema;
 pub mod dynamic_output;
+pub mod row_security;
+pub mod compliance;
+pub mod temporal;
+pub mod ttl;
+pub mod replication;
 pub mod migration_free;
 pub mod dynamic_thoughts;
 pub mod bug_detection;
@@ -51,21 +66,30 @@ pub mod security_limits;
 pub mod native_events;
 pub mod workers;
 pub mod threading;
+pub mod priority_scheduler;
 pub mod quantum_optimization;
 pub mod optimization_algorithms;
 pub mod gpu_execution;
+pub mod gpu_devices;
 pub mod thought_kernel;
+pub mod reasoning_strategies;
 pub mod reinforcement_learning;
 pub mod hnsw;
 pub mod sensory_streams;
 pub mod cognitive_graph;
 pub mod model_registry;
+pub mod camera_calibration;
 pub mod thought_serialization;
+pub mod brain_bundle;
 pub mod autonomous_schema;
 pub mod embedded;
 pub mod conscience_persistent_loop;
 pub mod global_workspace;
 pub mod background_daemon;
+pub mod jobs;
+pub mod connectors;
+pub mod quotas;
+pub mod transactions;
 pub mod working_memory;
 pub mod memory_bridge;
 pub mod narrative_generator;
@@ -97,7 +121,7 @@ mod aot_tests;
 
 pub use column_store::{ColumnStore, InMemoryColumnStore};
 pub use compression::{Compressor, Decompressor};
-pub use block::{Block, BlockMetadata};
+pub use block::{Block, BlockMetadata, BlockPredicate, ZoneValue};
 pub use writer::ColumnWriter;
 pub use reader::ColumnReader;
 
@@ -106,6 +130,9 @@ pub use gpu_execution::{
     Backend, GpuEngine, GpuTensor, GpuColumn, GpuMask, GpuBackend,
     GpuEmbeddingStore, CpuBackend, MetalBackend, CudaBackend, VulkanBackend,
 };
+pub use gpu_devices::{
+    DeviceMemoryPool, GpuDeviceInfo, GpuMetrics, MultiGpuConfig, MultiGpuEngine, PlacementPolicy, PoolBuffer,
+};
 pub use dynamic_output::DynamicOutputManager;
 
 #[cfg(test)]
@@ -5,6 +5,8 @@
 use crate::cognitive::{CognitiveBrain, Memory, MemoryType, Pattern, PatternType};
 use crate::working_memory::WorkingMemoryScratchpad;
 use crate::conscience_persistent_loop::CPLEvent;
+use crate::embeddings::EmbeddingProvider;
+use crate::infinite_context::{ContextMetadata, ContextType, InfiniteContextManager};
 use narayana_core::{Error, Result};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -12,6 +14,8 @@ use std::time::{SystemTime, UNIX_EPOCH};
 use parking_lot::RwLock;
 use tokio::sync::broadcast;
 use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 use tracing::{debug, info, warn};
 
 /// Memory Bridge - Episodic to Semantic conversion
@@ -492,3 +496,457 @@ impl MemoryBridge {
     }
 }
 
+
+/// Tag carried by RAG contexts exported from brain memories
+pub const BRAIN_MEMORY_TAG: &str = "brain_memory";
+
+/// Tag added to brain memories promoted from RAG documents
+pub const RAG_PROMOTED_TAG: &str = "rag_promoted";
+
+/// Provenance source recorded on promoted memories
+const RAG_SOURCE: &str = "rag";
+
+/// Configuration for brain ↔ RAG synchronization
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RagSyncConfig {
+    /// Memory types exported into the RAG store
+    pub memory_types: Vec<MemoryType>,
+    /// RAG documents carrying this tag are promoted into brain memories on sync
+    pub promote_tag: Option<String>,
+    /// Memory type given to promoted documents
+    pub promoted_memory_type: MemoryType,
+    /// Texts per embedding provider call
+    pub embed_batch_size: usize,
+}
+
+impl Default for RagSyncConfig {
+    fn default() -> Self {
+        Self {
+            memory_types: vec![MemoryType::Episodic, MemoryType::Semantic],
+            promote_tag: Some("promote_to_brain".to_string()),
+            promoted_memory_type: MemoryType::Semantic,
+            embed_batch_size: 64,
+        }
+    }
+}
+
+/// Outcome of one sync pass
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RagSyncReport {
+    /// Memories exported for the first time
+    pub exported: usize,
+    /// Memories re-exported after their content, tags or embedding changed
+    pub updated: usize,
+    /// Exported contexts removed because their memory is gone
+    pub removed: usize,
+    /// Memories that were given an embedding
+    pub embedded: usize,
+    /// RAG documents promoted into brain memories
+    pub promoted: usize,
+    /// Memories or documents that could not be embedded, exported or promoted
+    pub failed: usize,
+}
+
+/// RAG context mirroring a brain memory
+#[derive(Debug, Clone)]
+struct ExportedMemory {
+    context_id: String,
+    fingerprint: u64,
+}
+
+#[derive(Debug, Default)]
+struct RagLinks {
+    exported: HashMap<String, ExportedMemory>, // memory_id -> exported context
+    promoted: HashMap<String, String>,         // context_id -> memory_id
+}
+
+/// Keeps brain memories and the RAG context store in step
+///
+/// Episodic/semantic memories are embedded (when an embedder is set) and mirrored
+/// into the context store so RAG retrieval finds them; documents ingested into the
+/// store can be promoted into brain memories, which keep their provenance.
+pub struct RagMemorySync {
+    brain: Arc<CognitiveBrain>,
+    store: Arc<InfiniteContextManager>,
+    embedder: Option<Arc<dyn EmbeddingProvider>>,
+    config: RagSyncConfig,
+    links: RwLock<RagLinks>,
+    // One sync pass at a time, so a memory is never exported twice
+    sync_lock: tokio::sync::Mutex<()>,
+}
+
+impl RagMemorySync {
+    pub fn new(brain: Arc<CognitiveBrain>, store: Arc<InfiniteContextManager>, config: RagSyncConfig) -> Self {
+        Self {
+            brain,
+            store,
+            embedder: None,
+            config,
+            links: RwLock::new(RagLinks::default()),
+            sync_lock: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Embed memories that have no vector before exporting them
+    pub fn with_embedder(mut self, embedder: Arc<dyn EmbeddingProvider>) -> Self {
+        self.embedder = Some(embedder);
+        self
+    }
+
+    /// RAG context holding `memory_id`: its export, or the document it was promoted from
+    pub fn context_for_memory(&self, memory_id: &str) -> Option<String> {
+        let links = self.links.read();
+        links.exported.get(memory_id).map(|export| export.context_id.clone()).or_else(|| {
+            links.promoted.iter()
+                .find(|(_, promoted)| promoted.as_str() == memory_id)
+                .map(|(context_id, _)| context_id.clone())
+        })
+    }
+
+    /// Brain memory behind `context_id`: the memory it exports, or the one promoted from it
+    pub fn memory_for_context(&self, context_id: &str) -> Option<String> {
+        let links = self.links.read();
+        links.promoted.get(context_id).cloned().or_else(|| {
+            links.exported.iter()
+                .find(|(_, export)| export.context_id == context_id)
+                .map(|(memory_id, _)| memory_id.clone())
+        })
+    }
+
+    /// Run one sync pass: embed, export changed memories, drop stale exports, promote tagged documents
+    pub async fn sync(&self) -> Result<RagSyncReport> {
+        let _guard = self.sync_lock.lock().await;
+        let mut report = RagSyncReport::default();
+        self.embed_missing(&mut report).await;
+        self.export_memories(&mut report);
+        self.promote_tagged(&mut report);
+        Ok(report)
+    }
+
+    /// Sync periodically in the background
+    pub fn start_background_sync(self: Arc<Self>, interval: std::time::Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match self.sync().await {
+                    Ok(report) if report != RagSyncReport::default() => debug!("Brain/RAG sync: {:?}", report),
+                    Ok(_) => {}
+                    Err(e) => warn!("Brain/RAG sync error: {}", e),
+                }
+            }
+        })
+    }
+
+    /// Promote a RAG document into a brain memory, recording where it came from
+    ///
+    /// Promoting the same document again returns the existing memory.
+    pub fn promote_document(&self, context_id: &str, memory_type: MemoryType) -> Result<String> {
+        let mut links = self.links.write();
+        if let Some(memory_id) = links.promoted.get(context_id) {
+            if self.brain.memories.read().contains_key(memory_id) {
+                return Ok(memory_id.clone());
+            }
+        }
+        let entry = self.store.get_entry(context_id)?
+            .ok_or_else(|| Error::Storage(format!("Context {} not found", context_id)))?;
+        if entry.metadata.tags.iter().any(|tag| tag == BRAIN_MEMORY_TAG) {
+            return Err(Error::Storage(format!("Context {} is an export of a brain memory", context_id)));
+        }
+
+        let text = String::from_utf8_lossy(&entry.content).into_owned();
+        let mut tags = entry.metadata.tags.clone();
+        tags.push(RAG_PROMOTED_TAG.to_string());
+        let memory_id = self.brain.store_memory(memory_type, serde_json::Value::String(text), entry.embedding.clone(), tags, None)?;
+
+        let provenance = serde_json::json!({
+            "source": RAG_SOURCE,
+            "context_id": entry.id,
+            "context_type": entry.metadata.context_type,
+            "agent_id": entry.metadata.agent_id,
+            "conversation_id": entry.metadata.conversation_id,
+            "message_id": entry.metadata.message_id,
+            "ingested_at": entry.created_at,
+            "promoted_at": now_secs(),
+            "version": entry.version,
+        });
+        if let Some(memory) = self.brain.memories.write().get_mut(&memory_id) {
+            memory.context.insert("provenance".to_string(), provenance);
+        }
+        links.promoted.insert(context_id.to_string(), memory_id.clone());
+        info!("Promoted RAG context {} into memory {}", context_id, memory_id);
+        Ok(memory_id)
+    }
+
+    async fn embed_missing(&self, report: &mut RagSyncReport) {
+        let Some(embedder) = &self.embedder else {
+            return;
+        };
+        let pending: Vec<(String, String)> = self.brain.memories.read().values()
+            .filter(|memory| memory.embedding.is_none() && self.config.memory_types.contains(&memory.memory_type))
+            .map(|memory| (memory.id.clone(), memory_text(memory)))
+            .collect();
+
+        for chunk in pending.chunks(self.config.embed_batch_size.max(1)) {
+            let texts: Vec<String> = chunk.iter().map(|(_, text)| text.clone()).collect();
+            let vectors = match embedder.embed(&texts, None).await {
+                Ok(vectors) if vectors.len() == texts.len() => vectors,
+                Ok(vectors) => {
+                    warn!("Embedding provider returned {} vectors for {} memories", vectors.len(), texts.len());
+                    report.failed += chunk.len();
+                    continue;
+                }
+                Err(e) => {
+                    warn!("Failed to embed {} memories: {}", texts.len(), e);
+                    report.failed += chunk.len();
+                    continue;
+                }
+            };
+            let mut memories = self.brain.memories.write();
+            for ((memory_id, _), vector) in chunk.iter().zip(vectors) {
+                if let Some(memory) = memories.get_mut(memory_id) {
+                    if memory.embedding.is_none() {
+                        memory.embedding = Some(vector);
+                        report.embedded += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    fn export_memories(&self, report: &mut RagSyncReport) {
+        let mut links = self.links.write();
+        let mut live = std::collections::HashSet::new();
+        let mut changed = Vec::new();
+        {
+            let memories = self.brain.memories.read();
+            for memory in memories.values() {
+                if !self.config.memory_types.contains(&memory.memory_type) {
+                    continue;
+                }
+                // A promoted memory is already retrievable through its source document
+                if promoted_from(memory).is_some_and(|context_id| links.promoted.get(context_id) == Some(&memory.id)) {
+                    continue;
+                }
+                live.insert(memory.id.clone());
+                let fingerprint = fingerprint(memory);
+                if links.exported.get(&memory.id).map(|export| export.fingerprint) != Some(fingerprint) {
+                    changed.push((memory.clone(), fingerprint));
+                }
+            }
+        }
+
+        for (memory, fingerprint) in changed {
+            let previous = links.exported.get(&memory.id).map(|export| export.context_id.clone());
+            match self.export(&memory) {
+                Ok(context_id) => {
+                    if let Some(previous) = previous {
+                        self.store.remove_context(&previous);
+                        report.updated += 1;
+                    } else {
+                        report.exported += 1;
+                    }
+                    links.exported.insert(memory.id.clone(), ExportedMemory { context_id, fingerprint });
+                }
+                Err(e) => {
+                    warn!("Failed to export memory {} to the RAG store: {}", memory.id, e);
+                    report.failed += 1;
+                }
+            }
+        }
+
+        let stale: Vec<String> = links.exported.keys().filter(|id| !live.contains(*id)).cloned().collect();
+        for memory_id in stale {
+            if let Some(export) = links.exported.remove(&memory_id) {
+                self.store.remove_context(&export.context_id);
+                report.removed += 1;
+            }
+        }
+    }
+
+    fn export(&self, memory: &Memory) -> Result<String> {
+        let mut tags = memory.tags.clone();
+        tags.push(BRAIN_MEMORY_TAG.to_string());
+        let metadata = ContextMetadata {
+            agent_id: None,
+            conversation_id: None,
+            message_id: Some(memory.id.clone()),
+            context_type: ContextType::Memory,
+            tags,
+            priority: 0.5,
+            importance: memory.strength.clamp(0.0, 1.0),
+            related_ids: memory.associations.clone(),
+        };
+        self.store.add_context(bytes::Bytes::from(memory_text(memory)), metadata, memory.embedding.clone(), None)
+    }
+
+    fn promote_tagged(&self, report: &mut RagSyncReport) {
+        let Some(tag) = &self.config.promote_tag else {
+            return;
+        };
+        for context_id in self.store.ids_with_tag(tag) {
+            if self.links.read().promoted.contains_key(&context_id) {
+                continue;
+            }
+            match self.promote_document(&context_id, self.config.promoted_memory_type.clone()) {
+                Ok(_) => report.promoted += 1,
+                Err(e) => {
+                    warn!("Failed to promote RAG context {}: {}", context_id, e);
+                    report.failed += 1;
+                }
+            }
+        }
+    }
+}
+
+/// Source context of a memory promoted from the RAG store
+fn promoted_from(memory: &Memory) -> Option<&str> {
+    let provenance = memory.context.get("provenance")?;
+    if provenance.get("source")?.as_str()? != RAG_SOURCE {
+        return None;
+    }
+    provenance.get("context_id")?.as_str()
+}
+
+fn memory_text(memory: &Memory) -> String {
+    match &memory.content {
+        serde_json::Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Changes to any of these make the exported context stale
+fn fingerprint(memory: &Memory) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    memory.content.to_string().hash(&mut hasher);
+    memory.tags.hash(&mut hasher);
+    memory.associations.hash(&mut hasher);
+    if let Some(embedding) = &memory.embedding {
+        embedding.iter().for_each(|value| value.to_bits().hash(&mut hasher));
+    }
+    hasher.finish()
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+
+    struct LengthProvider;
+
+    #[async_trait]
+    impl EmbeddingProvider for LengthProvider {
+        async fn embed(&self, texts: &[String], _model: Option<&str>) -> Result<Vec<Vec<f32>>> {
+            Ok(texts.iter().map(|t| vec![t.len() as f32, 1.0]).collect())
+        }
+    }
+
+    fn setup() -> (Arc<CognitiveBrain>, Arc<InfiniteContextManager>) {
+        (Arc::new(CognitiveBrain::new()), Arc::new(InfiniteContextManager::new(Default::default())))
+    }
+
+    fn document(store: &InfiniteContextManager, text: &str, tags: &[&str]) -> String {
+        let metadata = ContextMetadata {
+            agent_id: Some("ingest".to_string()),
+            conversation_id: None,
+            message_id: None,
+            context_type: ContextType::Knowledge,
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            priority: 0.5,
+            importance: 0.5,
+            related_ids: Vec::new(),
+        };
+        store.add_context(bytes::Bytes::from(text.to_string()), metadata, Some(vec![3.0, 4.0]), None).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_memories_follow_brain_changes() {
+        let (brain, store) = setup();
+        let sync = RagMemorySync::new(brain.clone(), store.clone(), RagSyncConfig::default());
+        let memory_id = brain.store_memory(MemoryType::Episodic, serde_json::json!("met Ada at the lab"), None, vec!["people".to_string()], None).unwrap();
+        brain.store_memory(MemoryType::Procedural, serde_json::json!("how to ride a bike"), None, vec![], None).unwrap();
+
+        let report = sync.sync().await.unwrap();
+        assert_eq!(report.exported, 1);
+        let context_id = sync.context_for_memory(&memory_id).unwrap();
+        assert_eq!(sync.memory_for_context(&context_id), Some(memory_id.clone()));
+        let entry = store.get_entry(&context_id).unwrap().unwrap();
+        assert_eq!(&entry.content[..], b"met Ada at the lab");
+        assert!(entry.metadata.tags.contains(&BRAIN_MEMORY_TAG.to_string()));
+
+        // Unchanged memories are not exported again
+        assert_eq!(sync.sync().await.unwrap(), RagSyncReport::default());
+
+        brain.memories.write().get_mut(&memory_id).unwrap().content = serde_json::json!("met Ada and Grace at the lab");
+        assert_eq!(sync.sync().await.unwrap().updated, 1);
+        let updated_id = sync.context_for_memory(&memory_id).unwrap();
+        assert!(store.get_entry(&context_id).unwrap().is_none());
+        assert_eq!(&store.get_entry(&updated_id).unwrap().unwrap().content[..], b"met Ada and Grace at the lab");
+
+        brain.memories.write().remove(&memory_id);
+        assert_eq!(sync.sync().await.unwrap().removed, 1);
+        assert!(store.get_entry(&updated_id).unwrap().is_none());
+        assert!(store.ids_with_tag(BRAIN_MEMORY_TAG).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_exported_memories_are_embedded_and_searchable() {
+        let (brain, store) = setup();
+        let sync = RagMemorySync::new(brain.clone(), store.clone(), RagSyncConfig::default())
+            .with_embedder(Arc::new(LengthProvider));
+        let memory_id = brain.store_memory(MemoryType::Semantic, serde_json::json!("water boils at 100C"), None, vec![], None).unwrap();
+
+        let report = sync.sync().await.unwrap();
+        assert_eq!((report.embedded, report.exported), (1, 1));
+        let embedding = brain.memories.read()[&memory_id].embedding.clone().unwrap();
+        assert_eq!(embedding, vec![19.0, 1.0]);
+
+        let hits = store.search("water", Some(&embedding), 5).unwrap();
+        assert_eq!(hits[0].metadata.message_id.as_deref(), Some(memory_id.as_str()));
+    }
+
+    #[tokio::test]
+    async fn test_tagged_documents_are_promoted_with_provenance() {
+        let (brain, store) = setup();
+        let sync = RagMemorySync::new(brain.clone(), store.clone(), RagSyncConfig::default());
+        let context_id = document(&store, "the launch moved to friday", &["promote_to_brain"]);
+        document(&store, "unrelated note", &[]);
+
+        let report = sync.sync().await.unwrap();
+        assert_eq!((report.promoted, report.exported), (1, 0));
+        let memory_id = sync.memory_for_context(&context_id).unwrap();
+        let memory = brain.memories.read()[&memory_id].clone();
+        assert_eq!(memory.memory_type, MemoryType::Semantic);
+        assert_eq!(memory.content, serde_json::json!("the launch moved to friday"));
+        assert_eq!(memory.embedding, Some(vec![3.0, 4.0]));
+        assert!(memory.tags.contains(&RAG_PROMOTED_TAG.to_string()));
+        let provenance = &memory.context["provenance"];
+        assert_eq!(provenance["source"], "rag");
+        assert_eq!(provenance["context_id"], context_id.as_str());
+        assert_eq!(provenance["agent_id"], "ingest");
+
+        // Promotion is idempotent and the promoted memory is not mirrored back
+        assert_eq!(sync.promote_document(&context_id, MemoryType::Semantic).unwrap(), memory_id);
+        assert_eq!(sync.sync().await.unwrap(), RagSyncReport::default());
+        assert_eq!(brain.memories.read().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_exports_are_not_promoted() {
+        let (brain, store) = setup();
+        let sync = RagMemorySync::new(brain.clone(), store.clone(), RagSyncConfig::default());
+        let memory_id = brain.store_memory(MemoryType::Episodic, serde_json::json!("saw a comet"), None, vec![], None).unwrap();
+        sync.sync().await.unwrap();
+
+        let context_id = sync.context_for_memory(&memory_id).unwrap();
+        assert!(sync.promote_document(&context_id, MemoryType::Semantic).is_err());
+        assert!(sync.promote_document("missing", MemoryType::Semantic).is_err());
+    }
+}
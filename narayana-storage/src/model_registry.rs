@@ -876,6 +876,393 @@ struct ModelCacheEntry {
     cached_at: u64,
 }
 
+/// Semantic version of a model artifact (`MAJOR.MINOR.PATCH[-pre]`)
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SemVer {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    pub pre: Option<String>,
+}
+
+impl SemVer {
+    pub fn parse(version: &str) -> Result<Self> {
+        let version = version.trim().trim_start_matches('v');
+        let (core, pre) = match version.split_once('-') {
+            Some((core, pre)) if !pre.is_empty() => (core, Some(pre.to_string())),
+            Some(_) => return Err(Error::Storage(format!("Invalid version: {}", version))),
+            None => (version, None),
+        };
+        let parts: Vec<&str> = core.split('.').collect();
+        if parts.len() != 3 {
+            return Err(Error::Storage(format!("Version must be MAJOR.MINOR.PATCH: {}", version)));
+        }
+        let number = |part: &str| part.parse::<u64>()
+            .map_err(|_| Error::Storage(format!("Invalid version component '{}' in {}", part, version)));
+        if let Some(pre) = &pre {
+            if !pre.chars().all(|c| c.is_ascii_alphanumeric() || c == '.') {
+                return Err(Error::Storage(format!("Invalid pre-release tag: {}", pre)));
+            }
+        }
+        Ok(Self { major: number(parts[0])?, minor: number(parts[1])?, patch: number(parts[2])?, pre })
+    }
+}
+
+impl std::fmt::Display for SemVer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if let Some(pre) = &self.pre {
+            write!(f, "-{}", pre)?;
+        }
+        Ok(())
+    }
+}
+
+impl PartialOrd for SemVer {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SemVer {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            // A pre-release sorts before its release
+            .then_with(|| match (&self.pre, &other.pre) {
+                (None, None) => std::cmp::Ordering::Equal,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (Some(a), Some(b)) => a.cmp(b),
+            })
+    }
+}
+
+/// Deployment stage of a model version
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ModelStage {
+    /// Registered, not yet deployed anywhere
+    None,
+    Staging,
+    Production,
+    Archived,
+}
+
+impl ModelStage {
+    /// Allowed lifecycle moves: None→Staging, Staging→Production, any→Archived,
+    /// Archived→Staging (restore) and Production→Staging (rollback for re-validation)
+    pub fn can_transition_to(&self, target: ModelStage) -> bool {
+        matches!(
+            (self, target),
+            (ModelStage::None, ModelStage::Staging)
+                | (ModelStage::Staging, ModelStage::Production)
+                | (ModelStage::Production, ModelStage::Staging)
+                | (ModelStage::Archived, ModelStage::Staging)
+                | (ModelStage::None | ModelStage::Staging | ModelStage::Production, ModelStage::Archived)
+        )
+    }
+}
+
+/// Metadata of one stored model artifact
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelVersion {
+    pub name: String,
+    pub version: SemVer,
+    pub model_type: ModelType,
+    /// Artifact format, e.g. "onnx" or "policy-json"
+    pub format: String,
+    pub stage: ModelStage,
+    /// SHA-256 of the artifact bytes (hex)
+    pub checksum: String,
+    pub size_bytes: u64,
+    pub metadata: HashMap<String, serde_json::Value>,
+    pub created_at: u64,
+    pub stage_updated_at: u64,
+}
+
+/// Maximum artifact size accepted by the registry
+const MAX_ARTIFACT_SIZE: usize = 2_000_000_000;
+
+/// Versioned model artifacts with checksums and deployment stages.
+/// With a root directory, artifacts live at `<root>/<name>/<version>/artifact.bin`
+/// next to `version.json`; without one they are kept in memory.
+pub struct ModelArtifactRegistry {
+    root: Option<std::path::PathBuf>,
+    versions: Arc<RwLock<HashMap<String, Vec<ModelVersion>>>>,
+    blobs: Arc<RwLock<HashMap<(String, SemVer), Arc<Vec<u8>>>>>,
+}
+
+impl ModelArtifactRegistry {
+    pub fn new(root: Option<std::path::PathBuf>) -> Result<Self> {
+        if let Some(root) = &root {
+            std::fs::create_dir_all(root)
+                .map_err(|e| Error::Storage(format!("Failed to create model artifact directory: {}", e)))?;
+        }
+        Ok(Self {
+            root,
+            versions: Arc::new(RwLock::new(HashMap::new())),
+            blobs: Arc::new(RwLock::new(HashMap::new())),
+        })
+    }
+
+    /// Load version metadata written by a previous run
+    pub fn load_persisted(&self) -> Result<usize> {
+        let Some(root) = &self.root else { return Ok(0) };
+        let mut loaded = 0;
+        let models = std::fs::read_dir(root)
+            .map_err(|e| Error::Storage(format!("Failed to read model artifact directory: {}", e)))?;
+        for model_dir in models.flatten() {
+            let Ok(versions) = std::fs::read_dir(model_dir.path()) else { continue };
+            for version_dir in versions.flatten() {
+                let meta_path = version_dir.path().join("version.json");
+                match std::fs::read(&meta_path).map(|bytes| serde_json::from_slice::<ModelVersion>(&bytes)) {
+                    Ok(Ok(version)) => {
+                        let mut all = self.versions.write();
+                        let entry = all.entry(version.name.clone()).or_default();
+                        entry.push(version);
+                        entry.sort_by(|a, b| a.version.cmp(&b.version));
+                        loaded += 1;
+                    }
+                    Ok(Err(e)) => warn!("Skipping corrupt model metadata {:?}: {}", meta_path, e),
+                    Err(_) => {}
+                }
+            }
+        }
+        info!("Loaded {} model versions", loaded);
+        Ok(loaded)
+    }
+
+    /// Store a new artifact version (stage `None`). Versions are immutable.
+    pub fn register_version(
+        &self,
+        name: &str,
+        version: &str,
+        model_type: ModelType,
+        format: &str,
+        bytes: Vec<u8>,
+        metadata: HashMap<String, serde_json::Value>,
+    ) -> Result<ModelVersion> {
+        validate_model_name(name)?;
+        let version = SemVer::parse(version)?;
+        if bytes.is_empty() || bytes.len() > MAX_ARTIFACT_SIZE {
+            return Err(Error::Storage(format!("Artifact size must be 1..={} bytes", MAX_ARTIFACT_SIZE)));
+        }
+        if self.get_version(name, &version.to_string()).is_some() {
+            return Err(Error::Storage(format!("Model {} version {} already exists", name, version)));
+        }
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let model_version = ModelVersion {
+            name: name.to_string(),
+            version: version.clone(),
+            model_type,
+            format: format.to_string(),
+            stage: ModelStage::None,
+            checksum: sha256_hex(&bytes),
+            size_bytes: bytes.len() as u64,
+            metadata,
+            created_at: now,
+            stage_updated_at: now,
+        };
+
+        if let Some(dir) = self.version_dir(name, &version) {
+            std::fs::create_dir_all(&dir)
+                .map_err(|e| Error::Storage(format!("Failed to create model version directory: {}", e)))?;
+            write_atomic(&dir.join("artifact.bin"), &bytes)?;
+            self.persist_metadata(&model_version)?;
+        } else {
+            self.blobs.write().insert((name.to_string(), version.clone()), Arc::new(bytes));
+        }
+
+        let mut all = self.versions.write();
+        let entry = all.entry(name.to_string()).or_default();
+        entry.push(model_version.clone());
+        entry.sort_by(|a, b| a.version.cmp(&b.version));
+        info!("Registered model {} version {} ({} bytes)", name, version, model_version.size_bytes);
+        Ok(model_version)
+    }
+
+    /// Move a version to another stage. Promoting to Production archives the
+    /// previous Production version so each model has at most one.
+    pub fn transition_stage(&self, name: &str, version: &str, target: ModelStage) -> Result<ModelVersion> {
+        let version = SemVer::parse(version)?;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let mut changed = Vec::new();
+        {
+            let mut all = self.versions.write();
+            let versions = all.get_mut(name)
+                .ok_or_else(|| Error::Storage(format!("Model {} not found", name)))?;
+            let idx = versions.iter().position(|v| v.version == version)
+                .ok_or_else(|| Error::Storage(format!("Model {} version {} not found", name, version)))?;
+            let current = versions[idx].stage;
+            if !current.can_transition_to(target) {
+                return Err(Error::Storage(format!(
+                    "Cannot move model {} version {} from {:?} to {:?}", name, version, current, target
+                )));
+            }
+            if target == ModelStage::Production {
+                for other in versions.iter_mut().filter(|v| v.stage == ModelStage::Production) {
+                    other.stage = ModelStage::Archived;
+                    other.stage_updated_at = now;
+                    changed.push(other.clone());
+                }
+            }
+            versions[idx].stage = target;
+            versions[idx].stage_updated_at = now;
+            changed.push(versions[idx].clone());
+        }
+        for version in &changed {
+            self.persist_metadata(version)?;
+        }
+        info!("Model {} version {} moved to {:?}", name, version, target);
+        Ok(changed.pop().expect("target version is always recorded"))
+    }
+
+    pub fn get_version(&self, name: &str, version: &str) -> Option<ModelVersion> {
+        let version = SemVer::parse(version).ok()?;
+        self.versions.read().get(name)?.iter().find(|v| v.version == version).cloned()
+    }
+
+    /// All versions of a model, oldest first
+    pub fn list_versions(&self, name: &str) -> Vec<ModelVersion> {
+        self.versions.read().get(name).cloned().unwrap_or_default()
+    }
+
+    pub fn list_models(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.versions.read().keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Highest version currently in `stage`
+    pub fn latest_in_stage(&self, name: &str, stage: ModelStage) -> Option<ModelVersion> {
+        self.versions.read().get(name)?.iter().rev().find(|v| v.stage == stage).cloned()
+    }
+
+    /// Version a consumer should load: Production, falling back to Staging
+    pub fn resolve_serving(&self, name: &str) -> Option<ModelVersion> {
+        self.latest_in_stage(name, ModelStage::Production)
+            .or_else(|| self.latest_in_stage(name, ModelStage::Staging))
+    }
+
+    /// Read an artifact and verify its checksum
+    pub fn load_artifact(&self, name: &str, version: &str) -> Result<Vec<u8>> {
+        let model_version = self.get_version(name, version)
+            .ok_or_else(|| Error::Storage(format!("Model {} version {} not found", name, version)))?;
+        let bytes = match self.version_dir(name, &model_version.version) {
+            Some(dir) => std::fs::read(dir.join("artifact.bin"))
+                .map_err(|e| Error::Storage(format!("Failed to read model artifact: {}", e)))?,
+            None => self.blobs.read()
+                .get(&(name.to_string(), model_version.version.clone()))
+                .map(|b| b.as_ref().clone())
+                .ok_or_else(|| Error::Storage(format!("Artifact missing for model {} version {}", name, version)))?,
+        };
+        let checksum = sha256_hex(&bytes);
+        if checksum != model_version.checksum {
+            error!("Checksum mismatch for model {} version {}", name, version);
+            return Err(Error::Storage(format!(
+                "Checksum mismatch for model {} version {}: expected {}, got {}",
+                name, version, model_version.checksum, checksum
+            )));
+        }
+        Ok(bytes)
+    }
+
+    /// Load the newest artifact in `stage`
+    pub fn load_by_stage(&self, name: &str, stage: ModelStage) -> Result<(ModelVersion, Vec<u8>)> {
+        let version = self.latest_in_stage(name, stage)
+            .ok_or_else(|| Error::Storage(format!("Model {} has no {:?} version", name, stage)))?;
+        let bytes = self.load_artifact(name, &version.version.to_string())?;
+        Ok((version, bytes))
+    }
+
+    /// Delete a version that is not serving traffic
+    pub fn delete_version(&self, name: &str, version: &str) -> Result<()> {
+        let version = SemVer::parse(version)?;
+        {
+            let mut all = self.versions.write();
+            let versions = all.get_mut(name)
+                .ok_or_else(|| Error::Storage(format!("Model {} not found", name)))?;
+            let idx = versions.iter().position(|v| v.version == version)
+                .ok_or_else(|| Error::Storage(format!("Model {} version {} not found", name, version)))?;
+            if versions[idx].stage == ModelStage::Production {
+                return Err(Error::Storage("Cannot delete a Production model version; archive it first".to_string()));
+            }
+            versions.remove(idx);
+            if versions.is_empty() {
+                all.remove(name);
+            }
+        }
+        self.blobs.write().remove(&(name.to_string(), version.clone()));
+        if let Some(dir) = self.version_dir(name, &version) {
+            let _ = std::fs::remove_dir_all(dir);
+        }
+        Ok(())
+    }
+
+    fn version_dir(&self, name: &str, version: &SemVer) -> Option<std::path::PathBuf> {
+        self.root.as_ref().map(|root| root.join(name).join(version.to_string()))
+    }
+
+    fn persist_metadata(&self, version: &ModelVersion) -> Result<()> {
+        let Some(dir) = self.version_dir(&version.name, &version.version) else { return Ok(()) };
+        let bytes = serde_json::to_vec_pretty(version)
+            .map_err(|e| Error::Serialization(format!("Failed to serialize model metadata: {}", e)))?;
+        write_atomic(&dir.join("version.json"), &bytes)
+    }
+}
+
+impl ModelRegistry {
+    /// Put the serving (Production, else Staging) version of `name` into a slot
+    pub fn register_from_artifacts(
+        &self,
+        slot: ModelSlotType,
+        artifacts: &ModelArtifactRegistry,
+        name: &str,
+        architecture: ModelArchitecture,
+    ) -> Result<String> {
+        let version = artifacts.resolve_serving(name)
+            .ok_or_else(|| Error::Storage(format!("Model {} has no Production or Staging version", name)))?;
+        let weights = artifacts.load_artifact(name, &version.version.to_string())?;
+        self.register_model(slot, Model {
+            model_id: format!("{}@{}", name, version.version),
+            model_type: version.model_type,
+            weights,
+            architecture,
+            hyperparameters: version.metadata.clone(),
+            version: version.version.to_string(),
+        })
+    }
+}
+
+/// Model names become directory names, so keep them to a safe alphabet
+fn validate_model_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.len() > 128 {
+        return Err(Error::Storage("Model name must be 1-128 characters".to_string()));
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.') || name.starts_with('.') {
+        return Err(Error::Storage("Model name may only contain letters, digits, '-', '_' and '.'".to_string()));
+    }
+    Ok(())
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    hex::encode(Sha256::digest(bytes))
+}
+
+/// Write to a temp file and rename so readers never see a partial file
+fn write_atomic(path: &std::path::Path, bytes: &[u8]) -> Result<()> {
+    let temp_path = path.with_extension("tmp");
+    std::fs::write(&temp_path, bytes)
+        .map_err(|e| Error::Storage(format!("Failed to write {:?}: {}", path, e)))?;
+    std::fs::rename(&temp_path, path)
+        .map_err(|e| {
+            let _ = std::fs::remove_file(&temp_path);
+            Error::Storage(format!("Failed to write {:?}: {}", path, e))
+        })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -909,5 +1296,43 @@ mod tests {
         let output = registry.request_inference(ModelSlotType::Perception, input).await.unwrap();
         assert_eq!(output.output_type, OutputType::Perception);
     }
-}
 
+    #[test]
+    fn test_semver_ordering() {
+        let v = |s: &str| SemVer::parse(s).unwrap();
+        assert!(v("1.2.0") > v("1.1.9"));
+        assert!(v("2.0.0-rc.1") < v("2.0.0"));
+        assert_eq!(v("v1.0.3").to_string(), "1.0.3");
+        assert!(SemVer::parse("1.0").is_err());
+    }
+
+    #[test]
+    fn test_artifact_stage_lifecycle() {
+        let dir = std::env::temp_dir().join(format!("narayana_models_{}", Uuid::new_v4()));
+        let registry = ModelArtifactRegistry::new(Some(dir.clone())).unwrap();
+        registry.register_version("yolov8n", "1.0.0", ModelType::Perception, "onnx", vec![1; 64], HashMap::new()).unwrap();
+        registry.register_version("yolov8n", "1.1.0", ModelType::Perception, "onnx", vec![2; 64], HashMap::new()).unwrap();
+        assert!(registry.register_version("yolov8n", "1.1.0", ModelType::Perception, "onnx", vec![3], HashMap::new()).is_err());
+
+        // None -> Production is not allowed
+        assert!(registry.transition_stage("yolov8n", "1.0.0", ModelStage::Production).is_err());
+        registry.transition_stage("yolov8n", "1.0.0", ModelStage::Staging).unwrap();
+        registry.transition_stage("yolov8n", "1.0.0", ModelStage::Production).unwrap();
+        registry.transition_stage("yolov8n", "1.1.0", ModelStage::Staging).unwrap();
+        registry.transition_stage("yolov8n", "1.1.0", ModelStage::Production).unwrap();
+
+        assert_eq!(registry.get_version("yolov8n", "1.0.0").unwrap().stage, ModelStage::Archived);
+        let (version, bytes) = registry.load_by_stage("yolov8n", ModelStage::Production).unwrap();
+        assert_eq!(version.version.to_string(), "1.1.0");
+        assert_eq!(bytes, vec![2; 64]);
+        assert!(registry.delete_version("yolov8n", "1.1.0").is_err());
+
+        // Metadata survives a restart; tampered artifacts are rejected
+        let reloaded = ModelArtifactRegistry::new(Some(dir.clone())).unwrap();
+        assert_eq!(reloaded.load_persisted().unwrap(), 2);
+        assert_eq!(reloaded.resolve_serving("yolov8n").unwrap().version.to_string(), "1.1.0");
+        std::fs::write(dir.join("yolov8n").join("1.1.0").join("artifact.bin"), vec![9; 64]).unwrap();
+        assert!(reloaded.load_artifact("yolov8n", "1.1.0").is_err());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -12,7 +12,10 @@ use std::time::{SystemTime, UNIX_EPOCH};
 use parking_lot::RwLock;
 use tokio::sync::broadcast;
 use std::collections::VecDeque;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
+
+/// Memory tag of persisted narrative summaries
+pub const NARRATIVE_SUMMARY_TAG: &str = "narrative_summary";
 
 /// Narrative Generator - Constructs sense of self
 pub struct NarrativeGenerator {
@@ -27,6 +30,48 @@ pub struct NarrativeGenerator {
     
     // Identity markers
     identity_markers: Arc<RwLock<Vec<IdentityMarker>>>,
+
+    // Scheduled summaries
+    schedule: NarrativeScheduleConfig,
+    last_summary_at: Arc<RwLock<Option<u64>>>,
+}
+
+/// Cadence and length of scheduled narrative summaries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NarrativeScheduleConfig {
+    pub enabled: bool,
+    /// Seconds between summaries (daily by default)
+    pub interval_secs: u64,
+    /// Approximate summary length in words
+    pub max_words: usize,
+    /// Most salient events included in one summary
+    pub max_events: usize,
+}
+
+impl Default for NarrativeScheduleConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_secs: 86400,
+            max_words: 150,
+            max_events: 40,
+        }
+    }
+}
+
+/// First-person summary of a period, persisted as an episodic memory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NarrativeSummary {
+    /// ID of the episodic memory holding the summary
+    #[serde(default)]
+    pub id: String,
+    pub text: String,
+    pub period_start: u64,
+    pub period_end: u64,
+    pub event_ids: Vec<String>,
+    /// "llm" or "template"
+    pub generated_by: String,
+    pub created_at: u64,
 }
 
 /// Narrative - Continuous story of self
@@ -95,8 +140,16 @@ impl NarrativeGenerator {
             narrative: Arc::new(RwLock::new(narrative)),
             narrative_history: Arc::new(RwLock::new(VecDeque::with_capacity(100))),
             identity_markers: Arc::new(RwLock::new(Vec::new())),
+            schedule: NarrativeScheduleConfig::default(),
+            last_summary_at: Arc::new(RwLock::new(None)),
         }
     }
+
+    /// Set the summary schedule
+    pub fn with_schedule(mut self, schedule: NarrativeScheduleConfig) -> Self {
+        self.schedule = schedule;
+        self
+    }
     
     /// Update narrative (main cycle)
     pub async fn update_narrative(&self) -> Result<()> {
@@ -170,7 +223,7 @@ impl NarrativeGenerator {
         let recent_threshold = now.saturating_sub(86400);
         
         for memory in memories.values() {
-            if memory.memory_type == MemoryType::Episodic {
+            if memory.memory_type == MemoryType::Episodic && !is_summary(memory) {
                 if memory.created_at >= recent_threshold {
                     // Score by strength and recency
                     let recency = 1.0 / (1.0 + (now.saturating_sub(memory.created_at)) as f64 / 3600.0);
@@ -476,5 +529,304 @@ impl NarrativeGenerator {
     pub fn get_narrative_history(&self) -> Vec<NarrativeSnapshot> {
         self.narrative_history.read().iter().cloned().collect()
     }
+
+    /// Summarize the period since the last summary if one is due
+    ///
+    /// The schedule resumes from the newest persisted summary, so restarts
+    /// neither skip nor repeat a period.
+    pub async fn maybe_summarize(&self) -> Result<Option<NarrativeSummary>> {
+        if !self.schedule.enabled || self.schedule.interval_secs == 0 {
+            return Ok(None);
+        }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        let last = *self.last_summary_at.read();
+        let last = match last {
+            Some(last) => last,
+            None => {
+                let last = narrative_summaries(&self.brain, None, None, 1)
+                    .first()
+                    .map(|summary| summary.period_end)
+                    .unwrap_or(now);
+                *self.last_summary_at.write() = Some(last);
+                last
+            }
+        };
+        if now.saturating_sub(last) < self.schedule.interval_secs {
+            return Ok(None);
+        }
+
+        let summary = self.summarize_period(last, now).await?;
+        Ok(Some(summary))
+    }
+
+    /// Summarize experiences and episodic memories of a period in the first person
+    /// and persist the summary as an episodic memory
+    pub async fn summarize_period(&self, start: u64, end: u64) -> Result<NarrativeSummary> {
+        if end < start {
+            return Err(Error::Storage(format!("Invalid narrative period {}..{}", start, end)));
+        }
+        let events = self.period_events(start, end);
+
+        let (text, generated_by) = match self.summarize_with_llm(&events, start, end).await {
+            Some(text) => (text, "llm"),
+            None => (self.template_summary(&events, start, end), "template"),
+        };
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let mut summary = NarrativeSummary {
+            id: String::new(),
+            text,
+            period_start: start,
+            period_end: end,
+            event_ids: events.iter().map(|event| event.id.clone()).collect(),
+            generated_by: generated_by.to_string(),
+            created_at: now,
+        };
+
+        let content = serde_json::to_value(&summary)
+            .map_err(|e| Error::Serialization(format!("Failed to serialize narrative summary: {}", e)))?;
+        summary.id = self.brain.store_memory(
+            MemoryType::Episodic,
+            content,
+            None,
+            vec![NARRATIVE_SUMMARY_TAG.to_string()],
+            None,
+        )?;
+
+        {
+            let mut last = self.last_summary_at.write();
+            *last = Some(last.map_or(end, |last| last.max(end)));
+        }
+        let _ = self.event_sender.send(CPLEvent::NarrativeSummarized {
+            memory_id: summary.id.clone(),
+            period_end: end,
+        });
+        info!("Narrative summary {} written for {}..{} ({} events, {})",
+              summary.id, start, end, summary.event_ids.len(), generated_by);
+
+        Ok(summary)
+    }
+
+    /// Most salient events of a period, in chronological order
+    fn period_events(&self, start: u64, end: u64) -> Vec<PeriodEvent> {
+        let mut events = Vec::new();
+        {
+            let experiences = self.brain.experiences.read();
+            for experience in experiences.values() {
+                if experience.timestamp < start || experience.timestamp > end {
+                    continue;
+                }
+                let mut text = format!("{}: {}", experience.event_type, self.content_to_narrative_fragment(&experience.observation));
+                if let Some(outcome) = &experience.outcome {
+                    text.push_str(&format!(" (outcome: {})", self.content_to_narrative_fragment(outcome)));
+                }
+                events.push(PeriodEvent {
+                    id: experience.id.clone(),
+                    timestamp: experience.timestamp,
+                    text,
+                    salience: 0.5 + experience.reward.unwrap_or(0.0).abs().min(1.0),
+                    reward: experience.reward,
+                });
+            }
+        }
+        {
+            let memories = self.brain.memories.read();
+            for memory in memories.values() {
+                if memory.memory_type != MemoryType::Episodic || is_summary(memory)
+                    || memory.created_at < start || memory.created_at > end {
+                    continue;
+                }
+                events.push(PeriodEvent {
+                    id: memory.id.clone(),
+                    timestamp: memory.created_at,
+                    text: self.content_to_narrative_fragment(&memory.content),
+                    salience: memory.strength,
+                    reward: None,
+                });
+            }
+        }
+
+        events.sort_by(|a, b| b.salience.partial_cmp(&a.salience).unwrap_or(std::cmp::Ordering::Equal));
+        events.truncate(self.schedule.max_events);
+        events.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then_with(|| a.id.cmp(&b.id)));
+        events
+    }
+
+    /// Summary written by the brain's LLM manager, when one is configured
+    #[cfg(feature = "llm")]
+    async fn summarize_with_llm(&self, events: &[PeriodEvent], start: u64, end: u64) -> Option<String> {
+        use narayana_llm::{Message, MessageRole};
+
+        if events.is_empty() {
+            return None;
+        }
+        let llm = self.brain.get_llm_manager()?;
+        let prompt = format!(
+            "You are a robot writing your own diary. In the first person and in at most {} words, \
+            summarize what you experienced between {} and {} (unix seconds), focusing on what mattered \
+            and what you learned:\n\n{}",
+            self.schedule.max_words,
+            start,
+            end,
+            events.iter().map(|event| format!("- {}", event.text)).collect::<Vec<_>>().join("\n")
+        );
+        match llm.chat(vec![Message { role: MessageRole::User, content: prompt }], None).await {
+            Ok(text) if !text.trim().is_empty() => Some(truncate_words(text.trim(), self.schedule.max_words)),
+            Ok(_) => None,
+            Err(e) => {
+                warn!("LLM narrative summary failed, using template: {}", e);
+                None
+            }
+        }
+    }
+
+    #[cfg(not(feature = "llm"))]
+    async fn summarize_with_llm(&self, _events: &[PeriodEvent], _start: u64, _end: u64) -> Option<String> {
+        None
+    }
+
+    /// First-person summary assembled from the events themselves
+    fn template_summary(&self, events: &[PeriodEvent], start: u64, end: u64) -> String {
+        let hours = (end.saturating_sub(start) as f64 / 3600.0).round() as u64;
+        if events.is_empty() {
+            return format!("Nothing notable happened to me in the last {} hours.", hours);
+        }
+
+        let mut text = format!("In the last {} hours I went through {} notable events.", hours, events.len());
+        for event in events {
+            text.push_str(&format!(" I remember {}.", event.text.trim_end_matches('.')));
+        }
+        let rewards: Vec<f64> = events.iter().filter_map(|event| event.reward).filter(|r| r.is_finite()).collect();
+        if !rewards.is_empty() {
+            let average = rewards.iter().sum::<f64>() / rewards.len() as f64;
+            text.push_str(if average > 0.1 {
+                " Overall, things went well for me."
+            } else if average < -0.1 {
+                " Overall, it was a difficult period for me."
+            } else {
+                " Overall, it was an uneventful period for me."
+            });
+        }
+        truncate_words(&text, self.schedule.max_words)
+    }
+}
+
+/// Event considered for a narrative summary
+struct PeriodEvent {
+    id: String,
+    timestamp: u64,
+    text: String,
+    salience: f64,
+    reward: Option<f64>,
+}
+
+fn is_summary(memory: &Memory) -> bool {
+    memory.tags.iter().any(|tag| tag == NARRATIVE_SUMMARY_TAG)
+}
+
+fn truncate_words(text: &str, max_words: usize) -> String {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    if words.len() <= max_words {
+        return text.to_string();
+    }
+    format!("{}...", words[..max_words].join(" "))
 }
 
+/// Persisted narrative summaries overlapping `since..until`, newest first
+pub fn narrative_summaries(
+    brain: &CognitiveBrain,
+    since: Option<u64>,
+    until: Option<u64>,
+    limit: usize,
+) -> Vec<NarrativeSummary> {
+    let mut summaries: Vec<NarrativeSummary> = brain
+        .retrieve_memories_by_tag(NARRATIVE_SUMMARY_TAG)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|memory| {
+            let mut summary: NarrativeSummary = serde_json::from_value(memory.content).ok()?;
+            summary.id = memory.id;
+            Some(summary)
+        })
+        .filter(|summary| since.is_none_or(|since| summary.period_end >= since))
+        .filter(|summary| until.is_none_or(|until| summary.period_start <= until))
+        .collect();
+    summaries.sort_by(|a, b| b.period_end.cmp(&a.period_end).then_with(|| b.created_at.cmp(&a.created_at)));
+    summaries.truncate(limit);
+    summaries
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> u64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+    }
+
+    fn generator(brain: Arc<CognitiveBrain>, schedule: NarrativeScheduleConfig) -> NarrativeGenerator {
+        let (sender, _) = broadcast::channel(16);
+        NarrativeGenerator::new(brain, sender).with_schedule(schedule)
+    }
+
+    #[tokio::test]
+    async fn test_summary_is_persisted_and_queryable() {
+        let brain = Arc::new(CognitiveBrain::new());
+        brain.store_experience(
+            "delivery".to_string(),
+            serde_json::json!({"description": "carried the cup to the kitchen"}),
+            None,
+            None,
+            Some(0.9),
+            None,
+        ).unwrap();
+        brain.store_memory(MemoryType::Episodic, serde_json::json!({"event": "met Ana"}), None, vec![], None).unwrap();
+
+        let narrative = generator(brain.clone(), NarrativeScheduleConfig::default());
+        let start = now() - 3600;
+        let summary = narrative.summarize_period(start, now()).await.unwrap();
+        assert_eq!(summary.generated_by, "template");
+        assert_eq!(summary.event_ids.len(), 2);
+        assert!(summary.text.starts_with("In the last 1 hours I went through 2 notable events."));
+        assert!(summary.text.contains("carried the cup to the kitchen"));
+        assert!(summary.text.ends_with("things went well for me."));
+
+        let stored = narrative_summaries(&brain, None, None, 10);
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].id, summary.id);
+        assert_eq!(brain.memories.read()[&summary.id].memory_type, MemoryType::Episodic);
+        assert!(narrative_summaries(&brain, Some(now() + 10), None, 10).is_empty());
+
+        // Summaries are not themselves summarized later
+        let next = narrative.summarize_period(start, now()).await.unwrap();
+        assert_eq!(next.event_ids.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_schedule_resumes_from_last_summary() {
+        let brain = Arc::new(CognitiveBrain::new());
+        let schedule = NarrativeScheduleConfig { interval_secs: 3600, max_words: 12, ..NarrativeScheduleConfig::default() };
+
+        // Nothing persisted yet: the first period starts now
+        let narrative = generator(brain.clone(), schedule.clone());
+        assert!(narrative.maybe_summarize().await.unwrap().is_none());
+
+        // A new generator picks up after the last persisted summary, which is overdue
+        let end = now() - 3700;
+        narrative.summarize_period(end - 3600, end).await.unwrap();
+        let restarted = generator(brain.clone(), schedule);
+        let summary = restarted.maybe_summarize().await.unwrap().unwrap();
+        assert_eq!(summary.period_start, end);
+        assert!(summary.text.split_whitespace().count() <= 12);
+        assert!(restarted.maybe_summarize().await.unwrap().is_none());
+        assert_eq!(narrative_summaries(&brain, None, None, 10).len(), 2);
+    }
+}
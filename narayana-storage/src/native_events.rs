@@ -4,7 +4,7 @@
 use narayana_core::{Error, Result};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::time::{SystemTime, UNIX_EPOCH, Duration};
 use parking_lot::RwLock;
 use dashmap::DashMap;
@@ -268,7 +268,16 @@ pub struct EventsMetrics {
 #[async_trait::async_trait]
 pub trait EventPersistence: Send + Sync {
     async fn save_event(&self, stream: &StreamName, event: &Event) -> Result<()>;
+    /// Save events of one stream in order
+    async fn save_events(&self, stream: &StreamName, events: &[Event]) -> Result<()> {
+        for event in events {
+            self.save_event(stream, event).await?;
+        }
+        Ok(())
+    }
     async fn load_events(&self, stream: &StreamName, offset: &EventOffset, limit: usize) -> Result<Vec<Event>>;
+    /// Delete saved events of one stream by id; returns how many were deleted
+    async fn remove_events(&self, stream: &StreamName, ids: &HashSet<EventId>) -> Result<usize>;
     async fn save_subscription(&self, subscription: &EventSubscription) -> Result<()>;
     async fn load_subscription(&self, id: &str) -> Result<Option<EventSubscription>>;
     async fn save_consumer_offset(&self, subscription_id: &str, stream: &StreamName, offset: EventId) -> Result<()>;
@@ -310,6 +319,18 @@ impl EventPersistence for InMemoryEventPersistence {
         Ok(())
     }
 
+    async fn save_events(&self, stream: &StreamName, batch: &[Event]) -> Result<()> {
+        const MAX_EVENTS_PER_STREAM: usize = 10_000_000;
+        let mut events = self.events.entry(stream.clone()).or_insert_with(Vec::new);
+        if events.len() + batch.len() > MAX_EVENTS_PER_STREAM {
+            let to_remove = (events.len() + batch.len() - MAX_EVENTS_PER_STREAM).max(MAX_EVENTS_PER_STREAM / 10);
+            let to_remove = to_remove.min(events.len());
+            events.drain(0..to_remove);
+        }
+        events.extend_from_slice(batch);
+        Ok(())
+    }
+
     async fn load_events(&self, stream: &StreamName, offset: &EventOffset, limit: usize) -> Result<Vec<Event>> {
         let events = self.events.get(stream)
             .ok_or_else(|| Error::Storage(format!("Stream {} not found", stream.0)))?;
@@ -332,6 +353,15 @@ impl EventPersistence for InMemoryEventPersistence {
         Ok(events[start_idx..end_idx].to_vec())
     }
 
+    async fn remove_events(&self, stream: &StreamName, ids: &HashSet<EventId>) -> Result<usize> {
+        let Some(mut events) = self.events.get_mut(stream) else {
+            return Ok(0);
+        };
+        let before = events.len();
+        events.retain(|e| !ids.contains(&e.id));
+        Ok(before - events.len())
+    }
+
     async fn save_subscription(&self, subscription: &EventSubscription) -> Result<()> {
         self.subscriptions.insert(subscription.id.clone(), subscription.clone());
         Ok(())
@@ -562,6 +592,119 @@ impl NativeEventsSystem {
         Ok(event.id)
     }
 
+    /// Publish events in one call: stream limits are applied and events
+    /// persisted once per stream rather than once per event. Nothing is
+    /// published if any event is too large. Returns ids in input order.
+    pub async fn publish_events(&self, mut events: Vec<Event>) -> Result<Vec<EventId>> {
+        let mut total_size = 0usize;
+        for event in &events {
+            let event_size = serde_json::to_string(event)
+                .map_err(|e| Error::Storage(format!("Failed to serialize event: {}", e)))?
+                .len();
+            if event_size > self.config.max_message_size {
+                return Err(Error::Storage(format!(
+                    "Event size {} exceeds maximum {}",
+                    event_size, self.config.max_message_size
+                )));
+            }
+            if let Some(payload_size) = event.payload.as_object()
+                .and_then(|obj| serde_json::to_string(obj).ok())
+                .map(|s| s.len()) {
+                if payload_size > self.config.max_message_size / 2 {
+                    return Err(Error::Storage(format!(
+                        "Event payload size {} exceeds maximum {}",
+                        payload_size, self.config.max_message_size / 2
+                    )));
+                }
+            }
+            total_size += event_size;
+        }
+        if events.is_empty() {
+            return Ok(Vec::new());
+        }
+        let average_size = (total_size / events.len()).max(1);
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        // Group by stream, keeping each stream's events in input order
+        let mut streams: Vec<StreamName> = Vec::new();
+        let mut positions: HashMap<StreamName, Vec<usize>> = HashMap::new();
+        for (position, event) in events.iter().enumerate() {
+            positions.entry(event.stream.clone())
+                .or_insert_with(|| {
+                    streams.push(event.stream.clone());
+                    Vec::new()
+                })
+                .push(position);
+        }
+
+        for stream in &streams {
+            let positions = &positions[stream];
+            {
+                let mut sequence = self.stream_sequences.entry(stream.clone()).or_insert(0);
+                for &position in positions {
+                    let event = &mut events[position];
+                    if event.id.0 == 0 {
+                        *sequence += 1;
+                        event.id = EventId(*sequence);
+                    }
+                    if event.timestamp == 0 {
+                        event.timestamp = now;
+                    }
+                }
+            }
+            let batch: Vec<Event> = positions.iter().map(|&position| events[position].clone()).collect();
+
+            let (max_events_limit, max_size_limit) = {
+                let streams = self.streams.read();
+                let config = streams.get(stream);
+                (config.and_then(|sc| sc.max_events), config.and_then(|sc| sc.max_size))
+            };
+            {
+                let mut stream_events = self.stream_events.entry(stream.clone())
+                    .or_insert_with(Vec::new);
+                // Evict the oldest events (FIFO) to make room for the whole batch
+                if let Some(max_events) = max_events_limit {
+                    let max_events = max_events as usize;
+                    let excess = (stream_events.len() + batch.len()).saturating_sub(max_events);
+                    let drain_end = excess.min(stream_events.len());
+                    stream_events.drain(0..drain_end);
+                }
+                if let Some(max_size) = max_size_limit {
+                    let estimated_size = (stream_events.len() + batch.len()) * average_size;
+                    if estimated_size > max_size as usize {
+                        let target_size = (max_size as usize * 9) / 10; // 90% of max
+                        let events_to_remove = (estimated_size.saturating_sub(target_size) / average_size).max(1);
+                        let drain_end = events_to_remove.min(stream_events.len());
+                        stream_events.drain(0..drain_end);
+                    }
+                }
+                stream_events.extend(batch.iter().cloned());
+            }
+
+            if let Some(ref persistence) = self.persistence {
+                persistence.save_events(stream, &batch).await?;
+            }
+
+            for event in batch {
+                if let Some(ref topic) = event.topic {
+                    self.publish_to_topic(topic.clone(), event.clone()).await?;
+                }
+                if let Some(ref queue) = event.queue {
+                    self.enqueue(queue.clone(), event).await?;
+                }
+            }
+        }
+
+        let mut metrics = self.metrics.write();
+        metrics.events_published += events.len() as u64;
+
+        debug!("Published {} events to {} streams", events.len(), streams.len());
+        Ok(events.iter().map(|event| event.id).collect())
+    }
+
     /// Publish event to topic (pub/sub)
     async fn publish_to_topic(&self, topic: TopicName, event: Event) -> Result<()> {
         if let Some(subscribers) = self.topic_subscribers.get(&topic) {
@@ -984,6 +1127,59 @@ impl NativeEventsSystem {
             last_event_id: events.last().map(|e| e.id),
         })
     }
+
+    /// Read retained events with id >= `from`, oldest first
+    /// Stream events are kept in publish order, which is ascending id order for
+    /// sequence-assigned ids and for producers that assign increasing ids.
+    pub fn read_stream(&self, stream: &StreamName, from: EventId, max_events: usize) -> Vec<Event> {
+        let Some(events) = self.stream_events.get(stream) else {
+            return Vec::new();
+        };
+        let start = events.partition_point(|e| e.id.0 < from.0);
+        events[start..].iter().take(max_events).cloned().collect()
+    }
+
+    /// Number of retained events with id >= `from`
+    pub fn count_stream_from(&self, stream: &StreamName, from: EventId) -> usize {
+        self.stream_events
+            .get(stream)
+            .map_or(0, |events| events.len() - events.partition_point(|e| e.id.0 < from.0))
+    }
+
+    /// Drop retained events of a stream by id, and delete them from
+    /// persistence as published events are saved there; returns how many
+    /// retained events were dropped
+    pub async fn remove_events(&self, stream: &StreamName, ids: &[EventId]) -> Result<usize> {
+        if ids.is_empty() {
+            return Ok(0);
+        }
+        let ids: HashSet<EventId> = ids.iter().copied().collect();
+        let removed = match self.stream_events.get_mut(stream) {
+            Some(mut events) => {
+                let before = events.len();
+                events.retain(|e| !ids.contains(&e.id));
+                before - events.len()
+            }
+            None => 0,
+        };
+        if let Some(ref persistence) = self.persistence {
+            persistence.remove_events(stream, &ids).await?;
+        }
+        Ok(removed)
+    }
+
+    /// Streams that have retained events
+    pub fn stream_names(&self) -> Vec<StreamName> {
+        self.stream_events.iter().map(|entry| entry.key().clone()).collect()
+    }
+
+    /// First pair of neighbouring events whose ids don't increase, if any
+    pub fn find_id_regression(&self, stream: &StreamName) -> Option<(EventId, EventId)> {
+        let events = self.stream_events.get(stream)?;
+        events.windows(2)
+            .find(|pair| pair[1].id.0 <= pair[0].id.0)
+            .map(|pair| (pair[0].id, pair[1].id))
+    }
 }
 
 /// Stream statistics
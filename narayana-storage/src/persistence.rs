@@ -950,69 +950,12 @@ impl S3Backend {
     /// Validate S3 endpoint to prevent SSRF attacks
     /// SECURITY: Only allow HTTPS endpoints from trusted S3-compatible services
     fn validate_s3_endpoint(endpoint: &str) -> Result<()> {
-        use crate::security_utils::SecurityUtils;
-        
-        // Must be HTTPS (not HTTP) to prevent man-in-the-middle
-        if !endpoint.starts_with("https://") {
-            return Err(Error::Storage(format!(
-                "S3 endpoint must use HTTPS protocol: {}",
-                endpoint
-            )));
-        }
-        
-        // Extract host from URL
-        let url_lower = endpoint.to_lowercase();
-        let host_part = if let Some(start) = url_lower.find("://") {
-            let after_protocol = &url_lower[start + 3..];
-            if let Some(slash_pos) = after_protocol.find('/') {
-                &after_protocol[..slash_pos]
-            } else if let Some(colon_pos) = after_protocol.find(':') {
-                &after_protocol[..colon_pos]
-            } else {
-                after_protocol
-            }
-        } else {
-            return Err(Error::Storage(format!("Invalid endpoint URL format: {}", endpoint)));
-        };
-        
-        // Remove port if present
-        let host = if let Some(colon_pos) = host_part.rfind(':') {
-            // Check if IPv6 bracket notation [::1]:8080
-            if host_part.starts_with('[') && host_part.contains(']') {
-                if let Some(bracket_end) = host_part.find(']') {
-                    &host_part[1..bracket_end]
-                } else {
-                    host_part
-                }
-            } else {
-                &host_part[..colon_pos]
-            }
-        } else {
-            // Remove IPv6 brackets if present
-            if host_part.starts_with('[') && host_part.ends_with(']') {
-                &host_part[1..host_part.len()-1]
-            } else {
-                host_part
-            }
-        };
+        use narayana_core::egress::{self, EgressSubsystem};
         
-        // SECURITY: Block localhost and private IPs to prevent SSRF
-        if SecurityUtils::is_localhost(host) {
-            return Err(Error::Storage(format!(
-                "S3 endpoint cannot target localhost: {}",
-                endpoint
-            )));
-        }
-        
-        // Check if it's a private IP
-        if let Ok(ip) = host.parse::<std::net::IpAddr>() {
-            if SecurityUtils::is_private_ip(&ip) {
-                return Err(Error::Storage(format!(
-                    "S3 endpoint cannot target private IP addresses: {}",
-                    endpoint
-                )));
-            }
-        }
+        // SECURITY: The egress policy requires HTTPS for persistence traffic (prevents
+        // man-in-the-middle) and blocks localhost and private IPs to prevent SSRF
+        let target = egress::check_url(EgressSubsystem::Persistence, endpoint)?;
+        let host = target.host.as_str();
         
         // SECURITY: Only allow known S3-compatible domains or whitelist
         // Allow AWS S3 domains and common S3-compatible services
@@ -1032,10 +975,9 @@ impl S3Backend {
         });
         
         if !is_allowed {
-            // SECURITY: For custom endpoints, require explicit allowlist via config
-            // In production, would check against a configurable allowlist
+            // SECURITY: Custom endpoints are allowed but should be pinned down with an
+            // egress allowlist for the persistence subsystem (NARAYANA_EGRESS_PERSISTENCE_ALLOW)
             warn!("S3 endpoint uses non-standard domain: {}. This may be a security risk.", host);
-            // Allow but warn - in production, would require explicit allowlist
         }
         
         Ok(())
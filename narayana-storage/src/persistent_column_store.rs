@@ -1,5 +1,12 @@
 // Real Persistent Columnar Storage
 // Actually writes to disk with compression, indexing, and proper block management
+// Blocks are checksummed when written and verified when read; corrupted blocks
+// are quarantined and rebuilt from intact copies when a source has one.
+// With a write-ahead log attached, each write is logged and synced before its
+// blocks, and tables replay the logged writes their metadata misses as they load.
+// Writes to a table are serialized by its write lock. Replacing a table's rows
+// writes the new blocks next to the old ones and switches over by rewriting
+// the table metadata, so a crash leaves either the old rows or the new ones.
 
 use async_trait::async_trait;
 use narayana_core::{Error, Result, schema::Schema, types::{TableId, CompressionType}, column::Column};
@@ -13,10 +20,13 @@ use tracing::{info, warn};
 use bytes::Bytes;
 use bincode;
 
-use crate::block::{Block, BlockMetadata};
+use crate::block::{self, Block, BlockMetadata, BlockMetadataV1};
+use crate::column_store::{TableLocks, TableWriteGuard};
 use crate::writer::ColumnWriter;
 use crate::reader::ColumnReader;
 use crate::index::{Index, BTreeIndex};
+use crate::self_healing::{BlockHealer, BlockRef, CorruptionKind};
+use crate::wal::WriteAheadLog;
 
 /// Persistent columnar store that actually writes to disk
 pub struct PersistentColumnStore {
@@ -26,6 +36,36 @@ pub struct PersistentColumnStore {
     block_reader: ColumnReader,
     indexes: Arc<RwLock<HashMap<(TableId, u32), Box<dyn Index + Send + Sync>>>>,
     compression: CompressionType,
+    healer: Arc<BlockHealer>,
+    wal: Option<Arc<WriteAheadLog>>,
+    /// Serializes writes per table, so each is logged with the row count it
+    /// starts at
+    locks: TableLocks,
+}
+
+/// Progress of `load_all_tables_with_progress`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TableLoadProgress {
+    pub total: usize,
+    pub loaded: usize,
+    /// Directories without a metadata file
+    pub skipped: usize,
+    /// Tables whose metadata could not be read
+    pub failed: usize,
+    /// Writes replayed from the WAL into loaded tables
+    pub replayed: usize,
+}
+
+impl TableLoadProgress {
+    pub fn processed(&self) -> usize {
+        self.loaded + self.skipped + self.failed
+    }
+}
+
+/// Outcome of reading a block from disk
+enum BlockRead {
+    Column(Column),
+    Corrupt(CorruptionKind, String),
 }
 
 #[derive(Clone)]
@@ -51,9 +91,58 @@ impl PersistentColumnStore {
             block_reader: ColumnReader::new(compression),
             indexes: Arc::new(RwLock::new(HashMap::new())),
             compression,
+            healer: Arc::new(BlockHealer::new()),
+            wal: None,
+            locks: TableLocks::default(),
         })
     }
 
+    /// Log every write to `wal` before writing its blocks. Attach it before
+    /// loading tables, which replay the writes it read back.
+    pub fn with_wal(mut self, wal: Arc<WriteAheadLog>) -> Self {
+        self.wal = Some(wal);
+        self
+    }
+
+    pub fn wal(&self) -> Option<Arc<WriteAheadLog>> {
+        self.wal.clone()
+    }
+
+    /// Apply the WAL's writes for a table just loaded that its metadata
+    /// doesn't cover; returns how many were applied. The caller holds the
+    /// table's write lock.
+    async fn replay_wal(&self, table_id: TableId) -> usize {
+        let Some(wal) = &self.wal else { return 0 };
+        let mut replayed = 0;
+        for write in wal.take_recovered(table_id) {
+            let row_count = self.tables.read().get(&table_id).map(|t| t.row_count).unwrap_or(0);
+            let rows = write.columns.first().map(Column::len).unwrap_or(0);
+            if row_count >= write.row_start + rows {
+                continue; // flushed before the crash
+            }
+            if row_count != write.row_start {
+                warn!(
+                    "Skipping WAL entry {} for table {}: it starts at row {} but the table has {} rows",
+                    write.lsn, table_id.0, write.row_start, row_count
+                );
+                continue;
+            }
+            match self.apply_write(table_id, write.columns).await {
+                Ok(()) => replayed += 1,
+                Err(e) => warn!("Failed to replay WAL entry {} into table {}: {}", write.lsn, table_id.0, e),
+            }
+        }
+        if replayed > 0 {
+            info!("Replayed {} writes from the WAL into table {}", replayed, table_id.0);
+        }
+        replayed
+    }
+
+    /// Corrupted-block incidents, quarantine and rebuild sources
+    pub fn block_healer(&self) -> Arc<BlockHealer> {
+        self.healer.clone()
+    }
+
     fn table_dir(&self, table_id: &TableId) -> PathBuf {
         self.data_dir.join(format!("table_{}", table_id.0))
     }
@@ -80,7 +169,7 @@ impl PersistentColumnStore {
             row_count: metadata.row_count,
         };
 
-        let bytes = bincode::serialize(&serializable)
+        let bytes = block::encode_with_stats(&serializable)
             .map_err(|e| Error::Serialization(format!("Failed to serialize metadata: {}", e)))?;
 
         // ATOMIC WRITE: Write to temp file, sync, then rename
@@ -125,7 +214,7 @@ impl PersistentColumnStore {
             .map_err(|e| Error::Storage(format!("Failed to read metadata: {}", e)))?;
 
         // SECURITY: Handle deserialization errors gracefully - return None if metadata is corrupted
-        let serializable: SerializableTableMetadata = match bincode::deserialize(&bytes) {
+        let serializable: SerializableTableMetadata = match block::decode_with_stats::<_, SerializableTableMetadata<BlockMetadataV1>>(&bytes, SerializableTableMetadata::from) {
             Ok(s) => s,
             Err(e) => {
                 warn!("Failed to deserialize metadata for table {}: {}. Skipping corrupted metadata.", table_id.0, e);
@@ -191,7 +280,7 @@ impl PersistentColumnStore {
         // Write block metadata with atomic write
         let metadata_path = file_path.with_extension("meta");
         let metadata_temp_path = metadata_path.with_extension("meta.tmp");
-        let metadata_bytes = bincode::serialize(metadata)
+        let metadata_bytes = block::encode_with_stats(metadata)
             .map_err(|e| Error::Serialization(format!("Failed to serialize block metadata: {}", e)))?;
         
         {
@@ -220,92 +309,104 @@ impl PersistentColumnStore {
                 Error::Storage(format!("Failed to rename metadata temp file: {}", e))
             })?;
 
+        // Checksum of the bytes on disk, verified on every read
+        write_checksum(&file_path, &block.data).await?;
+
         Ok(())
     }
 
-    async fn read_block_from_disk(&self, table_id: &TableId, column_id: u32, block_id: u64) -> Result<Option<(Block, BlockMetadata)>> {
-        let file_path = self.column_file_path(table_id, column_id, block_id);
-        
-        if !file_path.exists() {
-            return Ok(None);
-        }
-
-        // Read block data
-        let data = fs::read(&file_path).await
-            .map_err(|e| Error::Storage(format!("Failed to read block: {}", e)))?;
+    /// Read and decode a block, checking it against its checksum
+    /// Block layout comes from the table metadata; blocks written before
+    /// checksums were kept have no `.sum` file and are trusted as-is.
+    async fn read_block_from_disk(&self, table_id: &TableId, column_id: u32, metadata: &BlockMetadata) -> Result<BlockRead> {
+        let file_path = self.column_file_path(table_id, column_id, metadata.block_id);
 
-        // Read block metadata
-        let metadata_path = file_path.with_extension("meta");
-        let metadata_bytes = fs::read(&metadata_path).await
-            .map_err(|e| Error::Storage(format!("Failed to read block metadata: {}", e)))?;
-        let metadata: BlockMetadata = bincode::deserialize(&metadata_bytes)
-            .map_err(|e| Error::Deserialization(format!("Failed to deserialize block metadata: {}", e)))?;
-
-        let block = Block {
-            column_id,
-            data: Bytes::from(data),
-            row_count: metadata.row_count,
-            data_type: metadata.data_type.clone(),
-            compression: metadata.compression,
-            uncompressed_size: metadata.uncompressed_size,
-            compressed_size: metadata.compressed_size,
+        let data = match fs::read(&file_path).await {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(BlockRead::Corrupt(CorruptionKind::Missing, "Block file not found".to_string()));
+            }
+            Err(e) => return Err(Error::Storage(format!("Failed to read block: {}", e))),
         };
 
-        Ok(Some((block, metadata)))
-    }
-
-    async fn update_index(&self, table_id: TableId, column_id: u32, block_metadata: &BlockMetadata) -> Result<()> {
-        let key = (table_id, column_id);
-        let mut indexes = self.indexes.write();
-        
-        if !indexes.contains_key(&key) {
-            indexes.insert(key.clone(), Box::new(BTreeIndex::new()));
-        }
-
-        if let Some(index) = indexes.get_mut(&key) {
-            // Index by min/max values for range queries
-            if let Some(ref min_val) = block_metadata.min_value {
-                index.insert(min_val.clone(), block_metadata.block_id)?;
+        if let Some(expected) = read_checksum(&file_path).await? {
+            let actual = block_checksum(&data);
+            if actual != expected {
+                return Ok(BlockRead::Corrupt(
+                    CorruptionKind::ChecksumMismatch,
+                    format!("expected checksum {}, found {}", expected, actual),
+                ));
             }
         }
 
-        Ok(())
+        match self.decode_block(column_id, metadata, data) {
+            Ok(column) => Ok(BlockRead::Column(column)),
+            Err(e) => Ok(BlockRead::Corrupt(CorruptionKind::Unreadable, e.to_string())),
+        }
     }
-}
 
-#[derive(serde::Serialize, serde::Deserialize)]
-struct SerializableTableMetadata {
-    schema: Schema,
-    block_metadata: HashMap<u32, Vec<BlockMetadata>>,
-    row_count: usize,
-}
+    fn decode_block(&self, column_id: u32, metadata: &BlockMetadata, data: Vec<u8>) -> Result<Column> {
+        self.block_reader.read_block(&block_from(column_id, metadata, data))
+    }
 
-#[async_trait]
-impl crate::column_store::ColumnStore for PersistentColumnStore {
-    async fn create_table(&self, table_id: TableId, schema: Schema) -> Result<()> {
-        let metadata = {
-            let mut tables = self.tables.write();
-            if tables.contains_key(&table_id) {
-                return Err(Error::Storage(format!("Table {} already exists", table_id.0)));
+    /// Read a block for a query; None when it is quarantined and couldn't be rebuilt
+    async fn read_healthy_block(&self, table_id: &TableId, column_id: u32, metadata: &BlockMetadata) -> Result<Option<Column>> {
+        let block = BlockRef { table_id: *table_id, column_id, block_id: metadata.block_id };
+        if self.healer.is_quarantined(&block) {
+            return Ok(None);
+        }
+        match self.read_block_from_disk(table_id, column_id, metadata).await? {
+            BlockRead::Column(column) => Ok(Some(column)),
+            BlockRead::Corrupt(kind, detail) => {
+                let file_path = self.column_file_path(table_id, column_id, metadata.block_id);
+                let expected = read_checksum(&file_path).await.ok().flatten();
+                let incident = self.healer.record(block, kind, detail, expected);
+                self.move_to_quarantine(table_id, &file_path).await;
+                Ok(self.rebuild_block(incident, table_id, column_id, metadata).await)
             }
+        }
+    }
 
-            let metadata = TableMetadata {
-                schema: schema.clone(),
-                column_files: HashMap::new(),
-                block_metadata: HashMap::new(),
-                row_count: 0,
-            };
-
-            tables.insert(table_id.clone(), metadata.clone());
-            metadata
-        };
-        self.save_table_metadata(&table_id, &metadata).await?;
+    /// Move a corrupted block's files aside (kept under `quarantine/` for
+    /// inspection); its `.sum` stays so a rebuild can still be verified
+    async fn move_to_quarantine(&self, table_id: &TableId, file_path: &Path) {
+        let quarantine_dir = self.table_dir(table_id).join("quarantine");
+        if let Err(e) = fs::create_dir_all(&quarantine_dir).await {
+            warn!("Failed to create quarantine directory: {}", e);
+            return;
+        }
+        let stamp = chrono::Utc::now().timestamp_millis();
+        for path in [file_path.to_path_buf(), file_path.with_extension("meta")] {
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else { continue };
+            let target = quarantine_dir.join(format!("{}.{}", name, stamp));
+            match fs::rename(&path, &target).await {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => warn!("Failed to quarantine {:?}: {}", path, e),
+            }
+        }
+    }
 
-        info!("Created persistent table {}", table_id.0);
-        Ok(())
+    /// Rebuild a quarantined block from the healer's sources, verifying the
+    /// copy against the block's checksum (or, without one, that it decodes)
+    async fn rebuild_block(&self, incident: u64, table_id: &TableId, column_id: u32, metadata: &BlockMetadata) -> Option<Column> {
+        let expected = self.healer.incident(incident)?.expected_checksum;
+        let (source, data) = self.healer.fetch(incident, |data| match &expected {
+            Some(expected) => block_checksum(data) == *expected,
+            None => self.decode_block(column_id, metadata, data.to_vec()).is_ok(),
+        }).await?;
+        let block = block_from(column_id, metadata, data);
+        let column = self.block_reader.read_block(&block).ok()?;
+        if let Err(e) = self.write_block_to_disk(table_id, column_id, &block, metadata).await {
+            warn!("Failed to write rebuilt block: {}", e);
+            return None;
+        }
+        self.healer.resolve(incident, source);
+        Some(column)
     }
 
-    async fn write_columns(&self, table_id: TableId, columns: Vec<Column>) -> Result<()> {
+    /// Write columns as blocks and save the table metadata
+    async fn apply_write(&self, table_id: TableId, columns: Vec<Column>) -> Result<()> {
         // Prepare all blocks first
         let mut all_blocks_data = Vec::new();
         for (idx, column) in columns.into_iter().enumerate() {
@@ -315,8 +416,24 @@ impl crate::column_store::ColumnStore for PersistentColumnStore {
         }
         
         // Process each column
-        for (column_id, blocks, column_len) in all_blocks_data {
-            for (block, metadata) in blocks {
+        for (column_id, blocks, _column_len) in all_blocks_data {
+            // The writer numbers blocks and rows from zero; continue after what is stored
+            let (mut next_block, row_offset) = {
+                let tables = self.tables.read();
+                let table = tables
+                    .get(&table_id)
+                    .ok_or_else(|| Error::Storage(format!("Table {} not found", table_id.0)))?;
+                let existing = table.block_metadata.get(&column_id);
+                (
+                    existing.and_then(|b| b.iter().map(|m| m.block_id + 1).max()).unwrap_or(0),
+                    existing.map(|b| b.iter().map(|m| m.row_count).sum::<usize>()).unwrap_or(0),
+                )
+            };
+            for (block, mut metadata) in blocks {
+                metadata.block_id = next_block;
+                metadata.row_start += row_offset;
+                next_block += 1;
+
                 // Write to disk (outside of lock)
                 self.write_block_to_disk(&table_id, column_id, &block, &metadata).await?;
                 
@@ -340,7 +457,11 @@ impl crate::column_store::ColumnStore for PersistentColumnStore {
                     }
                     
                     // Update row count
-                    table.row_count = table.row_count.max(column_len);
+                    let column_rows: usize = table.block_metadata
+                        .get(&column_id)
+                        .map(|blocks| blocks.iter().map(|b| b.row_count).sum())
+                        .unwrap_or(0);
+                    table.row_count = table.row_count.max(column_rows);
                 }
                 
                 // Update index (outside of lock)
@@ -362,6 +483,99 @@ impl crate::column_store::ColumnStore for PersistentColumnStore {
         Ok(())
     }
 
+    async fn update_index(&self, table_id: TableId, column_id: u32, block_metadata: &BlockMetadata) -> Result<()> {
+        let key = (table_id, column_id);
+        let mut indexes = self.indexes.write();
+        
+        if !indexes.contains_key(&key) {
+            indexes.insert(key.clone(), Box::new(BTreeIndex::new()));
+        }
+
+        if let Some(index) = indexes.get_mut(&key) {
+            // Index by min/max values for range queries
+            if let Some(ref min_val) = block_metadata.min_value {
+                index.insert(min_val.clone(), block_metadata.block_id)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SerializableTableMetadata<B = BlockMetadata> {
+    #[serde(with = "crate::column_store::schema_json")]
+    schema: Schema,
+    block_metadata: HashMap<u32, Vec<B>>,
+    row_count: usize,
+}
+
+impl From<SerializableTableMetadata<BlockMetadataV1>> for SerializableTableMetadata {
+    fn from(v1: SerializableTableMetadata<BlockMetadataV1>) -> Self {
+        Self {
+            schema: v1.schema,
+            block_metadata: block::upgrade_blocks(v1.block_metadata),
+            row_count: v1.row_count,
+        }
+    }
+}
+
+#[async_trait]
+impl crate::column_store::ColumnStore for PersistentColumnStore {
+    async fn create_table(&self, table_id: TableId, schema: Schema) -> Result<()> {
+        let metadata = {
+            let mut tables = self.tables.write();
+            if tables.contains_key(&table_id) {
+                return Err(Error::Storage(format!("Table {} already exists", table_id.0)));
+            }
+
+            let metadata = TableMetadata {
+                schema: schema.clone(),
+                column_files: HashMap::new(),
+                block_metadata: HashMap::new(),
+                row_count: 0,
+            };
+
+            tables.insert(table_id.clone(), metadata.clone());
+            metadata
+        };
+        self.save_table_metadata(&table_id, &metadata).await?;
+
+        info!("Created persistent table {}", table_id.0);
+        Ok(())
+    }
+
+    async fn write_columns(&self, table_id: TableId, columns: Vec<Column>) -> Result<()> {
+        let guard = self.locks.lock(table_id).await;
+        self.write_locked(&guard, columns).await
+    }
+
+    async fn lock_table(&self, table_id: TableId) -> TableWriteGuard {
+        self.locks.lock(table_id).await
+    }
+
+    async fn write_locked(&self, guard: &TableWriteGuard, columns: Vec<Column>) -> Result<()> {
+        let table_id = guard.table_id();
+        let Some(wal) = &self.wal else {
+            return self.apply_write(table_id, columns).await;
+        };
+        let row_start = self.tables.read()
+            .get(&table_id)
+            .ok_or_else(|| Error::Storage(format!("Table {} not found", table_id.0)))?
+            .row_count;
+        let lsn = wal.log_write(table_id, row_start, &columns).await?;
+        match self.apply_write(table_id, columns).await {
+            Ok(()) => {
+                wal.complete(lsn);
+                Ok(())
+            }
+            Err(e) => {
+                wal.abort(lsn).await;
+                Err(e)
+            }
+        }
+    }
+
     async fn read_columns(
         &self,
         table_id: TableId,
@@ -383,7 +597,7 @@ impl crate::column_store::ColumnStore for PersistentColumnStore {
                             let relevant_blocks: Vec<BlockMetadata> = blocks.iter()
                                 .filter(|block_meta| {
                                     let row_end = block_meta.row_start + block_meta.row_count;
-                                    row_start < row_end && (row_start + row_count) > block_meta.row_start
+                                    row_start < row_end && row_start.saturating_add(row_count) > block_meta.row_start
                                 })
                                 .cloned()
                                 .collect();
@@ -393,22 +607,40 @@ impl crate::column_store::ColumnStore for PersistentColumnStore {
                 .collect()
         };
 
-        // Read blocks from disk (outside of lock)
-        let mut result = Vec::new();
+        // Read blocks from disk (outside of lock). Rows of corrupted blocks
+        // that can't be rebuilt are left out of every column, so the rows
+        // served stay aligned across columns.
+        let row_end = row_start.saturating_add(row_count);
+        let mut pieces = Vec::new();
+        let mut lost = Vec::new();
         for (column_id, blocks_metadata) in blocks_to_read {
-            let mut column_data: Option<Column> = None;
-            
+            let mut column_pieces = Vec::new();
             for block_meta in blocks_metadata {
-                // Read block from disk
-                if let Some((block, _)) = self.read_block_from_disk(&table_id, column_id, block_meta.block_id).await? {
-                    // Decompress and read column data
-                    let decompressed = self.block_reader.read_block(&block)?;
-                    
+                match self.read_healthy_block(&table_id, column_id, &block_meta).await? {
+                    Some(column) => column_pieces.push((block_meta.row_start, column)),
+                    None => lost.push((block_meta.row_start, block_meta.row_start + block_meta.row_count)),
+                }
+            }
+            pieces.push(column_pieces);
+        }
+        if !lost.is_empty() {
+            warn!("Serving table {} without {} quarantined block(s)", table_id.0, lost.len());
+        }
+
+        let mut result = Vec::new();
+        for column_pieces in pieces {
+            let mut column_data: Option<Column> = None;
+            for (piece_start, piece) in column_pieces {
+                // Blocks were picked by row range; keep the exact rows asked for
+                let start = row_start.max(piece_start);
+                let end = row_end.min(piece_start + piece.len());
+                for (from, to) in healthy_ranges(start, end, &lost) {
+                    let part = piece.slice(from - piece_start, to - from)?;
                     // Merge with existing column data
                     column_data = match column_data.take() {
-                        None => Some(decompressed),
+                        None => Some(part),
                         Some(existing) => {
-                            match existing.append(&decompressed) {
+                            match existing.append(&part) {
                                 Ok(merged) => Some(merged),
                                 Err(e) => {
                                     warn!("Failed to append column data: {}", e);
@@ -421,15 +653,7 @@ impl crate::column_store::ColumnStore for PersistentColumnStore {
             }
             
             if let Some(col) = column_data {
-                // Slice to requested range
-                match col.slice(row_start, row_count) {
-                    Ok(sliced) => result.push(sliced),
-                    Err(e) => {
-                        warn!("Failed to slice column: {}", e);
-                        // Return full column if slice fails
-                        result.push(col);
-                    }
-                }
+                result.push(col);
             }
         }
 
@@ -447,8 +671,9 @@ impl crate::column_store::ColumnStore for PersistentColumnStore {
 
         // Load from disk if not in memory
         if let Some(metadata) = self.load_table_metadata(&table_id).await? {
-            let mut tables = self.tables.write();
-            tables.insert(table_id.clone(), metadata.clone());
+            let _guard = self.locks.lock(table_id).await;
+            self.tables.write().entry(table_id).or_insert(metadata.clone());
+            self.replay_wal(table_id).await;
             Ok(metadata.schema)
         } else {
             Err(Error::Storage(format!("Table {} not found", table_id.0)))
@@ -472,7 +697,82 @@ impl crate::column_store::ColumnStore for PersistentColumnStore {
             .unwrap_or_default())
     }
 
+    async fn replace_rows(&self, guard: &TableWriteGuard, columns: Vec<Column>) -> Result<()> {
+        let table_id = guard.table_id();
+        let old = self.tables.read()
+            .get(&table_id)
+            .cloned()
+            .ok_or_else(|| Error::Storage(format!("Table {} not found", table_id.0)))?;
+        // The rows of quarantined blocks weren't read, so they would be lost
+        let quarantined = old.block_metadata.iter().any(|(&column_id, blocks)| {
+            blocks.iter().any(|b| self.healer.is_quarantined(&BlockRef { table_id, column_id, block_id: b.block_id }))
+        });
+        if quarantined {
+            return Err(Error::Storage(format!("Table {} has quarantined blocks and can't be rewritten", table_id.0)));
+        }
+
+        // New blocks are numbered after every stored one, so the old blocks
+        // stay intact until the metadata points at the new ones
+        let first_block = old.block_metadata.values().flatten().map(|b| b.block_id + 1).max().unwrap_or(0);
+        let mut metadata = TableMetadata {
+            schema: old.schema.clone(),
+            column_files: HashMap::new(),
+            block_metadata: HashMap::new(),
+            row_count: 0,
+        };
+        for (idx, column) in columns.iter().enumerate().filter(|(_, c)| c.len() > 0) {
+            let column_id = idx as u32;
+            let mut next_block = first_block;
+            for (block, mut block_meta) in self.block_writer.write_column(column, column_id)? {
+                block_meta.block_id = next_block;
+                next_block += 1;
+                self.write_block_to_disk(&table_id, column_id, &block, &block_meta).await?;
+                metadata.block_metadata.entry(column_id).or_default().push(block_meta);
+            }
+            metadata.column_files.insert(column_id, self.column_file_path(&table_id, column_id, first_block));
+            metadata.row_count = metadata.row_count.max(column.len());
+        }
+
+        // Logged writes are all in the old metadata; none may be replayed
+        // over the new rows
+        if let Some(wal) = &self.wal {
+            wal.log_rewrite(table_id).await?;
+        }
+        self.save_table_metadata(&table_id, &metadata).await?;
+        self.tables.write().insert(table_id, metadata.clone());
+        self.indexes.write().retain(|(tid, _), _| *tid != table_id);
+        for (&column_id, blocks) in &metadata.block_metadata {
+            for block_meta in blocks {
+                self.update_index(table_id, column_id, block_meta).await?;
+            }
+        }
+
+        for (&column_id, blocks) in &old.block_metadata {
+            for block_meta in blocks {
+                let file_path = self.column_file_path(&table_id, column_id, block_meta.block_id);
+                for path in [file_path.clone(), file_path.with_extension("meta"), file_path.with_extension("sum")] {
+                    if let Err(e) = fs::remove_file(&path).await {
+                        if e.kind() != std::io::ErrorKind::NotFound {
+                            warn!("Failed to remove replaced block file {:?}: {}", path, e);
+                        }
+                    }
+                }
+            }
+        }
+        info!("Replaced the rows of persistent table {} ({} rows)", table_id.0, metadata.row_count);
+        Ok(())
+    }
+
     async fn delete_table(&self, table_id: TableId) -> Result<()> {
+        let _guard = self.locks.lock(table_id).await;
+        if !self.tables.read().contains_key(&table_id) {
+            return Err(Error::Storage(format!("Table {} not found", table_id.0)));
+        }
+        // Writes logged before this must not come back if the id is reused
+        if let Some(wal) = &self.wal {
+            wal.log_drop_table(table_id).await?;
+        }
+
         // Remove from tables first (inside lock)
         {
             let mut tables = self.tables.write();
@@ -481,6 +781,7 @@ impl crate::column_store::ColumnStore for PersistentColumnStore {
             }
             tables.remove(&table_id);
         }
+        self.locks.remove(table_id);
         
         // Delete table directory (outside of lock)
         let table_dir = self.table_dir(&table_id);
@@ -494,6 +795,7 @@ impl crate::column_store::ColumnStore for PersistentColumnStore {
             let mut indexes = self.indexes.write();
             indexes.retain(|(tid, _), _| *tid != table_id);
         }
+        self.healer.forget_table(&table_id);
 
         info!("Deleted persistent table {}", table_id.0);
         Ok(())
@@ -503,14 +805,28 @@ impl crate::column_store::ColumnStore for PersistentColumnStore {
 impl PersistentColumnStore {
     /// Load all tables from disk on startup
     pub async fn load_all_tables(&self) -> Result<()> {
+        self.load_all_tables_with_progress(|_| {}).await
+    }
+
+    /// Load table metadata, reporting progress after each table. Tables become
+    /// queryable as soon as they are loaded, so callers may serve while this runs.
+    pub async fn load_all_tables_with_progress<F>(&self, on_progress: F) -> Result<()>
+    where
+        F: Fn(TableLoadProgress) + Send + Sync,
+    {
         if !self.data_dir.exists() {
+            on_progress(TableLoadProgress::default());
             return Ok(());
         }
 
         // CRITICAL: Clean up any orphaned temp files from previous crashes
-        self.cleanup_temp_files().await?;
+        // (a failure here must not keep the tables from loading)
+        if let Err(e) = self.cleanup_temp_files().await {
+            warn!("Failed to clean up temp files: {}", e);
+        }
 
-        // Scan for table directories
+        // Scan for table directories first so the total is known up front
+        let mut table_ids = Vec::new();
         let mut entries = fs::read_dir(&self.data_dir).await
             .map_err(|e| Error::Storage(format!("Failed to read data directory: {}", e)))?;
 
@@ -519,39 +835,74 @@ impl PersistentColumnStore {
             
             let path = entry.path();
             if path.is_dir() {
-                let dir_name = path.file_name()
-                    .and_then(|n| n.to_str())
-                    .ok_or_else(|| Error::Storage("Invalid directory name".to_string()))?;
+                // A stray directory must not keep the other tables from loading
+                let Some(dir_name) = path.file_name().and_then(|n| n.to_str()) else {
+                    warn!("Skipping directory with invalid name {:?}", path);
+                    continue;
+                };
                 
-                if dir_name.starts_with("table_") {
-                    let table_id_str = dir_name.strip_prefix("table_")
-                        .ok_or_else(|| Error::Storage("Invalid table directory name".to_string()))?;
-                    let table_id = table_id_str.parse::<u64>()
-                        .map_err(|_| Error::Storage("Invalid table ID".to_string()))
-                        .map(|id| TableId(id))?;
-                    
-                    // SECURITY: Handle deserialization errors gracefully - skip corrupted tables
-                    match self.load_table_metadata(&table_id).await {
-                        Ok(Some(metadata)) => {
-                            let mut tables = self.tables.write();
-                            tables.insert(table_id, metadata);
-                        }
-                        Ok(None) => {
-                            // No metadata file, skip
-                        }
-                        Err(e) => {
-                            // Log error but continue - don't fail startup due to corrupted metadata
-                            warn!("Warning: Failed to load metadata for table {}: {}. Skipping.", table_id.0, e);
-                        }
+                if let Some(table_id_str) = dir_name.strip_prefix("table_") {
+                    match table_id_str.parse::<u64>() {
+                        Ok(id) => table_ids.push(TableId(id)),
+                        Err(_) => warn!("Skipping directory {} with invalid table ID", dir_name),
                     }
                 }
             }
         }
+        table_ids.sort_by_key(|id| id.0);
 
+        let mut progress = TableLoadProgress { total: table_ids.len(), ..Default::default() };
+        on_progress(progress);
+        for table_id in table_ids {
+            // SECURITY: Handle deserialization errors gracefully - skip corrupted tables
+            match self.load_table_metadata(&table_id).await {
+                Ok(Some(metadata)) => {
+                    let _guard = self.locks.lock(table_id).await;
+                    self.tables.write().insert(table_id, metadata);
+                    progress.replayed += self.replay_wal(table_id).await;
+                    progress.loaded += 1;
+                }
+                Ok(None) => {
+                    // No metadata file, skip
+                    progress.skipped += 1;
+                }
+                Err(e) => {
+                    // Log error but continue - don't fail startup due to corrupted metadata
+                    warn!("Warning: Failed to load metadata for table {}: {}. Skipping.", table_id.0, e);
+                    progress.failed += 1;
+                }
+            }
+            on_progress(progress);
+        }
+
+        if let Some(wal) = &self.wal {
+            wal.finish_recovery();
+        }
         info!("Loaded {} tables from disk", self.tables.read().len());
         Ok(())
     }
 
+    /// Retry rebuilding quarantined blocks (sources may have caught up since
+    /// the blocks were found corrupted); returns how many were rebuilt
+    pub async fn rebuild_quarantined_blocks(&self) -> usize {
+        let mut rebuilt = 0;
+        for incident in self.healer.quarantined() {
+            let block = &incident.block;
+            let metadata = {
+                let tables = self.tables.read();
+                tables.get(&block.table_id)
+                    .and_then(|t| t.block_metadata.get(&block.column_id))
+                    .and_then(|blocks| blocks.iter().find(|m| m.block_id == block.block_id))
+                    .cloned()
+            };
+            let Some(metadata) = metadata else { continue };
+            if self.rebuild_block(incident.id, &block.table_id, block.column_id, &metadata).await.is_some() {
+                rebuilt += 1;
+            }
+        }
+        rebuilt
+    }
+
     /// Clean up orphaned temp files from previous crashes
     async fn cleanup_temp_files(&self) -> Result<()> {
         if !self.data_dir.exists() {
@@ -594,3 +945,242 @@ impl PersistentColumnStore {
     }
 }
 
+/// Hex SHA-256 of a block's bytes on disk
+fn block_checksum(data: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    hex::encode(Sha256::digest(data))
+}
+
+/// Checksum stored next to a block file, if it has one
+async fn read_checksum(file_path: &Path) -> Result<Option<String>> {
+    match fs::read_to_string(file_path.with_extension("sum")).await {
+        Ok(sum) => Ok(Some(sum.trim().to_string())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(Error::Storage(format!("Failed to read block checksum: {}", e))),
+    }
+}
+
+/// Write a block's checksum next to it (temp file, sync, rename)
+async fn write_checksum(file_path: &Path, data: &[u8]) -> Result<()> {
+    let sum_path = file_path.with_extension("sum");
+    let temp_path = sum_path.with_extension("sum.tmp");
+    let result = async {
+        let mut file = fs::File::create(&temp_path).await?;
+        file.write_all(block_checksum(data).as_bytes()).await?;
+        file.sync_all().await?;
+        fs::rename(&temp_path, &sum_path).await
+    }.await;
+    result.map_err(|e| {
+        // Cleanup temp file on error
+        let _ = std::fs::remove_file(&temp_path);
+        Error::Storage(format!("Failed to write block checksum: {}", e))
+    })
+}
+
+fn block_from(column_id: u32, metadata: &BlockMetadata, data: Vec<u8>) -> Block {
+    Block {
+        column_id,
+        data: Bytes::from(data),
+        row_count: metadata.row_count,
+        data_type: metadata.data_type.clone(),
+        compression: metadata.compression,
+        uncompressed_size: metadata.uncompressed_size,
+        compressed_size: metadata.compressed_size,
+    }
+}
+
+/// Parts of rows `start..end` outside every `lost` range
+fn healthy_ranges(start: usize, end: usize, lost: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    if start >= end {
+        return Vec::new();
+    }
+    let mut ranges = vec![(start, end)];
+    for &(lost_start, lost_end) in lost {
+        ranges = ranges
+            .into_iter()
+            .flat_map(|(from, to)| {
+                let before = (from, lost_start.min(to));
+                let after = (lost_end.max(from), to);
+                [before, after].into_iter().filter(|(a, b)| a < b)
+            })
+            .collect();
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::column_store::ColumnStore;
+    use crate::self_healing::{DirectoryBlockSource, IncidentStatus};
+    use narayana_core::schema::{DataType, Field};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("narayana_{}_{}", name, uuid::Uuid::new_v4()))
+    }
+
+    /// Table 1 with two Int64 columns written as two blocks each (rows 0..3 and 3..5)
+    async fn store_with_table() -> (PersistentColumnStore, PathBuf) {
+        let dir = temp_dir("healing");
+        let store = PersistentColumnStore::new(&dir, CompressionType::None).unwrap();
+        let field = |name: &str| Field {
+            name: name.to_string(),
+            data_type: DataType::Int64,
+            nullable: false,
+            default_value: None,
+        };
+        store.create_table(TableId(1), Schema::new(vec![field("id"), field("value")])).await.unwrap();
+        store.write_columns(TableId(1), vec![Column::Int64(vec![1, 2, 3]), Column::Int64(vec![10, 20, 30])]).await.unwrap();
+        store.write_columns(TableId(1), vec![Column::Int64(vec![4, 5]), Column::Int64(vec![40, 50])]).await.unwrap();
+        (store, dir)
+    }
+
+    fn ints(columns: &[Column]) -> Vec<Vec<i64>> {
+        columns.iter()
+            .map(|c| match c {
+                Column::Int64(v) => v.clone(),
+                other => panic!("unexpected column {:?}", other),
+            })
+            .collect()
+    }
+
+    fn corrupt(dir: &Path, column_id: u32, block_id: u64) -> Vec<u8> {
+        let path = dir.join("table_1").join(format!("col_{}_block_{}.dat", column_id, block_id));
+        let original = std::fs::read(&path).unwrap();
+        let mut damaged = original.clone();
+        damaged[0] ^= 0xff;
+        std::fs::write(&path, damaged).unwrap();
+        original
+    }
+
+    #[tokio::test]
+    async fn test_corrupted_block_is_quarantined_and_healthy_rows_served() {
+        let (store, dir) = store_with_table().await;
+        corrupt(&dir, 1, 0);
+
+        // Rows of the bad block are left out of every column
+        let columns = store.read_columns(TableId(1), vec![0, 1], 0, 5).await.unwrap();
+        assert_eq!(ints(&columns), vec![vec![4, 5], vec![40, 50]]);
+        // Row ranges that don't touch it are unaffected
+        let columns = store.read_columns(TableId(1), vec![0, 1], 3, 2).await.unwrap();
+        assert_eq!(ints(&columns), vec![vec![4, 5], vec![40, 50]]);
+
+        let incidents = store.block_healer().incidents();
+        assert_eq!(incidents.len(), 1);
+        assert_eq!(incidents[0].block, BlockRef { table_id: TableId(1), column_id: 1, block_id: 0 });
+        assert_eq!(incidents[0].kind, CorruptionKind::ChecksumMismatch);
+        assert_eq!(incidents[0].status, IncidentStatus::Quarantined);
+        assert_eq!(std::fs::read_dir(dir.join("table_1").join("quarantine")).unwrap().count(), 2);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_corrupted_block_is_rebuilt_from_source() {
+        let (store, dir) = store_with_table().await;
+        let backup = temp_dir("healing_backup");
+        std::fs::create_dir_all(backup.join("table_1")).unwrap();
+        let original = corrupt(&dir, 1, 0);
+
+        // Without a source the block stays quarantined
+        assert_eq!(ints(&store.read_columns(TableId(1), vec![1], 0, 5).await.unwrap()), vec![vec![40, 50]]);
+        assert_eq!(store.rebuild_quarantined_blocks().await, 0);
+
+        // A copy that doesn't match the checksum is rejected
+        std::fs::write(backup.join("table_1").join("col_1_block_0.dat"), b"garbage").unwrap();
+        store.block_healer().add_source(Arc::new(DirectoryBlockSource::new(&backup)));
+        assert_eq!(store.rebuild_quarantined_blocks().await, 0);
+
+        std::fs::write(backup.join("table_1").join("col_1_block_0.dat"), &original).unwrap();
+        assert_eq!(store.rebuild_quarantined_blocks().await, 1);
+        let columns = store.read_columns(TableId(1), vec![0, 1], 0, 5).await.unwrap();
+        assert_eq!(ints(&columns)[1], vec![10, 20, 30, 40, 50]);
+
+        let incident = &store.block_healer().incidents()[0];
+        assert_eq!(incident.rebuild_attempts, 4);
+        assert!(matches!(&incident.status, IncidentStatus::Rebuilt { source } if source.starts_with("dir:")));
+
+        // A missing block found on read is rebuilt right away
+        std::fs::remove_file(dir.join("table_1").join("col_1_block_0.dat")).unwrap();
+        let columns = store.read_columns(TableId(1), vec![1], 0, 5).await.unwrap();
+        assert_eq!(ints(&columns), vec![vec![10, 20, 30, 40, 50]]);
+        assert_eq!(store.block_healer().incidents()[1].kind, CorruptionKind::Missing);
+        assert!(store.block_healer().quarantined().is_empty());
+
+        let _ = std::fs::remove_dir_all(dir);
+        let _ = std::fs::remove_dir_all(backup);
+    }
+
+    async fn store_with_wal(dir: &Path) -> PersistentColumnStore {
+        let wal = crate::wal::WriteAheadLog::open(dir.join("wal")).await.unwrap();
+        PersistentColumnStore::new(dir.join("columnar"), CompressionType::None).unwrap().with_wal(Arc::new(wal))
+    }
+
+    #[tokio::test]
+    async fn test_wal_replays_writes_missing_from_metadata() {
+        let dir = temp_dir("wal_replay");
+        let schema = Schema::new(vec![Field {
+            name: "id".to_string(),
+            data_type: DataType::Int64,
+            nullable: false,
+            default_value: None,
+        }]);
+        let metadata_path = dir.join("columnar").join("table_1").join("metadata.bin");
+        {
+            let store = store_with_wal(&dir).await;
+            store.create_table(TableId(1), schema).await.unwrap();
+            store.write_columns(TableId(1), vec![Column::Int64(vec![1, 2, 3])]).await.unwrap();
+            let flushed = std::fs::read(&metadata_path).unwrap();
+            store.write_columns(TableId(1), vec![Column::Int64(vec![4, 5])]).await.unwrap();
+            // Crash before the second write's metadata reached disk
+            std::fs::write(&metadata_path, flushed).unwrap();
+        }
+
+        let store = store_with_wal(&dir).await;
+        let progress = std::sync::Mutex::new(TableLoadProgress::default());
+        store.load_all_tables_with_progress(|p| *progress.lock().unwrap() = p).await.unwrap();
+        assert_eq!(progress.lock().unwrap().replayed, 1);
+        let columns = store.read_columns(TableId(1), vec![0], 0, 10).await.unwrap();
+        assert_eq!(ints(&columns), vec![vec![1, 2, 3, 4, 5]]);
+
+        // Replayed writes are flushed; loading again doesn't repeat them
+        let store = store_with_wal(&dir).await;
+        store.load_all_tables().await.unwrap();
+        let columns = store.read_columns(TableId(1), vec![0], 0, 10).await.unwrap();
+        assert_eq!(ints(&columns), vec![vec![1, 2, 3, 4, 5]]);
+        assert_eq!(store.wal().unwrap().stats().await.recovering, 0);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_wal_does_not_replay_into_recreated_table() {
+        let dir = temp_dir("wal_drop");
+        let schema = Schema::new(vec![Field {
+            name: "id".to_string(),
+            data_type: DataType::Int64,
+            nullable: false,
+            default_value: None,
+        }]);
+        {
+            let store = store_with_wal(&dir).await;
+            store.create_table(TableId(1), schema.clone()).await.unwrap();
+            store.write_columns(TableId(1), vec![Column::Int64(vec![1, 2])]).await.unwrap();
+            store.delete_table(TableId(1)).await.unwrap();
+            store.create_table(TableId(1), schema).await.unwrap();
+        }
+
+        let store = store_with_wal(&dir).await;
+        store.load_all_tables().await.unwrap();
+        assert!(store.read_columns(TableId(1), vec![0], 0, 10).await.unwrap().is_empty());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_healthy_ranges() {
+        assert_eq!(healthy_ranges(0, 10, &[]), vec![(0, 10)]);
+        assert_eq!(healthy_ranges(0, 10, &[(3, 5), (8, 12)]), vec![(0, 3), (5, 8)]);
+        assert!(healthy_ranges(4, 4, &[]).is_empty());
+    }
+}
@@ -918,3 +918,580 @@ impl Default for PredictiveScalingConfig {
     }
 }
 
+
+// ============================================
+// Workload forecasting - time-of-day load sizing thread pools, cache and compaction
+// ============================================
+
+/// Load observed over one sampling interval
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct WorkloadSample {
+    /// End of the interval (Unix seconds)
+    pub timestamp: u64,
+    pub interval_secs: u64,
+    pub queries: u64,
+    pub ingested_rows: u64,
+}
+
+/// Forecasting and sizing settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkloadScalingConfig {
+    /// Length of a time-of-day slot; must divide a day
+    pub slot_minutes: u32,
+    /// How far ahead resources are sized
+    pub lead_minutes: u32,
+    /// Weight of a new sample in its slot's average
+    pub smoothing: f64,
+    /// Samples before a slot's own average is trusted
+    pub min_slot_samples: u64,
+    pub min_threads: usize,
+    pub max_threads: usize,
+    pub min_cache_bytes: u64,
+    pub max_cache_bytes: u64,
+    /// Load (share of the busiest slot) from which compaction is deferred
+    pub peak_ratio: f64,
+    /// Load below which compaction runs aggressively
+    pub quiet_ratio: f64,
+    /// Relative change below which threads and cache are left alone
+    pub hysteresis: f64,
+    /// Applied actions kept
+    pub max_actions: usize,
+    /// Where the learned profile is kept across restarts
+    pub profile_path: Option<std::path::PathBuf>,
+}
+
+impl Default for WorkloadScalingConfig {
+    fn default() -> Self {
+        let cpus = num_cpus::get();
+        Self {
+            slot_minutes: 15,
+            lead_minutes: 30,
+            smoothing: 0.3,
+            min_slot_samples: 2,
+            min_threads: cpus,
+            max_threads: cpus * 4,
+            min_cache_bytes: 64 * 1024 * 1024,
+            max_cache_bytes: 1024 * 1024 * 1024,
+            peak_ratio: 0.75,
+            quiet_ratio: 0.25,
+            hysteresis: 0.1,
+            max_actions: 200,
+            profile_path: None,
+        }
+    }
+}
+
+/// Average load of one time-of-day slot
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+struct SlotLoad {
+    queries_per_minute: f64,
+    ingest_rows_per_minute: f64,
+    samples: u64,
+}
+
+/// Predicted load at a point in time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadForecast {
+    /// Unix seconds the forecast is for
+    pub at: u64,
+    /// Time-of-day slot of `at`
+    pub slot: usize,
+    pub queries_per_minute: f64,
+    pub ingest_rows_per_minute: f64,
+    /// Load as a share (0-1) of the busiest slot
+    pub load_ratio: f64,
+    /// 0-1, grows with the samples behind the slot
+    pub confidence: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompactionMode {
+    /// A peak is on or ahead: hold compaction back
+    Deferred,
+    Normal,
+    /// Quiet now and ahead: compact while it's cheap
+    Aggressive,
+}
+
+impl std::fmt::Display for CompactionMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CompactionMode::Deferred => write!(f, "deferred"),
+            CompactionMode::Normal => write!(f, "normal"),
+            CompactionMode::Aggressive => write!(f, "aggressive"),
+        }
+    }
+}
+
+/// Resource sizes for the forecast load
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourcePlan {
+    pub thread_pool_size: usize,
+    pub cache_budget_bytes: u64,
+    pub compaction: CompactionMode,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScalingResource {
+    ThreadPool,
+    CacheBudget,
+    Compaction,
+}
+
+/// A resource change made (or attempted) by the scaler
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppliedScalingAction {
+    pub timestamp: u64,
+    pub resource: ScalingResource,
+    /// None on the first sizing
+    pub from: Option<String>,
+    pub to: String,
+    pub reason: String,
+    /// Set when the actuator rejected the change (the old size stays)
+    pub error: Option<String>,
+}
+
+/// Forecasts, current sizes and action counts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkloadScalingStatus {
+    pub samples: u64,
+    pub now: Option<LoadForecast>,
+    pub ahead: Option<LoadForecast>,
+    pub plan: Option<ResourcePlan>,
+    pub actions_applied: u64,
+    pub actions_failed: u64,
+}
+
+/// Carries out resource changes (the query pool, caches, compaction jobs)
+pub trait ResourceActuator: Send + Sync {
+    fn resize_thread_pool(&self, threads: usize) -> Result<()>;
+    fn set_cache_budget(&self, bytes: u64) -> Result<()>;
+    fn set_compaction_mode(&self, mode: CompactionMode) -> Result<()>;
+}
+
+#[derive(Default)]
+struct ScalerState {
+    samples: u64,
+    now: Option<LoadForecast>,
+    ahead: Option<LoadForecast>,
+    plan: Option<ResourcePlan>,
+    actions: VecDeque<AppliedScalingAction>,
+    actions_applied: u64,
+    actions_failed: u64,
+    /// Resources whose last change failed, retried on the next tick
+    failed: Vec<ScalingResource>,
+}
+
+/// Learns load per time of day and sizes resources ahead of predicted peaks
+pub struct WorkloadScaler {
+    config: WorkloadScalingConfig,
+    slots: RwLock<Vec<SlotLoad>>,
+    state: RwLock<ScalerState>,
+    actuator: Option<Arc<dyn ResourceActuator>>,
+}
+
+impl WorkloadScaler {
+    /// Create a scaler, loading the profile learned before a restart
+    pub fn new(config: WorkloadScalingConfig) -> Result<Self> {
+        if config.slot_minutes == 0 || 1440 % config.slot_minutes != 0 {
+            return Err(Error::Storage("slot_minutes must divide a day".to_string()));
+        }
+        let slot_count = (1440 / config.slot_minutes) as usize;
+        let mut slots = vec![SlotLoad::default(); slot_count];
+        if let Some(path) = config.profile_path.as_ref().filter(|p| p.exists()) {
+            let loaded = std::fs::read(path)
+                .map_err(|e| Error::Storage(format!("Failed to read load profile: {}", e)))
+                .and_then(|bytes| serde_json::from_slice::<Vec<SlotLoad>>(&bytes)
+                    .map_err(|e| Error::Serialization(e.to_string())));
+            match loaded {
+                Ok(profile) if profile.len() == slot_count => slots = profile,
+                // Slot length changed: start learning afresh
+                Ok(_) => warn!("Load profile at {:?} has a different slot length, ignoring it", path),
+                Err(e) => warn!("Failed to load load profile from {:?}: {}", path, e),
+            }
+        }
+        Ok(Self {
+            config,
+            slots: RwLock::new(slots),
+            state: RwLock::new(ScalerState::default()),
+            actuator: None,
+        })
+    }
+
+    pub fn with_actuator(mut self, actuator: Arc<dyn ResourceActuator>) -> Self {
+        self.actuator = Some(actuator);
+        self
+    }
+
+    pub fn config(&self) -> &WorkloadScalingConfig {
+        &self.config
+    }
+
+    /// Fold a sample into the average of its time-of-day slot
+    pub fn record(&self, sample: WorkloadSample) {
+        if sample.interval_secs == 0 {
+            return;
+        }
+        let minutes = sample.interval_secs as f64 / 60.0;
+        let queries = sample.queries as f64 / minutes;
+        let ingest = sample.ingested_rows as f64 / minutes;
+        let slot = self.slot_of(sample.timestamp.saturating_sub(sample.interval_secs / 2));
+        let alpha = self.config.smoothing.clamp(0.0, 1.0);
+
+        let mut slots = self.slots.write();
+        let load = &mut slots[slot];
+        if load.samples == 0 {
+            load.queries_per_minute = queries;
+            load.ingest_rows_per_minute = ingest;
+        } else {
+            load.queries_per_minute += alpha * (queries - load.queries_per_minute);
+            load.ingest_rows_per_minute += alpha * (ingest - load.ingest_rows_per_minute);
+        }
+        load.samples += 1;
+        drop(slots);
+        self.state.write().samples += 1;
+    }
+
+    /// Predicted load at `at` (Unix seconds); None until a slot has enough samples
+    pub fn forecast(&self, at: u64) -> Option<LoadForecast> {
+        let slots = self.slots.read();
+        let trusted: Vec<&SlotLoad> = slots.iter().filter(|s| s.samples >= self.config.min_slot_samples).collect();
+        if trusted.is_empty() {
+            return None;
+        }
+        let peak_queries = trusted.iter().map(|s| s.queries_per_minute).fold(0.0, f64::max);
+        let peak_ingest = trusted.iter().map(|s| s.ingest_rows_per_minute).fold(0.0, f64::max);
+
+        let slot = self.slot_of(at);
+        let load = slots[slot];
+        let (queries, ingest) = if load.samples >= self.config.min_slot_samples {
+            (load.queries_per_minute, load.ingest_rows_per_minute)
+        } else {
+            // Not learned yet: the average of the slots that are
+            let n = trusted.len() as f64;
+            (
+                trusted.iter().map(|s| s.queries_per_minute).sum::<f64>() / n,
+                trusted.iter().map(|s| s.ingest_rows_per_minute).sum::<f64>() / n,
+            )
+        };
+        let ratio = |value: f64, peak: f64| if peak > 0.0 { value / peak } else { 0.0 };
+        let min_samples = self.config.min_slot_samples.max(1) as f64;
+        Some(LoadForecast {
+            at,
+            slot,
+            queries_per_minute: queries,
+            ingest_rows_per_minute: ingest,
+            load_ratio: ratio(queries, peak_queries).max(ratio(ingest, peak_ingest)).clamp(0.0, 1.0),
+            confidence: load.samples as f64 / (load.samples as f64 + min_samples),
+        })
+    }
+
+    /// Forecast the next `hours` slot by slot, from `from` (Unix seconds)
+    pub fn forecast_range(&self, from: u64, hours: u32) -> Vec<LoadForecast> {
+        let step = self.config.slot_minutes as u64 * 60;
+        let slots = (hours as u64 * 3600).div_ceil(step);
+        (0..slots).filter_map(|i| self.forecast(from + i * step)).collect()
+    }
+
+    /// Sizes for the busier of the load now and `lead_minutes` ahead
+    pub fn plan(&self, now: &LoadForecast, ahead: &LoadForecast) -> ResourcePlan {
+        let config = &self.config;
+        let ratio = now.load_ratio.max(ahead.load_ratio);
+        let threads = config.min_threads as f64 + ratio * config.max_threads.saturating_sub(config.min_threads) as f64;
+        let cache = config.min_cache_bytes as f64 + ratio * config.max_cache_bytes.saturating_sub(config.min_cache_bytes) as f64;
+        let compaction = if ratio >= config.peak_ratio {
+            CompactionMode::Deferred
+        } else if ratio <= config.quiet_ratio {
+            CompactionMode::Aggressive
+        } else {
+            CompactionMode::Normal
+        };
+        ResourcePlan {
+            thread_pool_size: (threads.round() as usize).clamp(config.min_threads, config.max_threads.max(config.min_threads)),
+            cache_budget_bytes: cache.round() as u64,
+            compaction,
+        }
+    }
+
+    /// Forecast from `now` (Unix seconds) and apply the resource changes the
+    /// forecast calls for. Returns the actions taken.
+    pub fn tick(&self, now: u64) -> Vec<AppliedScalingAction> {
+        let Some(current) = self.forecast(now) else {
+            return Vec::new();
+        };
+        let Some(ahead) = self.forecast(now + self.config.lead_minutes as u64 * 60) else {
+            return Vec::new();
+        };
+        let target = self.plan(&current, &ahead);
+        let (previous, failed) = {
+            let state = self.state.read();
+            (state.plan, state.failed.clone())
+        };
+        let retry = |resource: ScalingResource| failed.contains(&resource);
+        let reason = if ahead.load_ratio > current.load_ratio {
+            format!(
+                "Load forecast to reach {:.0}% of peak in {} minutes ({:.0}% now)",
+                ahead.load_ratio * 100.0, self.config.lead_minutes, current.load_ratio * 100.0
+            )
+        } else {
+            format!(
+                "Load at {:.0}% of peak, {:.0}% forecast in {} minutes",
+                current.load_ratio * 100.0, ahead.load_ratio * 100.0, self.config.lead_minutes
+            )
+        };
+
+        let changed = |from: f64, to: f64| (to - from).abs() > from.abs() * self.config.hysteresis;
+        let mut applied = previous.unwrap_or(target);
+        let mut actions = Vec::new();
+        let mut act = |resource: ScalingResource, from: Option<String>, to: String, result: Result<()>| {
+            let error = result.err().map(|e| e.to_string());
+            if let Some(ref e) = error {
+                warn!("Predictive scaling failed to change {:?} to {}: {}", resource, to, e);
+            } else {
+                info!("Predictive scaling: {:?} {} -> {} ({})", resource, from.as_deref().unwrap_or("unset"), to, reason);
+            }
+            let ok = error.is_none();
+            actions.push(AppliedScalingAction {
+                timestamp: now,
+                resource,
+                from,
+                to,
+                reason: reason.clone(),
+                error,
+            });
+            ok
+        };
+
+        let threads_from = previous.map(|p| p.thread_pool_size);
+        if retry(ScalingResource::ThreadPool)
+            || threads_from.is_none_or(|from| from != target.thread_pool_size && changed(from as f64, target.thread_pool_size as f64))
+        {
+            let result = self.actuate(|a| a.resize_thread_pool(target.thread_pool_size));
+            if act(ScalingResource::ThreadPool, threads_from.map(|t| t.to_string()), target.thread_pool_size.to_string(), result) {
+                applied.thread_pool_size = target.thread_pool_size;
+            }
+        }
+        let cache_from = previous.map(|p| p.cache_budget_bytes);
+        if retry(ScalingResource::CacheBudget)
+            || cache_from.is_none_or(|from| from != target.cache_budget_bytes && changed(from as f64, target.cache_budget_bytes as f64))
+        {
+            let result = self.actuate(|a| a.set_cache_budget(target.cache_budget_bytes));
+            if act(ScalingResource::CacheBudget, cache_from.map(|b| b.to_string()), target.cache_budget_bytes.to_string(), result) {
+                applied.cache_budget_bytes = target.cache_budget_bytes;
+            }
+        }
+        let compaction_from = previous.map(|p| p.compaction);
+        if retry(ScalingResource::Compaction) || compaction_from != Some(target.compaction) {
+            let result = self.actuate(|a| a.set_compaction_mode(target.compaction));
+            if act(ScalingResource::Compaction, compaction_from.map(|m| m.to_string()), target.compaction.to_string(), result) {
+                applied.compaction = target.compaction;
+            }
+        }
+
+        {
+            let mut state = self.state.write();
+            state.now = Some(current);
+            state.ahead = Some(ahead);
+            state.plan = Some(applied);
+            state.failed = actions.iter().filter(|a| a.error.is_some()).map(|a| a.resource).collect();
+            for action in &actions {
+                if action.error.is_some() {
+                    state.actions_failed += 1;
+                } else {
+                    state.actions_applied += 1;
+                }
+                state.actions.push_back(action.clone());
+            }
+            while state.actions.len() > self.config.max_actions {
+                state.actions.pop_front();
+            }
+        }
+        if let Err(e) = self.save_profile() {
+            warn!("Failed to save load profile: {}", e);
+        }
+        actions
+    }
+
+    pub fn status(&self) -> WorkloadScalingStatus {
+        let state = self.state.read();
+        WorkloadScalingStatus {
+            samples: state.samples,
+            now: state.now.clone(),
+            ahead: state.ahead.clone(),
+            plan: state.plan,
+            actions_applied: state.actions_applied,
+            actions_failed: state.actions_failed,
+        }
+    }
+
+    /// Most recent actions first
+    pub fn actions(&self, limit: usize) -> Vec<AppliedScalingAction> {
+        self.state.read().actions.iter().rev().take(limit).cloned().collect()
+    }
+
+    fn actuate(&self, change: impl FnOnce(&dyn ResourceActuator) -> Result<()>) -> Result<()> {
+        match &self.actuator {
+            Some(actuator) => change(actuator.as_ref()),
+            None => Ok(()),
+        }
+    }
+
+    fn slot_of(&self, at: u64) -> usize {
+        ((at / 60) % 1440 / self.config.slot_minutes as u64) as usize
+    }
+
+    fn save_profile(&self) -> Result<()> {
+        let Some(path) = &self.config.profile_path else {
+            return Ok(());
+        };
+        let bytes = serde_json::to_vec(&*self.slots.read()).map_err(|e| Error::Serialization(e.to_string()))?;
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, bytes)
+            .and_then(|_| std::fs::rename(&tmp, path))
+            .map_err(|e| Error::Storage(format!("Failed to write load profile: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: u64 = 86_400;
+
+    fn config() -> WorkloadScalingConfig {
+        WorkloadScalingConfig {
+            min_threads: 2,
+            max_threads: 10,
+            min_cache_bytes: 100,
+            max_cache_bytes: 1_100,
+            ..Default::default()
+        }
+    }
+
+    /// Two days of 15 minute samples: 10 queries/min, 100 from 14:00 to 15:00
+    fn learn(scaler: &WorkloadScaler) {
+        for day in 0..2 {
+            for slot in 0..96u64 {
+                let end = day * DAY + (slot + 1) * 900;
+                let busy = (56..60).contains(&slot);
+                scaler.record(WorkloadSample {
+                    timestamp: end,
+                    interval_secs: 900,
+                    queries: if busy { 1_500 } else { 150 },
+                    ingested_rows: 0,
+                });
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct Recorder {
+        calls: parking_lot::Mutex<Vec<String>>,
+        fail_threads: bool,
+    }
+
+    impl ResourceActuator for Recorder {
+        fn resize_thread_pool(&self, threads: usize) -> Result<()> {
+            if self.fail_threads {
+                return Err(Error::Storage("pool busy".to_string()));
+            }
+            self.calls.lock().push(format!("threads={}", threads));
+            Ok(())
+        }
+
+        fn set_cache_budget(&self, bytes: u64) -> Result<()> {
+            self.calls.lock().push(format!("cache={}", bytes));
+            Ok(())
+        }
+
+        fn set_compaction_mode(&self, mode: CompactionMode) -> Result<()> {
+            self.calls.lock().push(format!("compaction={}", mode));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_forecast_follows_time_of_day() {
+        let scaler = WorkloadScaler::new(config()).unwrap();
+        assert!(scaler.forecast(0).is_none());
+        learn(&scaler);
+
+        let busy = scaler.forecast(2 * DAY + 14 * 3600 + 600).unwrap();
+        assert!((busy.queries_per_minute - 100.0).abs() < 1e-9);
+        assert!((busy.load_ratio - 1.0).abs() < 1e-9);
+        assert!(busy.confidence > 0.4);
+        let quiet = scaler.forecast(2 * DAY + 3 * 3600).unwrap();
+        assert!((quiet.load_ratio - 0.1).abs() < 1e-9);
+
+        // 24 hours of 15 minute slots
+        assert_eq!(scaler.forecast_range(0, 24).len(), 96);
+    }
+
+    #[test]
+    fn test_resources_scale_ahead_of_peak() {
+        let recorder = Arc::new(Recorder::default());
+        let scaler = WorkloadScaler::new(config()).unwrap().with_actuator(recorder.clone());
+        learn(&scaler);
+
+        // 03:00: quiet now and ahead
+        let actions = scaler.tick(2 * DAY + 3 * 3600);
+        assert_eq!(actions.len(), 3);
+        assert_eq!(*recorder.calls.lock(), vec!["threads=3", "cache=200", "compaction=aggressive"]);
+
+        // 13:40: the 14:00 peak is within the lead time
+        recorder.calls.lock().clear();
+        let actions = scaler.tick(2 * DAY + 13 * 3600 + 40 * 60);
+        assert!(actions.iter().all(|a| a.error.is_none() && a.from.is_some()));
+        assert_eq!(*recorder.calls.lock(), vec!["threads=10", "cache=1100", "compaction=deferred"]);
+        let status = scaler.status();
+        assert_eq!(status.plan.unwrap().thread_pool_size, 10);
+        assert!(status.ahead.unwrap().load_ratio > status.now.unwrap().load_ratio);
+        assert_eq!(status.actions_applied, 6);
+
+        // Nothing changes on the next tick
+        recorder.calls.lock().clear();
+        assert!(scaler.tick(2 * DAY + 13 * 3600 + 41 * 60).is_empty());
+        assert_eq!(scaler.actions(2)[0].resource, ScalingResource::Compaction);
+    }
+
+    #[test]
+    fn test_failed_actions_keep_previous_size() {
+        let recorder = Arc::new(Recorder { fail_threads: true, ..Default::default() });
+        let scaler = WorkloadScaler::new(config()).unwrap().with_actuator(recorder);
+        learn(&scaler);
+
+        scaler.tick(2 * DAY + 13 * 3600 + 40 * 60);
+        let status = scaler.status();
+        assert_eq!(status.actions_failed, 1);
+        assert_eq!(status.actions_applied, 2);
+        let failed = scaler.actions(10).into_iter().find(|a| a.resource == ScalingResource::ThreadPool).unwrap();
+        assert_eq!(failed.error.as_deref(), Some("Storage error: pool busy"));
+
+        // Failed changes are retried on the next tick, even without a new target
+        let actions = scaler.tick(2 * DAY + 13 * 3600 + 41 * 60);
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].resource, ScalingResource::ThreadPool);
+        assert_eq!(scaler.status().actions_failed, 2);
+    }
+
+    #[test]
+    fn test_profile_survives_restart() {
+        let path = std::env::temp_dir().join(format!("narayana_load_profile_{}.json", uuid::Uuid::new_v4()));
+        let config = WorkloadScalingConfig { profile_path: Some(path.clone()), ..config() };
+        let scaler = WorkloadScaler::new(config.clone()).unwrap();
+        learn(&scaler);
+        scaler.tick(2 * DAY);
+
+        let restarted = WorkloadScaler::new(config.clone()).unwrap();
+        let forecast = restarted.forecast(14 * 3600).unwrap();
+        assert!((forecast.queries_per_minute - 100.0).abs() < 1e-9);
+
+        // A different slot length starts afresh
+        let resliced = WorkloadScaler::new(WorkloadScalingConfig { slot_minutes: 30, ..config }).unwrap();
+        assert!(resliced.forecast(14 * 3600).is_none());
+        assert!(WorkloadScaler::new(WorkloadScalingConfig { slot_minutes: 7, ..Default::default() }).is_err());
+        let _ = std::fs::remove_file(path);
+    }
+}
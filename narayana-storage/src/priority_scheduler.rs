@@ -0,0 +1,477 @@
+// Priority-class task scheduler
+// CPU-bound work is submitted in one of three classes: realtime (robot control
+// loops), interactive (queries) and background (compaction, backups). Each
+// worker keeps a deque per class and steals from the others when its own run
+// dry. Some workers only ever run realtime tasks and background tasks are
+// capped to a number of workers, so a burst of compaction can't hold up a
+// control tick. A class whose tasks have waited past the starvation threshold
+// is served ahead of the higher ones.
+
+use anyhow::{anyhow, Result};
+use crossbeam::deque::{Injector, Steal, Stealer, Worker};
+use parking_lot::{Condvar, Mutex};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::panic::AssertUnwindSafe;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+
+/// How long an idle worker sleeps before looking for starved or stealable work again
+const IDLE_WAIT: Duration = Duration::from_millis(10);
+
+/// Priority class of a task, highest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskClass {
+    /// Control loops with a deadline every tick
+    Realtime,
+    /// Work someone is waiting on, like queries
+    Interactive,
+    /// Maintenance that can wait, like compaction
+    Background,
+}
+
+impl TaskClass {
+    pub const ALL: [TaskClass; 3] = [TaskClass::Realtime, TaskClass::Interactive, TaskClass::Background];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Scheduler configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchedulerConfig {
+    /// Worker threads, reserved ones included
+    pub workers: usize,
+    /// Workers that only run realtime tasks
+    pub realtime_reserved_workers: usize,
+    /// Most background tasks running at once
+    pub background_max_workers: usize,
+    /// How long a class with queued tasks may go unserved before it runs ahead
+    /// of higher classes
+    pub starvation_threshold: Duration,
+    /// Thread name prefix
+    pub thread_name_prefix: String,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        (&narayana_core::config::TaskSchedulerConfigSection::default()).into()
+    }
+}
+
+impl From<&narayana_core::config::TaskSchedulerConfigSection> for SchedulerConfig {
+    fn from(config: &narayana_core::config::TaskSchedulerConfigSection) -> Self {
+        let workers = match config.workers {
+            0 => num_cpus::get().max(2),
+            n => n,
+        };
+        let general = workers.saturating_sub(config.realtime_reserved_workers).max(1);
+        Self {
+            workers,
+            realtime_reserved_workers: config.realtime_reserved_workers,
+            background_max_workers: config.background_max_workers.unwrap_or(general / 2).max(1),
+            starvation_threshold: Duration::from_millis(config.starvation_threshold_ms),
+            thread_name_prefix: "narayana-sched".to_string(),
+        }
+    }
+}
+
+/// Counters of one class
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ClassMetrics {
+    pub submitted: u64,
+    pub completed: u64,
+    pub panicked: u64,
+    /// Tasks waiting now
+    pub queued: usize,
+    /// Tasks running now
+    pub running: usize,
+    pub avg_wait_us: u64,
+    pub max_wait_us: u64,
+    /// Worker time spent running the class's tasks
+    pub busy_us: u64,
+    /// Share of all worker time since the scheduler started spent on the class
+    pub utilization: f64,
+    /// Tasks run ahead of a higher class because the class was starving
+    pub promoted: u64,
+}
+
+/// Scheduler counters by class
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchedulerMetrics {
+    pub workers: usize,
+    pub realtime_reserved_workers: usize,
+    pub classes: HashMap<TaskClass, ClassMetrics>,
+}
+
+struct Task {
+    class: TaskClass,
+    enqueued_at: Instant,
+    run: Box<dyn FnOnce() + Send>,
+}
+
+#[derive(Default)]
+struct ClassCounters {
+    submitted: AtomicU64,
+    completed: AtomicU64,
+    panicked: AtomicU64,
+    queued: AtomicUsize,
+    running: AtomicUsize,
+    wait_us_total: AtomicU64,
+    wait_us_max: AtomicU64,
+    busy_us: AtomicU64,
+    promoted: AtomicU64,
+    /// Microseconds since start when the class was last served or began
+    /// waiting; 0 while nothing is queued
+    waiting_since_us: AtomicU64,
+}
+
+struct Shared {
+    config: SchedulerConfig,
+    injectors: [Injector<Task>; 3],
+    stealers: Vec<[Stealer<Task>; 3]>,
+    counters: [ClassCounters; 3],
+    started: Instant,
+    shutdown: AtomicBool,
+    sleep: Mutex<()>,
+    wake: Condvar,
+}
+
+/// A worker's deques, one per class
+type Deques = [Worker<Task>; 3];
+
+thread_local! {
+    /// The scheduler a worker thread belongs to and its deques, so tasks
+    /// submitted from a task stay on that worker unless stolen
+    static LOCAL: RefCell<Option<(usize, Rc<Deques>)>> = const { RefCell::new(None) };
+}
+
+/// Work-stealing scheduler with priority classes
+pub struct PriorityScheduler {
+    shared: Arc<Shared>,
+    threads: Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl PriorityScheduler {
+    /// Start the scheduler's worker threads
+    pub fn new(config: SchedulerConfig) -> Result<Self> {
+        if config.workers == 0 {
+            return Err(anyhow!("Scheduler needs at least one worker"));
+        }
+        if config.realtime_reserved_workers >= config.workers {
+            return Err(anyhow!(
+                "Scheduler reserves {} of {} workers for realtime tasks; at least one must be left for the others",
+                config.realtime_reserved_workers, config.workers
+            ));
+        }
+        if config.background_max_workers == 0 {
+            return Err(anyhow!("background_max_workers must be at least 1"));
+        }
+
+        let deques: Vec<Deques> = (0..config.workers)
+            .map(|_| [Worker::new_fifo(), Worker::new_fifo(), Worker::new_fifo()])
+            .collect();
+        let stealers = deques
+            .iter()
+            .map(|d| [d[0].stealer(), d[1].stealer(), d[2].stealer()])
+            .collect();
+        let shared = Arc::new(Shared {
+            config,
+            injectors: [Injector::new(), Injector::new(), Injector::new()],
+            stealers,
+            counters: Default::default(),
+            started: Instant::now(),
+            shutdown: AtomicBool::new(false),
+            sleep: Mutex::new(()),
+            wake: Condvar::new(),
+        });
+
+        let scheduler = Self { shared: shared.clone(), threads: Mutex::new(Vec::new()) };
+        for (index, local) in deques.into_iter().enumerate() {
+            let shared = shared.clone();
+            let reserved = index < shared.config.realtime_reserved_workers;
+            let name = format!(
+                "{}-{}-{}",
+                shared.config.thread_name_prefix,
+                if reserved { "rt" } else { "w" },
+                index
+            );
+            let handle = std::thread::Builder::new()
+                .name(name)
+                .spawn(move || shared.run_worker(index, reserved, local))
+                .map_err(|e| anyhow!("Failed to start scheduler worker: {}", e))?;
+            scheduler.threads.lock().push(handle);
+        }
+        Ok(scheduler)
+    }
+
+    /// Run `f` as a `class` task. The receiver gets its result, or an error if
+    /// it panicked or the scheduler shut down before running it.
+    pub fn submit<F, R>(&self, class: TaskClass, f: F) -> oneshot::Receiver<R>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let (tx, rx) = oneshot::channel();
+        self.spawn(class, move || {
+            let _ = tx.send(f());
+        });
+        rx
+    }
+
+    /// Run `f` as a `class` task without waiting for it
+    pub fn spawn<F>(&self, class: TaskClass, f: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.shared.push(Task { class, enqueued_at: Instant::now(), run: Box::new(f) });
+    }
+
+    /// Counters by class
+    pub fn metrics(&self) -> SchedulerMetrics {
+        let shared = &self.shared;
+        let capacity_us = (shared.started.elapsed().as_micros() as f64 * shared.config.workers as f64).max(1.0);
+        let classes = TaskClass::ALL
+            .iter()
+            .map(|class| {
+                let c = &shared.counters[class.index()];
+                let served = c.completed.load(Ordering::Relaxed) + c.panicked.load(Ordering::Relaxed);
+                let busy_us = c.busy_us.load(Ordering::Relaxed);
+                let metrics = ClassMetrics {
+                    submitted: c.submitted.load(Ordering::Relaxed),
+                    completed: c.completed.load(Ordering::Relaxed),
+                    panicked: c.panicked.load(Ordering::Relaxed),
+                    queued: c.queued.load(Ordering::Relaxed),
+                    running: c.running.load(Ordering::Relaxed),
+                    avg_wait_us: c.wait_us_total.load(Ordering::Relaxed).checked_div(served).unwrap_or(0),
+                    max_wait_us: c.wait_us_max.load(Ordering::Relaxed),
+                    busy_us,
+                    utilization: (busy_us as f64 / capacity_us).min(1.0),
+                    promoted: c.promoted.load(Ordering::Relaxed),
+                };
+                (*class, metrics)
+            })
+            .collect();
+        SchedulerMetrics {
+            workers: shared.config.workers,
+            realtime_reserved_workers: shared.config.realtime_reserved_workers,
+            classes,
+        }
+    }
+
+    /// Scheduler configuration
+    pub fn config(&self) -> &SchedulerConfig {
+        &self.shared.config
+    }
+
+    /// Stop the workers and wait for the tasks they are running; queued tasks
+    /// are dropped. Blocks, so call it off the async runtime.
+    pub fn shutdown(&self) {
+        self.shared.stop();
+        let current = std::thread::current().id();
+        let threads: Vec<JoinHandle<()>> = self.threads.lock().drain(..).collect();
+        for handle in threads {
+            // A task shutting down its own scheduler can't wait for itself
+            if handle.thread().id() != current {
+                let _ = handle.join();
+            }
+        }
+    }
+}
+
+impl Drop for PriorityScheduler {
+    fn drop(&mut self) {
+        // Workers finish their current task and exit; nothing waits for them
+        self.shared.stop();
+    }
+}
+
+impl Shared {
+    fn id(&self) -> usize {
+        self as *const Self as usize
+    }
+
+    fn now_us(&self) -> u64 {
+        // Never 0, which means "nothing waiting"
+        (self.started.elapsed().as_micros() as u64).max(1)
+    }
+
+    fn stop(&self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        let _guard = self.sleep.lock();
+        self.wake.notify_all();
+    }
+
+    fn push(&self, task: Task) {
+        if self.shutdown.load(Ordering::SeqCst) {
+            return;
+        }
+        let index = task.class.index();
+        let counters = &self.counters[index];
+        counters.submitted.fetch_add(1, Ordering::Relaxed);
+        if counters.queued.fetch_add(1, Ordering::SeqCst) == 0 {
+            let _ = counters.waiting_since_us.compare_exchange(0, self.now_us(), Ordering::SeqCst, Ordering::SeqCst);
+        }
+        let task = LOCAL.with(|local| match &*local.borrow() {
+            Some((id, deques)) if *id == self.id() => {
+                deques[index].push(task);
+                None
+            }
+            _ => Some(task),
+        });
+        if let Some(task) = task {
+            self.injectors[index].push(task);
+        }
+        let _guard = self.sleep.lock();
+        self.wake.notify_all();
+    }
+
+    fn run_worker(self: Arc<Self>, index: usize, reserved: bool, local: Deques) {
+        let local = Rc::new(local);
+        LOCAL.with(|l| *l.borrow_mut() = Some((self.id(), local.clone())));
+        while !self.shutdown.load(Ordering::SeqCst) {
+            match self.next_task(index, reserved, &local) {
+                Some(task) => self.run(task),
+                None => self.park(reserved),
+            }
+        }
+        LOCAL.with(|l| *l.borrow_mut() = None);
+    }
+
+    /// Whether the class has queued tasks and has gone unserved past the threshold
+    fn starving(&self, class: TaskClass, now_us: u64) -> Option<u64> {
+        let counters = &self.counters[class.index()];
+        let since = counters.waiting_since_us.load(Ordering::SeqCst);
+        let threshold = self.config.starvation_threshold.as_micros() as u64;
+        (counters.queued.load(Ordering::SeqCst) > 0 && since != 0 && now_us.saturating_sub(since) >= threshold)
+            .then_some(since)
+    }
+
+    fn next_task(&self, index: usize, reserved: bool, local: &Deques) -> Option<Task> {
+        let classes: &[TaskClass] = if reserved { &[TaskClass::Realtime] } else { &TaskClass::ALL };
+        let now_us = self.now_us();
+        // Starving classes first, the longest unserved first; then by priority
+        let mut order: Vec<(TaskClass, Option<u64>)> = classes.iter().map(|c| (*c, self.starving(*c, now_us))).collect();
+        order.sort_by_key(|(class, starving)| (starving.is_none(), starving.unwrap_or(0), class.index()));
+
+        for (class, starving) in order {
+            let background = class == TaskClass::Background;
+            if background && !self.reserve_background() {
+                continue;
+            }
+            let Some(task) = self.take(index, class, local) else {
+                if background {
+                    self.counters[class.index()].running.fetch_sub(1, Ordering::SeqCst);
+                }
+                continue;
+            };
+            let counters = &self.counters[class.index()];
+            if !background {
+                counters.running.fetch_add(1, Ordering::SeqCst);
+            }
+            let left = counters.queued.fetch_sub(1, Ordering::SeqCst) - 1;
+            counters.waiting_since_us.store(if left > 0 { now_us } else { 0 }, Ordering::SeqCst);
+            let wait_us = task.enqueued_at.elapsed().as_micros() as u64;
+            counters.wait_us_total.fetch_add(wait_us, Ordering::Relaxed);
+            counters.wait_us_max.fetch_max(wait_us, Ordering::Relaxed);
+            let jumped_queue = TaskClass::ALL[..class.index()]
+                .iter()
+                .any(|higher| self.counters[higher.index()].queued.load(Ordering::SeqCst) > 0);
+            if starving.is_some() && jumped_queue {
+                counters.promoted.fetch_add(1, Ordering::Relaxed);
+            }
+            return Some(task);
+        }
+        None
+    }
+
+    /// Claim a background slot if one is free
+    fn reserve_background(&self) -> bool {
+        let running = &self.counters[TaskClass::Background.index()].running;
+        running
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| (n < self.config.background_max_workers).then_some(n + 1))
+            .is_ok()
+    }
+
+    /// A task of `class` from this worker's deque, the shared queue or another worker
+    fn take(&self, index: usize, class: TaskClass, local: &Deques) -> Option<Task> {
+        let deque = &local[class.index()];
+        if let Some(task) = deque.pop() {
+            return Some(task);
+        }
+        loop {
+            let mut retry = false;
+            match self.injectors[class.index()].steal_batch_and_pop(deque) {
+                Steal::Success(task) => return Some(task),
+                Steal::Retry => retry = true,
+                Steal::Empty => {}
+            }
+            for (other, stealers) in self.stealers.iter().enumerate() {
+                if other == index {
+                    continue;
+                }
+                match stealers[class.index()].steal() {
+                    Steal::Success(task) => return Some(task),
+                    Steal::Retry => retry = true,
+                    Steal::Empty => {}
+                }
+            }
+            if !retry {
+                return None;
+            }
+        }
+    }
+
+    fn run(&self, task: Task) {
+        let class = task.class;
+        let started = Instant::now();
+        // A panicking task drops its result sender; the worker carries on
+        let outcome = std::panic::catch_unwind(AssertUnwindSafe(task.run));
+        let counters = &self.counters[class.index()];
+        counters.busy_us.fetch_add(started.elapsed().as_micros() as u64, Ordering::Relaxed);
+        match outcome {
+            Ok(()) => counters.completed.fetch_add(1, Ordering::Relaxed),
+            Err(_) => {
+                tracing::warn!("{:?} task panicked", class);
+                counters.panicked.fetch_add(1, Ordering::Relaxed)
+            }
+        };
+        counters.running.fetch_sub(1, Ordering::SeqCst);
+        // A background slot opened up
+        if class == TaskClass::Background && self.counters[class.index()].queued.load(Ordering::SeqCst) > 0 {
+            let _guard = self.sleep.lock();
+            self.wake.notify_all();
+        }
+    }
+
+    /// Whether a worker could find a task to run now
+    fn has_runnable(&self, reserved: bool) -> bool {
+        let queued = |class: TaskClass| self.counters[class.index()].queued.load(Ordering::SeqCst) > 0;
+        if queued(TaskClass::Realtime) {
+            return true;
+        }
+        if reserved {
+            return false;
+        }
+        let background_free = self.counters[TaskClass::Background.index()].running.load(Ordering::SeqCst)
+            < self.config.background_max_workers;
+        queued(TaskClass::Interactive) || (queued(TaskClass::Background) && background_free)
+    }
+
+    fn park(&self, reserved: bool) {
+        let mut guard = self.sleep.lock();
+        if self.shutdown.load(Ordering::SeqCst) || self.has_runnable(reserved) {
+            drop(guard);
+            std::thread::yield_now();
+            return;
+        }
+        self.wake.wait_for(&mut guard, IDLE_WAIT);
+    }
+}
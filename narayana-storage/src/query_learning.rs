@@ -85,6 +85,10 @@ pub struct QueryLearningEngine {
     learning_window: Duration,
     min_frequency_threshold: u64,
     auto_apply_optimizations: bool,
+    recommendations: Arc<DashMap<String, Recommendation>>,
+    /// Sort keys applied per table
+    sort_keys: Arc<DashMap<String, Vec<String>>>,
+    applier: Option<Arc<dyn RecommendationApplier>>,
 }
 
 /// Query execution record
@@ -159,9 +163,19 @@ impl QueryLearningEngine {
             learning_window: Duration::from_secs(3600), // 1 hour
             min_frequency_threshold: 10, // Minimum queries before learning
             auto_apply_optimizations: true,
+            recommendations: Arc::new(DashMap::new()),
+            sort_keys: Arc::new(DashMap::new()),
+            applier: None,
         }
     }
 
+    /// Carry out applied recommendations through `applier` (without one,
+    /// applying only updates what the engine plans with)
+    pub fn with_applier(mut self, applier: Arc<dyn RecommendationApplier>) -> Self {
+        self.applier = Some(applier);
+        self
+    }
+
     /// Enable learning mode
     pub fn enable(&self) {
         *self.enabled.write() = true;
@@ -262,6 +276,11 @@ impl QueryLearningEngine {
             pattern.tables_accessed.insert(table.clone());
         }
 
+        let sort_fields = parse_order_by(&execution.query_text);
+        if !sort_fields.is_empty() {
+            pattern.sort_fields = sort_fields;
+        }
+
         // Learn filter patterns
         self.learn_filter_patterns(&mut pattern, execution)?;
 
@@ -376,9 +395,12 @@ impl QueryLearningEngine {
             // Suggest indexes for frequently filtered columns
             for filter in &pattern.filters {
                 if filter.selectivity < 0.1 { // High selectivity
-                    hints.push(OptimizationHint::CreateIndex {
-                        column: filter.column.clone(),
-                        index_type: "btree".to_string(),
+                    let column = column_name(&filter.column).to_string();
+                    let indexed = pattern.tables_accessed.iter().any(|table| self.has_index(table, &column));
+                    hints.push(if indexed {
+                        OptimizationHint::UseIndex { column, index_type: "btree".to_string() }
+                    } else {
+                        OptimizationHint::CreateIndex { column, index_type: "btree".to_string() }
                     });
                 }
             }
@@ -631,6 +653,7 @@ impl QueryLearningEngine {
     pub fn clear_patterns(&self) {
         self.patterns.clear();
         self.plan_cache.clear();
+        self.recommendations.retain(|_, r| r.status == RecommendationStatus::Applied);
         {
             let mut stats = self.statistics.write();
             stats.patterns_learned = 0;
@@ -709,3 +732,406 @@ pub struct PerformanceReport {
     pub indexes_created: u64,
     pub cache_hit_rate: f64,
 }
+
+// ============================================
+// Advisor - index, sort key and materialized view recommendations
+// ============================================
+
+/// Filters returning at most this share of scanned rows are worth an index
+const MAX_INDEX_SELECTIVITY: f64 = 0.1;
+/// Executions of a query pattern before it's worth a materialized view
+const MIN_VIEW_FREQUENCY: u64 = 100;
+/// Average execution time (ms) before a pattern is worth a materialized view
+const MIN_VIEW_EXECUTION_MS: f64 = 50.0;
+/// Rough share of a sorted query's time saved by a matching sort key
+const SORT_KEY_SAVINGS: f64 = 0.3;
+/// Rough share of a query's time saved by reading a materialized view
+const VIEW_SAVINGS: f64 = 0.9;
+
+/// A concrete change recommended by the advisor
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RecommendationAction {
+    CreateIndex { table: String, column: String, index_type: String },
+    SortKey { table: String, columns: Vec<String> },
+    MaterializedView { name: String, query_template: String, tables: Vec<String> },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecommendationStatus {
+    Pending,
+    Applied,
+    Dismissed,
+    /// The applier rejected it; it can be applied again
+    Failed,
+}
+
+impl RecommendationStatus {
+    pub fn parse(status: &str) -> Option<Self> {
+        match status {
+            "pending" => Some(Self::Pending),
+            "applied" => Some(Self::Applied),
+            "dismissed" => Some(Self::Dismissed),
+            "failed" => Some(Self::Failed),
+            _ => None,
+        }
+    }
+}
+
+/// Executions of a pattern when its recommendation was applied
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatternBaseline {
+    pub pattern_id: String,
+    pub executions: u64,
+    pub average_execution_time_ms: f64,
+}
+
+/// Affected queries before and after a recommendation was applied
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecommendationImpact {
+    pub executions_before: u64,
+    pub average_before_ms: f64,
+    pub executions_after: u64,
+    /// None until an affected query runs again
+    pub average_after_ms: Option<f64>,
+    /// Positive when affected queries got faster
+    pub improvement_percentage: Option<f64>,
+}
+
+/// A recommendation and its review state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Recommendation {
+    pub id: String,
+    pub action: RecommendationAction,
+    pub reason: String,
+    /// Query patterns it would speed up
+    pub patterns: Vec<String>,
+    /// Rough execution time (ms) the recorded workload would have saved
+    pub estimated_benefit_ms: f64,
+    pub status: RecommendationStatus,
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub applied_at: Option<u64>,
+    pub error: Option<String>,
+    pub baseline: Vec<PatternBaseline>,
+    /// Measured when read, for applied recommendations
+    pub impact: Option<RecommendationImpact>,
+}
+
+/// Carries out applied recommendations (creates the index, re-sorts the
+/// table, builds the view)
+#[async_trait::async_trait]
+pub trait RecommendationApplier: Send + Sync {
+    async fn apply(&self, action: &RecommendationAction) -> Result<()>;
+}
+
+impl QueryLearningEngine {
+    /// Tell the advisor about an index that already exists
+    pub fn register_index(&self, table: &str, column: &str) {
+        self.index_suggester.existing_indexes.write().insert(format!("{}.{}", table, column));
+    }
+
+    fn has_index(&self, table: &str, column: &str) -> bool {
+        self.index_suggester.existing_indexes.read().contains(&format!("{}.{}", table, column))
+    }
+
+    /// Analyze the learned patterns and refresh the recommendations
+    ///
+    /// Reviewed (applied or dismissed) recommendations keep their state;
+    /// pending ones the workload no longer supports are dropped. Returns the
+    /// recommendations, biggest estimated benefit first.
+    pub fn recommend(&self) -> Vec<Recommendation> {
+        let now = now_secs();
+        let mut current = HashSet::new();
+        for (action, reason, patterns, benefit) in self.analyze_workload() {
+            let id = recommendation_id(&action);
+            current.insert(id.clone());
+            self.recommendations.entry(id.clone())
+                .and_modify(|r| {
+                    if r.status == RecommendationStatus::Pending {
+                        r.reason = reason.clone();
+                        r.patterns = patterns.clone();
+                        r.estimated_benefit_ms = benefit;
+                        r.updated_at = now;
+                    }
+                })
+                .or_insert_with(|| Recommendation {
+                    id,
+                    action,
+                    reason,
+                    patterns,
+                    estimated_benefit_ms: benefit,
+                    status: RecommendationStatus::Pending,
+                    created_at: now,
+                    updated_at: now,
+                    applied_at: None,
+                    error: None,
+                    baseline: Vec::new(),
+                    impact: None,
+                });
+        }
+        self.recommendations.retain(|id, r| r.status != RecommendationStatus::Pending || current.contains(id));
+        self.recommendations()
+    }
+
+    /// Recommendations, biggest estimated benefit first, with measured impact
+    pub fn recommendations(&self) -> Vec<Recommendation> {
+        let mut recommendations: Vec<Recommendation> = self.recommendations.iter()
+            .map(|entry| self.with_impact(entry.value().clone()))
+            .collect();
+        recommendations.sort_by(|a, b| b.estimated_benefit_ms.total_cmp(&a.estimated_benefit_ms));
+        recommendations
+    }
+
+    pub fn recommendation(&self, id: &str) -> Option<Recommendation> {
+        self.recommendations.get(id).map(|r| self.with_impact(r.value().clone()))
+    }
+
+    /// Apply a recommendation and start measuring its impact
+    pub async fn apply_recommendation(&self, id: &str) -> Result<Recommendation> {
+        let action = match self.recommendations.get(id) {
+            Some(r) if r.status == RecommendationStatus::Applied => {
+                return Err(Error::Storage("Recommendation already applied".to_string()));
+            }
+            Some(r) => r.action.clone(),
+            None => return Err(Error::Storage("Recommendation not found".to_string())),
+        };
+
+        if let Some(applier) = &self.applier {
+            if let Err(e) = applier.apply(&action).await {
+                warn!("Failed to apply recommendation {}: {}", id, e);
+                if let Some(mut r) = self.recommendations.get_mut(id) {
+                    r.status = RecommendationStatus::Failed;
+                    r.error = Some(e.to_string());
+                    r.updated_at = now_secs();
+                }
+                return Err(e);
+            }
+        }
+
+        match &action {
+            RecommendationAction::CreateIndex { table, column, .. } => {
+                self.register_index(table, column);
+                self.statistics.write().indexes_created += 1;
+            }
+            RecommendationAction::SortKey { table, columns } => {
+                self.sort_keys.insert(table.clone(), columns.clone());
+            }
+            RecommendationAction::MaterializedView { .. } => {}
+        }
+        self.statistics.write().optimizations_applied += 1;
+
+        let mut recommendation = self.recommendations.get_mut(id)
+            .ok_or_else(|| Error::Storage("Recommendation not found".to_string()))?;
+        // Plans cached for the affected patterns predate the change
+        for pattern_id in &recommendation.patterns {
+            self.plan_cache.remove(pattern_id);
+        }
+        recommendation.baseline = recommendation.patterns.iter()
+            .filter_map(|pattern_id| self.patterns.get(pattern_id))
+            .map(|p| PatternBaseline {
+                pattern_id: p.pattern_id.clone(),
+                executions: p.total_executions,
+                average_execution_time_ms: p.average_execution_time_ms,
+            })
+            .collect();
+        let now = now_secs();
+        recommendation.status = RecommendationStatus::Applied;
+        recommendation.applied_at = Some(now);
+        recommendation.updated_at = now;
+        recommendation.error = None;
+        info!("Applied recommendation {} ({:?})", id, recommendation.action);
+        let recommendation = recommendation.clone();
+        Ok(self.with_impact(recommendation))
+    }
+
+    /// Set a recommendation aside; it isn't suggested again unless applied
+    pub fn dismiss_recommendation(&self, id: &str) -> Option<Recommendation> {
+        let mut recommendation = self.recommendations.get_mut(id)?;
+        if recommendation.status != RecommendationStatus::Applied {
+            recommendation.status = RecommendationStatus::Dismissed;
+            recommendation.updated_at = now_secs();
+        }
+        let recommendation = recommendation.clone();
+        Some(self.with_impact(recommendation))
+    }
+
+    /// Recommended actions with their reason, patterns and estimated benefit
+    fn analyze_workload(&self) -> Vec<(RecommendationAction, String, Vec<String>, f64)> {
+        let patterns: Vec<QueryPattern> = self.patterns.iter()
+            .filter(|p| p.frequency >= self.min_frequency_threshold)
+            .map(|p| p.value().clone())
+            .collect();
+
+        // (table, column) -> patterns, benefit, best selectivity
+        let mut indexes: HashMap<(String, String), (Vec<String>, f64, f64)> = HashMap::new();
+        // (table, columns) -> patterns, benefit, executions
+        let mut sort_keys: HashMap<(String, Vec<String>), (Vec<String>, f64, u64)> = HashMap::new();
+        let mut actions = Vec::new();
+
+        for pattern in &patterns {
+            let workload_ms = pattern.frequency as f64 * pattern.average_execution_time_ms;
+            let single_table = match pattern.tables_accessed.len() {
+                1 => pattern.tables_accessed.iter().next().cloned(),
+                _ => None,
+            };
+
+            for filter in pattern.filters.iter().filter(|f| f.selectivity < MAX_INDEX_SELECTIVITY) {
+                let (table, column) = match column_name(&filter.column).split_once('.') {
+                    Some((table, column)) => (Some(table.to_string()), column.to_string()),
+                    None => (single_table.clone(), column_name(&filter.column).to_string()),
+                };
+                let Some(table) = table.filter(|_| !column.is_empty()) else {
+                    continue;
+                };
+                if self.has_index(&table, &column) {
+                    continue;
+                }
+                let entry = indexes.entry((table, column)).or_insert((Vec::new(), 0.0, 1.0));
+                if !entry.0.contains(&pattern.pattern_id) {
+                    entry.0.push(pattern.pattern_id.clone());
+                    entry.1 += workload_ms * (1.0 - filter.selectivity);
+                }
+                entry.2 = entry.2.min(filter.selectivity);
+            }
+
+            if let Some(table) = &single_table {
+                if !pattern.sort_fields.is_empty()
+                    && self.sort_keys.get(table).is_none_or(|key| !key.starts_with(&pattern.sort_fields))
+                {
+                    let entry = sort_keys.entry((table.clone(), pattern.sort_fields.clone())).or_default();
+                    entry.0.push(pattern.pattern_id.clone());
+                    entry.1 += workload_ms * SORT_KEY_SAVINGS;
+                    entry.2 += pattern.frequency;
+                }
+            }
+
+            if pattern.frequency >= MIN_VIEW_FREQUENCY && pattern.average_execution_time_ms >= MIN_VIEW_EXECUTION_MS {
+                let mut tables: Vec<String> = pattern.tables_accessed.iter().cloned().collect();
+                tables.sort();
+                actions.push((
+                    RecommendationAction::MaterializedView {
+                        name: format!("mv_{}", pattern.pattern_id.trim_start_matches("pattern_")),
+                        query_template: pattern.query_template.clone(),
+                        tables,
+                    },
+                    format!(
+                        "Query ran {} times averaging {:.1} ms",
+                        pattern.frequency, pattern.average_execution_time_ms
+                    ),
+                    vec![pattern.pattern_id.clone()],
+                    workload_ms * VIEW_SAVINGS,
+                ));
+            }
+        }
+
+        for ((table, column), (patterns, benefit, selectivity)) in indexes {
+            actions.push((
+                RecommendationAction::CreateIndex { table, column, index_type: "btree".to_string() },
+                format!(
+                    "Filtered by {} quer{} returning as little as {:.2}% of scanned rows",
+                    patterns.len(),
+                    if patterns.len() == 1 { "y" } else { "ies" },
+                    selectivity * 100.0
+                ),
+                patterns,
+                benefit,
+            ));
+        }
+
+        // One sort key per table: the order most executions ask for
+        let mut best: HashMap<String, (Vec<String>, Vec<String>, f64, u64)> = HashMap::new();
+        for ((table, columns), (patterns, benefit, executions)) in sort_keys {
+            let current = best.entry(table).or_insert_with(|| (Vec::new(), Vec::new(), 0.0, 0));
+            if executions > current.3 || (executions == current.3 && columns < current.0) {
+                *current = (columns, patterns, benefit, executions);
+            }
+        }
+        for (table, (columns, patterns, benefit, executions)) in best {
+            actions.push((
+                RecommendationAction::SortKey { table, columns: columns.clone() },
+                format!("{} executions sort by {}", executions, columns.join(", ")),
+                patterns,
+                benefit,
+            ));
+        }
+        actions
+    }
+
+    fn with_impact(&self, mut recommendation: Recommendation) -> Recommendation {
+        if recommendation.status != RecommendationStatus::Applied || recommendation.baseline.is_empty() {
+            return recommendation;
+        }
+        let (mut before, mut before_ms, mut after, mut after_ms) = (0u64, 0.0, 0u64, 0.0);
+        for baseline in &recommendation.baseline {
+            before += baseline.executions;
+            before_ms += baseline.executions as f64 * baseline.average_execution_time_ms;
+            let Some(pattern) = self.patterns.get(&baseline.pattern_id) else {
+                continue;
+            };
+            let total_ms = pattern.total_executions as f64 * pattern.average_execution_time_ms;
+            if pattern.total_executions >= baseline.executions {
+                after += pattern.total_executions - baseline.executions;
+                after_ms += (total_ms - baseline.executions as f64 * baseline.average_execution_time_ms).max(0.0);
+            } else {
+                // Relearned since (patterns were cleared): all of it is after
+                after += pattern.total_executions;
+                after_ms += total_ms;
+            }
+        }
+        let average_before_ms = if before > 0 { before_ms / before as f64 } else { 0.0 };
+        let average_after_ms = (after > 0).then(|| after_ms / after as f64);
+        recommendation.impact = Some(RecommendationImpact {
+            executions_before: before,
+            average_before_ms,
+            executions_after: after,
+            average_after_ms,
+            improvement_percentage: average_after_ms
+                .filter(|_| average_before_ms > 0.0)
+                .map(|after| (average_before_ms - after) / average_before_ms * 100.0),
+        });
+        recommendation
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+fn recommendation_id(action: &RecommendationAction) -> String {
+    use std::collections::hash_map::DefaultHasher;
+    let mut hasher = DefaultHasher::new();
+    action.hash(&mut hasher);
+    format!("rec_{:016x}", hasher.finish())
+}
+
+/// Column of a learned filter ("users.id = 5" -> "users.id")
+fn column_name(filter_column: &str) -> &str {
+    let column = filter_column.trim();
+    let end = column
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '.'))
+        .unwrap_or(column.len());
+    &column[..end]
+}
+
+/// Columns of a query's ORDER BY clause, in order
+fn parse_order_by(query: &str) -> Vec<String> {
+    let lower = query.to_ascii_lowercase();
+    let Some(start) = lower.rfind("order by") else {
+        return Vec::new();
+    };
+    let clause = &query[start + "order by".len()..];
+    let end = [" limit ", " offset ", ";", ")"]
+        .iter()
+        .filter_map(|stop| clause.to_ascii_lowercase().find(stop))
+        .min()
+        .unwrap_or(clause.len());
+    clause[..end]
+        .split(',')
+        .filter_map(|item| item.split_whitespace().next())
+        .map(column_name)
+        .filter(|column| !column.is_empty())
+        .map(str::to_string)
+        .collect()
+}
@@ -0,0 +1,899 @@
+// Storage Quotas - size limits per table, per database and in total
+// Every write goes through QuotaColumnStore, which refuses one that would take
+// its table, the table's database or the whole store past a limit, and refuses
+// all writes while the data directory's disk is nearly full (read-only mode).
+// Usage is recounted from block metadata every `check_interval` and kept
+// current in between from the size of each write. Crossing a warning
+// threshold, refusing a write and entering or leaving read-only mode are
+// published to the registered sinks (e.g. narayana-rde) once per crossing.
+
+use async_trait::async_trait;
+use narayana_core::config::{QuotaLimits, QuotasConfig};
+use narayana_core::{column::Column, schema::Schema, types::TableId, Error, Result};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{error, info, warn};
+
+use crate::block::BlockMetadata;
+use crate::column_store::{ColumnStore, TableWriteGuard};
+use crate::database_manager::DatabaseManager;
+
+/// Quota events kept for the status endpoint
+const MAX_RECENT_EVENTS: usize = 100;
+
+/// What happened
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaEventKind {
+    /// Usage reached `warn_ratio` of a limit
+    Warning,
+    /// A limit is reached; writes to the scope are refused
+    Exceeded,
+    /// Free disk space fell below the threshold; all writes are refused
+    ReadOnly,
+    /// Free disk space recovered; writes are accepted again
+    Writable,
+}
+
+impl QuotaEventKind {
+    /// RDE event name
+    pub fn event_name(&self) -> &'static str {
+        match self {
+            QuotaEventKind::Warning => "quota.warning",
+            QuotaEventKind::Exceeded => "quota.exceeded",
+            QuotaEventKind::ReadOnly => "disk.read_only",
+            QuotaEventKind::Writable => "disk.writable",
+        }
+    }
+}
+
+/// A quota warning, refusal or read-only transition
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuotaEvent {
+    pub kind: QuotaEventKind,
+    /// "total", "database:<name>", "table:<database>.<table>" or "disk"
+    pub scope: String,
+    pub used_bytes: u64,
+    pub used_rows: u64,
+    pub limits: QuotaLimits,
+    pub free_disk_percent: Option<f64>,
+    pub message: String,
+    /// Unix ms
+    pub at: u64,
+}
+
+/// Destination for quota events (e.g. narayana-rde)
+#[async_trait]
+pub trait QuotaSink: Send + Sync {
+    async fn emit(&self, event: &QuotaEvent) -> Result<()>;
+}
+
+/// How close a scope is to its limits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaLevel {
+    Warning,
+    Exceeded,
+}
+
+/// Usage and limits of one scope
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScopeUsage {
+    pub scope: String,
+    pub bytes: u64,
+    pub rows: u64,
+    pub limits: QuotaLimits,
+    pub level: Option<QuotaLevel>,
+}
+
+/// Space on the disk holding the data directory
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DiskSpace {
+    pub total_bytes: u64,
+    pub available_bytes: u64,
+}
+
+impl DiskSpace {
+    /// Space on the disk holding `path`
+    pub fn of(path: &Path) -> std::io::Result<Self> {
+        Ok(Self {
+            total_bytes: fs2::total_space(path)?,
+            available_bytes: fs2::available_space(path)?,
+        })
+    }
+
+    pub fn free_percent(&self) -> f64 {
+        if self.total_bytes == 0 {
+            return 100.0;
+        }
+        self.available_bytes as f64 * 100.0 / self.total_bytes as f64
+    }
+}
+
+/// Everything the quota manager knows
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuotaStatus {
+    pub enabled: bool,
+    pub read_only: bool,
+    pub disk: Option<DiskSpace>,
+    pub free_disk_percent: Option<f64>,
+    pub total: ScopeUsage,
+    pub databases: Vec<ScopeUsage>,
+    pub tables: Vec<ScopeUsage>,
+    /// Latest events, oldest first
+    pub recent_events: Vec<QuotaEvent>,
+}
+
+#[derive(Debug, Clone)]
+struct TableUsage {
+    database: String,
+    table: String,
+    bytes: u64,
+    rows: u64,
+}
+
+/// Tracks usage against `storage.quotas` and decides which writes may proceed
+pub struct QuotaManager {
+    config: RwLock<QuotasConfig>,
+    store: Arc<dyn ColumnStore>,
+    db_manager: Arc<DatabaseManager>,
+    data_dir: PathBuf,
+    tables: RwLock<HashMap<TableId, TableUsage>>,
+    /// Scopes at or past their warning threshold
+    levels: Mutex<HashMap<String, QuotaLevel>>,
+    read_only: AtomicBool,
+    disk: RwLock<Option<DiskSpace>>,
+    recent: RwLock<VecDeque<QuotaEvent>>,
+    sinks: RwLock<Vec<Arc<dyn QuotaSink>>>,
+}
+
+impl QuotaManager {
+    /// `store` is the unwrapped store usage is counted from; `data_dir` is
+    /// where free disk space is measured
+    pub fn new(
+        config: QuotasConfig,
+        store: Arc<dyn ColumnStore>,
+        db_manager: Arc<DatabaseManager>,
+        data_dir: PathBuf,
+    ) -> Self {
+        Self {
+            config: RwLock::new(config),
+            store,
+            db_manager,
+            data_dir,
+            tables: RwLock::new(HashMap::new()),
+            levels: Mutex::new(HashMap::new()),
+            read_only: AtomicBool::new(false),
+            disk: RwLock::new(None),
+            recent: RwLock::new(VecDeque::new()),
+            sinks: RwLock::new(Vec::new()),
+        }
+    }
+
+    pub fn add_sink(&self, sink: Arc<dyn QuotaSink>) {
+        self.sinks.write().push(sink);
+    }
+
+    /// Apply a new `storage.quotas` section; levels are re-evaluated on the next check
+    pub fn update(&self, config: &QuotasConfig) {
+        *self.config.write() = config.clone();
+        if !config.enabled || config.read_only_below_free_percent <= 0.0 {
+            self.read_only.store(false, Ordering::Release);
+        }
+    }
+
+    /// Writes are refused because the disk is nearly full
+    pub fn is_read_only(&self) -> bool {
+        self.read_only.load(Ordering::Acquire)
+    }
+
+    /// Wrap `inner` (normally the store given to `new`) so its writes are checked
+    pub fn wrap(self: &Arc<Self>, inner: Arc<dyn ColumnStore>) -> Arc<dyn ColumnStore> {
+        Arc::new(QuotaColumnStore { inner, quotas: self.clone() })
+    }
+
+    /// Recount and check disk space every `check_interval` until dropped
+    pub fn start(self: &Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let quotas = Arc::downgrade(self);
+        tokio::spawn(async move {
+            loop {
+                let interval = match quotas.upgrade() {
+                    Some(quotas) => {
+                        quotas.check().await;
+                        quotas.config.read().check_interval
+                    }
+                    None => break,
+                };
+                tokio::time::sleep(interval).await;
+            }
+        })
+    }
+
+    /// Recount usage, measure free disk space and publish threshold crossings
+    pub async fn check(&self) {
+        if !self.config.read().enabled {
+            return;
+        }
+        if let Err(e) = self.recount().await {
+            warn!("Failed to recount storage usage: {}", e);
+        }
+        let mut events = match DiskSpace::of(&self.data_dir) {
+            Ok(disk) => self.apply_disk_space(disk),
+            Err(e) => {
+                warn!("Failed to measure free space of {}: {}", self.data_dir.display(), e);
+                Vec::new()
+            }
+        };
+        events.extend(self.evaluate());
+        self.publish(events).await;
+    }
+
+    /// Recount every table from its block metadata. Tables whose store keeps
+    /// no block metadata are counted from their data the first time only.
+    pub async fn recount(&self) -> Result<()> {
+        let mut counted = HashMap::new();
+        for database in self.db_manager.list_databases() {
+            for table in self.db_manager.list_tables(database.id)? {
+                let known = self.tables.read().get(&table.table_id).cloned();
+                let usage = match self.count_table(table.table_id, &table.schema, known.is_none()).await {
+                    Ok(Some((bytes, rows))) => TableUsage { database: database.name.clone(), table: table.name.clone(), bytes, rows },
+                    Ok(None) => match known {
+                        Some(known) => known,
+                        None => continue,
+                    },
+                    // Not loaded yet (startup recovery) or dropped meanwhile
+                    Err(_) => continue,
+                };
+                counted.insert(table.table_id, usage);
+            }
+        }
+        *self.tables.write() = counted;
+        Ok(())
+    }
+
+    /// (bytes, rows) of a table; None when its store keeps no block metadata
+    /// and `scan` is false
+    async fn count_table(&self, table_id: TableId, schema: &Schema, scan: bool) -> Result<Option<(u64, u64)>> {
+        let mut bytes = 0u64;
+        let mut rows = 0u64;
+        let mut blocks = 0usize;
+        for column_id in 0..schema.fields.len() as u32 {
+            let metadata: Vec<BlockMetadata> = self.store.get_block_metadata(table_id, column_id).await?;
+            blocks += metadata.len();
+            bytes += metadata.iter().map(|block| block.compressed_size as u64).sum::<u64>();
+            if column_id == 0 {
+                rows = metadata.iter().map(|block| block.row_count as u64).sum();
+            }
+        }
+        if blocks > 0 {
+            return Ok(Some((bytes, rows)));
+        }
+        if !scan {
+            return Ok(None);
+        }
+        let column_ids = (0..schema.fields.len() as u32).collect();
+        let columns = self.store.read_columns(table_id, column_ids, 0, usize::MAX).await?;
+        Ok(Some((columns_bytes(&columns), columns.first().map_or(0, |c| c.len() as u64))))
+    }
+
+    /// Record free disk space, entering or leaving read-only mode
+    pub fn apply_disk_space(&self, disk: DiskSpace) -> Vec<QuotaEvent> {
+        *self.disk.write() = Some(disk);
+        let config = self.config.read().clone();
+        let free = disk.free_percent();
+        let was_read_only = self.is_read_only();
+        let read_only = if was_read_only {
+            free < config.resume_above_free_percent
+        } else {
+            config.read_only_below_free_percent > 0.0 && free < config.read_only_below_free_percent
+        };
+        if read_only == was_read_only {
+            return Vec::new();
+        }
+        self.read_only.store(read_only, Ordering::Release);
+        let (kind, message) = if read_only {
+            error!("Storage is read-only: {:.1}% disk space free in {}", free, self.data_dir.display());
+            (QuotaEventKind::ReadOnly, format!(
+                "{:.1}% disk space free, below {}%; writes are refused until it is above {}%",
+                free, config.read_only_below_free_percent, config.resume_above_free_percent
+            ))
+        } else {
+            info!("Storage is writable again: {:.1}% disk space free", free);
+            (QuotaEventKind::Writable, format!("{:.1}% disk space free; writes are accepted again", free))
+        };
+        vec![QuotaEvent {
+            kind,
+            scope: "disk".to_string(),
+            used_bytes: disk.total_bytes.saturating_sub(disk.available_bytes),
+            used_rows: 0,
+            limits: QuotaLimits::default(),
+            free_disk_percent: Some(free),
+            message,
+            at: now_millis(),
+        }]
+    }
+
+    /// Check a write of `bytes`/`rows` to a table and count it if allowed;
+    /// `release` it again if the write then fails. Returns the events to publish.
+    fn admit(&self, table_id: TableId, bytes: u64, rows: u64) -> (Result<()>, Vec<QuotaEvent>) {
+        let config = self.config.read().clone();
+        if !config.enabled {
+            return (Ok(()), Vec::new());
+        }
+        if self.is_read_only() {
+            let free = self.disk.read().map(|disk| disk.free_percent()).unwrap_or(0.0);
+            return (Err(Error::ReadOnly(format!(
+                "only {:.1}% disk space free (writes resume above {}%)",
+                free, config.resume_above_free_percent
+            ))), Vec::new());
+        }
+
+        let (database, table) = self.table_names(table_id);
+        let mut tables = self.tables.write();
+        let scopes = [
+            Scope::Table(&database, &table),
+            Scope::Database(&database),
+            Scope::Total,
+        ];
+        for scope in scopes {
+            let limits = scope.limits(&config);
+            if limits.is_unlimited() {
+                continue;
+            }
+            let (used_bytes, used_rows) = scope.usage(table_id, &tables);
+            let over_bytes = limits.max_bytes.filter(|&max| used_bytes + bytes > max);
+            let over_rows = limits.max_rows.filter(|&max| used_rows + rows > max);
+            if over_bytes.is_none() && over_rows.is_none() {
+                continue;
+            }
+            let label = scope.label();
+            let message = match over_bytes {
+                Some(max) => format!(
+                    "{} would hold {} of its {} byte limit",
+                    label, used_bytes + bytes, max
+                ),
+                None => format!(
+                    "{} would hold {} of its {} row limit",
+                    label, used_rows + rows, over_rows.unwrap_or_default()
+                ),
+            };
+            let mut events = Vec::new();
+            if self.raise(&label, QuotaLevel::Exceeded) {
+                warn!("Quota exceeded: {}", message);
+                events.push(QuotaEvent {
+                    kind: QuotaEventKind::Exceeded,
+                    scope: label,
+                    used_bytes,
+                    used_rows,
+                    limits: limits.clone(),
+                    free_disk_percent: None,
+                    message: message.clone(),
+                    at: now_millis(),
+                });
+            }
+            return (Err(Error::QuotaExceeded(message)), events);
+        }
+
+        let usage = tables.entry(table_id).or_insert_with(|| TableUsage {
+            database: database.clone(),
+            table: table.clone(),
+            bytes: 0,
+            rows: 0,
+        });
+        usage.bytes += bytes;
+        usage.rows += rows;
+
+        let mut events = Vec::new();
+        for scope in scopes {
+            let limits = scope.limits(&config);
+            let (used_bytes, used_rows) = scope.usage(table_id, &tables);
+            if level_of(used_bytes, used_rows, &limits, config.warn_ratio) == Some(QuotaLevel::Warning) {
+                let label = scope.label();
+                if self.raise(&label, QuotaLevel::Warning) {
+                    events.push(warning(label, used_bytes, used_rows, limits));
+                }
+            }
+        }
+        (Ok(()), events)
+    }
+
+    /// Uncount a write that failed
+    fn release(&self, table_id: TableId, bytes: u64, rows: u64) {
+        if let Some(usage) = self.tables.write().get_mut(&table_id) {
+            usage.bytes = usage.bytes.saturating_sub(bytes);
+            usage.rows = usage.rows.saturating_sub(rows);
+        }
+    }
+
+    /// Count a table whose rows were replaced
+    fn replaced(&self, table_id: TableId, bytes: u64, rows: u64) {
+        if let Some(usage) = self.tables.write().get_mut(&table_id) {
+            usage.bytes = bytes;
+            usage.rows = rows;
+        }
+    }
+
+    fn forget(&self, table_id: TableId) {
+        self.tables.write().remove(&table_id);
+    }
+
+    /// Raise a scope's level; false when it was already there
+    fn raise(&self, scope: &str, level: QuotaLevel) -> bool {
+        let mut levels = self.levels.lock();
+        match levels.get(scope) {
+            Some(current) if *current >= level => false,
+            _ => {
+                levels.insert(scope.to_string(), level);
+                true
+            }
+        }
+    }
+
+    /// Bring every scope's level in line with its usage: lowered levels are
+    /// reset quietly, newly reached warnings are returned
+    pub fn evaluate(&self) -> Vec<QuotaEvent> {
+        let config = self.config.read().clone();
+        let status = self.scopes(&config);
+        let mut levels = self.levels.lock();
+        let mut events = Vec::new();
+        for usage in status {
+            match (usage.level, levels.get(&usage.scope).copied()) {
+                (None, Some(_)) => {
+                    levels.remove(&usage.scope);
+                }
+                (Some(level), current) if current.is_none_or(|current| current > level) => {
+                    // Exceeded is only announced when a write is refused
+                    levels.insert(usage.scope.clone(), QuotaLevel::Warning);
+                    if current.is_none() {
+                        events.push(warning(usage.scope, usage.bytes, usage.rows, usage.limits));
+                    }
+                }
+                _ => {}
+            }
+        }
+        events
+    }
+
+    /// Usage of every scope with limits or data
+    fn scopes(&self, config: &QuotasConfig) -> Vec<ScopeUsage> {
+        let tables = self.tables.read();
+        let mut databases: BTreeMap<String, (u64, u64)> = config.databases.keys().map(|name| (name.clone(), (0, 0))).collect();
+        let mut total = (0u64, 0u64);
+        let mut scopes = Vec::new();
+        for usage in tables.values() {
+            if !usage.database.is_empty() {
+                let database = databases.entry(usage.database.clone()).or_default();
+                database.0 += usage.bytes;
+                database.1 += usage.rows;
+            }
+            total.0 += usage.bytes;
+            total.1 += usage.rows;
+            let limits = Scope::Table(&usage.database, &usage.table).limits(config);
+            scopes.push(scope_usage(Scope::Table(&usage.database, &usage.table).label(), usage.bytes, usage.rows, limits, config.warn_ratio));
+        }
+        for (name, (bytes, rows)) in databases {
+            let limits = Scope::Database(&name).limits(config);
+            scopes.push(scope_usage(Scope::Database(&name).label(), bytes, rows, limits, config.warn_ratio));
+        }
+        scopes.push(scope_usage(Scope::Total.label(), total.0, total.1, config.total.clone(), config.warn_ratio));
+        scopes
+    }
+
+    pub fn status(&self) -> QuotaStatus {
+        let config = self.config.read().clone();
+        let mut scopes = self.scopes(&config);
+        let total = scopes.pop().expect("scopes end with the total");
+        let (mut databases, mut tables): (Vec<ScopeUsage>, Vec<ScopeUsage>) =
+            scopes.into_iter().partition(|scope| scope.scope.starts_with("database:"));
+        databases.sort_by(|a, b| a.scope.cmp(&b.scope));
+        tables.sort_by(|a, b| a.scope.cmp(&b.scope));
+        let disk = *self.disk.read();
+        QuotaStatus {
+            enabled: config.enabled,
+            read_only: self.is_read_only(),
+            disk,
+            free_disk_percent: disk.map(|disk| disk.free_percent()),
+            total,
+            databases,
+            tables,
+            recent_events: self.recent.read().iter().cloned().collect(),
+        }
+    }
+
+    /// Remember events and hand them to the sinks
+    pub async fn publish(&self, events: Vec<QuotaEvent>) {
+        if events.is_empty() {
+            return;
+        }
+        {
+            let mut recent = self.recent.write();
+            for event in &events {
+                if recent.len() >= MAX_RECENT_EVENTS {
+                    recent.pop_front();
+                }
+                recent.push_back(event.clone());
+            }
+        }
+        let sinks: Vec<Arc<dyn QuotaSink>> = self.sinks.read().clone();
+        for event in &events {
+            for sink in &sinks {
+                if let Err(e) = sink.emit(event).await {
+                    warn!("Failed to publish {} event for {}: {}", event.kind.event_name(), event.scope, e);
+                }
+            }
+        }
+    }
+
+    /// (database, table) names; tables outside the database manager are
+    /// counted in the total only
+    fn table_names(&self, table_id: TableId) -> (String, String) {
+        if let Some(usage) = self.tables.read().get(&table_id) {
+            return (usage.database.clone(), usage.table.clone());
+        }
+        let Some(info) = self.db_manager.get_table_info(table_id) else {
+            return (String::new(), format!("#{}", table_id.0));
+        };
+        let database = self.db_manager.list_databases()
+            .into_iter()
+            .find(|db| db.id == info.database_id)
+            .map(|db| db.name)
+            .unwrap_or_default();
+        (database, info.name)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Scope<'a> {
+    Table(&'a str, &'a str),
+    Database(&'a str),
+    Total,
+}
+
+impl Scope<'_> {
+    fn label(&self) -> String {
+        match self {
+            Scope::Table(database, table) => format!("table:{}.{}", database, table),
+            Scope::Database(database) => format!("database:{}", database),
+            Scope::Total => "total".to_string(),
+        }
+    }
+
+    fn limits(&self, config: &QuotasConfig) -> QuotaLimits {
+        match self {
+            Scope::Table("", _) | Scope::Database("") => QuotaLimits::default(),
+            Scope::Table(database, table) => config.tables.get(&format!("{}.{}", database, table))
+                .unwrap_or(&config.default_table)
+                .clone(),
+            Scope::Database(database) => config.databases.get(*database)
+                .unwrap_or(&config.default_database)
+                .clone(),
+            Scope::Total => config.total.clone(),
+        }
+    }
+
+    /// (bytes, rows) stored in the scope of `table_id`
+    fn usage(&self, table_id: TableId, tables: &HashMap<TableId, TableUsage>) -> (u64, u64) {
+        let sum = |filter: &dyn Fn(&TableUsage) -> bool| {
+            tables.values().filter(|usage| filter(usage)).fold((0, 0), |(bytes, rows), usage| (bytes + usage.bytes, rows + usage.rows))
+        };
+        match self {
+            Scope::Table(..) => tables.get(&table_id).map_or((0, 0), |usage| (usage.bytes, usage.rows)),
+            Scope::Database(database) => sum(&|usage| usage.database == *database),
+            Scope::Total => sum(&|_| true),
+        }
+    }
+}
+
+fn level_of(bytes: u64, rows: u64, limits: &QuotaLimits, warn_ratio: f64) -> Option<QuotaLevel> {
+    let ratio = |used: u64, max: Option<u64>| max.map_or(0.0, |max| if max == 0 { f64::INFINITY } else { used as f64 / max as f64 });
+    let ratio = ratio(bytes, limits.max_bytes).max(ratio(rows, limits.max_rows));
+    if ratio >= 1.0 {
+        Some(QuotaLevel::Exceeded)
+    } else if ratio >= warn_ratio && warn_ratio > 0.0 {
+        Some(QuotaLevel::Warning)
+    } else {
+        None
+    }
+}
+
+fn scope_usage(scope: String, bytes: u64, rows: u64, limits: QuotaLimits, warn_ratio: f64) -> ScopeUsage {
+    let level = level_of(bytes, rows, &limits, warn_ratio);
+    ScopeUsage { scope, bytes, rows, limits, level }
+}
+
+fn warning(scope: String, used_bytes: u64, used_rows: u64, limits: QuotaLimits) -> QuotaEvent {
+    let message = format!(
+        "{} holds {} bytes / {} rows of its {} byte / {} row limit",
+        scope,
+        used_bytes,
+        used_rows,
+        limits.max_bytes.map_or("unlimited".to_string(), |max| max.to_string()),
+        limits.max_rows.map_or("unlimited".to_string(), |max| max.to_string()),
+    );
+    info!("Quota warning: {}", message);
+    QuotaEvent {
+        kind: QuotaEventKind::Warning,
+        scope,
+        used_bytes,
+        used_rows,
+        limits,
+        free_disk_percent: None,
+        message,
+        at: now_millis(),
+    }
+}
+
+/// Approximate stored size of columns (uncompressed)
+pub fn columns_bytes(columns: &[Column]) -> u64 {
+    columns.iter().map(|column| match column {
+        Column::Int8(v) => v.len() as u64,
+        Column::UInt8(v) => v.len() as u64,
+        Column::Boolean(v) => v.len() as u64,
+        Column::Int16(v) => v.len() as u64 * 2,
+        Column::UInt16(v) => v.len() as u64 * 2,
+        Column::Int32(v) => v.len() as u64 * 4,
+        Column::UInt32(v) => v.len() as u64 * 4,
+        Column::Float32(v) => v.len() as u64 * 4,
+        Column::Date(v) => v.len() as u64 * 4,
+        Column::Int64(v) => v.len() as u64 * 8,
+        Column::UInt64(v) => v.len() as u64 * 8,
+        Column::Float64(v) => v.len() as u64 * 8,
+        Column::Timestamp(v) => v.len() as u64 * 8,
+        Column::String(v) => v.iter().map(|s| s.len() as u64 + 4).sum(),
+        Column::Binary(v) => v.iter().map(|b| b.len() as u64 + 4).sum(),
+    }).sum()
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// A column store whose writes are checked against the quotas
+pub struct QuotaColumnStore {
+    inner: Arc<dyn ColumnStore>,
+    quotas: Arc<QuotaManager>,
+}
+
+#[async_trait]
+impl ColumnStore for QuotaColumnStore {
+    async fn create_table(&self, table_id: TableId, schema: Schema) -> Result<()> {
+        if self.quotas.is_read_only() {
+            return Err(Error::ReadOnly("disk space is low; tables cannot be created".to_string()));
+        }
+        self.inner.create_table(table_id, schema).await
+    }
+
+    async fn write_columns(&self, table_id: TableId, columns: Vec<Column>) -> Result<()> {
+        let guard = self.inner.lock_table(table_id).await;
+        self.write_locked(&guard, columns).await
+    }
+
+    async fn lock_table(&self, table_id: TableId) -> TableWriteGuard {
+        self.inner.lock_table(table_id).await
+    }
+
+    async fn write_locked(&self, guard: &TableWriteGuard, columns: Vec<Column>) -> Result<()> {
+        let table_id = guard.table_id();
+        let bytes = columns_bytes(&columns);
+        let rows = columns.first().map_or(0, |column| column.len() as u64);
+        let (admitted, events) = self.quotas.admit(table_id, bytes, rows);
+        self.quotas.publish(events).await;
+        admitted?;
+        let result = self.inner.write_locked(guard, columns).await;
+        if result.is_err() {
+            self.quotas.release(table_id, bytes, rows);
+        }
+        result
+    }
+
+    /// Always allowed, like deletes: it is how rows are removed
+    async fn replace_rows(&self, guard: &TableWriteGuard, columns: Vec<Column>) -> Result<()> {
+        let bytes = columns_bytes(&columns);
+        let rows = columns.first().map_or(0, |column| column.len() as u64);
+        self.inner.replace_rows(guard, columns).await?;
+        self.quotas.replaced(guard.table_id(), bytes, rows);
+        Ok(())
+    }
+
+    async fn read_columns(
+        &self,
+        table_id: TableId,
+        column_ids: Vec<u32>,
+        row_start: usize,
+        row_count: usize,
+    ) -> Result<Vec<Column>> {
+        self.inner.read_columns(table_id, column_ids, row_start, row_count).await
+    }
+
+    async fn get_schema(&self, table_id: TableId) -> Result<Schema> {
+        self.inner.get_schema(table_id).await
+    }
+
+    async fn get_block_metadata(&self, table_id: TableId, column_id: u32) -> Result<Vec<BlockMetadata>> {
+        self.inner.get_block_metadata(table_id, column_id).await
+    }
+
+    /// Always allowed, so space can be freed in read-only mode
+    async fn delete_table(&self, table_id: TableId) -> Result<()> {
+        self.inner.delete_table(table_id).await?;
+        self.quotas.forget(table_id);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::column_store::InMemoryColumnStore;
+    use narayana_core::schema::{DataType, Field};
+
+    #[derive(Default)]
+    struct RecordingSink {
+        events: Mutex<Vec<QuotaEvent>>,
+    }
+
+    #[async_trait]
+    impl QuotaSink for RecordingSink {
+        async fn emit(&self, event: &QuotaEvent) -> Result<()> {
+            self.events.lock().push(event.clone());
+            Ok(())
+        }
+    }
+
+    struct Fixture {
+        quotas: Arc<QuotaManager>,
+        store: Arc<dyn ColumnStore>,
+        db_manager: Arc<DatabaseManager>,
+        sink: Arc<RecordingSink>,
+    }
+
+    impl Fixture {
+        fn new(config: QuotasConfig) -> Self {
+            let inner: Arc<dyn ColumnStore> = Arc::new(InMemoryColumnStore::new());
+            let db_manager = Arc::new(DatabaseManager::new());
+            let quotas = Arc::new(QuotaManager::new(config, inner.clone(), db_manager.clone(), std::env::temp_dir()));
+            let sink = Arc::new(RecordingSink::default());
+            quotas.add_sink(sink.clone());
+            Self { store: quotas.wrap(inner), quotas, db_manager, sink }
+        }
+
+        async fn table(&self, database: &str, name: &str) -> TableId {
+            let db_id = self.db_manager.get_database_by_name(database)
+                .unwrap_or_else(|| self.db_manager.create_database(database.to_string()).unwrap());
+            let schema = Schema::new(vec![Field {
+                name: "value".to_string(),
+                data_type: DataType::Int64,
+                nullable: false,
+                default_value: None,
+            }]);
+            let table_id = self.db_manager.create_table(db_id, name.to_string(), schema.clone()).unwrap();
+            self.store.create_table(table_id, schema).await.unwrap();
+            table_id
+        }
+
+        /// Write `rows` Int64 values (8 bytes each)
+        async fn write(&self, table_id: TableId, rows: usize) -> Result<()> {
+            self.store.write_columns(table_id, vec![Column::Int64(vec![1; rows])]).await
+        }
+
+        fn kinds(&self) -> Vec<(QuotaEventKind, String)> {
+            self.sink.events.lock().iter().map(|e| (e.kind, e.scope.clone())).collect()
+        }
+    }
+
+    fn limits(max_bytes: Option<u64>, max_rows: Option<u64>) -> QuotaLimits {
+        QuotaLimits { max_bytes, max_rows }
+    }
+
+    #[tokio::test]
+    async fn test_writes_past_a_table_limit_are_refused() {
+        let mut config = QuotasConfig::default();
+        config.tables.insert("default.readings".to_string(), limits(Some(800), None));
+        let fixture = Fixture::new(config);
+        let readings = fixture.table("default", "readings").await;
+        let other = fixture.table("default", "other").await;
+
+        fixture.write(readings, 70).await.unwrap();
+        assert!(fixture.kinds().is_empty());
+        fixture.write(readings, 10).await.unwrap();
+        assert_eq!(fixture.kinds(), vec![(QuotaEventKind::Warning, "table:default.readings".to_string())]);
+        fixture.write(readings, 20).await.unwrap();
+        let refused = fixture.write(readings, 1).await.unwrap_err();
+        assert!(matches!(refused, Error::QuotaExceeded(ref m) if m.contains("table:default.readings would hold 808 of its 800 byte limit")), "{}", refused);
+        assert!(fixture.write(readings, 1).await.is_err());
+        assert_eq!(fixture.kinds().len(), 2, "each crossing is published once");
+        assert_eq!(fixture.kinds()[1].0, QuotaEventKind::Exceeded);
+
+        // Other tables are not limited
+        fixture.write(other, 1000).await.unwrap();
+        let status = fixture.quotas.status();
+        assert_eq!((status.total.bytes, status.total.rows), (8800, 1100));
+        let table = status.tables.iter().find(|t| t.scope == "table:default.readings").unwrap();
+        assert_eq!((table.bytes, table.level), (800, Some(QuotaLevel::Exceeded)));
+    }
+
+    #[tokio::test]
+    async fn test_database_and_total_limits_cover_all_their_tables() {
+        let mut config = QuotasConfig {
+            default_database: limits(None, Some(100)),
+            total: limits(Some(2000), None),
+            ..Default::default()
+        };
+        config.databases.insert("archive".to_string(), QuotaLimits::default());
+        let fixture = Fixture::new(config);
+        let a = fixture.table("robots", "a").await;
+        let b = fixture.table("robots", "b").await;
+        let archived = fixture.table("archive", "old").await;
+
+        fixture.write(a, 60).await.unwrap();
+        fixture.write(b, 40).await.unwrap();
+        let refused = fixture.write(b, 1).await.unwrap_err();
+        assert!(refused.to_string().contains("database:robots would hold 101 of its 100 row limit"), "{}", refused);
+        // The archive database has its own (unlimited) entry, but the total still applies
+        fixture.write(archived, 150).await.unwrap();
+        assert!(matches!(fixture.write(archived, 1).await, Err(Error::QuotaExceeded(_))));
+
+        // Deleting a table frees its quota
+        fixture.store.delete_table(a).await.unwrap();
+        fixture.write(b, 10).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_low_disk_space_makes_storage_read_only() {
+        let fixture = Fixture::new(QuotasConfig::default());
+        let table = fixture.table("default", "t").await;
+        let disk = |available| DiskSpace { total_bytes: 1000, available_bytes: available };
+
+        assert!(fixture.quotas.apply_disk_space(disk(60)).is_empty());
+        let events = fixture.quotas.apply_disk_space(disk(40));
+        assert_eq!(events[0].kind, QuotaEventKind::ReadOnly);
+        assert!(matches!(fixture.write(table, 1).await, Err(Error::ReadOnly(_))));
+        assert!(matches!(fixture.store.create_table(TableId(99), Schema::new(Vec::new())).await, Err(Error::ReadOnly(_))));
+
+        // Stays read-only until free space is back above the resume threshold
+        assert!(fixture.quotas.apply_disk_space(disk(80)).is_empty());
+        assert!(fixture.quotas.is_read_only());
+        let events = fixture.quotas.apply_disk_space(disk(100));
+        assert_eq!(events[0].kind, QuotaEventKind::Writable);
+        fixture.write(table, 1).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_recount_and_evaluate_follow_config_changes() {
+        let fixture = Fixture::new(QuotasConfig::default());
+        let table = fixture.table("default", "t").await;
+        fixture.write(table, 100).await.unwrap();
+        fixture.quotas.recount().await.unwrap();
+        assert_eq!(fixture.quotas.status().total.bytes, 800, "the write estimate is kept when the store keeps no block metadata");
+
+        let config = QuotasConfig {
+            default_table: limits(Some(900), None),
+            ..Default::default()
+        };
+        fixture.quotas.update(&config);
+        let events = fixture.quotas.evaluate();
+        assert_eq!(events.len(), 1);
+        assert_eq!((events[0].kind, events[0].scope.as_str()), (QuotaEventKind::Warning, "table:default.t"));
+        assert!(fixture.quotas.evaluate().is_empty());
+
+        fixture.quotas.update(&QuotasConfig::default());
+        assert!(fixture.quotas.evaluate().is_empty());
+        assert!(fixture.quotas.levels.lock().is_empty());
+
+        let disabled = QuotasConfig {
+            enabled: false,
+            total: limits(Some(1), None),
+            ..Default::default()
+        };
+        fixture.quotas.update(&disabled);
+        fixture.write(table, 10).await.unwrap();
+    }
+}
@@ -0,0 +1,831 @@
+// Reasoning Strategies - Pluggable Ways for the Thought Kernel to Think
+// Strategy registry selectable per thought type, with per-strategy budgets and
+// a comparison mode that scores strategies against each other on real thoughts
+
+use crate::cognitive::*;
+use async_trait::async_trait;
+use narayana_core::{Error, Result};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// Thought type of thoughts that don't name one
+pub const DEFAULT_THOUGHT_TYPE: &str = "default";
+
+/// Thoughts kept around for outcome scoring
+const MAX_PENDING_OUTCOMES: usize = 10_000;
+
+/// What a strategy reasons about
+#[derive(Clone)]
+pub struct ReasoningRequest {
+    pub thought_id: String,
+    pub thought_type: String,
+    pub content: serde_json::Value,
+    pub context: HashMap<String, serde_json::Value>,
+    pub brain: Arc<CognitiveBrain>,
+}
+
+/// A way of turning a thought into a result
+#[async_trait]
+pub trait ReasoningStrategy: Send + Sync {
+    /// Name the strategy is registered and selected under
+    fn name(&self) -> &str;
+
+    /// Reason about `request`, charging the work done to `meter`
+    async fn reason(&self, request: &ReasoningRequest, meter: &BudgetMeter) -> Result<serde_json::Value>;
+}
+
+/// Limits on a single run of a strategy
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StrategyBudget {
+    /// Reasoning steps: candidates weighed, branches expanded, policies consulted
+    pub max_steps: usize,
+    /// Requests to the brain's LLM
+    pub max_llm_calls: usize,
+    #[serde(with = "duration_millis")]
+    pub timeout: Duration,
+}
+
+impl Default for StrategyBudget {
+    fn default() -> Self {
+        Self {
+            max_steps: 32,
+            max_llm_calls: 8,
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+mod duration_millis {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_u64(duration.as_millis() as u64)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Duration, D::Error> {
+        u64::deserialize(deserializer).map(Duration::from_millis)
+    }
+}
+
+/// Charges the work of one strategy run against its budget
+pub struct BudgetMeter {
+    budget: StrategyBudget,
+    steps: AtomicUsize,
+    llm_calls: AtomicUsize,
+    exhausted: AtomicBool,
+}
+
+impl BudgetMeter {
+    fn new(budget: StrategyBudget) -> Self {
+        Self {
+            budget,
+            steps: AtomicUsize::new(0),
+            llm_calls: AtomicUsize::new(0),
+            exhausted: AtomicBool::new(false),
+        }
+    }
+
+    pub fn budget(&self) -> &StrategyBudget {
+        &self.budget
+    }
+
+    /// Take a reasoning step; fails once the step budget is spent
+    pub fn step(&self) -> Result<()> {
+        self.charge(&self.steps, self.budget.max_steps, "step")
+    }
+
+    /// Make an LLM request; fails once the LLM call budget is spent
+    pub fn llm_call(&self) -> Result<()> {
+        self.charge(&self.llm_calls, self.budget.max_llm_calls, "LLM call")
+    }
+
+    pub fn steps_remaining(&self) -> usize {
+        self.budget.max_steps.saturating_sub(self.steps.load(Ordering::Relaxed))
+    }
+
+    pub fn llm_calls_remaining(&self) -> usize {
+        self.budget.max_llm_calls.saturating_sub(self.llm_calls.load(Ordering::Relaxed))
+    }
+
+    fn charge(&self, counter: &AtomicUsize, limit: usize, what: &str) -> Result<()> {
+        let charged = counter.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used| (used < limit).then_some(used + 1));
+        if charged.is_err() {
+            self.exhausted.store(true, Ordering::Relaxed);
+            return Err(Error::Storage(format!("Reasoning {} budget of {} exhausted", what, limit)));
+        }
+        Ok(())
+    }
+}
+
+/// Answers with the action of the most confident learned pattern matching the
+/// thought's content, without weighing alternatives
+pub struct ReactiveStrategy;
+
+#[async_trait]
+impl ReasoningStrategy for ReactiveStrategy {
+    fn name(&self) -> &str {
+        "reactive"
+    }
+
+    async fn reason(&self, request: &ReasoningRequest, meter: &BudgetMeter) -> Result<serde_json::Value> {
+        meter.step()?;
+        let patterns = request.brain.find_matching_patterns(&request.content)?;
+        Ok(match patterns.first() {
+            Some(pattern) => serde_json::json!({
+                "action": pattern.action,
+                "pattern_id": pattern.id,
+                "confidence": pattern.confidence,
+            }),
+            None => serde_json::json!({ "action": null }),
+        })
+    }
+}
+
+/// Weighs every matching pattern by confidence, how often it was seen and the
+/// reward its outcome recorded, one step per candidate, and answers with the
+/// best. Stops weighing, rather than failing, when the step budget runs out
+pub struct DeliberativeStrategy;
+
+#[async_trait]
+impl ReasoningStrategy for DeliberativeStrategy {
+    fn name(&self) -> &str {
+        "deliberative"
+    }
+
+    async fn reason(&self, request: &ReasoningRequest, meter: &BudgetMeter) -> Result<serde_json::Value> {
+        let patterns = request.brain.find_matching_patterns(&request.content)?;
+        let mut candidates = Vec::new();
+        for pattern in &patterns {
+            if meter.steps_remaining() == 0 {
+                break;
+            }
+            meter.step()?;
+            let reward = pattern.outcome.get("reward")
+                .and_then(|reward| reward.as_f64())
+                .filter(|reward| reward.is_finite())
+                .unwrap_or(1.0);
+            let score = pattern.confidence * (1.0 + (pattern.frequency.max(1) as f64).ln()) * reward;
+            candidates.push((score, pattern));
+        }
+        candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let alternatives: Vec<serde_json::Value> = candidates.iter()
+            .skip(1)
+            .take(5)
+            .map(|(score, pattern)| serde_json::json!({ "pattern_id": pattern.id, "score": score }))
+            .collect();
+        Ok(match candidates.first() {
+            Some((score, pattern)) => serde_json::json!({
+                "action": pattern.action,
+                "pattern_id": pattern.id,
+                "score": score,
+                "considered": candidates.len(),
+                "alternatives": alternatives,
+            }),
+            None => serde_json::json!({ "action": null, "considered": 0 }),
+        })
+    }
+}
+
+/// Asks the brain's LLM for independent approaches to the thought, then for
+/// the most promising of them. Needs the `llm` feature and an LLM manager on
+/// the brain
+pub struct TreeOfThoughtStrategy {
+    branches: usize,
+}
+
+impl TreeOfThoughtStrategy {
+    pub fn new(branches: usize) -> Self {
+        Self { branches: branches.clamp(1, 10) }
+    }
+}
+
+#[async_trait]
+impl ReasoningStrategy for TreeOfThoughtStrategy {
+    fn name(&self) -> &str {
+        "tree_of_thought"
+    }
+
+    #[cfg(feature = "llm")]
+    async fn reason(&self, request: &ReasoningRequest, meter: &BudgetMeter) -> Result<serde_json::Value> {
+        use narayana_llm::{Message, MessageRole};
+
+        let llm = request.brain.get_llm_manager()
+            .ok_or_else(|| Error::Storage("Tree-of-thought reasoning needs an LLM manager on the brain".to_string()))?;
+        // One call per branch plus the one choosing between them
+        let branches = self.branches
+            .min(meter.llm_calls_remaining().saturating_sub(1))
+            .min(meter.steps_remaining());
+        if branches == 0 {
+            meter.exhausted.store(true, Ordering::Relaxed);
+            return Err(Error::Storage("Tree-of-thought budget leaves no room for a branch".to_string()));
+        }
+        let problem = match &request.content {
+            serde_json::Value::String(text) => text.clone(),
+            other => other.to_string(),
+        };
+        let llm = &llm;
+        let ask = move |prompt: String| async move {
+            meter.llm_call()?;
+            llm.chat(vec![Message { role: MessageRole::User, content: prompt }], None).await
+                .map_err(|e| Error::Storage(format!("LLM request failed: {}", e)))
+        };
+
+        let mut approaches = Vec::with_capacity(branches);
+        for branch in 1..=branches {
+            meter.step()?;
+            approaches.push(ask(format!(
+                "Problem: {}\n\nApproach this problem from a different angle (approach {} of {}). \
+                Give your reasoning and solution.",
+                problem, branch, branches
+            )).await?);
+        }
+        let verdict = ask(format!(
+            "Problem: {}\n\n{}\n\nWhich approach is the most promising? Reply with its number only.",
+            problem,
+            approaches.iter().enumerate()
+                .map(|(i, approach)| format!("Approach {}:\n{}", i + 1, approach))
+                .collect::<Vec<_>>()
+                .join("\n\n")
+        )).await?;
+        let chosen = verdict.split(|c: char| !c.is_ascii_digit())
+            .find_map(|digits| digits.parse::<usize>().ok())
+            .filter(|n| (1..=approaches.len()).contains(n))
+            .unwrap_or(1);
+        Ok(serde_json::json!({
+            "answer": approaches[chosen - 1],
+            "chosen": chosen,
+            "approaches": approaches,
+        }))
+    }
+
+    #[cfg(not(feature = "llm"))]
+    async fn reason(&self, _request: &ReasoningRequest, _meter: &BudgetMeter) -> Result<serde_json::Value> {
+        Err(Error::Storage("Tree-of-thought reasoning needs the llm feature".to_string()))
+    }
+}
+
+/// Lets a policy on the brain's RL engine pick the action: the configured
+/// policy, or the one named after the thought type
+#[derive(Default)]
+pub struct RlPolicyStrategy {
+    policy_id: Option<String>,
+}
+
+impl RlPolicyStrategy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Always consult `policy_id`, whatever the thought type
+    pub fn with_policy(policy_id: impl Into<String>) -> Self {
+        Self { policy_id: Some(policy_id.into()) }
+    }
+}
+
+#[async_trait]
+impl ReasoningStrategy for RlPolicyStrategy {
+    fn name(&self) -> &str {
+        "rl_policy"
+    }
+
+    async fn reason(&self, request: &ReasoningRequest, meter: &BudgetMeter) -> Result<serde_json::Value> {
+        meter.step()?;
+        let engine = request.brain.get_rl_engine()
+            .ok_or_else(|| Error::Storage("RL policy reasoning needs an RL engine on the brain".to_string()))?;
+        let policy_id = self.policy_id.as_deref().unwrap_or(&request.thought_type);
+        let action = engine.evaluate_policy(policy_id, &request.content)?;
+        Ok(serde_json::json!({
+            "action": action.action_type,
+            "parameters": action.parameters,
+            "policy_id": policy_id,
+        }))
+    }
+}
+
+/// One strategy's run on a thought
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyTrial {
+    pub strategy: String,
+    pub result: Option<serde_json::Value>,
+    pub error: Option<String>,
+    /// The run hit its step or LLM call budget, or timed out
+    pub budget_exceeded: bool,
+    pub elapsed_ms: u64,
+    pub steps: usize,
+    pub llm_calls: usize,
+}
+
+/// Result of reasoning about a thought, with every strategy that ran on it
+#[derive(Debug, Clone)]
+pub struct ReasoningOutcome {
+    /// Strategy whose result the thought takes
+    pub strategy: String,
+    pub result: serde_json::Value,
+    /// The selected strategy's run first, then any compared strategies'
+    pub trials: Vec<StrategyTrial>,
+}
+
+/// How a strategy has done on one thought type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyReport {
+    pub strategy: String,
+    pub runs: u64,
+    pub failures: u64,
+    pub budget_exceeded: u64,
+    pub mean_elapsed_ms: f64,
+    pub mean_steps: f64,
+    pub llm_calls: u64,
+    /// Outcomes recorded with `record_outcome`
+    pub outcomes: u64,
+    pub mean_outcome: Option<f64>,
+    /// Compared thoughts on which this strategy scored highest
+    pub wins: u64,
+}
+
+#[derive(Clone)]
+struct RegisteredStrategy {
+    strategy: Arc<dyn ReasoningStrategy>,
+    budget: StrategyBudget,
+}
+
+#[derive(Default)]
+struct StrategyStats {
+    runs: u64,
+    failures: u64,
+    budget_exceeded: u64,
+    elapsed_ms: u64,
+    steps: u64,
+    llm_calls: u64,
+    outcomes: u64,
+    outcome_total: f64,
+    wins: u64,
+}
+
+/// A reasoned thought awaiting outcome scores
+struct PendingThought {
+    thought_type: String,
+    trials: Vec<StrategyTrial>,
+    scores: HashMap<String, f64>,
+}
+
+#[derive(Default)]
+struct PendingOutcomes {
+    order: VecDeque<String>,
+    thoughts: HashMap<String, PendingThought>,
+}
+
+/// Reasoning strategies by name, which one each thought type uses, and how
+/// they compare
+pub struct ReasoningStrategyRegistry {
+    strategies: RwLock<HashMap<String, RegisteredStrategy>>,
+    /// Thought type -> strategy name
+    assignments: RwLock<HashMap<String, String>>,
+    default_strategy: RwLock<String>,
+    /// Thought type -> strategies run alongside the selected one
+    comparisons: RwLock<HashMap<String, Vec<String>>>,
+    /// (thought type, strategy) -> stats
+    stats: RwLock<HashMap<(String, String), StrategyStats>>,
+    pending: RwLock<PendingOutcomes>,
+}
+
+impl Default for ReasoningStrategyRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReasoningStrategyRegistry {
+    /// Registry with the built-in strategies under default budgets; thoughts
+    /// reason reactively unless assigned otherwise
+    pub fn new() -> Self {
+        let registry = Self {
+            strategies: RwLock::new(HashMap::new()),
+            assignments: RwLock::new(HashMap::new()),
+            default_strategy: RwLock::new("reactive".to_string()),
+            comparisons: RwLock::new(HashMap::new()),
+            stats: RwLock::new(HashMap::new()),
+            pending: RwLock::new(PendingOutcomes::default()),
+        };
+        registry.register(Arc::new(ReactiveStrategy), StrategyBudget::default());
+        registry.register(Arc::new(DeliberativeStrategy), StrategyBudget::default());
+        registry.register(Arc::new(TreeOfThoughtStrategy::new(3)), StrategyBudget::default());
+        registry.register(Arc::new(RlPolicyStrategy::new()), StrategyBudget::default());
+        registry
+    }
+
+    /// Register a strategy, replacing any registered under the same name
+    pub fn register(&self, strategy: Arc<dyn ReasoningStrategy>, budget: StrategyBudget) {
+        let name = strategy.name().to_string();
+        self.strategies.write().insert(name, RegisteredStrategy { strategy, budget });
+    }
+
+    pub fn strategy_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.strategies.read().keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    pub fn budget(&self, strategy: &str) -> Option<StrategyBudget> {
+        self.strategies.read().get(strategy).map(|entry| entry.budget)
+    }
+
+    pub fn set_budget(&self, strategy: &str, budget: StrategyBudget) -> Result<()> {
+        let mut strategies = self.strategies.write();
+        let entry = strategies.get_mut(strategy).ok_or_else(|| unknown_strategy(strategy))?;
+        entry.budget = budget;
+        Ok(())
+    }
+
+    /// Reason about thoughts of `thought_type` with `strategy`
+    pub fn assign(&self, thought_type: &str, strategy: &str) -> Result<()> {
+        self.ensure_registered(strategy)?;
+        self.assignments.write().insert(thought_type.to_string(), strategy.to_string());
+        Ok(())
+    }
+
+    /// Strategy for thought types without an assignment
+    pub fn set_default(&self, strategy: &str) -> Result<()> {
+        self.ensure_registered(strategy)?;
+        *self.default_strategy.write() = strategy.to_string();
+        Ok(())
+    }
+
+    /// Strategy thoughts of `thought_type` reason with
+    pub fn strategy_for(&self, thought_type: &str) -> String {
+        self.assignments.read()
+            .get(thought_type)
+            .cloned()
+            .unwrap_or_else(|| self.default_strategy.read().clone())
+    }
+
+    /// Comparison mode: also run `strategies` on every thought of
+    /// `thought_type`. The thought still takes the selected strategy's result;
+    /// the others are only recorded, for `record_outcome` to score
+    pub fn compare(&self, thought_type: &str, strategies: Vec<String>) -> Result<()> {
+        for strategy in &strategies {
+            self.ensure_registered(strategy)?;
+        }
+        self.comparisons.write().insert(thought_type.to_string(), strategies);
+        Ok(())
+    }
+
+    pub fn stop_comparing(&self, thought_type: &str) {
+        self.comparisons.write().remove(thought_type);
+    }
+
+    /// Reason with the strategy selected for the request's thought type
+    pub async fn run(&self, request: ReasoningRequest) -> Result<ReasoningOutcome> {
+        let strategy = self.strategy_for(&request.thought_type);
+        self.run_as(&strategy, request).await
+    }
+
+    /// Reason with `strategy`, plus any strategies compared on the thought type
+    pub async fn run_as(&self, strategy: &str, request: ReasoningRequest) -> Result<ReasoningOutcome> {
+        let selected = self.entry(strategy)?;
+        let challengers: Vec<RegisteredStrategy> = self.comparisons.read()
+            .get(&request.thought_type)
+            .map(|names| names.iter()
+                .filter(|name| name.as_str() != strategy)
+                .filter_map(|name| self.entry(name).ok())
+                .collect())
+            .unwrap_or_default();
+
+        let request = Arc::new(request);
+        let handles: Vec<_> = challengers.into_iter()
+            .map(|entry| {
+                let request = request.clone();
+                tokio::spawn(async move { run_trial(&entry, &request).await })
+            })
+            .collect();
+        let mut trials = vec![run_trial(&selected, &request).await];
+        for handle in handles {
+            match handle.await {
+                Ok(trial) => trials.push(trial),
+                Err(e) => warn!("Compared reasoning strategy panicked: {}", e),
+            }
+        }
+        self.record_trials(&request, &trials);
+
+        let first = &trials[0];
+        match &first.result {
+            Some(result) => Ok(ReasoningOutcome {
+                strategy: strategy.to_string(),
+                result: result.clone(),
+                trials: trials.clone(),
+            }),
+            None => Err(Error::Storage(format!(
+                "Reasoning strategy {} failed: {}",
+                strategy,
+                first.error.as_deref().unwrap_or("no result")
+            ))),
+        }
+    }
+
+    /// Trials recorded for a thought still awaiting outcome scores
+    pub fn trials(&self, thought_id: &str) -> Vec<StrategyTrial> {
+        self.pending.read().thoughts.get(thought_id)
+            .map(|pending| pending.trials.clone())
+            .unwrap_or_default()
+    }
+
+    /// Score how well `strategy`'s result for a thought turned out (higher is
+    /// better). Once every strategy that produced a result for the thought is
+    /// scored, the highest-scoring one is credited with a win
+    pub fn record_outcome(&self, thought_id: &str, strategy: &str, score: f64) -> Result<()> {
+        if !score.is_finite() {
+            return Err(Error::Storage("Outcome score must be finite".to_string()));
+        }
+        let mut pending = self.pending.write();
+        let thought = pending.thoughts.get_mut(thought_id)
+            .ok_or_else(|| Error::Storage(format!("No reasoning awaiting an outcome for thought {}", thought_id)))?;
+        if !thought.trials.iter().any(|trial| trial.strategy == strategy && trial.result.is_some()) {
+            return Err(Error::Storage(format!("Strategy {} produced no result for thought {}", strategy, thought_id)));
+        }
+        let first_score = thought.scores.insert(strategy.to_string(), score).is_none();
+        let thought_type = thought.thought_type.clone();
+        let succeeded = thought.trials.iter().filter(|trial| trial.result.is_some()).count();
+        let winner = (succeeded > 1 && thought.scores.len() == succeeded)
+            .then(|| thought.scores.iter()
+                .max_by(|a, b| a.1.total_cmp(b.1))
+                .map(|(name, _)| name.clone()))
+            .flatten();
+
+        let mut stats = self.stats.write();
+        if first_score {
+            let entry = stats.entry((thought_type.clone(), strategy.to_string())).or_default();
+            entry.outcomes += 1;
+            entry.outcome_total += score;
+        }
+        if let Some(winner) = winner {
+            stats.entry((thought_type, winner)).or_default().wins += 1;
+            pending.thoughts.remove(thought_id);
+            pending.order.retain(|id| id != thought_id);
+        }
+        Ok(())
+    }
+
+    /// Stats of every strategy run on `thought_type`, best mean outcome first
+    pub fn report(&self, thought_type: &str) -> Vec<StrategyReport> {
+        let mut reports: Vec<StrategyReport> = self.stats.read().iter()
+            .filter(|((kind, _), _)| kind == thought_type)
+            .map(|((_, strategy), stats)| {
+                let runs = stats.runs.max(1) as f64;
+                StrategyReport {
+                    strategy: strategy.clone(),
+                    runs: stats.runs,
+                    failures: stats.failures,
+                    budget_exceeded: stats.budget_exceeded,
+                    mean_elapsed_ms: stats.elapsed_ms as f64 / runs,
+                    mean_steps: stats.steps as f64 / runs,
+                    llm_calls: stats.llm_calls,
+                    outcomes: stats.outcomes,
+                    mean_outcome: (stats.outcomes > 0).then(|| stats.outcome_total / stats.outcomes as f64),
+                    wins: stats.wins,
+                }
+            })
+            .collect();
+        reports.sort_by(|a, b| {
+            let score = |report: &StrategyReport| report.mean_outcome.unwrap_or(f64::NEG_INFINITY);
+            score(b).total_cmp(&score(a)).then_with(|| a.strategy.cmp(&b.strategy))
+        });
+        reports
+    }
+
+    /// Strategy with the best mean outcome on `thought_type`, if any was scored
+    pub fn best_strategy(&self, thought_type: &str) -> Option<String> {
+        self.report(thought_type).into_iter()
+            .find(|report| report.mean_outcome.is_some())
+            .map(|report| report.strategy)
+    }
+
+    fn ensure_registered(&self, strategy: &str) -> Result<()> {
+        self.entry(strategy).map(|_| ())
+    }
+
+    fn entry(&self, strategy: &str) -> Result<RegisteredStrategy> {
+        self.strategies.read().get(strategy).cloned().ok_or_else(|| unknown_strategy(strategy))
+    }
+
+    fn record_trials(&self, request: &ReasoningRequest, trials: &[StrategyTrial]) {
+        {
+            let mut stats = self.stats.write();
+            for trial in trials {
+                let entry = stats.entry((request.thought_type.clone(), trial.strategy.clone())).or_default();
+                entry.runs += 1;
+                entry.failures += u64::from(trial.result.is_none());
+                entry.budget_exceeded += u64::from(trial.budget_exceeded);
+                entry.elapsed_ms += trial.elapsed_ms;
+                entry.steps += trial.steps as u64;
+                entry.llm_calls += trial.llm_calls as u64;
+            }
+        }
+
+        let mut pending = self.pending.write();
+        if pending.thoughts.insert(request.thought_id.clone(), PendingThought {
+            thought_type: request.thought_type.clone(),
+            trials: trials.to_vec(),
+            scores: HashMap::new(),
+        }).is_none() {
+            pending.order.push_back(request.thought_id.clone());
+        }
+        while pending.order.len() > MAX_PENDING_OUTCOMES {
+            if let Some(oldest) = pending.order.pop_front() {
+                pending.thoughts.remove(&oldest);
+            }
+        }
+    }
+}
+
+fn unknown_strategy(strategy: &str) -> Error {
+    Error::Storage(format!("Unknown reasoning strategy: {}", strategy))
+}
+
+/// Run one strategy under its budget
+async fn run_trial(entry: &RegisteredStrategy, request: &ReasoningRequest) -> StrategyTrial {
+    let meter = BudgetMeter::new(entry.budget);
+    let started = Instant::now();
+    let outcome = tokio::time::timeout(entry.budget.timeout, entry.strategy.reason(request, &meter)).await;
+    let timed_out = outcome.is_err();
+    let (result, error) = match outcome {
+        Ok(Ok(result)) => (Some(result), None),
+        Ok(Err(e)) => (None, Some(e.to_string())),
+        Err(_) => (None, Some(format!("Timed out after {:?}", entry.budget.timeout))),
+    };
+    let name = entry.strategy.name().to_string();
+    if let Some(error) = &error {
+        debug!("Reasoning strategy {} failed on thought {}: {}", name, request.thought_id, error);
+    }
+    StrategyTrial {
+        strategy: name,
+        result,
+        error,
+        budget_exceeded: timed_out || meter.exhausted.load(Ordering::Relaxed),
+        elapsed_ms: started.elapsed().as_millis() as u64,
+        steps: meter.steps.load(Ordering::Relaxed),
+        llm_calls: meter.llm_calls.load(Ordering::Relaxed),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reinforcement_learning::{RLAlgorithm, RLConfig, RLEngine};
+    use crate::thought_kernel::{ThoughtContext, ThoughtKernel};
+
+    fn context(content: serde_json::Value, thought_type: &str) -> ThoughtContext {
+        ThoughtContext {
+            content,
+            priority: 0.5,
+            deadline: None,
+            parent_thought_id: None,
+            context: HashMap::new(),
+            shared_memory_id: None,
+            gpu_required: false,
+            thought_type: Some(thought_type.to_string()),
+        }
+    }
+
+    /// Brain knowing two responses to an obstacle, the slower one rewarded more
+    fn brain_with_patterns() -> Arc<CognitiveBrain> {
+        let brain = Arc::new(CognitiveBrain::new());
+        for (action, reward) in [("stop", 0.2), ("detour", 0.9)] {
+            brain.learn_pattern(
+                "seed",
+                PatternType::Behavioral,
+                serde_json::json!({"obstacle": true}),
+                serde_json::json!(action),
+                serde_json::json!({"reward": reward}),
+            ).unwrap();
+        }
+        brain
+    }
+
+    struct Endless;
+
+    #[async_trait]
+    impl ReasoningStrategy for Endless {
+        fn name(&self) -> &str {
+            "endless"
+        }
+
+        async fn reason(&self, _request: &ReasoningRequest, meter: &BudgetMeter) -> Result<serde_json::Value> {
+            loop {
+                meter.step()?;
+                tokio::task::yield_now().await;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_strategy_selected_per_thought_type() {
+        let kernel = ThoughtKernel::new(brain_with_patterns());
+        kernel.strategies().assign("navigation", "deliberative").unwrap();
+        assert!(kernel.strategies().assign("navigation", "missing").is_err());
+
+        let reasoned = kernel.reason(context(serde_json::json!({"obstacle": true}), "navigation")).await.unwrap();
+        assert_eq!(reasoned.strategy.as_deref(), Some("deliberative"));
+        assert_eq!(reasoned.result["action"], "detour");
+        assert_eq!(reasoned.result["considered"], 2);
+
+        let reacted = kernel.reason(context(serde_json::json!({"obstacle": true}), "reflex")).await.unwrap();
+        assert_eq!(reacted.strategy.as_deref(), Some("reactive"));
+        assert!(reacted.result["pattern_id"].is_string());
+        let report = kernel.strategies().report("reflex");
+        assert_eq!((report[0].strategy.as_str(), report[0].runs), ("reactive", 1));
+    }
+
+    #[tokio::test]
+    async fn test_budgets_bound_strategy_runs() {
+        let registry = ReasoningStrategyRegistry::new();
+        registry.register(Arc::new(Endless), StrategyBudget { max_steps: 5, ..StrategyBudget::default() });
+        let request = ReasoningRequest {
+            thought_id: "t1".to_string(),
+            thought_type: "plan".to_string(),
+            content: serde_json::json!({}),
+            context: HashMap::new(),
+            brain: Arc::new(CognitiveBrain::new()),
+        };
+
+        let error = registry.run_as("endless", request.clone()).await.unwrap_err();
+        assert!(error.to_string().contains("step budget of 5 exhausted"));
+        let trial = &registry.trials("t1")[0];
+        assert!(trial.budget_exceeded);
+        assert_eq!(trial.steps, 5);
+
+        registry.set_budget("endless", StrategyBudget {
+            max_steps: usize::MAX,
+            max_llm_calls: 0,
+            timeout: Duration::from_millis(20),
+        }).unwrap();
+        let error = registry.run_as("endless", request).await.unwrap_err();
+        assert!(error.to_string().contains("Timed out"));
+        let report = &registry.report("plan")[0];
+        assert_eq!((report.runs, report.failures, report.budget_exceeded), (2, 2, 2));
+    }
+
+    #[tokio::test]
+    async fn test_comparison_mode_credits_better_strategy() {
+        let brain = brain_with_patterns();
+        let kernel = ThoughtKernel::new(brain);
+        let strategies = kernel.strategies();
+        strategies.compare("navigation", vec!["deliberative".to_string(), "rl_policy".to_string()]).unwrap();
+
+        for _ in 0..3 {
+            let result = kernel.reason(context(serde_json::json!({"obstacle": true}), "navigation")).await.unwrap();
+            assert_eq!(result.strategy.as_deref(), Some("reactive"));
+            let trials = strategies.trials(&result.thought_id);
+            assert_eq!(trials.iter().map(|t| t.strategy.as_str()).collect::<Vec<_>>(), ["reactive", "deliberative", "rl_policy"]);
+            // The brain has no RL engine, so only two strategies can be scored
+            assert!(trials[2].error.is_some());
+            assert!(strategies.record_outcome(&result.thought_id, "rl_policy", 1.0).is_err());
+
+            for trial in &trials[..2] {
+                let score = if trial.result.as_ref().unwrap()["action"] == "detour" { 1.0 } else { 0.0 };
+                strategies.record_outcome(&result.thought_id, &trial.strategy, score).unwrap();
+            }
+            // Fully scored thoughts stop waiting for outcomes
+            assert!(strategies.trials(&result.thought_id).is_empty());
+        }
+
+        let report = strategies.report("navigation");
+        let deliberative = report.iter().find(|r| r.strategy == "deliberative").unwrap();
+        assert_eq!(deliberative.mean_outcome, Some(1.0));
+        assert_eq!((deliberative.runs, deliberative.outcomes), (3, 3));
+        let rl = report.iter().find(|r| r.strategy == "rl_policy").unwrap();
+        assert_eq!((rl.failures, rl.outcomes, rl.wins), (3, 0, 0));
+        assert_eq!(strategies.best_strategy("navigation").as_deref(), Some("deliberative"));
+        assert_eq!(report.iter().map(|r| r.wins).sum::<u64>(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_rl_policy_strategy_uses_thought_type_policy() {
+        let brain = Arc::new(CognitiveBrain::new());
+        let engine = Arc::new(RLEngine::new(brain.clone(), RLConfig {
+            learning_rate: 0.01,
+            discount_factor: 0.99,
+            epsilon: 0.0,
+            batch_size: 4,
+            replay_buffer_size: 100,
+            update_frequency: 1,
+            algorithm: RLAlgorithm::DQN,
+        }));
+        engine.create_policy("grasp", &serde_json::json!({})).unwrap();
+        brain.set_rl_engine(engine);
+        let kernel = ThoughtKernel::new(brain);
+        kernel.strategies().assign("grasp", "rl_policy").unwrap();
+
+        let result = kernel.reason(context(serde_json::json!({"object": "cup"}), "grasp")).await.unwrap();
+        assert_eq!(result.result["policy_id"], "grasp");
+        assert_eq!(result.result["action"], "exploit");
+        assert!(kernel.reason(context(serde_json::json!({}), "untrained")).await.is_ok());
+        kernel.strategies().assign("untrained", "rl_policy").unwrap();
+        assert!(kernel.reason(context(serde_json::json!({}), "untrained")).await.is_err());
+    }
+}
@@ -2,6 +2,8 @@
 // Production-ready RL training engine with Q-learning, actor-critic, and policy gradients
 
 use crate::cognitive::*;
+use crate::feature_store::FeatureStore;
+use crate::model_registry::{ModelArtifactRegistry, ModelType, ModelVersion};
 use narayana_core::{Error, Result};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -100,6 +102,22 @@ impl RLEngine {
         Ok(action)
     }
 
+    /// Evaluate a policy on a state built from online features (`{"view:feature": value}`)
+    pub fn evaluate_policy_with_features(
+        &self,
+        policy_id: &str,
+        store: &FeatureStore,
+        entity: &str,
+        features: &[String],
+    ) -> Result<Action> {
+        let values = store.get_online_features(entity, features)?;
+        let state: serde_json::Map<String, serde_json::Value> = features.iter()
+            .zip(values)
+            .map(|(name, value)| (name.clone(), serde_json::json!(value)))
+            .collect();
+        self.evaluate_policy(policy_id, &serde_json::Value::Object(state))
+    }
+
     /// Record reward trace
     pub fn reward_trace(&self, trace_id: &str, reward: f64, state: &serde_json::Value) -> Result<()> {
         let mut traces = self.reward_traces.write();
@@ -444,6 +462,85 @@ impl RLEngine {
         Ok(())
     }
 
+    /// Publish a policy as a versioned artifact (stage `None`) in the model registry
+    pub fn publish_policy(
+        &self,
+        policy_id: &str,
+        artifacts: &ModelArtifactRegistry,
+        version: &str,
+    ) -> Result<ModelVersion> {
+        let bytes = {
+            let policies = self.policies.read();
+            let policy = policies.get(policy_id)
+                .ok_or_else(|| Error::Storage(format!("Policy {} not found", policy_id)))?;
+            serde_json::to_vec(policy)
+                .map_err(|e| Error::Serialization(format!("Failed to serialize policy: {}", e)))?
+        };
+        let mut metadata = HashMap::new();
+        metadata.insert("algorithm".to_string(), serde_json::json!(self.config.algorithm));
+        artifacts.register_version(policy_id, version, ModelType::Reward, POLICY_ARTIFACT_FORMAT, bytes, metadata)
+    }
+
+    /// Load the serving (Production, else Staging) version of a policy from the registry,
+    /// replacing any in-memory policy with the same id
+    pub fn load_policy_from_registry(&self, policy_id: &str, artifacts: &ModelArtifactRegistry) -> Result<ModelVersion> {
+        let version = artifacts.resolve_serving(policy_id)
+            .ok_or_else(|| Error::Storage(format!("Policy {} has no Production or Staging version", policy_id)))?;
+        if version.format != POLICY_ARTIFACT_FORMAT {
+            return Err(Error::Storage(format!(
+                "Model {} has format '{}', expected '{}'", policy_id, version.format, POLICY_ARTIFACT_FORMAT
+            )));
+        }
+        let bytes = artifacts.load_artifact(policy_id, &version.version.to_string())?;
+        let mut policy: Policy = serde_json::from_slice(&bytes)
+            .map_err(|e| Error::Deserialization(format!("Failed to deserialize policy: {}", e)))?;
+        policy.policy_id = policy_id.to_string();
+        self.policies.write().insert(policy_id.to_string(), policy);
+        info!("Loaded policy {} version {} ({:?})", policy_id, version.version, version.stage);
+        Ok(version)
+    }
+
+    /// Learned policies and value functions, for brain bundles
+    pub fn export_state(&self) -> Result<RLState> {
+        let mut policies = HashMap::new();
+        for (policy_id, policy) in self.policies.read().iter() {
+            let value = serde_json::to_value(policy)
+                .map_err(|e| Error::Serialization(format!("Failed to serialize policy: {}", e)))?;
+            policies.insert(policy_id.clone(), value);
+        }
+        let value_functions = self.value_functions.read()
+            .iter()
+            .map(|(id, function)| (id.clone(), function.values.clone()))
+            .collect();
+        Ok(RLState {
+            config: self.config.clone(),
+            policies,
+            value_functions,
+        })
+    }
+
+    pub fn has_policy(&self, policy_id: &str) -> bool {
+        self.policies.read().contains_key(policy_id)
+    }
+
+    pub fn has_value_function(&self, function_id: &str) -> bool {
+        self.value_functions.read().contains_key(function_id)
+    }
+
+    /// Install a policy exported by `export_state`, replacing any with the same id
+    pub fn import_policy(&self, policy_id: &str, policy: &serde_json::Value) -> Result<()> {
+        let mut policy: Policy = serde_json::from_value(policy.clone())
+            .map_err(|e| Error::Deserialization(format!("Failed to deserialize policy: {}", e)))?;
+        policy.policy_id = policy_id.to_string();
+        self.policies.write().insert(policy_id.to_string(), policy);
+        Ok(())
+    }
+
+    /// Install a value function exported by `export_state`, replacing any with the same id
+    pub fn import_value_function(&self, function_id: &str, values: HashMap<String, f64>) {
+        self.value_functions.write().insert(function_id.to_string(), ValueFunction { values });
+    }
+
     /// Get policy statistics
     pub fn get_policy_stats(&self, policy_id: &str) -> Result<PolicyStats> {
         let policies = self.policies.read();
@@ -459,8 +556,11 @@ impl RLEngine {
     }
 }
 
+/// Artifact format of policies stored in the model registry
+pub const POLICY_ARTIFACT_FORMAT: &str = "rl-policy-json";
+
 /// Policy for action selection
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct Policy {
     policy_id: String,
     q_values: HashMap<String, f64>, // State-action -> Q-value
@@ -591,6 +691,17 @@ struct ValueFunction {
     values: HashMap<String, f64>,
 }
 
+/// Learned RL state carried in brain bundles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RLState {
+    /// Configuration of the exporting engine; informational on import
+    pub config: RLConfig,
+    /// Policy id -> policy, in the registry artifact format
+    pub policies: HashMap<String, serde_json::Value>,
+    /// Value function id -> state values
+    pub value_functions: HashMap<String, HashMap<String, f64>>,
+}
+
 /// Reward trace
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RewardTrace {
@@ -613,6 +724,7 @@ pub struct PolicyStats {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::model_registry::ModelStage;
 
     #[test]
     fn test_rl_engine_creation() {
@@ -650,5 +762,29 @@ mod tests {
         let policies = engine.policies.read();
         assert!(policies.contains_key("test_policy"));
     }
-}
 
+    #[test]
+    fn test_policy_roundtrip_through_model_registry() {
+        let config = RLConfig {
+            learning_rate: 0.01,
+            discount_factor: 0.99,
+            epsilon: 0.1,
+            batch_size: 32,
+            replay_buffer_size: 10000,
+            update_frequency: 100,
+            algorithm: RLAlgorithm::QLearning,
+        };
+        let artifacts = ModelArtifactRegistry::new(None).unwrap();
+        let trainer = RLEngine::new(Arc::new(CognitiveBrain::new()), config.clone());
+        trainer.create_policy("grasp", &serde_json::json!({})).unwrap();
+        trainer.policies.write().get_mut("grasp").unwrap().q_values.insert("s:a".to_string(), 0.75);
+        trainer.publish_policy("grasp", &artifacts, "1.0.0").unwrap();
+
+        let robot = RLEngine::new(Arc::new(CognitiveBrain::new()), config);
+        assert!(robot.load_policy_from_registry("grasp", &artifacts).is_err());
+        artifacts.transition_stage("grasp", "1.0.0", ModelStage::Staging).unwrap();
+        artifacts.transition_stage("grasp", "1.0.0", ModelStage::Production).unwrap();
+        robot.load_policy_from_registry("grasp", &artifacts).unwrap();
+        assert_eq!(robot.policies.read()["grasp"].q_values.get("s:a"), Some(&0.75));
+    }
+}
@@ -0,0 +1,871 @@
+// Multi-primary replication between regions
+// Every region takes writes to a replicated table and ships them to the
+// others asynchronously. Replicated tables are system-versioned, so rows are
+// identified by the versioning key column and a write replaces the current
+// row for its key. Each key carries a vector clock: a change whose clock
+// descends from the local one replaces the row, and one concurrent with it
+// is a conflict, resolved per table:
+// - counter columns are CRDT counters: each write ships its delta and
+//   concurrent deltas all count
+// - set columns (JSON arrays) are observed-remove sets: each write ships
+//   the elements it added and removed, applied on top of the local set
+// - every other column goes to the last writer (by commit time, then region
+//   name), or to a merge worker that gets both rows and returns the merged one
+// Deletes always resolve by last write wins. Changes that came in from a
+// peer are not shipped on, so regions form a full mesh.
+//
+// Changes wait in memory until every peer has acknowledged them; when more
+// than `max_pending` wait, the oldest go and peers that hadn't got them miss
+// them (counted as dropped). A peer's lag is the age of the oldest change it
+// hasn't acknowledged; an origin's lag is how long its last applied change
+// took to arrive.
+
+use async_trait::async_trait;
+use narayana_core::config::ReplicationPeer;
+use narayana_core::{column::Column, schema::DataType, types::TableId, Error, Result};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{hash_map::Entry, BTreeMap, HashMap, VecDeque};
+use std::fmt::Write;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex;
+use tracing::warn;
+
+use crate::anomaly_detection::is_numeric;
+use crate::column_store::{ColumnStore, WriteLocked};
+use crate::quantum_sync::VectorClock;
+use crate::row_security::{cell, take_rows};
+use crate::temporal::{key_text, TemporalManager};
+
+/// Changes in one batch, shipped or applied
+pub const MAX_BATCH_CHANGES: usize = 1000;
+/// Longest error kept per peer
+const MAX_ERROR_LENGTH: usize = 512;
+
+/// How concurrent writes to a row's plain columns are resolved
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictStrategy {
+    /// The later write wins (by commit time, then region name)
+    #[default]
+    LastWriteWins,
+    /// `merge_worker` gets both versions and returns the merged row
+    MergeWorker,
+}
+
+/// How a table is replicated
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableReplication {
+    #[serde(default)]
+    pub strategy: ConflictStrategy,
+    /// Worker merging concurrent versions (strategy `merge_worker`)
+    #[serde(default)]
+    pub merge_worker: Option<String>,
+    /// Numeric columns replicated as counters
+    #[serde(default)]
+    pub counters: Vec<String>,
+    /// String or Json columns holding JSON arrays, replicated as sets
+    #[serde(default)]
+    pub sets: Vec<String>,
+}
+
+/// One write in the region it was made in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicatedChange {
+    pub origin: String,
+    /// Position in the origin's change stream
+    pub seq: u64,
+    /// Table name, the same in every region
+    pub table: String,
+    pub key: Value,
+    /// The key's clock once this change was made
+    pub clock: VectorClock,
+    /// When the change was made (Unix ms)
+    pub committed_at: u64,
+    #[serde(default)]
+    pub deleted: bool,
+    /// The row as written, one single-row column per field; empty for deletes
+    #[serde(default)]
+    pub row: Vec<Column>,
+    /// Counter deltas by column
+    #[serde(default)]
+    pub increments: BTreeMap<String, Value>,
+    /// Set elements added, by column
+    #[serde(default)]
+    pub added: BTreeMap<String, Vec<Value>>,
+    /// Set elements removed, by column
+    #[serde(default)]
+    pub removed: BTreeMap<String, Vec<Value>>,
+}
+
+/// Changes shipped from one region to another
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeBatch {
+    pub origin: String,
+    /// Identifies the origin's run; its sequence numbers restart with a new one
+    pub epoch: u64,
+    pub changes: Vec<ReplicatedChange>,
+}
+
+/// What applying a batch did
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ApplyReport {
+    pub applied: usize,
+    /// Changes already applied, or for tables not replicated here
+    pub skipped: usize,
+    pub conflicts: usize,
+    /// Last change of the origin applied, which it can stop resending
+    pub acked_seq: u64,
+    /// Columns written per table, for the caller's indexes
+    #[serde(skip)]
+    pub written: Vec<(TableId, Vec<Column>)>,
+}
+
+/// Sends change batches to a peer region
+#[async_trait]
+pub trait ReplicationTransport: Send + Sync {
+    async fn send(&self, peer: &ReplicationPeer, batch: &ChangeBatch) -> Result<ApplyReport>;
+}
+
+/// Runs merge workers for tables with the `merge_worker` strategy
+#[async_trait]
+pub trait MergeResolver: Send + Sync {
+    /// Merge two concurrent versions of a row into one, by column name
+    async fn merge(&self, worker_id: &str, table: &str, local: &Map<String, Value>, remote: &Map<String, Value>) -> Result<Map<String, Value>>;
+}
+
+/// Shipping state of one peer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerStatus {
+    pub region: String,
+    pub url: String,
+    pub acked_seq: u64,
+    /// Changes it hasn't acknowledged
+    pub pending: usize,
+    /// Age of the oldest of them (ms)
+    pub lag_ms: u64,
+    pub shipped: u64,
+    pub failures: u64,
+    pub last_shipped_at: Option<u64>,
+    pub last_error: Option<String>,
+}
+
+/// Changes applied from one origin region
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OriginStatus {
+    pub region: String,
+    pub applied_seq: u64,
+    pub applied: u64,
+    pub conflicts: u64,
+    /// Time from commit at the origin to apply here, for the last change (ms)
+    pub lag_ms: u64,
+    pub last_applied_at: Option<u64>,
+}
+
+/// Replication state of this region
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicationStatus {
+    pub region: String,
+    pub last_seq: u64,
+    /// Changes some peer still needs
+    pub pending: usize,
+    pub dropped: u64,
+    pub peers: Vec<PeerStatus>,
+    pub origins: Vec<OriginStatus>,
+    pub tables: BTreeMap<String, TableReplication>,
+}
+
+impl ReplicationStatus {
+    /// Prometheus text exposition of the lag and throughput figures
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+        let peer_metrics: [Metric<PeerStatus>; 4] = [
+            ("pending_changes", "gauge", "Changes a peer hasn't acknowledged", |p| p.pending as u64),
+            ("lag_ms", "gauge", "Age of the oldest change a peer hasn't acknowledged", |p| p.lag_ms),
+            ("shipped_total", "counter", "Changes acknowledged by a peer", |p| p.shipped),
+            ("ship_failures_total", "counter", "Failed shipments to a peer", |p| p.failures),
+        ];
+        for (name, kind, help, value) in peer_metrics {
+            let _ = writeln!(out, "# HELP narayana_replication_{} {}", name, help);
+            let _ = writeln!(out, "# TYPE narayana_replication_{} {}", name, kind);
+            for peer in &self.peers {
+                let _ = writeln!(out, "narayana_replication_{}{{peer=\"{}\"}} {}", name, escape_label(&peer.region), value(peer));
+            }
+        }
+        let origin_metrics: [Metric<OriginStatus>; 3] = [
+            ("applied_total", "counter", "Changes applied from an origin region", |o| o.applied),
+            ("conflicts_total", "counter", "Concurrent writes resolved", |o| o.conflicts),
+            ("apply_lag_ms", "gauge", "Commit-to-apply time of the last change from an origin", |o| o.lag_ms),
+        ];
+        for (name, kind, help, value) in origin_metrics {
+            let _ = writeln!(out, "# HELP narayana_replication_{} {}", name, help);
+            let _ = writeln!(out, "# TYPE narayana_replication_{} {}", name, kind);
+            for origin in &self.origins {
+                let _ = writeln!(out, "narayana_replication_{}{{origin=\"{}\"}} {}", name, escape_label(&origin.region), value(origin));
+            }
+        }
+        let _ = writeln!(out, "# HELP narayana_replication_dropped_total Changes dropped before every peer had them");
+        let _ = writeln!(out, "# TYPE narayana_replication_dropped_total counter");
+        let _ = writeln!(out, "narayana_replication_dropped_total {}", self.dropped);
+        out
+    }
+}
+
+/// Name, type, help and value of a per-peer or per-origin metric
+type Metric<T> = (&'static str, &'static str, &'static str, fn(&T) -> u64);
+
+/// Last write to a key, as this region knows it
+#[derive(Debug, Clone)]
+struct KeyState {
+    clock: VectorClock,
+    committed_at: u64,
+    region: String,
+    deleted: bool,
+}
+
+impl KeyState {
+    /// Whether this write wins over `other` by last write wins
+    fn wins_over(&self, committed_at: u64, region: &str) -> bool {
+        (self.committed_at, self.region.as_str()) > (committed_at, region)
+    }
+}
+
+struct ReplicatedTable {
+    table_id: TableId,
+    name: String,
+    config: RwLock<TableReplication>,
+    /// Held for the whole of a write or apply, so a key's clock and its
+    /// current row change together
+    keys: Mutex<HashMap<String, KeyState>>,
+}
+
+struct PeerState {
+    peer: ReplicationPeer,
+    acked: u64,
+    shipped: u64,
+    failures: u64,
+    last_shipped_at: Option<u64>,
+    last_error: Option<String>,
+}
+
+#[derive(Default)]
+struct Outbox {
+    changes: VecDeque<ReplicatedChange>,
+    last_seq: u64,
+    dropped: u64,
+}
+
+#[derive(Default)]
+struct OriginState {
+    epoch: u64,
+    status: OriginStatus,
+}
+
+/// Replicated tables of this region and their exchange with peers
+pub struct ReplicationManager {
+    region: String,
+    epoch: u64,
+    max_pending: usize,
+    peers: RwLock<Vec<PeerState>>,
+    tables: RwLock<HashMap<TableId, Arc<ReplicatedTable>>>,
+    outbox: parking_lot::Mutex<Outbox>,
+    origins: RwLock<HashMap<String, OriginState>>,
+    transport: Option<Arc<dyn ReplicationTransport>>,
+    merge: Option<Arc<dyn MergeResolver>>,
+    /// One shipment at a time, so acknowledgements arrive in order
+    shipping: Mutex<()>,
+}
+
+impl ReplicationManager {
+    pub fn new(region: String, peers: Vec<ReplicationPeer>, max_pending: usize) -> Self {
+        let peers = peers
+            .into_iter()
+            .map(|peer| PeerState { peer, acked: 0, shipped: 0, failures: 0, last_shipped_at: None, last_error: None })
+            .collect();
+        Self {
+            region,
+            epoch: now(),
+            max_pending: max_pending.max(1),
+            peers: RwLock::new(peers),
+            tables: RwLock::new(HashMap::new()),
+            outbox: parking_lot::Mutex::new(Outbox::default()),
+            origins: RwLock::new(HashMap::new()),
+            transport: None,
+            merge: None,
+            shipping: Mutex::new(()),
+        }
+    }
+
+    pub fn with_transport(mut self, transport: Arc<dyn ReplicationTransport>) -> Self {
+        self.transport = Some(transport);
+        self
+    }
+
+    pub fn with_merge_resolver(mut self, merge: Arc<dyn MergeResolver>) -> Self {
+        self.merge = Some(merge);
+        self
+    }
+
+    pub fn region(&self) -> &str {
+        &self.region
+    }
+
+    /// Replicate a system-versioned table, or change how it is replicated.
+    /// `name` must be the table's name in every region.
+    pub async fn configure(
+        &self,
+        store: &dyn ColumnStore,
+        temporal: &TemporalManager,
+        table_id: TableId,
+        name: String,
+        config: TableReplication,
+    ) -> Result<()> {
+        let versioning = temporal.status(table_id).await
+            .ok_or_else(|| Error::Storage("Replicated tables must be system-versioned".to_string()))?;
+        let schema = store.get_schema(table_id).await?;
+        let reserved = [&versioning.config.key_column, &versioning.config.period_column];
+        for column in config.counters.iter().chain(&config.sets) {
+            let field = schema.field(column).ok_or_else(|| Error::ColumnNotFound(column.clone()))?;
+            if reserved.contains(&column) {
+                return Err(Error::Storage(format!("Column '{}' is the versioning key or period column", column)));
+            }
+            let valid = if config.counters.contains(column) {
+                is_numeric(&field.data_type)
+            } else {
+                matches!(field.data_type, DataType::String | DataType::Json)
+            };
+            if !valid {
+                return Err(Error::Storage(format!(
+                    "Column '{}' can't be a {}",
+                    column,
+                    if config.counters.contains(column) { "counter (not numeric)" } else { "set (not String or Json)" }
+                )));
+            }
+        }
+        if config.counters.iter().any(|c| config.sets.contains(c)) {
+            return Err(Error::Storage("A column can't be both a counter and a set".to_string()));
+        }
+        if config.strategy == ConflictStrategy::MergeWorker && config.merge_worker.as_deref().is_none_or(str::is_empty) {
+            return Err(Error::Storage("The merge_worker strategy needs a merge_worker".to_string()));
+        }
+
+        if self.tables.read().values().any(|t| t.name == name && t.table_id != table_id) {
+            return Err(Error::Storage(format!("Another table is replicated as '{}'", name)));
+        }
+        match self.table(table_id) {
+            Some(table) if table.name == name => *table.config.write() = config,
+            // Keep the clocks of a table already replicated under another name
+            existing => {
+                let keys = match existing {
+                    Some(table) => std::mem::take(&mut *table.keys.lock().await),
+                    None => HashMap::new(),
+                };
+                let table = ReplicatedTable { table_id, name, config: RwLock::new(config), keys: Mutex::new(keys) };
+                self.tables.write().insert(table_id, Arc::new(table));
+            }
+        }
+        Ok(())
+    }
+
+    /// Stop replicating a table
+    pub fn remove(&self, table_id: TableId) -> bool {
+        self.tables.write().remove(&table_id).is_some()
+    }
+
+    /// Forget a dropped table
+    pub fn drop_table(&self, table_id: TableId) {
+        self.remove(table_id);
+    }
+
+    pub fn is_replicated(&self, table_id: TableId) -> bool {
+        self.tables.read().contains_key(&table_id)
+    }
+
+    /// Write rows, recording them for peers if the table is replicated
+    pub async fn write(&self, store: &dyn ColumnStore, temporal: &TemporalManager, table_id: TableId, columns: Vec<Column>) -> Result<Vec<Column>> {
+        let Some(table) = self.table(table_id) else {
+            return temporal.write(store, table_id, columns).await;
+        };
+        // The table's write lock comes before its key clocks
+        let guard = store.lock_table(table_id).await;
+        let store = &WriteLocked::new(store, &guard);
+        let mut clocks = table.keys.lock().await;
+        let config = table.config.read().clone();
+        let versioning = temporal.status(table_id).await
+            .ok_or_else(|| Error::Storage("Replicated tables must stay system-versioned".to_string()))?;
+        let schema = store.get_schema(table_id).await?;
+        let key_idx = schema.field_index(&versioning.config.key_column)
+            .ok_or_else(|| Error::ColumnNotFound(versioning.config.key_column.clone()))?;
+        let period_idx = schema.field_index(&versioning.config.period_column)
+            .ok_or_else(|| Error::ColumnNotFound(versioning.config.period_column.clone()))?;
+
+        // Counters and sets ship what changed, so they need the rows replaced
+        let mut previous: HashMap<String, Option<Map<String, Value>>> = HashMap::new();
+        if let Some(keys) = columns.get(key_idx) {
+            for row in 0..keys.len() {
+                let key = cell(keys, row);
+                if let Entry::Vacant(entry) = previous.entry(key_text(&key)) {
+                    entry.insert(temporal.current(store, table_id, &key).await?);
+                }
+            }
+        }
+
+        let written = temporal.write(store, table_id, columns).await?;
+        for row in 0..written.first().map_or(0, |c| c.len()) {
+            let key = cell(&written[key_idx], row);
+            let text = key_text(&key);
+            let values: Map<String, Value> = schema.fields.iter()
+                .zip(&written)
+                .map(|(field, column)| (field.name.clone(), cell(column, row)))
+                .collect();
+            let before = previous.insert(text.clone(), Some(values.clone())).flatten();
+            let committed_at = cell(&written[period_idx], row).as_i64().unwrap_or_default().unsigned_abs();
+
+            let mut change = self.change(&table.name, key, committed_at);
+            for column in &config.counters {
+                let old = before.as_ref().and_then(|b| b.get(column)).unwrap_or(&Value::Null);
+                change.increments.insert(column.clone(), sub(values.get(column).unwrap_or(&Value::Null), old));
+            }
+            for column in &config.sets {
+                let old = before.as_ref().and_then(|b| b.get(column)).map(set_elements).unwrap_or_default();
+                let new = values.get(column).map(set_elements).unwrap_or_default();
+                change.added.insert(column.clone(), new.iter().filter(|e| !old.contains(e)).cloned().collect());
+                change.removed.insert(column.clone(), old.into_iter().filter(|e| !new.contains(e)).collect());
+            }
+            change.row = written.iter().map(|column| take_rows(column, &[row])).collect();
+            change.clock = self.local_write(&mut clocks, &text, committed_at, false);
+            self.push(change);
+        }
+        Ok(written)
+    }
+
+    /// Delete rows by key, recording the deletes for peers if the table is
+    /// replicated; returns the tombstones written
+    pub async fn delete(&self, store: &dyn ColumnStore, temporal: &TemporalManager, table_id: TableId, keys: &[Value]) -> Result<Vec<Column>> {
+        let Some(table) = self.table(table_id) else {
+            return temporal.delete(store, table_id, keys).await;
+        };
+        // The table's write lock comes before its key clocks
+        let guard = store.lock_table(table_id).await;
+        let store = &WriteLocked::new(store, &guard);
+        let mut clocks = table.keys.lock().await;
+        let tombstones = temporal.delete(store, table_id, keys).await?;
+        let versioning = temporal.status(table_id).await
+            .ok_or_else(|| Error::Storage("Replicated tables must stay system-versioned".to_string()))?;
+        let schema = store.get_schema(table_id).await?;
+        let (Some(key_idx), Some(period_idx)) = (
+            schema.field_index(&versioning.config.key_column),
+            schema.field_index(&versioning.config.period_column),
+        ) else {
+            return Ok(tombstones);
+        };
+        for row in 0..tombstones.first().map_or(0, |c| c.len()) {
+            let key = cell(&tombstones[key_idx], row);
+            let text = key_text(&key);
+            let committed_at = cell(&tombstones[period_idx], row).as_i64().unwrap_or_default().unsigned_abs();
+            let mut change = self.change(&table.name, key, committed_at);
+            change.deleted = true;
+            change.clock = self.local_write(&mut clocks, &text, committed_at, true);
+            self.push(change);
+        }
+        Ok(tombstones)
+    }
+
+    /// Apply a batch of changes from a peer; changes already applied are skipped
+    pub async fn apply(&self, store: &dyn ColumnStore, temporal: &TemporalManager, batch: ChangeBatch) -> Result<ApplyReport> {
+        if batch.origin == self.region {
+            return Err(Error::Storage(format!("Changes from this region ('{}')", self.region)));
+        }
+        if batch.changes.len() > MAX_BATCH_CHANGES {
+            return Err(Error::Storage(format!("At most {} changes per batch", MAX_BATCH_CHANGES)));
+        }
+        {
+            // A new run of the origin numbers its changes from the start again
+            let mut origins = self.origins.write();
+            let origin = origins.entry(batch.origin.clone()).or_default();
+            if origin.epoch != batch.epoch {
+                origin.epoch = batch.epoch;
+                origin.status.applied_seq = 0;
+            }
+        }
+
+        let mut report = ApplyReport::default();
+        for change in batch.changes {
+            let applied_seq = self.origins.read().get(&batch.origin).map_or(0, |o| o.status.applied_seq);
+            if change.origin != batch.origin || change.seq <= applied_seq {
+                report.skipped += 1;
+                continue;
+            }
+            let outcome = match self.table_named(&change.table) {
+                Some(table) => Some(self.apply_change(store, temporal, &table, &change).await?),
+                None => {
+                    warn!("Skipping replicated change for table '{}', which isn't replicated here", change.table);
+                    None
+                }
+            };
+            let received_at = now();
+            let mut origins = self.origins.write();
+            let origin = &mut origins.entry(batch.origin.clone()).or_default().status;
+            origin.applied_seq = change.seq;
+            match outcome {
+                Some((conflict, written)) => {
+                    report.applied += 1;
+                    origin.applied += 1;
+                    if conflict {
+                        report.conflicts += 1;
+                        origin.conflicts += 1;
+                    }
+                    origin.lag_ms = received_at.saturating_sub(change.committed_at);
+                    origin.last_applied_at = Some(received_at);
+                    report.written.extend(written);
+                }
+                None => report.skipped += 1,
+            }
+        }
+        report.acked_seq = self.origins.read().get(&batch.origin).map_or(0, |o| o.status.applied_seq);
+        Ok(report)
+    }
+
+    /// Apply one change; returns whether it conflicted and what was written
+    async fn apply_change(
+        &self,
+        store: &dyn ColumnStore,
+        temporal: &TemporalManager,
+        table: &ReplicatedTable,
+        change: &ReplicatedChange,
+    ) -> Result<(bool, Option<(TableId, Vec<Column>)>)> {
+        let table_id = table.table_id;
+        // The table's write lock comes before its key clocks
+        let guard = store.lock_table(table_id).await;
+        let store = &WriteLocked::new(store, &guard);
+        let mut clocks = table.keys.lock().await;
+        let config = table.config.read().clone();
+        let schema = store.get_schema(table_id).await?;
+        if !change.deleted && change.row.len() != schema.fields.len() {
+            return Err(Error::Storage(format!(
+                "Change for table '{}' has {} columns, expected {}",
+                change.table, change.row.len(), schema.fields.len()
+            )));
+        }
+        let key = key_text(&change.key);
+        let local = clocks.get(&key).cloned();
+        let concurrent = match &local {
+            None => false,
+            // Already reflected here
+            Some(state) if state.clock == change.clock || change.clock.happened_before(&state.clock) => {
+                return Ok((false, None));
+            }
+            Some(state) => !state.clock.happened_before(&change.clock),
+        };
+
+        let remote_wins = local.as_ref().is_none_or(|state| !state.wins_over(change.committed_at, &change.origin));
+        let mut state = KeyState {
+            clock: change.clock.clone(),
+            committed_at: change.committed_at,
+            region: change.origin.clone(),
+            deleted: change.deleted,
+        };
+        let current = match &local {
+            Some(local) if concurrent && !local.deleted && !change.deleted => temporal.current(store, table_id, &change.key).await?,
+            _ => None,
+        };
+        let written = match (concurrent, current) {
+            // Both sides wrote the row: merge
+            (true, Some(current)) => {
+                let local = local.as_ref().expect("concurrent writes have local state");
+                let remote: Map<String, Value> = schema.fields.iter()
+                    .zip(&change.row)
+                    .map(|(field, column)| (field.name.clone(), cell(column, 0)))
+                    .collect();
+                let mut merged = match config.strategy {
+                    ConflictStrategy::LastWriteWins if remote_wins => remote.clone(),
+                    ConflictStrategy::LastWriteWins => current.clone(),
+                    ConflictStrategy::MergeWorker => {
+                        let merge = self.merge.as_ref()
+                            .ok_or_else(|| Error::Storage("No merge worker runtime available".to_string()))?;
+                        let worker = config.merge_worker.as_deref().unwrap_or_default();
+                        merge.merge(worker, &table.name, &current, &remote).await?
+                    }
+                };
+                for column in &config.counters {
+                    let increment = change.increments.get(column).unwrap_or(&Value::Null);
+                    merged.insert(column.clone(), add(current.get(column).unwrap_or(&Value::Null), increment));
+                }
+                for column in &config.sets {
+                    let removed = change.removed.get(column).cloned().unwrap_or_default();
+                    let mut elements: Vec<Value> = set_elements(current.get(column).unwrap_or(&Value::Null))
+                        .into_iter()
+                        .filter(|e| !removed.contains(e))
+                        .collect();
+                    for element in change.added.get(column).into_iter().flatten() {
+                        if !elements.contains(element) {
+                            elements.push(element.clone());
+                        }
+                    }
+                    merged.insert(column.clone(), Value::String(Value::Array(elements).to_string()));
+                }
+                let row = schema.fields.iter()
+                    .zip(&change.row)
+                    .map(|(field, template)| {
+                        let value = merged.get(&field.name).or_else(|| remote.get(&field.name)).unwrap_or(&Value::Null);
+                        column_like(template, value)
+                            .map_err(|e| Error::Storage(format!("Merged value of '{}': {}", field.name, e)))
+                    })
+                    .collect::<Result<Vec<Column>>>()?;
+                if !remote_wins {
+                    state.committed_at = local.committed_at;
+                    state.region = local.region.clone();
+                }
+                Some(temporal.write(store, table_id, row).await?)
+            }
+            // A delete on either side: the later write wins
+            (true, None) if !remote_wins => {
+                let local = local.as_ref().expect("concurrent writes have local state");
+                state.committed_at = local.committed_at;
+                state.region = local.region.clone();
+                state.deleted = local.deleted;
+                None
+            }
+            _ if change.deleted => Some(temporal.delete(store, table_id, std::slice::from_ref(&change.key)).await?),
+            _ => Some(temporal.write(store, table_id, change.row.clone()).await?),
+        };
+        if let Some(local) = &local {
+            state.clock.merge(&local.clock);
+        }
+        clocks.insert(key, state);
+        Ok((concurrent, written.filter(|w| !w.is_empty()).map(|w| (table_id, w))))
+    }
+
+    /// Ship pending changes to every peer; returns the changes acknowledged
+    pub async fn ship(&self) -> usize {
+        let Some(transport) = &self.transport else {
+            return 0;
+        };
+        let _shipping = self.shipping.lock().await;
+        let targets: Vec<(ReplicationPeer, u64)> = self.peers.read().iter().map(|p| (p.peer.clone(), p.acked)).collect();
+        let mut acknowledged = 0;
+        for (index, (peer, acked)) in targets.into_iter().enumerate() {
+            let changes: Vec<ReplicatedChange> = self.outbox.lock().changes
+                .iter()
+                .filter(|c| c.seq > acked)
+                .take(MAX_BATCH_CHANGES)
+                .cloned()
+                .collect();
+            let Some(last) = changes.last().map(|c| c.seq) else { continue };
+            let batch = ChangeBatch { origin: self.region.clone(), epoch: self.epoch, changes };
+            let result = transport.send(&peer, &batch).await;
+            let mut peers = self.peers.write();
+            let state = &mut peers[index];
+            match result {
+                Ok(report) => {
+                    let acked_now = report.acked_seq.min(last).max(state.acked);
+                    let count = batch.changes.iter().filter(|c| c.seq > state.acked && c.seq <= acked_now).count();
+                    acknowledged += count;
+                    state.shipped += count as u64;
+                    state.acked = acked_now;
+                    state.last_shipped_at = Some(now());
+                    state.last_error = None;
+                }
+                Err(e) => {
+                    let mut error = e.to_string();
+                    if error.len() > MAX_ERROR_LENGTH {
+                        error.truncate(error.floor_char_boundary(MAX_ERROR_LENGTH));
+                    }
+                    warn!("Failed to ship changes to region {}: {}", peer.region, error);
+                    state.failures += 1;
+                    state.last_error = Some(error);
+                }
+            }
+        }
+
+        // Every peer has what's left behind the slowest one
+        let min_acked = self.peers.read().iter().map(|p| p.acked).min().unwrap_or(u64::MAX);
+        let mut outbox = self.outbox.lock();
+        while outbox.changes.front().is_some_and(|c| c.seq <= min_acked) {
+            outbox.changes.pop_front();
+        }
+        acknowledged
+    }
+
+    pub fn status(&self) -> ReplicationStatus {
+        let now = now();
+        let outbox = self.outbox.lock();
+        let peers = self.peers.read().iter().map(|p| {
+            let mut pending = outbox.changes.iter().filter(|c| c.seq > p.acked);
+            let oldest = pending.next();
+            PeerStatus {
+                region: p.peer.region.clone(),
+                url: p.peer.url.clone(),
+                acked_seq: p.acked,
+                pending: oldest.map_or(0, |_| 1 + pending.count()),
+                lag_ms: oldest.map_or(0, |c| now.saturating_sub(c.committed_at)),
+                shipped: p.shipped,
+                failures: p.failures,
+                last_shipped_at: p.last_shipped_at,
+                last_error: p.last_error.clone(),
+            }
+        }).collect();
+        let mut origins: Vec<OriginStatus> = self.origins.read().iter()
+            .map(|(region, origin)| OriginStatus { region: region.clone(), ..origin.status.clone() })
+            .collect();
+        origins.sort_by(|a, b| a.region.cmp(&b.region));
+        let tables = self.tables.read().values()
+            .map(|t| (t.name.clone(), t.config.read().clone()))
+            .collect();
+        ReplicationStatus {
+            region: self.region.clone(),
+            last_seq: outbox.last_seq,
+            pending: outbox.changes.len(),
+            dropped: outbox.dropped,
+            peers,
+            origins,
+            tables,
+        }
+    }
+
+    fn table(&self, table_id: TableId) -> Option<Arc<ReplicatedTable>> {
+        self.tables.read().get(&table_id).cloned()
+    }
+
+    fn table_named(&self, name: &str) -> Option<Arc<ReplicatedTable>> {
+        self.tables.read().values().find(|t| t.name == name).cloned()
+    }
+
+    /// Record a write made here; returns the key's new clock
+    fn local_write(&self, keys: &mut HashMap<String, KeyState>, key: &str, committed_at: u64, deleted: bool) -> VectorClock {
+        let state = keys.entry(key.to_string()).or_insert_with(|| KeyState {
+            clock: VectorClock::new(self.region.clone()),
+            committed_at,
+            region: self.region.clone(),
+            deleted,
+        });
+        state.clock.tick(&self.region);
+        state.committed_at = committed_at;
+        state.region = self.region.clone();
+        state.deleted = deleted;
+        state.clock.clone()
+    }
+
+    fn change(&self, table: &str, key: Value, committed_at: u64) -> ReplicatedChange {
+        ReplicatedChange {
+            origin: self.region.clone(),
+            seq: 0,
+            table: table.to_string(),
+            key,
+            clock: VectorClock::new(self.region.clone()),
+            committed_at,
+            deleted: false,
+            row: Vec::new(),
+            increments: BTreeMap::new(),
+            added: BTreeMap::new(),
+            removed: BTreeMap::new(),
+        }
+    }
+
+    /// Number a change and keep it until every peer has it
+    fn push(&self, mut change: ReplicatedChange) {
+        let mut outbox = self.outbox.lock();
+        outbox.last_seq += 1;
+        change.seq = outbox.last_seq;
+        if self.peers.read().is_empty() {
+            return;
+        }
+        outbox.changes.push_back(change);
+        while outbox.changes.len() > self.max_pending {
+            outbox.changes.pop_front();
+            outbox.dropped += 1;
+        }
+    }
+}
+
+/// `value` as a one-row column of `template`'s type
+fn column_like(template: &Column, value: &Value) -> std::result::Result<Column, String> {
+    fn int<T: TryFrom<i64>>(value: &Value) -> std::result::Result<T, String> {
+        value.as_i64()
+            .or_else(|| value.as_f64().filter(|f| f.fract() == 0.0).map(|f| f as i64))
+            .and_then(|n| T::try_from(n).ok())
+            .ok_or_else(|| format!("expected an integer in range, got {}", value))
+    }
+    fn uint<T: TryFrom<u64>>(value: &Value) -> std::result::Result<T, String> {
+        value.as_u64()
+            .and_then(|n| T::try_from(n).ok())
+            .ok_or_else(|| format!("expected a non-negative integer in range, got {}", value))
+    }
+    fn float(value: &Value) -> std::result::Result<f64, String> {
+        value.as_f64().ok_or_else(|| format!("expected a number, got {}", value))
+    }
+    Ok(match template {
+        Column::Int8(_) => Column::Int8(vec![int(value)?]),
+        Column::Int16(_) => Column::Int16(vec![int(value)?]),
+        Column::Int32(_) => Column::Int32(vec![int(value)?]),
+        Column::Int64(_) => Column::Int64(vec![int(value)?]),
+        Column::UInt8(_) => Column::UInt8(vec![uint(value)?]),
+        Column::UInt16(_) => Column::UInt16(vec![uint(value)?]),
+        Column::UInt32(_) => Column::UInt32(vec![uint(value)?]),
+        Column::UInt64(_) => Column::UInt64(vec![uint(value)?]),
+        Column::Float32(_) => Column::Float32(vec![float(value)? as f32]),
+        Column::Float64(_) => Column::Float64(vec![float(value)?]),
+        Column::Boolean(_) => Column::Boolean(vec![value.as_bool().ok_or_else(|| format!("expected a boolean, got {}", value))?]),
+        Column::String(_) => Column::String(vec![match value {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        }]),
+        Column::Binary(_) => Column::Binary(vec![value.as_str()
+            .and_then(|s| hex::decode(s).ok())
+            .ok_or_else(|| "expected hex-encoded bytes".to_string())?]),
+        Column::Timestamp(_) => Column::Timestamp(vec![int(value)?]),
+        Column::Date(_) => Column::Date(vec![int(value)?]),
+    })
+}
+
+/// `a + b` for counter values; null counts as 0
+fn add(a: &Value, b: &Value) -> Value {
+    match (a.as_i64().or(a.is_null().then_some(0)), b.as_i64().or(b.is_null().then_some(0))) {
+        (Some(a), Some(b)) => Value::from(a.saturating_add(b)),
+        _ => number(a.as_f64().unwrap_or_default() + b.as_f64().unwrap_or_default()),
+    }
+}
+
+/// `a - b` for counter values; null counts as 0
+fn sub(a: &Value, b: &Value) -> Value {
+    match (a.as_i64().or(a.is_null().then_some(0)), b.as_i64().or(b.is_null().then_some(0))) {
+        (Some(a), Some(b)) => Value::from(a.saturating_sub(b)),
+        _ => number(a.as_f64().unwrap_or_default() - b.as_f64().unwrap_or_default()),
+    }
+}
+
+fn number(value: f64) -> Value {
+    serde_json::Number::from_f64(value).map_or(Value::Null, Value::Number)
+}
+
+/// Elements of a set column's JSON array text, without duplicates
+fn set_elements(value: &Value) -> Vec<Value> {
+    let items = match value {
+        Value::String(text) => match serde_json::from_str(text) {
+            Ok(Value::Array(items)) => items,
+            _ => Vec::new(),
+        },
+        Value::Array(items) => items.clone(),
+        _ => Vec::new(),
+    };
+    let mut elements = Vec::with_capacity(items.len());
+    for item in items {
+        if !elements.contains(&item) {
+            elements.push(item);
+        }
+    }
+    elements
+}
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
@@ -0,0 +1,344 @@
+// RocksDB-backed columnar storage
+// Same compressed blocks as the file store, kept in one RocksDB instance; a
+// write lands its blocks and the updated table metadata in a single batch, as
+// does replacing a table's rows.
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use narayana_core::{Error, Result, schema::Schema, types::{TableId, CompressionType}, column::Column};
+use parking_lot::RwLock;
+use rocksdb::{DBCompressionType, Direction, IteratorMode, Options, WriteBatch, WriteOptions, DB};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::block::{self, Block, BlockMetadata, BlockMetadataV1};
+use crate::column_store::{ColumnStore, TableLocks, TableWriteGuard};
+use crate::reader::ColumnReader;
+use crate::writer::ColumnWriter;
+
+const META_PREFIX: &str = "meta/";
+
+/// Column store keeping table metadata and column blocks in RocksDB
+pub struct RocksDbColumnStore {
+    path: PathBuf,
+    db: Arc<DB>,
+    tables: RwLock<HashMap<TableId, TableMetadata>>,
+    block_writer: ColumnWriter,
+    block_reader: ColumnReader,
+    locks: TableLocks,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+struct TableMetadata<B = BlockMetadata> {
+    #[serde(with = "crate::column_store::schema_json")]
+    schema: Schema,
+    block_metadata: HashMap<u32, Vec<B>>,
+    row_count: usize,
+}
+
+impl From<TableMetadata<BlockMetadataV1>> for TableMetadata {
+    fn from(v1: TableMetadata<BlockMetadataV1>) -> Self {
+        Self {
+            schema: v1.schema,
+            block_metadata: block::upgrade_blocks(v1.block_metadata),
+            row_count: v1.row_count,
+        }
+    }
+}
+
+fn meta_key(table_id: TableId) -> String {
+    format!("{}{:020}", META_PREFIX, table_id.0)
+}
+
+fn block_key(table_id: TableId, column_id: u32, block_id: u64) -> String {
+    format!("block/{:020}/{:010}/{:020}", table_id.0, column_id, block_id)
+}
+
+impl RocksDbColumnStore {
+    /// Open (or create) the database at `path` and load every table's metadata
+    pub fn open(path: impl AsRef<Path>, compression: CompressionType) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        std::fs::create_dir_all(&path)
+            .map_err(|e| Error::Storage(format!("Failed to create RocksDB directory: {}", e)))?;
+
+        let mut options = Options::default();
+        options.create_if_missing(true);
+        // Blocks are already compressed by the column writer
+        options.set_compression_type(DBCompressionType::None);
+        let db = DB::open(&options, &path)
+            .map_err(|e| Error::Storage(format!("RocksDB open error: {}", e)))?;
+
+        let store = Self {
+            path,
+            db: Arc::new(db),
+            tables: RwLock::new(HashMap::new()),
+            block_writer: ColumnWriter::new(compression, 64 * 1024), // 64KB blocks
+            block_reader: ColumnReader::new(compression),
+            locks: TableLocks::default(),
+        };
+        store.load_tables()?;
+        Ok(store)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn table_ids(&self) -> Vec<TableId> {
+        self.tables.read().keys().copied().collect()
+    }
+
+    fn load_tables(&self) -> Result<()> {
+        let mut tables = self.tables.write();
+        for item in self.db.iterator(IteratorMode::From(META_PREFIX.as_bytes(), Direction::Forward)) {
+            let (key, value) = item.map_err(|e| Error::Storage(format!("RocksDB iterator error: {}", e)))?;
+            let Some(id) = key.strip_prefix(META_PREFIX.as_bytes()) else { break };
+            let Some(table_id) = std::str::from_utf8(id).ok().and_then(|id| id.parse().ok()).map(TableId) else {
+                warn!("Skipping unrecognised RocksDB metadata key {:?}", String::from_utf8_lossy(&key));
+                continue;
+            };
+            match block::decode_with_stats::<TableMetadata, TableMetadata<BlockMetadataV1>>(&value, TableMetadata::from) {
+                Ok(metadata) => {
+                    tables.insert(table_id, metadata);
+                }
+                Err(e) => warn!("Failed to deserialize metadata for table {}: {}. Skipping.", table_id.0, e),
+            }
+        }
+        info!("Loaded {} tables from RocksDB at {:?}", tables.len(), self.path);
+        Ok(())
+    }
+
+    fn put_metadata(&self, batch: &mut WriteBatch, table_id: TableId, metadata: &TableMetadata) -> Result<()> {
+        let bytes = block::encode_with_stats(metadata)
+            .map_err(|e| Error::Serialization(format!("Failed to serialize metadata: {}", e)))?;
+        batch.put(meta_key(table_id), bytes);
+        Ok(())
+    }
+
+    /// Durably write `batch` on the blocking pool so the fsync doesn't stall the executor
+    async fn commit(&self, batch: WriteBatch) -> Result<()> {
+        let db = self.db.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut options = WriteOptions::default();
+            options.set_sync(true);
+            db.write_opt(batch, &options)
+        })
+        .await
+        .map_err(|e| Error::Storage(format!("RocksDB write task failed: {}", e)))?
+        .map_err(|e| Error::Storage(format!("RocksDB write error: {}", e)))
+    }
+
+    fn metadata(&self, table_id: TableId) -> Result<TableMetadata> {
+        self.tables
+            .read()
+            .get(&table_id)
+            .cloned()
+            .ok_or_else(|| Error::Storage(format!("Table {} not found", table_id.0)))
+    }
+}
+
+#[async_trait]
+impl ColumnStore for RocksDbColumnStore {
+    async fn create_table(&self, table_id: TableId, schema: Schema) -> Result<()> {
+        let _guard = self.locks.lock(table_id).await;
+        if self.tables.read().contains_key(&table_id) {
+            return Err(Error::Storage(format!("Table {} already exists", table_id.0)));
+        }
+        let metadata = TableMetadata {
+            schema,
+            block_metadata: HashMap::new(),
+            row_count: 0,
+        };
+        let mut batch = WriteBatch::default();
+        self.put_metadata(&mut batch, table_id, &metadata)?;
+        self.commit(batch).await?;
+        self.tables.write().insert(table_id, metadata);
+
+        info!("Created RocksDB table {}", table_id.0);
+        Ok(())
+    }
+
+    async fn write_columns(&self, table_id: TableId, columns: Vec<Column>) -> Result<()> {
+        let guard = self.locks.lock(table_id).await;
+        self.write_locked(&guard, columns).await
+    }
+
+    async fn lock_table(&self, table_id: TableId) -> TableWriteGuard {
+        self.locks.lock(table_id).await
+    }
+
+    async fn write_locked(&self, guard: &TableWriteGuard, columns: Vec<Column>) -> Result<()> {
+        let table_id = guard.table_id();
+        // Compress outside the lock
+        let mut encoded = Vec::with_capacity(columns.len());
+        for (idx, column) in columns.iter().enumerate() {
+            let column_id = idx as u32;
+            encoded.push((column_id, self.block_writer.write_column(column, column_id)?));
+        }
+
+        // The table guard orders metadata versions; the map lock is only
+        // taken to snapshot and publish them
+        let mut metadata = self.metadata(table_id)?;
+        let mut batch = WriteBatch::default();
+        for (column_id, blocks) in encoded {
+            let existing = metadata.block_metadata.entry(column_id).or_default();
+            // The writer numbers blocks and rows from zero; continue after what is stored
+            let mut next_block = existing.iter().map(|b| b.block_id + 1).max().unwrap_or(0);
+            let row_offset: usize = existing.iter().map(|b| b.row_count).sum();
+            for (block, mut block_meta) in blocks {
+                block_meta.block_id = next_block;
+                block_meta.row_start += row_offset;
+                next_block += 1;
+                batch.put(block_key(table_id, column_id, block_meta.block_id), &block.data);
+                existing.push(block_meta);
+            }
+            let column_rows: usize = existing.iter().map(|b| b.row_count).sum();
+            metadata.row_count = metadata.row_count.max(column_rows);
+        }
+        self.put_metadata(&mut batch, table_id, &metadata)?;
+        self.commit(batch).await?;
+        self.tables.write().insert(table_id, metadata);
+        Ok(())
+    }
+
+    async fn read_columns(
+        &self,
+        table_id: TableId,
+        column_ids: Vec<u32>,
+        row_start: usize,
+        row_count: usize,
+    ) -> Result<Vec<Column>> {
+        let row_end = row_start.saturating_add(row_count);
+        let blocks_to_read: Vec<(u32, Vec<BlockMetadata>)> = {
+            let tables = self.tables.read();
+            let table = tables
+                .get(&table_id)
+                .ok_or_else(|| Error::Storage(format!("Table {} not found", table_id.0)))?;
+            column_ids
+                .iter()
+                .filter_map(|&column_id| {
+                    table.block_metadata.get(&column_id).map(|blocks| {
+                        let relevant = blocks
+                            .iter()
+                            .filter(|b| row_start < b.row_start + b.row_count && row_end > b.row_start)
+                            .cloned()
+                            .collect();
+                        (column_id, relevant)
+                    })
+                })
+                .collect()
+        };
+
+        let mut result = Vec::new();
+        for (column_id, blocks) in blocks_to_read {
+            let Some(first_row) = blocks.first().map(|b| b.row_start) else { continue };
+            let mut column_data: Option<Column> = None;
+            for block_meta in blocks {
+                let data = self
+                    .db
+                    .get(block_key(table_id, column_id, block_meta.block_id))
+                    .map_err(|e| Error::Storage(format!("RocksDB get error: {}", e)))?
+                    .ok_or_else(|| Error::Storage(format!(
+                        "Missing block {} of column {} in table {}",
+                        block_meta.block_id, column_id, table_id.0
+                    )))?;
+                let block = Block {
+                    column_id,
+                    data: Bytes::from(data),
+                    row_count: block_meta.row_count,
+                    data_type: block_meta.data_type.clone(),
+                    compression: block_meta.compression,
+                    uncompressed_size: block_meta.uncompressed_size,
+                    compressed_size: block_meta.compressed_size,
+                };
+                let decompressed = self.block_reader.read_block(&block)?;
+                column_data = Some(match column_data.take() {
+                    None => decompressed,
+                    Some(existing) => existing.append(&decompressed)?,
+                });
+            }
+            if let Some(column) = column_data {
+                // Blocks were picked by row range; trim to the exact rows asked for
+                let offset = row_start.saturating_sub(first_row).min(column.len());
+                let count = row_count.min(column.len() - offset);
+                result.push(column.slice(offset, count)?);
+            }
+        }
+        Ok(result)
+    }
+
+    async fn get_schema(&self, table_id: TableId) -> Result<Schema> {
+        self.tables
+            .read()
+            .get(&table_id)
+            .map(|table| table.schema.clone())
+            .ok_or_else(|| Error::Storage(format!("Table {} not found", table_id.0)))
+    }
+
+    async fn get_block_metadata(
+        &self,
+        table_id: TableId,
+        column_id: u32,
+    ) -> Result<Vec<BlockMetadata>> {
+        let tables = self.tables.read();
+        let table = tables
+            .get(&table_id)
+            .ok_or_else(|| Error::Storage(format!("Table {} not found", table_id.0)))?;
+        Ok(table.block_metadata.get(&column_id).cloned().unwrap_or_default())
+    }
+
+    async fn replace_rows(&self, guard: &TableWriteGuard, columns: Vec<Column>) -> Result<()> {
+        let table_id = guard.table_id();
+        let mut encoded = Vec::with_capacity(columns.len());
+        for (idx, column) in columns.iter().enumerate().filter(|(_, c)| c.len() > 0) {
+            let column_id = idx as u32;
+            encoded.push((column_id, self.block_writer.write_column(column, column_id)?));
+        }
+
+        let mut metadata = TableMetadata {
+            schema: self.metadata(table_id)?.schema,
+            block_metadata: HashMap::new(),
+            row_count: 0,
+        };
+        let mut batch = WriteBatch::default();
+        batch.delete_range(
+            format!("block/{:020}/", table_id.0),
+            format!("block/{:020}0", table_id.0),
+        );
+        for (column_id, blocks) in encoded {
+            let stored = metadata.block_metadata.entry(column_id).or_default();
+            for (block, block_meta) in blocks {
+                batch.put(block_key(table_id, column_id, block_meta.block_id), &block.data);
+                stored.push(block_meta);
+            }
+            let column_rows: usize = stored.iter().map(|b| b.row_count).sum();
+            metadata.row_count = metadata.row_count.max(column_rows);
+        }
+        self.put_metadata(&mut batch, table_id, &metadata)?;
+        self.commit(batch).await?;
+        self.tables.write().insert(table_id, metadata);
+        Ok(())
+    }
+
+    async fn delete_table(&self, table_id: TableId) -> Result<()> {
+        let guard = self.locks.lock(table_id).await;
+        if !self.tables.read().contains_key(&table_id) {
+            return Err(Error::Storage(format!("Table {} not found", table_id.0)));
+        }
+        let mut batch = WriteBatch::default();
+        batch.delete(meta_key(table_id));
+        // '0' sorts right after '/', so this range covers every block of the table
+        batch.delete_range(
+            format!("block/{:020}/", table_id.0),
+            format!("block/{:020}0", table_id.0),
+        );
+        self.commit(batch).await?;
+        self.tables.write().remove(&table_id);
+        self.locks.release(guard);
+
+        info!("Deleted RocksDB table {}", table_id.0);
+        Ok(())
+    }
+}
@@ -0,0 +1,885 @@
+// Row-level security and column masking
+// Each table can carry row policies - predicates over the row's columns and
+// the caller's claims - and column masks. Reads go through a
+// `SecuredColumnStore` built for the caller, so every access path that scans
+// through it (REST, WebSocket, workers and the query executor) sees the same
+// filtered, masked rows. The server serves neither GraphQL nor the
+// connection-based gRPC services; embedders that do build their
+// `DirectConnection` with `for_principal` to get the same rows.
+//
+// A policy applies to callers holding any of its roles (every caller when it
+// lists none). A row is visible when any applicable policy admits it. Once a
+// table has policies, callers none of them apply to see no rows at all.
+// Rows past a TTL column's expiry are left out before policies see them.
+// `row_start` and `row_count` address the table's rows before filtering.
+//
+// Policy syntax: column names, string, number, boolean and null literals,
+// `$sub`, `$tenant` (null without a tenant claim), comparisons
+// (== != > >= < <=), `in` against a literal list or `$roles`, `!`, `&&`,
+// `||` and parentheses. A bare column is true unless it is false or 0.
+//
+//   tenant_id == $tenant || 'auditor' in $roles
+//
+// A mask replaces a column's values with a hash, a partial reveal or a
+// redaction for callers holding any of its roles (every caller when it lists
+// none). Masked columns are returned as strings. When several masks cover a
+// column the first applicable one by name wins.
+
+use async_trait::async_trait;
+use narayana_core::{column::Column, schema::Schema, types::TableId, Error, Result};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::Arc;
+
+use crate::block::{BlockMetadata, BlockPredicate};
+use crate::column_store::{ColumnStore, TableWriteGuard};
+use crate::ttl::TtlColumnStore;
+
+/// Longest policy expression accepted
+const MAX_EXPRESSION_LENGTH: usize = 4096;
+/// Deepest nesting of `!` and parentheses
+const MAX_DEPTH: usize = 32;
+const MAX_POLICIES_PER_TABLE: usize = 64;
+const MAX_MASKS_PER_TABLE: usize = 256;
+const MAX_NAME_LENGTH: usize = 128;
+/// What a redacted value reads as
+const REDACTED: &str = "****";
+
+/// Who is reading: the claims policies and masks are evaluated against
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Principal {
+    pub subject: String,
+    #[serde(default)]
+    pub tenant: Option<String>,
+    #[serde(default)]
+    pub roles: Vec<String>,
+}
+
+impl Principal {
+    pub fn new(subject: impl Into<String>, roles: Vec<String>) -> Self {
+        Self { subject: subject.into(), tenant: None, roles }
+    }
+
+    pub fn with_tenant(mut self, tenant: Option<String>) -> Self {
+        self.tenant = tenant;
+        self
+    }
+
+    /// Whether something restricted to `roles` applies to this caller
+    fn covered_by(&self, roles: &[String]) -> bool {
+        roles.is_empty() || roles.iter().any(|role| self.roles.contains(role))
+    }
+}
+
+/// A row policy: rows it applies to are visible only where `expression` holds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RowPolicy {
+    pub name: String,
+    pub expression: String,
+    /// Roles the policy applies to; every caller when empty
+    #[serde(default)]
+    pub roles: Vec<String>,
+}
+
+impl RowPolicy {
+    /// Columns the expression reads, so they can be checked against the schema
+    pub fn columns(&self) -> Result<Vec<String>> {
+        let expr = Expr::parse(&self.expression)?;
+        let mut names = Vec::new();
+        expr.columns(&mut names);
+        let mut names: Vec<String> = names.into_iter().map(str::to_string).collect();
+        names.sort();
+        names.dedup();
+        Ok(names)
+    }
+}
+
+/// How a masked column's values are rewritten
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "function", rename_all = "snake_case")]
+pub enum MaskFunction {
+    /// Hex SHA-256 of the value; equal values still hash alike
+    Hash,
+    /// Keep the first and last few characters and star out the rest
+    Partial {
+        #[serde(default)]
+        show_first: usize,
+        #[serde(default)]
+        show_last: usize,
+    },
+    /// Replace the value entirely
+    Redact,
+}
+
+/// A mask on one column
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColumnMask {
+    pub name: String,
+    pub column: String,
+    #[serde(flatten)]
+    pub function: MaskFunction,
+    /// Roles the mask applies to; every caller when empty
+    #[serde(default)]
+    pub roles: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct TableSecurity {
+    policies: Vec<(RowPolicy, Arc<Expr>)>,
+    masks: Vec<ColumnMask>,
+}
+
+impl TableSecurity {
+    fn is_empty(&self) -> bool {
+        self.policies.is_empty() && self.masks.is_empty()
+    }
+}
+
+/// Row policies and column masks for every table
+#[derive(Debug, Default)]
+pub struct RowSecurity {
+    // Copied on write so reads only hold the lock to clone an Arc
+    tables: RwLock<HashMap<TableId, Arc<TableSecurity>>>,
+}
+
+impl RowSecurity {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a policy, or replace the one with the same name
+    pub fn set_policy(&self, table_id: TableId, policy: RowPolicy) -> Result<()> {
+        validate_name(&policy.name)?;
+        let expr = Arc::new(Expr::parse(&policy.expression)?);
+        self.update(table_id, |table| {
+            match table.policies.iter().position(|(p, _)| p.name == policy.name) {
+                Some(index) => table.policies[index] = (policy, expr),
+                None if table.policies.len() >= MAX_POLICIES_PER_TABLE => {
+                    return Err(Error::Storage(format!(
+                        "Table already has {} row policies (max {})",
+                        table.policies.len(), MAX_POLICIES_PER_TABLE
+                    )));
+                }
+                None => table.policies.push((policy, expr)),
+            }
+            Ok(())
+        })
+    }
+
+    /// Remove a policy; false if there was none by that name
+    pub fn remove_policy(&self, table_id: TableId, name: &str) -> bool {
+        self.update(table_id, |table| {
+            let before = table.policies.len();
+            table.policies.retain(|(p, _)| p.name != name);
+            Ok(table.policies.len() != before)
+        })
+        .unwrap_or(false)
+    }
+
+    pub fn policies(&self, table_id: TableId) -> Vec<RowPolicy> {
+        self.table(table_id)
+            .map(|table| table.policies.iter().map(|(p, _)| p.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Add a mask, or replace the one with the same name
+    pub fn set_mask(&self, table_id: TableId, mask: ColumnMask) -> Result<()> {
+        validate_name(&mask.name)?;
+        if mask.column.is_empty() {
+            return Err(Error::Storage("Mask column cannot be empty".to_string()));
+        }
+        self.update(table_id, |table| {
+            match table.masks.iter().position(|m| m.name == mask.name) {
+                Some(index) => table.masks[index] = mask,
+                None if table.masks.len() >= MAX_MASKS_PER_TABLE => {
+                    return Err(Error::Storage(format!(
+                        "Table already has {} column masks (max {})",
+                        table.masks.len(), MAX_MASKS_PER_TABLE
+                    )));
+                }
+                None => table.masks.push(mask),
+            }
+            table.masks.sort_by(|a, b| a.name.cmp(&b.name));
+            Ok(())
+        })
+    }
+
+    /// Remove a mask; false if there was none by that name
+    pub fn remove_mask(&self, table_id: TableId, name: &str) -> bool {
+        self.update(table_id, |table| {
+            let before = table.masks.len();
+            table.masks.retain(|m| m.name != name);
+            Ok(table.masks.len() != before)
+        })
+        .unwrap_or(false)
+    }
+
+    pub fn masks(&self, table_id: TableId) -> Vec<ColumnMask> {
+        self.table(table_id).map(|table| table.masks.clone()).unwrap_or_default()
+    }
+
+    /// Forget a dropped table's policies and masks
+    pub fn drop_table(&self, table_id: TableId) {
+        self.tables.write().remove(&table_id);
+    }
+
+    /// `store` as seen by `principal`
+    pub fn secure(self: &Arc<Self>, store: Arc<dyn ColumnStore>, principal: Principal) -> Arc<dyn ColumnStore> {
+        Arc::new(SecuredColumnStore::new(Arc::new(TtlColumnStore::new(store)), self.clone(), principal))
+    }
+
+    fn table(&self, table_id: TableId) -> Option<Arc<TableSecurity>> {
+        self.tables.read().get(&table_id).cloned()
+    }
+
+    fn update<T>(&self, table_id: TableId, f: impl FnOnce(&mut TableSecurity) -> Result<T>) -> Result<T> {
+        let mut tables = self.tables.write();
+        let mut table = tables.get(&table_id).map(|t| (**t).clone()).unwrap_or_default();
+        let result = f(&mut table)?;
+        if table.is_empty() {
+            tables.remove(&table_id);
+        } else {
+            tables.insert(table_id, Arc::new(table));
+        }
+        Ok(result)
+    }
+}
+
+/// A column store that applies row policies and column masks for one caller
+pub struct SecuredColumnStore {
+    inner: Arc<dyn ColumnStore>,
+    security: Arc<RowSecurity>,
+    principal: Principal,
+}
+
+impl SecuredColumnStore {
+    pub fn new(inner: Arc<dyn ColumnStore>, security: Arc<RowSecurity>, principal: Principal) -> Self {
+        Self { inner, security, principal }
+    }
+
+    pub fn principal(&self) -> &Principal {
+        &self.principal
+    }
+
+    /// Positions (into `column_ids`) of the columns policies refer to,
+    /// appending any that weren't requested
+    fn policy_columns(schema: &Schema, exprs: &[&Expr], column_ids: &mut Vec<u32>) -> Result<HashMap<String, usize>> {
+        let mut positions = HashMap::new();
+        for expr in exprs {
+            let mut names = Vec::new();
+            expr.columns(&mut names);
+            for name in names {
+                if positions.contains_key(name) {
+                    continue;
+                }
+                let id = schema.field_index(name)
+                    .ok_or_else(|| Error::Storage(format!("Row policy refers to unknown column '{}'", name)))? as u32;
+                let position = match column_ids.iter().position(|&c| c == id) {
+                    Some(position) => position,
+                    None => {
+                        column_ids.push(id);
+                        column_ids.len() - 1
+                    }
+                };
+                positions.insert(name.to_string(), position);
+            }
+        }
+        Ok(positions)
+    }
+}
+
+#[async_trait]
+impl ColumnStore for SecuredColumnStore {
+    async fn create_table(&self, table_id: TableId, schema: Schema) -> Result<()> {
+        self.inner.create_table(table_id, schema).await
+    }
+
+    async fn write_columns(&self, table_id: TableId, columns: Vec<Column>) -> Result<()> {
+        self.inner.write_columns(table_id, columns).await
+    }
+
+    async fn read_columns(
+        &self,
+        table_id: TableId,
+        column_ids: Vec<u32>,
+        row_start: usize,
+        row_count: usize,
+    ) -> Result<Vec<Column>> {
+        let Some(security) = self.security.table(table_id) else {
+            return self.inner.read_columns(table_id, column_ids, row_start, row_count).await;
+        };
+        let schema = self.inner.get_schema(table_id).await?;
+        let requested = column_ids.len();
+
+        let applicable: Vec<&Expr> = security.policies
+            .iter()
+            .filter(|(policy, _)| self.principal.covered_by(&policy.roles))
+            .map(|(_, expr)| expr.as_ref())
+            .collect();
+        let mut read_ids = column_ids;
+        let positions = Self::policy_columns(&schema, &applicable, &mut read_ids)?;
+        let mut columns = self.inner.read_columns(table_id, read_ids.clone(), row_start, row_count).await?;
+        // Stores skip columns that hold no data; anything else would leave
+        // policies and masks looking at the wrong column
+        if columns.len() != read_ids.len() {
+            if columns.iter().all(|c| c.len() == 0) {
+                columns.truncate(requested);
+                return Ok(columns);
+            }
+            return Err(Error::Storage(format!(
+                "Read returned {} of {} columns for table {}",
+                columns.len(), read_ids.len(), table_id.0
+            )));
+        }
+
+        if !security.policies.is_empty() {
+            let rows = columns.first().map(|c| c.len()).unwrap_or(0);
+            let visible: Vec<usize> = (0..rows)
+                .filter(|&row| {
+                    let lookup = |name: &str| {
+                        positions.get(name)
+                            .and_then(|&position| columns.get(position))
+                            .map_or(Value::Null, |column| cell(column, row))
+                    };
+                    applicable.iter().any(|expr| expr.eval(&lookup, &self.principal))
+                })
+                .collect();
+            if visible.len() != rows {
+                columns = columns.iter().map(|column| take_rows(column, &visible)).collect();
+            }
+        }
+        columns.truncate(requested);
+
+        for (column, id) in columns.iter_mut().zip(&read_ids) {
+            let Some(field) = schema.fields.get(*id as usize) else { continue };
+            let mask = security.masks
+                .iter()
+                .find(|mask| mask.column == field.name && self.principal.covered_by(&mask.roles));
+            if let Some(mask) = mask {
+                *column = mask_column(column, &mask.function);
+            }
+        }
+        Ok(columns)
+    }
+
+    async fn get_schema(&self, table_id: TableId) -> Result<Schema> {
+        self.inner.get_schema(table_id).await
+    }
+
+    async fn get_block_metadata(&self, table_id: TableId, column_id: u32) -> Result<Vec<BlockMetadata>> {
+        self.inner.get_block_metadata(table_id, column_id).await
+    }
+
+    /// Policies only drop rows inside the ranges read, but zone maps hold
+    /// unmasked values, so masked columns aren't pruned on
+    async fn matching_row_ranges(
+        &self,
+        table_id: TableId,
+        column_id: u32,
+        predicate: &BlockPredicate,
+    ) -> Result<Option<Vec<Range<usize>>>> {
+        if let Some(security) = self.security.table(table_id) {
+            let schema = self.inner.get_schema(table_id).await?;
+            let masked = schema.fields.get(column_id as usize).is_some_and(|field| {
+                security.masks
+                    .iter()
+                    .any(|mask| mask.column == field.name && self.principal.covered_by(&mask.roles))
+            });
+            if masked {
+                return Ok(None);
+            }
+        }
+        self.inner.matching_row_ranges(table_id, column_id, predicate).await
+    }
+
+    async fn delete_table(&self, table_id: TableId) -> Result<()> {
+        self.inner.delete_table(table_id).await?;
+        self.security.drop_table(table_id);
+        Ok(())
+    }
+
+    async fn lock_table(&self, table_id: TableId) -> TableWriteGuard {
+        self.inner.lock_table(table_id).await
+    }
+
+    async fn write_locked(&self, guard: &TableWriteGuard, columns: Vec<Column>) -> Result<()> {
+        self.inner.write_locked(guard, columns).await
+    }
+
+    /// A caller's view only sees some rows, so it can't stand in for all of them
+    async fn replace_rows(&self, guard: &TableWriteGuard, _columns: Vec<Column>) -> Result<()> {
+        Err(Error::Storage(format!("Rows of table {} can't be replaced through a secured view", guard.table_id().0)))
+    }
+}
+
+fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.len() > MAX_NAME_LENGTH {
+        return Err(Error::Storage(format!("Name must be 1-{} characters", MAX_NAME_LENGTH)));
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        return Err(Error::Storage(format!("Invalid name '{}': use letters, digits, '_' and '-'", name)));
+    }
+    Ok(())
+}
+
+/// The rows at `indices`, in order
+pub(crate) fn take_rows(column: &Column, indices: &[usize]) -> Column {
+    macro_rules! take {
+        ($variant:ident, $values:expr) => {
+            Column::$variant(indices.iter().filter_map(|&i| $values.get(i).cloned()).collect())
+        };
+    }
+    match column {
+        Column::Int8(v) => take!(Int8, v),
+        Column::Int16(v) => take!(Int16, v),
+        Column::Int32(v) => take!(Int32, v),
+        Column::Int64(v) => take!(Int64, v),
+        Column::UInt8(v) => take!(UInt8, v),
+        Column::UInt16(v) => take!(UInt16, v),
+        Column::UInt32(v) => take!(UInt32, v),
+        Column::UInt64(v) => take!(UInt64, v),
+        Column::Float32(v) => take!(Float32, v),
+        Column::Float64(v) => take!(Float64, v),
+        Column::Boolean(v) => take!(Boolean, v),
+        Column::String(v) => take!(String, v),
+        Column::Binary(v) => take!(Binary, v),
+        Column::Timestamp(v) => take!(Timestamp, v),
+        Column::Date(v) => take!(Date, v),
+    }
+}
+
+/// One value as JSON; null past the end of the column
+pub(crate) fn cell(column: &Column, row: usize) -> Value {
+    fn float(value: Option<f64>) -> Value {
+        value.and_then(serde_json::Number::from_f64).map_or(Value::Null, Value::Number)
+    }
+    match column {
+        Column::Int8(v) => v.get(row).map_or(Value::Null, |x| Value::from(*x)),
+        Column::Int16(v) => v.get(row).map_or(Value::Null, |x| Value::from(*x)),
+        Column::Int32(v) => v.get(row).map_or(Value::Null, |x| Value::from(*x)),
+        Column::Int64(v) => v.get(row).map_or(Value::Null, |x| Value::from(*x)),
+        Column::UInt8(v) => v.get(row).map_or(Value::Null, |x| Value::from(*x)),
+        Column::UInt16(v) => v.get(row).map_or(Value::Null, |x| Value::from(*x)),
+        Column::UInt32(v) => v.get(row).map_or(Value::Null, |x| Value::from(*x)),
+        Column::UInt64(v) => v.get(row).map_or(Value::Null, |x| Value::from(*x)),
+        Column::Float32(v) => float(v.get(row).map(|x| *x as f64)),
+        Column::Float64(v) => float(v.get(row).copied()),
+        Column::Boolean(v) => v.get(row).map_or(Value::Null, |x| Value::Bool(*x)),
+        Column::String(v) => v.get(row).map_or(Value::Null, |x| Value::String(x.clone())),
+        Column::Binary(v) => v.get(row).map_or(Value::Null, |x| Value::String(hex::encode(x))),
+        Column::Timestamp(v) => v.get(row).map_or(Value::Null, |x| Value::from(*x)),
+        Column::Date(v) => v.get(row).map_or(Value::Null, |x| Value::from(*x)),
+    }
+}
+
+fn mask_column(column: &Column, function: &MaskFunction) -> Column {
+    Column::String(
+        (0..column.len())
+            .map(|row| {
+                let text = match cell(column, row) {
+                    Value::String(s) => s,
+                    other => other.to_string(),
+                };
+                mask_value(&text, function)
+            })
+            .collect(),
+    )
+}
+
+fn mask_value(value: &str, function: &MaskFunction) -> String {
+    match function {
+        MaskFunction::Hash => hex::encode(Sha256::digest(value.as_bytes())),
+        MaskFunction::Partial { show_first, show_last } => {
+            let chars: Vec<char> = value.chars().collect();
+            // Reveal nothing rather than the whole value when it's too short
+            // to hide anything
+            if show_first + show_last >= chars.len() {
+                return "*".repeat(chars.len());
+            }
+            let hidden = chars.len() - show_first - show_last;
+            chars[..*show_first]
+                .iter()
+                .copied()
+                .chain(std::iter::repeat_n('*', hidden))
+                .chain(chars[chars.len() - show_last..].iter().copied())
+                .collect()
+        }
+        MaskFunction::Redact => REDACTED.to_string(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Or(Vec<Expr>),
+    And(Vec<Expr>),
+    Not(Box<Expr>),
+    Compare(Operand, CompareOp, Operand),
+    In(Operand, Vec<Value>),
+    HasRole(Operand),
+    Truthy(Operand),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Operand {
+    Column(String),
+    Literal(Value),
+    Subject,
+    Tenant,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+impl Expr {
+    fn parse(expression: &str) -> Result<Self> {
+        if expression.len() > MAX_EXPRESSION_LENGTH {
+            return Err(Error::Storage(format!("policy must be at most {} bytes", MAX_EXPRESSION_LENGTH)));
+        }
+        let mut parser = Parser { tokens: tokenize(expression)?, pos: 0, depth: 0 };
+        if parser.tokens.is_empty() {
+            return Err(Error::Storage("policy cannot be empty".to_string()));
+        }
+        let expr = parser.or()?;
+        if let Some(token) = parser.tokens.get(parser.pos) {
+            return Err(Error::Storage(format!("Invalid policy: unexpected {}", token)));
+        }
+        Ok(expr)
+    }
+
+    /// Names of the columns the expression reads
+    fn columns<'a>(&'a self, names: &mut Vec<&'a str>) {
+        let mut operand = |operand: &'a Operand| {
+            if let Operand::Column(name) = operand {
+                names.push(name);
+            }
+        };
+        match self {
+            Expr::Or(exprs) | Expr::And(exprs) => exprs.iter().for_each(|e| e.columns(names)),
+            Expr::Not(expr) => expr.columns(names),
+            Expr::Compare(left, _, right) => {
+                operand(left);
+                operand(right);
+            }
+            Expr::In(value, _) | Expr::HasRole(value) | Expr::Truthy(value) => operand(value),
+        }
+    }
+
+    fn eval(&self, lookup: &dyn Fn(&str) -> Value, principal: &Principal) -> bool {
+        match self {
+            Expr::Or(exprs) => exprs.iter().any(|e| e.eval(lookup, principal)),
+            Expr::And(exprs) => exprs.iter().all(|e| e.eval(lookup, principal)),
+            Expr::Not(expr) => !expr.eval(lookup, principal),
+            Expr::Compare(left, op, right) => {
+                compare(&left.resolve(lookup, principal), *op, &right.resolve(lookup, principal))
+            }
+            Expr::In(operand, values) => {
+                let value = operand.resolve(lookup, principal);
+                values.iter().any(|v| equal(&value, v))
+            }
+            Expr::HasRole(operand) => match operand.resolve(lookup, principal) {
+                Value::String(role) => principal.roles.contains(&role),
+                _ => false,
+            },
+            Expr::Truthy(operand) => match operand.resolve(lookup, principal) {
+                Value::Null | Value::Bool(false) => false,
+                Value::Number(n) => n.as_f64() != Some(0.0),
+                _ => true,
+            },
+        }
+    }
+}
+
+impl Operand {
+    fn resolve(&self, lookup: &dyn Fn(&str) -> Value, principal: &Principal) -> Value {
+        match self {
+            Operand::Column(name) => lookup(name),
+            Operand::Literal(value) => value.clone(),
+            Operand::Subject => Value::String(principal.subject.clone()),
+            Operand::Tenant => principal.tenant.clone().map_or(Value::Null, Value::String),
+        }
+    }
+}
+
+/// JSON equality, except that numbers compare by value (1 == 1.0)
+fn equal(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Number(l), Value::Number(r)) => l.as_f64() == r.as_f64(),
+        _ => left == right,
+    }
+}
+
+fn compare(left: &Value, op: CompareOp, right: &Value) -> bool {
+    use std::cmp::Ordering;
+
+    let ordering = match (left, right) {
+        (Value::Number(l), Value::Number(r)) => l.as_f64().zip(r.as_f64()).and_then(|(l, r)| l.partial_cmp(&r)),
+        (Value::String(l), Value::String(r)) => Some(l.cmp(r)),
+        _ => None,
+    };
+    match op {
+        CompareOp::Eq => equal(left, right),
+        CompareOp::Ne => !equal(left, right),
+        CompareOp::Gt => ordering == Some(Ordering::Greater),
+        CompareOp::Ge => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+        CompareOp::Lt => ordering == Some(Ordering::Less),
+        CompareOp::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Column(String),
+    Literal(Value),
+    Subject,
+    Tenant,
+    Roles,
+    Op(CompareOp),
+    In,
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Comma,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Column(name) => write!(f, "column '{}'", name),
+            Token::Literal(value) => write!(f, "{}", value),
+            Token::Subject => write!(f, "'$sub'"),
+            Token::Tenant => write!(f, "'$tenant'"),
+            Token::Roles => write!(f, "'$roles'"),
+            Token::Op(_) => write!(f, "comparison"),
+            Token::In => write!(f, "'in'"),
+            Token::And => write!(f, "'&&'"),
+            Token::Or => write!(f, "'||'"),
+            Token::Not => write!(f, "'!'"),
+            Token::LParen => write!(f, "'('"),
+            Token::RParen => write!(f, "')'"),
+            Token::LBracket => write!(f, "'['"),
+            Token::RBracket => write!(f, "']'"),
+            Token::Comma => write!(f, "','"),
+        }
+    }
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>> {
+    let invalid = |message: String| Error::Storage(format!("Invalid policy: {}", message));
+    let chars: Vec<char> = expression.chars().collect();
+    let is_ident = |c: char| c == '_' || c.is_alphanumeric();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        let token = match c {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            ',' => Token::Comma,
+            '[' => Token::LBracket,
+            ']' => Token::RBracket,
+            '&' if next == Some('&') => Token::And,
+            '|' if next == Some('|') => Token::Or,
+            '=' if next == Some('=') => Token::Op(CompareOp::Eq),
+            '!' if next == Some('=') => Token::Op(CompareOp::Ne),
+            '>' if next == Some('=') => Token::Op(CompareOp::Ge),
+            '<' if next == Some('=') => Token::Op(CompareOp::Le),
+            '!' => Token::Not,
+            '>' => Token::Op(CompareOp::Gt),
+            '<' => Token::Op(CompareOp::Lt),
+            '\'' | '"' => {
+                let mut value = String::new();
+                let mut j = i + 1;
+                loop {
+                    match chars.get(j) {
+                        None => return Err(invalid("unterminated string".to_string())),
+                        Some('\\') => {
+                            value.extend(chars.get(j + 1));
+                            j += 2;
+                        }
+                        Some(&q) if q == c => break,
+                        Some(&other) => {
+                            value.push(other);
+                            j += 1;
+                        }
+                    }
+                }
+                tokens.push(Token::Literal(Value::String(value)));
+                i = j + 1;
+                continue;
+            }
+            c if c.is_ascii_digit() || (c == '-' && next.is_some_and(|n| n.is_ascii_digit())) => {
+                let start = i;
+                i += 1;
+                while i < chars.len() && (chars[i].is_ascii_alphanumeric() || matches!(chars[i], '.' | '-' | '+')) {
+                    i += 1;
+                }
+                let text: String = chars[start..i].iter().collect();
+                let number = text.parse::<serde_json::Number>()
+                    .map_err(|_| invalid(format!("invalid number '{}'", text)))?;
+                tokens.push(Token::Literal(Value::Number(number)));
+                continue;
+            }
+            c if c == '$' || c == '_' || c.is_alphabetic() => {
+                let start = i;
+                i += 1;
+                while i < chars.len() && is_ident(chars[i]) {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                tokens.push(match word.as_str() {
+                    "true" => Token::Literal(Value::Bool(true)),
+                    "false" => Token::Literal(Value::Bool(false)),
+                    "null" => Token::Literal(Value::Null),
+                    "in" => Token::In,
+                    "$sub" => Token::Subject,
+                    "$tenant" => Token::Tenant,
+                    "$roles" => Token::Roles,
+                    other if other.starts_with('$') => return Err(invalid(format!("unknown variable '{}'", other))),
+                    _ => Token::Column(word),
+                });
+                continue;
+            }
+            other => return Err(invalid(format!("unexpected character '{}'", other))),
+        };
+        // Two-character operators
+        i += if matches!(token, Token::And | Token::Or) || (matches!(token, Token::Op(_)) && next == Some('=')) { 2 } else { 1 };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Result<Token> {
+        let token = self.tokens.get(self.pos).cloned()
+            .ok_or_else(|| Error::Storage("Invalid policy: unexpected end of expression".to_string()))?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<()> {
+        let token = self.next()?;
+        if token != expected {
+            return Err(Error::Storage(format!("Invalid policy: expected {}, found {}", expected, token)));
+        }
+        Ok(())
+    }
+
+    fn or(&mut self) -> Result<Expr> {
+        let mut exprs = vec![self.and()?];
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            exprs.push(self.and()?);
+        }
+        Ok(if exprs.len() == 1 { exprs.remove(0) } else { Expr::Or(exprs) })
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        let mut exprs = vec![self.unary()?];
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            exprs.push(self.unary()?);
+        }
+        Ok(if exprs.len() == 1 { exprs.remove(0) } else { Expr::And(exprs) })
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(Error::Storage(format!("policy nests deeper than {} levels", MAX_DEPTH)));
+        }
+        let expr = match self.peek() {
+            Some(Token::Not) => {
+                self.pos += 1;
+                Expr::Not(Box::new(self.unary()?))
+            }
+            Some(Token::LParen) => {
+                self.pos += 1;
+                let expr = self.or()?;
+                self.expect(Token::RParen)?;
+                expr
+            }
+            _ => self.comparison()?,
+        };
+        self.depth -= 1;
+        Ok(expr)
+    }
+
+    fn operand(&mut self) -> Result<Operand> {
+        match self.next()? {
+            Token::Column(name) => Ok(Operand::Column(name)),
+            Token::Literal(value) => Ok(Operand::Literal(value)),
+            Token::Subject => Ok(Operand::Subject),
+            Token::Tenant => Ok(Operand::Tenant),
+            token => Err(Error::Storage(format!("Invalid policy: expected a column or value, found {}", token))),
+        }
+    }
+
+    fn comparison(&mut self) -> Result<Expr> {
+        let left = self.operand()?;
+        match self.peek().cloned() {
+            Some(Token::Op(op)) => {
+                self.pos += 1;
+                Ok(Expr::Compare(left, op, self.operand()?))
+            }
+            Some(Token::In) => {
+                self.pos += 1;
+                if self.peek() == Some(&Token::Roles) {
+                    self.pos += 1;
+                    return Ok(Expr::HasRole(left));
+                }
+                self.expect(Token::LBracket)?;
+                let mut values = Vec::new();
+                if self.peek() == Some(&Token::RBracket) {
+                    self.pos += 1;
+                    return Ok(Expr::In(left, values));
+                }
+                loop {
+                    match self.next()? {
+                        Token::Literal(value) => values.push(value),
+                        token => return Err(Error::Storage(format!("Invalid policy: 'in' lists take values, found {}", token))),
+                    }
+                    match self.next()? {
+                        Token::Comma => continue,
+                        Token::RBracket => break,
+                        token => return Err(Error::Storage(format!("Invalid policy: expected ',' or ']', found {}", token))),
+                    }
+                }
+                Ok(Expr::In(left, values))
+            }
+            _ => match left {
+                Operand::Literal(_) => Err(Error::Storage("Invalid policy: a value must be compared with something".to_string())),
+                operand => Ok(Expr::Truthy(operand)),
+            },
+        }
+    }
+}
@@ -1,5 +1,6 @@
 // Security utilities for preventing common vulnerabilities
 
+use narayana_core::egress::{self, EgressSubsystem};
 use narayana_core::{Error, Result};
 use std::path::{Path, PathBuf};
 
@@ -104,145 +105,24 @@ impl SecurityUtils {
     }
     
     /// Validate URL to prevent SSRF attacks
-    /// SECURITY: Also prevents DNS rebinding attacks by validating host immediately
+    /// SECURITY: Delegates to the shared egress policy (narayana_core::egress). This is a
+    /// static check; clients should connect through `egress::pinned_client` so the
+    /// resolved addresses are vetted and pinned (DNS rebinding protection).
     pub fn validate_http_url(url: &str) -> Result<()> {
-        let url_lower = url.to_lowercase();
-        
-        // Only allow HTTP and HTTPS
-        if !url_lower.starts_with("http://") && !url_lower.starts_with("https://") {
-            return Err(Error::Storage(format!(
-                "Only http and https protocols are allowed: {}",
-                url
-            )));
-        }
-        
-        // SECURITY: DNS rebinding protection - validate host before DNS resolution
-        // This prevents attackers from using DNS rebinding to bypass SSRF protection
-        // by resolving to a public IP initially, then rebinding to localhost
-        
-        // SECURITY: Extract host safely - handle edge cases
-        let host_part = if let Some(start) = url.find("://") {
-            let after_protocol = &url[start + 3..];
-            // SECURITY: Prevent empty host
-            if after_protocol.is_empty() {
-                return Err(Error::Storage(format!("Invalid URL: empty host in {}", url)));
-            }
-            
-            // SECURITY: Handle host extraction with port
-            if let Some(slash_pos) = after_protocol.find('/') {
-                let host_with_port = &after_protocol[..slash_pos];
-                // Remove port if present
-                if let Some(colon_pos) = host_with_port.rfind(':') {
-                    // Check if this is IPv6 bracket notation [::1]:8080
-                    if host_with_port.starts_with('[') && host_with_port.contains(']') {
-                        // IPv6 with port - extract properly
-                        if let Some(bracket_end) = host_with_port.find(']') {
-                            &host_with_port[1..bracket_end]
-                        } else {
-                            host_with_port
-                        }
-                    } else {
-                        &host_with_port[..colon_pos]
-                    }
-                } else {
-                    // Remove IPv6 brackets if present
-                    if host_with_port.starts_with('[') && host_with_port.ends_with(']') {
-                        &host_with_port[1..host_with_port.len()-1]
-                    } else {
-                        host_with_port
-                    }
-                }
-            } else if let Some(colon_pos) = after_protocol.find(':') {
-                let host_with_port = &after_protocol[..colon_pos];
-                // Remove IPv6 brackets if present
-                if host_with_port.starts_with('[') && host_with_port.ends_with(']') {
-                    &host_with_port[1..host_with_port.len()-1]
-                } else {
-                    host_with_port
-                }
-            } else {
-                // Remove IPv6 brackets if present
-                if after_protocol.starts_with('[') && after_protocol.ends_with(']') {
-                    &after_protocol[1..after_protocol.len()-1]
-                } else {
-                    after_protocol
-                }
-            }
-        } else {
-            return Err(Error::Storage(format!("Invalid URL format: {}", url)));
-        };
-        
-        // SECURITY: Validate host is not empty after processing
-        if host_part.is_empty() {
-            return Err(Error::Storage(format!("Invalid URL: empty host in {}", url)));
-        }
-        
-        // Block localhost variants
-        if Self::is_localhost(host_part) {
-            return Err(Error::Storage(
-                "URL cannot target localhost".to_string()
-            ));
-        }
-        
-        // Block private IP ranges
-        if let Ok(ip) = host_part.parse::<std::net::IpAddr>() {
-            if Self::is_private_ip(&ip) {
-                return Err(Error::Storage(
-                    "URL cannot target private IP addresses".to_string()
-                ));
-            }
-        }
-        
-        Ok(())
+        egress::check_url(EgressSubsystem::General, url).map(|_| ())
     }
     
     /// Check if host is localhost
     /// SECURITY: Comprehensive localhost detection to prevent SSRF
     pub fn is_localhost(host: &str) -> bool {
-        let host_lower = host.to_lowercase();
-        matches!(host_lower.as_str(), 
-            "localhost" | "127.0.0.1" | "::1" | "0.0.0.0" | "[::1]" | "[::]" |
-            "127.1" | "127.0.1" | "127.000.000.001" | "127.0.0.0" |
-            "localhost." | ".localhost" | "localhost.localdomain"
-        ) || host_lower.starts_with("127.") // Any 127.x.x.x
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        egress::is_local_hostname(host)
+            || egress::parse_ip_host(host).is_some_and(|ip| ip.is_loopback() || ip.is_unspecified())
     }
     
-    /// Check if IP address is private/internal
+    /// Check if IP address is private/internal (loopback, private, link-local, reserved)
     pub fn is_private_ip(ip: &std::net::IpAddr) -> bool {
-        match ip {
-            std::net::IpAddr::V4(ipv4) => {
-                let octets = ipv4.octets();
-                // 10.0.0.0/8
-                if octets[0] == 10 {
-                    return true;
-                }
-                // 172.16.0.0/12
-                if octets[0] == 172 && octets[1] >= 16 && octets[1] <= 31 {
-                    return true;
-                }
-                // 192.168.0.0/16
-                if octets[0] == 192 && octets[1] == 168 {
-                    return true;
-                }
-                // 169.254.0.0/16 (link-local)
-                if octets[0] == 169 && octets[1] == 254 {
-                    return true;
-                }
-                false
-            }
-            std::net::IpAddr::V6(ipv6) => {
-                let segments = ipv6.segments();
-                // fc00::/7 (unique local)
-                if segments[0] & 0xfe00 == 0xfc00 {
-                    return true;
-                }
-                // fe80::/10 (link-local)
-                if segments[0] & 0xffc0 == 0xfe80 {
-                    return true;
-                }
-                false
-            }
-        }
+        egress::is_blocked_ip(ip)
     }
     
     /// Constant-time string comparison to prevent timing attacks
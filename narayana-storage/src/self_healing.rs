@@ -775,6 +775,235 @@ pub struct RepairReport {
     pub actions: Vec<String>,
 }
 
+/// A column block of a table
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BlockRef {
+    pub table_id: TableId,
+    pub column_id: u32,
+    pub block_id: u64,
+}
+
+/// What was wrong with a block
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CorruptionKind {
+    /// Block data doesn't match the checksum written with it
+    ChecksumMismatch,
+    /// Block file is gone
+    Missing,
+    /// Block metadata or data can't be decoded
+    Unreadable,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IncidentStatus {
+    /// Block is quarantined; reads skip its rows
+    Quarantined,
+    /// Block was rebuilt from an intact copy
+    Rebuilt { source: String },
+}
+
+/// Record of one corrupted block
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorruptionIncident {
+    pub id: u64,
+    pub block: BlockRef,
+    pub kind: CorruptionKind,
+    pub detail: String,
+    pub detected_at: u64,
+    pub status: IncidentStatus,
+    pub resolved_at: Option<u64>,
+    /// Checksum the block was written with, if known
+    pub expected_checksum: Option<String>,
+    pub rebuild_attempts: u32,
+}
+
+/// Where intact copies of blocks can be fetched from (replicas, backups)
+#[async_trait::async_trait]
+pub trait BlockSource: Send + Sync {
+    fn name(&self) -> String;
+    /// Block bytes as written, or None if the source doesn't have the block
+    async fn fetch_block(&self, block: &BlockRef) -> Result<Option<Vec<u8>>>;
+}
+
+/// Block source over a copy of the columnar data directory (a replica's
+/// mirror or a restored backup) laid out as `table_<id>/col_<c>_block_<b>.dat`
+pub struct DirectoryBlockSource {
+    root: std::path::PathBuf,
+}
+
+impl DirectoryBlockSource {
+    pub fn new(root: impl Into<std::path::PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+#[async_trait::async_trait]
+impl BlockSource for DirectoryBlockSource {
+    fn name(&self) -> String {
+        format!("dir:{}", self.root.display())
+    }
+
+    async fn fetch_block(&self, block: &BlockRef) -> Result<Option<Vec<u8>>> {
+        let path = self.root
+            .join(format!("table_{}", block.table_id.0))
+            .join(format!("col_{}_block_{}.dat", block.column_id, block.block_id));
+        match tokio::fs::read(&path).await {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(Error::Storage(format!("Failed to read block copy: {}", e))),
+        }
+    }
+}
+
+/// Incidents kept; resolved ones are dropped first
+const MAX_INCIDENTS: usize = 10_000;
+
+/// Corrupted-block bookkeeping: incident records, the quarantine, and the
+/// sources blocks can be rebuilt from
+pub struct BlockHealer {
+    sources: RwLock<Vec<Arc<dyn BlockSource>>>,
+    incidents: RwLock<Vec<CorruptionIncident>>,
+    /// Quarantined block -> its open incident
+    quarantine: RwLock<HashMap<BlockRef, u64>>,
+    next_id: std::sync::atomic::AtomicU64,
+}
+
+impl Default for BlockHealer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BlockHealer {
+    pub fn new() -> Self {
+        Self {
+            sources: RwLock::new(Vec::new()),
+            incidents: RwLock::new(Vec::new()),
+            quarantine: RwLock::new(HashMap::new()),
+            next_id: std::sync::atomic::AtomicU64::new(1),
+        }
+    }
+
+    /// Add a source to rebuild corrupted blocks from (tried in order)
+    pub fn add_source(&self, source: Arc<dyn BlockSource>) {
+        info!("Block rebuild source added: {}", source.name());
+        self.sources.write().push(source);
+    }
+
+    /// Record a corrupted block and quarantine it; returns the incident id
+    /// (the open incident if the block is already quarantined)
+    pub fn record(&self, block: BlockRef, kind: CorruptionKind, detail: String, expected_checksum: Option<String>) -> u64 {
+        let mut quarantine = self.quarantine.write();
+        if let Some(id) = quarantine.get(&block) {
+            return *id;
+        }
+        let id = self.next_id.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        warn!(
+            "Corrupted block quarantined (table {}, column {}, block {}): {:?}: {}",
+            block.table_id.0, block.column_id, block.block_id, kind, detail
+        );
+        quarantine.insert(block.clone(), id);
+
+        let mut incidents = self.incidents.write();
+        if incidents.len() >= MAX_INCIDENTS {
+            let oldest = incidents.iter()
+                .position(|i| i.status != IncidentStatus::Quarantined)
+                .unwrap_or(0);
+            incidents.remove(oldest);
+        }
+        incidents.push(CorruptionIncident {
+            id,
+            block,
+            kind,
+            detail,
+            detected_at: now_secs(),
+            status: IncidentStatus::Quarantined,
+            resolved_at: None,
+            expected_checksum,
+            rebuild_attempts: 0,
+        });
+        id
+    }
+
+    /// Whether reads must skip the block
+    pub fn is_quarantined(&self, block: &BlockRef) -> bool {
+        self.quarantine.read().contains_key(block)
+    }
+
+    /// Incidents of blocks still in quarantine
+    pub fn quarantined(&self) -> Vec<CorruptionIncident> {
+        self.incidents.read()
+            .iter()
+            .filter(|i| i.status == IncidentStatus::Quarantined)
+            .cloned()
+            .collect()
+    }
+
+    /// All incident records, oldest first
+    pub fn incidents(&self) -> Vec<CorruptionIncident> {
+        self.incidents.read().clone()
+    }
+
+    pub fn incident(&self, id: u64) -> Option<CorruptionIncident> {
+        self.incidents.read().iter().find(|i| i.id == id).cloned()
+    }
+
+    /// Fetch an intact copy of a quarantined block from the first source
+    /// whose bytes pass `verify`; returns the source name and the bytes
+    pub async fn fetch<F>(&self, id: u64, verify: F) -> Option<(String, Vec<u8>)>
+    where
+        F: Fn(&[u8]) -> bool,
+    {
+        let block = {
+            let mut incidents = self.incidents.write();
+            let incident = incidents.iter_mut()
+                .find(|i| i.id == id && i.status == IncidentStatus::Quarantined)?;
+            incident.rebuild_attempts += 1;
+            incident.block.clone()
+        };
+        let sources = self.sources.read().clone();
+        for source in sources {
+            match source.fetch_block(&block).await {
+                Ok(Some(bytes)) if verify(&bytes) => return Some((source.name(), bytes)),
+                Ok(Some(_)) => warn!("Block copy from {} failed verification", source.name()),
+                Ok(None) => {}
+                Err(e) => warn!("Block source {} unavailable: {}", source.name(), e),
+            }
+        }
+        None
+    }
+
+    /// Close an incident once its block was rebuilt from `source`
+    pub fn resolve(&self, id: u64, source: String) {
+        let block = {
+            let mut incidents = self.incidents.write();
+            let Some(incident) = incidents.iter_mut().find(|i| i.id == id) else {
+                return;
+            };
+            info!(
+                "Block rebuilt from {} (table {}, column {}, block {})",
+                source, incident.block.table_id.0, incident.block.column_id, incident.block.block_id
+            );
+            incident.status = IncidentStatus::Rebuilt { source };
+            incident.resolved_at = Some(now_secs());
+            incident.block.clone()
+        };
+        self.quarantine.write().remove(&block);
+    }
+
+    /// Forget the quarantine of a dropped table
+    pub fn forget_table(&self, table_id: &TableId) {
+        self.quarantine.write().retain(|block, _| block.table_id != *table_id);
+    }
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
 /// Automatic failover manager
 pub struct FailoverManager {
     available_nodes: Arc<RwLock<Vec<String>>>,
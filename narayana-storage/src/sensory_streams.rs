@@ -8,7 +8,7 @@ use crate::compression::{Compressor, Decompressor, Lz4Compressor, ZstdCompressor
 use crate::advanced_indexing::AdvancedIndexManager;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use parking_lot::RwLock;
 use tokio::sync::broadcast;
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -20,6 +20,8 @@ use bytes::Bytes;
 pub struct SensoryStreamManager {
     streams: Arc<RwLock<HashMap<String, Arc<SensoryStream>>>>,
     stream_processors: Arc<RwLock<HashMap<String, StreamProcessor>>>,
+    event_tables: Arc<RwLock<HashMap<String, SensoryEventTable>>>,
+    typed_streams: Arc<RwLock<HashMap<String, TypedStream>>>,
     event_sender: broadcast::Sender<StreamEvent>,
 }
 
@@ -33,6 +35,8 @@ impl SensoryStreamManager {
         Self {
             streams: Arc::new(RwLock::new(HashMap::new())),
             stream_processors: Arc::new(RwLock::new(HashMap::new())),
+            event_tables: Arc::new(RwLock::new(HashMap::new())),
+            typed_streams: Arc::new(RwLock::new(HashMap::new())),
             event_sender: sender,
         }
     }
@@ -154,6 +158,18 @@ impl SensoryStreamManager {
         // Store in memory system
         stream.store_columns(compressed).await?;
 
+        // Declared streams also keep the values as time-stamped samples
+        if let Some(values) = data.sample_values() {
+            let unit = self.typed_streams.read().get(stream_id).map(|typed| typed.schema.timestamp_unit);
+            if let Some(unit) = unit {
+                let timestamp_ms = match data.timestamp() {
+                    0 => now_millis(),
+                    timestamp => unit.to_millis(timestamp),
+                };
+                self.push_sample(stream_id, timestamp_ms, values)?;
+            }
+        }
+
         // Emit event
         // EDGE CASE: Handle broadcast channel overflow gracefully
         // If channel is full, message is dropped (non-blocking)
@@ -182,6 +198,249 @@ impl SensoryStreamManager {
     pub fn subscribe(&self) -> broadcast::Receiver<StreamEvent> {
         self.event_sender.subscribe()
     }
+
+    /// Create an event table, or update the retention policy of an existing one
+    pub fn create_event_table(&self, table: &str, retention: RetentionPolicy) -> Result<()> {
+        if table.is_empty() || table.len() > MAX_EVENT_NAME_LEN {
+            return Err(Error::Storage(format!("Event table name must be 1-{} characters", MAX_EVENT_NAME_LEN)));
+        }
+        if !table.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+            return Err(Error::Storage("Event table name may only contain letters, digits, '_' and '-'".to_string()));
+        }
+        if retention.max_rows == 0 {
+            return Err(Error::Storage("Retention max_rows must be greater than 0".to_string()));
+        }
+
+        let mut tables = self.event_tables.write();
+        match tables.get_mut(table) {
+            Some(existing) => {
+                existing.retention = retention;
+                existing.enforce_retention(now_millis());
+            }
+            None => {
+                tables.insert(table.to_string(), SensoryEventTable { retention, rows: VecDeque::new() });
+                info!("Created sensory event table: {}", table);
+            }
+        }
+        Ok(())
+    }
+
+    /// Append an event to a table, dropping rows its retention policy no longer keeps
+    pub fn insert_event(&self, table: &str, record: SensoryEventRecord) -> Result<()> {
+        record.validate()?;
+        {
+            let mut tables = self.event_tables.write();
+            let events = tables.get_mut(table)
+                .ok_or_else(|| Error::Storage(format!("Event table {} not found", table)))?;
+            events.insert(record);
+            events.enforce_retention(now_millis());
+        }
+
+        let event = StreamEvent::DataReceived {
+            stream_id: table.to_string(),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        };
+        // No subscribers is fine
+        let _ = self.event_sender.send(event);
+        Ok(())
+    }
+
+    /// Events in a table matching `query`, oldest first
+    pub fn query_events(&self, table: &str, query: &SensoryEventQuery) -> Result<Vec<SensoryEventRecord>> {
+        let tables = self.event_tables.read();
+        let events = tables.get(table)
+            .ok_or_else(|| Error::Storage(format!("Event table {} not found", table)))?;
+
+        let since = query.since_ms.unwrap_or(0);
+        let until = query.until_ms.unwrap_or(u64::MAX);
+        // Rows are kept in time order; start at the first one in range
+        let start = events.rows.partition_point(|r| r.timestamp_ms < since);
+        let mut matches: Vec<SensoryEventRecord> = events.rows
+            .range(start..)
+            .take_while(|r| r.timestamp_ms <= until)
+            .filter(|r| query.kind.as_ref().is_none_or(|kind| &r.kind == kind))
+            .filter(|r| query.source.as_ref().is_none_or(|source| &r.source == source))
+            .cloned()
+            .collect();
+        // A limit keeps the most recent matches
+        if let Some(limit) = query.limit {
+            let excess = matches.len().saturating_sub(limit);
+            matches.drain(..excess);
+        }
+        Ok(matches)
+    }
+
+    /// Number of rows in an event table
+    pub fn event_count(&self, table: &str) -> Option<usize> {
+        self.event_tables.read().get(table).map(|t| t.rows.len())
+    }
+
+    /// Drop expired rows from every event table, returning how many were removed
+    pub fn enforce_retention(&self) -> usize {
+        let now = now_millis();
+        self.event_tables.write()
+            .values_mut()
+            .map(|table| table.enforce_retention(now))
+            .sum()
+    }
+
+    /// Declare a stream's channels, units and expected rate. Its samples are
+    /// then kept for windowed queries and checked for gaps. Redeclaring keeps
+    /// the samples when the channel count is unchanged.
+    pub fn declare_stream(&self, stream_id: &str, schema: StreamSchema) -> Result<()> {
+        if stream_id.is_empty() || stream_id.len() > MAX_EVENT_NAME_LEN {
+            return Err(Error::Storage(format!("Stream id must be 1-{} characters", MAX_EVENT_NAME_LEN)));
+        }
+        schema.validate()?;
+        if let Some(stream) = self.streams.read().get(stream_id) {
+            if !matches!(stream.stream_type, StreamType::Sensor(_) | StreamType::IMU) {
+                return Err(Error::Storage(format!(
+                    "Stream {} carries {:?} data, which has no per-sample values",
+                    stream_id, stream.stream_type
+                )));
+            }
+        }
+        let mut typed = self.typed_streams.write();
+        match typed.get_mut(stream_id) {
+            Some(existing) => {
+                if existing.schema.channels.len() != schema.channels.len() {
+                    existing.samples.clear();
+                }
+                existing.schema = schema;
+                existing.enforce_capacity();
+            }
+            None => {
+                info!("Declared stream {} ({} channels @ {}Hz)", stream_id, schema.channels.len(), schema.expected_hz);
+                typed.insert(stream_id.to_string(), TypedStream::new(schema));
+            }
+        }
+        Ok(())
+    }
+
+    /// Declaration of a stream, if it has one
+    pub fn stream_schema(&self, stream_id: &str) -> Option<StreamSchema> {
+        self.typed_streams.read().get(stream_id).map(|typed| typed.schema.clone())
+    }
+
+    /// Add one sample (a value per declared channel) to a declared stream. A
+    /// sample arriving later than the gap tolerance allows records a gap.
+    pub fn push_sample(&self, stream_id: &str, timestamp_ms: u64, values: Vec<f64>) -> Result<()> {
+        let gap = {
+            let mut typed = self.typed_streams.write();
+            let stream = typed.get_mut(stream_id)
+                .ok_or_else(|| Error::Storage(format!("Stream {} is not declared", stream_id)))?;
+            if values.len() != stream.schema.channels.len() {
+                return Err(Error::Storage(format!(
+                    "Stream {} has {} channels, got {} values",
+                    stream_id, stream.schema.channels.len(), values.len()
+                )));
+            }
+            if values.iter().any(|v| !v.is_finite()) {
+                return Err(Error::Storage("Sample values must be finite".to_string()));
+            }
+            stream.insert(timestamp_ms, values)
+        };
+        if let Some(gap) = gap {
+            debug!("Stream {} gap: {} samples missing", stream_id, gap.missing_samples);
+            let _ = self.event_sender.send(StreamEvent::Gap { stream_id: stream_id.to_string(), gap });
+        }
+        Ok(())
+    }
+
+    /// Mark declared streams that have gone quiet for longer than their gap
+    /// tolerance as dropped out, returning the ones newly dropped. They
+    /// recover with their next sample, which records the gap.
+    pub fn check_dropouts(&self) -> Vec<String> {
+        let now = now_millis();
+        let dropped: Vec<(String, u64)> = self.typed_streams.write()
+            .iter_mut()
+            .filter_map(|(stream_id, stream)| {
+                let last = stream.samples.back()?.0;
+                let overdue = now.saturating_sub(last) as f64 > stream.schema.gap_threshold_ms();
+                if !overdue || stream.dropped_out {
+                    return None;
+                }
+                stream.dropped_out = true;
+                Some((stream_id.clone(), last))
+            })
+            .collect();
+        for (stream_id, last_sample_ms) in &dropped {
+            warn!("Stream {} dropped out (last sample at {})", stream_id, last_sample_ms);
+            let _ = self.event_sender.send(StreamEvent::Dropout {
+                stream_id: stream_id.clone(),
+                last_sample_ms: *last_sample_ms,
+            });
+        }
+        dropped.into_iter().map(|(stream_id, _)| stream_id).collect()
+    }
+
+    /// Check for dropouts every `interval`
+    pub fn start_dropout_monitor(self: Arc<Self>, interval: std::time::Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                self.check_dropouts();
+            }
+        })
+    }
+
+    /// Rate, gaps and dropout state of a declared stream
+    pub fn stream_health(&self, stream_id: &str) -> Option<StreamHealth> {
+        let typed = self.typed_streams.read();
+        let stream = typed.get(stream_id)?;
+        Some(StreamHealth {
+            stream_id: stream_id.to_string(),
+            expected_hz: stream.schema.expected_hz,
+            observed_hz: stream.observed_hz(),
+            sample_count: stream.samples.len(),
+            last_sample_ms: stream.samples.back().map(|(timestamp, _)| *timestamp),
+            dropped_out: stream.dropped_out,
+            gap_count: stream.gap_count,
+            missing_samples: stream.missing_samples,
+        })
+    }
+
+    /// Gaps recorded on a declared stream that ended at or after `since_ms`, oldest first
+    pub fn stream_gaps(&self, stream_id: &str, since_ms: u64) -> Result<Vec<StreamGap>> {
+        let typed = self.typed_streams.read();
+        let stream = typed.get(stream_id)
+            .ok_or_else(|| Error::Storage(format!("Stream {} is not declared", stream_id)))?;
+        Ok(stream.gaps.iter().filter(|gap| gap.end_ms >= since_ms).cloned().collect())
+    }
+
+    /// Samples of a declared stream in a time window, raw or resampled onto an even grid
+    pub fn query_window(&self, stream_id: &str, query: &WindowQuery) -> Result<StreamWindow> {
+        if !query.last_secs.is_finite() || query.last_secs <= 0.0 {
+            return Err(Error::Storage("Window length must be a positive number of seconds".to_string()));
+        }
+        let end_ms = query.until_ms.unwrap_or_else(now_millis);
+        let start_ms = end_ms.saturating_sub((query.last_secs * 1000.0).round() as u64);
+
+        let typed = self.typed_streams.read();
+        let stream = typed.get(stream_id)
+            .ok_or_else(|| Error::Storage(format!("Stream {} is not declared", stream_id)))?;
+        let gaps = stream.gaps.iter()
+            .filter(|gap| gap.end_ms > start_ms && gap.start_ms < end_ms)
+            .cloned()
+            .collect();
+        let (timestamps_ms, values) = match query.resample_hz {
+            None => stream.raw_window(start_ms, end_ms),
+            Some(hz) => stream.resampled_window(end_ms, query.last_secs, hz, query.method)?,
+        };
+        Ok(StreamWindow {
+            stream_id: stream_id.to_string(),
+            channels: stream.schema.channels.clone(),
+            start_ms,
+            end_ms,
+            timestamps_ms,
+            values,
+            gaps,
+        })
+    }
 }
 
 /// Sensory stream
@@ -682,6 +941,32 @@ pub enum StreamData {
     },
 }
 
+impl StreamData {
+    /// Producer timestamp, in the unit the stream's declaration names
+    pub fn timestamp(&self) -> u64 {
+        match self {
+            StreamData::CameraFrame { timestamp, .. }
+            | StreamData::AudioSamples { timestamp, .. }
+            | StreamData::IMUData { timestamp, .. }
+            | StreamData::LidarPoints { timestamp, .. }
+            | StreamData::SensorData { timestamp, .. } => *timestamp,
+        }
+    }
+
+    /// Values of one sample for a declared stream: sensor readings, or the
+    /// IMU's accelerometer, gyroscope and magnetometer axes in that order.
+    /// Frames, audio buffers and scans are not single samples.
+    fn sample_values(&self) -> Option<Vec<f64>> {
+        match self {
+            StreamData::SensorData { values, .. } => Some(values.clone()),
+            StreamData::IMUData { accel, gyro, mag, .. } => {
+                Some(accel.iter().chain(gyro).chain(mag).map(|&v| v as f64).collect())
+            }
+            _ => None,
+        }
+    }
+}
+
 /// 3D point
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Point3D {
@@ -733,6 +1018,490 @@ pub enum StreamEvent {
     StreamStarted { stream_id: String },
     StreamStopped { stream_id: String },
     Error { stream_id: String, error: String },
+    /// A declared stream resumed after missing samples
+    Gap { stream_id: String, gap: StreamGap },
+    /// A declared stream has sent nothing for longer than its gap tolerance
+    Dropout { stream_id: String, last_sample_ms: u64 },
+}
+
+/// Longest event table, source, kind, feature or label name
+const MAX_EVENT_NAME_LEN: usize = 128;
+/// Most features or labels on one event
+const MAX_EVENT_FIELDS: usize = 64;
+/// Longest label value
+const MAX_LABEL_LEN: usize = 1024;
+
+/// How long an event table keeps its rows
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    /// Drop rows older than this (None keeps them regardless of age)
+    pub max_age_secs: Option<u64>,
+    /// Keep at most this many rows, dropping the oldest first
+    pub max_rows: usize,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            max_age_secs: Some(7 * 24 * 60 * 60), // 7 days
+            max_rows: 100_000,
+        }
+    }
+}
+
+/// A discrete sensory event (a sound, a detection), stored as one row of an event table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SensoryEventRecord {
+    /// Wall-clock time in milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+    /// Device or stream the event came from
+    pub source: String,
+    /// Event type, e.g. "voice_activity"
+    pub kind: String,
+    /// Numeric features, e.g. loudness or pitch
+    pub features: BTreeMap<String, f64>,
+    /// Categorical attributes, e.g. a classification
+    pub labels: BTreeMap<String, String>,
+}
+
+impl SensoryEventRecord {
+    fn validate(&self) -> Result<()> {
+        let name_ok = |name: &str| !name.is_empty() && name.len() <= MAX_EVENT_NAME_LEN;
+        if !name_ok(&self.source) || !name_ok(&self.kind) {
+            return Err(Error::Storage(format!("Event source and kind must be 1-{} characters", MAX_EVENT_NAME_LEN)));
+        }
+        if self.features.len() > MAX_EVENT_FIELDS || self.labels.len() > MAX_EVENT_FIELDS {
+            return Err(Error::Storage(format!("Events carry at most {} features and {} labels", MAX_EVENT_FIELDS, MAX_EVENT_FIELDS)));
+        }
+        if self.features.iter().any(|(name, value)| !name_ok(name) || !value.is_finite()) {
+            return Err(Error::Storage("Event features need a valid name and a finite value".to_string()));
+        }
+        if self.labels.iter().any(|(name, value)| !name_ok(name) || value.len() > MAX_LABEL_LEN) {
+            return Err(Error::Storage(format!("Event labels need a valid name and at most {} bytes", MAX_LABEL_LEN)));
+        }
+        Ok(())
+    }
+}
+
+/// Filter for `SensoryStreamManager::query_events`
+#[derive(Debug, Clone, Default)]
+pub struct SensoryEventQuery {
+    /// Earliest timestamp, inclusive
+    pub since_ms: Option<u64>,
+    /// Latest timestamp, inclusive
+    pub until_ms: Option<u64>,
+    pub kind: Option<String>,
+    pub source: Option<String>,
+    /// Return only the most recent matches
+    pub limit: Option<usize>,
+}
+
+/// Time-ordered event rows with their retention policy
+struct SensoryEventTable {
+    retention: RetentionPolicy,
+    rows: VecDeque<SensoryEventRecord>,
+}
+
+impl SensoryEventTable {
+    fn insert(&mut self, record: SensoryEventRecord) {
+        // Events almost always arrive in order; late ones are slotted in
+        let at = self.rows.partition_point(|r| r.timestamp_ms <= record.timestamp_ms);
+        self.rows.insert(at, record);
+    }
+
+    fn enforce_retention(&mut self, now_ms: u64) -> usize {
+        let before = self.rows.len();
+        if let Some(max_age_secs) = self.retention.max_age_secs {
+            let cutoff = now_ms.saturating_sub(max_age_secs.saturating_mul(1000));
+            let expired = self.rows.partition_point(|r| r.timestamp_ms < cutoff);
+            self.rows.drain(..expired);
+        }
+        let excess = self.rows.len().saturating_sub(self.retention.max_rows);
+        self.rows.drain(..excess);
+        before - self.rows.len()
+    }
+}
+
+/// Most gaps remembered per declared stream
+const MAX_STREAM_GAPS: usize = 1_000;
+/// Most points one windowed query may resample to
+const MAX_WINDOW_POINTS: usize = 1_000_000;
+/// Highest declared or resampling rate
+const MAX_STREAM_HZ: f64 = 100_000.0;
+
+/// Name and unit of one channel of a declared stream
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelSpec {
+    pub name: String,
+    /// e.g. "m/s^2", "degC"
+    pub unit: String,
+}
+
+/// Unit of the `timestamp` on `StreamData` pushed to a declared stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimestampUnit {
+    Seconds,
+    #[default]
+    Millis,
+    Micros,
+}
+
+impl TimestampUnit {
+    fn to_millis(self, timestamp: u64) -> u64 {
+        match self {
+            TimestampUnit::Seconds => timestamp.saturating_mul(1000),
+            TimestampUnit::Millis => timestamp,
+            TimestampUnit::Micros => timestamp / 1000,
+        }
+    }
+}
+
+/// Typed declaration of a stream: what each value means and how often it comes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StreamSchema {
+    pub channels: Vec<ChannelSpec>,
+    pub expected_hz: f64,
+    /// An interval longer than this many expected periods is a gap
+    #[serde(default = "default_gap_tolerance")]
+    pub gap_tolerance: f64,
+    /// Samples kept, oldest dropped first
+    #[serde(default = "default_max_samples")]
+    pub max_samples: usize,
+    #[serde(default)]
+    pub timestamp_unit: TimestampUnit,
+}
+
+fn default_gap_tolerance() -> f64 {
+    2.0
+}
+
+fn default_max_samples() -> usize {
+    100_000
+}
+
+impl StreamSchema {
+    pub fn new(channels: Vec<ChannelSpec>, expected_hz: f64) -> Self {
+        Self {
+            channels,
+            expected_hz,
+            gap_tolerance: default_gap_tolerance(),
+            max_samples: default_max_samples(),
+            timestamp_unit: TimestampUnit::default(),
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.channels.is_empty() || self.channels.len() > MAX_EVENT_FIELDS {
+            return Err(Error::Storage(format!("A stream declares 1-{} channels", MAX_EVENT_FIELDS)));
+        }
+        for (idx, channel) in self.channels.iter().enumerate() {
+            if channel.name.is_empty() || channel.name.len() > MAX_EVENT_NAME_LEN || channel.unit.len() > MAX_EVENT_NAME_LEN {
+                return Err(Error::Storage(format!(
+                    "Channel names must be 1-{} characters and units at most {}",
+                    MAX_EVENT_NAME_LEN, MAX_EVENT_NAME_LEN
+                )));
+            }
+            if self.channels[..idx].iter().any(|other| other.name == channel.name) {
+                return Err(Error::Storage(format!("Channel {} is declared twice", channel.name)));
+            }
+        }
+        if !self.expected_hz.is_finite() || self.expected_hz <= 0.0 || self.expected_hz > MAX_STREAM_HZ {
+            return Err(Error::Storage(format!("Expected rate must be above 0 and at most {}Hz", MAX_STREAM_HZ)));
+        }
+        if !self.gap_tolerance.is_finite() || self.gap_tolerance < 1.0 {
+            return Err(Error::Storage("Gap tolerance must be at least one period".to_string()));
+        }
+        if self.max_samples < 2 {
+            return Err(Error::Storage("A stream keeps at least 2 samples".to_string()));
+        }
+        Ok(())
+    }
+
+    fn period_ms(&self) -> f64 {
+        1000.0 / self.expected_hz
+    }
+
+    /// Longest interval between samples that is not a gap
+    fn gap_threshold_ms(&self) -> f64 {
+        self.period_ms() * self.gap_tolerance
+    }
+}
+
+/// Samples missing between two received ones
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StreamGap {
+    /// Last sample before the gap
+    pub start_ms: u64,
+    /// First sample after it
+    pub end_ms: u64,
+    /// Samples the expected rate called for in between
+    pub missing_samples: u64,
+}
+
+/// Observed state of a declared stream
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StreamHealth {
+    pub stream_id: String,
+    pub expected_hz: f64,
+    /// Rate over the most recent samples (None with fewer than two)
+    pub observed_hz: Option<f64>,
+    pub sample_count: usize,
+    pub last_sample_ms: Option<u64>,
+    pub dropped_out: bool,
+    pub gap_count: u64,
+    pub missing_samples: u64,
+}
+
+/// How a window is resampled onto its grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResampleMethod {
+    /// Interpolate between the samples either side of each point
+    #[default]
+    Linear,
+    /// Take the closest sample
+    Nearest,
+    /// Average the samples within half a grid step of each point
+    Mean,
+}
+
+/// Window for `SensoryStreamManager::query_window`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WindowQuery {
+    /// Window length, ending at `until_ms`
+    pub last_secs: f64,
+    /// End of the window (default: now)
+    #[serde(default)]
+    pub until_ms: Option<u64>,
+    /// Resample onto an even grid at this rate, the last point at the end of
+    /// the window (default: the raw samples)
+    #[serde(default)]
+    pub resample_hz: Option<f64>,
+    #[serde(default)]
+    pub method: ResampleMethod,
+}
+
+impl WindowQuery {
+    /// The last `secs` seconds up to now, raw
+    pub fn last(secs: f64) -> Self {
+        Self { last_secs: secs, ..Default::default() }
+    }
+
+    pub fn until(mut self, until_ms: u64) -> Self {
+        self.until_ms = Some(until_ms);
+        self
+    }
+
+    pub fn resampled(mut self, hz: f64, method: ResampleMethod) -> Self {
+        self.resample_hz = Some(hz);
+        self.method = method;
+        self
+    }
+}
+
+/// Samples of a declared stream over a window
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StreamWindow {
+    pub stream_id: String,
+    pub channels: Vec<ChannelSpec>,
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub timestamps_ms: Vec<u64>,
+    /// One series per channel, aligned with `timestamps_ms`. Resampled points
+    /// with no sample close enough (inside a gap) are NaN.
+    pub values: Vec<Vec<f64>>,
+    /// Gaps overlapping the window
+    pub gaps: Vec<StreamGap>,
+}
+
+impl StreamWindow {
+    /// Series of the named channel
+    pub fn channel(&self, name: &str) -> Option<&[f64]> {
+        let idx = self.channels.iter().position(|channel| channel.name == name)?;
+        Some(&self.values[idx])
+    }
+
+    /// A Timestamp column followed by a Float64 column per channel, for analytics
+    pub fn to_columns(&self) -> Vec<Column> {
+        let timestamps = self.timestamps_ms.iter().map(|&t| t.min(i64::MAX as u64) as i64).collect();
+        std::iter::once(Column::Timestamp(timestamps))
+            .chain(self.values.iter().map(|series| Column::Float64(series.clone())))
+            .collect()
+    }
+
+    /// Least-squares linear extrapolation of each channel to `at_ms`, from the
+    /// window's non-NaN points. NaN for channels with fewer than two points.
+    pub fn extrapolate(&self, at_ms: u64) -> Vec<f64> {
+        self.values
+            .iter()
+            .map(|series| {
+                let points: Vec<(f64, f64)> = self.timestamps_ms.iter()
+                    .zip(series)
+                    .filter(|(_, value)| !value.is_nan())
+                    .map(|(&t, &value)| (t as f64, value))
+                    .collect();
+                if points.len() < 2 {
+                    return f64::NAN;
+                }
+                let n = points.len() as f64;
+                let mean_t = points.iter().map(|(t, _)| t).sum::<f64>() / n;
+                let mean_v = points.iter().map(|(_, v)| v).sum::<f64>() / n;
+                let covariance: f64 = points.iter().map(|(t, v)| (t - mean_t) * (v - mean_v)).sum();
+                let variance: f64 = points.iter().map(|(t, _)| (t - mean_t).powi(2)).sum();
+                let slope = if variance > 0.0 { covariance / variance } else { 0.0 };
+                mean_v + slope * (at_ms as f64 - mean_t)
+            })
+            .collect()
+    }
+}
+
+/// Samples, gaps and dropout state of a declared stream
+struct TypedStream {
+    schema: StreamSchema,
+    /// (timestamp_ms, one value per channel), in time order
+    samples: VecDeque<(u64, Vec<f64>)>,
+    gaps: VecDeque<StreamGap>,
+    gap_count: u64,
+    missing_samples: u64,
+    dropped_out: bool,
+}
+
+impl TypedStream {
+    fn new(schema: StreamSchema) -> Self {
+        Self {
+            schema,
+            samples: VecDeque::new(),
+            gaps: VecDeque::new(),
+            gap_count: 0,
+            missing_samples: 0,
+            dropped_out: false,
+        }
+    }
+
+    /// Insert in time order. Only a sample extending the stream can close a gap;
+    /// late ones are slotted in.
+    fn insert(&mut self, timestamp_ms: u64, values: Vec<f64>) -> Option<StreamGap> {
+        let last = self.samples.back().map(|(timestamp, _)| *timestamp);
+        let gap = match last {
+            Some(last) if timestamp_ms >= last => {
+                let interval = (timestamp_ms - last) as f64;
+                (interval > self.schema.gap_threshold_ms()).then(|| StreamGap {
+                    start_ms: last,
+                    end_ms: timestamp_ms,
+                    missing_samples: ((interval / self.schema.period_ms()).round() as u64).saturating_sub(1),
+                })
+            }
+            _ => None,
+        };
+        let at = self.samples.partition_point(|(timestamp, _)| *timestamp <= timestamp_ms);
+        self.samples.insert(at, (timestamp_ms, values));
+        self.dropped_out = false;
+        self.enforce_capacity();
+        if let Some(gap) = &gap {
+            self.gap_count += 1;
+            self.missing_samples = self.missing_samples.saturating_add(gap.missing_samples);
+            self.gaps.push_back(gap.clone());
+            if self.gaps.len() > MAX_STREAM_GAPS {
+                self.gaps.pop_front();
+            }
+        }
+        gap
+    }
+
+    fn enforce_capacity(&mut self) {
+        let excess = self.samples.len().saturating_sub(self.schema.max_samples);
+        self.samples.drain(..excess);
+    }
+
+    fn observed_hz(&self) -> Option<f64> {
+        const RECENT: usize = 100;
+        let skip = self.samples.len().saturating_sub(RECENT);
+        let first = self.samples.get(skip)?.0;
+        let last = self.samples.back()?.0;
+        let intervals = self.samples.len() - skip - 1;
+        (intervals > 0 && last > first).then(|| intervals as f64 * 1000.0 / (last - first) as f64)
+    }
+
+    fn raw_window(&self, start_ms: u64, end_ms: u64) -> (Vec<u64>, Vec<Vec<f64>>) {
+        let from = self.samples.partition_point(|(timestamp, _)| *timestamp < start_ms);
+        let to = self.samples.partition_point(|(timestamp, _)| *timestamp <= end_ms);
+        let mut values = vec![Vec::with_capacity(to - from); self.schema.channels.len()];
+        let timestamps = self.samples.range(from..to)
+            .map(|(timestamp, sample)| {
+                for (series, value) in values.iter_mut().zip(sample) {
+                    series.push(*value);
+                }
+                *timestamp
+            })
+            .collect();
+        (timestamps, values)
+    }
+
+    fn resampled_window(&self, end_ms: u64, last_secs: f64, hz: f64, method: ResampleMethod) -> Result<(Vec<u64>, Vec<Vec<f64>>)> {
+        if !hz.is_finite() || hz <= 0.0 || hz > MAX_STREAM_HZ {
+            return Err(Error::Storage(format!("Resampling rate must be above 0 and at most {}Hz", MAX_STREAM_HZ)));
+        }
+        let points = (last_secs * hz).round().max(1.0);
+        if points > MAX_WINDOW_POINTS as f64 {
+            return Err(Error::Storage(format!("Window would resample to more than {} points", MAX_WINDOW_POINTS)));
+        }
+        let points = points as usize;
+        let step_ms = 1000.0 / hz;
+        let threshold = self.schema.gap_threshold_ms();
+        let grid: Vec<f64> = (0..points)
+            .map(|k| end_ms as f64 - (points - 1 - k) as f64 * step_ms)
+            .collect();
+
+        let mut values = vec![Vec::with_capacity(points); self.schema.channels.len()];
+        for &t in &grid {
+            // First sample at or after t
+            let after = self.samples.partition_point(|(timestamp, _)| (*timestamp as f64) < t);
+            let next = self.samples.get(after);
+            let prev = after.checked_sub(1).and_then(|idx| self.samples.get(idx));
+            for (channel, series) in values.iter_mut().enumerate() {
+                let value = match method {
+                    ResampleMethod::Linear => match (prev, next) {
+                        (_, Some((tn, vn))) if *tn as f64 == t => vn[channel],
+                        (Some((tp, vp)), Some((tn, vn))) if (*tn - *tp) as f64 <= threshold => {
+                            let fraction = (t - *tp as f64) / (*tn - *tp) as f64;
+                            vp[channel] + (vn[channel] - vp[channel]) * fraction
+                        }
+                        // Hold the newest sample at the edge of the stream
+                        (Some((tp, vp)), None) if t - *tp as f64 <= threshold => vp[channel],
+                        (None, Some((tn, vn))) if *tn as f64 - t <= threshold => vn[channel],
+                        _ => f64::NAN,
+                    },
+                    ResampleMethod::Nearest => [prev, next]
+                        .into_iter()
+                        .flatten()
+                        .map(|(timestamp, sample)| ((*timestamp as f64 - t).abs(), sample[channel]))
+                        .filter(|(distance, _)| *distance <= threshold)
+                        .min_by(|a, b| a.0.total_cmp(&b.0))
+                        .map_or(f64::NAN, |(_, value)| value),
+                    ResampleMethod::Mean => {
+                        let from = self.samples.partition_point(|(timestamp, _)| (*timestamp as f64) <= t - step_ms / 2.0);
+                        let to = self.samples.partition_point(|(timestamp, _)| (*timestamp as f64) <= t + step_ms / 2.0);
+                        if from == to {
+                            f64::NAN
+                        } else {
+                            self.samples.range(from..to).map(|(_, sample)| sample[channel]).sum::<f64>() / (to - from) as f64
+                        }
+                    }
+                };
+                series.push(value);
+            }
+        }
+        let timestamps = grid.into_iter().map(|t| t.max(0.0).round() as u64).collect();
+        Ok((timestamps, values))
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
 }
 
 #[cfg(test)]
@@ -770,5 +1539,135 @@ mod tests {
         let result = manager.push_data("imu1", imu_data).await;
         assert!(result.is_ok());
     }
-}
 
+    fn temperature_schema(hz: f64) -> StreamSchema {
+        StreamSchema::new(vec![ChannelSpec { name: "temp".to_string(), unit: "degC".to_string() }], hz)
+    }
+
+    #[test]
+    fn test_gap_detection() {
+        let manager = SensoryStreamManager::new();
+        manager.declare_stream("thermo", temperature_schema(10.0)).unwrap();
+        let mut events = manager.subscribe();
+
+        for t in [0, 100, 200, 700, 800] {
+            manager.push_sample("thermo", t, vec![t as f64]).unwrap();
+        }
+        assert!(manager.push_sample("thermo", 900, vec![1.0, 2.0]).is_err());
+        assert!(manager.push_sample("thermo", 900, vec![f64::NAN]).is_err());
+
+        let gaps = manager.stream_gaps("thermo", 0).unwrap();
+        assert_eq!(gaps, vec![StreamGap { start_ms: 200, end_ms: 700, missing_samples: 4 }]);
+        match events.try_recv().unwrap() {
+            StreamEvent::Gap { stream_id, gap } => {
+                assert_eq!(stream_id, "thermo");
+                assert_eq!(gap.missing_samples, 4);
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+
+        let health = manager.stream_health("thermo").unwrap();
+        assert_eq!(health.sample_count, 5);
+        assert_eq!(health.gap_count, 1);
+        assert_eq!(health.missing_samples, 4);
+        assert_eq!(health.last_sample_ms, Some(800));
+    }
+
+    #[test]
+    fn test_dropout_reported_once() {
+        let manager = SensoryStreamManager::new();
+        manager.declare_stream("thermo", temperature_schema(10.0)).unwrap();
+        let old = now_millis() - 10_000;
+        manager.push_sample("thermo", old, vec![20.0]).unwrap();
+
+        assert_eq!(manager.check_dropouts(), vec!["thermo".to_string()]);
+        assert!(manager.check_dropouts().is_empty());
+        assert!(manager.stream_health("thermo").unwrap().dropped_out);
+
+        manager.push_sample("thermo", now_millis(), vec![21.0]).unwrap();
+        assert!(!manager.stream_health("thermo").unwrap().dropped_out);
+        assert_eq!(manager.stream_gaps("thermo", 0).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_window_resampling() {
+        let manager = SensoryStreamManager::new();
+        manager.declare_stream("thermo", temperature_schema(10.0)).unwrap();
+        // A ramp at 10Hz with a gap between 1000 and 2000
+        for t in (0..=1000).step_by(100).chain((2000..=3000).step_by(100)) {
+            manager.push_sample("thermo", t, vec![t as f64 / 100.0]).unwrap();
+        }
+
+        let raw = manager.query_window("thermo", &WindowQuery::last(1.0).until(3000)).unwrap();
+        assert_eq!(raw.timestamps_ms.len(), 11);
+        assert_eq!(raw.channel("temp").unwrap()[0], 20.0);
+        assert!(raw.gaps.is_empty());
+
+        let window = WindowQuery::last(0.5).until(3000).resampled(20.0, ResampleMethod::Linear);
+        let resampled = manager.query_window("thermo", &window).unwrap();
+        assert_eq!(resampled.timestamps_ms, vec![2550, 2600, 2650, 2700, 2750, 2800, 2850, 2900, 2950, 3000]);
+        assert_eq!(resampled.values[0][0], 25.5);
+        assert_eq!(resampled.values[0][9], 30.0);
+
+        // Points inside the gap are not interpolated across it
+        let across = WindowQuery::last(3.0).until(3000).resampled(2.0, ResampleMethod::Linear);
+        let across = manager.query_window("thermo", &across).unwrap();
+        assert_eq!(across.timestamps_ms[..3], [500, 1000, 1500]);
+        assert_eq!(across.values[0][..2], [5.0, 10.0]);
+        assert!(across.values[0][2].is_nan());
+        assert_eq!(across.gaps.len(), 1);
+
+        let nearest = WindowQuery::last(0.2).until(2960).resampled(10.0, ResampleMethod::Nearest);
+        assert_eq!(manager.query_window("thermo", &nearest).unwrap().values[0], vec![29.0, 30.0]);
+
+        let mean = WindowQuery::last(0.4).until(3000).resampled(5.0, ResampleMethod::Mean);
+        assert_eq!(manager.query_window("thermo", &mean).unwrap().values[0], vec![28.5, 30.0]);
+
+        assert!(manager.query_window("thermo", &WindowQuery::last(0.0)).is_err());
+        assert!(manager.query_window("missing", &WindowQuery::last(1.0)).is_err());
+    }
+
+    #[test]
+    fn test_window_extrapolation_and_columns() {
+        let manager = SensoryStreamManager::new();
+        manager.declare_stream("thermo", temperature_schema(10.0)).unwrap();
+        for t in (0..=1000).step_by(100) {
+            manager.push_sample("thermo", t, vec![1.0 + t as f64 / 100.0]).unwrap();
+        }
+        let window = manager.query_window("thermo", &WindowQuery::last(1.0).until(1000)).unwrap();
+        let predicted = window.extrapolate(1500);
+        assert!((predicted[0] - 16.0).abs() < 1e-9);
+
+        let columns = window.to_columns();
+        assert_eq!(columns.len(), 2);
+        assert!(matches!(&columns[0], Column::Timestamp(ts) if ts.len() == 11 && ts[10] == 1000));
+        assert!(matches!(&columns[1], Column::Float64(values) if values[0] == 1.0));
+    }
+
+    #[tokio::test]
+    async fn test_declared_imu_stream() {
+        let manager = SensoryStreamManager::new();
+        manager.register_imu_stream("imu1").unwrap();
+        assert!(manager.register_camera_stream("camera1", 64, 48, 30).is_ok());
+        assert!(manager.declare_stream("camera1", temperature_schema(30.0)).is_err());
+
+        let axes = ["ax", "ay", "az", "gx", "gy", "gz", "mx", "my", "mz"];
+        let channels = axes.iter().map(|axis| ChannelSpec { name: axis.to_string(), unit: String::new() }).collect();
+        let mut schema = StreamSchema::new(channels, 100.0);
+        schema.timestamp_unit = TimestampUnit::Micros;
+        manager.declare_stream("imu1", schema).unwrap();
+
+        let imu_data = StreamData::IMUData {
+            accel: vec![0.0, 0.0, 9.8],
+            gyro: vec![0.1, 0.0, 0.0],
+            mag: vec![0.0, 0.5, 0.0],
+            timestamp: 5_000_000,
+        };
+        manager.push_data("imu1", imu_data).await.unwrap();
+
+        let window = manager.query_window("imu1", &WindowQuery::last(1.0).until(5_000)).unwrap();
+        assert_eq!(window.timestamps_ms, vec![5_000]);
+        assert!((window.channel("az").unwrap()[0] - 9.8).abs() < 1e-6);
+        assert!((window.channel("my").unwrap()[0] - 0.5).abs() < 1e-6);
+    }
+}
@@ -118,5 +118,52 @@ mod sensory_tests {
         
         // Temporal index should have been created
     }
-}
 
+    #[test]
+    fn test_event_table_query_and_retention() {
+        use crate::sensory_streams::{RetentionPolicy, SensoryEventQuery, SensoryEventRecord};
+        use std::time::{SystemTime, UNIX_EPOCH};
+
+        let manager = SensoryStreamManager::new();
+        let retention = RetentionPolicy { max_age_secs: Some(60), max_rows: 3 };
+        manager.create_event_table("sounds", retention).unwrap();
+        assert!(manager.create_event_table("bad name", RetentionPolicy::default()).is_err());
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
+        let record = |offset_ms: u64, kind: &str| SensoryEventRecord {
+            timestamp_ms: now - offset_ms,
+            source: "mic".to_string(),
+            kind: kind.to_string(),
+            features: [("loudness_db".to_string(), -20.0)].into_iter().collect(),
+            labels: Default::default(),
+        };
+
+        // Too old for the table's retention
+        manager.insert_event("sounds", record(120_000, "voice")).unwrap();
+        assert_eq!(manager.event_count("sounds"), Some(0));
+        // Out of order inserts are kept in time order
+        manager.insert_event("sounds", record(1_000, "voice")).unwrap();
+        manager.insert_event("sounds", record(3_000, "noise")).unwrap();
+        manager.insert_event("sounds", record(2_000, "voice")).unwrap();
+
+        let all = manager.query_events("sounds", &SensoryEventQuery::default()).unwrap();
+        let offsets: Vec<u64> = all.iter().map(|r| now - r.timestamp_ms).collect();
+        assert_eq!(offsets, vec![3_000, 2_000, 1_000]);
+
+        let query = SensoryEventQuery { kind: Some("voice".to_string()), limit: Some(1), ..Default::default() };
+        let voices = manager.query_events("sounds", &query).unwrap();
+        assert_eq!(voices.len(), 1);
+        assert_eq!(voices[0].timestamp_ms, now - 1_000);
+
+        // max_rows drops the oldest
+        manager.insert_event("sounds", record(500, "voice")).unwrap();
+        let all = manager.query_events("sounds", &SensoryEventQuery::default()).unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0].kind, "voice");
+
+        let mut invalid = record(0, "voice");
+        invalid.features.insert("pitch".to_string(), f64::NAN);
+        assert!(manager.insert_event("sounds", invalid).is_err());
+        assert!(manager.insert_event("missing", record(0, "voice")).is_err());
+    }
+}
@@ -0,0 +1,178 @@
+// Per-database storage backend selection
+// Routes every ColumnStore call to the filesystem, RocksDB or in-memory store
+// picked for the table's database in `storage.backends`. A table stays on the
+// backend it was created on; tables the router has not seen (e.g. loaded from
+// the filesystem store at startup) are served by the filesystem store.
+
+use async_trait::async_trait;
+use narayana_core::config::{StorageBackendKind, StorageBackendsConfig};
+use narayana_core::{Error, Result, schema::Schema, types::TableId, column::Column};
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::info;
+
+use crate::block::BlockMetadata;
+use crate::column_store::{ColumnStore, InMemoryColumnStore, TableWriteGuard};
+use crate::database_manager::DatabaseManager;
+use crate::rocksdb_column_store::RocksDbColumnStore;
+
+/// ColumnStore that dispatches to the backend configured for each database
+pub struct StorageBackends {
+    filesystem: Arc<dyn ColumnStore>,
+    rocksdb: Option<Arc<dyn ColumnStore>>,
+    memory: Option<Arc<InMemoryColumnStore>>,
+    db_manager: Arc<DatabaseManager>,
+    config: RwLock<StorageBackendsConfig>,
+    table_routes: RwLock<HashMap<TableId, StorageBackendKind>>,
+}
+
+impl StorageBackends {
+    pub fn new(
+        filesystem: Arc<dyn ColumnStore>,
+        db_manager: Arc<DatabaseManager>,
+        config: &StorageBackendsConfig,
+    ) -> Self {
+        Self {
+            filesystem,
+            rocksdb: None,
+            memory: None,
+            db_manager,
+            config: RwLock::new(config.clone()),
+            table_routes: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Attach the RocksDB store; tables it already holds are routed to it
+    pub fn with_rocksdb(mut self, store: Arc<RocksDbColumnStore>) -> Self {
+        self.claim(store.table_ids(), StorageBackendKind::RocksDb);
+        self.rocksdb = Some(store);
+        self
+    }
+
+    /// Attach the in-memory store; tables restored into it are routed to it
+    pub fn with_memory(mut self, store: Arc<InMemoryColumnStore>) -> Self {
+        self.claim(store.table_ids(), StorageBackendKind::Memory);
+        self.memory = Some(store);
+        self
+    }
+
+    /// Apply a new `storage.backends` section; only affects tables created afterwards
+    pub fn update(&self, config: &StorageBackendsConfig) {
+        *self.config.write() = config.clone();
+    }
+
+    /// Backend a table lives on
+    pub fn backend_of(&self, table_id: TableId) -> StorageBackendKind {
+        self.table_routes
+            .read()
+            .get(&table_id)
+            .copied()
+            .unwrap_or(StorageBackendKind::FileSystem)
+    }
+
+    fn claim(&self, table_ids: Vec<TableId>, kind: StorageBackendKind) {
+        let mut routes = self.table_routes.write();
+        for table_id in table_ids {
+            routes.insert(table_id, kind);
+        }
+    }
+
+    fn store(&self, kind: StorageBackendKind) -> Result<Arc<dyn ColumnStore>> {
+        match kind {
+            StorageBackendKind::FileSystem => Ok(self.filesystem.clone()),
+            StorageBackendKind::RocksDb => self
+                .rocksdb
+                .clone()
+                .ok_or_else(|| Error::Storage("RocksDB storage backend is not enabled".to_string())),
+            StorageBackendKind::Memory => self
+                .memory
+                .clone()
+                .map(|store| store as Arc<dyn ColumnStore>)
+                .ok_or_else(|| Error::Storage("In-memory storage backend is not enabled".to_string())),
+        }
+    }
+
+    fn store_for(&self, table_id: TableId) -> Result<Arc<dyn ColumnStore>> {
+        self.store(self.backend_of(table_id))
+    }
+
+    /// Backend for a new table, from the database it was registered under
+    fn backend_for_new_table(&self, table_id: TableId) -> StorageBackendKind {
+        let database = self.db_manager.get_table_info(table_id).and_then(|table| {
+            self.db_manager
+                .list_databases()
+                .into_iter()
+                .find(|db| db.id == table.database_id)
+                .map(|db| db.name)
+        });
+        let config = self.config.read();
+        match database {
+            Some(name) => config.backend_for(&name),
+            None => config.default,
+        }
+    }
+}
+
+#[async_trait]
+impl ColumnStore for StorageBackends {
+    async fn create_table(&self, table_id: TableId, schema: Schema) -> Result<()> {
+        let kind = self.backend_for_new_table(table_id);
+        self.store(kind)?.create_table(table_id, schema).await?;
+        self.table_routes.write().insert(table_id, kind);
+        if kind != StorageBackendKind::FileSystem {
+            info!("Table {} stored on the {:?} backend", table_id.0, kind);
+        }
+        Ok(())
+    }
+
+    async fn write_columns(&self, table_id: TableId, columns: Vec<Column>) -> Result<()> {
+        self.store_for(table_id)?.write_columns(table_id, columns).await
+    }
+
+    async fn read_columns(
+        &self,
+        table_id: TableId,
+        column_ids: Vec<u32>,
+        row_start: usize,
+        row_count: usize,
+    ) -> Result<Vec<Column>> {
+        self.store_for(table_id)?
+            .read_columns(table_id, column_ids, row_start, row_count)
+            .await
+    }
+
+    async fn get_schema(&self, table_id: TableId) -> Result<Schema> {
+        self.store_for(table_id)?.get_schema(table_id).await
+    }
+
+    async fn get_block_metadata(
+        &self,
+        table_id: TableId,
+        column_id: u32,
+    ) -> Result<Vec<BlockMetadata>> {
+        self.store_for(table_id)?.get_block_metadata(table_id, column_id).await
+    }
+
+    async fn delete_table(&self, table_id: TableId) -> Result<()> {
+        self.store_for(table_id)?.delete_table(table_id).await?;
+        self.table_routes.write().remove(&table_id);
+        Ok(())
+    }
+
+    async fn lock_table(&self, table_id: TableId) -> TableWriteGuard {
+        match self.store_for(table_id) {
+            Ok(store) => store.lock_table(table_id).await,
+            // Writes made with it fail the same way
+            Err(_) => TableWriteGuard::unlocked(table_id),
+        }
+    }
+
+    async fn write_locked(&self, guard: &TableWriteGuard, columns: Vec<Column>) -> Result<()> {
+        self.store_for(guard.table_id())?.write_locked(guard, columns).await
+    }
+
+    async fn replace_rows(&self, guard: &TableWriteGuard, columns: Vec<Column>) -> Result<()> {
+        self.store_for(guard.table_id())?.replace_rows(guard, columns).await
+    }
+}
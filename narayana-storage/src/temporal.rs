@@ -0,0 +1,601 @@
+// System-versioned temporal tables
+// A versioned table keeps every version of its rows. Rows are identified by
+// a key column: writing a row whose key is already present supersedes the
+// current version instead of adding a second live row, and deleting a key
+// ends its current version. All versions stay in the table, append-only,
+// with the time each became current in the table's period column (Unix
+// milliseconds in an Int64 or Timestamp column, set on write whatever the
+// client sent). A delete appends a tombstone row - a copy of the last
+// version - whose period value is the negated delete time. The version log
+// is rebuilt from those two columns, so re-enabling versioning after a
+// restart restores the table's history.
+//
+// Reads through `TemporalManager::at` see the rows current at a point in
+// system time, now by default; `FOR SYSTEM_TIME AS OF <ts>` is a read at
+// `ts`. `row_start` and `row_count` address those rows, not the stored
+// versions. Versions that stopped being current longer than the table's
+// retention ago are dropped by `purge`, which rewrites the table without
+// them; reads from before the purge horizon are refused rather than
+// answered from partial history.
+
+use async_trait::async_trait;
+use narayana_core::{column::Column, schema::{DataType, Schema}, types::TableId, Error, Result};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex;
+use tracing::info;
+
+use crate::block::{BlockMetadata, BlockPredicate};
+use crate::column_store::{ColumnStore, TableWriteGuard, WriteLocked};
+use crate::full_text::FullTextIndexManager;
+use crate::row_security::{cell, take_rows};
+
+/// Keys one delete call can end
+pub const MAX_DELETE_KEYS: usize = 1000;
+/// Versions returned by one history lookup, newest kept
+const MAX_HISTORY: usize = 1000;
+/// Longest retention accepted (10 years)
+const MAX_RETENTION_SECS: u64 = 10 * 365 * 24 * 60 * 60;
+
+/// How a table is versioned
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TemporalConfig {
+    /// Column identifying a row across its versions
+    pub key_column: String,
+    /// Int64 or Timestamp column holding when each version became current
+    pub period_column: String,
+    /// How long superseded versions are kept; forever when unset
+    #[serde(default)]
+    pub retention_secs: Option<u64>,
+}
+
+/// A versioned table's configuration and size
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemporalStatus {
+    #[serde(flatten)]
+    pub config: TemporalConfig,
+    /// Rows stored, every version and tombstone included
+    pub stored_rows: usize,
+    /// Rows current now
+    pub current_rows: usize,
+    /// Earliest time reads can be answered for (Unix ms); 0 until a purge
+    pub history_since: u64,
+}
+
+/// One version of a row
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RowVersion {
+    /// When this version became current (Unix ms)
+    pub valid_from: u64,
+    /// When it was superseded or deleted; None while current
+    pub valid_to: Option<u64>,
+    /// Whether it ended by a delete rather than a newer version
+    pub deleted: bool,
+    pub values: serde_json::Map<String, Value>,
+}
+
+/// What a stored row is in the table's history
+#[derive(Debug, Clone)]
+struct StoredRow {
+    key: String,
+    from: u64,
+    to: Option<u64>,
+    tombstone: bool,
+    /// Ended by a delete
+    deleted: bool,
+}
+
+impl StoredRow {
+    fn visible_at(&self, at: u64) -> bool {
+        !self.tombstone && self.from <= at && self.to.is_none_or(|to| at < to)
+    }
+}
+
+/// Which stored row was current when, for one table
+struct VersionLog {
+    config: TemporalConfig,
+    key_idx: usize,
+    period_idx: usize,
+    /// Indexed by stored row
+    rows: Vec<StoredRow>,
+    /// Key -> stored row of its current version
+    current: HashMap<String, usize>,
+    /// Latest system time handed out, so versions never go back in time
+    clock: u64,
+    history_since: u64,
+}
+
+impl VersionLog {
+    fn new(config: TemporalConfig, schema: &Schema) -> Result<Self> {
+        let key_idx = schema.field_index(&config.key_column)
+            .ok_or_else(|| Error::ColumnNotFound(config.key_column.clone()))?;
+        let period_idx = schema.field_index(&config.period_column)
+            .ok_or_else(|| Error::ColumnNotFound(config.period_column.clone()))?;
+        if key_idx == period_idx {
+            return Err(Error::Storage("The key column can't be the period column".to_string()));
+        }
+        if !matches!(schema.fields[period_idx].data_type, DataType::Int64 | DataType::Timestamp) {
+            return Err(Error::Storage(format!("Period column '{}' must be Int64 or Timestamp", config.period_column)));
+        }
+        if !schema.embeddings.is_empty() {
+            return Err(Error::Storage("Tables with generated embedding columns can't be versioned".to_string()));
+        }
+        if config.retention_secs.is_some_and(|secs| secs == 0 || secs > MAX_RETENTION_SECS) {
+            return Err(Error::Storage(format!("retention_secs must be between 1 and {}", MAX_RETENTION_SECS)));
+        }
+        Ok(Self {
+            config,
+            key_idx,
+            period_idx,
+            rows: Vec::new(),
+            current: HashMap::new(),
+            clock: 0,
+            history_since: 0,
+        })
+    }
+
+    /// Take in rows stored since the log last looked, whoever wrote them
+    async fn catch_up(&mut self, store: &dyn ColumnStore, table_id: TableId) -> Result<()> {
+        let ids = vec![self.key_idx as u32, self.period_idx as u32];
+        let columns = store.read_columns(table_id, ids, self.rows.len(), usize::MAX).await?;
+        // Stores skip columns that hold no data
+        if columns.len() < 2 {
+            return Ok(());
+        }
+        for row in 0..columns[0].len() {
+            let period = match cell(&columns[1], row) {
+                Value::Number(n) => n.as_i64().unwrap_or(0),
+                _ => 0,
+            };
+            self.append(key_text(&cell(&columns[0], row)), period);
+        }
+        Ok(())
+    }
+
+    /// Record the next stored row from its key and period value
+    fn append(&mut self, key: String, period: i64) {
+        let row = self.rows.len();
+        let tombstone = period < 0;
+        let at = period.unsigned_abs();
+        self.clock = self.clock.max(at);
+        if let Some(previous) = self.current.remove(&key) {
+            let previous = &mut self.rows[previous];
+            previous.to = Some(at.max(previous.from));
+            previous.deleted = tombstone;
+        }
+        if tombstone {
+            self.rows.push(StoredRow { key, from: at, to: Some(at), tombstone, deleted: false });
+        } else {
+            self.current.insert(key.clone(), row);
+            self.rows.push(StoredRow { key, from: at, to: None, tombstone, deleted: false });
+        }
+    }
+
+    /// Next system time, never before one already handed out
+    fn tick(&mut self) -> u64 {
+        self.clock = self.clock.max(now());
+        self.clock
+    }
+
+    /// Stored rows visible at `at` (now when None), in stored order
+    fn visible(&self, at: Option<u64>) -> Result<Vec<usize>> {
+        match at {
+            None => {
+                let mut rows: Vec<usize> = self.current.values().copied().collect();
+                rows.sort_unstable();
+                Ok(rows)
+            }
+            Some(at) if at < self.history_since => Err(Error::Storage(format!(
+                "History before {} has been purged",
+                self.history_since
+            ))),
+            Some(at) => Ok((0..self.rows.len()).filter(|&row| self.rows[row].visible_at(at)).collect()),
+        }
+    }
+
+    /// Forget stored rows that were removed from the table (sorted)
+    fn remove(&mut self, removed: &[usize]) {
+        let mut row = 0;
+        self.rows.retain(|_| {
+            let keep = removed.binary_search(&row).is_err();
+            row += 1;
+            keep
+        });
+        self.current = self.rows
+            .iter()
+            .enumerate()
+            .filter(|(_, r)| !r.tombstone && r.to.is_none())
+            .map(|(row, r)| (r.key.clone(), row))
+            .collect();
+    }
+
+    fn status(&self) -> TemporalStatus {
+        TemporalStatus {
+            config: self.config.clone(),
+            stored_rows: self.rows.len(),
+            current_rows: self.current.len(),
+            history_since: self.history_since,
+        }
+    }
+}
+
+/// Versioning of all system-versioned tables
+#[derive(Default)]
+pub struct TemporalManager {
+    tables: RwLock<HashMap<TableId, Arc<Mutex<VersionLog>>>>,
+}
+
+impl TemporalManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Version a table, rebuilding its history from the rows it holds;
+    /// replaces the configuration of a table that is already versioned
+    pub async fn enable(&self, store: &dyn ColumnStore, table_id: TableId, config: TemporalConfig) -> Result<TemporalStatus> {
+        let schema = store.get_schema(table_id).await?;
+        let mut log = VersionLog::new(config, &schema)?;
+        log.catch_up(store, table_id).await?;
+        let status = log.status();
+        self.tables.write().insert(table_id, Arc::new(Mutex::new(log)));
+        info!("Table {} is system-versioned ({} stored rows)", table_id.0, status.stored_rows);
+        Ok(status)
+    }
+
+    /// Stop versioning a table; every stored version becomes a plain row
+    pub fn disable(&self, table_id: TableId) -> bool {
+        self.tables.write().remove(&table_id).is_some()
+    }
+
+    pub fn is_versioned(&self, table_id: TableId) -> bool {
+        self.tables.read().contains_key(&table_id)
+    }
+
+    pub fn versioned_tables(&self) -> Vec<TableId> {
+        let mut tables: Vec<TableId> = self.tables.read().keys().copied().collect();
+        tables.sort_by_key(|t| t.0);
+        tables
+    }
+
+    pub async fn status(&self, table_id: TableId) -> Option<TemporalStatus> {
+        let log = self.log(table_id)?;
+        let status = log.lock().await.status();
+        Some(status)
+    }
+
+    /// Forget a dropped table
+    pub fn drop_table(&self, table_id: TableId) {
+        self.tables.write().remove(&table_id);
+    }
+
+    /// Write rows; into a versioned table each becomes the current version
+    /// of its key, stamped with the write's system time. Returns the columns
+    /// as stored.
+    pub async fn write(&self, store: &dyn ColumnStore, table_id: TableId, mut columns: Vec<Column>) -> Result<Vec<Column>> {
+        let Some(log) = self.log(table_id) else {
+            store.write_columns(table_id, columns.clone()).await?;
+            return Ok(columns);
+        };
+        // The table's write lock is always taken before its log
+        let guard = store.lock_table(table_id).await;
+        let mut log = log.lock().await;
+        log.catch_up(store, table_id).await?;
+        let rows = columns.first().map(|c| c.len()).unwrap_or(0);
+        let period_idx = log.period_idx;
+        if columns.len() <= period_idx.max(log.key_idx) {
+            return Err(Error::Storage(format!("Expected a value for every column, got {} columns", columns.len())));
+        }
+        let at = log.tick();
+        columns[period_idx] = period_column(&columns[period_idx], vec![at as i64; rows]);
+        store.write_locked(&guard, columns.clone()).await?;
+        for row in 0..rows {
+            let key = key_text(&cell(&columns[log.key_idx], row));
+            log.append(key, at as i64);
+        }
+        Ok(columns)
+    }
+
+    /// End the current version of each key by appending a tombstone; keys
+    /// with no current version are skipped. Returns the tombstone columns
+    /// written (empty when nothing was deleted).
+    pub async fn delete(&self, store: &dyn ColumnStore, table_id: TableId, keys: &[Value]) -> Result<Vec<Column>> {
+        if keys.len() > MAX_DELETE_KEYS {
+            return Err(Error::Storage(format!("At most {} keys can be deleted at once", MAX_DELETE_KEYS)));
+        }
+        let log = self.log(table_id).ok_or_else(|| not_versioned(table_id))?;
+        let guard = store.lock_table(table_id).await;
+        let mut log = log.lock().await;
+        log.catch_up(store, table_id).await?;
+        let mut rows: Vec<usize> = keys.iter().filter_map(|key| log.current.get(&key_text(key)).copied()).collect();
+        rows.sort_unstable();
+        rows.dedup();
+        if rows.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let schema = store.get_schema(table_id).await?;
+        let mut tombstones = read_rows(store, table_id, &schema, &rows).await?;
+        let at = log.tick();
+        tombstones[log.period_idx] = period_column(&tombstones[log.period_idx], vec![-(at as i64); rows.len()]);
+        store.write_locked(&guard, tombstones.clone()).await?;
+        for row in 0..rows.len() {
+            let key = key_text(&cell(&tombstones[log.key_idx], row));
+            log.append(key, -(at as i64));
+        }
+        Ok(tombstones)
+    }
+
+    /// Versions of the row with `key`, oldest first
+    pub async fn history(&self, store: &dyn ColumnStore, table_id: TableId, key: &Value) -> Result<Vec<RowVersion>> {
+        let log = self.log(table_id).ok_or_else(|| not_versioned(table_id))?;
+        let mut log = log.lock().await;
+        log.catch_up(store, table_id).await?;
+        let key = key_text(key);
+        let mut rows: Vec<usize> = (0..log.rows.len())
+            .filter(|&row| !log.rows[row].tombstone && log.rows[row].key == key)
+            .collect();
+        if rows.len() > MAX_HISTORY {
+            rows.drain(..rows.len() - MAX_HISTORY);
+        }
+        if rows.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let schema = store.get_schema(table_id).await?;
+        let columns = read_rows(store, table_id, &schema, &rows).await?;
+        Ok(rows.iter().enumerate().map(|(position, &row)| {
+            let stored = &log.rows[row];
+            RowVersion {
+                valid_from: stored.from,
+                valid_to: stored.to,
+                deleted: stored.deleted,
+                values: schema.fields.iter().zip(&columns)
+                    .map(|(field, column)| (field.name.clone(), cell(column, position)))
+                    .collect(),
+            }
+        }).collect())
+    }
+
+    /// Current version of the row with `key`, by column name
+    pub async fn current(&self, store: &dyn ColumnStore, table_id: TableId, key: &Value) -> Result<Option<serde_json::Map<String, Value>>> {
+        let log = self.log(table_id).ok_or_else(|| not_versioned(table_id))?;
+        let mut log = log.lock().await;
+        log.catch_up(store, table_id).await?;
+        let Some(&row) = log.current.get(&key_text(key)) else {
+            return Ok(None);
+        };
+        let schema = store.get_schema(table_id).await?;
+        let columns = read_rows(store, table_id, &schema, &[row]).await?;
+        Ok(Some(schema.fields.iter().zip(&columns).map(|(field, column)| (field.name.clone(), cell(column, 0))).collect()))
+    }
+
+    /// `store` as of system time `as_of`, or now when None
+    pub fn at(self: &Arc<Self>, store: Arc<dyn ColumnStore>, as_of: Option<u64>) -> Arc<dyn ColumnStore> {
+        Arc::new(TemporalColumnStore { inner: store, temporal: self.clone(), as_of })
+    }
+
+    /// Drop the table's versions that stopped being current more than its
+    /// retention before `now` (Unix ms); returns the stored rows removed
+    pub async fn purge(
+        &self,
+        store: &dyn ColumnStore,
+        table_id: TableId,
+        full_text: Option<&FullTextIndexManager>,
+        now: u64,
+    ) -> Result<usize> {
+        let log = self.log(table_id).ok_or_else(|| not_versioned(table_id))?;
+        let guard = store.lock_table(table_id).await;
+        let mut log = log.lock().await;
+        let Some(retention_secs) = log.config.retention_secs else {
+            return Ok(0);
+        };
+        log.catch_up(store, table_id).await?;
+        let cutoff = now.saturating_sub(retention_secs.saturating_mul(1000));
+        let removed: Vec<usize> = (0..log.rows.len())
+            .filter(|&row| log.rows[row].to.is_some_and(|to| to <= cutoff))
+            .collect();
+        if removed.is_empty() {
+            return Ok(0);
+        }
+
+        let schema = store.get_schema(table_id).await?;
+        let column_ids: Vec<u32> = (0..schema.fields.len() as u32).collect();
+        let columns = store.read_columns(table_id, column_ids, 0, usize::MAX).await?;
+        if columns.len() != schema.fields.len() {
+            return Err(Error::Storage(format!("Read returned {} of {} columns", columns.len(), schema.fields.len())));
+        }
+        let kept: Vec<usize> = (0..log.rows.len()).filter(|row| removed.binary_search(row).is_err()).collect();
+        let rewritten: Vec<Column> = columns.iter().map(|column| take_rows(column, &kept)).collect();
+        store.replace_rows(&guard, rewritten.clone()).await?;
+        log.remove(&removed);
+        log.history_since = log.history_since.max(cutoff);
+        if let Some(full_text) = full_text {
+            full_text.reindex_table(table_id, &schema, &rewritten)?;
+        }
+        info!("Purged {} expired versions from table {}", removed.len(), table_id.0);
+        Ok(removed.len())
+    }
+
+    /// Purge every versioned table that has a retention; returns the stored
+    /// rows removed
+    pub async fn purge_expired(&self, store: &dyn ColumnStore, full_text: Option<&FullTextIndexManager>) -> usize {
+        let mut purged = 0;
+        for table_id in self.versioned_tables() {
+            match self.purge(store, table_id, full_text, now()).await {
+                Ok(rows) => purged += rows,
+                Err(e) => tracing::warn!("Failed to purge history of table {}: {}", table_id.0, e),
+            }
+        }
+        purged
+    }
+
+    /// Stored rows (sorted) were removed from a table behind the log's back
+    pub async fn rows_removed(&self, table_id: TableId, removed: &[usize]) {
+        if let Some(log) = self.log(table_id) {
+            log.lock().await.remove(removed);
+        }
+    }
+
+    fn log(&self, table_id: TableId) -> Option<Arc<Mutex<VersionLog>>> {
+        self.tables.read().get(&table_id).cloned()
+    }
+}
+
+/// A column store that shows versioned tables as of one system time
+pub struct TemporalColumnStore {
+    inner: Arc<dyn ColumnStore>,
+    temporal: Arc<TemporalManager>,
+    as_of: Option<u64>,
+}
+
+#[async_trait]
+impl ColumnStore for TemporalColumnStore {
+    async fn create_table(&self, table_id: TableId, schema: Schema) -> Result<()> {
+        self.inner.create_table(table_id, schema).await
+    }
+
+    async fn write_columns(&self, table_id: TableId, columns: Vec<Column>) -> Result<()> {
+        self.temporal.write(self.inner.as_ref(), table_id, columns).await.map(|_| ())
+    }
+
+    async fn lock_table(&self, table_id: TableId) -> TableWriteGuard {
+        self.inner.lock_table(table_id).await
+    }
+
+    async fn write_locked(&self, guard: &TableWriteGuard, columns: Vec<Column>) -> Result<()> {
+        let store = WriteLocked::new(self.inner.as_ref(), guard);
+        self.temporal.write(&store, guard.table_id(), columns).await.map(|_| ())
+    }
+
+    async fn replace_rows(&self, guard: &TableWriteGuard, columns: Vec<Column>) -> Result<()> {
+        if self.temporal.log(guard.table_id()).is_some() {
+            return Err(Error::Storage(format!(
+                "Table {} is system-versioned; its rows can't be replaced through a temporal view",
+                guard.table_id().0
+            )));
+        }
+        self.inner.replace_rows(guard, columns).await
+    }
+
+    async fn read_columns(
+        &self,
+        table_id: TableId,
+        column_ids: Vec<u32>,
+        row_start: usize,
+        row_count: usize,
+    ) -> Result<Vec<Column>> {
+        let Some(log) = self.temporal.log(table_id) else {
+            if self.as_of.is_some() {
+                return Err(not_versioned(table_id));
+            }
+            return self.inner.read_columns(table_id, column_ids, row_start, row_count).await;
+        };
+        let rows: Vec<usize> = {
+            let mut log = log.lock().await;
+            log.catch_up(self.inner.as_ref(), table_id).await?;
+            log.visible(self.as_of)?
+                .into_iter()
+                .skip(row_start)
+                .take(row_count)
+                .collect()
+        };
+        let (Some(&first), Some(&last)) = (rows.first(), rows.last()) else {
+            // Nothing visible: the columns' types, with no rows
+            let columns = self.inner.read_columns(table_id, column_ids, 0, 1).await?;
+            return Ok(columns.iter().map(|column| take_rows(column, &[])).collect());
+        };
+        let columns = self.inner.read_columns(table_id, column_ids, first, last - first + 1).await?;
+        let offsets: Vec<usize> = rows.iter().map(|row| row - first).collect();
+        Ok(columns.iter().map(|column| take_rows(column, &offsets)).collect())
+    }
+
+    async fn get_schema(&self, table_id: TableId) -> Result<Schema> {
+        self.inner.get_schema(table_id).await
+    }
+
+    async fn get_block_metadata(&self, table_id: TableId, column_id: u32) -> Result<Vec<BlockMetadata>> {
+        self.inner.get_block_metadata(table_id, column_id).await
+    }
+
+    /// Zone maps cover every stored version while reads address the visible
+    /// rows, so scans of versioned tables aren't pruned
+    async fn matching_row_ranges(
+        &self,
+        table_id: TableId,
+        column_id: u32,
+        predicate: &BlockPredicate,
+    ) -> Result<Option<Vec<Range<usize>>>> {
+        if self.temporal.log(table_id).is_some() {
+            return Ok(None);
+        }
+        self.inner.matching_row_ranges(table_id, column_id, predicate).await
+    }
+
+    async fn delete_table(&self, table_id: TableId) -> Result<()> {
+        self.inner.delete_table(table_id).await?;
+        self.temporal.drop_table(table_id);
+        Ok(())
+    }
+}
+
+/// Parse a point in system time: Unix milliseconds, an RFC 3339 timestamp,
+/// or either inside `FOR SYSTEM_TIME AS OF <ts>` (optionally quoted)
+pub fn parse_as_of(text: &str) -> Result<u64> {
+    let mut text = text.trim();
+    let upper = text.to_ascii_uppercase();
+    if upper.starts_with("FOR SYSTEM_TIME AS OF") {
+        text = text["FOR SYSTEM_TIME AS OF".len()..].trim();
+    }
+    let text = text.trim_matches(|c| c == '\'' || c == '"');
+    if let Ok(ms) = text.parse::<u64>() {
+        return Ok(ms);
+    }
+    chrono::DateTime::parse_from_rfc3339(text)
+        .ok()
+        .and_then(|t| u64::try_from(t.timestamp_millis()).ok())
+        .ok_or_else(|| Error::Storage("AS OF must be Unix milliseconds or an RFC 3339 timestamp".to_string()))
+}
+
+/// Stored rows `rows` (sorted) of every column, in schema order
+async fn read_rows(store: &dyn ColumnStore, table_id: TableId, schema: &Schema, rows: &[usize]) -> Result<Vec<Column>> {
+    let (first, last) = (rows[0], rows[rows.len() - 1]);
+    let column_ids: Vec<u32> = (0..schema.fields.len() as u32).collect();
+    let columns = store.read_columns(table_id, column_ids, first, last - first + 1).await?;
+    if columns.len() != schema.fields.len() {
+        return Err(Error::Storage(format!("Read returned {} of {} columns", columns.len(), schema.fields.len())));
+    }
+    let offsets: Vec<usize> = rows.iter().map(|row| row - first).collect();
+    Ok(columns.iter().map(|column| take_rows(column, &offsets)).collect())
+}
+
+/// `values` in the period column's own type
+fn period_column(existing: &Column, values: Vec<i64>) -> Column {
+    match existing {
+        Column::Timestamp(_) => Column::Timestamp(values),
+        _ => Column::Int64(values),
+    }
+}
+
+/// Keys compare by their text, so 7 and "7" are the same row
+pub(crate) fn key_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn not_versioned(table_id: TableId) -> Error {
+    Error::Storage(format!("Table {} is not system-versioned", table_id.0))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
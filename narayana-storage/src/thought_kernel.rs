@@ -1,12 +1,15 @@
 // Unified Thought Kernel - The Linux Kernel for Robot Minds
 // Production-ready thought scheduling with priorities, deadlines, cancellation,
-// event hooks, shared memory regions, and GPU scheduling
+// event hooks, shared memory regions, GPU scheduling and pluggable reasoning strategies
 
 use crate::cognitive::*;
 use crate::dynamic_thoughts::*;
 use crate::gpu_execution::GpuEngine;
+use crate::model_registry::{ModelArtifactRegistry, ModelVersion};
+use crate::reasoning_strategies::{ReasoningRequest, ReasoningStrategyRegistry, DEFAULT_THOUGHT_TYPE};
 use narayana_core::{Error, Result};
 use serde::{Deserialize, Serialize};
+use std::future::Future;
 use std::sync::Arc;
 use std::collections::HashMap;
 use parking_lot::RwLock;
@@ -24,6 +27,8 @@ pub struct ThoughtKernel {
     shared_memory: Arc<SharedMemoryRegion>,
     event_hooks: Arc<RwLock<HashMap<String, Vec<Box<dyn Fn(&ThoughtEvent) + Send + Sync>>>>>,
     cancellation_tokens: Arc<RwLock<HashMap<String, CancellationToken>>>,
+    model_artifacts: Option<Arc<ModelArtifactRegistry>>,
+    strategies: Arc<ReasoningStrategyRegistry>,
 }
 
 impl ThoughtKernel {
@@ -43,9 +48,39 @@ impl ThoughtKernel {
             shared_memory,
             event_hooks: Arc::new(RwLock::new(HashMap::new())),
             cancellation_tokens: Arc::new(RwLock::new(HashMap::new())),
+            model_artifacts: None,
+            strategies: Arc::new(ReasoningStrategyRegistry::new()),
         }
     }
 
+    /// Reason with the strategies of `registry` instead of the built-in defaults
+    pub fn with_strategies(mut self, registry: Arc<ReasoningStrategyRegistry>) -> Self {
+        self.strategies = registry;
+        self
+    }
+
+    /// Reasoning strategies, their per-thought-type selection and comparison stats
+    pub fn strategies(&self) -> &Arc<ReasoningStrategyRegistry> {
+        &self.strategies
+    }
+
+    /// Resolve models for thoughts from a versioned artifact registry
+    pub fn with_model_artifacts(mut self, artifacts: Arc<ModelArtifactRegistry>) -> Self {
+        self.model_artifacts = Some(artifacts);
+        self
+    }
+
+    /// Select the model version a thought should load: Production, falling back to Staging
+    pub fn select_model(&self, name: &str) -> Result<(ModelVersion, Vec<u8>)> {
+        let artifacts = self.model_artifacts.as_ref()
+            .ok_or_else(|| Error::Storage("Thought kernel has no model registry configured".to_string()))?;
+        let version = artifacts.resolve_serving(name)
+            .ok_or_else(|| Error::Storage(format!("Model {} has no Production or Staging version", name)))?;
+        let bytes = artifacts.load_artifact(name, &version.version.to_string())?;
+        debug!("Thought kernel selected model {} version {}", name, version.version);
+        Ok((version, bytes))
+    }
+
     /// Spawn a thought with full kernel support
     pub async fn spawn_thought<F>(
         &self,
@@ -54,6 +89,36 @@ impl ThoughtKernel {
     ) -> Result<ThoughtResult>
     where
         F: Fn(ThoughtProcessingContext, serde_json::Value) -> Result<serde_json::Value> + Send + Sync + 'static,
+    {
+        self.run_thought(ctx, move |proc_ctx, content| std::future::ready(processor(proc_ctx, content))).await
+    }
+
+    /// Spawn a thought processed by the reasoning strategy selected for its
+    /// thought type (plus any strategies compared on that type)
+    pub async fn reason(&self, ctx: ThoughtContext) -> Result<ThoughtResult> {
+        let thought_type = ctx.thought_type.clone().unwrap_or_else(|| DEFAULT_THOUGHT_TYPE.to_string());
+        let strategy = self.strategies.strategy_for(&thought_type);
+        let strategies = self.strategies.clone();
+        let brain = self.brain.clone();
+        let selected = strategy.clone();
+        let mut result = self.run_thought(ctx, move |proc_ctx, content| async move {
+            let request = ReasoningRequest {
+                thought_id: proc_ctx.current_thought_id,
+                thought_type,
+                content,
+                context: proc_ctx.context,
+                brain,
+            };
+            strategies.run_as(&selected, request).await.map(|outcome| outcome.result)
+        }).await?;
+        result.strategy = Some(strategy);
+        Ok(result)
+    }
+
+    async fn run_thought<F, Fut>(&self, ctx: ThoughtContext, processor: F) -> Result<ThoughtResult>
+    where
+        F: FnOnce(ThoughtProcessingContext, serde_json::Value) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<serde_json::Value>> + Send + 'static,
     {
         let thought_id = self.brain.create_thought(
             ctx.content.clone(),
@@ -123,7 +188,7 @@ impl ThoughtKernel {
             }
 
             // Process thought
-            let result = processor(proc_ctx, ctx_clone.content)?;
+            let result = processor(proc_ctx, ctx_clone.content).await?;
 
             // Check cancellation again
             if *token_clone.cancelled.read() {
@@ -177,6 +242,7 @@ impl ThoughtKernel {
             thought_id,
             result,
             spawned_thoughts: spawned_thoughts_clone,
+            strategy: None,
         })
     }
 
@@ -293,6 +359,9 @@ pub struct ThoughtContext {
     pub context: HashMap<String, serde_json::Value>,
     pub shared_memory_id: Option<String>,
     pub gpu_required: bool,
+    /// Selects the reasoning strategy `ThoughtKernel::reason` uses
+    #[serde(default)]
+    pub thought_type: Option<String>,
 }
 
 /// Thought result
@@ -301,6 +370,9 @@ pub struct ThoughtResult {
     pub thought_id: String,
     pub result: serde_json::Value,
     pub spawned_thoughts: Vec<String>,
+    /// Reasoning strategy that produced the result, for reasoned thoughts
+    #[serde(default)]
+    pub strategy: Option<String>,
 }
 
 /// Thought event
@@ -449,6 +521,7 @@ mod tests {
             context: HashMap::new(),
             shared_memory_id: None,
             gpu_required: false,
+            thought_type: None,
         };
 
         let result = kernel.spawn_thought(ctx, |_ctx, content| {
@@ -471,6 +544,7 @@ mod tests {
             context: HashMap::new(),
             shared_memory_id: None,
             gpu_required: false,
+            thought_type: None,
         };
 
         let thought_id = kernel.brain.create_thought(
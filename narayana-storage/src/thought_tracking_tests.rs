@@ -51,6 +51,7 @@ mod thought_tracking_tests {
             gpu_required: false,
             context: HashMap::new(),
             shared_memory_id: None,
+            thought_type: None,
         };
         
         // Spawn child thought
@@ -12,6 +12,7 @@ use tokio::runtime::Handle;
 use tokio::task::JoinHandle;
 use std::thread::{self, ThreadId};
 use std::collections::HashMap;
+use crate::priority_scheduler::{PriorityScheduler, SchedulerConfig, SchedulerMetrics};
 
 /// Thread pool type for different operations
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -453,6 +454,9 @@ pub struct ThreadManager {
     
     /// Thread-local storage registry
     tls_registry: Arc<RwLock<HashMap<String, Box<dyn ThreadLocalStorage>>>>,
+    
+    /// Priority-class scheduler
+    scheduler: Option<Arc<PriorityScheduler>>,
 }
 
 /// Thread-local storage trait
@@ -497,6 +501,9 @@ struct InternalThreadingConfig {
     
     /// Enable thread-local storage
     enable_thread_local_storage: bool,
+    
+    /// Priority-class scheduler configuration
+    scheduler: SchedulerConfig,
 }
 
 impl From<narayana_core::config::ThreadingConfig> for InternalThreadingConfig {
@@ -637,6 +644,7 @@ impl From<narayana_core::config::ThreadingConfig> for InternalThreadingConfig {
             enable_thread_priorities: config.enable_thread_priorities,
             thread_spawn_timeout: Duration::from_secs(config.thread_spawn_timeout_secs),
             enable_thread_local_storage: config.enable_thread_local_storage,
+            scheduler: (&config.scheduler).into(),
         }
     }
 }
@@ -650,10 +658,16 @@ impl ThreadManager {
     
     /// Create new thread manager
     pub fn new(config: InternalThreadingConfig) -> Result<Self> {
+        let scheduler = if config.enabled {
+            Some(Arc::new(PriorityScheduler::new(config.scheduler.clone())?))
+        } else {
+            None
+        };
         let manager = Self {
             pools: Arc::new(DashMap::new()),
             global_config: config.clone(),
             tls_registry: Arc::new(RwLock::new(HashMap::new())),
+            scheduler,
         };
         
         // Initialize thread pools
@@ -706,6 +720,16 @@ impl ThreadManager {
         Ok(pool.spawn(f))
     }
     
+    /// Priority-class scheduler, if threading is enabled
+    pub fn scheduler(&self) -> Option<Arc<PriorityScheduler>> {
+        self.scheduler.clone()
+    }
+    
+    /// Scheduler counters by class
+    pub fn scheduler_metrics(&self) -> Option<SchedulerMetrics> {
+        self.scheduler.as_ref().map(|s| s.metrics())
+    }
+    
     /// Get all pool statistics
     pub fn get_all_stats(&self) -> HashMap<ThreadPoolType, ThreadPoolStats> {
         self.pools.iter()
@@ -745,6 +769,7 @@ impl ThreadManager {
     /// Start monitoring thread pools
     fn start_monitoring(&self) {
         let pools = self.pools.clone();
+        let scheduler = self.scheduler.clone();
         let interval = self.global_config.monitoring_interval;
         
         tokio::spawn(async move {
@@ -764,6 +789,19 @@ impl ThreadManager {
                         stats.tasks_completed,
                     );
                 }
+                if let Some(ref scheduler) = scheduler {
+                    for (class, metrics) in scheduler.metrics().classes {
+                        tracing::debug!(
+                            "Scheduler {:?}: {:.1}% utilization, {} queued, {} running, {} completed, max wait {}us",
+                            class,
+                            metrics.utilization * 100.0,
+                            metrics.queued,
+                            metrics.running,
+                            metrics.completed,
+                            metrics.max_wait_us,
+                        );
+                    }
+                }
             }
         });
     }
@@ -811,6 +849,11 @@ impl ThreadManager {
         }
         
         self.pools.clear();
+        
+        if let Some(scheduler) = self.scheduler.clone() {
+            tracing::info!("Shutting down priority scheduler");
+            let _ = tokio::task::spawn_blocking(move || scheduler.shutdown()).await;
+        }
     }
 }
 
@@ -405,6 +405,40 @@ impl TraitCalculator {
         );
     }
     
+    /// All trait interactions as (from, to, strength)
+    pub fn trait_interactions(&self) -> Vec<(TraitType, TraitType, f64)> {
+        self.trait_interactions.read()
+            .iter()
+            .map(|((from, to), strength)| (from.clone(), to.clone(), *strength))
+            .collect()
+    }
+
+    pub fn environmental_weight(&self) -> f64 {
+        self.environmental_weight
+    }
+
+    /// All environmental factors
+    pub fn environmental_factors(&self) -> Vec<EnvironmentalFactor> {
+        self.environmental_factors.read().values().cloned().collect()
+    }
+
+    /// Restore a previously recorded environmental factor, keeping its timestamp
+    pub fn restore_environmental_factor(&self, factor: EnvironmentalFactor) -> Result<()> {
+        if !factor.value.is_finite() || !factor.decay_rate.is_finite() {
+            return Err(Error::Storage("Invalid environmental factor values (NaN/Inf)".to_string()));
+        }
+        let trait_type = self.trait_type_from_string(&factor.factor_type).ok();
+        self.environmental_factors.write().insert(factor.factor_type.clone(), EnvironmentalFactor {
+            value: factor.value.clamp(0.0, 1.0),
+            decay_rate: factor.decay_rate.clamp(0.0, 1.0),
+            ..factor
+        });
+        if let Some(trait_type) = trait_type {
+            self.cached_traits.write().remove(&trait_type);
+        }
+        Ok(())
+    }
+
     /// Helper: convert string to trait type
     fn trait_type_from_string(&self, s: &str) -> Result<TraitType> {
         match s {
@@ -0,0 +1,787 @@
+// Client Transactions - explicit begin / statements / commit / rollback
+// A client begins a transaction and then runs statements bound to it. Inserts
+// are staged in the registry, validated as they arrive, and only reach storage
+// on commit, in statement order; reads see committed rows followed by the
+// transaction's own staged ones. Rolling back discards the staged rows. Ids and
+// read/write sets are tracked by the core TransactionManager. A transaction
+// that runs no statement for its timeout is rolled back by the reaper.
+// A commit holds the write lock of every table it writes, checks every staged
+// batch before writing any, and cuts the tables already written back to their
+// rows from before the commit if a write fails, so it lands whole or not at all.
+
+use async_trait::async_trait;
+use narayana_core::config::TransactionsConfig;
+use narayana_core::{column::Column, schema::Schema, types::{TableId, TransactionId}, Error, Result, TransactionManager};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+use crate::column_store::{stored_row_count, ColumnStore, TableWriteGuard};
+
+/// An open transaction as clients see it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransactionInfo {
+    pub transaction_id: u64,
+    pub owner: String,
+    pub started_at_ms: u64,
+    /// Time since the last statement
+    pub idle_ms: u64,
+    /// Idle time after which the transaction is rolled back
+    pub timeout_ms: u64,
+    pub statements: u64,
+    pub staged_rows: usize,
+    /// Tables with staged rows, in the order they are written on commit
+    pub tables: Vec<u64>,
+}
+
+/// Rows a commit wrote to one table
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommittedWrite {
+    pub table_id: u64,
+    pub rows: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommitResult {
+    pub transaction_id: u64,
+    pub tables: Vec<CommittedWrite>,
+    pub rows: usize,
+}
+
+/// Counts of open transactions and how transactions ended
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TransactionMetrics {
+    pub active: usize,
+    pub staged_rows: usize,
+    /// Age of the oldest open transaction
+    pub oldest_age_ms: u64,
+    pub begun: u64,
+    pub committed: u64,
+    pub rolled_back: u64,
+    /// Rolled back for being idle past their timeout
+    pub reaped: u64,
+    /// Commits that failed while writing
+    pub failed: u64,
+}
+
+/// Where a commit writes staged rows. The registry holds the write lock of
+/// every table the transaction wrote from before the first write until the
+/// rows are published or rolled back.
+#[async_trait]
+pub trait CommitTarget: Send + Sync {
+    /// The store rows are written to and rolled back in
+    fn store(&self) -> &dyn ColumnStore;
+
+    /// Refuse a table's staged rows before anything is written
+    async fn validate(&self, _table_id: TableId, _schema: &Schema, _columns: &[Column]) -> Result<()> {
+        Ok(())
+    }
+
+    /// Write a table's staged rows with its write lock; returns them as stored
+    async fn write(&self, guard: &TableWriteGuard, columns: Vec<Column>) -> Result<Vec<Column>>;
+
+    /// Every table was written: index or announce one table's rows
+    fn publish(&self, _table_id: TableId, _schema: &Schema, _columns: &[Column]) {}
+
+    /// A failed commit removed the `removed` rows it had written to the table
+    async fn reverted(&self, _table_id: TableId, _removed: &[usize]) {}
+}
+
+/// Commits straight into a store
+pub struct StoreCommit<'a>(pub &'a dyn ColumnStore);
+
+#[async_trait]
+impl CommitTarget for StoreCommit<'_> {
+    fn store(&self) -> &dyn ColumnStore {
+        self.0
+    }
+
+    async fn write(&self, guard: &TableWriteGuard, columns: Vec<Column>) -> Result<Vec<Column>> {
+        self.0.write_locked(guard, columns.clone()).await?;
+        Ok(columns)
+    }
+}
+
+impl TransactionMetrics {
+    /// Prometheus text exposition (`narayana_transactions_*`)
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+        let metrics: [(&str, &str, &str, u64); 8] = [
+            ("active", "gauge", "Open transactions", self.active as u64),
+            ("staged_rows", "gauge", "Rows staged by open transactions", self.staged_rows as u64),
+            ("oldest_age_ms", "gauge", "Age of the oldest open transaction", self.oldest_age_ms),
+            ("begun_total", "counter", "Transactions begun", self.begun),
+            ("committed_total", "counter", "Transactions committed", self.committed),
+            ("rolled_back_total", "counter", "Transactions rolled back by their client", self.rolled_back),
+            ("reaped_total", "counter", "Transactions rolled back for being idle", self.reaped),
+            ("failed_total", "counter", "Commits that failed while writing", self.failed),
+        ];
+        for (name, kind, help, value) in metrics {
+            let _ = writeln!(out, "# HELP narayana_transactions_{} {}", name, help);
+            let _ = writeln!(out, "# TYPE narayana_transactions_{} {}", name, kind);
+            let _ = writeln!(out, "narayana_transactions_{} {}", name, value);
+        }
+        out
+    }
+}
+
+/// Rows staged for one table
+struct StagedWrite {
+    table_id: TableId,
+    columns: Vec<Column>,
+}
+
+struct Session {
+    owner: String,
+    started: Instant,
+    started_at_ms: u64,
+    last_active: Instant,
+    timeout: Duration,
+    statements: u64,
+    staged_rows: usize,
+    writes: Vec<StagedWrite>,
+}
+
+impl Session {
+    fn expired(&self, now: Instant) -> bool {
+        now.duration_since(self.last_active) > self.timeout
+    }
+
+    fn info(&self, id: TransactionId, now: Instant) -> TransactionInfo {
+        TransactionInfo {
+            transaction_id: id.0,
+            owner: self.owner.clone(),
+            started_at_ms: self.started_at_ms,
+            idle_ms: now.duration_since(self.last_active).as_millis() as u64,
+            timeout_ms: self.timeout.as_millis() as u64,
+            statements: self.statements,
+            staged_rows: self.staged_rows,
+            tables: self.writes.iter().map(|write| write.table_id.0).collect(),
+        }
+    }
+}
+
+struct Inner {
+    manager: TransactionManager,
+    sessions: HashMap<TransactionId, Session>,
+}
+
+/// Open client transactions, keyed by id and bound to the client that began them
+pub struct TransactionRegistry {
+    inner: Mutex<Inner>,
+    /// Commits write one at a time, so their rows don't interleave
+    commit_lock: tokio::sync::Mutex<()>,
+    config: TransactionsConfig,
+    begun: AtomicU64,
+    committed: AtomicU64,
+    rolled_back: AtomicU64,
+    reaped: AtomicU64,
+    failed: AtomicU64,
+}
+
+impl TransactionRegistry {
+    pub fn new(config: TransactionsConfig) -> Self {
+        Self {
+            inner: Mutex::new(Inner { manager: TransactionManager::new(), sessions: HashMap::new() }),
+            commit_lock: tokio::sync::Mutex::new(()),
+            config,
+            begun: AtomicU64::new(0),
+            committed: AtomicU64::new(0),
+            rolled_back: AtomicU64::new(0),
+            reaped: AtomicU64::new(0),
+            failed: AtomicU64::new(0),
+        }
+    }
+
+    /// Begin a transaction for `owner`, rolled back after `timeout` without a
+    /// statement (default: `default_timeout`)
+    pub fn begin(&self, owner: &str, timeout: Option<Duration>) -> Result<TransactionInfo> {
+        let timeout = timeout.unwrap_or(self.config.default_timeout);
+        if timeout.is_zero() || timeout > self.config.max_timeout {
+            return Err(Error::Transaction(format!(
+                "Transaction timeout must be above 0 and at most {}s",
+                self.config.max_timeout.as_secs_f64()
+            )));
+        }
+        let mut inner = self.inner.lock();
+        if inner.sessions.len() >= self.config.max_active {
+            return Err(Error::Transaction(format!("Too many open transactions (max {})", self.config.max_active)));
+        }
+        let owned = inner.sessions.values().filter(|session| session.owner == owner).count();
+        if owned >= self.config.max_per_owner {
+            return Err(Error::Transaction(format!(
+                "Too many open transactions for this client (max {})",
+                self.config.max_per_owner
+            )));
+        }
+        let id = inner.manager.begin_transaction();
+        let now = Instant::now();
+        let session = Session {
+            owner: owner.to_string(),
+            started: now,
+            started_at_ms: now_millis(),
+            last_active: now,
+            timeout,
+            statements: 0,
+            staged_rows: 0,
+            writes: Vec::new(),
+        };
+        let info = session.info(id, now);
+        inner.sessions.insert(id, session);
+        self.begun.fetch_add(1, Ordering::Relaxed);
+        Ok(info)
+    }
+
+    /// An open transaction of `owner`
+    pub fn get(&self, id: TransactionId, owner: &str) -> Result<TransactionInfo> {
+        self.with_session(id, owner, false, |_, session, now| Ok(session.info(id, now)))
+    }
+
+    /// Open transactions, of one owner or (None) of everyone
+    pub fn list(&self, owner: Option<&str>) -> Vec<TransactionInfo> {
+        let now = Instant::now();
+        let inner = self.inner.lock();
+        let mut list: Vec<TransactionInfo> = inner.sessions.iter()
+            .filter(|(_, session)| owner.is_none_or(|owner| session.owner == owner))
+            .map(|(id, session)| session.info(*id, now))
+            .collect();
+        list.sort_by_key(|info| info.transaction_id);
+        list
+    }
+
+    /// Stage rows for a table; they are written when the transaction commits.
+    /// Returns the number of rows staged.
+    pub fn stage_insert(&self, id: TransactionId, owner: &str, table_id: TableId, columns: Vec<Column>) -> Result<usize> {
+        let rows = columns.first().map(Column::len).unwrap_or(0);
+        if rows == 0 || columns.iter().any(|column| column.len() != rows) {
+            return Err(Error::Transaction("Staged columns must be non-empty and of equal length".to_string()));
+        }
+        let max_staged_rows = self.config.max_staged_rows;
+        self.with_session(id, owner, true, |manager, session, _| {
+            if session.staged_rows + rows > max_staged_rows {
+                return Err(Error::Transaction(format!(
+                    "Transaction would stage more than {} rows",
+                    max_staged_rows
+                )));
+            }
+            match session.writes.iter_mut().find(|write| write.table_id == table_id) {
+                Some(write) => {
+                    if write.columns.len() != columns.len() {
+                        return Err(Error::Transaction(format!(
+                            "Table {} was staged with {} columns, got {}",
+                            table_id.0, write.columns.len(), columns.len()
+                        )));
+                    }
+                    let appended = write.columns.iter()
+                        .zip(&columns)
+                        .map(|(staged, column)| staged.append(column))
+                        .collect::<Result<Vec<_>>>()?;
+                    write.columns = appended;
+                }
+                None => session.writes.push(StagedWrite { table_id, columns }),
+            }
+            manager.record_write(id, table_id.0)?;
+            session.staged_rows += rows;
+            Ok(rows)
+        })
+    }
+
+    /// Rows the transaction staged for a table, for reading its own writes.
+    /// Counts as a statement.
+    pub fn staged(&self, id: TransactionId, owner: &str, table_id: TableId) -> Result<Option<Vec<Column>>> {
+        self.with_session(id, owner, true, |manager, session, _| {
+            manager.record_read(id, table_id.0)?;
+            Ok(session.writes.iter()
+                .find(|write| write.table_id == table_id)
+                .map(|write| write.columns.clone()))
+        })
+    }
+
+    /// Read up to `limit` rows of a table inside the transaction: its committed
+    /// rows from `storage`, then the rows the transaction staged for it
+    pub async fn read(
+        &self,
+        storage: &dyn ColumnStore,
+        id: TransactionId,
+        owner: &str,
+        table_id: TableId,
+        column_ids: &[u32],
+        limit: usize,
+    ) -> Result<Vec<Column>> {
+        let staged = self.staged(id, owner, table_id)?;
+        let committed = storage.read_columns(table_id, column_ids.to_vec(), 0, limit).await?;
+        // Empty columns are left out of reads
+        let committed_rows = match committed.first() {
+            Some(column) if committed.len() == column_ids.len() => column.len(),
+            _ => 0,
+        };
+        let Some(staged) = staged else {
+            return Ok(if committed_rows == 0 { Vec::new() } else { committed });
+        };
+        let room = limit.saturating_sub(committed_rows);
+        let mut columns = Vec::with_capacity(column_ids.len());
+        for (idx, &column_id) in column_ids.iter().enumerate() {
+            let column = staged.get(column_id as usize)
+                .ok_or_else(|| Error::ColumnNotFound(format!("column {}", column_id)))?;
+            let own = column.slice(0, room.min(column.len()))?;
+            columns.push(match committed_rows {
+                0 => own,
+                _ => committed[idx].append(&own)?,
+            });
+        }
+        Ok(columns)
+    }
+
+    /// Commit: lock every table the transaction wrote, check each table's
+    /// staged rows, then write them through `target` in the order the tables
+    /// were first written and publish them once all are stored. Commits run
+    /// one at a time. If a write fails, the tables already written are cut
+    /// back to their rows from before the commit and the write's error is
+    /// returned; only a failed rollback leaves rows behind, and says where.
+    pub async fn commit(&self, id: TransactionId, owner: &str, target: &dyn CommitTarget) -> Result<CommitResult> {
+        let session = self.take(id, owner)?;
+        let _commit = self.commit_lock.lock().await;
+        match self.apply(id, target, session.writes).await {
+            Ok(tables) => {
+                self.inner.lock().manager.commit_transaction(id)?;
+                self.committed.fetch_add(1, Ordering::Relaxed);
+                let rows = tables.iter().map(|t| t.rows).sum();
+                Ok(CommitResult { transaction_id: id.0, tables, rows })
+            }
+            Err(e) => {
+                let _ = self.inner.lock().manager.abort_transaction(id);
+                self.failed.fetch_add(1, Ordering::Relaxed);
+                warn!("Commit of transaction {} failed: {}", id.0, e);
+                Err(e)
+            }
+        }
+    }
+
+    async fn apply(&self, id: TransactionId, target: &dyn CommitTarget, writes: Vec<StagedWrite>) -> Result<Vec<CommittedWrite>> {
+        let store = target.store();
+        // Tables are locked in id order, so commits never wait on each other
+        // in a cycle
+        let mut table_ids: Vec<TableId> = writes.iter().map(|write| write.table_id).collect();
+        table_ids.sort_by_key(|table_id| table_id.0);
+        let mut guards = HashMap::with_capacity(table_ids.len());
+        for table_id in table_ids {
+            guards.insert(table_id, store.lock_table(table_id).await);
+        }
+
+        let mut schemas = Vec::with_capacity(writes.len());
+        let mut rows_before = Vec::with_capacity(writes.len());
+        for write in &writes {
+            let schema = store.get_schema(write.table_id).await
+                .map_err(|_| Error::Transaction(format!("Table {} no longer exists", write.table_id.0)))?;
+            validate_staged(&schema, write)?;
+            target.validate(write.table_id, &schema, &write.columns).await?;
+            rows_before.push(stored_row_count(store, write.table_id).await?);
+            schemas.push(schema);
+        }
+
+        let mut written: Vec<(TableId, Vec<Column>)> = Vec::with_capacity(writes.len());
+        for write in writes {
+            match target.write(&guards[&write.table_id], write.columns).await {
+                Ok(columns) => written.push((write.table_id, columns)),
+                Err(e) => {
+                    warn!("Commit of transaction {} failed at table {}, rolling back {} tables: {}", id.0, write.table_id.0, written.len(), e);
+                    self.roll_back_writes(id, target, &guards, &written, &rows_before).await?;
+                    return Err(e);
+                }
+            }
+        }
+        for ((table_id, columns), schema) in written.iter().zip(&schemas) {
+            target.publish(*table_id, schema, columns);
+        }
+        Ok(written
+            .iter()
+            .map(|(table_id, columns)| CommittedWrite { table_id: table_id.0, rows: columns.first().map_or(0, |c| c.len()) })
+            .collect())
+    }
+
+    /// Cut each written table back to the rows it had before the commit,
+    /// latest write first
+    async fn roll_back_writes(
+        &self,
+        id: TransactionId,
+        target: &dyn CommitTarget,
+        guards: &HashMap<TableId, TableWriteGuard>,
+        written: &[(TableId, Vec<Column>)],
+        rows_before: &[usize],
+    ) -> Result<()> {
+        let store = target.store();
+        let mut failed = Vec::new();
+        for ((table_id, _), &rows) in written.iter().zip(rows_before).rev() {
+            let result = async {
+                let rows_after = stored_row_count(store, *table_id).await?;
+                let kept = match rows {
+                    0 => Vec::new(),
+                    _ => {
+                        let schema = store.get_schema(*table_id).await?;
+                        store.read_columns(*table_id, (0..schema.fields.len() as u32).collect(), 0, rows).await?
+                    }
+                };
+                store.replace_rows(&guards[table_id], kept).await?;
+                let removed: Vec<usize> = (rows..rows_after).collect();
+                target.reverted(*table_id, &removed).await;
+                Ok::<_, Error>(())
+            }.await;
+            if let Err(e) = result {
+                failed.push(format!("{} ({})", table_id.0, e));
+            }
+        }
+        if failed.is_empty() {
+            return Ok(());
+        }
+        Err(Error::Transaction(format!(
+            "Commit of transaction {} failed and its rows could not be rolled back from tables {}",
+            id.0, failed.join(", ")
+        )))
+    }
+
+    /// Discard the transaction's staged rows
+    pub fn rollback(&self, id: TransactionId, owner: &str) -> Result<TransactionInfo> {
+        let session = self.take(id, owner)?;
+        self.inner.lock().manager.abort_transaction(id)?;
+        self.rolled_back.fetch_add(1, Ordering::Relaxed);
+        Ok(session.info(id, Instant::now()))
+    }
+
+    /// Roll back every transaction idle past its timeout, returning their ids
+    pub fn reap_idle(&self) -> Vec<TransactionId> {
+        let now = Instant::now();
+        let mut inner = self.inner.lock();
+        let expired: Vec<TransactionId> = inner.sessions.iter()
+            .filter(|(_, session)| session.expired(now))
+            .map(|(id, _)| *id)
+            .collect();
+        for id in &expired {
+            Self::expire(&mut inner, *id);
+        }
+        drop(inner);
+        if !expired.is_empty() {
+            self.reaped.fetch_add(expired.len() as u64, Ordering::Relaxed);
+            info!("Rolled back {} idle transactions", expired.len());
+        }
+        expired
+    }
+
+    /// Reap idle transactions every `interval`
+    pub fn start_reaper(self: Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                self.reap_idle();
+            }
+        })
+    }
+
+    pub fn metrics(&self) -> TransactionMetrics {
+        let now = Instant::now();
+        let inner = self.inner.lock();
+        TransactionMetrics {
+            active: inner.sessions.len(),
+            staged_rows: inner.sessions.values().map(|session| session.staged_rows).sum(),
+            oldest_age_ms: inner.sessions.values()
+                .map(|session| now.duration_since(session.started).as_millis() as u64)
+                .max()
+                .unwrap_or(0),
+            begun: self.begun.load(Ordering::Relaxed),
+            committed: self.committed.load(Ordering::Relaxed),
+            rolled_back: self.rolled_back.load(Ordering::Relaxed),
+            reaped: self.reaped.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Run `f` on an open transaction of `owner`; `statement` marks it active.
+    /// A transaction past its timeout is rolled back here rather than used.
+    fn with_session<T>(
+        &self,
+        id: TransactionId,
+        owner: &str,
+        statement: bool,
+        f: impl FnOnce(&mut TransactionManager, &mut Session, Instant) -> Result<T>,
+    ) -> Result<T> {
+        let now = Instant::now();
+        let mut guard = self.inner.lock();
+        let inner = &mut *guard;
+        let session = match inner.sessions.get_mut(&id) {
+            Some(session) if session.owner == owner => session,
+            _ => return Err(not_found(id)),
+        };
+        if session.expired(now) {
+            Self::expire(inner, id);
+            drop(guard);
+            self.reaped.fetch_add(1, Ordering::Relaxed);
+            return Err(Error::Transaction(format!("Transaction {} timed out and was rolled back", id.0)));
+        }
+        let result = f(&mut inner.manager, session, now);
+        if statement {
+            session.last_active = now;
+            session.statements += 1;
+        }
+        result
+    }
+
+    /// Remove an open transaction of `owner` to finish it
+    fn take(&self, id: TransactionId, owner: &str) -> Result<Session> {
+        self.with_session(id, owner, false, |_, _, _| Ok(()))?;
+        self.inner.lock().sessions.remove(&id).ok_or_else(|| not_found(id))
+    }
+
+    fn expire(inner: &mut Inner, id: TransactionId) {
+        inner.sessions.remove(&id);
+        let _ = inner.manager.abort_transaction(id);
+    }
+}
+
+/// Staged rows still fit their table: the schema can change between staging
+/// and commit
+fn validate_staged(schema: &Schema, write: &StagedWrite) -> Result<()> {
+    let table_id = write.table_id.0;
+    if !schema.generated_field_indexes().is_empty() {
+        return Err(Error::Transaction(format!("Table {} has generated embedding columns", table_id)));
+    }
+    if write.columns.len() != schema.fields.len() {
+        return Err(Error::Transaction(format!(
+            "Table {} has {} columns, {} were staged", table_id, schema.fields.len(), write.columns.len()
+        )));
+    }
+    Ok(())
+}
+
+fn not_found(id: TransactionId) -> Error {
+    Error::Transaction(format!("Transaction {} not found", id.0))
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry() -> TransactionRegistry {
+        TransactionRegistry::new(TransactionsConfig { max_per_owner: 2, ..Default::default() })
+    }
+
+    /// Tables 1 (strings, empty) and 2 (integers, holding one row)
+    async fn store() -> crate::column_store::InMemoryColumnStore {
+        use narayana_core::schema::{DataType, Field};
+
+        let store = crate::column_store::InMemoryColumnStore::new();
+        let schema = |data_type| Schema::new(vec![Field { name: "value".to_string(), data_type, nullable: false, default_value: None }]);
+        store.create_table(TableId(1), schema(DataType::String)).await.unwrap();
+        store.create_table(TableId(2), schema(DataType::Int64)).await.unwrap();
+        store.write_columns(TableId(2), vec![Column::Int64(vec![0])]).await.unwrap();
+        store
+    }
+
+    async fn int_values(store: &dyn ColumnStore, table_id: TableId) -> Vec<i64> {
+        match store.read_columns(table_id, vec![0], 0, usize::MAX).await.unwrap().first() {
+            Some(Column::Int64(values)) => values.clone(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Writes into a store, recording what it is asked to do; writes to
+    /// `fail_table` fail
+    struct Recording<'a> {
+        store: StoreCommit<'a>,
+        fail_table: Option<u64>,
+        written: Mutex<Vec<u64>>,
+        published: Mutex<Vec<u64>>,
+        reverted: Mutex<Vec<(u64, Vec<usize>)>>,
+    }
+
+    impl<'a> Recording<'a> {
+        fn new(store: &'a dyn ColumnStore, fail_table: Option<u64>) -> Self {
+            Self {
+                store: StoreCommit(store),
+                fail_table,
+                written: Mutex::new(Vec::new()),
+                published: Mutex::new(Vec::new()),
+                reverted: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl CommitTarget for Recording<'_> {
+        fn store(&self) -> &dyn ColumnStore {
+            self.store.store()
+        }
+
+        async fn write(&self, guard: &TableWriteGuard, columns: Vec<Column>) -> Result<Vec<Column>> {
+            if self.fail_table == Some(guard.table_id().0) {
+                return Err(Error::Storage("disk full".to_string()));
+            }
+            self.written.lock().push(guard.table_id().0);
+            self.store.write(guard, columns).await
+        }
+
+        fn publish(&self, table_id: TableId, _schema: &Schema, _columns: &[Column]) {
+            self.published.lock().push(table_id.0);
+        }
+
+        async fn reverted(&self, table_id: TableId, removed: &[usize]) {
+            self.reverted.lock().push((table_id.0, removed.to_vec()));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_commit_writes_staged_rows_in_order() {
+        let store = store().await;
+        let registry = registry();
+        let txn = registry.begin("alice", None).unwrap();
+        let id = TransactionId(txn.transaction_id);
+
+        registry.stage_insert(id, "alice", TableId(2), vec![Column::Int64(vec![1, 2])]).unwrap();
+        registry.stage_insert(id, "alice", TableId(1), vec![Column::String(vec!["a".to_string()])]).unwrap();
+        registry.stage_insert(id, "alice", TableId(2), vec![Column::Int64(vec![3])]).unwrap();
+        assert!(registry.stage_insert(id, "alice", TableId(2), vec![Column::Float64(vec![1.0])]).is_err());
+        assert!(registry.stage_insert(id, "bob", TableId(2), vec![Column::Int64(vec![4])]).is_err());
+
+        let staged = registry.staged(id, "alice", TableId(2)).unwrap().unwrap();
+        assert!(matches!(&staged[0], Column::Int64(values) if values == &vec![1, 2, 3]));
+        let info = registry.get(id, "alice").unwrap();
+        assert_eq!(info.staged_rows, 4);
+        assert_eq!(info.tables, vec![2, 1]);
+
+        let target = Recording::new(&store, None);
+        let result = registry.commit(id, "alice", &target).await.unwrap();
+        assert_eq!(*target.written.lock(), vec![2, 1]);
+        assert_eq!(*target.published.lock(), vec![2, 1]);
+        assert_eq!(result.rows, 4);
+        assert_eq!(int_values(&store, TableId(2)).await, vec![0, 1, 2, 3]);
+        assert!(registry.get(id, "alice").is_err());
+
+        let metrics = registry.metrics();
+        assert_eq!((metrics.active, metrics.begun, metrics.committed), (0, 1, 1));
+    }
+
+    #[tokio::test]
+    async fn test_failed_commit_rolls_back_written_tables() {
+        let store = store().await;
+        let registry = registry();
+        let id = TransactionId(registry.begin("alice", None).unwrap().transaction_id);
+        registry.stage_insert(id, "alice", TableId(2), vec![Column::Int64(vec![1, 2])]).unwrap();
+        registry.stage_insert(id, "alice", TableId(1), vec![Column::String(vec!["a".to_string()])]).unwrap();
+
+        // Table 2 is written, then table 1 fails: table 2 loses the new rows
+        let target = Recording::new(&store, Some(1));
+        let err = registry.commit(id, "alice", &target).await.unwrap_err();
+        assert!(matches!(err, Error::Storage(_)));
+        assert_eq!(*target.written.lock(), vec![2]);
+        assert!(target.published.lock().is_empty());
+        assert_eq!(*target.reverted.lock(), vec![(2, vec![1, 2])]);
+        assert_eq!(int_values(&store, TableId(2)).await, vec![0]);
+        assert_eq!(registry.metrics().failed, 1);
+        assert!(registry.get(id, "alice").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_commit_checks_every_table_before_writing() {
+        let store = store().await;
+        let registry = registry();
+        let id = TransactionId(registry.begin("alice", None).unwrap().transaction_id);
+        registry.stage_insert(id, "alice", TableId(2), vec![Column::Int64(vec![1])]).unwrap();
+        // Table 1 has one column
+        registry.stage_insert(id, "alice", TableId(1), vec![Column::String(vec!["a".to_string()]), Column::Int64(vec![1])]).unwrap();
+
+        let target = Recording::new(&store, None);
+        let err = registry.commit(id, "alice", &target).await.unwrap_err();
+        assert!(err.to_string().contains("Table 1 has 1 columns"));
+        assert!(target.written.lock().is_empty());
+        assert_eq!(int_values(&store, TableId(2)).await, vec![0]);
+
+        // Nor is anything written when a table is gone
+        let id = TransactionId(registry.begin("alice", None).unwrap().transaction_id);
+        registry.stage_insert(id, "alice", TableId(2), vec![Column::Int64(vec![1])]).unwrap();
+        registry.stage_insert(id, "alice", TableId(9), vec![Column::Int64(vec![1])]).unwrap();
+        let err = registry.commit(id, "alice", &target).await.unwrap_err();
+        assert!(err.to_string().contains("Table 9 no longer exists"));
+        assert!(target.written.lock().is_empty());
+        assert_eq!(registry.metrics().failed, 2);
+    }
+
+    #[tokio::test]
+    async fn test_read_sees_own_staged_rows() {
+        use crate::column_store::InMemoryColumnStore;
+        use narayana_core::schema::{DataType, Field, Schema};
+
+        let storage = InMemoryColumnStore::new();
+        let field = |name: &str, data_type| Field {
+            name: name.to_string(),
+            data_type,
+            nullable: false,
+            default_value: None,
+        };
+        let schema = Schema::new(vec![field("id", DataType::Int64), field("name", DataType::String)]);
+        storage.create_table(TableId(1), schema).await.unwrap();
+        storage.write_columns(TableId(1), vec![Column::Int64(vec![1]), Column::String(vec!["a".to_string()])]).await.unwrap();
+
+        let registry = registry();
+        let id = TransactionId(registry.begin("alice", None).unwrap().transaction_id);
+        let committed = registry.read(&storage, id, "alice", TableId(1), &[1], 10).await.unwrap();
+        assert!(matches!(&committed[0], Column::String(values) if values.len() == 1));
+
+        let staged = vec![Column::Int64(vec![2, 3]), Column::String(vec!["b".to_string(), "c".to_string()])];
+        registry.stage_insert(id, "alice", TableId(1), staged).unwrap();
+        let columns = registry.read(&storage, id, "alice", TableId(1), &[1, 0], 2).await.unwrap();
+        assert!(matches!(&columns[0], Column::String(values) if values == &vec!["a".to_string(), "b".to_string()]));
+        assert!(matches!(&columns[1], Column::Int64(values) if values == &vec![1, 2]));
+        assert_eq!(registry.get(id, "alice").unwrap().statements, 3);
+    }
+
+    #[test]
+    fn test_rollback_and_limits() {
+        let registry = registry();
+        let first = TransactionId(registry.begin("alice", None).unwrap().transaction_id);
+        registry.begin("alice", None).unwrap();
+        assert!(registry.begin("alice", None).is_err());
+        assert!(registry.begin("bob", Some(Duration::from_secs(3600))).is_err());
+        assert_eq!(registry.list(Some("alice")).len(), 2);
+        assert!(registry.list(Some("bob")).is_empty());
+
+        registry.stage_insert(first, "alice", TableId(1), vec![Column::Int32(vec![1])]).unwrap();
+        assert!(registry.rollback(first, "bob").is_err());
+        let rolled_back = registry.rollback(first, "alice").unwrap();
+        assert_eq!(rolled_back.staged_rows, 1);
+        assert!(registry.rollback(first, "alice").is_err());
+        assert_eq!(registry.metrics().rolled_back, 1);
+        registry.begin("alice", None).unwrap();
+    }
+
+    #[test]
+    fn test_idle_transactions_are_reaped() {
+        let registry = registry();
+        let idle = TransactionId(registry.begin("alice", Some(Duration::from_millis(20))).unwrap().transaction_id);
+        let busy = TransactionId(registry.begin("bob", Some(Duration::from_millis(20))).unwrap().transaction_id);
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(registry.stage_insert(busy, "bob", TableId(1), vec![Column::Int32(vec![1])]).is_err());
+
+        let other = TransactionId(registry.begin("carol", None).unwrap().transaction_id);
+        let idle2 = TransactionId(registry.begin("dave", Some(Duration::from_millis(1))).unwrap().transaction_id);
+        std::thread::sleep(Duration::from_millis(5));
+        let mut reaped = registry.reap_idle();
+        reaped.sort_by_key(|id| id.0);
+        assert_eq!(reaped, vec![idle, idle2]);
+        assert!(registry.get(other, "carol").is_ok());
+
+        let metrics = registry.metrics();
+        assert_eq!((metrics.active, metrics.reaped), (1, 3));
+        assert!(metrics.to_prometheus().contains("narayana_transactions_reaped_total 3"));
+    }
+}
@@ -0,0 +1,486 @@
+// Row-level TTL
+// A table's schema can name a TTL column (Timestamp, Int64 or UInt64) holding
+// each row's expiry time in milliseconds since the epoch; 0 or less never
+// expires. Reads served to callers go through a `TtlColumnStore` (row
+// security wraps every caller's store in one), which leaves out rows whose
+// expiry has passed from the moment it passes. `TtlCompactor` removes them
+// from storage when compaction runs: holding the table's write lock, it
+// replaces the table's rows with the live ones and the rows behind the
+// expired ones move up, as with an erasure, so generated embeddings and
+// spatial indexes are renumbered and version logs and full-text indexes
+// follow before any other write gets in.
+// `row_start` and `row_count` address the table's rows before filtering.
+
+use async_trait::async_trait;
+use narayana_core::{column::Column, schema::Schema, types::TableId, Error, Result};
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+use crate::block::{BlockMetadata, BlockPredicate};
+use crate::column_store::{ColumnStore, TableWriteGuard};
+use crate::database_manager::DatabaseManager;
+use crate::embeddings::EmbeddingsManager;
+use crate::full_text::FullTextIndexManager;
+use crate::geospatial::SpatialIndexManager;
+use crate::row_security::take_rows;
+use crate::vector_search::VectorStore;
+
+/// Whether a TTL column value has passed
+pub fn is_expired(expires_at_ms: i64, now_ms: i64) -> bool {
+    expires_at_ms > 0 && expires_at_ms <= now_ms
+}
+
+/// Rows of a TTL column that have expired, in order
+pub fn expired_rows(column: &Column, now_ms: i64) -> Vec<usize> {
+    rows_where(column, |expired| expired, now_ms)
+}
+
+fn live_rows(column: &Column, now_ms: i64) -> Vec<usize> {
+    rows_where(column, |expired| !expired, now_ms)
+}
+
+fn rows_where(column: &Column, keep: impl Fn(bool) -> bool, now_ms: i64) -> Vec<usize> {
+    match column {
+        Column::Timestamp(v) | Column::Int64(v) => (0..v.len()).filter(|&row| keep(is_expired(v[row], now_ms))).collect(),
+        Column::UInt64(v) => (0..v.len())
+            .filter(|&row| keep(is_expired(i64::try_from(v[row]).unwrap_or(i64::MAX), now_ms)))
+            .collect(),
+        // Not a TTL column type: nothing expires
+        other => (0..other.len()).filter(|_| keep(false)).collect(),
+    }
+}
+
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+/// A column store that hides expired rows of tables with a TTL column
+pub struct TtlColumnStore {
+    inner: Arc<dyn ColumnStore>,
+}
+
+impl TtlColumnStore {
+    pub fn new(inner: Arc<dyn ColumnStore>) -> Self {
+        Self { inner }
+    }
+}
+
+#[async_trait]
+impl ColumnStore for TtlColumnStore {
+    async fn create_table(&self, table_id: TableId, schema: Schema) -> Result<()> {
+        self.inner.create_table(table_id, schema).await
+    }
+
+    async fn write_columns(&self, table_id: TableId, columns: Vec<Column>) -> Result<()> {
+        self.inner.write_columns(table_id, columns).await
+    }
+
+    async fn read_columns(
+        &self,
+        table_id: TableId,
+        column_ids: Vec<u32>,
+        row_start: usize,
+        row_count: usize,
+    ) -> Result<Vec<Column>> {
+        let schema = self.inner.get_schema(table_id).await?;
+        let Some(ttl_id) = schema.ttl_field_index().map(|idx| idx as u32) else {
+            return self.inner.read_columns(table_id, column_ids, row_start, row_count).await;
+        };
+        let requested = column_ids.len();
+        let mut read_ids = column_ids;
+        let position = match read_ids.iter().position(|&id| id == ttl_id) {
+            Some(position) => position,
+            None => {
+                read_ids.push(ttl_id);
+                read_ids.len() - 1
+            }
+        };
+        let mut columns = self.inner.read_columns(table_id, read_ids.clone(), row_start, row_count).await?;
+        // Stores skip columns that hold no data
+        if columns.len() != read_ids.len() {
+            if columns.iter().all(|c| c.len() == 0) {
+                columns.truncate(requested);
+                return Ok(columns);
+            }
+            return Err(Error::Storage(format!(
+                "Read returned {} of {} columns for table {}",
+                columns.len(), read_ids.len(), table_id.0
+            )));
+        }
+
+        let live = live_rows(&columns[position], now_millis());
+        if live.len() != columns[position].len() {
+            columns = columns.iter().map(|column| take_rows(column, &live)).collect();
+        }
+        columns.truncate(requested);
+        Ok(columns)
+    }
+
+    async fn get_schema(&self, table_id: TableId) -> Result<Schema> {
+        self.inner.get_schema(table_id).await
+    }
+
+    async fn get_block_metadata(&self, table_id: TableId, column_id: u32) -> Result<Vec<BlockMetadata>> {
+        self.inner.get_block_metadata(table_id, column_id).await
+    }
+
+    /// Zone maps still describe the expired rows, so scans of tables with a
+    /// TTL column aren't pruned
+    async fn matching_row_ranges(
+        &self,
+        table_id: TableId,
+        column_id: u32,
+        predicate: &BlockPredicate,
+    ) -> Result<Option<Vec<Range<usize>>>> {
+        if self.inner.get_schema(table_id).await?.ttl_field_index().is_some() {
+            return Ok(None);
+        }
+        self.inner.matching_row_ranges(table_id, column_id, predicate).await
+    }
+
+    async fn delete_table(&self, table_id: TableId) -> Result<()> {
+        self.inner.delete_table(table_id).await
+    }
+
+    async fn lock_table(&self, table_id: TableId) -> TableWriteGuard {
+        self.inner.lock_table(table_id).await
+    }
+
+    async fn write_locked(&self, guard: &TableWriteGuard, columns: Vec<Column>) -> Result<()> {
+        self.inner.write_locked(guard, columns).await
+    }
+
+    async fn replace_rows(&self, guard: &TableWriteGuard, columns: Vec<Column>) -> Result<()> {
+        self.inner.replace_rows(guard, columns).await
+    }
+}
+
+/// Outcome of a compaction pass
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TtlCompaction {
+    /// Tables with a TTL column that were checked
+    pub tables: usize,
+    pub rows_removed: usize,
+}
+
+/// Removes expired rows from storage
+pub struct TtlCompactor {
+    /// Must see every stored row, so never a caller's secured store
+    store: Arc<dyn ColumnStore>,
+    db_manager: Arc<DatabaseManager>,
+    full_text: Option<Arc<FullTextIndexManager>>,
+    spatial: Option<Arc<SpatialIndexManager>>,
+    embeddings: Option<(Arc<EmbeddingsManager>, Arc<VectorStore>)>,
+}
+
+impl TtlCompactor {
+    pub fn new(store: Arc<dyn ColumnStore>, db_manager: Arc<DatabaseManager>) -> Self {
+        Self { store, db_manager, full_text: None, spatial: None, embeddings: None }
+    }
+
+    /// Reindex a table's full-text indexes after removing rows
+    pub fn with_full_text(mut self, full_text: Arc<FullTextIndexManager>) -> Self {
+        self.full_text = Some(full_text);
+        self
+    }
+
+    /// Renumber the rows of a table's spatial indexes after removing rows
+    pub fn with_spatial(mut self, spatial: Arc<SpatialIndexManager>) -> Self {
+        self.spatial = Some(spatial);
+        self
+    }
+
+    /// Renumber the embeddings generated from a table's `embed` columns
+    pub fn with_embeddings(mut self, embeddings: Arc<EmbeddingsManager>, vectors: Arc<VectorStore>) -> Self {
+        self.embeddings = Some((embeddings, vectors));
+        self
+    }
+
+    /// Remove expired rows from every table with a TTL column
+    pub async fn compact(&self) -> TtlCompaction {
+        let mut compaction = TtlCompaction::default();
+        let mut tables: Vec<TableId> = self.db_manager.list_databases()
+            .into_iter()
+            .filter_map(|db| self.db_manager.list_tables(db.id).ok())
+            .flatten()
+            .filter(|table| table.schema.ttl_column.is_some())
+            .map(|table| table.table_id)
+            .collect();
+        tables.sort_by_key(|id| id.0);
+        for table_id in tables {
+            compaction.tables += 1;
+            match self.compact_table(table_id, now_millis()).await {
+                Ok(rows) => compaction.rows_removed += rows,
+                Err(e) => warn!("Failed to remove expired rows from table {}: {}", table_id.0, e),
+            }
+        }
+        compaction
+    }
+
+    /// Remove the rows of one table that expired by `now_ms`; returns how many
+    pub async fn compact_table(&self, table_id: TableId, now_ms: i64) -> Result<usize> {
+        let guard = self.store.lock_table(table_id).await;
+        let schema = self.store.get_schema(table_id).await?;
+        let Some(ttl_idx) = schema.ttl_field_index() else {
+            return Ok(0);
+        };
+        let column_ids: Vec<u32> = (0..schema.fields.len() as u32).collect();
+        let columns = self.store.read_columns(table_id, column_ids, 0, usize::MAX).await?;
+        if columns.len() != schema.fields.len() {
+            // Stores skip columns that hold no data
+            if columns.iter().all(|c| c.len() == 0) {
+                return Ok(0);
+            }
+            return Err(Error::Storage(format!("Read returned {} of {} columns", columns.len(), schema.fields.len())));
+        }
+        let removed = expired_rows(&columns[ttl_idx], now_ms);
+        if removed.is_empty() {
+            return Ok(0);
+        }
+
+        let kept: Vec<usize> = (0..columns[ttl_idx].len()).filter(|row| removed.binary_search(row).is_err()).collect();
+        let rewritten: Vec<Column> = columns.iter().map(|column| take_rows(column, &kept)).collect();
+        self.store.replace_rows(&guard, rewritten.clone()).await?;
+
+        // Embedding and spatial ids are row offsets: drop the removed rows'
+        // and move the rest up to their new offsets
+        let remap = |row: u64| match removed.binary_search(&(row as usize)) {
+            Ok(_) => None,
+            Err(before) => Some(row - before as u64),
+        };
+        if let Some(spatial) = &self.spatial {
+            spatial.retain(table_id, kept.len() as u64, remap);
+        }
+        if let Some((embeddings, vectors)) = &self.embeddings {
+            for annotation in &schema.embeddings {
+                let name = EmbeddingsManager::index_name(table_id, annotation);
+                if !vectors.has_index(&name) {
+                    continue;
+                }
+                vectors.retain(&name, |embedding| remap(embedding.id))?;
+            }
+            embeddings.rows_removed(table_id, &removed);
+        }
+        self.db_manager.temporal().rows_removed(table_id, &removed).await;
+        if let Some(full_text) = &self.full_text {
+            full_text.reindex_table(table_id, &schema, &rewritten)?;
+        }
+        info!("Removed {} expired rows from table {}", removed.len(), table_id.0);
+        Ok(removed.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::column_store::InMemoryColumnStore;
+    use narayana_core::schema::{DataType, Field};
+
+    fn schema() -> Schema {
+        let field = |name: &str, data_type: DataType| Field {
+            name: name.to_string(),
+            data_type,
+            nullable: false,
+            default_value: None,
+        };
+        Schema::new(vec![field("id", DataType::Int64), field("expires_at", DataType::Timestamp)])
+            .with_ttl_column("expires_at")
+    }
+
+    fn ids(columns: &[Column]) -> Vec<i64> {
+        match &columns[0] {
+            Column::Int64(v) => v.clone(),
+            other => panic!("unexpected column {:?}", other),
+        }
+    }
+
+    /// Rows 1 and 3 expired a minute ago, row 2 never expires, row 4 expires in an hour
+    async fn store_with_rows() -> Arc<InMemoryColumnStore> {
+        let store = Arc::new(InMemoryColumnStore::new());
+        let now = now_millis();
+        store.create_table(TableId(1), schema()).await.unwrap();
+        store.write_columns(TableId(1), vec![
+            Column::Int64(vec![1, 2, 3, 4]),
+            Column::Timestamp(vec![now - 60_000, 0, now - 60_000, now + 3_600_000]),
+        ]).await.unwrap();
+        store
+    }
+
+    #[test]
+    fn test_expired_rows() {
+        assert_eq!(expired_rows(&Column::Timestamp(vec![0, -5, 100, 200, 300]), 200), vec![2, 3]);
+        assert_eq!(expired_rows(&Column::UInt64(vec![u64::MAX, 50]), 100), vec![1]);
+        assert!(expired_rows(&Column::String(vec!["x".to_string()]), 100).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reads_leave_out_expired_rows() {
+        let store = store_with_rows().await;
+        let ttl = TtlColumnStore::new(store.clone());
+
+        // The TTL column is read alongside even when not asked for
+        let columns = ttl.read_columns(TableId(1), vec![0], 0, 10).await.unwrap();
+        assert_eq!(columns.len(), 1);
+        assert_eq!(ids(&columns), vec![2, 4]);
+        // Row ranges address the stored rows
+        assert_eq!(ids(&ttl.read_columns(TableId(1), vec![0, 1], 2, 2).await.unwrap()), vec![4]);
+        // Underneath, every row is still stored
+        assert_eq!(ids(&store.read_columns(TableId(1), vec![0], 0, 10).await.unwrap()), vec![1, 2, 3, 4]);
+    }
+
+    #[tokio::test]
+    async fn test_scans_over_expired_rows_are_not_pruned() {
+        use crate::block::ZoneValue;
+        use crate::persistent_column_store::PersistentColumnStore;
+        use narayana_core::types::CompressionType;
+
+        let dir = std::env::temp_dir().join(format!("narayana_ttl_zones_{}", uuid::Uuid::new_v4()));
+        let store = Arc::new(PersistentColumnStore::new(&dir, CompressionType::None).unwrap());
+        let now = now_millis();
+        let mut schema = schema();
+        schema.fields[1].data_type = DataType::Int64;
+        store.create_table(TableId(1), schema).await.unwrap();
+        store.write_columns(TableId(1), vec![
+            Column::Int64(vec![1, 2]),
+            Column::Int64(vec![now - 60_000, 0]),
+        ]).await.unwrap();
+
+        // The block's zone map still admits the expired row's id
+        let predicate = BlockPredicate::Eq(ZoneValue::Int(1));
+        assert_eq!(store.matching_row_ranges(TableId(1), 0, &predicate).await.unwrap(), Some(vec![0..2]));
+        let ttl = TtlColumnStore::new(store.clone());
+        assert_eq!(ttl.matching_row_ranges(TableId(1), 0, &predicate).await.unwrap(), None);
+        assert_eq!(ids(&ttl.read_columns(TableId(1), vec![0], 0, 10).await.unwrap()), vec![2]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_compaction_removes_expired_rows() {
+        let store = store_with_rows().await;
+        let db_manager = Arc::new(DatabaseManager::new());
+        let db_id = db_manager.create_database("default".to_string()).unwrap();
+        db_manager.create_table(db_id, "sessions".to_string(), schema()).unwrap();
+        let compactor = TtlCompactor::new(store.clone(), db_manager);
+
+        assert_eq!(compactor.compact_table(TableId(1), now_millis()).await.unwrap(), 2);
+        assert_eq!(ids(&store.read_columns(TableId(1), vec![0], 0, 10).await.unwrap()), vec![2, 4]);
+        assert_eq!(compactor.compact_table(TableId(1), now_millis()).await.unwrap(), 0);
+        // Once row 4's hour is up it goes too
+        assert_eq!(compactor.compact_table(TableId(1), now_millis() + 7_200_000).await.unwrap(), 1);
+        assert_eq!(ids(&store.read_columns(TableId(1), vec![0], 0, 10).await.unwrap()), vec![2]);
+    }
+
+    #[tokio::test]
+    async fn test_compaction_renumbers_spatial_index() {
+        use crate::geospatial::{Geometry, Point, SpatialPredicate};
+
+        let now = now_millis();
+        let mut fields = schema().fields;
+        fields.push(Field { name: "place".to_string(), data_type: DataType::Point, nullable: false, default_value: None });
+        let schema = Schema::new(fields).with_ttl_column("expires_at");
+        let places: Vec<Vec<u8>> = (0..3).map(|i| Geometry::Point(Point::new(i as f64, 0.0).unwrap()).encode()).collect();
+        let store = Arc::new(InMemoryColumnStore::new());
+        store.create_table(TableId(1), schema.clone()).await.unwrap();
+        store.write_columns(TableId(1), vec![
+            Column::Int64(vec![1, 2, 3]),
+            Column::Timestamp(vec![0, now - 60_000, 0]),
+            Column::Binary(places.clone()),
+        ]).await.unwrap();
+        let spatial = Arc::new(SpatialIndexManager::new());
+        spatial.create_index(TableId(1), &schema, "place", &places).unwrap();
+
+        let compactor = TtlCompactor::new(store.clone(), Arc::new(DatabaseManager::new())).with_spatial(spatial.clone());
+        assert_eq!(compactor.compact_table(TableId(1), now).await.unwrap(), 1);
+        // Row 2 moved up to row 1, and its place with it
+        let near = SpatialPredicate::Nearest { lon: 2.0, lat: 0.0, k: 10 };
+        let hits = spatial.query(TableId(1), "place", &near, 10).unwrap();
+        assert_eq!(hits.iter().map(|h| h.doc_id).collect::<Vec<_>>(), vec![1, 0]);
+        assert!(hits[0].distance_meters < 1.0);
+    }
+
+    /// Pauses every full-table read until released
+    struct PausingStore {
+        inner: Arc<InMemoryColumnStore>,
+        reading: tokio::sync::Notify,
+        resume: tokio::sync::Notify,
+    }
+
+    #[async_trait]
+    impl ColumnStore for PausingStore {
+        async fn create_table(&self, table_id: TableId, schema: Schema) -> Result<()> {
+            self.inner.create_table(table_id, schema).await
+        }
+
+        async fn write_columns(&self, table_id: TableId, columns: Vec<Column>) -> Result<()> {
+            self.inner.write_columns(table_id, columns).await
+        }
+
+        async fn read_columns(&self, table_id: TableId, column_ids: Vec<u32>, row_start: usize, row_count: usize) -> Result<Vec<Column>> {
+            let columns = self.inner.read_columns(table_id, column_ids, row_start, row_count).await?;
+            if row_count == usize::MAX {
+                self.reading.notify_one();
+                self.resume.notified().await;
+            }
+            Ok(columns)
+        }
+
+        async fn get_schema(&self, table_id: TableId) -> Result<Schema> {
+            self.inner.get_schema(table_id).await
+        }
+
+        async fn get_block_metadata(&self, table_id: TableId, column_id: u32) -> Result<Vec<BlockMetadata>> {
+            self.inner.get_block_metadata(table_id, column_id).await
+        }
+
+        async fn delete_table(&self, table_id: TableId) -> Result<()> {
+            self.inner.delete_table(table_id).await
+        }
+
+        async fn lock_table(&self, table_id: TableId) -> TableWriteGuard {
+            self.inner.lock_table(table_id).await
+        }
+
+        async fn write_locked(&self, guard: &TableWriteGuard, columns: Vec<Column>) -> Result<()> {
+            self.inner.write_locked(guard, columns).await
+        }
+
+        async fn replace_rows(&self, guard: &TableWriteGuard, columns: Vec<Column>) -> Result<()> {
+            self.inner.replace_rows(guard, columns).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_insert_during_compaction_survives() {
+        let inner = store_with_rows().await;
+        let store = Arc::new(PausingStore {
+            inner: inner.clone(),
+            reading: tokio::sync::Notify::new(),
+            resume: tokio::sync::Notify::new(),
+        });
+        let compactor = Arc::new(TtlCompactor::new(store.clone(), Arc::new(DatabaseManager::new())));
+
+        let compaction = tokio::spawn({
+            let compactor = compactor.clone();
+            async move { compactor.compact_table(TableId(1), now_millis()).await }
+        });
+        // The compaction has read the table; an insert now waits for it
+        store.reading.notified().await;
+        let insert = tokio::spawn({
+            let store = store.clone();
+            async move { store.write_columns(TableId(1), vec![Column::Int64(vec![5]), Column::Timestamp(vec![0])]).await }
+        });
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert!(!insert.is_finished());
+
+        store.resume.notify_one();
+        assert_eq!(compaction.await.unwrap().unwrap(), 2);
+        insert.await.unwrap().unwrap();
+        assert_eq!(ids(&inner.read_columns(TableId(1), vec![0], 0, 10).await.unwrap()), vec![2, 4, 5]);
+    }
+}
@@ -108,6 +108,45 @@ impl VectorIndex {
         Ok(())
     }
 
+    /// Number of embeddings in the index
+    pub fn len(&self) -> usize {
+        self.embeddings.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.embeddings.read().is_empty()
+    }
+
+    /// Keep the embeddings `keep` gives an id (their own or a new one) and
+    /// drop the rest; returns how many were dropped. The HNSW graph is
+    /// rebuilt so nothing of a dropped vector is left in it.
+    pub fn retain(&mut self, mut keep: impl FnMut(&Embedding) -> Option<u64>) -> Result<usize> {
+        let embeddings = std::mem::take(&mut *self.embeddings.write());
+        let before = embeddings.len();
+        let mut kept = HashMap::with_capacity(before);
+        let mut renumbered = false;
+        for (_, mut embedding) in embeddings {
+            if let Some(id) = keep(&embedding) {
+                renumbered |= id != embedding.id;
+                embedding.id = id;
+                kept.insert(id, embedding);
+            }
+        }
+        let dropped = before - kept.len();
+
+        if let IndexType::HNSW { m, ef_construction } = self.index_type {
+            if dropped > 0 || renumbered {
+                let hnsw = HNSWIndex::new(m, ef_construction, self.dimension);
+                for embedding in kept.values() {
+                    hnsw.insert(embedding.id, embedding.vector.clone())?;
+                }
+                self.hnsw_index = Some(Arc::new(hnsw));
+            }
+        }
+        *self.embeddings.write() = kept;
+        Ok(dropped)
+    }
+
     /// Search for similar embeddings (GPU-accelerated if enabled)
     pub fn search(&self, query_vector: &[f32], k: usize) -> Result<Vec<SearchResult>> {
         if query_vector.len() != self.dimension {
@@ -254,6 +293,11 @@ impl VectorStore {
         Ok(())
     }
 
+    /// Check whether an index exists
+    pub fn has_index(&self, name: &str) -> bool {
+        self.indexes.read().contains_key(name)
+    }
+
     /// Add embedding to index
     pub fn add_embedding(&self, index_name: &str, embedding: Embedding) -> Result<()> {
         let indexes = self.indexes.read();
@@ -264,6 +308,40 @@ impl VectorStore {
         }
     }
 
+    /// Ids of the embeddings `select` accepts
+    pub fn embedding_ids(&self, index_name: &str, select: impl Fn(&Embedding) -> bool) -> Result<Vec<u64>> {
+        let indexes = self.indexes.read();
+        let index = indexes.get(index_name)
+            .ok_or_else(|| Error::Storage(format!("Index '{}' not found", index_name)))?;
+        let mut ids: Vec<u64> = index.embeddings.read().values().filter(|e| select(e)).map(|e| e.id).collect();
+        ids.sort_unstable();
+        Ok(ids)
+    }
+
+    /// Drop the embeddings `remove` selects; returns their ids
+    pub fn remove_embeddings(&self, index_name: &str, remove: impl Fn(&Embedding) -> bool) -> Result<Vec<u64>> {
+        let mut removed = Vec::new();
+        self.retain(index_name, |embedding| {
+            if remove(embedding) {
+                removed.push(embedding.id);
+                None
+            } else {
+                Some(embedding.id)
+            }
+        })?;
+        removed.sort_unstable();
+        Ok(removed)
+    }
+
+    /// Keep and renumber embeddings; see `VectorIndex::retain`
+    pub fn retain(&self, index_name: &str, keep: impl FnMut(&Embedding) -> Option<u64>) -> Result<usize> {
+        let mut indexes = self.indexes.write();
+        match indexes.get_mut(index_name) {
+            Some(index) => index.retain(keep),
+            None => Err(Error::Storage(format!("Index '{}' not found", index_name))),
+        }
+    }
+
     /// Search in index
     pub fn search(&self, index_name: &str, query_vector: &[f32], k: usize) -> Result<Vec<SearchResult>> {
         let indexes = self.indexes.read();
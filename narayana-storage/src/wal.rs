@@ -0,0 +1,619 @@
+// Write-Ahead Log for the persistent column store
+// Every write is appended here and fsynced before its blocks are written, so an
+// acknowledged insert survives a crash that comes before its blocks and table
+// metadata are on disk. On startup the log is read back and each table replays
+// the writes its metadata doesn't cover yet. A checkpoint starts a new segment
+// and deletes the segments whose writes have all been flushed.
+//
+// Segments are `{first_lsn:020}.wal` files: an 8-byte magic followed by frames
+// of [payload length u32][checksum u64][bincode entry]. A frame that is cut
+// short or fails its checksum ends the segment; in the newest segment that is
+// a write torn by the crash, and the file is truncated there.
+//
+// A log can also hold a Raft node's term, vote and log (see `RaftStorage`).
+// Those records are never completed, so checkpoints keep every segment from
+// the first of them on.
+
+use crate::consensus::{ConsensusEntry, RaftPersistentState, RaftStorage};
+use async_trait::async_trait;
+use narayana_core::{column::Column, types::TableId, Error, Result};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+use tracing::{info, warn};
+
+const SEGMENT_MAGIC: &[u8; 8] = b"NZWAL\x00\x00\x01";
+const FRAME_HEADER_BYTES: usize = 12;
+/// Frames claiming more than this are treated as corrupt
+const MAX_FRAME_BYTES: usize = 1 << 30;
+
+/// What a log entry records
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum WalRecord {
+    /// Columns appended to a table that had `row_start` rows
+    Write { table_id: TableId, row_start: usize, columns: Vec<Column> },
+    /// The write logged at `lsn` failed and must not be replayed
+    Abort { lsn: u64 },
+    /// The table was deleted; its earlier writes must not be replayed
+    DropTable { table_id: TableId },
+    /// The table's rows were replaced; its earlier writes must not be replayed
+    RewriteTable { table_id: TableId },
+    /// A Raft node's term and vote
+    RaftHardState { term: u64, voted_for: Option<String> },
+    /// Entries appended to a Raft log
+    RaftAppend { entries: Vec<ConsensusEntry> },
+    /// Raft log entries from `from_index` on were dropped
+    RaftTruncate { from_index: u64 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalEntry {
+    /// Log sequence number, increasing across segments
+    pub lsn: u64,
+    pub record: WalRecord,
+}
+
+/// `WalEntry` borrowing the columns of a write, so logging doesn't copy them.
+/// Variants must stay in the order of `WalRecord`; bincode encodes both alike.
+#[derive(Serialize)]
+struct WalEntryRef<'a> {
+    lsn: u64,
+    record: WalRecordRef<'a>,
+}
+
+#[derive(Serialize)]
+enum WalRecordRef<'a> {
+    Write { table_id: TableId, row_start: usize, columns: &'a [Column] },
+    Abort { lsn: u64 },
+    DropTable { table_id: TableId },
+    RewriteTable { table_id: TableId },
+    RaftHardState { term: u64, voted_for: Option<&'a str> },
+    RaftAppend { entries: &'a [ConsensusEntry] },
+    RaftTruncate { from_index: u64 },
+}
+
+impl WalRecordRef<'_> {
+    fn is_raft(&self) -> bool {
+        matches!(
+            self,
+            WalRecordRef::RaftHardState { .. } | WalRecordRef::RaftAppend { .. } | WalRecordRef::RaftTruncate { .. }
+        )
+    }
+}
+
+/// A write read back on open that its table still has to replay
+#[derive(Debug, Clone)]
+pub(crate) struct RecoveredWrite {
+    pub lsn: u64,
+    pub row_start: usize,
+    pub columns: Vec<Column>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct WalStats {
+    pub segments: usize,
+    pub bytes: u64,
+    /// Last sequence number handed out (0 before the first entry)
+    pub last_lsn: u64,
+    /// Writes logged but not yet flushed to blocks
+    pub in_flight: usize,
+    /// Writes read back on open whose tables haven't replayed them yet
+    pub recovering: usize,
+    pub appended: u64,
+    pub checkpoints: u64,
+}
+
+struct Segment {
+    first_lsn: u64,
+    path: PathBuf,
+    bytes: u64,
+}
+
+struct Writer {
+    file: fs::File,
+    /// Oldest first; the last one is appended to
+    segments: Vec<Segment>,
+    next_lsn: u64,
+}
+
+pub struct WriteAheadLog {
+    dir: PathBuf,
+    writer: tokio::sync::Mutex<Writer>,
+    /// Logged writes whose blocks aren't flushed yet
+    in_flight: Mutex<BTreeSet<u64>>,
+    recovered: Mutex<HashMap<TableId, Vec<RecoveredWrite>>>,
+    /// Raft state read back on open, until `RaftStorage::load` takes it
+    raft_recovered: Mutex<RaftPersistentState>,
+    /// Sequence number of the first Raft record; checkpoints keep it
+    raft_first_lsn: Mutex<Option<u64>>,
+    appended: AtomicU64,
+    checkpoints: AtomicU64,
+}
+
+impl WriteAheadLog {
+    /// Open the log in `dir`, reading back the writes of earlier runs (see
+    /// `take_recovered`). New entries go to a fresh segment.
+    pub async fn open(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir).await
+            .map_err(|e| Error::Storage(format!("Failed to create WAL directory: {}", e)))?;
+
+        let mut segments = Vec::new();
+        let mut entries = fs::read_dir(&dir).await
+            .map_err(|e| Error::Storage(format!("Failed to read WAL directory: {}", e)))?;
+        while let Some(entry) = entries.next_entry().await
+            .map_err(|e| Error::Storage(format!("Failed to read WAL directory entry: {}", e)))? {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("wal") {
+                continue;
+            }
+            match path.file_stem().and_then(|s| s.to_str()).and_then(|s| s.parse::<u64>().ok()) {
+                Some(first_lsn) => segments.push(Segment { first_lsn, path, bytes: 0 }),
+                None => warn!("Skipping WAL file {:?} with an invalid name", path),
+            }
+        }
+        segments.sort_by_key(|s| s.first_lsn);
+
+        let mut logged = Vec::new();
+        let last = segments.len().saturating_sub(1);
+        for (i, segment) in segments.iter_mut().enumerate() {
+            let data = fs::read(&segment.path).await
+                .map_err(|e| Error::Storage(format!("Failed to read WAL segment: {}", e)))?;
+            let (entries, valid) = read_segment(&data);
+            if valid < data.len() {
+                if i == last {
+                    warn!("Truncating WAL segment {:?} after a torn write at byte {}", segment.path, valid);
+                    truncate(&segment.path, valid as u64).await?;
+                } else {
+                    warn!("WAL segment {:?} is corrupt from byte {}; later entries in it are lost", segment.path, valid);
+                }
+            }
+            segment.bytes = valid as u64;
+            logged.extend(entries);
+        }
+
+        let next_lsn = logged.iter().map(|e| e.lsn + 1)
+            .chain(segments.iter().map(|s| s.first_lsn))
+            .max()
+            .unwrap_or(1);
+        let (raft_recovered, raft_first_lsn) = recover_raft(&logged);
+        let recovered = recover(logged);
+        let count: usize = recovered.values().map(Vec::len).sum();
+        if count > 0 {
+            info!("WAL holds {} writes across {} tables to check against table metadata", count, recovered.len());
+        }
+
+        // An empty newest segment would have the new segment's name
+        segments.retain(|s| s.first_lsn != next_lsn);
+        let (file, segment) = create_segment(&dir, next_lsn).await?;
+        segments.push(segment);
+        Ok(Self {
+            dir,
+            writer: tokio::sync::Mutex::new(Writer { file, segments, next_lsn }),
+            in_flight: Mutex::new(BTreeSet::new()),
+            recovered: Mutex::new(recovered),
+            raft_recovered: Mutex::new(raft_recovered),
+            raft_first_lsn: Mutex::new(raft_first_lsn),
+            appended: AtomicU64::new(0),
+            checkpoints: AtomicU64::new(0),
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Log a write and sync it to disk; call `complete` once its blocks and
+    /// table metadata are flushed, or `abort` if it failed
+    pub async fn log_write(&self, table_id: TableId, row_start: usize, columns: &[Column]) -> Result<u64> {
+        self.append(|lsn| WalEntryRef { lsn, record: WalRecordRef::Write { table_id, row_start, columns } }, true).await
+    }
+
+    /// The write is on disk; its entry is no longer needed
+    pub fn complete(&self, lsn: u64) {
+        self.in_flight.lock().remove(&lsn);
+    }
+
+    /// The write failed; make sure it isn't replayed
+    pub async fn abort(&self, lsn: u64) {
+        if let Err(e) = self.append(|own| WalEntryRef { lsn: own, record: WalRecordRef::Abort { lsn } }, false).await {
+            warn!("Failed to log abort of WAL entry {}: {}", lsn, e);
+        }
+        self.in_flight.lock().remove(&lsn);
+    }
+
+    /// Log that a table is being deleted
+    pub async fn log_drop_table(&self, table_id: TableId) -> Result<()> {
+        self.recovered.lock().remove(&table_id);
+        self.append(|lsn| WalEntryRef { lsn, record: WalRecordRef::DropTable { table_id } }, false).await?;
+        Ok(())
+    }
+
+    /// Log that a table's rows are being replaced, once every write before
+    /// this is in its table metadata
+    pub async fn log_rewrite(&self, table_id: TableId) -> Result<()> {
+        self.recovered.lock().remove(&table_id);
+        self.append(|lsn| WalEntryRef { lsn, record: WalRecordRef::RewriteTable { table_id } }, false).await?;
+        Ok(())
+    }
+
+    async fn append<'a>(&self, entry: impl FnOnce(u64) -> WalEntryRef<'a>, in_flight: bool) -> Result<u64> {
+        let mut writer = self.writer.lock().await;
+        let lsn = writer.next_lsn;
+        let entry = entry(lsn);
+        let payload = bincode::serialize(&entry)
+            .map_err(|e| Error::Serialization(format!("Failed to encode WAL entry: {}", e)))?;
+        if payload.len() > MAX_FRAME_BYTES {
+            return Err(Error::Storage(format!("Write of {} bytes is too large for the WAL", payload.len())));
+        }
+        let mut frame = Vec::with_capacity(FRAME_HEADER_BYTES + payload.len());
+        frame.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        frame.extend_from_slice(&frame_checksum(&payload).to_le_bytes());
+        frame.extend_from_slice(&payload);
+
+        let written = async {
+            writer.file.write_all(&frame).await?;
+            writer.file.sync_data().await
+        }.await;
+        if let Err(e) = written {
+            // Cut off whatever part of the frame made it, so later entries stay readable
+            let segment = writer.segments.last().expect("WAL has a current segment");
+            let _ = writer.file.set_len(segment.bytes).await;
+            return Err(Error::Storage(format!("Failed to write WAL entry: {}", e)));
+        }
+        writer.segments.last_mut().expect("WAL has a current segment").bytes += frame.len() as u64;
+        writer.next_lsn += 1;
+        if in_flight {
+            self.in_flight.lock().insert(lsn);
+        }
+        if entry.record.is_raft() {
+            self.raft_first_lsn.lock().get_or_insert(lsn);
+        }
+        self.appended.fetch_add(1, Ordering::Relaxed);
+        Ok(lsn)
+    }
+
+    /// Writes of an earlier run for `table_id`, oldest first; each is handed out once
+    pub(crate) fn take_recovered(&self, table_id: TableId) -> Vec<RecoveredWrite> {
+        self.recovered.lock().remove(&table_id).unwrap_or_default()
+    }
+
+    /// Drop the recovered writes no table claimed (their tables weren't
+    /// loaded), so checkpoints can delete the old segments; returns how many
+    pub fn finish_recovery(&self) -> usize {
+        let leftover = std::mem::take(&mut *self.recovered.lock());
+        let count = leftover.values().map(Vec::len).sum();
+        if count > 0 {
+            let mut tables: Vec<u64> = leftover.keys().map(|t| t.0).collect();
+            tables.sort_unstable();
+            warn!("Discarding {} WAL writes for tables that were not loaded: {:?}", count, tables);
+        }
+        count
+    }
+
+    /// Start a new segment and delete the segments holding only flushed
+    /// writes; returns how many were deleted
+    pub async fn checkpoint(&self) -> Result<usize> {
+        let mut writer = self.writer.lock().await;
+        let current = writer.segments.last().expect("WAL has a current segment");
+        if current.bytes > SEGMENT_MAGIC.len() as u64 {
+            let (file, segment) = create_segment(&self.dir, writer.next_lsn).await?;
+            writer.file = file;
+            writer.segments.push(segment);
+        }
+
+        // Entries below this are no longer needed
+        let oldest_needed = [
+            self.in_flight.lock().first().copied(),
+            self.recovered.lock().values().flatten().map(|w| w.lsn).min(),
+            *self.raft_first_lsn.lock(),
+        ].into_iter().flatten().min().unwrap_or(writer.next_lsn);
+        let mut removable = 0;
+        while removable + 1 < writer.segments.len() && writer.segments[removable + 1].first_lsn <= oldest_needed {
+            removable += 1;
+        }
+        for segment in writer.segments.drain(..removable) {
+            if let Err(e) = fs::remove_file(&segment.path).await {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    warn!("Failed to remove WAL segment {:?}: {}", segment.path, e);
+                }
+            }
+        }
+        sync_dir(&self.dir).await;
+        self.checkpoints.fetch_add(1, Ordering::Relaxed);
+        Ok(removable)
+    }
+
+    /// Checkpoint every `interval`
+    pub fn start_checkpoints(self: Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                match self.checkpoint().await {
+                    Ok(0) => {}
+                    Ok(removed) => info!("WAL checkpoint removed {} segments", removed),
+                    Err(e) => warn!("WAL checkpoint failed: {}", e),
+                }
+            }
+        })
+    }
+
+    pub async fn stats(&self) -> WalStats {
+        let writer = self.writer.lock().await;
+        WalStats {
+            segments: writer.segments.len(),
+            bytes: writer.segments.iter().map(|s| s.bytes).sum(),
+            last_lsn: writer.next_lsn - 1,
+            in_flight: self.in_flight.lock().len(),
+            recovering: self.recovered.lock().values().map(Vec::len).sum(),
+            appended: self.appended.load(Ordering::Relaxed),
+            checkpoints: self.checkpoints.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[async_trait]
+impl RaftStorage for WriteAheadLog {
+    async fn load(&self) -> Result<RaftPersistentState> {
+        Ok(std::mem::take(&mut *self.raft_recovered.lock()))
+    }
+
+    async fn save_hard_state(&self, term: u64, voted_for: Option<&str>) -> Result<()> {
+        self.append(|lsn| WalEntryRef { lsn, record: WalRecordRef::RaftHardState { term, voted_for } }, false).await?;
+        Ok(())
+    }
+
+    async fn append_entries(&self, entries: &[ConsensusEntry]) -> Result<()> {
+        self.append(|lsn| WalEntryRef { lsn, record: WalRecordRef::RaftAppend { entries } }, false).await?;
+        Ok(())
+    }
+
+    async fn truncate_log(&self, from_index: u64) -> Result<()> {
+        self.append(|lsn| WalEntryRef { lsn, record: WalRecordRef::RaftTruncate { from_index } }, false).await?;
+        Ok(())
+    }
+}
+
+/// Entries of a segment and the length of its valid prefix
+fn read_segment(data: &[u8]) -> (Vec<WalEntry>, usize) {
+    let Some(mut rest) = data.strip_prefix(SEGMENT_MAGIC.as_slice()) else {
+        return (Vec::new(), 0);
+    };
+    let mut entries = Vec::new();
+    let mut valid = SEGMENT_MAGIC.len();
+    while rest.len() >= FRAME_HEADER_BYTES {
+        let len = u32::from_le_bytes(rest[..4].try_into().unwrap()) as usize;
+        let checksum = u64::from_le_bytes(rest[4..12].try_into().unwrap());
+        let Some(payload) = rest.get(FRAME_HEADER_BYTES..FRAME_HEADER_BYTES + len) else { break };
+        if len > MAX_FRAME_BYTES || frame_checksum(payload) != checksum {
+            break;
+        }
+        let Ok(entry) = bincode::deserialize::<WalEntry>(payload) else { break };
+        entries.push(entry);
+        rest = &rest[FRAME_HEADER_BYTES + len..];
+        valid += FRAME_HEADER_BYTES + len;
+    }
+    (entries, valid)
+}
+
+/// Writes to replay, by table: aborted writes and writes made before their
+/// table was deleted or rewritten are left out
+fn recover(entries: Vec<WalEntry>) -> HashMap<TableId, Vec<RecoveredWrite>> {
+    let mut aborted = HashSet::new();
+    let mut cut_off: HashMap<TableId, u64> = HashMap::new();
+    for entry in &entries {
+        match entry.record {
+            WalRecord::Abort { lsn } => {
+                aborted.insert(lsn);
+            }
+            WalRecord::DropTable { table_id } | WalRecord::RewriteTable { table_id } => {
+                let last = cut_off.entry(table_id).or_default();
+                *last = (*last).max(entry.lsn);
+            }
+            _ => {}
+        }
+    }
+    let mut recovered: HashMap<TableId, Vec<RecoveredWrite>> = HashMap::new();
+    for entry in entries {
+        let WalRecord::Write { table_id, row_start, columns } = entry.record else { continue };
+        if aborted.contains(&entry.lsn) || cut_off.get(&table_id).is_some_and(|&lsn| lsn > entry.lsn) {
+            continue;
+        }
+        recovered.entry(table_id).or_default().push(RecoveredWrite { lsn: entry.lsn, row_start, columns });
+    }
+    for writes in recovered.values_mut() {
+        writes.sort_by_key(|w| w.lsn);
+    }
+    recovered
+}
+
+/// Raft term, vote and log as the Raft records leave them, and the sequence
+/// number of the first of those records
+fn recover_raft(entries: &[WalEntry]) -> (RaftPersistentState, Option<u64>) {
+    let mut state = RaftPersistentState::default();
+    let mut first_lsn = None;
+    for entry in entries {
+        match &entry.record {
+            WalRecord::RaftHardState { term, voted_for } => {
+                state.term = *term;
+                state.voted_for = voted_for.clone();
+            }
+            WalRecord::RaftAppend { entries } => {
+                for appended in entries {
+                    if appended.index != state.log.len() as u64 + 1 {
+                        warn!("Raft log entry {} doesn't follow entry {}; ignoring it", appended.index, state.log.len());
+                        continue;
+                    }
+                    state.log.push(appended.clone());
+                }
+            }
+            WalRecord::RaftTruncate { from_index } => {
+                state.log.truncate(from_index.saturating_sub(1) as usize);
+            }
+            _ => continue,
+        }
+        first_lsn.get_or_insert(entry.lsn);
+    }
+    (state, first_lsn)
+}
+
+async fn create_segment(dir: &Path, first_lsn: u64) -> Result<(fs::File, Segment)> {
+    let path = dir.join(format!("{:020}.wal", first_lsn));
+    let created = async {
+        let mut file = fs::OpenOptions::new().create(true).truncate(true).write(true).open(&path).await?;
+        file.write_all(SEGMENT_MAGIC).await?;
+        file.sync_all().await?;
+        Ok::<_, std::io::Error>(file)
+    }.await;
+    let file = created.map_err(|e| Error::Storage(format!("Failed to create WAL segment: {}", e)))?;
+    sync_dir(dir).await;
+    Ok((file, Segment { first_lsn, path, bytes: SEGMENT_MAGIC.len() as u64 }))
+}
+
+async fn truncate(path: &Path, len: u64) -> Result<()> {
+    let truncated = async {
+        let file = fs::OpenOptions::new().write(true).open(path).await?;
+        file.set_len(len).await?;
+        file.sync_all().await
+    }.await;
+    truncated.map_err(|e| Error::Storage(format!("Failed to truncate WAL segment: {}", e)))
+}
+
+/// Make created and removed segment files durable (not possible on every platform)
+async fn sync_dir(dir: &Path) {
+    if let Ok(dir) = fs::File::open(dir).await {
+        let _ = dir.sync_all().await;
+    }
+}
+
+fn frame_checksum(payload: &[u8]) -> u64 {
+    use sha2::{Digest, Sha256};
+    let digest = Sha256::digest(payload);
+    u64::from_le_bytes(digest[..8].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("narayana_{}_{}", name, uuid::Uuid::new_v4()))
+    }
+
+    fn rows(writes: &[RecoveredWrite]) -> Vec<(usize, Vec<i64>)> {
+        writes.iter()
+            .map(|w| match &w.columns[0] {
+                Column::Int64(v) => (w.row_start, v.clone()),
+                other => panic!("unexpected column {:?}", other),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_recovers_writes_skipping_aborted_and_dropped() {
+        let dir = temp_dir("wal");
+        {
+            let wal = WriteAheadLog::open(&dir).await.unwrap();
+            let first = wal.log_write(TableId(1), 0, &[Column::Int64(vec![1, 2])]).await.unwrap();
+            wal.complete(first);
+            let failed = wal.log_write(TableId(1), 2, &[Column::Int64(vec![3])]).await.unwrap();
+            wal.abort(failed).await;
+            wal.log_write(TableId(1), 2, &[Column::Int64(vec![4])]).await.unwrap();
+            wal.log_write(TableId(2), 0, &[Column::Int64(vec![9])]).await.unwrap();
+            wal.log_drop_table(TableId(2)).await.unwrap();
+            wal.log_write(TableId(2), 0, &[Column::Int64(vec![10])]).await.unwrap();
+        }
+
+        let wal = WriteAheadLog::open(&dir).await.unwrap();
+        assert_eq!(rows(&wal.take_recovered(TableId(1))), vec![(0, vec![1, 2]), (2, vec![4])]);
+        assert_eq!(rows(&wal.take_recovered(TableId(2))), vec![(0, vec![10])]);
+        assert!(wal.take_recovered(TableId(1)).is_empty());
+        // Numbering carries on after the entries read back
+        assert_eq!(wal.stats().await.last_lsn, 7);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_torn_tail_is_truncated() {
+        let dir = temp_dir("wal_torn");
+        let path = {
+            let wal = WriteAheadLog::open(&dir).await.unwrap();
+            wal.log_write(TableId(1), 0, &[Column::Int64(vec![1])]).await.unwrap();
+            wal.log_write(TableId(1), 1, &[Column::Int64(vec![2])]).await.unwrap();
+            dir.join(format!("{:020}.wal", 1))
+        };
+        // Cut the second frame short, as a crash mid-append would
+        let len = std::fs::metadata(&path).unwrap().len();
+        std::fs::OpenOptions::new().write(true).open(&path).unwrap().set_len(len - 3).unwrap();
+
+        let wal = WriteAheadLog::open(&dir).await.unwrap();
+        assert_eq!(rows(&wal.take_recovered(TableId(1))), vec![(0, vec![1])]);
+        let (_, valid) = read_segment(&std::fs::read(&path).unwrap());
+        assert_eq!(valid as u64, std::fs::metadata(&path).unwrap().len());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_keeps_segments_with_unflushed_writes() {
+        let dir = temp_dir("wal_checkpoint");
+        let wal = WriteAheadLog::open(&dir).await.unwrap();
+        let flushed = wal.log_write(TableId(1), 0, &[Column::Int64(vec![1])]).await.unwrap();
+        wal.complete(flushed);
+        let unflushed = wal.log_write(TableId(1), 1, &[Column::Int64(vec![2])]).await.unwrap();
+
+        // The first segment holds an unflushed write
+        assert_eq!(wal.checkpoint().await.unwrap(), 0);
+        assert_eq!(wal.stats().await.segments, 2);
+        // An empty current segment isn't rotated again
+        wal.complete(unflushed);
+        assert_eq!(wal.checkpoint().await.unwrap(), 1);
+        assert_eq!(wal.checkpoint().await.unwrap(), 0);
+        let stats = wal.stats().await;
+        assert_eq!((stats.segments, stats.in_flight, stats.appended), (1, 0, 2));
+
+        drop(wal);
+        let wal = WriteAheadLog::open(&dir).await.unwrap();
+        assert!(wal.take_recovered(TableId(1)).is_empty());
+        assert_eq!(wal.stats().await.last_lsn, 2);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_raft_state_is_recovered_and_kept_by_checkpoints() {
+        let dir = temp_dir("wal_raft");
+        let entry = |term, index| ConsensusEntry { term, index, data: vec![index as u8] };
+        {
+            let wal = WriteAheadLog::open(&dir).await.unwrap();
+            wal.save_hard_state(1, Some("n1")).await.unwrap();
+            wal.append_entries(&[entry(1, 1), entry(1, 2), entry(1, 3)]).await.unwrap();
+            wal.save_hard_state(2, None).await.unwrap();
+            wal.truncate_log(3).await.unwrap();
+            wal.append_entries(&[entry(2, 3)]).await.unwrap();
+            // Raft records are never flushed like table writes
+            wal.checkpoint().await.unwrap();
+            assert_eq!(wal.checkpoint().await.unwrap(), 0);
+        }
+
+        let wal = WriteAheadLog::open(&dir).await.unwrap();
+        let state = wal.load().await.unwrap();
+        assert_eq!(state, RaftPersistentState {
+            term: 2,
+            voted_for: None,
+            log: vec![entry(1, 1), entry(1, 2), entry(2, 3)],
+        });
+        assert!(wal.take_recovered(TableId(1)).is_empty());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
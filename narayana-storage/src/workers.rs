@@ -7,6 +7,7 @@ use crate::dynamic_output::DynamicOutputManager;
 use crate::cognitive::CognitiveBrain;
 use crate::ColumnStore;
 use narayana_core::transforms::{OutputConfig, TransformEngine, ConfigContext};
+use narayana_core::egress::{self, EgressSubsystem};
 use anyhow::{anyhow, Context, Result};
 use dashmap::DashMap;
 // Removed futures::StreamExt - not needed
@@ -877,7 +878,32 @@ impl WorkerManager {
         // Find worker by route
         let worker = self.find_worker_by_route(&request.url, &request.edge_location)
             .ok_or_else(|| anyhow!("No worker found for route: {}", request.url))?;
-        
+        self.run_worker(worker, request, storage, db_manager, brain).await
+    }
+    
+    /// Execute a worker by id regardless of its route (event-driven invocation,
+    /// e.g. RDE subscriptions targeting a deployed worker)
+    pub async fn invoke_worker(
+        &self,
+        worker_id: &str,
+        request: WorkerRequest,
+        storage: Arc<dyn ColumnStore>,
+        db_manager: Arc<DatabaseManager>,
+        brain: Option<Arc<CognitiveBrain>>,
+    ) -> Result<WorkerResponse> {
+        let worker = self.get_worker(worker_id)
+            .ok_or_else(|| anyhow!("Worker not found: {}", worker_id))?;
+        self.run_worker(worker, request, storage, db_manager, brain).await
+    }
+    
+    async fn run_worker(
+        &self,
+        worker: WorkerEnvironment,
+        request: WorkerRequest,
+        storage: Arc<dyn ColumnStore>,
+        db_manager: Arc<DatabaseManager>,
+        brain: Option<Arc<CognitiveBrain>>,
+    ) -> Result<WorkerResponse> {
         // Check if worker is active
         if !worker.active {
             return Err(anyhow!("Worker is not active: {}", worker.id));
@@ -911,6 +937,11 @@ impl WorkerManager {
         // Create execution context with resources
         let worker_id = worker.id.clone(); // Save worker ID before moving
         
+        // Workers read tables as `worker:<id>` with the `worker` role, so row
+        // policies and column masks apply to them like any other caller
+        let principal = crate::row_security::Principal::new(format!("worker:{}", worker_id), vec!["worker".to_string()]);
+        let storage = db_manager.row_security().secure(storage, principal);
+        
         // Clone self for the context (WorkerManager is now Clone)
         let worker_manager_arc = Arc::new(self.clone());
         
@@ -3442,38 +3473,19 @@ impl WorkerRuntime for QuickJSRuntime {
                                 false
                             };
                             
-                            // SECURITY: Prevent SSRF attacks - block localhost and private IPs
+                            // SECURITY: Prevent SSRF attacks via the shared egress policy: the host is
+                            // resolved, every address vetted, and the client pinned to those addresses
                             // BUT: Allow if URL is in whitelist (for Docker/localhost access)
-                            use crate::security_utils::SecurityUtils;
-                            if !is_whitelisted {
-                                if let Err(e) = SecurityUtils::validate_http_url(&url) {
-                                    // SECURITY: Log SSRF attempt for monitoring
-                                    tracing::warn!("SSRF attempt blocked: {} - {}", url, e);
-                                    
-                                    results.insert(
-                                        idx.to_string(),
-                                        serde_json::json!({
-                                            "error": "Forbidden: URL not allowed",
-                                            "ok": false,
-                                            "status": 403,
-                                            "statusText": "Forbidden"
-                                        })
-                                    );
-                                    continue;
-                                }
-                                
-                                // SECURITY: Additional URL validation - check for URL encoding bypasses
-                                // Decode URL to check for encoded localhost/private IPs
-                                if let Ok(decoded) = urlencoding::decode(&url) {
-                                    let decoded_lower = decoded.to_lowercase();
-                                    // Check for encoded localhost patterns
-                                    if decoded_lower.contains("127.") || 
-                                       decoded_lower.contains("localhost") ||
-                                       decoded_lower.contains("192.168") ||
-                                       decoded_lower.contains("10.") ||
-                                       decoded_lower.contains("172.16") ||
-                                       decoded_lower.contains("169.254") {
-                                        tracing::warn!("SSRF attempt with encoded URL blocked: {}", url);
+                            let request_client = if !is_whitelisted {
+                                let builder = reqwest::Client::builder()
+                                    .timeout(Duration::from_secs(30))
+                                    .connect_timeout(Duration::from_secs(10));
+                                match handle_clone.block_on(egress::pinned_client(EgressSubsystem::Workers, &url, builder)) {
+                                    Ok(pinned) => pinned,
+                                    Err(e) => {
+                                        // SECURITY: Log SSRF attempt for monitoring
+                                        tracing::warn!("SSRF attempt blocked: {} - {}", url, e);
+                                        
                                         results.insert(
                                             idx.to_string(),
                                             serde_json::json!({
@@ -3489,7 +3501,8 @@ impl WorkerRuntime for QuickJSRuntime {
                             } else {
                                 // URL is whitelisted - log for audit but allow
                                 tracing::info!("Whitelisted URL accessed: {} (worker: {})", url, ctx_clone.env.id);
-                            }
+                                client_clone.clone()
+                            };
                             
                             // SECURITY: Check body size limit (with integer overflow protection)
                             if let Some(ref body_bytes) = body {
@@ -3583,7 +3596,7 @@ impl WorkerRuntime for QuickJSRuntime {
                                     }));
                                 }
                                 
-                                let mut request_builder = client_clone.request(method_parsed, &url);
+                                let mut request_builder = request_client.request(method_parsed, &url);
                                 
                                 // Add sanitized headers
                                 for (key, value) in &sanitized_headers {
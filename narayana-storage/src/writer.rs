@@ -1,5 +1,6 @@
 use narayana_core::{Error, Result, column::Column, schema::DataType, types::CompressionType};
-use crate::block::{Block, BlockMetadata};
+use crate::block::{Block, BlockMetadata, BlockStats};
+use std::hash::{Hash, Hasher};
 use crate::compression::{create_compressor, Compressor};
 use bytes::{Bytes, BytesMut};
 use bincode;
@@ -32,6 +33,7 @@ impl ColumnWriter {
                         column_id,
                         row_offset,
                         DataType::Int8,
+                        ordered_stats(chunk, |v| v.to_le_bytes().to_vec(), |v| *v as u64),
                     )?;
                     blocks.push((block, metadata));
                     row_offset += chunk.len();
@@ -46,6 +48,7 @@ impl ColumnWriter {
                         column_id,
                         row_offset,
                         DataType::Int32,
+                        ordered_stats(chunk, |v| v.to_le_bytes().to_vec(), |v| *v as u64),
                     )?;
                     blocks.push((block, metadata));
                     row_offset += chunk.len();
@@ -60,6 +63,7 @@ impl ColumnWriter {
                         column_id,
                         row_offset,
                         DataType::Int64,
+                        ordered_stats(chunk, |v| v.to_le_bytes().to_vec(), |v| *v as u64),
                     )?;
                     blocks.push((block, metadata));
                     row_offset += chunk.len();
@@ -74,6 +78,7 @@ impl ColumnWriter {
                         column_id,
                         row_offset,
                         DataType::UInt64,
+                        ordered_stats(chunk, |v| v.to_le_bytes().to_vec(), |v| *v),
                     )?;
                     blocks.push((block, metadata));
                     row_offset += chunk.len();
@@ -88,6 +93,7 @@ impl ColumnWriter {
                         column_id,
                         row_offset,
                         DataType::Float64,
+                        float_stats(chunk),
                     )?;
                     blocks.push((block, metadata));
                     row_offset += chunk.len();
@@ -104,6 +110,7 @@ impl ColumnWriter {
                         column_id,
                         row_offset,
                         DataType::Boolean,
+                        ordered_stats(chunk, |v| vec![*v as u8], |v| *v as u64),
                     )?;
                     blocks.push((block, metadata));
                     row_offset += chunk.len();
@@ -115,7 +122,8 @@ impl ColumnWriter {
                     let serialized = bincode::serialize(chunk)
                         .map_err(|e| Error::Serialization(format!("Failed to serialize: {}", e)))?;
                     let compressed = compressor.compress(&serialized)?;
-                    
+                    let stats = string_stats(chunk);
+
                     let block = Block {
                         column_id,
                         data: Bytes::from(compressed.clone()),
@@ -135,9 +143,10 @@ impl ColumnWriter {
                         compression: self.compression,
                         uncompressed_size: serialized.len(),
                         compressed_size: compressed.len(),
-                        min_value: None,
-                        max_value: None,
+                        min_value: stats.min_value,
+                        max_value: stats.max_value,
                         null_count: 0,
+                        distinct_count: stats.distinct_count,
                     };
 
                     blocks.push((block, metadata));
@@ -159,6 +168,7 @@ impl ColumnWriter {
         column_id: u32,
         row_start: usize,
         data_type: DataType,
+        stats: BlockStats,
     ) -> Result<(Block, BlockMetadata)> {
         // True column-oriented: direct memory copy, no serialization overhead
         use std::mem;
@@ -195,15 +205,58 @@ impl ColumnWriter {
             compression: self.compression,
             uncompressed_size,
             compressed_size: compressed.len(),
-            min_value: None,
-            max_value: None,
+            min_value: stats.min_value,
+            max_value: stats.max_value,
             null_count: 0,
+            distinct_count: stats.distinct_count,
         };
 
         Ok((block, metadata))
     }
 }
 
+/// Longest string kept as a zone map bound; blocks with longer bounds keep none
+const MAX_STRING_BOUND: usize = 256;
+
+fn ordered_stats<T: Ord>(chunk: &[T], encode: impl Fn(&T) -> Vec<u8>, hash: impl Fn(&T) -> u64) -> BlockStats {
+    BlockStats::gather(chunk, Ord::cmp, encode, hash)
+}
+
+fn float_stats(chunk: &[f64]) -> BlockStats {
+    // Adding 0.0 folds -0.0 into 0.0, which compare equal
+    let mut stats = BlockStats::gather(
+        chunk,
+        f64::total_cmp,
+        |v| (v + 0.0).to_le_bytes().to_vec(),
+        |v| (v + 0.0).to_bits(),
+    );
+    // NaN compares false with everything but !=, which bounds can't express
+    if chunk.iter().any(|v| v.is_nan()) {
+        stats.min_value = None;
+        stats.max_value = None;
+    }
+    stats
+}
+
+fn string_stats(chunk: &[String]) -> BlockStats {
+    let mut stats = BlockStats::gather(
+        chunk,
+        Ord::cmp,
+        |v| v.as_bytes().to_vec(),
+        |v| {
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            v.hash(&mut hasher);
+            hasher.finish()
+        },
+    );
+    let too_long = |bound: &Option<Vec<u8>>| bound.as_ref().is_some_and(|b| b.len() > MAX_STRING_BOUND);
+    if too_long(&stats.min_value) || too_long(&stats.max_value) {
+        stats.min_value = None;
+        stats.max_value = None;
+    }
+    stats
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -242,6 +295,30 @@ mod tests {
         assert!(blocks.len() > 1); // Should create multiple blocks
     }
 
+    #[test]
+    fn test_block_statistics() {
+        let writer = ColumnWriter::new(CompressionType::LZ4, 3);
+        let column = narayana_core::column::Column::Int64(vec![5, -2, 5, 100, 7]);
+        let blocks = writer.write_column(&column, 0).unwrap();
+        let (_, first) = &blocks[0];
+        assert_eq!(first.min_value, Some((-2i64).to_le_bytes().to_vec()));
+        assert_eq!(first.max_value, Some(5i64.to_le_bytes().to_vec()));
+        assert_eq!((first.null_count, first.distinct_count), (0, Some(2)));
+        assert_eq!(blocks[1].1.min_value, Some(7i64.to_le_bytes().to_vec()));
+
+        let column = narayana_core::column::Column::Float64(vec![1.5, -0.0, -0.5]);
+        let (_, metadata) = &writer.write_column(&column, 0).unwrap()[0];
+        assert_eq!(metadata.min_value, Some((-0.5f64).to_le_bytes().to_vec()));
+        assert_eq!(metadata.max_value, Some(1.5f64.to_le_bytes().to_vec()));
+        let column = narayana_core::column::Column::Float64(vec![1.5, f64::NAN]);
+        assert!(writer.write_column(&column, 0).unwrap()[0].1.min_value.is_none());
+
+        let column = narayana_core::column::Column::String(vec!["pear".to_string(), "apple".to_string()]);
+        let (_, metadata) = &writer.write_column(&column, 0).unwrap()[0];
+        assert_eq!(metadata.min_value.as_deref(), Some("apple".as_bytes()));
+        assert_eq!(metadata.max_value.as_deref(), Some("pear".as_bytes()));
+    }
+
     #[test]
     fn test_block_metadata() {
         let writer = ColumnWriter::new(CompressionType::None, 100);
@@ -48,6 +48,7 @@ export function useAvatarWebSocket(options: UseAvatarWebSocketOptions = {}) {
   const maxConsecutiveFailures = 3 // Stop after 3 immediate failures
   const reconnectDelay = 3000
   const lastSuccessfulConnection = useRef<number | null>(null) // Track when we last had a successful connection
+  const lastSeq = useRef(0) // Last bridge sequence number seen, sent on reconnect to replay missed messages
 
   // Validate WebSocket URL
   const validateWebSocketUrl = useCallback((url: string): boolean => {
@@ -97,7 +98,13 @@ export function useAvatarWebSocket(options: UseAvatarWebSocketOptions = {}) {
     }
     
     try {
-      const ws = new WebSocket(wsUrl)
+      let connectUrl = wsUrl
+      if (lastSeq.current > 0) {
+        const resumeUrl = new URL(wsUrl)
+        resumeUrl.searchParams.set('last_seq', String(lastSeq.current))
+        connectUrl = resumeUrl.toString()
+      }
+      const ws = new WebSocket(connectUrl)
       wsRef.current = ws
 
       ws.onopen = () => {
@@ -125,6 +132,18 @@ export function useAvatarWebSocket(options: UseAvatarWebSocketOptions = {}) {
           // Parse message - handle both Rust enum format and flat format
           const rawMessage = JSON.parse(event.data)
           let message: AvatarMessage | null = null
+
+          // Broadcast messages carry a sequence number; skip ones already applied
+          if (typeof rawMessage.seq === 'number') {
+            if (rawMessage.seq <= lastSeq.current) {
+              return
+            }
+            lastSeq.current = rawMessage.seq
+          } else if (rawMessage.Resumed) {
+            // Bridge restarted or replay caught up; continue from where it says we are
+            lastSeq.current = rawMessage.Resumed.last_seq
+            return
+          }
           
           // Handle Rust enum serialization format: {"Expression": {...}}, {"Gesture": {...}}, etc.
           if (rawMessage.Expression) {
@@ -4,7 +4,10 @@ export type WsMessage =
   | { type: 'subscribe'; channel: string; filter?: any }
   | { type: 'unsubscribe'; channel: string }
   | { type: 'ping'; id?: string }
-  | { type: 'event'; channel: string; event: any; timestamp?: number }
+  | { type: 'ack'; seq: number }
+  | { type: 'event'; channel: string; event: any; timestamp?: number; seq?: number }
+  | { type: 'session'; resume_token: string; last_seq: number }
+  | { type: 'resumed'; last_seq: number; replayed: number; missed: number }
   | { type: 'subscribed'; channel: string }
   | { type: 'unsubscribed'; channel: string }
   | { type: 'error'; code: string; message: string }
@@ -18,6 +21,8 @@ interface UseWebSocketOptions {
   onClose?: () => void
   reconnectInterval?: number
   maxReconnectAttempts?: number
+  // Acknowledge received events every N events so the server can trim its replay buffer
+  ackInterval?: number
 }
 
 export function useWebSocket(options: UseWebSocketOptions = {}) {
@@ -29,6 +34,7 @@ export function useWebSocket(options: UseWebSocketOptions = {}) {
     onClose,
     reconnectInterval = 3000,
     maxReconnectAttempts = 10,
+    ackInterval = 32,
   } = options
 
   const [isConnected, setIsConnected] = useState(false)
@@ -37,6 +43,10 @@ export function useWebSocket(options: UseWebSocketOptions = {}) {
   const reconnectAttemptsRef = useRef(0)
   const reconnectTimeoutRef = useRef<ReturnType<typeof setTimeout> | null>(null)
   const subscriptionsRef = useRef<Set<string>>(new Set())
+  // Session resumption: token from the server and the last event sequence number seen
+  const resumeTokenRef = useRef<string | null>(null)
+  const lastSeqRef = useRef(0)
+  const ackedSeqRef = useRef(0)
 
   const connect = useCallback(() => {
     if (wsRef.current?.readyState === WebSocket.OPEN) {
@@ -44,7 +54,14 @@ export function useWebSocket(options: UseWebSocketOptions = {}) {
     }
 
     try {
-      const ws = new WebSocket(url)
+      let connectUrl = url
+      if (resumeTokenRef.current) {
+        const resumeUrl = new URL(url)
+        resumeUrl.searchParams.set('resume_token', resumeTokenRef.current)
+        resumeUrl.searchParams.set('last_seq', String(lastSeqRef.current))
+        connectUrl = resumeUrl.toString()
+      }
+      const ws = new WebSocket(connectUrl)
       wsRef.current = ws
 
       ws.onopen = () => {
@@ -66,6 +83,24 @@ export function useWebSocket(options: UseWebSocketOptions = {}) {
       ws.onmessage = (event) => {
         try {
           const message: WsMessage = JSON.parse(event.data)
+
+          if (message.type === 'session') {
+            // New session: a resume was not possible (or this is the first connect)
+            resumeTokenRef.current = message.resume_token
+            lastSeqRef.current = message.last_seq
+            ackedSeqRef.current = message.last_seq
+          } else if (message.type === 'event' && message.seq !== undefined) {
+            // Drop duplicates delivered both by replay and before the disconnect
+            if (message.seq <= lastSeqRef.current) {
+              return
+            }
+            lastSeqRef.current = message.seq
+            if (message.seq - ackedSeqRef.current >= ackInterval) {
+              ackedSeqRef.current = message.seq
+              ws.send(JSON.stringify({ type: 'ack', seq: message.seq }))
+            }
+          }
+
           setLastMessage(message)
 
           if (message.type === 'subscribed') {
@@ -108,7 +143,7 @@ export function useWebSocket(options: UseWebSocketOptions = {}) {
         // Silently handle - connection will retry automatically
       }
     }
-  }, [url, onMessage, onError, onOpen, onClose, reconnectInterval, maxReconnectAttempts])
+  }, [url, onMessage, onError, onOpen, onClose, reconnectInterval, maxReconnectAttempts, ackInterval])
 
   const disconnect = useCallback(() => {
     if (reconnectTimeoutRef.current) {
@@ -116,11 +151,15 @@ export function useWebSocket(options: UseWebSocketOptions = {}) {
       reconnectTimeoutRef.current = null
     }
     if (wsRef.current) {
+      // An explicit close ends the session on the server, so don't try to resume it
       wsRef.current.close()
       wsRef.current = null
     }
     setIsConnected(false)
     subscriptionsRef.current.clear()
+    resumeTokenRef.current = null
+    lastSeqRef.current = 0
+    ackedSeqRef.current = 0
   }, [])
 
   const sendMessage = useCallback(
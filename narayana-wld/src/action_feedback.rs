@@ -0,0 +1,327 @@
+//! Action feedback: World → brain outcome flow
+//!
+//! Every action the broker sends gets an action ID. Protocol adapters report
+//! what happened (success, failure, or an observed effect) against that ID,
+//! and each result is stored as an experience in the cognitive brain, which
+//! forwards it to its RL engine when one is attached.
+
+use crate::event_transformer::WorldAction;
+use narayana_core::Error;
+use narayana_storage::cognitive::CognitiveBrain;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value as JsonValue};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use parking_lot::Mutex;
+use tokio::sync::broadcast;
+use tracing::debug;
+
+/// Actions tracked for result correlation; oldest are forgotten first
+const MAX_TRACKED_ACTIONS: usize = 10_000;
+/// How long results are accepted after an action is sent
+const ACTION_RESULT_TTL: Duration = Duration::from_secs(600);
+/// Experience type under which action results are stored
+pub const ACTION_RESULT_EXPERIENCE: &str = "world_action_result";
+
+/// Identifier assigned to each action sent through the broker
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ActionId(pub String);
+
+impl ActionId {
+    pub fn new() -> Self {
+        Self(uuid::Uuid::new_v4().to_string())
+    }
+}
+
+impl Default for ActionId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for ActionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// What an adapter observed about an action
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionStatus {
+    /// The action was carried out
+    Success,
+    /// The action could not be carried out
+    Failure,
+    /// An effect of the action was observed (may follow a success)
+    Observed,
+}
+
+impl ActionStatus {
+    /// Reward used when the adapter doesn't supply one
+    fn default_reward(self) -> Option<f64> {
+        match self {
+            ActionStatus::Success => Some(1.0),
+            ActionStatus::Failure => Some(-1.0),
+            ActionStatus::Observed => None,
+        }
+    }
+
+    /// Whether this status settles the action
+    fn is_final(self) -> bool {
+        !matches!(self, ActionStatus::Observed)
+    }
+}
+
+/// Result reported by a protocol adapter for an action
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionResult {
+    pub action_id: ActionId,
+    pub status: ActionStatus,
+    /// Observed effect of the action in the world
+    #[serde(default)]
+    pub effect: Option<JsonValue>,
+    /// Failure reason
+    #[serde(default)]
+    pub error: Option<String>,
+    /// Reward in [-1.0, 1.0]; defaults to +1 for success, -1 for failure
+    #[serde(default)]
+    pub reward: Option<f64>,
+    /// Adapter that reported the result
+    #[serde(default)]
+    pub reported_by: String,
+}
+
+impl ActionResult {
+    pub fn success(action_id: ActionId, effect: Option<JsonValue>) -> Self {
+        Self::new(action_id, ActionStatus::Success, effect, None)
+    }
+
+    pub fn failure(action_id: ActionId, error: impl Into<String>) -> Self {
+        Self::new(action_id, ActionStatus::Failure, None, Some(error.into()))
+    }
+
+    pub fn observed(action_id: ActionId, effect: JsonValue) -> Self {
+        Self::new(action_id, ActionStatus::Observed, Some(effect), None)
+    }
+
+    fn new(action_id: ActionId, status: ActionStatus, effect: Option<JsonValue>, error: Option<String>) -> Self {
+        Self {
+            action_id,
+            status,
+            effect,
+            error,
+            reward: None,
+            reported_by: String::new(),
+        }
+    }
+
+    /// Set an explicit reward
+    pub fn with_reward(mut self, reward: f64) -> Self {
+        self.reward = Some(reward);
+        self
+    }
+
+    /// Set the reporting adapter
+    pub fn with_reporter(mut self, reporter: impl Into<String>) -> Self {
+        self.reported_by = reporter.into();
+        self
+    }
+
+    /// Parse a result reported as JSON, e.g. `{"status": "success", "effect": {...}}`
+    pub fn from_json(action_id: ActionId, payload: &JsonValue, reporter: &str) -> Result<Self, Error> {
+        let status = payload.get("status")
+            .cloned()
+            .ok_or_else(|| Error::Storage("Missing 'status' field".to_string()))?;
+        let status: ActionStatus = serde_json::from_value(status)
+            .map_err(|_| Error::Storage("status must be 'success', 'failure' or 'observed'".to_string()))?;
+        let error = match payload.get("error") {
+            None | Some(JsonValue::Null) => None,
+            Some(JsonValue::String(e)) => Some(e.clone()),
+            Some(_) => return Err(Error::Storage("error must be a string".to_string())),
+        };
+        let reward = match payload.get("reward") {
+            None | Some(JsonValue::Null) => None,
+            Some(r) => Some(r.as_f64().ok_or_else(|| Error::Storage("reward must be a number".to_string()))?),
+        };
+        let result = Self {
+            action_id,
+            status,
+            effect: payload.get("effect").filter(|e| !e.is_null()).cloned(),
+            error,
+            reward,
+            reported_by: reporter.to_string(),
+        };
+        result.validate()?;
+        Ok(result)
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        if self.action_id.0.is_empty() || self.action_id.0.len() > 128 {
+            return Err(Error::Storage("Invalid action_id".to_string()));
+        }
+        if self.reported_by.len() > 64 {
+            return Err(Error::Storage("Invalid reporter name".to_string()));
+        }
+        if self.error.as_ref().is_some_and(|e| e.len() > 10_000) {
+            return Err(Error::Storage("Error message too large".to_string()));
+        }
+        if let Some(effect) = &self.effect {
+            let size = serde_json::to_string(effect)
+                .map_err(|e| Error::Storage(format!("Invalid effect JSON: {}", e)))?
+                .len();
+            if size > 1_000_000 {
+                return Err(Error::Storage("Effect payload too large".to_string()));
+            }
+        }
+        if let Some(reward) = self.reward {
+            if !reward.is_finite() || !(-1.0..=1.0).contains(&reward) {
+                return Err(Error::Storage("Reward must be between -1.0 and 1.0".to_string()));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A result correlated with the action it reports on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionOutcome {
+    pub action: WorldAction,
+    pub result: ActionResult,
+    /// Time from sending the action to the report
+    pub latency_ms: u64,
+    /// Experience the result was stored as
+    pub experience_id: String,
+}
+
+struct TrackedAction {
+    action: WorldAction,
+    sent_at: Instant,
+    settled: bool,
+}
+
+#[derive(Default)]
+struct Tracked {
+    actions: HashMap<ActionId, TrackedAction>,
+    order: VecDeque<ActionId>,
+}
+
+impl Tracked {
+    fn expire(&mut self, now: Instant) {
+        while let Some(id) = self.order.front() {
+            let expired = self.actions.get(id)
+                .is_none_or(|a| now.duration_since(a.sent_at) > ACTION_RESULT_TTL);
+            if !expired && self.order.len() <= MAX_TRACKED_ACTIONS {
+                break;
+            }
+            if let Some(id) = self.order.pop_front() {
+                self.actions.remove(&id);
+            }
+        }
+    }
+}
+
+/// Correlates adapter results with sent actions and feeds them to the brain
+pub struct ActionFeedback {
+    brain: Arc<CognitiveBrain>,
+    tracked: Mutex<Tracked>,
+    outcome_sender: broadcast::Sender<ActionOutcome>,
+}
+
+impl ActionFeedback {
+    pub fn new(brain: Arc<CognitiveBrain>) -> Self {
+        let (outcome_sender, _) = broadcast::channel(1000);
+        Self {
+            brain,
+            tracked: Mutex::new(Tracked::default()),
+            outcome_sender,
+        }
+    }
+
+    /// Start tracking an action and return its ID
+    pub fn track(&self, action: WorldAction) -> ActionId {
+        let id = ActionId::new();
+        let now = Instant::now();
+        let mut tracked = self.tracked.lock();
+        tracked.actions.insert(id.clone(), TrackedAction { action, sent_at: now, settled: false });
+        tracked.order.push_back(id.clone());
+        tracked.expire(now);
+        id
+    }
+
+    /// Whether results are still accepted for an action
+    pub fn is_tracked(&self, action_id: &ActionId) -> bool {
+        let mut tracked = self.tracked.lock();
+        tracked.expire(Instant::now());
+        tracked.actions.contains_key(action_id)
+    }
+
+    /// Number of actions still awaiting a success or failure report
+    pub fn pending_count(&self) -> usize {
+        let mut tracked = self.tracked.lock();
+        tracked.expire(Instant::now());
+        tracked.actions.values().filter(|a| !a.settled).count()
+    }
+
+    /// Correlate a result with its action and store it as an experience
+    ///
+    /// Fails for unknown or expired action IDs, and for a second success or
+    /// failure on an action that is already settled. Observed effects are
+    /// accepted any time the action is tracked.
+    pub fn report(&self, result: ActionResult) -> Result<ActionOutcome, Error> {
+        result.validate()?;
+
+        let (action, latency) = {
+            let now = Instant::now();
+            let mut tracked = self.tracked.lock();
+            tracked.expire(now);
+            let entry = tracked.actions.get_mut(&result.action_id)
+                .ok_or_else(|| Error::Storage(format!("Unknown or expired action: {}", result.action_id)))?;
+            if entry.settled && result.status.is_final() {
+                return Err(Error::Storage(format!("Action {} already has a result", result.action_id)));
+            }
+            entry.settled |= result.status.is_final();
+            (entry.action.clone(), now.duration_since(entry.sent_at))
+        };
+        let latency_ms = latency.as_millis() as u64;
+
+        let reward = result.reward.or_else(|| result.status.default_reward());
+        let experience_id = self.brain.store_experience(
+            ACTION_RESULT_EXPERIENCE.to_string(),
+            json!({
+                "effect": result.effect,
+                "reported_by": result.reported_by,
+                "latency_ms": latency_ms,
+                "reported_at": SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs(),
+            }),
+            Some(json!({
+                "action_id": result.action_id,
+                "action": action,
+            })),
+            Some(json!({
+                "status": result.status,
+                "error": result.error,
+            })),
+            reward,
+            None,
+        )?;
+        debug!("Stored result of action {} as experience {}", result.action_id, experience_id);
+
+        let outcome = ActionOutcome { action, result, latency_ms, experience_id };
+        if self.outcome_sender.send(outcome.clone()).is_err() {
+            // No subscribers is the common case
+            debug!("No action outcome subscribers");
+        }
+        Ok(outcome)
+    }
+
+    /// Subscribe to correlated action outcomes
+    pub fn subscribe(&self) -> broadcast::Receiver<ActionOutcome> {
+        self.outcome_sender.subscribe()
+    }
+}
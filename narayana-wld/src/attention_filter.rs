@@ -6,6 +6,7 @@
 //! - Desimone & Duncan (1995): Relevance and attention
 //! - Friston (2010): Prediction error
 
+use crate::emotion::EmotionBus;
 use crate::event_transformer::WorldEvent;
 use narayana_core::Error;
 use narayana_storage::cognitive::CognitiveBrain;
@@ -21,6 +22,7 @@ pub struct AttentionFilter {
     config: AttentionFilterConfig,
     event_history: Arc<RwLock<VecDeque<EventHistoryEntry>>>,
     predictions: Arc<RwLock<PredictionModel>>,
+    emotion: Arc<RwLock<Option<Arc<EmotionBus>>>>,
 }
 
 #[derive(Debug, Clone)]
@@ -72,9 +74,21 @@ impl AttentionFilter {
                 last_event_type: None,
                 total_events: 0,
             })),
+            emotion: Arc::new(RwLock::new(None)),
         }
     }
 
+    /// Let the shared emotion state modulate salience: high arousal raises
+    /// urgency, negative valence raises sensitivity to magnitude
+    pub fn set_emotion_bus(&self, bus: Arc<EmotionBus>) {
+        *self.emotion.write() = Some(bus);
+    }
+
+    /// Stop emotional modulation
+    pub fn remove_emotion_bus(&self) {
+        *self.emotion.write() = None;
+    }
+
     /// Compute salience for a world event
     pub fn compute_salience(&self, event: &WorldEvent) -> Result<f64, Error> {
         let event_type = self.get_event_type(event);
@@ -93,6 +107,15 @@ impl AttentionFilter {
         let relevance = if relevance.is_finite() { relevance.clamp(0.0, 1.0) } else { 0.0 };
         let magnitude = if magnitude.is_finite() { magnitude.clamp(0.0, 1.0) } else { 0.0 };
         let prediction_error = if prediction_error.is_finite() { prediction_error.clamp(0.0, 1.0) } else { 0.0 };
+
+        // Emotional modulation (vigilance when aroused or distressed)
+        let (urgency, magnitude) = match self.emotion.read().as_ref().map(|bus| bus.current()) {
+            Some(emotion) => (
+                (urgency * (1.0 + 0.25 * emotion.arousal)).clamp(0.0, 1.0),
+                (magnitude * (1.0 + 0.25 * (-emotion.valence).max(0.0))).clamp(0.0, 1.0),
+            ),
+            None => (urgency, magnitude),
+        };
         
         // Weighted combination with bounds checking
         let salience = 
@@ -0,0 +1,307 @@
+//! Emotion state bus
+//!
+//! One shared affective state for the whole agent, in the PAD model
+//! (Mehrabian & Russell, 1974): valence, arousal and dominance, each in
+//! [-1.0, 1.0]. Any crate can nudge or set it; speech prosody, avatar
+//! expression and the attention filter subscribe to it. Without new input the
+//! state decays exponentially back to its baseline.
+
+use narayana_core::Error;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use parking_lot::RwLock;
+use tokio::sync::broadcast;
+use tracing::debug;
+
+/// Smallest change worth publishing from decay alone
+const DECAY_PUBLISH_EPSILON: f64 = 0.01;
+
+/// Emotion dimensions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmotionDimension {
+    /// Pleasantness: negative (distress) to positive (pleasure)
+    Valence,
+    /// Activation: calm/drowsy to excited/alert
+    Arousal,
+    /// Sense of control: submissive to dominant
+    Dominance,
+}
+
+/// Point in emotion space
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct EmotionState {
+    pub valence: f64,
+    pub arousal: f64,
+    pub dominance: f64,
+}
+
+/// Discrete label for the region of emotion space a state falls in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmotionLabel {
+    Neutral,
+    Joy,
+    Contentment,
+    Interest,
+    Surprise,
+    Anger,
+    Fear,
+    Sadness,
+}
+
+impl EmotionState {
+    pub fn new(valence: f64, arousal: f64, dominance: f64) -> Self {
+        Self { valence, arousal, dominance }
+    }
+
+    pub fn get(&self, dimension: EmotionDimension) -> f64 {
+        match dimension {
+            EmotionDimension::Valence => self.valence,
+            EmotionDimension::Arousal => self.arousal,
+            EmotionDimension::Dominance => self.dominance,
+        }
+    }
+
+    fn get_mut(&mut self, dimension: EmotionDimension) -> &mut f64 {
+        match dimension {
+            EmotionDimension::Valence => &mut self.valence,
+            EmotionDimension::Arousal => &mut self.arousal,
+            EmotionDimension::Dominance => &mut self.dominance,
+        }
+    }
+
+    /// Distance from neutral, in [0.0, 1.0]
+    pub fn intensity(&self) -> f64 {
+        let squared = self.valence.powi(2) + self.arousal.powi(2) + self.dominance.powi(2);
+        (squared / 3.0).sqrt().clamp(0.0, 1.0)
+    }
+
+    /// Discrete label for this state
+    pub fn label(&self) -> EmotionLabel {
+        let EmotionState { valence, arousal, dominance } = *self;
+        if self.intensity() < 0.1 {
+            EmotionLabel::Neutral
+        } else if valence >= 0.2 {
+            if arousal >= 0.0 { EmotionLabel::Joy } else { EmotionLabel::Contentment }
+        } else if valence <= -0.2 {
+            if arousal < 0.0 {
+                EmotionLabel::Sadness
+            } else if dominance >= 0.0 {
+                EmotionLabel::Anger
+            } else {
+                EmotionLabel::Fear
+            }
+        } else if arousal >= 0.5 {
+            EmotionLabel::Surprise
+        } else if arousal >= 0.1 {
+            EmotionLabel::Interest
+        } else {
+            EmotionLabel::Neutral
+        }
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        if [self.valence, self.arousal, self.dominance].iter().any(|v| !v.is_finite()) {
+            return Err(Error::Storage("Emotion values must be finite numbers".to_string()));
+        }
+        Ok(())
+    }
+
+    fn clamped(self) -> Self {
+        Self {
+            valence: self.valence.clamp(-1.0, 1.0),
+            arousal: self.arousal.clamp(-1.0, 1.0),
+            dominance: self.dominance.clamp(-1.0, 1.0),
+        }
+    }
+
+    fn max_difference(&self, other: &EmotionState) -> f64 {
+        (self.valence - other.valence).abs()
+            .max((self.arousal - other.arousal).abs())
+            .max((self.dominance - other.dominance).abs())
+    }
+}
+
+/// Emotion bus configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmotionConfig {
+    /// State the emotion decays back to
+    pub baseline: EmotionState,
+    /// Time for the distance from baseline to halve
+    pub half_life_ms: u64,
+}
+
+impl Default for EmotionConfig {
+    fn default() -> Self {
+        Self {
+            baseline: EmotionState::default(),
+            half_life_ms: 30_000,
+        }
+    }
+}
+
+/// Published whenever the emotion state changes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmotionUpdate {
+    pub state: EmotionState,
+    pub label: EmotionLabel,
+    pub intensity: f64,
+    /// Component that caused the change ("decay" for decay alone)
+    pub source: String,
+    pub timestamp: u64,
+}
+
+struct Current {
+    state: EmotionState,
+    updated_at: Instant,
+    /// Last state sent to subscribers
+    published: EmotionState,
+}
+
+/// Shared emotion state with decay and subscriptions
+pub struct EmotionBus {
+    config: EmotionConfig,
+    current: RwLock<Current>,
+    sender: broadcast::Sender<EmotionUpdate>,
+}
+
+impl EmotionBus {
+    pub fn new(config: EmotionConfig) -> Result<Self, Error> {
+        config.baseline.validate()?;
+        if config.half_life_ms == 0 {
+            return Err(Error::Storage("Emotion half_life_ms must be greater than 0".to_string()));
+        }
+        let baseline = config.baseline.clamped();
+        let (sender, _) = broadcast::channel(256);
+        Ok(Self {
+            config,
+            current: RwLock::new(Current {
+                state: baseline,
+                updated_at: Instant::now(),
+                published: baseline,
+            }),
+            sender,
+        })
+    }
+
+    /// Current state, with decay applied up to now
+    pub fn current(&self) -> EmotionState {
+        let current = self.current.read();
+        self.decayed(current.state, current.updated_at.elapsed())
+    }
+
+    /// Current value of one dimension
+    pub fn dimension(&self, dimension: EmotionDimension) -> f64 {
+        self.current().get(dimension)
+    }
+
+    /// Replace the state
+    pub fn set(&self, state: EmotionState, source: &str) -> Result<EmotionState, Error> {
+        state.validate()?;
+        Ok(self.update(source, |_| state))
+    }
+
+    /// Add a delta to every dimension of the (decayed) state
+    pub fn nudge(&self, delta: EmotionState, source: &str) -> Result<EmotionState, Error> {
+        delta.validate()?;
+        Ok(self.update(source, |s| EmotionState {
+            valence: s.valence + delta.valence,
+            arousal: s.arousal + delta.arousal,
+            dominance: s.dominance + delta.dominance,
+        }))
+    }
+
+    /// Add a delta to one dimension of the (decayed) state
+    pub fn nudge_dimension(&self, dimension: EmotionDimension, delta: f64, source: &str) -> Result<EmotionState, Error> {
+        if !delta.is_finite() {
+            return Err(Error::Storage("Emotion values must be finite numbers".to_string()));
+        }
+        Ok(self.update(source, |mut s| {
+            *s.get_mut(dimension) += delta;
+            s
+        }))
+    }
+
+    /// Subscribe to emotion updates
+    pub fn subscribe(&self) -> broadcast::Receiver<EmotionUpdate> {
+        self.sender.subscribe()
+    }
+
+    /// Publish decay to subscribers every `interval` while the bus is alive
+    pub fn start_decay(self: &Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let bus: Weak<Self> = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let Some(bus) = bus.upgrade() else {
+                    break;
+                };
+                bus.publish_decay();
+            }
+        })
+    }
+
+    /// Publish the decayed state if it moved noticeably since the last update
+    pub fn publish_decay(&self) {
+        let state = {
+            let mut current = self.current.write();
+            let state = self.decayed(current.state, current.updated_at.elapsed());
+            if state.max_difference(&current.published) < DECAY_PUBLISH_EPSILON {
+                return;
+            }
+            current.published = state;
+            state
+        };
+        self.publish(state, "decay");
+    }
+
+    fn update(&self, source: &str, f: impl FnOnce(EmotionState) -> EmotionState) -> EmotionState {
+        let state = {
+            let mut current = self.current.write();
+            let decayed = self.decayed(current.state, current.updated_at.elapsed());
+            let state = f(decayed).clamped();
+            current.state = state;
+            current.updated_at = Instant::now();
+            current.published = state;
+            state
+        };
+        self.publish(state, source);
+        state
+    }
+
+    fn publish(&self, state: EmotionState, source: &str) {
+        let source: String = source.chars().take(64).collect();
+        debug!("Emotion updated by {}: {:?}", source, state);
+        // No subscribers is fine
+        let _ = self.sender.send(EmotionUpdate {
+            state,
+            label: state.label(),
+            intensity: state.intensity(),
+            source,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+        });
+    }
+
+    fn decayed(&self, state: EmotionState, elapsed: Duration) -> EmotionState {
+        let factor = 0.5f64.powf(elapsed.as_millis() as f64 / self.config.half_life_ms as f64);
+        let baseline = self.config.baseline.clamped();
+        let toward = |value: f64, base: f64| base + (value - base) * factor;
+        EmotionState {
+            valence: toward(state.valence, baseline.valence),
+            arousal: toward(state.arousal, baseline.arousal),
+            dominance: toward(state.dominance, baseline.dominance),
+        }
+    }
+}
+
+impl Default for EmotionBus {
+    fn default() -> Self {
+        Self::new(EmotionConfig::default()).expect("default emotion config is valid")
+    }
+}
@@ -16,14 +16,18 @@ pub mod event_transformer;
 pub mod attention_filter;
 pub mod config;
 pub mod protocol_adapters;
+pub mod action_feedback;
+pub mod emotion;
 
-pub use world_broker::{WorldBroker, WorldBrokerHandle};
+pub use world_broker::{StreamPrediction, WorldBroker, WorldBrokerHandle};
 pub use config::WorldBrokerConfig;
 pub use event_transformer::{WorldEvent, WorldAction, EventTransformer};
 pub use attention_filter::AttentionFilter;
 pub use sensory_interface::SensoryInterface;
 pub use motor_interface::MotorInterface;
-pub use protocol_adapters::{ProtocolAdapter, HttpAdapter, WebSocketAdapter};
+pub use action_feedback::{ActionFeedback, ActionId, ActionOutcome, ActionResult, ActionStatus};
+pub use emotion::{EmotionBus, EmotionConfig, EmotionDimension, EmotionLabel, EmotionState, EmotionUpdate};
+pub use protocol_adapters::{ProtocolAdapter, HttpAdapter, WebSocketAdapter, SimulationAdapter, SimulationConfig};
 
 #[cfg(test)]
 mod tests;
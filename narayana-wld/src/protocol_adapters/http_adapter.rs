@@ -1,11 +1,12 @@
 //! HTTP/REST protocol adapter
 
+use crate::action_feedback::{ActionId, ActionResult};
 use crate::event_transformer::{WorldEvent, WorldAction};
 use crate::world_broker::WorldBrokerHandle;
 use narayana_core::Error;
 use async_trait::async_trait;
 use axum::{
-    extract::State,
+    extract::{Path, State},
     http::StatusCode,
     response::Json,
     routing::{get, post},
@@ -55,6 +56,7 @@ impl crate::protocol_adapters::ProtocolAdapter for HttpAdapter {
         // Create router
         let app = Router::new()
             .route("/world/events", post(handle_event))
+            .route("/world/actions/:action_id/result", post(handle_action_result))
             .route("/world/health", get(health_check))
             .with_state(HttpAdapterState {
                 event_sender: sender,
@@ -148,6 +150,36 @@ async fn handle_event(
     Ok(Json(json!({"status": "ok"})))
 }
 
+async fn handle_action_result(
+    State(state): State<HttpAdapterState>,
+    Path(action_id): Path<String>,
+    Json(payload): Json<JsonValue>,
+) -> Result<Json<JsonValue>, StatusCode> {
+    let action_id = ActionId(action_id);
+    if !state.broker.is_action_tracked(&action_id) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let result = match ActionResult::from_json(action_id, &payload, "http") {
+        Ok(r) => r,
+        Err(e) => {
+            warn!("Failed to parse action result: {}", e);
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    };
+
+    match state.broker.report_action_result(result) {
+        Ok(outcome) => Ok(Json(json!({
+            "status": "ok",
+            "experience_id": outcome.experience_id,
+        }))),
+        Err(e) => {
+            warn!("Failed to record action result: {}", e);
+            Err(StatusCode::CONFLICT)
+        }
+    }
+}
+
 async fn health_check() -> Json<JsonValue> {
     Json(json!({"status": "healthy", "protocol": "http"}))
 }
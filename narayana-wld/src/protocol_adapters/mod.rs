@@ -2,7 +2,9 @@
 
 pub mod http_adapter;
 pub mod websocket_adapter;
+pub mod simulation_adapter;
 
+use crate::action_feedback::ActionId;
 use crate::event_transformer::{WorldEvent, WorldAction};
 use narayana_core::Error;
 use async_trait::async_trait;
@@ -35,6 +37,13 @@ pub trait ProtocolAdapter: Send + Sync {
     /// Send action to external system
     async fn send_action(&self, action: WorldAction) -> Result<(), Error>;
 
+    /// Send an action along with the ID its result should be reported against
+    /// Default implementation drops the ID - adapters that report results
+    /// through `WorldBrokerHandle::report_action_result` should override.
+    async fn send_tracked_action(&self, _action_id: &ActionId, action: WorldAction) -> Result<(), Error> {
+        self.send_action(action).await
+    }
+
     /// Subscribe to incoming events
     fn subscribe_events(&self) -> broadcast::Receiver<WorldEvent>;
     
@@ -93,6 +102,7 @@ pub trait ProtocolAdapter: Send + Sync {
 
 pub use http_adapter::HttpAdapter;
 pub use websocket_adapter::WebSocketAdapter;
+pub use simulation_adapter::{SimulationAdapter, SimulationConfig};
 
 
 
@@ -0,0 +1,357 @@
+//! Simulation protocol adapter
+//!
+//! Generates synthetic sensory events from scripted scenarios and seeded
+//! random walks, and records every action it is asked to send, so the broker
+//! can be exercised end to end without hardware. Runs are deterministic for a
+//! given config: time advances in ticks, either on a timer or one `step` at a
+//! time.
+
+use crate::action_feedback::{ActionId, ActionResult, ActionStatus};
+use crate::event_transformer::{WorldEvent, WorldAction};
+use crate::world_broker::WorldBrokerHandle;
+use narayana_core::Error;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::broadcast;
+use parking_lot::{Mutex, RwLock};
+use tracing::{info, warn};
+
+/// Actions kept for inspection; oldest are dropped first
+const MAX_RECORDED_ACTIONS: usize = 10_000;
+
+/// An event injected at a fixed tick
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptedEvent {
+    pub tick: u64,
+    pub event: WorldEvent,
+}
+
+/// A sensor whose reading moves by a random step each time it fires
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RandomWalk {
+    pub source: String,
+    /// Field of the sensor data holding the reading
+    #[serde(default = "default_walk_field")]
+    pub field: String,
+    pub start: f64,
+    /// Largest change per reading, in either direction
+    pub max_step: f64,
+    pub min: f64,
+    pub max: f64,
+    /// Fire every N ticks
+    #[serde(default = "default_every_ticks")]
+    pub every_ticks: u64,
+}
+
+fn default_walk_field() -> String {
+    "value".to_string()
+}
+
+fn default_every_ticks() -> u64 {
+    1
+}
+
+/// Configuration for a simulation run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationConfig {
+    /// Seed for the random walks
+    pub seed: u64,
+    /// Simulated time per tick
+    pub tick_interval_ms: u64,
+    /// Advance ticks on a timer once started; otherwise only `step` advances
+    pub realtime: bool,
+    /// Stop generating after this many ticks
+    pub max_ticks: Option<u64>,
+    /// Unix time (seconds) of tick 0; defaults to the wall clock at start
+    pub epoch: Option<u64>,
+    pub scripted: Vec<ScriptedEvent>,
+    pub random_walks: Vec<RandomWalk>,
+    /// Report this status for every action sent through the adapter
+    pub auto_report: Option<ActionStatus>,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            tick_interval_ms: 100,
+            realtime: false,
+            max_ticks: None,
+            epoch: None,
+            scripted: Vec::new(),
+            random_walks: Vec::new(),
+            auto_report: None,
+        }
+    }
+}
+
+impl SimulationConfig {
+    /// Validate configuration values
+    pub fn validate(&self) -> Result<(), String> {
+        if self.tick_interval_ms == 0 {
+            return Err("tick_interval_ms must be greater than 0".to_string());
+        }
+        for walk in &self.random_walks {
+            if walk.source.is_empty() || walk.source.len() > 256 {
+                return Err("random walk source must be 1-256 characters".to_string());
+            }
+            if walk.every_ticks == 0 {
+                return Err(format!("random walk '{}': every_ticks must be greater than 0", walk.source));
+            }
+            if !(walk.min <= walk.start && walk.start <= walk.max) || walk.max_step < 0.0 {
+                return Err(format!("random walk '{}': start must lie within [min, max] and max_step must be >= 0", walk.source));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An action the adapter was asked to send
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedAction {
+    /// Tick the action arrived at
+    pub tick: u64,
+    pub action_id: Option<ActionId>,
+    pub action: WorldAction,
+}
+
+/// SplitMix64: small, seedable and identical on every platform
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in [-1.0, 1.0)
+    fn next_signed_unit(&mut self) -> f64 {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) * 2.0 - 1.0
+    }
+}
+
+struct SimulationState {
+    tick: u64,
+    epoch: u64,
+    rng: Rng,
+    readings: Vec<f64>,
+    recorded: VecDeque<RecordedAction>,
+}
+
+/// Simulation adapter for headless runs and integration tests
+///
+/// Clones share the same simulation, so a test can keep one to drive and
+/// inspect while the broker owns another.
+#[derive(Clone)]
+pub struct SimulationAdapter {
+    config: SimulationConfig,
+    state: Arc<Mutex<SimulationState>>,
+    event_sender: broadcast::Sender<WorldEvent>,
+    broker: Arc<RwLock<Option<WorldBrokerHandle>>>,
+    is_running: Arc<RwLock<bool>>,
+}
+
+impl SimulationAdapter {
+    pub fn new(config: SimulationConfig) -> Result<Self, Error> {
+        config.validate()
+            .map_err(|e| Error::Storage(format!("Invalid simulation config: {}", e)))?;
+        let (event_sender, _) = broadcast::channel(1000);
+        let state = SimulationState {
+            tick: 0,
+            epoch: config.epoch.unwrap_or(0),
+            rng: Rng(config.seed),
+            readings: config.random_walks.iter().map(|w| w.start).collect(),
+            recorded: VecDeque::new(),
+        };
+        Ok(Self {
+            config,
+            state: Arc::new(Mutex::new(state)),
+            event_sender,
+            broker: Arc::new(RwLock::new(None)),
+            is_running: Arc::new(RwLock::new(false)),
+        })
+    }
+
+    /// Current tick (number of ticks generated so far)
+    pub fn tick(&self) -> u64 {
+        self.state.lock().tick
+    }
+
+    /// Advance one tick and deliver its events to the broker (if started)
+    ///
+    /// Returns the generated events; empty once `max_ticks` is reached.
+    pub async fn step(&self) -> Result<Vec<WorldEvent>, Error> {
+        let events = generate_tick(&self.config, &self.state);
+        let broker = self.broker.read().clone();
+        deliver(&events, broker.as_ref(), &self.event_sender).await?;
+        Ok(events)
+    }
+
+    /// Advance `ticks` ticks, returning all generated events
+    pub async fn run_ticks(&self, ticks: u64) -> Result<Vec<WorldEvent>, Error> {
+        let mut events = Vec::new();
+        for _ in 0..ticks {
+            events.extend(self.step().await?);
+        }
+        Ok(events)
+    }
+
+    /// Actions recorded so far, oldest first
+    pub fn recorded_actions(&self) -> Vec<RecordedAction> {
+        self.state.lock().recorded.iter().cloned().collect()
+    }
+
+    /// Clear recorded actions
+    pub fn clear_recorded_actions(&self) {
+        self.state.lock().recorded.clear();
+    }
+
+    fn record(&self, action_id: Option<ActionId>, action: WorldAction) -> Result<(), Error> {
+        {
+            let mut state = self.state.lock();
+            if state.recorded.len() >= MAX_RECORDED_ACTIONS {
+                state.recorded.pop_front();
+            }
+            let tick = state.tick;
+            state.recorded.push_back(RecordedAction { tick, action_id: action_id.clone(), action });
+        }
+
+        let (Some(status), Some(action_id)) = (self.config.auto_report, action_id) else {
+            return Ok(());
+        };
+        let broker = self.broker.read().clone()
+            .ok_or_else(|| Error::Storage("Simulation adapter not running".to_string()))?;
+        let result = match status {
+            ActionStatus::Success => ActionResult::success(action_id, None),
+            ActionStatus::Failure => ActionResult::failure(action_id, "simulated failure"),
+            ActionStatus::Observed => ActionResult::observed(action_id, json!({"simulated": true})),
+        };
+        broker.report_action_result(result.with_reporter("simulation"))?;
+        Ok(())
+    }
+}
+
+/// Generate the events of the next tick and advance the clock
+fn generate_tick(config: &SimulationConfig, state: &Mutex<SimulationState>) -> Vec<WorldEvent> {
+    let mut state = state.lock();
+    if config.max_ticks.is_some_and(|max| state.tick >= max) {
+        return Vec::new();
+    }
+    let tick = state.tick;
+    let timestamp = state.epoch + tick.saturating_mul(config.tick_interval_ms) / 1000;
+
+    let mut events: Vec<WorldEvent> = config.scripted.iter()
+        .filter(|s| s.tick == tick)
+        .map(|s| s.event.clone())
+        .collect();
+
+    for (i, walk) in config.random_walks.iter().enumerate() {
+        if !tick.is_multiple_of(walk.every_ticks) {
+            continue;
+        }
+        // The first reading is the start value
+        if tick > 0 {
+            let step = state.rng.next_signed_unit() * walk.max_step;
+            state.readings[i] = (state.readings[i] + step).clamp(walk.min, walk.max);
+        }
+        events.push(WorldEvent::SensorData {
+            source: walk.source.clone(),
+            data: json!({ walk.field.clone(): state.readings[i] }),
+            timestamp,
+        });
+    }
+
+    state.tick += 1;
+    events
+}
+
+async fn deliver(
+    events: &[WorldEvent],
+    broker: Option<&WorldBrokerHandle>,
+    event_sender: &broadcast::Sender<WorldEvent>,
+) -> Result<(), Error> {
+    for event in events {
+        if let Some(broker) = broker {
+            broker.process_world_event(event.clone()).await?;
+        }
+        // No subscribers is fine
+        let _ = event_sender.send(event.clone());
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl crate::protocol_adapters::ProtocolAdapter for SimulationAdapter {
+    fn protocol_name(&self) -> &str {
+        "simulation"
+    }
+
+    async fn start(&self, broker: WorldBrokerHandle) -> Result<(), Error> {
+        {
+            let mut running = self.is_running.write();
+            if *running {
+                return Err(Error::Storage("Simulation adapter already running".to_string()));
+            }
+            *running = true;
+        }
+        *self.broker.write() = Some(broker.clone());
+        if self.config.epoch.is_none() {
+            self.state.lock().epoch = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+        }
+
+        if self.config.realtime {
+            let config = self.config.clone();
+            let state = self.state.clone();
+            let event_sender = self.event_sender.clone();
+            let is_running = self.is_running.clone();
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(Duration::from_millis(config.tick_interval_ms));
+                while *is_running.read() {
+                    interval.tick().await;
+                    if !*is_running.read() {
+                        break;
+                    }
+                    if config.max_ticks.is_some_and(|max| state.lock().tick >= max) {
+                        break;
+                    }
+                    let events = generate_tick(&config, &state);
+                    if let Err(e) = deliver(&events, Some(&broker), &event_sender).await {
+                        warn!("Simulation adapter failed to deliver event: {}", e);
+                    }
+                }
+            });
+        }
+
+        info!("Simulation adapter started (realtime: {})", self.config.realtime);
+        Ok(())
+    }
+
+    async fn stop(&self) -> Result<(), Error> {
+        *self.is_running.write() = false;
+        *self.broker.write() = None;
+        info!("Simulation adapter stopped");
+        Ok(())
+    }
+
+    async fn send_action(&self, action: WorldAction) -> Result<(), Error> {
+        self.record(None, action)
+    }
+
+    async fn send_tracked_action(&self, action_id: &ActionId, action: WorldAction) -> Result<(), Error> {
+        self.record(Some(action_id.clone()), action)
+    }
+
+    fn subscribe_events(&self) -> broadcast::Receiver<WorldEvent> {
+        self.event_sender.subscribe()
+    }
+}
@@ -1,7 +1,9 @@
 //! WebSocket protocol adapter
 
+use crate::action_feedback::{ActionId, ActionOutcome, ActionResult};
 use crate::event_transformer::{WorldEvent, WorldAction};
 use crate::world_broker::WorldBrokerHandle;
+use narayana_core::wire::{WireFormat, WireFrame, WireTagged};
 use narayana_core::Error;
 use async_trait::async_trait;
 use serde_json::Value as JsonValue;
@@ -14,6 +16,7 @@ use tracing::{info, warn, debug};
 pub struct WebSocketAdapter {
     path: String,
     event_sender: Arc<RwLock<Option<broadcast::Sender<WorldEvent>>>>,
+    broker: Arc<RwLock<Option<WorldBrokerHandle>>>,
     is_running: Arc<RwLock<bool>>,
 }
 
@@ -22,9 +25,111 @@ impl WebSocketAdapter {
         Self {
             path,
             event_sender: Arc::new(RwLock::new(None)),
+            broker: Arc::new(RwLock::new(None)),
             is_running: Arc::new(RwLock::new(false)),
         }
     }
+
+    /// Sub-protocols to offer when upgrading a connection; binary formats first
+    pub fn subprotocols(&self) -> &'static [&'static str] {
+        &WireFormat::SUBPROTOCOLS
+    }
+
+    /// Decode a frame from a connection using its negotiated format and
+    /// publish the resulting event to subscribers
+    pub fn handle_frame(&self, format: WireFormat, frame: &WireFrame) -> Result<WorldEvent, Error> {
+        let event = decode_event(format, frame)?;
+        if let Some(sender) = self.event_sender.read().as_ref() {
+            let _ = sender.send(event.clone());
+        }
+        Ok(event)
+    }
+
+    /// Encode an action for a connection using its negotiated format
+    pub fn encode_action(&self, format: WireFormat, action: &WorldAction) -> Result<WireFrame, Error> {
+        format.encode(action)
+    }
+
+    /// Encode an action together with its ID, as `{"action_id": ..., "action": ...}`,
+    /// so the client can report the result back
+    pub fn encode_tracked_action(
+        &self,
+        format: WireFormat,
+        action_id: &ActionId,
+        action: &WorldAction,
+    ) -> Result<WireFrame, Error> {
+        format.encode_tagged(
+            action.wire_tag(),
+            &serde_json::json!({ "action_id": action_id, "action": action }),
+        )
+    }
+
+    /// Decode an action result frame from a connection and report it to the
+    /// broker: `{"type": "action_result", "action_id": ..., "status": ...}`
+    pub fn handle_result_frame(&self, format: WireFormat, frame: &WireFrame) -> Result<ActionOutcome, Error> {
+        if let Some(tag) = frame.tag() {
+            if tag != ACTION_RESULT_TAG {
+                return Err(Error::Deserialization(format!("Unexpected action result tag {:#04x}", tag)));
+            }
+        }
+        let (_, payload) = format.decode_tagged::<JsonValue>(frame)?;
+        if payload.get("type").and_then(|v| v.as_str()) != Some("action_result") {
+            return Err(Error::Deserialization("Expected an action_result frame".to_string()));
+        }
+        let action_id = payload.get("action_id")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| Error::Storage("Missing 'action_id' field".to_string()))?;
+        let result = ActionResult::from_json(ActionId(action_id.to_string()), &payload, "websocket")?;
+        let broker = self.broker.read().clone()
+            .ok_or_else(|| Error::Storage("WebSocket adapter not running".to_string()))?;
+        broker.report_action_result(result)
+    }
+}
+
+/// Binary frame type tags for incoming events, keyed by their `type` field
+const EVENT_TAGS: [(&str, u8); 4] = [
+    ("sensor", 0x01),
+    ("user_input", 0x02),
+    ("system", 0x03),
+    ("command", 0x04),
+];
+
+/// Binary frame type tag for action results reported by clients
+const ACTION_RESULT_TAG: u8 = 0x05;
+
+/// Binary frame type tags for outgoing actions
+impl WireTagged for WorldAction {
+    fn wire_tag(&self) -> u8 {
+        match self {
+            WorldAction::ActuatorCommand { .. } => 0x40,
+            WorldAction::UserResponse { .. } => 0x41,
+            WorldAction::SystemNotification { .. } => 0x42,
+            WorldAction::DataTransmission { .. } => 0x43,
+        }
+    }
+}
+
+/// Decode an event frame: JSON text, or a tagged MessagePack/CBOR frame whose
+/// payload has the same shape as the JSON object
+fn decode_event(format: WireFormat, frame: &WireFrame) -> Result<WorldEvent, Error> {
+    // Reject unknown tags before paying for the decode
+    if let Some(tag) = frame.tag() {
+        if !EVENT_TAGS.iter().any(|(_, t)| *t == tag) {
+            return Err(Error::Deserialization(format!("Unknown event type tag {:#04x}", tag)));
+        }
+    }
+    let (tag, payload) = format.decode_tagged::<JsonValue>(frame)?;
+    let event = parse_event_from_json(&payload)?;
+    if let Some(tag) = tag {
+        let event_type = payload.get("type").and_then(|v| v.as_str()).unwrap_or_default();
+        if !EVENT_TAGS.iter().any(|(name, t)| *t == tag && *name == event_type) {
+            return Err(Error::Deserialization(format!(
+                "Frame type tag {:#04x} does not match event type '{}'",
+                tag, event_type
+            )));
+        }
+    }
+    Ok(event)
 }
 
 #[async_trait]
@@ -33,13 +138,14 @@ impl crate::protocol_adapters::ProtocolAdapter for WebSocketAdapter {
         "websocket"
     }
 
-    async fn start(&self, _broker: WorldBrokerHandle) -> Result<(), Error> {
+    async fn start(&self, broker: WorldBrokerHandle) -> Result<(), Error> {
         if *self.is_running.read() {
             return Err(Error::Storage("WebSocket adapter already running".to_string()));
         }
 
         let (sender, _) = broadcast::channel(1000);
         *self.event_sender.write() = Some(sender.clone());
+        *self.broker.write() = Some(broker);
         *self.is_running.write() = true;
 
         info!("WebSocket adapter started on path: {}", self.path);
@@ -49,6 +155,7 @@ impl crate::protocol_adapters::ProtocolAdapter for WebSocketAdapter {
     async fn stop(&self) -> Result<(), Error> {
         *self.is_running.write() = false;
         *self.event_sender.write() = None;
+        *self.broker.write() = None;
         info!("WebSocket adapter stopped");
         Ok(())
     }
@@ -71,8 +178,9 @@ impl crate::protocol_adapters::ProtocolAdapter for WebSocketAdapter {
     }
 }
 
-// WebSocket handling would be implemented when integrated with HTTP server
-// For now, this is a placeholder adapter
+// Socket handling lives in the HTTP server integration; it negotiates one of
+// `subprotocols()` and passes frames through `handle_frame` / `encode_action`,
+// and action results through `handle_result_frame`
 
 fn parse_event_from_json(payload: &JsonValue) -> Result<WorldEvent, Error> {
     // Similar to HTTP adapter parsing
@@ -8,6 +8,14 @@ mod tests {
     use crate::attention_filter::{AttentionFilter, AttentionFilterConfig};
     use crate::config::WorldBrokerConfig;
     use crate::world_broker::WorldBroker;
+    use crate::protocol_adapters::ProtocolAdapter;
+    use crate::protocol_adapters::simulation_adapter::{
+        RandomWalk, ScriptedEvent, SimulationAdapter, SimulationConfig,
+    };
+    use crate::emotion::{EmotionBus, EmotionConfig, EmotionDimension, EmotionLabel, EmotionState};
+    use crate::action_feedback::{
+        ActionFeedback, ActionId, ActionResult, ActionStatus, ACTION_RESULT_EXPERIENCE,
+    };
     use serde_json::json;
     use std::sync::Arc;
     use parking_lot::RwLock;
@@ -459,6 +467,32 @@ mod tests {
         // (Can't easily test this without creating a mock adapter)
     }
 
+    #[tokio::test]
+    async fn test_world_broker_stream_prediction() {
+        use narayana_storage::sensory_streams::{ChannelSpec, SensoryStreamManager, StreamSchema, WindowQuery};
+
+        let brain = create_test_brain();
+        let cpl = create_test_cpl(brain.clone());
+        let broker = WorldBroker::new(brain, cpl, WorldBrokerConfig::default()).unwrap();
+        assert!(broker.predict_stream("range", 500, 1.0).is_err());
+
+        let streams = Arc::new(SensoryStreamManager::new());
+        let channels = vec![ChannelSpec { name: "distance".to_string(), unit: "m".to_string() }];
+        streams.declare_stream("range", StreamSchema::new(channels, 10.0)).unwrap();
+        for t in (0..=1000).step_by(100) {
+            streams.push_sample("range", t, vec![10.0 - t as f64 / 200.0]).unwrap();
+        }
+        broker.set_sensory_streams(streams);
+
+        let window = broker.stream_window("range", &WindowQuery::last(1.0).until(1000)).unwrap();
+        assert_eq!(window.timestamps_ms.len(), 11);
+
+        let prediction = broker.predict_stream("range", 500, 1.0).unwrap();
+        assert_eq!(prediction.at_ms, 1500);
+        assert_eq!(prediction.based_on_samples, 11);
+        assert!((prediction.values[0] - 2.5).abs() < 1e-9);
+    }
+
     // ============================================================================
     // Sensory Interface Tests
     // ============================================================================
@@ -798,6 +832,307 @@ mod tests {
         // May sanitize unicode or handle it
     }
 
+    // ============================================================================
+    // WebSocket Wire Format Tests
+    // ============================================================================
+
+    #[test]
+    fn test_websocket_adapter_binary_frames() {
+        use crate::protocol_adapters::WebSocketAdapter;
+        use narayana_core::wire::{WireFormat, WireFrame};
+
+        let adapter = WebSocketAdapter::new("/wld/ws".to_string());
+        let payload = json!({"type": "sensor", "source": "cam", "data": {"lux": 42}, "timestamp": 7});
+
+        for format in [WireFormat::MessagePack, WireFormat::Cbor] {
+            let frame = format.encode_tagged(0x01, &payload).unwrap();
+            match adapter.handle_frame(format, &frame).unwrap() {
+                WorldEvent::SensorData { source, data, timestamp } => {
+                    assert_eq!(source, "cam");
+                    assert_eq!(data["lux"], 42);
+                    assert_eq!(timestamp, 7);
+                }
+                other => panic!("unexpected event: {:?}", other),
+            }
+
+            // Tag must match the event type
+            let mismatched = format.encode_tagged(0x04, &payload).unwrap();
+            assert!(adapter.handle_frame(format, &mismatched).is_err());
+            let unknown = format.encode_tagged(0x7f, &payload).unwrap();
+            assert!(adapter.handle_frame(format, &unknown).is_err());
+        }
+
+        // JSON text keeps working, and binary is refused on a JSON connection
+        let text = WireFrame::Text(payload.to_string());
+        assert!(adapter.handle_frame(WireFormat::Json, &text).is_ok());
+        let binary = WireFormat::MessagePack.encode_tagged(0x01, &payload).unwrap();
+        assert!(adapter.handle_frame(WireFormat::Json, &binary).is_err());
+
+        let action = WorldAction::UserResponse { user_id: "u1".to_string(), message: "hi".to_string() };
+        let frame = adapter.encode_action(WireFormat::Cbor, &action).unwrap();
+        assert_eq!(frame.tag(), Some(0x41));
+    }
+
+    // ============================================================================
+    // Action Feedback Tests
+    // ============================================================================
+
+    fn notification() -> WorldAction {
+        WorldAction::SystemNotification {
+            channel: "alerts".to_string(),
+            content: json!({"message": "open valve"}),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_action_result_stored_as_experience() {
+        let brain = create_test_brain();
+        let feedback = ActionFeedback::new(brain.clone());
+        let mut outcomes = feedback.subscribe();
+
+        let action_id = feedback.track(notification());
+        assert_eq!(feedback.pending_count(), 1);
+
+        let result = ActionResult::success(action_id.clone(), Some(json!({"valve": "open"})))
+            .with_reporter("http");
+        let outcome = feedback.report(result).unwrap();
+        assert_eq!(feedback.pending_count(), 0);
+        assert_eq!(outcomes.recv().await.unwrap().experience_id, outcome.experience_id);
+
+        let experience = brain.get_experience_transformed(&outcome.experience_id, None).unwrap();
+        assert_eq!(experience["event_type"], ACTION_RESULT_EXPERIENCE);
+        assert_eq!(experience["reward"], 1.0);
+        assert_eq!(experience["action"]["action_id"], action_id.0);
+        assert_eq!(experience["observation"]["effect"]["valve"], "open");
+        assert_eq!(experience["outcome"]["status"], "success");
+    }
+
+    #[tokio::test]
+    async fn test_action_result_correlation() {
+        let brain = create_test_brain();
+        let feedback = ActionFeedback::new(brain.clone());
+        let action_id = feedback.track(notification());
+
+        // Unknown IDs are rejected
+        let unknown = ActionResult::success(ActionId::new(), None);
+        assert!(feedback.report(unknown).is_err());
+
+        // Failures default to a negative reward; an explicit reward wins
+        let failed = feedback.report(ActionResult::failure(action_id.clone(), "valve stuck")).unwrap();
+        let experience = brain.get_experience_transformed(&failed.experience_id, None).unwrap();
+        assert_eq!(experience["reward"], -1.0);
+        assert_eq!(experience["outcome"]["error"], "valve stuck");
+
+        // A settled action takes no second verdict, but effects still correlate
+        assert!(feedback.report(ActionResult::success(action_id.clone(), None)).is_err());
+        let observed = ActionResult::observed(action_id.clone(), json!({"pressure": 2.5})).with_reward(0.25);
+        let outcome = feedback.report(observed).unwrap();
+        let experience = brain.get_experience_transformed(&outcome.experience_id, None).unwrap();
+        assert_eq!(experience["reward"], 0.25);
+        assert_eq!(experience["outcome"]["status"], "observed");
+    }
+
+    #[tokio::test]
+    async fn test_action_result_validation() {
+        let feedback = ActionFeedback::new(create_test_brain());
+        let action_id = feedback.track(notification());
+
+        let out_of_range = ActionResult::success(action_id.clone(), None).with_reward(5.0);
+        assert!(feedback.report(out_of_range).is_err());
+
+        assert!(ActionResult::from_json(action_id.clone(), &json!({"status": "done"}), "http").is_err());
+        assert!(ActionResult::from_json(action_id.clone(), &json!({"effect": {}}), "http").is_err());
+        assert!(ActionResult::from_json(action_id.clone(), &json!({"status": "failure", "error": 7}), "http").is_err());
+
+        let parsed = ActionResult::from_json(
+            action_id.clone(),
+            &json!({"status": "failure", "error": "timeout", "reward": -0.5}),
+            "http",
+        ).unwrap();
+        assert_eq!(parsed.status, ActionStatus::Failure);
+        assert_eq!(parsed.reward, Some(-0.5));
+        assert!(feedback.report(parsed).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_world_broker_action_feedback() {
+        let brain = create_test_brain();
+        let cpl = create_test_cpl(brain.clone());
+        let mut config = WorldBrokerConfig::default();
+        config.enabled_adapters = vec![];
+
+        let broker = WorldBroker::new(brain, cpl, config).unwrap();
+        broker.start().await.unwrap();
+
+        let action_id = broker.send_action(notification()).await.unwrap();
+        let outcome = broker.report_action_result(ActionResult::success(action_id, None)).unwrap();
+        assert!(matches!(outcome.action, WorldAction::SystemNotification { .. }));
+
+        broker.stop().await.unwrap();
+    }
+
+    // ============================================================================
+    // Simulation Adapter Tests
+    // ============================================================================
+
+    fn simulation_config(seed: u64) -> SimulationConfig {
+        SimulationConfig {
+            seed,
+            epoch: Some(1_700_000_000),
+            max_ticks: Some(20),
+            scripted: vec![ScriptedEvent {
+                tick: 3,
+                event: WorldEvent::Command { command: "dock".to_string(), args: json!({}) },
+            }],
+            random_walks: vec![RandomWalk {
+                source: "thermometer".to_string(),
+                field: "celsius".to_string(),
+                start: 20.0,
+                max_step: 0.5,
+                min: 19.0,
+                max: 21.0,
+                every_ticks: 2,
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn readings(events: &[WorldEvent]) -> Vec<f64> {
+        events.iter().filter_map(|e| match e {
+            WorldEvent::SensorData { data, .. } => data["celsius"].as_f64(),
+            _ => None,
+        }).collect()
+    }
+
+    #[tokio::test]
+    async fn test_simulation_adapter_deterministic() {
+        let first = SimulationAdapter::new(simulation_config(7)).unwrap();
+        let second = SimulationAdapter::new(simulation_config(7)).unwrap();
+        let other_seed = SimulationAdapter::new(simulation_config(8)).unwrap();
+
+        let events = first.run_ticks(25).await.unwrap();
+        let a = readings(&events);
+        assert_eq!(a, readings(&second.run_ticks(25).await.unwrap()));
+        assert_ne!(a, readings(&other_seed.run_ticks(25).await.unwrap()));
+
+        // Every other tick up to max_ticks, starting at the start value
+        assert_eq!(a.len(), 10);
+        assert_eq!(a[0], 20.0);
+        assert!(a.iter().all(|v| (19.0..=21.0).contains(v)));
+        assert_eq!(first.tick(), 20);
+
+        let commands = events.iter().filter(|e| matches!(e, WorldEvent::Command { .. })).count();
+        assert_eq!(commands, 1);
+    }
+
+    #[tokio::test]
+    async fn test_simulation_adapter_invalid_config() {
+        let mut config = simulation_config(1);
+        config.random_walks[0].start = 30.0;
+        assert!(SimulationAdapter::new(config).is_err());
+
+        let config = SimulationConfig { tick_interval_ms: 0, ..Default::default() };
+        assert!(SimulationAdapter::new(config).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_simulation_adapter_with_broker() {
+        let brain = create_test_brain();
+        let cpl = create_test_cpl(brain.clone());
+        let mut config = WorldBrokerConfig::default();
+        config.enabled_adapters = vec!["simulation".to_string()];
+
+        let simulation = SimulationAdapter::new(SimulationConfig {
+            auto_report: Some(ActionStatus::Success),
+            ..simulation_config(3)
+        }).unwrap();
+        let broker = WorldBroker::new(brain, cpl, config).unwrap();
+        broker.register_adapter(Box::new(simulation.clone()));
+        broker.start().await.unwrap();
+
+        let mut events = simulation.subscribe_events();
+        let generated = simulation.run_ticks(4).await.unwrap();
+        assert_eq!(generated.len(), 3);
+        assert!(matches!(events.recv().await.unwrap(), WorldEvent::SensorData { .. }));
+
+        // Actions are recorded with their IDs and their results fed back
+        let mut outcomes = broker.subscribe_action_outcomes();
+        let action_id = broker.send_action(notification()).await.unwrap();
+        let recorded = simulation.recorded_actions();
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[0].tick, 4);
+        assert_eq!(recorded[0].action_id.as_ref(), Some(&action_id));
+        assert_eq!(outcomes.recv().await.unwrap().result.action_id, action_id);
+
+        broker.stop().await.unwrap();
+    }
+
+    // ============================================================================
+    // Emotion Bus Tests
+    // ============================================================================
+
+    #[tokio::test]
+    async fn test_emotion_bus_updates_and_labels() {
+        let bus = EmotionBus::default();
+        let mut updates = bus.subscribe();
+        assert_eq!(bus.current().label(), EmotionLabel::Neutral);
+
+        bus.set(EmotionState::new(0.8, 0.6, 0.2), "test").unwrap();
+        let update = updates.recv().await.unwrap();
+        assert_eq!(update.label, EmotionLabel::Joy);
+        assert_eq!(update.source, "test");
+
+        // Nudges accumulate and clamp to [-1, 1]
+        bus.nudge(EmotionState::new(-2.0, 0.0, 0.0), "test").unwrap();
+        assert_eq!(bus.dimension(EmotionDimension::Valence), -1.0);
+        assert_eq!(bus.current().label(), EmotionLabel::Anger);
+        bus.nudge_dimension(EmotionDimension::Dominance, -1.0, "test").unwrap();
+        assert_eq!(bus.current().label(), EmotionLabel::Fear);
+        bus.nudge_dimension(EmotionDimension::Arousal, -1.5, "test").unwrap();
+        assert_eq!(bus.current().label(), EmotionLabel::Sadness);
+
+        assert!(bus.set(EmotionState::new(f64::NAN, 0.0, 0.0), "test").is_err());
+        assert!(bus.nudge_dimension(EmotionDimension::Arousal, f64::INFINITY, "test").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_emotion_bus_decay() {
+        let bus = EmotionBus::new(EmotionConfig {
+            baseline: EmotionState::new(0.1, 0.0, 0.0),
+            half_life_ms: 20,
+        }).unwrap();
+        bus.set(EmotionState::new(1.0, 1.0, 1.0), "test").unwrap();
+        let mut updates = bus.subscribe();
+
+        sleep(Duration::from_millis(200)).await;
+        let state = bus.current();
+        assert!((state.valence - 0.1).abs() < 0.01);
+        assert!(state.arousal.abs() < 0.01);
+
+        bus.publish_decay();
+        let update = updates.recv().await.unwrap();
+        assert_eq!(update.source, "decay");
+        // Nothing left to publish
+        bus.publish_decay();
+        assert!(updates.try_recv().is_err());
+
+        assert!(EmotionBus::new(EmotionConfig { half_life_ms: 0, ..Default::default() }).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_attention_filter_emotional_modulation() {
+        let event = WorldEvent::Command { command: "halt".to_string(), args: json!({}) };
+        let calm = AttentionFilter::new(create_test_brain(), AttentionFilterConfig::default());
+        let aroused = AttentionFilter::new(create_test_brain(), AttentionFilterConfig::default());
+        let bus = Arc::new(EmotionBus::default());
+        bus.set(EmotionState::new(-0.5, 1.0, 0.0), "test").unwrap();
+        aroused.set_emotion_bus(bus);
+
+        let calm_salience = calm.compute_salience(&event).unwrap();
+        let aroused_salience = aroused.compute_salience(&event).unwrap();
+        assert!(aroused_salience > calm_salience);
+    }
+
     // ============================================================================
     // Performance Tests
     // ============================================================================
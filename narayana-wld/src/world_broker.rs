@@ -3,8 +3,10 @@
 //! Integrates sensory interface, motor interface, attention filter,
 //! and protocol adapters to mediate bidirectional communication.
 
+use crate::action_feedback::{ActionFeedback, ActionId, ActionOutcome, ActionResult};
 use crate::attention_filter::{AttentionFilter, AttentionFilterConfig};
 use crate::config::WorldBrokerConfig;
+use crate::emotion::EmotionBus;
 use crate::event_transformer::{EventTransformer, WorldEvent, WorldAction};
 use crate::motor_interface::MotorInterface;
 use crate::protocol_adapters::ProtocolAdapter;
@@ -12,6 +14,9 @@ use crate::sensory_interface::SensoryInterface;
 use narayana_core::Error;
 use narayana_storage::cognitive::CognitiveBrain;
 use narayana_storage::conscience_persistent_loop::{ConsciencePersistentLoop, CPLEvent};
+use narayana_storage::cpl_manager::CPLWorldAdapter;
+use narayana_storage::sensory_streams::{ChannelSpec, SensoryStreamManager, StreamWindow, WindowQuery};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 use parking_lot::RwLock;
@@ -26,18 +31,35 @@ pub struct WorldBroker {
     motor_interface: Arc<MotorInterface>,
     transformer: Arc<RwLock<EventTransformer>>,
     attention_filter: Arc<AttentionFilter>,
-    adapters: Arc<RwLock<HashMap<String, Box<dyn ProtocolAdapter + Send + Sync>>>>,
+    adapters: Arc<RwLock<HashMap<String, Arc<dyn ProtocolAdapter + Send + Sync>>>>,
+    action_feedback: Arc<ActionFeedback>,
+    emotion: Arc<EmotionBus>,
+    emotion_decay: RwLock<Option<tokio::task::JoinHandle<()>>>,
+    sensory_streams: RwLock<Option<Arc<SensoryStreamManager>>>,
     config: WorldBrokerConfig,
     action_sender: broadcast::Sender<WorldAction>,
     is_running: Arc<RwLock<bool>>,
 }
 
+/// Expected values of a sensory stream at a future time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StreamPrediction {
+    pub stream_id: String,
+    pub at_ms: u64,
+    pub channels: Vec<ChannelSpec>,
+    /// One per channel; NaN where the lookback held fewer than two samples
+    pub values: Vec<f64>,
+    pub based_on_samples: usize,
+}
+
 /// Handle for async operations (avoids Arc<WorldBroker> issues)
 #[derive(Clone)]
 pub struct WorldBrokerHandle {
     sensory: Arc<SensoryInterface>,
     motor: Arc<MotorInterface>,
     action_sender: broadcast::Sender<WorldAction>,
+    action_feedback: Arc<ActionFeedback>,
+    emotion: Arc<EmotionBus>,
 }
 
 impl WorldBrokerHandle {
@@ -48,6 +70,21 @@ impl WorldBrokerHandle {
     pub fn subscribe_actions(&self) -> broadcast::Receiver<WorldAction> {
         self.action_sender.subscribe()
     }
+
+    /// Report the result of an action sent by the broker
+    pub fn report_action_result(&self, result: ActionResult) -> Result<ActionOutcome, Error> {
+        self.action_feedback.report(result)
+    }
+
+    /// Whether results are still accepted for an action
+    pub fn is_action_tracked(&self, action_id: &ActionId) -> bool {
+        self.action_feedback.is_tracked(action_id)
+    }
+
+    /// Shared emotion state
+    pub fn emotion_bus(&self) -> Arc<EmotionBus> {
+        self.emotion.clone()
+    }
 }
 
 impl WorldBroker {
@@ -78,6 +115,10 @@ impl WorldBroker {
             attention_config,
         ));
 
+        // Shared emotion state modulates attention
+        let emotion = Arc::new(EmotionBus::default());
+        attention_filter.set_emotion_bus(emotion.clone());
+
         // Create sensory interface
         let sensory_interface = Arc::new(SensoryInterface::new(
             brain.clone(),
@@ -95,6 +136,9 @@ impl WorldBroker {
         // Create action broadcast channel
         let (action_sender, _) = broadcast::channel(config.event_buffer_size);
 
+        // Correlate adapter-reported results with sent actions
+        let action_feedback = Arc::new(ActionFeedback::new(brain.clone()));
+
         Ok(Self {
             brain,
             cpl,
@@ -103,6 +147,10 @@ impl WorldBroker {
             transformer,
             attention_filter,
             adapters: Arc::new(RwLock::new(HashMap::new())),
+            action_feedback,
+            emotion,
+            emotion_decay: RwLock::new(None),
+            sensory_streams: RwLock::new(None),
             config,
             action_sender,
             is_running: Arc::new(RwLock::new(false)),
@@ -126,6 +174,8 @@ impl WorldBroker {
             sensory: self.sensory_interface.clone(),
             motor: self.motor_interface.clone(),
             action_sender: self.action_sender.clone(),
+            action_feedback: self.action_feedback.clone(),
+            emotion: self.emotion.clone(),
         };
 
         // Start protocol adapters
@@ -139,6 +189,10 @@ impl WorldBroker {
         // Start CPL event listener
         self.start_cpl_listener().await?;
 
+        // Let subscribers see emotion settle back to baseline
+        let decay = self.emotion.start_decay(std::time::Duration::from_secs(1));
+        *self.emotion_decay.write() = Some(decay);
+
         info!("World Broker started successfully");
         Ok(())
     }
@@ -158,27 +212,18 @@ impl WorldBroker {
 
         // CNS integration is handled externally
 
-        // Stop all adapters
-        let adapters = self.adapters.read();
-        let adapter_names: Vec<String> = adapters.keys().cloned().collect();
-        drop(adapters); // Release lock before async operations
-        
-        for name in adapter_names {
-            let adapter = {
-                let adapters = self.adapters.read();
-                // Note: We can't clone the adapter, so we'll stop them by name
-                // This is a limitation - in production, we'd need a better way
-                adapters.get(&name).map(|_| ())
-            };
-            
-            if adapter.is_some() {
-                // Re-acquire to get adapter for stopping
-                let adapters = self.adapters.read();
-                if let Some(adapter) = adapters.get(&name) {
-                    if let Err(e) = adapter.stop().await {
-                        warn!("Error stopping adapter {}: {}", name, e);
-                    }
-                }
+        if let Some(decay) = self.emotion_decay.write().take() {
+            decay.abort();
+        }
+
+        // Stop all adapters (cloned out so no lock is held across await)
+        let adapters: Vec<(String, Arc<dyn ProtocolAdapter + Send + Sync>)> = self.adapters.read()
+            .iter()
+            .map(|(name, adapter)| (name.clone(), adapter.clone()))
+            .collect();
+        for (name, adapter) in adapters {
+            if let Err(e) = adapter.stop().await {
+                warn!("Error stopping adapter {}: {}", name, e);
             }
         }
 
@@ -208,7 +253,7 @@ impl WorldBroker {
             warn!("Adapter {} already registered, replacing", name);
         }
         
-        adapters.insert(name.clone(), adapter);
+        adapters.insert(name.clone(), Arc::from(adapter));
         info!("Registered protocol adapter: {}", name);
     }
 
@@ -218,8 +263,8 @@ impl WorldBroker {
         adapter_name: String,
         handle: WorldBrokerHandle,
     ) -> Result<(), Error> {
-        let adapters = self.adapters.read();
-        if let Some(adapter) = adapters.get(&adapter_name) {
+        let adapter = self.adapters.read().get(&adapter_name).cloned();
+        if let Some(adapter) = adapter {
             info!("Starting protocol adapter: {}", adapter_name);
             adapter.start(handle).await?;
         } else {
@@ -244,25 +289,72 @@ impl WorldBroker {
         Ok(())
     }
 
+    /// Read declared sensory streams for prediction
+    pub fn set_sensory_streams(&self, streams: Arc<SensoryStreamManager>) {
+        *self.sensory_streams.write() = Some(streams);
+    }
+
+    /// Window of a declared sensory stream
+    pub fn stream_window(&self, stream_id: &str, query: &WindowQuery) -> Result<StreamWindow, Error> {
+        let streams = self.sensory_streams.read().clone()
+            .ok_or_else(|| Error::Storage("No sensory streams attached to the world broker".to_string()))?;
+        streams.query_window(stream_id, query)
+    }
+
+    /// Predict a declared stream `horizon_ms` past its newest sample by
+    /// extrapolating the trend of the last `lookback_secs`
+    pub fn predict_stream(&self, stream_id: &str, horizon_ms: u64, lookback_secs: f64) -> Result<StreamPrediction, Error> {
+        let streams = self.sensory_streams.read().clone()
+            .ok_or_else(|| Error::Storage("No sensory streams attached to the world broker".to_string()))?;
+        let last_sample_ms = streams.stream_health(stream_id)
+            .and_then(|health| health.last_sample_ms)
+            .ok_or_else(|| Error::Storage(format!("Stream {} has no samples", stream_id)))?;
+        let window = streams.query_window(stream_id, &WindowQuery::last(lookback_secs).until(last_sample_ms))?;
+        let at_ms = last_sample_ms.saturating_add(horizon_ms);
+        Ok(StreamPrediction {
+            stream_id: stream_id.to_string(),
+            at_ms,
+            values: window.extrapolate(at_ms),
+            channels: window.channels,
+            based_on_samples: window.timestamps_ms.len(),
+        })
+    }
+
     /// Send action to external world
-    pub async fn send_action(&self, action: WorldAction) -> Result<(), Error> {
+    ///
+    /// Returns the ID adapters report the action's result against.
+    pub async fn send_action(&self, action: WorldAction) -> Result<ActionId, Error> {
         // Validate action before sending
         validate_action(&action)?;
-        
+        let action_id = self.action_feedback.track(action.clone());
+
         // Broadcast to all subscribers (non-blocking)
         if self.action_sender.send(action.clone()).is_err() {
             warn!("Action broadcast channel full, message dropped");
         }
 
         // Send via all adapters
-        let adapters = self.adapters.read();
-        for (name, adapter) in adapters.iter() {
-            if let Err(e) = adapter.send_action(action.clone()).await {
+        let adapters: Vec<(String, Arc<dyn ProtocolAdapter + Send + Sync>)> = self.adapters.read()
+            .iter()
+            .map(|(name, adapter)| (name.clone(), adapter.clone()))
+            .collect();
+        for (name, adapter) in &adapters {
+            if let Err(e) = adapter.send_tracked_action(&action_id, action.clone()).await {
                 warn!("Error sending action via adapter {}: {}", name, e);
             }
         }
 
-        Ok(())
+        Ok(action_id)
+    }
+
+    /// Report the result of an action sent by the broker
+    pub fn report_action_result(&self, result: ActionResult) -> Result<ActionOutcome, Error> {
+        self.action_feedback.report(result)
+    }
+
+    /// Subscribe to action results correlated with their actions
+    pub fn subscribe_action_outcomes(&self) -> broadcast::Receiver<ActionOutcome> {
+        self.action_feedback.subscribe()
     }
 
     /// Get sensory interface
@@ -279,6 +371,36 @@ impl WorldBroker {
     pub fn attention_filter(&self) -> &Arc<AttentionFilter> {
         &self.attention_filter
     }
+
+    /// Get shared emotion state
+    pub fn emotion_bus(&self) -> &Arc<EmotionBus> {
+        &self.emotion
+    }
+
+    /// Get action feedback tracker
+    pub fn action_feedback(&self) -> &Arc<ActionFeedback> {
+        &self.action_feedback
+    }
+}
+
+/// Lets a CPL manager start and stop the broker together with its CPL
+#[async_trait::async_trait]
+impl CPLWorldAdapter for WorldBroker {
+    fn name(&self) -> String {
+        "world_broker".to_string()
+    }
+
+    async fn start(&self) -> Result<(), Error> {
+        WorldBroker::start(self).await
+    }
+
+    async fn stop(&self) -> Result<(), Error> {
+        WorldBroker::stop(self).await
+    }
+
+    fn is_healthy(&self) -> bool {
+        *self.is_running.read()
+    }
 }
 
 /// Validate world action before sending
@@ -0,0 +1,101 @@
+// Per-route admission control tests
+// Route matching and specificity, bounded queues, queue timeouts, slot release
+// on drop and config reload
+
+use narayana_core::config::{ConnectionPoolConfig, RouteLimit};
+use narayana_server::admission::{AdmissionController, Rejection};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn controller(limits: Vec<RouteLimit>) -> Arc<AdmissionController> {
+    Arc::new(AdmissionController::new(&ConnectionPoolConfig {
+        route_limits: limits,
+        ..Default::default()
+    }))
+}
+
+#[tokio::test]
+async fn test_most_specific_route_wins() {
+    let admission = controller(vec![
+        RouteLimit::new("/api/v1/tables", 8, 0, Duration::from_secs(1)),
+        RouteLimit::new("/api/v1/tables/*/query", 1, 0, Duration::from_secs(1)),
+        RouteLimit::new("/api/v1/tables/7/query", 1, 0, Duration::from_secs(1)),
+    ]);
+    let query = admission.admit("/api/v1/tables/3/query").await.unwrap();
+    assert_eq!(query.route(), Some("/api/v1/tables/*/query"));
+    let literal = admission.admit("/api/v1/tables/7/query").await.unwrap();
+    assert_eq!(literal.route(), Some("/api/v1/tables/7/query"));
+    let insert = admission.admit("/api/v1/tables/3/insert").await.unwrap();
+    assert_eq!(insert.route(), Some("/api/v1/tables"));
+    // Prefixes match whole segments only
+    assert_eq!(admission.admit("/api/v1/tablespace").await.unwrap().route(), None);
+    assert_eq!(admission.admit("/api/v1/brains").await.unwrap().route(), None);
+}
+
+#[tokio::test]
+async fn test_queue_full_and_timeout() {
+    let admission = controller(vec![RouteLimit::new("/api/v1/analytics", 1, 1, Duration::from_millis(50))]);
+    let running = admission.admit("/api/v1/analytics/window").await.unwrap();
+
+    let queued = {
+        let admission = admission.clone();
+        tokio::spawn(async move { admission.admit("/api/v1/analytics/aggregate").await.map(|_| ()) })
+    };
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    assert_eq!(admission.stats().routes[0].queued, 1);
+
+    // Queue is full: refused right away
+    let rejection = admission.admit("/api/v1/analytics/window").await.err().unwrap();
+    assert_eq!(rejection, Rejection::QueueFull { retry_after_secs: 1 });
+
+    // The queued request gives up once the timeout passes
+    assert_eq!(queued.await.unwrap(), Err(Rejection::QueueTimeout { retry_after_secs: 1 }));
+    let stats = &admission.stats().routes[0];
+    assert_eq!((stats.in_flight, stats.queued), (1, 0));
+    assert_eq!((stats.admitted, stats.rejected_queue_full, stats.rejected_timeout), (1, 1, 1));
+    drop(running);
+    assert_eq!(admission.stats().routes[0].in_flight, 0);
+}
+
+#[tokio::test]
+async fn test_queued_request_gets_freed_slot() {
+    let admission = controller(vec![RouteLimit::new("/api/v1/ml", 1, 4, Duration::from_secs(5))]);
+    let running = admission.admit("/api/v1/ml/train").await.unwrap();
+    let waiter = {
+        let admission = admission.clone();
+        tokio::spawn(async move { admission.admit("/api/v1/ml/train").await.is_ok() })
+    };
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    drop(running);
+    assert!(waiter.await.unwrap());
+    assert_eq!(admission.stats().routes[0].admitted, 2);
+}
+
+#[tokio::test]
+async fn test_abandoned_wait_releases_queue_position() {
+    let admission = controller(vec![RouteLimit::new("/api/v1/ml", 1, 1, Duration::from_secs(30))]);
+    let _running = admission.admit("/api/v1/ml/train").await.unwrap();
+    // Client disconnects while queued
+    let abandoned = tokio::time::timeout(Duration::from_millis(20), admission.admit("/api/v1/ml/train")).await;
+    assert!(abandoned.is_err());
+    assert_eq!(admission.stats().routes[0].queued, 0);
+}
+
+#[tokio::test]
+async fn test_reload_keeps_unchanged_groups() {
+    let analytics = RouteLimit::new("/api/v1/analytics", 2, 0, Duration::from_secs(1));
+    let admission = controller(vec![analytics.clone(), RouteLimit::new("/api/v1/ml", 1, 0, Duration::from_secs(1))]);
+    let _running = admission.admit("/api/v1/analytics/window").await.unwrap();
+
+    admission.update(&ConnectionPoolConfig {
+        route_limits: vec![analytics, RouteLimit::new("/api/v1/ml", 3, 0, Duration::from_secs(1))],
+        ..Default::default()
+    });
+    let routes = admission.stats().routes;
+    assert_eq!((routes[0].in_flight, routes[0].admitted), (1, 1));
+    assert_eq!(routes[1].max_concurrent, 3);
+
+    admission.update(&ConnectionPoolConfig { route_limits: vec![], ..Default::default() });
+    assert!(admission.stats().routes.is_empty());
+    assert_eq!(admission.admit("/api/v1/analytics/window").await.unwrap().route(), None);
+}
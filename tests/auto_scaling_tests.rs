@@ -363,3 +363,103 @@ async fn test_load_balancing_across_spawned_databases() {
     assert_eq!(selected, Some("db-1-def456".to_string()));
 }
 
+
+fn shard_scaler(thresholds: ShardScalingThresholds) -> (Arc<SimpleDatabaseManager>, ShardScaler) {
+    use narayana_storage::consensus::RaftConsensus;
+
+    let databases = Arc::new(SimpleDatabaseManager::new());
+    let raft = Arc::new(RaftConsensus::new("n1".to_string()));
+    let scaler = ShardScaler::new(
+        raft,
+        Arc::new(DatabaseShardProvisioner::new(databases.clone())),
+        ShardScalingThresholds { cooldown: Duration::ZERO, ..thresholds },
+    );
+    (databases, scaler)
+}
+
+fn shard_load(shard_id: u64, size_bytes: u64, reads_per_second: f64) -> ShardLoad {
+    ShardLoad {
+        shard_id,
+        size_bytes,
+        row_count: 0,
+        reads_per_second,
+        writes_per_second: 0.0,
+        last_updated: 0,
+    }
+}
+
+#[tokio::test]
+async fn test_shard_split_and_merge_through_consensus() {
+    let (databases, scaler) = shard_scaler(ShardScalingThresholds {
+        split_size_bytes: 1000,
+        merge_size_bytes: 100,
+        ..Default::default()
+    });
+    scaler.consensus().start_election().await.unwrap();
+    let shard_id = scaler.bootstrap_table(7).await.unwrap().unwrap();
+    assert_eq!(scaler.bootstrap_table(7).await.unwrap(), None);
+
+    scaler.update_load(shard_load(shard_id, 5000, 0.0));
+    let events = scaler.run_once().await;
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].action, ShardScalingAction::Split { shard_id });
+
+    let shards = scaler.shards();
+    assert_eq!(shards.len(), 2);
+    assert_eq!(shards[0].key_start, 0);
+    assert_eq!(shards[0].key_end + 1, shards[1].key_start);
+    assert_eq!(shards[1].key_end, u64::MAX);
+    let names = databases.list_databases();
+    assert!(!names.contains(&DatabaseShardProvisioner::shard_database(shard_id)));
+    assert!(shards.iter().all(|s| names.contains(&DatabaseShardProvisioner::shard_database(s.shard_id))));
+
+    // Both halves cold: merged back into one shard owning every key
+    for shard in &shards {
+        scaler.update_load(shard_load(shard.shard_id, 10, 0.0));
+    }
+    let events = scaler.run_once().await;
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].action, ShardScalingAction::Merge { left_id: shards[0].shard_id, right_id: shards[1].shard_id });
+    let merged = scaler.shards();
+    assert_eq!(merged.len(), 1);
+    assert_eq!((merged[0].key_start, merged[0].key_end), (0, u64::MAX));
+    assert_eq!(databases.list_databases(), vec![DatabaseShardProvisioner::shard_database(merged[0].shard_id)]);
+}
+
+#[tokio::test]
+async fn test_shard_readers_follow_read_load() {
+    let (databases, scaler) = shard_scaler(ShardScalingThresholds {
+        reads_per_node: 100.0,
+        max_readers: 2,
+        ..Default::default()
+    });
+    scaler.consensus().start_election().await.unwrap();
+    let shard_id = scaler.bootstrap_table(1).await.unwrap().unwrap();
+
+    scaler.update_load(shard_load(shard_id, 10_000_000, 450.0));
+    scaler.run_once().await;
+    scaler.run_once().await;
+    // Capped at max_readers
+    scaler.run_once().await;
+    let readers = scaler.shards()[0].readers.clone();
+    assert_eq!(readers.len(), 2);
+    assert!(readers.iter().all(|r| databases.list_databases().contains(r)));
+
+    scaler.update_load(shard_load(shard_id, 10_000_000, 20.0));
+    let events = scaler.run_once().await;
+    assert!(matches!(events[0].action, ShardScalingAction::RetireReader { .. }));
+    assert_eq!(scaler.shards()[0].readers.len(), 1);
+    assert!(!databases.list_databases().contains(&readers[1]));
+}
+
+#[tokio::test]
+async fn test_shard_scaling_requires_leader() {
+    let (_databases, scaler) = shard_scaler(ShardScalingThresholds::default());
+    assert!(scaler.run_once().await.is_empty());
+
+    let result = scaler.execute(ShardScalingAction::Split { shard_id: 1 }, "manual").await;
+    assert!(result.is_err());
+    let events = scaler.events(10);
+    assert_eq!(events.len(), 1);
+    assert!(events[0].error.is_some());
+}
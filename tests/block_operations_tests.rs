@@ -30,6 +30,7 @@ fn test_block_metadata_creation() {
         min_value: None,
         max_value: None,
         null_count: 0,
+        distinct_count: None,
     };
     
     assert_eq!(metadata.block_id, 1);
@@ -52,6 +53,7 @@ fn test_block_metadata_compression_ratio() {
         min_value: None,
         max_value: None,
         null_count: 0,
+        distinct_count: None,
     };
     
     assert_eq!(metadata1.compression_ratio(), 0.5);
@@ -68,6 +70,7 @@ fn test_block_metadata_compression_ratio() {
         min_value: None,
         max_value: None,
         null_count: 0,
+        distinct_count: None,
     };
     
     assert_eq!(metadata2.compression_ratio(), 1.0);
@@ -84,6 +87,7 @@ fn test_block_metadata_compression_ratio() {
         min_value: None,
         max_value: None,
         null_count: 0,
+        distinct_count: None,
     };
     
     assert_eq!(metadata3.compression_ratio(), 1.0);
@@ -103,6 +107,7 @@ fn test_block_metadata_min_max_values() {
         min_value: Some(vec![0, 0, 0, 1]), // Little-endian representation of 1
         max_value: Some(vec![0xFF, 0xFF, 0xFF, 0x7F]), // Max i32
         null_count: 0,
+        distinct_count: None,
     };
     
     assert!(metadata.min_value.is_some());
@@ -124,6 +129,7 @@ fn test_block_metadata_null_count() {
         min_value: None,
         max_value: None,
         null_count: 25,
+        distinct_count: None,
     };
     
     assert_eq!(metadata.null_count, 25);
@@ -444,6 +450,7 @@ fn test_block_metadata_zero_compression_ratio() {
         min_value: None,
         max_value: None,
         null_count: 0,
+        distinct_count: None,
     };
     
     assert_eq!(metadata.compression_ratio(), 1.0);
@@ -464,6 +471,7 @@ fn test_block_metadata_negative_compression() {
         min_value: None,
         max_value: None,
         null_count: 0,
+        distinct_count: None,
     };
     
     // Should handle gracefully
@@ -484,6 +492,7 @@ fn test_block_metadata_max_values() {
         min_value: None,
         max_value: None,
         null_count: usize::MAX,
+        distinct_count: None,
     };
     
     // Should handle max values
@@ -532,6 +541,7 @@ fn test_block_metadata_serialization() {
         min_value: Some(vec![1, 2, 3, 4]),
         max_value: Some(vec![5, 6, 7, 8]),
         null_count: 10,
+        distinct_count: None,
     };
     
     let serialized = serde_json::to_string(&metadata).unwrap();
@@ -646,6 +656,7 @@ fn test_block_metadata_statistics() {
         min_value: Some(vec![0, 0, 0, 1]),
         max_value: Some(vec![0xFF, 0xFF, 0xFF, 0x7F]),
         null_count: 50,
+        distinct_count: None,
     };
     
     // Verify statistics
@@ -0,0 +1,110 @@
+// Bulk insert tests
+// Payload parsing (JSON array / NDJSON), per-row validation and batching
+
+use narayana_core::{column::Column, schema::{DataType, Field}};
+use narayana_server::bulk_insert::{parse_rows, RowBatchBuilder};
+use serde_json::{json, Value};
+
+fn field(name: &str, data_type: DataType, nullable: bool, default_value: Option<Value>) -> Field {
+    Field { name: name.to_string(), data_type, nullable, default_value }
+}
+
+fn fields() -> Vec<Field> {
+    vec![
+        field("id", DataType::Int32, false, None),
+        field("name", DataType::String, false, None),
+        field("score", DataType::Float64, true, None),
+        field("active", DataType::Boolean, false, Some(json!(true))),
+    ]
+}
+
+fn no_geometry(_: &DataType, _: Value) -> Result<Vec<u8>, String> {
+    Err("geometry not supported".to_string())
+}
+
+#[test]
+fn test_parse_json_array_and_ndjson() {
+    let rows = parse_rows(r#"[{"id": 1}, {"id": 2}]"#, false).unwrap();
+    assert_eq!(rows.len(), 2);
+
+    let rows = parse_rows(r#"{"rows": [[1, "a", 1.0, true]]}"#, false).unwrap();
+    assert_eq!(rows.len(), 1);
+
+    assert!(parse_rows(r#"[{"id": 1}"#, false).is_err());
+
+    // A bad NDJSON line only fails its own row
+    let rows = parse_rows("{\"id\": 1}\n\n{oops\n{\"id\": 3}\n", true).unwrap();
+    assert_eq!(rows.len(), 3);
+    assert!(rows[0].is_ok());
+    assert_eq!(rows[1].as_ref().unwrap_err().row, 1);
+    assert_eq!(rows[1].as_ref().unwrap_err().code, "PARSE_ERROR");
+    assert!(rows[2].is_ok());
+}
+
+#[test]
+fn test_rows_are_validated_individually() {
+    let mut builder = RowBatchBuilder::new(fields(), &no_geometry);
+
+    assert!(builder.push_row(0, json!({"id": 1, "name": "a", "score": 0.5, "active": false})).is_ok());
+    assert!(builder.push_row(1, json!([2, "b", null, true])).is_ok());
+    // Defaults fill omitted fields, nullable fields accept null
+    assert!(builder.push_row(2, json!({"id": 3, "name": "c", "score": null})).is_ok());
+
+    let error = builder.push_row(3, json!({"id": 300000000000i64, "name": "d"})).unwrap_err();
+    assert_eq!((error.row, error.field.as_deref(), error.code.as_str()), (3, Some("id"), "INVALID_VALUE"));
+
+    let error = builder.push_row(4, json!({"id": 5})).unwrap_err();
+    assert_eq!((error.field.as_deref(), error.code.as_str()), (Some("name"), "MISSING_VALUE"));
+
+    let error = builder.push_row(5, json!({"id": 6, "name": "f", "extra": 1})).unwrap_err();
+    assert_eq!(error.code, "UNKNOWN_FIELD");
+
+    let error = builder.push_row(6, json!([7, "g"])).unwrap_err();
+    assert_eq!(error.code, "COLUMN_COUNT_MISMATCH");
+
+    let error = builder.push_row(7, json!("row")).unwrap_err();
+    assert_eq!(error.code, "INVALID_ROW");
+
+    assert_eq!(builder.len(), 3);
+    let (columns, positions) = builder.take_batch();
+    assert_eq!(positions, vec![0, 1, 2]);
+    assert!(matches!(&columns[0], Column::Int32(v) if v == &vec![1, 2, 3]));
+    assert!(matches!(&columns[1], Column::String(v) if v == &vec!["a", "b", "c"]));
+    assert!(matches!(&columns[2], Column::Float64(v) if v == &vec![0.5, 0.0, 0.0]));
+    assert!(matches!(&columns[3], Column::Boolean(v) if v == &vec![false, true, true]));
+}
+
+#[test]
+fn test_take_batch_resets_builder() {
+    let mut builder = RowBatchBuilder::new(fields(), &no_geometry);
+    builder.push_row(0, json!([1, "a", 1.0, true])).unwrap();
+    let (first, _) = builder.take_batch();
+    assert_eq!(first[0].len(), 1);
+    assert!(builder.is_empty());
+
+    builder.push_row(1, json!([2, "b", 2.0, false])).unwrap();
+    builder.push_row(2, json!([3, "c", 3.0, false])).unwrap();
+    let (second, positions) = builder.take_batch();
+    assert_eq!(second[1].len(), 2);
+    assert_eq!(positions, vec![1, 2]);
+}
+
+#[test]
+fn test_binary_and_semi_structured_values() {
+    let fields = vec![
+        field("blob", DataType::Binary, false, None),
+        field("meta", DataType::Json, false, None),
+        field("small", DataType::UInt8, false, None),
+    ];
+    let mut builder = RowBatchBuilder::new(fields, &no_geometry);
+    builder.push_row(0, json!(["0a0b", {"k": 1}, 255])).unwrap();
+    builder.push_row(1, json!([[1, 2], "raw", "7"])).unwrap();
+    assert!(builder.push_row(2, json!(["zz", {}, 1])).is_err());
+    assert!(builder.push_row(3, json!(["", {}, 256])).is_err());
+    assert!(builder.push_row(4, json!(["", {}, -1])).is_err());
+
+    let (columns, _) = builder.take_batch();
+    assert!(matches!(&columns[0], Column::Binary(v) if v == &vec![vec![10, 11], vec![1, 2]]));
+    assert!(matches!(&columns[1], Column::String(v) if v == &vec![r#"{"k":1}"#.to_string(), "raw".to_string()]));
+    assert!(matches!(&columns[2], Column::UInt8(v) if v == &vec![255, 7]));
+}
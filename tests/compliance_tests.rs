@@ -0,0 +1,265 @@
+// Compliance tests
+// Subject erasure across tagged tables, RAG memory indexes and blobs, in
+// delete and shred modes, and lineage of derived assets
+
+use narayana_core::column::Column;
+use narayana_core::config::BlobStoreConfig;
+use narayana_core::schema::{DataType, Field, Schema};
+use narayana_core::types::TableId;
+use narayana_storage::blob_store::BlobStore;
+use narayana_storage::column_store::{ColumnStore, InMemoryColumnStore};
+use narayana_storage::compliance::*;
+use narayana_storage::geospatial::{Geometry, Point, SpatialIndexManager, SpatialPredicate};
+use narayana_storage::vector_search::{Embedding, IndexType, VectorStore};
+use serde_json::json;
+use std::collections::HashMap;
+
+const ORDERS: TableId = TableId(1);
+const DERIVED: TableId = TableId(2);
+
+fn field(name: &str, data_type: DataType) -> Field {
+    Field { name: name.to_string(), data_type, nullable: false, default_value: None }
+}
+
+fn strings(column: &Column) -> Vec<String> {
+    match column {
+        Column::String(values) => values.clone(),
+        other => panic!("expected String column, got {:?}", other.data_type()),
+    }
+}
+
+struct Fixture {
+    store: InMemoryColumnStore,
+    vectors: VectorStore,
+    blobs: BlobStore,
+    compliance: ComplianceManager,
+    avatars: Vec<String>,
+    _dir: tempfile::TempDir,
+}
+
+impl Fixture {
+    fn targets(&self) -> ErasureTargets<'_> {
+        ErasureTargets {
+            store: &self.store,
+            vectors: &self.vectors,
+            full_text: None,
+            spatial: None,
+            embeddings: None,
+            blobs: Some(&self.blobs),
+            temporal: None,
+        }
+    }
+
+    async fn erase(&self, subject: &str, mode: ErasureMode, dry_run: bool) -> ErasureReport {
+        let request = ErasureRequest { subject: subject.to_string(), mode, dry_run };
+        self.compliance.erase(&request, &self.targets()).await.unwrap()
+    }
+
+    async fn read(&self, columns: Vec<u32>) -> Vec<Column> {
+        self.store.read_columns(ORDERS, columns, 0, usize::MAX).await.unwrap()
+    }
+}
+
+async fn setup() -> Fixture {
+    let dir = tempfile::tempdir().unwrap();
+    let blobs = BlobStore::open(dir.path(), &BlobStoreConfig::default()).unwrap();
+    let ann = blobs.put(b"ann's avatar", None).unwrap().0.reference().to_string();
+    let shared = blobs.put(b"default avatar", None).unwrap().0.reference().to_string();
+
+    let store = InMemoryColumnStore::new();
+    let schema = Schema::new(vec![
+        field("user_id", DataType::Int64),
+        field("email", DataType::String),
+        field("avatar", DataType::String),
+        field("amount", DataType::Int64),
+    ]);
+    store.create_table(ORDERS, schema).await.unwrap();
+    store
+        .write_columns(ORDERS, vec![
+            Column::Int64(vec![7, 8, 7, 9]),
+            Column::String(vec!["ann@a.io".into(), "bob@b.io".into(), "ann@a.io".into(), "cy@c.io".into()]),
+            Column::String(vec![ann.clone(), shared.clone(), shared.clone(), String::new()]),
+            Column::Int64(vec![10, 20, 30, 40]),
+        ])
+        .await
+        .unwrap();
+
+    let vectors = VectorStore::new();
+    vectors.create_index("memories".to_string(), 2, IndexType::HNSW { m: 8, ef_construction: 32 });
+    for (id, user) in [(1, "7"), (2, "8"), (3, "7")] {
+        let metadata = HashMap::from([("user".to_string(), json!(user))]);
+        vectors
+            .add_embedding("memories", Embedding { id, vector: vec![id as f32, 1.0], metadata, timestamp: 0 })
+            .unwrap();
+    }
+
+    let compliance = ComplianceManager::new();
+    compliance
+        .tag_table(ORDERS, TableTags {
+            subject_column: "user_id".to_string(),
+            columns: vec![
+                PersonalColumn { column: "email".to_string(), category: Some("email".to_string()) },
+                PersonalColumn { column: "avatar".to_string(), category: None },
+            ],
+        })
+        .unwrap();
+    compliance.tag_vector_index("memories", VectorIndexTags { subject_field: "user".to_string() }).unwrap();
+
+    Fixture { store, vectors, blobs, compliance, avatars: vec![ann, shared], _dir: dir }
+}
+
+#[tokio::test]
+async fn test_delete_erases_rows_memories_and_blobs() {
+    let fixture = setup().await;
+    let report = fixture.erase("7", ErasureMode::Delete, false).await;
+
+    assert!(report.is_complete(), "{:?}", report);
+    assert_eq!(report.tables, vec![TableErasure { table_id: 1, rows: 2, columns: vec![], embeddings: 0 }]);
+    assert_eq!(report.vector_indexes, vec![IndexErasure { index: "memories".to_string(), embeddings: 2 }]);
+    assert_ne!(report.subject_hash, "7");
+
+    let columns = fixture.read(vec![0, 3]).await;
+    assert!(matches!(&columns[0], Column::Int64(v) if v == &vec![8, 9]));
+    assert!(matches!(&columns[1], Column::Int64(v) if v == &vec![20, 40]));
+
+    assert_eq!(fixture.vectors.embedding_ids("memories", |_| true).unwrap(), vec![2]);
+
+    // The avatar only the subject used is gone; the one bob still uses stays
+    let ann_blob = fixture.avatars[0].trim_start_matches("blob:");
+    assert_eq!(report.blobs_deleted, vec![ann_blob.to_string()]);
+    assert_eq!(report.blobs_retained, vec![fixture.avatars[1].trim_start_matches("blob:").to_string()]);
+    assert!(fixture.blobs.info(ann_blob).is_none());
+
+    // Nothing left to erase, and the report is kept
+    let again = fixture.erase("7", ErasureMode::Delete, false).await;
+    assert!(again.tables.is_empty() && again.vector_indexes.is_empty());
+    assert_eq!(fixture.compliance.reports(10).len(), 2);
+}
+
+#[tokio::test]
+async fn test_shred_keeps_rows_but_not_personal_data() {
+    let fixture = setup().await;
+
+    // A dry run reports without touching anything
+    let dry = fixture.erase("7", ErasureMode::Shred, true).await;
+    assert_eq!(dry.tables[0].rows, 2);
+    assert_eq!(dry.vector_indexes[0].embeddings, 2);
+    assert_eq!(strings(&fixture.read(vec![1]).await[0])[0], "ann@a.io");
+    assert!(fixture.blobs.info(fixture.avatars[0].trim_start_matches("blob:")).is_some());
+
+    let report = fixture.erase("7", ErasureMode::Shred, false).await;
+    assert_eq!(report.tables[0].columns, vec!["email", "avatar"]);
+
+    let columns = fixture.read(vec![0, 1, 3]).await;
+    let emails = strings(&columns[1]);
+    assert!(emails[0].starts_with(SHREDDED_PREFIX) && emails[2].starts_with(SHREDDED_PREFIX));
+    // Each value is sealed separately, so equal values don't stay equal
+    assert_ne!(emails[0], emails[2]);
+    assert_eq!(emails[1], "bob@b.io");
+    assert!(matches!(&columns[2], Column::Int64(v) if v == &vec![10, 20, 30, 40]));
+    assert_eq!(fixture.vectors.embedding_ids("memories", |_| true).unwrap(), vec![2]);
+}
+
+#[tokio::test]
+async fn test_delete_renumbers_spatial_index() {
+    const VISITS: TableId = TableId(3);
+    let fixture = setup().await;
+    let schema = Schema::new(vec![field("user_id", DataType::Int64), field("place", DataType::Point)]);
+    let places: Vec<Vec<u8>> = (0..4).map(|i| Geometry::Point(Point::new(i as f64, 0.0).unwrap()).encode()).collect();
+    fixture.store.create_table(VISITS, schema.clone()).await.unwrap();
+    fixture.store
+        .write_columns(VISITS, vec![Column::Int64(vec![8, 7, 9, 7]), Column::Binary(places.clone())])
+        .await
+        .unwrap();
+    fixture.compliance
+        .tag_table(VISITS, TableTags {
+            subject_column: "user_id".to_string(),
+            columns: vec![PersonalColumn { column: "place".to_string(), category: Some("location".to_string()) }],
+        })
+        .unwrap();
+    let spatial = SpatialIndexManager::new();
+    spatial.create_index(VISITS, &schema, "place", &places).unwrap();
+
+    let targets = ErasureTargets { spatial: Some(&spatial), ..fixture.targets() };
+    let request = ErasureRequest { subject: "7".to_string(), mode: ErasureMode::Delete, dry_run: false };
+    fixture.compliance.erase(&request, &targets).await.unwrap();
+
+    // Rows 0 and 2 remain as rows 0 and 1, at their own places
+    let near = SpatialPredicate::Nearest { lon: 0.0, lat: 0.0, k: 10 };
+    let hits = spatial.query(VISITS, "place", &near, 10).unwrap();
+    assert_eq!(hits.iter().map(|h| h.doc_id).collect::<Vec<_>>(), vec![0, 1]);
+    // Row 1 is the place at longitude 2, about 222 km away
+    assert!((hits[1].distance_meters - 2.0 * 111_195.0).abs() < 1_000.0);
+}
+
+#[tokio::test]
+async fn test_lineage_flags_untagged_copies() {
+    let fixture = setup().await;
+    let edge = |source, target| LineageEdge { source, target, transform: Some("nightly rollup".to_string()), recorded_at: 0 };
+    fixture.compliance.record_lineage(edge(DataAsset::Table(1), DataAsset::Table(2))).unwrap();
+    fixture.compliance.record_lineage(edge(DataAsset::Table(2), DataAsset::VectorIndex("summaries".into()))).unwrap();
+    fixture.compliance.record_lineage(edge(DataAsset::Table(2), DataAsset::Table(2))).unwrap_err();
+    // Recording an edge twice keeps one
+    fixture.compliance.record_lineage(edge(DataAsset::Table(1), DataAsset::Table(2))).unwrap();
+    assert_eq!(fixture.compliance.lineage(None).len(), 2);
+    assert_eq!(
+        fixture.compliance.downstream(&DataAsset::Table(1)),
+        vec![DataAsset::Table(2), DataAsset::VectorIndex("summaries".into())]
+    );
+
+    let report = fixture.erase("7", ErasureMode::Delete, true).await;
+    assert_eq!(report.untracked, vec![DataAsset::Table(2), DataAsset::VectorIndex("summaries".into())]);
+    assert!(!report.is_complete());
+
+    // A tagged but missing table is reported, not fatal
+    fixture.compliance
+        .tag_table(DERIVED, TableTags { subject_column: "user_id".to_string(), columns: vec![] })
+        .unwrap();
+    let report = fixture.erase("7", ErasureMode::Delete, true).await;
+    assert_eq!(report.untracked, vec![DataAsset::VectorIndex("summaries".into())]);
+    assert_eq!(report.errors.len(), 1);
+    assert_eq!(report.tables.len(), 1);
+
+    fixture.compliance.drop_table(DERIVED);
+    assert!(fixture.compliance.table_tags(DERIVED).is_none());
+    assert_eq!(fixture.compliance.lineage(None).len(), 0);
+}
+
+#[test]
+fn test_invalid_tags_are_rejected() {
+    let compliance = ComplianceManager::new();
+    let tags = |subject: &str, columns: &[&str]| TableTags {
+        subject_column: subject.to_string(),
+        columns: columns.iter().map(|c| PersonalColumn { column: c.to_string(), category: None }).collect(),
+    };
+    assert!(compliance.tag_table(ORDERS, tags("", &[])).is_err());
+    assert!(compliance.tag_table(ORDERS, tags("user_id", &["email", "email"])).is_err());
+    assert!(compliance.tag_vector_index("memories", VectorIndexTags { subject_field: String::new() }).is_err());
+    assert!(compliance.tagged_tables().is_empty());
+    assert!(!compliance.untag_table(ORDERS));
+
+    compliance.tag_table(ORDERS, tags("user_id", &["email"])).unwrap();
+    assert!(compliance.untag_table(ORDERS));
+}
+
+#[test]
+fn test_retain_renumbers_embeddings() {
+    let vectors = VectorStore::new();
+    vectors.create_index("rows".to_string(), 2, IndexType::Flat);
+    for id in 0..5u64 {
+        vectors
+            .add_embedding("rows", Embedding { id, vector: vec![1.0, id as f32], metadata: HashMap::new(), timestamp: 0 })
+            .unwrap();
+    }
+    // Drop rows 1 and 3 and move the rest up, as deleting table rows does
+    let dropped = vectors.retain("rows", |e| match e.id {
+        1 | 3 => None,
+        id => Some(id - (id > 1) as u64 - (id > 3) as u64),
+    });
+    assert_eq!(dropped.unwrap(), 2);
+    let mut results = vectors.search("rows", &[1.0, 4.0], 10).unwrap();
+    results.sort_by_key(|r| r.id);
+    let found: Vec<(u64, f32)> = results.iter().map(|r| (r.id, r.embedding.vector[1])).collect();
+    assert_eq!(found, vec![(0, 0.0), (1, 2.0), (2, 4.0)]);
+    assert!(vectors.retain("missing", |e| Some(e.id)).is_err());
+}
@@ -259,6 +259,7 @@ fn test_block_metadata() {
         min_value: Some(serde_json::Value::Number(1.into())),
         max_value: Some(serde_json::Value::Number(100.into())),
         null_count: 0,
+        distinct_count: None,
     };
     
     assert_eq!(metadata.block_id, 1);
@@ -1,18 +1,34 @@
 // Tests for consensus algorithms
 
 use narayana_storage::consensus::*;
+use std::sync::Arc;
 
 #[test]
 fn test_raft_consensus_creation() {
     let raft = RaftConsensus::new("node-1".to_string());
     // Raft should start as follower
+    assert_eq!(raft.status().role, RaftRole::Follower);
 }
 
 #[tokio::test]
 async fn test_raft_request_vote() {
     let raft = RaftConsensus::new("node-1".to_string());
-    let voted = raft.request_vote(1).await;
-    assert!(voted);
+    let response = raft.handle_request_vote(RequestVoteRequest {
+        term: 1,
+        candidate_id: "node-2".to_string(),
+        last_log_index: 0,
+        last_log_term: 0,
+    }).await.unwrap();
+    assert!(response.vote_granted);
+
+    // Only one vote per term
+    let response = raft.handle_request_vote(RequestVoteRequest {
+        term: 1,
+        candidate_id: "node-3".to_string(),
+        last_log_index: 0,
+        last_log_term: 0,
+    }).await.unwrap();
+    assert!(!response.vote_granted);
 }
 
 #[tokio::test]
@@ -21,11 +37,261 @@ async fn test_raft_append_entries() {
     let entries = vec![ConsensusEntry {
         term: 1,
         index: 1,
-        data: b"data".to_vec(),
+        data: RaftCommand::Noop.encode().unwrap(),
     }];
-    
-    let success = raft.append_entries(1, entries).await;
-    assert!(success);
+
+    let response = raft.handle_append_entries(AppendEntriesRequest {
+        term: 1,
+        leader_id: "node-2".to_string(),
+        prev_log_index: 0,
+        prev_log_term: 0,
+        entries,
+        leader_commit: 0,
+    }).await.unwrap();
+    assert!(response.success);
+    assert_eq!(response.match_index, 1);
+    assert_eq!(raft.leader_id(), Some("node-2".to_string()));
+}
+
+fn raft_cluster(ids: &[&str]) -> (Arc<LocalRaftNetwork>, Vec<Arc<RaftConsensus>>) {
+    let network = Arc::new(LocalRaftNetwork::new());
+    let voters: Vec<String> = ids.iter().map(|s| s.to_string()).collect();
+    let nodes: Vec<Arc<RaftConsensus>> = ids.iter().map(|id| {
+        let node = Arc::new(RaftConsensus::with_config(
+            id.to_string(),
+            voters.clone(),
+            RaftConfig::default(),
+            network.clone(),
+        ));
+        network.register(node.clone());
+        node
+    }).collect();
+    (network, nodes)
+}
+
+fn table_entry(table_id: u64, name: &str) -> MetadataCommand {
+    MetadataCommand::CreateTable(TableCatalogEntry {
+        table_id,
+        name: name.to_string(),
+        database: "default".to_string(),
+        schema_json: "{}".to_string(),
+    })
+}
+
+#[tokio::test]
+async fn test_raft_leader_election_and_replication() {
+    let (_network, nodes) = raft_cluster(&["n1", "n2", "n3"]);
+    nodes[0].start_election().await.unwrap();
+    assert!(nodes[0].is_leader());
+    assert_eq!(nodes[1].leader_id(), Some("n1".to_string()));
+
+    nodes[0].propose(table_entry(1, "events")).await.unwrap();
+    // Followers learn the commit index on the next heartbeat
+    nodes[0].replicate().await.unwrap();
+
+    for node in &nodes {
+        assert!(node.metadata().tables.contains_key(&1));
+    }
+}
+
+#[tokio::test]
+async fn test_raft_rejects_proposals_on_followers() {
+    let (_network, nodes) = raft_cluster(&["n1", "n2", "n3"]);
+    nodes[0].start_election().await.unwrap();
+    assert!(nodes[1].propose(table_entry(1, "events")).await.is_err());
+}
+
+#[tokio::test]
+async fn test_raft_no_commit_without_quorum() {
+    let (network, nodes) = raft_cluster(&["n1", "n2", "n3"]);
+    nodes[0].start_election().await.unwrap();
+    network.disconnect("n2");
+    network.disconnect("n3");
+
+    let before = nodes[0].status().commit_index;
+    nodes[0].propose(table_entry(7, "isolated")).await.unwrap();
+    assert_eq!(nodes[0].status().commit_index, before);
+    assert!(!nodes[0].metadata().tables.contains_key(&7));
+}
+
+#[tokio::test]
+async fn test_raft_joint_consensus_membership_change() {
+    let (network, nodes) = raft_cluster(&["n1", "n2", "n3"]);
+    let n4 = Arc::new(RaftConsensus::with_config(
+        "n4".to_string(),
+        vec![],
+        RaftConfig::default(),
+        network.clone(),
+    ));
+    network.register(n4.clone());
+
+    nodes[0].start_election().await.unwrap();
+    nodes[0].add_node("n4".to_string()).await.unwrap();
+    nodes[0].replicate().await.unwrap();
+
+    let membership = nodes[0].status().membership;
+    assert_eq!(membership, ClusterMembership::Stable {
+        voters: vec!["n1".to_string(), "n2".to_string(), "n3".to_string(), "n4".to_string()],
+    });
+    assert_eq!(n4.status().membership, membership);
+
+    nodes[0].remove_node("n1").await.unwrap();
+    nodes[0].replicate().await.unwrap();
+    // The leader steps down once the configuration without it commits
+    assert!(!nodes[0].is_leader());
+    assert!(!nodes[1].status().membership.contains("n1"));
+}
+
+/// Routes through a local network and records the addresses nodes learn
+struct AddressBook {
+    network: Arc<LocalRaftNetwork>,
+    peers: std::sync::Mutex<Vec<(String, String)>>,
+}
+
+#[async_trait::async_trait]
+impl RaftTransport for AddressBook {
+    async fn request_vote(&self, target: &str, request: RequestVoteRequest) -> narayana_core::Result<RequestVoteResponse> {
+        self.network.request_vote(target, request).await
+    }
+
+    async fn append_entries(&self, target: &str, request: AppendEntriesRequest) -> narayana_core::Result<AppendEntriesResponse> {
+        self.network.append_entries(target, request).await
+    }
+
+    fn add_peer(&self, node_id: &str, address: &str) {
+        self.peers.lock().unwrap().push((node_id.to_string(), address.to_string()));
+    }
+}
+
+#[tokio::test]
+async fn test_raft_join_replicates_the_address() {
+    let network = Arc::new(LocalRaftNetwork::new());
+    let book = |network: &Arc<LocalRaftNetwork>| Arc::new(AddressBook {
+        network: network.clone(),
+        peers: std::sync::Mutex::new(Vec::new()),
+    });
+    let (leader_book, joiner_book) = (book(&network), book(&network));
+    let leader = Arc::new(RaftConsensus::with_config("n1".to_string(), vec!["n1".to_string()], RaftConfig::default(), leader_book.clone()));
+    let joiner = Arc::new(RaftConsensus::with_config("n2".to_string(), vec![], RaftConfig::default(), joiner_book.clone()));
+    network.register(leader.clone());
+    network.register(joiner.clone());
+
+    leader.start_election().await.unwrap();
+    leader.join("n2".to_string(), "http://n2:8080".to_string()).await.unwrap();
+    leader.replicate().await.unwrap();
+
+    let registered = ("n2".to_string(), "http://n2:8080".to_string());
+    assert!(leader_book.peers.lock().unwrap().contains(&registered));
+    assert!(joiner_book.peers.lock().unwrap().contains(&registered));
+    assert_eq!(leader.metadata().nodes.get("n2").map(String::as_str), Some("http://n2:8080"));
+    assert_eq!(joiner.peer_address("n2").as_deref(), Some("http://n2:8080"));
+    assert_eq!(joiner.status().membership, ClusterMembership::Stable {
+        voters: vec!["n1".to_string(), "n2".to_string()],
+    });
+}
+
+#[tokio::test]
+async fn test_raft_state_survives_restart() {
+    use narayana_storage::wal::WriteAheadLog;
+
+    let dir = std::env::temp_dir().join(format!("narayana_raft_{}", uuid::Uuid::new_v4()));
+    let open = || async {
+        let log = Arc::new(WriteAheadLog::open(&dir).await.unwrap());
+        RaftConsensus::open("n1".to_string(), vec!["n1".to_string()], RaftConfig::default(), Arc::new(LocalRaftNetwork::new()), log)
+            .await
+            .unwrap()
+    };
+    {
+        let raft = open().await;
+        raft.start_election().await.unwrap();
+        raft.propose(table_entry(1, "events")).await.unwrap();
+        assert!(raft.metadata().tables.contains_key(&1));
+    }
+
+    let raft = open().await;
+    let status = raft.status();
+    assert_eq!((status.term, status.last_log_index, status.commit_index), (1, 2, 0));
+    // The recovered entries are applied once the next leader commits them
+    raft.start_election().await.unwrap();
+    assert_eq!(raft.status().term, 2);
+    assert!(raft.metadata().tables.contains_key(&1));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+/// Local node answering every join request with `response`; counts the requests
+async fn join_endpoint(response: serde_json::Value) -> (String, Arc<std::sync::atomic::AtomicUsize>) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let counter = requests.clone();
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let mut data = Vec::new();
+            let mut chunk = [0u8; 4096];
+            let header_end = loop {
+                let n = socket.read(&mut chunk).await.unwrap();
+                data.extend_from_slice(&chunk[..n]);
+                if let Some(pos) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+                    break pos + 4;
+                }
+            };
+            let head = String::from_utf8_lossy(&data[..header_end]).to_lowercase();
+            let length: usize = head
+                .lines()
+                .find_map(|line| line.strip_prefix("content-length: "))
+                .map_or(0, |length| length.trim().parse().unwrap());
+            while data.len() < header_end + length {
+                let n = socket.read(&mut chunk).await.unwrap();
+                data.extend_from_slice(&chunk[..n]);
+            }
+            counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            let body = response.to_string();
+            let reply = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            socket.write_all(reply.as_bytes()).await.unwrap();
+        }
+    });
+    (url, requests)
+}
+
+fn join_config(join: &str, attempts: u32) -> narayana_core::config::ClusterConfig {
+    let mut config = narayana_core::config::ClusterConfig::default();
+    config.join = Some(join.to_string());
+    config.advertise_url = Some("http://127.0.0.1:1".to_string());
+    config.join_attempts = attempts;
+    config.join_retry_interval = std::time::Duration::from_millis(10);
+    config
+}
+
+#[tokio::test]
+async fn test_join_follows_the_leader_address() {
+    let (leader, leader_requests) = join_endpoint(serde_json::json!({"accepted": true, "leader_id": "node-1"})).await;
+    let (follower, follower_requests) = join_endpoint(serde_json::json!({
+        "accepted": false,
+        "leader_id": "node-1",
+        "leader_address": leader,
+    }))
+    .await;
+    narayana_server::cluster::join_cluster(&join_config(&follower, 3), "node-3".to_string()).await.unwrap();
+    assert_eq!(follower_requests.load(std::sync::atomic::Ordering::SeqCst), 1);
+    assert_eq!(leader_requests.load(std::sync::atomic::Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_join_gives_up_after_its_attempts() {
+    // Mid-election: no node knows a leader
+    let (node, requests) = join_endpoint(serde_json::json!({"accepted": false, "leader_id": null})).await;
+    let err = narayana_server::cluster::join_cluster(&join_config(&node, 3), "node-3".to_string()).await.unwrap_err();
+    assert!(err.to_string().contains("after 3 attempts"), "{}", err);
+    assert_eq!(requests.load(std::sync::atomic::Ordering::SeqCst), 3);
+
+    let err = narayana_server::cluster::join_cluster(&join_config("http://127.0.0.1:1", 2), "node-3".to_string()).await.unwrap_err();
+    assert!(err.to_string().contains("failed"), "{}", err);
 }
 
 #[test]
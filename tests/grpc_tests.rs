@@ -0,0 +1,47 @@
+// gRPC brain service tests
+// Calls over a real connection must carry a bearer token from the HTTP API's TokenManager
+
+use narayana_api::grpc_advanced::brain_proto::{brain_service_client::BrainServiceClient, CreateThoughtRequest};
+use narayana_server::grpc::brain_service;
+use narayana_server::security::TokenManager;
+use narayana_storage::cognitive::CognitiveBrain;
+use std::sync::Arc;
+use tonic::transport::Channel;
+
+async fn serve(token_manager: Arc<TokenManager>) -> BrainServiceClient<Channel> {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(
+        tonic::transport::Server::builder()
+            .add_service(brain_service(Arc::new(CognitiveBrain::new()), token_manager))
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener)),
+    );
+    BrainServiceClient::connect(format!("http://{}", addr)).await.unwrap()
+}
+
+fn create_thought(token: Option<&str>) -> tonic::Request<CreateThoughtRequest> {
+    let mut request = tonic::Request::new(CreateThoughtRequest { content_json: r#"{"goal":"dock"}"#.to_string(), priority: 0.5 });
+    if let Some(token) = token {
+        request.metadata_mut().insert("authorization", format!("Bearer {}", token).parse().unwrap());
+    }
+    request
+}
+
+#[tokio::test]
+async fn test_brain_service_requires_a_valid_token() {
+    let token_manager = Arc::new(TokenManager::new("grpc-test-secret-that-is-long-enough".to_string()));
+    let mut client = serve(token_manager.clone()).await;
+
+    let missing = client.create_thought(create_thought(None)).await.unwrap_err();
+    assert_eq!(missing.code(), tonic::Code::Unauthenticated);
+
+    let forged = TokenManager::new("another-secret-that-is-long-enough!!".to_string())
+        .generate_token("robot".to_string(), vec!["user".to_string()])
+        .unwrap();
+    let rejected = client.create_thought(create_thought(Some(&forged))).await.unwrap_err();
+    assert_eq!(rejected.code(), tonic::Code::Unauthenticated);
+
+    let token = token_manager.generate_token("robot".to_string(), vec!["user".to_string()]).unwrap();
+    let created = client.create_thought(create_thought(Some(&token))).await.unwrap().into_inner();
+    assert!(!created.thought_id.is_empty());
+}
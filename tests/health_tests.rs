@@ -0,0 +1,182 @@
+// Liveness/readiness registry tests
+// Criticality, disabled checks, timeouts, caching and startup recovery progress
+
+use async_trait::async_trait;
+use narayana_core::config::HealthChecksConfig;
+use narayana_server::health::{CheckOutcome, HealthCheck, HealthRegistry, HealthStatus, RecoveryGate, StartupPhase};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+struct FixedCheck {
+    name: &'static str,
+    outcome: CheckOutcome,
+    delay: Duration,
+    cacheable: bool,
+    calls: AtomicUsize,
+}
+
+impl FixedCheck {
+    fn new(name: &'static str, outcome: CheckOutcome) -> Self {
+        Self { name, outcome, delay: Duration::ZERO, cacheable: false, calls: AtomicUsize::new(0) }
+    }
+}
+
+#[async_trait]
+impl HealthCheck for FixedCheck {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn cacheable(&self) -> bool {
+        self.cacheable
+    }
+
+    async fn check(&self) -> CheckOutcome {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        if !self.delay.is_zero() {
+            tokio::time::sleep(self.delay).await;
+        }
+        self.outcome.clone()
+    }
+}
+
+fn config(critical: &[&str]) -> HealthChecksConfig {
+    HealthChecksConfig {
+        critical: critical.iter().map(|c| c.to_string()).collect(),
+        ..HealthChecksConfig::default()
+    }
+}
+
+#[tokio::test]
+async fn test_all_up_is_ready() {
+    let registry = HealthRegistry::new(&config(&["storage"]));
+    registry.register(Arc::new(FixedCheck::new("storage", CheckOutcome::up())));
+    registry.register(Arc::new(FixedCheck::new("llm", CheckOutcome::up())));
+
+    let report = registry.readiness().await;
+    assert!(report.ready);
+    assert_eq!(report.status, HealthStatus::Up);
+    assert_eq!(report.checks.len(), 2);
+    assert!(report.checks.iter().find(|c| c.name == "storage").unwrap().critical);
+    assert!(!report.checks.iter().find(|c| c.name == "llm").unwrap().critical);
+}
+
+#[tokio::test]
+async fn test_critical_down_is_not_ready() {
+    let registry = HealthRegistry::new(&config(&["storage"]));
+    registry.register(Arc::new(FixedCheck::new("storage", CheckOutcome::down("disk gone"))));
+    registry.register(Arc::new(FixedCheck::new("llm", CheckOutcome::up())));
+
+    let report = registry.readiness().await;
+    assert!(!report.ready);
+    assert_eq!(report.status, HealthStatus::Down);
+    assert_eq!(report.checks[0].outcome.message.as_deref(), Some("disk gone"));
+}
+
+#[tokio::test]
+async fn test_non_critical_down_is_degraded_but_ready() {
+    let registry = HealthRegistry::new(&config(&["storage"]));
+    registry.register(Arc::new(FixedCheck::new("storage", CheckOutcome::up())));
+    registry.register(Arc::new(FixedCheck::new("llm", CheckOutcome::down("unreachable"))));
+
+    let report = registry.readiness().await;
+    assert!(report.ready);
+    assert_eq!(report.status, HealthStatus::Degraded);
+
+    // Making it critical through a config update flips readiness
+    registry.update(&config(&["storage", "llm"]));
+    assert!(!registry.readiness().await.ready);
+}
+
+#[tokio::test]
+async fn test_disabled_checks_are_skipped() {
+    let mut cfg = config(&["storage"]);
+    cfg.disabled = vec!["storage".to_string()];
+    let registry = HealthRegistry::new(&cfg);
+    let check = Arc::new(FixedCheck::new("storage", CheckOutcome::down("disk gone")));
+    registry.register(check.clone());
+
+    let report = registry.readiness().await;
+    assert!(report.ready);
+    assert!(report.checks.is_empty());
+    assert_eq!(check.calls.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn test_slow_check_times_out_as_down() {
+    let mut cfg = config(&["persistence"]);
+    cfg.check_timeout_ms = 20;
+    let registry = HealthRegistry::new(&cfg);
+    let mut slow = FixedCheck::new("persistence", CheckOutcome::up());
+    slow.delay = Duration::from_secs(5);
+    registry.register(Arc::new(slow));
+
+    let report = registry.readiness().await;
+    assert!(!report.ready);
+    assert_eq!(report.checks[0].outcome.status, HealthStatus::Down);
+    assert!(report.checks[0].outcome.message.as_deref().unwrap().contains("timed out"));
+}
+
+#[tokio::test]
+async fn test_cacheable_results_are_reused() {
+    let registry = HealthRegistry::new(&config(&[]));
+    let mut llm = FixedCheck::new("llm", CheckOutcome::up());
+    llm.cacheable = true;
+    let llm = Arc::new(llm);
+    registry.register(llm.clone());
+
+    assert!(!registry.readiness().await.checks[0].cached);
+    assert!(registry.readiness().await.checks[0].cached);
+    assert_eq!(llm.calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_recovery_gate_blocks_readiness_until_complete() {
+    let registry = HealthRegistry::new(&HealthChecksConfig::default());
+    let gate = Arc::new(RecoveryGate::new());
+    registry.register(gate.clone());
+
+    assert!(!registry.readiness().await.ready);
+    assert_eq!(registry.liveness().status, HealthStatus::Up);
+
+    gate.mark_complete(None);
+    let report = registry.readiness().await;
+    assert!(report.ready);
+    assert_eq!(report.checks[0].name, "wal_replay");
+}
+
+#[tokio::test]
+async fn test_recovery_progress_and_degraded_mode() {
+    let registry = HealthRegistry::new(&HealthChecksConfig::default());
+    let gate = Arc::new(RecoveryGate::new());
+    gate.set_serve_while_loading(true);
+    registry.register(gate.clone());
+
+    gate.enter_phase(StartupPhase::LoadingTables);
+    gate.set_table_progress(3, 1, 10);
+    let report = registry.readiness().await;
+    assert!(report.ready);
+    assert_eq!(report.status, HealthStatus::Degraded);
+    let details = &report.checks[0].outcome.details;
+    assert_eq!(details["phase"], "loading_tables");
+    assert_eq!(details["tables_loaded"], 3);
+    assert_eq!(details["tables_total"], 10);
+    assert_eq!(details["serving"], true);
+
+    gate.mark_complete(Some("1 tables could not be loaded".to_string()));
+    let progress = gate.progress();
+    assert_eq!(progress.phase, StartupPhase::Ready);
+    let report = registry.readiness().await;
+    assert_eq!(report.status, HealthStatus::Up);
+    assert_eq!(report.checks[0].outcome.message.as_deref(), Some("1 tables could not be loaded"));
+}
+
+#[tokio::test]
+async fn test_register_replaces_same_name() {
+    let registry = HealthRegistry::new(&config(&["storage"]));
+    registry.register(Arc::new(FixedCheck::new("storage", CheckOutcome::down("old"))));
+    registry.register(Arc::new(FixedCheck::new("storage", CheckOutcome::up())));
+    assert_eq!(registry.check_names(), vec!["storage".to_string()]);
+    assert!(registry.readiness().await.ready);
+}
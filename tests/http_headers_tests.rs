@@ -0,0 +1,112 @@
+// CORS and security header middleware tests
+// Preflights, origin matching, per-route overrides and hot reload
+
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{header, Method, StatusCode},
+    middleware::{self, Next},
+    routing::get,
+    Router,
+};
+use narayana_core::config::{CorsPolicyConfig, NetworkConfig, RouteHeadersOverride};
+use narayana_server::http_headers::{origin_allowed, HttpHeadersPolicy};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+fn network() -> NetworkConfig {
+    let mut network = NetworkConfig {
+        cors_origins: vec!["https://app.example.com".to_string(), "https://*.tools.example.com".to_string()],
+        ..Default::default()
+    };
+    network.cors.allow_credentials = true;
+    network.route_headers.push(RouteHeadersOverride {
+        path_prefix: "/api/v1/public".to_string(),
+        enable_cors: None,
+        cors_origins: Some(vec!["*".to_string()]),
+        cors: Some(CorsPolicyConfig::default()),
+        security_headers: None,
+    });
+    network
+}
+
+fn app(policy: Arc<HttpHeadersPolicy>) -> Router {
+    Router::new()
+        .route("/api/v1/tables", get(|| async { "tables" }))
+        .route("/api/v1/public/status", get(|| async { "ok" }))
+        .layer(middleware::from_fn(move |request: Request, next: Next| {
+            let policy = policy.clone();
+            async move { policy.apply(request, next).await }
+        }))
+}
+
+fn preflight(path: &str, origin: &str, request_headers: &str) -> Request {
+    Request::builder()
+        .method(Method::OPTIONS)
+        .uri(path)
+        .header(header::ORIGIN, origin)
+        .header(header::ACCESS_CONTROL_REQUEST_METHOD, "DELETE")
+        .header(header::ACCESS_CONTROL_REQUEST_HEADERS, request_headers)
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[test]
+fn test_origin_matching() {
+    let allowed = network().cors_origins;
+    assert!(origin_allowed(&allowed, "https://app.example.com"));
+    assert!(origin_allowed(&allowed, "https://ci.tools.example.com"));
+    assert!(!origin_allowed(&allowed, "http://app.example.com"));
+    assert!(!origin_allowed(&allowed, "https://evil.com"));
+    assert!(!origin_allowed(&allowed, "https://tools.example.com.evil.com"));
+}
+
+#[tokio::test]
+async fn test_preflight_checks_origin_method_and_headers() {
+    let app = app(Arc::new(HttpHeadersPolicy::new(&network())));
+
+    let ok = app.clone().oneshot(preflight("/api/v1/tables", "https://app.example.com", "authorization")).await.unwrap();
+    assert_eq!(ok.status(), StatusCode::NO_CONTENT);
+    assert_eq!(ok.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], "https://app.example.com");
+    assert_eq!(ok.headers()[header::ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+
+    let evil = app.clone().oneshot(preflight("/api/v1/tables", "https://evil.com", "authorization")).await.unwrap();
+    assert_eq!(evil.status(), StatusCode::FORBIDDEN);
+    assert!(!evil.headers().contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+
+    let custom = app.oneshot(preflight("/api/v1/tables", "https://app.example.com", "x-custom")).await.unwrap();
+    assert_eq!(custom.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_route_override_and_security_headers() {
+    let app = app(Arc::new(HttpHeadersPolicy::new(&network())));
+    let request = Request::builder()
+        .uri("/api/v1/public/status")
+        .header(header::ORIGIN, "https://anyone.example.org")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    // Wildcard origins never carry credentials
+    assert_eq!(response.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+    assert!(!response.headers().contains_key(header::ACCESS_CONTROL_ALLOW_CREDENTIALS));
+    assert_eq!(response.headers()[header::X_CONTENT_TYPE_OPTIONS], "nosniff");
+    assert_eq!(response.headers()[header::X_FRAME_OPTIONS], "DENY");
+    assert!(response.headers().contains_key(header::CONTENT_SECURITY_POLICY));
+}
+
+#[tokio::test]
+async fn test_policy_update_applies_without_restart() {
+    let policy = Arc::new(HttpHeadersPolicy::new(&network()));
+    let app = app(policy.clone());
+
+    let mut reloaded = network();
+    reloaded.cors_origins.push("https://admin.example.com".to_string());
+    reloaded.security_headers.hsts_max_age_secs = Some(31536000);
+    policy.update(&reloaded);
+
+    let response = app.oneshot(preflight("/api/v1/tables", "https://admin.example.com", "content-type")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert_eq!(response.headers()[header::STRICT_TRANSPORT_SECURITY], "max-age=31536000; includeSubDomains");
+}
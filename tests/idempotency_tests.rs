@@ -0,0 +1,171 @@
+// Idempotency-Key middleware tests
+// Replay of stored responses, key reuse with a different body, scoping and
+// requests that are not stored, expiry and eviction past max_entries
+
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{Method, StatusCode},
+    middleware::{self, Next},
+    routing::post,
+    Router,
+};
+use narayana_core::config::NetworkConfig;
+use narayana_server::idempotency::{IdempotencyStore, IDEMPOTENT_REPLAYED_HEADER};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+fn app(store: Arc<IdempotencyStore>, calls: Arc<AtomicUsize>) -> Router {
+    let created = calls.clone();
+    let failing = calls;
+    Router::new()
+        .route("/api/v1/tables/1/insert", post(move |body: String| {
+            let calls = created.clone();
+            async move {
+                let n = calls.fetch_add(1, Ordering::SeqCst) + 1;
+                (StatusCode::CREATED, format!("insert #{} {}", n, body))
+            }
+        }))
+        .route("/api/v1/fail", post(move || {
+            let calls = failing.clone();
+            async move {
+                calls.fetch_add(1, Ordering::SeqCst);
+                StatusCode::SERVICE_UNAVAILABLE
+            }
+        }))
+        .layer(middleware::from_fn(move |request: Request, next: Next| {
+            let store = store.clone();
+            async move {
+                let scope = request.headers().get("x-user")
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or("anonymous")
+                    .to_string();
+                store.apply(&scope, request, next).await
+            }
+        }))
+}
+
+fn request(uri: &str, key: Option<&str>, user: &str, body: &str) -> Request<Body> {
+    let mut builder = Request::builder().method(Method::POST).uri(uri).header("x-user", user);
+    if let Some(key) = key {
+        builder = builder.header("Idempotency-Key", key);
+    }
+    builder.body(Body::from(body.to_string())).unwrap()
+}
+
+async fn send(app: &Router, request: Request<Body>) -> (StatusCode, bool, String) {
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let replayed = response.headers().contains_key(IDEMPOTENT_REPLAYED_HEADER);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, replayed, String::from_utf8(body.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn test_replay_returns_original_response() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let app = app(Arc::new(IdempotencyStore::new(&NetworkConfig::default())), calls.clone());
+
+    let first = send(&app, request("/api/v1/tables/1/insert", Some("k1"), "alice", "row")).await;
+    assert_eq!(first, (StatusCode::CREATED, false, "insert #1 row".to_string()));
+
+    let retry = send(&app, request("/api/v1/tables/1/insert", Some("k1"), "alice", "row")).await;
+    assert_eq!(retry, (StatusCode::CREATED, true, "insert #1 row".to_string()));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    // Without a key every request runs
+    send(&app, request("/api/v1/tables/1/insert", None, "alice", "row")).await;
+    send(&app, request("/api/v1/tables/1/insert", None, "alice", "row")).await;
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_key_reuse_with_different_request_is_rejected() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let app = app(Arc::new(IdempotencyStore::new(&NetworkConfig::default())), calls.clone());
+
+    send(&app, request("/api/v1/tables/1/insert", Some("k2"), "alice", "a")).await;
+    let (status, _, _) = send(&app, request("/api/v1/tables/1/insert", Some("k2"), "alice", "b")).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_keys_are_scoped_per_caller() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let app = app(Arc::new(IdempotencyStore::new(&NetworkConfig::default())), calls.clone());
+
+    send(&app, request("/api/v1/tables/1/insert", Some("shared"), "alice", "row")).await;
+    let (_, replayed, body) = send(&app, request("/api/v1/tables/1/insert", Some("shared"), "bob", "row")).await;
+    assert!(!replayed);
+    assert_eq!(body, "insert #2 row");
+}
+
+#[tokio::test]
+async fn test_server_errors_are_not_stored() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let store = Arc::new(IdempotencyStore::new(&NetworkConfig::default()));
+    let app = app(store.clone(), calls.clone());
+
+    for _ in 0..2 {
+        let (status, replayed, _) = send(&app, request("/api/v1/fail", Some("k3"), "alice", "")).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert!(!replayed);
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert!(store.is_empty());
+}
+
+#[tokio::test]
+async fn test_invalid_key_and_disabled_store() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let app_enabled = app(Arc::new(IdempotencyStore::new(&NetworkConfig::default())), calls.clone());
+    let long_key = "k".repeat(300);
+    let (status, _, _) = send(&app_enabled, request("/api/v1/tables/1/insert", Some(&long_key), "alice", "row")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let mut network = NetworkConfig::default();
+    network.idempotency.enabled = false;
+    let app_disabled = app(Arc::new(IdempotencyStore::new(&network)), calls.clone());
+    send(&app_disabled, request("/api/v1/tables/1/insert", Some("k4"), "alice", "row")).await;
+    let (_, replayed, _) = send(&app_disabled, request("/api/v1/tables/1/insert", Some("k4"), "alice", "row")).await;
+    assert!(!replayed);
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_oldest_keys_are_evicted_past_max_entries() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let mut network = NetworkConfig::default();
+    network.idempotency.max_entries = 2;
+    let store = Arc::new(IdempotencyStore::new(&network));
+    let app = app(store.clone(), calls.clone());
+
+    for key in ["a", "b", "c"] {
+        send(&app, request("/api/v1/tables/1/insert", Some(key), "alice", "row")).await;
+    }
+    assert_eq!(store.len(), 2);
+    // "c" is still replayed; "a" was evicted, so its retry runs again
+    let (_, replayed, _) = send(&app, request("/api/v1/tables/1/insert", Some("c"), "alice", "row")).await;
+    assert!(replayed);
+    let (_, replayed, _) = send(&app, request("/api/v1/tables/1/insert", Some("a"), "alice", "row")).await;
+    assert!(!replayed);
+    assert_eq!(calls.load(Ordering::SeqCst), 4);
+}
+
+#[tokio::test]
+async fn test_expired_keys_are_dropped() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let mut network = NetworkConfig::default();
+    network.idempotency.window_secs = 0;
+    let store = Arc::new(IdempotencyStore::new(&network));
+    let app = app(store.clone(), calls.clone());
+
+    for _ in 0..3 {
+        let (_, replayed, _) = send(&app, request("/api/v1/tables/1/insert", Some("k5"), "alice", "row")).await;
+        assert!(!replayed);
+    }
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+    assert_eq!(store.len(), 1);
+}
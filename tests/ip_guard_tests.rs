@@ -0,0 +1,131 @@
+// Per-IP guard tests
+// CIDR matching, trusted-proxy client resolution, sliding-window limits,
+// admin allow/deny lists and greylisting after auth failures
+
+use narayana_core::config::IpAccessConfig;
+use narayana_server::ip_guard::{IpDecision, IpGuard, IpRange};
+use std::net::IpAddr;
+
+fn ip(value: &str) -> IpAddr {
+    value.parse().unwrap()
+}
+
+fn config() -> IpAccessConfig {
+    IpAccessConfig {
+        requests_per_window: 20,
+        window_secs: 60,
+        exempt: vec![],
+        greylist_after_failures: 3,
+        greylist_divisor: 4,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_ip_range_matching() {
+    let v4 = IpRange::parse("10.1.0.0/16").unwrap();
+    assert!(v4.contains(&ip("10.1.200.3")));
+    assert!(!v4.contains(&ip("10.2.0.1")));
+    assert!(v4.contains(&ip("::ffff:10.1.0.9")));
+
+    let v6 = IpRange::parse("2001:db8::/32").unwrap();
+    assert!(v6.contains(&ip("2001:db8:1::1")));
+    assert!(!v6.contains(&ip("2001:db9::1")));
+
+    assert!(IpRange::parse("192.168.1.7").unwrap().contains(&ip("192.168.1.7")));
+    assert!(IpRange::parse("0.0.0.0/0").unwrap().contains(&ip("8.8.8.8")));
+    assert!(IpRange::parse("10.0.0.0/33").is_none());
+    assert!(IpRange::parse("not-an-ip").is_none());
+}
+
+#[test]
+fn test_client_ip_only_trusts_configured_proxies() {
+    let guard = IpGuard::new(&IpAccessConfig {
+        trusted_proxies: vec!["10.0.0.0/8".to_string()],
+        ..config()
+    });
+
+    // Untrusted peer: forwarded header is ignored
+    assert_eq!(guard.client_ip(Some(ip("203.0.113.5")), Some("1.2.3.4")), Some(ip("203.0.113.5")));
+    // Trusted proxy chain: rightmost untrusted hop wins
+    assert_eq!(
+        guard.client_ip(Some(ip("10.0.0.2")), Some("6.6.6.6, 198.51.100.7, 10.0.0.9")),
+        Some(ip("198.51.100.7"))
+    );
+    // Trusted proxy without header falls back to the peer
+    assert_eq!(guard.client_ip(Some(ip("10.0.0.2")), None), Some(ip("10.0.0.2")));
+}
+
+#[test]
+fn test_sliding_window_limit_per_ip() {
+    let guard = IpGuard::new(&config());
+    let client = ip("198.51.100.1");
+    for _ in 0..20 {
+        assert_eq!(guard.check(client, "/api/v1/tables", false), IpDecision::Allow);
+    }
+    assert!(matches!(guard.check(client, "/api/v1/tables", false), IpDecision::RateLimited { .. }));
+    // Other addresses have their own window
+    assert_eq!(guard.check(ip("198.51.100.2"), "/api/v1/tables", false), IpDecision::Allow);
+    assert_eq!(guard.stats().blocked_rate_limited, 1);
+}
+
+#[test]
+fn test_exempt_addresses_are_not_limited() {
+    let guard = IpGuard::new(&IpAccessConfig {
+        exempt: vec!["127.0.0.0/8".to_string()],
+        ..config()
+    });
+    for _ in 0..100 {
+        assert_eq!(guard.check(ip("127.0.0.1"), "/api/v1/tables", false), IpDecision::Allow);
+    }
+}
+
+#[test]
+fn test_admin_allow_and_deny_lists() {
+    let guard = IpGuard::new(&IpAccessConfig {
+        admin_allow: vec!["10.0.0.0/8".to_string()],
+        admin_deny: vec!["10.9.0.0/16".to_string()],
+        ..config()
+    });
+    assert_eq!(guard.check(ip("10.1.2.3"), "/api/v1/schema/load", false), IpDecision::Allow);
+    assert_eq!(guard.check(ip("10.9.2.3"), "/api/v1/schema/load", false), IpDecision::Forbidden);
+    assert_eq!(guard.check(ip("203.0.113.9"), "/api/v1/system/stats", false), IpDecision::Forbidden);
+    // Non-admin paths and look-alike prefixes are unaffected
+    assert_eq!(guard.check(ip("203.0.113.9"), "/api/v1/tables", false), IpDecision::Allow);
+    assert_eq!(guard.check(ip("203.0.113.9"), "/api/v1/schemas", false), IpDecision::Allow);
+    assert_eq!(guard.stats().blocked_admin_forbidden, 2);
+}
+
+#[test]
+fn test_greylisting_after_auth_failures() {
+    let guard = IpGuard::new(&config());
+    let client = ip("198.51.100.20");
+    guard.record_auth_failure(client);
+    guard.record_auth_failure(client);
+    assert_eq!(guard.check(client, "/api/v1/auth/login", true), IpDecision::Allow);
+
+    guard.record_auth_failure(client);
+    assert!(matches!(guard.check(client, "/api/v1/auth/login", true), IpDecision::Greylisted { .. }));
+
+    // Other requests get the reduced limit (20 / 4, one already used above)
+    for _ in 0..4 {
+        assert_eq!(guard.check(client, "/api/v1/tables", false), IpDecision::Allow);
+    }
+    assert!(matches!(guard.check(client, "/api/v1/tables", false), IpDecision::RateLimited { .. }));
+
+    let stats = guard.stats();
+    assert_eq!(stats.greylisted.len(), 1);
+    assert_eq!(stats.greylisted[0].ip, "198.51.100.20");
+    assert_eq!(stats.blocked_greylisted, 1);
+}
+
+#[test]
+fn test_update_replaces_rules() {
+    let guard = IpGuard::new(&config());
+    assert_eq!(guard.check(ip("203.0.113.1"), "/metrics", false), IpDecision::Allow);
+    guard.update(&IpAccessConfig {
+        admin_deny: vec!["203.0.113.0/24".to_string()],
+        ..config()
+    });
+    assert_eq!(guard.check(ip("203.0.113.1"), "/metrics", false), IpDecision::Forbidden);
+}
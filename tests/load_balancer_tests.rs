@@ -0,0 +1,174 @@
+// Load balancer routing tests
+// Consistent hashing on caller-supplied keys, circuit breakers with half-open
+// probing, and outlier ejection
+
+use narayana_storage::advanced_load_balancer::*;
+use std::time::Duration;
+
+fn balancer(config: AdvancedLoadBalancerConfig, nodes: &[&str]) -> AdvancedLoadBalancer {
+    let lb = AdvancedLoadBalancer::new(config);
+    for (i, id) in nodes.iter().enumerate() {
+        lb.add_node(LoadBalancerNode {
+            id: id.to_string(),
+            address: format!("127.0.0.1:{}", 8080 + i),
+            health_status: HealthStatus::Healthy,
+            ..Default::default()
+        });
+    }
+    lb
+}
+
+fn route(lb: &AdvancedLoadBalancer, key: &str) -> String {
+    lb.select_node(Some(RequestContext::with_routing_key(key))).unwrap().unwrap()
+}
+
+#[test]
+fn test_consistent_hash_keeps_key_affinity() {
+    let config = AdvancedLoadBalancerConfig {
+        strategy: LoadBalancingStrategy::ConsistentHashing,
+        enable_circuit_breaker: false,
+        ..Default::default()
+    };
+    let lb = balancer(config, &["node-1", "node-2", "node-3"]);
+    let robots: Vec<String> = (0..200).map(|i| format!("robot-{}", i)).collect();
+    let before: Vec<String> = robots.iter().map(|r| route(&lb, r)).collect();
+
+    // Same key, same backend
+    for (robot, node) in robots.iter().zip(&before) {
+        assert_eq!(&route(&lb, robot), node);
+    }
+    // Every backend gets a share
+    for node in ["node-1", "node-2", "node-3"] {
+        assert!(before.iter().any(|n| n == node), "{} got no keys", node);
+    }
+
+    // Losing a backend only moves the keys it owned
+    lb.remove_node("node-2");
+    for (robot, node) in robots.iter().zip(&before) {
+        let now = route(&lb, robot);
+        if node != "node-2" {
+            assert_eq!(&now, node);
+        } else {
+            assert_ne!(now, "node-2");
+        }
+    }
+}
+
+#[test]
+fn test_routing_key_takes_precedence() {
+    let config = AdvancedLoadBalancerConfig {
+        strategy: LoadBalancingStrategy::RendezvousHashing,
+        ..Default::default()
+    };
+    let lb = balancer(config, &["node-1", "node-2", "node-3", "node-4"]);
+    let expected = route(&lb, "robot-7");
+    for session in 0..20 {
+        let context = RequestContext {
+            session_id: Some(format!("session-{}", session)),
+            ..RequestContext::with_routing_key("robot-7")
+        };
+        assert_eq!(lb.select_node(Some(context)).unwrap().unwrap(), expected);
+    }
+}
+
+#[test]
+fn test_circuit_breaker_half_open_probing() {
+    let config = AdvancedLoadBalancerConfig {
+        strategy: LoadBalancingStrategy::ConsistentHashing,
+        circuit_breaker_failure_threshold: 3,
+        circuit_breaker_success_threshold: 2,
+        circuit_breaker_timeout: Duration::from_millis(50),
+        circuit_breaker_half_open_max_requests: 1,
+        ..Default::default()
+    };
+    let lb = balancer(config, &["node-1"]);
+
+    for _ in 0..3 {
+        lb.record_result("node-1", false, 10.0);
+    }
+    assert_eq!(lb.circuit_breaker_state("node-1"), Some(CircuitBreakerState::Open));
+    assert_eq!(lb.get_node("node-1").unwrap().circuit_breaker_state, CircuitBreakerState::Open);
+    assert_eq!(lb.select_node(None).unwrap(), None);
+
+    // After the timeout one probe at a time goes through
+    std::thread::sleep(Duration::from_millis(60));
+    assert_eq!(route(&lb, "robot-1"), "node-1");
+    assert_eq!(lb.circuit_breaker_state("node-1"), Some(CircuitBreakerState::HalfOpen));
+    assert_eq!(lb.select_node(None).unwrap(), None);
+
+    // A failed probe reopens the breaker
+    lb.record_result("node-1", false, 10.0);
+    assert_eq!(lb.circuit_breaker_state("node-1"), Some(CircuitBreakerState::Open));
+    assert_eq!(lb.select_node(None).unwrap(), None);
+
+    // Enough successful probes close it
+    std::thread::sleep(Duration::from_millis(60));
+    for _ in 0..2 {
+        assert_eq!(route(&lb, "robot-1"), "node-1");
+        lb.record_result("node-1", true, 10.0);
+    }
+    assert_eq!(lb.circuit_breaker_state("node-1"), Some(CircuitBreakerState::Closed));
+    assert_eq!(lb.get_node("node-1").unwrap().circuit_breaker_state, CircuitBreakerState::Closed);
+    assert_eq!(lb.stats().circuit_breakers_open, 0);
+}
+
+#[test]
+fn test_outlier_ejection() {
+    let config = AdvancedLoadBalancerConfig {
+        strategy: LoadBalancingStrategy::ConsistentHashing,
+        enable_circuit_breaker: false,
+        outlier_ejection: OutlierEjectionConfig {
+            enabled: true,
+            consecutive_failures: 3,
+            base_ejection_time: Duration::from_millis(50),
+            max_ejection_percent: 50.0,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let lb = balancer(config, &["node-1", "node-2"]);
+    let robot = (0..100)
+        .map(|i| format!("robot-{}", i))
+        .find(|r| route(&lb, r) == "node-1")
+        .unwrap();
+
+    for _ in 0..3 {
+        lb.record_result("node-1", false, 10.0);
+    }
+    assert!(lb.is_ejected("node-1"));
+    assert_eq!(lb.stats().nodes_ejected, 1);
+    assert_eq!(route(&lb, &robot), "node-2");
+
+    // Never every backend at once
+    for _ in 0..3 {
+        lb.record_result("node-2", false, 10.0);
+    }
+    assert!(!lb.is_ejected("node-2"));
+
+    // The ejection expires and the key returns home
+    std::thread::sleep(Duration::from_millis(60));
+    assert!(!lb.is_ejected("node-1"));
+    assert_eq!(route(&lb, &robot), "node-1");
+}
+
+#[test]
+fn test_outlier_ejection_on_error_rate() {
+    let config = AdvancedLoadBalancerConfig {
+        enable_circuit_breaker: false,
+        outlier_ejection: OutlierEjectionConfig {
+            enabled: true,
+            consecutive_failures: 0,
+            error_rate_threshold: 0.5,
+            min_requests: 10,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let lb = balancer(config, &["node-1", "node-2", "node-3"]);
+    for i in 0..9 {
+        lb.record_result("node-1", i % 2 == 0, 10.0);
+    }
+    assert!(!lb.is_ejected("node-1"));
+    lb.record_result("node-1", false, 10.0);
+    assert!(lb.is_ejected("node-1"));
+}
@@ -0,0 +1,263 @@
+// Natural-language query tests
+// Questions answered through a scripted model: the generated spec is checked
+// against the catalog, run read-only under the caller's row policies, and
+// returned with its plan and SQL
+
+use async_trait::async_trait;
+use narayana_core::column::Column;
+use narayana_core::schema::{DataType, Field, Schema};
+use narayana_core::types::{CompressionType, TableId};
+use narayana_query::cancellation::QueryContext;
+use narayana_query::plan::PlanNode;
+use narayana_server::nlq::{parse_reply, NaturalLanguageQuery, NlqError, QueryModel};
+use narayana_storage::block::BlockMetadata;
+use narayana_storage::column_store::{ColumnStore, InMemoryColumnStore};
+use narayana_storage::database_manager::DatabaseManager;
+use narayana_storage::persistent_column_store::PersistentColumnStore;
+use narayana_storage::row_security::{Principal, RowPolicy};
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+
+/// Replies with the scripted answers in order, recording what it was asked
+struct ScriptedModel {
+    replies: Mutex<Vec<String>>,
+    prompts: Mutex<Vec<(String, String)>>,
+    available: bool,
+}
+
+impl ScriptedModel {
+    fn new(replies: &[&str]) -> Arc<Self> {
+        Arc::new(Self {
+            replies: Mutex::new(replies.iter().rev().map(|r| r.to_string()).collect()),
+            prompts: Mutex::new(Vec::new()),
+            available: true,
+        })
+    }
+}
+
+#[async_trait]
+impl QueryModel for ScriptedModel {
+    fn available(&self) -> bool {
+        self.available
+    }
+
+    async fn complete(&self, system: &str, user: &str) -> Result<String, String> {
+        self.prompts.lock().unwrap().push((system.to_string(), user.to_string()));
+        self.replies.lock().unwrap().pop().ok_or_else(|| "no reply scripted".to_string())
+    }
+}
+
+fn field(name: &str, data_type: DataType) -> Field {
+    Field { name: name.to_string(), data_type, nullable: false, default_value: None }
+}
+
+async fn setup(model: Arc<ScriptedModel>) -> (NaturalLanguageQuery, Arc<DatabaseManager>) {
+    let store: Arc<dyn ColumnStore> = Arc::new(InMemoryColumnStore::new());
+    let db_manager = Arc::new(DatabaseManager::new());
+    let db = db_manager.create_database("default".to_string()).unwrap();
+    let schema = Schema::new(vec![
+        field("region", DataType::String),
+        field("customer", DataType::String),
+        field("amount", DataType::Int64),
+    ]);
+    let orders = db_manager.create_table(db, "orders".to_string(), schema.clone()).unwrap();
+    store.create_table(orders, schema).await.unwrap();
+    store
+        .write_columns(orders, vec![
+            Column::String(vec!["eu".into(), "us".into(), "eu".into(), "apac".into()]),
+            Column::String(vec!["ann".into(), "bob".into(), "cy".into(), "di".into()]),
+            Column::Int64(vec![10, 20, 30, 5]),
+        ])
+        .await
+        .unwrap();
+    let secrets_schema = Schema::new(vec![field("password", DataType::String)]);
+    let secrets = db_manager.create_table(db, "secrets".to_string(), secrets_schema.clone()).unwrap();
+    store.create_table(secrets, secrets_schema).await.unwrap();
+
+    let nlq = NaturalLanguageQuery::new(model, store, db_manager.clone()).with_hidden_tables(&["secrets"]);
+    (nlq, db_manager)
+}
+
+fn anyone() -> Principal {
+    Principal::new("user-1", Vec::new())
+}
+
+#[tokio::test]
+async fn test_question_runs_generated_query() {
+    let model = ScriptedModel::new(&[
+        "```json\n{\"table\": \"orders\", \"columns\": [\"customer\", \"amount\"], \
+\"filter\": {\"Gte\": {\"column\": \"amount\", \"value\": 10}}, \
+\"order_by\": [{\"column\": \"amount\", \"ascending\": false}], \"limit\": 2}\n```",
+        "The largest orders are cy's (30) and bob's (20).",
+    ]);
+    let (nlq, _) = setup(model.clone()).await;
+
+    let answer = nlq.ask("Which two orders of at least 10 are largest?", None, true, anyone(), &QueryContext::detached()).await.unwrap();
+    assert_eq!(answer.columns, vec!["customer", "amount"]);
+    assert_eq!(answer.rows, vec![json!({"customer": "cy", "amount": 30}), json!({"customer": "bob", "amount": 20})]);
+    assert_eq!(answer.row_count, 2);
+    assert!(!answer.truncated);
+    assert_eq!(answer.sql, "SELECT customer, amount FROM orders WHERE amount >= 10 ORDER BY amount DESC LIMIT 2");
+    assert!(matches!(answer.plan, PlanNode::Limit { limit: 2, .. }));
+    assert_eq!(answer.summary.as_deref(), Some("The largest orders are cy's (30) and bob's (20)."));
+
+    // The catalog grounds the translation; hidden tables stay out of it
+    let prompts = model.prompts.lock().unwrap();
+    assert!(prompts[0].0.contains("- orders (region String, customer String, amount Int64)"));
+    assert!(!prompts[0].0.contains("secrets"));
+    assert!(prompts[1].1.contains(&answer.sql));
+}
+
+#[tokio::test]
+async fn test_aggregates_group_and_order() {
+    let model = ScriptedModel::new(&[
+        "{\"table\": \"orders\", \"group_by\": [\"region\"], \
+\"aggregates\": [{\"Count\": {\"column\": null}}, {\"Sum\": {\"column\": \"amount\"}}], \
+\"filter\": {\"Not\": {\"expr\": {\"In\": {\"column\": \"region\", \"values\": [\"apac\"]}}}}, \
+\"order_by\": [{\"column\": \"sum_amount\", \"ascending\": false}]}",
+    ]);
+    let (nlq, _) = setup(model).await;
+
+    let answer = nlq.ask("Total order amount per region outside apac", None, false, anyone(), &QueryContext::detached()).await.unwrap();
+    assert_eq!(answer.columns, vec!["region", "count", "sum_amount"]);
+    assert_eq!(answer.rows, vec![
+        json!({"region": "eu", "count": 2, "sum_amount": 40.0}),
+        json!({"region": "us", "count": 1, "sum_amount": 20.0}),
+    ]);
+    assert_eq!(
+        answer.sql,
+        "SELECT region, COUNT(*) AS count, SUM(amount) AS sum_amount FROM orders \
+WHERE NOT region IN ('apac') GROUP BY region ORDER BY sum_amount DESC LIMIT 100"
+    );
+    assert!(answer.summary.is_none());
+}
+
+#[tokio::test]
+async fn test_replies_are_checked_against_the_catalog() {
+    let replies = [
+        ("{\"table\": \"orders\", \"columns\": [\"total\"]}", "Unknown result column 'total'"),
+        ("{\"table\": \"orders\", \"filter\": {\"Eq\": {\"column\": \"vip\", \"value\": true}}}", "Unknown column 'vip'"),
+        ("{\"table\": \"secrets\"}", "Unknown table 'secrets'"),
+        ("{\"table\": \"orders\", \"aggregates\": [{\"Sum\": {\"column\": \"amount\"}}], \"columns\": [\"amount\"]}", "Unknown result column 'amount'"),
+        ("{\"table\": \"orders\", \"filter\": {\"Match\": {\"column\": \"customer\", \"query\": \"ann\"}}}", "not supported"),
+        ("{\"error\": \"there is no shipping data\"}", "there is no shipping data"),
+        ("I don't know", "did not reply with a query"),
+    ];
+    for (reply, expected) in replies {
+        let (nlq, _) = setup(ScriptedModel::new(&[reply])).await;
+        match nlq.ask("question", None, false, anyone(), &QueryContext::detached()).await {
+            Err(NlqError::Unanswerable { message, reply: said }) => {
+                assert!(message.contains(expected), "{} should mention {}", message, expected);
+                assert_eq!(said, reply);
+            }
+            other => panic!("expected an unanswerable question for {}, got {:?}", reply, other.map(|a| a.sql)),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_queries_run_under_row_policies() {
+    let model = ScriptedModel::new(&["{\"table\": \"orders\", \"columns\": [\"customer\"]}"]);
+    let (nlq, db_manager) = setup(model).await;
+    let orders = db_manager.get_table_by_name("default", "orders").unwrap();
+    db_manager.row_security().set_policy(orders, RowPolicy {
+        name: "own_region".to_string(),
+        expression: "region == $tenant".to_string(),
+        roles: Vec::new(),
+    }).unwrap();
+
+    let principal = Principal::new("user-1", Vec::new()).with_tenant(Some("eu".to_string()));
+    let answer = nlq.ask("Who ordered?", None, false, principal, &QueryContext::detached()).await.unwrap();
+    assert_eq!(answer.rows, vec![json!({"customer": "ann"}), json!({"customer": "cy"})]);
+}
+
+#[tokio::test]
+async fn test_questions_need_a_provider() {
+    let model = Arc::new(ScriptedModel {
+        replies: Mutex::new(Vec::new()),
+        prompts: Mutex::new(Vec::new()),
+        available: false,
+    });
+    let (nlq, _) = setup(model).await;
+    assert!(matches!(nlq.ask("Who ordered?", None, false, anyone(), &QueryContext::detached()).await, Err(NlqError::NoProvider)));
+    assert!(matches!(nlq.ask("  ", None, false, anyone(), &QueryContext::detached()).await, Err(NlqError::InvalidQuestion(_))));
+}
+
+/// Records the row ranges read from the wrapped store
+struct RecordingStore {
+    inner: PersistentColumnStore,
+    reads: Mutex<Vec<(usize, usize)>>,
+}
+
+#[async_trait]
+impl ColumnStore for RecordingStore {
+    async fn create_table(&self, table_id: TableId, schema: Schema) -> narayana_core::Result<()> {
+        self.inner.create_table(table_id, schema).await
+    }
+
+    async fn write_columns(&self, table_id: TableId, columns: Vec<Column>) -> narayana_core::Result<()> {
+        self.inner.write_columns(table_id, columns).await
+    }
+
+    async fn read_columns(
+        &self,
+        table_id: TableId,
+        column_ids: Vec<u32>,
+        row_start: usize,
+        row_count: usize,
+    ) -> narayana_core::Result<Vec<Column>> {
+        self.reads.lock().unwrap().push((row_start, row_count));
+        self.inner.read_columns(table_id, column_ids, row_start, row_count).await
+    }
+
+    async fn get_schema(&self, table_id: TableId) -> narayana_core::Result<Schema> {
+        self.inner.get_schema(table_id).await
+    }
+
+    async fn get_block_metadata(&self, table_id: TableId, column_id: u32) -> narayana_core::Result<Vec<BlockMetadata>> {
+        self.inner.get_block_metadata(table_id, column_id).await
+    }
+
+    async fn delete_table(&self, table_id: TableId) -> narayana_core::Result<()> {
+        self.inner.delete_table(table_id).await
+    }
+}
+
+#[tokio::test]
+async fn test_filtered_questions_read_only_matching_blocks() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = Arc::new(RecordingStore {
+        inner: PersistentColumnStore::new(dir.path(), CompressionType::None).unwrap(),
+        reads: Mutex::new(Vec::new()),
+    });
+    let db_manager = Arc::new(DatabaseManager::new());
+    let db = db_manager.create_database("default".to_string()).unwrap();
+    let schema = Schema::new(vec![field("customer", DataType::String), field("amount", DataType::Int64)]);
+    let orders = db_manager.create_table(db, "orders".to_string(), schema.clone()).unwrap();
+    store.create_table(orders, schema).await.unwrap();
+    // Two writes, so each column has a block for rows 0..3 and one for 3..5
+    store.write_columns(orders, vec![
+        Column::String(vec!["ann".into(), "bob".into(), "cy".into()]),
+        Column::Int64(vec![10, 20, 30]),
+    ]).await.unwrap();
+    store.write_columns(orders, vec![
+        Column::String(vec!["di".into(), "ed".into()]),
+        Column::Int64(vec![40, 50]),
+    ]).await.unwrap();
+
+    let model = ScriptedModel::new(&[
+        "{\"table\": \"orders\", \"columns\": [\"customer\"], \"filter\": {\"Gte\": {\"column\": \"amount\", \"value\": 45}}}",
+    ]);
+    let nlq = NaturalLanguageQuery::new(model, store.clone(), db_manager);
+    let answer = nlq.ask("Who spent at least 45?", None, false, anyone(), &QueryContext::detached()).await.unwrap();
+    assert_eq!(answer.rows, vec![json!({"customer": "ed"})]);
+    assert_eq!(*store.reads.lock().unwrap(), vec![(3, 2)]);
+}
+
+#[test]
+fn test_parse_reply_rejects_unknown_keys() {
+    assert!(parse_reply("{\"table\": \"orders\", \"join\": \"customers\"}").is_err());
+    let spec = parse_reply("Here you go: {\"table\": \"orders\", \"limit\": 5} Hope that helps").unwrap();
+    assert_eq!(spec.table, "orders");
+    assert_eq!(spec.limit, Some(5));
+}
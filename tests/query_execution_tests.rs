@@ -6,11 +6,13 @@ use narayana_core::{
     column::Column,
     Error,
 };
-use narayana_storage::{ColumnStore, InMemoryColumnStore};
+use narayana_storage::{BlockMetadata, ColumnStore, InMemoryColumnStore};
+use narayana_core::types::CompressionType;
 use narayana_query::{
     executor::{QueryExecutor, DefaultQueryExecutor},
     plan::{QueryPlan, PlanNode, Filter, OrderBy, AggregateExpr, JoinType, JoinCondition},
     operators::{FilterOperator, ProjectOperator, ScanOperator},
+    cancellation::{is_cancelled_error, QueryRegistry},
 };
 
 // ============================================================================
@@ -1063,3 +1065,242 @@ fn test_query_performance_complex_filter() {
     assert!(duration.as_secs() < 5);
 }
 
+
+// ============================================================================
+// QUERY CANCELLATION TESTS
+// ============================================================================
+
+async fn executor_with_rows(rows: i64) -> DefaultQueryExecutor<InMemoryColumnStore> {
+    let store = InMemoryColumnStore::new();
+    let schema = Schema::new(vec![
+        Field {
+            name: "id".to_string(),
+            data_type: DataType::Int64,
+            nullable: false,
+            default_value: None,
+        },
+    ]);
+    store.create_table(TableId(0), schema).await.unwrap();
+    store.write_columns(TableId(0), vec![Column::Int64((0..rows).collect())]).await.unwrap();
+    DefaultQueryExecutor::new(store)
+}
+
+fn filtered_scan_plan() -> QueryPlan {
+    let schema = Schema::new(vec![
+        Field {
+            name: "id".to_string(),
+            data_type: DataType::Int64,
+            nullable: false,
+            default_value: None,
+        },
+    ]);
+    QueryPlan::new(PlanNode::Filter {
+        predicate: Filter::Gt {
+            column: "id".to_string(),
+            value: serde_json::Value::Number(99_000.into()),
+        },
+        input: Box::new(PlanNode::Scan { table_id: 0, column_ids: vec![0], filter: None }),
+    }, schema)
+}
+
+#[tokio::test]
+async fn test_query_executor_reports_progress() {
+    let executor = executor_with_rows(200_000).await;
+    let registry = std::sync::Arc::new(QueryRegistry::new());
+    let query = registry.register("filtered scan", None);
+
+    // Large enough to be filtered in several chunks
+    let result = executor.execute_with_context(filtered_scan_plan(), query.context()).await.unwrap();
+    assert_eq!(result[0].len(), 100_999);
+
+    let info = registry.get(query.id()).unwrap();
+    assert_eq!(info.rows_processed, 200_000);
+    assert_eq!(info.progress, Some(1.0));
+    drop(query);
+    assert!(registry.list().is_empty());
+}
+
+#[tokio::test]
+async fn test_query_executor_stops_when_cancelled() {
+    let executor = executor_with_rows(1_000).await;
+    let registry = std::sync::Arc::new(QueryRegistry::new());
+    let query = registry.register("filtered scan", None);
+    assert!(registry.cancel(query.id()));
+
+    let error = executor.execute_with_context(filtered_scan_plan(), query.context()).await.unwrap_err();
+    assert!(is_cancelled_error(&error));
+    assert_eq!(registry.get(query.id()).unwrap().rows_processed, 0);
+}
+
+/// In-memory store that reports 1000-row Int64 blocks with zone maps, like
+/// the persistent stores do
+struct ZoneMappedStore {
+    inner: InMemoryColumnStore,
+    rows: usize,
+}
+
+#[async_trait::async_trait]
+impl ColumnStore for ZoneMappedStore {
+    async fn create_table(&self, table_id: TableId, schema: Schema) -> narayana_core::Result<()> {
+        self.inner.create_table(table_id, schema).await
+    }
+
+    async fn write_columns(&self, table_id: TableId, columns: Vec<Column>) -> narayana_core::Result<()> {
+        self.inner.write_columns(table_id, columns).await
+    }
+
+    async fn read_columns(
+        &self,
+        table_id: TableId,
+        column_ids: Vec<u32>,
+        row_start: usize,
+        row_count: usize,
+    ) -> narayana_core::Result<Vec<Column>> {
+        self.inner.read_columns(table_id, column_ids, row_start, row_count).await
+    }
+
+    async fn get_schema(&self, table_id: TableId) -> narayana_core::Result<Schema> {
+        self.inner.get_schema(table_id).await
+    }
+
+    async fn get_block_metadata(&self, _table_id: TableId, column_id: u32) -> narayana_core::Result<Vec<BlockMetadata>> {
+        Ok((0..self.rows).step_by(1000).enumerate().map(|(block_id, row_start)| {
+            let row_count = 1000.min(self.rows - row_start);
+            BlockMetadata {
+                block_id: block_id as u64,
+                column_id,
+                row_start,
+                row_count,
+                data_type: DataType::Int64,
+                compression: CompressionType::None,
+                uncompressed_size: row_count * 8,
+                compressed_size: row_count * 8,
+                min_value: Some((row_start as i64).to_le_bytes().to_vec()),
+                max_value: Some(((row_start + row_count - 1) as i64).to_le_bytes().to_vec()),
+                null_count: 0,
+                distinct_count: Some(row_count as u64),
+            }
+        }).collect())
+    }
+
+    async fn delete_table(&self, table_id: TableId) -> narayana_core::Result<()> {
+        self.inner.delete_table(table_id).await
+    }
+}
+
+#[tokio::test]
+async fn test_query_executor_skips_blocks_outside_filter() {
+    let store = ZoneMappedStore { inner: InMemoryColumnStore::new(), rows: 4_000 };
+    let schema = Schema::new(vec![
+        Field {
+            name: "id".to_string(),
+            data_type: DataType::Int64,
+            nullable: false,
+            default_value: None,
+        },
+    ]);
+    store.create_table(TableId(0), schema.clone()).await.unwrap();
+    store.write_columns(TableId(0), vec![Column::Int64((0..4_000).collect())]).await.unwrap();
+    let executor = DefaultQueryExecutor::new(store);
+    let registry = std::sync::Arc::new(QueryRegistry::new());
+
+    let plan = |predicate| QueryPlan::new(PlanNode::Filter {
+        predicate,
+        input: Box::new(PlanNode::Scan { table_id: 0, column_ids: vec![0], filter: None }),
+    }, schema.clone());
+
+    // Only the last two blocks can hold ids above 2500
+    let query = registry.register("pruned scan", None);
+    let result = executor.execute_with_context(plan(Filter::Gt {
+        column: "id".to_string(),
+        value: serde_json::json!(2_500),
+    }), query.context()).await.unwrap();
+    assert_eq!(result[0].len(), 1_499);
+    assert_eq!(registry.get(query.id()).unwrap().rows_processed, 2_000);
+
+    // Conjuncts intersect, and a value outside every block reads nothing
+    let query = registry.register("pruned scan", None);
+    let result = executor.execute_with_context(plan(Filter::And {
+        left: Box::new(Filter::Gte { column: "id".to_string(), value: serde_json::json!(1_000) }),
+        right: Box::new(Filter::Eq { column: "id".to_string(), value: serde_json::json!(9_999) }),
+    }), query.context()).await.unwrap();
+    assert_eq!(result[0].len(), 0);
+    assert_eq!(registry.get(query.id()).unwrap().rows_processed, 0);
+
+    // Predicates that cannot be pruned on still scan every row
+    let query = registry.register("full scan", None);
+    let result = executor.execute_with_context(plan(Filter::Not {
+        expr: Box::new(Filter::Lt { column: "id".to_string(), value: serde_json::json!(3_990) }),
+    }), query.context()).await.unwrap();
+    assert_eq!(result[0].len(), 10);
+    assert_eq!(registry.get(query.id()).unwrap().rows_processed, 4_000);
+}
+
+// ============================================================================
+// PREDICT TESTS
+// ============================================================================
+
+/// Linear model stored as JSON weights, one output per row
+struct LinearScorer(Vec<f32>);
+
+impl narayana_query::ml_integration::BatchScorer for LinearScorer {
+    fn score(&self, batch: &[f32], _rows: usize, features: usize) -> narayana_core::Result<Vec<Vec<f32>>> {
+        Ok(batch.chunks(features)
+            .map(|row| vec![row.iter().zip(&self.0).map(|(x, w)| x * w).sum()])
+            .collect())
+    }
+}
+
+#[tokio::test]
+async fn test_predict_plan_scores_scanned_rows() {
+    use narayana_query::ml_integration::{BatchScorer, PredictEngine, PredictOutput};
+    use narayana_storage::model_registry::{ModelArtifactRegistry, ModelStage, ModelType};
+    use std::sync::Arc;
+
+    let field = |name: &str, data_type| Field { name: name.to_string(), data_type, nullable: false, default_value: None };
+    let schema = Schema::new(vec![
+        field("id", DataType::Int64),
+        field("rooms", DataType::Int32),
+        field("area", DataType::Float64),
+    ]);
+    let store = InMemoryColumnStore::new();
+    store.create_table(TableId(7), schema.clone()).await.unwrap();
+    store.write_columns(TableId(7), vec![
+        Column::Int64(vec![1, 2, 3]),
+        Column::Int32(vec![1, 2, 3]),
+        Column::Float64(vec![10.0, 20.0, 30.0]),
+    ]).await.unwrap();
+
+    let artifacts = Arc::new(ModelArtifactRegistry::new(None).unwrap());
+    artifacts.register_version("price", "1.0.0", ModelType::Planning, "linear-json",
+        serde_json::to_vec(&vec![1.0f32, 2.0]).unwrap(), Default::default()).unwrap();
+    artifacts.transition_stage("price", "1.0.0", ModelStage::Staging).unwrap();
+    let predictor = Arc::new(PredictEngine::new(artifacts));
+    predictor.register_format("linear-json", Arc::new(|bytes: &[u8]| {
+        let weights: Vec<f32> = serde_json::from_slice(bytes)
+            .map_err(|e| Error::Deserialization(e.to_string()))?;
+        Ok(Arc::new(LinearScorer(weights)) as Arc<dyn BatchScorer>)
+    }));
+
+    // Features are named in model input order, whatever the scan order
+    let plan = || QueryPlan::new(PlanNode::Predict {
+        model: "price".to_string(),
+        features: vec!["area".to_string(), "rooms".to_string()],
+        output: PredictOutput::Value,
+        input: Box::new(PlanNode::Limit {
+            limit: 2,
+            offset: 0,
+            input: Box::new(PlanNode::Scan { table_id: 7, column_ids: vec![0, 1, 2], filter: None }),
+        }),
+    }, schema.clone());
+
+    let executor = DefaultQueryExecutor::new(store).with_predictor(predictor);
+    let result = executor.execute(plan()).await.unwrap();
+    assert_eq!(result.len(), 4);
+    assert!(matches!(&result[0], Column::Int64(ids) if *ids == vec![1, 2]));
+    assert!(matches!(&result[3], Column::Float64(scores) if *scores == vec![12.0, 24.0]));
+
+    // Without a model registry, PREDICT is refused
+    let executor = DefaultQueryExecutor::new(executor.store);
+    assert!(executor.execute(plan()).await.is_err());
+}
@@ -1,7 +1,8 @@
 // Query Learning Tests - 99% Coverage
 
 use narayana_storage::query_learning::*;
-use std::time::SystemTime;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 #[test]
 fn test_query_learning_enable_disable() {
@@ -577,3 +578,141 @@ async fn test_query_learning_average_execution_time() {
     }
 }
 
+fn execution(query: &str, table: &str, filter: &str, time_ms: f64, rows_returned: u64) -> QueryExecution {
+    QueryExecution {
+        query_id: "query".to_string(),
+        query_text: query.to_string(),
+        normalized_query: query.to_string(),
+        execution_time_ms: time_ms,
+        timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
+        columns_accessed: vec!["id".to_string(), "email".to_string()],
+        tables_accessed: vec![table.to_string()],
+        rows_scanned: 10_000,
+        rows_returned,
+        filters_applied: if filter.is_empty() { vec![] } else { vec![filter.to_string()] },
+        indexes_used: vec![],
+        join_count: 0,
+    }
+}
+
+#[tokio::test]
+async fn test_advisor_recommends_indexes_sort_keys_and_views() {
+    let engine = QueryLearningEngine::new();
+    engine.enable();
+
+    for _ in 0..20 {
+        engine.record_query(execution("SELECT * FROM users WHERE email = 'a@b.c'", "users", "email = 'a@b.c'", 40.0, 1)).unwrap();
+        engine.record_query(execution("SELECT * FROM events ORDER BY ts DESC, id LIMIT 10", "events", "", 30.0, 10)).unwrap();
+        // Not selective enough for an index
+        engine.record_query(execution("SELECT * FROM users WHERE active = true", "users", "active = true", 40.0, 9_000)).unwrap();
+    }
+    for _ in 0..120 {
+        engine.record_query(execution("SELECT region, SUM(total) FROM orders GROUP BY region", "orders", "", 200.0, 5)).unwrap();
+    }
+    // Too rare to act on
+    for _ in 0..3 {
+        engine.record_query(execution("SELECT * FROM users WHERE name = 'x'", "users", "name = 'x'", 40.0, 1)).unwrap();
+    }
+
+    let recommendations = engine.recommend();
+    let actions: Vec<&RecommendationAction> = recommendations.iter().map(|r| &r.action).collect();
+    assert_eq!(actions.len(), 3, "{:?}", actions);
+    assert!(actions.contains(&&RecommendationAction::CreateIndex {
+        table: "users".to_string(),
+        column: "email".to_string(),
+        index_type: "btree".to_string(),
+    }));
+    assert!(actions.contains(&&RecommendationAction::SortKey {
+        table: "events".to_string(),
+        columns: vec!["ts".to_string(), "id".to_string()],
+    }));
+    assert!(matches!(
+        &recommendations[0].action,
+        RecommendationAction::MaterializedView { tables, .. } if tables == &vec!["orders".to_string()]
+    ));
+    assert!(recommendations.iter().all(|r| r.status == RecommendationStatus::Pending && r.estimated_benefit_ms > 0.0));
+
+    // Refreshing keeps ids stable
+    let ids: Vec<String> = recommendations.iter().map(|r| r.id.clone()).collect();
+    let refreshed: Vec<String> = engine.recommend().iter().map(|r| r.id.clone()).collect();
+    assert_eq!(ids, refreshed);
+}
+
+#[tokio::test]
+async fn test_advisor_apply_dismiss_and_impact() {
+    let engine = QueryLearningEngine::new();
+    engine.enable();
+    let query = "SELECT * FROM users WHERE email = 'a@b.c'";
+    for _ in 0..20 {
+        engine.record_query(execution(query, "users", "email = 'a@b.c'", 100.0, 1)).unwrap();
+    }
+    let sort_query = "SELECT * FROM users ORDER BY created_at";
+    for _ in 0..20 {
+        engine.record_query(execution(sort_query, "users", "", 10.0, 100)).unwrap();
+    }
+
+    let recommendations = engine.recommend();
+    let index = recommendations.iter().find(|r| matches!(r.action, RecommendationAction::CreateIndex { .. })).unwrap();
+    let sort_key = recommendations.iter().find(|r| matches!(r.action, RecommendationAction::SortKey { .. })).unwrap();
+
+    let dismissed = engine.dismiss_recommendation(&sort_key.id).unwrap();
+    assert_eq!(dismissed.status, RecommendationStatus::Dismissed);
+    assert!(engine.dismiss_recommendation("rec_missing").is_none());
+
+    let applied = engine.apply_recommendation(&index.id).await.unwrap();
+    assert_eq!(applied.status, RecommendationStatus::Applied);
+    let impact = applied.impact.unwrap();
+    assert_eq!((impact.executions_before, impact.executions_after), (20, 0));
+    assert!(impact.average_after_ms.is_none());
+    assert!(engine.apply_recommendation(&index.id).await.is_err());
+    assert!(engine.apply_recommendation("rec_missing").await.is_err());
+    assert_eq!(engine.get_statistics().indexes_created, 1);
+
+    // The indexed query got faster
+    for _ in 0..10 {
+        engine.record_query(execution(query, "users", "email = 'a@b.c'", 20.0, 1)).unwrap();
+    }
+    let impact = engine.recommendation(&index.id).unwrap().impact.unwrap();
+    assert_eq!(impact.executions_after, 10);
+    assert!((impact.average_after_ms.unwrap() - 20.0).abs() < 1e-6);
+    assert!((impact.improvement_percentage.unwrap() - 80.0).abs() < 1e-6);
+
+    // Reviewed recommendations keep their state and an index isn't suggested twice
+    let recommendations = engine.recommend();
+    assert_eq!(recommendations.len(), 2);
+    assert_eq!(engine.recommendation(&sort_key.id).unwrap().status, RecommendationStatus::Dismissed);
+    assert!(engine.get_optimization_suggestions(&engine.get_patterns().iter()
+        .find(|p| p.query_template == query).unwrap().pattern_id)
+        .iter()
+        .all(|hint| !matches!(hint, OptimizationHint::CreateIndex { .. })));
+}
+
+/// Fails every action
+struct RejectingApplier;
+
+#[async_trait::async_trait]
+impl RecommendationApplier for RejectingApplier {
+    async fn apply(&self, _action: &RecommendationAction) -> narayana_core::Result<()> {
+        Err(narayana_core::Error::Storage("read-only table".to_string()))
+    }
+}
+
+#[tokio::test]
+async fn test_advisor_failed_apply() {
+    let engine = QueryLearningEngine::new().with_applier(Arc::new(RejectingApplier));
+    engine.enable();
+    engine.register_index("users", "id");
+    for _ in 0..20 {
+        engine.record_query(execution("SELECT * FROM users WHERE email = 'x'", "users", "email = 'x'", 50.0, 1)).unwrap();
+        engine.record_query(execution("SELECT * FROM users WHERE id = 1", "users", "id = 1", 50.0, 1)).unwrap();
+    }
+
+    // users.id is already indexed
+    let recommendations = engine.recommend();
+    assert_eq!(recommendations.len(), 1);
+    assert!(engine.apply_recommendation(&recommendations[0].id).await.is_err());
+    let failed = engine.recommendation(&recommendations[0].id).unwrap();
+    assert_eq!(failed.status, RecommendationStatus::Failed);
+    assert_eq!(failed.error.as_deref(), Some("Storage error: read-only table"));
+    assert_eq!(engine.get_statistics().indexes_created, 0);
+}
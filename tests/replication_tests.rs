@@ -0,0 +1,306 @@
+// Replication tests
+// Multi-primary replication between two regions: rows ship both ways,
+// concurrent writes resolve by last write wins, counters, sets or a merge
+// worker, and peers' lag and failures show in the status
+
+use narayana_core::column::Column;
+use narayana_core::config::ReplicationPeer;
+use narayana_core::schema::{DataType, Field, Schema};
+use narayana_core::types::TableId;
+use narayana_core::{Error, Result};
+use narayana_storage::column_store::{ColumnStore, InMemoryColumnStore};
+use narayana_storage::replication::*;
+use narayana_storage::temporal::{TemporalConfig, TemporalManager};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+const ROBOTS: TableId = TableId(1);
+
+struct Region {
+    store: Arc<dyn ColumnStore>,
+    temporal: Arc<TemporalManager>,
+    replication: Arc<ReplicationManager>,
+}
+
+/// Delivers batches straight to the other region's manager
+#[derive(Default)]
+struct Loopback {
+    regions: std::sync::RwLock<HashMap<String, Arc<Region>>>,
+    down: std::sync::Mutex<bool>,
+}
+
+#[async_trait::async_trait]
+impl ReplicationTransport for Loopback {
+    async fn send(&self, peer: &ReplicationPeer, batch: &ChangeBatch) -> Result<ApplyReport> {
+        if *self.down.lock().unwrap() {
+            return Err(Error::Storage("connection refused".to_string()));
+        }
+        let region = self.regions.read().unwrap().get(&peer.region).cloned().unwrap();
+        region.replication.apply(region.store.as_ref(), &region.temporal, batch.clone()).await
+    }
+}
+
+/// Merges two robots by keeping the longer name
+struct LongestName;
+
+#[async_trait::async_trait]
+impl MergeResolver for LongestName {
+    async fn merge(&self, worker_id: &str, _table: &str, local: &Map<String, Value>, remote: &Map<String, Value>) -> Result<Map<String, Value>> {
+        assert_eq!(worker_id, "merge-robots");
+        let len = |row: &Map<String, Value>| row["name"].as_str().unwrap_or_default().len();
+        Ok(if len(remote) > len(local) { remote.clone() } else { local.clone() })
+    }
+}
+
+fn field(name: &str, data_type: DataType) -> Field {
+    Field { name: name.to_string(), data_type, nullable: false, default_value: None }
+}
+
+fn peer(region: &str) -> ReplicationPeer {
+    ReplicationPeer { region: region.to_string(), url: format!("https://{}.example.com", region), token: None }
+}
+
+async fn region(name: &str, peer_name: &str, transport: &Arc<Loopback>, config: &TableReplication) -> Arc<Region> {
+    let store: Arc<dyn ColumnStore> = Arc::new(InMemoryColumnStore::new());
+    let schema = Schema::new(vec![
+        field("id", DataType::Int64),
+        field("name", DataType::String),
+        field("hits", DataType::Int64),
+        field("tags", DataType::Json),
+        field("sys_from", DataType::Int64),
+    ]);
+    store.create_table(ROBOTS, schema).await.unwrap();
+    let temporal = Arc::new(TemporalManager::new());
+    let versioning = TemporalConfig { key_column: "id".to_string(), period_column: "sys_from".to_string(), retention_secs: None };
+    temporal.enable(store.as_ref(), ROBOTS, versioning).await.unwrap();
+    let replication = ReplicationManager::new(name.to_string(), vec![peer(peer_name)], 100)
+        .with_transport(transport.clone())
+        .with_merge_resolver(Arc::new(LongestName));
+    replication.configure(store.as_ref(), &temporal, ROBOTS, "robots".to_string(), config.clone()).await.unwrap();
+    let region = Arc::new(Region { store, temporal, replication: Arc::new(replication) });
+    transport.regions.write().unwrap().insert(name.to_string(), region.clone());
+    region
+}
+
+async fn pair(config: TableReplication) -> (Arc<Region>, Arc<Region>, Arc<Loopback>) {
+    let transport = Arc::new(Loopback::default());
+    let eu = region("eu-west", "us-east", &transport, &config).await;
+    let us = region("us-east", "eu-west", &transport, &config).await;
+    (eu, us, transport)
+}
+
+fn counters_and_sets() -> TableReplication {
+    TableReplication { counters: vec!["hits".to_string()], sets: vec!["tags".to_string()], ..Default::default() }
+}
+
+async fn write(region: &Region, id: i64, name: &str, hits: i64, tags: Value) {
+    let columns = vec![
+        Column::Int64(vec![id]),
+        Column::String(vec![name.to_string()]),
+        Column::Int64(vec![hits]),
+        Column::String(vec![tags.to_string()]),
+        Column::Int64(vec![0]),
+    ];
+    region.replication.write(region.store.as_ref(), &region.temporal, ROBOTS, columns).await.unwrap();
+    // Commit times a few milliseconds apart, so the later write is clear
+    tokio::time::sleep(Duration::from_millis(5)).await;
+}
+
+async fn row(region: &Region, id: i64) -> Option<Map<String, Value>> {
+    region.temporal.current(region.store.as_ref(), ROBOTS, &json!(id)).await.unwrap()
+}
+
+fn tags(row: &Map<String, Value>) -> Vec<String> {
+    let mut tags: Vec<String> = serde_json::from_str(row["tags"].as_str().unwrap()).unwrap();
+    tags.sort();
+    tags
+}
+
+#[tokio::test]
+async fn test_writes_ship_both_ways_and_concurrent_updates_go_to_the_last_writer() {
+    let (eu, us, _) = pair(TableReplication::default()).await;
+    write(&eu, 1, "atlas", 0, json!([])).await;
+    write(&us, 2, "bolt", 0, json!([])).await;
+    assert_eq!(eu.replication.ship().await, 1);
+    assert_eq!(us.replication.ship().await, 1);
+    assert_eq!(row(&us, 1).await.unwrap()["name"], json!("atlas"));
+    assert_eq!(row(&eu, 2).await.unwrap()["name"], json!("bolt"));
+
+    // Neither region has seen the other's update when it makes its own
+    write(&eu, 1, "atlas-eu", 0, json!([])).await;
+    write(&us, 1, "atlas-us", 0, json!([])).await;
+    eu.replication.ship().await;
+    us.replication.ship().await;
+    for region in [&eu, &us] {
+        assert_eq!(row(region, 1).await.unwrap()["name"], json!("atlas-us"));
+        let status = region.replication.status();
+        assert_eq!(status.origins[0].conflicts, 1);
+        assert_eq!(status.pending, 0);
+    }
+
+    // Updates made after seeing each other's are not conflicts
+    write(&eu, 1, "atlas-2", 0, json!([])).await;
+    eu.replication.ship().await;
+    assert_eq!(row(&us, 1).await.unwrap()["name"], json!("atlas-2"));
+    assert_eq!(us.replication.status().origins[0].conflicts, 1);
+}
+
+#[tokio::test]
+async fn test_concurrent_counters_add_up_and_sets_keep_both_sides() {
+    let (eu, us, _) = pair(counters_and_sets()).await;
+    write(&eu, 1, "atlas", 10, json!(["outdoor"])).await;
+    eu.replication.ship().await;
+
+    write(&eu, 1, "atlas", 15, json!(["outdoor", "red"])).await;
+    write(&us, 1, "atlas", 13, json!(["blue"])).await;
+    eu.replication.ship().await;
+    us.replication.ship().await;
+    for region in [&eu, &us] {
+        let row = row(region, 1).await.unwrap();
+        assert_eq!(row["hits"], json!(18));
+        // us removed "outdoor" and nobody added it back
+        assert_eq!(tags(&row), vec!["blue", "red"]);
+    }
+
+    // A remove on one side and an add on the other both apply
+    write(&eu, 1, "atlas", 18, json!(["red"])).await;
+    write(&us, 1, "atlas", 18, json!(["blue", "red", "green"])).await;
+    eu.replication.ship().await;
+    us.replication.ship().await;
+    for region in [&eu, &us] {
+        assert_eq!(tags(&row(region, 1).await.unwrap()), vec!["green", "red"]);
+    }
+}
+
+#[tokio::test]
+async fn test_merge_worker_and_deletes() {
+    let config = TableReplication {
+        strategy: ConflictStrategy::MergeWorker,
+        merge_worker: Some("merge-robots".to_string()),
+        ..Default::default()
+    };
+    let (eu, us, _) = pair(config).await;
+    write(&eu, 1, "atlas", 0, json!([])).await;
+    write(&eu, 2, "bolt", 0, json!([])).await;
+    eu.replication.ship().await;
+
+    write(&eu, 1, "atlas-prime", 0, json!([])).await;
+    write(&us, 1, "atlas-2", 0, json!([])).await;
+    eu.replication.ship().await;
+    us.replication.ship().await;
+    for region in [&eu, &us] {
+        assert_eq!(row(region, 1).await.unwrap()["name"], json!("atlas-prime"));
+    }
+
+    // A delete and a later concurrent update: the update wins
+    eu.replication.delete(eu.store.as_ref(), &eu.temporal, ROBOTS, &[json!(2)]).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(5)).await;
+    write(&us, 2, "bolt-2", 0, json!([])).await;
+    eu.replication.ship().await;
+    us.replication.ship().await;
+    for region in [&eu, &us] {
+        assert_eq!(row(region, 2).await.unwrap()["name"], json!("bolt-2"));
+    }
+
+    // A delete after seeing the row removes it everywhere
+    us.replication.delete(us.store.as_ref(), &us.temporal, ROBOTS, &[json!(1)]).await.unwrap();
+    us.replication.ship().await;
+    assert!(row(&eu, 1).await.is_none());
+}
+
+#[tokio::test]
+async fn test_redelivered_batches_are_skipped() {
+    let (eu, us, _) = pair(counters_and_sets()).await;
+    write(&eu, 1, "atlas", 0, json!([])).await;
+    write(&eu, 1, "atlas", 5, json!([])).await;
+    let region = eu.replication.region().to_string();
+    let rows = eu.store.read_columns(ROBOTS, vec![0, 1, 2, 3, 4], 0, usize::MAX).await.unwrap();
+    let change = |seq: u64, hits: i64| ReplicatedChange {
+        origin: region.clone(),
+        seq,
+        table: "robots".to_string(),
+        key: json!(1),
+        clock: serde_json::from_value(json!({"clocks": {region.clone(): seq}})).unwrap(),
+        committed_at: 1,
+        deleted: false,
+        row: rows.iter().map(|c| c.slice(0, 1).unwrap()).collect(),
+        increments: [("hits".to_string(), json!(hits))].into(),
+        added: Default::default(),
+        removed: Default::default(),
+    };
+    let batch = ChangeBatch { origin: region.clone(), epoch: 7, changes: vec![change(1, 0), change(2, 5)] };
+
+    let first = us.replication.apply(us.store.as_ref(), &us.temporal, batch.clone()).await.unwrap();
+    assert_eq!((first.applied, first.skipped, first.acked_seq), (2, 0, 2));
+    let again = us.replication.apply(us.store.as_ref(), &us.temporal, batch.clone()).await.unwrap();
+    assert_eq!((again.applied, again.skipped, again.acked_seq), (0, 2, 2));
+
+    // Changes from this region and oversized batches are refused
+    let own = ChangeBatch { origin: "us-east".to_string(), ..batch.clone() };
+    assert!(us.replication.apply(us.store.as_ref(), &us.temporal, own).await.is_err());
+    let oversized = ChangeBatch { changes: vec![change(3, 0); MAX_BATCH_CHANGES + 1], ..batch };
+    assert!(us.replication.apply(us.store.as_ref(), &us.temporal, oversized).await.is_err());
+}
+
+#[tokio::test]
+async fn test_failed_shipments_show_as_lag_until_the_peer_is_back() {
+    let (eu, us, transport) = pair(TableReplication::default()).await;
+    *transport.down.lock().unwrap() = true;
+    write(&eu, 1, "atlas", 0, json!([])).await;
+    write(&eu, 2, "bolt", 0, json!([])).await;
+    assert_eq!(eu.replication.ship().await, 0);
+
+    let status = eu.replication.status();
+    let peer = &status.peers[0];
+    assert_eq!((peer.pending, peer.failures, peer.acked_seq), (2, 1, 0));
+    assert!(peer.lag_ms >= 5);
+    assert_eq!(peer.last_error.as_deref(), Some("Storage error: connection refused"));
+    let metrics = status.to_prometheus();
+    assert!(metrics.contains("narayana_replication_pending_changes{peer=\"us-east\"} 2"));
+    assert!(metrics.contains("narayana_replication_ship_failures_total{peer=\"us-east\"} 1"));
+
+    *transport.down.lock().unwrap() = false;
+    assert_eq!(eu.replication.ship().await, 2);
+    let status = eu.replication.status();
+    assert_eq!((status.pending, status.peers[0].lag_ms, status.peers[0].shipped), (0, 0, 2));
+    assert!(status.peers[0].last_error.is_none());
+    let applied = us.replication.status();
+    assert_eq!((applied.origins[0].region.as_str(), applied.origins[0].applied), ("eu-west", 2));
+}
+
+#[tokio::test]
+async fn test_outbox_overflow_and_configuration() {
+    let (eu, _, _) = pair(TableReplication::default()).await;
+    let small = ReplicationManager::new("eu-west".to_string(), vec![peer("us-east")], 2);
+    small.configure(eu.store.as_ref(), &eu.temporal, ROBOTS, "robots".to_string(), TableReplication::default()).await.unwrap();
+    for id in 1..=3 {
+        let columns = vec![
+            Column::Int64(vec![id]),
+            Column::String(vec!["robot".to_string()]),
+            Column::Int64(vec![0]),
+            Column::String(vec!["[]".to_string()]),
+            Column::Int64(vec![0]),
+        ];
+        small.write(eu.store.as_ref(), &eu.temporal, ROBOTS, columns).await.unwrap();
+    }
+    let status = small.status();
+    assert_eq!((status.last_seq, status.pending, status.dropped), (3, 2, 1));
+
+    let invalid = [
+        TableReplication { counters: vec!["name".to_string()], ..Default::default() },
+        TableReplication { sets: vec!["hits".to_string()], ..Default::default() },
+        TableReplication { counters: vec!["sys_from".to_string()], ..Default::default() },
+        TableReplication { counters: vec!["missing".to_string()], ..Default::default() },
+        TableReplication { strategy: ConflictStrategy::MergeWorker, ..Default::default() },
+    ];
+    for config in invalid {
+        assert!(small.configure(eu.store.as_ref(), &eu.temporal, ROBOTS, "robots".to_string(), config).await.is_err());
+    }
+    // Only system-versioned tables replicate
+    eu.temporal.disable(ROBOTS);
+    assert!(small.configure(eu.store.as_ref(), &eu.temporal, ROBOTS, "robots".to_string(), TableReplication::default()).await.is_err());
+    assert!(small.remove(ROBOTS));
+    assert!(!small.is_replicated(ROBOTS));
+}
@@ -0,0 +1,160 @@
+// Row-level security tests
+// Policies evaluated against the caller's tenant and roles, and per-role
+// column masks, applied to reads through a secured column store
+
+use narayana_core::column::Column;
+use narayana_core::schema::{DataType, Field, Schema};
+use narayana_core::types::TableId;
+use narayana_storage::column_store::{ColumnStore, InMemoryColumnStore};
+use narayana_storage::row_security::{ColumnMask, MaskFunction, Principal, RowPolicy, RowSecurity};
+use std::sync::Arc;
+
+const TABLE: TableId = TableId(1);
+
+fn field(name: &str, data_type: DataType) -> Field {
+    Field { name: name.to_string(), data_type, nullable: false, default_value: None }
+}
+
+fn strings(column: &Column) -> Vec<String> {
+    match column {
+        Column::String(values) => values.clone(),
+        other => panic!("expected String column, got {:?}", other.data_type()),
+    }
+}
+
+async fn setup() -> (Arc<dyn ColumnStore>, Arc<RowSecurity>) {
+    let store: Arc<dyn ColumnStore> = Arc::new(InMemoryColumnStore::new());
+    let schema = Schema::new(vec![
+        field("tenant_id", DataType::String),
+        field("email", DataType::String),
+        field("amount", DataType::Int64),
+    ]);
+    store.create_table(TABLE, schema).await.unwrap();
+    store
+        .write_columns(TABLE, vec![
+            Column::String(vec!["acme".into(), "globex".into(), "acme".into()]),
+            Column::String(vec!["ann@acme.io".into(), "bob@globex.io".into(), "cy@acme.io".into()]),
+            Column::Int64(vec![10, 20, 30]),
+        ])
+        .await
+        .unwrap();
+    (store, Arc::new(RowSecurity::new()))
+}
+
+fn policy(name: &str, expression: &str, roles: &[&str]) -> RowPolicy {
+    RowPolicy {
+        name: name.to_string(),
+        expression: expression.to_string(),
+        roles: roles.iter().map(|r| r.to_string()).collect(),
+    }
+}
+
+fn principal(tenant: Option<&str>, roles: &[&str]) -> Principal {
+    Principal::new("user-1", roles.iter().map(|r| r.to_string()).collect())
+        .with_tenant(tenant.map(str::to_string))
+}
+
+#[tokio::test]
+async fn test_row_policies_filter_by_tenant_and_role() {
+    let (store, security) = setup().await;
+    security.set_policy(TABLE, policy("own_tenant", "tenant_id == $tenant", &[])).unwrap();
+    security.set_policy(TABLE, policy("auditors", "'auditor' in $roles || amount > 1000", &[])).unwrap();
+
+    // The policy column is read for filtering but only the requested columns come back
+    let acme = security.secure(store.clone(), principal(Some("acme"), &["user"]));
+    let columns = acme.read_columns(TABLE, vec![2], 0, 100).await.unwrap();
+    assert_eq!(columns.len(), 1);
+    assert!(matches!(&columns[0], Column::Int64(v) if v == &vec![10, 30]));
+
+    let auditor = security.secure(store.clone(), principal(None, &["auditor"]));
+    assert_eq!(auditor.read_columns(TABLE, vec![0], 0, 100).await.unwrap()[0].len(), 3);
+
+    let nobody = security.secure(store.clone(), principal(None, &["user"]));
+    assert_eq!(nobody.read_columns(TABLE, vec![0, 1], 0, 100).await.unwrap()[1].len(), 0);
+
+    // Once removed, the remaining policy decides alone
+    assert!(security.remove_policy(TABLE, "auditors"));
+    assert_eq!(auditor.read_columns(TABLE, vec![0], 0, 100).await.unwrap()[0].len(), 0);
+    assert!(!security.remove_policy(TABLE, "auditors"));
+
+    // Tables without policies are untouched
+    assert!(security.remove_policy(TABLE, "own_tenant"));
+    assert_eq!(nobody.read_columns(TABLE, vec![0], 0, 100).await.unwrap()[0].len(), 3);
+}
+
+#[tokio::test]
+async fn test_callers_no_policy_applies_to_see_nothing() {
+    let (store, security) = setup().await;
+    security.set_policy(TABLE, policy("support", "amount < 25", &["support"])).unwrap();
+
+    let support = security.secure(store.clone(), principal(None, &["support"]));
+    assert_eq!(support.read_columns(TABLE, vec![0], 0, 100).await.unwrap()[0].len(), 2);
+    let user = security.secure(store.clone(), principal(None, &["user"]));
+    assert_eq!(user.read_columns(TABLE, vec![0], 0, 100).await.unwrap()[0].len(), 0);
+
+    // A policy on a column the table doesn't have fails the read closed
+    security.set_policy(TABLE, policy("broken", "region == 'eu'", &["support"])).unwrap();
+    assert!(support.read_columns(TABLE, vec![0], 0, 100).await.is_err());
+}
+
+#[tokio::test]
+async fn test_column_masks_per_role() {
+    let (store, security) = setup().await;
+    security
+        .set_mask(TABLE, ColumnMask {
+            name: "email_support".to_string(),
+            column: "email".to_string(),
+            function: MaskFunction::Partial { show_first: 2, show_last: 3 },
+            roles: vec!["support".to_string()],
+        })
+        .unwrap();
+    security
+        .set_mask(TABLE, ColumnMask {
+            name: "email_analyst".to_string(),
+            column: "email".to_string(),
+            function: MaskFunction::Hash,
+            roles: vec!["analyst".to_string()],
+        })
+        .unwrap();
+    security
+        .set_mask(TABLE, ColumnMask {
+            name: "amount".to_string(),
+            column: "amount".to_string(),
+            function: MaskFunction::Redact,
+            roles: Vec::new(),
+        })
+        .unwrap();
+
+    let support = security.secure(store.clone(), principal(None, &["support"]));
+    let columns = support.read_columns(TABLE, vec![1, 2], 0, 100).await.unwrap();
+    assert_eq!(strings(&columns[0])[0], "an******.io");
+    assert_eq!(strings(&columns[1]), vec!["****"; 3]);
+
+    // Hashes are stable so masked values can still be grouped and joined
+    let analyst = security.secure(store.clone(), principal(None, &["analyst"]));
+    let hashed = strings(&analyst.read_columns(TABLE, vec![1], 0, 100).await.unwrap()[0]);
+    assert_eq!(hashed[0].len(), 64);
+    assert_ne!(hashed[0], hashed[1]);
+    assert_eq!(hashed, strings(&analyst.read_columns(TABLE, vec![1], 0, 100).await.unwrap()[0]));
+
+    let user = security.secure(store.clone(), principal(None, &["user"]));
+    assert_eq!(strings(&user.read_columns(TABLE, vec![1], 0, 100).await.unwrap()[0])[1], "bob@globex.io");
+    assert_eq!(security.masks(TABLE).len(), 3);
+}
+
+#[test]
+fn test_invalid_policies_are_rejected() {
+    let security = RowSecurity::new();
+    for expression in ["", "tenant_id = $tenant", "$tenant ==", "'x'", "$user == 'a'", "amount in $tenant", "(a"] {
+        assert!(
+            security.set_policy(TABLE, policy("p", expression, &[])).is_err(),
+            "{:?} should not parse",
+            expression
+        );
+    }
+    assert!(security.set_policy(TABLE, policy("bad name", "a == 1", &[])).is_err());
+    assert!(security.policies(TABLE).is_empty());
+
+    let p = policy("p", "tenant_id == $tenant && !(archived || region in ['eu'])", &[]);
+    assert_eq!(p.columns().unwrap(), vec!["archived", "region", "tenant_id"]);
+}
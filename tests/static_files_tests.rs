@@ -0,0 +1,171 @@
+// Static file hosting tests
+// ETag revalidation, precompressed and cached variants, SPA fallback,
+// cache headers, path safety and the dev-server redirect
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header, HeaderMap, HeaderValue, Method, Response, StatusCode, Uri};
+use narayana_core::config::NetworkConfig;
+use narayana_server::static_files::StaticFiles;
+use std::path::Path;
+
+const APP_JS: &str = "export function main() { console.log('narayana avatar client'); }\n";
+
+fn ui_dir() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    std::fs::write(root.join("index.html"), "<!DOCTYPE html><div id=\"root\"></div>").unwrap();
+    std::fs::create_dir(root.join("assets")).unwrap();
+    std::fs::write(root.join("assets/index-BQ3k2x9a.js"), APP_JS.repeat(100)).unwrap();
+    std::fs::write(root.join("assets/index-BQ3k2x9a.js.br"), b"fake-brotli").unwrap();
+    std::fs::write(root.join("assets/style.css"), "body { margin: 0; }\n".repeat(200)).unwrap();
+    std::fs::write(root.join("logo.png"), [0x89, b'P', b'N', b'G']).unwrap();
+    dir
+}
+
+fn files(dir: &Path) -> StaticFiles {
+    let mut network = NetworkConfig::default();
+    network.static_files.directory = Some(dir.to_string_lossy().to_string());
+    StaticFiles::new(&network)
+}
+
+fn headers(pairs: &[(header::HeaderName, &'static str)]) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    for (name, value) in pairs {
+        headers.insert(name.clone(), HeaderValue::from_static(value));
+    }
+    headers
+}
+
+async fn get(files: &StaticFiles, path: &str, headers: &HeaderMap) -> Response<Body> {
+    files.serve(&Method::GET, &path.parse::<Uri>().unwrap(), headers).await
+}
+
+fn header_str(response: &Response<Body>, name: header::HeaderName) -> Option<&str> {
+    response.headers().get(name).and_then(|v| v.to_str().ok())
+}
+
+async fn body(response: Response<Body>) -> Vec<u8> {
+    to_bytes(response.into_body(), usize::MAX).await.unwrap().to_vec()
+}
+
+#[tokio::test]
+async fn test_serves_files_with_etag_and_revalidates() {
+    let dir = ui_dir();
+    let files = files(dir.path());
+
+    let response = get(&files, "/logo.png", &HeaderMap::new()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(header_str(&response, header::CONTENT_TYPE), Some("image/png"));
+    assert_eq!(header_str(&response, header::CACHE_CONTROL), Some("public, max-age=3600"));
+    let etag = header_str(&response, header::ETAG).unwrap().to_string();
+    assert!(etag.starts_with('"'));
+    assert_eq!(body(response).await, vec![0x89, b'P', b'N', b'G']);
+
+    let mut conditional = HeaderMap::new();
+    conditional.insert(header::IF_NONE_MATCH, HeaderValue::from_str(&format!("\"other\", W/{}", etag)).unwrap());
+    let response = get(&files, "/logo.png", &conditional).await;
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(header_str(&response, header::ETAG), Some(etag.as_str()));
+    assert!(body(response).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_spa_fallback_and_not_found() {
+    let dir = ui_dir();
+    let files = files(dir.path());
+
+    for path in ["/", "/avatar/settings", "/dashboard/"] {
+        let response = get(&files, path, &HeaderMap::new()).await;
+        assert_eq!(response.status(), StatusCode::OK, "{}", path);
+        assert_eq!(header_str(&response, header::CACHE_CONTROL), Some("no-cache"));
+        assert_eq!(header_str(&response, header::CONTENT_TYPE), Some("text/html; charset=utf-8"));
+        assert!(String::from_utf8(body(response).await).unwrap().contains("id=\"root\""));
+    }
+
+    // Missing assets and unmatched API paths are real 404s
+    assert_eq!(get(&files, "/assets/missing.js", &HeaderMap::new()).await.status(), StatusCode::NOT_FOUND);
+    assert_eq!(get(&files, "/api/v1/nope", &HeaderMap::new()).await.status(), StatusCode::NOT_FOUND);
+
+    let mut network = NetworkConfig::default();
+    network.static_files.directory = Some(dir.path().to_string_lossy().to_string());
+    network.static_files.spa_fallback = false;
+    files.update(&network);
+    assert_eq!(get(&files, "/avatar/settings", &HeaderMap::new()).await.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_precompressed_files_are_preferred() {
+    let dir = ui_dir();
+    let files = files(dir.path());
+
+    let response = get(&files, "/assets/index-BQ3k2x9a.js", &headers(&[(header::ACCEPT_ENCODING, "gzip, br")])).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(header_str(&response, header::CONTENT_ENCODING), Some("br"));
+    assert_eq!(header_str(&response, header::VARY), Some("Accept-Encoding"));
+    assert_eq!(header_str(&response, header::CONTENT_TYPE), Some("text/javascript; charset=utf-8"));
+    assert_eq!(header_str(&response, header::CACHE_CONTROL), Some("public, max-age=31536000, immutable"));
+    assert!(header_str(&response, header::ETAG).unwrap().ends_with("-br\""));
+    assert_eq!(body(response).await, b"fake-brotli");
+
+    // Without brotli the server compresses with gzip itself
+    let response = get(&files, "/assets/index-BQ3k2x9a.js", &headers(&[(header::ACCEPT_ENCODING, "br;q=0, gzip")])).await;
+    assert_eq!(header_str(&response, header::CONTENT_ENCODING), Some("gzip"));
+    let gzipped = body(response).await;
+    assert_eq!(&gzipped[..2], &[0x1f, 0x8b]);
+    assert!(gzipped.len() < APP_JS.len() * 100);
+
+    // Identity when nothing is accepted
+    let response = get(&files, "/assets/index-BQ3k2x9a.js", &HeaderMap::new()).await;
+    assert!(response.headers().get(header::CONTENT_ENCODING).is_none());
+    assert_eq!(body(response).await.len(), APP_JS.len() * 100);
+}
+
+#[tokio::test]
+async fn test_compressed_variants_are_cached() {
+    let dir = ui_dir();
+    let files = files(dir.path());
+    assert_eq!(files.cached_bytes(), 0);
+
+    // style.css gets br and gzip; the .js already ships a .br so only gzip is added
+    assert_eq!(files.precompress_all().await, 3);
+    let cached = files.cached_bytes();
+    assert!(cached > 0);
+
+    let response = get(&files, "/assets/style.css", &headers(&[(header::ACCEPT_ENCODING, "br")])).await;
+    assert_eq!(header_str(&response, header::CONTENT_ENCODING), Some("br"));
+    assert_eq!(files.cached_bytes(), cached);
+}
+
+#[tokio::test]
+async fn test_head_and_other_methods() {
+    let dir = ui_dir();
+    let files = files(dir.path());
+
+    let response = files.serve(&Method::HEAD, &"/logo.png".parse().unwrap(), &HeaderMap::new()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(header_str(&response, header::CONTENT_LENGTH), Some("4"));
+    assert!(body(response).await.is_empty());
+
+    let response = files.serve(&Method::POST, &"/logo.png".parse().unwrap(), &HeaderMap::new()).await;
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(header_str(&response, header::ALLOW), Some("GET, HEAD"));
+}
+
+#[tokio::test]
+async fn test_rejects_path_traversal() {
+    let dir = ui_dir();
+    let files = files(dir.path());
+
+    for path in ["/../secret.txt", "/assets/%2e%2e/%2e%2e/secret.txt", "/assets//index.html", "/a%5c..%5csecret.txt"] {
+        let response = get(&files, path, &HeaderMap::new()).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{}", path);
+    }
+}
+
+#[tokio::test]
+async fn test_redirects_to_dev_server_without_directory() {
+    let files = StaticFiles::new(&NetworkConfig::default());
+    let response = get(&files, "/", &HeaderMap::new()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(String::from_utf8(body(response).await).unwrap().contains("http://localhost:3000"));
+}
@@ -0,0 +1,120 @@
+// Storage backend tests
+// In-memory snapshots and restore, and routing tables to the backend chosen
+// for their database
+
+use narayana_core::column::Column;
+use narayana_core::config::{StorageBackendKind, StorageBackendsConfig};
+use narayana_core::schema::{DataType, Field, Schema};
+use narayana_core::types::TableId;
+use narayana_storage::column_store::{ColumnStore, InMemoryColumnStore};
+use narayana_storage::database_manager::DatabaseManager;
+use narayana_storage::storage_backends::StorageBackends;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn schema() -> Schema {
+    Schema::new(vec![Field {
+        name: "id".to_string(),
+        data_type: DataType::Int64,
+        nullable: false,
+        default_value: None,
+    }])
+}
+
+fn ints(column: &Column) -> Vec<i64> {
+    match column {
+        Column::Int64(values) => values.clone(),
+        other => panic!("expected Int64 column, got {:?}", other.data_type()),
+    }
+}
+
+#[tokio::test]
+async fn test_memory_snapshot_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("memory.snapshot");
+
+    let store = InMemoryColumnStore::new();
+    store.create_table(TableId(7), schema()).await.unwrap();
+    store.write_columns(TableId(7), vec![Column::Int64(vec![1, 2, 3])]).await.unwrap();
+    assert_eq!(store.snapshot(&path).unwrap(), 1);
+
+    let restored = InMemoryColumnStore::restore(&path).unwrap();
+    assert_eq!(restored.table_ids(), vec![TableId(7)]);
+    let columns = restored.read_columns(TableId(7), vec![0], 0, 10).await.unwrap();
+    assert_eq!(ints(&columns[0]), vec![1, 2, 3]);
+
+    // A missing snapshot is an empty store, a corrupt one is an error
+    assert!(InMemoryColumnStore::restore(dir.path().join("none")).unwrap().table_ids().is_empty());
+    std::fs::write(&path, b"not a snapshot").unwrap();
+    assert!(InMemoryColumnStore::restore(&path).is_err());
+}
+
+#[tokio::test]
+async fn test_periodic_snapshots_only_after_changes() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("memory.snapshot");
+    let store = Arc::new(InMemoryColumnStore::new());
+    let task = store.start_snapshots(path.clone(), Duration::from_millis(20));
+
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    assert!(path.exists());
+    let first = std::fs::metadata(&path).unwrap().modified().unwrap();
+
+    store.create_table(TableId(1), schema()).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(80)).await;
+    assert_eq!(InMemoryColumnStore::restore(&path).unwrap().table_ids(), vec![TableId(1)]);
+    assert!(std::fs::metadata(&path).unwrap().modified().unwrap() >= first);
+
+    // The task stops once the store is dropped
+    drop(store);
+    tokio::time::timeout(Duration::from_secs(1), task).await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn test_tables_follow_their_database_backend() {
+    let db_manager = Arc::new(DatabaseManager::new());
+    let filesystem = Arc::new(InMemoryColumnStore::new());
+    let memory = Arc::new(InMemoryColumnStore::new());
+    let mut config = StorageBackendsConfig::default();
+    config.databases.insert("scratch".to_string(), StorageBackendKind::Memory);
+    let router = StorageBackends::new(filesystem.clone(), db_manager.clone(), &config)
+        .with_memory(memory.clone());
+
+    let main_db = db_manager.create_database("main".to_string()).unwrap();
+    let scratch_db = db_manager.create_database("scratch".to_string()).unwrap();
+    let users = db_manager.create_table(main_db, "users".to_string(), schema()).unwrap();
+    let temp = db_manager.create_table(scratch_db, "temp".to_string(), schema()).unwrap();
+    router.create_table(users, schema()).await.unwrap();
+    router.create_table(temp, schema()).await.unwrap();
+
+    assert_eq!(router.backend_of(users), StorageBackendKind::FileSystem);
+    assert_eq!(router.backend_of(temp), StorageBackendKind::Memory);
+    assert_eq!(filesystem.table_ids(), vec![users]);
+    assert_eq!(memory.table_ids(), vec![temp]);
+
+    router.write_columns(temp, vec![Column::Int64(vec![4, 5])]).await.unwrap();
+    assert_eq!(ints(&router.read_columns(temp, vec![0], 0, 10).await.unwrap()[0]), vec![4, 5]);
+    assert!(filesystem.read_columns(temp, vec![0], 0, 10).await.is_err());
+
+    router.delete_table(temp).await.unwrap();
+    assert!(memory.table_ids().is_empty());
+    assert_eq!(router.backend_of(temp), StorageBackendKind::FileSystem);
+}
+
+#[tokio::test]
+async fn test_restored_tables_are_routed_and_missing_backends_fail() {
+    let memory = InMemoryColumnStore::new();
+    memory.create_table(TableId(42), schema()).await.unwrap();
+    let db_manager = Arc::new(DatabaseManager::new());
+    let router = StorageBackends::new(Arc::new(InMemoryColumnStore::new()), db_manager.clone(), &StorageBackendsConfig::default())
+        .with_memory(Arc::new(memory));
+    assert_eq!(router.backend_of(TableId(42)), StorageBackendKind::Memory);
+    assert!(router.get_schema(TableId(42)).await.is_ok());
+
+    // A database mapped to RocksDB without the store attached is refused
+    let config = StorageBackendsConfig { default: StorageBackendKind::RocksDb, ..StorageBackendsConfig::default() };
+    router.update(&config);
+    let db = db_manager.create_database("analytics".to_string()).unwrap();
+    let table = db_manager.create_table(db, "events".to_string(), schema()).unwrap();
+    assert!(router.create_table(table, schema()).await.is_err());
+}
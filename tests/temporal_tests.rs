@@ -0,0 +1,197 @@
+// Temporal table tests
+// System-versioned tables: updates by key keep history, deletes leave
+// tombstones, AS OF reads, history rebuilt on re-enable, and retention
+
+use narayana_core::column::Column;
+use narayana_core::schema::{DataType, Field, Schema};
+use narayana_core::types::{CompressionType, TableId};
+use narayana_storage::block::{BlockPredicate, ZoneValue};
+use narayana_storage::column_store::{ColumnStore, InMemoryColumnStore};
+use narayana_storage::persistent_column_store::PersistentColumnStore;
+use narayana_storage::temporal::*;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+
+const ROBOTS: TableId = TableId(1);
+
+fn field(name: &str, data_type: DataType) -> Field {
+    Field { name: name.to_string(), data_type, nullable: false, default_value: None }
+}
+
+fn config(retention_secs: Option<u64>) -> TemporalConfig {
+    TemporalConfig { key_column: "id".to_string(), period_column: "sys_from".to_string(), retention_secs }
+}
+
+fn rows(ids: &[i64], states: &[&str]) -> Vec<Column> {
+    vec![
+        Column::Int64(ids.to_vec()),
+        Column::String(states.iter().map(|s| s.to_string()).collect()),
+        Column::Int64(vec![0; ids.len()]),
+    ]
+}
+
+fn strings(column: &Column) -> Vec<String> {
+    match column {
+        Column::String(values) => values.clone(),
+        other => panic!("expected String column, got {:?}", other.data_type()),
+    }
+}
+
+async fn setup(retention_secs: Option<u64>) -> (Arc<dyn ColumnStore>, Arc<TemporalManager>) {
+    setup_on(Arc::new(InMemoryColumnStore::new()), retention_secs).await
+}
+
+async fn setup_on(store: Arc<dyn ColumnStore>, retention_secs: Option<u64>) -> (Arc<dyn ColumnStore>, Arc<TemporalManager>) {
+    let schema = Schema::new(vec![
+        field("id", DataType::Int64),
+        field("state", DataType::String),
+        field("sys_from", DataType::Int64),
+    ]);
+    store.create_table(ROBOTS, schema).await.unwrap();
+    let temporal = Arc::new(TemporalManager::new());
+    temporal.enable(store.as_ref(), ROBOTS, config(retention_secs)).await.unwrap();
+    (store, temporal)
+}
+
+/// States visible as of `as_of`, in stored order
+async fn states(store: &Arc<dyn ColumnStore>, temporal: &Arc<TemporalManager>, as_of: Option<u64>) -> Vec<String> {
+    let view = temporal.at(store.clone(), as_of);
+    let columns = view.read_columns(ROBOTS, vec![1], 0, usize::MAX).await.unwrap();
+    strings(&columns[0])
+}
+
+async fn write(temporal: &TemporalManager, store: &Arc<dyn ColumnStore>, ids: &[i64], states: &[&str]) {
+    temporal.write(store.as_ref(), ROBOTS, rows(ids, states)).await.unwrap();
+    // Versions a millisecond apart, so each is current for a while
+    tokio::time::sleep(Duration::from_millis(5)).await;
+}
+
+#[tokio::test]
+async fn test_updates_keep_history_and_as_of_reads_see_it() {
+    let (store, temporal) = setup(None).await;
+    write(&temporal, &store, &[1, 2], &["idle", "idle"]).await;
+    write(&temporal, &store, &[1], &["busy"]).await;
+    write(&temporal, &store, &[1], &["charging"]).await;
+
+    // Every version is stored, but reads see one row per key
+    assert_eq!(temporal.status(ROBOTS).await.unwrap().stored_rows, 4);
+    assert_eq!(states(&store, &temporal, None).await, vec!["idle", "charging"]);
+
+    let history = temporal.history(store.as_ref(), ROBOTS, &json!(1)).await.unwrap();
+    let versions: Vec<&str> = history.iter().map(|v| v.values["state"].as_str().unwrap()).collect();
+    assert_eq!(versions, vec!["idle", "busy", "charging"]);
+    assert_eq!(history[0].valid_to, Some(history[1].valid_from));
+    assert_eq!(history[2].valid_to, None);
+    // The period column holds when each version became current
+    assert_eq!(history[1].values["sys_from"], json!(history[1].valid_from));
+
+    let during_busy = history[1].valid_from;
+    assert_eq!(states(&store, &temporal, Some(during_busy)).await, vec!["idle", "busy"]);
+    assert!(states(&store, &temporal, Some(history[0].valid_from - 1)).await.is_empty());
+
+    // row_start and row_count address visible rows
+    let view = temporal.at(store.clone(), None);
+    let second = view.read_columns(ROBOTS, vec![0], 1, 1).await.unwrap();
+    assert!(matches!(&second[0], Column::Int64(v) if v == &[1]));
+}
+
+#[tokio::test]
+async fn test_delete_leaves_tombstone_and_history_survives_reenable() {
+    let (store, temporal) = setup(None).await;
+    write(&temporal, &store, &[1, 2], &["idle", "busy"]).await;
+    let before_delete = temporal.history(store.as_ref(), ROBOTS, &json!(2)).await.unwrap()[0].valid_from;
+
+    let tombstones = temporal.delete(store.as_ref(), ROBOTS, &[json!(2), json!(99)]).await.unwrap();
+    assert!(matches!(&tombstones[0], Column::Int64(v) if v == &[2]));
+    assert!(matches!(&tombstones[2], Column::Int64(v) if v[0] < 0));
+    assert_eq!(states(&store, &temporal, None).await, vec!["idle"]);
+    assert_eq!(states(&store, &temporal, Some(before_delete)).await, vec!["idle", "busy"]);
+
+    let history = temporal.history(store.as_ref(), ROBOTS, &json!("2")).await.unwrap();
+    assert_eq!(history.len(), 1);
+    assert!(history[0].deleted && history[0].valid_to.is_some());
+
+    // A fresh manager rebuilds the same history from the stored rows
+    let restarted = Arc::new(TemporalManager::new());
+    let status = restarted.enable(store.as_ref(), ROBOTS, config(None)).await.unwrap();
+    assert_eq!((status.stored_rows, status.current_rows), (3, 1));
+    assert_eq!(states(&store, &restarted, None).await, vec!["idle"]);
+    assert_eq!(states(&store, &restarted, Some(before_delete)).await, vec!["idle", "busy"]);
+
+    // A deleted key can come back
+    write(&temporal, &store, &[2], &["rebooted"]).await;
+    assert_eq!(states(&store, &temporal, None).await, vec!["idle", "rebooted"]);
+}
+
+#[tokio::test]
+async fn test_purge_drops_expired_versions_and_refuses_older_reads() {
+    let (store, temporal) = setup(Some(60)).await;
+    write(&temporal, &store, &[1, 2], &["idle", "idle"]).await;
+    write(&temporal, &store, &[1], &["busy"]).await;
+    temporal.delete(store.as_ref(), ROBOTS, &[json!(2)]).await.unwrap();
+    let first = temporal.history(store.as_ref(), ROBOTS, &json!(1)).await.unwrap()[0].valid_from;
+    let now = temporal.history(store.as_ref(), ROBOTS, &json!(1)).await.unwrap()[1].valid_from;
+
+    // Nothing has been superseded for a minute yet
+    assert_eq!(temporal.purge(store.as_ref(), ROBOTS, None, now).await.unwrap(), 0);
+
+    let later = now + 61_000;
+    // id 1's first version, id 2's version and its tombstone
+    assert_eq!(temporal.purge(store.as_ref(), ROBOTS, None, later).await.unwrap(), 3);
+    let stored = store.read_columns(ROBOTS, vec![1], 0, usize::MAX).await.unwrap();
+    assert_eq!(strings(&stored[0]), vec!["busy"]);
+    assert_eq!(states(&store, &temporal, None).await, vec!["busy"]);
+
+    let status = temporal.status(ROBOTS).await.unwrap();
+    assert_eq!(status.history_since, later - 60_000);
+    let view = temporal.at(store.clone(), Some(first));
+    let err = view.read_columns(ROBOTS, vec![1], 0, usize::MAX).await.unwrap_err();
+    assert!(err.to_string().contains("purged"));
+
+    // Writes after the purge line up with the rewritten table
+    write(&temporal, &store, &[3], &["new"]).await;
+    assert_eq!(states(&store, &temporal, None).await, vec!["busy", "new"]);
+}
+
+#[tokio::test]
+async fn test_configuration_and_as_of_parsing() {
+    let (store, temporal) = setup(None).await;
+    let invalid = [
+        TemporalConfig { key_column: "id".to_string(), period_column: "state".to_string(), retention_secs: None },
+        TemporalConfig { key_column: "id".to_string(), period_column: "id".to_string(), retention_secs: None },
+        TemporalConfig { key_column: "serial".to_string(), period_column: "sys_from".to_string(), retention_secs: None },
+        config(Some(0)),
+    ];
+    for config in invalid {
+        assert!(temporal.enable(store.as_ref(), ROBOTS, config).await.is_err());
+    }
+
+    // Tables that aren't versioned read as stored, but not AS OF
+    assert!(temporal.disable(ROBOTS));
+    assert!(!temporal.is_versioned(ROBOTS));
+    assert!(temporal.at(store.clone(), Some(1)).read_columns(ROBOTS, vec![0], 0, 10).await.is_err());
+    assert!(temporal.delete(store.as_ref(), ROBOTS, &[json!(1)]).await.is_err());
+
+    assert_eq!(parse_as_of("1700000000000").unwrap(), 1_700_000_000_000);
+    assert_eq!(parse_as_of("2023-11-14T22:13:20Z").unwrap(), 1_700_000_000_000);
+    assert_eq!(parse_as_of("FOR SYSTEM_TIME AS OF '2023-11-14T22:13:20Z'").unwrap(), 1_700_000_000_000);
+    assert_eq!(parse_as_of("for system_time as of 1700000000000").unwrap(), 1_700_000_000_000);
+    assert!(parse_as_of("yesterday").is_err());
+}
+
+#[tokio::test]
+async fn test_versioned_scans_are_not_pruned() {
+    let dir = tempfile::tempdir().unwrap();
+    let persistent = PersistentColumnStore::new(dir.path(), CompressionType::None).unwrap();
+    let (store, temporal) = setup_on(Arc::new(persistent), None).await;
+    write(&temporal, &store, &[1, 2], &["idle", "idle"]).await;
+    write(&temporal, &store, &[1], &["busy"]).await;
+
+    // The zone maps cover all three stored versions; the view shows two rows
+    let predicate = BlockPredicate::Eq(ZoneValue::Int(2));
+    assert!(store.matching_row_ranges(ROBOTS, 0, &predicate).await.unwrap().is_some());
+    let view = temporal.at(store.clone(), None);
+    assert_eq!(view.matching_row_ranges(ROBOTS, 0, &predicate).await.unwrap(), None);
+    assert_eq!(states(&store, &temporal, None).await, vec!["idle", "busy"]);
+}
@@ -2,6 +2,7 @@
 // NarayanaDB - Fully Multithreaded with Ample Controls
 
 use narayana_storage::threading::*;
+use narayana_storage::priority_scheduler::*;
 use narayana_core::config::ThreadingConfig;
 use std::sync::Arc;
 use std::time::Duration;
@@ -839,3 +840,149 @@ fn test_threading_config_all_pools() {
     assert!(config.sync_pool.min_threads > 0);
 }
 
+fn scheduler_config(workers: usize, realtime_reserved_workers: usize, background_max_workers: usize) -> SchedulerConfig {
+    SchedulerConfig {
+        workers,
+        realtime_reserved_workers,
+        background_max_workers,
+        starvation_threshold: Duration::from_millis(20),
+        thread_name_prefix: "test-sched".to_string(),
+    }
+}
+
+#[test]
+fn test_scheduler_results_and_metrics() {
+    let scheduler = PriorityScheduler::new(scheduler_config(3, 1, 1)).unwrap();
+    let receivers: Vec<_> = TaskClass::ALL
+        .iter()
+        .map(|class| scheduler.submit(*class, move || format!("{:?}", class)))
+        .collect();
+    let results: Vec<String> = receivers.into_iter().map(|rx| rx.blocking_recv().unwrap()).collect();
+    assert_eq!(results, vec!["Realtime", "Interactive", "Background"]);
+
+    // A panicking task drops its result and the worker keeps going
+    let panicked = scheduler.submit(TaskClass::Interactive, || -> u32 { panic!("boom") });
+    assert!(panicked.blocking_recv().is_err());
+    assert_eq!(scheduler.submit(TaskClass::Interactive, || 7).blocking_recv().unwrap(), 7);
+
+    let metrics = scheduler.metrics();
+    assert_eq!(metrics.workers, 3);
+    let interactive = &metrics.classes[&TaskClass::Interactive];
+    assert_eq!(interactive.submitted, 3);
+    assert_eq!(interactive.completed, 2);
+    assert_eq!(interactive.panicked, 1);
+    assert_eq!(interactive.queued, 0);
+    assert_eq!(metrics.classes[&TaskClass::Realtime].completed, 1);
+    assert!(metrics.classes.values().all(|c| (0.0..=1.0).contains(&c.utilization)));
+    scheduler.shutdown();
+}
+
+#[test]
+fn test_scheduler_config_validation() {
+    assert!(PriorityScheduler::new(scheduler_config(0, 0, 1)).is_err());
+    assert!(PriorityScheduler::new(scheduler_config(2, 2, 1)).is_err());
+    assert!(PriorityScheduler::new(scheduler_config(2, 1, 0)).is_err());
+
+    let config = SchedulerConfig::from(&narayana_core::config::TaskSchedulerConfigSection {
+        workers: 5,
+        realtime_reserved_workers: 1,
+        background_max_workers: None,
+        starvation_threshold_ms: 50,
+    });
+    assert_eq!(config.background_max_workers, 2);
+    assert_eq!(config.starvation_threshold, Duration::from_millis(50));
+}
+
+#[test]
+fn test_realtime_runs_while_background_saturates() {
+    let scheduler = PriorityScheduler::new(scheduler_config(2, 1, 1)).unwrap();
+    let background: Vec<_> = (0..4)
+        .map(|_| scheduler.submit(TaskClass::Background, || thread::sleep(Duration::from_millis(200))))
+        .collect();
+    thread::sleep(Duration::from_millis(20));
+
+    // The reserved worker picks realtime work up while compaction-style tasks run
+    let started = std::time::Instant::now();
+    let name = scheduler
+        .submit(TaskClass::Realtime, || thread::current().name().unwrap_or_default().to_string())
+        .blocking_recv()
+        .unwrap();
+    assert!(started.elapsed() < Duration::from_millis(100));
+    assert!(name.starts_with("test-sched-rt"));
+
+    // Background work is capped to one worker
+    let metrics = scheduler.metrics();
+    assert_eq!(metrics.classes[&TaskClass::Background].running, 1);
+    assert_eq!(metrics.classes[&TaskClass::Background].queued, 3);
+    drop(background);
+    scheduler.shutdown();
+}
+
+#[test]
+fn test_scheduler_prevents_starvation() {
+    let scheduler = PriorityScheduler::new(scheduler_config(1, 0, 1)).unwrap();
+    let order = Arc::new(std::sync::Mutex::new(Vec::new()));
+    // Hold the only worker so everything below queues up together
+    let gate = scheduler.submit(TaskClass::Interactive, || thread::sleep(Duration::from_millis(30)));
+    let background = {
+        let order = order.clone();
+        scheduler.submit(TaskClass::Background, move || order.lock().unwrap().push("background"))
+    };
+    let interactive: Vec<_> = (0..20)
+        .map(|_| {
+            let order = order.clone();
+            scheduler.submit(TaskClass::Interactive, move || {
+                thread::sleep(Duration::from_millis(5));
+                order.lock().unwrap().push("interactive");
+            })
+        })
+        .collect();
+    gate.blocking_recv().unwrap();
+    background.blocking_recv().unwrap();
+    for rx in interactive {
+        rx.blocking_recv().unwrap();
+    }
+
+    // Background waited past the threshold and ran ahead of queued interactive work
+    let position = order.lock().unwrap().iter().position(|c| *c == "background").unwrap();
+    assert!(position < 20, "background ran at position {}", position);
+    assert!(scheduler.metrics().classes[&TaskClass::Background].promoted >= 1);
+    scheduler.shutdown();
+}
+
+#[test]
+fn test_scheduler_steals_nested_tasks() {
+    let scheduler = Arc::new(PriorityScheduler::new(scheduler_config(4, 0, 1)).unwrap());
+    let names = Arc::new(std::sync::Mutex::new(std::collections::HashSet::new()));
+    let inner = scheduler.clone();
+    let inner_names = names.clone();
+    // Tasks submitted from a task land on that worker's deque; idle workers steal them
+    let receivers = scheduler
+        .submit(TaskClass::Interactive, move || {
+            (0..8)
+                .map(|_| {
+                    let names = inner_names.clone();
+                    inner.submit(TaskClass::Interactive, move || {
+                        thread::sleep(Duration::from_millis(20));
+                        names.lock().unwrap().insert(thread::current().name().unwrap_or_default().to_string());
+                    })
+                })
+                .collect::<Vec<_>>()
+        })
+        .blocking_recv()
+        .unwrap();
+    for rx in receivers {
+        rx.blocking_recv().unwrap();
+    }
+    assert!(names.lock().unwrap().len() > 1);
+    scheduler.shutdown();
+}
+
+#[tokio::test]
+async fn test_thread_manager_scheduler() {
+    let manager = ThreadManager::from_core_config(ThreadingConfig::default()).unwrap();
+    let scheduler = manager.scheduler().unwrap();
+    assert_eq!(scheduler.submit(TaskClass::Background, || 42).await.unwrap(), 42);
+    assert_eq!(manager.scheduler_metrics().unwrap().classes[&TaskClass::Background].completed, 1);
+    manager.shutdown().await;
+}
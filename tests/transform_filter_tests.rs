@@ -1213,3 +1213,87 @@ fn test_non_array_data_with_row_filters() {
     assert_eq!(result, json!({ "field": "value" }));
 }
 
+
+// ============================================================================
+// FUNCTIONALITY TESTS - Profiles Per Token And Role
+// ============================================================================
+
+fn masked_email_profile() -> OutputConfig {
+    OutputConfig {
+        default_filters: vec![DefaultFilter::MaskFields {
+            fields: vec!["email".to_string()],
+            pattern: "*".to_string(),
+            preserve_length: true,
+        }],
+        output_transforms: vec![OutputTransform::RenameField {
+            from: "name".to_string(),
+            to: "customer".to_string(),
+        }],
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_profile_resolved_by_token_then_role() {
+    use narayana_storage::dynamic_output::ProfileConsumer;
+
+    let manager = DynamicOutputManager::new();
+    let context = ConfigContext::Database { table_id: 1 };
+    let entity_id = "1".to_string();
+
+    // Profiles can be added to entities that have no config yet
+    manager.set_profile(context.clone(), entity_id.clone(), "masked".to_string(), masked_email_profile()).await.unwrap();
+    manager.set_profile(context.clone(), entity_id.clone(), "full".to_string(), OutputConfig::default()).await.unwrap();
+    manager.bind_profile(&context, &entity_id, ProfileConsumer::Role("analyst".to_string()), "masked").unwrap();
+    manager.bind_profile(&context, &entity_id, ProfileConsumer::Token("alice".to_string()), "full").unwrap();
+
+    let analyst = vec!["reader".to_string(), "analyst".to_string()];
+    let (profile, config) = manager.get_config_for_consumer(&context, &entity_id, "bob", &analyst).unwrap().unwrap();
+    assert_eq!(profile, "masked");
+    let rows = json!([{ "name": "Ann", "email": "ann@example.com" }]);
+    let shaped = TransformEngine::apply_config(rows, &config).unwrap();
+    assert_eq!(shaped, json!([{ "customer": "Ann", "email": "***************" }]));
+
+    // A token binding wins over role bindings
+    let (profile, _) = manager.get_config_for_consumer(&context, &entity_id, "alice", &analyst).unwrap().unwrap();
+    assert_eq!(profile, "full");
+
+    // Unbound callers get no profile
+    assert!(manager.get_config_for_consumer(&context, &entity_id, "bob", &[]).unwrap().is_none());
+    assert_eq!(manager.profile_bindings(&context, &entity_id).len(), 2);
+}
+
+#[tokio::test]
+async fn test_profile_bindings_follow_profile_lifecycle() {
+    use narayana_storage::dynamic_output::ProfileConsumer;
+
+    let manager = DynamicOutputManager::new();
+    let context = ConfigContext::Database { table_id: 2 };
+    let entity_id = "2".to_string();
+    let role = ProfileConsumer::Role("analyst".to_string());
+    let analyst = vec!["analyst".to_string()];
+
+    // Only existing profiles can be bound
+    assert!(manager.bind_profile(&context, &entity_id, role.clone(), "masked").is_err());
+    manager.set_profile(context.clone(), entity_id.clone(), "masked".to_string(), masked_email_profile()).await.unwrap();
+    manager.bind_profile(&context, &entity_id, role.clone(), "masked").unwrap();
+    assert!(manager.bind_profile(&context, &entity_id, ProfileConsumer::Token(String::new()), "masked").is_err());
+
+    // Rolling back the profile leaves a dangling binding, which fails closed
+    manager.rollback_change(context.clone(), entity_id.clone(), 0).await.unwrap();
+    assert!(manager.get_config_for_consumer(&context, &entity_id, "bob", &analyst).is_err());
+
+    // Removing a profile drops its bindings
+    manager.set_profile(context.clone(), entity_id.clone(), "masked".to_string(), masked_email_profile()).await.unwrap();
+    manager.remove_profile(context.clone(), entity_id.clone(), "masked".to_string()).await.unwrap();
+    assert!(manager.profile_bindings(&context, &entity_id).is_empty());
+    assert!(!manager.unbind_profile(&context, &entity_id, &role));
+
+    // Nested profiles and bad names are rejected
+    let nested = OutputConfig {
+        profiles: [("inner".to_string(), OutputConfig::default())].into_iter().collect(),
+        ..Default::default()
+    };
+    assert!(manager.set_profile(context.clone(), entity_id.clone(), "outer".to_string(), nested).await.is_err());
+    assert!(manager.set_profile(context.clone(), entity_id.clone(), "../x".to_string(), OutputConfig::default()).await.is_err());
+}
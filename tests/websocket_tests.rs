@@ -3,6 +3,7 @@
 
 use narayana_api::websocket::{ConnectionId, Channel, WsMessage, EventFilter};
 use narayana_server::websocket_manager::{WebSocketManager, WebSocketConfig, ConnectionState};
+use narayana_core::wire::{WireFormat, WireFrame, WireTagged};
 use std::sync::Arc;
 use tokio::sync::mpsc;
 use std::time::Duration;
@@ -468,6 +469,88 @@ fn test_message_event_with_timestamp() {
     }
 }
 
+// ============================================================================
+// Binary Protocol Tests
+// ============================================================================
+
+#[test]
+fn test_messages_round_trip_over_binary_protocols() {
+    let messages = vec![
+        WsMessage::Subscribe {
+            channel: "db:1".to_string(),
+            filter: Some(EventFilter::EventType("insert".to_string())),
+        },
+        WsMessage::Ack { seq: 42 },
+        WsMessage::Event {
+            channel: "db:1".to_string(),
+            event: serde_json::json!({"rows": [[1, "a"], [2, "b"]], "table_id": 7}),
+            timestamp: Some(1234567890),
+            seq: Some(3),
+        },
+        WsMessage::Resumed { last_seq: 9, replayed: 2, missed: 0 },
+        WsMessage::error("query_error", "boom"),
+        WsMessage::Pong { id: None },
+    ];
+
+    for format in [WireFormat::MessagePack, WireFormat::Cbor] {
+        for message in &messages {
+            let frame = format.encode(message).unwrap();
+            assert_eq!(frame.tag(), Some(message.wire_tag()));
+            let decoded: WsMessage = format.decode(&frame).unwrap();
+            assert_eq!(decoded.to_json().unwrap(), message.to_json().unwrap());
+        }
+    }
+}
+
+#[test]
+fn test_binary_frame_with_wrong_tag_is_rejected() {
+    let message = WsMessage::Ping { id: Some("1".to_string()) };
+    let frame = match WireFormat::MessagePack.encode(&message).unwrap() {
+        WireFrame::Binary(mut bytes) => {
+            bytes[0] = WsMessage::Ack { seq: 0 }.wire_tag();
+            WireFrame::Binary(bytes)
+        }
+        WireFrame::Text(_) => panic!("expected a binary frame"),
+    };
+    assert!(WireFormat::MessagePack.decode::<WsMessage>(&frame).is_err());
+
+    // JSON text is still understood on a binary connection
+    let text = WireFrame::Text(message.to_json().unwrap());
+    assert!(WireFormat::MessagePack.decode::<WsMessage>(&text).is_ok());
+}
+
+#[test]
+fn test_supported_protocols() {
+    let manager = WebSocketManager::new(WebSocketConfig::default());
+    assert_eq!(manager.supported_protocols()[0], "narayana.msgpack");
+    assert!(manager.supported_protocols().contains(&"narayana.json"));
+
+    let manager = WebSocketManager::new(WebSocketConfig {
+        binary_protocols: false,
+        ..WebSocketConfig::default()
+    });
+    assert_eq!(manager.supported_protocols(), &["narayana.json"]);
+}
+
+#[tokio::test]
+async fn test_wire_format_recorded_per_connection() {
+    let manager = WebSocketManager::new(WebSocketConfig::default());
+    let (tx1, _rx1) = mpsc::unbounded_channel();
+    let (tx2, _rx2) = mpsc::unbounded_channel();
+    manager.register_connection("json".to_string(), None, tx1).unwrap();
+    manager.register_connection("binary".to_string(), None, tx2).unwrap();
+
+    manager.set_wire_format(&"binary".to_string(), WireFormat::MessagePack);
+
+    let state = manager.get_connection_state(&"json".to_string()).unwrap();
+    assert_eq!(state.wire_format, WireFormat::Json);
+    let state = manager.get_connection_state(&"binary".to_string()).unwrap();
+    assert_eq!(state.wire_format, WireFormat::MessagePack);
+    let counts = manager.wire_format_counts();
+    assert_eq!(counts.get(&WireFormat::Json), Some(&1));
+    assert_eq!(counts.get(&WireFormat::MessagePack), Some(&1));
+}
+
 // ============================================================================
 // Security Tests
 // ============================================================================
@@ -518,6 +601,194 @@ async fn test_broadcast_subscriber_limit() {
     assert!(count <= 10_000);
 }
 
+// ============================================================================
+// Session Resumption Tests
+// ============================================================================
+
+fn drain(rx: &mut mpsc::UnboundedReceiver<WsMessage>) -> Vec<WsMessage> {
+    let mut messages = Vec::new();
+    while let Ok(message) = rx.try_recv() {
+        messages.push(message);
+    }
+    messages
+}
+
+fn resume_token(manager: &WebSocketManager, connection_id: &ConnectionId) -> String {
+    match manager.session_message(connection_id) {
+        Some(WsMessage::Session { resume_token, .. }) => resume_token,
+        other => panic!("Expected session message, got {:?}", other),
+    }
+}
+
+fn event_seqs(messages: &[WsMessage]) -> Vec<u64> {
+    messages.iter().filter_map(|m| match m {
+        WsMessage::Event { seq, .. } => *seq,
+        _ => None,
+    }).collect()
+}
+
+#[tokio::test]
+async fn test_events_are_sequenced() {
+    let manager = WebSocketManager::new(WebSocketConfig::default());
+    let connection_id = "conn-1".to_string();
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    manager.register_connection(connection_id.clone(), Some("user-1".to_string()), tx).unwrap();
+    let channel = "brain:thoughts".to_string();
+    manager.subscribe(&connection_id, channel.clone(), None).unwrap();
+
+    for i in 0..3 {
+        manager.broadcast_to_channel(&channel, WsMessage::event(channel.clone(), serde_json::json!({"i": i})));
+    }
+    assert_eq!(event_seqs(&drain(&mut rx)), vec![1, 2, 3]);
+}
+
+#[tokio::test]
+async fn test_resume_replays_missed_events() {
+    let manager = WebSocketManager::new(WebSocketConfig::default());
+    let connection_id = "conn-1".to_string();
+    let user = Some("user-1".to_string());
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    manager.register_connection(connection_id.clone(), user.clone(), tx.clone()).unwrap();
+    let channel = "brain:thoughts".to_string();
+    manager.subscribe(&connection_id, channel.clone(), None).unwrap();
+    let token = resume_token(&manager, &connection_id);
+
+    manager.broadcast_to_channel(&channel, WsMessage::event(channel.clone(), serde_json::json!({"n": 1})));
+    manager.broadcast_to_channel(&channel, WsMessage::event(channel.clone(), serde_json::json!({"n": 2})));
+    assert_eq!(event_seqs(&drain(&mut rx)), vec![1, 2]);
+
+    // Network blip: the socket drops, events keep arriving
+    manager.release_connection(&connection_id, &tx, false);
+    assert_eq!(manager.connection_count(), 0);
+    assert_eq!(manager.detached_session_count(), 1);
+    for n in 3..=5 {
+        manager.broadcast_to_channel(&channel, WsMessage::event(channel.clone(), serde_json::json!({"n": n})));
+    }
+
+    // Client saw seq 1 before the blip (seq 2 was lost in flight)
+    let (tx2, mut rx2) = mpsc::unbounded_channel();
+    let outcome = manager.resume_connection(&token, &user, 1, tx2).unwrap();
+    assert_eq!(outcome.connection_id, connection_id);
+    assert_eq!(outcome.replayed, 4);
+    assert_eq!(outcome.missed, 0);
+
+    let replay = drain(&mut rx2);
+    assert!(matches!(replay[0], WsMessage::Resumed { last_seq: 5, replayed: 4, missed: 0 }));
+    assert_eq!(event_seqs(&replay), vec![2, 3, 4, 5]);
+
+    // Subscriptions survived; new events continue the sequence
+    manager.broadcast_to_channel(&channel, WsMessage::event(channel.clone(), serde_json::json!({"n": 6})));
+    assert_eq!(event_seqs(&drain(&mut rx2)), vec![6]);
+    assert_eq!(manager.connection_count(), 1);
+    assert_eq!(manager.detached_session_count(), 0);
+}
+
+#[tokio::test]
+async fn test_resume_reports_events_lost_from_full_buffer() {
+    let mut config = WebSocketConfig::default();
+    config.replay_buffer_size = 2;
+    let manager = WebSocketManager::new(config);
+    let connection_id = "conn-1".to_string();
+    let (tx, _rx) = mpsc::unbounded_channel();
+    manager.register_connection(connection_id.clone(), None, tx.clone()).unwrap();
+    let channel = "brain:thoughts".to_string();
+    manager.subscribe(&connection_id, channel.clone(), None).unwrap();
+    let token = resume_token(&manager, &connection_id);
+
+    manager.release_connection(&connection_id, &tx, false);
+    for n in 1..=5 {
+        manager.broadcast_to_channel(&channel, WsMessage::event(channel.clone(), serde_json::json!({"n": n})));
+    }
+
+    let (tx2, mut rx2) = mpsc::unbounded_channel();
+    let outcome = manager.resume_connection(&token, &None, 0, tx2).unwrap();
+    assert_eq!(outcome.missed, 3);
+    assert_eq!(event_seqs(&drain(&mut rx2)), vec![4, 5]);
+}
+
+#[tokio::test]
+async fn test_resume_rejects_other_user_and_unknown_token() {
+    let manager = WebSocketManager::new(WebSocketConfig::default());
+    let connection_id = "conn-1".to_string();
+    let (tx, _rx) = mpsc::unbounded_channel();
+    manager.register_connection(connection_id.clone(), Some("user-1".to_string()), tx.clone()).unwrap();
+    let token = resume_token(&manager, &connection_id);
+    manager.release_connection(&connection_id, &tx, false);
+
+    let (tx2, _rx2) = mpsc::unbounded_channel();
+    assert!(manager.resume_connection(&token, &Some("user-2".to_string()), 0, tx2.clone()).is_err());
+    assert!(manager.resume_connection("not-a-token", &Some("user-1".to_string()), 0, tx2.clone()).is_err());
+    assert!(manager.resume_connection(&token, &Some("user-1".to_string()), 99, tx2).is_err());
+}
+
+#[tokio::test]
+async fn test_client_close_ends_session() {
+    let manager = WebSocketManager::new(WebSocketConfig::default());
+    let connection_id = "conn-1".to_string();
+    let (tx, _rx) = mpsc::unbounded_channel();
+    manager.register_connection(connection_id.clone(), None, tx.clone()).unwrap();
+    let token = resume_token(&manager, &connection_id);
+
+    manager.release_connection(&connection_id, &tx, true);
+    assert_eq!(manager.detached_session_count(), 0);
+    let (tx2, _rx2) = mpsc::unbounded_channel();
+    assert!(manager.resume_connection(&token, &None, 0, tx2).is_err());
+}
+
+#[tokio::test]
+async fn test_stale_socket_release_does_not_detach_resumed_session() {
+    let manager = WebSocketManager::new(WebSocketConfig::default());
+    let connection_id = "conn-1".to_string();
+    let (tx, _rx) = mpsc::unbounded_channel();
+    manager.register_connection(connection_id.clone(), None, tx.clone()).unwrap();
+    let token = resume_token(&manager, &connection_id);
+
+    // The client reconnects before the server noticed the old socket died
+    let (tx2, _rx2) = mpsc::unbounded_channel();
+    manager.resume_connection(&token, &None, 0, tx2).unwrap();
+    manager.release_connection(&connection_id, &tx, false);
+    assert_eq!(manager.connection_count(), 1);
+    assert_eq!(manager.detached_session_count(), 0);
+}
+
+#[tokio::test]
+async fn test_acknowledge_trims_replay_buffer() {
+    let manager = WebSocketManager::new(WebSocketConfig::default());
+    let connection_id = "conn-1".to_string();
+    let (tx, _rx) = mpsc::unbounded_channel();
+    manager.register_connection(connection_id.clone(), None, tx.clone()).unwrap();
+    let channel = "brain:thoughts".to_string();
+    manager.subscribe(&connection_id, channel.clone(), None).unwrap();
+    let token = resume_token(&manager, &connection_id);
+    for n in 1..=3 {
+        manager.broadcast_to_channel(&channel, WsMessage::event(channel.clone(), serde_json::json!({"n": n})));
+    }
+
+    manager.acknowledge(&connection_id, 2);
+    manager.release_connection(&connection_id, &tx, false);
+    let (tx2, mut rx2) = mpsc::unbounded_channel();
+    let outcome = manager.resume_connection(&token, &None, 0, tx2).unwrap();
+    // Acknowledged events are gone even though the client asked from 0
+    assert_eq!(outcome.missed, 2);
+    assert_eq!(event_seqs(&drain(&mut rx2)), vec![3]);
+}
+
+#[tokio::test]
+async fn test_expired_sessions_are_cleaned_up() {
+    let mut config = WebSocketConfig::default();
+    config.session_ttl_secs = 1;
+    let manager = WebSocketManager::new(config);
+    let connection_id = "conn-1".to_string();
+    let (tx, _rx) = mpsc::unbounded_channel();
+    manager.register_connection(connection_id.clone(), None, tx.clone()).unwrap();
+    manager.release_connection(&connection_id, &tx, false);
+    assert_eq!(manager.detached_session_count(), 1);
+
+    sleep(Duration::from_secs(2)).await;
+    manager.cleanup_stale_connections();
+    assert_eq!(manager.detached_session_count(), 0);
+}
+
 // ============================================================================
 // Cleanup Tests
 // ============================================================================